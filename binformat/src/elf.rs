@@ -1,11 +1,12 @@
+use std::collections::HashMap;
 use std::fmt;
-use crate::{datastructure, RawSymbol};
+use crate::{datastructure, Dependencies, Indirection, RawSymbol};
 use processor_shared::{AddressMap, Addressed, Section, SectionKind};
 use object::elf;
-use object::read::elf::{ElfFile, FileHeader, SectionHeader};
+use object::read::elf::{Dyn, ElfFile, FileHeader, SectionHeader};
 use object::{
-    Endian, Object, ObjectSection, ObjectSymbol, ObjectSymbolTable, RelocationKind,
-    RelocationTarget,
+    Architecture, Object, ObjectKind, ObjectSection, ObjectSymbol, ObjectSymbolTable,
+    RelocationKind, RelocationTarget, SectionIndex, SymbolKind,
 };
 
 pub struct ElfDebugInfo<'data, Elf: FileHeader> {
@@ -15,6 +16,42 @@ pub struct ElfDebugInfo<'data, Elf: FileHeader> {
     pub sections: Vec<Section>,
     /// Any parsed but not yet relocated symbols.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Instruction set of 32-bit ARM code from each address on, as marked by mapping symbols.
+    /// Sorted by address.
+    pub modes: Vec<(usize, ArmMode)>,
+    /// Functions of 32-bit ARM binaries made of Thumb instructions, known by their address
+    /// having the lowest bit set.
+    pub thumb_functions: Vec<usize>,
+    /// Relocations of BPF objects as the address they're applied to together with the address of
+    /// the symbol they refer to, which still has to be applied to the instruction.
+    pub relocations: Vec<(usize, usize)>,
+}
+
+/// What follows a mapping symbol of 32-bit ARM code, which switches between the ARM and Thumb
+/// instruction sets and mixes in data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmMode {
+    Arm,
+    Thumb,
+    Data,
+}
+
+impl ArmMode {
+    /// Mode a mapping symbol such as `$t` or `$d.realdata` switches to.
+    pub fn from_mapping_symbol(name: &str) -> Option<Self> {
+        let kind = name.split_once('.').map_or(name, |(kind, _)| kind);
+        match kind {
+            "$a" => Some(Self::Arm),
+            "$t" => Some(Self::Thumb),
+            "$d" => Some(Self::Data),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `name` is a mapping symbol of AArch64 code, which only marks data.
+fn is_aarch64_mapping_symbol(name: &str) -> bool {
+    matches!(name.split_once('.').map_or(name, |(kind, _)| kind), "$x" | "$d")
 }
 
 impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
@@ -23,13 +60,108 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
             obj,
             syms: AddressMap::default(),
             sections: Vec::new(),
+            modes: Vec::new(),
+            thumb_functions: Vec::new(),
+            relocations: Vec::new(),
         };
         this.sections = parse_sections(obj);
-        this.parse_symbols();
-        this.parse_imports();
+        if is_bpf_object(obj) {
+            this.layout_bpf();
+            this.parse_bpf_symbols();
+            this.parse_bpf_relocations();
+        } else {
+            this.parse_symbols();
+            this.parse_imports();
+        }
         Ok(this)
     }
 
+    /// Give the loaded sections of a BPF object consecutive addresses, as every program, map and
+    /// global is in a section starting at zero until it's loaded.
+    fn layout_bpf(&mut self) {
+        let endian = self.obj.endian();
+        let section_headers = match self.obj.raw_header().sections(endian, self.obj.data()) {
+            Ok(section_headers) => section_headers,
+            Err(..) => return,
+        };
+
+        let mut addr = BPF_BASE;
+        let sections = section_headers.iter().zip(self.obj.sections());
+        for (parsed, (header, section)) in self.sections.iter_mut().zip(sections) {
+            let sh_flags: u64 = header.sh_flags(endian).into();
+            if sh_flags as u32 & elf::SHF_ALLOC == 0 {
+                continue;
+            }
+
+            let align = (section.align() as usize).max(8);
+            addr = (addr + align - 1) & !(align - 1);
+            parsed.start = addr;
+            parsed.end = addr + section.size() as usize;
+            addr = parsed.end;
+        }
+    }
+
+    /// Address the section at `idx` was laid out at, if it's loaded.
+    fn bpf_section_base(&self, idx: SectionIndex) -> Option<usize> {
+        self.sections.get(idx.0).map(|section| section.start).filter(|&start| start != 0)
+    }
+
+    /// Symbols of a BPF object, whose values are offsets into their section.
+    fn parse_bpf_symbols(&mut self) {
+        for sym in self.obj.symbols() {
+            if matches!(sym.kind(), SymbolKind::Section | SymbolKind::File) {
+                continue;
+            }
+
+            let name = match sym.name() {
+                Ok(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            let base = match sym.section_index().and_then(|idx| self.bpf_section_base(idx)) {
+                Some(base) => base,
+                None => continue,
+            };
+
+            self.syms.push(Addressed {
+                addr: base + sym.address() as usize,
+                item: RawSymbol {
+                    name,
+                    module: None,
+                    indirection: None,
+                },
+            });
+        }
+    }
+
+    /// Relocations of the programs of a BPF object against maps, globals and other functions.
+    /// Those against symbols defined by the kernel are left alone.
+    fn parse_bpf_relocations(&mut self) {
+        for section in self.obj.sections() {
+            let base = match self.bpf_section_base(section.index()) {
+                Some(base) => base,
+                None => continue,
+            };
+
+            for (offset, reloc) in section.relocations() {
+                let sym = match reloc.target() {
+                    RelocationTarget::Symbol(idx) => match self.obj.symbol_by_index(idx) {
+                        Ok(sym) => sym,
+                        Err(..) => continue,
+                    },
+                    _ => continue,
+                };
+
+                let target = match sym.section_index().and_then(|idx| self.bpf_section_base(idx)) {
+                    Some(target) => target + sym.address() as usize,
+                    None => continue,
+                };
+
+                self.relocations.push((base + offset as usize, target));
+            }
+        }
+    }
+
     pub fn parse_imports(&mut self) {
         let relocations = match self.obj.dynamic_relocations() {
             Some(relocations) => relocations,
@@ -41,53 +173,92 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
             None => return,
         };
 
+        let arch = self.obj.architecture();
+
+        // GOT slots together with the symbol the loader fills them with.
+        let mut slots = HashMap::new();
+
         for (r_offset, reloc) in relocations {
-            if let RelocationTarget::Symbol(idx) = reloc.target() {
-                let opt_section = self.obj.sections().find(|section| {
-                    (section.address()..section.address() + section.size()).contains(&r_offset)
-                });
+            let idx = match reloc.target() {
+                RelocationTarget::Symbol(idx) => idx,
+                _ => continue,
+            };
+
+            let name = match dyn_syms.symbol_by_index(idx).and_then(|sym| sym.name()) {
+                Ok(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            let addr = r_offset as usize;
+            let indirection = match reloc.kind() {
+                // hard-coded address to function which doesn't require a relocation
+                RelocationKind::Absolute => None,
+                RelocationKind::Elf(r_type) if is_copy(arch, r_type) => None,
+                // address in the GOT which will contain the address of the symbol
+                RelocationKind::Elf(r_type) if is_got_slot(arch, r_type) => {
+                    slots.insert(addr, name);
+                    Some(Indirection::Got)
+                }
+                _ => continue,
+            };
+
+            // TODO: find modules
+            self.syms.push(Addressed {
+                addr,
+                item: RawSymbol {
+                    name,
+                    module: None,
+                    indirection,
+                },
+            });
+        }
+
+        self.parse_plt(&slots);
+    }
 
-                let section = match opt_section {
-                    Some(section) => section,
+    /// Name the stubs in the PLT after the symbol in the GOT slot they jump through.
+    ///
+    /// Only x86, AArch64 and RISC-V stubs are recognized.
+    fn parse_plt(&mut self, slots: &HashMap<usize, &'data str>) {
+        let arch = self.obj.architecture();
+        let endian = self.obj.endian();
+        let section_headers = match self.obj.raw_header().sections(endian, self.obj.data()) {
+            Ok(section_headers) => section_headers,
+            Err(..) => return,
+        };
+
+        for (header, section) in section_headers.iter().zip(self.obj.sections()) {
+            if !matches!(section.name(), Ok(".plt" | ".plt.sec" | ".plt.got")) {
+                continue;
+            }
+
+            let bytes = match section.data() {
+                Ok(bytes) => bytes,
+                Err(..) => continue,
+            };
+
+            let entry_size: u64 = header.sh_entsize(endian).into();
+            let entry_size = match entry_size {
+                0 => PLT_ENTRY_SIZE,
+                size => size as usize,
+            };
+
+            let base = section.address() as usize;
+            for (idx, stub) in bytes.chunks(entry_size).enumerate() {
+                let addr = base + idx * entry_size;
+                let slot = match plt_slot(arch, stub, addr) {
+                    Some(slot) => slot,
                     None => continue,
                 };
 
-                if let Ok(sym) = dyn_syms.symbol_by_index(idx) {
-                    let name = match sym.name() {
-                        Ok(name) => name,
-                        Err(..) => continue,
-                    };
-
-                    let addr = match reloc.kind() {
-                        // hard-coded address to function which doesn't require a relocation
-                        RelocationKind::Absolute => r_offset as usize,
-                        RelocationKind::Elf(elf::R_X86_64_GLOB_DAT) => r_offset as usize,
-                        RelocationKind::Elf(elf::R_X86_64_COPY) => r_offset as usize,
-                        // address in .got.plt section which contains an address to the function
-                        RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) => {
-                            let width = if self.obj.is_64() { 8 } else { 4 };
-
-                            let bytes = match section.data_range(r_offset, width) {
-                                Ok(Some(bytes)) => bytes,
-                                _ => continue,
-                            };
-
-                            let phys_addr = if self.obj.is_64() {
-                                self.obj.endian().read_u64_bytes(bytes.try_into().unwrap()) as usize
-                            } else {
-                                self.obj.endian().read_u32_bytes(bytes.try_into().unwrap()) as usize
-                            };
-
-                            // idk why we need this
-                            phys_addr.saturating_sub(6)
-                        }
-                        _ => continue,
-                    };
-
-                    // TODO: find modules
+                if let Some(&name) = slots.get(&slot) {
                     self.syms.push(Addressed {
                         addr,
-                        item: RawSymbol { name, module: None },
+                        item: RawSymbol {
+                            name,
+                            module: None,
+                            indirection: Some(Indirection::Plt),
+                        },
                     });
                 }
             }
@@ -96,14 +267,223 @@ impl<'data, Elf: FileHeader> ElfDebugInfo<'data, Elf> {
 
     pub fn parse_symbols(&mut self) {
         self.syms.extend(crate::parse_symbol_table(self.obj));
+
+        let mut entry = self.obj.entry() as usize;
+        match self.obj.architecture() {
+            Architecture::Arm => {
+                self.parse_arm_modes();
+                if entry & 1 == 1 {
+                    entry &= !1;
+                    self.thumb_functions.push(entry);
+                }
+            }
+            Architecture::Aarch64 => {
+                self.syms.retain(|sym| !is_aarch64_mapping_symbol(sym.item.name));
+            }
+            _ => {}
+        }
+
         self.syms.push(Addressed {
-            addr: self.obj.entry() as usize,
+            addr: entry,
             item: RawSymbol {
                 name: "entry",
                 module: None,
+                indirection: None,
             },
         });
     }
+
+    /// Take the mapping symbols out of the symbols, as they don't name anything, and clear the
+    /// lowest bit of Thumb functions.
+    fn parse_arm_modes(&mut self) {
+        let modes = &mut self.modes;
+        self.syms.retain(|sym| match ArmMode::from_mapping_symbol(sym.item.name) {
+            Some(mode) => {
+                modes.push((sym.addr, mode));
+                false
+            }
+            None => true,
+        });
+
+        let mut thumb: Vec<usize> = self
+            .obj
+            .symbols()
+            .filter(|sym| sym.kind() == SymbolKind::Text && sym.address() & 1 == 1)
+            .map(|sym| sym.address() as usize)
+            .collect();
+
+        thumb.sort_unstable();
+        for sym in self.syms.iter_mut() {
+            if thumb.binary_search(&sym.addr).is_ok() {
+                sym.addr &= !1;
+            }
+        }
+
+        self.thumb_functions.extend(thumb.into_iter().map(|addr| addr & !1));
+        self.modes.sort_by_key(|&(addr, _)| addr);
+    }
+}
+
+/// Libraries the binary needs and where it asks for them to be searched, as found in it's
+/// dynamic section.
+pub fn dependencies<Elf: FileHeader>(obj: &ElfFile<Elf>) -> Result<Dependencies, object::Error> {
+    let endian = obj.endian();
+    let data = obj.data();
+    let sections = obj.raw_header().sections(endian, data)?;
+
+    let mut deps = Dependencies::default();
+    let (dynamic, link) = match sections.dynamic(endian, data)? {
+        Some(dynamic) => dynamic,
+        None => return Ok(deps),
+    };
+
+    let strings = sections.strings(endian, data, link)?;
+    for entry in dynamic {
+        let tag = match entry.tag32(endian) {
+            Some(tag @ (elf::DT_NEEDED | elf::DT_RPATH | elf::DT_RUNPATH)) => tag,
+            _ => continue,
+        };
+
+        let value = String::from_utf8_lossy(entry.string(endian, strings)?).into_owned();
+        let dirs = value.split(':').filter(|dir| !dir.is_empty()).map(str::to_string);
+        match tag {
+            elf::DT_NEEDED => deps.libraries.push(value),
+            elf::DT_RPATH => deps.rpaths.extend(dirs),
+            _ => deps.runpaths.extend(dirs),
+        }
+    }
+
+    Ok(deps)
+}
+
+/// Size of a PLT stub, unless the section says otherwise.
+const PLT_ENTRY_SIZE: usize = 16;
+
+/// Whether a relocation copies the symbol's data into the binary.
+fn is_copy(arch: Architecture, r_type: u32) -> bool {
+    match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => r_type == elf::R_X86_64_COPY,
+        Architecture::I386 => r_type == elf::R_386_COPY,
+        Architecture::Arm => r_type == elf::R_ARM_COPY,
+        Architecture::Aarch64 => r_type == elf::R_AARCH64_COPY,
+        Architecture::Riscv32 | Architecture::Riscv64 => r_type == elf::R_RISCV_COPY,
+        _ => false,
+    }
+}
+
+/// Whether a relocation fills a GOT slot with the symbol's address.
+fn is_got_slot(arch: Architecture, r_type: u32) -> bool {
+    match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => {
+            matches!(r_type, elf::R_X86_64_GLOB_DAT | elf::R_X86_64_JUMP_SLOT)
+        }
+        Architecture::I386 => matches!(r_type, elf::R_386_GLOB_DAT | elf::R_386_JMP_SLOT),
+        Architecture::Arm => matches!(r_type, elf::R_ARM_GLOB_DAT | elf::R_ARM_JUMP_SLOT),
+        Architecture::Aarch64 => {
+            matches!(r_type, elf::R_AARCH64_GLOB_DAT | elf::R_AARCH64_JUMP_SLOT)
+        }
+        Architecture::Riscv32 | Architecture::Riscv64 => r_type == elf::R_RISCV_JUMP_SLOT,
+        _ => false,
+    }
+}
+
+/// GOT slot a PLT stub at `addr` jumps through.
+fn plt_slot(arch: Architecture, stub: &[u8], addr: usize) -> Option<usize> {
+    match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => x86_plt_slot(stub, addr, true),
+        Architecture::I386 => x86_plt_slot(stub, addr, false),
+        Architecture::Aarch64 => aarch64_plt_slot(stub, addr, true),
+        Architecture::Aarch64_Ilp32 => aarch64_plt_slot(stub, addr, false),
+        Architecture::Riscv64 => riscv_plt_slot(stub, addr, true),
+        Architecture::Riscv32 => riscv_plt_slot(stub, addr, false),
+        _ => None,
+    }
+}
+
+/// GOT slot an x86 PLT stub at `addr` jumps through.
+///
+/// Stubs start with `jmp [rip + disp32]` or `jmp [disp32]` on 32-bit, optionally preceded by
+/// an `endbr` and the jump having a `bnd` prefix.
+fn x86_plt_slot(stub: &[u8], addr: usize, is_64: bool) -> Option<usize> {
+    let jmp = stub.windows(2).position(|opcode| opcode == [0xff, 0x25])?;
+    let disp = stub.get(jmp + 2..jmp + 6)?;
+    let disp = i32::from_le_bytes(disp.try_into().unwrap());
+
+    if is_64 {
+        Some((addr + jmp + 6).wrapping_add_signed(disp as isize))
+    } else {
+        Some(disp as u32 as usize)
+    }
+}
+
+/// Little endian 32-bit instructions of a stub, together with their offset.
+fn words(stub: &[u8]) -> impl Iterator<Item = (usize, u32)> + '_ {
+    stub.chunks_exact(4)
+        .enumerate()
+        .map(|(idx, word)| (idx * 4, u32::from_le_bytes(word.try_into().unwrap())))
+}
+
+/// GOT slot an AArch64 PLT stub at `addr` jumps through.
+///
+/// Stubs start with `adrp x16, page` followed by `ldr x17, [x16, #offset]`, or a `ldr w17` when
+/// pointers are 32-bit, optionally preceded by a `bti c`.
+fn aarch64_plt_slot(stub: &[u8], addr: usize, is_64: bool) -> Option<usize> {
+    let (ldr, scale) = match is_64 {
+        true => (0xf9400000, 8),
+        false => (0xb9400000, 4),
+    };
+
+    let words: Vec<(usize, u32)> = words(stub).collect();
+    words.windows(2).find_map(|pair| {
+        let [(offset, adrp), (_, load)] = [pair[0], pair[1]];
+
+        // adrp x16, page
+        if adrp & 0x9f00001f != 0x90000010 {
+            return None;
+        }
+
+        // ldr x17, [x16, #imm12 * scale]
+        if load & 0xffc003ff != ldr | (16 << 5) | 17 {
+            return None;
+        }
+
+        let immlo = (adrp >> 29) & 0x3;
+        let immhi = (adrp >> 5) & 0x7ffff;
+        let page = (((immhi << 2 | immlo) << 11) as i32 as i64) << 1;
+        let page = ((addr + offset) & !0xfff).wrapping_add_signed(page as isize);
+        let imm12 = (load >> 10) & 0xfff;
+        Some(page + imm12 as usize * scale)
+    })
+}
+
+/// GOT slot a RISC-V PLT stub at `addr` jumps through.
+///
+/// Stubs start with `auipc t3, hi` followed by `ld t3, lo(t3)`, or a `lw` on 32-bit.
+fn riscv_plt_slot(stub: &[u8], addr: usize, is_64: bool) -> Option<usize> {
+    // `ld` and `lw` only differ in their funct3.
+    let load = match is_64 {
+        true => 0x3003,
+        false => 0x2003,
+    };
+
+    let words: Vec<(usize, u32)> = words(stub).collect();
+    words.windows(2).find_map(|pair| {
+        let [(offset, auipc), (_, ld)] = [pair[0], pair[1]];
+
+        // auipc t3, hi
+        if auipc & 0xfff != 0xe17 {
+            return None;
+        }
+
+        // ld t3, lo(t3)
+        if ld & 0xfffff != (28 << 15) | load | (28 << 7) {
+            return None;
+        }
+
+        let hi = (auipc & 0xfffff000) as i32 as isize;
+        let lo = (ld as i32 >> 20) as isize;
+        Some((addr + offset).wrapping_add_signed(hi + lo))
+    })
 }
 
 /// Common ELF dwarf section names I've found so far.
@@ -130,6 +510,17 @@ const DWARF_SECTIONS: [&str; 20] = [
     ".debug_types",
 ];
 
+/// Address the sections of BPF objects are laid out from.
+const BPF_BASE: usize = 0x1000;
+
+/// Whether `obj` is a BPF object as compiled, before it's programs are loaded into the kernel.
+pub fn is_bpf_object<Elf: FileHeader>(obj: &ElfFile<'_, Elf>) -> bool {
+    obj.architecture() == Architecture::Bpf && obj.kind() == ObjectKind::Relocatable
+}
+
+/// Prefixes of sections holding constants and variables.
+const DATA_SECTIONS: [&str; 5] = [".rodata", ".data", ".bss", ".tdata", ".tbss"];
+
 fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Vec<Section> {
     let mut sections = Vec::new();
     let endian = obj.endian();
@@ -222,6 +613,11 @@ fn parse_sections<'data, Elf: FileHeader>(obj: &'data ElfFile<'data, Elf>) -> Ve
             }
         }
 
+        // Constants and variables.
+        if DATA_SECTIONS.iter().any(|prefix| name.starts_with(prefix)) {
+            kind = SectionKind::Data;
+        }
+
         // File path to gdb startup script.
         if name == ".debug_gdb_scripts" {
             kind = SectionKind::CString;
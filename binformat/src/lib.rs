@@ -3,11 +3,46 @@ use processor_shared::{AddressMap, Addressed};
 
 pub mod elf;
 pub mod macho;
+pub mod ordinals;
 pub mod pe;
 
 pub struct RawSymbol<'data> {
     pub name: &'data str,
     pub module: Option<&'data str>,
+    /// Set for stubs and slots the symbol is reached through, rather than the symbol itself.
+    pub indirection: Option<Indirection>,
+}
+
+/// How an imported symbol is reached through relocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indirection {
+    /// Stub in the procedure linkage table that jumps to the symbol.
+    Plt,
+    /// Slot in the global offset table that holds the symbol's address.
+    Got,
+}
+
+impl Indirection {
+    /// Appended to the name of the symbol.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Plt => "@plt",
+            Self::Got => "@got",
+        }
+    }
+}
+
+/// Shared libraries a binary imports and the directories it asks for them to be searched in.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Dependencies {
+    /// Names or paths of the libraries in the order they're loaded.
+    pub libraries: Vec<String>,
+    /// Directories searched first as they're stored, e.g. relative to `$ORIGIN` or
+    /// `@loader_path`.
+    pub rpaths: Vec<String>,
+    /// Directories of an ELF's `DT_RUNPATH`, which replaces `DT_RPATH` and is searched after
+    /// `LD_LIBRARY_PATH` instead of before it.
+    pub runpaths: Vec<String>,
 }
 
 fn parse_symbol_table<'data, Obj: Object<'data, 'data>>(
@@ -18,7 +53,11 @@ fn parse_symbol_table<'data, Obj: Object<'data, 'data>>(
         match sym.name() {
             Ok(name) => syms.push(Addressed {
                 addr: sym.address() as usize,
-                item: RawSymbol { name, module: None },
+                item: RawSymbol {
+                    name,
+                    module: None,
+                    indirection: None,
+                },
             }),
             Err(err) => {
                 log::complex!(
@@ -1,7 +1,7 @@
-use crate::RawSymbol;
+use crate::{Dependencies, RawSymbol};
 use processor_shared::{AddressMap, Addressed, Section, SectionKind};
 use object::macho::{self, DyldInfoCommand, DysymtabCommand, LinkeditDataCommand};
-use object::read::macho::{MachHeader, MachOFile, SymbolTable};
+use object::read::macho::{LoadCommandVariant, MachHeader, MachOFile, SymbolTable};
 use object::{Endianness, Object, ObjectSection, ObjectSegment, ReadRef, SectionFlags};
 use std::mem::size_of;
 
@@ -88,25 +88,122 @@ const DYLD_CHAINED_PTR_START_MULTI: u16 = 0x8000;
 /// used in chain_starts[] to denote last start in list for page.
 const DYLD_CHAINED_PTR_START_LAST: u16 = 0x8000;
 
-#[derive(Debug, Clone, Copy)]
-enum ChainedFixupPointerGeneric {
-    Generic32,
-    Generic64,
-    GenericArm64e,
-    Firmware32,
+/// What a pointer in a fixup chain is replaced with once loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChainedFixup {
+    /// Address of an import, by it's index into the imports table.
+    Bind(u64),
+    /// Address within the image.
+    Rebase(u64),
 }
 
-impl ChainedFixupPointerGeneric {
-    fn bind_and_stride(&self, ptr: u64) -> (bool, u64) {
-        match self {
-            Self::Generic32 => ((ptr >> 31) != 0, (ptr >> 21) & 0x3FF),
-            Self::Generic64 => ((ptr >> 63) != 0, (ptr >> 51) & 0xFFF),
-            Self::GenericArm64e => ((ptr >> 63) != 0, (ptr >> 52) & 0x7FF),
-            Self::Firmware32 => (false, (ptr >> 26) & 0x3F),
+/// Number of bytes between pointers in a chain together with the width of each pointer.
+fn chain_stride_and_width(format: u16) -> Option<(u64, usize)> {
+    match format {
+        DYLD_CHAINED_PTR_ARM64E
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => Some((8, 8)),
+        DYLD_CHAINED_PTR_ARM64E_KERNEL
+        | DYLD_CHAINED_PTR_64
+        | DYLD_CHAINED_PTR_64_OFFSET
+        | DYLD_CHAINED_PTR_64_KERNEL_CACHE => Some((4, 8)),
+        DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE => Some((1, 8)),
+        DYLD_CHAINED_PTR_32 | DYLD_CHAINED_PTR_32_CACHE | DYLD_CHAINED_PTR_32_FIRMWARE => {
+            Some((4, 4))
         }
+        // DYLD_CHAINED_PTR_ARM64E_FIRMWARE not supported anywhere by the looks of it.
+        _ => None,
     }
 }
 
+/// Decode a pointer of a chain, returns the fixup and how many strides away the next pointer is.
+fn decode_chained_ptr(format: u16, ptr: u64, base_addr: u64) -> Option<(ChainedFixup, u64)> {
+    let decoded = match format {
+        DYLD_CHAINED_PTR_ARM64E
+        | DYLD_CHAINED_PTR_ARM64E_KERNEL
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
+            let auth = ptr >> 63 != 0;
+            let bind = (ptr >> 62) & 1 != 0;
+            let fixup = if bind {
+                if format == DYLD_CHAINED_PTR_ARM64E_USERLAND24 {
+                    ChainedFixup::Bind(ptr & 0xFFFFFF)
+                } else {
+                    ChainedFixup::Bind(ptr & 0xFFFF)
+                }
+            } else if auth {
+                ChainedFixup::Rebase(base_addr + (ptr & 0xFFFFFFFF))
+            } else {
+                let target = ptr & 0x7FFFFFFFFFF;
+                let high8 = (ptr >> 43) & 0xFF;
+                // Only the original arm64e format stores targets as addresses.
+                let target = if format == DYLD_CHAINED_PTR_ARM64E {
+                    target
+                } else {
+                    base_addr + target
+                };
+                ChainedFixup::Rebase(target | high8 << 56)
+            };
+            (fixup, (ptr >> 51) & 0x7FF)
+        }
+        DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => {
+            let fixup = if ptr >> 63 != 0 {
+                ChainedFixup::Bind(ptr & 0xFFFFFF)
+            } else {
+                let target = ptr & 0xFFFFFFFFF;
+                let high8 = (ptr >> 36) & 0xFF;
+                let target = if format == DYLD_CHAINED_PTR_64_OFFSET {
+                    base_addr + target
+                } else {
+                    target
+                };
+                ChainedFixup::Rebase(target | high8 << 56)
+            };
+            (fixup, (ptr >> 51) & 0xFFF)
+        }
+        DYLD_CHAINED_PTR_64_KERNEL_CACHE | DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE => {
+            (ChainedFixup::Rebase(base_addr + (ptr & 0x3FFFFFFF)), (ptr >> 51) & 0xFFF)
+        }
+        DYLD_CHAINED_PTR_32 => {
+            let fixup = if (ptr >> 31) & 1 != 0 {
+                ChainedFixup::Bind(ptr & 0xFFFFF)
+            } else {
+                ChainedFixup::Rebase(ptr & 0x3FFFFFF)
+            };
+            (fixup, (ptr >> 26) & 0x1F)
+        }
+        DYLD_CHAINED_PTR_32_CACHE => {
+            (ChainedFixup::Rebase(base_addr + (ptr & 0x3FFFFFFF)), (ptr >> 30) & 0x3)
+        }
+        DYLD_CHAINED_PTR_32_FIRMWARE => (ChainedFixup::Rebase(ptr & 0x3FFFFFF), (ptr >> 26) & 0x3F),
+        _ => return None,
+    };
+
+    Some(decoded)
+}
+
+/// Where a segment is mapped in memory and stored in the file.
+struct SegmentMapping {
+    addr: u64,
+    size: u64,
+    file_off: u64,
+}
+
+/// Offset into the file where `addr` is stored.
+fn file_offset(segments: &[SegmentMapping], addr: u64) -> Option<u64> {
+    segments
+        .iter()
+        .find(|seg| (seg.addr..seg.addr + seg.size).contains(&addr))
+        .map(|seg| seg.file_off + (addr - seg.addr))
+}
+
+/// Offset of the header into the object's data, which is only non-zero for images of a shared
+/// cache as their data is the whole cache.
+fn header_offset<Mach: MachHeader>(obj: &MachOFile<Mach>) -> u64 {
+    let header = obj.raw_header() as *const Mach as usize;
+    header.wrapping_sub(obj.data().as_ptr() as usize) as u64
+}
+
 pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     /// Parsed Mach-O header.
     obj: &'data MachOFile<'data, Mach>,
@@ -116,6 +213,8 @@ pub struct MachoDebugInfo<'data, Mach: MachHeader> {
     dylibs: Vec<&'data str>,
     /// Any parsed but not yet relocated symbols.
     pub syms: AddressMap<RawSymbol<'data>>,
+    /// Pointers rebased by chained fixups, with the address they point to.
+    pub pointers: AddressMap<usize>,
     /// Parsed sections with extra metadata.
     pub sections: Vec<Section>,
     // ---- Required load commands ----
@@ -132,6 +231,7 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             obj,
             base_addr: obj.segments().next().map(|seg| seg.address()).unwrap_or(0),
             syms: AddressMap::default(),
+            pointers: AddressMap::default(),
             sections: Vec::new(),
             dylibs: Vec::new(),
             chained_fixups: None,
@@ -144,9 +244,20 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
         this.parse_load_cmds()?;
         this.parse_global_syms();
         if let Some(chained_fixups) = this.chained_fixups {
+            let segments: Vec<SegmentMapping> = obj
+                .segments()
+                .map(|seg| SegmentMapping {
+                    addr: seg.address(),
+                    size: seg.size(),
+                    file_off: seg.file_range().0,
+                })
+                .collect();
+
             parse_chained_fixups::<Mach>(
                 this.base_addr,
+                &segments,
                 &mut this.syms,
+                &mut this.pointers,
                 &this.dylibs,
                 chained_fixups,
                 this.obj.data(),
@@ -166,7 +277,9 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             self.dylibs.push("");
         }
 
-        let mut load_cmds_iter = header.load_commands(endian, self.obj.data(), 0)?;
+        // Images of a shared cache don't start at the beginning of the data.
+        let mut load_cmds_iter =
+            header.load_commands(endian, self.obj.data(), header_offset(self.obj))?;
         while let Some(lcmd) = load_cmds_iter.next()? {
             if let Some(cmd) = lcmd.symtab()? {
                 self.symtab = Some(cmd.symbols(endian, self.obj.data())?);
@@ -256,11 +369,35 @@ impl<'data, Mach: MachHeader<Endian = Endianness>> MachoDebugInfo<'data, Mach> {
             item: RawSymbol {
                 name: "entry",
                 module: None,
+                indirection: None,
             },
         });
     }
 }
 
+/// Dylibs the binary links against and it's `LC_RPATH` entries, which `@rpath` expands to.
+pub fn dependencies<Mach: MachHeader<Endian = Endianness>>(
+    obj: &MachOFile<Mach>,
+) -> Result<Dependencies, object::Error> {
+    let endian = obj.endian();
+    let mut deps = Dependencies::default();
+
+    let header = obj.raw_header();
+    let mut load_cmds_iter = header.load_commands(endian, obj.data(), header_offset(obj))?;
+    while let Some(lcmd) = load_cmds_iter.next()? {
+        if let Some(dylib) = lcmd.dylib()? {
+            let name = lcmd.string(endian, dylib.dylib.name)?;
+            deps.libraries.push(String::from_utf8_lossy(name).into_owned());
+        }
+        if let LoadCommandVariant::Rpath(rpath) = lcmd.variant()? {
+            let path = lcmd.string(endian, rpath.path)?;
+            deps.rpaths.push(String::from_utf8_lossy(path).into_owned());
+        }
+    }
+
+    Ok(deps)
+}
+
 /// Common Mach-O dwarf section names I've found so far.
 const DWARF_SECTIONS: [&str; 20] = [
     "__debug_abbrev",
@@ -305,7 +442,7 @@ fn parse_sections<'data, Mach: MachHeader>(obj: &'data MachOFile<'data, Mach>) -
             },
             // Section that doesn't occupy file space but is
             // zero-filled at runtime, used for uninitialized data.
-            macho::S_ZEROFILL => (SectionKind::Raw, "ZEROFILL"),
+            macho::S_ZEROFILL => (SectionKind::Data, "ZEROFILL"),
             // Section containing C string literals. Null-terminated ASCII strings.
             macho::S_CSTRING_LITERALS => (SectionKind::CString, "CSTRING_LITERALS"),
             // Section containing 4-byte literals.
@@ -382,6 +519,11 @@ fn parse_sections<'data, Mach: MachHeader>(obj: &'data MachOFile<'data, Mach>) -
             _ => (SectionKind::Raw, "UNKNOWN")
         };
 
+        // Constants and variables.
+        if kind == SectionKind::Raw && matches!(name.as_str(), "__data" | "__const" | "__common") {
+            kind = SectionKind::Data;
+        }
+
         // Section contains DWARF debug info.
         if DWARF_SECTIONS.contains(&name.as_str()) {
             kind = SectionKind::Debug;
@@ -450,7 +592,7 @@ fn parse_chained_import<'data>(
         imports.push(ImportEntry {
             lib_ordinal: (raw & 0xff) as u64,
             addend: 0,
-            weak: (raw >> 8) != 0,
+            weak: (raw >> 8) & 1 != 0,
             name,
         });
     }
@@ -477,15 +619,15 @@ fn parse_chained_import_addend<'data>(
         }
     };
 
-    let addend: u32 = *data.read_at(entry_off).unwrap_or(&0);
+    let addend = *data.read_at::<u32>(entry_off + size_of::<u32>() as u64).unwrap_or(&0) as i32;
     let sym_name_addr = symbols_addr + (raw >> 9) as u64;
     let sym_name_range = sym_name_addr..data.len() as u64;
     let sym_name = data.read_bytes_at_until(sym_name_range, 0).unwrap_or(&[]);
     if let Ok(name) = std::str::from_utf8(sym_name) {
         imports.push(ImportEntry {
             lib_ordinal: (raw & 0xff) as u64,
-            addend: addend as u64,
-            weak: (raw >> 8) != 0,
+            addend: addend as i64 as u64,
+            weak: (raw >> 8) & 1 != 0,
             name,
         });
     }
@@ -512,15 +654,15 @@ fn parse_chained_import_addend64<'data>(
         }
     };
 
-    let addend: u64 = *data.read_at(entry_off).unwrap_or(&0);
-    let sym_name_addr = symbols_addr + (raw >> 17) as u64;
+    let addend: u64 = *data.read_at(entry_off + size_of::<u64>() as u64).unwrap_or(&0);
+    let sym_name_addr = symbols_addr + (raw >> 32);
     let sym_name_range = sym_name_addr..data.len() as u64;
     let sym_name = data.read_bytes_at_until(sym_name_range, 0).unwrap_or(&[]);
     if let Ok(name) = std::str::from_utf8(sym_name) {
         imports.push(ImportEntry {
             lib_ordinal: raw & 0xffff,
             addend,
-            weak: (raw >> 16) != 0,
+            weak: (raw >> 16) & 1 != 0,
             name,
         });
     }
@@ -538,6 +680,7 @@ fn parse_page_starts_table_starts(page_starts: u64, page_count: u64, data: &[u8]
                     g idx.to_string(),
                     y "."
                 );
+                page_start_offs.push(Vec::new());
                 continue;
             }
         };
@@ -546,21 +689,14 @@ fn parse_page_starts_table_starts(page_starts: u64, page_count: u64, data: &[u8]
             let overflow_idx = (start & !DYLD_CHAINED_PTR_START_MULTI) as u64;
             let mut sub_page_addr = page_starts + size_of::<u16>() as u64 * overflow_idx;
             let mut page_start_sub_starts = Vec::new();
-            loop {
-                let sub_page_start: u16 = match data.read_at(sub_page_addr) {
-                    Ok(page_start) => *page_start,
-                    Err(()) => continue,
-                };
-                if sub_page_start & DYLD_CHAINED_PTR_START_LAST == 0 {
-                    page_start_sub_starts.push(sub_page_start);
-                    page_start_offs.push(page_start_sub_starts.clone());
-                    sub_page_addr += size_of::<u16>() as u64;
-                } else {
-                    page_start_sub_starts.push(sub_page_start & !DYLD_CHAINED_PTR_START_LAST);
-                    page_start_offs.push(page_start_sub_starts);
+            while let Ok(&sub_page_start) = data.read_at::<u16>(sub_page_addr) {
+                page_start_sub_starts.push(sub_page_start & !DYLD_CHAINED_PTR_START_LAST);
+                if sub_page_start & DYLD_CHAINED_PTR_START_LAST != 0 {
                     break;
                 }
+                sub_page_addr += size_of::<u16>() as u64;
             }
+            page_start_offs.push(page_start_sub_starts);
         } else {
             page_start_offs.push(vec![start]);
         }
@@ -569,9 +705,12 @@ fn parse_page_starts_table_starts(page_starts: u64, page_count: u64, data: &[u8]
     page_start_offs
 }
 
+#[allow(clippy::too_many_arguments)]
 fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
     base_addr: u64,
+    segments: &[SegmentMapping],
     syms: &mut AddressMap<RawSymbol<'data>>,
+    pointers: &mut AddressMap<usize>,
     dylibs: &[&'data str],
     chained_fixups: &LinkeditDataCommand<Mach::Endian>,
     data: &'data [u8],
@@ -667,21 +806,9 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
             }
         };
 
-        let (stride_size, format) = match starts.pointer_format {
-            DYLD_CHAINED_PTR_ARM64E
-            | DYLD_CHAINED_PTR_ARM64E_USERLAND
-            | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => (8, ChainedFixupPointerGeneric::GenericArm64e),
-            DYLD_CHAINED_PTR_ARM64E_KERNEL => (4, ChainedFixupPointerGeneric::GenericArm64e),
-            // DYLD_CHAINED_PTR_ARM64E_FIRMWARE not supported anywhere by the looks of it.
-            DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET | DYLD_CHAINED_PTR_64_KERNEL_CACHE => {
-                (4, ChainedFixupPointerGeneric::Generic64)
-            }
-            DYLD_CHAINED_PTR_32 | DYLD_CHAINED_PTR_32_CACHE => {
-                (4, ChainedFixupPointerGeneric::Generic32)
-            }
-            DYLD_CHAINED_PTR_32_FIRMWARE => (4, ChainedFixupPointerGeneric::Firmware32),
-            DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE => (1, ChainedFixupPointerGeneric::Generic64),
-            _ => {
+        let (stride, width) = match chain_stride_and_width(starts.pointer_format) {
+            Some(stride_and_width) => stride_and_width,
+            None => {
                 log::complex!(
                     w "[macho::parse_chained_fixups] ",
                     y "Unknown or unsupported pointer format ",
@@ -699,72 +826,45 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
         let page_start_offs = parse_page_starts_table_starts(chain_addr, page_count, data);
 
         for (jdx, page_starts) in page_start_offs.into_iter().enumerate() {
-            let page_addr = starts.segment_offset + jdx as u64 * starts.page_size as u64;
+            let page_addr =
+                base_addr + starts.segment_offset + jdx as u64 * starts.page_size as u64;
+
             for start in page_starts {
                 if start == DYLD_CHAINED_PTR_START_NONE {
                     continue;
                 }
 
-                let mut chain_entry_addr = page_addr + start as u64;
-                let mut fixups_done = false;
-                while !fixups_done {
-                    let ptr = match format {
-                        ChainedFixupPointerGeneric::Generic32
-                        | ChainedFixupPointerGeneric::Firmware32 => {
-                            data.read_at::<u32>(chain_entry_addr).map(|ptr| *ptr as u64)
-                        }
-                        ChainedFixupPointerGeneric::Generic64
-                        | ChainedFixupPointerGeneric::GenericArm64e => {
-                            data.read_at::<u64>(chain_entry_addr).copied()
+                let mut entry_addr = page_addr + start as u64;
+                loop {
+                    let bytes = file_offset(segments, entry_addr)
+                        .and_then(|off| data.read_bytes_at(off, width as u64).ok());
+
+                    let ptr = match bytes {
+                        Some(bytes) => {
+                            let mut ptr = [0u8; 8];
+                            ptr[..width].copy_from_slice(bytes);
+                            u64::from_le_bytes(ptr)
                         }
-                    };
-                    let ptr = match ptr {
-                        Ok(ptr) => ptr,
-                        Err(()) => {
+                        None => {
                             log::complex!(
                                 w "[macho::parse_chained_fixups] ",
-                                y "Couldn't read fixup pointer at offset ",
-                                g format!("{chain_entry_addr:#x}"),
+                                y "Couldn't read fixup pointer at ",
+                                g format!("{entry_addr:#x}"),
                                 y "."
                             );
-                            continue;
+                            break;
                         }
                     };
 
-                    let (bind, next_entry_stride_count) = format.bind_and_stride(ptr);
-
-                    if bind {
-                        let ordinal = match starts.pointer_format {
-                            DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => ptr & 0xFFFFF,
-                            DYLD_CHAINED_PTR_ARM64E
-                            | DYLD_CHAINED_PTR_ARM64E_KERNEL
-                            | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
-                                if starts.pointer_format == DYLD_CHAINED_PTR_ARM64E_USERLAND24 {
-                                    ptr & 0xFFFFFF
-                                } else {
-                                    ptr & 0xFFFF
-                                }
-                            }
-                            DYLD_CHAINED_PTR_32 => ptr & 0xFFFFF,
-                            _ => {
-                                log::complex!(
-                                    w "[macho::parse_chained_fixups] ",
-                                    y "Unknown bind format at ",
-                                    g format!("{chain_entry_addr:#x}"),
-                                    y "."
-                                );
-                                chain_entry_addr += next_entry_stride_count * stride_size;
-                                if next_entry_stride_count == 0 {
-                                    fixups_done = true;
-                                }
-                                continue;
-                            }
+                    let (fixup, next) =
+                        match decode_chained_ptr(starts.pointer_format, ptr, base_addr) {
+                            Some(decoded) => decoded,
+                            None => break,
                         };
 
-                        if let Some(entry) = imports.get(ordinal as usize) {
-                            let target_addr = base_addr + chain_entry_addr;
-
-                            if !entry.name.is_empty() {
+                    match fixup {
+                        ChainedFixup::Bind(ordinal) => match imports.get(ordinal as usize) {
+                            Some(entry) if !entry.name.is_empty() => {
                                 let module = dylibs.get(entry.lib_ordinal as usize).map(|lib| {
                                     // Strip path prefix.
                                     lib
@@ -775,82 +875,47 @@ fn parse_chained_fixups<'data, Mach: MachHeader<Endian = Endianness>>(
                                 });
 
                                 syms.push(Addressed {
-                                    addr: target_addr as usize,
+                                    addr: entry_addr as usize,
                                     item: RawSymbol {
                                         name: entry.name,
                                         module,
+                                        indirection: None,
                                     }
                                 });
-                            } else {
-                                log::complex!(
-                                    w "[macho::parse_chained_fixups] ",
-                                    y "Import table entry at ",
-                                    g format!("{target_addr:#x}"),
-                                    y " has no entries.",
-                                );
                             }
-                        } else {
-                            log::complex!(
+                            Some(_) => log::complex!(
+                                w "[macho::parse_chained_fixups] ",
+                                y "Import table entry at ",
+                                g format!("{entry_addr:#x}"),
+                                y " has no entries.",
+                            ),
+                            None => log::complex!(
                                 w "[macho::parse_chained_fixups] ",
                                 y "Ordinal ",
                                 g ordinal.to_string(),
                                 y " has no matching import.",
-                            );
-                        }
-                    } else {
-                        let _entry_addr = match starts.pointer_format {
-                            DYLD_CHAINED_PTR_ARM64E
-                            | DYLD_CHAINED_PTR_ARM64E_KERNEL
-                            | DYLD_CHAINED_PTR_ARM64E_USERLAND
-                            | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => {
-                                let auth = ptr & 1 != 0;
-                                let mut entry_addr = if auth { ptr & 0xFFFF } else { ptr & 0xFFFA };
-                                if starts.pointer_format != DYLD_CHAINED_PTR_ARM64E || auth {
-                                    entry_addr += base_addr;
-                                }
-                                entry_addr
-                            }
-                            DYLD_CHAINED_PTR_64 => ptr & 0x7FFFFFFFFFF,
-                            DYLD_CHAINED_PTR_64_OFFSET => (ptr & 0x7FFFFFFFFFF) + base_addr,
-                            DYLD_CHAINED_PTR_64_KERNEL_CACHE
-                            | DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE => ptr & 0x3FFFFFFF,
-                            DYLD_CHAINED_PTR_32
-                            | DYLD_CHAINED_PTR_32_CACHE
-                            | DYLD_CHAINED_PTR_32_FIRMWARE => ptr & 0x3FFFFFF,
-                            _ => {
-                                log::complex!(
-                                    w "[macho::parse_chained_fixups] ",
-                                    y "Unknown bind format at ",
-                                    g format!("{chain_entry_addr:#x}"),
-                                    y "."
-                                );
-                                chain_entry_addr += next_entry_stride_count * stride_size;
-                                if next_entry_stride_count == 0 {
-                                    fixups_done = true;
-                                }
-                                continue;
-                            }
-                        };
+                            ),
+                        },
+                        ChainedFixup::Rebase(target) => pointers.push(Addressed {
+                            addr: entry_addr as usize,
+                            item: target as usize,
+                        }),
+                    }
 
-                        let _reloc_addr = base_addr + chain_entry_addr;
-                        // FIXME: we just don't handle relocations.
-                        // Relocation from {reloc_addr:#x} to {entry_addr:#x}.
+                    if next == 0 {
+                        break;
                     }
 
-                    chain_entry_addr += next_entry_stride_count * stride_size;
+                    entry_addr += next * stride;
 
-                    if chain_entry_addr > page_addr + starts.page_size as u64 {
+                    if entry_addr >= page_addr + starts.page_size as u64 {
                         log::complex!(
                             w "[macho::parse_chained_fixups] ",
                             y "Pointer at ",
-                            g format!("{chain_entry_addr:#x}"),
+                            g format!("{entry_addr:#x}"),
                             y " left page."
                         );
-                        fixups_done = true;
-                    }
-
-                    if next_entry_stride_count == 0 {
-                        fixups_done = true;
+                        break;
                     }
                 }
             }
@@ -89,7 +89,11 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
                         std::str::from_utf8(module).ok().and_then(|x| x.strip_suffix(".dll"));
                     self.syms.push(Addressed {
                         addr: addr as usize,
-                        item: RawSymbol { name, module },
+                        item: RawSymbol {
+                            name,
+                            module,
+                            indirection: None,
+                        },
                     });
                 }
 
@@ -108,6 +112,7 @@ impl<'data, Pe: ImageNtHeaders> PeDebugInfo<'data, Pe> {
             item: RawSymbol {
                 name: "entry",
                 module: None,
+                indirection: None,
             },
         });
     }
@@ -137,6 +142,51 @@ const DWARF_SECTIONS: [&str; 20] = [
     ".debug_types",
 ];
 
+/// Imported modules and functions in import table order, functions imported by ordinal are
+/// named through [`crate::ordinals`] or `ord<N>` if it doesn't know them.
+pub fn import_names<Pe: ImageNtHeaders>(
+    obj: &PeFile<Pe>,
+) -> Result<Vec<(String, String)>, object::Error> {
+    let mut names = Vec::new();
+    let import_table = match obj.import_table()? {
+        Some(table) => table,
+        None => return Ok(names),
+    };
+
+    let mut import_descs = import_table.descriptors()?;
+    while let Some(import_desc) = import_descs.next()? {
+        let module = import_table.name(import_desc.name.get(LE))?;
+        let module = String::from_utf8_lossy(module).into_owned();
+
+        // The import address table is overwritten by the loader, prefer the lookup table.
+        let thunk = match import_desc.original_first_thunk.get(LE) {
+            0 => import_desc.first_thunk.get(LE),
+            thunk => thunk,
+        };
+
+        let mut thunks = import_table.thunks(thunk)?;
+        while let Some(func) = thunks.next::<Pe>()? {
+            let name = if func.is_ordinal() {
+                crate::ordinals::import_name(&module, func.ordinal())
+            } else {
+                match import_table.hint_name(func.address()) {
+                    Ok((_, name)) => String::from_utf8_lossy(name).into_owned(),
+                    Err(..) => continue,
+                }
+            };
+
+            names.push((module.clone(), name));
+        }
+    }
+
+    Ok(names)
+}
+
+/// Seconds since the unix epoch the linker wrote in the file header.
+pub fn timestamp<Pe: ImageNtHeaders>(obj: &PeFile<Pe>) -> u32 {
+    obj.nt_headers().file_header().time_date_stamp.get(LE)
+}
+
 fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> Vec<Section> {
     let mut sections = Vec::new();
 
@@ -153,6 +203,11 @@ fn parse_sections<'data, Pe: ImageNtHeaders>(obj: &'data PeFile<'data, Pe>) -> V
         let characteristics = header.characteristics.get(LE);
         let (mut kind, ident) = (SectionKind::Raw, "UNKNOWN");
 
+        // Constants and variables.
+        if matches!(name.as_str(), ".rdata" | ".data" | ".bss") {
+            kind = SectionKind::Data;
+        }
+
         // Section contains code.
         if characteristics & pe::IMAGE_SCN_CNT_CODE != 0 {
             kind = SectionKind::Code;
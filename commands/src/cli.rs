@@ -17,22 +17,93 @@ OPTIONS:
   -N, --names         Print all symbols exposed by object
   -S, --simplify      Replace common types with shortened paths
   -D, --disassemble   Path to object you're disassembling
+  -A, --strings       Print all strings found in the object's sections
+  -X, --dump-asm      Print the disassembly listing of an object
+  -F, --format        Format of the dumped listing: text, html or json
+  -Z, --analyze       Print a summary of the object's sections, functions and symbols
+  -M, --metadata      Print hashes, build information and the rich header of the object
+  -V, --sections      Print the object's segments and sections with their permissions
+      --checksec      Print the exploit mitigations the object was built with
+  -J, --json          Print the summary, metadata, sections or mitigations as JSON
+  -I, --image         Image of a dyld shared cache, or member of an archive or fat Mach-O, to open
+  -W, --raw           Load an object without a header, such as firmware, as code
+  -Q, --arch          Architecture of a raw object, e.g. x86_64, armv7 or riscv64
+  -O, --base          Address a raw object is loaded at, e.g. 0x8000000
+  -U, --endian        Endianness of a raw object: little or big
+  -E, --script        Run a rhai script against the object, given after the script
   -T, --tracing       Trace all syscalls performed
+  -P, --attach        Pid of a running process to debug
+  -G, --gdbserver     Serve the object to gdb on an address like :1234, given before the object
+  -K, --connect       Debug the object through a gdb stub at host:port, given before the object
+  -Y, --core          Inspect a core dump or minidump of the object, given before the object
+      --triage        Run each input in a directory given after the object, grouping crashes
   -C, --config        Path to config used for disassembling
-  -B, --debug         Enable extra debug information";
+  -B, --debug         Enable extra debug information
+  -R, --debug-render  Annotate each line with it's rendering cost";
 
-const ABBRV: &[&str] = &["-H", "-L", "-S", "-D", "-C", "-T", "-B"];
+const ABBRV: &[&str] = &[
+    "-H", "-L", "-S", "-D", "-A", "-X", "-F", "-Z", "-M", "-V", "-J", "-I", "-W", "-Q", "-O", "-U",
+    "-E", "-C", "-T", "-P", "-G", "-K", "-Y", "-B", "-R",
+];
 const NAMES: &[&str] = &[
     "--help",
     "--libs",
     "--names",
     "--simplify",
     "--disassemble",
+    "--strings",
+    "--dump-asm",
+    "--format",
+    "--analyze",
+    "--metadata",
+    "--sections",
+    "--checksec",
+    "--json",
+    "--image",
+    "--raw",
+    "--arch",
+    "--base",
+    "--endian",
+    "--script",
     "--tracing",
+    "--attach",
+    "--gdbserver",
+    "--connect",
+    "--core",
+    "--triage",
     "--config",
     "--debug",
+    "--debug-render",
 ];
 
+/// Formats a listing can be exported to.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    #[default]
+    Text,
+    Html,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "text" | "txt" => Some(Self::Text),
+            "html" | "htm" => Some(Self::Html),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Html => "html",
+            Self::Json => "json",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Cli {
     /// Print shared libraries the object is linked against.
@@ -47,15 +118,78 @@ pub struct Cli {
     /// Disassemble object into `readable` assembly,
     pub disassemble: bool,
 
+    /// Print strings found in the object's sections.
+    pub strings: bool,
+
+    /// Print the disassembly listing.
+    pub dump_asm: bool,
+
+    /// Format of the printed listing.
+    pub format: Option<ExportFormat>,
+
+    /// Print a summary of the analysis without opening the GUI.
+    pub analyze: bool,
+
+    /// Print hashes and build information without opening the GUI.
+    pub metadata: bool,
+
+    /// Print segments and sections without opening the GUI.
+    pub sections: bool,
+
+    /// Print exploit mitigations such as RELRO, NX and PIE without opening the GUI.
+    pub checksec: bool,
+
+    /// Print the summary, metadata, sections or mitigations as JSON.
+    pub json: bool,
+
+    /// Image to open when the object is a dyld shared cache, archive or fat Mach-O.
+    pub image: Option<String>,
+
+    /// Load the object as code without a header.
+    pub raw: bool,
+
+    /// Architecture of a raw object.
+    pub arch: Option<String>,
+
+    /// Address a raw object is loaded at.
+    pub base: Option<usize>,
+
+    /// Whether a raw object is big-endian, defaulting to the architecture's usual endianness.
+    pub big_endian: Option<bool>,
+
+    /// Script to run against the object.
+    pub script: Option<PathBuf>,
+
     /// Record syscalls.
     pub tracing: bool,
 
     /// Show egui debug overlay.
     pub debug: bool,
 
+    /// Show the number of tokens and glyphs each line is made of.
+    pub debug_render: bool,
+
     /// Path to symbol being disassembled.
     pub path: Option<PathBuf>,
 
+    /// Pid of a running process to attach to.
+    pub attach: Option<u32>,
+
+    /// Address to serve the GDB remote protocol on.
+    pub gdbserver: Option<String>,
+
+    /// Address of a GDB remote protocol stub to debug through.
+    pub connect: Option<String>,
+
+    /// Core dump or minidump to inspect.
+    pub core: Option<PathBuf>,
+
+    /// Directory of inputs that crash the object, which are run and grouped by how they crash.
+    pub triage: Option<PathBuf>,
+
+    /// Arguments the object is run with whilst triaging, `@@` being replaced by each input.
+    pub triage_args: Vec<String>,
+
     /// Optional path to config.
     pub config: Option<PathBuf>,
 }
@@ -96,8 +230,157 @@ impl Cli {
                         }
                     }
                 }
+                "-A" | "--strings" => {
+                    cli.strings = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-X" | "--dump-asm" => {
+                    cli.dump_asm = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-F" | "--format" => match args.next().as_deref().map(ExportFormat::parse) {
+                    Some(Some(format)) => cli.format = Some(format),
+                    _ => exit!(1 => "Missing or invalid format, expected text, html or json."),
+                },
+                "-Z" | "--analyze" => {
+                    cli.analyze = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-M" | "--metadata" => {
+                    cli.metadata = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-V" | "--sections" => {
+                    cli.sections = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "--checksec" => {
+                    cli.checksec = true;
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-J" | "--json" => cli.json = true,
+                "-I" | "--image" => match args.next() {
+                    Some(image) => cli.image = Some(image),
+                    None => exit!(1 => "Missing image to open."),
+                },
+                "-W" | "--raw" => cli.raw = true,
+                "-Q" | "--arch" => match args.next() {
+                    Some(arch) => cli.arch = Some(arch),
+                    None => exit!(1 => "Missing architecture of the raw object."),
+                },
+                "-O" | "--base" => {
+                    let index = debugvault::Index::default();
+                    match args.next().map(|addr| crate::debug::eval_address(&addr, &index)) {
+                        Some(Ok(addr)) => cli.base = Some(addr),
+                        _ => exit!(1 => "Missing or invalid address to load the raw object at."),
+                    }
+                }
+                "-U" | "--endian" => match args.next().as_deref() {
+                    Some("little" | "le") => cli.big_endian = Some(false),
+                    Some("big" | "be") => cli.big_endian = Some(true),
+                    _ => exit!(1 => "Missing or invalid endianness, expected little or big."),
+                },
+                "-E" | "--script" => {
+                    match args.next() {
+                        Some(path) => cli.script = Some(PathBuf::from(path)),
+                        None => exit!(1 => "Missing path to a script."),
+                    }
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
                 "-T" | "--tracing" => cli.tracing = true,
+                "-P" | "--attach" => match args.next().map(|pid| pid.parse()) {
+                    Some(Ok(pid)) => cli.attach = Some(pid),
+                    _ => exit!(1 => "Missing or invalid pid to attach to."),
+                },
+                "-G" | "--gdbserver" => {
+                    match args.next() {
+                        Some(addr) => cli.gdbserver = Some(addr),
+                        None => exit!(1 => "Missing address to listen on."),
+                    }
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-K" | "--connect" => {
+                    match args.next() {
+                        Some(addr) => cli.connect = Some(addr),
+                        None => exit!(1 => "Missing address to connect to."),
+                    }
+
+                    if let Some(path) = args.next().as_deref() {
+                        if !NAMES.contains(&path) && !ABBRV.contains(&path) {
+                            cli.path = Some(PathBuf::from(path));
+                        }
+                    }
+                }
+                "-Y" | "--core" => {
+                    match args.next() {
+                        Some(core) => cli.core = Some(PathBuf::from(core)),
+                        None => exit!(1 => "Missing path to a core dump."),
+                    }
+
+                    // The object is otherwise found through the core.
+                    if let Some(path) = args.peek() {
+                        if !NAMES.contains(&path.as_str()) && !ABBRV.contains(&path.as_str()) {
+                            cli.path = args.next().map(PathBuf::from);
+                        }
+                    }
+                }
+                "--triage" => {
+                    cli.path = args.next().map(PathBuf::from);
+                    match args.next() {
+                        Some(dir) => cli.triage = Some(PathBuf::from(dir)),
+                        None => exit!(1 => "Missing directory of crashing inputs."),
+                    }
+
+                    // The object's arguments go up to the next option.
+                    let is_arg = |arg: &String| {
+                        !NAMES.contains(&arg.as_str()) && !ABBRV.contains(&arg.as_str())
+                    };
+                    while let Some(arg) = args.next_if(is_arg) {
+                        cli.triage_args.push(arg);
+                    }
+                }
                 "-B" | "--debug" => cli.debug = true,
+                "-R" | "--debug-render" => cli.debug_render = true,
                 unknown => {
                     let mut distance = u32::MAX;
                     let mut best_guess = "";
@@ -124,7 +407,127 @@ impl Cli {
     }
 
     fn validate_args(&mut self) {
-        if self.disassemble || self.libs || self.names {
+        // The binary is found through the process.
+        if self.attach.is_some()
+            && (self.path.is_some()
+                || self.libs
+                || self.names
+                || self.strings
+                || self.dump_asm
+                || self.analyze
+                || self.metadata
+                || self.sections
+                || self.checksec
+                || self.raw
+                || self.script.is_some()
+                || self.gdbserver.is_some()
+                || self.connect.is_some()
+                || self.core.is_some()
+                || self.triage.is_some()
+                || self.tracing)
+        {
+            exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+        }
+
+        if self.format.is_some() && !self.dump_asm {
+            exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+        }
+
+        if self.json
+            && !self.analyze
+            && !self.metadata
+            && !self.sections
+            && !self.checksec
+            && self.triage.is_none()
+        {
+            exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+        }
+
+        // Remote targets are debugged in the GUI, syscalls can't be traced through a stub.
+        if self.connect.is_some() {
+            if self.path.is_none() {
+                exit!(1 => "Missing path to the object the remote target is running.");
+            }
+
+            if self.tracing
+                || self.libs
+                || self.names
+                || self.strings
+                || self.dump_asm
+                || self.analyze
+                || self.metadata
+                || self.sections
+                || self.checksec
+                || self.script.is_some()
+                || self.gdbserver.is_some()
+                || self.triage.is_some()
+            {
+                exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+            }
+        }
+
+        // Cores are inspected in the GUI and can't be resumed.
+        if self.core.is_some()
+            && (self.tracing
+                || self.libs
+                || self.names
+                || self.strings
+                || self.dump_asm
+                || self.analyze
+                || self.metadata
+                || self.sections
+                || self.checksec
+                || self.raw
+                || self.image.is_some()
+                || self.script.is_some()
+                || self.gdbserver.is_some()
+                || self.connect.is_some()
+                || self.triage.is_some())
+        {
+            exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+        }
+
+        if self.image.is_some() && self.path.is_none() {
+            exit!(1 => "Missing path to a shared cache.");
+        }
+
+        // Raw objects have no header to find the architecture or the entrypoint in.
+        if self.raw {
+            if self.arch.is_none() {
+                exit!(1 => "Missing architecture of the raw object, see `--arch`.");
+            }
+
+            if self.image.is_some()
+                || self.checksec
+                || self.gdbserver.is_some()
+                || self.connect.is_some()
+                || self.triage.is_some()
+            {
+                exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+            }
+        } else if self.arch.is_some() || self.base.is_some() || self.big_endian.is_some() {
+            exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
+        }
+
+        let script = self.script.is_some();
+        let gdbserver = self.gdbserver.is_some();
+        let triage = self.triage.is_some();
+        let actions = [
+            self.disassemble,
+            self.libs,
+            self.names,
+            self.strings,
+            self.dump_asm,
+            self.analyze,
+            self.metadata,
+            self.sections,
+            self.checksec,
+            script,
+            gdbserver,
+            triage,
+        ];
+
+        if actions.contains(&true) {
             if self.path.is_none() {
                 exit!(1 => "Missing path to an object.");
             }
@@ -138,7 +541,7 @@ impl Cli {
             exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
         }
 
-        if self.disassemble as usize + self.libs as usize + self.names as usize > 1 {
+        if actions.iter().filter(|&&action| action).count() > 1 {
             exit!(1 => "Invalid combination of arguements.\n\n{HELP}");
         }
     }
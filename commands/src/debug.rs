@@ -2,18 +2,30 @@
 //!
 //! ```text
 //! <input> = <ws> <expr> <ws>
-//! <expr>  = <compound> | <number> | <symbol>
+//! <expr>  = <compound> | <unary>
+//! <unary> = '*' <ws> <unary> | <cast> <ws> <unary> | <number> | <symbol> | <register>
 //!
 //! <number> = ['-'] <integer> | ['-'] <hex>
 //!
 //! <hex>     = '0' ('x' | 'X') {'A'..'F' | 'a'..'f' | '0'..'9'}+
 //! <integer> = {'0'..'9'}+
 //!
-//! <symbol>  = {<characters>}+ # if known in database
+//! <symbol>   = {<characters>}+ # if known in database, otherwise a register
+//! <register> = '$' {<characters>}+
+//!
+//! <cast> = '(' <ws> <type> <ws> {'*'}+ <ws> ')' # e.g. '(unsigned int *)'
 //!
 //! <compound> = <expr> <ws> <op> <ws> <expr> | '(' <ws> <expr> <ws> ')'
-//! <op> = '+' | '-' | '*' | '/' | '%'
+//! <op> = '+' | '-' | '*' | '/' | '%' | '==' | '!=' | '<' | '<=' | '>' | '>=' | '&&' | '||'
 //! ```
+//!
+//! Operators bind like they do in C. Comparisons and logical operators evaluate to 1 or 0, the
+//! right side of '&&' and '||' only being evaluated if it's needed. As symbols can contain
+//! generics, '<' and '>' have to be separated from a symbol by whitespace.
+//!
+//! Registers and dereferences need a stopped process, see [`Machine`]. Dereferencing reads a
+//! word unless what's dereferenced is cast to a pointer of a narrower integer. Pointer
+//! arithmetic isn't scaled by the size of what's pointed to.
 
 // TODO: Implement binary presidence (10 + 10 * 10 == 110).
 //       This likely requires parsing in two steps where we first generate tokens.
@@ -23,6 +35,37 @@ use std::fmt;
 
 const MAX_DEPTH: usize = 256;
 
+/// Integer types that expressions can be cast to pointers of, by their size and signedness.
+const TYPES: &[(&[&str], Width)] = &[
+    (&["char", "signed char", "int8_t", "i8"], Width::new(1, true)),
+    (&["unsigned char", "uint8_t", "u8", "bool", "_Bool"], Width::new(1, false)),
+    (&["short", "short int", "signed short", "int16_t", "i16"], Width::new(2, true)),
+    (&["unsigned short", "unsigned short int", "uint16_t", "u16"], Width::new(2, false)),
+    (&["int", "signed", "signed int", "int32_t", "i32"], Width::new(4, true)),
+    (&["unsigned", "unsigned int", "uint32_t", "u32"], Width::new(4, false)),
+    (
+        &["long", "long int", "long long", "signed long", "int64_t", "ssize_t", "i64", "isize"],
+        Width::new(8, true),
+    ),
+    (
+        &["unsigned long", "unsigned long long", "uint64_t", "size_t", "void", "u64", "usize"],
+        Width::new(8, false),
+    ),
+];
+
+/// Operators comparing the operands they're between.
+const COMPARISONS: &[(&str, Operator)] = &[
+    ("==", Operator::Eq),
+    ("!=", Operator::Ne),
+    ("<=", Operator::Le),
+    (">=", Operator::Ge),
+    ("<", Operator::Lt),
+    (">", Operator::Gt),
+];
+
+/// What's read when dereferencing an expression that wasn't cast.
+const WORD: Width = Width::new(std::mem::size_of::<usize>(), false);
+
 type Span = std::ops::RangeInclusive<usize>;
 
 /// Trait for indicating that any error has to be propagated up.
@@ -44,6 +87,44 @@ impl<T> Failing for Option<T> {
     }
 }
 
+/// A stopped process that expressions can refer to the registers and memory of.
+pub trait Machine {
+    /// Value of a register by it's name, e.g. `rax`.
+    fn register(&self, name: &str) -> Option<u64>;
+
+    /// Read `len` bytes of memory at runtime address `addr`.
+    fn read(&self, addr: usize, len: usize) -> Option<Vec<u8>>;
+
+    /// Offset between addresses in the binary and where it's loaded, added to symbols.
+    fn bias(&self) -> usize;
+}
+
+/// Integer that's read when dereferencing a pointer to it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Width {
+    size: usize,
+    signed: bool,
+}
+
+impl Width {
+    const fn new(size: usize, signed: bool) -> Self {
+        Self { size, signed }
+    }
+
+    /// Integer stored in the little endian `bytes`.
+    fn decode(self, bytes: &[u8]) -> isize {
+        let mut word = [0; 8];
+        word[..bytes.len()].copy_from_slice(bytes);
+
+        let value = u64::from_le_bytes(word);
+        let shift = 64 - self.size as u32 * 8;
+        match self.signed {
+            true if shift != 0 => ((value << shift) as i64 >> shift) as isize,
+            _ => value as isize,
+        }
+    }
+}
+
 /// Index into expression arena.
 #[derive(Debug, PartialEq, Clone, Copy)]
 struct ExprRef(usize);
@@ -289,7 +370,7 @@ impl<'src> Context<'src> {
         loop {
             match self.peek() {
                 // operators are ambiguous
-                Some('+' | '-' | '*' | '/' | '%') if depth == 0 => break,
+                Some('+' | '-' | '*' | '/' | '%' | '=' | '!' | '&' | '|') if depth == 0 => break,
                 // brackets are ambiguous
                 Some('(' | ')') if depth == 0 => break,
                 // whitespace is ambiguous
@@ -314,6 +395,49 @@ impl<'src> Context<'src> {
         Ok(&self.src[start..self.offset])
     }
 
+    /// Reads a cast to a pointer such as `(unsigned int *)`, returning the width of what it
+    /// points to. Leaves the stream untouched if there isn't one.
+    fn cast(&mut self) -> Option<Width> {
+        let inner = self.src().strip_prefix('(')?;
+        let end = inner.find(')')?;
+        let ty = inner[..end].trim();
+        let name = ty.trim_end_matches(|chr: char| chr == '*' || chr.is_whitespace());
+        let pointers = ty[name.len()..].matches('*').count();
+        if pointers == 0 {
+            return None;
+        }
+
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        let &(_, width) = TYPES.iter().find(|(names, _)| names.contains(&name.as_str()))?;
+        self.offset += end + 2;
+
+        // Pointers to pointers read a pointer.
+        Some(if pointers == 1 { width } else { WORD })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, Error> {
+        self.consume_whitespace();
+
+        if self.consume('*').is_ok() {
+            self.descent()?;
+            let inner = self.parse_unary()?;
+            self.ascent();
+            return Ok(Expr::Deref(self.store(inner)));
+        }
+
+        if let Some(width) = self.cast() {
+            self.descent()?;
+            let inner = self.parse_unary()?;
+            self.ascent();
+            return Ok(Expr::Cast {
+                width,
+                expr: self.store(inner),
+            });
+        }
+
+        self.parse_primary()
+    }
+
     fn parse_primary(&mut self) -> Result<Expr, Error> {
         self.consume_whitespace();
 
@@ -341,14 +465,14 @@ impl<'src> Context<'src> {
     }
 
     fn parse_high_precedence(&mut self) -> Result<Expr, Error> {
-        let mut lhs = self.parse_primary()?;
+        let mut lhs = self.parse_unary()?;
 
         loop {
             self.consume_whitespace();
             match self.peek() {
                 Some('*') => {
                     self.consume('*')?;
-                    let rhs = self.parse_primary()?;
+                    let rhs = self.parse_unary()?;
                     lhs = Expr::Compound {
                         lhs: self.store(lhs),
                         op: Operator::Mul,
@@ -357,7 +481,7 @@ impl<'src> Context<'src> {
                 }
                 Some('/') => {
                     self.consume('/')?;
-                    let rhs = self.parse_primary()?;
+                    let rhs = self.parse_unary()?;
                     lhs = Expr::Compound {
                         lhs: self.store(lhs),
                         op: Operator::Div,
@@ -366,7 +490,7 @@ impl<'src> Context<'src> {
                 }
                 Some('%') => {
                     self.consume('%')?;
-                    let rhs = self.parse_primary()?;
+                    let rhs = self.parse_unary()?;
                     lhs = Expr::Compound {
                         lhs: self.store(lhs),
                         op: Operator::Mod,
@@ -411,9 +535,54 @@ impl<'src> Context<'src> {
         Ok(lhs)
     }
 
+    /// Operator out of `ops` at the start of the stream, which is consumed.
+    fn operator(&mut self, ops: &[(&str, Operator)]) -> Option<Operator> {
+        let &(token, op) = ops.iter().find(|(token, _)| self.src().starts_with(token))?;
+        self.offset += token.len();
+        Some(op)
+    }
+
+    /// Operands parsed with `operand`, separated by any of the left associative `ops`.
+    fn parse_binary(
+        &mut self,
+        ops: &[(&str, Operator)],
+        operand: fn(&mut Self) -> Result<Expr, Error>,
+    ) -> Result<Expr, Error> {
+        let mut lhs = operand(self)?;
+
+        loop {
+            self.consume_whitespace();
+            let op = match self.operator(ops) {
+                Some(op) => op,
+                None => break,
+            };
+
+            let rhs = operand(self)?;
+            lhs = Expr::Compound {
+                lhs: self.store(lhs),
+                op,
+                rhs: self.store(rhs),
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, Error> {
+        self.parse_binary(COMPARISONS, Self::parse_low_precedence)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, Error> {
+        self.parse_binary(&[("&&", Operator::And)], Self::parse_comparison)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, Error> {
+        self.parse_binary(&[("||", Operator::Or)], Self::parse_and)
+    }
+
     fn expr_inner(&mut self) -> Result<Expr, Error> {
         self.descent()?;
-        let expr = self.parse_low_precedence()?;
+        let expr = self.parse_or()?;
         self.ascent();
         Ok(expr)
     }
@@ -434,13 +603,21 @@ impl<'src> Context<'src> {
 }
 
 /// Basic mathematical operations.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 enum Operator {
     Add,
     Min,
     Mul,
     Div,
     Mod,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
 }
 
 /// Representation of any given expression.
@@ -456,6 +633,13 @@ enum Expr {
         op: Operator,
         rhs: ExprRef,
     },
+    /// Value in memory at the address.
+    Deref(ExprRef),
+    /// Address of the integer that's read when it's dereferenced.
+    Cast {
+        width: Width,
+        expr: ExprRef,
+    },
 }
 
 /// Storable [`Expr`] with needed [`ExprRef`]'s.
@@ -474,19 +658,76 @@ impl CompleteExpr {
         &self.children[expr_ref.0]
     }
 
-    fn eval_recursive(&self, node: &Expr, index: &Index) -> Result<isize, Error> {
+    fn eval_recursive(
+        &self,
+        node: &Expr,
+        index: &Index,
+        machine: Option<&dyn Machine>,
+    ) -> Result<isize, Error> {
         match node {
             Expr::Number(val) => Ok(*val),
-            Expr::Symbol { val, .. } => match index.get_func_by_name(val) {
-                Some(addr) => Ok(addr as isize),
-                None => Err(Error {
+            Expr::Symbol { val, .. } => {
+                let name = val.strip_prefix('$');
+                let addr = match name {
+                    Some(_) => None,
+                    None => index.get_func_by_name(val),
+                };
+
+                if let Some(addr) = addr {
+                    let bias = machine.map_or(0, |machine| machine.bias());
+                    return Ok(addr.wrapping_add(bias) as isize);
+                }
+
+                let register = machine.and_then(|machine| machine.register(name.unwrap_or(val)));
+                match register {
+                    Some(value) => Ok(value as isize),
+                    None if name.is_some() && machine.is_none() => Err(Error {
+                        offset: None,
+                        msg: format!("Register '{val}' requires a stopped process"),
+                    }),
+                    None if name.is_some() => Err(Error {
+                        offset: None,
+                        msg: format!("Unknown register '{val}'"),
+                    }),
+                    None => Err(Error {
+                        offset: None,
+                        msg: format!("Unknown symbol '{val}'"),
+                    }),
+                }
+            }
+            Expr::Deref(inner) => {
+                let inner = self.load(*inner);
+                let width = match *inner {
+                    Expr::Cast { width, .. } => width,
+                    _ => WORD,
+                };
+
+                let addr = self.eval_recursive(inner, index, machine)?;
+                let machine = machine.ok_or_else(|| Error {
                     offset: None,
-                    msg: format!("Unknown symbol '{val}'"),
-                }),
-            },
+                    msg: "Dereferencing requires a stopped process".to_string(),
+                })?;
+
+                match machine.read(addr as usize, width.size) {
+                    Some(bytes) if bytes.len() == width.size => Ok(width.decode(&bytes)),
+                    _ => Err(Error {
+                        offset: None,
+                        msg: format!("Can't read memory at {addr:#x}"),
+                    }),
+                }
+            }
+            Expr::Cast { expr, .. } => self.eval_recursive(self.load(*expr), index, machine),
             Expr::Compound { lhs, op, rhs } => {
-                let lhs = self.eval_recursive(self.load(*lhs), index)?;
-                let rhs = self.eval_recursive(self.load(*rhs), index)?;
+                let lhs = self.eval_recursive(self.load(*lhs), index, machine)?;
+
+                // The right side isn't evaluated if it can't change the outcome.
+                match op {
+                    Operator::And if lhs == 0 => return Ok(0),
+                    Operator::Or if lhs != 0 => return Ok(1),
+                    _ => {}
+                }
+
+                let rhs = self.eval_recursive(self.load(*rhs), index, machine)?;
                 let err = || Error {
                     offset: None,
                     msg: "Expression overflowed".to_string(),
@@ -498,6 +739,13 @@ impl CompleteExpr {
                     Operator::Mod => lhs.checked_rem(rhs).ok_or_else(err),
                     Operator::Add => lhs.checked_add(rhs).ok_or_else(err),
                     Operator::Min => lhs.checked_sub(rhs).ok_or_else(err),
+                    Operator::Eq => Ok((lhs == rhs) as isize),
+                    Operator::Ne => Ok((lhs != rhs) as isize),
+                    Operator::Lt => Ok((lhs < rhs) as isize),
+                    Operator::Le => Ok((lhs <= rhs) as isize),
+                    Operator::Gt => Ok((lhs > rhs) as isize),
+                    Operator::Ge => Ok((lhs >= rhs) as isize),
+                    Operator::And | Operator::Or => Ok((rhs != 0) as isize),
                 }
             }
         }
@@ -507,7 +755,13 @@ impl CompleteExpr {
     ///
     /// Returns [`None`] if the expression overflows.
     pub fn eval(&self, index: &Index) -> Result<isize, Error> {
-        self.eval_recursive(&self.root, index)
+        self.eval_recursive(&self.root, index, None)
+    }
+
+    /// Evaluate the expression against a stopped process, which registers and dereferences
+    /// require. Symbols are relocated to where the binary is loaded.
+    pub fn eval_in(&self, index: &Index, machine: &dyn Machine) -> Result<isize, Error> {
+        self.eval_recursive(&self.root, index, Some(machine))
     }
 
     fn find_matching_symbol<'src>(
@@ -528,6 +782,9 @@ impl CompleteExpr {
 
                 None
             }
+            Expr::Deref(inner) | Expr::Cast { expr: inner, .. } => {
+                self.find_matching_symbol(self.load(*inner), cursor)
+            }
             Expr::Number(_) => None,
         }
     }
@@ -538,7 +795,7 @@ impl CompleteExpr {
         cursor: usize,
     ) -> Option<(debugvault::prefix::Match, Span)> {
         if let Some((prefix, span)) = self.find_matching_symbol(&self.root, cursor) {
-            return Some((index.prefixes.find(prefix), span));
+            return Some((index.prefixes().find(prefix), span));
         }
 
         None
@@ -560,6 +817,59 @@ impl CompleteExpr {
     }
 }
 
+/// Evaluate an address typed by the user. Unlike in expressions, plain hex digits such as
+/// `401a30` are taken as a hex address unless there's a symbol by that name.
+pub fn eval_address(s: &str, index: &Index) -> Result<usize, Error> {
+    let s = s.trim();
+
+    if !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        if let Some(addr) = index.get_func_by_name(s) {
+            return Ok(addr);
+        }
+
+        return usize::from_str_radix(s, 16).map_err(|_| Error {
+            offset: None,
+            msg: "Address is too large".to_string(),
+        });
+    }
+
+    let addr = CompleteExpr::parse(s)?.eval(index)?;
+    usize::try_from(addr).map_err(|_| Error {
+        offset: None,
+        msg: "Address is negative".to_string(),
+    })
+}
+
+/// Text of a log message with the expressions in braces replaced by their value, e.g.
+/// `rdi = {$rdi}`.
+pub fn format_message(
+    msg: &str,
+    eval: &mut dyn FnMut(&CompleteExpr) -> Result<isize, Error>,
+) -> Result<String, Error> {
+    let mut text = String::new();
+    let mut rest = msg;
+
+    while let Some(start) = rest.find('{') {
+        let offset = msg.len() - rest.len() + start;
+        let len = rest[start..].find('}').ok_or(Error {
+            offset: Some(offset),
+            msg: "Missing closing brace".to_string(),
+        })?;
+
+        let expr = CompleteExpr::parse(&rest[start + 1..start + len]).map_err(|err| Error {
+            offset: err.offset.map(|inner| offset + 1 + inner),
+            msg: err.msg,
+        })?;
+
+        text += &rest[..start];
+        text += &eval(&expr)?.to_string();
+        rest = &rest[start + len + 1..];
+    }
+
+    text += rest;
+    Ok(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -656,6 +966,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn address() {
+        let mut index = debugvault::Index::default();
+        index.insert_func(0x1000, "main");
+        index.insert_func(0x2000, "add");
+
+        assert_eq!(eval_address("401a30", &index), Ok(0x401a30));
+        assert_eq!(eval_address(" 0x10 ", &index), Ok(0x10));
+        assert_eq!(eval_address("main + 0x40", &index), Ok(0x1040));
+        assert_eq!(eval_address("add", &index), Ok(0x2000));
+        assert!(eval_address("main - 0x2000", &index).is_err());
+        assert!(eval_address("missing", &index).is_err());
+    }
+
+    struct Stopped;
+
+    impl Machine for Stopped {
+        fn register(&self, name: &str) -> Option<u64> {
+            match name {
+                "rax" => Some(5),
+                "rsp" => Some(0x7f00),
+                _ => None,
+            }
+        }
+
+        fn read(&self, addr: usize, len: usize) -> Option<Vec<u8>> {
+            let memory = [0xfe, 0xff, 0xff, 0xff, 0x10, 0x40, 0, 0];
+            let offset = addr.checked_sub(0x7f08)?;
+            memory.get(offset..offset + len).map(|bytes| bytes.to_vec())
+        }
+
+        fn bias(&self) -> usize {
+            0x400000
+        }
+    }
+
+    #[test]
+    fn machine() {
+        let mut index = debugvault::Index::default();
+        index.insert_func(0x1000, "main");
+        let eval = |s: &str| CompleteExpr::parse(s).unwrap().eval_in(&index, &Stopped);
+
+        assert_eq!(eval("$rax + *(int*)(rsp+8)"), Ok(3));
+        assert_eq!(eval("*(unsigned int *) ($rsp + 8)"), Ok(0xfffffffe));
+        assert_eq!(eval("*(char *)(rsp + 12) * 2"), Ok(0x20));
+        assert_eq!(eval("*(long *)(rsp + 8)"), Ok(0x4010fffffffe));
+        assert_eq!(eval("*(rsp + 8)"), Ok(0x4010fffffffe));
+        assert_eq!(eval("**(int **)(rsp + 8)"), Err(Error {
+            offset: None,
+            msg: "Can't read memory at 0x4010fffffffe".to_string(),
+        }));
+        assert_eq!(eval("main + 4"), Ok(0x401004));
+        assert!(eval("$rbx").is_err());
+        assert!(eval("$main").is_err());
+
+        let expr = CompleteExpr::parse("$rax").unwrap();
+        assert!(expr.eval(&index).is_err());
+        let expr = CompleteExpr::parse("*(int *)0x7f08").unwrap();
+        assert!(expr.eval(&index).is_err());
+    }
+
+    #[test]
+    fn comparisons() {
+        eval_eq!("1 + 1 == 2", 1);
+        eval_eq!("3 != 3", 0);
+        eval_eq!("2 < 3 && 3 <= 3", 1);
+        eval_eq!("2 > 3 || 3 >= 4", 0);
+        eval_eq!("0 && 1 || 1", 1);
+        eval_eq!("(1 || 0) * 5", 5);
+
+        let index = debugvault::Index::default();
+        let eval = |s: &str| CompleteExpr::parse(s).unwrap().eval_in(&index, &Stopped);
+        assert_eq!(eval("$rax==5"), Ok(1));
+        assert_eq!(eval("rax != 5 && *(int *)0 == 0"), Ok(0));
+        assert_eq!(eval("rax > 4 || *0"), Ok(1));
+    }
+
+    #[test]
+    fn message() {
+        let index = debugvault::Index::default();
+        let mut eval = |expr: &CompleteExpr| expr.eval_in(&index, &Stopped);
+
+        assert_eq!(format_message("hit", &mut eval), Ok("hit".to_string()));
+        assert_eq!(
+            format_message("rax = {$rax}, {*(int *)(rsp + 8)}!", &mut eval),
+            Ok("rax = 5, -2!".to_string())
+        );
+        assert!(format_message("rax = {$rax", &mut eval).is_err());
+        assert!(format_message("{}", &mut eval).is_err());
+    }
+
     #[test]
     fn operation_order() {
         eval_eq!("1 + 10 * 10", 101);
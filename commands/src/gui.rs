@@ -2,25 +2,514 @@ use std::fmt;
 use std::path::{Path, PathBuf};
 
 use crate::debug::CompleteExpr;
+use tokenizing::{Syntax, Theme};
 
-pub const HELP: &str = "\
-Available commands:
-    pwd                -- Display the current path
-    cd <path>          -- Change the current directory to the specified path
-    quit               -- Exit the program
-    goto <expr>        -- Jump to code/data at the specified expression
-    clear              -- Clear out terminal
-    help               -- Display this help message";
+/// Description of a command, used for parsing, `help` and suggestions.
+#[derive(Debug, PartialEq)]
+pub struct CommandInfo {
+    /// Name of the command followed by it's aliases.
+    pub names: &'static [&'static str],
+
+    /// Arguments the command takes.
+    pub usage: &'static str,
+
+    /// One line description.
+    pub summary: &'static str,
+
+    /// Extended description shown by `help <command>`.
+    pub details: &'static str,
+}
+
+impl CommandInfo {
+    pub fn name(&self) -> &'static str {
+        self.names[0]
+    }
+}
+
+/// Every command the terminal understands.
+pub const COMMANDS: &[CommandInfo] = &[
+    CommandInfo {
+        names: &["exec", "e"],
+        usage: "exec <path>",
+        summary: "Load the binary at the specified path",
+        details: "Replaces the currently loaded binary, if any.",
+    },
+    CommandInfo {
+        names: &["dyld", "member"],
+        usage: "dyld <image> <path>",
+        summary: "Load an image of a dyld shared cache, archive or fat Mach-O at the path",
+        details: "Images are given by their path or file name e.g. 'dyld Foundation \
+                  /System/Library/dyld/dyld_shared_cache_arm64e'. Files the cache is split \
+                  into are found next to it. Archives are picked from by member, e.g. \
+                  'member crt1.o libc.a', and fat Mach-O's by architecture, e.g. 'member \
+                  arm64e /usr/bin/true'.",
+    },
+    CommandInfo {
+        names: &["pwd"],
+        usage: "pwd",
+        summary: "Display the current path",
+        details: "Prints the working directory used for resolving relative paths.",
+    },
+    CommandInfo {
+        names: &["cd"],
+        usage: "cd <path>",
+        summary: "Change the current directory to the specified path",
+        details: "Supports '~' for the home directory.",
+    },
+    CommandInfo {
+        names: &["quit", "q"],
+        usage: "quit",
+        summary: "Exit the program",
+        details: "",
+    },
+    CommandInfo {
+        names: &["goto", "g"],
+        usage: "goto <expr>",
+        summary: "Jump to code/data at the specified expression",
+        details: "Expressions can contain symbol names, numbers and arithmetic \
+                  e.g. 'goto main + 0x10'. Plain hex digits are taken as an address \
+                  e.g. 'goto 401a30'.",
+    },
+    CommandInfo {
+        names: &["search", "s"],
+        usage: "search <pattern>",
+        summary: "Search for a byte pattern or instruction text",
+        details: "Byte patterns are hex bytes with '??' wildcards e.g. 'search E8 ?? ?? ?? ??', \
+                  anything else is searched for in the disassembly. Quote the pattern to always \
+                  search the disassembly. Press F3 to go to the next result.",
+    },
+    CommandInfo {
+        names: &["patch"],
+        usage: "patch <expr> \"<asm>\"",
+        summary: "Assemble instructions over the code at the specified expression",
+        details: "Instructions are separated by ';' e.g. 'patch main \"xor eax, eax; ret\"', \
+                  bytes written in hex are also accepted e.g. 'patch main \"90 90\"'. What's \
+                  left of the last instruction overwritten is filled with NOPs. Common x86 and \
+                  AArch64 instructions without memory operands are supported.",
+    },
+    CommandInfo {
+        names: &["break", "b"],
+        usage: "break <expr> [if <cond>] [after <n>] [log \"<msg>\"]",
+        summary: "Toggle a breakpoint at the specified expression",
+        details: "Breakpoints can also be toggled by clicking left of an instruction. Given any \
+                  clauses the breakpoint is set instead, only stopping when the condition is \
+                  non-zero and from the n-th time it held on. With 'log' the message is printed \
+                  instead of stopping, expressions in braces being replaced by their value \
+                  e.g. 'break loop if $rcx > 8 log \"i = {$rcx}\"'. Functions that aren't in \
+                  the binary are stopped at once a library defining them is loaded.",
+    },
+    CommandInfo {
+        names: &["hbreak", "hb"],
+        usage: "hbreak <expr>",
+        summary: "Toggle a hardware breakpoint at the specified expression",
+        details: "Hardware breakpoints use a debug register instead of writing a trap into the \
+                  code, for code that checks itself or can't be written to. They share the 4 \
+                  debug registers with watchpoints. A breakpoint that's already set is turned \
+                  into a hardware breakpoint.",
+    },
+    CommandInfo {
+        names: &["watch", "w"],
+        usage: "watch <expr>",
+        summary: "Toggle a watchpoint on writes to the specified expression",
+        details: "Watches up to 8 bytes, as many as the address is aligned to. \
+                  At most 4 watchpoints can be set.",
+    },
+    CommandInfo {
+        names: &["print", "p"],
+        usage: "print <expr>",
+        summary: "Evaluate an expression against the stopped process",
+        details: "Besides symbols and numbers, expressions can contain registers of the \
+                  selected thread e.g. 'print $rax + 8' and dereference memory with '*', which \
+                  reads a pointer unless cast to a narrower integer e.g. \
+                  'print *(int *)(rsp + 8)'.",
+    },
+    CommandInfo {
+        names: &["x"],
+        usage: "x/[n][fmt][size] <expr>",
+        summary: "Show memory of the stopped process at the specified expression",
+        details: "Shows n units of size 'b', 'h', 'w' or 'g' (1, 2, 4 or 8 bytes) formatted \
+                  as 'x' hex, 'd' signed, 'u' unsigned, 'o' octal or 'c' characters \
+                  e.g. 'x/16xb 0x401000'. Defaults to a single hex word.",
+    },
+    CommandInfo {
+        names: &["run", "r"],
+        usage: "run [args]",
+        summary: "Debug the loaded binary with the given arguments",
+        details: "Kills the process that was being debugged, if any. Arguments given replace \
+                  the ones set with 'set args', which are used when none are given.",
+    },
+    CommandInfo {
+        names: &["attach"],
+        usage: "attach <pid>",
+        summary: "Debug a running process",
+        details: "Loads the binary the process was started from, the process stays stopped \
+                  until continued.",
+    },
+    CommandInfo {
+        names: &["core"],
+        usage: "core <path>",
+        summary: "Inspect a core dump or minidump of the loaded binary",
+        details: "Shows the threads, registers, stacks and memory of the process when it \
+                  crashed. Cores can't be resumed.",
+    },
+    CommandInfo {
+        names: &["detach"],
+        usage: "detach",
+        summary: "Stop debugging an attached process, leaving it running",
+        details: "",
+    },
+    CommandInfo {
+        names: &["trace"],
+        usage: "trace <on|off>",
+        summary: "Toggle tracing the syscalls of debugged processes",
+        details: "Applies to processes started or attached to afterwards, syscalls are shown \
+                  in the syscalls window.",
+    },
+    CommandInfo {
+        names: &["record"],
+        usage: "record <on|off>",
+        summary: "Toggle recording the instructions executed by debugged processes",
+        details: "Whilst recording, continuing single steps the selected thread, which is a lot \
+                  slower. The instructions are shown in the trace window, which exports them, \
+                  and the listing is colored by how often each was executed. Turning it on \
+                  again starts a new trace.",
+    },
+    CommandInfo {
+        names: &["handle"],
+        usage: "handle <signal> [actions]",
+        summary: "Change whether a signal stops the debugged process and is passed on to it",
+        details: "Actions are 'stop' or 'nostop' and 'pass' or 'nopass', 'ignore' being the \
+                  same as 'nopass', e.g. 'handle SIGSEGV stop nopass'. Signals are given by \
+                  their name, with or without 'SIG', or number. Without actions it shows how \
+                  the signal is handled, 'info signals' shows every signal. SIGINT and SIGTRAP \
+                  stop without being passed on and signals used for timers and notifications, \
+                  such as SIGALRM and SIGCHLD, are passed on without stopping. Only processes \
+                  debugged on linux can be changed.",
+    },
+    CommandInfo {
+        names: &["set"],
+        usage: "set <setting> <value>",
+        summary: "Change how processes are debugged or how the GUI looks",
+        details: "'set follow-fork <parent|child|ask>' chooses which process is debugged after \
+                  it forks, the other one is left running. With 'ask' the process stops at the \
+                  fork and the choice is made when it's continued. Exec'ing a new binary \
+                  always stops, loading the new binary. 'set libraries <on|off>' chooses \
+                  whether imports are followed into the shared libraries defining them when \
+                  not debugging, processes stopping in a library always show it. \
+                  'set theme <dark|light|solarized>' switches the colors of the GUI. \
+                  'set syntax <intel|att>' chooses the flavor x86 instructions are shown in, \
+                  'set uppercase <on|off>' whether mnemonics are in uppercase, \
+                  'set immediates <hex|dec>' the radix of immediates, 'set bytes <on|off>' \
+                  whether the bytes of instructions are shown and 'set symbols <on|off>' \
+                  whether addresses are shown as the symbol they're in plus an offset. \
+                  'set args [args]' sets the arguments 'run' uses, 'set env <name>=<value>' \
+                  sets a variable for processes started afterwards and 'set aslr <on|off>' \
+                  whether their addresses are randomized, which can only be turned off on \
+                  linux.",
+    },
+    CommandInfo {
+        names: &["continue", "c"],
+        usage: "continue",
+        summary: "Resume the debugged process",
+        details: "",
+    },
+    CommandInfo {
+        names: &["step"],
+        usage: "step",
+        summary: "Execute the debugged process until the next source line",
+        details: "Steps the thread that's selected, see 'thread', into the functions it calls \
+                  that have line info. Without line info a single instruction is executed.",
+    },
+    CommandInfo {
+        names: &["next", "n"],
+        usage: "next",
+        summary: "Execute the debugged process until the next source line, stepping over calls",
+        details: "Steps the thread that's selected, see 'thread'.",
+    },
+    CommandInfo {
+        names: &["stepi", "si"],
+        usage: "stepi",
+        summary: "Execute a single instruction of the debugged process",
+        details: "Steps the thread that's selected, see 'thread'.",
+    },
+    CommandInfo {
+        names: &["finish"],
+        usage: "finish",
+        summary: "Execute the debugged process until the current function returns",
+        details: "Steps the thread that's selected, see 'thread', out of the function it's in, \
+                  stopping in the function that called it.",
+    },
+    CommandInfo {
+        names: &["reverse-stepi", "rsi"],
+        usage: "reverse-stepi",
+        summary: "Undo the last instruction the debugged process executed whilst recording",
+        details: "Restores the registers and the memory the instruction wrote, see 'record'. The \
+                  last 10000 instructions can be stepped back through, as long as the process \
+                  wasn't continued or stepped without recording since. Memory written by \
+                  syscalls isn't restored.",
+    },
+    CommandInfo {
+        names: &["kill"],
+        usage: "kill",
+        summary: "Kill the debugged process",
+        details: "",
+    },
+    CommandInfo {
+        names: &["thread", "t"],
+        usage: "thread <tid>",
+        summary: "Switch to another thread of the stopped process",
+        details: "Shows the registers and call stack of the thread, which is also the one \
+                  that's stepped. Threads are listed in the threads window.",
+    },
+    CommandInfo {
+        names: &["info", "i"],
+        usage: "info <subject>",
+        summary: "Show the registers, breakpoints, sections, libraries or signals",
+        details: "Subjects are 'regs' for the registers of the stopped process, 'break' for \
+                  breakpoints and watchpoints, 'sections' for the loaded binary's sections, \
+                  'libs' for the shared libraries it imports and where they were found, or \
+                  those the debugged process loaded and where, and 'signals' for how each \
+                  signal is handled.",
+    },
+    CommandInfo {
+        names: &["coverage"],
+        usage: "coverage <path>",
+        summary: "Color the instructions executed according to a coverage file",
+        details: "Reads drcov files as written by DynamoRIO and fuzzers, lcov tracefiles, which \
+                  need the binary's line info, and lists of addresses in hex. Covered \
+                  instructions are colored in the listing and flow graph, and the functions \
+                  window shows the percentage of each function that was executed. LLVM \
+                  profiles have to be exported to lcov with 'llvm-cov export' first.",
+    },
+    CommandInfo {
+        names: &["script"],
+        usage: "script <path>",
+        summary: "Run a rhai script against the loaded binary",
+        details: "Scripts can call functions(), sections(), symbol(addr), address_of(name), \
+                  read_bytes(addr, len), xrefs_to(addr), comment(addr, text), \
+                  rename(addr, name), prototype(addr, decl), set_breakpoint(addr), \
+                  clear_breakpoint(addr), breakpoints() and show(addr).",
+    },
+    CommandInfo {
+        names: &["clear"],
+        usage: "clear",
+        summary: "Clear out terminal",
+        details: "Also clears the log.",
+    },
+    CommandInfo {
+        names: &["help", "?"],
+        usage: "help [command]",
+        summary: "Display this help message",
+        details: "Given a command, displays it's usage and description.",
+    },
+];
+
+/// Subjects of the `info` command, by their name followed by it's aliases.
+const INFO_SUBJECTS: &[(&[&str], Info)] = &[
+    (&["regs", "registers"], Info::Registers),
+    (&["break", "breakpoints"], Info::Breakpoints),
+    (&["sections"], Info::Sections),
+    (&["libs", "libraries"], Info::Libraries),
+    (&["signals"], Info::Signals),
+];
+
+/// Keywords starting the clauses of the `break` command.
+const CLAUSES: &[&str] = &["if", "after", "log"];
+
+/// Formats of the `x` command, by the letter they're given with.
+const FORMATS: &[(char, Format)] = &[
+    ('x', Format::Hex),
+    ('d', Format::Signed),
+    ('u', Format::Unsigned),
+    ('o', Format::Octal),
+    ('c', Format::Char),
+];
+
+/// Unit sizes of the `x` command, by the letter they're given with.
+const SIZES: &[(char, usize)] = &[('b', 1), ('h', 2), ('w', 4), ('g', 8)];
+
+/// Names of the settings of the `set` command.
+const SETTINGS: &[&str] = &[
+    "follow-fork",
+    "libraries",
+    "theme",
+    "syntax",
+    "uppercase",
+    "immediates",
+    "bytes",
+    "symbols",
+    "args",
+    "env",
+    "aslr",
+];
+
+/// Find a command by it's name or one of it's aliases.
+pub fn lookup(name: &str) -> Option<&'static CommandInfo> {
+    COMMANDS.iter().find(|cmd| cmd.names.contains(&name))
+}
+
+/// Help message of a given command, or of all commands if none is given.
+pub fn help(name: Option<&str>) -> String {
+    if let Some(cmd) = name.and_then(lookup) {
+        let mut msg = format!("{}\n    {}", cmd.usage, cmd.summary);
+        if cmd.names.len() > 1 {
+            msg += &format!("\n    Aliases: {}", cmd.names[1..].join(", "));
+        }
+        if !cmd.details.is_empty() {
+            msg += &format!("\n    {}", cmd.details);
+        }
+        return msg;
+    }
+
+    let mut msg = String::from("Available commands:");
+    for cmd in COMMANDS {
+        msg += &format!("\n    {:<18} -- {}", cmd.usage, cmd.summary);
+    }
+    msg
+}
 
 #[derive(Debug, PartialEq)]
 pub enum Command {
     Load(PathBuf),
+    LoadImage(String, PathBuf),
     PrintPath,
     ChangeDir(PathBuf),
     Quit,
     Goto(usize),
+    Search(String),
+    Patch(usize, String),
+    Break(usize),
+    /// Function that isn't in the binary, stopped at once a library defining it is loaded.
+    PendingBreak(String),
+    /// Breakpoint with clauses, which is set rather than toggled.
+    BreakIf(usize, BreakCondition),
+    HardwareBreak(usize),
+    Watch(usize),
+    /// Expression that's evaluated once there's a process to evaluate it against.
+    Print(CompleteExpr),
+    Examine(Examine, CompleteExpr),
+    Run(Vec<String>),
+    Attach(u32),
+    Core(PathBuf),
+    Detach,
+    Trace(bool),
+    /// Whether the instructions executed are recorded.
+    Record(bool),
+    Set(Setting),
+    Continue,
+    /// Execute until the next source line, stepping into calls.
+    StepLine,
+    /// Execute until the next source line, stepping over calls.
+    Next,
+    /// Execute a single instruction.
+    Step,
+    /// Execute until the current function returns.
+    Finish,
+    /// Undo the last instruction executed whilst recording.
+    ReverseStep,
+    Kill,
+    Thread(u32),
+    /// Signal, as it was given, and how it's handled from now on.
+    Handle(String, SignalHandling),
+    Info(Info),
+    /// Coverage file to color the executed instructions by.
+    Coverage(PathBuf),
+    Script(PathBuf),
     Clear,
-    Help,
+    Help(Option<&'static str>),
+}
+
+/// What the `info` command shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Info {
+    Registers,
+    Breakpoints,
+    Sections,
+    Libraries,
+    Signals,
+}
+
+/// Actions of the `handle` command, leaving what isn't given as it was.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SignalHandling {
+    /// Whether the process stops when it receives the signal.
+    pub stop: Option<bool>,
+    /// Whether the signal is delivered to the process.
+    pub pass: Option<bool>,
+}
+
+/// Clauses of the `break` command, telling when the breakpoint stops.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct BreakCondition {
+    /// Expression that has to be non-zero for the breakpoint to trigger.
+    pub expr: Option<String>,
+    /// Hit the breakpoint starts triggering at.
+    pub hits: Option<usize>,
+    /// Message printed instead of stopping.
+    pub log: Option<String>,
+}
+
+/// How the `x` command shows memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Examine {
+    /// Number of units shown.
+    pub count: usize,
+    pub format: Format,
+    /// Bytes in each unit.
+    pub size: usize,
+}
+
+impl Default for Examine {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            format: Format::Hex,
+            size: 4,
+        }
+    }
+}
+
+/// How units of memory are formatted by the `x` command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Hex,
+    Signed,
+    Unsigned,
+    Octal,
+    Char,
+}
+
+/// Settings changed with the `set` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Setting {
+    FollowFork(FollowFork),
+    /// Whether imports are followed into shared libraries when not debugging.
+    Libraries(bool),
+    /// Name of the built-in theme to switch to.
+    Theme(&'static str),
+    Syntax(Syntax),
+    /// Whether mnemonics are shown in uppercase.
+    Uppercase(bool),
+    /// Whether immediates are shown in hexadecimal instead of decimal.
+    HexImmediates(bool),
+    /// Whether the bytes encoding instructions are shown.
+    OpcodeBytes(bool),
+    /// Whether addresses are shown as a symbol plus an offset.
+    Symbols(bool),
+    /// Arguments of processes started with `run` without any.
+    Args(Vec<String>),
+    /// Name and value of a variable set for processes started afterwards.
+    Env(String, String),
+    /// Whether the addresses of processes started afterwards are randomized.
+    Aslr(bool),
+}
+
+/// Which process is debugged after it forks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowFork {
+    Parent,
+    Child,
+    Ask,
 }
 
 #[derive(Debug, PartialEq)]
@@ -31,6 +520,19 @@ pub enum Error {
     PathIsntFile(PathBuf),
     PathIsntDir(PathBuf),
     InvalidEnv,
+    InvalidPid(String),
+    InvalidTid(String),
+    InvalidSwitch(String),
+    UnknownInfo(String),
+    UnknownSetting(String),
+    InvalidFollowFork(String),
+    InvalidAction(String),
+    UnknownTheme(String),
+    InvalidSyntax(String),
+    InvalidRadix(String),
+    InvalidFormat(String),
+    InvalidCount(String),
+    DuplicateClause(&'static str),
     Debugger(crate::debug::Error),
 }
 
@@ -52,6 +554,43 @@ impl fmt::Display for Error {
                 f.write_fmt(format_args!("Path {path:?} isn't a directory."))
             }
             Self::InvalidEnv => f.write_str("Invalid environmental variable pair."),
+            Self::InvalidPid(pid) => f.write_fmt(format_args!("'{pid}' isn't a valid pid.")),
+            Self::InvalidTid(tid) => f.write_fmt(format_args!("'{tid}' isn't a valid thread id.")),
+            Self::InvalidSwitch(s) => {
+                f.write_fmt(format_args!("Expected 'on' or 'off', got '{s}'."))
+            }
+            Self::UnknownInfo(s) => f.write_fmt(format_args!(
+                "Can't show '{s}', expected 'regs', 'break', 'sections', 'libs' or 'signals'."
+            )),
+            Self::UnknownSetting(s) => f.write_fmt(format_args!(
+                "Can't set '{s}', expected 'follow-fork', 'libraries', 'theme', 'syntax', \
+                 'uppercase', 'immediates', 'bytes', 'symbols', 'args', 'env' or 'aslr'."
+            )),
+            Self::InvalidFollowFork(s) => {
+                f.write_fmt(format_args!("Expected 'parent', 'child' or 'ask', got '{s}'."))
+            }
+            Self::InvalidAction(s) => f.write_fmt(format_args!(
+                "Expected 'stop', 'nostop', 'pass', 'nopass' or 'ignore', got '{s}'."
+            )),
+            Self::UnknownTheme(s) => {
+                f.write_fmt(format_args!("Expected 'dark', 'light' or 'solarized', got '{s}'."))
+            }
+            Self::InvalidSyntax(s) => {
+                f.write_fmt(format_args!("Expected 'intel' or 'att', got '{s}'."))
+            }
+            Self::InvalidRadix(s) => {
+                f.write_fmt(format_args!("Expected 'hex' or 'dec', got '{s}'."))
+            }
+            Self::InvalidFormat(s) => f.write_fmt(format_args!(
+                "Invalid format '{s}', expected a count followed by a format \
+                 ('x', 'd', 'u', 'o' or 'c') and a size ('b', 'h', 'w' or 'g')."
+            )),
+            Self::InvalidCount(s) => {
+                f.write_fmt(format_args!("Expected a number of hits, got '{s}'."))
+            }
+            Self::DuplicateClause(clause) => {
+                f.write_fmt(format_args!("Clause '{clause}' is given more than once."))
+            }
             Self::Debugger(err) => err.fmt(f),
         }
     }
@@ -81,29 +620,56 @@ fn collapse_homedir(path: PathBuf) -> PathBuf {
     }
 }
 
-fn possible_command(unknown: &str) -> Option<&str> {
-    const CMDS: &[&str] = &[
-        "exec",
-        "pwd",
-        "cd",
-        "quit",
-        "run",
-        "goto",
-        "set",
-        "break",
-        "delete",
-        "stop",
-        "continue",
-        "clear",
-        "trace",
-        "follow-children",
-        "help",
-    ];
+/// Splits the arguments of the `break` command into the address expression and it's clauses,
+/// by their keyword. Keywords inside quotes don't start a clause.
+fn split_clauses(s: &str) -> (&str, Vec<(&'static str, &str)>) {
+    let mut starts = Vec::new();
+    let mut quoted = false;
+    let mut prev = ' ';
+
+    for (idx, chr) in s.char_indices() {
+        if chr == '"' {
+            quoted = !quoted;
+        }
+
+        if prev == ' ' && !quoted {
+            let word = s[idx..].split(' ').next().unwrap_or_default();
+            if let Some(&keyword) = CLAUSES.iter().find(|&&keyword| keyword == word) {
+                starts.push((idx, keyword));
+            }
+        }
+
+        prev = chr;
+    }
+
+    let expr = match starts.first() {
+        Some(&(start, _)) => &s[..start],
+        None => s,
+    };
+
+    let clauses = starts
+        .iter()
+        .enumerate()
+        .map(|(idx, &(start, keyword))| {
+            let end = starts.get(idx + 1).map_or(s.len(), |&(end, _)| end);
+            (keyword, s[start + keyword.len()..end].trim())
+        })
+        .collect();
+
+    (expr, clauses)
+}
 
+/// Whether `s` could name a function of a library, rather than being any other expression.
+fn is_function_name(s: &str) -> bool {
+    let valid = |chr: char| chr.is_alphanumeric() || matches!(chr, '_' | '.' | '@' | ':');
+    s.starts_with(|chr: char| chr.is_alphabetic() || chr == '_') && s.chars().all(valid)
+}
+
+fn possible_command(unknown: &str) -> Option<&str> {
     let mut distance = u32::MAX;
     let mut best_guess = "";
-    for cmd in CMDS {
-        let d = triple_accel::levenshtein_exp(unknown.as_bytes(), cmd.as_bytes());
+    for cmd in COMMANDS.iter().flat_map(|cmd| cmd.names) {
+        let d = triple_accel::rdamerau_exp(unknown.as_bytes(), cmd.as_bytes());
         if d < distance {
             distance = d;
             best_guess = cmd;
@@ -305,16 +871,264 @@ impl<'src> Context<'src> {
         }
     }
 
-    #[allow(dead_code)]
-    fn parse_env(&mut self) -> Result<String, Error> {
+    /// Remaining whitespace separated arguments.
+    fn parse_args(&mut self) -> Vec<String> {
+        let args = self.src().split_whitespace().map(String::from).collect();
+
+        // mark all remaining characters as read
+        self.offset = self.src.len();
+
+        args
+    }
+
+    fn parse_env(&mut self) -> Result<(String, String), Error> {
         let s = self.parse_arg("environmental variable")?;
-        let (var, val) = s.split_once("=").ok_or(Error::InvalidEnv)?;
-        Ok(format!("{var}={val}"))
+        match s.split_once('=') {
+            Some((var, val)) if !var.is_empty() => Ok((var.to_string(), val.to_string())),
+            _ => Err(Error::InvalidEnv),
+        }
+    }
+
+    fn parse_pid(&mut self) -> Result<u32, Error> {
+        let s = self.parse_arg("pid")?;
+        s.parse().map_err(|_| Error::InvalidPid(s.to_string()))
+    }
+
+    fn parse_tid(&mut self) -> Result<u32, Error> {
+        let s = self.parse_arg("tid")?;
+        s.parse().map_err(|_| Error::InvalidTid(s.to_string()))
+    }
+
+    fn parse_address(&mut self) -> Result<usize, Error> {
+        let offset = self.offset;
+        let s = self.parse_arg("expr")?;
+
+        if s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return crate::debug::eval_address(s, self.index).map_err(Error::Debugger);
+        }
+
+        self.offset = offset;
+        self.parse_debug_expr()
+    }
+
+    /// Address expression followed by quoted instructions.
+    fn parse_patch(&mut self) -> Result<Command, Error> {
+        let rest = self.src();
+
+        // mark all remaining characters as read
+        self.offset = self.src.len();
+
+        let quote = rest.find('"').ok_or(Error::Missing("quoted instructions"))?;
+        let expr = rest[..quote].trim();
+        if expr.is_empty() {
+            return Err(Error::Missing("expr"));
+        }
+
+        let addr = if expr.bytes().all(|b| b.is_ascii_hexdigit()) {
+            crate::debug::eval_address(expr, self.index)
+        } else {
+            CompleteExpr::parse(expr)
+                .and_then(|parsed| parsed.eval(self.index))
+                .map(|val| val as usize)
+        };
+
+        let src = rest[quote + 1..].trim_end();
+        let src = src.strip_suffix('"').ok_or(Error::Missing("closing quote"))?;
+        if src.trim().is_empty() {
+            return Err(Error::Missing("instructions"));
+        }
+
+        Ok(Command::Patch(addr.map_err(Error::Debugger)?, src.to_string()))
+    }
+
+    fn parse_info(&mut self) -> Result<Info, Error> {
+        let start = self.offset;
+        let s = self.parse_arg("subject")?;
+
+        if let Some(&(_, info)) = INFO_SUBJECTS.iter().find(|(names, _)| names.contains(&s)) {
+            return Ok(info);
+        }
+
+        for (names, _) in INFO_SUBJECTS {
+            if names[0].starts_with(s) {
+                self.suggestions.push(self.src[..start].to_string() + names[0]);
+            }
+        }
+
+        Err(Error::UnknownInfo(s.to_string()))
+    }
+
+    /// Signal followed by the actions of the `handle` command, the last of conflicting actions
+    /// is the one that's taken.
+    fn parse_handle(&mut self) -> Result<Command, Error> {
+        let signal = self.parse_next("signal")?.to_string();
+
+        let mut handling = SignalHandling::default();
+        for action in self.parse_args() {
+            match action.as_str() {
+                "stop" => handling.stop = Some(true),
+                "nostop" => handling.stop = Some(false),
+                "pass" => handling.pass = Some(true),
+                "nopass" | "ignore" => handling.pass = Some(false),
+                _ => return Err(Error::InvalidAction(action)),
+            }
+        }
+
+        Ok(Command::Handle(signal, handling))
+    }
+
+    fn parse_switch(&mut self) -> Result<bool, Error> {
+        match self.parse_arg("on or off")? {
+            "on" => Ok(true),
+            "off" => Ok(false),
+            s => Err(Error::InvalidSwitch(s.to_string())),
+        }
+    }
+
+    fn parse_setting(&mut self) -> Result<Setting, Error> {
+        let start = self.offset;
+        let name = self.parse_next("setting")?;
+
+        if name == "follow-fork" {
+            return match self.parse_arg("parent, child or ask")? {
+                "parent" => Ok(Setting::FollowFork(FollowFork::Parent)),
+                "child" => Ok(Setting::FollowFork(FollowFork::Child)),
+                "ask" => Ok(Setting::FollowFork(FollowFork::Ask)),
+                s => Err(Error::InvalidFollowFork(s.to_string())),
+            };
+        }
+
+        if name == "libraries" {
+            return Ok(Setting::Libraries(self.parse_switch()?));
+        }
+
+        if name == "theme" {
+            let name = self.parse_arg("theme")?;
+            return match Theme::by_name(name) {
+                Some(theme) => Ok(Setting::Theme(theme.name)),
+                None => Err(Error::UnknownTheme(name.to_string())),
+            };
+        }
+
+        if name == "syntax" {
+            return match self.parse_arg("intel or att")? {
+                "intel" => Ok(Setting::Syntax(Syntax::Intel)),
+                "att" => Ok(Setting::Syntax(Syntax::Att)),
+                s => Err(Error::InvalidSyntax(s.to_string())),
+            };
+        }
+
+        if name == "uppercase" {
+            return Ok(Setting::Uppercase(self.parse_switch()?));
+        }
+
+        if name == "immediates" {
+            return match self.parse_arg("hex or dec")? {
+                "hex" => Ok(Setting::HexImmediates(true)),
+                "dec" => Ok(Setting::HexImmediates(false)),
+                s => Err(Error::InvalidRadix(s.to_string())),
+            };
+        }
+
+        if name == "bytes" {
+            return Ok(Setting::OpcodeBytes(self.parse_switch()?));
+        }
+
+        if name == "symbols" {
+            return Ok(Setting::Symbols(self.parse_switch()?));
+        }
+
+        if name == "args" {
+            return Ok(Setting::Args(self.parse_args()));
+        }
+
+        if name == "env" {
+            let (var, val) = self.parse_env()?;
+            return Ok(Setting::Env(var, val));
+        }
+
+        if name == "aslr" {
+            return Ok(Setting::Aslr(self.parse_switch()?));
+        }
+
+        for setting in SETTINGS {
+            if setting.starts_with(name) {
+                self.suggestions.push(self.src[..start].to_string() + setting + " ");
+            }
+        }
+
+        Err(Error::UnknownSetting(name.to_string()))
+    }
+
+    /// Address expression followed by the clauses of a conditional breakpoint, if any.
+    fn parse_break(&mut self) -> Result<Command, Error> {
+        let (s, clauses) = split_clauses(self.src());
+        if clauses.is_empty() {
+            let offset = self.offset;
+            let s = self.parse_arg("expr")?;
+            return match self.eval_debug_expr(offset, s) {
+                Ok(addr) => Ok(Command::Break(addr)),
+                Err(_) if is_function_name(s) => Ok(Command::PendingBreak(s.to_string())),
+                Err(err) => Err(err),
+            };
+        }
+
+        let mut condition = BreakCondition::default();
+        for (keyword, arg) in clauses {
+            let duplicate = match keyword {
+                "if" => condition.expr.is_some(),
+                "after" => condition.hits.is_some(),
+                _ => condition.log.is_some(),
+            };
+
+            if duplicate {
+                return Err(Error::DuplicateClause(keyword));
+            }
+
+            match keyword {
+                "if" => {
+                    if arg.is_empty() {
+                        return Err(Error::Missing("condition"));
+                    }
+
+                    CompleteExpr::parse(arg).map_err(Error::Debugger)?;
+                    condition.expr = Some(arg.to_string());
+                }
+                "after" => match arg.parse() {
+                    Ok(0) | Err(_) => return Err(Error::InvalidCount(arg.to_string())),
+                    Ok(hits) => condition.hits = Some(hits),
+                },
+                _ => {
+                    let msg = arg.strip_prefix('"').ok_or(Error::Missing("quoted message"))?;
+                    let msg = msg.strip_suffix('"').ok_or(Error::Missing("closing quote"))?;
+
+                    // Expressions in the message are evaluated when the breakpoint is hit.
+                    crate::debug::format_message(msg, &mut |_| Ok(0)).map_err(Error::Debugger)?;
+                    condition.log = Some(msg.to_string());
+                }
+            }
+        }
+
+        let addr = self.eval_debug_expr(self.offset, s.trim())?;
+
+        // mark all remaining characters as read
+        self.offset = self.src.len();
+
+        Ok(Command::BreakIf(addr, condition))
     }
 
     fn parse_debug_expr(&mut self) -> Result<usize, Error> {
         let offset = self.offset;
         let s = self.parse_arg("expr")?;
+        self.eval_debug_expr(offset, s)
+    }
+
+    /// Evaluates `s`, found at `offset`, suggesting symbols if it fails.
+    fn eval_debug_expr(&mut self, offset: usize, s: &str) -> Result<usize, Error> {
+        if s.is_empty() {
+            return Err(Error::Missing("expr"));
+        }
+
         let expr = CompleteExpr::parse(s).map_err(Error::Debugger)?;
 
         let err = match expr.eval(self.index) {
@@ -330,7 +1144,7 @@ impl<'src> Context<'src> {
         if let Some((suggestions, span)) = expr.autocomplete(self.index, relative_cursor) {
             let span = span.start() + offset..span.end() + offset;
 
-            for suggestion in suggestions.iter(&self.index.prefixes) {
+            for suggestion in suggestions.iter(&self.index.prefixes()) {
                 let mut src = self.src.to_string();
                 src.replace_range(span.clone(), suggestion.as_str());
                 self.suggestions.push(src);
@@ -340,19 +1154,125 @@ impl<'src> Context<'src> {
         Err(Error::Debugger(err))
     }
 
+    fn parse_expr(&mut self) -> Result<CompleteExpr, Error> {
+        let s = self.parse_arg("expr")?;
+        CompleteExpr::parse(s).map_err(Error::Debugger)
+    }
+
+    /// Parses the `/[count][format][size]` following the name of the `x` command.
+    fn parse_examine(&mut self, format: Option<&str>) -> Result<Command, Error> {
+        let mut examine = Examine::default();
+
+        if let Some(s) = format {
+            let digits = s.find(|chr: char| !chr.is_ascii_digit()).unwrap_or(s.len());
+            if digits > 0 {
+                examine.count = match s[..digits].parse() {
+                    Ok(0) | Err(_) => return Err(Error::InvalidFormat(s.to_string())),
+                    Ok(count) => count,
+                };
+            }
+
+            let mut size = None;
+            for chr in s[digits..].chars() {
+                if let Some(&(_, format)) = FORMATS.iter().find(|(name, _)| *name == chr) {
+                    examine.format = format;
+                } else if let Some(&(_, bytes)) = SIZES.iter().find(|(name, _)| *name == chr) {
+                    size = Some(bytes);
+                } else {
+                    return Err(Error::InvalidFormat(s.to_string()));
+                }
+            }
+
+            // Characters are bytes unless told otherwise.
+            examine.size = match size {
+                Some(size) => size,
+                None if examine.format == Format::Char => 1,
+                None => examine.size,
+            };
+        }
+
+        Ok(Command::Examine(examine, self.parse_expr()?))
+    }
+
+    fn parse_command_name(&mut self) -> Result<Option<&'static str>, Error> {
+        let name = self.src().trim();
+
+        // mark all remaining characters as read
+        self.offset = self.src.len();
+
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        match lookup(name) {
+            Some(cmd) => Ok(Some(cmd.name())),
+            None => Err(Error::UnknownName(name.to_string())),
+        }
+    }
+
     fn parse(&mut self) -> Result<Command, Error> {
-        let name = match self.parse_next("command")? {
-            "exec" | "e" => Command::Load(self.parse_file_path()?),
+        let name = self.parse_next("command")?;
+
+        // Only `x` takes a format, e.g. `x/16xb`.
+        let (name, format) = match name.split_once('/') {
+            Some((name @ "x", format)) => (name, Some(format)),
+            _ => (name, None),
+        };
+
+        let cmd = match lookup(name) {
+            Some(cmd) => cmd,
+            None => {
+                let names = COMMANDS.iter().flat_map(|cmd| cmd.names);
+                for &cmd in names.filter(|cmd| cmd.starts_with(name)) {
+                    self.suggestions.push(cmd.to_string() + " ");
+                }
+
+                return Err(Error::UnknownName(name.to_string()));
+            }
+        };
+
+        let cmd = match cmd.name() {
+            "exec" => Command::Load(self.parse_file_path()?),
+            "dyld" => {
+                let image = self.parse_next("image")?.to_string();
+                Command::LoadImage(image, self.parse_file_path()?)
+            }
             "pwd" => Command::PrintPath,
             "cd" => Command::ChangeDir(self.parse_dir_path()?),
-            "quit" | "q" => Command::Quit,
-            "goto" | "g" => Command::Goto(self.parse_debug_expr()?),
+            "quit" => Command::Quit,
+            "goto" => Command::Goto(self.parse_address()?),
+            "search" => Command::Search(self.parse_arg("pattern")?.to_string()),
+            "patch" => self.parse_patch()?,
+            "break" => self.parse_break()?,
+            "hbreak" => Command::HardwareBreak(self.parse_debug_expr()?),
+            "watch" => Command::Watch(self.parse_debug_expr()?),
+            "print" => Command::Print(self.parse_expr()?),
+            "x" => self.parse_examine(format)?,
+            "run" => Command::Run(self.parse_args()),
+            "attach" => Command::Attach(self.parse_pid()?),
+            "core" => Command::Core(self.parse_file_path()?),
+            "detach" => Command::Detach,
+            "trace" => Command::Trace(self.parse_switch()?),
+            "record" => Command::Record(self.parse_switch()?),
+            "set" => Command::Set(self.parse_setting()?),
+            "continue" => Command::Continue,
+            "step" => Command::StepLine,
+            "next" => Command::Next,
+            "stepi" => Command::Step,
+            "finish" => Command::Finish,
+            "reverse-stepi" => Command::ReverseStep,
+            "kill" => Command::Kill,
+            "thread" => Command::Thread(self.parse_tid()?),
+            "handle" => self.parse_handle()?,
+            "info" => Command::Info(self.parse_info()?),
+            "coverage" => Command::Coverage(self.parse_file_path()?),
+            "script" => Command::Script(self.parse_file_path()?),
             "clear" => Command::Clear,
-            "help" | "?" => Command::Help,
-            name => return Err(Error::UnknownName(name.to_string())),
+            "help" => Command::Help(self.parse_command_name()?),
+            name => unreachable!("command '{name}' is missing a parser"),
         };
 
-        Ok(name)
+        Ok(cmd)
     }
 }
 
@@ -413,6 +1333,12 @@ mod tests {
         eval_eq!(["f"; 0x1234], "goto main", Command::Goto(96));
     }
 
+    #[test]
+    fn goto_hex() {
+        eval_eq!("goto 401a30", Command::Goto(0x401a30));
+        eval_eq!(["add"; 0x1234], "goto add", Command::Goto(0x1234));
+    }
+
     #[test]
     fn expr_eval() {
         eval_eq!(["abc::f"; 0x1234], "goto abc::f", Command::Goto(0x1234));
@@ -423,6 +1349,271 @@ mod tests {
         );
     }
 
+    #[test]
+    fn search() {
+        eval_eq!("search E8 ?? ?? ", Command::Search("E8 ?? ??".to_string()));
+        eval_eq!("s \"call rax\"", Command::Search("\"call rax\"".to_string()));
+    }
+
+    #[test]
+    fn patch() {
+        eval_eq!(
+            "patch 401000 \"jmp 0x401050\"",
+            Command::Patch(0x401000, "jmp 0x401050".to_string())
+        );
+        eval_eq!(
+            ["main"; 0x1000],
+            "patch main + 4  \"xor eax, eax; ret\" ",
+            Command::Patch(0x1004, "xor eax, eax; ret".to_string())
+        );
+
+        let index = debugvault::Index::default();
+        assert_eq!(
+            Command::parse(&index, "patch 401000 nop", 0),
+            Err((Error::Missing("quoted instructions"), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "patch 401000 \"nop", 0),
+            Err((Error::Missing("closing quote"), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn conditional_break() {
+        let index = debugvault::Index::default();
+        let condition = |expr: Option<&str>, hits, log: Option<&str>| BreakCondition {
+            expr: expr.map(str::to_string),
+            hits,
+            log: log.map(str::to_string),
+        };
+
+        eval_eq!(
+            ["main"; 0x1000],
+            "break main + 4 if $rdi == 0",
+            Command::BreakIf(0x1004, condition(Some("$rdi == 0"), None, None))
+        );
+        eval_eq!(
+            "b 0x4010 after 3 if *(int *)rsp",
+            Command::BreakIf(0x4010, condition(Some("*(int *)rsp"), Some(3), None))
+        );
+        eval_eq!(
+            "break 0x4010 log \"i = {$rcx} if after\" after 2",
+            Command::BreakIf(0x4010, condition(None, Some(2), Some("i = {$rcx} if after")))
+        );
+        assert_eq!(
+            Command::parse(&index, "break 0x4010 after zero", 0),
+            Err((Error::InvalidCount("zero".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "break 0x4010 if", 0),
+            Err((Error::Missing("condition"), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "break 0x4010 log i", 0),
+            Err((Error::Missing("quoted message"), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "break 0x4010 after 1 after 2", 0),
+            Err((Error::DuplicateClause("after"), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "break if 1", 0),
+            Err((Error::Missing("expr"), Vec::new()))
+        );
+        assert!(Command::parse(&index, "break 0x4010 log \"{$rax\"", 0).is_err());
+    }
+
+    #[test]
+    fn pending_break() {
+        let index = debugvault::Index::default();
+
+        eval_eq!(["main"; 0x1000], "break main", Command::Break(0x1000));
+        eval_eq!(["main"; 0x1000], "b  plugin_add ", Command::PendingBreak("plugin_add".into()));
+        eval_eq!("b std::process::exit", Command::PendingBreak("std::process::exit".into()));
+        assert!(Command::parse(&index, "break plugin_add + 4", 0).is_err());
+        assert!(Command::parse(&index, "break $rax", 0).is_err());
+        assert!(Command::parse(&index, "break 0x10 +", 0).is_err());
+    }
+
+    #[test]
+    fn print() {
+        let index = debugvault::Index::default();
+        let expr = |s| CompleteExpr::parse(s).unwrap();
+
+        eval_eq!("print $rax + 8", Command::Print(expr("$rax + 8")));
+        eval_eq!("p *(int *)(rsp + 8) ", Command::Print(expr("*(int *)(rsp + 8)")));
+        assert_eq!(
+            Command::parse(&index, "print", 0),
+            Err((Error::Missing("expr"), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn examine() {
+        let index = debugvault::Index::default();
+        let expr = |s| CompleteExpr::parse(s).unwrap();
+        let examine = |count, format, size| Examine { count, format, size };
+
+        eval_eq!("x 0x401000", Command::Examine(Examine::default(), expr("0x401000")));
+        eval_eq!(
+            "x/16xb 0x401000",
+            Command::Examine(examine(16, Format::Hex, 1), expr("0x401000"))
+        );
+        eval_eq!("x/gd $rsp", Command::Examine(examine(1, Format::Signed, 8), expr("$rsp")));
+        eval_eq!("x/4c rdi", Command::Examine(examine(4, Format::Char, 1), expr("rdi")));
+        eval_eq!("x/2ho rsp", Command::Examine(examine(2, Format::Octal, 2), expr("rsp")));
+        assert_eq!(
+            Command::parse(&index, "x/0x rsp", 0),
+            Err((Error::InvalidFormat("0x".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "x/4q rsp", 0),
+            Err((Error::InvalidFormat("4q".to_string()), Vec::new()))
+        );
+        assert!(matches!(
+            Command::parse(&index, "print/x rax", 0),
+            Err((Error::UnknownName(_), _))
+        ));
+    }
+
+    #[test]
+    fn run_args() {
+        eval_eq!("run", Command::Run(Vec::new()));
+        eval_eq!(
+            "r  -v   input.txt ",
+            Command::Run(vec!["-v".to_string(), "input.txt".to_string()])
+        );
+        eval_eq!(["main"; 0x1000], "b main + 4", Command::Break(0x1004));
+        eval_eq!(["main"; 0x1000], "hb main", Command::HardwareBreak(0x1000));
+        eval_eq!("hbreak 0x401000", Command::HardwareBreak(0x401000));
+        eval_eq!("watch 0x4010", Command::Watch(0x4010));
+        eval_eq!("attach  4242 ", Command::Attach(4242));
+        eval_eq!("detach", Command::Detach);
+        eval_eq!("thread 4243", Command::Thread(4243));
+        eval_eq!("t  12 ", Command::Thread(12));
+        eval_eq!("trace on", Command::Trace(true));
+        eval_eq!("trace  off ", Command::Trace(false));
+        eval_eq!("record on", Command::Record(true));
+
+        let index = debugvault::Index::default();
+        assert_eq!(
+            Command::parse(&index, "trace yes", 0),
+            Err((Error::InvalidSwitch("yes".to_string()), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn info() {
+        eval_eq!("info regs", Command::Info(Info::Registers));
+        eval_eq!("i  breakpoints ", Command::Info(Info::Breakpoints));
+        eval_eq!("info sections", Command::Info(Info::Sections));
+        eval_eq!("info libs", Command::Info(Info::Libraries));
+        eval_eq!("info signals", Command::Info(Info::Signals));
+
+        let index = debugvault::Index::default();
+        assert_eq!(
+            Command::parse(&index, "info se", 0),
+            Err((Error::UnknownInfo("se".to_string()), vec!["info sections".to_string()]))
+        );
+    }
+
+    #[test]
+    fn handle() {
+        let nopass = SignalHandling {
+            stop: Some(true),
+            pass: Some(false),
+        };
+
+        eval_eq!("handle SIGSEGV stop nopass", Command::Handle("SIGSEGV".into(), nopass));
+        eval_eq!("handle  segv ignore  stop", Command::Handle("segv".into(), nopass));
+        eval_eq!("handle 10", Command::Handle("10".into(), SignalHandling::default()));
+        eval_eq!(
+            "handle SIGUSR1 stop nostop",
+            Command::Handle("SIGUSR1".into(), SignalHandling {
+                stop: Some(false),
+                pass: None,
+            })
+        );
+
+        let index = debugvault::Index::default();
+        assert_eq!(
+            Command::parse(&index, "handle", 0),
+            Err((Error::Missing("signal"), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "handle SIGINT print", 0),
+            Err((Error::InvalidAction("print".to_string()), Vec::new()))
+        );
+    }
+
+    #[test]
+    fn set() {
+        eval_eq!("set follow-fork child", Command::Set(Setting::FollowFork(FollowFork::Child)));
+        eval_eq!("set  follow-fork  ask ", Command::Set(Setting::FollowFork(FollowFork::Ask)));
+        eval_eq!("set libraries on", Command::Set(Setting::Libraries(true)));
+        eval_eq!("set theme solarized", Command::Set(Setting::Theme("solarized")));
+        eval_eq!("set syntax att", Command::Set(Setting::Syntax(Syntax::Att)));
+        eval_eq!("set uppercase on", Command::Set(Setting::Uppercase(true)));
+        eval_eq!("set immediates dec", Command::Set(Setting::HexImmediates(false)));
+        eval_eq!("set bytes off", Command::Set(Setting::OpcodeBytes(false)));
+        eval_eq!("set symbols off", Command::Set(Setting::Symbols(false)));
+        eval_eq!("set args", Command::Set(Setting::Args(Vec::new())));
+        eval_eq!(
+            "set args -v  input.txt",
+            Command::Set(Setting::Args(vec!["-v".to_string(), "input.txt".to_string()]))
+        );
+        eval_eq!(
+            "set env LD_PRELOAD=./hook.so",
+            Command::Set(Setting::Env("LD_PRELOAD".to_string(), "./hook.so".to_string()))
+        );
+        eval_eq!(
+            "set env EMPTY=",
+            Command::Set(Setting::Env("EMPTY".to_string(), String::new()))
+        );
+        eval_eq!("set aslr off", Command::Set(Setting::Aslr(false)));
+
+        let index = debugvault::Index::default();
+        assert_eq!(
+            Command::parse(&index, "set follow", 0),
+            Err((Error::UnknownSetting("follow".to_string()), vec!["set follow-fork ".to_string()]))
+        );
+        assert_eq!(
+            Command::parse(&index, "set follow-fork both", 0),
+            Err((Error::InvalidFollowFork("both".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "set libraries yes", 0),
+            Err((Error::InvalidSwitch("yes".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "set theme blue", 0),
+            Err((Error::UnknownTheme("blue".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "set syntax gas", 0),
+            Err((Error::InvalidSyntax("gas".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "set immediates oct", 0),
+            Err((Error::InvalidRadix("oct".to_string()), Vec::new()))
+        );
+        assert_eq!(
+            Command::parse(&index, "set env PATH", 0),
+            Err((Error::InvalidEnv, Vec::new()))
+        );
+    }
+
+    #[test]
+    fn complete_command() {
+        let index = debugvault::Index::default();
+        let (err, suggestions) = Command::parse(&index, "ex", 0).unwrap_err();
+        assert_eq!(err, Error::UnknownName("ex".to_string()));
+        assert_eq!(suggestions, ["exec "]);
+
+        let (_, suggestions) = Command::parse(&index, "de", 0).unwrap_err();
+        assert_eq!(suggestions, ["detach "]);
+    }
+
     #[test]
     fn change_dir() {
         let home = expand_homedir(PathBuf::from("~"));
@@ -436,4 +1627,63 @@ mod tests {
     fn change_dir_invalid() {
         eval_eq!("cd ???", Command::ChangeDir(PathBuf::from("???")));
     }
+
+    #[test]
+    fn load_image() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        eval_eq!(
+            &format!("dyld  Foundation {path} "),
+            Command::LoadImage("Foundation".to_string(), PathBuf::from(path))
+        );
+        eval_eq!(
+            &format!("member arm64e {path}"),
+            Command::LoadImage("arm64e".to_string(), PathBuf::from(path))
+        );
+    }
+
+    #[test]
+    fn core() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        eval_eq!(&format!("core {path}"), Command::Core(PathBuf::from(path)));
+    }
+
+    #[test]
+    fn coverage() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+        eval_eq!(&format!("coverage {path}"), Command::Coverage(PathBuf::from(path)));
+    }
+
+    #[test]
+    fn step() {
+        eval_eq!("step", Command::StepLine);
+        eval_eq!("next", Command::Next);
+        eval_eq!("n", Command::Next);
+        eval_eq!("stepi", Command::Step);
+        eval_eq!("si", Command::Step);
+        eval_eq!("finish", Command::Finish);
+        eval_eq!("rsi", Command::ReverseStep);
+    }
+
+    #[test]
+    fn help() {
+        eval_eq!("help", Command::Help(None));
+        eval_eq!("? g", Command::Help(Some("goto")));
+        eval_eq!("help  cd ", Command::Help(Some("cd")));
+    }
+
+    #[test]
+    fn help_lists_all_commands() {
+        let msg = super::help(None);
+        for cmd in COMMANDS {
+            assert!(msg.contains(cmd.usage));
+        }
+        assert!(super::help(Some("goto")).starts_with("goto <expr>"));
+    }
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(possible_command("gto"), Some("goto"));
+        assert_eq!(possible_command("hlep"), Some("help"));
+        assert_eq!(possible_command("disassemble"), None);
+    }
 }
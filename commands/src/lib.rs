@@ -2,8 +2,12 @@ mod cli;
 mod debug;
 mod gui;
 
-pub use cli::Cli;
-pub use gui::{Command, Error as CommandError, HELP as CMD_HELP};
+pub use cli::{Cli, ExportFormat};
+pub use debug::{eval_address, format_message, CompleteExpr, Error as ExprError, Machine};
+pub use gui::{
+    help, lookup, BreakCondition, Command, CommandInfo, Error as CommandError, Examine, FollowFork,
+    Format, Info, Setting, SignalHandling, COMMANDS,
+};
 use once_cell::sync::Lazy;
 
 pub static ARGS: Lazy<cli::Cli> = Lazy::new(cli::Cli::parse);
@@ -5,12 +5,57 @@ pub static CONFIG: Lazy<Config> = Lazy::new(Config::parse);
 use egui::Color32;
 use serde::de::{self, Deserializer, Visitor};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::RwLock;
+use tokenizing::{Kind, Theme};
+
+mod settings;
+
+pub use settings::{parse_shortcut, Settings, DEFAULT_KEYS, FONT_SIZES, SETTINGS};
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
+    /// Overrides of the dark theme's colors.
     #[serde(default = "defaults::colors")]
     pub colors: Colors,
+    #[serde(default = "defaults::search")]
+    pub search: Search,
+    #[serde(default = "defaults::analysis")]
+    pub analysis: Analysis,
+    #[serde(default)]
+    pub startup_location: StartupLocation,
+}
+
+/// Where the listing starts after a binary is loaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupLocation {
+    #[default]
+    EntryPoint,
+    Main,
+    FirstSymbol,
+    FirstSection,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Analysis {
+    /// Warn about code at addresses that don't match the architecture's instruction alignment.
+    #[serde(default = "defaults::check_alignment")]
+    pub check_alignment: bool,
+    /// FLIRT `.sig` or `.pat` files used to name statically linked library functions.
+    #[serde(default = "defaults::signatures")]
+    pub signatures: Vec<std::path::PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Search {
+    /// Upper bound on the number of results any search returns, unless overridden.
+    #[serde(default = "defaults::max_results")]
+    pub max_results: usize,
+    /// Minimum number of characters a string must have to be reported.
+    #[serde(default = "defaults::min_string_len")]
+    pub min_string_len: usize,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,6 +78,9 @@ pub struct Colors {
     pub bg_primary: Color32,
     #[serde(default = "defaults::bg_secondary", deserialize_with = "color32")]
     pub bg_secondary: Color32,
+    /// Colors of named roles used by annotations, see [`Colors::role_color`].
+    #[serde(default, deserialize_with = "color32_map")]
+    pub roles: HashMap<String, Color32>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -47,8 +95,6 @@ pub struct SourceColors {
     pub function: Color32,
     #[serde(default = "defaults::operator", deserialize_with = "color32")]
     pub operator: Color32,
-    #[serde(default = "defaults::code_string", deserialize_with = "color32")]
-    pub string: Color32,
     #[serde(default = "defaults::variable", deserialize_with = "color32")]
     pub variable: Color32,
     #[serde(default = "defaults::constant", deserialize_with = "color32")]
@@ -88,61 +134,150 @@ pub struct AsmColors {
 }
 
 impl Colors {
-    pub fn get_by_style(&self, style: &str) -> Color32 {
-        if style.starts_with("none") || style == "_parent" {
-            return defaults::anything();
-        }
-        if style.starts_with("keyword") || style.starts_with("module") {
-            return self.src.keyword;
-        }
-        if style.starts_with("function") {
-            return self.src.function;
-        }
-        if style.starts_with("type") || style.starts_with("property") {
-            return self.src.tipe;
-        }
-        if style.starts_with("punctuation") {
-            return self.delimiter;
-        }
-        if style.starts_with("operator") {
-            return self.src.operator;
-        }
-        if style.starts_with("comment") {
-            return self.comment;
-        }
-        if style.starts_with("string") {
-            return self.src.string;
-        }
-        if style.starts_with("variable.member") {
-            return self.src.field;
-        }
-        if style.starts_with("variable.builtin") {
-            return self.src.variable;
+    /// The built-in theme called `name`, with the colors of the config applied to the dark one.
+    pub fn theme(&self, name: &str) -> Theme {
+        if name != Theme::DARK.name {
+            if let Some(theme) = Theme::by_name(name) {
+                return theme;
+            }
+
+            log::warning!("Unknown theme '{name}', using the dark theme.");
         }
-        if style.starts_with("variable") {
-            return self.src.variable;
+
+        Theme {
+            mnemonic: self.asm.opcode,
+            register: self.asm.register,
+            immediate: self.asm.immediate,
+            expr: self.asm.expr,
+            bracket: self.brackets,
+            delimiter: self.delimiter,
+            label: self.asm.label,
+            section: self.asm.section,
+            segment: self.asm.segment,
+            component: self.asm.component,
+            primitive: self.asm.primitive,
+            pointer: self.asm.pointer,
+            annotation: self.asm.annotation,
+            invalid: self.asm.invalid,
+            string: self.asm.string,
+            comment: self.comment,
+            bytes: self.bytes,
+            address: self.address,
+            keyword: self.src.keyword,
+            tipe: self.src.tipe,
+            field: self.src.field,
+            function: self.src.function,
+            operator: self.src.operator,
+            variable: self.src.variable,
+            constant: self.src.constant,
+            highlight: self.src.highlight,
+            bg_primary: self.bg_primary,
+            bg_secondary: self.bg_secondary,
+            ..Theme::DARK
         }
-        if style.starts_with("escape") {
-            return self.src.string;
+    }
+
+    /// Kind of a token with a named role such as `"syscall"` or `"crypto"`.
+    ///
+    /// Roles set in the config take precedence, then the built-in and registered roles, see
+    /// [`register_role`]. Any other role gets a color derived from its name, such that it's
+    /// consistent across runs.
+    pub fn role_kind(&self, role: &str) -> Kind {
+        if let Some(color) = self.roles.get(role) {
+            return Kind::Custom(*color);
         }
-        if style.starts_with("number") || style.starts_with("constant") || style == "boolean" {
-            return self.src.constant;
+
+        match ROLES.read().unwrap().get(role) {
+            Some(&kind) => kind,
+            None => Kind::Custom(defaults::role(role)),
         }
+    }
+
+    /// Color of a named role in the current theme, see [`Colors::role_kind`].
+    pub fn role_color(&self, role: &str) -> Color32 {
+        tokenizing::theme().color(self.role_kind(role))
+    }
+}
+
+/// Roles known without being set in the config, along with the kind they're shown as.
+static ROLES: Lazy<RwLock<HashMap<String, Kind>>> = Lazy::new(|| {
+    let roles = [
+        ("string", Kind::String),
+        ("comment", Kind::Comment),
+        ("address", Kind::Address),
+        ("invalid", Kind::Invalid),
+        ("breakpoint", Kind::Invalid),
+        ("changed", Kind::Invalid),
+        ("heat", Kind::Invalid),
+        ("covered", Kind::Bytes),
+        ("user", Kind::Annotation),
+    ];
 
-        log::complex!(
-            w "[colors::get_by_style] ",
-            y format!("{style} is missing a highlighting implementation."),
-        );
+    RwLock::new(roles.into_iter().map(|(role, kind)| (role.to_string(), kind)).collect())
+});
+
+/// Register `role` for annotators and plugins, such that it's shown as `kind` in the current
+/// theme unless the config sets a color for it. Registering a role again replaces its kind.
+pub fn register_role(role: &str, kind: Kind) {
+    ROLES.write().unwrap().insert(role.to_string(), kind);
+}
 
-        defaults::anything()
+/// Kind of a token highlighted as `style` by tree-sitter.
+pub fn style_kind(style: &str) -> Kind {
+    if style.starts_with("none") || style == "_parent" {
+        return Kind::Plain;
     }
+    if style.starts_with("keyword") || style.starts_with("module") {
+        return Kind::Keyword;
+    }
+    if style.starts_with("function") {
+        return Kind::Function;
+    }
+    if style.starts_with("type") || style.starts_with("property") {
+        return Kind::Type;
+    }
+    if style.starts_with("punctuation") {
+        return Kind::Delimiter;
+    }
+    if style.starts_with("operator") {
+        return Kind::Operator;
+    }
+    if style.starts_with("comment") {
+        return Kind::Comment;
+    }
+    if style.starts_with("string") {
+        return Kind::String;
+    }
+    if style.starts_with("variable.member") {
+        return Kind::Field;
+    }
+    if style.starts_with("variable.builtin") {
+        return Kind::Variable;
+    }
+    if style.starts_with("variable") {
+        return Kind::Variable;
+    }
+    if style.starts_with("escape") {
+        return Kind::String;
+    }
+    if style.starts_with("number") || style.starts_with("constant") || style == "boolean" {
+        return Kind::Constant;
+    }
+
+    log::complex!(
+        w "[config::style_kind] ",
+        y format!("{style} is missing a highlighting implementation."),
+    );
+
+    Kind::Plain
 }
 
-/// Default color values when one is missing in the config.yaml's colors field.
+/// Default values when one is missing in the config.yaml, the colors being the dark theme's.
 /// Important to note is that any update to these defaults should also be reflected in the
 /// config.yaml.
 mod defaults {
     use egui::Color32;
+    use tokenizing::Theme;
 
     pub fn config() -> super::Config {
         serde_yaml::from_str("").unwrap()
@@ -156,99 +291,123 @@ mod defaults {
     pub fn asm_colors() -> super::AsmColors {
         serde_yaml::from_str("").unwrap()
     }
+    pub fn search() -> super::Search {
+        serde_yaml::from_str("").unwrap()
+    }
 
-    pub fn anything() -> Color32 {
-        Color32::from_rgb(0xc8, 0xc8, 0xc8)
+    pub fn analysis() -> super::Analysis {
+        serde_yaml::from_str("").unwrap()
+    }
+
+    pub fn max_results() -> usize {
+        10_000
+    }
+    pub fn min_string_len() -> usize {
+        4
+    }
+    pub fn check_alignment() -> bool {
+        true
+    }
+    pub fn signatures() -> Vec<std::path::PathBuf> {
+        Vec::new()
+    }
+
+    /// Hash the role's name to pick a hue, keeping saturation and brightness readable.
+    pub fn role(role: &str) -> Color32 {
+        // FNV-1a
+        let mut hash: u32 = 0x811c9dc5;
+        for byte in role.bytes() {
+            hash ^= byte as u32;
+            hash = hash.wrapping_mul(0x01000193);
+        }
+
+        let hue = (hash % 360) as f32 / 360.0;
+        egui::ecolor::Hsva::new(hue, 0.6, 0.9, 1.0).into()
     }
 
     pub fn keyword() -> Color32 {
-        Color32::from_rgb(0xff, 0x59, 0x00)
+        Theme::DARK.keyword
     }
     pub fn tipe() -> Color32 {
-        Color32::from_rgb(0xfa, 0xa5, 0x1b)
+        Theme::DARK.tipe
     }
     pub fn field() -> Color32 {
-        Color32::from_rgb(0x28, 0x8c, 0xc7)
+        Theme::DARK.field
     }
     pub fn function() -> Color32 {
-        Color32::from_rgb(0x02, 0xed, 0x6e)
+        Theme::DARK.function
     }
     pub fn operator() -> Color32 {
-        Color32::from_rgb(0xff, 0xa5, 0x00)
-    }
-    pub fn code_string() -> Color32 {
-        Color32::from_rgb(0x02, 0xed, 0x6e)
+        Theme::DARK.operator
     }
     pub fn variable() -> Color32 {
-        Color32::from_rgb(0xd4, 0x6c, 0xcb)
+        Theme::DARK.variable
     }
     pub fn constant() -> Color32 {
-        Color32::from_rgb(0x9b, 0x51, 0xc2)
+        Theme::DARK.constant
     }
     pub fn highlight() -> Color32 {
-        Color32::from_rgb(255, 100, 0)
+        Theme::DARK.highlight
     }
-
     pub fn section() -> Color32 {
-        label()
+        Theme::DARK.section
     }
     pub fn opcode() -> Color32 {
-        Color32::from_rgb(0xff, 0xff, 0xff)
+        Theme::DARK.mnemonic
     }
     pub fn component() -> Color32 {
-        Color32::from_rgb(0xf5, 0x62, 0x81)
+        Theme::DARK.component
     }
     pub fn register() -> Color32 {
-        component()
+        Theme::DARK.register
     }
     pub fn label() -> Color32 {
-        Color32::from_rgb(0x3e, 0xbc, 0xe6)
+        Theme::DARK.label
     }
     pub fn segment() -> Color32 {
-        Color32::from_rgb(0x02, 0xed, 0x6e)
+        Theme::DARK.segment
     }
     pub fn invalid() -> Color32 {
-        Color32::from_rgb(0xff, 0x43, 0x43)
+        Theme::DARK.invalid
     }
     pub fn pointer() -> Color32 {
-        invalid()
+        Theme::DARK.pointer
     }
     pub fn expr() -> Color32 {
-        Color32::from_rgb(0x99, 0x99, 0x99)
+        Theme::DARK.expr
     }
     pub fn immediate() -> Color32 {
-        Color32::from_rgb(0x3e, 0xbc, 0xe6)
+        Theme::DARK.immediate
     }
     pub fn annotation() -> Color32 {
-        immediate()
+        Theme::DARK.annotation
     }
     pub fn primitive() -> Color32 {
-        Color32::from_rgb(0x69, 0xb5, 0xa9)
+        Theme::DARK.primitive
     }
     pub fn asm_string() -> Color32 {
-        Color32::from_rgb(0xe6, 0xab, 0x3e)
+        Theme::DARK.string
     }
-
     pub fn brackets() -> Color32 {
-        Color32::from_rgb(0x60, 0x60, 0x60)
+        Theme::DARK.bracket
     }
     pub fn comment() -> Color32 {
-        Color32::from_rgb(0xa0, 0xa0, 0xa0)
+        Theme::DARK.comment
     }
     pub fn address() -> Color32 {
-        Color32::from_rgb(0x40, 0x40, 0x40)
+        Theme::DARK.address
     }
     pub fn bytes() -> Color32 {
-        Color32::from_rgb(0x02, 0xed, 0x6e)
+        Theme::DARK.bytes
     }
     pub fn delimiter() -> Color32 {
-        comment()
+        Theme::DARK.delimiter
     }
     pub fn bg_primary() -> Color32 {
-        Color32::from_rgb(0x30, 0x30, 0x30)
+        Theme::DARK.bg_primary
     }
     pub fn bg_secondary() -> Color32 {
-        Color32::from_rgb(0x2d, 0x2d, 0x2d)
+        Theme::DARK.bg_secondary
     }
 }
 
@@ -292,3 +451,13 @@ fn color32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color32, D::Err
 
     deserializer.deserialize_str(ColorParsing)
 }
+
+fn color32_map<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Color32>, D::Error> {
+    #[derive(Deserialize)]
+    struct Hex(#[serde(deserialize_with = "color32")] Color32);
+
+    let map: HashMap<String, Hex> = HashMap::deserialize(deserializer)?;
+    Ok(map.into_iter().map(|(role, Hex(color))| (role, color)).collect())
+}
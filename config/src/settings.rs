@@ -0,0 +1,216 @@
+//! Preferences edited from the GUI, kept in a TOML file next to the config.yaml.
+//!
+//! Unlike the config.yaml, which is only read, the settings file is written back whenever a
+//! preference changes.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+pub static SETTINGS: Lazy<RwLock<Settings>> = Lazy::new(|| RwLock::new(Settings::load()));
+
+/// Most binaries remembered as recently opened.
+const MAX_RECENT_FILES: usize = 10;
+
+/// Smallest and largest size of the text that can be set.
+pub const FONT_SIZES: std::ops::RangeInclusive<f32> = 8.0..=32.0;
+
+/// Shortcuts of what can be done from the keyboard, used unless they're set in the settings.
+///
+/// These follow IDA and Ghidra where they can, with a modifier added to the keys that would
+/// otherwise be typed in the terminal.
+pub const DEFAULT_KEYS: &[(&str, &str)] = &[
+    ("open", "Ctrl+O"),
+    ("save", "Ctrl+S"),
+    ("undo", "Ctrl+Z"),
+    ("redo", "Ctrl+Shift+Z"),
+    ("goto", "Ctrl+G, Ctrl+P"),
+    ("search", "Alt+T"),
+    ("next_result", "F3, Ctrl+T"),
+    ("previous_result", "Shift+F3"),
+    ("back", "Escape, Alt+Left"),
+    ("forward", "Ctrl+Enter, Alt+Right"),
+    ("rename", "Ctrl+N"),
+    ("comment", "Ctrl+Semicolon"),
+    ("bookmark", "Alt+M"),
+    ("toggle_breakpoint", "F2"),
+    ("continue", "F9"),
+    ("step", "F7"),
+    ("next", "F8"),
+    ("step_out", "Ctrl+F7"),
+    ("step_back", "Shift+F7"),
+    ("next_tab", "Ctrl+Tab"),
+    ("preferences", "Ctrl+Comma"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Size of the monospaced text the panels are drawn with.
+    pub font_size: f32,
+    /// Name of the built-in theme, one of dark, light or solarized.
+    pub theme: String,
+    /// How instructions are shown, kept as a `[format]` table.
+    pub format: tokenizing::Format,
+    /// Panels that are open when bite starts, by their name.
+    pub panels: Vec<String>,
+    /// Shortcuts by the name of what they do, e.g. `undo = "Ctrl+Z"`, replacing the defaults.
+    pub keys: BTreeMap<String, String>,
+    /// Binaries opened most recently, the latest first.
+    pub recent_files: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font_size: 14.0,
+            theme: tokenizing::Theme::DARK.name.to_string(),
+            format: tokenizing::Format::DEFAULT,
+            panels: ["disassembly", "functions", "logs"].map(String::from).to_vec(),
+            keys: BTreeMap::new(),
+            recent_files: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    /// Where the settings are kept, `None` if there's no data directory.
+    pub fn path() -> Option<PathBuf> {
+        let mut path = dirs::data_dir()?;
+        path.push("bite");
+        path.push("settings.toml");
+        Some(path)
+    }
+
+    /// Read the settings file, any setting that's missing or invalid taking it's default.
+    pub fn load() -> Self {
+        let raw = match Self::path().map(std::fs::read_to_string) {
+            Some(Ok(raw)) => raw,
+            _ => return Self::default(),
+        };
+
+        match toml::from_str::<Self>(&raw) {
+            Ok(mut settings) => {
+                let (min, max) = FONT_SIZES.into_inner();
+                settings.font_size = settings.font_size.clamp(min, max);
+                settings
+            }
+            Err(err) => {
+                log::warning!("Failed to parse settings.\nError: {err}.");
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the settings file, creating the directory it's in if needed.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let raw = toml::to_string_pretty(self).map_err(io::Error::other)?;
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        std::fs::write(path, raw)
+    }
+
+    /// Shortcuts bound to `action` as they're written, several being separated by commas.
+    pub fn keys(&self, action: &str) -> &str {
+        match self.keys.get(action) {
+            Some(keys) => keys,
+            None => match DEFAULT_KEYS.iter().find(|(name, _)| *name == action) {
+                Some((_, keys)) => keys,
+                None => "",
+            },
+        }
+    }
+
+    /// Shortcuts bound to `action`, those that aren't valid being left out.
+    pub fn shortcuts(&self, action: &str) -> Vec<egui::KeyboardShortcut> {
+        self.keys(action).split(',').filter_map(parse_shortcut).collect()
+    }
+
+    /// Remember `path` as the binary opened last.
+    pub fn opened(&mut self, path: &Path) {
+        self.recent_files.retain(|recent| recent != path);
+        self.recent_files.insert(0, path.to_path_buf());
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+}
+
+/// Parse a shortcut such as `Ctrl+Shift+Z`, where `Ctrl` is `Cmd` on macOS.
+pub fn parse_shortcut(s: &str) -> Option<egui::KeyboardShortcut> {
+    let mut modifiers = egui::Modifiers::NONE;
+    let mut parts = s.split('+').map(str::trim).peekable();
+
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            let key = egui::Key::from_name(part)?;
+            return Some(egui::KeyboardShortcut::new(modifiers, key));
+        }
+
+        modifiers = modifiers
+            | match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "cmd" => egui::Modifiers::COMMAND,
+                "shift" => egui::Modifiers::SHIFT,
+                "alt" | "option" => egui::Modifiers::ALT,
+                _ => return None,
+            };
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortcuts() {
+        let redo = parse_shortcut("Ctrl+Shift+Z").unwrap();
+        assert_eq!(redo.logical_key, egui::Key::Z);
+        assert_eq!(redo.modifiers, egui::Modifiers::COMMAND | egui::Modifiers::SHIFT);
+        assert_eq!(parse_shortcut(" Ctrl + Shift + Z "), Some(redo));
+        assert_eq!(parse_shortcut("Cmd+Shift+Z"), Some(redo));
+
+        // Keys and modifiers that don't exist, and modifiers without a key.
+        assert_eq!(parse_shortcut("Ctrl+Foo"), None);
+        assert_eq!(parse_shortcut("Hyper+Z"), None);
+        assert_eq!(parse_shortcut("Ctrl+"), None);
+
+        for (action, keys) in DEFAULT_KEYS {
+            let shortcuts = Settings::default().shortcuts(action);
+            assert_eq!(shortcuts.len(), keys.split(',').count(), "{action} = {keys:?}");
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut settings = Settings {
+            font_size: 18.0,
+            ..Settings::default()
+        };
+        settings.keys.insert("undo".to_string(), "Ctrl+U".to_string());
+        settings.opened(Path::new("/bin/true"));
+
+        // Only the shortcuts that were set are written, the others keep following the defaults.
+        let raw = toml::to_string_pretty(&settings).unwrap();
+        assert!(!raw.contains("redo"));
+
+        let read: Settings = toml::from_str(&raw).unwrap();
+        assert_eq!(read, settings);
+        assert_eq!(read.keys("undo"), "Ctrl+U");
+        assert_eq!(read.keys("redo"), "Ctrl+Shift+Z");
+
+        // Settings missing from the file take their default.
+        let read: Settings = toml::from_str("font_size = 16.0").unwrap();
+        assert_eq!(read.theme, Settings::default().theme);
+        for (action, keys) in DEFAULT_KEYS {
+            assert_eq!(read.keys(action), *keys);
+        }
+    }
+}
@@ -0,0 +1,725 @@
+//! Software breakpoints, hardware breakpoints and hardware watchpoints.
+//!
+//! The GUI and the debugger share a [`BreakpointSet`] describing where execution should stop.
+//! The debugger only patches the tracee's memory whilst it's stopped, so changes to the set are
+//! applied the next time the tracee stops, see [`crate::Handle::interrupt`].
+//!
+//! Hardware breakpoints share the debug registers with the watchpoints, leaving the tracee's
+//! code untouched. Backends without debug registers for them insert them as traps instead.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::Error;
+
+/// Instruction that raises a trap, `int3` on x86 and `brk #0` on aarch64.
+#[cfg(not(target_arch = "aarch64"))]
+pub const TRAP: &[u8] = &[0xcc];
+
+/// Instruction that raises a trap, `int3` on x86 and `brk #0` on aarch64.
+#[cfg(target_arch = "aarch64")]
+pub const TRAP: &[u8] = &0xd4200000u32.to_le_bytes();
+
+/// Number of watchpoints and hardware breakpoints that can be set at once, limited by the debug
+/// registers.
+pub const MAX_WATCHPOINTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    /// Stop after the memory got written to.
+    Write,
+    /// Stop after the memory got read or written to.
+    ReadWrite,
+    /// Stop before the instruction at the address executes, used by hardware breakpoints.
+    Execute,
+}
+
+/// Memory that's watched using the debug registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchpoint {
+    /// Address as found in the binary.
+    pub addr: usize,
+    /// Either 1, 2, 4 or 8 bytes, the address must be aligned to the length.
+    pub len: usize,
+    pub kind: WatchKind,
+}
+
+/// When a breakpoint stops execution, breakpoints without one always stop.
+///
+/// The debugger doesn't evaluate these itself, whoever resumes the tracee decides whether a hit
+/// is reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Condition {
+    /// Expression that has to evaluate to non-zero for the breakpoint to trigger.
+    pub expr: Option<String>,
+    /// Hit the breakpoint starts triggering at, only counting hits where `expr` held.
+    pub hits: Option<usize>,
+    /// Message that's printed instead of stopping, with expressions in braces replaced by
+    /// their value.
+    pub log: Option<String>,
+}
+
+/// Addresses, as found in the binary, execution should stop at.
+#[derive(Default)]
+pub struct BreakpointSet {
+    addrs: Mutex<BTreeSet<usize>>,
+    conditions: Mutex<BTreeMap<usize, Condition>>,
+    /// Addresses of breakpoints that use a debug register instead of patching in a trap.
+    hardware: Mutex<BTreeSet<usize>>,
+    /// Addresses stopped at on behalf of the debugger itself, such as when stepping over a call.
+    temporary: Mutex<BTreeSet<usize>>,
+    watchpoints: Mutex<Vec<Watchpoint>>,
+    /// Functions to stop at once a library defining them is loaded, by their name.
+    pending: Mutex<BTreeSet<String>>,
+    /// Functions of the breakpoints that were pending, by the address they got set at.
+    resolved: Mutex<BTreeMap<usize, String>>,
+    /// Set whenever the breakpoints changed since the debugger last applied them.
+    dirty: AtomicBool,
+}
+
+impl BreakpointSet {
+    /// Set a breakpoint at `addr` if there isn't one, otherwise clear it.
+    /// Returns whether a breakpoint is now set.
+    pub fn toggle(&self, addr: usize) -> bool {
+        let mut addrs = self.addrs.lock().unwrap();
+        let set = if addrs.remove(&addr) {
+            self.conditions.lock().unwrap().remove(&addr);
+            self.hardware.lock().unwrap().remove(&addr);
+            self.resolved.lock().unwrap().remove(&addr);
+            false
+        } else {
+            addrs.insert(addr)
+        };
+
+        self.dirty.store(true, Ordering::Release);
+        set
+    }
+
+    /// Set a breakpoint at `addr` that only triggers when `condition` holds, replacing the
+    /// condition of any breakpoint that's already there.
+    pub fn set_conditional(&self, addr: usize, condition: Condition) {
+        self.addrs.lock().unwrap().insert(addr);
+
+        let mut conditions = self.conditions.lock().unwrap();
+        if condition == Condition::default() {
+            conditions.remove(&addr);
+        } else {
+            conditions.insert(addr, condition);
+        }
+
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Condition of the breakpoint at `addr`, None if it always stops.
+    pub fn condition(&self, addr: usize) -> Option<Condition> {
+        self.conditions.lock().unwrap().get(&addr).cloned()
+    }
+
+    /// Make the breakpoint at `addr` use a debug register if `hardware`, otherwise a trap, setting
+    /// it if there isn't one. Hardware breakpoints count towards [`MAX_WATCHPOINTS`].
+    pub fn set_hardware(&self, addr: usize, hardware: bool) -> Result<(), Error> {
+        {
+            let watchpoints = self.watchpoints.lock().unwrap();
+            let mut addrs = self.hardware.lock().unwrap();
+            if hardware && !addrs.contains(&addr) {
+                if watchpoints.len() + addrs.len() == MAX_WATCHPOINTS {
+                    return Err(Error::TooManyWatchpoints);
+                }
+
+                addrs.insert(addr);
+            } else if !hardware {
+                addrs.remove(&addr);
+            }
+        }
+
+        self.addrs.lock().unwrap().insert(addr);
+        self.dirty.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Whether the breakpoint at `addr` uses a debug register.
+    pub fn is_hardware(&self, addr: usize) -> bool {
+        self.hardware.lock().unwrap().contains(&addr)
+    }
+
+    pub fn contains(&self, addr: usize) -> bool {
+        self.addrs.lock().unwrap().contains(&addr)
+    }
+
+    pub fn addrs(&self) -> Vec<usize> {
+        self.addrs.lock().unwrap().iter().copied().collect()
+    }
+
+    /// Stop at the function `name` once a library defining it is loaded, unless it's already
+    /// pending, in which case it's cleared. Returns whether it's now pending.
+    pub fn toggle_pending(&self, name: &str) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        let set = !pending.remove(name) && pending.insert(name.to_string());
+
+        self.dirty.store(true, Ordering::Release);
+        set
+    }
+
+    /// Functions that are stopped at once a library defining them is loaded.
+    pub fn pending(&self) -> Vec<String> {
+        self.pending.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// Function the breakpoint at `addr` was set on, if it was pending until it's library got
+    /// loaded.
+    pub fn function(&self, addr: usize) -> Option<String> {
+        self.resolved.lock().unwrap().get(&addr).cloned()
+    }
+
+    /// Set the breakpoint on the pending function `name`, now found at `addr`.
+    pub(crate) fn resolve(&self, name: &str, addr: usize) {
+        if !self.pending.lock().unwrap().remove(name) {
+            return;
+        }
+
+        self.addrs.lock().unwrap().insert(addr);
+        self.resolved.lock().unwrap().insert(addr, name.to_string());
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Breakpoints set on pending functions, by their address.
+    pub(crate) fn resolved(&self) -> Vec<(usize, String)> {
+        let resolved = self.resolved.lock().unwrap();
+        resolved.iter().map(|(&addr, name)| (addr, name.clone())).collect()
+    }
+
+    /// Make the breakpoint at `addr` pending again, as the library it was in got unloaded.
+    pub(crate) fn unresolve(&self, addr: usize) {
+        let name = match self.resolved.lock().unwrap().remove(&addr) {
+            Some(name) => name,
+            None => return,
+        };
+
+        self.addrs.lock().unwrap().remove(&addr);
+        self.conditions.lock().unwrap().remove(&addr);
+        self.hardware.lock().unwrap().remove(&addr);
+        self.pending.lock().unwrap().insert(name);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Stop at `addr` until the temporary breakpoints are cleared, without it being one of the
+    /// [`BreakpointSet::addrs`].
+    pub fn set_temporary(&self, addr: usize) {
+        self.temporary.lock().unwrap().insert(addr);
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    pub fn clear_temporary(&self) {
+        self.temporary.lock().unwrap().clear();
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Addresses to insert traps at, including the temporary breakpoints. Hardware breakpoints
+    /// are left out if they're put in the debug registers instead, see
+    /// [`BreakpointSet::debug_registers`].
+    pub(crate) fn wanted(&self, debug_registers: bool) -> BTreeSet<usize> {
+        let mut addrs = self.addrs.lock().unwrap().clone();
+        if debug_registers {
+            for addr in self.hardware.lock().unwrap().iter() {
+                addrs.remove(addr);
+            }
+        }
+
+        addrs.extend(self.temporary.lock().unwrap().iter().copied());
+        addrs
+    }
+
+    pub fn clear(&self) {
+        self.addrs.lock().unwrap().clear();
+        self.conditions.lock().unwrap().clear();
+        self.hardware.lock().unwrap().clear();
+        self.watchpoints.lock().unwrap().clear();
+        self.pending.lock().unwrap().clear();
+        self.resolved.lock().unwrap().clear();
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Add a watchpoint, replacing any watchpoint at the same address.
+    pub fn set_watchpoint(&self, watchpoint: Watchpoint) -> Result<(), Error> {
+        let len = watchpoint.len;
+        if !matches!(len, 1 | 2 | 4 | 8) || watchpoint.addr % len != 0 {
+            return Err(Error::InvalidWatchpoint);
+        }
+
+        let mut watchpoints = self.watchpoints.lock().unwrap();
+        watchpoints.retain(|wp| wp.addr != watchpoint.addr);
+
+        if watchpoints.len() + self.hardware.lock().unwrap().len() == MAX_WATCHPOINTS {
+            return Err(Error::TooManyWatchpoints);
+        }
+
+        watchpoints.push(watchpoint);
+        self.dirty.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Remove the watchpoint at `addr`, returns whether there was one.
+    pub fn remove_watchpoint(&self, addr: usize) -> bool {
+        let mut watchpoints = self.watchpoints.lock().unwrap();
+        let len = watchpoints.len();
+        watchpoints.retain(|wp| wp.addr != addr);
+
+        let removed = watchpoints.len() != len;
+        if removed {
+            self.dirty.store(true, Ordering::Release);
+        }
+
+        removed
+    }
+
+    pub fn watchpoints(&self) -> Vec<Watchpoint> {
+        self.watchpoints.lock().unwrap().clone()
+    }
+
+    /// Debug registers that are left for watchpoints and hardware breakpoints.
+    pub fn free_debug_registers(&self) -> usize {
+        MAX_WATCHPOINTS.saturating_sub(self.debug_registers().len())
+    }
+
+    /// What to put in the debug registers, the watchpoints followed by the hardware breakpoints.
+    pub(crate) fn debug_registers(&self) -> Vec<Watchpoint> {
+        let mut watchpoints = self.watchpoints();
+        watchpoints.extend(self.hardware.lock().unwrap().iter().map(|&addr| Watchpoint {
+            addr,
+            len: 1,
+            kind: WatchKind::Execute,
+        }));
+
+        watchpoints
+    }
+
+    /// Whether the set changed since the last call.
+    pub(crate) fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+}
+
+/// Value of x86's DR7 enabling `watchpoints` in DR0 to DR3 respectively.
+pub(crate) fn dr7(watchpoints: &[Watchpoint]) -> u64 {
+    watchpoints.iter().take(MAX_WATCHPOINTS).enumerate().fold(0, |dr7, (idx, wp)| {
+        let rw: u64 = match wp.kind {
+            WatchKind::Execute => 0b00,
+            WatchKind::Write => 0b01,
+            WatchKind::ReadWrite => 0b11,
+        };
+
+        let len: u64 = match wp.len {
+            1 => 0b00,
+            2 => 0b01,
+            8 => 0b10,
+            _ => 0b11,
+        };
+
+        // Local enable bit, followed by the condition and length fields.
+        dr7 | (1u64 << (idx * 2)) | (rw << (16 + idx * 4)) | (len << (18 + idx * 4))
+    })
+}
+
+/// Reads and writes to the memory of a stopped tracee.
+pub(crate) trait Memory {
+    type Error;
+
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), Self::Error>;
+    fn write(&self, addr: usize, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Breakpoints currently patched into the tracee, by their runtime address.
+#[derive(Default)]
+pub(crate) struct Inserted {
+    /// Original bytes overwritten by [`TRAP`].
+    originals: BTreeMap<usize, Vec<u8>>,
+    /// Breakpoints the debugger stops at for itself, which stay inserted regardless of the set.
+    internal: BTreeSet<usize>,
+}
+
+impl Inserted {
+    pub fn contains(&self, addr: usize) -> bool {
+        self.originals.contains_key(&addr)
+    }
+
+    /// Insert a breakpoint the debugger handles itself, kept when syncing with the set.
+    pub fn insert_internal<M: Memory>(&mut self, mem: &M, addr: usize) -> Result<(), M::Error> {
+        self.internal.insert(addr);
+        self.insert(mem, addr)
+    }
+
+    pub fn is_internal(&self, addr: usize) -> bool {
+        self.internal.contains(&addr)
+    }
+
+    /// Stop keeping the breakpoint at `addr` inserted for the debugger, it's removed once it's
+    /// stepped over or synced unless the set has it.
+    pub fn remove_internal(&mut self, addr: usize) {
+        self.internal.remove(&addr);
+    }
+
+    /// Forget the breakpoint at `addr` without restoring it's original bytes, as the memory it
+    /// was in got unmapped.
+    pub fn forget(&mut self, addr: usize) {
+        self.originals.remove(&addr);
+        self.internal.remove(&addr);
+    }
+
+    pub fn insert<M: Memory>(&mut self, mem: &M, addr: usize) -> Result<(), M::Error> {
+        if self.contains(addr) {
+            return Ok(());
+        }
+
+        let mut original = vec![0; TRAP.len()];
+        mem.read(addr, &mut original)?;
+        mem.write(addr, TRAP)?;
+        self.originals.insert(addr, original);
+        Ok(())
+    }
+
+    pub fn remove<M: Memory>(&mut self, mem: &M, addr: usize) -> Result<(), M::Error> {
+        match self.originals.remove(&addr) {
+            Some(original) => mem.write(addr, &original),
+            None => Ok(()),
+        }
+    }
+
+    /// Restore the original bytes of all breakpoints.
+    pub fn remove_all<M: Memory>(&mut self, mem: &M) -> Result<(), M::Error> {
+        let addrs: Vec<usize> = self.originals.keys().copied().collect();
+        for addr in addrs {
+            self.remove(mem, addr)?;
+        }
+
+        Ok(())
+    }
+
+    /// Restore the original bytes in a copy of the tracee's memory, such as that of a forked
+    /// child, the breakpoints stay inserted in the tracee.
+    pub fn restore<M: Memory>(&self, mem: &M) -> Result<(), M::Error> {
+        for (&addr, original) in &self.originals {
+            mem.write(addr, original)?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the traps in `buf`, read from `addr`, with the original bytes.
+    pub fn hide(&self, addr: usize, buf: &mut [u8]) {
+        let end = addr + buf.len();
+        let start = addr.saturating_sub(TRAP.len() - 1);
+
+        for (&bp, original) in self.originals.range(start..end) {
+            for (idx, &byte) in original.iter().enumerate() {
+                if let Some(offset) = (bp + idx).checked_sub(addr).filter(|&o| o < buf.len()) {
+                    buf[offset] = byte;
+                }
+            }
+        }
+    }
+
+    /// Write `bytes` at `addr`, keeping the breakpoints they overlap inserted.
+    pub fn write<M: Memory>(&mut self, mem: &M, addr: usize, bytes: &[u8]) -> Result<(), M::Error> {
+        let start = addr.saturating_sub(TRAP.len() - 1);
+        let overlapping: Vec<usize> =
+            self.originals.range(start..addr + bytes.len()).map(|(&bp, _)| bp).collect();
+
+        for &bp in &overlapping {
+            self.remove(mem, bp)?;
+        }
+
+        mem.write(addr, bytes)?;
+
+        for bp in overlapping {
+            self.insert(mem, bp)?;
+        }
+
+        Ok(())
+    }
+
+    /// Make the inserted breakpoints match `set` along with the internal ones, `bias` being the
+    /// offset between the addresses in the binary and where it's loaded. Hardware breakpoints
+    /// are only inserted as traps without `debug_registers` to put them in.
+    pub fn sync<M: Memory>(
+        &mut self,
+        mem: &M,
+        set: &BreakpointSet,
+        bias: usize,
+        debug_registers: bool,
+    ) -> Result<(), M::Error> {
+        let mut wanted: BTreeSet<usize> =
+            set.wanted(debug_registers).into_iter().map(|addr| addr.wrapping_add(bias)).collect();
+        wanted.extend(self.internal.iter().copied());
+
+        let stale: Vec<usize> =
+            self.originals.keys().copied().filter(|addr| !wanted.contains(addr)).collect();
+
+        for addr in stale {
+            self.remove(mem, addr)?;
+        }
+
+        for addr in wanted {
+            self.insert(mem, addr)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct Buffer(RefCell<Vec<u8>>);
+
+    impl Memory for Buffer {
+        type Error = ();
+
+        fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), ()> {
+            let bytes = self.0.borrow();
+            buf.copy_from_slice(bytes.get(addr..addr + buf.len()).ok_or(())?);
+            Ok(())
+        }
+
+        fn write(&self, addr: usize, bytes: &[u8]) -> Result<(), ()> {
+            let mut mem = self.0.borrow_mut();
+            mem.get_mut(addr..addr + bytes.len()).ok_or(())?.copy_from_slice(bytes);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn toggle() {
+        let set = BreakpointSet::default();
+        assert!(set.toggle(0x10));
+        assert!(set.take_dirty());
+        assert!(!set.take_dirty());
+        assert!(!set.toggle(0x10));
+        assert!(set.addrs().is_empty());
+    }
+
+    #[test]
+    fn conditions() {
+        let set = BreakpointSet::default();
+        let condition = Condition {
+            expr: Some("$rdi == 0".to_string()),
+            hits: Some(3),
+            log: None,
+        };
+
+        set.set_conditional(0x10, condition.clone());
+        assert!(set.contains(0x10));
+        assert_eq!(set.condition(0x10), Some(condition));
+        assert_eq!(set.condition(0x20), None);
+
+        set.set_conditional(0x10, Condition::default());
+        assert!(set.contains(0x10));
+        assert_eq!(set.condition(0x10), None);
+
+        set.set_conditional(0x10, Condition {
+            hits: Some(2),
+            ..Condition::default()
+        });
+        assert!(!set.toggle(0x10));
+        assert_eq!(set.condition(0x10), None);
+    }
+
+    #[test]
+    fn temporary() {
+        let set = BreakpointSet::default();
+        set.toggle(0x10);
+        set.set_temporary(0x20);
+        assert_eq!(set.addrs(), vec![0x10]);
+        assert_eq!(set.wanted(false).into_iter().collect::<Vec<_>>(), vec![0x10, 0x20]);
+
+        set.clear_temporary();
+        assert_eq!(set.wanted(false).into_iter().collect::<Vec<_>>(), vec![0x10]);
+    }
+
+    #[test]
+    fn pending() {
+        let set = BreakpointSet::default();
+        assert!(set.toggle_pending("puts"));
+        assert!(set.toggle_pending("malloc"));
+        assert!(!set.toggle_pending("malloc"));
+        assert_eq!(set.pending(), vec!["puts"]);
+
+        set.resolve("malloc", 0x30);
+        set.resolve("puts", 0x20);
+        assert!(set.pending().is_empty());
+        assert_eq!(set.addrs(), vec![0x20]);
+        assert_eq!(set.function(0x20).as_deref(), Some("puts"));
+
+        // Unloading the library makes it pending again.
+        set.unresolve(0x20);
+        assert!(set.addrs().is_empty());
+        assert_eq!(set.pending(), vec!["puts"]);
+
+        set.resolve("puts", 0x40);
+        assert!(!set.toggle(0x40));
+        assert_eq!(set.function(0x40), None);
+        assert!(set.resolved().is_empty());
+    }
+
+    #[test]
+    fn watchpoints() {
+        let set = BreakpointSet::default();
+        let write = |addr, len| Watchpoint {
+            addr,
+            len,
+            kind: WatchKind::Write,
+        };
+
+        assert!(set.set_watchpoint(write(0x1001, 4)).is_err());
+        assert!(set.set_watchpoint(write(0x1000, 3)).is_err());
+
+        for idx in 0..MAX_WATCHPOINTS {
+            set.set_watchpoint(write(0x1000 + idx * 8, 8)).unwrap();
+        }
+
+        assert!(set.set_watchpoint(write(0x2000, 8)).is_err());
+        assert!(set.remove_watchpoint(0x1000));
+        assert!(!set.remove_watchpoint(0x1000));
+        assert!(set.set_watchpoint(write(0x2000, 8)).is_ok());
+    }
+
+    #[test]
+    fn hardware() {
+        let set = BreakpointSet::default();
+        set.toggle(0x10);
+        set.set_hardware(0x10, true).unwrap();
+        set.set_hardware(0x20, true).unwrap();
+        assert!(set.is_hardware(0x10));
+        assert_eq!(set.addrs(), vec![0x10, 0x20]);
+        assert!(set.wanted(true).is_empty());
+        assert_eq!(set.wanted(false).len(), 2);
+
+        let execute = |addr| Watchpoint {
+            addr,
+            len: 1,
+            kind: WatchKind::Execute,
+        };
+
+        // Hardware breakpoints take up debug registers after the watchpoints.
+        let write = Watchpoint {
+            addr: 0x1000,
+            len: 8,
+            kind: WatchKind::Write,
+        };
+
+        set.set_watchpoint(write).unwrap();
+        assert_eq!(set.debug_registers(), vec![write, execute(0x10), execute(0x20)]);
+        set.set_hardware(0x30, true).unwrap();
+        assert_eq!(set.free_debug_registers(), 0);
+        assert!(set.set_hardware(0x40, true).is_err());
+        assert!(set.set_watchpoint(Watchpoint { addr: 0x2000, ..write }).is_err());
+
+        set.set_hardware(0x30, false).unwrap();
+        assert!(!set.is_hardware(0x30));
+        assert!(set.wanted(true).contains(&0x30));
+        assert!(!set.toggle(0x10));
+        assert!(!set.is_hardware(0x10));
+        assert_eq!(set.debug_registers(), vec![write, execute(0x20)]);
+    }
+
+    #[test]
+    fn dr7_encoding() {
+        let watchpoints = [
+            Watchpoint {
+                addr: 0x1000,
+                len: 8,
+                kind: WatchKind::Write,
+            },
+            Watchpoint {
+                addr: 0x2000,
+                len: 1,
+                kind: WatchKind::ReadWrite,
+            },
+            Watchpoint {
+                addr: 0x3000,
+                len: 1,
+                kind: WatchKind::Execute,
+            },
+        ];
+
+        // DR0: enabled, write, 8 bytes. DR1: enabled, read/write, 1 byte. DR2: enabled, execute.
+        assert_eq!(dr7(&watchpoints), (0b0011_1001 << 16) | 0b01_0101);
+    }
+
+    #[test]
+    fn sync_restores_original_bytes() {
+        let original: Vec<u8> = (0..32).collect();
+        let mem = Buffer(RefCell::new(original.clone()));
+        let set = BreakpointSet::default();
+        let mut inserted = Inserted::default();
+
+        set.toggle(0x4);
+        inserted.sync(&mem, &set, 0x10, true).unwrap();
+        assert_eq!(&mem.0.borrow()[0x14..][..TRAP.len()], TRAP);
+
+        set.toggle(0x4);
+        inserted.sync(&mem, &set, 0x10, true).unwrap();
+        assert_eq!(*mem.0.borrow(), original);
+    }
+
+    #[test]
+    fn sync_keeps_internal_breakpoints() {
+        let original: Vec<u8> = (0..32).collect();
+        let mem = Buffer(RefCell::new(original.clone()));
+        let set = BreakpointSet::default();
+        let mut inserted = Inserted::default();
+
+        inserted.insert_internal(&mem, 0x8).unwrap();
+        inserted.sync(&mem, &set, 0x10, true).unwrap();
+        assert!(inserted.is_internal(0x8));
+        assert_eq!(&mem.0.borrow()[0x8..][..TRAP.len()], TRAP);
+
+        // Put back after the memory was shared with a process that isn't traced.
+        inserted.remove_all(&mem).unwrap();
+        inserted.sync(&mem, &set, 0x10, true).unwrap();
+        assert!(inserted.contains(0x8));
+
+        inserted.remove_internal(0x8);
+        inserted.sync(&mem, &set, 0x10, true).unwrap();
+        assert_eq!(*mem.0.borrow(), original);
+
+        // Unmapped memory can't be restored.
+        inserted.insert_internal(&mem, 0x8).unwrap();
+        inserted.forget(0x8);
+        assert!(!inserted.contains(0x8));
+        assert_eq!(&mem.0.borrow()[0x8..][..TRAP.len()], TRAP);
+    }
+
+    #[test]
+    fn writes_keep_breakpoints() {
+        let mem = Buffer(RefCell::new(vec![0; 16]));
+        let mut inserted = Inserted::default();
+        inserted.insert(&mem, 0x8).unwrap();
+
+        inserted.write(&mem, 0x6, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(&mem.0.borrow()[0x8..][..TRAP.len()], TRAP);
+
+        let mut buf = mem.0.borrow()[0x4..0xc].to_vec();
+        inserted.hide(0x4, &mut buf);
+        assert_eq!(&buf[2..6], &[1, 2, 3, 4]);
+
+        inserted.remove_all(&mem).unwrap();
+        assert_eq!(&mem.0.borrow()[0x6..0xa], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn restore_leaves_breakpoints_inserted() {
+        let original: Vec<u8> = (0..16).collect();
+        let mem = Buffer(RefCell::new(original.clone()));
+        let mut inserted = Inserted::default();
+        inserted.insert(&mem, 0x4).unwrap();
+
+        let child = Buffer(RefCell::new(mem.0.borrow().clone()));
+        inserted.restore(&child).unwrap();
+        assert_eq!(*child.0.borrow(), original);
+        assert!(inserted.contains(0x4));
+        assert_eq!(&mem.0.borrow()[0x4..][..TRAP.len()], TRAP);
+    }
+}
@@ -0,0 +1,772 @@
+//! Post-mortem debugging of ELF core files and Windows minidumps.
+//!
+//! A core is a target that stopped for good: it's threads, registers and memory can be
+//! inspected, but it can't be resumed. Cores of x86_64 and aarch64 processes are understood,
+//! though stacks are only unwound on x86_64.
+
+use crate::maps::Permissions;
+use crate::unwind::{self, Regs};
+use crate::{
+    CallFrameInfo, Error, FollowFork, Frame, Handle, Library, MemoryRegion, Registers, Signals,
+    Stop, Syscall, Target, Thread, X86_FLAGS,
+};
+use memmap2::Mmap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const ET_CORE: u16 = 4;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const PF_X: u32 = 1;
+const PF_W: u32 = 2;
+const PF_R: u32 = 4;
+
+const NT_PRSTATUS: u32 = 1;
+const NT_AUXV: u32 = 6;
+const NT_FILE: u32 = 0x46494c45;
+const AT_ENTRY: u64 = 9;
+
+/// Offset of `pr_cursig`, `pr_pid` and `pr_reg` in a 64-bit `elf_prstatus`.
+const PR_CURSIG: usize = 12;
+const PR_PID: usize = 32;
+const PR_REG: usize = 112;
+
+/// Order of the registers in x86_64's `user_regs_struct`.
+const X86_64_REGS: [&str; 27] = [
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx",
+    "rsi", "rdi", "orig_rax", "rip", "cs", "rflags", "rsp", "ss", "fs_base", "gs_base", "ds",
+    "es", "fs", "gs",
+];
+
+/// Order the x86_64 registers are shown in, the same as for live processes.
+const X86_64_GENERAL: [&str; 18] = [
+    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12",
+    "r13", "r14", "r15", "rip", "rflags",
+];
+const X86_64_SEGMENTS: [&str; 8] = ["cs", "ss", "ds", "es", "fs", "gs", "fs_base", "gs_base"];
+
+/// Order of the registers in aarch64's `user_pt_regs`.
+const AARCH64_REGS: [&str; 34] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+    "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26",
+    "x27", "x28", "x29", "x30", "sp", "pc", "pstate",
+];
+
+const MD_THREAD_LIST: u32 = 3;
+const MD_MODULE_LIST: u32 = 4;
+const MD_MEMORY_LIST: u32 = 5;
+const MD_EXCEPTION: u32 = 6;
+const MD_SYSTEM_INFO: u32 = 7;
+const MD_MEMORY64_LIST: u32 = 9;
+const PROCESSOR_ARCHITECTURE_AMD64: u16 = 9;
+
+/// Offsets of the registers in an AMD64 `CONTEXT`.
+const AMD64_CONTEXT: [(&str, usize, usize); 24] = [
+    ("rax", 0x78, 8),
+    ("rbx", 0x90, 8),
+    ("rcx", 0x80, 8),
+    ("rdx", 0x88, 8),
+    ("rsi", 0xa8, 8),
+    ("rdi", 0xb0, 8),
+    ("rbp", 0xa0, 8),
+    ("rsp", 0x98, 8),
+    ("r8", 0xb8, 8),
+    ("r9", 0xc0, 8),
+    ("r10", 0xc8, 8),
+    ("r11", 0xd0, 8),
+    ("r12", 0xd8, 8),
+    ("r13", 0xe0, 8),
+    ("r14", 0xe8, 8),
+    ("r15", 0xf0, 8),
+    ("rip", 0xf8, 8),
+    ("rflags", 0x44, 4),
+    ("cs", 0x38, 2),
+    ("ss", 0x42, 2),
+    ("ds", 0x3a, 2),
+    ("es", 0x3c, 2),
+    ("fs", 0x3e, 2),
+    ("gs", 0x40, 2),
+];
+
+/// Exception codes followed by the signal linux raises for them.
+const SIGNALS: [(u32, i32); 11] = [
+    (0xc0000005, 11), // EXCEPTION_ACCESS_VIOLATION
+    (0xc0000006, 7),  // EXCEPTION_IN_PAGE_ERROR
+    (0xc00000fd, 11), // EXCEPTION_STACK_OVERFLOW
+    (0xc000001d, 4),  // EXCEPTION_ILLEGAL_INSTRUCTION
+    (0xc0000096, 4),  // EXCEPTION_PRIV_INSTRUCTION
+    (0xc0000094, 8),  // EXCEPTION_INT_DIVIDE_BY_ZERO
+    (0xc0000095, 8),  // EXCEPTION_INT_OVERFLOW
+    (0xc000008e, 8),  // EXCEPTION_FLT_DIVIDE_BY_ZERO
+    (0xc0000090, 8),  // EXCEPTION_FLT_INVALID_OPERATION
+    (0x80000003, 5),  // EXCEPTION_BREAKPOINT
+    (0xc0000409, 6),  // STATUS_STACK_BUFFER_OVERRUN, raised by fail-fast
+];
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+/// Little-endian integer of `size` bytes.
+fn uint_at(bytes: &[u8], offset: usize, size: usize) -> Option<u64> {
+    let bytes = bytes.get(offset..offset + size)?;
+    Some(bytes.iter().rev().fold(0, |value, &byte| (value << 8) | byte as u64))
+}
+
+/// A thread as it was when the core was written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CoreThread {
+    tid: u32,
+    /// Signal the thread received, zero if it only stopped as another thread did.
+    signal: i32,
+    /// Register values at runtime, in the order they're shown.
+    values: Vec<(&'static str, u64)>,
+}
+
+impl CoreThread {
+    fn value(&self, name: &str) -> Option<u64> {
+        self.values.iter().find(|(reg, _)| *reg == name).map(|&(_, value)| value)
+    }
+
+    fn pc(&self) -> u64 {
+        self.value("rip").or_else(|| self.value("pc")).unwrap_or(0)
+    }
+
+    fn registers(&self) -> Registers {
+        let (segments, general) =
+            self.values.iter().copied().partition(|(name, _)| X86_64_SEGMENTS.contains(name));
+
+        let flags = match self.value("rflags") {
+            Some(value) => X86_FLAGS
+                .iter()
+                .map(|&(name, bit)| (name, value & (1 << bit) != 0))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Registers {
+            general,
+            flags,
+            segments,
+        }
+    }
+}
+
+/// Memory of the process, backed by the core file.
+struct Memory {
+    mmap: Mmap,
+    /// Regions with the offset of their bytes in the file and how many of them it stores, as
+    /// cores leave out memory that can be read from the mapped files.
+    regions: Vec<(MemoryRegion, usize, usize)>,
+}
+
+impl Memory {
+    /// Read the memory at runtime address `addr`, stopping early at the first byte that isn't
+    /// stored in the core.
+    fn read(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(len);
+
+        while bytes.len() < len {
+            let addr = addr + bytes.len();
+            let stored = self.regions.iter().find_map(|(region, offset, stored)| {
+                let skip = addr.checked_sub(region.start)?;
+                match skip < *stored {
+                    true => Some((offset + skip, stored - skip)),
+                    false => None,
+                }
+            });
+
+            match stored {
+                Some((offset, stored)) => {
+                    let chunk = (len - bytes.len()).min(stored);
+                    match self.mmap.get(offset..offset + chunk) {
+                        Some(chunk) => bytes.extend_from_slice(chunk),
+                        None => break,
+                    }
+                }
+                None => break,
+            }
+        }
+
+        match bytes.is_empty() && len != 0 {
+            true => Err(Error::NotInCore(addr)),
+            false => Ok(bytes),
+        }
+    }
+}
+
+/// Everything found in a core file.
+#[derive(Default)]
+struct Parsed {
+    regions: Vec<(MemoryRegion, usize, usize)>,
+    threads: Vec<CoreThread>,
+    /// Whether stacks can be unwound, which is only the case on x86_64.
+    x86_64: bool,
+    /// Entrypoint of the executable at runtime.
+    entrypoint: Option<usize>,
+    /// Path of the executable the process was started from.
+    executable: Option<PathBuf>,
+}
+
+/// Runtime address and path of the files mapped into the process, from a `NT_FILE` note.
+fn parse_file_note(desc: &[u8]) -> Vec<(usize, usize, u64, String)> {
+    let count = u64_at(desc, 0).unwrap_or(0) as usize;
+    let page_size = u64_at(desc, 8).unwrap_or(0);
+    let names = 16 + count.saturating_mul(24);
+
+    let mut paths = desc.get(names..).unwrap_or_default().split(|&b| b == 0);
+    (0..count)
+        .map_while(|idx| {
+            let entry = 16 + idx * 24;
+            let start = u64_at(desc, entry)? as usize;
+            let end = u64_at(desc, entry + 8)? as usize;
+            let offset = u64_at(desc, entry + 16)? * page_size;
+            let path = String::from_utf8_lossy(paths.next()?).into_owned();
+            Some((start, end, offset, path))
+        })
+        .collect()
+}
+
+/// Thread described by a `NT_PRSTATUS` note.
+fn parse_prstatus(desc: &[u8], machine: u16) -> Option<CoreThread> {
+    let (layout, shown): (&[&'static str], &[&'static str]) = match machine {
+        EM_X86_64 => (&X86_64_REGS, &X86_64_GENERAL),
+        EM_AARCH64 => (&AARCH64_REGS, &AARCH64_REGS),
+        _ => return None,
+    };
+
+    let value = |name: &str| {
+        let idx = layout.iter().position(|reg| *reg == name)?;
+        u64_at(desc, PR_REG + idx * 8)
+    };
+
+    let segments: &[&'static str] = match machine {
+        EM_X86_64 => &X86_64_SEGMENTS,
+        _ => &[],
+    };
+
+    let values = shown
+        .iter()
+        .chain(segments)
+        .map(|&name| Some((name, value(name)?)))
+        .collect::<Option<_>>()?;
+
+    Some(CoreThread {
+        tid: u32_at(desc, PR_PID)?,
+        signal: u16_at(desc, PR_CURSIG)? as i32,
+        values,
+    })
+}
+
+fn parse_elf(bytes: &[u8]) -> Result<Parsed, Error> {
+    let invalid = || Error::InvalidCore;
+
+    // Only 64-bit little-endian cores are understood.
+    if bytes.get(4) != Some(&2) || bytes.get(5) != Some(&1) {
+        return Err(Error::Unsupported);
+    }
+
+    if u16_at(bytes, 16) != Some(ET_CORE) {
+        return Err(invalid());
+    }
+
+    let machine = u16_at(bytes, 18).ok_or_else(invalid)?;
+    let phoff = u64_at(bytes, 32).ok_or_else(invalid)? as usize;
+    let phentsize = u16_at(bytes, 54).ok_or_else(invalid)? as usize;
+    let phnum = u16_at(bytes, 56).ok_or_else(invalid)? as usize;
+
+    let mut parsed = Parsed {
+        x86_64: machine == EM_X86_64,
+        ..Parsed::default()
+    };
+    let mut files = Vec::new();
+
+    for idx in 0..phnum {
+        let phdr = bytes.get(phoff + idx * phentsize..).ok_or_else(invalid)?;
+        let kind = u32_at(phdr, 0).ok_or_else(invalid)?;
+        let flags = u32_at(phdr, 4).ok_or_else(invalid)?;
+        let offset = u64_at(phdr, 8).ok_or_else(invalid)? as usize;
+        let vaddr = u64_at(phdr, 16).ok_or_else(invalid)? as usize;
+        let filesz = u64_at(phdr, 32).ok_or_else(invalid)? as usize;
+        let memsz = u64_at(phdr, 40).ok_or_else(invalid)? as usize;
+
+        match kind {
+            PT_LOAD => {
+                let region = MemoryRegion {
+                    start: vaddr,
+                    end: vaddr + memsz,
+                    perms: Permissions {
+                        read: flags & PF_R != 0,
+                        write: flags & PF_W != 0,
+                        exec: flags & PF_X != 0,
+                        shared: false,
+                    },
+                    offset: 0,
+                    path: None,
+                };
+
+                parsed.regions.push((region, offset, filesz.min(memsz)));
+            }
+            PT_NOTE => {
+                let notes = bytes.get(offset..offset + filesz).ok_or_else(invalid)?;
+                let mut pos = 0;
+
+                while let (Some(namesz), Some(descsz), Some(kind)) =
+                    (u32_at(notes, pos), u32_at(notes, pos + 4), u32_at(notes, pos + 8))
+                {
+                    let desc = pos + 12 + (namesz as usize).next_multiple_of(4);
+                    let next = desc + (descsz as usize).next_multiple_of(4);
+                    let desc = notes.get(desc..desc + descsz as usize).ok_or_else(invalid)?;
+                    pos = next;
+
+                    match kind {
+                        NT_PRSTATUS => parsed.threads.extend(parse_prstatus(desc, machine)),
+                        NT_AUXV => {
+                            parsed.entrypoint = desc
+                                .chunks_exact(16)
+                                .find(|entry| u64_at(entry, 0) == Some(AT_ENTRY))
+                                .and_then(|entry| u64_at(entry, 8))
+                                .map(|entry| entry as usize);
+                        }
+                        NT_FILE => files = parse_file_note(desc),
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if parsed.threads.is_empty() {
+        return Err(Error::Unsupported);
+    }
+
+    // The executable is mapped first, followed by the libraries it loaded.
+    parsed.executable = files.first().map(|(_, _, _, path)| PathBuf::from(path));
+    for (region, _, _) in parsed.regions.iter_mut() {
+        if let Some((start, _, offset, path)) =
+            files.iter().find(|(start, end, _, _)| (*start..*end).contains(&region.start))
+        {
+            region.offset = offset + (region.start - start) as u64;
+            region.path = Some(path.clone());
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Directory of streams in a minidump, by their type.
+fn streams(bytes: &[u8]) -> Option<Vec<(u32, &[u8])>> {
+    let count = u32_at(bytes, 8)? as usize;
+    let dir = u32_at(bytes, 12)? as usize;
+
+    (0..count)
+        .map(|idx| {
+            let entry = dir + idx * 12;
+            let kind = u32_at(bytes, entry)?;
+            let len = u32_at(bytes, entry + 4)? as usize;
+            let rva = u32_at(bytes, entry + 8)? as usize;
+            Some((kind, bytes.get(rva..rva + len)?))
+        })
+        .collect()
+}
+
+/// `MINIDUMP_STRING` at `rva`, a length in bytes followed by UTF-16.
+fn minidump_string(bytes: &[u8], rva: usize) -> Option<String> {
+    let len = u32_at(bytes, rva)? as usize;
+    let units: Vec<u16> = bytes
+        .get(rva + 4..rva + 4 + len)?
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+
+    Some(String::from_utf16_lossy(&units))
+}
+
+fn parse_minidump(bytes: &[u8]) -> Result<Parsed, Error> {
+    let invalid = || Error::InvalidCore;
+    let streams = streams(bytes).ok_or_else(invalid)?;
+    let stream = |kind: u32| streams.iter().find(|(k, _)| *k == kind).map(|&(_, data)| data);
+
+    let arch = stream(MD_SYSTEM_INFO).and_then(|info| u16_at(info, 0));
+    if arch != Some(PROCESSOR_ARCHITECTURE_AMD64) {
+        return Err(Error::Unsupported);
+    }
+
+    let mut parsed = Parsed {
+        x86_64: true,
+        ..Parsed::default()
+    };
+
+    // Thread that raised the exception and the signal linux would've sent for it.
+    let exception = stream(MD_EXCEPTION).and_then(|exception| {
+        let tid = u32_at(exception, 0)?;
+        let code = u32_at(exception, 8)?;
+        let signal = SIGNALS.iter().find(|(c, _)| *c == code).map_or(11, |&(_, sig)| sig);
+        Some((tid, signal))
+    });
+
+    let threads = stream(MD_THREAD_LIST).ok_or_else(invalid)?;
+    let count = u32_at(threads, 0).ok_or_else(invalid)? as usize;
+    for idx in 0..count {
+        let entry = 4 + idx * 48;
+        let tid = u32_at(threads, entry).ok_or_else(invalid)?;
+        let len = u32_at(threads, entry + 40).ok_or_else(invalid)? as usize;
+        let rva = u32_at(threads, entry + 44).ok_or_else(invalid)? as usize;
+        let context = bytes.get(rva..rva + len).ok_or_else(invalid)?;
+
+        let values = AMD64_CONTEXT
+            .iter()
+            .map(|&(name, offset, size)| Some((name, uint_at(context, offset, size)?)))
+            .collect::<Option<_>>()
+            .ok_or_else(invalid)?;
+
+        let signal = match exception {
+            Some((crashed, signal)) if crashed == tid => signal,
+            _ => 0,
+        };
+
+        parsed.threads.push(CoreThread { tid, signal, values });
+    }
+
+    // Threads are listed in no particular order, the one that crashed is inspected first.
+    parsed.threads.sort_by_key(|thread| thread.signal == 0);
+
+    if let Some(list) = stream(MD_MEMORY_LIST) {
+        let count = u32_at(list, 0).ok_or_else(invalid)? as usize;
+        for idx in 0..count {
+            let entry = 4 + idx * 16;
+            let start = u64_at(list, entry).ok_or_else(invalid)? as usize;
+            let len = u32_at(list, entry + 8).ok_or_else(invalid)? as usize;
+            let rva = u32_at(list, entry + 12).ok_or_else(invalid)? as usize;
+            parsed.regions.push((dumped(start, len), rva, len));
+        }
+    }
+
+    // Full dumps store all memory one region after another.
+    if let Some(list) = stream(MD_MEMORY64_LIST) {
+        let count = u64_at(list, 0).ok_or_else(invalid)? as usize;
+        let mut rva = u64_at(list, 8).ok_or_else(invalid)? as usize;
+        for idx in 0..count {
+            let entry = 16 + idx * 16;
+            let start = u64_at(list, entry).ok_or_else(invalid)? as usize;
+            let len = u64_at(list, entry + 8).ok_or_else(invalid)? as usize;
+            parsed.regions.push((dumped(start, len), rva, len));
+            rva += len;
+        }
+    }
+
+    let mut modules = Vec::new();
+    if let Some(list) = stream(MD_MODULE_LIST) {
+        let count = u32_at(list, 0).ok_or_else(invalid)? as usize;
+        for idx in 0..count {
+            let entry = 4 + idx * 108;
+            let base = u64_at(list, entry).ok_or_else(invalid)? as usize;
+            let size = u32_at(list, entry + 8).ok_or_else(invalid)? as usize;
+            let name = u32_at(list, entry + 20).ok_or_else(invalid)? as usize;
+            let name = minidump_string(bytes, name).ok_or_else(invalid)?;
+            modules.push((base, base + size, name));
+        }
+    }
+
+    // The executable is listed first, followed by the libraries it loaded.
+    parsed.executable = modules.first().map(|(_, _, path)| PathBuf::from(path));
+    parsed.regions.sort_by_key(|(region, _, _)| region.start);
+    for (region, _, _) in parsed.regions.iter_mut() {
+        if let Some((start, _, path)) =
+            modules.iter().find(|(start, end, _)| (*start..*end).contains(&region.start))
+        {
+            region.offset = (region.start - start) as u64;
+            region.path = Some(path.clone());
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Region of a minidump, which doesn't store how it was protected.
+fn dumped(start: usize, len: usize) -> MemoryRegion {
+    MemoryRegion {
+        start,
+        end: start + len,
+        perms: Permissions {
+            read: true,
+            ..Permissions::default()
+        },
+        offset: 0,
+        path: None,
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<Parsed, Error> {
+    match bytes.get(..4) {
+        Some(b"\x7fELF") => parse_elf(bytes),
+        Some(b"MDMP") => parse_minidump(bytes),
+        _ => Err(Error::InvalidCore),
+    }
+}
+
+/// Executable the process of the core at `path` was started from, as recorded in the core.
+pub fn executable(path: &Path) -> Result<PathBuf, Error> {
+    let file = std::fs::File::open(path)?;
+    let mmap = unsafe { Mmap::map(&file)? };
+    parse(&mmap)?.executable.ok_or(Error::InvalidCore)
+}
+
+/// A process as it was when it dumped core.
+pub struct CoreTarget {
+    memory: Arc<Memory>,
+    threads: Vec<CoreThread>,
+    /// Index into `threads` of the thread whose registers and stack are inspected.
+    current: usize,
+    x86_64: bool,
+    /// Offset between addresses in the binary and where it was loaded.
+    bias: usize,
+    /// Whether the stop that caused the core was reported.
+    reported: bool,
+}
+
+impl CoreTarget {
+    /// Open the ELF core or minidump at `path`, whose executable is the loaded binary with
+    /// `entrypoint`.
+    pub fn open(path: &Path, entrypoint: usize) -> Result<Self, Error> {
+        let file = std::fs::File::open(path)?;
+        let mmap = unsafe { Mmap::map(&file)? };
+        let parsed = parse(&mmap)?;
+
+        let bias = match parsed.entrypoint {
+            Some(runtime) if entrypoint != 0 => runtime.wrapping_sub(entrypoint),
+            _ => 0,
+        };
+
+        Ok(Self {
+            memory: Arc::new(Memory {
+                mmap,
+                regions: parsed.regions,
+            }),
+            threads: parsed.threads,
+            current: 0,
+            x86_64: parsed.x86_64,
+            bias,
+            reported: false,
+        })
+    }
+
+    /// Offset between addresses in the binary and where it was loaded.
+    pub fn bias(&self) -> usize {
+        self.bias
+    }
+
+    fn thread(&self) -> &CoreThread {
+        &self.threads[self.current]
+    }
+}
+
+impl Target for CoreTarget {
+    fn pc(&self) -> Result<usize, Error> {
+        Ok((self.thread().pc() as usize).wrapping_sub(self.bias))
+    }
+
+    fn handle(&self) -> Handle {
+        Handle::Core(CoreHandle {
+            memory: Arc::clone(&self.memory),
+        })
+    }
+
+    fn trace_syscalls(&mut self, _report: Box<dyn FnMut(Syscall) + Send>) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn watch_libraries(
+        &mut self,
+        _report: Box<dyn FnMut(Vec<Library>) + Send>,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn registers(&self) -> Result<Registers, Error> {
+        Ok(self.thread().registers())
+    }
+
+    /// Cores are only inspected, never changed.
+    fn write_registers(&mut self, _registers: &[(&str, u64)]) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        self.memory.read(addr, len)
+    }
+
+    fn write_memory(&mut self, _addr: usize, _bytes: &[u8]) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        let thread = self.thread();
+        let pc = thread.pc();
+
+        // Unwinding is only supported on x86_64.
+        let regs = match (self.x86_64, thread.value("rsp"), thread.value("rbp")) {
+            (true, Some(sp), Some(fp)) => Regs { pc, sp, fp },
+            _ => {
+                return Ok(vec![Frame {
+                    pc: (pc as usize).wrapping_sub(self.bias),
+                    sp: thread.value("sp").unwrap_or(0) as usize,
+                }])
+            }
+        };
+
+        let read = |addr: u64| {
+            let bytes = self.memory.read(addr as usize, 8).ok()?;
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        };
+
+        Ok(unwind::unwind(cfi, regs, self.bias, read))
+    }
+
+    fn threads(&self) -> Result<Vec<Thread>, Error> {
+        let mut threads: Vec<Thread> = self
+            .threads
+            .iter()
+            .enumerate()
+            .map(|(idx, thread)| {
+                let pc = (thread.pc() as usize).wrapping_sub(self.bias);
+                Thread {
+                    tid: thread.tid,
+                    name: String::new(),
+                    pc,
+                    stop: (thread.signal != 0).then_some(Stop::Signal(thread.signal, pc)),
+                    current: idx == self.current,
+                }
+            })
+            .collect();
+
+        threads.sort_by_key(|thread| thread.tid);
+        Ok(threads)
+    }
+
+    fn select_thread(&mut self, tid: u32) -> Result<(), Error> {
+        match self.threads.iter().position(|thread| thread.tid == tid) {
+            Some(idx) => {
+                self.current = idx;
+                Ok(())
+            }
+            None => Err(Error::NoSuchThread),
+        }
+    }
+
+    fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        match follow {
+            FollowFork::Parent => Ok(()),
+            FollowFork::Child | FollowFork::Ask => Err(Error::Unsupported),
+        }
+    }
+
+    fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        match signals.is_default() {
+            true => Ok(()),
+            false => Err(Error::Unsupported),
+        }
+    }
+
+    /// Reports why the core was written, a core can't be resumed afterwards.
+    fn cont(&mut self) -> Result<Stop, Error> {
+        if std::mem::replace(&mut self.reported, true) {
+            return Err(Error::Unsupported);
+        }
+
+        let thread = self.thread();
+        let pc = (thread.pc() as usize).wrapping_sub(self.bias);
+        match thread.signal {
+            0 => Ok(Stop::Attached(pc)),
+            signal => Ok(Stop::Signal(signal, pc)),
+        }
+    }
+
+    fn step(&mut self) -> Result<Stop, Error> {
+        Err(Error::Unsupported)
+    }
+}
+
+/// Reads the memory of a [`CoreTarget`] from other threads.
+#[derive(Clone)]
+pub struct CoreHandle {
+    memory: Arc<Memory>,
+}
+
+impl CoreHandle {
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        Ok(self.memory.regions.iter().map(|(region, _, _)| region.clone()).collect())
+    }
+
+    /// Read the memory at runtime address `addr`, stopping early at the first byte that isn't
+    /// stored in the core.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        self.memory.read(addr, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_note() {
+        let mut desc = Vec::new();
+        for value in [2u64, 0x1000, 0x400000, 0x401000, 0, 0x7f0000, 0x7f2000, 3] {
+            desc.extend_from_slice(&value.to_le_bytes());
+        }
+        desc.extend_from_slice(b"/bin/true\0/lib/libc.so.6\0");
+
+        assert_eq!(
+            parse_file_note(&desc),
+            [
+                (0x400000, 0x401000, 0, "/bin/true".to_string()),
+                (0x7f0000, 0x7f2000, 0x3000, "/lib/libc.so.6".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn prstatus() {
+        let mut desc = vec![0; PR_REG + X86_64_REGS.len() * 8];
+        desc[PR_CURSIG..PR_CURSIG + 2].copy_from_slice(&11u16.to_le_bytes());
+        desc[PR_PID..PR_PID + 4].copy_from_slice(&1234u32.to_le_bytes());
+        for (idx, value) in [(16, 0x401000u64), (18, 0x246), (19, 0x7ffe0000)] {
+            let offset = PR_REG + idx * 8;
+            desc[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let thread = parse_prstatus(&desc, EM_X86_64).unwrap();
+        assert_eq!((thread.tid, thread.signal, thread.pc()), (1234, 11, 0x401000));
+
+        let registers = thread.registers();
+        assert_eq!(registers.get("rsp"), Some(0x7ffe0000));
+        assert_eq!(registers.general.len(), X86_64_GENERAL.len());
+        assert_eq!(registers.segments.len(), X86_64_SEGMENTS.len());
+        assert_eq!(registers.flag("ZF"), Some(true));
+        assert_eq!(registers.flag("CF"), Some(false));
+        assert!(parse_prstatus(&desc, 3).is_none());
+    }
+
+    #[test]
+    fn minidump_names() {
+        let mut bytes = vec![0; 4];
+        bytes[0..4].copy_from_slice(&8u32.to_le_bytes());
+        bytes.extend("a.exe".encode_utf16().take(4).flat_map(u16::to_le_bytes));
+        assert_eq!(minidump_string(&bytes, 0).as_deref(), Some("a.ex"));
+        assert_eq!(uint_at(&[0x34, 0x12, 0xff], 0, 2), Some(0x1234));
+        assert!(matches!(parse(b"\0\0\0\0"), Err(Error::InvalidCore)));
+    }
+}
@@ -0,0 +1,418 @@
+//! Server for the GDB remote serial protocol, so gdb, lldb or IDA can debug a tracee through us.
+//!
+//! Only all-stop mode of a single threaded x86_64 tracee is supported, with addresses being
+//! runtime addresses as the client sees the process. The client can't restart the process and
+//! processes we spawned are killed once the client detaches or disconnects.
+
+use crate::rsp::{self, Framing, Incoming, REGISTERS};
+use crate::rsp::{gdb_signal, hex, parse_usize, register_value, unhex};
+use crate::{BreakpointSet, Debugger, Error, Handle, Stop, WatchKind, Watchpoint};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::{mpsc, Arc};
+
+/// Largest packet we accept, as told to the client in hex.
+const PACKET_SIZE: &str = "4000";
+
+/// Address to listen on given something like `:1234` or `0.0.0.0:1234`, gdbserver-style.
+/// Without a host only connections from this machine are accepted.
+pub fn listen_addr(addr: &str) -> String {
+    match addr.strip_prefix(':') {
+        Some(port) => format!("127.0.0.1:{port}"),
+        None => addr.to_string(),
+    }
+}
+
+/// Accept a single client on `listener` and let it control `debugger` until it disconnects.
+///
+/// `breakpoints` must be the set `debugger` was created with.
+pub fn serve(
+    debugger: Debugger,
+    breakpoints: Arc<BreakpointSet>,
+    listener: TcpListener,
+) -> Result<(), Error> {
+    let (stream, _) = listener.accept()?;
+    stream.set_nodelay(true)?;
+
+    let (tx, rx) = mpsc::channel();
+    let reader = stream.try_clone()?;
+    let handle = debugger.handle();
+    std::thread::spawn(move || read_packets(reader, handle, tx));
+
+    let mut server = Server {
+        debugger,
+        breakpoints,
+        stream,
+        stop: None,
+        ack: true,
+        closing: false,
+    };
+
+    let result = server.run(rx);
+    let _ = server.stream.shutdown(Shutdown::Both);
+    result
+}
+
+/// Forward packets from the client, stopping the tracee when asked to.
+fn read_packets(mut stream: TcpStream, handle: Handle, tx: mpsc::Sender<Incoming>) {
+    let mut framing = Framing::default();
+    let mut buf = [0; 4096];
+
+    loop {
+        let len = match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(len) => len,
+        };
+
+        for &byte in &buf[..len] {
+            match framing.feed(byte) {
+                // The server is blocked on the running tracee.
+                Some(Incoming::Interrupt) => handle.pause(),
+                Some(incoming) => {
+                    let Ok(()) = tx.send(incoming) else {
+                        return;
+                    };
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Requests from the client we understand.
+#[derive(Debug, PartialEq, Eq)]
+enum Packet<'a> {
+    StopReason,
+    ReadRegisters,
+    WriteRegisters(Vec<u8>),
+    ReadRegister(usize),
+    WriteRegister(usize, Vec<u8>),
+    ReadMemory(usize, usize),
+    WriteMemory(usize, Vec<u8>),
+    /// Breakpoint type, runtime address and length or kind.
+    InsertBreakpoint(u8, usize, usize),
+    RemoveBreakpoint(u8, usize, usize),
+    Continue,
+    Step,
+    Kill,
+    Detach,
+    /// Packets such as `qSupported` and `Hg0`, which are answered without the tracee.
+    Query(&'a str),
+}
+
+impl<'a> Packet<'a> {
+    /// Returns None for malformed packets.
+    fn parse(packet: &'a str) -> Option<Self> {
+        let args = packet.get(1..).unwrap_or("");
+
+        // vCont packets hold an action per thread, we only have a single thread.
+        if let Some(action) = packet.strip_prefix("vCont;") {
+            return match action.as_bytes().first() {
+                Some(b'c' | b'C') => Some(Self::Continue),
+                Some(b's' | b'S') => Some(Self::Step),
+                _ => None,
+            };
+        }
+
+        if packet.starts_with("vKill") {
+            return Some(Self::Kill);
+        }
+
+        let packet = match packet.as_bytes().first() {
+            Some(b'?') => Self::StopReason,
+            Some(b'g') => Self::ReadRegisters,
+            Some(b'G') => Self::WriteRegisters(unhex(args)?),
+            Some(b'p') => Self::ReadRegister(parse_usize(args)?),
+            Some(b'P') => {
+                let (reg, value) = args.split_once('=')?;
+                Self::WriteRegister(parse_usize(reg)?, unhex(value)?)
+            }
+            Some(b'm') => {
+                let (addr, len) = args.split_once(',')?;
+                Self::ReadMemory(parse_usize(addr)?, parse_usize(len)?)
+            }
+            Some(b'M') => {
+                let (addr, args) = args.split_once(',')?;
+                let (len, data) = args.split_once(':')?;
+                let data = unhex(data)?;
+                if data.len() != parse_usize(len)? {
+                    return None;
+                }
+                Self::WriteMemory(parse_usize(addr)?, data)
+            }
+            Some(&cmd @ (b'Z' | b'z')) => {
+                let mut fields = args.splitn(3, ',');
+                let kind = fields.next()?.parse().ok()?;
+                let addr = parse_usize(fields.next()?)?;
+                // Conditions may follow the length.
+                let len = parse_usize(fields.next()?.split(';').next()?)?;
+                match cmd {
+                    b'Z' => Self::InsertBreakpoint(kind, addr, len),
+                    _ => Self::RemoveBreakpoint(kind, addr, len),
+                }
+            }
+            Some(b'c' | b'C') => Self::Continue,
+            Some(b's' | b'S') => Self::Step,
+            Some(b'k') => Self::Kill,
+            Some(b'D') => Self::Detach,
+            _ => Self::Query(packet),
+        };
+
+        Some(packet)
+    }
+}
+
+struct Server {
+    debugger: Debugger,
+    breakpoints: Arc<BreakpointSet>,
+    stream: TcpStream,
+    /// Why the tracee last stopped, None if it hasn't run yet.
+    stop: Option<Stop>,
+    /// Whether packets are acknowledged, which the client can turn off.
+    ack: bool,
+    /// Set once the client is done with the tracee.
+    closing: bool,
+}
+
+impl Server {
+    fn run(&mut self, packets: mpsc::Receiver<Incoming>) -> Result<(), Error> {
+        for incoming in packets {
+            let packet = match incoming {
+                Incoming::Packet(packet) => packet,
+                Incoming::Corrupt => {
+                    self.stream.write_all(b"-")?;
+                    continue;
+                }
+                Incoming::Interrupt => continue,
+            };
+
+            if self.ack {
+                self.stream.write_all(b"+")?;
+            }
+
+            let reply = match Packet::parse(&packet) {
+                Some(packet) => self.reply(packet),
+                None => Some("E01".to_string()),
+            };
+
+            if let Some(reply) = reply {
+                self.stream.write_all(&rsp::frame(&reply))?;
+            }
+
+            if self.closing {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reply to `packet`, None if it doesn't take one.
+    fn reply(&mut self, packet: Packet) -> Option<String> {
+        let ok_or_err = |result: Result<(), Error>| match result {
+            Ok(()) => "OK".to_string(),
+            Err(_) => "E01".to_string(),
+        };
+
+        let reply = match packet {
+            Packet::StopReason => match self.stop {
+                Some(stop) => self.stop_reply(stop),
+                None => "S05".to_string(),
+            },
+            Packet::ReadRegisters => match self.debugger.registers() {
+                Ok(regs) => REGISTERS
+                    .iter()
+                    .map(|&(name, width)| {
+                        let value = regs.get(name).unwrap_or(0);
+                        hex(&value.to_le_bytes()[..width])
+                    })
+                    .collect(),
+                Err(_) => "E01".to_string(),
+            },
+            Packet::WriteRegisters(bytes) => {
+                let mut offset = 0;
+                let mut result = Ok(());
+                for &(name, width) in &REGISTERS {
+                    let value = match bytes.get(offset..offset + width).and_then(register_value) {
+                        Some(value) => value,
+                        None => break,
+                    };
+
+                    offset += width;
+                    result = self.debugger.set_register(name, value);
+                    if result.is_err() {
+                        break;
+                    }
+                }
+                ok_or_err(result)
+            }
+            Packet::ReadRegister(idx) => {
+                let regs = self.debugger.registers();
+                match (REGISTERS.get(idx), regs) {
+                    (Some(&(name, width)), Ok(regs)) => {
+                        hex(&regs.get(name).unwrap_or(0).to_le_bytes()[..width])
+                    }
+                    _ => "E01".to_string(),
+                }
+            }
+            Packet::WriteRegister(idx, bytes) => {
+                match (REGISTERS.get(idx), register_value(&bytes)) {
+                    (Some(&(name, _)), Some(value)) => {
+                        ok_or_err(self.debugger.set_register(name, value))
+                    }
+                    _ => "E01".to_string(),
+                }
+            }
+            Packet::ReadMemory(addr, len) => match self.debugger.read_memory(addr, len) {
+                Ok(bytes) if !bytes.is_empty() || len == 0 => hex(&bytes),
+                // Memory isn't mapped.
+                _ => "E14".to_string(),
+            },
+            Packet::WriteMemory(addr, bytes) => match self.debugger.write_memory(addr, &bytes) {
+                Ok(()) => "OK".to_string(),
+                Err(_) => "E14".to_string(),
+            },
+            Packet::InsertBreakpoint(kind, addr, len) => {
+                let addr = addr.wrapping_sub(self.debugger.bias());
+                match kind {
+                    0 => {
+                        if !self.breakpoints.contains(addr) {
+                            self.breakpoints.toggle(addr);
+                        }
+                        "OK".to_string()
+                    }
+                    1 => ok_or_err(self.breakpoints.set_hardware(addr, true)),
+                    2 | 4 => {
+                        let kind = if kind == 2 {
+                            WatchKind::Write
+                        } else {
+                            WatchKind::ReadWrite
+                        };
+                        ok_or_err(self.breakpoints.set_watchpoint(Watchpoint { addr, len, kind }))
+                    }
+                    // Read watchpoints aren't supported by x86.
+                    _ => String::new(),
+                }
+            }
+            Packet::RemoveBreakpoint(kind, addr, _) => {
+                let addr = addr.wrapping_sub(self.debugger.bias());
+                match kind {
+                    0 | 1 => {
+                        if self.breakpoints.contains(addr) {
+                            self.breakpoints.toggle(addr);
+                        }
+                        "OK".to_string()
+                    }
+                    2 | 4 => {
+                        self.breakpoints.remove_watchpoint(addr);
+                        "OK".to_string()
+                    }
+                    _ => String::new(),
+                }
+            }
+            Packet::Continue => self.resume(Debugger::cont),
+            Packet::Step => self.resume(Debugger::step),
+            Packet::Kill => {
+                self.debugger.handle().kill();
+                self.closing = true;
+                return None;
+            }
+            Packet::Detach => {
+                self.closing = true;
+                "OK".to_string()
+            }
+            Packet::Query(query) => self.query(query),
+        };
+
+        Some(reply)
+    }
+
+    fn resume(&mut self, resume: fn(&mut Debugger) -> Result<Stop, Error>) -> String {
+        match resume(&mut self.debugger) {
+            Ok(stop) => {
+                self.stop = Some(stop);
+                self.stop_reply(stop)
+            }
+            Err(Error::Exited) => "W00".to_string(),
+            Err(_) => "E01".to_string(),
+        }
+    }
+
+    fn stop_reply(&self, stop: Stop) -> String {
+        match stop {
+            Stop::Exited(code) => format!("W{:02x}", code as u8),
+            Stop::Killed(sig) => format!("X{:02x}", gdb_signal(sig)),
+            Stop::Signal(sig, _) => format!("S{:02x}", gdb_signal(sig)),
+            Stop::Watchpoint(addr, _) => {
+                let watchpoints = self.breakpoints.watchpoints();
+                let kind = match watchpoints.iter().find(|wp| wp.addr == addr).map(|wp| wp.kind) {
+                    Some(WatchKind::ReadWrite) => "awatch",
+                    _ => "watch",
+                };
+
+                format!("T05{kind}:{:x};", addr.wrapping_add(self.debugger.bias()))
+            }
+            Stop::Attached(..)
+            | Stop::Breakpoint(..)
+            | Stop::Stepped(..)
+            | Stop::Forked(..)
+            | Stop::Exec(..) => "S05".to_string(),
+        }
+    }
+
+    fn query(&mut self, query: &str) -> String {
+        let pid = self.debugger.pid();
+        let bias = self.debugger.bias();
+
+        match query.split(':').next().unwrap_or(query) {
+            "qSupported" => format!("PacketSize={PACKET_SIZE};QStartNoAckMode+"),
+            "QStartNoAckMode" => {
+                self.ack = false;
+                "OK".to_string()
+            }
+            "vCont?" => "vCont;c;C;s;S".to_string(),
+            "qC" => format!("QC{pid:x}"),
+            "qfThreadInfo" => format!("m{pid:x}"),
+            "qsThreadInfo" => "l".to_string(),
+            "qOffsets" => format!("Text={bias:x};Data={bias:x};Bss={bias:x}"),
+            "qSymbol" => "OK".to_string(),
+            // Selecting or checking on our only thread.
+            _ if query.starts_with('H') || query.starts_with('T') => "OK".to_string(),
+            // Unsupported packets get an empty reply.
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(packet: &str) -> Packet<'_> {
+        Packet::parse(packet).unwrap()
+    }
+
+    #[test]
+    fn packets() {
+        assert_eq!(parse("m7ffe0010,8"), Packet::ReadMemory(0x7ffe0010, 8));
+        assert_eq!(parse("M1000,2:cc90"), Packet::WriteMemory(0x1000, vec![0xcc, 0x90]));
+        assert_eq!(parse("Z0,401000,1"), Packet::InsertBreakpoint(0, 0x401000, 1));
+        assert_eq!(parse("z2,8000,4;X1,0"), Packet::RemoveBreakpoint(2, 0x8000, 4));
+        assert_eq!(
+            parse("P10=0010400000000000"),
+            Packet::WriteRegister(16, vec![0, 0x10, 0x40, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(parse("vCont;s:1"), Packet::Step);
+        assert_eq!(parse("C0b"), Packet::Continue);
+        assert_eq!(parse("vKill;1f"), Packet::Kill);
+        assert_eq!(parse("qSupported:swbreak+"), Packet::Query("qSupported:swbreak+"));
+        assert_eq!(Packet::parse("M1000,3:cc90"), None);
+        assert_eq!(Packet::parse("pzz"), None);
+    }
+
+    #[test]
+    fn listen_addrs() {
+        assert_eq!(listen_addr(":1234"), "127.0.0.1:1234");
+        assert_eq!(listen_addr("0.0.0.0:1234"), "0.0.0.0:1234");
+    }
+}
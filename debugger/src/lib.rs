@@ -0,0 +1,557 @@
+//! Control over a debugged process.
+//!
+//! Linux and Windows on x86_64 and macOS are supported, elsewhere [`Debugger::spawn`] always
+//! fails. Other targets can be debugged through a stub speaking the GDB remote protocol, see
+//! [`RemoteTarget`]. Processes that dumped core can be inspected post-mortem on any platform,
+//! see [`CoreTarget`].
+
+pub mod breakpoint;
+pub mod coredump;
+pub mod gdbserver;
+pub mod lines;
+pub mod maps;
+pub mod remote;
+#[cfg_attr(not(all(target_os = "linux", target_arch = "x86_64")), allow(dead_code))]
+pub mod rendezvous;
+mod rsp;
+pub mod signals;
+pub mod stdio;
+#[cfg_attr(not(all(target_os = "linux", target_arch = "x86_64")), allow(dead_code))]
+pub mod syscalls;
+pub mod trace;
+#[cfg_attr(not(target_arch = "x86_64"), allow(dead_code))]
+pub mod unwind;
+pub mod variables;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(all(windows, target_arch = "x86_64"))]
+mod windows;
+#[cfg(not(any(
+    all(target_os = "linux", target_arch = "x86_64"),
+    all(windows, target_arch = "x86_64"),
+    target_os = "macos"
+)))]
+mod unsupported;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+pub use linux::{exe_path, processes, Debugger, ProcessHandle};
+#[cfg(target_os = "macos")]
+pub use macos::{exe_path, processes, Debugger, ProcessHandle};
+#[cfg(all(windows, target_arch = "x86_64"))]
+pub use windows::{exe_path, processes, Debugger, ProcessHandle};
+#[cfg(not(any(
+    all(target_os = "linux", target_arch = "x86_64"),
+    all(windows, target_arch = "x86_64"),
+    target_os = "macos"
+)))]
+pub use unsupported::{exe_path, processes, Debugger, ProcessHandle};
+
+pub use breakpoint::{BreakpointSet, Condition, WatchKind, Watchpoint};
+pub use coredump::{CoreHandle, CoreTarget};
+pub use lines::LineTable;
+pub use maps::MemoryRegion;
+pub use remote::{RemoteHandle, RemoteTarget};
+pub use rendezvous::Library;
+pub use signals::{Handling, Signals};
+pub use stdio::{Console, Stdio};
+pub use syscalls::Syscall;
+pub use trace::Trace;
+pub use unwind::{CallFrameInfo, Frame};
+pub use variables::Place;
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Status flags in x86's `rflags` by their bit.
+pub(crate) const X86_FLAGS: [(&str, u32); 9] = [
+    ("CF", 0),
+    ("PF", 2),
+    ("AF", 4),
+    ("ZF", 6),
+    ("SF", 7),
+    ("TF", 8),
+    ("IF", 9),
+    ("DF", 10),
+    ("OF", 11),
+];
+
+#[derive(Debug)]
+pub enum Error {
+    /// Tracing the process isn't supported on this platform.
+    Unsupported,
+    /// The process already exited.
+    Exited,
+    /// We stopped tracing the process.
+    Detached,
+    /// Path or arguments contain a nul byte.
+    InvalidArgs,
+    /// Working directory to spawn the process in isn't a directory.
+    InvalidCwd(PathBuf),
+    /// File to use as the spawned process' stdin can't be opened.
+    InvalidStdin(PathBuf),
+    /// Watchpoint has an invalid length or isn't aligned to it's length.
+    InvalidWatchpoint,
+    /// All debug registers are in use.
+    TooManyWatchpoints,
+    /// Register doesn't exist or can't be written to.
+    InvalidRegister,
+    /// Thread doesn't exist or already exited.
+    NoSuchThread,
+    /// Remote target sent a reply we didn't expect.
+    Protocol(String),
+    /// File isn't an ELF core or minidump, or is truncated.
+    InvalidCore,
+    /// Memory at the address wasn't written to the core.
+    NotInCore(usize),
+    /// Debug info describes a variable's location with an expression we can't evaluate.
+    InvalidExpression,
+    /// A mach call failed with the given `kern_return_t`.
+    #[cfg(target_os = "macos")]
+    Mach(i32),
+    IO(std::io::Error),
+    #[cfg(unix)]
+    Sys(nix::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported => f.write_str("Debugging isn't supported on this platform."),
+            Self::Exited => f.write_str("Process already exited."),
+            Self::Detached => f.write_str("Detached from process."),
+            Self::InvalidArgs => f.write_str("Arguments can't contain nul bytes."),
+            Self::InvalidCwd(path) => {
+                f.write_fmt(format_args!("Working directory {path:?} isn't a directory."))
+            }
+            Self::InvalidStdin(path) => {
+                f.write_fmt(format_args!("Can't open {path:?} as the process' input."))
+            }
+            Self::InvalidWatchpoint => {
+                f.write_str("Watchpoints must be 1, 2, 4 or 8 bytes long and aligned.")
+            }
+            Self::TooManyWatchpoints => f.write_fmt(format_args!(
+                "At most {} watchpoints and hardware breakpoints can be set.",
+                breakpoint::MAX_WATCHPOINTS
+            )),
+            Self::InvalidRegister => f.write_str("Register doesn't exist or can't be written to."),
+            Self::NoSuchThread => f.write_str("Thread doesn't exist."),
+            Self::Protocol(reply) => {
+                f.write_fmt(format_args!("Remote target replied with '{reply}'."))
+            }
+            Self::InvalidCore => f.write_str("File isn't a valid core dump or minidump."),
+            Self::NotInCore(addr) => {
+                f.write_fmt(format_args!("Memory at {addr:#x} isn't stored in the core."))
+            }
+            Self::InvalidExpression => f.write_str("Variable's location can't be evaluated."),
+            #[cfg(target_os = "macos")]
+            Self::Mach(code) => f.write_fmt(format_args!("Mach call failed with error {code}.")),
+            Self::IO(err) => f.write_fmt(format_args!("{err}.")),
+            #[cfg(unix)]
+            Self::Sys(err) => f.write_fmt(format_args!("{err}.")),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::IO(err)
+    }
+}
+
+#[cfg(unix)]
+impl From<nix::Error> for Error {
+    fn from(err: nix::Error) -> Self {
+        Error::Sys(err)
+    }
+}
+
+/// A process running on the system.
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    /// Executable the process was started from, unknown for processes we can't inspect.
+    pub path: Option<PathBuf>,
+}
+
+/// Register values of a stopped process, by their name.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Registers {
+    /// General purpose registers, including the instruction pointer.
+    pub general: Vec<(&'static str, u64)>,
+    /// Status flags decoded from the flags register.
+    pub flags: Vec<(&'static str, bool)>,
+    pub segments: Vec<(&'static str, u64)>,
+}
+
+impl Registers {
+    /// Value of a general purpose or segment register.
+    pub fn get(&self, name: &str) -> Option<u64> {
+        self.general
+            .iter()
+            .chain(self.segments.iter())
+            .find(|(reg, _)| *reg == name)
+            .map(|&(_, value)| value)
+    }
+
+    pub fn flag(&self, name: &str) -> Option<bool> {
+        self.flags.iter().find(|(flag, _)| *flag == name).map(|&(_, set)| set)
+    }
+}
+
+/// A thread of a stopped process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thread {
+    pub tid: u32,
+    pub name: String,
+    /// Address the thread is stopped at, as found in the binary.
+    pub pc: usize,
+    /// Why the thread stopped, None if it was only stopped as another thread did.
+    pub stop: Option<Stop>,
+    /// Whether it's the thread that's stepped and whose registers and stack are inspected.
+    pub current: bool,
+}
+
+/// Why the tracee stopped, addresses are as found in the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Attached to a running process whilst it was at the address.
+    Attached(usize),
+    /// Hit a breakpoint at the address.
+    Breakpoint(usize),
+    /// Executed a single instruction, stopping at the address.
+    Stepped(usize),
+    /// Watchpoint at the first address triggered whilst at the second address.
+    Watchpoint(usize, usize),
+    /// Received a signal whilst at the address.
+    Signal(i32, usize),
+    /// Forked a child with the given pid whilst at the address, see [`FollowFork`].
+    Forked(u32, usize),
+    /// Process with the given pid exec'd a new binary, stopping before it's first instruction
+    /// at the address.
+    Exec(u32, usize),
+    /// Exited with an exit code.
+    Exited(i32),
+    /// Terminated by a signal.
+    Killed(i32),
+}
+
+/// Name of a signal a target stopped for, macOS numbers it's signals differently than the
+/// others.
+fn signal_name(sig: i32) -> Option<&'static str> {
+    match cfg!(target_os = "macos") {
+        true => None,
+        false => signals::name(sig),
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Attached(addr) => f.write_fmt(format_args!("Attached whilst at {addr:#x}.")),
+            Self::Breakpoint(addr) => f.write_fmt(format_args!("Hit breakpoint at {addr:#x}.")),
+            Self::Stepped(addr) => f.write_fmt(format_args!("Stepped to {addr:#x}.")),
+            Self::Watchpoint(addr, pc) => {
+                f.write_fmt(format_args!("Watchpoint at {addr:#x} triggered at {pc:#x}."))
+            }
+            Self::Signal(sig, addr) => match signal_name(*sig) {
+                Some(name) => f.write_fmt(format_args!("Received {name} at {addr:#x}.")),
+                None => f.write_fmt(format_args!("Received signal {sig} at {addr:#x}.")),
+            },
+            Self::Forked(pid, addr) => {
+                f.write_fmt(format_args!("Forked child {pid} at {addr:#x}."))
+            }
+            Self::Exec(pid, addr) => f.write_fmt(format_args!(
+                "Process {pid} exec'd a new binary, stopped at {addr:#x}."
+            )),
+            Self::Exited(code) => f.write_fmt(format_args!("Exited with code {code}.")),
+            Self::Killed(sig) => match signal_name(*sig) {
+                Some(name) => f.write_fmt(format_args!("Killed by {name}.")),
+                None => f.write_fmt(format_args!("Killed by signal {sig}.")),
+            },
+        }
+    }
+}
+
+/// Which process is debugged after the tracee forks, the other one is left running.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FollowFork {
+    #[default]
+    Parent,
+    Child,
+    /// Stop at the fork, the process is chosen once the tracee is continued.
+    Ask,
+}
+
+/// How a spawned process is set up, besides it's arguments.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Environment {
+    /// Variables set on top of the ones inherited from us, replacing those of the same name.
+    pub vars: Vec<(String, String)>,
+    /// Directory the process starts in, ours if None.
+    pub cwd: Option<PathBuf>,
+    /// File read as the process' stdin instead of the console.
+    pub stdin: Option<PathBuf>,
+    /// Whether the process is loaded at the same addresses each run. Only Linux can turn off
+    /// randomization for a single process, elsewhere it's ignored.
+    pub disable_aslr: bool,
+}
+
+impl Environment {
+    /// Set `name` to `value`, replacing the variable's previous value.
+    pub fn set_var(&mut self, name: String, value: String) {
+        match self.vars.iter_mut().find(|(var, _)| *var == name) {
+            Some((_, previous)) => *previous = value,
+            None => self.vars.push((name, value)),
+        }
+    }
+}
+
+/// Used when spawning, which isn't supported everywhere.
+#[cfg_attr(
+    not(any(
+        all(target_os = "linux", target_arch = "x86_64"),
+        all(windows, target_arch = "x86_64"),
+        target_os = "macos"
+    )),
+    allow(dead_code)
+)]
+impl Environment {
+    /// Check the working directory exists and open the file used as stdin, if any, before
+    /// the process is spawned.
+    pub(crate) fn prepare(&self) -> Result<Option<std::fs::File>, Error> {
+        if let Some(ref cwd) = self.cwd {
+            if !cwd.is_dir() {
+                return Err(Error::InvalidCwd(cwd.clone()));
+            }
+        }
+
+        match self.stdin {
+            Some(ref path) => match std::fs::File::open(path) {
+                Ok(file) => Ok(Some(file)),
+                Err(_) => Err(Error::InvalidStdin(path.clone())),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Our variables with the ones set applied.
+    pub(crate) fn merged_vars(&self) -> Vec<(OsString, OsString)> {
+        // Names are case-insensitive on Windows.
+        let same = |a: &OsStr, b: &str| match cfg!(windows) {
+            true => a.to_string_lossy().eq_ignore_ascii_case(b),
+            false => a == b,
+        };
+
+        let mut vars: Vec<(OsString, OsString)> = std::env::vars_os()
+            .filter(|(name, _)| !self.vars.iter().any(|(set, _)| same(name, set)))
+            .collect();
+
+        for (name, value) in &self.vars {
+            vars.push((name.into(), value.into()));
+        }
+
+        vars
+    }
+
+    /// Our variables with the ones set applied as `name=value`, as `execve` takes them.
+    #[cfg(unix)]
+    pub(crate) fn envp(&self) -> Result<Vec<std::ffi::CString>, Error> {
+        use std::os::unix::ffi::OsStringExt;
+
+        let vars = self.merged_vars().into_iter().map(|(name, value)| {
+            let mut var = name.into_vec();
+            var.push(b'=');
+            var.extend(value.into_vec());
+            std::ffi::CString::new(var).map_err(|_| Error::InvalidArgs)
+        });
+
+        vars.collect()
+    }
+}
+
+/// A stopped process that's being debugged, either by us or through a remote stub.
+pub trait Target: Send {
+    /// Address the target is stopped at, as found in the binary.
+    fn pc(&self) -> Result<usize, Error>;
+
+    fn handle(&self) -> Handle;
+
+    /// Report every syscall the target makes to `report` once it returns.
+    fn trace_syscalls(&mut self, report: Box<dyn FnMut(Syscall) + Send>) -> Result<(), Error>;
+
+    /// Report the shared libraries loaded into the target to `report` each time they change.
+    fn watch_libraries(
+        &mut self,
+        report: Box<dyn FnMut(Vec<Library>) + Send>,
+    ) -> Result<(), Error>;
+
+    fn registers(&self) -> Result<Registers, Error>;
+
+    /// Set registers of the current thread, named as in [`Registers`], stopping at the first
+    /// that can't be set.
+    fn write_registers(&mut self, registers: &[(&str, u64)]) -> Result<(), Error>;
+
+    /// Read the target's memory at runtime address `addr`, without our breakpoints.
+    fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error>;
+
+    /// Write to the target's memory at runtime address `addr`, keeping our breakpoints.
+    fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error>;
+
+    /// Frames on the stack of the current thread, innermost first.
+    fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error>;
+
+    /// Threads of the target, ordered by their id.
+    fn threads(&self) -> Result<Vec<Thread>, Error>;
+
+    /// Make `tid` the thread that's stepped and whose registers and stack are inspected.
+    fn select_thread(&mut self, tid: u32) -> Result<(), Error>;
+
+    /// Choose which process to debug after the target forks.
+    fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error>;
+
+    /// Choose which signals the target stops for and which are passed on to it.
+    fn set_signals(&mut self, signals: Signals) -> Result<(), Error>;
+
+    /// Resume execution until the next breakpoint, signal or exit.
+    fn cont(&mut self) -> Result<Stop, Error>;
+
+    /// Execute a single instruction.
+    fn step(&mut self) -> Result<Stop, Error>;
+}
+
+impl Target for Debugger {
+    fn pc(&self) -> Result<usize, Error> {
+        Debugger::pc(self)
+    }
+
+    fn handle(&self) -> Handle {
+        Debugger::handle(self)
+    }
+
+    fn trace_syscalls(&mut self, report: Box<dyn FnMut(Syscall) + Send>) -> Result<(), Error> {
+        Debugger::trace_syscalls(self, report)
+    }
+
+    fn watch_libraries(
+        &mut self,
+        report: Box<dyn FnMut(Vec<Library>) + Send>,
+    ) -> Result<(), Error> {
+        Debugger::watch_libraries(self, report)
+    }
+
+    fn registers(&self) -> Result<Registers, Error> {
+        Debugger::registers(self)
+    }
+
+    fn write_registers(&mut self, registers: &[(&str, u64)]) -> Result<(), Error> {
+        for &(name, value) in registers {
+            Debugger::set_register(self, name, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        Debugger::read_memory(self, addr, len)
+    }
+
+    fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        Debugger::write_memory(self, addr, bytes)
+    }
+
+    fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        Debugger::backtrace(self, cfi)
+    }
+
+    fn threads(&self) -> Result<Vec<Thread>, Error> {
+        Debugger::threads(self)
+    }
+
+    fn select_thread(&mut self, tid: u32) -> Result<(), Error> {
+        Debugger::select_thread(self, tid)
+    }
+
+    fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        Debugger::set_follow_fork(self, follow)
+    }
+
+    fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        Debugger::set_signals(self, signals)
+    }
+
+    fn cont(&mut self) -> Result<Stop, Error> {
+        Debugger::cont(self)
+    }
+
+    fn step(&mut self) -> Result<Stop, Error> {
+        Debugger::step(self)
+    }
+}
+
+/// Controls a running [`Target`] from other threads.
+#[derive(Clone)]
+pub enum Handle {
+    Process(ProcessHandle),
+    Remote(RemoteHandle),
+    /// Cores can't be resumed, so there's nothing to stop.
+    Core(CoreHandle),
+}
+
+impl Handle {
+    /// Stop the target so changes to the breakpoints are applied, it's resumed right after.
+    pub fn interrupt(&self) {
+        match self {
+            Self::Process(handle) => handle.interrupt(),
+            Self::Remote(handle) => handle.interrupt(),
+            Self::Core(_) => {}
+        }
+    }
+
+    /// Stop the target, which is reported as it receiving a signal.
+    pub fn pause(&self) {
+        match self {
+            Self::Process(handle) => handle.pause(),
+            Self::Remote(handle) => handle.pause(),
+            Self::Core(_) => {}
+        }
+    }
+
+    pub fn kill(&self) {
+        match self {
+            Self::Process(handle) => handle.kill(),
+            Self::Remote(handle) => handle.kill(),
+            Self::Core(_) => {}
+        }
+    }
+
+    /// Stop debugging the running target, leaving it running.
+    pub fn detach(&self) {
+        match self {
+            Self::Process(handle) => handle.detach(),
+            Self::Remote(handle) => handle.detach(),
+            Self::Core(_) => {}
+        }
+    }
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        match self {
+            Self::Process(handle) => handle.memory_maps(),
+            Self::Remote(handle) => handle.memory_maps(),
+            Self::Core(handle) => handle.memory_maps(),
+        }
+    }
+
+    /// Read the target's memory at runtime address `addr`.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        match self {
+            Self::Process(handle) => handle.read_memory(addr, len),
+            Self::Remote(handle) => handle.read_memory(addr, len),
+            Self::Core(handle) => handle.read_memory(addr, len),
+        }
+    }
+}
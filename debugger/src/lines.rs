@@ -0,0 +1,203 @@
+//! Stepping by source line, using the line table of the binary's debug info.
+//!
+//! A line is stepped by single stepping until the thread reaches the start of another line.
+//! Calls into functions without line info, or any call when stepping over them, are run to
+//! completion by continuing to a temporary breakpoint at their return address, which is also
+//! how the current function is stepped out of.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::{BreakpointSet, CallFrameInfo, Error, Registers, Stop, Target};
+
+/// Longest instruction a call is encoded with, in bytes.
+const MAX_CALL_LEN: usize = 16;
+
+/// Rows of the line table, telling which source line each instruction belongs to.
+#[derive(Debug, Default, Clone)]
+pub struct LineTable {
+    /// Address each row starts at, along with it's file and line, sorted by address.
+    rows: Vec<(usize, Arc<Path>, usize)>,
+}
+
+impl LineTable {
+    /// Line table of `rows`, where a line of 0 marks code that isn't from any line.
+    pub fn new(mut rows: Vec<(usize, Arc<Path>, usize)>) -> Self {
+        rows.sort_by_key(|&(addr, ..)| addr);
+        Self { rows }
+    }
+
+    /// File and line of the instruction at `addr`.
+    pub fn line(&self, addr: usize) -> Option<(&Path, usize)> {
+        let idx = self.rows.partition_point(|&(start, ..)| start <= addr).checked_sub(1)?;
+        let (_, ref path, line) = self.rows[idx];
+        (line != 0).then_some((path, line))
+    }
+
+    /// Whether `addr` is the first instruction of a row, somewhere a line starts.
+    pub fn is_row_start(&self, addr: usize) -> bool {
+        self.rows.binary_search_by_key(&addr, |&(start, ..)| start).is_ok()
+    }
+}
+
+/// Stack pointer and where the binary is loaded relative to the addresses in it.
+fn frame(target: &dyn Target, regs: &Registers) -> Result<(usize, usize), Error> {
+    let sp = regs.get("rsp").or_else(|| regs.get("sp")).ok_or(Error::InvalidRegister)?;
+    let pc = regs.get("rip").or_else(|| regs.get("pc")).ok_or(Error::InvalidRegister)?;
+    let bias = (pc as usize).wrapping_sub(target.pc()?);
+    Ok((sp as usize, bias))
+}
+
+/// Where the call that was just stepped into from `prev_pc` returns to, None if the
+/// instruction at `prev_pc` wasn't a call.
+fn return_addr(
+    target: &dyn Target,
+    prev_pc: usize,
+    prev_sp: usize,
+    pc: usize,
+) -> Result<Option<usize>, Error> {
+    let regs = target.registers()?;
+    let (sp, bias) = frame(target, &regs)?;
+
+    // Calls push the return address, unless there's a link register.
+    let ret = match regs.get("lr") {
+        Some(lr) => lr as usize,
+        None if sp < prev_sp => {
+            let bytes = target.handle().read_memory(sp, std::mem::size_of::<usize>())?;
+            match bytes.try_into() {
+                Ok(word) => usize::from_le_bytes(word),
+                Err(_) => return Ok(None),
+            }
+        }
+        None => return Ok(None),
+    };
+
+    let ret = ret.wrapping_sub(bias);
+    let called = ret > prev_pc && ret <= prev_pc + MAX_CALL_LEN && ret != pc;
+    Ok(called.then_some(ret))
+}
+
+/// Continue until the function that was called returns to `ret`, `sp` being the stack pointer
+/// on entry to it. Returns the stop if it stopped anywhere else.
+fn finish(
+    target: &mut dyn Target,
+    breakpoints: &BreakpointSet,
+    ret: usize,
+    sp: usize,
+) -> Result<Option<Stop>, Error> {
+    breakpoints.set_temporary(ret);
+
+    let stop = loop {
+        match target.cont() {
+            // Recursive calls return to the same address from deeper down the stack, without a
+            // return address on the stack it's where the stack pointer was on entry.
+            Ok(Stop::Breakpoint(addr)) if addr == ret => {
+                let regs = target.registers()?;
+                if frame(target, &regs)?.0 >= sp {
+                    break Ok(None);
+                }
+            }
+            Ok(stop) => break Ok(Some(stop)),
+            Err(err) => break Err(err),
+        }
+    };
+
+    breakpoints.clear_temporary();
+    stop
+}
+
+/// Execute the current thread until it reaches another source line, stepping into functions
+/// with line info unless `over`. Without line info a single instruction is executed.
+pub fn step_line(
+    target: &mut dyn Target,
+    lines: &LineTable,
+    breakpoints: &BreakpointSet,
+    over: bool,
+) -> Result<Stop, Error> {
+    let mut pc = target.pc()?;
+    let start = match lines.line(pc) {
+        Some((path, line)) => (path.to_path_buf(), line),
+        None => return target.step(),
+    };
+
+    let (start_sp, _) = frame(target, &target.registers()?)?;
+    loop {
+        let prev_pc = pc;
+        let (prev_sp, _) = frame(target, &target.registers()?)?;
+
+        pc = match target.step()? {
+            Stop::Stepped(pc) => pc,
+            stop => return Ok(stop),
+        };
+
+        if let Some(ret) = return_addr(target, prev_pc, prev_sp, pc)? {
+            if over || lines.line(pc).is_none() {
+                let (sp, _) = frame(target, &target.registers()?)?;
+                match finish(target, breakpoints, ret, sp)? {
+                    Some(stop) => return Ok(stop),
+                    None => pc = ret,
+                }
+
+                continue;
+            }
+        }
+
+        let (sp, _) = frame(target, &target.registers()?)?;
+        match lines.line(pc) {
+            // Returned into a caller without line info, such as the C runtime.
+            None if sp > start_sp => return Ok(Stop::Stepped(pc)),
+            None => {}
+            Some((path, line)) => {
+                let moved = path != start.0.as_path() || line != start.1;
+                if moved && lines.is_row_start(pc) {
+                    return Ok(Stop::Stepped(pc));
+                }
+            }
+        }
+    }
+}
+
+/// Continue until the current function returns to it's caller, found by walking the stack
+/// with `cfi`. Without a caller the thread is continued.
+pub fn step_out(
+    target: &mut dyn Target,
+    cfi: &CallFrameInfo,
+    breakpoints: &BreakpointSet,
+) -> Result<Stop, Error> {
+    let caller = match target.backtrace(cfi)?.get(1) {
+        Some(&caller) => caller,
+        None => return target.cont(),
+    };
+
+    // The caller's stack pointer is where it was before the call.
+    match finish(target, breakpoints, caller.pc, caller.sp)? {
+        Some(stop) => Ok(stop),
+        None => Ok(Stop::Stepped(caller.pc)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows() {
+        let main: Arc<Path> = Arc::from(Path::new("/src/main.c"));
+        let util: Arc<Path> = Arc::from(Path::new("/src/util.c"));
+        let lines = LineTable::new(vec![
+            (0x1010, Arc::clone(&main), 4),
+            (0x1000, Arc::clone(&main), 3),
+            (0x1018, Arc::clone(&main), 0),
+            (0x1020, Arc::clone(&util), 4),
+            (0x1030, Arc::clone(&main), 3),
+        ]);
+
+        assert_eq!(lines.line(0x1004), Some((&*main, 3)));
+        assert_eq!(lines.line(0x1010), Some((&*main, 4)));
+        assert_eq!(lines.line(0x1018), None);
+        assert_eq!(lines.line(0xfff), None);
+        assert!(lines.is_row_start(0x1010));
+        assert!(!lines.is_row_start(0x1014));
+        assert_eq!(lines.line(0x1024), Some((&*util, 4)));
+    }
+}
@@ -0,0 +1,1740 @@
+use crate::breakpoint::{self, Inserted, Memory, TRAP};
+use crate::rendezvous;
+use crate::unwind::{self, Regs};
+use crate::{
+    BreakpointSet, CallFrameInfo, Environment, Error, FollowFork, Frame, Handle, Library,
+    MemoryRegion, ProcessInfo, Registers, Signals, Stdio, Stop, Syscall, Thread, WatchKind,
+    Watchpoint, X86_FLAGS,
+};
+use nix::errno::Errno;
+use nix::libc::{self, c_long, c_void};
+use nix::sys::personality::{self, Persona};
+use nix::sys::ptrace;
+use nix::sys::signal::{self, Signal};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::{chdir, execve, fork, ForkResult, Pid};
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::sync::Arc;
+
+/// Auxiliary vector entry holding where the program headers are loaded.
+const AT_PHDR: u64 = 3;
+
+/// Auxiliary vector entry holding the number of program headers.
+const AT_PHNUM: u64 = 5;
+
+/// Auxiliary vector entry holding the program's entrypoint.
+const AT_ENTRY: u64 = 9;
+
+/// Offset of `u_debugreg` in the `struct user` accessed through `PTRACE_PEEKUSER`.
+const DEBUGREG_OFFSET: usize = 848;
+
+/// Debug status register, tells which of DR0 to DR3 triggered.
+const DR6: usize = 6;
+
+/// Debug control register, enables DR0 to DR3.
+const DR7: usize = 7;
+
+/// Granularity of ptrace memory accesses.
+const WORD: usize = std::mem::size_of::<c_long>();
+
+/// Value of `rax` when entering a syscall, as the kernel sets it before running the syscall.
+const ENOSYS: i64 = -(libc::ENOSYS as i64);
+
+/// A stopped process we're attached to.
+#[derive(Clone, Copy)]
+pub(crate) struct Tracee {
+    pid: Pid,
+    /// Thread whose registers are accessed, memory is shared by all threads.
+    tid: Pid,
+}
+
+impl Tracee {
+    /// The same process, accessing the registers of thread `tid`.
+    fn thread(&self, tid: Pid) -> Self {
+        Self { pid: self.pid, tid }
+    }
+
+    fn pc(&self) -> Result<usize, Error> {
+        Ok(ptrace::getregs(self.tid)?.rip as usize)
+    }
+
+    fn set_pc(&self, pc: usize) -> Result<(), Error> {
+        let mut regs = ptrace::getregs(self.tid)?;
+        regs.rip = pc as u64;
+        Ok(ptrace::setregs(self.tid, regs)?)
+    }
+
+    pub fn read_registers(&self) -> Result<Registers, Error> {
+        let regs = ptrace::getregs(self.tid)?;
+
+        let general = vec![
+            ("rax", regs.rax),
+            ("rbx", regs.rbx),
+            ("rcx", regs.rcx),
+            ("rdx", regs.rdx),
+            ("rsi", regs.rsi),
+            ("rdi", regs.rdi),
+            ("rbp", regs.rbp),
+            ("rsp", regs.rsp),
+            ("r8", regs.r8),
+            ("r9", regs.r9),
+            ("r10", regs.r10),
+            ("r11", regs.r11),
+            ("r12", regs.r12),
+            ("r13", regs.r13),
+            ("r14", regs.r14),
+            ("r15", regs.r15),
+            ("rip", regs.rip),
+            ("rflags", regs.eflags),
+        ];
+
+        let flags = X86_FLAGS
+            .iter()
+            .map(|&(name, bit)| (name, regs.eflags & (1 << bit) != 0))
+            .collect();
+
+        let segments = vec![
+            ("cs", regs.cs),
+            ("ss", regs.ss),
+            ("ds", regs.ds),
+            ("es", regs.es),
+            ("fs", regs.fs),
+            ("gs", regs.gs),
+            ("fs_base", regs.fs_base),
+            ("gs_base", regs.gs_base),
+        ];
+
+        Ok(Registers {
+            general,
+            flags,
+            segments,
+        })
+    }
+
+    /// Set a general purpose or segment register, named as in [`Tracee::read_registers`].
+    pub fn write_register(&self, name: &str, value: u64) -> Result<(), Error> {
+        let mut regs = ptrace::getregs(self.tid)?;
+        let reg = match name {
+            "rax" => &mut regs.rax,
+            "rbx" => &mut regs.rbx,
+            "rcx" => &mut regs.rcx,
+            "rdx" => &mut regs.rdx,
+            "rsi" => &mut regs.rsi,
+            "rdi" => &mut regs.rdi,
+            "rbp" => &mut regs.rbp,
+            "rsp" => &mut regs.rsp,
+            "r8" => &mut regs.r8,
+            "r9" => &mut regs.r9,
+            "r10" => &mut regs.r10,
+            "r11" => &mut regs.r11,
+            "r12" => &mut regs.r12,
+            "r13" => &mut regs.r13,
+            "r14" => &mut regs.r14,
+            "r15" => &mut regs.r15,
+            "rip" => &mut regs.rip,
+            "rflags" => &mut regs.eflags,
+            "cs" => &mut regs.cs,
+            "ss" => &mut regs.ss,
+            "ds" => &mut regs.ds,
+            "es" => &mut regs.es,
+            "fs" => &mut regs.fs,
+            "gs" => &mut regs.gs,
+            "fs_base" => &mut regs.fs_base,
+            "gs_base" => &mut regs.gs_base,
+            _ => return Err(Error::InvalidRegister),
+        };
+
+        *reg = value;
+        Ok(ptrace::setregs(self.tid, regs)?)
+    }
+
+    fn read_debugreg(&self, idx: usize) -> Result<u64, Error> {
+        let offset = DEBUGREG_OFFSET + idx * WORD;
+
+        // PEEKUSER returns the value read, so errors can only be told apart through errno.
+        Errno::clear();
+        let value = unsafe {
+            libc::ptrace(
+                libc::PTRACE_PEEKUSER,
+                self.tid.as_raw(),
+                offset as *mut c_void,
+                std::ptr::null_mut::<c_void>(),
+            )
+        };
+
+        if value == -1 && Errno::last() != Errno::UnknownErrno {
+            return Err(Error::Sys(Errno::last()));
+        }
+
+        Ok(value as u64)
+    }
+
+    fn write_debugreg(&self, idx: usize, value: u64) -> Result<(), Error> {
+        let offset = DEBUGREG_OFFSET + idx * WORD;
+        let res = unsafe {
+            libc::ptrace(
+                libc::PTRACE_POKEUSER,
+                self.tid.as_raw(),
+                offset as *mut c_void,
+                value as *mut c_void,
+            )
+        };
+
+        Errno::result(res)?;
+        Ok(())
+    }
+
+    /// Value of the auxiliary vector entry `key`, as passed to the process by the kernel.
+    fn auxv(&self, key: u64) -> Result<Option<usize>, Error> {
+        let auxv = std::fs::read(format!("/proc/{}/auxv", self.pid))?;
+        let entry = auxv
+            .chunks_exact(16)
+            .map(|pair| {
+                let key = u64::from_ne_bytes(pair[..8].try_into().unwrap());
+                let value = u64::from_ne_bytes(pair[8..].try_into().unwrap());
+                (key, value)
+            })
+            .find(|&(entry, _)| entry == key);
+
+        Ok(entry.map(|(_, value)| value as usize))
+    }
+
+    /// Runtime address of the entrypoint, as passed to the process by the kernel.
+    fn entrypoint(&self) -> Result<usize, Error> {
+        Ok(self.auxv(AT_ENTRY)?.unwrap_or(0))
+    }
+}
+
+impl Memory for Tracee {
+    type Error = Error;
+
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), Error> {
+        for (idx, chunk) in buf.chunks_mut(WORD).enumerate() {
+            let addr = addr + idx * WORD;
+            let word = ptrace::read(self.tid, addr as ptrace::AddressType)?;
+            chunk.copy_from_slice(&word.to_ne_bytes()[..chunk.len()]);
+        }
+
+        Ok(())
+    }
+
+    fn write(&self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        for (idx, chunk) in bytes.chunks(WORD).enumerate() {
+            let addr = addr + idx * WORD;
+
+            // Partial words have to keep the bytes that follow them.
+            let mut word = ptrace::read(self.tid, addr as ptrace::AddressType)?.to_ne_bytes();
+            word[..chunk.len()].copy_from_slice(chunk);
+            let word = c_long::from_ne_bytes(word);
+
+            unsafe {
+                ptrace::write(self.tid, addr as ptrace::AddressType, word as ptrace::AddressType)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn memory_maps(pid: Pid) -> Result<Vec<MemoryRegion>, Error> {
+    let maps = std::fs::read_to_string(format!("/proc/{pid}/maps"))?;
+    Ok(crate::maps::parse(&maps))
+}
+
+/// Read `len` bytes at `addr`, stopping early at the first byte that isn't mapped.
+fn read_memory(pid: Pid, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mem = std::fs::File::open(format!("/proc/{pid}/mem"))?;
+    let mut bytes = vec![0; len];
+    let mut read = 0;
+
+    while read < len {
+        match mem.read_at(&mut bytes[read..], (addr + read) as u64) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            // Unmapped memory can't be read.
+            Err(_) if read != 0 => break,
+            Err(err) => return Err(Error::IO(err)),
+        }
+    }
+
+    bytes.truncate(read);
+    Ok(bytes)
+}
+
+/// Executable a running process was started from.
+pub fn exe_path(pid: u32) -> Result<PathBuf, Error> {
+    Ok(std::fs::read_link(format!("/proc/{pid}/exe"))?)
+}
+
+/// Entrypoint as found in the binary `pid` is running, 0 if it isn't a 64-bit ELF.
+fn binary_entrypoint(pid: Pid) -> usize {
+    let mut header = [0; 32];
+    let read = std::fs::File::open(format!("/proc/{pid}/exe"))
+        .and_then(|exe| exe.read_exact_at(&mut header, 0));
+
+    if read.is_err() || header[..5] != *b"\x7fELF\x02" {
+        return 0;
+    }
+
+    u64::from_le_bytes(header[24..].try_into().unwrap()) as usize
+}
+
+/// Processes running on the system, ordered by their pid.
+pub fn processes() -> Vec<ProcessInfo> {
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut processes: Vec<ProcessInfo> = entries
+        .filter_map(|entry| {
+            let pid: u32 = entry.ok()?.file_name().to_str()?.parse().ok()?;
+            let name = std::fs::read_to_string(format!("/proc/{pid}/comm")).ok()?;
+
+            Some(ProcessInfo {
+                pid,
+                name: name.trim_end().to_string(),
+                path: exe_path(pid).ok(),
+            })
+        })
+        .collect();
+
+    processes.sort_unstable_by_key(|process| process.pid);
+    processes
+}
+
+/// Threads of the process, by their id.
+fn tasks(pid: Pid) -> Vec<Pid> {
+    let entries = match std::fs::read_dir(format!("/proc/{pid}/task")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .map(Pid::from_raw)
+        .collect()
+}
+
+/// Whether `tid` is a thread of process `pid`, rather than another process.
+fn is_thread(pid: Pid, tid: Pid) -> bool {
+    Path::new(&format!("/proc/{pid}/task/{tid}")).exists()
+}
+
+/// Whether thread `tid` exited, but isn't reaped as other threads of it's process still run.
+fn is_zombie(pid: Pid, tid: Pid) -> bool {
+    let stat = match std::fs::read_to_string(format!("/proc/{pid}/task/{tid}/stat")) {
+        Ok(stat) => stat,
+        Err(_) => return false,
+    };
+
+    // The state follows the name, which can contain any character.
+    match stat.rsplit_once(')') {
+        Some((_, rest)) => rest.trim_start().starts_with(['Z', 'X']),
+        None => false,
+    }
+}
+
+/// Wait for every child of the calling thread, including the threads it traces.
+///
+/// Other threads of ours may be debugging processes of their own.
+fn wait_flags() -> Option<WaitPidFlag> {
+    Some(WaitPidFlag::__WALL | WaitPidFlag::__WNOTHREAD)
+}
+
+/// Wait for `tid` to stop after attaching or being created, signals that arrive before are
+/// delivered once it's resumed.
+fn wait_attached(tid: Pid) -> Result<Option<Signal>, Error> {
+    let mut pending = None;
+    loop {
+        match waitpid(tid, wait_flags())? {
+            WaitStatus::Stopped(_, Signal::SIGSTOP) => return Ok(pending),
+            WaitStatus::Stopped(_, sig) => {
+                pending = Some(sig);
+                ptrace::cont(tid, None)?;
+            }
+            _ => return Err(Error::Exited),
+        }
+    }
+}
+
+/// State of a single thread of the tracee.
+#[derive(Default)]
+struct Task {
+    /// Signal to deliver when resuming.
+    pending: Option<Signal>,
+    /// Syscall the thread is in the middle of.
+    syscall: Option<Syscall>,
+    /// Whether the thread runs, otherwise it's in a ptrace-stop.
+    running: bool,
+    /// Whether a `SIGSTOP` we sent the thread is yet to be reported.
+    stopping: bool,
+    /// Why the thread stopped, None if it was only stopped as another thread did.
+    stop: Option<Stop>,
+}
+
+/// A child the tracee forked, stopped until it's decided which process is followed.
+struct Fork {
+    pid: Pid,
+    /// Signal to deliver when resuming.
+    pending: Option<Signal>,
+    /// Whether the child shares the parent's memory until it exec's or exits.
+    vfork: bool,
+}
+
+/// Threads are debugged all-stop, once one of them stops the others are stopped as well and
+/// they're all resumed together. Only the current thread is stepped.
+pub struct Debugger {
+    tracee: Tracee,
+    /// Threads of the tracee by their id, the main thread's id being the process'.
+    threads: BTreeMap<Pid, Task>,
+    breakpoints: Arc<BreakpointSet>,
+    inserted: Inserted,
+    /// Offset between addresses in the binary and where it's loaded.
+    bias: usize,
+    /// Set by [`Handle::interrupt`] to tell apart our own stops.
+    interrupted: Arc<AtomicBool>,
+    /// Pid of the tracee shared with it's handles, it changes when following a forked child.
+    handle_pid: Arc<AtomicI32>,
+    /// Watchpoints and hardware breakpoints in the debug registers, in the order of DR0 to DR3.
+    watching: Vec<Watchpoint>,
+    /// Set by [`Handle::detach`] to stop tracing whilst the tracee runs.
+    detaching: Arc<AtomicBool>,
+    /// Whether we attached to an already running process, which is left running once dropped.
+    attached: bool,
+    /// Called with every syscall made, see [`Debugger::trace_syscalls`].
+    report_syscall: Option<Box<dyn FnMut(Syscall) + Send>>,
+    /// Runtime address of the dynamic linker's `r_debug`, None until it's set up.
+    r_debug: Option<usize>,
+    /// Shared libraries loaded by the dynamic linker, as of the last time they changed.
+    libraries: Vec<Library>,
+    /// Called whenever the libraries change, see [`Debugger::watch_libraries`].
+    report_libraries: Option<Box<dyn FnMut(Vec<Library>) + Send>>,
+    follow: FollowFork,
+    /// Which signals stop the tracee and which are passed on to it.
+    signals: Signals,
+    /// Child forked at the last stop, followed or let go once the tracee is resumed.
+    forked: Option<Fork>,
+    /// Forked children whose initial stop was reported before the fork.
+    children: Vec<Pid>,
+    /// Whether the tracee shares it's memory with a process we don't trace after a vfork, which
+    /// would trap on our breakpoints.
+    sharing: bool,
+    exited: bool,
+}
+
+impl Debugger {
+    /// Start `path` with `args` set up as `env` describes, stopped before it's first instruction
+    /// executes. It's standard streams are the pipes of `stdio` if given, otherwise they're ours.
+    ///
+    /// `entrypoint` is the entrypoint as found in the binary, used for finding where
+    /// position-independent executables get loaded.
+    pub fn spawn(
+        path: &Path,
+        args: &[String],
+        env: &Environment,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        stdio: Option<Stdio>,
+    ) -> Result<Self, Error> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::InvalidArgs)?;
+        let mut argv = vec![path.clone()];
+        for arg in args {
+            argv.push(CString::new(arg.as_bytes()).map_err(|_| Error::InvalidArgs)?);
+        }
+
+        let envp = env.envp()?;
+        let input = env.prepare()?;
+        let cwd = match env.cwd {
+            Some(ref cwd) => {
+                Some(CString::new(cwd.as_os_str().as_bytes()).map_err(|_| Error::InvalidArgs)?)
+            }
+            None => None,
+        };
+
+        // SAFETY: The child only performs async-signal-safe calls before exec'ing.
+        let pid = match unsafe { fork()? } {
+            ForkResult::Child => {
+                if let Some(ref stdio) = stdio {
+                    stdio.redirect();
+                }
+
+                if let Some(ref input) = input {
+                    crate::stdio::redirect_stdin(input);
+                }
+
+                if let Some(ref cwd) = cwd {
+                    if chdir(cwd.as_c_str()).is_err() {
+                        unsafe { nix::libc::_exit(127) }
+                    }
+                }
+
+                // Inherited across exec, unlike most of the process' state.
+                if env.disable_aslr {
+                    if let Ok(persona) = personality::get() {
+                        let _ = personality::set(persona | Persona::ADDR_NO_RANDOMIZE);
+                    }
+                }
+
+                let _ = ptrace::traceme();
+                let _ = execve(&path, &argv, &envp);
+                unsafe { nix::libc::_exit(127) }
+            }
+            ForkResult::Parent { child } => child,
+        };
+
+        // The child's ends are only open in the child, so it closing them ends the pipes.
+        drop(stdio);
+        drop(input);
+
+        // The child stops once it exec'd.
+        match waitpid(pid, wait_flags())? {
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {}
+            _ => return Err(Error::Exited),
+        }
+
+        let tracee = Tracee { pid, tid: pid };
+        let bias = match entrypoint {
+            0 => 0,
+            _ => tracee.entrypoint()?.wrapping_sub(entrypoint),
+        };
+
+        let mut debugger = Self {
+            tracee,
+            threads: BTreeMap::from([(pid, Task::default())]),
+            breakpoints,
+            inserted: Inserted::default(),
+            bias,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            handle_pid: Arc::new(AtomicI32::new(pid.as_raw())),
+            watching: Vec::new(),
+            detaching: Arc::new(AtomicBool::new(false)),
+            attached: false,
+            report_syscall: None,
+            r_debug: None,
+            libraries: Vec::new(),
+            report_libraries: None,
+            follow: FollowFork::default(),
+            signals: Signals::default(),
+            forked: None,
+            children: Vec::new(),
+            sharing: false,
+            exited: false,
+        };
+
+        ptrace::setoptions(pid, debugger.options())?;
+        debugger.watch_rendezvous(true)?;
+        debugger.breakpoints.take_dirty();
+        debugger.sync()?;
+        Ok(debugger)
+    }
+
+    /// Start tracing the running process `pid`, which is stopped until [`Debugger::cont`].
+    ///
+    /// `entrypoint` is the entrypoint as found in the binary the process was started from.
+    pub fn attach(
+        pid: u32,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+    ) -> Result<Self, Error> {
+        let pid = Pid::from_raw(pid as i32);
+        ptrace::attach(pid)?;
+
+        // Other signals may arrive before the stop caused by attaching, these are delivered
+        // once the tracee is resumed.
+        let pending = wait_attached(pid)?;
+
+        let tracee = Tracee { pid, tid: pid };
+        let bias = match entrypoint {
+            0 => 0,
+            _ => tracee.entrypoint()?.wrapping_sub(entrypoint),
+        };
+
+        let task = Task {
+            pending,
+            ..Task::default()
+        };
+
+        let mut debugger = Self {
+            tracee,
+            threads: BTreeMap::from([(pid, task)]),
+            breakpoints,
+            inserted: Inserted::default(),
+            bias,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            handle_pid: Arc::new(AtomicI32::new(pid.as_raw())),
+            watching: Vec::new(),
+            detaching: Arc::new(AtomicBool::new(false)),
+            attached: true,
+            report_syscall: None,
+            r_debug: None,
+            libraries: Vec::new(),
+            report_libraries: None,
+            follow: FollowFork::default(),
+            signals: Signals::default(),
+            forked: None,
+            children: Vec::new(),
+            sharing: false,
+            exited: false,
+        };
+
+        ptrace::setoptions(pid, debugger.options())?;
+
+        // Threads we haven't attached to yet can create new ones, so look again until there
+        // are no new threads.
+        loop {
+            let new: Vec<Pid> = tasks(pid)
+                .into_iter()
+                .filter(|tid| !debugger.threads.contains_key(tid))
+                .collect();
+
+            if new.is_empty() {
+                break;
+            }
+
+            for tid in new {
+                // Threads can exit before we get to them.
+                if ptrace::attach(tid).is_err() {
+                    continue;
+                }
+
+                if let Ok(pending) = wait_attached(tid) {
+                    let _ = ptrace::setoptions(tid, debugger.options());
+                    let task = Task {
+                        pending,
+                        ..Task::default()
+                    };
+                    debugger.threads.insert(tid, task);
+                }
+            }
+        }
+
+        debugger.watch_rendezvous(true)?;
+        debugger.breakpoints.take_dirty();
+        debugger.sync()?;
+        Ok(debugger)
+    }
+
+    /// Options of every thread, threads and children created later inherit them.
+    fn options(&self) -> ptrace::Options {
+        let mut options = ptrace::Options::PTRACE_O_TRACECLONE
+            | ptrace::Options::PTRACE_O_TRACEFORK
+            | ptrace::Options::PTRACE_O_TRACEVFORK
+            | ptrace::Options::PTRACE_O_TRACEVFORKDONE
+            | ptrace::Options::PTRACE_O_TRACEEXEC;
+        if !self.attached {
+            options |= ptrace::Options::PTRACE_O_EXITKILL;
+        }
+
+        // Tells syscall-stops apart from traps.
+        if self.report_syscall.is_some() {
+            options |= ptrace::Options::PTRACE_O_TRACESYSGOOD;
+        }
+
+        options
+    }
+
+    /// Address the current thread is stopped at, as found in the binary.
+    pub fn pc(&self) -> Result<usize, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        Ok(self.tracee.pc()?.wrapping_sub(self.bias))
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.tracee.pid.as_raw() as u32
+    }
+
+    /// Offset between addresses in the binary and where it's loaded.
+    pub fn bias(&self) -> usize {
+        self.bias
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle::Process(ProcessHandle {
+            pid: Arc::clone(&self.handle_pid),
+            interrupted: Arc::clone(&self.interrupted),
+            detaching: Arc::clone(&self.detaching),
+        })
+    }
+
+    /// Report every syscall the tracee makes to `report` once it returns, strace-style.
+    pub fn trace_syscalls<F>(&mut self, report: F) -> Result<(), Error>
+    where
+        F: FnMut(Syscall) + Send + 'static,
+    {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.report_syscall = Some(Box::new(report));
+        for &tid in self.threads.keys() {
+            ptrace::setoptions(tid, self.options())?;
+        }
+
+        Ok(())
+    }
+
+    /// Report the shared libraries loaded into the tracee to `report`, right away if there are
+    /// any and then each time the dynamic linker loads or unloads one.
+    pub fn watch_libraries<F>(&mut self, mut report: F) -> Result<(), Error>
+    where
+        F: FnMut(Vec<Library>) + Send + 'static,
+    {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        if !self.libraries.is_empty() {
+            report(self.libraries.clone());
+        }
+
+        self.report_libraries = Some(Box::new(report));
+        Ok(())
+    }
+
+    /// Shared libraries loaded into the tracee, in the order they were loaded.
+    pub fn libraries(&self) -> &[Library] {
+        &self.libraries
+    }
+
+    /// Stop at the breakpoint the dynamic linker calls whenever it changes the loaded
+    /// libraries. If it isn't set up yet, stop at the entrypoint first when `entry` is set, by
+    /// which the linker loaded the libraries the binary needs.
+    fn watch_rendezvous(&mut self, entry: bool) -> Result<(), Error> {
+        let phdrs = self.tracee.auxv(AT_PHDR)?;
+        let count = self.tracee.auxv(AT_PHNUM)?;
+        let r_debug = match (phdrs, count) {
+            (Some(phdrs), Some(count)) => rendezvous::find(&self.tracee, phdrs, count)?,
+            _ => None,
+        };
+
+        match r_debug {
+            Some(addr) => {
+                let brk = rendezvous::read(&self.tracee, addr)?.brk;
+                self.inserted.insert_internal(&self.tracee, brk)?;
+                self.r_debug = Some(addr);
+                self.load_libraries()
+            }
+            // Statically linked binaries don't have a dynamic linker.
+            None if entry => match self.tracee.auxv(AT_ENTRY)? {
+                Some(entrypoint) => self.inserted.insert_internal(&self.tracee, entrypoint),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Handle hitting one of our own breakpoints at runtime address `pc`.
+    fn internal_breakpoint(&mut self, pc: usize) -> Result<(), Error> {
+        if self.r_debug.is_some() {
+            return self.load_libraries();
+        }
+
+        // Reached the entrypoint, where the dynamic linker is done setting up.
+        self.inserted.remove_internal(pc);
+        self.watch_rendezvous(false)
+    }
+
+    /// Read the libraries the dynamic linker loaded, unless it's in the middle of changing
+    /// them. Breakpoints on functions of unloaded libraries become pending again, and pending
+    /// ones are set on the functions of libraries that got loaded.
+    fn load_libraries(&mut self) -> Result<(), Error> {
+        let r_debug = match self.r_debug {
+            Some(addr) => rendezvous::read(&self.tracee, addr)?,
+            None => return Ok(()),
+        };
+
+        if !r_debug.consistent {
+            return Ok(());
+        }
+
+        let objects = rendezvous::objects(&self.tracee, &r_debug)?;
+        let (kept, unloaded): (Vec<Library>, Vec<Library>) =
+            std::mem::take(&mut self.libraries).into_iter().partition(|library| {
+                objects.iter().any(|(path, bias)| *path == library.path && *bias == library.bias)
+            });
+
+        // The libraries are already unmapped, so their breakpoints can't be restored.
+        for library in &unloaded {
+            for (addr, name) in self.breakpoints.resolved() {
+                let runtime = addr.wrapping_add(self.bias);
+                if library.symbol(&name) == Some(runtime) {
+                    self.inserted.forget(runtime);
+                    self.breakpoints.unresolve(addr);
+                }
+            }
+        }
+
+        let loaded = objects.len() - kept.len();
+        self.libraries = objects
+            .into_iter()
+            .map(|(path, bias)| {
+                let library = Library::new(path, bias);
+                match kept.iter().find(|&kept| *kept == library) {
+                    Some(kept) => kept.clone(),
+                    None => library,
+                }
+            })
+            .collect();
+
+        if loaded == 0 && unloaded.is_empty() {
+            return Ok(());
+        }
+
+        self.resolve_pending();
+        if let Some(ref mut report) = self.report_libraries {
+            report(self.libraries.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Make the breakpoints on functions of libraries pending again, as they're loaded
+    /// elsewhere by the next process.
+    fn forget_libraries(&mut self) {
+        for (addr, _) in self.breakpoints.resolved() {
+            self.breakpoints.unresolve(addr);
+        }
+
+        self.r_debug = None;
+        self.libraries.clear();
+    }
+
+    /// Set the pending breakpoints on functions the loaded libraries define.
+    fn resolve_pending(&self) {
+        for name in self.breakpoints.pending() {
+            if let Some(addr) = self.libraries.iter().find_map(|library| library.symbol(&name)) {
+                // Breakpoints are kept by their address in the binary.
+                self.breakpoints.resolve(&name, addr.wrapping_sub(self.bias));
+            }
+        }
+    }
+
+    /// Threads of the stopped tracee, ordered by their id.
+    pub fn threads(&self) -> Result<Vec<Thread>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let pid = self.tracee.pid;
+        let threads = self
+            .threads
+            .iter()
+            .filter_map(|(&tid, task)| {
+                // Threads that exited whilst others still run can't be inspected.
+                let pc = self.tracee.thread(tid).pc().ok()?;
+                let name = std::fs::read_to_string(format!("/proc/{pid}/task/{tid}/comm"));
+
+                Some(Thread {
+                    tid: tid.as_raw() as u32,
+                    name: name.unwrap_or_default().trim_end().to_string(),
+                    pc: pc.wrapping_sub(self.bias),
+                    stop: task.stop,
+                    current: tid == self.tracee.tid,
+                })
+            })
+            .collect();
+
+        Ok(threads)
+    }
+
+    /// Make `tid` the thread that's stepped and whose registers and stack are inspected.
+    pub fn select_thread(&mut self, tid: u32) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let tid = Pid::from_raw(tid as i32);
+        if !self.threads.contains_key(&tid) || is_zombie(self.tracee.pid, tid) {
+            return Err(Error::NoSuchThread);
+        }
+
+        self.tracee.tid = tid;
+        Ok(())
+    }
+
+    /// Choose which process to debug after the tracee forks, applied to a fork the tracee is
+    /// stopped at as well.
+    pub fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        self.follow = follow;
+        Ok(())
+    }
+
+    /// Choose which signals the tracee stops for and which are passed on to it. Signals
+    /// received before are handled as they were.
+    pub fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        self.signals = signals;
+        Ok(())
+    }
+
+    /// Stop when `len` bytes at `addr`, as found in the binary, are accessed.
+    pub fn set_watchpoint(
+        &mut self,
+        addr: usize,
+        len: usize,
+        kind: WatchKind,
+    ) -> Result<(), Error> {
+        self.breakpoints.set_watchpoint(Watchpoint { addr, len, kind })?;
+        self.sync()
+    }
+
+    /// Apply the breakpoints and watchpoints of the shared [`BreakpointSet`].
+    fn sync(&mut self) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        if !self.sharing {
+            self.inserted.sync(&self.tracee, &self.breakpoints, self.bias, true)?;
+        }
+
+        let watchpoints = self.breakpoints.debug_registers();
+        if watchpoints == self.watching {
+            return Ok(());
+        }
+
+        self.watching = watchpoints;
+
+        // Each thread has it's own debug registers.
+        for &tid in self.threads.keys() {
+            let set = self.set_watchpoints(tid);
+
+            // Threads that exited whilst others still run can't be changed.
+            if tid == self.tracee.tid {
+                set?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Put the watchpoints being watched in the debug registers of thread `tid`.
+    fn set_watchpoints(&self, tid: Pid) -> Result<(), Error> {
+        let thread = self.tracee.thread(tid);
+
+        // Addresses can only be changed whilst the watchpoints are disabled.
+        thread.write_debugreg(DR7, 0)?;
+        for (idx, watchpoint) in self.watching.iter().enumerate() {
+            thread.write_debugreg(idx, watchpoint.addr.wrapping_add(self.bias) as u64)?;
+        }
+        thread.write_debugreg(DR7, breakpoint::dr7(&self.watching))
+    }
+
+    /// Debug register of the hardware breakpoint at runtime address `pc`, if there's one.
+    fn hardware_breakpoint(&self, pc: usize) -> Option<usize> {
+        self.watching.iter().position(|wp| {
+            wp.kind == WatchKind::Execute && wp.addr.wrapping_add(self.bias) == pc
+        })
+    }
+
+    /// Regions of memory mapped into the tracee.
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        memory_maps(self.tracee.pid)
+    }
+
+    /// Registers of the stopped tracee's current thread.
+    pub fn registers(&self) -> Result<Registers, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.read_registers()
+    }
+
+    /// Set a register of the stopped tracee's current thread, named as in [`Registers`].
+    pub fn set_register(&mut self, name: &str, value: u64) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.write_register(name, value)
+    }
+
+    /// Read the stopped tracee's memory at runtime address `addr`, without our breakpoints.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let mut bytes = read_memory(self.tracee.pid, addr, len)?;
+        self.inserted.hide(addr, &mut bytes);
+        Ok(bytes)
+    }
+
+    /// Write to the stopped tracee's memory at runtime address `addr`.
+    pub fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.inserted.write(&self.tracee, addr, bytes)
+    }
+
+    /// Frames on the stack of the stopped tracee's current thread, innermost first.
+    pub fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let regs = ptrace::getregs(self.tracee.tid)?;
+        let regs = Regs {
+            pc: regs.rip,
+            sp: regs.rsp,
+            fp: regs.rbp,
+        };
+
+        let pid = self.tracee.pid;
+        let read = |addr: u64| {
+            let bytes = read_memory(pid, addr as usize, 8).ok()?;
+            Some(u64::from_ne_bytes(bytes.try_into().ok()?))
+        };
+
+        Ok(unwind::unwind(cfi, regs, self.bias, read))
+    }
+
+    /// Runtime address the current thread faulted on, if it's stopped by a signal that reports
+    /// one, such as the memory a `SIGSEGV` was raised for.
+    pub fn fault_addr(&self) -> Result<Option<usize>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let info = ptrace::getsiginfo(self.tracee.tid)?;
+        let faults = [Signal::SIGSEGV, Signal::SIGBUS, Signal::SIGILL, Signal::SIGFPE];
+        if !faults.iter().any(|&sig| sig as i32 == info.si_signo) {
+            return Ok(None);
+        }
+
+        // SAFETY: Each of the signals above fills in the address.
+        Ok(Some(unsafe { info.si_addr() } as usize))
+    }
+
+    /// Resume all threads until the next breakpoint, signal or exit.
+    pub fn cont(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        loop {
+            if let Some(stop) = self.step_over_breakpoint()? {
+                return Ok(stop);
+            }
+
+            // Includes forks of the instruction that was stepped over.
+            self.follow_fork()?;
+
+            if self.breakpoints.take_dirty() {
+                self.resolve_pending();
+                self.sync()?;
+            }
+
+            // Threads that stopped for events we handle ourselves are resumed right away.
+            self.resume()?;
+            let stop = loop {
+                match self.wait(None, false)? {
+                    Some(stop) => break stop,
+                    None => self.resume()?,
+                }
+            };
+
+            if let Stop::Exited(..) | Stop::Killed(..) = stop {
+                return Ok(stop);
+            }
+
+            self.stop_others()?;
+
+            // Our own breakpoints are continued from, unless one of the set is there as well.
+            if let Stop::Breakpoint(addr) = stop {
+                let pc = addr.wrapping_add(self.bias);
+                if self.inserted.is_internal(pc) {
+                    self.internal_breakpoint(pc)?;
+                    if !self.breakpoints.wanted(true).contains(&addr) {
+                        continue;
+                    }
+                }
+            }
+
+            if let Stop::Forked(..) = stop {
+                if self.follow != FollowFork::Ask {
+                    continue;
+                }
+            }
+
+            if let Stop::Signal(sig, _) = stop {
+                if sig == Signal::SIGSTOP as i32 {
+                    // The tracee is detached from once we're dropped.
+                    if self.detaching.load(Ordering::Acquire) {
+                        return Err(Error::Detached);
+                    }
+
+                    // We stopped the tracee ourselves to apply breakpoints.
+                    if self.interrupted.swap(false, Ordering::AcqRel) {
+                        continue;
+                    }
+                }
+
+                // Signals that are passed on are delivered as the tracee is resumed.
+                if !self.signals.get(sig).stop {
+                    continue;
+                }
+            }
+
+            return Ok(stop);
+        }
+    }
+
+    /// Execute a single instruction of the current thread, the other threads stay stopped.
+    pub fn step(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.follow_fork()?;
+
+        if self.breakpoints.take_dirty() {
+            self.sync()?;
+        }
+
+        let pc = self.tracee.pc()?;
+        if self.inserted.contains(pc) || self.hardware_breakpoint(pc).is_some() {
+            return match self.step_over_breakpoint()? {
+                Some(stop) => Ok(stop),
+                None => Ok(Stop::Stepped(self.pc()?)),
+            };
+        }
+
+        match self.single_step()? {
+            Some(Stop::Signal(sig, addr)) if sig == Signal::SIGTRAP as i32 => {
+                Ok(Stop::Stepped(addr))
+            }
+            Some(stop) => Ok(stop),
+            None => Ok(Stop::Stepped(self.pc()?)),
+        }
+    }
+
+    /// Execute a single instruction of the current thread, returns None if the thread exited.
+    fn single_step(&mut self) -> Result<Option<Stop>, Error> {
+        let tid = self.tracee.tid;
+        loop {
+            let task = self.threads.get_mut(&tid).ok_or(Error::Exited)?;
+            ptrace::step(tid, task.pending.take())?;
+            task.running = true;
+            task.stop = None;
+
+            // Creating a thread is reported before the instruction finished. Signals that
+            // don't stop are delivered by stepping again.
+            match self.wait(Some(tid), true)? {
+                Some(Stop::Signal(sig, _))
+                    if sig != Signal::SIGTRAP as i32 && !self.signals.get(sig).stop => {}
+                Some(stop) => return Ok(Some(stop)),
+                None => {}
+            }
+
+            if !self.threads.contains_key(&tid) {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// If the current thread is stopped at one of our breakpoints, execute the original
+    /// instruction and put the breakpoint back.
+    fn step_over_breakpoint(&mut self) -> Result<Option<Stop>, Error> {
+        let pc = self.tracee.pc()?;
+        let hardware = self.hardware_breakpoint(pc);
+        if !self.inserted.contains(pc) && hardware.is_none() {
+            return Ok(None);
+        }
+
+        // Other threads stay stopped, so they can't run past the removed breakpoint. Hardware
+        // breakpoints would trigger again, so their debug register is disabled on this thread.
+        let dr7 = breakpoint::dr7(&self.watching);
+        match hardware {
+            Some(idx) => self.tracee.write_debugreg(DR7, dr7 & !(0b11 << (idx * 2)))?,
+            None => self.inserted.remove(&self.tracee, pc)?,
+        }
+
+        // Besides exiting, exec'ing or the stepped over instruction triggering a watchpoint,
+        // this is either the expected trap from stepping or a signal that's delivered when
+        // continuing. Forks are followed once the tracee is resumed.
+        let watchpoint = match self.single_step()? {
+            Some(stop @ (Stop::Exited(..) | Stop::Killed(..) | Stop::Exec(..))) => {
+                return Ok(Some(stop))
+            }
+            Some(stop @ Stop::Watchpoint(..)) => Some(stop),
+            _ => None,
+        };
+
+        if hardware.is_some() {
+            self.tracee.write_debugreg(DR7, dr7)?;
+        } else if self.inserted.is_internal(pc) {
+            self.inserted.insert_internal(&self.tracee, pc)?;
+        } else if self.breakpoints.contains(pc.wrapping_sub(self.bias)) {
+            self.inserted.insert(&self.tracee, pc)?;
+        }
+
+        Ok(watchpoint)
+    }
+
+    /// Resume every stopped thread, delivering their pending signals.
+    fn resume(&mut self) -> Result<(), Error> {
+        let trace_syscalls = self.report_syscall.is_some();
+        for (&tid, task) in self.threads.iter_mut().filter(|(_, task)| !task.running) {
+            let pending = task.pending.take();
+            let resumed = match trace_syscalls {
+                true => ptrace::syscall(tid, pending),
+                false => ptrace::cont(tid, pending),
+            };
+
+            match resumed {
+                // Threads can be killed whilst stopped, their exit is reported later on.
+                Ok(()) | Err(Errno::ESRCH) => task.running = true,
+                Err(err) => return Err(err.into()),
+            }
+
+            task.stop = None;
+        }
+
+        Ok(())
+    }
+
+    /// Stop the threads still running, so the tracee can be inspected and changed as a whole.
+    fn stop_others(&mut self) -> Result<(), Error> {
+        let pid = self.tracee.pid;
+        let running: Vec<Pid> = self
+            .threads
+            .iter()
+            .filter(|&(&tid, task)| task.running && !is_zombie(pid, tid))
+            .map(|(&tid, _)| tid)
+            .collect();
+
+        for &tid in &running {
+            // Unlike `kill`, this only stops the given thread.
+            let res = unsafe {
+                libc::syscall(libc::SYS_tgkill, pid.as_raw(), tid.as_raw(), libc::SIGSTOP)
+            };
+            if let Some(task) = self.threads.get_mut(&tid) {
+                task.stopping = res == 0;
+            }
+        }
+
+        for tid in running {
+            self.wait_stopped(tid)?;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for thread `tid` to stop after sending it a `SIGSTOP`, it may stop for another
+    /// reason first.
+    fn wait_stopped(&mut self, tid: Pid) -> Result<(), Error> {
+        let status = match waitpid(tid, wait_flags()) {
+            Ok(status) => status,
+            Err(Errno::ECHILD) => {
+                self.threads.remove(&tid);
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let task = match self.threads.get_mut(&tid) {
+            Some(task) => task,
+            None => return Ok(()),
+        };
+
+        task.running = false;
+        match status {
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => {
+                if let Some(syscall) = self.threads.remove(&tid).and_then(|task| task.syscall) {
+                    self.report(syscall);
+                }
+
+                // Only happens when the process is killed.
+                if tid == self.tracee.pid {
+                    self.exited = true;
+                }
+            }
+            WaitStatus::Stopped(_, Signal::SIGSTOP) if task.stopping => task.stopping = false,
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {
+                let thread = self.tracee.thread(tid);
+                let pc = thread.pc()?;
+
+                // A breakpoint hit at the same time is hit again once resumed.
+                let addr = pc.wrapping_sub(TRAP.len());
+                if self.inserted.contains(addr) {
+                    thread.set_pc(addr)?;
+                } else {
+                    let pc = pc.wrapping_sub(self.bias);
+                    task.stop = Some(Stop::Signal(Signal::SIGTRAP as i32, pc));
+                }
+            }
+            WaitStatus::Stopped(_, sig) => {
+                let pc = self.tracee.thread(tid).pc()?.wrapping_sub(self.bias);
+                if sig != Signal::SIGSTOP && self.signals.get(sig as i32).pass {
+                    task.pending = Some(sig);
+                }
+                task.stop = Some(Stop::Signal(sig as i32, pc));
+            }
+            WaitStatus::PtraceSyscall(_) => self.syscall_stop(tid)?,
+            WaitStatus::PtraceEvent(_, _, event) => {
+                if let Some(stop) = self.event(tid, event)? {
+                    if let Some(task) = self.threads.get_mut(&tid) {
+                        task.stop = Some(stop);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Start following the thread that thread `tid` created, which is left stopped.
+    fn cloned(&mut self, tid: Pid) -> Result<(), Error> {
+        let new = Pid::from_raw(ptrace::getevent(tid)? as i32);
+
+        // It's initial stop may have already been waited for.
+        if !self.threads.contains_key(&new) {
+            let pending = wait_attached(new)?;
+            self.add_thread(new, pending);
+        }
+
+        Ok(())
+    }
+
+    /// Follow a new thread that's stopped.
+    fn add_thread(&mut self, tid: Pid, pending: Option<Signal>) {
+        let task = Task {
+            pending,
+            ..Task::default()
+        };
+
+        self.threads.insert(tid, task);
+
+        // Debug registers aren't inherited.
+        if !self.watching.is_empty() {
+            let _ = self.set_watchpoints(tid);
+        }
+    }
+
+    /// Handle a ptrace event of thread `tid`, returns None if the thread should just be resumed.
+    fn event(&mut self, tid: Pid, event: i32) -> Result<Option<Stop>, Error> {
+        match event {
+            libc::PTRACE_EVENT_CLONE => self.cloned(tid)?,
+            libc::PTRACE_EVENT_FORK => return self.fork(tid, false),
+            libc::PTRACE_EVENT_VFORK => return self.fork(tid, true),
+            libc::PTRACE_EVENT_VFORK_DONE if self.sharing => {
+                // The child exec'd or exited, so the breakpoints can be put back.
+                self.sharing = false;
+                let thread = self.tracee.thread(tid);
+                self.inserted.sync(&thread, &self.breakpoints, self.bias, true)?;
+            }
+            libc::PTRACE_EVENT_EXEC => return self.exec().map(Some),
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    /// Stop the child thread `tid` forked, until it's decided which process is followed.
+    fn fork(&mut self, tid: Pid, vfork: bool) -> Result<Option<Stop>, Error> {
+        let pid = Pid::from_raw(ptrace::getevent(tid)? as i32);
+
+        // It's initial stop may have already been waited for.
+        let pending = match self.children.iter().position(|&child| child == pid) {
+            Some(idx) => {
+                self.children.remove(idx);
+                None
+            }
+            None => wait_attached(pid)?,
+        };
+
+        let fork = Fork {
+            pid,
+            pending,
+            vfork,
+        };
+
+        // Only a single fork is stopped at, others are let go.
+        if self.forked.is_some() {
+            self.release_child(fork)?;
+            return Ok(None);
+        }
+
+        self.forked = Some(fork);
+        let pc = self.tracee.thread(tid).pc()?.wrapping_sub(self.bias);
+        Ok(Some(Stop::Forked(pid.as_raw() as u32, pc)))
+    }
+
+    /// Follow the process chosen after the last fork, asking is the same as following the
+    /// parent once the tracee is resumed.
+    fn follow_fork(&mut self) -> Result<(), Error> {
+        match self.forked.take() {
+            Some(fork) if self.follow == FollowFork::Child => self.follow_child(fork),
+            Some(fork) => self.release_child(fork),
+            None => Ok(()),
+        }
+    }
+
+    /// Let a forked child run without the breakpoints it inherited.
+    fn release_child(&mut self, fork: Fork) -> Result<(), Error> {
+        if fork.vfork {
+            // Until the child exec's or exits, breakpoints can't be inserted in the memory it
+            // shares with the tracee.
+            self.inserted.remove_all(&self.tracee)?;
+            self.sharing = true;
+        } else {
+            // The child may have already been killed.
+            let _ = self.inserted.restore(&Tracee {
+                pid: fork.pid,
+                tid: fork.pid,
+            });
+        }
+
+        let _ = ptrace::detach(fork.pid, fork.pending);
+        Ok(())
+    }
+
+    /// Debug a forked child instead of the tracee, which is left running without our
+    /// breakpoints and watchpoints.
+    fn follow_child(&mut self, fork: Fork) -> Result<(), Error> {
+        if fork.vfork {
+            // The parent gets it's memory back once the child exec's, the breakpoints are
+            // inserted again into the child's new memory.
+            self.inserted.remove_all(&self.tracee)?;
+            self.sharing = true;
+        } else {
+            self.inserted.restore(&self.tracee)?;
+        }
+
+        self.report_unfinished();
+        for (&tid, task) in self.threads.iter_mut() {
+            if !self.watching.is_empty() {
+                let _ = self.tracee.thread(tid).write_debugreg(DR7, 0);
+            }
+
+            let _ = ptrace::detach(tid, task.pending.take());
+        }
+
+        let pid = fork.pid;
+        let task = Task {
+            pending: fork.pending,
+            ..Task::default()
+        };
+
+        self.tracee = Tracee { pid, tid: pid };
+        self.threads = BTreeMap::from([(pid, task)]);
+        self.handle_pid.store(pid.as_raw(), Ordering::Release);
+
+        // Debug registers aren't inherited.
+        if !self.watching.is_empty() {
+            self.set_watchpoints(pid)?;
+        }
+
+        Ok(())
+    }
+
+    /// Start debugging the binary the tracee exec'd, which replaced it's memory and threads.
+    fn exec(&mut self) -> Result<Stop, Error> {
+        let pid = self.tracee.pid;
+
+        // The thread that exec'd took over the id of the main thread.
+        self.threads.retain(|&tid, _| tid == pid);
+        self.tracee.tid = pid;
+
+        // The breakpoints and debug registers are gone along with the old binary.
+        self.inserted = Inserted::default();
+        self.sharing = false;
+        self.watching.clear();
+
+        self.bias = match binary_entrypoint(pid) {
+            0 => 0,
+            entrypoint => self.tracee.entrypoint()?.wrapping_sub(entrypoint),
+        };
+
+        // The new binary's libraries are loaded from scratch.
+        self.forget_libraries();
+        if let Some(ref mut report) = self.report_libraries {
+            report(Vec::new());
+        }
+        self.watch_rendezvous(true)?;
+
+        Ok(Stop::Exec(pid.as_raw() as u32, self.pc()?))
+    }
+
+    /// Checks whether the last trap came from a watchpoint or hardware breakpoint, clearing it's
+    /// status.
+    fn triggered_watchpoint(&mut self) -> Result<Option<Stop>, Error> {
+        if self.watching.is_empty() {
+            return Ok(None);
+        }
+
+        let dr6 = self.tracee.read_debugreg(DR6)?;
+        let hit = (0..self.watching.len()).find(|&idx| dr6 & (1u64 << idx) != 0);
+        let hit = match hit {
+            Some(idx) => self.watching[idx],
+            None => return Ok(None),
+        };
+
+        // The status isn't cleared by the processor.
+        self.tracee.write_debugreg(DR6, 0)?;
+
+        // Hardware breakpoints trigger before the instruction executed.
+        if hit.kind == WatchKind::Execute {
+            return Ok(Some(Stop::Breakpoint(hit.addr)));
+        }
+
+        let pc = self.tracee.pc()?.wrapping_sub(self.bias);
+        Ok(Some(Stop::Watchpoint(hit.addr, pc)))
+    }
+
+    /// Decode the syscall thread `tid` entered, or report it once it leaves the syscall.
+    fn syscall_stop(&mut self, tid: Pid) -> Result<(), Error> {
+        let regs = ptrace::getregs(tid)?;
+        let pid = self.tracee.pid;
+        let read = |addr: usize, len: usize| read_memory(pid, addr, len).unwrap_or_default();
+
+        let task = match self.threads.get_mut(&tid) {
+            Some(task) => task,
+            None => return Ok(()),
+        };
+
+        match task.syscall.take() {
+            Some(mut syscall) => {
+                syscall.exit(regs.rax as i64, &read);
+                self.report(syscall);
+            }
+            None if regs.rax as i64 == ENOSYS => {
+                let args = [regs.rdi, regs.rsi, regs.rdx, regs.r10, regs.r8, regs.r9];
+                task.syscall = Some(Syscall::enter(regs.orig_rax, args, &read));
+            }
+            // Leaving a syscall that was entered before we started tracing syscalls.
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    fn report(&mut self, syscall: Syscall) {
+        if let Some(ref mut report) = self.report_syscall {
+            report(syscall);
+        }
+    }
+
+    /// Report the syscalls threads are in the middle of, as syscalls such as `exit` never
+    /// return.
+    fn report_unfinished(&mut self) {
+        let syscalls: Vec<Syscall> =
+            self.threads.values_mut().filter_map(|task| task.syscall.take()).collect();
+
+        for syscall in syscalls {
+            self.report(syscall);
+        }
+    }
+
+    /// Wait for thread `tid`, or any thread if None, to change state. Returns None if the
+    /// thread should just be resumed.
+    ///
+    /// Traps are from single stepping if `stepping`, otherwise from our breakpoints.
+    fn wait(&mut self, tid: Option<Pid>, stepping: bool) -> Result<Option<Stop>, Error> {
+        let status = waitpid(tid, wait_flags())?;
+        let tid = match status.pid() {
+            Some(tid) => tid,
+            None => return Ok(None),
+        };
+
+        match status {
+            WaitStatus::Exited(_, code) if tid == self.tracee.pid => {
+                self.exited = true;
+                self.report_unfinished();
+                return Ok(Some(Stop::Exited(code)));
+            }
+            WaitStatus::Signaled(_, sig, _) if tid == self.tracee.pid => {
+                self.exited = true;
+                self.report_unfinished();
+                return Ok(Some(Stop::Killed(sig as i32)));
+            }
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => {
+                if let Some(syscall) = self.threads.remove(&tid).and_then(|task| task.syscall) {
+                    self.report(syscall);
+                }
+
+                if self.tracee.tid == tid {
+                    self.tracee.tid = self.tracee.pid;
+                }
+
+                return Ok(None);
+            }
+            _ => {}
+        }
+
+        let task = match self.threads.get_mut(&tid) {
+            Some(task) => task,
+            // New threads and children can stop before their creation is reported.
+            None => {
+                if let WaitStatus::Stopped(_, Signal::SIGSTOP) = status {
+                    match is_thread(self.tracee.pid, tid) {
+                        true => self.add_thread(tid, None),
+                        false => self.children.push(tid),
+                    }
+                }
+
+                return Ok(None);
+            }
+        };
+
+        task.running = false;
+        let stop = match status {
+            WaitStatus::PtraceSyscall(_) => {
+                self.syscall_stop(tid)?;
+                return Ok(None);
+            }
+            WaitStatus::PtraceEvent(_, _, event) => match self.event(tid, event)? {
+                Some(stop) => {
+                    self.tracee.tid = tid;
+                    stop
+                }
+                None => return Ok(None),
+            },
+            // Left over from stopping the other threads.
+            WaitStatus::Stopped(_, Signal::SIGSTOP) if task.stopping => {
+                task.stopping = false;
+                return Ok(None);
+            }
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {
+                self.tracee.tid = tid;
+                match self.triggered_watchpoint()? {
+                    Some(stop) => stop,
+                    None => {
+                        let pc = self.tracee.pc()?;
+
+                        // The trap already executed, leaving the pc after it.
+                        let addr = pc.wrapping_sub(TRAP.len());
+                        if !stepping && self.inserted.contains(addr) {
+                            self.tracee.set_pc(addr)?;
+                            Stop::Breakpoint(addr.wrapping_sub(self.bias))
+                        } else {
+                            Stop::Signal(Signal::SIGTRAP as i32, pc.wrapping_sub(self.bias))
+                        }
+                    }
+                }
+            }
+            WaitStatus::Stopped(_, sig) => {
+                if sig != Signal::SIGSTOP && self.signals.get(sig as i32).pass {
+                    task.pending = Some(sig);
+                }
+
+                self.tracee.tid = tid;
+                Stop::Signal(sig as i32, self.tracee.pc()?.wrapping_sub(self.bias))
+            }
+            _ => return Ok(None),
+        };
+
+        if let Some(task) = self.threads.get_mut(&tid) {
+            task.stop = Some(stop);
+        }
+
+        Ok(Some(stop))
+    }
+}
+
+impl Drop for Debugger {
+    fn drop(&mut self) {
+        self.forget_libraries();
+        if self.exited {
+            return;
+        }
+
+        if let Some(fork) = self.forked.take() {
+            let _ = self.release_child(fork);
+        }
+
+        for pid in std::mem::take(&mut self.children) {
+            let _ = self.release_child(Fork {
+                pid,
+                pending: None,
+                vfork: false,
+            });
+        }
+
+        if self.attached {
+            // Leave the process as we found it.
+            let _ = self.inserted.remove_all(&self.tracee);
+            if !self.watching.is_empty() {
+                for &tid in self.threads.keys() {
+                    let _ = self.tracee.thread(tid).write_debugreg(DR7, 0);
+                }
+            }
+
+            for (&tid, task) in self.threads.iter_mut() {
+                let _ = ptrace::detach(tid, task.pending.take());
+            }
+        } else {
+            let _ = signal::kill(self.tracee.pid, Signal::SIGKILL);
+
+            // Threads of the process have to be reaped before the process itself.
+            while let Ok(status) = waitpid(None::<Pid>, wait_flags()) {
+                if let WaitStatus::Exited(pid, _) | WaitStatus::Signaled(pid, ..) = status {
+                    if pid == self.tracee.pid {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Controls a running [`Debugger`] from other threads.
+#[derive(Clone)]
+pub struct ProcessHandle {
+    pid: Arc<AtomicI32>,
+    interrupted: Arc<AtomicBool>,
+    detaching: Arc<AtomicBool>,
+}
+
+impl ProcessHandle {
+    fn pid(&self) -> Pid {
+        Pid::from_raw(self.pid.load(Ordering::Acquire))
+    }
+
+    /// Stop the tracee so changes to the breakpoints are applied, it's resumed right after.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+        let _ = signal::kill(self.pid(), Signal::SIGSTOP);
+    }
+
+    /// Stop the tracee, which is reported as it receiving `SIGSTOP`.
+    pub fn pause(&self) {
+        let _ = signal::kill(self.pid(), Signal::SIGSTOP);
+    }
+
+    pub fn kill(&self) {
+        let _ = signal::kill(self.pid(), Signal::SIGKILL);
+    }
+
+    /// Stop tracing the running tracee, leaving it running.
+    pub fn detach(&self) {
+        self.detaching.store(true, Ordering::Release);
+        let _ = signal::kill(self.pid(), Signal::SIGSTOP);
+    }
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        memory_maps(self.pid())
+    }
+
+    /// Read the tracee's memory at runtime address `addr`.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        read_memory(self.pid(), addr, len)
+    }
+}
@@ -0,0 +1,1421 @@
+//! Debugging on macOS, execution is controlled through ptrace whilst memory and registers are
+//! accessed through the task's mach port.
+//!
+//! Getting the task port of another process requires running as root or being signed with the
+//! `com.apple.security.cs.debugger` entitlement, and the process not using the hardened runtime.
+//! Watchpoints are only supported on x86_64.
+
+use crate::breakpoint::{Inserted, Memory};
+use crate::maps::Permissions;
+use crate::{
+    BreakpointSet, CallFrameInfo, Environment, Error, FollowFork, Frame, Handle, Library,
+    MemoryRegion, ProcessInfo, Registers, Signals, Stdio, Stop, Syscall, Thread, WatchKind,
+    Watchpoint,
+};
+use mach2::kern_return::{kern_return_t, KERN_INVALID_ADDRESS, KERN_SUCCESS};
+use mach2::mach_port::mach_port_deallocate;
+use mach2::message::mach_msg_type_number_t;
+use mach2::port::{mach_port_t, MACH_PORT_NULL};
+use mach2::task::task_threads;
+use mach2::thread_act::{thread_get_state, thread_resume, thread_set_state, thread_suspend};
+use mach2::traps::{mach_task_self, task_for_pid};
+use mach2::vm::{
+    mach_vm_deallocate, mach_vm_protect, mach_vm_read_overwrite, mach_vm_region, mach_vm_write,
+};
+use mach2::vm_prot::{vm_prot_t, VM_PROT_COPY, VM_PROT_EXECUTE, VM_PROT_READ, VM_PROT_WRITE};
+use mach2::vm_region::{vm_region_basic_info_64, VM_REGION_BASIC_INFO_64};
+use nix::errno::Errno;
+use nix::libc::{self, c_int, c_void};
+use nix::sys::ptrace;
+use nix::sys::signal::{self, Signal};
+use nix::sys::wait::{waitpid, WaitStatus};
+use nix::unistd::{chdir, execve, fork, ForkResult, Pid};
+use std::ffi::{CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Largest path returned by `proc_pidpath` and `proc_regionfilename`.
+const PROC_PIDPATHINFO_MAXSIZE: usize = 4096;
+
+/// Granularity of reads that stop at the first unmapped byte.
+const PAGE_SIZE: usize = 0x1000;
+
+/// Magic of a 64-bit Mach-O header.
+const MH_MAGIC_64: u32 = 0xfeedfacf;
+
+/// File type of a Mach-O executable.
+const MH_EXECUTE: u32 = 2;
+
+/// Load command of a 64-bit segment.
+const LC_SEGMENT_64: u32 = 0x19;
+
+/// How far the pc is past a trap when it's reported, `brk` doesn't advance it.
+#[cfg(target_arch = "x86_64")]
+const TRAP_ADVANCE: usize = crate::breakpoint::TRAP.len();
+
+/// How far the pc is past a trap when it's reported, `brk` doesn't advance it.
+#[cfg(target_arch = "aarch64")]
+const TRAP_ADVANCE: usize = 0;
+
+/// How the tracee ended if `status` is it exiting or being killed.
+fn ended(status: WaitStatus) -> Option<Stop> {
+    match status {
+        WaitStatus::Exited(_, code) => Some(Stop::Exited(code)),
+        WaitStatus::Signaled(_, sig, _) => Some(Stop::Killed(sig as i32)),
+        _ => None,
+    }
+}
+
+fn kern(ret: kern_return_t) -> Result<(), Error> {
+    match ret {
+        KERN_SUCCESS => Ok(()),
+        _ => Err(Error::Mach(ret)),
+    }
+}
+
+/// State of a thread as read through `thread_get_state`, laid out like the kernel's structs.
+trait ThreadFlavor: Default {
+    const FLAVOR: c_int;
+}
+
+fn get_state<S: ThreadFlavor>(thread: mach_port_t) -> Result<S, Error> {
+    let mut state = S::default();
+    let mut count = (std::mem::size_of::<S>() / 4) as mach_msg_type_number_t;
+    kern(unsafe {
+        thread_get_state(thread, S::FLAVOR, &mut state as *mut S as *mut _, &mut count)
+    })?;
+    Ok(state)
+}
+
+fn set_state<S: ThreadFlavor>(thread: mach_port_t, state: &S) -> Result<(), Error> {
+    let count = (std::mem::size_of::<S>() / 4) as mach_msg_type_number_t;
+    kern(unsafe { thread_set_state(thread, S::FLAVOR, state as *const S as *mut _, count) })
+}
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use super::{get_state, set_state, ThreadFlavor};
+    use crate::unwind::{self, Regs};
+    use crate::{CallFrameInfo, Error, Frame, Registers, Watchpoint, X86_FLAGS};
+    use mach2::port::mach_port_t;
+
+    /// Trap flag in `rflags`, raising a trap after every instruction.
+    const TF: u64 = 1 << 8;
+
+    /// `x86_thread_state64_t`.
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct ThreadState {
+        rax: u64,
+        rbx: u64,
+        rcx: u64,
+        rdx: u64,
+        rdi: u64,
+        rsi: u64,
+        rbp: u64,
+        rsp: u64,
+        r8: u64,
+        r9: u64,
+        r10: u64,
+        r11: u64,
+        r12: u64,
+        r13: u64,
+        r14: u64,
+        r15: u64,
+        rip: u64,
+        rflags: u64,
+        cs: u64,
+        fs: u64,
+        gs: u64,
+    }
+
+    impl ThreadFlavor for ThreadState {
+        const FLAVOR: i32 = 4;
+    }
+
+    /// `x86_debug_state64_t`.
+    #[repr(C)]
+    #[derive(Default)]
+    struct DebugState {
+        dr: [u64; 8],
+    }
+
+    impl ThreadFlavor for DebugState {
+        const FLAVOR: i32 = 11;
+    }
+
+    impl ThreadState {
+        pub fn pc(&self) -> usize {
+            self.rip as usize
+        }
+
+        pub fn set_pc(&mut self, pc: usize) {
+            self.rip = pc as u64;
+        }
+
+        pub fn registers(&self) -> Registers {
+            let general = vec![
+                ("rax", self.rax),
+                ("rbx", self.rbx),
+                ("rcx", self.rcx),
+                ("rdx", self.rdx),
+                ("rsi", self.rsi),
+                ("rdi", self.rdi),
+                ("rbp", self.rbp),
+                ("rsp", self.rsp),
+                ("r8", self.r8),
+                ("r9", self.r9),
+                ("r10", self.r10),
+                ("r11", self.r11),
+                ("r12", self.r12),
+                ("r13", self.r13),
+                ("r14", self.r14),
+                ("r15", self.r15),
+                ("rip", self.rip),
+                ("rflags", self.rflags),
+            ];
+
+            let flags = X86_FLAGS
+                .iter()
+                .map(|&(name, bit)| (name, self.rflags & (1 << bit) != 0))
+                .collect();
+
+            let segments = vec![("cs", self.cs), ("fs", self.fs), ("gs", self.gs)];
+
+            Registers {
+                general,
+                flags,
+                segments,
+            }
+        }
+
+        /// Register named as in [`ThreadState::registers`].
+        pub fn register_mut(&mut self, name: &str) -> Option<&mut u64> {
+            Some(match name {
+                "rax" => &mut self.rax,
+                "rbx" => &mut self.rbx,
+                "rcx" => &mut self.rcx,
+                "rdx" => &mut self.rdx,
+                "rsi" => &mut self.rsi,
+                "rdi" => &mut self.rdi,
+                "rbp" => &mut self.rbp,
+                "rsp" => &mut self.rsp,
+                "r8" => &mut self.r8,
+                "r9" => &mut self.r9,
+                "r10" => &mut self.r10,
+                "r11" => &mut self.r11,
+                "r12" => &mut self.r12,
+                "r13" => &mut self.r13,
+                "r14" => &mut self.r14,
+                "r15" => &mut self.r15,
+                "rip" => &mut self.rip,
+                "rflags" => &mut self.rflags,
+                "cs" => &mut self.cs,
+                "fs" => &mut self.fs,
+                "gs" => &mut self.gs,
+                _ => return None,
+            })
+        }
+
+        pub fn backtrace<F>(&self, cfi: &CallFrameInfo, bias: usize, read: F) -> Vec<Frame>
+        where
+            F: Fn(u64) -> Option<u64>,
+        {
+            let regs = Regs {
+                pc: self.rip,
+                sp: self.rsp,
+                fp: self.rbp,
+            };
+
+            unwind::unwind(cfi, regs, bias, read)
+        }
+    }
+
+    /// Raise a trap after the next instruction `thread` executes.
+    pub fn set_single_step(thread: mach_port_t, enable: bool) -> Result<(), Error> {
+        let mut state: ThreadState = get_state(thread)?;
+        match enable {
+            true => state.rflags |= TF,
+            false => state.rflags &= !TF,
+        }
+        set_state(thread, &state)
+    }
+
+    /// Put runtime addresses of `watchpoints` in the debug registers of `thread`.
+    pub fn set_watchpoints(thread: mach_port_t, watchpoints: &[Watchpoint]) -> Result<(), Error> {
+        let mut state = DebugState::default();
+        for (idx, watchpoint) in watchpoints.iter().enumerate() {
+            state.dr[idx] = watchpoint.addr as u64;
+        }
+
+        state.dr[7] = crate::breakpoint::dr7(watchpoints);
+        set_state(thread, &state)
+    }
+
+    /// Index of the watchpoint that made `thread` trap, clearing it's status.
+    pub fn triggered_watchpoint(thread: mach_port_t, len: usize) -> Result<Option<usize>, Error> {
+        let mut state: DebugState = get_state(thread)?;
+        let dr6 = state.dr[6];
+        let hit = (0..len).find(|&idx| dr6 & (1u64 << idx) != 0);
+
+        // The status isn't cleared by the processor.
+        if hit.is_some() {
+            state.dr[6] = 0;
+            set_state(thread, &state)?;
+        }
+
+        Ok(hit)
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    use super::{get_state, set_state, ThreadFlavor};
+    use crate::{CallFrameInfo, Error, Frame, Registers, Watchpoint};
+    use mach2::port::mach_port_t;
+
+    /// Most frames walked, in case the frame pointers form a cycle.
+    const MAX_FRAMES: usize = 256;
+
+    /// Software step bit of `mdscr_el1`.
+    const MDSCR_SS: u64 = 1;
+
+    /// Condition flags in `cpsr` by their bit.
+    const FLAGS: [(&str, u32); 4] = [("N", 31), ("Z", 30), ("C", 29), ("V", 28)];
+
+    const GENERAL: [&str; 29] = [
+        "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+        "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26",
+        "x27", "x28",
+    ];
+
+    /// `arm_thread_state64_t`.
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct ThreadState {
+        x: [u64; 29],
+        fp: u64,
+        lr: u64,
+        sp: u64,
+        pc: u64,
+        cpsr: u32,
+        flags: u32,
+    }
+
+    impl ThreadFlavor for ThreadState {
+        const FLAVOR: i32 = 6;
+    }
+
+    /// `arm_debug_state64_t`.
+    #[repr(C)]
+    #[derive(Default)]
+    struct DebugState {
+        bvr: [u64; 16],
+        bcr: [u64; 16],
+        wvr: [u64; 16],
+        wcr: [u64; 16],
+        mdscr_el1: u64,
+    }
+
+    impl ThreadFlavor for DebugState {
+        const FLAVOR: i32 = 15;
+    }
+
+    impl ThreadState {
+        pub fn pc(&self) -> usize {
+            self.pc as usize
+        }
+
+        pub fn set_pc(&mut self, pc: usize) {
+            self.pc = pc as u64;
+        }
+
+        pub fn registers(&self) -> Registers {
+            let mut general: Vec<(&'static str, u64)> = GENERAL.into_iter().zip(self.x).collect();
+            general.extend([("fp", self.fp), ("lr", self.lr), ("sp", self.sp), ("pc", self.pc)]);
+
+            let flags = FLAGS
+                .iter()
+                .map(|&(name, bit)| (name, self.cpsr & (1 << bit) != 0))
+                .collect();
+
+            Registers {
+                general,
+                flags,
+                segments: Vec::new(),
+            }
+        }
+
+        /// Register named as in [`ThreadState::registers`].
+        pub fn register_mut(&mut self, name: &str) -> Option<&mut u64> {
+            match name {
+                "fp" => Some(&mut self.fp),
+                "lr" => Some(&mut self.lr),
+                "sp" => Some(&mut self.sp),
+                "pc" => Some(&mut self.pc),
+                _ => {
+                    let idx = GENERAL.iter().position(|&reg| reg == name)?;
+                    Some(&mut self.x[idx])
+                }
+            }
+        }
+
+        /// Walk the frame records, which are always kept on macOS.
+        pub fn backtrace<F>(&self, _cfi: &CallFrameInfo, bias: usize, read: F) -> Vec<Frame>
+        where
+            F: Fn(u64) -> Option<u64>,
+        {
+            let mut frames = vec![Frame {
+                pc: (self.pc as usize).wrapping_sub(bias),
+                sp: self.sp as usize,
+            }];
+
+            // Each record holds the caller's frame pointer followed by the return address.
+            let mut fp = self.fp;
+            while fp != 0 && frames.len() < MAX_FRAMES {
+                let (caller_fp, ret) = match (read(fp), read(fp + 8)) {
+                    (Some(caller_fp), Some(ret)) if ret != 0 => (caller_fp, ret),
+                    _ => break,
+                };
+
+                frames.push(Frame {
+                    pc: (ret as usize).wrapping_sub(bias),
+                    sp: fp as usize + 16,
+                });
+
+                // Callers' records are further up the stack.
+                if caller_fp <= fp {
+                    break;
+                }
+
+                fp = caller_fp;
+            }
+
+            frames
+        }
+    }
+
+    /// Raise a trap after the next instruction `thread` executes.
+    pub fn set_single_step(thread: mach_port_t, enable: bool) -> Result<(), Error> {
+        let mut state: DebugState = get_state(thread)?;
+        match enable {
+            true => state.mdscr_el1 |= MDSCR_SS,
+            false => state.mdscr_el1 &= !MDSCR_SS,
+        }
+        set_state(thread, &state)
+    }
+
+    pub fn set_watchpoints(_thread: mach_port_t, watchpoints: &[Watchpoint]) -> Result<(), Error> {
+        match watchpoints.is_empty() {
+            true => Ok(()),
+            false => Err(Error::Unsupported),
+        }
+    }
+
+    pub fn triggered_watchpoint(_thread: mach_port_t, _len: usize) -> Result<Option<usize>, Error> {
+        Ok(None)
+    }
+}
+
+use arch::ThreadState;
+
+/// Start and length of the region at or after `addr`, followed by it's basic info.
+fn region(task: mach_port_t, addr: usize) -> Option<(usize, usize, vm_region_basic_info_64)> {
+    let mut start = addr as u64;
+    let mut size = 0;
+    let mut info: vm_region_basic_info_64 = unsafe { std::mem::zeroed() };
+    let mut count = (std::mem::size_of_val(&info) / 4) as mach_msg_type_number_t;
+    let mut object_name: mach_port_t = MACH_PORT_NULL;
+
+    let ret = unsafe {
+        mach_vm_region(
+            task,
+            &mut start,
+            &mut size,
+            VM_REGION_BASIC_INFO_64,
+            &mut info as *mut _ as *mut c_int,
+            &mut count,
+            &mut object_name,
+        )
+    };
+
+    match ret {
+        KERN_SUCCESS => Some((start as usize, size as usize, info)),
+        _ => None,
+    }
+}
+
+fn region_path(pid: Pid, addr: usize) -> Option<String> {
+    let mut path = vec![0u8; PROC_PIDPATHINFO_MAXSIZE];
+    let len = unsafe {
+        libc::proc_regionfilename(
+            pid.as_raw(),
+            addr as u64,
+            path.as_mut_ptr() as *mut c_void,
+            path.len() as u32,
+        )
+    };
+
+    if len <= 0 {
+        return None;
+    }
+
+    path.truncate(len as usize);
+    String::from_utf8(path).ok()
+}
+
+fn memory_maps(pid: Pid, task: mach_port_t) -> Result<Vec<MemoryRegion>, Error> {
+    let mut regions = Vec::new();
+    let mut addr = 0;
+
+    while let Some((start, len, info)) = region(task, addr) {
+        let protection = info.protection;
+        let perms = Permissions {
+            read: protection & VM_PROT_READ != 0,
+            write: protection & VM_PROT_WRITE != 0,
+            exec: protection & VM_PROT_EXECUTE != 0,
+            shared: info.shared != 0,
+        };
+
+        regions.push(MemoryRegion {
+            start,
+            end: start + len,
+            perms,
+            offset: info.offset,
+            path: region_path(pid, start),
+        });
+
+        addr = start + len;
+    }
+
+    Ok(regions)
+}
+
+/// Read `len` bytes at `addr`, stopping early at the first byte that isn't mapped.
+fn read_memory(task: mach_port_t, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![0; len];
+    let mut read = 0;
+
+    while read < len {
+        let addr = addr + read;
+        let chunk = (len - read).min(PAGE_SIZE - addr % PAGE_SIZE);
+        let mut n = 0;
+
+        let ret = unsafe {
+            mach_vm_read_overwrite(
+                task,
+                addr as u64,
+                chunk as u64,
+                bytes[read..].as_mut_ptr() as u64,
+                &mut n,
+            )
+        };
+
+        match kern(ret) {
+            Ok(()) => read += n as usize,
+            // Unmapped memory can't be read.
+            Err(_) if read != 0 => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    bytes.truncate(read);
+    Ok(bytes)
+}
+
+/// Executable a running process was started from.
+pub fn exe_path(pid: u32) -> Result<PathBuf, Error> {
+    let mut path = vec![0u8; PROC_PIDPATHINFO_MAXSIZE];
+    let len = unsafe {
+        libc::proc_pidpath(pid as c_int, path.as_mut_ptr() as *mut c_void, path.len() as u32)
+    };
+
+    if len <= 0 {
+        return Err(Error::Sys(Errno::last()));
+    }
+
+    path.truncate(len as usize);
+    Ok(PathBuf::from(OsStr::from_bytes(&path)))
+}
+
+/// Processes running on the system, ordered by their pid.
+pub fn processes() -> Vec<ProcessInfo> {
+    // The number of processes can grow between both calls.
+    let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+    if count <= 0 {
+        return Vec::new();
+    }
+
+    let mut pids = vec![0 as c_int; count as usize + 32];
+    let size = (pids.len() * std::mem::size_of::<c_int>()) as c_int;
+    let count = unsafe { libc::proc_listallpids(pids.as_mut_ptr() as *mut c_void, size) };
+    pids.truncate(count.max(0) as usize);
+
+    let mut processes: Vec<ProcessInfo> = pids
+        .into_iter()
+        .filter(|&pid| pid > 0)
+        .filter_map(|pid| {
+            let mut name = [0u8; 256];
+            let len = unsafe {
+                libc::proc_name(pid, name.as_mut_ptr() as *mut c_void, name.len() as u32)
+            };
+
+            if len <= 0 {
+                return None;
+            }
+
+            Some(ProcessInfo {
+                pid: pid as u32,
+                name: String::from_utf8_lossy(&name[..len as usize]).into_owned(),
+                path: exe_path(pid as u32).ok(),
+            })
+        })
+        .collect();
+
+    processes.sort_unstable_by_key(|process| process.pid);
+    processes
+}
+
+/// A stopped process we're attached to.
+pub(crate) struct Tracee {
+    pid: Pid,
+    task: mach_port_t,
+    /// Thread that stopped last, registers are read from and written to it.
+    thread: mach_port_t,
+}
+
+impl Tracee {
+    fn new(pid: Pid) -> Result<Self, Error> {
+        let mut task = MACH_PORT_NULL;
+        let ret = unsafe { task_for_pid(mach_task_self(), pid.as_raw(), &mut task) };
+
+        // Only fails when we're not allowed to control the process.
+        if ret != KERN_SUCCESS {
+            return Err(Error::Sys(Errno::EPERM));
+        }
+
+        let mut tracee = Self {
+            pid,
+            task,
+            thread: MACH_PORT_NULL,
+        };
+
+        let threads = tracee.threads()?;
+        tracee.select(&threads, threads.first().copied());
+        Ok(tracee)
+    }
+
+    /// Threads of the task, each has to be deallocated once no longer used.
+    fn threads(&self) -> Result<Vec<mach_port_t>, Error> {
+        let mut list = std::ptr::null_mut();
+        let mut count = 0;
+        kern(unsafe { task_threads(self.task, &mut list, &mut count) })?;
+
+        let threads = unsafe { std::slice::from_raw_parts(list, count as usize).to_vec() };
+        let size = count as usize * std::mem::size_of::<mach_port_t>();
+        unsafe { mach_vm_deallocate(mach_task_self(), list as u64, size as u64) };
+
+        Ok(threads)
+    }
+
+    /// Make `thread`, one of `threads`, the current thread and deallocate the others.
+    fn select(&mut self, threads: &[mach_port_t], thread: Option<mach_port_t>) {
+        for &other in threads {
+            if Some(other) != thread {
+                unsafe { mach_port_deallocate(mach_task_self(), other) };
+            }
+        }
+
+        if let Some(thread) = thread {
+            if self.thread != MACH_PORT_NULL {
+                unsafe { mach_port_deallocate(mach_task_self(), self.thread) };
+            }
+
+            self.thread = thread;
+        }
+    }
+
+    fn state(&self) -> Result<ThreadState, Error> {
+        get_state(self.thread)
+    }
+
+    fn pc(&self) -> Result<usize, Error> {
+        Ok(self.state()?.pc())
+    }
+
+    fn set_pc(&self, pc: usize) -> Result<(), Error> {
+        let mut state = self.state()?;
+        state.set_pc(pc);
+        set_state(self.thread, &state)
+    }
+
+    pub fn read_registers(&self) -> Result<Registers, Error> {
+        Ok(self.state()?.registers())
+    }
+
+    /// Set a general purpose or segment register, named as in [`Tracee::read_registers`].
+    pub fn write_register(&self, name: &str, value: u64) -> Result<(), Error> {
+        let mut state = self.state()?;
+        *state.register_mut(name).ok_or(Error::InvalidRegister)? = value;
+        set_state(self.thread, &state)
+    }
+
+    /// Runtime address of the executable's Mach-O header minus where it expects to be loaded.
+    fn slide(&self) -> Result<usize, Error> {
+        let mut addr = 0;
+        while let Some((start, len, _)) = region(self.task, addr) {
+            addr = start + len;
+
+            let mut header = [0; 32];
+            if self.read(start, &mut header).is_err() {
+                continue;
+            }
+
+            let word = |off: usize| u32::from_le_bytes(header[off..off + 4].try_into().unwrap());
+            if word(0) != MH_MAGIC_64 || word(12) != MH_EXECUTE {
+                continue;
+            }
+
+            let mut commands = vec![0; word(20) as usize];
+            self.read(start + header.len(), &mut commands)?;
+
+            // The header is at the start of the __TEXT segment.
+            let mut offset = 0;
+            while offset + 40 <= commands.len() {
+                let command = &commands[offset..];
+                let cmd = u32::from_le_bytes(command[..4].try_into().unwrap());
+                let size = u32::from_le_bytes(command[4..8].try_into().unwrap()) as usize;
+
+                if cmd == LC_SEGMENT_64 && command[8..24].starts_with(b"__TEXT\0") {
+                    let vmaddr = u64::from_le_bytes(command[24..32].try_into().unwrap());
+                    return Ok(start.wrapping_sub(vmaddr as usize));
+                }
+
+                if size == 0 {
+                    break;
+                }
+
+                offset += size;
+            }
+
+            return Ok(0);
+        }
+
+        Ok(0)
+    }
+}
+
+impl Memory for Tracee {
+    type Error = Error;
+
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), Error> {
+        let mut read = 0;
+        kern(unsafe {
+            mach_vm_read_overwrite(
+                self.task,
+                addr as u64,
+                buf.len() as u64,
+                buf.as_mut_ptr() as u64,
+                &mut read,
+            )
+        })
+    }
+
+    fn write(&self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        let protection: vm_prot_t = match region(self.task, addr) {
+            Some((start, _, info)) if start <= addr => info.protection,
+            _ => return Err(Error::Mach(KERN_INVALID_ADDRESS)),
+        };
+
+        // Code is mapped read-only, so it's pages are replaced by a writable private copy.
+        let writable = protection & VM_PROT_WRITE != 0;
+        if !writable {
+            let copy = VM_PROT_READ | VM_PROT_WRITE | VM_PROT_COPY;
+            kern(unsafe { mach_vm_protect(self.task, addr as u64, bytes.len() as u64, 0, copy) })?;
+        }
+
+        let written = kern(unsafe {
+            mach_vm_write(self.task, addr as u64, bytes.as_ptr() as _, bytes.len() as u32)
+        });
+
+        if !writable {
+            let len = bytes.len() as u64;
+            kern(unsafe { mach_vm_protect(self.task, addr as u64, len, 0, protection) })?;
+        }
+
+        written
+    }
+}
+
+impl Drop for Tracee {
+    fn drop(&mut self) {
+        unsafe {
+            mach_port_deallocate(mach_task_self(), self.thread);
+            mach_port_deallocate(mach_task_self(), self.task);
+        }
+    }
+}
+
+pub struct Debugger {
+    tracee: Tracee,
+    breakpoints: Arc<BreakpointSet>,
+    inserted: Inserted,
+    /// Offset between addresses in the binary and where it's loaded.
+    bias: usize,
+    /// Signal to deliver when resuming.
+    pending: Option<Signal>,
+    /// Set by [`Handle::interrupt`] to tell apart our own stops.
+    interrupted: Arc<AtomicBool>,
+    /// Watchpoints in the debug registers, in the order of DR0 to DR3.
+    watching: Vec<Watchpoint>,
+    /// Set by [`Handle::detach`] to stop tracing whilst the tracee runs.
+    detaching: Arc<AtomicBool>,
+    /// Whether we attached to an already running process, which is left running once dropped.
+    attached: bool,
+    exited: bool,
+}
+
+impl Debugger {
+    /// Start `path` with `args` set up as `env` describes, stopped before it's first instruction
+    /// executes. It's standard streams are the pipes of `stdio` if given, otherwise they're ours.
+    /// Address space randomization can't be turned off for a forked process, so it's left on.
+    ///
+    /// Where the binary got loaded is read from it's Mach-O header, so `entrypoint` is only
+    /// used for telling whether it's relocatable.
+    pub fn spawn(
+        path: &Path,
+        args: &[String],
+        env: &Environment,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        stdio: Option<Stdio>,
+    ) -> Result<Self, Error> {
+        let path = CString::new(path.as_os_str().as_bytes()).map_err(|_| Error::InvalidArgs)?;
+        let mut argv = vec![path.clone()];
+        for arg in args {
+            argv.push(CString::new(arg.as_bytes()).map_err(|_| Error::InvalidArgs)?);
+        }
+
+        let envp = env.envp()?;
+        let input = env.prepare()?;
+        let cwd = match env.cwd {
+            Some(ref cwd) => {
+                Some(CString::new(cwd.as_os_str().as_bytes()).map_err(|_| Error::InvalidArgs)?)
+            }
+            None => None,
+        };
+
+        // SAFETY: The child only performs async-signal-safe calls before exec'ing.
+        let pid = match unsafe { fork()? } {
+            ForkResult::Child => {
+                if let Some(ref stdio) = stdio {
+                    stdio.redirect();
+                }
+
+                if let Some(ref input) = input {
+                    crate::stdio::redirect_stdin(input);
+                }
+
+                if let Some(ref cwd) = cwd {
+                    if chdir(cwd.as_c_str()).is_err() {
+                        unsafe { nix::libc::_exit(127) }
+                    }
+                }
+
+                let _ = ptrace::traceme();
+                let _ = execve(&path, &argv, &envp);
+                unsafe { nix::libc::_exit(127) }
+            }
+            ForkResult::Parent { child } => child,
+        };
+
+        // The child's ends are only open in the child, so it closing them ends the pipes.
+        drop(stdio);
+        drop(input);
+
+        // The child stops once it exec'd.
+        match waitpid(pid, None)? {
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {}
+            _ => return Err(Error::Exited),
+        }
+
+        let tracee = match Tracee::new(pid) {
+            Ok(tracee) => tracee,
+            Err(err) => {
+                let _ = ptrace::kill(pid);
+                let _ = waitpid(pid, None);
+                return Err(err);
+            }
+        };
+
+        let bias = match entrypoint {
+            0 => 0,
+            _ => tracee.slide()?,
+        };
+
+        let mut debugger = Self {
+            tracee,
+            breakpoints,
+            inserted: Inserted::default(),
+            bias,
+            pending: None,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            watching: Vec::new(),
+            detaching: Arc::new(AtomicBool::new(false)),
+            attached: false,
+            exited: false,
+        };
+
+        debugger.breakpoints.take_dirty();
+        debugger.sync()?;
+        Ok(debugger)
+    }
+
+    /// Start tracing the running process `pid`, which is stopped until [`Debugger::cont`].
+    ///
+    /// `entrypoint` is the entrypoint as found in the binary the process was started from.
+    pub fn attach(
+        pid: u32,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+    ) -> Result<Self, Error> {
+        let pid = Pid::from_raw(pid as i32);
+        let tracee = Tracee::new(pid)?;
+        ptrace::attach(pid)?;
+
+        // Other signals may arrive before the stop caused by attaching, these are delivered
+        // once the tracee is resumed.
+        let mut pending = None;
+        loop {
+            match waitpid(pid, None)? {
+                WaitStatus::Stopped(_, Signal::SIGSTOP) => break,
+                WaitStatus::Stopped(_, sig) => {
+                    pending = Some(sig);
+                    ptrace::cont(pid, None)?;
+                }
+                _ => return Err(Error::Exited),
+            }
+        }
+
+        let bias = match entrypoint {
+            0 => 0,
+            _ => tracee.slide()?,
+        };
+
+        let mut debugger = Self {
+            tracee,
+            breakpoints,
+            inserted: Inserted::default(),
+            bias,
+            pending,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            watching: Vec::new(),
+            detaching: Arc::new(AtomicBool::new(false)),
+            attached: true,
+            exited: false,
+        };
+
+        debugger.breakpoints.take_dirty();
+        debugger.sync()?;
+        Ok(debugger)
+    }
+
+    /// Address the tracee is stopped at, as found in the binary.
+    pub fn pc(&self) -> Result<usize, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        Ok(self.tracee.pc()?.wrapping_sub(self.bias))
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.tracee.pid.as_raw() as u32
+    }
+
+    /// Offset between addresses in the binary and where it's loaded.
+    pub fn bias(&self) -> usize {
+        self.bias
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle::Process(ProcessHandle {
+            pid: self.tracee.pid,
+            task: self.tracee.task,
+            interrupted: Arc::clone(&self.interrupted),
+            detaching: Arc::clone(&self.detaching),
+        })
+    }
+
+    /// Syscalls can't be traced through ptrace on macOS.
+    pub fn trace_syscalls<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Syscall) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    /// Only the dynamic linker of Linux is followed as it loads libraries.
+    pub fn watch_libraries<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Vec<Library>) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    /// Stop when `len` bytes at `addr`, as found in the binary, are accessed.
+    pub fn set_watchpoint(
+        &mut self,
+        addr: usize,
+        len: usize,
+        kind: WatchKind,
+    ) -> Result<(), Error> {
+        self.breakpoints.set_watchpoint(Watchpoint { addr, len, kind })?;
+        self.sync()
+    }
+
+    /// Apply the breakpoints and watchpoints of the shared [`BreakpointSet`].
+    fn sync(&mut self) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        // Hardware breakpoints are inserted as traps, the debug registers are only used for
+        // watchpoints.
+        self.inserted.sync(&self.tracee, &self.breakpoints, self.bias, false)?;
+
+        let watchpoints = self.breakpoints.watchpoints();
+        if watchpoints == self.watching {
+            return Ok(());
+        }
+
+        let runtime: Vec<Watchpoint> = watchpoints
+            .iter()
+            .map(|&wp| Watchpoint {
+                addr: wp.addr.wrapping_add(self.bias),
+                ..wp
+            })
+            .collect();
+
+        // Each thread has it's own debug registers.
+        let threads = self.tracee.threads()?;
+        let applied =
+            threads.iter().try_for_each(|&thread| arch::set_watchpoints(thread, &runtime));
+        for thread in threads {
+            unsafe { mach_port_deallocate(mach_task_self(), thread) };
+        }
+
+        applied?;
+        self.watching = watchpoints;
+        Ok(())
+    }
+
+    /// Regions of memory mapped into the tracee.
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        memory_maps(self.tracee.pid, self.tracee.task)
+    }
+
+    /// Registers of the stopped tracee's current thread.
+    pub fn registers(&self) -> Result<Registers, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.read_registers()
+    }
+
+    /// Set a register of the stopped tracee's current thread, named as in [`Registers`].
+    pub fn set_register(&mut self, name: &str, value: u64) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.write_register(name, value)
+    }
+
+    /// Read the stopped tracee's memory at runtime address `addr`, without our breakpoints.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let mut bytes = read_memory(self.tracee.task, addr, len)?;
+        self.inserted.hide(addr, &mut bytes);
+        Ok(bytes)
+    }
+
+    /// Write to the stopped tracee's memory at runtime address `addr`.
+    pub fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.inserted.write(&self.tracee, addr, bytes)
+    }
+
+    /// Runtime address the current thread faulted on. Signals are only seen through `waitpid`,
+    /// which doesn't tell, so there never is one.
+    pub fn fault_addr(&self) -> Result<Option<usize>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        Ok(None)
+    }
+
+    /// Frames on the stack of the stopped tracee's current thread, innermost first.
+    pub fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let task = self.tracee.task;
+        let read = |addr: u64| {
+            let bytes = read_memory(task, addr as usize, 8).ok()?;
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        };
+
+        Ok(self.tracee.state()?.backtrace(cfi, self.bias, read))
+    }
+
+    /// Mach ports aren't stable thread ids, so threads can't be listed.
+    pub fn threads(&self) -> Result<Vec<Thread>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn select_thread(&mut self, _tid: u32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Children aren't debugged, so only following the parent after a fork is supported.
+    pub fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        match follow {
+            FollowFork::Parent => Ok(()),
+            FollowFork::Child | FollowFork::Ask => Err(Error::Unsupported),
+        }
+    }
+
+    /// Only linux stops for and passes on signals as they're configured.
+    pub fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        match signals.is_default() {
+            true => Ok(()),
+            false => Err(Error::Unsupported),
+        }
+    }
+
+    /// Resume execution until the next breakpoint, signal or exit.
+    pub fn cont(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        loop {
+            if let Some(stop) = self.step_over_breakpoint()? {
+                return Ok(stop);
+            }
+
+            if self.breakpoints.take_dirty() {
+                self.sync()?;
+            }
+
+            ptrace::cont(self.tracee.pid, self.pending.take())?;
+
+            match self.wait(false)? {
+                Some(Stop::Signal(sig, _)) if sig == Signal::SIGSTOP as i32 => {
+                    // The tracee is detached from once we're dropped.
+                    if self.detaching.load(Ordering::Acquire) {
+                        return Err(Error::Detached);
+                    }
+
+                    // We stopped the tracee ourselves to apply breakpoints.
+                    if self.interrupted.swap(false, Ordering::AcqRel) {
+                        continue;
+                    }
+
+                    return Ok(Stop::Signal(sig, self.tracee.pc()?.wrapping_sub(self.bias)));
+                }
+                Some(stop) => return Ok(stop),
+                None => continue,
+            }
+        }
+    }
+
+    /// Execute a single instruction of the current thread.
+    pub fn step(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        if self.breakpoints.take_dirty() {
+            self.sync()?;
+        }
+
+        if self.inserted.contains(self.tracee.pc()?) {
+            return match self.step_over_breakpoint()? {
+                Some(stop) => Ok(stop),
+                None => Ok(Stop::Stepped(self.pc()?)),
+            };
+        }
+
+        match self.single_step()? {
+            Some(Stop::Signal(sig, addr)) if sig == Signal::SIGTRAP as i32 => {
+                Ok(Stop::Stepped(addr))
+            }
+            Some(stop) => Ok(stop),
+            None => Ok(Stop::Stepped(self.pc()?)),
+        }
+    }
+
+    /// Execute a single instruction of the current thread, whilst the other threads are
+    /// suspended.
+    ///
+    /// `PT_STEP` always steps the process' first thread, so the current thread is stepped by
+    /// setting it's trace flag instead.
+    fn single_step(&mut self) -> Result<Option<Stop>, Error> {
+        let thread = self.tracee.thread;
+        let threads = self.tracee.threads()?;
+        let others: Vec<mach_port_t> = threads.iter().copied().filter(|&t| t != thread).collect();
+
+        for &other in &others {
+            unsafe { thread_suspend(other) };
+        }
+
+        let stop = arch::set_single_step(thread, true)
+            .and_then(|_| Ok(ptrace::cont(self.tracee.pid, self.pending.take())?))
+            .and_then(|_| self.wait(true));
+
+        if !self.exited {
+            let _ = arch::set_single_step(thread, false);
+        }
+
+        for &other in &others {
+            unsafe { thread_resume(other) };
+        }
+
+        for thread in threads {
+            unsafe { mach_port_deallocate(mach_task_self(), thread) };
+        }
+
+        stop
+    }
+
+    /// If stopped at one of our breakpoints, execute the original instruction and put the
+    /// breakpoint back.
+    fn step_over_breakpoint(&mut self) -> Result<Option<Stop>, Error> {
+        let pc = self.tracee.pc()?;
+        if !self.inserted.contains(pc) {
+            return Ok(None);
+        }
+
+        self.inserted.remove(&self.tracee, pc)?;
+
+        // Besides exiting or the stepped over instruction triggering a watchpoint, this is either
+        // the expected trap from stepping or a signal that's delivered when continuing.
+        let watchpoint = match self.single_step()? {
+            Some(stop @ (Stop::Exited(..) | Stop::Killed(..))) => return Ok(Some(stop)),
+            Some(stop @ Stop::Watchpoint(..)) => Some(stop),
+            _ => None,
+        };
+
+        if self.breakpoints.contains(pc.wrapping_sub(self.bias)) {
+            self.inserted.insert(&self.tracee, pc)?;
+        }
+
+        Ok(watchpoint)
+    }
+
+    /// Checks whether the last trap came from a watchpoint, making the thread that triggered it
+    /// the current thread.
+    fn triggered_watchpoint(&mut self) -> Result<Option<Stop>, Error> {
+        if self.watching.is_empty() {
+            return Ok(None);
+        }
+
+        let threads = self.tracee.threads()?;
+        let mut hit = None;
+        for &thread in &threads {
+            if let Some(idx) = arch::triggered_watchpoint(thread, self.watching.len())? {
+                hit = Some((thread, self.watching[idx]));
+                break;
+            }
+        }
+
+        let (thread, watchpoint) = match hit {
+            Some(hit) => hit,
+            None => {
+                self.tracee.select(&threads, None);
+                return Ok(None);
+            }
+        };
+
+        self.tracee.select(&threads, Some(thread));
+        let pc = self.tracee.pc()?.wrapping_sub(self.bias);
+        Ok(Some(Stop::Watchpoint(watchpoint.addr, pc)))
+    }
+
+    /// Make the thread that hit one of our breakpoints the current thread, otherwise the
+    /// process' first thread.
+    fn select_thread(&mut self) -> Result<(), Error> {
+        let threads = self.tracee.threads()?;
+        let hit = threads.iter().copied().find(|&thread| match get_state::<ThreadState>(thread) {
+            Ok(state) => self.inserted.contains(state.pc().wrapping_sub(TRAP_ADVANCE)),
+            Err(_) => false,
+        });
+
+        self.tracee.select(&threads, hit.or(threads.first().copied()));
+        Ok(())
+    }
+
+    /// Wait for the tracee to change state, returns None if it should just be resumed.
+    ///
+    /// Traps are from single stepping if `stepping`, otherwise from our breakpoints.
+    fn wait(&mut self, stepping: bool) -> Result<Option<Stop>, Error> {
+        let status = waitpid(self.tracee.pid, None)?;
+        if let Some(stop) = ended(status) {
+            self.exited = true;
+            return Ok(Some(stop));
+        }
+
+        let stop = match status {
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {
+                if let Some(stop) = self.triggered_watchpoint()? {
+                    return Ok(Some(stop));
+                }
+
+                // The stepped thread stays the current thread.
+                if !stepping {
+                    self.select_thread()?;
+                }
+
+                let pc = self.tracee.pc()?;
+
+                // On x86 the trap already executed, leaving the pc after it.
+                let addr = pc.wrapping_sub(TRAP_ADVANCE);
+                if !stepping && self.inserted.contains(addr) {
+                    self.tracee.set_pc(addr)?;
+                    return Ok(Some(Stop::Breakpoint(addr.wrapping_sub(self.bias))));
+                }
+
+                Stop::Signal(Signal::SIGTRAP as i32, pc.wrapping_sub(self.bias))
+            }
+            WaitStatus::Stopped(_, sig) => {
+                if sig != Signal::SIGSTOP {
+                    self.pending = Some(sig);
+                }
+
+                Stop::Signal(sig as i32, self.tracee.pc()?.wrapping_sub(self.bias))
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(stop))
+    }
+}
+
+impl Drop for Debugger {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+
+        if self.attached {
+            // Leave the process as we found it.
+            let _ = self.inserted.remove_all(&self.tracee);
+            if !self.watching.is_empty() {
+                if let Ok(threads) = self.tracee.threads() {
+                    for thread in threads {
+                        let _ = arch::set_watchpoints(thread, &[]);
+                        unsafe { mach_port_deallocate(mach_task_self(), thread) };
+                    }
+                }
+            }
+
+            let _ = ptrace::detach(self.tracee.pid, self.pending.take());
+        } else {
+            let _ = ptrace::kill(self.tracee.pid);
+            let _ = waitpid(self.tracee.pid, None);
+        }
+    }
+}
+
+/// Controls a running [`Debugger`] from other threads.
+#[derive(Clone)]
+pub struct ProcessHandle {
+    pid: Pid,
+    task: mach_port_t,
+    interrupted: Arc<AtomicBool>,
+    detaching: Arc<AtomicBool>,
+}
+
+impl ProcessHandle {
+    /// Stop the tracee so changes to the breakpoints are applied, it's resumed right after.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+        let _ = signal::kill(self.pid, Signal::SIGSTOP);
+    }
+
+    /// Stop the tracee, which is reported as it receiving `SIGSTOP`.
+    pub fn pause(&self) {
+        let _ = signal::kill(self.pid, Signal::SIGSTOP);
+    }
+
+    pub fn kill(&self) {
+        let _ = signal::kill(self.pid, Signal::SIGKILL);
+    }
+
+    /// Stop tracing the running tracee, leaving it running.
+    pub fn detach(&self) {
+        self.detaching.store(true, Ordering::Release);
+        let _ = signal::kill(self.pid, Signal::SIGSTOP);
+    }
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        memory_maps(self.pid, self.task)
+    }
+
+    /// Read the tracee's memory at runtime address `addr`.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        read_memory(self.task, addr, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers() {
+        let mut state = ThreadState::default();
+        let names: Vec<&str> = {
+            let regs = state.registers();
+            regs.general.iter().chain(&regs.segments).map(|&(name, _)| name).collect()
+        };
+
+        // Every register that's shown can be set, without changing any other.
+        for (value, name) in names.iter().enumerate() {
+            *state.register_mut(name).unwrap() = value as u64 + 1;
+        }
+
+        let regs = state.registers();
+        let values: Vec<u64> = regs.general.iter().chain(&regs.segments).map(|r| r.1).collect();
+        assert_eq!(values, (1..=names.len() as u64).collect::<Vec<_>>());
+        assert!(state.register_mut("xmm0").is_none());
+
+        state.set_pc(0x1000);
+        assert_eq!(state.pc(), 0x1000);
+    }
+
+    #[test]
+    fn stops() {
+        let pid = Pid::from_raw(1);
+        assert_eq!(ended(WaitStatus::Exited(pid, 3)), Some(Stop::Exited(3)));
+        assert_eq!(ended(WaitStatus::Signaled(pid, Signal::SIGKILL, false)), Some(Stop::Killed(9)));
+        assert_eq!(ended(WaitStatus::Stopped(pid, Signal::SIGTRAP)), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! Memory regions mapped into a process.
+
+use std::fmt;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub exec: bool,
+    /// Whether writes are visible to other processes mapping the same memory.
+    pub shared: bool,
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, chr: char| if set { chr } else { '-' };
+
+        f.write_fmt(format_args!(
+            "{}{}{}{}",
+            flag(self.read, 'r'),
+            flag(self.write, 'w'),
+            flag(self.exec, 'x'),
+            if self.shared { 's' } else { 'p' },
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    pub start: usize,
+    pub end: usize,
+    pub perms: Permissions,
+    /// Offset into the backing file.
+    pub offset: u64,
+    /// File backing the region or a pseudo-path such as `[heap]` or `[stack]`.
+    pub path: Option<String>,
+}
+
+impl MemoryRegion {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, addr: usize) -> bool {
+        (self.start..self.end).contains(&addr)
+    }
+}
+
+/// Parse a line of `/proc/<pid>/maps`, e.g.
+/// `7f2c1e000000-7f2c1e022000 r-xp 00002000 08:01 1234   /usr/lib/libc.so.6`.
+fn parse_line(line: &str) -> Option<MemoryRegion> {
+    let mut rest = line;
+    let mut next_field = || {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(' ').unwrap_or(trimmed.len());
+        let (field, remaining) = trimmed.split_at(end);
+        rest = remaining;
+        Some(field).filter(|field| !field.is_empty())
+    };
+
+    let (start, end) = next_field()?.split_once('-')?;
+    let start = usize::from_str_radix(start, 16).ok()?;
+    let end = usize::from_str_radix(end, 16).ok()?;
+
+    let perms = next_field()?.as_bytes();
+    if perms.len() != 4 {
+        return None;
+    }
+
+    let perms = Permissions {
+        read: perms[0] == b'r',
+        write: perms[1] == b'w',
+        exec: perms[2] == b'x',
+        shared: perms[3] == b's',
+    };
+
+    let offset = u64::from_str_radix(next_field()?, 16).ok()?;
+
+    // Device and inode.
+    next_field()?;
+    next_field()?;
+
+    // Paths can contain spaces, so take whatever remains.
+    let path = rest.trim();
+    let path = (!path.is_empty()).then(|| path.to_string());
+
+    Some(MemoryRegion {
+        start,
+        end,
+        perms,
+        offset,
+        path,
+    })
+}
+
+/// Parse the contents of `/proc/<pid>/maps`, skipping malformed lines.
+pub fn parse(maps: &str) -> Vec<MemoryRegion> {
+    maps.lines().filter_map(parse_line).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions() {
+        let maps = "\
+55d0c5a00000-55d0c5a02000 r--p 00000000 08:01 1234                       /usr/bin/cat
+55d0c5a02000-55d0c5a07000 r-xp 00002000 08:01 1234                       /usr/bin/cat
+55d0c6c1b000-55d0c6c3c000 rw-p 00000000 00:00 0                          [heap]
+7f2c1e000000-7f2c1e001000 rw-s 00000000 00:05 42                         /tmp/some file
+7ffd2bb5d000-7ffd2bb5f000 rw-p 00000000 00:00 0
+garbage";
+
+        let regions = parse(maps);
+        assert_eq!(regions.len(), 5);
+
+        assert_eq!(regions[1].start, 0x55d0c5a02000);
+        assert_eq!(regions[1].len(), 0x5000);
+        assert_eq!(regions[1].offset, 0x2000);
+        assert_eq!(regions[1].perms.to_string(), "r-xp");
+        assert_eq!(regions[1].path.as_deref(), Some("/usr/bin/cat"));
+
+        assert_eq!(regions[2].path.as_deref(), Some("[heap]"));
+        assert!(regions[3].perms.shared);
+        assert_eq!(regions[3].path.as_deref(), Some("/tmp/some file"));
+        assert_eq!(regions[4].path, None);
+    }
+}
@@ -0,0 +1,777 @@
+//! Targets debugged through a stub speaking the GDB remote serial protocol, such as qemu,
+//! gdbserver or a probe attached to an embedded device.
+//!
+//! Only all-stop mode is supported. Registers are read as described by the stub, falling back
+//! to gdb's amd64 layout for stubs that don't describe them, and are assumed to be little-endian.
+
+use crate::rsp::{self, Framing, Incoming};
+use crate::unwind::{self, Regs};
+use crate::{
+    BreakpointSet, CallFrameInfo, Error, FollowFork, Frame, Handle, Library, MemoryRegion,
+    Registers, Signals, Stop, Syscall, Target, Thread, WatchKind, Watchpoint, X86_FLAGS,
+};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::io::{BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Most bytes read or written by a single packet.
+const MAX_TRANSFER: usize = 0x800;
+
+/// Signal stubs report traps with, as numbered by gdb.
+const SIGTRAP: u8 = 5;
+
+/// Registers shown with the segments instead of the general purpose registers.
+const SEGMENTS: [&str; 8] = ["cs", "ss", "ds", "es", "fs", "gs", "fs_base", "gs_base"];
+
+/// Names stubs give the instruction pointer, in the order they're looked for.
+const PC_NAMES: [&str; 3] = ["rip", "eip", "pc"];
+
+/// A register as described by the stub.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Register {
+    name: &'static str,
+    /// Width in bytes.
+    size: usize,
+}
+
+/// Register names have to outlive the connection as [`Registers`] borrows them, so every
+/// distinct name is leaked once.
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+    let mut names = NAMES.lock().unwrap();
+    match names.get(name) {
+        Some(&name) => name,
+        None => {
+            let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.insert(name);
+            name
+        }
+    }
+}
+
+/// Value of the attribute `key` of an xml tag, e.g. `name` in `reg name="rip" bitsize="64"`.
+fn attr<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("{key}=\"");
+    let (idx, _) = tag
+        .match_indices(&pattern)
+        .find(|&(idx, _)| tag[..idx].ends_with(char::is_whitespace))?;
+
+    let value = &tag[idx + pattern.len()..];
+    Some(&value[..value.find('"')?])
+}
+
+/// Registers of the target description `xml` by their number, with descriptions it includes
+/// read through `include`.
+fn parse_description<F>(
+    xml: &str,
+    include: &mut F,
+    regs: &mut Vec<(usize, Register)>,
+) -> Result<(), Error>
+where
+    F: FnMut(&str) -> Result<String, Error>,
+{
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        let tag = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        if let Some(attrs) = tag.strip_prefix("xi:include") {
+            if let Some(href) = attr(attrs, "href") {
+                parse_description(&include(href)?, include, regs)?;
+            }
+            continue;
+        }
+
+        let attrs = match tag.strip_prefix("reg") {
+            Some(attrs) if attrs.starts_with(char::is_whitespace) => attrs,
+            _ => continue,
+        };
+
+        let (name, bits) = match (attr(attrs, "name"), attr(attrs, "bitsize")) {
+            (Some(name), Some(bits)) => (name, bits.parse::<usize>().unwrap_or(0)),
+            _ => continue,
+        };
+
+        // Registers are numbered in order unless told otherwise.
+        let num = match attr(attrs, "regnum").and_then(|num| num.parse().ok()) {
+            Some(num) => num,
+            None => regs.last().map_or(0, |&(num, _)| num + 1),
+        };
+
+        let reg = Register {
+            name: intern(name),
+            size: bits / 8,
+        };
+        regs.push((num, reg));
+    }
+
+    Ok(())
+}
+
+/// Values of the registers `regs` in the reply to a `g` packet, leaving out registers that
+/// are unavailable or wider than 64 bits.
+fn register_values(regs: &[Register], reply: &str) -> Vec<(&'static str, u64)> {
+    let mut values = Vec::new();
+    let mut offset = 0;
+
+    for reg in regs {
+        let hex = match reply.get(offset..offset + reg.size * 2) {
+            Some(hex) => hex,
+            None => break,
+        };
+
+        offset += reg.size * 2;
+        if let Some(value) = rsp::unhex(hex).as_deref().and_then(rsp::register_value) {
+            values.push((reg.name, value));
+        }
+    }
+
+    values
+}
+
+/// Turn replies to requests that only acknowledge them into errors.
+fn ok(reply: String) -> Result<(), Error> {
+    match reply.as_str() {
+        "OK" => Ok(()),
+        "" => Err(Error::Unsupported),
+        _ => Err(Error::Protocol(reply)),
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    framing: Framing,
+    /// Whether packets are acknowledged, which stubs let us turn off.
+    ack: bool,
+}
+
+impl Connection {
+    fn send(&mut self, packet: &str) -> Result<(), Error> {
+        Ok(self.stream.write_all(&rsp::frame(packet))?)
+    }
+
+    fn receive(&mut self) -> Result<String, Error> {
+        let mut byte = [0];
+        loop {
+            self.reader.read_exact(&mut byte)?;
+            match self.framing.feed(byte[0]) {
+                Some(Incoming::Packet(packet)) => {
+                    if self.ack {
+                        self.stream.write_all(b"+")?;
+                    }
+                    return Ok(packet);
+                }
+                Some(Incoming::Corrupt) => self.stream.write_all(b"-")?,
+                _ => {}
+            }
+        }
+    }
+
+    fn request(&mut self, packet: &str) -> Result<String, Error> {
+        self.send(packet)?;
+        self.receive()
+    }
+
+    /// Wait for the target to stop, skipping the output stubs forward whilst it runs.
+    fn stop_reply(&mut self) -> Result<String, Error> {
+        loop {
+            let reply = self.receive()?;
+            if !reply.starts_with('O') || reply == "OK" {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// Read the target description `annex`, such as `target.xml`.
+    fn description(&mut self, annex: &str) -> Result<String, Error> {
+        let mut xml = String::new();
+        loop {
+            let packet = format!("qXfer:features:read:{annex}:{:x},{MAX_TRANSFER:x}", xml.len());
+            let reply = self.request(&packet)?;
+
+            match reply.split_at(reply.len().min(1)) {
+                ("m", data) => xml.push_str(data),
+                ("l", data) => {
+                    xml.push_str(data);
+                    return Ok(xml);
+                }
+                ("", _) => return Err(Error::Unsupported),
+                _ => return Err(Error::Protocol(reply)),
+            }
+        }
+    }
+}
+
+/// A target stopped by a remote stub.
+pub struct RemoteTarget {
+    conn: RefCell<Connection>,
+    breakpoints: Arc<BreakpointSet>,
+    /// Breakpoints inserted by the stub, by their runtime address.
+    inserted: BTreeSet<usize>,
+    /// Watchpoints and hardware breakpoints inserted by the stub.
+    watching: Vec<Watchpoint>,
+    /// Registers in the order of the `g` packet.
+    registers: Vec<Register>,
+    /// Length of a breakpoint instruction, as the stub wants to know.
+    breakpoint_kind: usize,
+    /// Offset between addresses in the binary and where it's loaded.
+    bias: usize,
+    /// Copy of the connection for interrupting the running target.
+    interrupter: Arc<TcpStream>,
+    /// Set by [`RemoteHandle::interrupt`] to tell apart our own stops.
+    interrupted: Arc<AtomicBool>,
+    /// Set by [`RemoteHandle::detach`] to stop debugging whilst the target runs.
+    detaching: Arc<AtomicBool>,
+    /// Set by [`RemoteHandle::kill`].
+    killing: Arc<AtomicBool>,
+    exited: bool,
+}
+
+impl RemoteTarget {
+    /// Connect to the stub listening at `addr`, e.g. `localhost:1234`, leaving the target
+    /// stopped where it is.
+    pub fn connect(addr: &str, breakpoints: Arc<BreakpointSet>) -> Result<Self, Error> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nodelay(true)?;
+
+        let interrupter = Arc::new(stream.try_clone()?);
+        let mut conn = Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            stream,
+            framing: Framing::default(),
+            ack: true,
+        };
+
+        let supported = conn.request("qSupported:swbreak+;hwbreak+")?;
+        let supports = |feature: &str| supported.split(';').any(|f| f == feature);
+
+        if supports("QStartNoAckMode+") && conn.request("QStartNoAckMode")? == "OK" {
+            conn.ack = false;
+        }
+
+        let stop = conn.request("?")?;
+        if stop.starts_with('W') || stop.starts_with('X') {
+            return Err(Error::Exited);
+        }
+
+        let mut regs = Vec::new();
+        if supports("qXfer:features:read+") {
+            let xml = conn.description("target.xml")?;
+            parse_description(&xml, &mut |annex| conn.description(annex), &mut regs)?;
+            regs.sort_by_key(|&(num, _)| num);
+        }
+
+        let registers: Vec<Register> = match regs.is_empty() {
+            true => rsp::REGISTERS
+                .iter()
+                .map(|&(name, size)| Register { name, size })
+                .collect(),
+            false => regs.into_iter().map(|(_, reg)| reg).collect(),
+        };
+
+        // x86 traps are a single byte, other architectures use 4 byte instructions.
+        let breakpoint_kind = match registers.iter().any(|reg| matches!(reg.name, "rip" | "eip")) {
+            true => 1,
+            false => 4,
+        };
+
+        // Stubs tell where relocatable images are loaded.
+        let offsets = conn.request("qOffsets")?;
+        let bias = offsets
+            .split(';')
+            .find_map(|offset| offset.strip_prefix("Text="))
+            .and_then(rsp::parse_usize)
+            .unwrap_or(0);
+
+        let mut target = Self {
+            conn: RefCell::new(conn),
+            breakpoints,
+            inserted: BTreeSet::new(),
+            watching: Vec::new(),
+            registers,
+            breakpoint_kind,
+            bias,
+            interrupter,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            detaching: Arc::new(AtomicBool::new(false)),
+            killing: Arc::new(AtomicBool::new(false)),
+            exited: false,
+        };
+
+        target.breakpoints.take_dirty();
+        target.sync()?;
+        Ok(target)
+    }
+
+    /// Offset between addresses in the binary and where it's loaded.
+    pub fn bias(&self) -> usize {
+        self.bias
+    }
+
+    fn register_values(&self) -> Result<Vec<(&'static str, u64)>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let reply = self.conn.borrow_mut().request("g")?;
+        if reply.starts_with('E') {
+            return Err(Error::Protocol(reply));
+        }
+
+        Ok(register_values(&self.registers, &reply))
+    }
+
+    /// Runtime value of the first register found named one of `names`.
+    fn register(&self, values: &[(&str, u64)], names: &[&str]) -> Option<u64> {
+        names
+            .iter()
+            .find_map(|name| values.iter().find(|(reg, _)| reg == name))
+            .map(|&(_, value)| value)
+    }
+
+    /// Set a register, named as in [`Registers`].
+    pub fn set_register(&mut self, name: &str, value: u64) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let idx = self.registers.iter().position(|reg| reg.name == name);
+        let reg = idx.map(|idx| (idx, self.registers[idx]));
+        let (idx, reg) = reg.filter(|(_, reg)| reg.size <= 8).ok_or(Error::InvalidRegister)?;
+
+        let value = rsp::hex(&value.to_le_bytes()[..reg.size]);
+        ok(self.conn.borrow_mut().request(&format!("P{idx:x}={value}"))?)
+    }
+
+    /// Read the target's memory at runtime address `addr`, stopping early at the first byte
+    /// that can't be read.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let mut conn = self.conn.borrow_mut();
+        let mut bytes = Vec::with_capacity(len);
+
+        while bytes.len() < len {
+            let chunk = (len - bytes.len()).min(MAX_TRANSFER);
+            let reply = conn.request(&format!("m{:x},{chunk:x}", addr + bytes.len()))?;
+
+            match rsp::unhex(&reply) {
+                Some(read) if !read.is_empty() => {
+                    bytes.extend_from_slice(&read);
+                    if read.len() < chunk {
+                        break;
+                    }
+                }
+                _ if !bytes.is_empty() => break,
+                _ => return Err(Error::Protocol(reply)),
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Write to the target's memory at runtime address `addr`.
+    pub fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let mut conn = self.conn.borrow_mut();
+        for (idx, chunk) in bytes.chunks(MAX_TRANSFER).enumerate() {
+            let addr = addr + idx * MAX_TRANSFER;
+            let packet = format!("M{addr:x},{:x}:{}", chunk.len(), rsp::hex(chunk));
+            ok(conn.request(&packet)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Have the stub insert the breakpoints and watchpoints of the shared [`BreakpointSet`].
+    fn sync(&mut self) -> Result<(), Error> {
+        let mut conn = self.conn.borrow_mut();
+        let wanted: BTreeSet<usize> = self
+            .breakpoints
+            .wanted(true)
+            .into_iter()
+            .map(|addr| addr.wrapping_add(self.bias))
+            .collect();
+
+        for &addr in self.inserted.difference(&wanted) {
+            ok(conn.request(&format!("z0,{addr:x},{:x}", self.breakpoint_kind))?)?;
+        }
+
+        for &addr in wanted.difference(&self.inserted) {
+            ok(conn.request(&format!("Z0,{addr:x},{:x}", self.breakpoint_kind))?)?;
+        }
+
+        self.inserted = wanted;
+
+        let watchpoints = self.breakpoints.debug_registers();
+        if watchpoints == self.watching {
+            return Ok(());
+        }
+
+        // Hardware breakpoints take the same kind as software breakpoints instead of a length.
+        let breakpoint_kind = self.breakpoint_kind;
+        let kind = |wp: &Watchpoint| match wp.kind {
+            WatchKind::Execute => (1, breakpoint_kind),
+            WatchKind::Write => (2, wp.len),
+            WatchKind::ReadWrite => (4, wp.len),
+        };
+
+        for wp in &self.watching {
+            let addr = wp.addr.wrapping_add(self.bias);
+            let (kind, len) = kind(wp);
+            ok(conn.request(&format!("z{kind},{addr:x},{len:x}"))?)?;
+        }
+
+        self.watching.clear();
+        for wp in watchpoints {
+            let addr = wp.addr.wrapping_add(self.bias);
+            let (kind, len) = kind(&wp);
+            ok(conn.request(&format!("Z{kind},{addr:x},{len:x}"))?)?;
+            self.watching.push(wp);
+        }
+
+        Ok(())
+    }
+
+    /// Why the target stopped, given the stub's stop reply, e.g. `T05watch:601040;`.
+    fn stop(&mut self, reply: &str) -> Result<Stop, Error> {
+        let sig = reply.get(1..3).and_then(|sig| u8::from_str_radix(sig, 16).ok());
+        let sig = match (reply.as_bytes().first(), sig) {
+            (Some(b'W'), Some(code)) => {
+                self.exited = true;
+                return Ok(Stop::Exited(code as i32));
+            }
+            (Some(b'X'), Some(sig)) => {
+                self.exited = true;
+                return Ok(Stop::Killed(rsp::linux_signal(sig)));
+            }
+            (Some(b'S' | b'T'), Some(sig)) => sig,
+            _ => return Err(Error::Protocol(reply.to_string())),
+        };
+
+        let pc = self.pc()?;
+
+        // T packets hold `key:value` pairs after the signal.
+        for pair in reply[3..].split(';') {
+            if let Some((key, value)) = pair.split_once(':') {
+                if matches!(key, "watch" | "rwatch" | "awatch") {
+                    let addr = rsp::parse_usize(value).unwrap_or(0).wrapping_sub(self.bias);
+                    return Ok(Stop::Watchpoint(addr, pc));
+                }
+            }
+        }
+
+        let hardware =
+            self.watching.iter().any(|wp| wp.kind == WatchKind::Execute && wp.addr == pc);
+        if sig == SIGTRAP && (hardware || self.inserted.contains(&pc.wrapping_add(self.bias))) {
+            return Ok(Stop::Breakpoint(pc));
+        }
+
+        Ok(Stop::Signal(rsp::linux_signal(sig), pc))
+    }
+
+    /// Resume the target with `action`, such as `c` for continuing.
+    fn resume(&mut self, action: &str) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        loop {
+            if self.breakpoints.take_dirty() {
+                self.sync()?;
+            }
+
+            let reply = {
+                let mut conn = self.conn.borrow_mut();
+                conn.send(action)?;
+                conn.stop_reply()?
+            };
+
+            let stop = self.stop(&reply)?;
+            if !matches!(stop, Stop::Signal(..)) {
+                return Ok(stop);
+            }
+
+            if self.killing.load(Ordering::Acquire) {
+                let _ = self.conn.borrow_mut().send("k");
+                self.exited = true;
+                return Ok(Stop::Killed(9));
+            }
+
+            // The target is detached from once we're dropped.
+            if self.detaching.load(Ordering::Acquire) {
+                return Err(Error::Detached);
+            }
+
+            // We stopped the target ourselves to apply breakpoints.
+            if self.interrupted.swap(false, Ordering::AcqRel) {
+                continue;
+            }
+
+            return Ok(stop);
+        }
+    }
+}
+
+impl Target for RemoteTarget {
+    fn pc(&self) -> Result<usize, Error> {
+        let values = self.register_values()?;
+        match self.register(&values, &PC_NAMES) {
+            Some(pc) => Ok((pc as usize).wrapping_sub(self.bias)),
+            None => Err(Error::InvalidRegister),
+        }
+    }
+
+    fn handle(&self) -> Handle {
+        Handle::Remote(RemoteHandle {
+            stream: Arc::clone(&self.interrupter),
+            interrupted: Arc::clone(&self.interrupted),
+            detaching: Arc::clone(&self.detaching),
+            killing: Arc::clone(&self.killing),
+        })
+    }
+
+    fn trace_syscalls(&mut self, _report: Box<dyn FnMut(Syscall) + Send>) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn watch_libraries(
+        &mut self,
+        _report: Box<dyn FnMut(Vec<Library>) + Send>,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn registers(&self) -> Result<Registers, Error> {
+        let values = self.register_values()?;
+        let (segments, general): (Vec<_>, Vec<_>) =
+            values.iter().copied().partition(|(name, _)| SEGMENTS.contains(name));
+
+        let flags = match self.register(&values, &["eflags", "rflags"]) {
+            Some(value) => X86_FLAGS
+                .iter()
+                .map(|&(name, bit)| (name, value & (1 << bit) != 0))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Registers {
+            general,
+            flags,
+            segments,
+        })
+    }
+
+    fn write_registers(&mut self, registers: &[(&str, u64)]) -> Result<(), Error> {
+        for &(name, value) in registers {
+            RemoteTarget::set_register(self, name, value)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        RemoteTarget::read_memory(self, addr, len)
+    }
+
+    fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        RemoteTarget::write_memory(self, addr, bytes)
+    }
+
+    fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        let values = self.register_values()?;
+        let pc = self.register(&values, &PC_NAMES).ok_or(Error::InvalidRegister)?;
+
+        // Unwinding is only supported on x86_64.
+        let regs = match (self.register(&values, &["rsp"]), self.register(&values, &["rbp"])) {
+            (Some(sp), Some(fp)) => Regs { pc, sp, fp },
+            _ => {
+                return Ok(vec![Frame {
+                    pc: (pc as usize).wrapping_sub(self.bias),
+                    sp: self.register(&values, &["sp"]).unwrap_or(0) as usize,
+                }])
+            }
+        };
+
+        let read = |addr: u64| {
+            let bytes = self.read_memory(addr as usize, 8).ok()?;
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        };
+
+        Ok(unwind::unwind(cfi, regs, self.bias, read))
+    }
+
+    fn threads(&self) -> Result<Vec<Thread>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    fn select_thread(&mut self, _tid: u32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Stubs keep debugging the process they were started with.
+    fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        match follow {
+            FollowFork::Parent => Ok(()),
+            FollowFork::Child | FollowFork::Ask => Err(Error::Unsupported),
+        }
+    }
+
+    /// Stubs decide how signals are handled themselves.
+    fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        match signals.is_default() {
+            true => Ok(()),
+            false => Err(Error::Unsupported),
+        }
+    }
+
+    fn cont(&mut self) -> Result<Stop, Error> {
+        self.resume("c")
+    }
+
+    fn step(&mut self) -> Result<Stop, Error> {
+        match self.resume("s")? {
+            Stop::Breakpoint(pc) => Ok(Stop::Stepped(pc)),
+            Stop::Signal(sig, pc) if sig == SIGTRAP as i32 => Ok(Stop::Stepped(pc)),
+            stop => Ok(stop),
+        }
+    }
+}
+
+impl Drop for RemoteTarget {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+
+        let mut conn = self.conn.borrow_mut();
+        if self.killing.load(Ordering::Acquire) {
+            let _ = conn.send("k");
+        } else {
+            // Stubs remove their breakpoints once detached from.
+            let _ = conn.request("D");
+        }
+
+        let _ = conn.stream.shutdown(Shutdown::Both);
+    }
+}
+
+/// Controls a running [`RemoteTarget`] from other threads.
+#[derive(Clone)]
+pub struct RemoteHandle {
+    stream: Arc<TcpStream>,
+    interrupted: Arc<AtomicBool>,
+    detaching: Arc<AtomicBool>,
+    killing: Arc<AtomicBool>,
+}
+
+impl RemoteHandle {
+    /// Stop the target so changes to the breakpoints are applied, it's resumed right after.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+        self.pause();
+    }
+
+    /// Stop the target, which stubs report as it receiving `SIGINT`.
+    pub fn pause(&self) {
+        let _ = (&*self.stream).write_all(&[0x03]);
+    }
+
+    pub fn kill(&self) {
+        self.killing.store(true, Ordering::Release);
+        self.pause();
+    }
+
+    /// Stop debugging the running target, leaving it running.
+    pub fn detach(&self) {
+        self.detaching.store(true, Ordering::Release);
+        self.pause();
+    }
+
+    /// Stubs don't tell how memory is mapped.
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    /// Memory can only be read whilst the target is stopped.
+    pub fn read_memory(&self, _addr: usize, _len: usize) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes() {
+        let tag = r#"reg name="rip" bitsize="64"
+            type="code_ptr" regnum="16""#;
+        assert_eq!(attr(tag, "name"), Some("rip"));
+        assert_eq!(attr(tag, "regnum"), Some("16"));
+        assert_eq!(attr(tag, "size"), None);
+    }
+
+    #[test]
+    fn description() {
+        let target = r#"<?xml version="1.0"?>
+            <!DOCTYPE target SYSTEM "gdb-target.dtd">
+            <target>
+              <architecture>aarch64</architecture>
+              <xi:include href="core.xml"/>
+              <reg name="fpcr" bitsize="32" regnum="67"/>
+            </target>"#;
+
+        let core = r#"<feature name="org.gnu.gdb.aarch64.core">
+              <reg name="x0" bitsize="64" type="int" regnum="0"/>
+              <reg name="x1" bitsize="64"/>
+              <reg name="pc" bitsize="64" type="code_ptr" regnum="32"/>
+              <reg name="cpsr" bitsize="32"/>
+              <register name="unrelated"/>
+            </feature>"#;
+
+        let mut regs = Vec::new();
+        let mut include = |annex: &str| {
+            assert_eq!(annex, "core.xml");
+            Ok(core.to_string())
+        };
+
+        parse_description(target, &mut include, &mut regs).unwrap();
+        let regs: Vec<(usize, &str, usize)> =
+            regs.iter().map(|&(num, reg)| (num, reg.name, reg.size)).collect();
+
+        assert_eq!(
+            regs,
+            [(0, "x0", 8), (1, "x1", 8), (32, "pc", 8), (33, "cpsr", 4), (67, "fpcr", 4)]
+        );
+    }
+
+    #[test]
+    fn values() {
+        let regs = [
+            Register { name: "r0", size: 4 },
+            Register { name: "q0", size: 16 },
+            Register { name: "pc", size: 4 },
+            Register { name: "sp", size: 4 },
+        ];
+
+        // Unavailable registers are sent as `x`s.
+        let reply = format!("10000000{}00800000xxxxxxxx", "0".repeat(32));
+        assert_eq!(register_values(&regs, &reply), [("r0", 0x10), ("pc", 0x8000)]);
+    }
+}
@@ -0,0 +1,317 @@
+//! Shared objects loaded by the dynamic linker, found through it's rendezvous structure.
+//!
+//! Once the dynamic linker set up the process, the executable's `DT_DEBUG` entry points to the
+//! linker's `r_debug`. It holds the list of loaded objects, along with the address of a
+//! function the linker calls before and after changing the list. A breakpoint on it tells when
+//! objects are loaded or unloaded.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use object::{Object, ObjectSymbol, SymbolKind};
+
+use crate::breakpoint::Memory;
+
+/// Program header of the dynamic section.
+const PT_DYNAMIC: u32 = 2;
+
+/// Program header of the program headers themselves.
+const PT_PHDR: u32 = 6;
+
+/// Size of a 64-bit program header.
+const PHDR_SIZE: usize = 56;
+
+/// Tag of the entry ending the dynamic section.
+const DT_NULL: usize = 0;
+
+/// Tag of the dynamic entry the linker sets to the address of `r_debug`.
+const DT_DEBUG: usize = 21;
+
+/// `r_state` once the linker is done changing the list of objects.
+const RT_CONSISTENT: u32 = 0;
+
+/// Most entries followed through the dynamic section or the list of objects, in case they're
+/// corrupted.
+const MAX_ENTRIES: usize = 4096;
+
+/// Longest path of an object that's read.
+const MAX_PATH: usize = 4096;
+
+const WORD: usize = std::mem::size_of::<u64>();
+
+/// A shared object loaded into the process.
+#[derive(Debug, Clone)]
+pub struct Library {
+    /// Path the dynamic linker loaded it from.
+    pub path: PathBuf,
+    /// Offset between addresses in the object and where it's loaded.
+    pub bias: usize,
+    /// Functions it defines by their name, at their address in the object. Only read once
+    /// they're looked up.
+    symbols: Arc<OnceLock<BTreeMap<String, usize>>>,
+}
+
+impl Library {
+    pub fn new(path: PathBuf, bias: usize) -> Self {
+        Self {
+            path,
+            bias,
+            symbols: Arc::default(),
+        }
+    }
+
+    /// Runtime address of the function `name`, if the library defines it.
+    pub fn symbol(&self, name: &str) -> Option<usize> {
+        let symbols = self.symbols.get_or_init(|| read_symbols(&self.path));
+        symbols.get(name).map(|&addr| addr.wrapping_add(self.bias))
+    }
+}
+
+/// Libraries are the same if the same object is loaded at the same address, their symbols
+/// follow from that.
+impl PartialEq for Library {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && self.bias == other.bias
+    }
+}
+
+impl Eq for Library {}
+
+/// Functions defined by the object at `path`, none if it can't be read.
+fn read_symbols(path: &Path) -> BTreeMap<String, usize> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return BTreeMap::new(),
+    };
+
+    // SAFETY: Objects aren't expected to change whilst they're loaded.
+    let mmap = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mmap) => mmap,
+        Err(_) => return BTreeMap::new(),
+    };
+
+    let object = match object::File::parse(&*mmap) {
+        Ok(object) => object,
+        Err(_) => return BTreeMap::new(),
+    };
+
+    // Stripped objects only have their exports.
+    object
+        .dynamic_symbols()
+        .chain(object.symbols())
+        .filter(|sym| sym.kind() == SymbolKind::Text && sym.is_definition())
+        .filter_map(|sym| Some((sym.name().ok()?.to_string(), sym.address() as usize)))
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+/// The dynamic linker's `r_debug`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Rendezvous {
+    /// Runtime address of the first object in the list.
+    map: usize,
+    /// Runtime address of the function called before and after the list changes.
+    pub brk: usize,
+    /// Whether the linker is done changing the list.
+    pub consistent: bool,
+}
+
+fn read_word<M: Memory>(mem: &M, addr: usize) -> Result<usize, M::Error> {
+    let mut word = [0; WORD];
+    mem.read(addr, &mut word)?;
+    Ok(u64::from_le_bytes(word) as usize)
+}
+
+/// Runtime address of the dynamic linker's `r_debug`, given the runtime address and number of
+/// the executable's program headers. None if the executable isn't dynamically linked, or the
+/// linker didn't set it up yet.
+pub(crate) fn find<M: Memory>(
+    mem: &M,
+    phdrs: usize,
+    count: usize,
+) -> Result<Option<usize>, M::Error> {
+    let mut headers = vec![0; count * PHDR_SIZE];
+    mem.read(phdrs, &mut headers)?;
+
+    let mut bias = None;
+    let mut dynamic = None;
+    for header in headers.chunks_exact(PHDR_SIZE) {
+        let kind = u32::from_le_bytes(header[..4].try_into().unwrap());
+        let vaddr = u64::from_le_bytes(header[16..24].try_into().unwrap()) as usize;
+
+        match kind {
+            PT_PHDR => bias = Some(phdrs.wrapping_sub(vaddr)),
+            PT_DYNAMIC => dynamic = Some(vaddr),
+            _ => {}
+        }
+    }
+
+    // Executables with an interpreter always describe their program headers.
+    let mut entry = match (bias, dynamic) {
+        (Some(bias), Some(dynamic)) => dynamic.wrapping_add(bias),
+        _ => return Ok(None),
+    };
+
+    for _ in 0..MAX_ENTRIES {
+        match read_word(mem, entry)? {
+            DT_NULL => break,
+            DT_DEBUG => {
+                let addr = read_word(mem, entry + WORD)?;
+                return Ok((addr != 0).then_some(addr));
+            }
+            _ => entry += 2 * WORD,
+        }
+    }
+
+    Ok(None)
+}
+
+/// Read the `r_debug` at runtime address `addr`.
+pub(crate) fn read<M: Memory>(mem: &M, addr: usize) -> Result<Rendezvous, M::Error> {
+    let mut state = [0; 4];
+    mem.read(addr + 3 * WORD, &mut state)?;
+
+    Ok(Rendezvous {
+        map: read_word(mem, addr + WORD)?,
+        brk: read_word(mem, addr + 2 * WORD)?,
+        consistent: u32::from_le_bytes(state) == RT_CONSISTENT,
+    })
+}
+
+/// Read a nul-terminated string at `addr`, a word at a time.
+fn read_path<M: Memory>(mem: &M, addr: usize) -> Result<PathBuf, M::Error> {
+    let mut bytes = Vec::new();
+    while bytes.len() < MAX_PATH {
+        let mut word = [0; WORD];
+        mem.read(addr + bytes.len(), &mut word)?;
+
+        match word.iter().position(|&byte| byte == 0) {
+            Some(len) => {
+                bytes.extend_from_slice(&word[..len]);
+                break;
+            }
+            None => bytes.extend_from_slice(&word),
+        }
+    }
+
+    Ok(PathBuf::from(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// Path and bias of the objects in the list of `rendezvous`, in the order they were loaded.
+/// The executable itself is left out, as it doesn't have a path.
+pub(crate) fn objects<M: Memory>(
+    mem: &M,
+    rendezvous: &Rendezvous,
+) -> Result<Vec<(PathBuf, usize)>, M::Error> {
+    let mut objects = Vec::new();
+    let mut map = rendezvous.map;
+
+    // Each `link_map` starts with `l_addr`, `l_name`, `l_ld` and `l_next`.
+    for _ in 0..MAX_ENTRIES {
+        if map == 0 {
+            break;
+        }
+
+        let bias = read_word(mem, map)?;
+        let name = read_word(mem, map + WORD)?;
+        if name != 0 {
+            let path = read_path(mem, name)?;
+            if !path.as_os_str().is_empty() {
+                objects.push((path, bias));
+            }
+        }
+
+        map = read_word(mem, map + 3 * WORD)?;
+    }
+
+    Ok(objects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Memory of a process, starting at `BASE`.
+    struct Process(RefCell<Vec<u8>>);
+
+    const BASE: usize = 0x1000;
+
+    impl Process {
+        fn put(&self, addr: usize, bytes: &[u8]) {
+            self.0.borrow_mut()[addr - BASE..][..bytes.len()].copy_from_slice(bytes);
+        }
+
+        fn put_words(&self, addr: usize, words: &[usize]) {
+            for (idx, &word) in words.iter().enumerate() {
+                self.put(addr + idx * WORD, &(word as u64).to_le_bytes());
+            }
+        }
+    }
+
+    impl Memory for Process {
+        type Error = ();
+
+        fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), ()> {
+            let mem = self.0.borrow();
+            let offset = addr.checked_sub(BASE).ok_or(())?;
+            buf.copy_from_slice(mem.get(offset..offset + buf.len()).ok_or(())?);
+            Ok(())
+        }
+
+        fn write(&self, _addr: usize, _bytes: &[u8]) -> Result<(), ()> {
+            Err(())
+        }
+    }
+
+    #[test]
+    fn objects_in_rendezvous() {
+        let process = Process(RefCell::new(vec![0; 0x1000]));
+
+        // PT_PHDR at 0x40 in the executable loaded at 0x1000, followed by PT_DYNAMIC at 0x200.
+        let mut phdr = [0; PHDR_SIZE];
+        phdr[..4].copy_from_slice(&PT_PHDR.to_le_bytes());
+        phdr[16..24].copy_from_slice(&0x40u64.to_le_bytes());
+        process.put(0x1040, &phdr);
+        phdr[..4].copy_from_slice(&PT_DYNAMIC.to_le_bytes());
+        phdr[16..24].copy_from_slice(&0x200u64.to_le_bytes());
+        process.put(0x1040 + PHDR_SIZE, &phdr);
+
+        // Before the linker set it up there's no `r_debug`.
+        process.put_words(0x1200, &[1, 0x10, DT_DEBUG, 0, DT_NULL, 0]);
+        assert_eq!(find(&process, 0x1040, 2), Ok(None));
+
+        process.put_words(0x1200, &[1, 0x10, DT_DEBUG, 0x1400, DT_NULL, 0]);
+        assert_eq!(find(&process, 0x1040, 2), Ok(Some(0x1400)));
+        assert_eq!(find(&process, 0x1040, 1), Ok(None));
+
+        // The executable, followed by a library whose path spans multiple words.
+        process.put_words(0x1400, &[1, 0x1500, 0x1800, 0]);
+        process.put_words(0x1500, &[0x1000, 0x1700, 0x1200, 0x1600]);
+        process.put_words(0x1600, &[0x7f00_0000_0000, 0x1710, 0, 0]);
+        process.put(0x1710, b"/usr/lib/libc.so.6\0");
+
+        let rendezvous = read(&process, 0x1400).unwrap();
+        assert_eq!(rendezvous.brk, 0x1800);
+        assert!(rendezvous.consistent);
+        assert_eq!(
+            objects(&process, &rendezvous),
+            Ok(vec![(PathBuf::from("/usr/lib/libc.so.6"), 0x7f00_0000_0000)])
+        );
+
+        // Libraries being added aren't consistent until the linker calls `brk` again.
+        process.put(0x1400 + 3 * WORD, &1u32.to_le_bytes());
+        assert!(!read(&process, 0x1400).unwrap().consistent);
+    }
+
+    #[test]
+    fn libraries_compare_by_address() {
+        let libc = Library::new(PathBuf::from("/usr/lib/libc.so.6"), 0x7f00_0000_0000);
+        assert_eq!(libc, libc.clone());
+        assert_ne!(libc, Library::new(libc.path.clone(), 0x7f10_0000_0000));
+
+        // Objects that can't be read don't define anything.
+        assert_eq!(libc.symbol("puts"), None);
+    }
+}
@@ -0,0 +1,244 @@
+//! Pieces of the GDB remote serial protocol shared by [`crate::gdbserver`] and [`crate::remote`].
+
+/// Registers in the order of gdb's amd64 `g` packet, followed by their width in bytes.
+pub(crate) const REGISTERS: [(&str, usize); 24] = [
+    ("rax", 8),
+    ("rbx", 8),
+    ("rcx", 8),
+    ("rdx", 8),
+    ("rsi", 8),
+    ("rdi", 8),
+    ("rbp", 8),
+    ("rsp", 8),
+    ("r8", 8),
+    ("r9", 8),
+    ("r10", 8),
+    ("r11", 8),
+    ("r12", 8),
+    ("r13", 8),
+    ("r14", 8),
+    ("r15", 8),
+    ("rip", 8),
+    ("rflags", 4),
+    ("cs", 4),
+    ("ss", 4),
+    ("ds", 4),
+    ("es", 4),
+    ("fs", 4),
+    ("gs", 4),
+];
+
+/// Linux signal numbers that gdb numbers differently, followed by gdb's number.
+const SIGNALS: [(i32, u8); 7] = [
+    (7, 10),  // SIGBUS
+    (10, 30), // SIGUSR1
+    (12, 31), // SIGUSR2
+    (17, 20), // SIGCHLD
+    (18, 19), // SIGCONT
+    (19, 17), // SIGSTOP
+    (20, 18), // SIGTSTP
+];
+
+/// Something received from the other side of the connection.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Incoming {
+    Packet(String),
+    /// Packet with a checksum that doesn't match it's data.
+    Corrupt,
+    /// Request to stop the running tracee.
+    Interrupt,
+}
+
+#[derive(Debug, Default)]
+enum State {
+    #[default]
+    Idle,
+    Data,
+    Escaped,
+    /// Run-length encoding, the next byte tells how often to repeat the last one.
+    Repeat,
+    Checksum(Option<u8>),
+}
+
+/// Splits the bytes received into packets, e.g. `$m4000,8#a5`.
+#[derive(Debug, Default)]
+pub(crate) struct Framing {
+    state: State,
+    data: Vec<u8>,
+    /// Checksum of the packet's bytes as sent, so including escapes.
+    sum: u8,
+}
+
+impl Framing {
+    pub fn feed(&mut self, byte: u8) -> Option<Incoming> {
+        match (&self.state, byte) {
+            (State::Idle, b'$') => {
+                self.data.clear();
+                self.sum = 0;
+                self.state = State::Data;
+            }
+            (State::Idle, 0x03) => return Some(Incoming::Interrupt),
+            // Acknowledgements of our packets, which can't get lost over TCP.
+            (State::Idle, _) => {}
+            (State::Data, b'#') => self.state = State::Checksum(None),
+            (State::Data, b'}') => {
+                self.sum = self.sum.wrapping_add(byte);
+                self.state = State::Escaped;
+            }
+            (State::Data, b'*') => {
+                self.sum = self.sum.wrapping_add(byte);
+                self.state = State::Repeat;
+            }
+            (State::Data, _) => {
+                self.sum = self.sum.wrapping_add(byte);
+                self.data.push(byte);
+            }
+            (State::Escaped, _) => {
+                self.sum = self.sum.wrapping_add(byte);
+                self.data.push(byte ^ 0x20);
+                self.state = State::Data;
+            }
+            (State::Repeat, _) => {
+                self.sum = self.sum.wrapping_add(byte);
+                if let Some(&last) = self.data.last() {
+                    let count = byte.saturating_sub(29) as usize;
+                    self.data.extend(std::iter::repeat_n(last, count));
+                }
+                self.state = State::Data;
+            }
+            (State::Checksum(None), _) => self.state = State::Checksum(Some(byte)),
+            (&State::Checksum(Some(high)), _) => {
+                self.state = State::Idle;
+                let checksum = std::str::from_utf8(&[high, byte])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+                let data = std::mem::take(&mut self.data);
+                if checksum != Some(self.sum) {
+                    return Some(Incoming::Corrupt);
+                }
+
+                return match String::from_utf8(data) {
+                    Ok(packet) => Some(Incoming::Packet(packet)),
+                    Err(_) => Some(Incoming::Corrupt),
+                };
+            }
+        }
+
+        None
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+}
+
+/// Frame `data` as a packet, escaping the bytes with a special meaning.
+pub(crate) fn frame(data: &str) -> Vec<u8> {
+    let mut packet = vec![b'$'];
+    for &byte in data.as_bytes() {
+        match byte {
+            b'$' | b'#' | b'}' | b'*' => packet.extend([b'}', byte ^ 0x20]),
+            _ => packet.push(byte),
+        }
+    }
+
+    // The checksum is over the bytes as sent.
+    let checksum = checksum_of(&packet[1..]);
+    packet.extend(format!("#{checksum:02x}").as_bytes());
+    packet
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+pub(crate) fn unhex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..s.len()).step_by(2).map(|idx| u8::from_str_radix(s.get(idx..idx + 2)?, 16).ok()).collect()
+}
+
+pub(crate) fn parse_usize(s: &str) -> Option<usize> {
+    usize::from_str_radix(s, 16).ok()
+}
+
+/// Little-endian register value as sent over the protocol.
+pub(crate) fn register_value(bytes: &[u8]) -> Option<u64> {
+    let mut value = [0; 8];
+    value.get_mut(..bytes.len())?.copy_from_slice(bytes);
+    Some(u64::from_le_bytes(value))
+}
+
+/// Linux signal number as gdb numbers them.
+pub(crate) fn gdb_signal(sig: i32) -> u8 {
+    match SIGNALS.iter().find(|&&(linux, _)| linux == sig) {
+        Some(&(_, gdb)) => gdb,
+        None => sig as u8,
+    }
+}
+
+/// Signal numbered by gdb as linux numbers them.
+pub(crate) fn linux_signal(sig: u8) -> i32 {
+    match SIGNALS.iter().find(|&&(_, gdb)| gdb == sig) {
+        Some(&(linux, _)) => linux,
+        None => sig as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(framing: &mut Framing, bytes: &[u8]) -> Vec<Incoming> {
+        bytes.iter().filter_map(|&byte| framing.feed(byte)).collect()
+    }
+
+    #[test]
+    fn framing() {
+        let mut framing = Framing::default();
+        assert_eq!(
+            feed(&mut framing, b"+$qC#b4\x03$g#00"),
+            [
+                Incoming::Packet("qC".to_string()),
+                Incoming::Interrupt,
+                Incoming::Corrupt
+            ]
+        );
+
+        // `}` escapes the byte that follows it.
+        assert_eq!(
+            feed(&mut framing, b"$M0,1:}]#ee"),
+            [Incoming::Packet("M0,1:}".to_string())]
+        );
+
+        // `0* ` is a zero repeated three more times.
+        assert_eq!(feed(&mut framing, b"$0* #7a"), [Incoming::Packet("0000".to_string())]);
+    }
+
+    #[test]
+    fn framed_replies() {
+        assert_eq!(frame("OK"), b"$OK#9a");
+        assert_eq!(frame(""), b"$#00");
+        assert_eq!(frame("a#"), b"$a}\x03#e1");
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(hex(&[0xde, 0xad, 0x01]), "dead01");
+        assert_eq!(unhex("dead01"), Some(vec![0xde, 0xad, 0x01]));
+        assert_eq!(unhex("dea"), None);
+        assert_eq!(register_value(&[0x10, 0x20]), Some(0x2010));
+        assert_eq!(register_value(&[0; 9]), None);
+    }
+
+    #[test]
+    fn signals() {
+        assert_eq!(gdb_signal(11), 11);
+        assert_eq!(gdb_signal(19), 17);
+        assert_eq!(linux_signal(17), 19);
+        assert_eq!(linux_signal(2), 2);
+    }
+}
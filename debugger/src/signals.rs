@@ -0,0 +1,135 @@
+//! Which signals received by the tracee stop it, and which are passed on to it.
+//!
+//! Signals are numbered as on linux, which remote stubs and Windows exceptions are translated
+//! to as well.
+
+use std::collections::BTreeMap;
+
+/// Names of the standard signals, the first being signal 1.
+const NAMES: [&str; 31] = [
+    "SIGHUP", "SIGINT", "SIGQUIT", "SIGILL", "SIGTRAP", "SIGABRT", "SIGBUS", "SIGFPE", "SIGKILL",
+    "SIGUSR1", "SIGSEGV", "SIGUSR2", "SIGPIPE", "SIGALRM", "SIGTERM", "SIGSTKFLT", "SIGCHLD",
+    "SIGCONT", "SIGSTOP", "SIGTSTP", "SIGTTIN", "SIGTTOU", "SIGURG", "SIGXCPU", "SIGXFSZ",
+    "SIGVTALRM", "SIGPROF", "SIGWINCH", "SIGIO", "SIGPWR", "SIGSYS",
+];
+
+/// Signals processes commonly use for timers and notifications, which are passed on without
+/// stopping.
+const QUIET: [&str; 7] =
+    ["SIGALRM", "SIGURG", "SIGCHLD", "SIGWINCH", "SIGIO", "SIGVTALRM", "SIGPROF"];
+
+/// Signals raised by the user or the debugger, which only stop the tracee.
+const NOT_PASSED: [&str; 3] = ["SIGINT", "SIGTRAP", "SIGSTOP"];
+
+/// Name of signal `sig`, if it's one of the standard signals.
+pub fn name(sig: i32) -> Option<&'static str> {
+    NAMES.get(usize::try_from(sig).ok()?.checked_sub(1)?).copied()
+}
+
+/// Number of the signal given by it's name, with or without the `SIG` prefix, or number.
+pub fn number(name: &str) -> Option<i32> {
+    if let Ok(sig) = name.parse() {
+        return (1..=64).contains(&sig).then_some(sig);
+    }
+
+    let name = name.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    NAMES.iter().position(|sig| sig[3..] == *name).map(|idx| idx as i32 + 1)
+}
+
+/// Numbers of the standard signals.
+pub fn standard() -> impl Iterator<Item = i32> {
+    1..=NAMES.len() as i32
+}
+
+/// What's done when the tracee receives a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Handling {
+    /// Whether the tracee stops, otherwise it's resumed right away.
+    pub stop: bool,
+    /// Whether the signal is delivered to the tracee once it's resumed, otherwise it's as if
+    /// it was never sent.
+    pub pass: bool,
+}
+
+impl Handling {
+    /// How signal `sig` is handled unless it's changed.
+    pub fn default_for(sig: i32) -> Self {
+        let name = name(sig).unwrap_or_default();
+        Self {
+            stop: !QUIET.contains(&name),
+            pass: !NOT_PASSED.contains(&name),
+        }
+    }
+}
+
+/// How each signal is handled, see [`Handling::default_for`] for those that weren't changed.
+///
+/// `SIGSTOP` is what the debugger stops threads with, so it's never passed on. `SIGKILL` can't
+/// be caught, so the tracee never stops for it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Signals {
+    changed: BTreeMap<i32, Handling>,
+}
+
+impl Signals {
+    pub fn get(&self, sig: i32) -> Handling {
+        match self.changed.get(&sig) {
+            Some(&handling) => handling,
+            None => Handling::default_for(sig),
+        }
+    }
+
+    pub fn set(&mut self, sig: i32, handling: Handling) {
+        if handling == Handling::default_for(sig) {
+            self.changed.remove(&sig);
+        } else {
+            self.changed.insert(sig, handling);
+        }
+    }
+
+    /// Whether every signal is handled as it is by default.
+    pub fn is_default(&self) -> bool {
+        self.changed.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(name(11), Some("SIGSEGV"));
+        assert_eq!(name(31), Some("SIGSYS"));
+        assert_eq!(name(0), None);
+        assert_eq!(name(34), None);
+
+        assert_eq!(number("SIGSEGV"), Some(11));
+        assert_eq!(number("segv"), Some(11));
+        assert_eq!(number("SigInt"), Some(2));
+        assert_eq!(number("34"), Some(34));
+        assert_eq!(number("0"), None);
+        assert_eq!(number("SIGFOO"), None);
+        assert_eq!(number("SIG"), None);
+    }
+
+    #[test]
+    fn handling() {
+        let mut signals = Signals::default();
+        let segv = number("SIGSEGV").unwrap();
+        assert_eq!(signals.get(segv), Handling { stop: true, pass: true });
+        assert_eq!(signals.get(2), Handling { stop: true, pass: false });
+        assert_eq!(signals.get(5), Handling { stop: true, pass: false });
+        assert_eq!(signals.get(17), Handling { stop: false, pass: true });
+        assert_eq!(signals.get(40), Handling { stop: true, pass: true });
+
+        signals.set(segv, Handling { stop: true, pass: false });
+        assert_eq!(signals.get(segv), Handling { stop: true, pass: false });
+        assert!(!signals.is_default());
+
+        // Changing it back forgets it was ever changed.
+        signals.set(segv, Handling::default_for(segv));
+        assert!(signals.is_default());
+    }
+}
@@ -0,0 +1,76 @@
+//! Standard streams of spawned processes, which are connected to pipes when the process'
+//! output is shown somewhere other than our own terminal.
+
+use std::io::{PipeReader, PipeWriter};
+
+use crate::Error;
+
+/// Our ends of the pipes connected to a spawned process' standard streams.
+pub struct Console {
+    /// Read by the process as it's stdin.
+    pub stdin: PipeWriter,
+    pub stdout: PipeReader,
+    pub stderr: PipeReader,
+}
+
+/// The process' ends of the pipes of a [`Console`], handed to it when it's spawned.
+pub struct Stdio {
+    pub(crate) stdin: PipeReader,
+    pub(crate) stdout: PipeWriter,
+    pub(crate) stderr: PipeWriter,
+}
+
+impl Console {
+    /// Pipes for a process that's yet to be spawned, along with the ends it's spawned with.
+    pub fn new() -> Result<(Self, Stdio), Error> {
+        let (child_stdin, stdin) = std::io::pipe()?;
+        let (stdout, child_stdout) = std::io::pipe()?;
+        let (stderr, child_stderr) = std::io::pipe()?;
+
+        let console = Self {
+            stdin,
+            stdout,
+            stderr,
+        };
+
+        let stdio = Stdio {
+            stdin: child_stdin,
+            stdout: child_stdout,
+            stderr: child_stderr,
+        };
+
+        Ok((console, stdio))
+    }
+}
+
+#[cfg(unix)]
+impl Stdio {
+    /// Make the pipes the standard streams of the forked child that's about to exec.
+    ///
+    /// Only performs async-signal-safe calls. The pipes themselves are closed on exec, unlike
+    /// the streams duplicated from them.
+    pub(crate) fn redirect(&self) {
+        use std::os::fd::AsRawFd;
+
+        // SAFETY: Duplicating a file descriptor we own onto the standard streams.
+        unsafe {
+            nix::libc::dup2(self.stdin.as_raw_fd(), 0);
+            nix::libc::dup2(self.stdout.as_raw_fd(), 1);
+            nix::libc::dup2(self.stderr.as_raw_fd(), 2);
+        }
+    }
+}
+
+/// Make `file` the stdin of the forked child that's about to exec, in place of the console's
+/// pipe.
+///
+/// Only performs async-signal-safe calls.
+#[cfg(unix)]
+pub(crate) fn redirect_stdin(file: &std::fs::File) {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: Duplicating a file descriptor we own onto stdin.
+    unsafe {
+        nix::libc::dup2(file.as_raw_fd(), 0);
+    }
+}
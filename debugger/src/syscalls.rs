@@ -0,0 +1,528 @@
+//! Decoding of the syscalls made by a traced process, formatted the way strace does.
+
+use std::fmt;
+
+/// Longest string read from the tracee for a path.
+const MAX_PATH_LEN: usize = 4096;
+
+/// Most bytes of a buffer that are shown.
+const MAX_BUF_LEN: usize = 32;
+
+/// `dirfd` referring to the working directory.
+const AT_FDCWD: i32 = -100;
+
+/// How an argument is formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arg {
+    Int,
+    UInt,
+    Hex,
+    Ptr,
+    Fd,
+    /// File descriptor of a directory paths are relative to.
+    DirFd,
+    /// Nul-terminated string.
+    Path,
+    /// Buffer read by the kernel, it's length being the argument at the index.
+    InBuf(usize),
+    /// Buffer filled by the kernel, it's length being the return value.
+    OutBuf,
+    OpenFlags,
+    Mode,
+    Prot,
+    MapFlags,
+    Signal,
+    Whence,
+    AccessMode,
+}
+
+/// How the return value is formatted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ret {
+    Int,
+    Hex,
+}
+
+struct Entry {
+    nr: u64,
+    name: &'static str,
+    args: &'static [Arg],
+    ret: Ret,
+}
+
+macro_rules! entries {
+    (@ret) => { Ret::Int };
+    (@ret $ret:ident) => { Ret::$ret };
+    ($($nr:literal $name:ident($($arg:ident $(($idx:literal))?),*) $(-> $ret:ident)?;)*) => {
+        &[$(Entry {
+            nr: $nr,
+            name: stringify!($name),
+            args: &[$(Arg::$arg $(($idx))?),*],
+            ret: entries!(@ret $($ret)?),
+        }),*]
+    };
+}
+
+mod x86_64 {
+    use super::{Arg, Entry, Ret};
+
+    /// Syscalls by their number, sorted.
+    pub(super) const TABLE: &[Entry] = entries! {
+        0 read(Fd, OutBuf, UInt);
+        1 write(Fd, InBuf(2), UInt);
+        2 open(Path, OpenFlags, Mode);
+        3 close(Fd);
+        4 stat(Path, Ptr);
+        5 fstat(Fd, Ptr);
+        6 lstat(Path, Ptr);
+        7 poll(Ptr, UInt, Int);
+        8 lseek(Fd, Int, Whence);
+        9 mmap(Ptr, UInt, Prot, MapFlags, Fd, UInt) -> Hex;
+        10 mprotect(Ptr, UInt, Prot);
+        11 munmap(Ptr, UInt);
+        12 brk(Ptr) -> Hex;
+        13 rt_sigaction(Signal, Ptr, Ptr, UInt);
+        14 rt_sigprocmask(Int, Ptr, Ptr, UInt);
+        15 rt_sigreturn();
+        16 ioctl(Fd, Hex, Hex);
+        17 pread64(Fd, OutBuf, UInt, Int);
+        18 pwrite64(Fd, InBuf(2), UInt, Int);
+        19 readv(Fd, Ptr, Int);
+        20 writev(Fd, Ptr, Int);
+        21 access(Path, AccessMode);
+        22 pipe(Ptr);
+        23 select(Int, Ptr, Ptr, Ptr, Ptr);
+        24 sched_yield();
+        25 mremap(Ptr, UInt, UInt, Hex, Ptr) -> Hex;
+        28 madvise(Ptr, UInt, Int);
+        32 dup(Fd);
+        33 dup2(Fd, Fd);
+        35 nanosleep(Ptr, Ptr);
+        39 getpid();
+        41 socket(Int, Int, Int);
+        42 connect(Fd, Ptr, Int);
+        43 accept(Fd, Ptr, Ptr);
+        44 sendto(Fd, InBuf(2), UInt, Hex, Ptr, Int);
+        45 recvfrom(Fd, OutBuf, UInt, Hex, Ptr, Ptr);
+        49 bind(Fd, Ptr, Int);
+        50 listen(Fd, Int);
+        56 clone(Hex, Ptr, Ptr, Ptr, Hex);
+        57 fork();
+        58 vfork();
+        59 execve(Path, Ptr, Ptr);
+        60 exit(Int);
+        61 wait4(Int, Ptr, Hex, Ptr);
+        62 kill(Int, Signal);
+        63 uname(Ptr);
+        72 fcntl(Fd, Int, Hex);
+        78 getdents(Fd, Ptr, UInt);
+        79 getcwd(OutBuf, UInt);
+        80 chdir(Path);
+        82 rename(Path, Path);
+        83 mkdir(Path, Mode);
+        84 rmdir(Path);
+        87 unlink(Path);
+        89 readlink(Path, OutBuf, UInt);
+        90 chmod(Path, Mode);
+        95 umask(Mode);
+        96 gettimeofday(Ptr, Ptr);
+        97 getrlimit(Int, Ptr);
+        102 getuid();
+        104 getgid();
+        107 geteuid();
+        108 getegid();
+        110 getppid();
+        158 arch_prctl(Hex, Hex);
+        186 gettid();
+        202 futex(Ptr, Int, Int, Ptr, Ptr, Int);
+        217 getdents64(Fd, Ptr, UInt);
+        218 set_tid_address(Ptr);
+        228 clock_gettime(Int, Ptr);
+        231 exit_group(Int);
+        232 epoll_wait(Fd, Ptr, Int, Int);
+        257 openat(DirFd, Path, OpenFlags, Mode);
+        262 newfstatat(DirFd, Path, Ptr, Hex);
+        273 set_robust_list(Ptr, UInt);
+        293 pipe2(Ptr, Hex);
+        302 prlimit64(Int, Int, Ptr, Ptr);
+        318 getrandom(OutBuf, UInt, Hex);
+        332 statx(DirFd, Path, Hex, Hex, Ptr);
+        334 rseq(Ptr, UInt, Hex, Hex);
+        435 clone3(Ptr, UInt);
+        439 faccessat2(DirFd, Path, AccessMode, Hex);
+    };
+}
+
+/// Syscalls of the architecture the tracer runs on.
+fn table() -> &'static [Entry] {
+    if cfg!(target_arch = "x86_64") {
+        x86_64::TABLE
+    } else {
+        &[]
+    }
+}
+
+fn lookup(nr: u64) -> Option<&'static Entry> {
+    let table = table();
+    table.binary_search_by_key(&nr, |entry| entry.nr).ok().map(|idx| &table[idx])
+}
+
+const OPEN_FLAGS: &[(&str, u64)] = &[
+    ("O_CREAT", 0o100),
+    ("O_EXCL", 0o200),
+    ("O_NOCTTY", 0o400),
+    ("O_TRUNC", 0o1000),
+    ("O_APPEND", 0o2000),
+    ("O_NONBLOCK", 0o4000),
+    ("O_DSYNC", 0o10000),
+    ("O_DIRECTORY", 0o200000),
+    ("O_NOFOLLOW", 0o400000),
+    ("O_CLOEXEC", 0o2000000),
+    ("O_PATH", 0o10000000),
+];
+
+const PROT_FLAGS: &[(&str, u64)] = &[("PROT_READ", 0x1), ("PROT_WRITE", 0x2), ("PROT_EXEC", 0x4)];
+
+const MAP_FLAGS: &[(&str, u64)] = &[
+    ("MAP_FIXED", 0x10),
+    ("MAP_ANONYMOUS", 0x20),
+    ("MAP_GROWSDOWN", 0x100),
+    ("MAP_DENYWRITE", 0x800),
+    ("MAP_NORESERVE", 0x4000),
+    ("MAP_POPULATE", 0x8000),
+    ("MAP_STACK", 0x20000),
+    ("MAP_FIXED_NOREPLACE", 0x100000),
+];
+
+const ACCESS_MODES: &[(&str, u64)] = &[("R_OK", 0x4), ("W_OK", 0x2), ("X_OK", 0x1)];
+
+const SIGNALS: [&str; 31] = [
+    "SIGHUP", "SIGINT", "SIGQUIT", "SIGILL", "SIGTRAP", "SIGABRT", "SIGBUS", "SIGFPE", "SIGKILL",
+    "SIGUSR1", "SIGSEGV", "SIGUSR2", "SIGPIPE", "SIGALRM", "SIGTERM", "SIGSTKFLT", "SIGCHLD",
+    "SIGCONT", "SIGSTOP", "SIGTSTP", "SIGTTIN", "SIGTTOU", "SIGURG", "SIGXCPU", "SIGXFSZ",
+    "SIGVTALRM", "SIGPROF", "SIGWINCH", "SIGIO", "SIGPWR", "SIGSYS",
+];
+
+const ERRNOS: [&str; 40] = [
+    "EPERM", "ENOENT", "ESRCH", "EINTR", "EIO", "ENXIO", "E2BIG", "ENOEXEC", "EBADF", "ECHILD",
+    "EAGAIN", "ENOMEM", "EACCES", "EFAULT", "ENOTBLK", "EBUSY", "EEXIST", "EXDEV", "ENODEV",
+    "ENOTDIR", "EISDIR", "EINVAL", "ENFILE", "EMFILE", "ENOTTY", "ETXTBSY", "EFBIG", "ENOSPC",
+    "ESPIPE", "EROFS", "EMLINK", "EPIPE", "EDOM", "ERANGE", "EDEADLK", "ENAMETOOLONG", "ENOLCK",
+    "ENOSYS", "ENOTEMPTY", "ELOOP",
+];
+
+/// Set flags joined by `|`, followed by any bits without a name.
+fn flags(mut value: u64, names: &[(&str, u64)], mut parts: Vec<String>) -> String {
+    for &(name, bit) in names {
+        if value & bit == bit {
+            parts.push(name.to_string());
+            value &= !bit;
+        }
+    }
+
+    if value != 0 || parts.is_empty() {
+        parts.push(format!("{value:#x}"));
+    }
+
+    parts.join("|")
+}
+
+/// Quoted and escaped like a C string, with `...` appended if `truncated`.
+fn quoted(bytes: &[u8], truncated: bool) -> String {
+    let mut s = String::with_capacity(bytes.len() + 2);
+    s.push('"');
+
+    for &byte in bytes {
+        match byte {
+            b'"' => s.push_str("\\\""),
+            b'\\' => s.push_str("\\\\"),
+            b'\n' => s.push_str("\\n"),
+            b'\r' => s.push_str("\\r"),
+            b'\t' => s.push_str("\\t"),
+            0x20..=0x7e => s.push(byte as char),
+            _ => s.push_str(&format!("\\x{byte:02x}")),
+        }
+    }
+
+    s.push('"');
+    if truncated {
+        s.push_str("...");
+    }
+
+    s
+}
+
+fn ptr(value: u64) -> String {
+    match value {
+        0 => "NULL".to_string(),
+        _ => format!("{value:#x}"),
+    }
+}
+
+/// Read `len` bytes at `addr` in the tracee, returns fewer bytes if memory isn't mapped.
+pub(crate) type Read<'a> = &'a dyn Fn(usize, usize) -> Vec<u8>;
+
+fn buffer(addr: u64, len: usize, read: Read) -> String {
+    if addr == 0 {
+        return ptr(addr);
+    }
+
+    let bytes = read(addr as usize, len.min(MAX_BUF_LEN));
+    if bytes.is_empty() && len != 0 {
+        return ptr(addr);
+    }
+
+    quoted(&bytes, len > bytes.len())
+}
+
+fn path(addr: u64, read: Read) -> String {
+    if addr == 0 {
+        return ptr(addr);
+    }
+
+    let bytes = read(addr as usize, MAX_PATH_LEN);
+    match bytes.iter().position(|&b| b == 0) {
+        Some(len) => quoted(&bytes[..len], false),
+        None if bytes.is_empty() => ptr(addr),
+        None => quoted(&bytes, true),
+    }
+}
+
+fn format_arg(arg: Arg, args: &[u64; 6], idx: usize, read: Read) -> String {
+    let value = args[idx];
+
+    match arg {
+        Arg::Int => (value as i64).to_string(),
+        Arg::UInt => value.to_string(),
+        Arg::Hex => format!("{value:#x}"),
+        Arg::Ptr | Arg::OutBuf => ptr(value),
+        Arg::Fd => (value as i32).to_string(),
+        Arg::DirFd => match value as i32 {
+            AT_FDCWD => "AT_FDCWD".to_string(),
+            fd => fd.to_string(),
+        },
+        Arg::Path => path(value, read),
+        Arg::InBuf(len) => buffer(value, args[len] as usize, read),
+        Arg::OpenFlags => {
+            let mode = match value & 0o3 {
+                0 => "O_RDONLY",
+                1 => "O_WRONLY",
+                2 => "O_RDWR",
+                _ => "O_ACCMODE",
+            };
+
+            flags(value & !0o3, OPEN_FLAGS, vec![mode.to_string()])
+        }
+        Arg::Mode => match value {
+            0 => "0".to_string(),
+            _ => format!("0{value:o}"),
+        },
+        Arg::Prot => match value {
+            0 => "PROT_NONE".to_string(),
+            _ => flags(value, PROT_FLAGS, Vec::new()),
+        },
+        Arg::MapFlags => {
+            let kind = match value & 0x3 {
+                1 => "MAP_SHARED",
+                2 => "MAP_PRIVATE",
+                3 => "MAP_SHARED_VALIDATE",
+                _ => return flags(value, MAP_FLAGS, Vec::new()),
+            };
+
+            flags(value & !0x3, MAP_FLAGS, vec![kind.to_string()])
+        }
+        Arg::Signal => match SIGNALS.get((value as usize).wrapping_sub(1)) {
+            Some(name) => name.to_string(),
+            None => value.to_string(),
+        },
+        Arg::Whence => match value {
+            0 => "SEEK_SET".to_string(),
+            1 => "SEEK_CUR".to_string(),
+            2 => "SEEK_END".to_string(),
+            3 => "SEEK_DATA".to_string(),
+            4 => "SEEK_HOLE".to_string(),
+            _ => value.to_string(),
+        },
+        Arg::AccessMode => match value {
+            0 => "F_OK".to_string(),
+            _ => flags(value, ACCESS_MODES, Vec::new()),
+        },
+    }
+}
+
+/// A syscall made by the tracee, with it's arguments formatted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Syscall {
+    pub nr: u64,
+    /// Name of the syscall, [`None`] for those missing from the table.
+    pub name: Option<&'static str>,
+    pub args: Vec<String>,
+    /// Value returned, [`None`] if it never returned e.g. `exit`.
+    pub ret: Option<i64>,
+    raw: [u64; 6],
+}
+
+impl Syscall {
+    /// Decode a syscall as it's entered, before the kernel touched any of it's buffers.
+    pub(crate) fn enter(nr: u64, raw: [u64; 6], read: Read) -> Self {
+        let (name, args) = match lookup(nr) {
+            Some(entry) => {
+                let args = entry
+                    .args
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, &arg)| format_arg(arg, &raw, idx, read))
+                    .collect();
+
+                (Some(entry.name), args)
+            }
+            None => (None, raw.iter().map(|value| format!("{value:#x}")).collect()),
+        };
+
+        Self {
+            nr,
+            name,
+            args,
+            ret: None,
+            raw,
+        }
+    }
+
+    /// Complete the syscall with it's return value, decoding the buffers it filled.
+    pub(crate) fn exit(&mut self, ret: i64, read: Read) {
+        self.ret = Some(ret);
+
+        let entry = match lookup(self.nr) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if ret < 0 {
+            return;
+        }
+
+        for (idx, &arg) in entry.args.iter().enumerate() {
+            if arg == Arg::OutBuf {
+                self.args[idx] = buffer(self.raw[idx], ret as usize, read);
+            }
+        }
+    }
+
+    /// Whether the syscall failed, returning an errno.
+    pub fn failed(&self) -> bool {
+        // Errors are returned as negative errno's.
+        self.ret.is_some_and(|ret| (-4095..0).contains(&ret))
+    }
+
+    /// Return value as strace shows it, `?` if it never returned.
+    pub fn format_ret(&self) -> String {
+        let ret = match self.ret {
+            Some(ret) => ret,
+            None => return "?".to_string(),
+        };
+
+        if self.failed() {
+            let errno = -ret as usize;
+            return match ERRNOS.get(errno - 1) {
+                Some(name) => format!("-1 {name}"),
+                None => format!("-1 errno {errno}"),
+            };
+        }
+
+        match lookup(self.nr).map(|entry| entry.ret) {
+            Some(Ret::Hex) => format!("{ret:#x}"),
+            _ => ret.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Syscall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(name) => f.write_str(name)?,
+            None => f.write_fmt(format_args!("syscall_{}", self.nr))?,
+        }
+
+        f.write_fmt(format_args!("({}) = {}", self.args.join(", "), self.format_ret()))
+    }
+}
+
+#[cfg(all(test, target_arch = "x86_64"))]
+mod tests {
+    use super::*;
+
+    const PATH: usize = 0x1000;
+    const BUF: usize = 0x2000;
+
+    /// Memory of a fake tracee, holding a path and a buffer.
+    fn read(addr: usize, len: usize) -> Vec<u8> {
+        let (start, bytes): (usize, &[u8]) = match addr {
+            PATH..=0x1fff => (PATH, b"/etc/passwd\0garbage"),
+            BUF..=0x2fff => (BUF, b"root:x:0:0:root:/root:/bin/bash\nbin:x:1:1"),
+            _ => return Vec::new(),
+        };
+
+        let bytes = bytes.get(addr - start..).unwrap_or_default();
+        bytes[..len.min(bytes.len())].to_vec()
+    }
+
+    fn syscall(nr: u64, args: [u64; 6], ret: Option<i64>) -> String {
+        let mut syscall = Syscall::enter(nr, args, &read);
+        if let Some(ret) = ret {
+            syscall.exit(ret, &read);
+        }
+
+        syscall.to_string()
+    }
+
+    #[test]
+    fn sorted() {
+        assert!(x86_64::TABLE.windows(2).all(|pair| pair[0].nr < pair[1].nr));
+    }
+
+    #[test]
+    fn paths_and_flags() {
+        let args = [-100i64 as u64, PATH as u64, 0o2000000, 0, 0, 0];
+        assert_eq!(
+            syscall(257, args, Some(3)),
+            "openat(AT_FDCWD, \"/etc/passwd\", O_RDONLY|O_CLOEXEC, 0) = 3"
+        );
+
+        let args = [3, PATH as u64, 0o1101, 0o644, 0, 0];
+        assert_eq!(
+            syscall(257, args, Some(-2)),
+            "openat(3, \"/etc/passwd\", O_WRONLY|O_CREAT|O_TRUNC, 0644) = -1 ENOENT"
+        );
+
+        let args = [0, 8192, 0x3, 0x22, -1i64 as u64, 0];
+        assert_eq!(
+            syscall(9, args, Some(0x7f0000000000)),
+            "mmap(NULL, 8192, PROT_READ|PROT_WRITE, MAP_PRIVATE|MAP_ANONYMOUS, -1, 0) \
+             = 0x7f0000000000"
+        );
+    }
+
+    #[test]
+    fn buffers() {
+        // Output buffers are only read once the syscall returns.
+        let mut read_call = Syscall::enter(0, [3, BUF as u64, 832, 0, 0, 0], &read);
+        assert_eq!(read_call.args[1], "0x2000");
+        read_call.exit(4, &read);
+        assert_eq!(read_call.to_string(), "read(3, \"root\", 832) = 4");
+
+        let args = [1, BUF as u64, 42, 0, 0, 0];
+        assert_eq!(
+            syscall(1, args, Some(42)),
+            "write(1, \"root:x:0:0:root:/root:/bin/bash\\n\"..., 42) = 42"
+        );
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(syscall(231, [0; 6], None), "exit_group(0) = ?");
+        assert_eq!(
+            syscall(1000, [1, 2, 0, 0, 0, 0], Some(0)),
+            "syscall_1000(0x1, 0x2, 0x0, 0x0, 0x0, 0x0) = 0"
+        );
+    }
+}
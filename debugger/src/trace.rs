@@ -0,0 +1,345 @@
+//! Recording the instructions a thread executes, by single stepping it.
+//!
+//! Addresses are stored as the difference to the previous one, which is small as most
+//! instructions follow each other. The differences are zigzag encoded varints, so most
+//! instructions take a single byte of the trace.
+//!
+//! Before each instruction the registers and the memory it's about to overwrite are saved, so
+//! the last instructions can be undone by writing them back. Memory written by system calls
+//! or through addresses that aren't known before the instruction executes isn't restored.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{self, Write};
+
+use crate::{BreakpointSet, Error, Registers, Stop, Target};
+
+/// Most instructions recorded, the thread is continued without recording afterwards.
+pub const MAX_LEN: usize = 100_000_000;
+
+/// Most instructions that can be stepped back through.
+pub const MAX_SNAPSHOTS: usize = 10_000;
+
+/// Memory an instruction writes, given it's address in the binary and the registers it's
+/// executed with, as the runtime address and length of each write.
+pub type Stores<'a> = &'a dyn Fn(usize, &Registers) -> Vec<(usize, usize)>;
+
+/// Instructions between the points the trace can be decoded from.
+const CHECKPOINT_LEN: usize = 4096;
+
+/// Addresses in the binary of the instructions a thread executed, in the order it did.
+#[derive(Debug, Default, Clone)]
+pub struct Trace {
+    bytes: Vec<u8>,
+    len: usize,
+    /// Address pushed last, which the next one is encoded relative to.
+    last: usize,
+    /// Offset into `bytes` and the address before it, every [`CHECKPOINT_LEN`] instructions.
+    checkpoints: Vec<(usize, usize)>,
+    /// Number of times each address was executed.
+    counts: HashMap<usize, usize>,
+}
+
+impl Trace {
+    pub fn push(&mut self, addr: usize) {
+        if self.len.is_multiple_of(CHECKPOINT_LEN) {
+            self.checkpoints.push((self.bytes.len(), self.last));
+        }
+
+        let delta = addr.wrapping_sub(self.last) as isize;
+        let mut zigzag = ((delta << 1) ^ (delta >> (isize::BITS - 1))) as usize;
+        while zigzag >= 0x80 {
+            self.bytes.push(zigzag as u8 | 0x80);
+            zigzag >>= 7;
+        }
+
+        self.bytes.push(zigzag as u8);
+        self.last = addr;
+        self.len += 1;
+        *self.counts.entry(addr).or_default() += 1;
+    }
+
+    /// Number of instructions recorded.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len >= MAX_LEN
+    }
+
+    /// Size of the recorded addresses in bytes, leaving out their counts.
+    pub fn size(&self) -> usize {
+        self.bytes.len() + self.checkpoints.len() * std::mem::size_of::<(usize, usize)>()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.iter_from(0)
+    }
+
+    /// Addresses from the instruction executed `start` instructions into the trace.
+    pub fn iter_from(&self, start: usize) -> Iter<'_> {
+        let mut iter = match self.checkpoints.get(start / CHECKPOINT_LEN) {
+            Some(&(offset, last)) => Iter {
+                bytes: &self.bytes[offset..],
+                last,
+            },
+            None => Iter { bytes: &[], last: 0 },
+        };
+
+        for _ in 0..start % CHECKPOINT_LEN {
+            iter.next();
+        }
+
+        iter
+    }
+
+    /// Number of times each address was executed.
+    pub fn counts(&self) -> &HashMap<usize, usize> {
+        &self.counts
+    }
+
+    /// Write the addresses in hex, one per line.
+    pub fn write_text(&self, writer: &mut impl Write) -> io::Result<()> {
+        for addr in self.iter() {
+            writeln!(writer, "{addr:#x}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write an object with the addresses in order and how often each was executed, with
+    /// addresses as strings in hex.
+    pub fn write_json(&self, writer: &mut impl Write) -> io::Result<()> {
+        write!(writer, "{{\"addresses\":[")?;
+        for (idx, addr) in self.iter().enumerate() {
+            if idx != 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "\"{addr:#x}\"")?;
+        }
+
+        write!(writer, "],\"counts\":{{")?;
+        let counts: BTreeMap<&usize, &usize> = self.counts.iter().collect();
+        for (idx, (addr, count)) in counts.into_iter().enumerate() {
+            if idx != 0 {
+                write!(writer, ",")?;
+            }
+            write!(writer, "\"{addr:#x}\":{count}")?;
+        }
+
+        writeln!(writer, "}}}}")
+    }
+}
+
+/// Addresses of a [`Trace`], decoded as they're iterated.
+pub struct Iter<'a> {
+    bytes: &'a [u8],
+    last: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let mut zigzag = 0usize;
+        let mut shift = 0;
+        loop {
+            let (&byte, rest) = self.bytes.split_first()?;
+            self.bytes = rest;
+            zigzag |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+
+        let delta = (zigzag >> 1) as isize ^ -((zigzag & 1) as isize);
+        self.last = self.last.wrapping_add(delta as usize);
+        Some(self.last)
+    }
+}
+
+/// State of a thread before it executed an instruction.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    /// Address in the binary of the instruction.
+    pc: usize,
+    /// General purpose registers.
+    registers: Vec<(&'static str, u64)>,
+    /// Memory the instruction overwrote, at it's runtime address.
+    memory: Vec<(usize, Vec<u8>)>,
+}
+
+/// State of a thread before each of the last [`MAX_SNAPSHOTS`] instructions it executed whilst
+/// recording, which it can be stepped back through.
+#[derive(Debug, Default)]
+pub struct History {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl History {
+    /// Number of instructions that can be stepped back through.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Forget the snapshots, once the thread executed anything without them being taken.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Save the state of the current thread before it executes the instruction at `pc`.
+    fn snapshot(&mut self, target: &dyn Target, pc: usize, stores: Stores) -> Result<(), Error> {
+        let registers = target.registers()?;
+
+        // Writes to memory that can't be read fault, so there's nothing to restore.
+        let memory = stores(pc, &registers)
+            .into_iter()
+            .filter_map(|(addr, len)| Some((addr, target.read_memory(addr, len).ok()?)))
+            .collect();
+
+        if self.snapshots.len() == MAX_SNAPSHOTS {
+            self.snapshots.pop_front();
+        }
+
+        self.snapshots.push_back(Snapshot {
+            pc,
+            registers: registers.general,
+            memory,
+        });
+
+        Ok(())
+    }
+
+    /// Undo the last instruction the current thread executed, returning the stop at it. None if
+    /// there's nothing to step back through.
+    pub fn step_back(&mut self, target: &mut dyn Target) -> Result<Option<Stop>, Error> {
+        let snapshot = match self.snapshots.pop_back() {
+            Some(snapshot) => snapshot,
+            None => return Ok(None),
+        };
+
+        for (addr, bytes) in snapshot.memory.iter().rev() {
+            target.write_memory(*addr, bytes)?;
+        }
+
+        target.write_registers(&snapshot.registers)?;
+        Ok(Some(Stop::Stepped(snapshot.pc)))
+    }
+}
+
+/// Execute a single instruction of the current thread, recording it in `trace` and saving the
+/// state before it in `history`.
+pub fn step(
+    target: &mut dyn Target,
+    trace: &mut Trace,
+    history: &mut History,
+    stores: Stores,
+) -> Result<Stop, Error> {
+    let pc = target.pc()?;
+    history.snapshot(&*target, pc, stores)?;
+    if !trace.is_full() {
+        trace.push(pc);
+    }
+
+    target.step()
+}
+
+/// Single step the current thread until it stops for anything but the step, recording each
+/// instruction it executes like [`step`]. Breakpoints are stopped at before the instruction
+/// they're on is recorded. Once the trace is full the thread is continued instead.
+pub fn record(
+    target: &mut dyn Target,
+    breakpoints: &BreakpointSet,
+    trace: &mut Trace,
+    history: &mut History,
+    stores: Stores,
+) -> Result<Stop, Error> {
+    loop {
+        if trace.is_full() {
+            history.clear();
+            return target.cont();
+        }
+
+        let pc = match step(target, trace, history, stores)? {
+            Stop::Stepped(pc) => pc,
+            stop => return Ok(stop),
+        };
+
+        // Steps execute the instruction a breakpoint is on without stopping.
+        if breakpoints.contains(pc) {
+            return Ok(Stop::Breakpoint(pc));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let addrs: Vec<usize> = (0..10_000)
+            .map(|idx| match idx % 7 {
+                0 => 0x401000 + idx,
+                3 => usize::MAX - idx,
+                5 => 0x7fff_0000_0000 + idx * 0x1000,
+                _ => 0x401000 + idx / 2,
+            })
+            .collect();
+
+        let mut trace = Trace::default();
+        for &addr in &addrs {
+            trace.push(addr);
+        }
+
+        assert_eq!(trace.len(), addrs.len());
+        assert_eq!(trace.iter().collect::<Vec<_>>(), addrs);
+        assert_eq!(trace.iter_from(4100).next(), Some(addrs[4100]));
+        assert_eq!(trace.iter_from(8192).collect::<Vec<_>>(), addrs[8192..]);
+        assert_eq!(trace.iter_from(addrs.len()).next(), None);
+    }
+
+    #[test]
+    fn compressed() {
+        let mut trace = Trace::default();
+        for addr in (0x401000..0x402000).step_by(4) {
+            trace.push(addr);
+        }
+
+        // The first address takes a few bytes, every one after it a single byte.
+        assert!(trace.bytes.len() < 1024 + 8);
+    }
+
+    #[test]
+    fn export() {
+        let mut trace = Trace::default();
+        for addr in [0x1000, 0x1004, 0x1000, 0x1004, 0x1008] {
+            trace.push(addr);
+        }
+
+        let counts = trace.counts();
+        assert_eq!(counts[&0x1000], 2);
+        assert_eq!(counts[&0x1008], 1);
+
+        let mut text = Vec::new();
+        trace.write_text(&mut text).unwrap();
+        assert_eq!(text, b"0x1000\n0x1004\n0x1000\n0x1004\n0x1008\n");
+
+        let mut json = Vec::new();
+        trace.write_json(&mut json).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"addresses\":[\"0x1000\",\"0x1004\",\"0x1000\",\"0x1004\",\"0x1008\"],\
+             \"counts\":{\"0x1000\":2,\"0x1004\":2,\"0x1008\":1}}\n"
+        );
+    }
+}
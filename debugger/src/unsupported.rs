@@ -0,0 +1,153 @@
+use crate::{
+    BreakpointSet, CallFrameInfo, Environment, Error, FollowFork, Frame, Handle, Library,
+    MemoryRegion, ProcessInfo, Registers, Signals, Stdio, Stop, Syscall, Thread, WatchKind,
+};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub fn exe_path(_pid: u32) -> Result<PathBuf, Error> {
+    Err(Error::Unsupported)
+}
+
+pub fn processes() -> Vec<ProcessInfo> {
+    Vec::new()
+}
+
+pub struct Debugger {
+    _private: (),
+}
+
+impl Debugger {
+    pub fn spawn(
+        _path: &Path,
+        _args: &[String],
+        _env: &Environment,
+        _breakpoints: Arc<BreakpointSet>,
+        _entrypoint: usize,
+        _stdio: Option<Stdio>,
+    ) -> Result<Self, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn attach(
+        _pid: u32,
+        _breakpoints: Arc<BreakpointSet>,
+        _entrypoint: usize,
+    ) -> Result<Self, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn pc(&self) -> Result<usize, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn pid(&self) -> u32 {
+        0
+    }
+
+    pub fn bias(&self) -> usize {
+        0
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle::Process(ProcessHandle { _private: () })
+    }
+
+    pub fn trace_syscalls<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Syscall) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    pub fn watch_libraries<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Vec<Library>) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    pub fn set_watchpoint(
+        &mut self,
+        _addr: usize,
+        _len: usize,
+        _kind: WatchKind,
+    ) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn registers(&self) -> Result<Registers, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn set_register(&mut self, _name: &str, _value: u64) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn read_memory(&self, _addr: usize, _len: usize) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn write_memory(&mut self, _addr: usize, _bytes: &[u8]) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn fault_addr(&self) -> Result<Option<usize>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn backtrace(&self, _cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn threads(&self) -> Result<Vec<Thread>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn select_thread(&mut self, _tid: u32) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn set_follow_fork(&mut self, _follow: FollowFork) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn set_signals(&mut self, _signals: Signals) -> Result<(), Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn cont(&mut self) -> Result<Stop, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn step(&mut self) -> Result<Stop, Error> {
+        Err(Error::Unsupported)
+    }
+}
+
+#[derive(Clone)]
+pub struct ProcessHandle {
+    _private: (),
+}
+
+impl ProcessHandle {
+    pub fn interrupt(&self) {}
+
+    pub fn pause(&self) {}
+
+    pub fn kill(&self) {}
+
+    pub fn detach(&self) {}
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        Err(Error::Unsupported)
+    }
+
+    pub fn read_memory(&self, _addr: usize, _len: usize) -> Result<Vec<u8>, Error> {
+        Err(Error::Unsupported)
+    }
+}
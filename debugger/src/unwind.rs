@@ -0,0 +1,201 @@
+//! Walking the stack of a stopped process.
+//!
+//! Callers are found through the binary's `.eh_frame` where it describes the function, falling
+//! back to following frame pointers elsewhere.
+
+use gimli::{
+    BaseAddresses, CfaRule, EhFrame, LittleEndian, RegisterRule, UnwindContext, UnwindSection,
+    UnwindTableRow, X86_64,
+};
+
+/// Most frames walked, corrupt stacks might otherwise never end.
+const MAX_FRAMES: usize = 256;
+
+/// A function call on the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Address as found in the binary, for callers it's where the call returns to.
+    pub pc: usize,
+    /// Stack pointer whilst in the frame.
+    pub sp: usize,
+}
+
+/// The binary's call frame information, used for recovering the registers of callers.
+#[derive(Default)]
+pub struct CallFrameInfo {
+    eh_frame: Vec<u8>,
+    /// Address of `.eh_frame` in the binary, pointers in it are relative to it.
+    addr: u64,
+}
+
+impl CallFrameInfo {
+    pub fn new(eh_frame: Vec<u8>, addr: usize) -> Self {
+        Self {
+            eh_frame,
+            addr: addr as u64,
+        }
+    }
+}
+
+/// Registers needed for finding a frame's caller, at their runtime values.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Regs {
+    pub pc: u64,
+    pub sp: u64,
+    pub fp: u64,
+}
+
+/// Frames on the stack, innermost first.
+///
+/// `read` returns the word at a runtime address and `bias` is the offset between addresses in
+/// the binary and where it's loaded.
+pub(crate) fn unwind<F>(cfi: &CallFrameInfo, mut regs: Regs, bias: usize, read: F) -> Vec<Frame>
+where
+    F: Fn(u64) -> Option<u64>,
+{
+    let eh_frame = EhFrame::new(&cfi.eh_frame, LittleEndian);
+    let bases = BaseAddresses::default().set_eh_frame(cfi.addr);
+    let mut ctx = UnwindContext::new();
+    let mut frames = Vec::new();
+
+    while frames.len() < MAX_FRAMES {
+        let pc = (regs.pc as usize).wrapping_sub(bias);
+        frames.push(Frame {
+            pc,
+            sp: regs.sp as usize,
+        });
+
+        // Calls can be the last instruction of a function, so the return address might already
+        // belong to the next one.
+        let lookup = if frames.len() == 1 { pc } else { pc.wrapping_sub(1) };
+        let row = eh_frame.unwind_info_for_address(
+            &bases,
+            &mut ctx,
+            lookup as u64,
+            EhFrame::cie_from_offset,
+        );
+
+        let caller = match row {
+            Ok(row) => caller_from_cfi(row, regs, &read),
+            Err(_) => caller_from_fp(regs, &read),
+        };
+
+        match caller {
+            // The stack grows down, so callers must be further up.
+            Some(caller) if caller.pc != 0 && caller.sp > regs.sp => regs = caller,
+            _ => break,
+        }
+    }
+
+    frames
+}
+
+fn caller_from_cfi<R, F>(row: &UnwindTableRow<R>, regs: Regs, read: &F) -> Option<Regs>
+where
+    R: gimli::Reader,
+    F: Fn(u64) -> Option<u64>,
+{
+    let cfa = match *row.cfa() {
+        CfaRule::RegisterAndOffset { register, offset } => {
+            let base = match register {
+                X86_64::RSP => regs.sp,
+                X86_64::RBP => regs.fp,
+                _ => return None,
+            };
+
+            base.wrapping_add_signed(offset)
+        }
+        CfaRule::Expression(_) => return None,
+    };
+
+    // Without a return address this is the outermost frame.
+    let pc = match row.register(X86_64::RA) {
+        RegisterRule::Undefined => return None,
+        rule => restore(rule, regs.pc, cfa, read)?,
+    };
+
+    let fp = restore(row.register(X86_64::RBP), regs.fp, cfa, read)?;
+
+    Some(Regs { pc, sp: cfa, fp })
+}
+
+/// Value of a register in the caller, `current` being it's value in the callee.
+fn restore<R, F>(rule: RegisterRule<R>, current: u64, cfa: u64, read: &F) -> Option<u64>
+where
+    R: gimli::Reader,
+    F: Fn(u64) -> Option<u64>,
+{
+    match rule {
+        // Callee-saved registers without a rule weren't touched.
+        RegisterRule::Undefined | RegisterRule::SameValue => Some(current),
+        RegisterRule::Offset(offset) => read(cfa.wrapping_add_signed(offset)),
+        RegisterRule::ValOffset(offset) => Some(cfa.wrapping_add_signed(offset)),
+        _ => None,
+    }
+}
+
+/// Assumes the callee started with `push rbp; mov rbp, rsp`.
+fn caller_from_fp<F>(regs: Regs, read: &F) -> Option<Regs>
+where
+    F: Fn(u64) -> Option<u64>,
+{
+    if regs.fp == 0 {
+        return None;
+    }
+
+    Some(Regs {
+        pc: read(regs.fp.wrapping_add(8))?,
+        sp: regs.fp.wrapping_add(16),
+        fp: read(regs.fp)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn stack(words: &[(u64, u64)]) -> impl Fn(u64) -> Option<u64> {
+        let words: HashMap<u64, u64> = words.iter().copied().collect();
+        move |addr| words.get(&addr).copied()
+    }
+
+    #[test]
+    fn frame_pointers() {
+        // main -> foo -> bar, each saving the caller's frame pointer and return address.
+        let read = stack(&[
+            (0x7f00, 0x7f40),
+            (0x7f08, 0x401234),
+            (0x7f40, 0x7f80),
+            (0x7f48, 0x401100),
+            (0x7f80, 0),
+            (0x7f88, 0x401010),
+        ]);
+
+        let regs = Regs {
+            pc: 0x401300,
+            sp: 0x7ef0,
+            fp: 0x7f00,
+        };
+
+        let frames = unwind(&CallFrameInfo::default(), regs, 0x1000, read);
+        let pcs: Vec<usize> = frames.iter().map(|frame| frame.pc).collect();
+        assert_eq!(pcs, [0x400300, 0x400234, 0x400100, 0x400010]);
+        assert_eq!(frames[1].sp, 0x7f10);
+    }
+
+    #[test]
+    fn corrupt_stack() {
+        // A frame pointer that points down the stack would loop forever.
+        let read = stack(&[(0x7f00, 0x7e00), (0x7f08, 0x401234), (0x7e08, 0x401100)]);
+
+        let regs = Regs {
+            pc: 0x401300,
+            sp: 0x7ef0,
+            fp: 0x7f00,
+        };
+
+        let frames = unwind(&CallFrameInfo::default(), regs, 0, read);
+        assert_eq!(frames.len(), 2);
+    }
+}
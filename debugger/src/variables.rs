@@ -0,0 +1,256 @@
+//! Finding variables of a stopped thread, by evaluating the DWARF expressions that describe
+//! where they're stored.
+
+use gimli::{
+    Encoding, EndianSlice, Evaluation, EvaluationResult, Format, LittleEndian, Location, Value,
+};
+
+use crate::{Error, Registers};
+
+/// Most operations evaluated for an expression, as expressions can branch backwards.
+const MAX_ITERATIONS: u32 = 10_000;
+
+/// Registers of x86_64 by their DWARF register number.
+const X86_64_REGISTERS: [&str; 17] = [
+    "rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12",
+    "r13", "r14", "r15", "rip",
+];
+
+/// Registers of aarch64 by their DWARF register number.
+const AARCH64_REGISTERS: [&str; 32] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+    "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26",
+    "x27", "x28", "x29", "x30", "sp",
+];
+
+/// Where a variable's value is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Place {
+    /// Memory at the runtime address.
+    Memory(usize),
+    /// General purpose register by it's name.
+    Register(&'static str),
+    /// The value isn't stored anywhere, but the expression computed it's bytes.
+    Bytes(Vec<u8>),
+    /// The compiler didn't keep the value around.
+    OptimizedOut,
+}
+
+/// State of a stopped thread that expressions are evaluated against.
+pub struct Context<'a> {
+    /// Registers of the innermost frame.
+    pub registers: &'a Registers,
+    /// Canonical frame address, the stack pointer before the current function was called.
+    pub cfa: Option<usize>,
+    /// Offset between addresses in the binary and where it's loaded.
+    pub bias: usize,
+    /// Size of a pointer in the expressions.
+    pub address_size: u8,
+    /// Reads the target's memory at a runtime address.
+    pub read: &'a dyn Fn(usize, usize) -> Result<Vec<u8>, Error>,
+}
+
+impl Context<'_> {
+    /// Where the value described by `expr` is, `frame_base` being the expression of the frame
+    /// base of the function it's declared in.
+    pub fn locate(&self, expr: &[u8], frame_base: Option<&[u8]>) -> Result<Place, Error> {
+        // Empty expressions are how optimized out values are described.
+        if expr.is_empty() {
+            return Ok(Place::OptimizedOut);
+        }
+
+        let pieces = self.evaluate(expr, frame_base)?;
+
+        match pieces.as_slice() {
+            [] => Ok(Place::OptimizedOut),
+            [piece] if piece.size_in_bits.is_none() => self.place(&piece.location),
+            // Values split over several places are put back together.
+            pieces => {
+                let mut bytes = Vec::new();
+                for piece in pieces {
+                    let size = piece.size_in_bits.unwrap_or(0) as usize / 8;
+                    match self.read(&self.place(&piece.location)?, size)? {
+                        Some(piece) => bytes.extend(piece),
+                        None => return Ok(Place::OptimizedOut),
+                    }
+                }
+
+                Ok(Place::Bytes(bytes))
+            }
+        }
+    }
+
+    /// The `size` bytes of the value at `place`, None if it was optimized out.
+    pub fn read(&self, place: &Place, size: usize) -> Result<Option<Vec<u8>>, Error> {
+        let mut bytes = match *place {
+            Place::Memory(addr) => return (self.read)(addr, size).map(Some),
+            Place::Register(name) => {
+                let value = self.registers.get(name).ok_or(Error::InvalidRegister)?;
+                value.to_le_bytes().to_vec()
+            }
+            Place::Bytes(ref bytes) => bytes.clone(),
+            Place::OptimizedOut => return Ok(None),
+        };
+
+        bytes.resize(size, 0);
+        Ok(Some(bytes))
+    }
+
+    fn encoding(&self) -> Encoding {
+        Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: self.address_size,
+        }
+    }
+
+    fn register(&self, register: gimli::Register) -> Result<&'static str, Error> {
+        let names: &[&'static str] = match self.registers.get("rip") {
+            Some(_) => &X86_64_REGISTERS,
+            None => &AARCH64_REGISTERS,
+        };
+
+        names.get(register.0 as usize).copied().ok_or(Error::InvalidRegister)
+    }
+
+    fn place(&self, location: &Location<EndianSlice<LittleEndian>>) -> Result<Place, Error> {
+        let place = match *location {
+            Location::Address { address } => Place::Memory(address as usize),
+            Location::Register { register } => Place::Register(self.register(register)?),
+            Location::Value { value } => {
+                let bytes = match value {
+                    Value::F32(value) => value.to_le_bytes().to_vec(),
+                    Value::F64(value) => value.to_le_bytes().to_vec(),
+                    value => value.to_u64(u64::MAX).map_err(invalid)?.to_le_bytes().to_vec(),
+                };
+
+                Place::Bytes(bytes)
+            }
+            Location::Bytes { value } => Place::Bytes(value.to_vec()),
+            Location::Empty | Location::ImplicitPointer { .. } => Place::OptimizedOut,
+        };
+
+        Ok(place)
+    }
+
+    /// Address the frame base expression `expr` evaluates to.
+    fn frame_base(&self, expr: &[u8]) -> Result<u64, Error> {
+        let pieces = self.evaluate(expr, None)?;
+        match pieces.first().map(|piece| &piece.location) {
+            Some(Location::Address { address }) => Ok(*address),
+            Some(&Location::Register { register }) => {
+                let name = self.register(register)?;
+                self.registers.get(name).ok_or(Error::InvalidRegister)
+            }
+            _ => Err(Error::InvalidExpression),
+        }
+    }
+
+    fn evaluate<'a>(
+        &self,
+        expr: &'a [u8],
+        frame_base: Option<&[u8]>,
+    ) -> Result<Vec<gimli::Piece<EndianSlice<'a, LittleEndian>>>, Error> {
+        let mut eval = Evaluation::new(EndianSlice::new(expr, LittleEndian), self.encoding());
+        eval.set_max_iterations(MAX_ITERATIONS);
+
+        let mut result = eval.evaluate().map_err(invalid)?;
+        loop {
+            result = match result {
+                EvaluationResult::Complete => break,
+                EvaluationResult::RequiresMemory { address, size, .. } => {
+                    let mut bytes = (self.read)(address as usize, size as usize)?;
+                    bytes.resize(8, 0);
+                    let word = u64::from_le_bytes(bytes.try_into().unwrap());
+                    eval.resume_with_memory(Value::Generic(word))
+                }
+                EvaluationResult::RequiresRegister { register, .. } => {
+                    let name = self.register(register)?;
+                    let value = self.registers.get(name).ok_or(Error::InvalidRegister)?;
+                    eval.resume_with_register(Value::Generic(value))
+                }
+                EvaluationResult::RequiresFrameBase => {
+                    let expr = frame_base.ok_or(Error::InvalidExpression)?;
+                    eval.resume_with_frame_base(self.frame_base(expr)?)
+                }
+                EvaluationResult::RequiresCallFrameCfa => {
+                    let cfa = self.cfa.ok_or(Error::InvalidExpression)?;
+                    eval.resume_with_call_frame_cfa(cfa as u64)
+                }
+                EvaluationResult::RequiresRelocatedAddress(addr) => {
+                    eval.resume_with_relocated_address(addr.wrapping_add(self.bias as u64))
+                }
+                _ => return Err(Error::InvalidExpression),
+            }
+            .map_err(invalid)?;
+        }
+
+        Ok(eval.result())
+    }
+}
+
+fn invalid(_: gimli::Error) -> Error {
+    Error::InvalidExpression
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn registers() -> Registers {
+        Registers {
+            general: vec![("rip", 0x401130), ("rsp", 0x7f00), ("rbp", 0x7f10), ("rdi", 3)],
+            ..Registers::default()
+        }
+    }
+
+    fn check(expr: &[u8], frame_base: Option<&[u8]>, expected: Place) {
+        let memory: HashMap<usize, Vec<u8>> =
+            HashMap::from([(0x7f10, 0x7f40u64.to_le_bytes().to_vec())]);
+        let read = |addr: usize, len: usize| match memory.get(&addr) {
+            Some(bytes) => Ok(bytes[..len].to_vec()),
+            None => Err(Error::InvalidExpression),
+        };
+
+        let registers = registers();
+        let ctx = Context {
+            registers: &registers,
+            cfa: Some(0x7f20),
+            bias: 0x400000,
+            address_size: 8,
+            read: &read,
+        };
+
+        assert_eq!(ctx.locate(expr, frame_base).unwrap(), expected);
+    }
+
+    #[test]
+    fn frame_base() {
+        // DW_OP_fbreg -20, with DW_OP_call_frame_cfa as the frame base.
+        check(&[0x91, 0x6c], Some(&[0x9c]), Place::Memory(0x7f0c));
+        // DW_OP_fbreg 8, with DW_OP_reg6 (rbp) as the frame base.
+        check(&[0x91, 0x08], Some(&[0x56]), Place::Memory(0x7f18));
+    }
+
+    #[test]
+    fn registers_and_memory() {
+        // DW_OP_reg5 (rdi)
+        check(&[0x55], None, Place::Register("rdi"));
+        // DW_OP_breg6 (rbp) 0, DW_OP_deref
+        check(&[0x76, 0x00, 0x06], None, Place::Memory(0x7f40));
+    }
+
+    #[test]
+    fn values() {
+        // DW_OP_addr 0x4010, relocated to where the binary is loaded.
+        let mut addr = vec![0x03];
+        addr.extend(0x4010u64.to_le_bytes());
+        check(&addr, None, Place::Memory(0x404010));
+        // DW_OP_lit1, DW_OP_stack_value, DW_OP_piece 4, DW_OP_lit2, DW_OP_stack_value,
+        // DW_OP_piece 4
+        let pieces = [0x31, 0x9f, 0x93, 0x04, 0x32, 0x9f, 0x93, 0x04];
+        check(&pieces, None, Place::Bytes(vec![1, 0, 0, 0, 2, 0, 0, 0]));
+        check(&[], None, Place::OptimizedOut);
+    }
+}
@@ -0,0 +1,1410 @@
+//! Debugging on Windows through the debug API, events are received with `WaitForDebugEvent`
+//! and threads stay suspended until the event is continued.
+//!
+//! Exceptions the program raises are passed on to it, only the ones it doesn't handle stop the
+//! debugger. They're reported as the signal linux would raise for them.
+
+use crate::breakpoint::{self, Inserted, Memory};
+use crate::maps::Permissions;
+use crate::unwind::{self, Regs};
+use crate::{
+    BreakpointSet, CallFrameInfo, Environment, Error, FollowFork, Frame, Handle, Library,
+    MemoryRegion, ProcessInfo, Registers, Signals, Stdio, Stop, Syscall, Thread, WatchKind,
+    Watchpoint, X86_FLAGS,
+};
+use std::collections::BTreeMap;
+use std::ffi::{c_void, OsString};
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::os::windows::io::AsRawHandle;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use windows_sys::Win32::Foundation::{
+    CloseHandle, SetHandleInformation, BOOL, DBG_CONTINUE, DBG_EXCEPTION_NOT_HANDLED, HANDLE,
+    HANDLE_FLAG_INHERIT, INVALID_HANDLE_VALUE,
+};
+use windows_sys::Win32::System::Diagnostics::Debug::{
+    ContinueDebugEvent, DebugActiveProcess, DebugActiveProcessStop, DebugBreakProcess,
+    DebugSetProcessKillOnExit, FlushInstructionCache, GetThreadContext, ReadProcessMemory,
+    SetThreadContext, WaitForDebugEvent, WriteProcessMemory, CONTEXT,
+    CONTEXT_DEBUG_REGISTERS_AMD64, CONTEXT_FULL_AMD64, CREATE_PROCESS_DEBUG_EVENT,
+    CREATE_THREAD_DEBUG_EVENT, DEBUG_EVENT, EXCEPTION_DEBUG_EVENT, EXCEPTION_DEBUG_INFO,
+    EXIT_PROCESS_DEBUG_EVENT, EXIT_THREAD_DEBUG_EVENT, LOAD_DLL_DEBUG_EVENT,
+};
+use windows_sys::Win32::System::Console::{GetStdHandle, STD_ERROR_HANDLE, STD_OUTPUT_HANDLE};
+use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+    TH32CS_SNAPPROCESS,
+};
+use windows_sys::Win32::System::LibraryLoader::{GetModuleHandleA, GetProcAddress};
+use windows_sys::Win32::System::Memory::{
+    VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_COMMIT, MEM_IMAGE, MEM_MAPPED,
+    PAGE_EXECUTE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY,
+    PAGE_READONLY, PAGE_READWRITE, PAGE_WRITECOPY,
+};
+use windows_sys::Win32::System::ProcessStatus::GetMappedFileNameW;
+use windows_sys::Win32::System::Threading::{
+    CreateProcessW, OpenProcess, QueryFullProcessImageNameW, ResumeThread, SuspendThread,
+    TerminateProcess, CREATE_UNICODE_ENVIRONMENT, DEBUG_ONLY_THIS_PROCESS, INFINITE,
+    PROCESS_ALL_ACCESS, PROCESS_INFORMATION, PROCESS_NAME_WIN32,
+    PROCESS_QUERY_LIMITED_INFORMATION, STARTUPINFOW, STARTF_USESTDHANDLES,
+};
+
+/// Exception codes the debugger handles itself.
+const EXCEPTION_BREAKPOINT: u32 = 0x80000003;
+const EXCEPTION_SINGLE_STEP: u32 = 0x80000004;
+const DBG_CONTROL_C: u32 = 0x40010005;
+
+/// Exception code of accessing memory that isn't mapped or lacks the permission.
+const EXCEPTION_ACCESS_VIOLATION: u32 = 0xc0000005;
+
+/// Exception codes followed by the signal linux raises for them.
+const SIGNALS: [(u32, i32); 13] = [
+    (0xc0000005, SIGSEGV), // EXCEPTION_ACCESS_VIOLATION
+    (0xc0000006, SIGBUS),  // EXCEPTION_IN_PAGE_ERROR
+    (0xc00000fd, SIGSEGV), // EXCEPTION_STACK_OVERFLOW
+    (0xc000001d, SIGILL),  // EXCEPTION_ILLEGAL_INSTRUCTION
+    (0xc0000096, SIGILL),  // EXCEPTION_PRIV_INSTRUCTION
+    (0xc0000094, SIGFPE),  // EXCEPTION_INT_DIVIDE_BY_ZERO
+    (0xc0000095, SIGFPE),  // EXCEPTION_INT_OVERFLOW
+    (0xc000008e, SIGFPE),  // EXCEPTION_FLT_DIVIDE_BY_ZERO
+    (0xc0000090, SIGFPE),  // EXCEPTION_FLT_INVALID_OPERATION
+    (0xc0000091, SIGFPE),  // EXCEPTION_FLT_OVERFLOW
+    (0xc0000093, SIGFPE),  // EXCEPTION_FLT_UNDERFLOW
+    (DBG_CONTROL_C, SIGINT),
+    (EXCEPTION_BREAKPOINT, SIGTRAP),
+];
+
+const SIGINT: i32 = 2;
+const SIGILL: i32 = 4;
+const SIGTRAP: i32 = 5;
+const SIGABRT: i32 = 6;
+const SIGBUS: i32 = 7;
+const SIGFPE: i32 = 8;
+const SIGSEGV: i32 = 11;
+const SIGSTOP: i32 = 19;
+
+/// Trap flag in `EFlags`, raising a trap after every instruction.
+const TF: u32 = 1 << 8;
+
+/// Granularity of reads that stop at the first unmapped byte.
+const PAGE_SIZE: usize = 0x1000;
+
+/// Signal linux would raise for the exception `code`.
+fn signal(code: u32) -> i32 {
+    match SIGNALS.iter().find(|&&(exception, _)| exception == code) {
+        Some(&(_, sig)) => sig,
+        None => SIGABRT,
+    }
+}
+
+/// General purpose, flag and segment registers of a thread in `ctx`.
+fn context_registers(ctx: &CONTEXT) -> Registers {
+    let rflags = ctx.EFlags as u64;
+
+    let general = vec![
+        ("rax", ctx.Rax),
+        ("rbx", ctx.Rbx),
+        ("rcx", ctx.Rcx),
+        ("rdx", ctx.Rdx),
+        ("rsi", ctx.Rsi),
+        ("rdi", ctx.Rdi),
+        ("rbp", ctx.Rbp),
+        ("rsp", ctx.Rsp),
+        ("r8", ctx.R8),
+        ("r9", ctx.R9),
+        ("r10", ctx.R10),
+        ("r11", ctx.R11),
+        ("r12", ctx.R12),
+        ("r13", ctx.R13),
+        ("r14", ctx.R14),
+        ("r15", ctx.R15),
+        ("rip", ctx.Rip),
+        ("rflags", rflags),
+    ];
+
+    let flags = X86_FLAGS
+        .iter()
+        .map(|&(name, bit)| (name, rflags & (1 << bit) != 0))
+        .collect();
+
+    let segments = vec![
+        ("cs", ctx.SegCs as u64),
+        ("ss", ctx.SegSs as u64),
+        ("ds", ctx.SegDs as u64),
+        ("es", ctx.SegEs as u64),
+        ("fs", ctx.SegFs as u64),
+        ("gs", ctx.SegGs as u64),
+    ];
+
+    Registers {
+        general,
+        flags,
+        segments,
+    }
+}
+
+/// Set a general purpose or segment register in `ctx`, named as in [`context_registers`].
+fn set_context_register(ctx: &mut CONTEXT, name: &str, value: u64) -> Result<(), Error> {
+    let reg = match name {
+        "rax" => &mut ctx.Rax,
+        "rbx" => &mut ctx.Rbx,
+        "rcx" => &mut ctx.Rcx,
+        "rdx" => &mut ctx.Rdx,
+        "rsi" => &mut ctx.Rsi,
+        "rdi" => &mut ctx.Rdi,
+        "rbp" => &mut ctx.Rbp,
+        "rsp" => &mut ctx.Rsp,
+        "r8" => &mut ctx.R8,
+        "r9" => &mut ctx.R9,
+        "r10" => &mut ctx.R10,
+        "r11" => &mut ctx.R11,
+        "r12" => &mut ctx.R12,
+        "r13" => &mut ctx.R13,
+        "r14" => &mut ctx.R14,
+        "r15" => &mut ctx.R15,
+        "rip" => &mut ctx.Rip,
+        "rflags" => {
+            ctx.EFlags = value as u32;
+            return Ok(());
+        }
+        _ => {
+            let segment = match name {
+                "cs" => &mut ctx.SegCs,
+                "ss" => &mut ctx.SegSs,
+                "ds" => &mut ctx.SegDs,
+                "es" => &mut ctx.SegEs,
+                "fs" => &mut ctx.SegFs,
+                "gs" => &mut ctx.SegGs,
+                _ => return Err(Error::InvalidRegister),
+            };
+
+            *segment = value as u16;
+            return Ok(());
+        }
+    };
+
+    *reg = value;
+    Ok(())
+}
+
+fn check(ok: BOOL) -> Result<(), Error> {
+    match ok {
+        0 => Err(Error::IO(std::io::Error::last_os_error())),
+        _ => Ok(()),
+    }
+}
+
+/// Nul-terminated UTF-16 string, as taken by the wide functions.
+fn wide(s: &std::ffi::OsStr) -> Result<Vec<u16>, Error> {
+    let mut wide: Vec<u16> = s.encode_wide().collect();
+    if wide.contains(&0) {
+        return Err(Error::InvalidArgs);
+    }
+
+    wide.push(0);
+    Ok(wide)
+}
+
+/// Append `arg` to `cmdline`, quoted such that `CommandLineToArgvW` parses it back.
+fn quote(cmdline: &mut String, arg: &str) {
+    if !cmdline.is_empty() {
+        cmdline.push(' ');
+    }
+
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        cmdline.push_str(arg);
+        return;
+    }
+
+    // Backslashes are only special in front of quotes.
+    cmdline.push('"');
+    let mut backslashes = 0;
+    for chr in arg.chars() {
+        match chr {
+            '\\' => {
+                backslashes += 1;
+                continue;
+            }
+            '"' => cmdline.extend(std::iter::repeat_n('\\', backslashes * 2 + 1)),
+            _ => cmdline.extend(std::iter::repeat_n('\\', backslashes)),
+        }
+
+        backslashes = 0;
+        cmdline.push(chr);
+    }
+
+    cmdline.extend(std::iter::repeat_n('\\', backslashes * 2));
+    cmdline.push('"');
+}
+
+/// Address of `DbgBreakPoint`, where threads created by `DebugBreakProcess` trap.
+///
+/// ntdll is mapped at the same address in every process.
+fn break_in_addr() -> usize {
+    unsafe {
+        let ntdll = GetModuleHandleA(b"ntdll.dll\0".as_ptr());
+        match GetProcAddress(ntdll, b"DbgBreakPoint\0".as_ptr()) {
+            Some(addr) => addr as usize,
+            None => 0,
+        }
+    }
+}
+
+/// A handle that's closed once dropped.
+struct OwnedHandle(HANDLE);
+
+// SAFETY: Process handles can be used from any thread.
+unsafe impl Send for OwnedHandle {}
+unsafe impl Sync for OwnedHandle {}
+
+impl Drop for OwnedHandle {
+    fn drop(&mut self) {
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+fn memory_maps(process: HANDLE) -> Result<Vec<MemoryRegion>, Error> {
+    let mut regions = Vec::new();
+    let mut addr = 0usize;
+
+    loop {
+        let mut info: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<MEMORY_BASIC_INFORMATION>();
+        if unsafe { VirtualQueryEx(process, addr as *const c_void, &mut info, size) } == 0 {
+            break;
+        }
+
+        let start = info.BaseAddress as usize;
+        let end = start.saturating_add(info.RegionSize);
+
+        // Reserved and free memory isn't mapped.
+        if info.State == MEM_COMMIT {
+            let protect = info.Protect & 0xff;
+            let perms = Permissions {
+                read: protect
+                    & (PAGE_READONLY
+                        | PAGE_READWRITE
+                        | PAGE_WRITECOPY
+                        | PAGE_EXECUTE_READ
+                        | PAGE_EXECUTE_READWRITE
+                        | PAGE_EXECUTE_WRITECOPY)
+                    != 0,
+                write: protect
+                    & (PAGE_READWRITE
+                        | PAGE_WRITECOPY
+                        | PAGE_EXECUTE_READWRITE
+                        | PAGE_EXECUTE_WRITECOPY)
+                    != 0,
+                exec: protect
+                    & (PAGE_EXECUTE
+                        | PAGE_EXECUTE_READ
+                        | PAGE_EXECUTE_READWRITE
+                        | PAGE_EXECUTE_WRITECOPY)
+                    != 0,
+                shared: info.Type == MEM_MAPPED,
+            };
+
+            let path = match info.Type {
+                MEM_IMAGE | MEM_MAPPED => mapped_path(process, start),
+                _ => None,
+            };
+
+            regions.push(MemoryRegion {
+                start,
+                end,
+                perms,
+                offset: 0,
+                path,
+            });
+        }
+
+        if end <= addr {
+            break;
+        }
+
+        addr = end;
+    }
+
+    Ok(regions)
+}
+
+/// Device path of the file mapped at `addr`.
+fn mapped_path(process: HANDLE, addr: usize) -> Option<String> {
+    let mut path = [0u16; 1024];
+    let len = unsafe {
+        GetMappedFileNameW(process, addr as *const c_void, path.as_mut_ptr(), path.len() as u32)
+    };
+
+    match len {
+        0 => None,
+        len => Some(String::from_utf16_lossy(&path[..len as usize])),
+    }
+}
+
+/// Read `len` bytes at `addr`, stopping early at the first byte that isn't mapped.
+fn read_memory(process: HANDLE, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = vec![0; len];
+    let mut read = 0;
+
+    while read < len {
+        let addr = addr + read;
+        let chunk = (len - read).min(PAGE_SIZE - addr % PAGE_SIZE);
+        let mut n = 0;
+
+        let ok = unsafe {
+            ReadProcessMemory(
+                process,
+                addr as *const c_void,
+                bytes[read..].as_mut_ptr() as *mut c_void,
+                chunk,
+                &mut n,
+            )
+        };
+
+        match check(ok) {
+            Ok(()) => read += n,
+            // Unmapped memory can't be read.
+            Err(_) if read != 0 => break,
+            Err(err) => return Err(err),
+        }
+    }
+
+    bytes.truncate(read);
+    Ok(bytes)
+}
+
+/// Executable a running process was started from.
+pub fn exe_path(pid: u32) -> Result<PathBuf, Error> {
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process == 0 {
+        return Err(Error::IO(std::io::Error::last_os_error()));
+    }
+
+    let process = OwnedHandle(process);
+    let mut path = [0u16; 1024];
+    let mut len = path.len() as u32;
+    check(unsafe {
+        QueryFullProcessImageNameW(process.0, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut len)
+    })?;
+
+    Ok(PathBuf::from(OsString::from_wide(&path[..len as usize])))
+}
+
+/// Processes running on the system, ordered by their pid.
+pub fn processes() -> Vec<ProcessInfo> {
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return Vec::new();
+    }
+
+    let snapshot = OwnedHandle(snapshot);
+    let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+    entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+
+    let mut processes = Vec::new();
+    let mut more = unsafe { Process32FirstW(snapshot.0, &mut entry) } != 0;
+    while more {
+        let pid = entry.th32ProcessID;
+
+        // Pid 0 is the idle process.
+        if pid != 0 {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(0);
+            processes.push(ProcessInfo {
+                pid,
+                name: String::from_utf16_lossy(&entry.szExeFile[..len]),
+                path: exe_path(pid).ok(),
+            });
+        }
+
+        more = unsafe { Process32NextW(snapshot.0, &mut entry) } != 0;
+    }
+
+    processes.sort_unstable_by_key(|process| process.pid);
+    processes
+}
+
+/// A stopped process we're debugging.
+pub(crate) struct Tracee {
+    pid: u32,
+    process: Arc<OwnedHandle>,
+    /// Threads by their id, their handles are closed by the system.
+    threads: BTreeMap<u32, HANDLE>,
+    /// Thread that stopped last, registers are read from and written to it.
+    thread: u32,
+    /// Thread the process was started with, or the first one when attaching.
+    main_thread: u32,
+}
+
+impl Tracee {
+    fn thread(&self) -> Result<HANDLE, Error> {
+        self.threads.get(&self.thread).copied().ok_or(Error::Exited)
+    }
+
+    fn context(&self, thread: HANDLE) -> Result<CONTEXT, Error> {
+        let mut ctx: CONTEXT = unsafe { std::mem::zeroed() };
+        ctx.ContextFlags = CONTEXT_FULL_AMD64 | CONTEXT_DEBUG_REGISTERS_AMD64;
+        check(unsafe { GetThreadContext(thread, &mut ctx) })?;
+        Ok(ctx)
+    }
+
+    fn set_context(&self, thread: HANDLE, ctx: &CONTEXT) -> Result<(), Error> {
+        check(unsafe { SetThreadContext(thread, ctx) })
+    }
+
+    fn pc(&self) -> Result<usize, Error> {
+        Ok(self.context(self.thread()?)?.Rip as usize)
+    }
+
+    fn set_pc(&self, pc: usize) -> Result<(), Error> {
+        let thread = self.thread()?;
+        let mut ctx = self.context(thread)?;
+        ctx.Rip = pc as u64;
+        self.set_context(thread, &ctx)
+    }
+
+    fn set_trap_flag(&self, enable: bool) -> Result<(), Error> {
+        let thread = self.thread()?;
+        let mut ctx = self.context(thread)?;
+        match enable {
+            true => ctx.EFlags |= TF,
+            false => ctx.EFlags &= !TF,
+        }
+        self.set_context(thread, &ctx)
+    }
+
+    pub fn read_registers(&self) -> Result<Registers, Error> {
+        Ok(context_registers(&self.context(self.thread()?)?))
+    }
+
+    /// Set a general purpose or segment register, named as in [`Tracee::read_registers`].
+    pub fn write_register(&self, name: &str, value: u64) -> Result<(), Error> {
+        let thread = self.thread()?;
+        let mut ctx = self.context(thread)?;
+        set_context_register(&mut ctx, name, value)?;
+        self.set_context(thread, &ctx)
+    }
+
+    /// Put runtime addresses of `watchpoints` in the debug registers of `thread`.
+    fn set_watchpoints(&self, thread: HANDLE, watchpoints: &[Watchpoint]) -> Result<(), Error> {
+        let mut ctx = self.context(thread)?;
+        let addrs = watchpoints.iter().map(|wp| wp.addr as u64);
+        let regs = [&mut ctx.Dr0, &mut ctx.Dr1, &mut ctx.Dr2, &mut ctx.Dr3];
+        for (reg, addr) in regs.into_iter().zip(addrs.chain(std::iter::repeat(0))) {
+            *reg = addr;
+        }
+
+        ctx.Dr7 = breakpoint::dr7(watchpoints);
+        self.set_context(thread, &ctx)
+    }
+
+    /// Enable the debug registers of `thread` as described by `dr7`, leaving their addresses.
+    fn set_dr7(&self, thread: HANDLE, dr7: u64) -> Result<(), Error> {
+        let mut ctx = self.context(thread)?;
+        ctx.Dr7 = dr7;
+        self.set_context(thread, &ctx)
+    }
+}
+
+impl Memory for Tracee {
+    type Error = Error;
+
+    fn read(&self, addr: usize, buf: &mut [u8]) -> Result<(), Error> {
+        let mut read = 0;
+        check(unsafe {
+            ReadProcessMemory(
+                self.process.0,
+                addr as *const c_void,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                &mut read,
+            )
+        })
+    }
+
+    fn write(&self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        let mut written = 0;
+
+        // Code pages are made writable whilst writing to them.
+        check(unsafe {
+            WriteProcessMemory(
+                self.process.0,
+                addr as *const c_void,
+                bytes.as_ptr() as *const c_void,
+                bytes.len(),
+                &mut written,
+            )
+        })?;
+
+        check(unsafe { FlushInstructionCache(self.process.0, addr as *const c_void, bytes.len()) })
+    }
+}
+
+pub struct Debugger {
+    tracee: Tracee,
+    breakpoints: Arc<BreakpointSet>,
+    inserted: Inserted,
+    /// Offset between addresses in the binary and where it's loaded.
+    bias: usize,
+    /// Thread whose debug event still has to be continued.
+    stopped: Option<u32>,
+    /// Whether the exception of the last event is passed on to the program.
+    pass_exception: bool,
+    /// Address the last exception was raised for, if it was an access violation.
+    fault: Option<usize>,
+    /// Thread that caused the last stop, together with why it stopped.
+    last_stop: Option<(u32, Stop)>,
+    /// Where threads created by [`ProcessHandle::interrupt`] trap.
+    break_in: usize,
+    /// Whether the loader already raised it's breakpoint, which is raised once it's done.
+    loaded: bool,
+    /// Set by [`Handle::interrupt`] to tell apart our own stops.
+    interrupted: Arc<AtomicBool>,
+    /// Watchpoints and hardware breakpoints in the debug registers, in the order of DR0 to DR3.
+    watching: Vec<Watchpoint>,
+    /// Set by [`Handle::detach`] to stop debugging whilst the process runs.
+    detaching: Arc<AtomicBool>,
+    /// Whether we attached to an already running process, which is left running once dropped.
+    attached: bool,
+    exited: bool,
+}
+
+impl Debugger {
+    /// Start `path` with `args` set up as `env` describes, stopped before it's first instruction
+    /// executes. It's standard streams are the pipes of `stdio` if given, otherwise they're ours.
+    /// Address space randomization is chosen by the image, so it can't be turned off.
+    ///
+    /// `entrypoint` is the entrypoint as found in the binary, used for finding where
+    /// relocated images get loaded.
+    pub fn spawn(
+        path: &Path,
+        args: &[String],
+        env: &Environment,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        stdio: Option<Stdio>,
+    ) -> Result<Self, Error> {
+        let mut cmdline = String::new();
+        quote(&mut cmdline, &path.to_string_lossy());
+        for arg in args {
+            quote(&mut cmdline, arg);
+        }
+
+        let app = wide(path.as_os_str())?;
+        let mut cmdline = wide(cmdline.as_ref())?;
+        let input = env.prepare()?;
+        let cwd = match env.cwd {
+            Some(ref cwd) => Some(wide(cwd.as_os_str())?),
+            None => None,
+        };
+
+        // Variables are each terminated by a nul and the block by another one, so an empty
+        // block is two nuls.
+        let mut block = Vec::new();
+        for (mut var, value) in env.merged_vars() {
+            var.push("=");
+            var.push(value);
+            block.extend(wide(&var)?);
+        }
+
+        if block.is_empty() {
+            block.push(0);
+        }
+        block.push(0);
+
+        let mut startup: STARTUPINFOW = unsafe { std::mem::zeroed() };
+        startup.cb = std::mem::size_of::<STARTUPINFOW>() as u32;
+        let mut info: PROCESS_INFORMATION = unsafe { std::mem::zeroed() };
+
+        let handles = match (&stdio, &input) {
+            (Some(stdio), input) => Some([
+                match input {
+                    Some(file) => file.as_raw_handle() as HANDLE,
+                    None => stdio.stdin.as_raw_handle() as HANDLE,
+                },
+                stdio.stdout.as_raw_handle() as HANDLE,
+                stdio.stderr.as_raw_handle() as HANDLE,
+            ]),
+            // Only stdin is replaced, the output still goes to our streams.
+            (None, Some(file)) => Some(unsafe {
+                [
+                    file.as_raw_handle() as HANDLE,
+                    GetStdHandle(STD_OUTPUT_HANDLE),
+                    GetStdHandle(STD_ERROR_HANDLE),
+                ]
+            }),
+            (None, None) => None,
+        };
+
+        // Handles are only handed to the process if they're inheritable.
+        let inherit = match handles {
+            Some(handles) => {
+                for handle in handles {
+                    // We may not have standard streams, e.g. without a console.
+                    if handle == 0 || handle == INVALID_HANDLE_VALUE {
+                        continue;
+                    }
+
+                    check(unsafe {
+                        SetHandleInformation(handle, HANDLE_FLAG_INHERIT, HANDLE_FLAG_INHERIT)
+                    })?;
+                }
+
+                startup.dwFlags |= STARTF_USESTDHANDLES;
+                startup.hStdInput = handles[0];
+                startup.hStdOutput = handles[1];
+                startup.hStdError = handles[2];
+                1
+            }
+            None => 0,
+        };
+
+        check(unsafe {
+            CreateProcessW(
+                app.as_ptr(),
+                cmdline.as_mut_ptr(),
+                std::ptr::null(),
+                std::ptr::null(),
+                inherit,
+                DEBUG_ONLY_THIS_PROCESS | CREATE_UNICODE_ENVIRONMENT,
+                block.as_ptr() as *const c_void,
+                cwd.as_ref().map_or(std::ptr::null(), |cwd| cwd.as_ptr()),
+                &startup,
+                &mut info,
+            )
+        })?;
+
+        unsafe { CloseHandle(info.hThread) };
+        let process = Arc::new(OwnedHandle(info.hProcess));
+
+        // The child's ends are only open in the child, so it closing them ends the pipes.
+        drop(stdio);
+        drop(input);
+
+        // The process hasn't run yet once it's creation is reported.
+        let debugger = Self::new(info.dwProcessId, process, breakpoints, entrypoint, false);
+        if debugger.is_err() {
+            unsafe { TerminateProcess(info.hProcess, 1) };
+        }
+
+        debugger
+    }
+
+    /// Start debugging the running process `pid`, which is stopped until [`Debugger::cont`].
+    ///
+    /// `entrypoint` is the entrypoint as found in the binary the process was started from.
+    pub fn attach(
+        pid: u32,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+    ) -> Result<Self, Error> {
+        let process = unsafe { OpenProcess(PROCESS_ALL_ACCESS, 0, pid) };
+        if process == 0 {
+            return Err(Error::IO(std::io::Error::last_os_error()));
+        }
+
+        let process = Arc::new(OwnedHandle(process));
+        check(unsafe { DebugActiveProcess(pid) })?;
+
+        // Leave the process running if we exit without detaching.
+        unsafe { DebugSetProcessKillOnExit(0) };
+
+        Self::new(pid, process, breakpoints, entrypoint, true)
+    }
+
+    /// Handle the creation of the process, which is the first debug event.
+    fn new(
+        pid: u32,
+        process: Arc<OwnedHandle>,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        attached: bool,
+    ) -> Result<Self, Error> {
+        let mut event: DEBUG_EVENT = unsafe { std::mem::zeroed() };
+        check(unsafe { WaitForDebugEvent(&mut event, INFINITE) })?;
+        if event.dwDebugEventCode != CREATE_PROCESS_DEBUG_EVENT {
+            return Err(Error::Exited);
+        }
+
+        let created = unsafe { event.u.CreateProcessInfo };
+        if created.hFile != 0 {
+            unsafe { CloseHandle(created.hFile) };
+        }
+
+        let start = created.lpStartAddress.map_or(0, |start| start as usize);
+        let bias = match entrypoint {
+            0 => 0,
+            _ => start.wrapping_sub(entrypoint),
+        };
+
+        let tracee = Tracee {
+            pid,
+            process,
+            threads: BTreeMap::from([(event.dwThreadId, created.hThread)]),
+            thread: event.dwThreadId,
+            main_thread: event.dwThreadId,
+        };
+
+        let mut debugger = Self {
+            tracee,
+            breakpoints,
+            inserted: Inserted::default(),
+            bias,
+            stopped: Some(event.dwThreadId),
+            pass_exception: false,
+            fault: None,
+            last_stop: None,
+            break_in: break_in_addr(),
+            loaded: attached,
+            interrupted: Arc::new(AtomicBool::new(false)),
+            watching: Vec::new(),
+            detaching: Arc::new(AtomicBool::new(false)),
+            attached,
+            exited: false,
+        };
+
+        // Attaching breaks into the process once it's threads and modules are reported.
+        if attached {
+            debugger.resume()?;
+            match debugger.wait()? {
+                Stop::Signal(SIGSTOP, _) => {}
+                _ => return Err(Error::Exited),
+            }
+        }
+
+        debugger.breakpoints.take_dirty();
+        debugger.sync()?;
+        Ok(debugger)
+    }
+
+    /// Address the process is stopped at, as found in the binary.
+    pub fn pc(&self) -> Result<usize, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        Ok(self.tracee.pc()?.wrapping_sub(self.bias))
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.tracee.pid
+    }
+
+    /// Offset between addresses in the binary and where it's loaded.
+    pub fn bias(&self) -> usize {
+        self.bias
+    }
+
+    pub fn handle(&self) -> Handle {
+        Handle::Process(ProcessHandle {
+            process: Arc::clone(&self.tracee.process),
+            interrupted: Arc::clone(&self.interrupted),
+            detaching: Arc::clone(&self.detaching),
+        })
+    }
+
+    /// Syscalls can't be traced through the debug API.
+    pub fn trace_syscalls<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Syscall) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    /// Only the dynamic linker of Linux is followed as it loads libraries.
+    pub fn watch_libraries<F>(&mut self, _report: F) -> Result<(), Error>
+    where
+        F: FnMut(Vec<Library>) + Send + 'static,
+    {
+        Err(Error::Unsupported)
+    }
+
+    /// Stop when `len` bytes at `addr`, as found in the binary, are accessed.
+    pub fn set_watchpoint(
+        &mut self,
+        addr: usize,
+        len: usize,
+        kind: WatchKind,
+    ) -> Result<(), Error> {
+        self.breakpoints.set_watchpoint(Watchpoint { addr, len, kind })?;
+        self.sync()
+    }
+
+    /// Watchpoints and hardware breakpoints being watched, at their runtime address.
+    fn runtime_watchpoints(&self) -> Vec<Watchpoint> {
+        self.watching
+            .iter()
+            .map(|&wp| Watchpoint {
+                addr: wp.addr.wrapping_add(self.bias),
+                ..wp
+            })
+            .collect()
+    }
+
+    /// Apply the breakpoints and watchpoints of the shared [`BreakpointSet`].
+    fn sync(&mut self) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.inserted.sync(&self.tracee, &self.breakpoints, self.bias, true)?;
+
+        let watchpoints = self.breakpoints.debug_registers();
+        if watchpoints == self.watching {
+            return Ok(());
+        }
+
+        self.watching = watchpoints;
+        let runtime = self.runtime_watchpoints();
+
+        // Each thread has it's own debug registers.
+        for &thread in self.tracee.threads.values() {
+            self.tracee.set_watchpoints(thread, &runtime)?;
+        }
+
+        Ok(())
+    }
+
+    /// Debug register of the hardware breakpoint at runtime address `pc`, if there's one.
+    fn hardware_breakpoint(&self, pc: usize) -> Option<usize> {
+        self.watching.iter().position(|wp| {
+            wp.kind == WatchKind::Execute && wp.addr.wrapping_add(self.bias) == pc
+        })
+    }
+
+    /// Regions of memory mapped into the process.
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        memory_maps(self.tracee.process.0)
+    }
+
+    /// Registers of the stopped process' current thread.
+    pub fn registers(&self) -> Result<Registers, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.read_registers()
+    }
+
+    /// Set a register of the stopped process' current thread, named as in [`Registers`].
+    pub fn set_register(&mut self, name: &str, value: u64) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.tracee.write_register(name, value)
+    }
+
+    /// Read the stopped process' memory at runtime address `addr`, without our breakpoints.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let mut bytes = read_memory(self.tracee.process.0, addr, len)?;
+        self.inserted.hide(addr, &mut bytes);
+        Ok(bytes)
+    }
+
+    /// Write to the stopped process' memory at runtime address `addr`.
+    pub fn write_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        self.inserted.write(&self.tracee, addr, bytes)
+    }
+
+    /// Runtime address the current thread faulted on, if it's stopped by an access violation.
+    pub fn fault_addr(&self) -> Result<Option<usize>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        Ok(self.fault)
+    }
+
+    /// Frames on the stack of the stopped process' current thread, innermost first.
+    pub fn backtrace(&self, cfi: &CallFrameInfo) -> Result<Vec<Frame>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let ctx = self.tracee.context(self.tracee.thread()?)?;
+        let regs = Regs {
+            pc: ctx.Rip,
+            sp: ctx.Rsp,
+            fp: ctx.Rbp,
+        };
+
+        let process = self.tracee.process.0;
+        let read = |addr: u64| {
+            let bytes = read_memory(process, addr as usize, 8).ok()?;
+            Some(u64::from_le_bytes(bytes.try_into().ok()?))
+        };
+
+        Ok(unwind::unwind(cfi, regs, self.bias, read))
+    }
+
+    /// Threads of the stopped process, ordered by their id.
+    pub fn threads(&self) -> Result<Vec<Thread>, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        let threads = self
+            .tracee
+            .threads
+            .iter()
+            .filter_map(|(&tid, &thread)| {
+                let pc = self.tracee.context(thread).ok()?.Rip as usize;
+                let stop = match self.last_stop {
+                    Some((stopped, stop)) if stopped == tid => Some(stop),
+                    _ => None,
+                };
+
+                Some(Thread {
+                    tid,
+                    name: String::new(),
+                    pc: pc.wrapping_sub(self.bias),
+                    stop,
+                    current: tid == self.tracee.thread,
+                })
+            })
+            .collect();
+
+        Ok(threads)
+    }
+
+    /// Make `tid` the thread that's stepped and whose registers and stack are inspected.
+    pub fn select_thread(&mut self, tid: u32) -> Result<(), Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        if !self.tracee.threads.contains_key(&tid) {
+            return Err(Error::NoSuchThread);
+        }
+
+        self.tracee.thread = tid;
+        Ok(())
+    }
+
+    /// Child processes aren't debugged as the tracee is started with `DEBUG_ONLY_THIS_PROCESS`,
+    /// so only following the parent is supported.
+    pub fn set_follow_fork(&mut self, follow: FollowFork) -> Result<(), Error> {
+        match follow {
+            FollowFork::Parent => Ok(()),
+            FollowFork::Child | FollowFork::Ask => Err(Error::Unsupported),
+        }
+    }
+
+    /// Exceptions are always handled the same way, only the signals of linux are configured.
+    pub fn set_signals(&mut self, signals: Signals) -> Result<(), Error> {
+        match signals.is_default() {
+            true => Ok(()),
+            false => Err(Error::Unsupported),
+        }
+    }
+
+    /// Resume execution until the next breakpoint, unhandled exception or exit.
+    pub fn cont(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        loop {
+            if let Some(stop) = self.step_over_breakpoint()? {
+                return Ok(stop);
+            }
+
+            if self.breakpoints.take_dirty() {
+                self.sync()?;
+            }
+
+            self.resume()?;
+            match self.wait()? {
+                Stop::Signal(SIGSTOP, pc) => {
+                    // The process is detached from once we're dropped.
+                    if self.detaching.load(Ordering::Acquire) {
+                        return Err(Error::Detached);
+                    }
+
+                    // We stopped the process ourselves to apply breakpoints.
+                    if self.interrupted.swap(false, Ordering::AcqRel) {
+                        continue;
+                    }
+
+                    return Ok(Stop::Signal(SIGSTOP, pc));
+                }
+                stop => return Ok(stop),
+            }
+        }
+    }
+
+    /// Execute a single instruction of the current thread.
+    pub fn step(&mut self) -> Result<Stop, Error> {
+        if self.exited {
+            return Err(Error::Exited);
+        }
+
+        if self.breakpoints.take_dirty() {
+            self.sync()?;
+        }
+
+        let pc = self.tracee.pc()?;
+        if self.inserted.contains(pc) || self.hardware_breakpoint(pc).is_some() {
+            return match self.step_over_breakpoint()? {
+                Some(stop) => Ok(stop),
+                None => Ok(Stop::Stepped(self.pc()?)),
+            };
+        }
+
+        match self.single_step()? {
+            Stop::Signal(SIGTRAP, addr) => Ok(Stop::Stepped(addr)),
+            stop => Ok(stop),
+        }
+    }
+
+    /// Execute a single instruction of the current thread, whilst the other threads are
+    /// suspended.
+    fn single_step(&mut self) -> Result<Stop, Error> {
+        let others: Vec<HANDLE> = self
+            .tracee
+            .threads
+            .iter()
+            .filter(|&(&tid, _)| tid != self.tracee.thread)
+            .map(|(_, &thread)| thread)
+            .collect();
+
+        for &thread in &others {
+            unsafe { SuspendThread(thread) };
+        }
+
+        let stepped = self.tracee.set_trap_flag(true);
+        let stop = stepped.and_then(|_| {
+            self.resume()?;
+            self.wait()
+        });
+
+        // Thread handles are closed by the system once the process exited.
+        if !self.exited {
+            let _ = self.tracee.set_trap_flag(false);
+            for &thread in &others {
+                unsafe { ResumeThread(thread) };
+            }
+        }
+
+        stop
+    }
+
+    /// If stopped at one of our breakpoints, execute the original instruction and put the
+    /// breakpoint back.
+    fn step_over_breakpoint(&mut self) -> Result<Option<Stop>, Error> {
+        let pc = self.tracee.pc()?;
+        let hardware = self.hardware_breakpoint(pc);
+        if !self.inserted.contains(pc) && hardware.is_none() {
+            return Ok(None);
+        }
+
+        // Hardware breakpoints would trigger again, so their debug register is disabled on the
+        // stepping thread.
+        let thread = self.tracee.thread()?;
+        let dr7 = breakpoint::dr7(&self.watching);
+        match hardware {
+            Some(idx) => self.tracee.set_dr7(thread, dr7 & !(0b11 << (idx * 2)))?,
+            None => self.inserted.remove(&self.tracee, pc)?,
+        }
+
+        // Besides exiting or the stepped over instruction triggering a watchpoint, this is either
+        // the expected trap from stepping or an exception that's passed on when continuing.
+        let watchpoint = match self.single_step()? {
+            stop @ (Stop::Exited(..) | Stop::Killed(..)) => return Ok(Some(stop)),
+            stop @ Stop::Watchpoint(..) => Some(stop),
+            _ => None,
+        };
+
+        if hardware.is_some() {
+            self.tracee.set_dr7(thread, dr7)?;
+        } else if self.breakpoints.contains(pc.wrapping_sub(self.bias)) {
+            self.inserted.insert(&self.tracee, pc)?;
+        }
+
+        Ok(watchpoint)
+    }
+
+    /// Checks whether the last trap came from a watchpoint or hardware breakpoint, clearing it's
+    /// status.
+    fn triggered_watchpoint(&mut self) -> Result<Option<Stop>, Error> {
+        if self.watching.is_empty() {
+            return Ok(None);
+        }
+
+        let thread = self.tracee.thread()?;
+        let mut ctx = self.tracee.context(thread)?;
+        let hit = (0..self.watching.len()).find(|&idx| ctx.Dr6 & (1u64 << idx) != 0);
+        let hit = match hit {
+            Some(idx) => self.watching[idx],
+            None => return Ok(None),
+        };
+
+        // The status isn't cleared by the processor.
+        ctx.Dr6 = 0;
+        self.tracee.set_context(thread, &ctx)?;
+
+        // Hardware breakpoints trigger before the instruction executed.
+        if hit.kind == WatchKind::Execute {
+            return Ok(Some(Stop::Breakpoint(hit.addr)));
+        }
+
+        let pc = (ctx.Rip as usize).wrapping_sub(self.bias);
+        Ok(Some(Stop::Watchpoint(hit.addr, pc)))
+    }
+
+    /// Continue the last debug event, resuming the process.
+    fn resume(&mut self) -> Result<(), Error> {
+        self.last_stop = None;
+        let status = match std::mem::take(&mut self.pass_exception) {
+            true => DBG_EXCEPTION_NOT_HANDLED,
+            false => DBG_CONTINUE,
+        };
+
+        match self.stopped.take() {
+            Some(tid) => check(unsafe { ContinueDebugEvent(self.tracee.pid, tid, status) }),
+            None => Ok(()),
+        }
+    }
+
+    /// Wait for the process to stop, continuing events that don't stop it.
+    fn wait(&mut self) -> Result<Stop, Error> {
+        loop {
+            let mut event: DEBUG_EVENT = unsafe { std::mem::zeroed() };
+            check(unsafe { WaitForDebugEvent(&mut event, INFINITE) })?;
+
+            let tid = event.dwThreadId;
+            self.stopped = Some(tid);
+
+            match event.dwDebugEventCode {
+                CREATE_THREAD_DEBUG_EVENT => {
+                    let thread = unsafe { event.u.CreateThread.hThread };
+                    self.tracee.threads.insert(tid, thread);
+
+                    // Each thread has it's own debug registers.
+                    if !self.watching.is_empty() {
+                        self.tracee.set_watchpoints(thread, &self.runtime_watchpoints())?;
+                    }
+                }
+                EXIT_THREAD_DEBUG_EVENT => {
+                    self.tracee.threads.remove(&tid);
+                    if self.tracee.thread == tid {
+                        self.tracee.thread = self.tracee.main_thread;
+                    }
+                }
+                LOAD_DLL_DEBUG_EVENT => {
+                    let file = unsafe { event.u.LoadDll.hFile };
+                    if file != 0 {
+                        unsafe { CloseHandle(file) };
+                    }
+                }
+                EXIT_PROCESS_DEBUG_EVENT => {
+                    self.exited = true;
+                    let code = unsafe { event.u.ExitProcess.dwExitCode };
+                    return Ok(Stop::Exited(code as i32));
+                }
+                EXCEPTION_DEBUG_EVENT => {
+                    let exception = unsafe { event.u.Exception };
+                    if let Some(stop) = self.exception(tid, &exception)? {
+                        self.last_stop = Some((tid, stop));
+                        return Ok(stop);
+                    }
+                }
+                _ => {}
+            }
+
+            self.resume()?;
+        }
+    }
+
+    /// Why the process stopped for the exception raised by thread `tid`, returns None if it
+    /// should just be resumed.
+    fn exception(&mut self, tid: u32, info: &EXCEPTION_DEBUG_INFO) -> Result<Option<Stop>, Error> {
+        let code = info.ExceptionRecord.ExceptionCode as u32;
+        let addr = info.ExceptionRecord.ExceptionAddress as usize;
+        self.fault = None;
+
+        match code {
+            EXCEPTION_BREAKPOINT => {
+                if self.inserted.contains(addr) {
+                    self.tracee.thread = tid;
+
+                    // The trap already executed, leaving the pc after it.
+                    self.tracee.set_pc(addr)?;
+                    return Ok(Some(Stop::Breakpoint(addr.wrapping_sub(self.bias))));
+                }
+
+                // Raised by a thread that only exists for breaking into the process.
+                if addr == self.break_in {
+                    self.tracee.thread = self.tracee.main_thread;
+                    let pc = self.tracee.pc()?.wrapping_sub(self.bias);
+                    return Ok(Some(Stop::Signal(SIGSTOP, pc)));
+                }
+
+                if !self.loaded {
+                    self.loaded = true;
+                    return Ok(None);
+                }
+
+                self.tracee.thread = tid;
+                Ok(Some(Stop::Signal(SIGTRAP, addr.wrapping_sub(self.bias))))
+            }
+            EXCEPTION_SINGLE_STEP => {
+                self.tracee.thread = tid;
+                if let Some(stop) = self.triggered_watchpoint()? {
+                    return Ok(Some(stop));
+                }
+
+                let pc = self.tracee.pc()?.wrapping_sub(self.bias);
+                Ok(Some(Stop::Signal(SIGTRAP, pc)))
+            }
+            // Programs handle most exceptions themselves.
+            _ if info.dwFirstChance != 0 && code != DBG_CONTROL_C => {
+                self.pass_exception = true;
+                Ok(None)
+            }
+            _ => {
+                // Access violations are described by whether it's a write and the address.
+                let record = &info.ExceptionRecord;
+                if code == EXCEPTION_ACCESS_VIOLATION && record.NumberParameters >= 2 {
+                    self.fault = Some(record.ExceptionInformation[1]);
+                }
+
+                self.tracee.thread = tid;
+                self.pass_exception = true;
+                Ok(Some(Stop::Signal(signal(code), addr.wrapping_sub(self.bias))))
+            }
+        }
+    }
+}
+
+impl Drop for Debugger {
+    fn drop(&mut self) {
+        // The system closes it's handles once the exit is continued.
+        if self.exited {
+            let _ = self.resume();
+            return;
+        }
+
+        if self.attached {
+            // Leave the process as we found it.
+            let _ = self.inserted.remove_all(&self.tracee);
+            if !self.watching.is_empty() {
+                for &thread in self.tracee.threads.values() {
+                    let _ = self.tracee.set_watchpoints(thread, &[]);
+                }
+            }
+
+            let _ = self.resume();
+        } else {
+            unsafe { TerminateProcess(self.tracee.process.0, 1) };
+            let _ = self.resume();
+        }
+
+        unsafe { DebugActiveProcessStop(self.tracee.pid) };
+    }
+}
+
+/// Controls a running [`Debugger`] from other threads.
+#[derive(Clone)]
+pub struct ProcessHandle {
+    process: Arc<OwnedHandle>,
+    interrupted: Arc<AtomicBool>,
+    detaching: Arc<AtomicBool>,
+}
+
+impl ProcessHandle {
+    /// Stop the process so changes to the breakpoints are applied, it's resumed right after.
+    pub fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::Release);
+        unsafe { DebugBreakProcess(self.process.0) };
+    }
+
+    /// Stop the process, which is reported as it receiving `SIGSTOP`.
+    pub fn pause(&self) {
+        unsafe { DebugBreakProcess(self.process.0) };
+    }
+
+    pub fn kill(&self) {
+        unsafe { TerminateProcess(self.process.0, 1) };
+    }
+
+    /// Stop debugging the running process, leaving it running.
+    pub fn detach(&self) {
+        self.detaching.store(true, Ordering::Release);
+        unsafe { DebugBreakProcess(self.process.0) };
+    }
+
+    pub fn memory_maps(&self) -> Result<Vec<MemoryRegion>, Error> {
+        memory_maps(self.process.0)
+    }
+
+    /// Read the process' memory at runtime address `addr`.
+    pub fn read_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, Error> {
+        read_memory(self.process.0, addr, len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        assert_eq!(signal(EXCEPTION_ACCESS_VIOLATION), SIGSEGV);
+        assert_eq!(signal(EXCEPTION_BREAKPOINT), SIGTRAP);
+        assert_eq!(signal(DBG_CONTROL_C), SIGINT);
+        assert_eq!(signal(0xc0000094), SIGFPE);
+        assert_eq!(signal(0xe06d7363), SIGABRT);
+    }
+
+    #[test]
+    fn registers() {
+        let mut ctx: CONTEXT = unsafe { std::mem::zeroed() };
+        let names: Vec<&str> = {
+            let regs = context_registers(&ctx);
+            regs.general.iter().chain(&regs.segments).map(|&(name, _)| name).collect()
+        };
+
+        // Every register that's shown can be set, without changing any other.
+        for (value, name) in names.iter().enumerate() {
+            set_context_register(&mut ctx, name, value as u64 + 1).unwrap();
+        }
+
+        let regs = context_registers(&ctx);
+        let values: Vec<u64> = regs.general.iter().chain(&regs.segments).map(|r| r.1).collect();
+        assert_eq!(values, (1..=names.len() as u64).collect::<Vec<_>>());
+        assert_eq!(ctx.Rip, values[names.iter().position(|&n| n == "rip").unwrap()]);
+        assert!(matches!(
+            set_context_register(&mut ctx, "xmm0", 0),
+            Err(Error::InvalidRegister)
+        ));
+
+        // Flags are decoded from `rflags`, with the zero flag at bit 6.
+        set_context_register(&mut ctx, "rflags", 1 << 6).unwrap();
+        let flags = context_registers(&ctx).flags;
+        assert_eq!(flags.iter().filter(|&&(_, set)| set).collect::<Vec<_>>(), [&("ZF", true)]);
+    }
+
+    #[test]
+    fn quoting() {
+        let mut cmdline = String::new();
+        for arg in ["a.exe", "", "two words", "say \"hi\"", "dir\\", "end\\ "] {
+            quote(&mut cmdline, arg);
+        }
+
+        assert_eq!(cmdline, r#"a.exe "" "two words" "say \"hi\"" dir\ "end\ ""#);
+    }
+}
@@ -0,0 +1,285 @@
+//! Locating debug info that was split off into a separate file, referenced either by a
+//! `.gnu_debuglink` section, by the build-id stored in `.note.gnu.build-id`, by the skeleton
+//! units of split DWARF or by the UUID of a Mach-O's dSYM bundle.
+
+use binformat::RawSymbol;
+use object::{Object, ObjectSymbol, SymbolKind};
+use processor_shared::{AddressMap, Addressed};
+use std::path::{Path, PathBuf};
+
+/// Directories distributions install debug info to.
+const DEBUG_DIRS: &[&str] = &["/usr/lib/debug", "/usr/local/lib/debug"];
+
+/// Path of a debug file by build-id, e.g. `<dir>/.build-id/ab/cdef.debug`.
+fn build_id_path(dir: &Path, build_id: &[u8]) -> Option<PathBuf> {
+    let (first, rest) = build_id.split_first()?;
+    if rest.is_empty() {
+        return None;
+    }
+
+    let rest: String = rest.iter().map(|byte| format!("{byte:02x}")).collect();
+    Some(dir.join(".build-id").join(format!("{first:02x}")).join(rest + ".debug"))
+}
+
+/// Directories whose `.build-id` directory is looked in for the debug file of the binary at
+/// `path`, those alongside the binary first.
+fn build_id_dirs(path: &Path) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut dirs = vec![dir.to_path_buf(), dir.join(".debug")];
+    dirs.extend(DEBUG_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// Directories debuginfod clients download debug info to, the first being set by the user.
+fn debuginfod_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(dir) = std::env::var_os("DEBUGINFOD_CACHE_PATH") {
+        dirs.push(PathBuf::from(dir));
+    }
+
+    if let Some(dir) = std::env::var_os("XDG_CACHE_HOME") {
+        dirs.push(Path::new(&dir).join("debuginfod_client"));
+    }
+
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(Path::new(&home).join(".cache/debuginfod_client"));
+    }
+
+    dirs
+}
+
+/// Path of a debug file downloaded by debuginfod, e.g. `<dir>/abcdef/debuginfo`.
+fn debuginfod_path(dir: &Path, build_id: &[u8]) -> PathBuf {
+    let build_id: String = build_id.iter().map(|byte| format!("{byte:02x}")).collect();
+    dir.join(build_id).join("debuginfo")
+}
+
+/// CRC-32 as used by `.gnu_debuglink`, the same as zlib's.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb88320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Whether the file at `path` has the checksum the debug link expects.
+fn crc_matches(path: &Path, crc: u32) -> bool {
+    match std::fs::read(path) {
+        Ok(data) => crc32(&data) == crc,
+        Err(..) => false,
+    }
+}
+
+/// Places a debug link is looked for, in the same order as gdb.
+fn debuglink_paths(path: &Path, link: &str) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut paths = vec![dir.join(link), dir.join(".debug").join(link)];
+
+    for debug_dir in DEBUG_DIRS {
+        let debug_dir = Path::new(debug_dir);
+        let absolute_dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let relative_dir = absolute_dir.strip_prefix("/").unwrap_or(&absolute_dir);
+        paths.push(debug_dir.join(relative_dir).join(link));
+        paths.push(debug_dir.join(link));
+    }
+
+    paths
+}
+
+/// Find the file containing the debug info of `obj`, if it's stored separately.
+pub fn find_debug_file(obj: &object::File, path: &Path) -> Option<PathBuf> {
+    if let Ok(Some(build_id)) = obj.build_id() {
+        for dir in build_id_dirs(path) {
+            match build_id_path(&dir, build_id) {
+                Some(candidate) if candidate.is_file() && candidate != path => {
+                    return Some(candidate)
+                }
+                _ => {}
+            }
+        }
+
+        for dir in debuginfod_dirs() {
+            let path = debuginfod_path(&dir, build_id);
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+    }
+
+    if let Ok(Some((link, crc))) = obj.gnu_debuglink() {
+        let link = std::str::from_utf8(link).ok()?;
+        return debuglink_paths(path, link).into_iter().find(|candidate| {
+            candidate.is_file() && candidate != path && crc_matches(candidate, crc)
+        });
+    }
+
+    None
+}
+
+/// Path of the DWARF package holding the split units of the binary at `path`, which is the
+/// binary's path with `.dwp` appended.
+pub fn package_path(path: &Path) -> PathBuf {
+    let mut package = path.as_os_str().to_os_string();
+    package.push(".dwp");
+    PathBuf::from(package)
+}
+
+/// Places the `.dwo` file named by a skeleton unit is looked for. The name is relative to the
+/// unit's compilation directory, which might not exist on this machine, so the directory of the
+/// binary is tried as well.
+pub fn dwo_paths(path: &Path, comp_dir: &Path, dwo_name: &Path) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut paths = vec![comp_dir.join(dwo_name)];
+
+    if dwo_name.is_relative() {
+        paths.push(dir.join(dwo_name));
+    }
+
+    if let Some(file_name) = dwo_name.file_name() {
+        paths.push(dir.join(file_name));
+    }
+
+    paths.dedup();
+    paths
+}
+
+/// Path of the DWARF inside the dSYM bundle next to the Mach-O at `path`, e.g.
+/// `ls.dSYM/Contents/Resources/DWARF/ls`.
+pub fn dsym_path(path: &Path) -> Option<PathBuf> {
+    let ext = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.to_string() + ".dSYM",
+        None => "dSYM".to_string(),
+    };
+
+    Some(path.with_extension(ext).join("Contents/Resources/DWARF").join(path.file_name()?))
+}
+
+/// Function symbols of a debug file that aren't already known.
+pub fn symbols<'data>(
+    obj: &object::File<'data>,
+    known: &AddressMap<RawSymbol>,
+) -> AddressMap<RawSymbol<'data>> {
+    let known: std::collections::HashSet<usize> = known.iter().map(|sym| sym.addr).collect();
+    let mut syms = AddressMap::default();
+
+    for sym in obj.symbols() {
+        if sym.kind() != SymbolKind::Text || sym.address() == 0 {
+            continue;
+        }
+
+        let addr = sym.address() as usize;
+        if known.contains(&addr) {
+            continue;
+        }
+
+        if let Ok(name) = sym.name() {
+            if !name.is_empty() {
+                syms.push(Addressed {
+                    addr,
+                    item: RawSymbol {
+                        name,
+                        module: None,
+                        indirection: None,
+                    },
+                });
+            }
+        }
+    }
+
+    syms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_id() {
+        assert_eq!(
+            build_id_path(Path::new("/usr/lib/debug"), &[0xab, 0xcd, 0x01]),
+            Some(PathBuf::from("/usr/lib/debug/.build-id/ab/cd01.debug"))
+        );
+        assert_eq!(build_id_path(Path::new("/usr/lib/debug"), &[0xab]), None);
+
+        let dirs = build_id_dirs(Path::new("/opt/app/bin/app"));
+        assert_eq!(dirs[0], PathBuf::from("/opt/app/bin"));
+        assert_eq!(dirs[1], PathBuf::from("/opt/app/bin/.debug"));
+        assert!(dirs.contains(&PathBuf::from("/usr/lib/debug")));
+    }
+
+    #[test]
+    fn debuglink_alongside_binary() {
+        let paths = debuglink_paths(Path::new("/nonexistent/bin/ls"), "ls.debug");
+        assert_eq!(paths[0], PathBuf::from("/nonexistent/bin/ls.debug"));
+        assert_eq!(paths[1], PathBuf::from("/nonexistent/bin/.debug/ls.debug"));
+        assert!(paths.contains(&PathBuf::from("/usr/lib/debug/nonexistent/bin/ls.debug")));
+    }
+
+    #[test]
+    fn debuginfod() {
+        assert_eq!(
+            debuginfod_path(Path::new("/home/user/.cache/debuginfod_client"), &[0xab, 0x01]),
+            PathBuf::from("/home/user/.cache/debuginfod_client/ab01/debuginfo")
+        );
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+
+        let name = format!("bite_debuglink_checksum_{}.debug", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, b"123456789").unwrap();
+        let (matching, other) = (crc_matches(&path, 0xcbf43926), crc_matches(&path, 0));
+        let _ = std::fs::remove_file(&path);
+
+        // A debug file that was rebuilt since the link was made is skipped.
+        assert!(matching);
+        assert!(!other);
+        assert!(!crc_matches(&path, 0xcbf43926));
+    }
+
+    #[test]
+    fn split_dwarf() {
+        assert_eq!(package_path(Path::new("/bin/ls")), PathBuf::from("/bin/ls.dwp"));
+
+        let paths = dwo_paths(
+            Path::new("/opt/app/bin/app"),
+            Path::new("/build/app"),
+            Path::new("obj/main.dwo"),
+        );
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("/build/app/obj/main.dwo"),
+                PathBuf::from("/opt/app/bin/obj/main.dwo"),
+                PathBuf::from("/opt/app/bin/main.dwo"),
+            ]
+        );
+
+        let paths = dwo_paths(Path::new("/bin/app"), Path::new("/"), Path::new("/tmp/main.dwo"));
+        assert_eq!(
+            paths,
+            [PathBuf::from("/tmp/main.dwo"), PathBuf::from("/bin/main.dwo")]
+        );
+    }
+
+    #[test]
+    fn dsym() {
+        assert_eq!(
+            dsym_path(Path::new("/bin/ls")),
+            Some(PathBuf::from("/bin/ls.dSYM/Contents/Resources/DWARF/ls"))
+        );
+        assert_eq!(
+            dsym_path(Path::new("/lib/libz.dylib")),
+            Some(PathBuf::from("/lib/libz.dylib.dSYM/Contents/Resources/DWARF/libz.dylib"))
+        );
+    }
+}
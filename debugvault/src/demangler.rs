@@ -1,7 +1,7 @@
 //! Symbol demangler for common mangling schemes.
 
-use tokenizing::{Token, Color32};
-use config::CONFIG;
+use tokenizing::{Kind, Token};
+use std::ops::Range;
 
 pub fn parse(s: &str) -> TokenStream {
     // symbols without leading underscores are accepted as
@@ -17,7 +17,8 @@ pub fn parse(s: &str) -> TokenStream {
     }
 
     // parse gnu/llvm/C/C++ symbols
-    if let Some(s) = crate::itanium::parse(s) {
+    if let Some(mut s) = crate::itanium::parse(s) {
+        color_cpp_roles(&mut s);
         return s;
     }
 
@@ -27,7 +28,8 @@ pub fn parse(s: &str) -> TokenStream {
     }
 
     // parse windows msvc C/C++ symbols
-    if let Some(s) = crate::msvc::parse(s) {
+    if let Some(mut s) = crate::msvc::parse(s) {
+        color_cpp_roles(&mut s);
         return s;
     }
 
@@ -35,6 +37,161 @@ pub fn parse(s: &str) -> TokenStream {
     TokenStream::simple(s)
 }
 
+fn is_bracket(token: &Token) -> bool {
+    let text = token.text.trim();
+    !text.is_empty() && text.chars().all(|c| c == '<' || c == '>')
+}
+
+fn is_identifier(token: &Token) -> bool {
+    token.kind == Kind::Component
+        && token.text.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '~')
+}
+
+/// Whether the scope operator follows the token at `idx`, skipping any template arguments.
+fn followed_by_scope(tokens: &[Token], idx: usize) -> bool {
+    let mut depth = 0usize;
+
+    for token in &tokens[idx + 1..] {
+        if is_bracket(token) {
+            for c in token.text.chars() {
+                match c {
+                    '<' => depth += 1,
+                    '>' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
+        if depth == 0 {
+            return &*token.text == "::";
+        }
+    }
+
+    false
+}
+
+/// The C++ demanglers mark all names as components, this marks them by what they name:
+/// namespaces and classes that scope a name, template arguments and operators.
+fn color_cpp_roles(stream: &mut TokenStream) {
+    let tokens = &mut stream.tokens;
+    let mut depth = 0usize;
+    let mut idx = 0;
+
+    while idx < tokens.len() {
+        let text = tokens[idx].text.clone();
+        let text = text.trim();
+
+        if text.starts_with("operator") {
+            tokens[idx].kind = Kind::Operator;
+
+            // Itanium names have the operator's symbol, such as `<<` or `new`, as separate tokens.
+            if text == "operator" {
+                while let Some(next) = tokens.get_mut(idx + 1) {
+                    if next.kind != Kind::Component {
+                        break;
+                    }
+
+                    let symbol = next.text.trim();
+                    let is_symbol = symbol.is_empty()
+                        || matches!(symbol, "new" | "new[]" | "delete" | "delete[]")
+                        || !symbol.starts_with(|c: char| c.is_alphanumeric() || c == '_');
+
+                    if !is_symbol {
+                        break;
+                    }
+
+                    next.kind = Kind::Operator;
+                    idx += 1;
+
+                    if !symbol.is_empty() {
+                        break;
+                    }
+                }
+            }
+
+            idx += 1;
+            continue;
+        }
+
+        if is_bracket(&tokens[idx]) {
+            for c in text.chars() {
+                match c {
+                    '<' => depth += 1,
+                    '>' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        } else if is_identifier(&tokens[idx]) {
+            if depth > 0 {
+                tokens[idx].kind = Kind::Type;
+            } else if followed_by_scope(tokens, idx) {
+                tokens[idx].kind = Kind::Keyword;
+            }
+        }
+
+        idx += 1;
+    }
+}
+
+/// How the generic arguments of a demangled name are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FoldGenerics {
+    /// Show all generic arguments.
+    Never,
+    /// Replace generic arguments longer than this many bytes with `…`, keeping the brackets.
+    Longer(usize),
+}
+
+/// Byte ranges of the outermost generic arguments in `name` that are longer than `max_len`.
+fn generic_folds(name: &str, max_len: usize) -> Vec<Range<usize>> {
+    // `operator<` and friends can't be told apart from generics.
+    if name.contains("operator") {
+        return Vec::new();
+    }
+
+    let bytes = name.as_bytes();
+    let mut open = Vec::new();
+    let mut folds: Vec<Range<usize>> = Vec::new();
+
+    for (idx, &byte) in bytes.iter().enumerate() {
+        match byte {
+            b'<' => {
+                // Generics follow a name or a turbofish, qualified paths such as `<T as Trait>`
+                // and `<impl T>` hold what the name is about and are never folded.
+                let prev = idx.checked_sub(1).map(|idx| bytes[idx]);
+                let after_name = prev.is_some_and(|b| b.is_ascii_alphanumeric() || b == b'_');
+                let turbofish =
+                    name[..idx].ends_with("::") && !name[idx + 1..].starts_with("impl ");
+                open.push((idx, after_name || turbofish));
+            }
+            // Arrow of a function pointer's return type.
+            b'>' if idx > 0 && bytes[idx - 1] == b'-' => {}
+            b'>' => {
+                let (start, generic) = match open.pop() {
+                    Some(open) => open,
+                    None => return Vec::new(),
+                };
+
+                if generic && idx - start - 1 > max_len {
+                    // Arguments nested inside are folded along with these.
+                    while folds.last().is_some_and(|fold| fold.start > start) {
+                        folds.pop();
+                    }
+                    folds.push(start + 1..idx);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !open.is_empty() {
+        return Vec::new();
+    }
+
+    folds
+}
+
 #[derive(Debug)]
 pub struct TokenStream {
     /// Unmovable string which the [Token]'s have a pointer to.
@@ -53,12 +210,17 @@ impl TokenStream {
     }
 
     pub fn simple(s: &str) -> Self {
+        Self::with_kind(s, Kind::Component)
+    }
+
+    /// A single token of the given kind.
+    pub fn with_kind(s: &str, kind: Kind) -> Self {
         let mut this = Self {
             inner: std::pin::Pin::new(s.to_string()),
             tokens: Vec::with_capacity(1),
         };
 
-        this.tokens.push(Token::from_string(s.to_string(), CONFIG.colors.asm.component));
+        this.tokens.push(Token::from_string(s.to_string(), kind));
         this
     }
 
@@ -69,19 +231,75 @@ impl TokenStream {
     }
 
     #[inline]
-    pub fn push(&mut self, text: &'static str, color: Color32) {
-        self.tokens.push(Token::from_str(text, color));
+    pub fn push(&mut self, text: &'static str, kind: Kind) {
+        self.tokens.push(Token::from_str(text, kind));
     }
 
     #[inline]
-    pub fn push_string(&mut self, text: String, color: Color32) {
-        self.tokens.push(Token::from_string(text, color));
+    pub fn push_string(&mut self, text: String, kind: Kind) {
+        self.tokens.push(Token::from_string(text, kind));
     }
 
     #[inline]
     pub fn tokens(&self) -> &[Token] {
         self.tokens.as_slice()
     }
+
+    /// Tokens with generic arguments folded as requested.
+    pub fn folded(&self, fold: FoldGenerics) -> Vec<Token> {
+        let folds = match fold {
+            FoldGenerics::Never => Vec::new(),
+            FoldGenerics::Longer(max_len) => {
+                let name = String::from_iter(self.tokens.iter().map(|t| &t.text[..]));
+                generic_folds(&name, max_len)
+            }
+        };
+
+        if folds.is_empty() {
+            return self.tokens.clone();
+        }
+
+        let mut tokens = Vec::with_capacity(self.tokens.len());
+        let mut folds = folds.into_iter().peekable();
+        let mut offset = 0;
+
+        for token in &self.tokens {
+            let text = &token.text[..];
+            let end = offset + text.len();
+            let mut pos = offset;
+
+            while pos < end {
+                let fold = match folds.peek() {
+                    Some(fold) if fold.start < end => fold.clone(),
+                    _ => {
+                        let unfolded = text[pos - offset..].to_string();
+                        tokens.push(Token::from_string(unfolded, token.kind));
+                        break;
+                    }
+                };
+
+                if pos < fold.start {
+                    let unfolded = &text[pos - offset..fold.start - offset];
+                    tokens.push(Token::from_string(unfolded.to_string(), token.kind));
+                    pos = fold.start;
+                }
+
+                // Folds spanning multiple tokens are only marked once.
+                if pos == fold.start {
+                    tokens.push(Token::from_str("…", Kind::Delimiter));
+                }
+
+                pos = fold.end.min(end);
+                if fold.end <= end {
+                    folds.next();
+                }
+            }
+
+            offset = end;
+        }
+
+        tokens
+    }
 }
 
 impl PartialEq for TokenStream {
@@ -89,3 +307,73 @@ impl PartialEq for TokenStream {
         self.inner == other.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(tokens: &[Token]) -> String {
+        String::from_iter(tokens.iter().map(|t| &t.text[..]))
+    }
+
+    #[test]
+    fn rust_manglings() {
+        let legacy = parse("_ZN4core3ptr13drop_in_place17h1b3d4e5f6a7b8c9dE");
+        assert_eq!(text(legacy.tokens()), "core::ptr::drop_in_place");
+
+        let v0 = parse("_RINvNvC3std3mem8align_ofjdE");
+        assert_eq!(text(v0.tokens()), "std::mem::align_of::<usize, f64>");
+    }
+
+    #[test]
+    fn cpp_roles() {
+        let stream = parse("_ZN2ns3FooIN5inner3BarEE3getEv");
+        let kind = |text: &str| stream.tokens().iter().find(|t| &*t.text == text).map(|t| t.kind);
+        assert_eq!(kind("ns"), Some(Kind::Keyword));
+        assert_eq!(kind("Foo"), Some(Kind::Keyword));
+        assert_eq!(kind("inner"), Some(Kind::Type));
+        assert_eq!(kind("Bar"), Some(Kind::Type));
+        assert_eq!(kind("get"), Some(Kind::Component));
+
+        let stream = parse("_ZN3FoolsEi");
+        let kind = |text: &str| stream.tokens().iter().find(|t| &*t.text == text).map(|t| t.kind);
+        assert_eq!(kind("operator"), Some(Kind::Operator));
+        assert_eq!(kind("<<"), Some(Kind::Operator));
+    }
+
+    #[test]
+    fn folding() {
+        let name = "<alloc::vec::Vec<core::option::Option<u8>> as core::ops::Drop>::drop";
+        let stream = TokenStream::simple(name);
+        assert_eq!(text(&stream.folded(FoldGenerics::Never)), name);
+        assert_eq!(
+            text(&stream.folded(FoldGenerics::Longer(8))),
+            "<alloc::vec::Vec<…> as core::ops::Drop>::drop"
+        );
+        assert_eq!(text(&stream.folded(FoldGenerics::Longer(64))), name);
+
+        let mut stream = TokenStream::new("");
+        stream.push("std::mem::align_of", Kind::Plain);
+        stream.push("::<", Kind::Plain);
+        stream.push("usize", Kind::Plain);
+        stream.push(", ", Kind::Plain);
+        stream.push("f64", Kind::Plain);
+        stream.push(">", Kind::Plain);
+        assert_eq!(
+            text(&stream.folded(FoldGenerics::Longer(4))),
+            "std::mem::align_of::<…>"
+        );
+
+        let impl_block = TokenStream::simple("bite::<impl bite::Decoder<u8>>::new");
+        assert_eq!(
+            text(&impl_block.folded(FoldGenerics::Longer(0))),
+            "bite::<impl bite::Decoder<…>>::new"
+        );
+
+        let arrow = TokenStream::simple("call<fn() -> u32>");
+        assert_eq!(text(&arrow.folded(FoldGenerics::Longer(4))), "call<…>");
+
+        let operator = TokenStream::simple("operator<<<std::string>");
+        assert_eq!(text(&operator.folded(FoldGenerics::Longer(0))), "operator<<<std::string>");
+    }
+}
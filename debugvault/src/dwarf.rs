@@ -1,4 +1,6 @@
 use crate::intern::InternMap;
+use crate::types::{Param, Prototype};
+use crate::variables::{Layout, Location, Scope, Variable};
 use crate::{AddressMap, Addressed, FileAttr};
 use object::{Object, ObjectSection, ObjectSymbol};
 use rustc_hash::FxHasher;
@@ -6,11 +8,15 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use std::io;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 use typed_arena::Arena;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Deepest chain of types followed when naming a type, as types can refer to themselves.
+const MAX_TYPE_DEPTH: usize = 16;
+
 #[derive(Debug)]
 pub enum Error {
     Gimli(gimli::Error),
@@ -39,10 +45,25 @@ impl From<object::Error> for Error {
 pub struct Dwarf {
     /// Mapping from addresses starting at the header base to source files.
     pub file_attrs: AddressMap<FileAttr>,
+
+    /// Mapping from addresses starting at the header base to function prototypes.
+    pub prototypes: AddressMap<Prototype>,
+
+    /// Mapping from addresses starting at the header base to the variables of functions.
+    pub scopes: AddressMap<Scope>,
+}
+
+/// What's declared by the function definitions of the debug info.
+#[derive(Default)]
+struct Functions {
+    prototypes: AddressMap<Prototype>,
+    scopes: AddressMap<Scope>,
 }
 
 impl Dwarf {
-    pub fn parse(obj: &object::File) -> Result<Self> {
+    /// Parse the debug info of `obj` at `path`, including units split off into a `.dwp` package
+    /// or `.dwo` files.
+    pub fn parse(obj: &object::File, path: &Path) -> Result<Self> {
         let endian = if obj.is_little_endian() {
             gimli::RunTimeEndian::Little
         } else {
@@ -59,8 +80,48 @@ impl Dwarf {
         let mut dwarf = gimli::Dwarf::load(&mut load_section)?;
         dwarf.populate_abbreviations_cache(gimli::AbbreviationsCacheStrategy::All);
         let file_attrs = dump_line(&dwarf)?;
+        let mut functions = dump_functions(&dwarf)?;
+
+        let package_path = crate::debuglink::package_path(path);
+        let package_data = std::fs::read(&package_path).ok();
+        let package_obj = package_data.as_deref().and_then(|data| object::File::parse(data).ok());
+        let package = package_obj.as_ref().and_then(|package_obj| {
+            let mut load_section = |id: gimli::SectionId| {
+                load_dwo_section(id, package_obj, endian, &arena_data, &arena_relocations)
+            };
+
+            let empty = empty_section(endian, &arena_data, &arena_relocations);
+            match gimli::DwarfPackage::load(&mut load_section, empty) {
+                Ok(package) => Some(package),
+                Err(err) => {
+                    log::complex!(
+                        w "[dwarf::parse] ",
+                        y "Failed to load package ",
+                        g format!("{package_path:?}"),
+                        y format!(": {err:?}."),
+                    );
+                    None
+                }
+            }
+        });
+
+        let mut load_dwo = |dwo_path: &Path| -> Result<_> {
+            let data = std::fs::read(dwo_path)?;
+            let dwo_obj = object::File::parse(&data[..])?;
+            let mut load_section = |id: gimli::SectionId| {
+                load_dwo_section(id, &dwo_obj, endian, &arena_data, &arena_relocations)
+            };
 
-        Ok(Dwarf { file_attrs })
+            gimli::Dwarf::load(&mut load_section)
+        };
+
+        dump_split_units(&dwarf, package.as_ref(), path, &mut load_dwo, &mut functions)?;
+
+        Ok(Dwarf {
+            file_attrs,
+            prototypes: functions.prototypes,
+            scopes: functions.scopes,
+        })
     }
 
     #[allow(dead_code)]
@@ -68,12 +129,13 @@ impl Dwarf {
         let file = std::fs::File::open(path)?;
         let mmap = unsafe { memmap2::Mmap::map(&file)? };
         let obj = object::File::parse(&*mmap)?;
-        Self::parse(&obj)
+        Self::parse(&obj, path)
     }
 
-    #[allow(dead_code)]
     pub fn merge(&mut self, other: Self) {
         self.file_attrs.extend(other.file_attrs);
+        self.prototypes.extend(other.prototypes);
+        self.scopes.extend(other.scopes);
     }
 }
 
@@ -321,13 +383,60 @@ fn load_file_section<'input, 'arena, Endian: gimli::Endianity>(
     })
 }
 
+/// Load a section of a `.dwo` file or `.dwp` package. These aren't relocated, and their data
+/// is copied so that it can outlive the file.
+fn load_dwo_section<'arena, Endian: gimli::Endianity>(
+    id: gimli::SectionId,
+    file: &object::File,
+    endian: Endian,
+    arena_data: &'arena Arena<Cow<'_, [u8]>>,
+    arena_relocations: &'arena Arena<RelocationMap>,
+) -> Result<Relocate<'arena, gimli::EndianSlice<'arena, Endian>>> {
+    let section = match id.dwo_name().and_then(|name| file.section_by_name(name)) {
+        Some(section) => section,
+        None => return Ok(empty_section(endian, arena_data, arena_relocations)),
+    };
+
+    let data = Cow::Owned(section.uncompressed_data()?.into_owned());
+    let reader = gimli::EndianSlice::new(arena_data.alloc(data), endian);
+    Ok(Relocate {
+        relocations: arena_relocations.alloc(RelocationMap::default()),
+        section: reader,
+        reader,
+    })
+}
+
+fn empty_section<'arena, Endian: gimli::Endianity>(
+    endian: Endian,
+    arena_data: &'arena Arena<Cow<'_, [u8]>>,
+    arena_relocations: &'arena Arena<RelocationMap>,
+) -> Relocate<'arena, gimli::EndianSlice<'arena, Endian>> {
+    // Use a non-zero capacity so that `ReaderOffsetId`s are unique.
+    let data = arena_data.alloc(Cow::Owned(Vec::with_capacity(1)));
+    let reader = gimli::EndianSlice::new(data, endian);
+    Relocate {
+        relocations: arena_relocations.alloc(RelocationMap::default()),
+        section: reader,
+        reader,
+    }
+}
+
 fn dump_line<R: Reader>(dwarf: &gimli::Dwarf<R>) -> Result<AddressMap<FileAttr>> {
+    let mut unit_count = 0;
+    let mut iter = dwarf.units();
+    while iter.next()?.is_some() {
+        unit_count += 1;
+    }
+
+    log::PROGRESS.set("Parsing dwarf.", unit_count);
+
     let mut iter = dwarf.units();
     let mut file_attrs = AddressMap::default();
     let path_cache = InternMap::new();
 
     let mut id = 0;
     while let Some(header) = iter.next()? {
+        log::PROGRESS.step();
         let unit = match dwarf.unit(header) {
             Ok(unit) => unit,
             Err(err) => {
@@ -428,3 +537,494 @@ fn dump_line_program<R: Reader>(
 
     Ok(())
 }
+
+fn dump_functions<R: Reader>(dwarf: &gimli::Dwarf<R>) -> Result<Functions> {
+    let mut iter = dwarf.units();
+    let mut functions = Functions::default();
+
+    while let Some(header) = iter.next()? {
+        let unit = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(..) => continue,
+        };
+
+        let mut tree = unit.entries_tree(None)?;
+        if let Err(err) = dump_entries(dwarf, &unit, tree.root()?, &mut functions) {
+            log::complex!(
+                w "[dwarf::dump_functions] ",
+                y "Failed to read functions: ",
+                y format!("{err:?}."),
+            );
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Find the functions of units split off from the skeleton units of `dwarf`, which are either
+/// in `package` or in the `.dwo` files the skeletons name.
+fn dump_split_units<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    package: Option<&gimli::DwarfPackage<R>>,
+    path: &Path,
+    load_dwo: &mut dyn FnMut(&Path) -> Result<gimli::Dwarf<R>>,
+    functions: &mut Functions,
+) -> Result<()> {
+    let mut iter = dwarf.units();
+
+    while let Some(header) = iter.next()? {
+        let skeleton = match dwarf.unit(header) {
+            Ok(unit) => unit,
+            Err(..) => continue,
+        };
+
+        let dwo_id = match skeleton.dwo_id {
+            Some(dwo_id) => dwo_id,
+            None => continue,
+        };
+
+        let split = match package.map(|package| package.find_cu(dwo_id, dwarf)) {
+            Some(Ok(Some(split))) => Some(split),
+            _ => load_split_dwarf(dwarf, &skeleton, path, load_dwo),
+        };
+
+        let split = match split {
+            Some(split) => split,
+            None => continue,
+        };
+
+        if let Err(err) = dump_split_unit(&split, &skeleton, dwo_id, functions) {
+            log::complex!(
+                w "[dwarf::dump_split_units] ",
+                y "Failed to read functions: ",
+                y format!("{err:?}."),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the `.dwo` file named by `skeleton`.
+fn load_split_dwarf<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    skeleton: &gimli::Unit<R>,
+    path: &Path,
+    load_dwo: &mut dyn FnMut(&Path) -> Result<gimli::Dwarf<R>>,
+) -> Option<gimli::Dwarf<R>> {
+    let dwo_name = skeleton.dwo_name().ok()??;
+    let dwo_name = dwarf.attr_string(skeleton, dwo_name).ok()?;
+    let dwo_name = PathBuf::from(&*dwo_name.to_string_lossy().ok()?);
+    let comp_dir = match skeleton.comp_dir {
+        Some(ref comp_dir) => PathBuf::from(&*comp_dir.to_string_lossy().ok()?),
+        None => PathBuf::new(),
+    };
+
+    let paths = crate::debuglink::dwo_paths(path, &comp_dir, &dwo_name);
+    let dwo_path = match paths.into_iter().find(|path| path.is_file()) {
+        Some(dwo_path) => dwo_path,
+        None => {
+            log::complex!(
+                w "[dwarf::load_split_dwarf] ",
+                y "Couldn't find split debug info ",
+                g format!("{dwo_name:?}"),
+                y ".",
+            );
+            return None;
+        }
+    };
+
+    match load_dwo(&dwo_path) {
+        Ok(mut split) => {
+            split.make_dwo(dwarf);
+            Some(split)
+        }
+        Err(err) => {
+            log::complex!(
+                w "[dwarf::load_split_dwarf] ",
+                y "Failed to load ",
+                g format!("{dwo_path:?}"),
+                y format!(": {err:?}."),
+            );
+            None
+        }
+    }
+}
+
+/// Find the functions of the unit in `split` matching `skeleton`, which holds it's addresses.
+fn dump_split_unit<R: Reader>(
+    split: &gimli::Dwarf<R>,
+    skeleton: &gimli::Unit<R>,
+    dwo_id: gimli::DwoId,
+    functions: &mut Functions,
+) -> Result<()> {
+    let mut iter = split.units();
+
+    while let Some(header) = iter.next()? {
+        let mut unit = split.unit(header)?;
+        if unit.dwo_id != Some(dwo_id) {
+            continue;
+        }
+
+        unit.copy_relocated_attributes(skeleton);
+        let mut tree = unit.entries_tree(None)?;
+        dump_entries(split, &unit, tree.root()?, functions)?;
+    }
+
+    Ok(())
+}
+
+/// Find the functions declared below `node`, including those in namespaces and classes.
+fn dump_entries<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    node: gimli::EntriesTreeNode<R>,
+    functions: &mut Functions,
+) -> Result<()> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        match child.entry().tag() {
+            gimli::DW_TAG_subprogram => dump_subprogram(dwarf, unit, child, functions)?,
+            gimli::DW_TAG_namespace
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_union_type => dump_entries(dwarf, unit, child, functions)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Prototype of a function definition, skipping declarations as they don't have an address.
+fn dump_subprogram<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    node: gimli::EntriesTreeNode<R>,
+    functions: &mut Functions,
+) -> Result<()> {
+    let entry = node.entry();
+    let addr = match entry.attr_value(gimli::DW_AT_low_pc)? {
+        Some(value) => dwarf.attr_address(unit, value)?,
+        None => None,
+    };
+
+    let addr = match addr {
+        Some(addr) if addr != 0 => addr as usize,
+        _ => return Ok(()),
+    };
+
+    // Out-of-line definitions refer to their declaration for the name and return type.
+    let decl = match declaration(entry)? {
+        Some(offset) => Some(unit.entry(offset)?),
+        None => None,
+    };
+
+    let name = match entry_name(dwarf, unit, entry)? {
+        Some(name) => name,
+        None => match decl {
+            Some(ref decl) => match entry_name(dwarf, unit, decl)? {
+                Some(name) => name,
+                None => return Ok(()),
+            },
+            None => return Ok(()),
+        },
+    };
+
+    let ret = match (entry_ref(entry, gimli::DW_AT_type)?, &decl) {
+        (None, Some(decl)) => entry_ref(decl, gimli::DW_AT_type)?,
+        (ret, _) => ret,
+    };
+
+    let mut prototype = Prototype {
+        ret: type_name(dwarf, unit, ret, 0)?,
+        name,
+        params: Vec::new(),
+        variadic: false,
+    };
+
+    let end = match entry_range(dwarf, unit, entry)? {
+        Some(range) => range.end,
+        None => addr,
+    };
+
+    let mut scope = Scope {
+        end,
+        frame_base: location(dwarf, unit, entry.attr_value(gimli::DW_AT_frame_base)?)?,
+        address_size: unit.encoding().address_size,
+        variables: Vec::new(),
+    };
+
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        match entry.tag() {
+            gimli::DW_TAG_formal_parameter => {
+                let param = variable(dwarf, unit, entry, true, None)?;
+                prototype.params.push(Param {
+                    ty: param.ty.clone(),
+                    name: param.name.clone(),
+                });
+                scope.variables.push(param);
+            }
+            gimli::DW_TAG_unspecified_parameters => prototype.variadic = true,
+            gimli::DW_TAG_variable => {
+                scope.variables.push(variable(dwarf, unit, entry, false, None)?);
+            }
+            gimli::DW_TAG_lexical_block => dump_block(dwarf, unit, child, &mut scope.variables)?,
+            _ => {}
+        }
+    }
+
+    functions.prototypes.push(Addressed {
+        addr,
+        item: prototype,
+    });
+
+    // Unnamed variables can't be shown.
+    scope.variables.retain(|var| !var.name.is_empty());
+    if !scope.variables.is_empty() {
+        functions.scopes.push(Addressed { addr, item: scope });
+    }
+
+    Ok(())
+}
+
+/// Variables declared in the lexical block `node`, which are only in scope within it.
+fn dump_block<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    node: gimli::EntriesTreeNode<R>,
+    variables: &mut Vec<Variable>,
+) -> Result<()> {
+    let block = entry_range(dwarf, unit, node.entry())?;
+
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        match child.entry().tag() {
+            gimli::DW_TAG_variable => {
+                variables.push(variable(dwarf, unit, child.entry(), false, block.clone())?);
+            }
+            gimli::DW_TAG_lexical_block => dump_block(dwarf, unit, child, variables)?,
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Parameter or local declared by `entry`, in scope within `block`.
+fn variable<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+    param: bool,
+    block: Option<Range<usize>>,
+) -> Result<Variable> {
+    // Inlined and out-of-line instances refer to the abstract one for the name and type.
+    let origin = match declaration(entry)? {
+        Some(offset) => Some(unit.entry(offset)?),
+        None => None,
+    };
+
+    let decl = origin.as_ref().unwrap_or(entry);
+    let ty = entry_ref(decl, gimli::DW_AT_type)?;
+    Ok(Variable {
+        name: entry_name(dwarf, unit, decl)?.unwrap_or_default(),
+        ty: type_name(dwarf, unit, ty, 0)?,
+        layout: layout(unit, ty, 0)?,
+        location: location(dwarf, unit, entry.attr_value(gimli::DW_AT_location)?)?,
+        param,
+        block,
+    })
+}
+
+/// Addresses spanned by `entry`, from the start of it's first range to the end of it's last.
+fn entry_range<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Result<Option<Range<usize>>> {
+    let mut ranges = dwarf.die_ranges(unit, entry)?;
+    let mut spanned: Option<Range<usize>> = None;
+
+    while let Some(range) = ranges.next()? {
+        let range = range.begin as usize..range.end as usize;
+        spanned = Some(match spanned {
+            Some(spanned) => spanned.start.min(range.start)..spanned.end.max(range.end),
+            None => range,
+        });
+    }
+
+    Ok(spanned)
+}
+
+/// Location given by an attribute such as `DW_AT_location`, either an expression or a list.
+fn location<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    value: Option<gimli::AttributeValue<R>>,
+) -> Result<Location> {
+    let entries = match value {
+        Some(gimli::AttributeValue::Exprloc(expr)) => {
+            return Ok(Location::Expr(expr.0.to_slice()?.into_owned()));
+        }
+        Some(value) => dwarf.attr_locations(unit, value)?,
+        None => None,
+    };
+
+    let mut entries = match entries {
+        Some(entries) => entries,
+        None => return Ok(Location::None),
+    };
+
+    let mut list = Vec::new();
+    while let Some(entry) = entries.next()? {
+        let range = entry.range.begin as usize..entry.range.end as usize;
+        list.push((range, entry.data.0.to_slice()?.into_owned()));
+    }
+
+    Ok(Location::List(list))
+}
+
+/// How values of the type at `offset` are laid out, where a missing type is `void`.
+fn layout<R: Reader>(
+    unit: &gimli::Unit<R>,
+    offset: Option<gimli::UnitOffset>,
+    depth: usize,
+) -> Result<Layout> {
+    let offset = match offset {
+        Some(offset) if depth < MAX_TYPE_DEPTH => offset,
+        _ => return Ok(Layout::Other(0)),
+    };
+
+    let entry = unit.entry(offset)?;
+    let size = match entry.attr_value(gimli::DW_AT_byte_size)? {
+        Some(value) => value.udata_value().unwrap_or(0) as usize,
+        None => 0,
+    };
+
+    let inner = || layout(unit, entry_ref(&entry, gimli::DW_AT_type)?, depth + 1);
+
+    let layout = match entry.tag() {
+        gimli::DW_TAG_base_type => match entry.attr_value(gimli::DW_AT_encoding)? {
+            Some(gimli::AttributeValue::Encoding(encoding)) => match encoding {
+                gimli::DW_ATE_signed_char | gimli::DW_ATE_unsigned_char if size == 1 => {
+                    Layout::Char
+                }
+                gimli::DW_ATE_boolean if size == 1 => Layout::Bool,
+                gimli::DW_ATE_signed | gimli::DW_ATE_signed_char => Layout::Signed(size),
+                gimli::DW_ATE_unsigned
+                | gimli::DW_ATE_unsigned_char
+                | gimli::DW_ATE_boolean
+                | gimli::DW_ATE_UTF => Layout::Unsigned(size),
+                gimli::DW_ATE_float => Layout::Float(size),
+                _ => Layout::Other(size),
+            },
+            _ => Layout::Other(size),
+        },
+        gimli::DW_TAG_pointer_type
+        | gimli::DW_TAG_reference_type
+        | gimli::DW_TAG_rvalue_reference_type => Layout::Pointer(Box::new(inner()?)),
+        gimli::DW_TAG_typedef
+        | gimli::DW_TAG_const_type
+        | gimli::DW_TAG_volatile_type
+        | gimli::DW_TAG_restrict_type
+        | gimli::DW_TAG_atomic_type => inner()?,
+        gimli::DW_TAG_enumeration_type => Layout::Signed(size),
+        _ => Layout::Other(size),
+    };
+
+    Ok(layout)
+}
+
+/// Name of `entry`, if it has one.
+fn entry_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Result<Option<String>> {
+    match entry.attr_value(gimli::DW_AT_name)? {
+        Some(value) => {
+            let name = dwarf.attr_string(unit, value)?;
+            Ok(Some(name.to_string_lossy()?.into_owned()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Entry that `attr` of `entry` refers to, if it's in the same unit.
+fn entry_ref<R: Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+    attr: gimli::DwAt,
+) -> Result<Option<gimli::UnitOffset>> {
+    match entry.attr_value(attr)? {
+        Some(gimli::AttributeValue::UnitRef(offset)) => Ok(Some(offset)),
+        _ => Ok(None),
+    }
+}
+
+/// Entry that `entry` completes, either as a definition or as an inlined instance.
+fn declaration<R: Reader>(
+    entry: &gimli::DebuggingInformationEntry<R>,
+) -> Result<Option<gimli::UnitOffset>> {
+    match entry_ref(entry, gimli::DW_AT_specification)? {
+        Some(offset) => Ok(Some(offset)),
+        None => entry_ref(entry, gimli::DW_AT_abstract_origin),
+    }
+}
+
+/// `ty` followed by a pointer or reference, function types become function pointers.
+fn pointer(ty: String, suffix: &str) -> String {
+    match ty.strip_suffix(" ()") {
+        Some(ret) => format!("{ret} (*)()"),
+        None if ty.ends_with(['*', '&']) => format!("{ty}{suffix}"),
+        None => format!("{ty} {suffix}"),
+    }
+}
+
+/// `ty` with a `qualifier`, which comes after it for pointers.
+fn qualify(ty: String, qualifier: &str) -> String {
+    if ty.ends_with(['*', '&']) {
+        format!("{ty} {qualifier}")
+    } else {
+        format!("{qualifier} {ty}")
+    }
+}
+
+/// C spelling of the type at `offset`, where a missing type is `void`.
+fn type_name<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    offset: Option<gimli::UnitOffset>,
+    depth: usize,
+) -> Result<String> {
+    let offset = match offset {
+        Some(..) if depth == MAX_TYPE_DEPTH => return Ok("...".to_string()),
+        Some(offset) => offset,
+        None => return Ok("void".to_string()),
+    };
+
+    let entry = unit.entry(offset)?;
+    let name = entry_name(dwarf, unit, &entry)?;
+    let inner = || type_name(dwarf, unit, entry_ref(&entry, gimli::DW_AT_type)?, depth + 1);
+
+    let ty = match entry.tag() {
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_array_type => pointer(inner()?, "*"),
+        gimli::DW_TAG_reference_type => pointer(inner()?, "&"),
+        gimli::DW_TAG_rvalue_reference_type => pointer(inner()?, "&&"),
+        gimli::DW_TAG_const_type => qualify(inner()?, "const"),
+        gimli::DW_TAG_volatile_type => qualify(inner()?, "volatile"),
+        gimli::DW_TAG_restrict_type | gimli::DW_TAG_atomic_type => inner()?,
+        gimli::DW_TAG_subroutine_type => format!("{} ()", inner()?),
+        tag => match name {
+            Some(name) => name,
+            None if tag == gimli::DW_TAG_structure_type => "struct".to_string(),
+            None if tag == gimli::DW_TAG_union_type => "union".to_string(),
+            None if tag == gimli::DW_TAG_enumeration_type => "enum".to_string(),
+            None => "?".to_string(),
+        },
+    };
+
+    Ok(ty)
+}
@@ -0,0 +1,220 @@
+//! Fuzzy matching of symbol names.
+//!
+//! A pattern matches a name if all of it's characters appear in the name in order. Matches
+//! are scored such that consecutive characters and characters at the start of a path segment
+//! or word rank higher, similar to the matchers found in editors.
+
+use std::sync::Arc;
+
+use crate::{Index, Symbol};
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 10;
+const BONUS_CONSECUTIVE: i64 = 4;
+const MAX_GAP_PENALTY: usize = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    /// Byte offsets of the matched characters.
+    pub positions: Vec<usize>,
+}
+
+/// A symbol matching a pattern.
+#[derive(Debug)]
+pub struct FuzzyMatch {
+    pub addr: usize,
+    pub symbol: Arc<Symbol>,
+    pub score: i64,
+    /// Byte offsets of the matched characters in [`Symbol::as_str`].
+    pub positions: Vec<usize>,
+}
+
+/// Whether the character at `idx` starts a word, e.g. `b` in `a::b`, `a_b` or `aB`.
+fn is_boundary(chars: &[(usize, char)], idx: usize) -> bool {
+    if idx == 0 {
+        return true;
+    }
+
+    let prev = chars[idx - 1].1;
+    let curr = chars[idx].1;
+    !prev.is_alphanumeric() || (prev.is_lowercase() && curr.is_uppercase())
+}
+
+/// Match `pattern` against `candidate`. Matching is case insensitive unless the pattern
+/// contains uppercase characters.
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<Match> {
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.is_empty() {
+        return Some(Match {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let case_sensitive = pattern.iter().any(|chr| chr.is_uppercase());
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    let chars: Vec<(usize, char)> = candidate.char_indices().collect();
+
+    // Find where the first full match ends.
+    let mut idx = 0;
+    let end = chars.iter().position(|&(_, chr)| {
+        if eq(pattern[idx], chr) {
+            idx += 1;
+        }
+        idx == pattern.len()
+    })?;
+
+    // Walk back from there to find the shortest match that ends there.
+    let mut idx = pattern.len();
+    let start = (0..=end).rev().find(|&ci| {
+        if eq(pattern[idx - 1], chars[ci].1) {
+            idx -= 1;
+        }
+        idx == 0
+    })?;
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut prev: Option<usize> = None;
+    let mut run = 0;
+    let mut idx = 0;
+
+    for ci in start..=end {
+        if idx == pattern.len() {
+            break;
+        }
+
+        let (offset, chr) = chars[ci];
+        if !eq(pattern[idx], chr) {
+            continue;
+        }
+
+        score += SCORE_MATCH;
+        if is_boundary(&chars, ci) {
+            score += BONUS_BOUNDARY;
+        }
+
+        match prev {
+            Some(prev) if prev + 1 == ci => {
+                run += 1;
+                score += BONUS_CONSECUTIVE * run;
+            }
+            Some(prev) => {
+                run = 0;
+                score -= (ci - prev - 1).min(MAX_GAP_PENALTY) as i64;
+            }
+            None => {}
+        }
+
+        positions.push(offset);
+        prev = Some(ci);
+        idx += 1;
+    }
+
+    // Prefer matches near the start and shorter names.
+    score -= start.min(10) as i64;
+    score -= chars.len() as i64 / 16;
+
+    Some(Match { score, positions })
+}
+
+impl Index {
+    /// Named functions matching `pattern`, best matches first.
+    pub fn fuzzy_search(&self, pattern: &str, limit: usize) -> Vec<FuzzyMatch> {
+        let thread_count = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk_size = self.syms.len().div_ceil(thread_count).max(1);
+
+        let mut matches: Vec<FuzzyMatch> = std::thread::scope(|s| {
+            let threads: Vec<_> = self
+                .syms
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    s.spawn(move || {
+                        chunk
+                            .iter()
+                            .filter(|func| !func.item.intrinsic())
+                            .filter_map(|func| {
+                                let m = fuzzy_match(pattern, func.item.as_str())?;
+                                Some(FuzzyMatch {
+                                    addr: func.addr,
+                                    symbol: Arc::clone(&func.item),
+                                    score: m.score,
+                                    positions: m.positions,
+                                })
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            threads.into_iter().flat_map(|t| t.join().unwrap()).collect()
+        });
+
+        sort_matches(&mut matches);
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// Sort matches best first, preferring shorter names for equal scores.
+pub fn sort_matches(matches: &mut [FuzzyMatch]) {
+    matches.sort_unstable_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.symbol.as_str().len().cmp(&b.symbol.as_str().len()))
+            .then_with(|| a.addr.cmp(&b.addr))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsequence() {
+        let m = fuzzy_match("dip", "core::ptr::drop_in_place").unwrap();
+        assert_eq!(m.positions, vec![11, 16, 19]);
+
+        assert!(fuzzy_match("pid", "core::ptr::drop_in_place").is_none());
+        assert!(fuzzy_match("", "main").is_some());
+    }
+
+    #[test]
+    fn smart_case() {
+        assert!(fuzzy_match("hashmap", "std::HashMap::new").is_some());
+        assert!(fuzzy_match("HashMap", "std::hashmap::new").is_none());
+    }
+
+    #[test]
+    fn ranking() {
+        let boundary = fuzzy_match("dip", "drop_in_place").unwrap();
+        let inner = fuzzy_match("dip", "addiprocess").unwrap();
+        assert!(boundary.score > inner.score);
+
+        let consecutive = fuzzy_match("main", "remain").unwrap();
+        let spread = fuzzy_match("main", "mxaxixn").unwrap();
+        assert!(consecutive.score > spread.score);
+    }
+
+    #[test]
+    fn search_index() {
+        let mut index = Index::default();
+        index.insert_func(0x1000, "parse_args");
+        index.insert_func(0x2000, "main");
+        index.insert_func(0x3000, "std::rt::lang_start");
+
+        let matches = index.fuzzy_search("man", 10);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].addr, 0x2000);
+
+        assert_eq!(index.fuzzy_search("a", 2).len(), 2);
+    }
+}
@@ -4,8 +4,7 @@ use super::error::{self, Result};
 use super::index_str::IndexStr;
 use super::subs::{Substitutable, SubstitutionTable};
 use crate::TokenStream;
-use tokenizing::{colors, Color32};
-use config::CONFIG;
+use tokenizing::Kind;
 
 use std::cell::Cell;
 use std::fmt;
@@ -376,7 +375,7 @@ impl<'a> DemangleContext<'a> {
     #[inline]
     fn ensure(&mut self, ch: char) {
         if self.last_char_written != Some(ch) {
-            self.push_owned(format!("{ch}"), CONFIG.colors.asm.component);
+            self.push_owned(format!("{ch}"), Kind::Component);
         }
     }
 
@@ -411,14 +410,14 @@ impl<'a> DemangleContext<'a> {
         }
     }
 
-    fn push_owned(&mut self, text: String, color: Color32) {
+    fn push_owned(&mut self, text: String, kind: Kind) {
         self.last_char_written = text.chars().last();
-        self.stream.push_string(text, color);
+        self.stream.push_string(text, kind);
     }
 
-    fn push(&mut self, text: &'static str, color: Color32) {
+    fn push(&mut self, text: &'static str, kind: Kind) {
         self.last_char_written = text.chars().last();
-        self.stream.push(text, color);
+        self.stream.push(text, kind);
     }
 
     fn demangle_inner_prefixes<'prev>(&mut self, scope: Option<ArgScopeStack<'prev, 'a>>) {
@@ -672,34 +671,34 @@ impl<'subs> Demangle<'subs> for FunctionArgSlice {
                 ctx.ensure_space();
             }
 
-            ctx.push("(", CONFIG.colors.brackets);
+            ctx.push("(", Kind::Bracket);
         }
 
         ctx.demangle_inner_prefixes(scope);
 
         if needs_paren {
-            ctx.push(")", CONFIG.colors.brackets);
+            ctx.push(")", Kind::Bracket);
         }
 
-        ctx.push("(", CONFIG.colors.brackets);
+        ctx.push("(", Kind::Bracket);
 
         // To maintain compatibility with libiberty, print `()` instead of
         // `(void)` for functions that take no arguments.
         if self.len() == 1 && self[0].is_void() {
-            ctx.push(")", CONFIG.colors.brackets);
+            ctx.push(")", Kind::Bracket);
             return;
         }
 
         let mut need_comma = false;
         for arg in self.iter() {
             if need_comma {
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
             }
             arg.demangle(ctx, scope);
             need_comma = true;
         }
 
-        ctx.push(")", CONFIG.colors.delimiter);
+        ctx.push(")", Kind::Delimiter);
         ctx.demangle_inners(scope)
     }
 }
@@ -893,7 +892,7 @@ macro_rules! define_vocabulary {
                     $($typename::$variant => $printable),*
                 });
 
-                ctx.push_owned(text, CONFIG.colors.asm.component);
+                ctx.push_owned(text, Kind::Component);
             }
         }
 
@@ -1022,7 +1021,7 @@ impl<'subs> Demangle<'subs> for MangledName {
                 enc.demangle(ctx, scope);
             }
             MangledName::BlockInvoke(ref enc, _) => {
-                ctx.push("invocation function for block in ", CONFIG.colors.comment);
+                ctx.push("invocation function for block in ", Kind::Comment);
                 enc.demangle(ctx, scope);
             }
             MangledName::Type(ref ty) => ty.demangle(ctx, scope),
@@ -1116,7 +1115,7 @@ impl<'subs> Demangle<'subs> for Encoding {
                     let scope = scope.push(template_args);
                     if !name.is_ctor_dtor_conversion(ctx.subs) {
                         fun_ty.0[0].demangle(ctx, scope);
-                        ctx.push(" ", colors::WHITE);
+                        ctx.push(" ", Kind::Plain);
                     }
 
                     scope
@@ -1188,14 +1187,14 @@ impl<'subs> Demangle<'subs> for CloneSuffix {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.push("[", CONFIG.colors.brackets);
-        ctx.push("clone", CONFIG.colors.asm.component);
+        ctx.push("[", Kind::Bracket);
+        ctx.push("clone", Kind::Component);
         self.0.demangle(ctx, scope);
         for nonnegative in &self.1 {
-            ctx.push(".", CONFIG.colors.delimiter);
-            ctx.push_owned(format!("{nonnegative}"), CONFIG.colors.comment);
+            ctx.push(".", Kind::Delimiter);
+            ctx.push_owned(format!("{nonnegative}"), Kind::Comment);
         }
-        ctx.push("]", CONFIG.colors.brackets);
+        ctx.push("]", Kind::Bracket);
     }
 }
 
@@ -1247,11 +1246,11 @@ impl<'subs> Demangle<'subs> for GlobalCtorDtor {
 
         match *self {
             GlobalCtorDtor::Ctor(ref name) => {
-                ctx.push("global constructors keyed to ", CONFIG.colors.comment);
+                ctx.push("global constructors keyed to ", Kind::Comment);
                 name.demangle(ctx, scope)
             }
             GlobalCtorDtor::Dtor(ref name) => {
-                ctx.push("global destructors keyed to ", CONFIG.colors.comment);
+                ctx.push("global destructors keyed to ", Kind::Comment);
                 name.demangle(ctx, scope)
             }
         }
@@ -1408,8 +1407,8 @@ impl<'subs> Demangle<'subs> for UnscopedName {
         match *self {
             UnscopedName::Unqualified(ref unqualified) => unqualified.demangle(ctx, scope),
             UnscopedName::Std(ref std) => {
-                ctx.push("std", CONFIG.colors.asm.component);
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("std", Kind::Component);
+                ctx.push("::", Kind::Delimiter);
                 std.demangle(ctx, scope)
             }
         }
@@ -1595,7 +1594,7 @@ impl<'subs> Demangle<'subs> for NestedName {
             NestedName::Unqualified(_, _, ref p, ref name) => {
                 p.demangle(ctx, scope);
                 if name.accepts_double_colon() {
-                    ctx.push("::", CONFIG.colors.delimiter);
+                    ctx.push("::", Kind::Delimiter);
                 }
                 name.demangle(ctx, scope);
             }
@@ -1899,7 +1898,7 @@ impl<'subs> Demangle<'subs> for Prefix {
             Prefix::Nested(ref prefix, ref unqualified) => {
                 prefix.demangle(ctx, scope);
                 if unqualified.accepts_double_colon() {
-                    ctx.push("::", CONFIG.colors.delimiter);
+                    ctx.push("::", Kind::Delimiter);
                 }
                 unqualified.demangle(ctx, scope)
             }
@@ -1913,7 +1912,7 @@ impl<'subs> Demangle<'subs> for Prefix {
             Prefix::Decltype(ref dt) => dt.demangle(ctx, scope),
             Prefix::DataMember(ref prefix, ref member) => {
                 prefix.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("::", Kind::Delimiter);
                 member.demangle(ctx, scope)
             }
         }
@@ -2049,7 +2048,7 @@ impl<'subs> Demangle<'subs> for UnqualifiedName {
     ) {
         match *self {
             UnqualifiedName::Operator(ref op_name) => {
-                ctx.push("operator", CONFIG.colors.asm.primitive);
+                ctx.push("operator", Kind::Primitive);
                 op_name.demangle(ctx, scope)
             }
             UnqualifiedName::CtorDtor(ref ctor_dtor) => ctor_dtor.demangle(ctx, scope),
@@ -2218,11 +2217,11 @@ impl<'subs> Demangle<'subs> for TaggedName {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.push("[", CONFIG.colors.brackets);
-        ctx.push("abi", CONFIG.colors.asm.annotation);
-        ctx.push(":", CONFIG.colors.delimiter);
+        ctx.push("[", Kind::Bracket);
+        ctx.push("abi", Kind::Annotation);
+        ctx.push(":", Kind::Delimiter);
         self.0.demangle(ctx, scope);
-        ctx.push("]", CONFIG.colors.brackets);
+        ctx.push("]", Kind::Bracket);
     }
 }
 
@@ -2304,9 +2303,9 @@ impl<'subs> Demangle<'subs> for Identifier {
 
             match (first, second) {
                 (b'.', b'N') | (b'_', b'N') | (b'$', b'N') => {
-                    ctx.push("(", CONFIG.colors.brackets);
-                    ctx.push("anonymous namespace", CONFIG.colors.comment);
-                    ctx.push(")", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
+                    ctx.push("anonymous namespace", Kind::Comment);
+                    ctx.push(")", Kind::Bracket);
                     return;
                 }
                 _ => {
@@ -2317,7 +2316,7 @@ impl<'subs> Demangle<'subs> for Identifier {
 
         let source_name = String::from_utf8_lossy(ident);
         ctx.set_source_name(self.start, self.end);
-        ctx.push_owned(source_name.into_owned(), CONFIG.colors.asm.component);
+        ctx.push_owned(source_name.into_owned(), Kind::Component);
     }
 }
 
@@ -2377,8 +2376,8 @@ impl<'subs> Demangle<'subs> for CloneTypeIdentifier {
 
         let source_name = String::from_utf8_lossy(ident);
         ctx.set_source_name(self.start, self.end);
-        ctx.push(".", CONFIG.colors.comment);
-        ctx.push_owned(source_name.into_owned(), CONFIG.colors.asm.component);
+        ctx.push(".", Kind::Comment);
+        ctx.push_owned(source_name.into_owned(), Kind::Component);
     }
 }
 
@@ -2571,16 +2570,16 @@ impl<'subs> Demangle<'subs> for OperatorName {
             }
             OperatorName::Literal(ref name) => {
                 name.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("operator ", CONFIG.colors.asm.primitive);
-                ctx.push("\"\"", CONFIG.colors.comment);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("operator ", Kind::Primitive);
+                ctx.push("\"\"", Kind::Comment);
             }
             OperatorName::VendorExtension(arity, ref name) => {
                 // TODO: no idea how this should be demangled...
                 name.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("operator ", CONFIG.colors.asm.primitive);
-                ctx.push_owned(format!("{arity}"), CONFIG.colors.asm.primitive);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("operator ", Kind::Primitive);
+                ctx.push_owned(format!("{arity}"), Kind::Primitive);
             }
         }
     }
@@ -2696,12 +2695,12 @@ impl<'subs> Demangle<'subs> for CallOffset {
     ) {
         match *self {
             CallOffset::NonVirtual(NvOffset(off)) => {
-                ctx.push_owned(format!("{{offset({off})}}"), CONFIG.colors.comment);
+                ctx.push_owned(format!("{{offset({off})}}"), Kind::Comment);
             }
             CallOffset::Virtual(VOffset(vbase, vcall)) => {
                 ctx.push_owned(
                     format!("{{virtual offset({vbase}, {vcall})}}"),
-                    CONFIG.colors.comment,
+                    Kind::Comment,
                 );
             }
         }
@@ -2888,7 +2887,7 @@ impl<'subs> Demangle<'subs> for CtorDtorName {
             | CtorDtorName::CompleteDestructor
             | CtorDtorName::BaseDestructor
             | CtorDtorName::MaybeInChargeDestructor => {
-                ctx.push("~", CONFIG.colors.asm.component);
+                ctx.push("~", Kind::Component);
                 leaf.demangle_as_leaf(ctx)
             }
         }
@@ -3224,15 +3223,15 @@ impl<'subs> Demangle<'subs> for Type {
             }
             Type::Complex(ref ty) => {
                 ty.demangle(ctx, scope);
-                ctx.push(" complex", CONFIG.colors.asm.annotation);
+                ctx.push(" complex", Kind::Annotation);
             }
             Type::Imaginary(ref ty) => {
                 ty.demangle(ctx, scope);
-                ctx.push(" imaginary", CONFIG.colors.asm.annotation);
+                ctx.push(" imaginary", Kind::Annotation);
             }
             Type::VendorExtension(ref name, ref template_args, ref ty) => {
                 ty.demangle(ctx, scope);
-                ctx.push(" ", colors::WHITE);
+                ctx.push(" ", Kind::Plain);
                 name.demangle(ctx, scope);
                 if let Some(ref args) = *template_args {
                     args.demangle(ctx, scope);
@@ -3241,7 +3240,7 @@ impl<'subs> Demangle<'subs> for Type {
             Type::PackExpansion(ref ty) => {
                 ty.demangle(ctx, scope);
                 if !ctx.is_template_argument_pack {
-                    ctx.push("...", CONFIG.colors.asm.component);
+                    ctx.push("...", Kind::Component);
                 }
             }
         }
@@ -3256,7 +3255,7 @@ impl<'subs> DemangleAsInner<'subs> for Type {
     ) {
         match *self {
             Type::Qualified(ref quals, _) => quals.demangle_as_inner(ctx, scope),
-            Type::PointerTo(_) => ctx.push("*", CONFIG.colors.asm.pointer),
+            Type::PointerTo(_) => ctx.push("*", Kind::Pointer),
             Type::RvalueRef(_) => {
                 while let Some(v) = ctx.inner.last().and_then(|ty| ty.downcast_to_type()) {
                     match v {
@@ -3272,7 +3271,7 @@ impl<'subs> DemangleAsInner<'subs> for Type {
                         _ => break,
                     }
                 }
-                ctx.push("&&", CONFIG.colors.asm.pointer)
+                ctx.push("&&", Kind::Pointer)
             }
             Type::LvalueRef(_) => {
                 while let Some(v) = ctx.inner.last().and_then(|ty| ty.downcast_to_type()) {
@@ -3290,7 +3289,7 @@ impl<'subs> DemangleAsInner<'subs> for Type {
                         _ => break,
                     }
                 }
-                ctx.push("&", CONFIG.colors.asm.pointer)
+                ctx.push("&", Kind::Pointer)
             }
             ref otherwise => {
                 unreachable!(
@@ -3426,17 +3425,17 @@ impl<'subs> Demangle<'subs> for CvQualifiers {
     ) {
         if self.konst {
             ctx.ensure_space();
-            ctx.push("const", CONFIG.colors.asm.annotation);
+            ctx.push("const", Kind::Annotation);
         }
 
         if self.volatile {
             ctx.ensure_space();
-            ctx.push("volatile", CONFIG.colors.asm.annotation);
+            ctx.push("volatile", Kind::Annotation);
         }
 
         if self.restrict {
             ctx.ensure_space();
-            ctx.push("restrict", CONFIG.colors.asm.annotation);
+            ctx.push("restrict", Kind::Annotation);
         }
     }
 }
@@ -3648,12 +3647,12 @@ impl<'subs> Demangle<'subs> for ExceptionSpec {
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
         match *self {
-            ExceptionSpec::NoExcept => ctx.push("noexcept", CONFIG.colors.asm.annotation),
+            ExceptionSpec::NoExcept => ctx.push("noexcept", Kind::Annotation),
             ExceptionSpec::Computed(ref expr) => {
-                ctx.push("noexcept", CONFIG.colors.asm.annotation);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("noexcept", Kind::Annotation);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
         }
     }
@@ -3891,10 +3890,10 @@ impl<'subs> Demangle<'subs> for Decltype {
     ) {
         match *self {
             Decltype::Expression(ref expr) | Decltype::IdExpression(ref expr) => {
-                ctx.push("decltype ", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("decltype ", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
         }
     }
@@ -3963,15 +3962,15 @@ impl<'subs> Demangle<'subs> for ClassEnumType {
         match *self {
             ClassEnumType::Named(ref name) => name.demangle(ctx, scope),
             ClassEnumType::ElaboratedStruct(ref name) => {
-                ctx.push("class ", CONFIG.colors.asm.primitive);
+                ctx.push("class ", Kind::Primitive);
                 name.demangle(ctx, scope)
             }
             ClassEnumType::ElaboratedUnion(ref name) => {
-                ctx.push("union ", CONFIG.colors.asm.primitive);
+                ctx.push("union ", Kind::Primitive);
                 name.demangle(ctx, scope)
             }
             ClassEnumType::ElaboratedEnum(ref name) => {
-                ctx.push("enum ", CONFIG.colors.asm.primitive);
+                ctx.push("enum ", Kind::Primitive);
                 name.demangle(ctx, scope)
             }
         }
@@ -4033,7 +4032,7 @@ impl<'subs> Demangle<'subs> for UnnamedTypeName {
     ) {
         ctx.push_owned(
             format!("{{unnamed type#{}}}", self.0.map_or(1, |n| n + 1)),
-            CONFIG.colors.comment,
+            Kind::Comment,
         );
     }
 }
@@ -4043,10 +4042,10 @@ impl<'subs> DemangleAsLeaf<'subs> for UnnamedTypeName {
         let text = format!("{{unnamed type#{}}}", self.0.map_or(1, |n| n + 1));
 
         if let Some(source_name) = ctx.source_name {
-            ctx.push_owned(text, CONFIG.colors.comment);
-            ctx.push_owned(source_name.to_string(), CONFIG.colors.asm.component);
+            ctx.push_owned(text, Kind::Comment);
+            ctx.push_owned(source_name.to_string(), Kind::Component);
         } else {
-            ctx.push_owned(text, CONFIG.colors.comment);
+            ctx.push_owned(text, Kind::Comment);
         }
     }
 }
@@ -4173,14 +4172,14 @@ impl<'subs> DemangleAsInner<'subs> for ArrayType {
                 if inner.is_qualified() {
                     inner.demangle_as_inner(ctx, scope);
                     ctx.ensure_space();
-                    ctx.push("(", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
                 } else {
-                    ctx.push("(", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
                     inner.demangle_as_inner(ctx, scope);
                 }
 
                 ctx.demangle_inners(scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
         }
 
@@ -4190,17 +4189,17 @@ impl<'subs> DemangleAsInner<'subs> for ArrayType {
 
         match *self {
             ArrayType::DimensionNumber(n, _) => {
-                ctx.push("[", CONFIG.colors.brackets);
-                ctx.push_owned(n.to_string(), CONFIG.colors.asm.immediate);
-                ctx.push("]", CONFIG.colors.brackets);
+                ctx.push("[", Kind::Bracket);
+                ctx.push_owned(n.to_string(), Kind::Immediate);
+                ctx.push("]", Kind::Bracket);
             }
             ArrayType::DimensionExpression(ref expr, _) => {
-                ctx.push("[", CONFIG.colors.brackets);
+                ctx.push("[", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push("]", CONFIG.colors.brackets);
+                ctx.push("]", Kind::Bracket);
             }
             ArrayType::NoDimension(_) => {
-                ctx.push("[]", CONFIG.colors.brackets);
+                ctx.push("[]", Kind::Bracket);
             }
         }
     }
@@ -4277,16 +4276,16 @@ impl<'subs> DemangleAsInner<'subs> for VectorType {
     ) {
         match *self {
             VectorType::DimensionNumber(n, _) => {
-                ctx.push("__vector", CONFIG.colors.asm.annotation);
-                ctx.push("(", CONFIG.colors.brackets);
-                ctx.push_owned(n.to_string(), CONFIG.colors.asm.immediate);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push("__vector", Kind::Annotation);
+                ctx.push("(", Kind::Bracket);
+                ctx.push_owned(n.to_string(), Kind::Immediate);
+                ctx.push(")", Kind::Bracket);
             }
             VectorType::DimensionExpression(ref expr, _) => {
-                ctx.push("__vector", CONFIG.colors.asm.annotation);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("__vector", Kind::Annotation);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
         }
     }
@@ -4340,7 +4339,7 @@ impl<'subs> DemangleAsInner<'subs> for PointerToMemberType {
         }
 
         self.0.demangle(ctx, scope);
-        ctx.push("::*", CONFIG.colors.delimiter);
+        ctx.push("::*", Kind::Delimiter);
     }
 
     fn downcast_to_pointer_to_member(&self) -> Option<&PointerToMemberType> {
@@ -4383,9 +4382,9 @@ impl<'subs> Demangle<'subs> for TemplateParam {
     ) {
         if ctx.is_lambda_arg {
             // To match libiberty, template references are converted to `auto`.
-            ctx.push("auto", CONFIG.colors.asm.primitive);
-            ctx.push(":", CONFIG.colors.delimiter);
-            ctx.push_owned((self.0 + 1).to_string(), CONFIG.colors.asm.immediate);
+            ctx.push("auto", Kind::Primitive);
+            ctx.push(":", Kind::Delimiter);
+            ctx.push_owned((self.0 + 1).to_string(), Kind::Immediate);
         } else {
             let arg = self.resolve(scope);
             arg.demangle(ctx, scope)
@@ -4526,8 +4525,8 @@ impl<'subs> Demangle<'subs> for FunctionParam {
         _: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
         match self.2 {
-            None => ctx.push("this", CONFIG.colors.asm.primitive),
-            Some(i) => ctx.push_owned(format!("{{parm#{}}}", i + 1), CONFIG.colors.comment),
+            None => ctx.push("this", Kind::Primitive),
+            Some(i) => ctx.push_owned(format!("{{parm#{}}}", i + 1), Kind::Comment),
         }
     }
 }
@@ -4565,14 +4564,14 @@ impl<'subs> Demangle<'subs> for TemplateArgs {
         inner_barrier!(ctx);
 
         if ctx.last_char_written == Some('<') {
-            ctx.push(" ", colors::WHITE);
+            ctx.push(" ", Kind::Plain);
         }
 
-        ctx.push("<", CONFIG.colors.asm.label);
+        ctx.push("<", Kind::Label);
         let mut need_comma = false;
         for arg_index in 0..self.0.len() {
             if need_comma {
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
             }
             if let Some(ref mut scope) = scope {
                 scope.in_arg = Some((arg_index, self));
@@ -4581,7 +4580,7 @@ impl<'subs> Demangle<'subs> for TemplateArgs {
             need_comma = true;
         }
 
-        ctx.push(">", CONFIG.colors.asm.label);
+        ctx.push(">", Kind::Label);
     }
 }
 
@@ -4678,7 +4677,7 @@ impl<'subs> Demangle<'subs> for TemplateArg {
                 let mut need_comma = false;
                 for arg in &args[..] {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     arg.demangle(ctx, scope);
                     need_comma = true;
@@ -4726,13 +4725,13 @@ impl<'subs> Demangle<'subs> for MemberName {
     ) {
         let needs_parens = self.0.get_template_args(ctx.subs).is_some();
         if needs_parens {
-            ctx.push("(", CONFIG.colors.brackets);
+            ctx.push("(", Kind::Bracket);
         }
 
         self.0.demangle(ctx, scope);
 
         if needs_parens {
-            ctx.push(")", CONFIG.colors.brackets);
+            ctx.push(")", Kind::Bracket);
         }
     }
 }
@@ -5234,11 +5233,11 @@ impl<'subs> Demangle<'subs> for Expression {
                 ref lhs,
                 ref rhs,
             ) => {
-                ctx.push("((", CONFIG.colors.brackets);
+                ctx.push("((", Kind::Bracket);
                 lhs.demangle(ctx, scope);
-                ctx.push(")>(", CONFIG.colors.brackets);
+                ctx.push(")>(", Kind::Bracket);
                 rhs.demangle(ctx, scope);
-                ctx.push("))", CONFIG.colors.brackets);
+                ctx.push("))", Kind::Bracket);
             }
             Expression::Binary(ref op, ref lhs, ref rhs) => {
                 lhs.demangle_as_subexpr(ctx, scope);
@@ -5252,9 +5251,9 @@ impl<'subs> Demangle<'subs> for Expression {
                 ref alternative,
             ) => {
                 condition.demangle_as_subexpr(ctx, scope);
-                ctx.push("?", CONFIG.colors.delimiter);
+                ctx.push("?", Kind::Delimiter);
                 consequent.demangle_as_subexpr(ctx, scope);
-                ctx.push(" : ", CONFIG.colors.delimiter);
+                ctx.push(" : ", Kind::Delimiter);
                 alternative.demangle_as_subexpr(ctx, scope)
             }
             Expression::Ternary(ref op, ref e1, ref e2, ref e3) => {
@@ -5263,301 +5262,301 @@ impl<'subs> Demangle<'subs> for Expression {
                 //
                 // TODO: should we detect and reject this during parsing
                 op.demangle(ctx, scope);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("(", Kind::Bracket);
                 e1.demangle(ctx, scope);
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
                 e2.demangle(ctx, scope);
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
                 e3.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::PrefixInc(ref expr) => {
-                ctx.push("++", CONFIG.colors.asm.expr);
+                ctx.push("++", Kind::Expr);
                 expr.demangle(ctx, scope)
             }
             Expression::PrefixDec(ref expr) => {
-                ctx.push("--", CONFIG.colors.asm.expr);
+                ctx.push("--", Kind::Expr);
                 expr.demangle(ctx, scope)
             }
             Expression::Call(ref functor_expr, ref args) => {
                 functor_expr.demangle_as_subexpr(ctx, scope);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("(", Kind::Bracket);
                 let mut need_comma = false;
                 for arg in args {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     arg.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::ConversionOne(ref ty, ref expr) => {
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("(", Kind::Bracket);
                 ty.demangle(ctx, scope);
-                ctx.push(")(", CONFIG.colors.brackets);
+                ctx.push(")(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::ConversionMany(ref ty, ref exprs) => {
                 ty.demangle(ctx, scope);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("(", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::ConversionBraced(ref ty, ref exprs) => {
                 ty.demangle(ctx, scope);
-                ctx.push("{{", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push("}}", CONFIG.colors.brackets);
+                ctx.push("}}", Kind::Bracket);
             }
             Expression::BracedInitList(ref expr) => {
-                ctx.push("{{", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push("}}", CONFIG.colors.brackets);
+                ctx.push("}}", Kind::Bracket);
             }
             // TODO: factor out all this duplication in the `new` variants.
             Expression::New(ref exprs, ref ty, ref init) => {
-                ctx.push("new ", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("new ", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(") ", CONFIG.colors.brackets);
+                ctx.push(") ", Kind::Bracket);
                 ty.demangle(ctx, scope);
                 if let Some(ref init) = *init {
                     init.demangle(ctx, scope);
                 }
             }
             Expression::GlobalNew(ref exprs, ref ty, ref init) => {
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("new ", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("new ", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
                 ty.demangle(ctx, scope);
                 if let Some(ref init) = *init {
                     init.demangle(ctx, scope);
                 }
             }
             Expression::NewArray(ref exprs, ref ty, ref init) => {
-                ctx.push("new", CONFIG.colors.asm.primitive);
-                ctx.push("[]", CONFIG.colors.brackets);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("new", Kind::Primitive);
+                ctx.push("[]", Kind::Bracket);
+                ctx.push(" (", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(") ", CONFIG.colors.brackets);
+                ctx.push(") ", Kind::Bracket);
                 ty.demangle(ctx, scope);
                 if let Some(ref init) = *init {
                     init.demangle(ctx, scope);
                 }
             }
             Expression::GlobalNewArray(ref exprs, ref ty, ref init) => {
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("new", CONFIG.colors.asm.primitive);
-                ctx.push("[] (", CONFIG.colors.brackets);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("new", Kind::Primitive);
+                ctx.push("[] (", Kind::Bracket);
                 let mut need_comma = false;
                 for expr in exprs {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     expr.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(") ", CONFIG.colors.brackets);
+                ctx.push(") ", Kind::Bracket);
                 ty.demangle(ctx, scope);
                 if let Some(ref init) = *init {
                     init.demangle(ctx, scope);
                 }
             }
             Expression::Delete(ref expr) => {
-                ctx.push("delete ", CONFIG.colors.asm.primitive);
+                ctx.push("delete ", Kind::Primitive);
                 expr.demangle(ctx, scope)
             }
             Expression::GlobalDelete(ref expr) => {
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("delete ", CONFIG.colors.asm.primitive);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("delete ", Kind::Primitive);
                 expr.demangle(ctx, scope)
             }
             Expression::DeleteArray(ref expr) => {
-                ctx.push("delete ", CONFIG.colors.asm.primitive);
-                ctx.push("[] ", CONFIG.colors.brackets);
+                ctx.push("delete ", Kind::Primitive);
+                ctx.push("[] ", Kind::Bracket);
                 expr.demangle(ctx, scope)
             }
             Expression::GlobalDeleteArray(ref expr) => {
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("delete ", CONFIG.colors.asm.primitive);
-                ctx.push("[] ", CONFIG.colors.brackets);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("delete ", Kind::Primitive);
+                ctx.push("[] ", Kind::Bracket);
                 expr.demangle(ctx, scope)
             }
             // TODO: factor out duplicated code from cast variants.
             Expression::DynamicCast(ref ty, ref expr) => {
-                ctx.push("dynamic_cast", CONFIG.colors.asm.primitive);
-                ctx.push("<", CONFIG.colors.asm.label);
+                ctx.push("dynamic_cast", Kind::Primitive);
+                ctx.push("<", Kind::Label);
                 ty.demangle(ctx, scope);
-                ctx.push(">", CONFIG.colors.asm.label);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push(">", Kind::Label);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::StaticCast(ref ty, ref expr) => {
-                ctx.push("static_cast", CONFIG.colors.asm.primitive);
-                ctx.push("<", CONFIG.colors.asm.label);
+                ctx.push("static_cast", Kind::Primitive);
+                ctx.push("<", Kind::Label);
                 ty.demangle(ctx, scope);
-                ctx.push(">", CONFIG.colors.asm.label);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push(">", Kind::Label);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::ConstCast(ref ty, ref expr) => {
-                ctx.push("const_cast", CONFIG.colors.asm.primitive);
-                ctx.push("<", CONFIG.colors.asm.label);
+                ctx.push("const_cast", Kind::Primitive);
+                ctx.push("<", Kind::Label);
                 ty.demangle(ctx, scope);
-                ctx.push(">", CONFIG.colors.asm.label);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push(">", Kind::Label);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::ReinterpretCast(ref ty, ref expr) => {
-                ctx.push("reinterpret_cast", CONFIG.colors.asm.primitive);
-                ctx.push("<", CONFIG.colors.asm.label);
+                ctx.push("reinterpret_cast", Kind::Primitive);
+                ctx.push("<", Kind::Label);
                 ty.demangle(ctx, scope);
-                ctx.push(">", CONFIG.colors.asm.label);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push(">", Kind::Label);
+                ctx.push("(", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::TypeidType(ref ty) => {
-                ctx.push("typeid", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("typeid", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 ty.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::TypeidExpr(ref expr) => {
-                ctx.push("typeid", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("typeid", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::SizeofType(ref ty) => {
-                ctx.push("sizeof", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("sizeof", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 ty.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::SizeofExpr(ref expr) => {
-                ctx.push("sizeof", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("sizeof", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::AlignofType(ref ty) => {
-                ctx.push("alignof", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("alignof", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 ty.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::AlignofExpr(ref expr) => {
-                ctx.push("alignof", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("alignof", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::Noexcept(ref expr) => {
-                ctx.push("noexcept", CONFIG.colors.asm.primitive);
-                ctx.push(" (", CONFIG.colors.brackets);
+                ctx.push("noexcept", Kind::Primitive);
+                ctx.push(" (", Kind::Bracket);
                 expr.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::Subobject(ref expr) => expr.demangle(ctx, scope),
             Expression::TemplateParam(ref param) => param.demangle(ctx, scope),
             Expression::FunctionParam(ref param) => param.demangle(ctx, scope),
             Expression::Member(ref expr, ref name) => {
                 expr.demangle_as_subexpr(ctx, scope);
-                ctx.push(".", CONFIG.colors.delimiter);
+                ctx.push(".", Kind::Delimiter);
                 name.demangle(ctx, scope)
             }
             Expression::DerefMember(ref expr, ref name) => {
                 expr.demangle(ctx, scope);
-                ctx.push("->", CONFIG.colors.brackets);
+                ctx.push("->", Kind::Bracket);
                 name.demangle(ctx, scope)
             }
             Expression::PointerToMember(ref e1, ref e2) => {
                 e1.demangle(ctx, scope);
-                ctx.push(".", CONFIG.colors.delimiter);
-                ctx.push("*", CONFIG.colors.brackets);
+                ctx.push(".", Kind::Delimiter);
+                ctx.push("*", Kind::Bracket);
                 e2.demangle(ctx, scope)
             }
             Expression::SizeofTemplatePack(ref param) => {
-                ctx.push("sizeof", CONFIG.colors.asm.primitive);
-                ctx.push("...", CONFIG.colors.delimiter);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("sizeof", Kind::Primitive);
+                ctx.push("...", Kind::Delimiter);
+                ctx.push("(", Kind::Bracket);
                 param.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::SizeofFunctionPack(ref param) => {
-                ctx.push("sizeof", CONFIG.colors.asm.primitive);
-                ctx.push("...", CONFIG.colors.delimiter);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("sizeof", Kind::Primitive);
+                ctx.push("...", Kind::Delimiter);
+                ctx.push("(", Kind::Bracket);
                 param.demangle(ctx, scope);
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::SizeofCapturedTemplatePack(ref args) => {
-                ctx.push("sizeof", CONFIG.colors.asm.primitive);
-                ctx.push("...", CONFIG.colors.delimiter);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("sizeof", Kind::Primitive);
+                ctx.push("...", Kind::Delimiter);
+                ctx.push("(", Kind::Bracket);
                 let mut need_comma = false;
                 for arg in args {
                     if need_comma {
-                        ctx.push(", ", CONFIG.colors.delimiter);
+                        ctx.push(", ", Kind::Delimiter);
                     }
                     arg.demangle(ctx, scope);
                     need_comma = true;
                 }
-                ctx.push(")", CONFIG.colors.brackets);
+                ctx.push(")", Kind::Bracket);
             }
             Expression::PackExpansion(ref pack) => {
                 pack.demangle_as_subexpr(ctx, scope);
-                ctx.push("...", CONFIG.colors.delimiter);
+                ctx.push("...", Kind::Delimiter);
             }
             Expression::Throw(ref expr) => {
-                ctx.push("throw ", CONFIG.colors.asm.primitive);
+                ctx.push("throw ", Kind::Primitive);
                 expr.demangle(ctx, scope)
             }
             Expression::Rethrow => {
-                ctx.push("throw", CONFIG.colors.asm.primitive);
+                ctx.push("throw", Kind::Primitive);
             }
             Expression::UnresolvedName(ref name) => name.demangle(ctx, scope),
             Expression::Primary(ref expr) => expr.demangle(ctx, scope),
@@ -5577,13 +5576,13 @@ impl Expression {
         );
 
         if needs_parens {
-            ctx.push("(", CONFIG.colors.brackets);
+            ctx.push("(", Kind::Bracket);
         }
 
         self.demangle(ctx, scope);
 
         if needs_parens {
-            ctx.push(")", CONFIG.colors.brackets);
+            ctx.push(")", Kind::Bracket);
         }
     }
 }
@@ -5678,31 +5677,31 @@ impl<'subs> Demangle<'subs> for UnresolvedName {
         match *self {
             UnresolvedName::Name(ref name) => name.demangle(ctx, scope),
             UnresolvedName::Global(ref name) => {
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("::", Kind::Delimiter);
                 name.demangle(ctx, scope)
             }
             UnresolvedName::Nested1(ref ty, ref levels, ref name) => {
                 ty.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("::", Kind::Delimiter);
                 for lvl in &levels[..] {
                     lvl.demangle(ctx, scope);
-                    ctx.push("::", CONFIG.colors.delimiter);
+                    ctx.push("::", Kind::Delimiter);
                 }
                 name.demangle(ctx, scope)
             }
             UnresolvedName::Nested2(ref levels, ref name) => {
                 for lvl in &levels[..] {
                     lvl.demangle(ctx, scope);
-                    ctx.push("::", CONFIG.colors.delimiter);
+                    ctx.push("::", Kind::Delimiter);
                 }
                 name.demangle(ctx, scope)
             }
             // `::A::x` or `::N::y` or `::A<T>::z`
             UnresolvedName::GlobalNested2(ref levels, ref name) => {
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("::", Kind::Delimiter);
                 for lvl in &levels[..] {
                     lvl.demangle(ctx, scope);
-                    ctx.push("::", CONFIG.colors.delimiter);
+                    ctx.push("::", Kind::Delimiter);
                 }
                 name.demangle(ctx, scope)
             }
@@ -5972,7 +5971,7 @@ impl<'subs> Demangle<'subs> for DestructorName {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.push("~", CONFIG.colors.delimiter);
+        ctx.push("~", Kind::Delimiter);
         match *self {
             DestructorName::Unresolved(ref ty) => ty.demangle(ctx, scope),
             DestructorName::Name(ref name) => name.demangle(ctx, scope),
@@ -6036,13 +6035,13 @@ impl<'subs> Demangle<'subs> for ExprPrimary {
         fn write_literal(ctx: &mut DemangleContext, start: usize, end: usize) {
             debug_assert!(start <= end);
             let start = if start < end && ctx.input[start] == b'n' {
-                ctx.push("-", CONFIG.colors.delimiter);
+                ctx.push("-", Kind::Delimiter);
                 start + 1
             } else {
                 start
             };
-            ctx.push("-", CONFIG.colors.delimiter);
-            ctx.push(&ctx.stream.inner()[start..end], CONFIG.colors.asm.component);
+            ctx.push("-", Kind::Delimiter);
+            ctx.push(&ctx.stream.inner()[start..end], Kind::Component);
         }
 
         match *self {
@@ -6052,12 +6051,12 @@ impl<'subs> Demangle<'subs> for ExprPrimary {
                 start,
                 end,
             ) => match &ctx.input[start..end] {
-                b"0" => ctx.push("false", CONFIG.colors.asm.primitive),
-                b"1" => ctx.push("true", CONFIG.colors.asm.primitive),
+                b"0" => ctx.push("false", Kind::Primitive),
+                b"1" => ctx.push("true", Kind::Primitive),
                 _ => {
-                    ctx.push("(", CONFIG.colors.brackets);
-                    ctx.push("bool", CONFIG.colors.asm.primitive);
-                    ctx.push(")", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
+                    ctx.push("bool", Kind::Primitive);
+                    ctx.push(")", Kind::Bracket);
                     write_literal(ctx, start, end)
                 }
             },
@@ -6065,7 +6064,7 @@ impl<'subs> Demangle<'subs> for ExprPrimary {
                 TypeHandle::Builtin(BuiltinType::Standard(StandardBuiltinType::Nullptr)),
                 _,
                 _,
-            ) => ctx.push("nullptr", CONFIG.colors.asm.primitive),
+            ) => ctx.push("nullptr", Kind::Primitive),
             ExprPrimary::Literal(
                 ref ty @ TypeHandle::Builtin(BuiltinType::Standard(StandardBuiltinType::Double)),
                 start,
@@ -6077,20 +6076,20 @@ impl<'subs> Demangle<'subs> for ExprPrimary {
                 end,
             ) => {
                 if ctx.show_expression_literal_types {
-                    ctx.push("(", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
                     ty.demangle(ctx, scope);
-                    ctx.push(")", CONFIG.colors.brackets);
+                    ctx.push(")", Kind::Bracket);
                 }
                 let start = if start < end && ctx.input[start] == b'n' {
-                    ctx.push("-[", CONFIG.colors.brackets);
+                    ctx.push("-[", Kind::Bracket);
                     start + 1
                 } else {
-                    ctx.push("[", CONFIG.colors.brackets);
+                    ctx.push("[", Kind::Bracket);
                     start
                 };
 
-                ctx.push(&ctx.stream.inner()[start..end], CONFIG.colors.asm.component);
-                ctx.push("]", CONFIG.colors.brackets);
+                ctx.push(&ctx.stream.inner()[start..end], Kind::Component);
+                ctx.push("]", Kind::Bracket);
             }
             ExprPrimary::Literal(
                 TypeHandle::Builtin(BuiltinType::Standard(StandardBuiltinType::Int)),
@@ -6099,9 +6098,9 @@ impl<'subs> Demangle<'subs> for ExprPrimary {
             ) => write_literal(ctx, start, end),
             ExprPrimary::Literal(ref ty, start, end) => {
                 if ctx.show_expression_literal_types {
-                    ctx.push("(", CONFIG.colors.brackets);
+                    ctx.push("(", Kind::Bracket);
                     ty.demangle(ctx, scope);
-                    ctx.push(")", CONFIG.colors.brackets);
+                    ctx.push(")", Kind::Bracket);
                 }
                 write_literal(ctx, start, end)
             }
@@ -6138,16 +6137,16 @@ impl<'subs> Demangle<'subs> for Initializer {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.push("(", CONFIG.colors.brackets);
+        ctx.push("(", Kind::Bracket);
         let mut need_comma = false;
         for expr in &self.0 {
             if need_comma {
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
             }
             expr.demangle(ctx, scope);
             need_comma = true;
         }
-        ctx.push(")", CONFIG.colors.brackets);
+        ctx.push(")", Kind::Bracket);
     }
 }
 
@@ -6226,14 +6225,14 @@ impl<'subs> Demangle<'subs> for LocalName {
         match *self {
             LocalName::Relative(ref encoding, Some(ref name), _) => {
                 encoding.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
+                ctx.push("::", Kind::Delimiter);
                 name.demangle(ctx, scope)
             }
             LocalName::Relative(ref encoding, None, _) => {
                 // No name means that this is the symbol for a string literal.
                 encoding.demangle(ctx, scope);
-                ctx.push("::", CONFIG.colors.delimiter);
-                ctx.push("string literal", CONFIG.colors.asm.primitive);
+                ctx.push("::", Kind::Delimiter);
+                ctx.push("string literal", Kind::Primitive);
             }
             LocalName::Default(ref encoding, _, _) => encoding.demangle(ctx, scope),
         }
@@ -6345,14 +6344,14 @@ impl<'subs> Demangle<'subs> for ClosureTypeName {
         ctx: &'ctx mut DemangleContext<'subs>,
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
-        ctx.push("{{", CONFIG.colors.brackets);
-        ctx.push("lambda", CONFIG.colors.asm.primitive);
-        ctx.push("(", CONFIG.colors.brackets);
+        ctx.push("{{", Kind::Bracket);
+        ctx.push("lambda", Kind::Primitive);
+        ctx.push("(", Kind::Bracket);
         self.0.demangle(ctx, scope);
 
-        ctx.push(")#", CONFIG.colors.brackets);
-        ctx.push_owned(self.1.map_or(1, |n| n + 2).to_string(), CONFIG.colors.asm.immediate);
-        ctx.push("}}", CONFIG.colors.brackets);
+        ctx.push(")#", Kind::Bracket);
+        ctx.push_owned(self.1.map_or(1, |n| n + 2).to_string(), Kind::Immediate);
+        ctx.push("}}", Kind::Bracket);
     }
 }
 
@@ -6404,7 +6403,7 @@ impl LambdaSig {
         let mut need_comma = false;
         for ty in &self.0 {
             if need_comma {
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
             }
             ty.demangle(ctx, scope);
             need_comma = true;
@@ -6588,12 +6587,12 @@ impl<'subs> DemangleAsLeaf<'subs> for WellKnownComponent {
             WellKnownComponent::Std => {
                 panic!("should never treat `WellKnownComponent::Std` as a leaf name")
             }
-            WellKnownComponent::StdAllocator => ctx.push("allocator", CONFIG.colors.asm.primitive),
-            WellKnownComponent::StdString1 => ctx.push("basic_string", CONFIG.colors.asm.primitive),
-            WellKnownComponent::StdString2 => ctx.push("string", CONFIG.colors.asm.primitive),
-            WellKnownComponent::StdIstream => ctx.push("basic_istream", CONFIG.colors.asm.primitive),
-            WellKnownComponent::StdOstream => ctx.push("ostream", CONFIG.colors.asm.primitive),
-            WellKnownComponent::StdIostream => ctx.push("basic_iostream", CONFIG.colors.asm.primitive),
+            WellKnownComponent::StdAllocator => ctx.push("allocator", Kind::Primitive),
+            WellKnownComponent::StdString1 => ctx.push("basic_string", Kind::Primitive),
+            WellKnownComponent::StdString2 => ctx.push("string", Kind::Primitive),
+            WellKnownComponent::StdIstream => ctx.push("basic_istream", Kind::Primitive),
+            WellKnownComponent::StdOstream => ctx.push("ostream", Kind::Primitive),
+            WellKnownComponent::StdIostream => ctx.push("basic_iostream", Kind::Primitive),
         }
     }
 }
@@ -6830,105 +6829,105 @@ impl<'subs> Demangle<'subs> for SpecialName {
     ) {
         match *self {
             SpecialName::VirtualTable(ref ty) => {
-                ctx.push("{{", CONFIG.colors.brackets);
-                ctx.push("vtable", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
+                ctx.push("vtable", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
 
                 ty.demangle(ctx, scope);
 
-                ctx.push(")}}", CONFIG.colors.brackets);
+                ctx.push(")}}", Kind::Bracket);
             }
             SpecialName::Vtt(ref ty) => {
-                ctx.push("{{", CONFIG.colors.brackets);
-                ctx.push("vtt", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
+                ctx.push("vtt", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
 
                 ty.demangle(ctx, scope);
-                ctx.push(")}}", CONFIG.colors.brackets);
+                ctx.push(")}}", Kind::Bracket);
             }
             SpecialName::Typeinfo(ref ty) => {
-                ctx.push("typeinfo", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("typeinfo", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 ty.demangle(ctx, scope)
             }
             SpecialName::TypeinfoName(ref ty) => {
-                ctx.push("typeinfo name", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("typeinfo name", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 ty.demangle(ctx, scope)
             }
             SpecialName::VirtualOverrideThunk(ref offset, ref encoding) => {
-                ctx.push("{{", CONFIG.colors.brackets);
-                ctx.push("virtual override thunk", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
+                ctx.push("virtual override thunk", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
 
                 offset.demangle(ctx, scope);
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
                 encoding.demangle(ctx, scope);
-                ctx.push(")}}", CONFIG.colors.brackets);
+                ctx.push(")}}", Kind::Bracket);
             }
             SpecialName::VirtualOverrideThunkCovariant(
                 ref this_offset,
                 ref result_offset,
                 ref encoding,
             ) => {
-                ctx.push("{{", CONFIG.colors.brackets);
-                ctx.push("virtual override thunk", CONFIG.colors.asm.primitive);
-                ctx.push("(", CONFIG.colors.brackets);
+                ctx.push("{{", Kind::Bracket);
+                ctx.push("virtual override thunk", Kind::Primitive);
+                ctx.push("(", Kind::Bracket);
 
                 this_offset.demangle(ctx, scope);
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
                 result_offset.demangle(ctx, scope);
-                ctx.push(", ", CONFIG.colors.delimiter);
+                ctx.push(", ", Kind::Delimiter);
                 encoding.demangle(ctx, scope);
-                ctx.push(")}}", CONFIG.colors.brackets);
+                ctx.push(")}}", Kind::Bracket);
             }
             SpecialName::Guard(ref name) => {
-                ctx.push("guard variable", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("guard variable", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 name.demangle(ctx, scope)
             }
             SpecialName::GuardTemporary(ref name, n) => {
-                ctx.push("reference temporary", CONFIG.colors.asm.primitive);
-                ctx.push(" #", CONFIG.colors.brackets);
-                ctx.push_owned(n.to_string(), CONFIG.colors.asm.immediate);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("reference temporary", Kind::Primitive);
+                ctx.push(" #", Kind::Bracket);
+                ctx.push_owned(n.to_string(), Kind::Immediate);
+                ctx.push(" for ", Kind::Bracket);
                 name.demangle(ctx, scope)
             }
             SpecialName::ConstructionVtable(ref ty1, _, ref ty2) => {
-                ctx.push("construction vtable", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("construction vtable", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
 
                 ty1.demangle(ctx, scope);
-                ctx.push("-in-", CONFIG.colors.brackets);
+                ctx.push("-in-", Kind::Bracket);
                 ty2.demangle(ctx, scope)
             }
             SpecialName::TypeinfoFunction(ref ty) => {
-                ctx.push("typeinfo fn", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("typeinfo fn", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 ty.demangle(ctx, scope)
             }
             SpecialName::TlsInit(ref name) => {
-                ctx.push("TLS init function", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("TLS init function", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 name.demangle(ctx, scope)
             }
             SpecialName::TlsWrapper(ref name) => {
-                ctx.push("TLS wrapper function", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("TLS wrapper function", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 name.demangle(ctx, scope)
             }
             SpecialName::TransactionClone(ref encoding) => {
-                ctx.push("transaction clone", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("transaction clone", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 encoding.demangle(ctx, scope)
             }
             SpecialName::NonTransactionClone(ref encoding) => {
-                ctx.push("non-transaction clone", CONFIG.colors.asm.primitive);
-                ctx.push(" for ", CONFIG.colors.brackets);
+                ctx.push("non-transaction clone", Kind::Primitive);
+                ctx.push(" for ", Kind::Bracket);
                 encoding.demangle(ctx, scope)
             }
             SpecialName::JavaResource(ref names) => {
-                ctx.push("java resource ", CONFIG.colors.asm.primitive);
+                ctx.push("java resource ", Kind::Primitive);
                 for name in names {
                     name.demangle(ctx, scope);
                 }
@@ -6999,15 +6998,15 @@ impl<'subs> Demangle<'subs> for ResourceName {
                 // Skip past the '$'
                 i += 1;
                 match ctx.input[i] {
-                    b'S' => ctx.push("/", CONFIG.colors.comment),
-                    b'_' => ctx.push(".", CONFIG.colors.comment),
-                    b'$' => ctx.push("$", CONFIG.colors.comment),
+                    b'S' => ctx.push("/", Kind::Comment),
+                    b'_' => ctx.push(".", Kind::Comment),
+                    b'$' => ctx.push("$", Kind::Comment),
                     _ => {
                         // Fall through
                     }
                 }
             } else {
-                ctx.push_owned(format!("{}", ch as char), CONFIG.colors.comment)
+                ctx.push_owned(format!("{}", ch as char), Kind::Comment)
             }
             i += 1;
         }
@@ -7061,12 +7060,12 @@ impl<'subs> Demangle<'subs> for SubobjectExpr {
         scope: Option<ArgScopeStack<'prev, 'subs>>,
     ) {
         self.expr.demangle(ctx, scope);
-        ctx.push(".", CONFIG.colors.comment);
-        ctx.push("<", CONFIG.colors.asm.label);
+        ctx.push(".", Kind::Comment);
+        ctx.push("<", Kind::Label);
         self.ty.demangle(ctx, scope);
-        ctx.push(" at offset ", CONFIG.colors.brackets);
-        ctx.push_owned(self.offset.to_string(), CONFIG.colors.asm.immediate);
-        ctx.push(">", CONFIG.colors.asm.label);
+        ctx.push(" at offset ", Kind::Bracket);
+        ctx.push_owned(self.offset.to_string(), Kind::Immediate);
+        ctx.push(">", Kind::Label);
     }
 }
 
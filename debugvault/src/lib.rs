@@ -1,13 +1,23 @@
-use binformat::RawSymbol;
+use binformat::{Indirection, RawSymbol};
+use config::CONFIG;
 use demangler::TokenStream;
+pub use demangler::FoldGenerics;
 use dwarf::Dwarf;
+use object::Object;
 use processor_shared::{AddressMap, Addressed};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 use std::fmt;
-use tokenizing::Token;
+use tokenizing::{Kind, Token};
+use types::Prototype;
+use variables::Scope;
 
+pub mod fuzzy;
 pub mod prefix;
+pub mod types;
+pub mod variables;
+mod debuglink;
 mod demangler;
 mod dwarf;
 mod error;
@@ -33,11 +43,27 @@ pub struct FileAttr {
     pub column_end: usize,
 }
 
+/// Whether symbols are shown by their mangled name, see [`show_mangled`].
+static SHOW_MANGLED: AtomicBool = AtomicBool::new(false);
+
+/// Show all symbols by their name as found in the binary instead of their demangled name.
+pub fn show_mangled(mangled: bool) {
+    SHOW_MANGLED.store(mangled, Ordering::Relaxed);
+}
+
+pub fn showing_mangled() -> bool {
+    SHOW_MANGLED.load(Ordering::Relaxed)
+}
+
 pub struct Symbol {
     name: TokenStream,
+    /// Name as found in the binary, if it was demangled.
+    mangled: Option<TokenStream>,
     name_as_str: Arc<str>,
     module: Option<String>,
     is_intrinsics: bool,
+    /// Set for stubs and slots an imported symbol is reached through.
+    indirection: Option<Indirection>,
 }
 
 fn is_name_an_intrinsic(name: &str) -> bool {
@@ -64,17 +90,43 @@ impl Default for Symbol {
     fn default() -> Self {
         Self {
             name: TokenStream::new(""),
+            mangled: None,
             name_as_str: Arc::from(""),
             module: None,
             is_intrinsics: false,
+            indirection: None,
         }
     }
 }
 
 impl Symbol {
+    /// A symbol named by the user, colored as such.
+    pub fn user_defined(name: &str) -> Self {
+        Self {
+            name: TokenStream::with_kind(name, CONFIG.colors.role_kind("user")),
+            mangled: None,
+            name_as_str: Arc::from(name),
+            module: None,
+            is_intrinsics: false,
+            indirection: None,
+        }
+    }
+
+    /// Tokens of the demangled name, or of the mangled name if they're shown.
     #[inline]
     pub fn name(&self) -> &[Token] {
-        self.name.tokens()
+        match self.mangled {
+            Some(ref mangled) if showing_mangled() => mangled.tokens(),
+            _ => self.name.tokens(),
+        }
+    }
+
+    /// Name with generic arguments folded, as monomorphized names can get very long.
+    pub fn display(&self, fold: FoldGenerics) -> Vec<Token> {
+        match self.mangled {
+            Some(ref mangled) if showing_mangled() => mangled.tokens().to_vec(),
+            _ => self.name.folded(fold),
+        }
     }
 
     #[inline]
@@ -87,6 +139,14 @@ impl Symbol {
         &self.name_as_str
     }
 
+    /// Name as found in the binary, which is how other binaries refer to it.
+    pub fn raw_name(&self) -> String {
+        match self.mangled {
+            Some(ref mangled) => String::from_iter(mangled.tokens().iter().map(|t| &t.text[..])),
+            None => self.as_str().to_string(),
+        }
+    }
+
     /// Is the function a unnamed compiler generated artifact.
     pub fn intrinsic(&self) -> bool {
         self.is_intrinsics
@@ -95,6 +155,17 @@ impl Symbol {
     pub fn imported(&self) -> bool {
         self.module.is_some()
     }
+
+    /// Stub in the PLT jumping to an imported function, which the loader binds lazily.
+    pub fn plt_stub(&self) -> bool {
+        self.indirection == Some(Indirection::Plt)
+    }
+
+    /// Stub or slot an imported symbol is reached through, if that's what this symbol is.
+    #[inline]
+    pub fn indirection(&self) -> Option<Indirection> {
+        self.indirection
+    }
 }
 
 impl fmt::Debug for Symbol {
@@ -120,30 +191,46 @@ pub struct Index {
     /// The addresses are sorted.
     pub file_attrs: AddressMap<FileAttr>,
 
-    /// Efficient string match searcher.
-    pub prefixes: prefix::PrefixMatcher,
+    /// Mapping from addresses starting at the header base to the prototype of the function
+    /// there, as declared in the debug info. The addresses are sorted.
+    pub prototypes: AddressMap<Prototype>,
+
+    /// Mapping from addresses starting at the header base to the parameters and locals of the
+    /// function there, as declared in the debug info. The addresses are sorted.
+    pub scopes: AddressMap<Scope>,
+
+    /// Efficient string match searcher, built by [`Index::complete`].
+    prefixes: RwLock<prefix::PrefixMatcher>,
+
+    /// Whether [`Index::complete`] finished.
+    completed: AtomicBool,
 
     /// Number of named compiler artifacts.
     named_len: usize,
+
+    /// Number of named PLT stubs, which are counted in `named_len` as well.
+    plt_stubs_len: usize,
 }
 
 impl Index {
     pub fn parse<'data>(
         obj: &object::File<'data>,
-        #[allow(unused_variables)]
         path: &Path,
         mut syms: AddressMap<RawSymbol<'data>>,
     ) -> Result<Self, Error> {
         let mut this = Self::default();
 
         let dwarf = match obj {
-            #[cfg(target_os = "macos")]
             object::File::MachO32(_) | object::File::MachO64(_) => macho_dwarf(obj, path),
-            _ => Dwarf::parse(obj),
+            _ => Dwarf::parse(obj, path),
         };
 
         match dwarf {
-            Ok(dwarf) => this.file_attrs.extend(dwarf.file_attrs),
+            Ok(dwarf) => {
+                this.file_attrs.extend(dwarf.file_attrs);
+                this.prototypes.extend(dwarf.prototypes);
+                this.scopes.extend(dwarf.scopes);
+            }
             Err(err) => log::complex!(
                 w "[dwarf::parse] ",
                 y format!("Failed to parse dwarf: {err:?}"),
@@ -170,17 +257,55 @@ impl Index {
             syms.extend(std::mem::take(&mut pdb.syms));
         }
 
+        // Stripped binaries might have their debug info stored in a separate file.
+        let debug_path = match obj.format() {
+            object::BinaryFormat::Elf => debuglink::find_debug_file(obj, path),
+            _ => None,
+        };
+
+        let debug_mmap = debug_path.as_ref().and_then(|debug_path| {
+            let file = std::fs::File::open(debug_path).ok()?;
+            unsafe { memmap2::Mmap::map(&file).ok() }
+        });
+
+        let debug_obj = debug_mmap.as_ref().and_then(|mmap| object::File::parse(&mmap[..]).ok());
+
+        // Shorten the symbols lifetime to that of the debug file.
+        let mut syms: AddressMap<RawSymbol> = syms;
+        if let (Some(debug_path), Some(debug_obj)) = (&debug_path, &debug_obj) {
+            log::complex!(
+                w "[index::parse] loading debug info from ",
+                g format!("{debug_path:?}"),
+                w ".",
+            );
+
+            if let Ok(dwarf) = Dwarf::parse(debug_obj, debug_path) {
+                this.file_attrs.extend(dwarf.file_attrs);
+                this.prototypes.extend(dwarf.prototypes);
+                this.scopes.extend(dwarf.scopes);
+            }
+
+            let debug_syms = debuglink::symbols(debug_obj, &syms);
+            syms.extend(debug_syms);
+        }
+
         log::PROGRESS.set("Parsing symbols.", syms.len());
         parallel_compute(syms.mapping, &mut this.syms, |Addressed { addr, item }| {
-            let demangled = demangler::parse(item.name);
+            let mut demangled = demangler::parse(item.name);
+            if let Some(indirection) = item.indirection {
+                demangled.push(indirection.suffix(), Kind::Comment);
+            }
             let is_intrinsics = is_name_an_intrinsic(item.name);
             let name_as_str = String::from_iter(demangled.tokens().iter().map(|t| &t.text[..]));
+            let mangled = (name_as_str != item.name).then(|| TokenStream::simple(item.name));
             let name_as_str = Arc::from(name_as_str);
             let symbol = Symbol {
                 name_as_str,
                 name: demangled,
+                mangled,
                 module: item.module.map(|x| x.to_string()),
                 is_intrinsics,
+                indirection: item.indirection,
             };
 
             log::PROGRESS.step();
@@ -191,7 +316,6 @@ impl Index {
         });
 
         this.sort_and_validate();
-        this.build_prefix_tree();
 
         log::complex!(
             w "[index::parse] found ",
@@ -203,9 +327,6 @@ impl Index {
     }
 
     fn sort_and_validate(&mut self) {
-        // Only keep one symbol per address.
-        self.syms.dedup_by_key(|func| func.addr);
-
         // Only keep valid symbols.
         self.syms.retain(|Addressed { addr, item: func }| {
             if *addr == 0 {
@@ -219,33 +340,85 @@ impl Index {
             true
         });
 
+        // Keep functions sorted so it can be binary searched. The sort is stable, such that the
+        // symbol found first is the one kept when there are several at an address.
+        self.syms.sort();
+        self.syms.dedup_by_key(|func| func.addr);
+
         // Count the number of function's that aren't compiler intrinsics.
         self.named_len = self.syms.iter().filter(|func| !func.item.intrinsic()).count();
-
-        // Keep functions sorted so it can be binary searched.
-        self.syms.sort_unstable();
+        self.plt_stubs_len = self
+            .syms
+            .iter()
+            .filter(|func| !func.item.intrinsic() && func.item.plt_stub())
+            .count();
 
         // Keep file attrs sorted so it can be binary searched.
         self.file_attrs.sort_unstable();
+
+        // Functions can be declared in multiple units, only keep one prototype per address.
+        self.prototypes.sort_unstable();
+        self.prototypes.dedup_by_key(|proto| proto.addr);
+
+        // The same goes for the variables of functions.
+        self.scopes.sort_unstable();
+        self.scopes.dedup_by_key(|scope| scope.addr);
     }
 
-    fn build_prefix_tree(&mut self) {
+    /// Finish indexing by building the lookup structures that aren't required for displaying
+    /// symbols. This is slow for large symbol tables, so it's meant to be run in the
+    /// background after [`Index::parse`], whilst the index is already in use.
+    pub fn complete(&self) {
+        if self.is_complete() {
+            return;
+        }
+
+        debug_assert!(
+            self.syms.windows(2).all(|w| w[0].addr < w[1].addr),
+            "symbols must be sorted and unique before completing the index"
+        );
+
         log::PROGRESS.set("Building prefix tree", self.syms.len() + 1);
 
         // Radix-prefix tree for fast lookups.
+        let mut prefixes = prefix::PrefixMatcher::default();
         for Addressed { item: func, .. } in self.syms.iter() {
-            self.prefixes.insert(func);
+            prefixes.insert(func);
             log::PROGRESS.step();
         }
 
-        self.prefixes.reorder();
+        prefixes.reorder();
         log::PROGRESS.step();
+
+        *self.prefixes.write().unwrap() = prefixes;
+        self.completed.store(true, Ordering::Release);
+
+        log::complex!(
+            w "[index::complete] indexed ",
+            g self.syms.len().to_string(),
+            w " symbols."
+        );
+    }
+
+    /// Whether all lookup structures are built, see [`Index::complete`].
+    pub fn is_complete(&self) -> bool {
+        self.completed.load(Ordering::Acquire)
+    }
+
+    /// Prefix searcher over all symbols, empty until the index is complete.
+    pub fn prefixes(&self) -> RwLockReadGuard<'_, prefix::PrefixMatcher> {
+        self.prefixes.read().unwrap()
     }
 
     pub fn named_funcs_count(&self) -> usize {
         self.named_len
     }
 
+    /// Number of named stubs in the PLT, see [`Symbol::plt_stub`].
+    pub fn plt_stubs_count(&self) -> usize {
+        self.plt_stubs_len
+    }
+
     pub fn functions(&self) -> impl Iterator<Item = &Addressed<Arc<Symbol>>> {
         self.syms.iter()
     }
@@ -257,6 +430,26 @@ impl Index {
         }
     }
 
+    /// Prototype of the function at `addr`, as declared in the debug info.
+    pub fn get_prototype(&self, addr: usize) -> Option<&Prototype> {
+        match self.prototypes.search(addr) {
+            Ok(idx) => Some(&self.prototypes[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    /// Variables of the function containing `addr`, as declared in the debug info.
+    pub fn get_scope(&self, addr: usize) -> Option<&Scope> {
+        let idx = match self.scopes.search(addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let scope = &self.scopes[idx].item;
+        (addr < scope.end).then_some(scope)
+    }
+
     pub fn get_sym_by_addr(&self, addr: usize) -> Option<Arc<Symbol>> {
         match self.syms.search(addr) {
             Ok(idx) => Some(self.syms[idx].item.clone()),
@@ -264,10 +457,86 @@ impl Index {
         }
     }
 
+    /// Symbol at or before `addr` together with it's address.
+    pub fn get_sym_containing(&self, addr: usize) -> Option<(usize, Arc<Symbol>)> {
+        let idx = match self.syms.search(addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let sym = &self.syms[idx];
+        Some((sym.addr, sym.item.clone()))
+    }
+
+    /// Source line of the instruction at or before `addr`.
+    pub fn get_file_containing(&self, addr: usize) -> Option<&FileAttr> {
+        let idx = match self.file_attrs.search(addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        Some(&self.file_attrs[idx].item)
+    }
+
     pub fn get_func_by_name(&self, name: &str) -> Option<usize> {
         self.syms.iter().find(|func| func.item.as_str() == name).map(|func| func.addr)
     }
 
+    /// Add symbols for strings found whilst analysing the binary, skipping addresses that
+    /// already have a symbol. Has to happen before [`Index::complete`].
+    pub fn insert_strings(&mut self, syms: Vec<(usize, String)>) {
+        let syms: Vec<_> = syms
+            .into_iter()
+            .filter(|(addr, _)| self.syms.search(*addr).is_err())
+            .collect();
+
+        for (addr, name) in syms {
+            self.syms.push(Addressed {
+                addr,
+                item: Arc::new(Symbol {
+                    name: TokenStream::with_kind(&name, CONFIG.colors.role_kind("string")),
+                    mangled: None,
+                    is_intrinsics: is_name_an_intrinsic(&name),
+                    name_as_str: Arc::from(name),
+                    module: None,
+                    indirection: None,
+                }),
+            });
+        }
+
+        self.sort_and_validate();
+    }
+
+    /// Name functions found by analysis, such as by signatures of library functions or by being
+    /// referenced from the headers. Addresses that already have a symbol keep it.
+    pub fn insert_identified(&mut self, syms: Vec<(usize, String)>) {
+        let syms: Vec<_> = syms
+            .into_iter()
+            .filter(|(addr, _)| self.syms.search(*addr).is_err())
+            .collect();
+
+        for (addr, name) in syms {
+            let demangled = demangler::parse(&name);
+            let name_as_str = String::from_iter(demangled.tokens().iter().map(|t| &t.text[..]));
+            let mangled = (name_as_str != name).then(|| TokenStream::simple(&name));
+            self.syms.push(Addressed {
+                addr,
+                item: Arc::new(Symbol {
+                    name: demangled,
+                    mangled,
+                    is_intrinsics: is_name_an_intrinsic(&name),
+                    name_as_str: Arc::from(name_as_str),
+                    module: None,
+                    indirection: None,
+                }),
+            });
+        }
+
+        self.sort_and_validate();
+    }
+
     /// Only used for tests.
     #[doc(hidden)]
     pub fn insert_func(&mut self, addr: usize, name: &str) {
@@ -275,30 +544,28 @@ impl Index {
             addr,
             item: Arc::new(Symbol {
                 name: TokenStream::simple(name),
+                mangled: None,
                 name_as_str: Arc::from(name),
                 module: None,
                 is_intrinsics: false,
+                indirection: None,
             }),
         })
     }
 }
 
-#[cfg(target_os = "macos")]
+/// Debug info of a Mach-O, merged with that of the dSYM bundle next to it. Bundles are generated
+/// with dsymutil on macOS if missing, elsewhere they have to be copied along with the binary.
 pub fn macho_dwarf(obj: &object::File, path: &Path) -> Result<Dwarf, dwarf::Error> {
-    let mut dwarf = Dwarf::parse(obj)?;
+    let mut dwarf = Dwarf::parse(obj, path)?;
 
-    let ext = if let Some(exist_ext) = path.extension().and_then(|ext| ext.to_str()) {
-        exist_ext.to_string() + ".dSYM"
-    } else {
-        "dSYM".to_string()
+    let dsym_path = match debuglink::dsym_path(path) {
+        Some(dsym_path) => dsym_path,
+        None => return Ok(dwarf),
     };
 
-    let opt_dsym = path
-        .with_extension(ext)
-        .join("Contents/Resources/DWARF")
-        .join(path.file_name().unwrap());
-
-    if !opt_dsym.is_file() {
+    #[cfg(target_os = "macos")]
+    if !dsym_path.is_file() {
         #[cfg(target_arch = "x86_64")]
         let dsymutil_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("bin/dsymutil_x86_64");
         #[cfg(target_arch = "aarch64")]
@@ -324,9 +591,28 @@ pub fn macho_dwarf(obj: &object::File, path: &Path) -> Result<Dwarf, dwarf::Erro
         }
     }
 
-    let dsym_dwarf = Dwarf::load(&opt_dsym)?;
-    dwarf.merge(dsym_dwarf);
+    if !dsym_path.is_file() {
+        return Ok(dwarf);
+    }
+
+    let file = std::fs::File::open(&dsym_path)?;
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    let dsym_obj = object::File::parse(&*mmap)?;
+
+    // A bundle left over from an earlier build describes different code.
+    if let (Ok(Some(uuid)), Ok(Some(dsym_uuid))) = (obj.mach_uuid(), dsym_obj.mach_uuid()) {
+        if uuid != dsym_uuid {
+            log::complex!(
+                w "[macho::dwarf] ",
+                y "Ignoring ",
+                g format!("{dsym_path:?}"),
+                y " as it's UUID doesn't match.",
+            );
+            return Ok(dwarf);
+        }
+    }
 
+    dwarf.merge(Dwarf::parse(&dsym_obj, &dsym_path)?);
     Ok(dwarf)
 }
 
@@ -371,3 +657,24 @@ where
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_symbol_per_address() {
+        let mut index = Index::default();
+        index.insert_func(0x30, "first");
+        index.insert_func(0x10, "start");
+        index.insert_func(0x30, "second");
+        index.insert_func(0x20, "");
+        index.sort_and_validate();
+
+        let syms: Vec<_> = index.syms.iter().map(|sym| (sym.addr, sym.item.as_str())).collect();
+        assert_eq!(syms, [(0x10, "start"), (0x30, "first")]);
+
+        index.complete();
+        assert!(index.is_complete());
+    }
+}
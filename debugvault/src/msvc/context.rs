@@ -1,7 +1,7 @@
 use super::{Literal, Modifiers, NestedPath, Scope, Type};
 
 use crate::TokenStream;
-use tokenizing::Color32;
+use tokenizing::Kind;
 
 /// Max recursion depth
 const MAX_DEPTH: usize = 256;
@@ -100,9 +100,9 @@ impl Context<'_> {
     }
 
     /// Pushes a [`Literal`] to the [`TokenStream`], resolving any indexing within a literal.
-    pub fn push_literal(&mut self, literal: &Literal, color: Color32) {
+    pub fn push_literal(&mut self, literal: &Literal, kind: Kind) {
         let literal = &self.stream.inner()[literal.start..literal.end];
-        self.stream.push(literal, color);
+        self.stream.push(literal, kind);
     }
 
     /// Create a reference to the underlying pinned string that holds the mangled symbol.
@@ -67,8 +67,7 @@ mod tests;
 
 use bitflags::bitflags;
 use context::{Backrefs, Context};
-use tokenizing::colors;
-use config::CONFIG;
+use tokenizing::Kind;
 
 #[cfg(test)]
 const PRINTING_SCOPE: bool = true;
@@ -424,141 +423,141 @@ impl<'a> PositionalDemangle<'a> for Type {
     fn demangle_pre(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         match self {
             Type::Unit => {}
-            Type::Nullptr => ctx.stream.push("std::nullptr_t", CONFIG.colors.asm.primitive),
+            Type::Nullptr => ctx.stream.push("std::nullptr_t", Kind::Primitive),
             Type::Void(modi) => {
-                ctx.stream.push("void", CONFIG.colors.asm.primitive);
+                ctx.stream.push("void", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Char(modi) => {
-                ctx.stream.push("char", CONFIG.colors.asm.primitive);
+                ctx.stream.push("char", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Char8(modi) => {
-                ctx.stream.push("char8_t", CONFIG.colors.asm.primitive);
+                ctx.stream.push("char8_t", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Char16(modi) => {
-                ctx.stream.push("char16_t", CONFIG.colors.asm.primitive);
+                ctx.stream.push("char16_t", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Char32(modi) => {
-                ctx.stream.push("char32_t", CONFIG.colors.asm.primitive);
+                ctx.stream.push("char32_t", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::IChar(modi) => {
-                ctx.stream.push("signed char", CONFIG.colors.asm.primitive);
+                ctx.stream.push("signed char", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UChar(modi) => {
-                ctx.stream.push("unsigned char", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned char", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::WChar(modi) => {
-                ctx.stream.push("wchar_t", CONFIG.colors.asm.primitive);
+                ctx.stream.push("wchar_t", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::IShort(modi) => {
-                ctx.stream.push("short", CONFIG.colors.asm.primitive);
+                ctx.stream.push("short", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UShort(modi) => {
-                ctx.stream.push("unsigned short", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned short", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int(modi) => {
-                ctx.stream.push("int", CONFIG.colors.asm.primitive);
+                ctx.stream.push("int", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UInt(modi) => {
-                ctx.stream.push("unsigned int", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned int", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Float(modi) => {
-                ctx.stream.push("float", CONFIG.colors.asm.primitive);
+                ctx.stream.push("float", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Double(modi) => {
-                ctx.stream.push("double", CONFIG.colors.asm.primitive);
+                ctx.stream.push("double", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::LDouble(modi) => {
-                ctx.stream.push("long double", CONFIG.colors.asm.primitive);
+                ctx.stream.push("long double", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Long(modi) => {
-                ctx.stream.push("long", CONFIG.colors.asm.primitive);
+                ctx.stream.push("long", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::ULong(modi) => {
-                ctx.stream.push("unsigned long", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned long", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::W64(modi, tipe) => {
-                ctx.stream.push("__w64 ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__w64 ", Kind::Primitive);
                 tipe.demangle(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int8(modi) => {
-                ctx.stream.push("__int8", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__int8", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UInt8(modi) => {
-                ctx.stream.push("unsigned __int8", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned __int8", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int16(modi) => {
-                ctx.stream.push("__int16", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__int16", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UInt16(modi) => {
-                ctx.stream.push("unsigned __int16", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned __int16", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int32(modi) => {
-                ctx.stream.push("__int32", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__int32", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UInt32(modi) => {
-                ctx.stream.push("unsigned __int32", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned __int32", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int64(modi) => {
-                ctx.stream.push("__int64", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__int64", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::UInt64(modi) => {
-                ctx.stream.push("unsigned __int64", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned __int64", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Int128(modi) => {
-                ctx.stream.push("__int128", CONFIG.colors.asm.primitive);
+                ctx.stream.push("__int128", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Uint128(modi) => {
-                ctx.stream.push("unsigned __int128", CONFIG.colors.asm.primitive);
+                ctx.stream.push("unsigned __int128", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Bool(modi) => {
-                ctx.stream.push("bool", CONFIG.colors.asm.primitive);
+                ctx.stream.push("bool", Kind::Primitive);
                 modi.demangle(ctx, backrefs);
             }
             Type::Union(modi, name) => {
-                ctx.stream.push("union ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("union ", Kind::Primitive);
                 name.demangle(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
             }
             Type::Enum(modi, name) => {
-                ctx.stream.push("enum ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("enum ", Kind::Primitive);
                 name.demangle(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
             }
             Type::Struct(modi, name) => {
-                ctx.stream.push("struct ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("struct ", Kind::Primitive);
                 name.demangle(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
             }
             Type::Class(modi, name) => {
-                ctx.stream.push("class ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("class ", Kind::Primitive);
                 name.demangle(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
             }
@@ -571,7 +570,7 @@ impl<'a> PositionalDemangle<'a> for Type {
                 match &**tipe {
                     Type::Function(func) => {
                         func.return_type.demangle_pre(ctx, backrefs);
-                        ctx.stream.push("(", CONFIG.colors.brackets);
+                        ctx.stream.push("(", Kind::Bracket);
                         func.calling_conv.demangle(ctx, backrefs);
                     }
                     Type::MemberFunction(func) => {
@@ -582,20 +581,20 @@ impl<'a> PositionalDemangle<'a> for Type {
                     Type::MemberFunctionPtr(func) => {
                         func.storage_scope.demangle(ctx, backrefs);
                         func.return_type.demangle_pre(ctx, backrefs);
-                        ctx.stream.push("(", CONFIG.colors.brackets);
+                        ctx.stream.push("(", Kind::Bracket);
                         func.calling_conv.demangle(ctx, backrefs);
                     }
                     Type::Array(..) => {
                         tipe.demangle_pre(ctx, backrefs);
-                        ctx.stream.push(" (", CONFIG.colors.brackets);
+                        ctx.stream.push(" (", Kind::Bracket);
                     }
                     _ => tipe.demangle_pre(ctx, backrefs),
                 }
 
                 match self {
-                    Type::Ptr(..) => ctx.stream.push(" *", CONFIG.colors.asm.pointer),
-                    Type::Ref(..) => ctx.stream.push(" &", CONFIG.colors.asm.pointer),
-                    Type::RValueRef(..) => ctx.stream.push(" &&", CONFIG.colors.asm.pointer),
+                    Type::Ptr(..) => ctx.stream.push(" *", Kind::Pointer),
+                    Type::Ref(..) => ctx.stream.push(" &", Kind::Pointer),
+                    Type::RValueRef(..) => ctx.stream.push(" &&", Kind::Pointer),
                     _ => {}
                 }
 
@@ -604,42 +603,42 @@ impl<'a> PositionalDemangle<'a> for Type {
             Type::Function(func) => {
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
             }
             Type::MemberFunction(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
             }
             Type::MemberFunctionPtr(func) => {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
                 func.class_name.demangle(ctx, backrefs);
             }
             Type::Inherited(func) => {
-                ctx.stream.push("&", CONFIG.colors.asm.pointer);
+                ctx.stream.push("&", Kind::Pointer);
                 func.storage_scope.demangle(ctx, backrefs);
                 func.return_type.demangle_pre(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
                 func.class_name.demangle(ctx, backrefs);
                 func.params.demangle(ctx, backrefs);
             }
             Type::Constant(val) => {
-                ctx.stream.push_string(val.to_string(), CONFIG.colors.asm.component);
+                ctx.stream.push_string(val.to_string(), Kind::Component);
             }
             Type::TemplateParameterIdx(idx) => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("template-parameter", CONFIG.colors.asm.primitive);
-                ctx.stream.push("-", CONFIG.colors.delimiter);
-                ctx.stream.push_string(idx.to_string(), CONFIG.colors.asm.component);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("template-parameter", Kind::Primitive);
+                ctx.stream.push("-", Kind::Delimiter);
+                ctx.stream.push_string(idx.to_string(), Kind::Component);
+                ctx.stream.push("'", Kind::Bracket);
             }
             Type::Typedef(modi, name) => {
-                ctx.push_literal(name, CONFIG.colors.asm.component);
+                ctx.push_literal(name, Kind::Component);
                 modi.demangle(ctx, backrefs);
             }
             Type::Variable(Variable {
@@ -650,7 +649,7 @@ impl<'a> PositionalDemangle<'a> for Type {
                 storage.demangle(ctx, backrefs);
                 tipe.demangle_pre(ctx, backrefs);
                 modi.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
             }
             Type::Encoded(_) => {}
             Type::Array(array) => {
@@ -663,22 +662,22 @@ impl<'a> PositionalDemangle<'a> for Type {
                 quali.demangle(ctx, backrefs);
             }
             Type::VCallThunk(_, calling_conv) => {
-                ctx.stream.push("[: ", CONFIG.colors.brackets);
-                ctx.stream.push("thunk", CONFIG.colors.asm.primitive);
-                ctx.stream.push("]: ", CONFIG.colors.brackets);
+                ctx.stream.push("[: ", Kind::Bracket);
+                ctx.stream.push("thunk", Kind::Primitive);
+                ctx.stream.push("]: ", Kind::Bracket);
 
                 calling_conv.demangle(ctx, backrefs);
             }
             Type::Extern(tipe) => {
-                ctx.stream.push("extern ", CONFIG.colors.asm.pointer);
-                ctx.stream.push("\"", CONFIG.colors.brackets);
-                ctx.stream.push("C", CONFIG.colors.asm.component);
-                ctx.stream.push("\" ", CONFIG.colors.brackets);
+                ctx.stream.push("extern ", Kind::Pointer);
+                ctx.stream.push("\"", Kind::Bracket);
+                ctx.stream.push("C", Kind::Component);
+                ctx.stream.push("\" ", Kind::Bracket);
 
                 tipe.demangle_pre(ctx, backrefs);
             }
             Type::Variadic => {
-                ctx.stream.push("...", CONFIG.colors.asm.component);
+                ctx.stream.push("...", Kind::Component);
             }
         }
     }
@@ -687,10 +686,10 @@ impl<'a> PositionalDemangle<'a> for Type {
         match self {
             Type::Ptr(_, tipe) | Type::Ref(_, tipe) => {
                 match **tipe {
-                    Type::Function(..) => ctx.stream.push(")", CONFIG.colors.brackets),
-                    Type::MemberFunction(..) => ctx.stream.push(")", CONFIG.colors.brackets),
-                    Type::MemberFunctionPtr(..) => ctx.stream.push(")", CONFIG.colors.brackets),
-                    Type::Array(..) => ctx.stream.push(")", CONFIG.colors.brackets),
+                    Type::Function(..) => ctx.stream.push(")", Kind::Bracket),
+                    Type::MemberFunction(..) => ctx.stream.push(")", Kind::Bracket),
+                    Type::MemberFunctionPtr(..) => ctx.stream.push(")", Kind::Bracket),
+                    Type::Array(..) => ctx.stream.push(")", Kind::Bracket),
                     _ => {}
                 }
 
@@ -714,26 +713,26 @@ impl<'a> PositionalDemangle<'a> for Type {
             Type::Variable(Variable { tipe, .. }) => tipe.demangle_post(ctx, backrefs),
             Type::Array(array) => {
                 for len in array.lens.iter() {
-                    ctx.stream.push("[", CONFIG.colors.brackets);
-                    ctx.stream.push_string(len.to_string(), CONFIG.colors.asm.annotation);
-                    ctx.stream.push("]", CONFIG.colors.brackets);
+                    ctx.stream.push("[", Kind::Bracket);
+                    ctx.stream.push_string(len.to_string(), Kind::Annotation);
+                    ctx.stream.push("]", Kind::Bracket);
                 }
             }
             Type::VBTable(_, scope) | Type::VFTable(_, scope) => match scope {
                 Some(scope) if !scope.0.is_empty() => {
-                    ctx.stream.push("{for `", CONFIG.colors.brackets);
+                    ctx.stream.push("{for `", Kind::Bracket);
                     scope.demangle(ctx, backrefs);
-                    ctx.stream.push("'}", CONFIG.colors.brackets);
+                    ctx.stream.push("'}", Kind::Bracket);
                 }
                 None => {
-                    ctx.stream.push("{for ??}", CONFIG.colors.brackets);
+                    ctx.stream.push("{for ??}", Kind::Bracket);
                 }
                 _ => {}
             },
             Type::VCallThunk(offset, _) => {
-                ctx.stream.push("{{", CONFIG.colors.brackets);
-                ctx.stream.push_string(offset.to_string(), CONFIG.colors.asm.component);
-                ctx.stream.push(", {{flat}}}}", CONFIG.colors.brackets);
+                ctx.stream.push("{{", Kind::Bracket);
+                ctx.stream.push_string(offset.to_string(), Kind::Component);
+                ctx.stream.push(", {{flat}}}}", Kind::Bracket);
             }
             Type::Extern(tipe) => tipe.demangle_post(ctx, backrefs),
             Type::W64(_, tipe) => tipe.demangle_post(ctx, backrefs),
@@ -1082,7 +1081,7 @@ impl<'a> PositionalDemangle<'a> for FunctionReturnType {
     fn demangle_pre(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         self.0.demangle_pre(ctx, backrefs);
         if self.0 != Type::Unit {
-            ctx.stream.push(" ", colors::WHITE);
+            ctx.stream.push(" ", Kind::Plain);
         }
     }
 
@@ -1335,53 +1334,53 @@ impl<'a> Demangle<'a> for Intrinsics {
                 match ctx.scope.0.first() {
                     Some(path) => path.demangle(ctx, backrefs),
                     _ => {
-                        ctx.stream.push("`", CONFIG.colors.brackets);
-                        ctx.stream.push("unnamed constructor", CONFIG.colors.asm.primitive);
-                        ctx.stream.push("'", CONFIG.colors.brackets);
+                        ctx.stream.push("`", Kind::Bracket);
+                        ctx.stream.push("unnamed constructor", Kind::Primitive);
+                        ctx.stream.push("'", Kind::Bracket);
                     }
                 };
                 return;
             }
             Intrinsics::Dtor => {
-                ctx.stream.push("~", CONFIG.colors.asm.component);
+                ctx.stream.push("~", Kind::Component);
 
                 match ctx.scope.0.first() {
                     Some(path) => path.demangle(ctx, backrefs),
                     _ => {
-                        ctx.stream.push("`", CONFIG.colors.brackets);
-                        ctx.stream.push("unnamed destructor", CONFIG.colors.asm.primitive);
-                        ctx.stream.push("'", CONFIG.colors.brackets);
+                        ctx.stream.push("`", Kind::Bracket);
+                        ctx.stream.push("unnamed destructor", Kind::Primitive);
+                        ctx.stream.push("'", Kind::Bracket);
                     }
                 };
                 return;
             }
             Intrinsics::DynamicInitializer(ref tipe) => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("dynamic initializer for ", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("dynamic initializer for ", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
 
                 tipe.demangle(ctx, backrefs);
-                ctx.stream.push("''", CONFIG.colors.brackets);
+                ctx.stream.push("''", Kind::Bracket);
                 return;
             }
             Intrinsics::DynamicAtExitDtor(ref tipe) => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("dynamic atexit destructor for ", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("dynamic atexit destructor for ", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
 
                 tipe.demangle(ctx, backrefs);
-                ctx.stream.push("''", CONFIG.colors.brackets);
+                ctx.stream.push("''", Kind::Bracket);
                 return;
             }
             Intrinsics::SourceName(ref src) => {
-                ctx.push_literal(src, CONFIG.colors.asm.component);
+                ctx.push_literal(src, Kind::Component);
                 return;
             }
             Intrinsics::RTTITypeDescriptor(_, ref tipe) => {
                 tipe.demangle(ctx, backrefs);
-                ctx.stream.push(" `", CONFIG.colors.brackets);
-                ctx.stream.push("RTTI Type Descriptor", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push(" `", Kind::Bracket);
+                ctx.stream.push("RTTI Type Descriptor", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
                 return;
             }
             Intrinsics::RTTIBaseClassDescriptor {
@@ -1390,39 +1389,39 @@ impl<'a> Demangle<'a> for Intrinsics {
                 vbtable_off,
                 flags,
             } => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("RTTI Base Class Descriptor at ", CONFIG.colors.asm.primitive);
-                ctx.stream.push("(", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("RTTI Base Class Descriptor at ", Kind::Primitive);
+                ctx.stream.push("(", Kind::Bracket);
 
-                ctx.stream.push_string(nv_off.to_string(), CONFIG.colors.asm.annotation);
-                ctx.stream.push(", ", CONFIG.colors.brackets);
+                ctx.stream.push_string(nv_off.to_string(), Kind::Annotation);
+                ctx.stream.push(", ", Kind::Bracket);
 
-                ctx.stream.push_string(ptr_off.to_string(), CONFIG.colors.asm.annotation);
-                ctx.stream.push(", ", CONFIG.colors.brackets);
+                ctx.stream.push_string(ptr_off.to_string(), Kind::Annotation);
+                ctx.stream.push(", ", Kind::Bracket);
 
-                ctx.stream.push_string(vbtable_off.to_string(), CONFIG.colors.asm.annotation);
-                ctx.stream.push(", ", CONFIG.colors.brackets);
+                ctx.stream.push_string(vbtable_off.to_string(), Kind::Annotation);
+                ctx.stream.push(", ", Kind::Bracket);
 
-                ctx.stream.push_string(flags.to_string(), CONFIG.colors.asm.annotation);
-                ctx.stream.push(")'", CONFIG.colors.brackets);
+                ctx.stream.push_string(flags.to_string(), Kind::Annotation);
+                ctx.stream.push(")'", Kind::Bracket);
                 return;
             }
             Intrinsics::RTTIBaseClassArray => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("RTTI Base Class Array", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("RTTI Base Class Array", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
                 return;
             }
             Intrinsics::RTTIClassHierarchyDescriptor => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("RTTI Class Hierarchy Descriptor", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("RTTI Class Hierarchy Descriptor", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
                 return;
             }
             Intrinsics::RTTIClassCompleteObjectLocator => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("RTTI Complete Object Locator", CONFIG.colors.asm.primitive);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("RTTI Complete Object Locator", Kind::Primitive);
+                ctx.stream.push("'", Kind::Bracket);
                 return;
             }
             Intrinsics::TypeCast => "operatorcast",
@@ -1496,7 +1495,7 @@ impl<'a> Demangle<'a> for Intrinsics {
         };
 
         // TODO: handle each cases colors individually
-        ctx.stream.push(literal, CONFIG.colors.asm.primitive);
+        ctx.stream.push(literal, Kind::Primitive);
     }
 }
 
@@ -1564,7 +1563,7 @@ impl<'a> Demangle<'a> for Parameters {
         }
 
         for param in params {
-            ctx.stream.push(", ", CONFIG.colors.delimiter);
+            ctx.stream.push(", ", Kind::Delimiter);
             param.demangle(ctx, backrefs);
         }
     }
@@ -1590,9 +1589,9 @@ impl Parse for FunctionParameters {
 
 impl<'a> Demangle<'a> for FunctionParameters {
     fn demangle(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
-        ctx.stream.push("(", CONFIG.colors.brackets);
+        ctx.stream.push("(", Kind::Bracket);
         self.0.demangle(ctx, backrefs);
-        ctx.stream.push(")", CONFIG.colors.brackets);
+        ctx.stream.push(")", Kind::Bracket);
     }
 }
 
@@ -1643,7 +1642,7 @@ impl<'a> Demangle<'a> for CallingConv {
             CallingConv::Anonymous => return,
         };
 
-        ctx.stream.push(literal, CONFIG.colors.asm.annotation);
+        ctx.stream.push(literal, Kind::Annotation);
     }
 }
 
@@ -1666,7 +1665,7 @@ impl<'a> Demangle<'a> for StorageVariable {
                 StorageVariable::Global | StorageVariable::FunctionLocalStatic => return,
             };
 
-            ctx.stream.push(literal, CONFIG.colors.asm.annotation);
+            ctx.stream.push(literal, Kind::Annotation);
         }
     }
 }
@@ -1744,28 +1743,28 @@ impl Parse for StorageScope {
 
 impl<'a> Demangle<'a> for StorageScope {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        let color = CONFIG.colors.asm.annotation;
+        let kind = Kind::Annotation;
 
         if PRINTING_SCOPE {
             if self.contains(StorageScope::PUBLIC) {
-                ctx.stream.push("public: ", color);
+                ctx.stream.push("public: ", kind);
             }
 
             if self.contains(StorageScope::PRIVATE) {
-                ctx.stream.push("private: ", color);
+                ctx.stream.push("private: ", kind);
             }
 
             if self.contains(StorageScope::PROTECTED) {
-                ctx.stream.push("protected: ", color);
+                ctx.stream.push("protected: ", kind);
             }
         }
 
         if self.contains(StorageScope::STATIC) {
-            ctx.stream.push("static ", color);
+            ctx.stream.push("static ", kind);
         }
 
         if self.contains(StorageScope::VIRTUAL) {
-            ctx.stream.push("virtual ", color);
+            ctx.stream.push("virtual ", kind);
         }
     }
 }
@@ -1805,14 +1804,14 @@ impl Parse for Modifiers {
 
 impl<'a> Demangle<'a> for Modifiers {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        let color = CONFIG.colors.asm.annotation;
+        let kind = Kind::Annotation;
 
         if self.contains(Modifiers::CONST) {
-            ctx.stream.push(" const", color);
+            ctx.stream.push(" const", kind);
         }
 
         if self.contains(Modifiers::VOLATILE) {
-            ctx.stream.push(" volatile", color);
+            ctx.stream.push(" volatile", kind);
         }
     }
 }
@@ -1875,34 +1874,34 @@ impl Parse for Qualifiers {
 
 impl<'a> Demangle<'a> for Qualifiers {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        let color = CONFIG.colors.asm.annotation;
+        let kind = Kind::Annotation;
 
         if self.0.contains(Modifiers::CONST) {
-            ctx.stream.push("const ", color);
+            ctx.stream.push("const ", kind);
         }
 
         if self.0.contains(Modifiers::VOLATILE) {
-            ctx.stream.push("volatile ", color);
+            ctx.stream.push("volatile ", kind);
         }
 
         if self.0.contains(Modifiers::FAR) {
-            ctx.stream.push("__far ", color);
+            ctx.stream.push("__far ", kind);
         }
 
         if self.0.contains(Modifiers::UNALIGNED) {
-            ctx.stream.push("__unaligned ", color);
+            ctx.stream.push("__unaligned ", kind);
         }
 
         if self.0.contains(Modifiers::RESTRICT) {
-            ctx.stream.push("__restrict ", color);
+            ctx.stream.push("__restrict ", kind);
         }
 
         if self.0.contains(Modifiers::LVALUE) {
-            ctx.stream.push("& ", CONFIG.colors.asm.pointer);
+            ctx.stream.push("& ", Kind::Pointer);
         }
 
         if self.0.contains(Modifiers::RVALUE) {
-            ctx.stream.push("&& ", CONFIG.colors.asm.pointer);
+            ctx.stream.push("&& ", Kind::Pointer);
         }
     }
 }
@@ -1952,34 +1951,34 @@ impl Parse for PointeeQualifiers {
 
 impl<'a> Demangle<'a> for PointeeQualifiers {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        let color = CONFIG.colors.asm.annotation;
+        let kind = Kind::Annotation;
 
         if self.0.contains(Modifiers::CONST) {
-            ctx.stream.push(" const", color);
+            ctx.stream.push(" const", kind);
         }
 
         if self.0.contains(Modifiers::VOLATILE) {
-            ctx.stream.push(" volatile", color);
+            ctx.stream.push(" volatile", kind);
         }
 
         if self.0.contains(Modifiers::FAR) {
-            ctx.stream.push(" __far", color);
+            ctx.stream.push(" __far", kind);
         }
 
         if self.0.contains(Modifiers::UNALIGNED) {
-            ctx.stream.push(" __unaligned", color);
+            ctx.stream.push(" __unaligned", kind);
         }
 
         if self.0.contains(Modifiers::RESTRICT) {
-            ctx.stream.push(" __restrict", color);
+            ctx.stream.push(" __restrict", kind);
         }
 
         if self.0.contains(Modifiers::LVALUE) {
-            ctx.stream.push(" &", CONFIG.colors.asm.pointer);
+            ctx.stream.push(" &", Kind::Pointer);
         }
 
         if self.0.contains(Modifiers::RVALUE) {
-            ctx.stream.push(" &&", CONFIG.colors.asm.pointer);
+            ctx.stream.push(" &&", Kind::Pointer);
         }
     }
 }
@@ -2047,9 +2046,9 @@ impl Parse for MD5 {
 
 impl<'a> Demangle<'a> for MD5 {
     fn demangle(&'a self, ctx: &mut Context<'a>, _: &mut Backrefs) {
-        ctx.stream.push("??@", CONFIG.colors.brackets);
-        ctx.push_literal(&self.0, CONFIG.colors.asm.component);
-        ctx.stream.push("@", CONFIG.colors.brackets);
+        ctx.stream.push("??@", Kind::Bracket);
+        ctx.push_literal(&self.0, Kind::Component);
+        ctx.stream.push("@", Kind::Bracket);
     }
 }
 
@@ -2084,7 +2083,7 @@ impl<'a> Demangle<'a> for Scope {
             part.demangle(ctx, backrefs);
 
             if idx != self.0.len() - 1 {
-                ctx.stream.push("::", CONFIG.colors.delimiter);
+                ctx.stream.push("::", Kind::Delimiter);
             }
         }
     }
@@ -2116,7 +2115,7 @@ impl<'a> Demangle<'a> for Path {
         self.scope.demangle(ctx, backrefs);
 
         if !self.scope.0.is_empty() {
-            ctx.stream.push("::", CONFIG.colors.delimiter);
+            ctx.stream.push("::", Kind::Delimiter);
         }
 
         self.name.0.demangle(ctx, backrefs);
@@ -2210,25 +2209,25 @@ impl Parse for NestedPath {
 impl<'a> Demangle<'a> for NestedPath {
     fn demangle(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         match self {
-            NestedPath::Literal(ident) => ctx.push_literal(ident, CONFIG.colors.asm.component),
+            NestedPath::Literal(ident) => ctx.push_literal(ident, Kind::Component),
             NestedPath::Interface(ident) => {
-                ctx.stream.push("[", CONFIG.colors.brackets);
+                ctx.stream.push("[", Kind::Bracket);
                 ident.demangle(ctx, backrefs);
-                ctx.stream.push("]", CONFIG.colors.brackets);
+                ctx.stream.push("]", Kind::Bracket);
             }
             NestedPath::Template(template) => template.demangle(ctx, backrefs),
             NestedPath::Intrinsics(int) => int.demangle(ctx, backrefs),
             NestedPath::Symbol(inner) => inner.demangle(ctx, backrefs),
             NestedPath::Disambiguator(val) => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push_string(val.to_string(), CONFIG.colors.asm.component);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push_string(val.to_string(), Kind::Component);
+                ctx.stream.push("'", Kind::Bracket);
             }
             NestedPath::MD5(md5) => md5.demangle(ctx, backrefs),
             NestedPath::Anonymous => {
-                ctx.stream.push("`", CONFIG.colors.brackets);
-                ctx.stream.push("anonymous namespace", CONFIG.colors.asm.component);
-                ctx.stream.push("'", CONFIG.colors.brackets);
+                ctx.stream.push("`", Kind::Bracket);
+                ctx.stream.push("anonymous namespace", Kind::Component);
+                ctx.stream.push("'", Kind::Bracket);
             }
         }
     }
@@ -2350,9 +2349,9 @@ impl Parse for Template {
 impl<'a> Demangle<'a> for Template {
     fn demangle(&'a self, ctx: &mut Context<'a>, backrefs: &mut Backrefs) {
         self.name.0.demangle(ctx, backrefs);
-        ctx.stream.push("<", CONFIG.colors.asm.annotation);
+        ctx.stream.push("<", Kind::Annotation);
         self.params.demangle(ctx, backrefs);
-        ctx.stream.push(">", CONFIG.colors.asm.annotation);
+        ctx.stream.push(">", Kind::Annotation);
     }
 }
 
@@ -2436,10 +2435,10 @@ impl<'a> Demangle<'a> for Symbol {
             if let Type::MemberFunction(ref func) = self.tipe {
                 func.storage_scope.demangle(ctx, backrefs);
                 func.calling_conv.demangle(ctx, backrefs);
-                ctx.stream.push(" ", colors::WHITE);
+                ctx.stream.push(" ", Kind::Plain);
                 self.path.scope.demangle(ctx, backrefs);
-                ctx.stream.push("::", CONFIG.colors.delimiter);
-                ctx.stream.push("operator ", CONFIG.colors.asm.primitive);
+                ctx.stream.push("::", Kind::Delimiter);
+                ctx.stream.push("operator ", Kind::Primitive);
                 func.return_type.0.demangle(ctx, backrefs);
                 func.params.demangle(ctx, backrefs);
                 return;
@@ -2452,13 +2451,13 @@ impl<'a> Demangle<'a> for Symbol {
                 if let Type::MemberFunction(ref func) = self.tipe {
                     func.storage_scope.demangle(ctx, backrefs);
                     func.calling_conv.demangle(ctx, backrefs);
-                    ctx.stream.push(" ", colors::WHITE);
+                    ctx.stream.push(" ", Kind::Plain);
                     self.path.scope.demangle(ctx, backrefs);
-                    ctx.stream.push("::", CONFIG.colors.delimiter);
-                    ctx.stream.push("operator", CONFIG.colors.asm.primitive);
-                    ctx.stream.push("<", CONFIG.colors.asm.annotation);
+                    ctx.stream.push("::", Kind::Delimiter);
+                    ctx.stream.push("operator", Kind::Primitive);
+                    ctx.stream.push("<", Kind::Annotation);
                     template.params.demangle(ctx, backrefs);
-                    ctx.stream.push("> ", CONFIG.colors.asm.annotation);
+                    ctx.stream.push("> ", Kind::Annotation);
 
                     func.return_type.0.demangle(ctx, backrefs);
                     func.params.demangle(ctx, backrefs);
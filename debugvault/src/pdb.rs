@@ -156,7 +156,11 @@ fn parse_pdb<'data>(
 
                 this.syms.push(Addressed {
                     addr: base_addr + addr,
-                    item: RawSymbol { name, module: None },
+                    item: RawSymbol {
+                        name,
+                        module: None,
+                        indirection: None,
+                    },
                 });
             }
             Ok(_) => {
@@ -203,7 +207,11 @@ fn parse_pdb_module<'data>(
 
                 syms.push(Addressed {
                     addr: base_addr + addr,
-                    item: RawSymbol { name, module: module_name },
+                    item: RawSymbol {
+                        name,
+                        module: module_name,
+                        indirection: None,
+                    },
                 });
             }
             Ok(SymbolData::Procedure(proc)) => {
@@ -82,8 +82,10 @@ mod test {
         Arc::new(Symbol {
             name_as_str: Arc::from(s),
             name: TokenStream::simple(s),
+            mangled: None,
             module: None,
-            is_intrinsics: false
+            is_intrinsics: false,
+            indirection: None,
         })
     }
 
@@ -89,8 +89,7 @@
 mod tests;
 
 use crate::TokenStream;
-use config::CONFIG;
-use tokenizing::{colors, Color32};
+use tokenizing::Kind;
 
 /// Max recursion depth.
 const MAX_DEPTH: usize = 256;
@@ -148,9 +147,9 @@ impl<'src> Parser {
     }
 
     #[inline]
-    fn push(&mut self, text: &'static str, color: Color32) {
+    fn push(&mut self, text: &'static str, kind: Kind) {
         if self.printing {
-            self.stream.push(text, color);
+            self.stream.push(text, kind);
         }
     }
 
@@ -225,7 +224,7 @@ impl<'src> Parser {
                 return Some(());
             }
 
-            self.push(delimiter, CONFIG.colors.asm.expr);
+            self.push(delimiter, Kind::Expr);
             f(self)?;
         }
 
@@ -323,8 +322,8 @@ impl<'src> Parser {
     /// Appends a generic (which can be a lifetime, type or constant) out of a list of generics.
     fn generic(&mut self) -> Option<()> {
         if let Some(lifetime) = self.lifetime() {
-            self.push(lifetime, CONFIG.colors.asm.annotation);
-            self.push(" ", colors::WHITE);
+            self.push(lifetime, Kind::Annotation);
+            self.push(" ", Kind::Plain);
             return Some(());
         }
 
@@ -367,7 +366,7 @@ impl<'src> Parser {
     fn constant(&mut self) -> Option<()> {
         // placeholder
         if self.eat(b'p') {
-            self.push("_", CONFIG.colors.brackets);
+            self.push("_", Kind::Bracket);
             return Some(());
         }
 
@@ -379,7 +378,7 @@ impl<'src> Parser {
 
         self.offset += 1;
         self.hex_nibbles()?;
-        self.push("_", CONFIG.colors.brackets);
+        self.push("_", Kind::Bracket);
         Some(())
     }
 
@@ -471,7 +470,7 @@ impl<'src> Parser {
 
                 self.disambiguator();
                 let ident = self.ident()?;
-                self.push(ident, CONFIG.colors.asm.component);
+                self.push(ident, Kind::Component);
             }
             // <T> (inherited impl)
             b'M' => {
@@ -479,9 +478,9 @@ impl<'src> Parser {
 
                 self.disambiguator();
                 self.dont_print(Self::path)?;
-                self.push("<", CONFIG.colors.asm.annotation);
+                self.push("<", Kind::Annotation);
                 self.tipe()?;
-                self.push(">", CONFIG.colors.asm.annotation);
+                self.push(">", Kind::Annotation);
             }
             // <T as Trait> (trait impl)
             b'X' => {
@@ -489,21 +488,21 @@ impl<'src> Parser {
 
                 self.disambiguator();
                 self.dont_print(Self::path)?;
-                self.push("<", CONFIG.colors.asm.annotation);
+                self.push("<", Kind::Annotation);
                 self.tipe()?;
-                self.push(" as ", CONFIG.colors.asm.annotation);
+                self.push(" as ", Kind::Annotation);
                 self.path()?;
-                self.push(">", CONFIG.colors.asm.annotation);
+                self.push(">", Kind::Annotation);
             }
             // <T as Trait> (trait definition)
             b'Y' => {
                 self.offset += 1;
 
-                self.push("<", CONFIG.colors.asm.annotation);
+                self.push("<", Kind::Annotation);
                 self.tipe()?;
-                self.push(" as ", CONFIG.colors.asm.annotation);
+                self.push(" as ", Kind::Annotation);
                 self.path()?;
-                self.push(">", CONFIG.colors.asm.annotation);
+                self.push(">", Kind::Annotation);
             }
             // ...::ident (nested path)
             b'N' => {
@@ -515,35 +514,35 @@ impl<'src> Parser {
                 let disambiguator = self.disambiguator();
                 let ident = self.ident()?;
 
-                self.push("::", CONFIG.colors.delimiter);
+                self.push("::", Kind::Delimiter);
 
                 match ns {
                     NameSpace::Closure => {
-                        self.push("{", CONFIG.colors.brackets);
-                        self.push("closure", CONFIG.colors.asm.primitive);
+                        self.push("{", Kind::Bracket);
+                        self.push("closure", Kind::Primitive);
 
                         if !ident.is_empty() {
-                            self.push(":", CONFIG.colors.delimiter);
-                            self.push(ident, CONFIG.colors.asm.component);
+                            self.push(":", Kind::Delimiter);
+                            self.push(ident, Kind::Component);
                         }
 
                         match disambiguator {
-                            Some(0) => self.push("#0", CONFIG.colors.brackets),
-                            Some(1) => self.push("#1", CONFIG.colors.brackets),
-                            Some(2) => self.push("#2", CONFIG.colors.brackets),
-                            Some(3) => self.push("#3", CONFIG.colors.brackets),
-                            Some(4) => self.push("#4", CONFIG.colors.brackets),
-                            Some(5) => self.push("#5", CONFIG.colors.brackets),
-                            Some(6) => self.push("#6", CONFIG.colors.brackets),
-                            Some(7) => self.push("#7", CONFIG.colors.brackets),
-                            Some(8) => self.push("#8", CONFIG.colors.brackets),
-                            Some(9) => self.push("#9", CONFIG.colors.brackets),
+                            Some(0) => self.push("#0", Kind::Bracket),
+                            Some(1) => self.push("#1", Kind::Bracket),
+                            Some(2) => self.push("#2", Kind::Bracket),
+                            Some(3) => self.push("#3", Kind::Bracket),
+                            Some(4) => self.push("#4", Kind::Bracket),
+                            Some(5) => self.push("#5", Kind::Bracket),
+                            Some(6) => self.push("#6", Kind::Bracket),
+                            Some(7) => self.push("#7", Kind::Bracket),
+                            Some(8) => self.push("#8", Kind::Bracket),
+                            Some(9) => self.push("#9", Kind::Bracket),
                             _ => {}
                         }
 
-                        self.push("}", CONFIG.colors.brackets);
+                        self.push("}", Kind::Bracket);
                     }
-                    _ => self.push(ident, CONFIG.colors.asm.component),
+                    _ => self.push(ident, Kind::Component),
                 }
             }
             // ...<T, U, ..> (generic args)
@@ -556,12 +555,12 @@ impl<'src> Parser {
 
                 // generics on types shouldn't print a '::'
                 if !next_is_type {
-                    self.push("::", CONFIG.colors.delimiter);
+                    self.push("::", Kind::Delimiter);
                 }
 
-                self.push("<", CONFIG.colors.asm.annotation);
+                self.push("<", Kind::Annotation);
                 self.delimited(", ", Self::generic)?;
-                self.push(">", CONFIG.colors.asm.annotation);
+                self.push(">", Kind::Annotation);
             }
             b'B' => {
                 self.offset += 1;
@@ -580,7 +579,7 @@ impl<'src> Parser {
 
         // basic types
         if let Some(tipe) = self.basic_tipe() {
-            self.push(tipe, CONFIG.colors.asm.primitive);
+            self.push(tipe, Kind::Primitive);
 
             self.depth -= 1;
             return Some(());
@@ -597,36 +596,36 @@ impl<'src> Parser {
             b'A' => {
                 self.offset += 1;
 
-                self.push("[", CONFIG.colors.brackets);
+                self.push("[", Kind::Bracket);
                 self.tipe()?;
-                self.push("; ", CONFIG.colors.brackets);
+                self.push("; ", Kind::Bracket);
                 self.constant()?;
-                self.push("]", CONFIG.colors.brackets);
+                self.push("]", Kind::Bracket);
             }
             // [T]
             b'S' => {
                 self.offset += 1;
 
-                self.push("[", CONFIG.colors.brackets);
+                self.push("[", Kind::Bracket);
                 self.tipe()?;
-                self.push("]", CONFIG.colors.brackets);
+                self.push("]", Kind::Bracket);
             }
             // (T1, T2, T3, ..)
             b'T' => {
                 self.offset += 1;
 
-                self.push("(", CONFIG.colors.brackets);
+                self.push("(", Kind::Bracket);
                 self.delimited(", ", Self::tipe)?;
-                self.push(")", CONFIG.colors.brackets);
+                self.push(")", Kind::Bracket);
             }
             // &T
             b'R' => {
                 self.offset += 1;
 
-                self.push("&", CONFIG.colors.asm.pointer);
+                self.push("&", Kind::Pointer);
                 if let Some(lifetime) = self.lifetime() {
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
-                    self.push(" ", colors::WHITE);
+                    self.push(lifetime, Kind::Annotation);
+                    self.push(" ", Kind::Plain);
                 }
 
                 self.tipe()?;
@@ -635,29 +634,29 @@ impl<'src> Parser {
             b'Q' => {
                 self.offset += 1;
 
-                self.push("&", CONFIG.colors.asm.pointer);
+                self.push("&", Kind::Pointer);
                 if let Some(lifetime) = self.lifetime() {
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
-                    self.push(" ", colors::WHITE);
+                    self.push(lifetime, Kind::Annotation);
+                    self.push(" ", Kind::Plain);
                 }
 
-                self.push("mut ", CONFIG.colors.asm.annotation);
+                self.push("mut ", Kind::Annotation);
                 self.tipe()?;
             }
             // *const T
             b'P' => {
                 self.offset += 1;
 
-                self.push("*", CONFIG.colors.asm.pointer);
-                self.push("const ", CONFIG.colors.asm.annotation);
+                self.push("*", Kind::Pointer);
+                self.push("const ", Kind::Annotation);
                 self.tipe()?;
             }
             // *mut T
             b'O' => {
                 self.offset += 1;
 
-                self.push("*", CONFIG.colors.asm.pointer);
-                self.push("mut ", CONFIG.colors.asm.annotation);
+                self.push("*", Kind::Pointer);
+                self.push("mut ", Kind::Annotation);
                 self.tipe()?;
             }
             // fn(..) -> ..
@@ -666,37 +665,37 @@ impl<'src> Parser {
                 self.binder();
 
                 if self.eat(b'U') {
-                    self.push("unsafe ", CONFIG.colors.asm.pointer);
+                    self.push("unsafe ", Kind::Pointer);
                 }
 
                 if self.eat(b'K') {
-                    self.push("extern ", CONFIG.colors.asm.pointer);
+                    self.push("extern ", Kind::Pointer);
 
                     if self.eat(b'C') {
-                        self.push("\"", CONFIG.colors.brackets);
-                        self.push("C", CONFIG.colors.asm.component);
-                        self.push("\" ", CONFIG.colors.brackets);
+                        self.push("\"", Kind::Bracket);
+                        self.push("C", Kind::Component);
+                        self.push("\" ", Kind::Bracket);
                     } else {
                         let ident = self.ident()?;
 
-                        self.push("\"", CONFIG.colors.brackets);
-                        self.push(ident, CONFIG.colors.asm.component);
-                        self.push("\"", CONFIG.colors.brackets);
+                        self.push("\"", Kind::Bracket);
+                        self.push(ident, Kind::Component);
+                        self.push("\"", Kind::Bracket);
                     }
                 }
 
-                self.push("fn", CONFIG.colors.asm.primitive);
-                self.push("(", CONFIG.colors.asm.primitive);
+                self.push("fn", Kind::Primitive);
+                self.push("(", Kind::Primitive);
                 self.delimited(", ", Self::tipe)?;
-                self.push(")", CONFIG.colors.brackets);
-                self.push(" -> ", CONFIG.colors.brackets);
+                self.push(")", Kind::Bracket);
+                self.push(" -> ", Kind::Bracket);
                 self.tipe()?;
             }
             // dyn ..
             b'D' => {
                 self.offset += 1;
                 self.binder();
-                self.push("dyn ", CONFIG.colors.asm.pointer);
+                self.push("dyn ", Kind::Pointer);
 
                 // associated traits e.g. Send + Sync + Pin
                 self.delimited(" + ", |this| {
@@ -704,20 +703,20 @@ impl<'src> Parser {
 
                     // associated trait bounds e.g. Trait<Assoc = X>
                     while this.eat(b'p') {
-                        this.push("<", CONFIG.colors.asm.annotation);
+                        this.push("<", Kind::Annotation);
                         let ident = this.ident()?;
-                        this.push(ident, CONFIG.colors.asm.component);
-                        this.push(" = ", CONFIG.colors.asm.expr);
+                        this.push(ident, Kind::Component);
+                        this.push(" = ", Kind::Expr);
                         this.tipe()?;
-                        this.push(">", CONFIG.colors.asm.annotation);
+                        this.push(">", Kind::Annotation);
                     }
 
                     Some(())
                 })?;
 
                 if let Some(lifetime) = self.lifetime() {
-                    self.push(" + ", CONFIG.colors.asm.expr);
-                    self.push(lifetime, CONFIG.colors.asm.annotation);
+                    self.push(" + ", Kind::Expr);
+                    self.push(lifetime, Kind::Annotation);
                 }
             }
             b'B' => {
@@ -1,5 +1,5 @@
 use crate::TokenStream;
-use config::CONFIG;
+use tokenizing::Kind;
 
 mod tests;
 
@@ -51,16 +51,16 @@ pub fn parse(s: &str) -> Option<TokenStream> {
         }
 
         if !in_first_part {
-            stream.push("::", CONFIG.colors.delimiter);
+            stream.push("::", Kind::Delimiter);
         }
 
         loop {
             if part.starts_with('.') {
                 if part[1..].starts_with('.') {
-                    stream.push("::", CONFIG.colors.delimiter);
+                    stream.push("::", Kind::Delimiter);
                     part = &part[2..];
                 } else {
-                    stream.push(".", CONFIG.colors.comment);
+                    stream.push(".", Kind::Comment);
                     part = &part[1..];
                 }
             } else if part.starts_with('$') {
@@ -71,21 +71,21 @@ pub fn parse(s: &str) -> Option<TokenStream> {
 
                 // source: compiler/rustc_symbol_mangling/src/legacy.rs
                 match escape {
-                    "SP" => stream.push("@", CONFIG.colors.comment),
-                    "BP" => stream.push("*", CONFIG.colors.asm.pointer),
-                    "RF" => stream.push("&", CONFIG.colors.asm.pointer),
-                    "LT" => stream.push("<", CONFIG.colors.asm.annotation),
-                    "GT" => stream.push(">", CONFIG.colors.asm.annotation),
-                    "LP" => stream.push("(", CONFIG.colors.asm.label),
-                    "RP" => stream.push(")", CONFIG.colors.asm.label),
+                    "SP" => stream.push("@", Kind::Comment),
+                    "BP" => stream.push("*", Kind::Pointer),
+                    "RF" => stream.push("&", Kind::Pointer),
+                    "LT" => stream.push("<", Kind::Annotation),
+                    "GT" => stream.push(">", Kind::Annotation),
+                    "LP" => stream.push("(", Kind::Label),
+                    "RP" => stream.push(")", Kind::Label),
                     "C" => {
                         // if the next character is a space don't print one
                         //
                         // this is to allow for a space between comma separated items
                         if let Some(b"$u20$") = after_escape.as_bytes().get(..5) {
-                            stream.push(",", CONFIG.colors.asm.expr);
+                            stream.push(",", Kind::Expr);
                         } else {
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                         }
                     }
                     _ => {
@@ -98,8 +98,8 @@ pub fn parse(s: &str) -> Option<TokenStream> {
 
                             if let (true, Some(chr)) = (all_lower_hex, chr) {
                                 if !chr.is_control() {
-                                    let color = CONFIG.colors.asm.component;
-                                    stream.push_string(chr.to_string(), color);
+                                    let kind = Kind::Component;
+                                    stream.push_string(chr.to_string(), kind);
                                     part = after_escape;
                                     continue;
                                 }
@@ -113,14 +113,14 @@ pub fn parse(s: &str) -> Option<TokenStream> {
                 part = after_escape;
             } else if let Some(idx) = part.find(|c| c == '$' || c == '.') {
                 let ident = &part[..idx];
-                stream.push(ident, CONFIG.colors.asm.component);
+                stream.push(ident, Kind::Component);
                 part = &part[idx..];
             } else {
                 break;
             }
         }
 
-        stream.push(part, CONFIG.colors.asm.component);
+        stream.push(part, Kind::Component);
         in_first_part = false;
     }
 
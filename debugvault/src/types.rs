@@ -0,0 +1,290 @@
+//! C function prototypes, attached to functions by the user or imported from debug info.
+//!
+//! Types are kept as the C text that spells them, as they're only shown to the user.
+
+use std::fmt;
+
+/// Keywords that can precede a declaration without being part of it's return type.
+const SPECIFIERS: &[&str] = &[
+    "extern",
+    "static",
+    "inline",
+    "__cdecl",
+    "__stdcall",
+    "__fastcall",
+    "__thiscall",
+    "__vectorcall",
+    "WINAPI",
+];
+
+/// Words that make up a type, so a parameter ending in one is unnamed.
+const TYPE_WORDS: &[&str] = &[
+    "void", "char", "short", "int", "long", "float", "double", "signed", "unsigned", "bool",
+    "_Bool",
+];
+
+/// Words that have to be followed by a type, so a parameter ending after one is unnamed.
+const QUALIFIERS: &[&str] = &["const", "volatile", "struct", "union", "enum"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param {
+    /// Type of the argument, e.g. `const char *`.
+    pub ty: String,
+    /// Empty for unnamed arguments.
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prototype {
+    pub ret: String,
+    pub name: String,
+    pub params: Vec<Param>,
+    /// Whether more arguments can follow the parameters, as for `printf`.
+    pub variadic: bool,
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | ':' | '~' | '$')
+}
+
+/// Split `text` into what precedes the identifier it ends with and the identifier.
+fn split_ident(text: &str) -> (&str, &str) {
+    let start = text.trim_end_matches(is_ident).len();
+    (text[..start].trim_end(), &text[start..])
+}
+
+/// Type with it's whitespace collapsed and pointers written as `char *`.
+fn normalize(ty: &str) -> String {
+    let mut normalized = String::with_capacity(ty.len());
+    for word in ty.split_whitespace() {
+        for c in word.chars() {
+            let pointer = matches!(c, '*' | '&');
+            let after_name = normalized.ends_with(|prev: char| prev != ' ' && is_ident(prev));
+            if pointer && after_name {
+                normalized.push(' ');
+            }
+            normalized.push(c);
+        }
+        normalized.push(' ');
+    }
+
+    normalized.trim_end().to_string()
+}
+
+/// Parameter as written in a declaration, e.g. `int flags` or `void (*handler)(int)`.
+fn parse_param(decl: &str) -> Option<Param> {
+    // Function pointers are named inside their parentheses.
+    if let Some(start) = decl.find("(*") {
+        let rest = &decl[start + 2..];
+        let end = rest.find(')')?;
+        let name = rest[..end].trim();
+        if !name.chars().all(is_ident) {
+            return None;
+        }
+
+        let ty = format!("{}(*){}", &decl[..start], &rest[end + 1..]);
+        return Some(Param {
+            ty: normalize(&ty),
+            name: name.to_string(),
+        });
+    }
+
+    // Arrays are passed as pointers to their first element.
+    let (decl, array) = match decl.find('[') {
+        Some(start) if decl.ends_with(']') => (decl[..start].trim_end(), true),
+        _ => (decl, false),
+    };
+
+    let (ty, name) = split_ident(decl);
+    let named = !ty.is_empty()
+        && !TYPE_WORDS.contains(&name)
+        && !QUALIFIERS.contains(&name)
+        && (ty.ends_with(['*', '&']) || ty.split_whitespace().any(|w| !QUALIFIERS.contains(&w)));
+
+    let (ty, name) = if named { (ty, name) } else { (decl, "") };
+    if ty.is_empty() {
+        return None;
+    }
+
+    let ty = if array { format!("{ty} *") } else { ty.to_string() };
+    Some(Param {
+        ty: normalize(&ty),
+        name: name.to_string(),
+    })
+}
+
+/// Split `text` at commas that aren't nested in parentheses or brackets.
+fn split_args(text: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (idx, c) in text.char_indices() {
+        match c {
+            '(' | '[' | '<' => depth += 1,
+            ')' | ']' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(text[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    args.push(text[start..].trim());
+    args
+}
+
+impl Prototype {
+    /// Parse a C declaration such as `int open(const char *path, int flags)`.
+    ///
+    /// A trailing semicolon, storage classes and calling convention keywords are ignored.
+    pub fn parse(decl: &str) -> Option<Self> {
+        let decl = decl.trim().trim_end_matches(';').trim_end();
+        if !decl.ends_with(')') {
+            return None;
+        }
+
+        // Find the parenthesis opening the argument list, which can contain parentheses itself.
+        let mut depth = 0usize;
+        let mut open = None;
+        for (idx, c) in decl.char_indices().rev() {
+            match c {
+                ')' => depth += 1,
+                '(' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        open = Some(idx);
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let open = open?;
+        let (head, name) = split_ident(decl[..open].trim_end());
+        let ret: Vec<&str> =
+            head.split_whitespace().filter(|word| !SPECIFIERS.contains(word)).collect();
+
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) || ret.is_empty() {
+            return None;
+        }
+
+        let mut params = Vec::new();
+        let mut variadic = false;
+        let args = decl[open + 1..decl.len() - 1].trim();
+        if !args.is_empty() && args != "void" {
+            for arg in split_args(args) {
+                if variadic {
+                    return None;
+                }
+
+                match arg {
+                    "..." => variadic = true,
+                    arg => params.push(parse_param(arg)?),
+                }
+            }
+        }
+
+        Some(Self {
+            ret: normalize(&ret.join(" ")),
+            name: name.to_string(),
+            params,
+            variadic,
+        })
+    }
+
+    /// Name of the argument at `idx`, or a generated one if it's unnamed.
+    pub fn arg_name(&self, idx: usize) -> String {
+        match self.params.get(idx) {
+            Some(param) if !param.name.is_empty() => param.name.clone(),
+            _ => format!("arg{}", idx + 1),
+        }
+    }
+}
+
+/// `ty` followed by `name`, without a space between pointers and the name.
+fn declare(f: &mut fmt::Formatter<'_>, ty: &str, name: &str) -> fmt::Result {
+    if name.is_empty() {
+        f.write_str(ty)
+    } else if ty.contains("(*)") {
+        f.write_str(&ty.replacen("(*)", &format!("(*{name})"), 1))
+    } else if ty.ends_with(['*', '&']) {
+        f.write_fmt(format_args!("{ty}{name}"))
+    } else {
+        f.write_fmt(format_args!("{ty} {name}"))
+    }
+}
+
+impl fmt::Display for Prototype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        declare(f, &self.ret, &self.name)?;
+        f.write_str("(")?;
+
+        for (idx, param) in self.params.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            declare(f, &param.ty, &param.name)?;
+        }
+
+        match (self.params.is_empty(), self.variadic) {
+            (true, false) => f.write_str("void")?,
+            (true, true) => f.write_str("...")?,
+            (false, true) => f.write_str(", ...")?,
+            (false, false) => {}
+        }
+
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(ty: &str, name: &str) -> Param {
+        Param {
+            ty: ty.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn prototypes() {
+        let open = Prototype::parse("int open(const char* path, int flags, ...);").unwrap();
+        assert_eq!(open.ret, "int");
+        assert_eq!(open.name, "open");
+        assert_eq!(open.params, [param("const char *", "path"), param("int", "flags")]);
+        assert!(open.variadic);
+        assert_eq!(open.to_string(), "int open(const char *path, int flags, ...)");
+
+        let strdup = Prototype::parse("extern char *strdup(const char *)").unwrap();
+        assert_eq!(strdup.ret, "char *");
+        assert_eq!(strdup.params, [param("const char *", "")]);
+        assert_eq!(strdup.arg_name(0), "arg1");
+        assert_eq!(strdup.to_string(), "char *strdup(const char *)");
+
+        let exit = Prototype::parse("void __cdecl exit(void)").unwrap();
+        assert!(exit.params.is_empty());
+        assert_eq!(exit.to_string(), "void exit(void)");
+    }
+
+    #[test]
+    fn params() {
+        let signal = Prototype::parse("void signal(int sig, void (*handler)(int))").unwrap();
+        assert_eq!(signal.params[1], param("void (*)(int)", "handler"));
+        assert_eq!(signal.to_string(), "void signal(int sig, void (*handler)(int))");
+
+        let read = Prototype::parse("long read(unsigned int, char buf[16], size_t n)").unwrap();
+        assert_eq!(
+            read.params,
+            [param("unsigned int", ""), param("char *", "buf"), param("size_t", "n")]
+        );
+
+        assert!(Prototype::parse("int").is_none());
+        assert!(Prototype::parse("open(int fd)").is_none());
+        assert!(Prototype::parse("int f(..., int)").is_none());
+    }
+}
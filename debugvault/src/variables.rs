@@ -0,0 +1,98 @@
+//! Parameters and locals of functions, as described by the debug info.
+//!
+//! Where a variable lives is kept as the DWARF expression computing it, which is only evaluated
+//! once there's a stopped process to evaluate it against.
+
+use std::ops::Range;
+
+/// How the bytes of a value are interpreted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// Signed integer of the given size.
+    Signed(usize),
+    /// Unsigned integer of the given size.
+    Unsigned(usize),
+    /// Floating point number of the given size.
+    Float(usize),
+    Bool,
+    /// Single byte character, pointers to them are strings.
+    Char,
+    /// Pointer to a value with the given layout.
+    Pointer(Box<Layout>),
+    /// Aggregate or anything else that's only shown as bytes, of the given size.
+    Other(usize),
+}
+
+impl Layout {
+    /// Bytes the value takes up, given the size of a pointer.
+    pub fn size(&self, address_size: usize) -> usize {
+        match *self {
+            Self::Signed(size) | Self::Unsigned(size) | Self::Float(size) | Self::Other(size) => {
+                size
+            }
+            Self::Bool | Self::Char => 1,
+            Self::Pointer(_) => address_size,
+        }
+    }
+}
+
+/// DWARF expression telling where a value is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    /// The value was optimized out.
+    None,
+    /// Expression that holds for the whole scope.
+    Expr(Vec<u8>),
+    /// Expressions that each only hold within their range of addresses.
+    List(Vec<(Range<usize>, Vec<u8>)>),
+}
+
+impl Location {
+    /// Expression that holds at the instruction at `addr`.
+    pub fn at(&self, addr: usize) -> Option<&[u8]> {
+        match self {
+            Self::None => None,
+            Self::Expr(expr) => Some(expr),
+            Self::List(list) => list
+                .iter()
+                .find(|(range, _)| range.contains(&addr))
+                .map(|(_, expr)| expr.as_slice()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    /// C spelling of the variable's type, e.g. `const char *`.
+    pub ty: String,
+    pub layout: Layout,
+    pub location: Location,
+    /// Whether it's one of the function's parameters rather than a local.
+    pub param: bool,
+    /// Addresses of the block the variable is declared in, None if that's the whole function.
+    pub block: Option<Range<usize>>,
+}
+
+/// Variables of a function definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    /// Address following the function's last instruction.
+    pub end: usize,
+    /// Where the frame base is, which the locations of variables tend to be relative to.
+    pub frame_base: Location,
+    /// Size of a pointer in the expressions.
+    pub address_size: u8,
+    /// Parameters followed by the locals, in the order they're declared.
+    pub variables: Vec<Variable>,
+}
+
+impl Scope {
+    /// Variables that are in scope at `addr`.
+    pub fn visible(&self, addr: usize) -> impl Iterator<Item = &Variable> {
+        self.variables.iter().filter(move |var| match var.block {
+            Some(ref block) => block.contains(&addr),
+            None => true,
+        })
+    }
+}
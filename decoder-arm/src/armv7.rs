@@ -3,10 +3,9 @@
 
 use core::fmt::{self, Display, Formatter};
 
-use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
+use decoder::{Decoded, Decodable, Description, Error, ErrorKind, Reader, ToTokens, XrefKind};
 use debugvault::Index;
-use tokenizing::TokenStream;
-use config::CONFIG;
+use tokenizing::{Kind, TokenStream};
 
 mod thumb;
 
@@ -35,7 +34,7 @@ impl ToTokens for ConditionedOpcode {
         match self.0 {
             Opcode::UDF | Opcode::Invalid => {
                 // invalid_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.invalid)
+                stream.push_owned(self.to_string(), Kind::Invalid)
             }
             Opcode::TBB
             | Opcode::TBH
@@ -48,7 +47,7 @@ impl ToTokens for ConditionedOpcode {
             | Opcode::BX
             | Opcode::BXJ => {
                 // control_flow_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::AND
             | Opcode::EOR
@@ -136,15 +135,15 @@ impl ToTokens for ConditionedOpcode {
             | Opcode::SMLAL
             | Opcode::SMLAL_halfword(_, _) => {
                 // arithmetic_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::PUSH | Opcode::POP => {
                 // stack_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::TST | Opcode::TEQ | Opcode::CMP | Opcode::CMN => {
                 // comparison_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::LDRSH
             | Opcode::LDRSHT
@@ -216,7 +215,7 @@ impl ToTokens for ConditionedOpcode {
             | Opcode::MOVT
             | Opcode::MVN => {
                 // data_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::HINT
             | Opcode::NOP
@@ -229,7 +228,7 @@ impl ToTokens for ConditionedOpcode {
             | Opcode::SRS(_, _)
             | Opcode::BKPT => {
                 // misc_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
             Opcode::DBG
             | Opcode::CPS(_)
@@ -262,7 +261,7 @@ impl ToTokens for ConditionedOpcode {
             | Opcode::MRRC(_, _)
             | Opcode::CDP2(_, _, _) => {
                 // platform_op
-                stream.push_owned(self.to_string(), CONFIG.colors.asm.opcode)
+                stream.push_owned(self.to_string(), Kind::Mnemonic)
             }
         }
     }
@@ -1680,26 +1679,62 @@ impl Bank {
 }
 
 impl Operand {
+    /// What kind of operand this is, e.g. `register list`.
+    fn kind(&self) -> &'static str {
+        match self {
+            Operand::Reg(_) => "register",
+            Operand::RegWBack(_, true) => "register, written back",
+            Operand::RegWBack(_, false) => "register",
+            Operand::RegList(_) => "register list",
+            Operand::RegDeref(_) => "memory at a register",
+            Operand::RegShift(_) => "shifted register",
+            Operand::RegDerefPreindexRegShift(_, _, _, true)
+            | Operand::RegDerefPreindexOffset(_, _, _, true)
+            | Operand::RegDerefPreindexReg(_, _, _, true) => {
+                "memory at a register plus an offset, written back"
+            }
+            Operand::RegDerefPreindexRegShift(..)
+            | Operand::RegDerefPreindexOffset(..)
+            | Operand::RegDerefPreindexReg(..) => "memory at a register plus an offset",
+            Operand::RegDerefPostindexRegShift(..)
+            | Operand::RegDerefPostindexOffset(..)
+            | Operand::RegDerefPostindexReg(..) => {
+                "memory at a register, adding an offset to it afterwards"
+            }
+            Operand::Imm12(_) | Operand::Imm32(_) => "immediate",
+            Operand::Imm64(_) | Operand::Imm64Special(_) => "address",
+            Operand::BranchOffset(_) | Operand::BranchThumbOffset(_) => "branch offset",
+            Operand::Coprocessor(_) => "coprocessor",
+            Operand::CoprocOption(_) => "coprocessor option",
+            Operand::CReg(_) => "coprocessor register",
+            Operand::BankedReg(..) => "banked register",
+            Operand::BankedSPSR(_) => "banked status register",
+            Operand::StatusRegMask(_) => "status register fields",
+            Operand::APSR | Operand::SPSR | Operand::CPSR => "status register",
+            Operand::Nothing => "nothing",
+        }
+    }
+
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index, _imm_override: Option<usize>) {
         match self {
             Operand::RegList(list) => format_reg_list(stream, *list),
             Operand::BankedReg(bank, reg) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
-                stream.push("_", CONFIG.colors.asm.expr);
-                stream.push(bank.as_str(), CONFIG.colors.asm.register);
+                stream.push(reg.as_str(), Kind::Register);
+                stream.push("_", Kind::Expr);
+                stream.push(bank.as_str(), Kind::Register);
             }
             Operand::BankedSPSR(bank) => {
-                stream.push("spsr", CONFIG.colors.asm.register);
-                stream.push("_", CONFIG.colors.asm.expr);
-                stream.push(bank.as_str(), CONFIG.colors.asm.register);
+                stream.push("spsr", Kind::Register);
+                stream.push("_", Kind::Expr);
+                stream.push(bank.as_str(), Kind::Register);
             }
             Operand::Reg(reg) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push(reg.as_str(), Kind::Register);
             }
             Operand::RegDeref(reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(reg.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegShift(shift) => format_shift(stream, *shift),
             Operand::RegDerefPostindexRegShift(reg, shift, add, wback) => {
@@ -1715,123 +1750,119 @@ impl Operand {
                 format_reg_imm_mem(stream, *reg, *offs, *add, true, *wback)
             }
             Operand::RegDerefPostindexReg(reg, offsreg, add, wback) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(reg.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
+                stream.push(", ", Kind::Expr);
 
                 if !*add {
-                    stream.push("-", CONFIG.colors.asm.expr);
+                    stream.push("-", Kind::Expr);
                 }
 
-                stream.push(offsreg.as_str(), CONFIG.colors.asm.register);
+                stream.push(offsreg.as_str(), Kind::Register);
 
                 if *wback {
-                    stream.push("!", CONFIG.colors.asm.expr);
+                    stream.push("!", Kind::Expr);
                 }
             }
             Operand::RegDerefPreindexReg(reg, offsreg, add, wback) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(reg.as_str(), Kind::Register);
+                stream.push(", ", Kind::Expr);
 
                 if !*add {
-                    stream.push("-", CONFIG.colors.asm.expr);
+                    stream.push("-", Kind::Expr);
                 }
 
-                stream.push(offsreg.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push(offsreg.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
 
                 if *wback {
-                    stream.push("!", CONFIG.colors.asm.expr);
+                    stream.push("!", Kind::Expr);
                 }
             }
             Operand::Imm12(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
+                match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                    Some((symbol, _)) => {
+                        stream.push("<", Kind::Immediate);
+                        stream.push_symbol(symbol.name(), *imm as usize);
+                        stream.push(">", Kind::Immediate);
                     }
                     None => {
-                        stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                        stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
                     }
                 }
             }
             Operand::Imm32(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
+                match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                    Some((symbol, _)) => {
+                        stream.push("<", Kind::Immediate);
+                        stream.push_symbol(symbol.name(), *imm as usize);
+                        stream.push(">", Kind::Immediate);
                     }
                     None => {
-                        stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                        stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
                     }
                 }
             }
             Operand::Imm64(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.immediate);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.immediate);
+                match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                    Some((symbol, _)) => {
+                        stream.push("<", Kind::Immediate);
+                        stream.push_symbol(symbol.name(), *imm as usize);
+                        stream.push(">", Kind::Immediate);
                     }
                     None => {
-                        stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(*imm), CONFIG.colors.asm.immediate);
+                        let text = decoder::encode_uhex(*imm);
+                        stream.push("#", Kind::Expr);
+                        stream.push_addr(text, Kind::Immediate, *imm as usize);
                     }
                 }
             }
             Operand::Imm64Special(imm) => {
-                stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm), CONFIG.colors.asm.immediate);
+                stream.push("#", Kind::Expr);
+                let text = decoder::encode_uhex(*imm);
+                stream.push_addr(text, Kind::Immediate, *imm as usize);
             }
             Operand::BranchOffset(offs) => {
                 if *offs >= 0 {
-                    stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push("$", Kind::Expr);
+                    stream.push("+", Kind::Immediate);
                 } else {
-                    stream.push("$", CONFIG.colors.asm.immediate);
+                    stream.push("$", Kind::Immediate);
                 }
-                stream.push_owned(decoder::encode_hex((offs * 4) as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned(decoder::encode_hex((offs * 4) as i64), Kind::Immediate);
             }
             Operand::BranchThumbOffset(offs) => {
                 if *offs >= 0 {
-                    stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push("$", Kind::Expr);
+                    stream.push("+", Kind::Immediate);
                 } else {
-                    stream.push("$", CONFIG.colors.asm.immediate);
+                    stream.push("$", Kind::Immediate);
                 }
-                stream.push_owned(decoder::encode_hex((offs * 2) as i64), CONFIG.colors.asm.immediate);
+                stream.push_owned(decoder::encode_hex((offs * 2) as i64), Kind::Immediate);
             }
             Operand::Coprocessor(num) => {
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(num.to_string(), CONFIG.colors.asm.register);
+                stream.push("p", Kind::Register);
+                stream.push_owned(num.to_string(), Kind::Register);
             }
             Operand::CoprocOption(num) => {
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(*num as i64), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push_owned(decoder::encode_hex(*num as i64), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegWBack(reg, wback) => {
-                stream.push(reg.as_str(), CONFIG.colors.asm.register);
+                stream.push(reg.as_str(), Kind::Register);
 
                 if *wback {
-                    stream.push("!", CONFIG.colors.asm.expr);
+                    stream.push("!", Kind::Expr);
                 }
             }
-            Operand::CReg(creg) => stream.push(creg.as_str(), CONFIG.colors.asm.register),
-            Operand::StatusRegMask(mask) => stream.push(mask.as_str(), CONFIG.colors.asm.register),
-            Operand::APSR => stream.push("apsr", CONFIG.colors.asm.register),
-            Operand::SPSR => stream.push("spsr", CONFIG.colors.asm.register),
-            Operand::CPSR => stream.push("cpsr", CONFIG.colors.asm.register),
+            Operand::CReg(creg) => stream.push(creg.as_str(), Kind::Register),
+            Operand::StatusRegMask(mask) => stream.push(mask.as_str(), Kind::Register),
+            Operand::APSR => stream.push("apsr", Kind::Register),
+            Operand::SPSR => stream.push("spsr", Kind::Register),
+            Operand::CPSR => stream.push("cpsr", Kind::Register),
             Operand::Nothing => panic!("tried to print Nothing operand"),
         }
     }
@@ -1910,44 +1941,44 @@ impl Instruction {
 }
 
 fn format_reg_list(stream: &mut TokenStream, mut list: u16) {
-    stream.push("{", CONFIG.colors.brackets);
+    stream.push("{", Kind::Bracket);
     let mut i = 0;
     let mut tail = false;
     while i < 16 {
         let present = (list & 1) == 1;
         if present {
             if tail {
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
             } else {
                 tail = true;
             }
-            stream.push(Reg::from_u8(i).as_str(), CONFIG.colors.asm.register);
+            stream.push(Reg::from_u8(i).as_str(), Kind::Register);
         }
         i += 1;
         list >>= 1;
     }
-    stream.push("}", CONFIG.colors.brackets);
+    stream.push("}", Kind::Bracket);
 }
 
 fn format_shift(stream: &mut TokenStream, shift: RegShift) {
     match shift.into_shift() {
         RegShiftStyle::RegImm(imm_shift) => {
             if imm_shift.imm() == 0 && imm_shift.stype() == ShiftStyle::LSL {
-                stream.push(imm_shift.shiftee().as_str(), CONFIG.colors.asm.register);
+                stream.push(imm_shift.shiftee().as_str(), Kind::Register);
             } else {
-                stream.push(imm_shift.shiftee().as_str(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push(imm_shift.stype().as_str(), CONFIG.colors.asm.segment);
-                stream.push(" ", CONFIG.colors.asm.expr);
-                stream.push_owned(imm_shift.imm().to_string(), CONFIG.colors.asm.immediate);
+                stream.push(imm_shift.shiftee().as_str(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push(imm_shift.stype().as_str(), Kind::Segment);
+                stream.push(" ", Kind::Expr);
+                stream.push_owned(imm_shift.imm().to_string(), Kind::Immediate);
             }
         }
         RegShiftStyle::RegReg(reg_shift) => {
-            stream.push(reg_shift.shiftee().as_str(), CONFIG.colors.asm.register);
-            stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(reg_shift.stype().as_str(), CONFIG.colors.asm.segment);
-            stream.push(" ", CONFIG.colors.asm.expr);
-            stream.push(reg_shift.shifter().as_str(), CONFIG.colors.asm.register);
+            stream.push(reg_shift.shiftee().as_str(), Kind::Register);
+            stream.push(", ", Kind::Expr);
+            stream.push(reg_shift.stype().as_str(), Kind::Segment);
+            stream.push(" ", Kind::Expr);
+            stream.push(reg_shift.shifter().as_str(), Kind::Register);
         }
     }
 }
@@ -1964,24 +1995,24 @@ fn format_reg_shift_mem(
 
     match (pre, wback) {
         (true, true) => {
-            stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push("[", Kind::Bracket);
+            stream.push(rd.as_str(), Kind::Register);
+            stream.push(", ", Kind::Expr);
+            stream.push(rd.as_str(), Kind::Register);
+            stream.push(op, Kind::Immediate);
             format_shift(stream, shift);
-            stream.push("]", CONFIG.colors.brackets);
-            stream.push("!", CONFIG.colors.asm.expr);
+            stream.push("]", Kind::Bracket);
+            stream.push("!", Kind::Expr);
         }
 
         (true, false) => {
-            stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push("[", Kind::Bracket);
+            stream.push(rd.as_str(), Kind::Register);
+            stream.push(", ", Kind::Expr);
+            stream.push(rd.as_str(), Kind::Register);
+            stream.push(op, Kind::Immediate);
             format_shift(stream, shift);
-            stream.push("]", CONFIG.colors.brackets);
+            stream.push("]", Kind::Bracket);
         }
         (false, true) => {
             unreachable!(
@@ -1990,11 +2021,11 @@ fn format_reg_shift_mem(
             );
         }
         (false, false) => {
-            stream.push("[", CONFIG.colors.brackets);
-            stream.push(rd.as_str(), CONFIG.colors.asm.register);
-            stream.push("]", CONFIG.colors.brackets);
-            stream.push(", ", CONFIG.colors.asm.expr);
-            stream.push(op, CONFIG.colors.asm.immediate);
+            stream.push("[", Kind::Bracket);
+            stream.push(rd.as_str(), Kind::Register);
+            stream.push("]", Kind::Bracket);
+            stream.push(", ", Kind::Expr);
+            stream.push(op, Kind::Immediate);
             format_shift(stream, shift)
         }
     }
@@ -2011,57 +2042,57 @@ fn format_reg_imm_mem(
     if imm != 0 {
         match (pre, wback) {
             (true, true) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push(", ", Kind::Expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(imm as i64), Kind::Immediate);
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(-(imm as i64)), Kind::Immediate);
                 }
-                stream.push("]", CONFIG.colors.brackets);
-                stream.push("!", CONFIG.colors.asm.expr);
+                stream.push("]", Kind::Bracket);
+                stream.push("!", Kind::Expr);
             }
             (true, false) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push(", ", Kind::Expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(imm as i64), Kind::Immediate);
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(-(imm as i64)), Kind::Immediate);
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             (false, _) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
+                stream.push(", ", Kind::Expr);
                 if add {
-                    stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(imm as i64), Kind::Immediate);
                 } else {
-                    stream.push_owned(decoder::encode_hex(imm as i64 * -1), CONFIG.colors.asm.immediate);
+                    stream.push_owned(decoder::encode_hex(-(imm as i64)), Kind::Immediate);
                 }
             }
         }
     } else {
         match (pre, wback) {
             (true, true) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
-                stream.push("!", CONFIG.colors.asm.expr);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
+                stream.push("!", Kind::Expr);
             }
             (true, false) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
             (false, _) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(rn.as_str(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(rn.as_str(), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
         }
     }
@@ -2070,7 +2101,11 @@ fn format_reg_imm_mem(
 impl Decoded for Instruction {
     #[inline]
     fn width(&self) -> usize {
-        4
+        if self.thumb && !self.wide {
+            2
+        } else {
+            4
+        }
     }
 
     fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
@@ -2099,6 +2134,47 @@ impl Decoded for Instruction {
             }
         }
     }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        // both `Imm64` variants are only ever produced by `update_rel_addrs`
+        self.operands.iter().find_map(|operand| match *operand {
+            Operand::Imm64Special(addr) => Some((addr as usize, XrefKind::Data)),
+            Operand::Imm64(addr) => match self.opcode {
+                Opcode::BL | Opcode::BLX => Some((addr as usize, XrefKind::Call)),
+                _ => Some((addr as usize, XrefKind::Jump)),
+            },
+            _ => None,
+        })
+    }
+
+    fn describe(&self, symbols: &Index) -> Option<Description> {
+        let operands = self
+            .operands
+            .iter()
+            .filter(|operand| **operand != Operand::Nothing)
+            .map(|operand| {
+                let mut stream = TokenStream::new();
+                operand.tokenize(&mut stream, symbols, None);
+                (stream.to_string(), operand.kind().to_string())
+            })
+            .collect();
+
+        let mnemonic = self.opcode.to_string();
+        let mut description = Description::new(crate::reference::ARMV7, &mnemonic, operands);
+
+        // only comparisons always write the flags, other instructions do if they have an `s`
+        let compares = matches!(self.opcode, Opcode::CMP | Opcode::CMN | Opcode::TST | Opcode::TEQ);
+        if !self.s && !compares {
+            description.flags = "";
+        }
+
+        if self.condition != ConditionCode::AL {
+            let condition = crate::reference::CONDITIONS[self.condition as usize];
+            description.notes.push(format!("Only executes if {condition}."));
+        }
+
+        Some(description)
+    }
 }
 
 impl Display for Instruction {
@@ -2143,7 +2219,7 @@ impl ToTokens for Instruction {
                             },
                             condition,
                         );
-                        stream.push_owned(op, CONFIG.colors.asm.opcode);
+                        stream.push_owned(op, Kind::Mnemonic);
                     } else if mask & 0b0010 != 0 {
                         // two flags
                         let op = format!(
@@ -2160,7 +2236,7 @@ impl ToTokens for Instruction {
                             },
                             condition,
                         );
-                        stream.push_owned(op, CONFIG.colors.asm.opcode);
+                        stream.push_owned(op, Kind::Mnemonic);
                     } else if mask & 0b0100 != 0 {
                         // one flag
                         let op = format!(
@@ -2172,15 +2248,15 @@ impl ToTokens for Instruction {
                             },
                             condition,
                         );
-                        stream.push_owned(op, CONFIG.colors.asm.opcode);
+                        stream.push_owned(op, Kind::Mnemonic);
                     } else {
                         // no flags
                         let op = format!("it {}", condition);
-                        stream.push_owned(op, CONFIG.colors.asm.opcode);
+                        stream.push_owned(op, Kind::Mnemonic);
                     }
                     // if the condition is AL, it won't get displayed. append it here.
                     if *cond == 14 {
-                        stream.push("al", CONFIG.colors.asm.opcode);
+                        stream.push("al", Kind::Mnemonic);
                     }
                     return;
                 } else {
@@ -2196,10 +2272,10 @@ impl ToTokens for Instruction {
                         if aif & 0b010 != 0 { "i" } else { "" },
                         if aif & 0b001 != 0 { "f" } else { "" },
                     );
-                    stream.push_owned(op, CONFIG.colors.asm.opcode);
+                    stream.push_owned(op, Kind::Mnemonic);
                     if let Operand::Imm12(mode) = &self.operands[1] {
-                        stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_hex(*mode as i64), CONFIG.colors.asm.immediate);
+                        stream.push(", #", Kind::Expr);
+                        stream.push_owned(decoder::encode_hex(*mode as i64), Kind::Immediate);
                     }
                     return;
                 } else {
@@ -2209,9 +2285,9 @@ impl ToTokens for Instruction {
             Opcode::SETEND => {
                 if let Operand::Imm12(i) = &self.operands[0] {
                     if *i == 0 {
-                        stream.push("setend le", CONFIG.colors.asm.opcode);
+                        stream.push("setend le", Kind::Mnemonic);
                     } else {
-                        stream.push("setend be", CONFIG.colors.asm.opcode);
+                        stream.push("setend be", Kind::Mnemonic);
                     }
                     return;
                 } else {
@@ -2225,9 +2301,9 @@ impl ToTokens for Instruction {
                     {
                         ConditionedOpcode(Opcode::POP, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
-                        stream.push(" {", CONFIG.colors.brackets);
-                        stream.push(rt.as_str(), CONFIG.colors.asm.register);
-                        stream.push("}", CONFIG.colors.brackets);
+                        stream.push(" {", Kind::Bracket);
+                        stream.push(rt.as_str(), Kind::Register);
+                        stream.push("}", Kind::Bracket);
                         return;
                     }
                     _ => {}
@@ -2240,9 +2316,9 @@ impl ToTokens for Instruction {
                     {
                         ConditionedOpcode(Opcode::PUSH, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
-                        stream.push(" {", CONFIG.colors.brackets);
-                        stream.push(rt.as_str(), CONFIG.colors.asm.register);
-                        stream.push("}", CONFIG.colors.brackets);
+                        stream.push(" {", Kind::Bracket);
+                        stream.push(rt.as_str(), Kind::Register);
+                        stream.push("}", Kind::Bracket);
                         return;
                     }
                     _ => {}
@@ -2255,7 +2331,7 @@ impl ToTokens for Instruction {
                     {
                         ConditionedOpcode(Opcode::POP, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
-                        stream.push(" ", CONFIG.colors.asm.expr);
+                        stream.push(" ", Kind::Expr);
                         format_reg_list(stream, list);
                         return;
                     }
@@ -2269,7 +2345,7 @@ impl ToTokens for Instruction {
                     {
                         ConditionedOpcode(Opcode::PUSH, self.s(), self.w(), self.condition)
                             .tokenize(stream, symbols);
-                        stream.push(" ", CONFIG.colors.asm.expr);
+                        stream.push(" ", Kind::Expr);
                         format_reg_list(stream, list);
                         return;
                     }
@@ -2287,12 +2363,12 @@ impl ToTokens for Instruction {
                 {
                     ConditionedOpcode(self.opcode, self.s(), self.w(), self.condition)
                         .tokenize(stream, symbols);
-                    stream.push(" ", CONFIG.colors.asm.expr);
-                    stream.push(rr.as_str(), CONFIG.colors.asm.register);
+                    stream.push(" ", Kind::Expr);
+                    stream.push(rr.as_str(), Kind::Register);
                     if wback {
-                        stream.push("!", CONFIG.colors.asm.expr);
+                        stream.push("!", Kind::Expr);
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     format_reg_list(stream, list);
                     return;
                 }
@@ -2301,192 +2377,192 @@ impl ToTokens for Instruction {
                 }
             },
             Opcode::STCL(coproc) => {
-                stream.push("stcl ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("stcl ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::STC(coproc) => {
-                stream.push("stc ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("stc ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::STC2L(coproc) => {
-                stream.push("stc2l ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("stc2l ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::STC2(coproc) => {
-                stream.push("stc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("stc2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::LDC(coproc) => {
-                stream.push("ldc ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("ldc ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::LDCL(coproc) => {
-                stream.push("ldcl ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("ldcl ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::LDC2(coproc) => {
-                stream.push("ldc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("ldc2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::LDC2L(coproc) => {
-                stream.push("ldc2l ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
+                stream.push("ldc2l ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::MRRC2(coproc, opc) => {
-                stream.push("mrrc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc.to_string(), CONFIG.colors.asm.register);
+                stream.push("mrrc2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::MCRR2(coproc, opc) => {
-                stream.push("mcrr2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc.to_string(), CONFIG.colors.asm.register);
+                stream.push("mcrr2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
             }
             Opcode::MRC2(coproc, opc1, opc2) => {
-                stream.push("mrc2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push("mrc2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc1.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
 
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc2.to_string(), Kind::Register);
             }
             Opcode::MCR2(coproc, opc1, opc2) => {
-                stream.push("mcr2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push("mcr2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc1.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
 
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc2.to_string(), Kind::Register);
             }
             Opcode::CDP2(coproc, opc1, opc2) => {
-                stream.push("cdp2 ", CONFIG.colors.asm.opcode);
-                stream.push("p", CONFIG.colors.asm.register);
-                stream.push_owned(coproc.to_string(), CONFIG.colors.asm.register);
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc1.to_string(), CONFIG.colors.asm.register);
+                stream.push("cdp2 ", Kind::Mnemonic);
+                stream.push("p", Kind::Register);
+                stream.push_owned(coproc.to_string(), Kind::Register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc1.to_string(), Kind::Register);
                 let ops = self.operands.iter();
                 for op in ops {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, imm_override);
                 }
 
-                stream.push(", ", CONFIG.colors.asm.expr);
-                stream.push_owned(opc2.to_string(), CONFIG.colors.asm.register);
+                stream.push(", ", Kind::Expr);
+                stream.push_owned(opc2.to_string(), Kind::Register);
             }
             _ => {
                 ConditionedOpcode(self.opcode, self.s(), self.w(), self.condition)
@@ -2496,7 +2572,7 @@ impl ToTokens for Instruction {
                     if let Operand::Nothing = first_op {
                         return;
                     }
-                    stream.push(" ", CONFIG.colors.asm.expr);
+                    stream.push(" ", Kind::Expr);
                     first_op.tokenize(stream, symbols, None);
                 } else {
                     return;
@@ -2506,7 +2582,7 @@ impl ToTokens for Instruction {
                     if let Operand::Nothing = op {
                         break;
                     }
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     op.tokenize(stream, symbols, None);
                 }
             }
@@ -2825,13 +2901,18 @@ impl Decodable for Decoder {
 
     fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
         let mut inst = Instruction::default();
-        read(self, reader, &mut inst).map_err(|err| Error::new(err, 4))?;
+        let width = if self.thumb { 2 } else { 4 };
+        read(self, reader, &mut inst).map_err(|err| Error::new(err, width))?;
         Ok(inst)
     }
 
     fn max_width(&self) -> usize {
         4
     }
+
+    fn instruction_alignment(&self) -> usize {
+        if self.thumb { 2 } else { 4 }
+    }
 }
 
 #[inline(always)]
@@ -2916,8 +2997,10 @@ fn read(decoder: &Decoder, words: &mut Reader, inst: &mut Instruction) -> Result
                 0b01 => {
                     inst.opcode = Opcode::BLX;
                     let operand = ((word & 0xffffff) as i32) << 8 >> 7;
+                    // the + 4 is to compensate for an architecturally-defined initial offset
+                    let operand = (operand | (((word >> 24) & 0b1) as i32)) + 4;
                     inst.operands = [
-                        Operand::BranchThumbOffset(operand | (((word >> 24) & 0b1) as i32)),
+                        Operand::BranchThumbOffset(operand),
                         Operand::Nothing,
                         Operand::Nothing,
                         Operand::Nothing,
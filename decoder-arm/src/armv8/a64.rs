@@ -8,9 +8,8 @@
 use core::fmt::{self, Display, Formatter};
 
 use debugvault::Index;
-use decoder::{Decodable, Decoded, Error, ErrorKind, Reader, ToTokens};
-use tokenizing::TokenStream;
-use config::CONFIG;
+use decoder::{Decodable, Decoded, Description, Error, ErrorKind, Reader, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
 
 #[allow(non_snake_case)]
 mod docs {
@@ -247,16 +246,18 @@ impl Decoded for Instruction {
                 self.operands[1] = Operand::Imm64Special(addr);
             },
             Opcode::LDR => {
-                let mut a_imm = 0;
-                let mut a_reg = 0;
-                if let Some(Instruction { opcode: Opcode::ADRP | Opcode::ADR, operands }) = prev_inst {
-                    if let [Operand::Register(_, reg), Operand::Imm64Special(imm), ..] = operands {
-                        a_imm = *imm;
-                        a_reg = *reg;
-                    } else {
-                        return;
+                // Only loads relative to the page `adrp` or `adr` just computed are resolved.
+                let (a_imm, a_reg) = match prev_inst {
+                    Some(Instruction { opcode: Opcode::ADRP | Opcode::ADR, operands }) => {
+                        match operands {
+                            [Operand::Register(_, reg), Operand::Imm64Special(imm), ..] => {
+                                (*imm, *reg)
+                            }
+                            _ => return,
+                        }
                     }
-                }
+                    _ => return,
+                };
 
                 if let Operand::RegPreIndex(l_reg, off, _) = self.operands[1] {
                     if a_reg == l_reg {
@@ -275,6 +276,65 @@ impl Decoded for Instruction {
             }
         }
     }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.opcode {
+            Opcode::BL => XrefKind::Call,
+            Opcode::B
+            | Opcode::Bcc(_)
+            | Opcode::CBZ
+            | Opcode::CBNZ
+            | Opcode::TBZ
+            | Opcode::TBNZ => XrefKind::Jump,
+            // `adrp` only gives us a page, the load that follows it has the full address
+            Opcode::ADR | Opcode::LDR => XrefKind::Data,
+            _ => return None,
+        };
+
+        self.operands.iter().find_map(|operand| match *operand {
+            Operand::Imm64(addr) | Operand::Imm64Special(addr) => Some((addr as usize, kind)),
+            _ => None,
+        })
+    }
+
+    fn describe(&self, symbols: &Index) -> Option<Description> {
+        let operands = self
+            .operands
+            .iter()
+            .filter(|operand| **operand != Operand::Nothing)
+            .map(|operand| {
+                let mut stream = TokenStream::new();
+                operand.tokenize(&mut stream, symbols);
+                (stream.to_string(), operand.kind())
+            })
+            .collect();
+
+        // the first word shown, e.g. `dmb` of `dmb ish` or `b` of `b.ne`
+        let first_word = |text: String| {
+            let word = text.split([' ', '.']).next().unwrap_or_default();
+            word.to_string()
+        };
+
+        let mut stream = TokenStream::new();
+        self.tokenize(&mut stream, symbols);
+        let mnemonic = first_word(stream.to_string());
+
+        let mut description = Description::new(crate::reference::AARCH64, &mnemonic, operands);
+
+        let mut stream = TokenStream::new();
+        self.opcode.tokenize(&mut stream, symbols);
+        let encoded = first_word(stream.to_string());
+        if encoded != mnemonic {
+            description.notes.push(format!("Alias of {encoded}, whose operands are listed."));
+        }
+
+        if let Opcode::Bcc(cond) = self.opcode {
+            let condition = crate::reference::CONDITIONS[cond as usize];
+            description.notes.push(format!("Only branches if {condition}."));
+        }
+
+        Some(description)
+    }
 }
 
 impl Display for Instruction {
@@ -291,7 +351,7 @@ impl ToTokens for Instruction {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
         match self.opcode {
             Opcode::ISB => {
-                stream.push("isb", CONFIG.colors.asm.opcode);
+                stream.push("isb", Kind::Mnemonic);
 
                 // the default/reserved/expected value for the immediate in `isb` is `0b1111`.
                 if let Operand::Imm16(15) = self.operands[0] {
@@ -300,13 +360,13 @@ impl ToTokens for Instruction {
             }
             Opcode::SBC => {
                 if let Operand::Register(_, 31) = self.operands[1] {
-                    stream.push("ngc ", CONFIG.colors.asm.opcode);
+                    stream.push("ngc ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 } else {
-                    stream.push("sbc", CONFIG.colors.asm.opcode);
+                    stream.push("sbc", Kind::Mnemonic);
                 }
             }
             Opcode::MOVN => {
@@ -324,10 +384,10 @@ impl ToTokens for Instruction {
                 } else {
                     unreachable!("movn operand 0 is always Register");
                 };
-                stream.push("mov ", CONFIG.colors.asm.opcode);
+                stream.push("mov ", Kind::Mnemonic);
                 self.operands[0].tokenize(stream, symbols);
-                stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(imm), CONFIG.colors.asm.immediate);
+                stream.push(", #", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(imm), Kind::Immediate);
                 return;
             }
             Opcode::MOVZ => {
@@ -345,100 +405,100 @@ impl ToTokens for Instruction {
                 } else {
                     unreachable!("movn operand 0 is always Register");
                 };
-                stream.push("mov ", CONFIG.colors.asm.opcode);
+                stream.push("mov ", Kind::Mnemonic);
                 self.operands[0].tokenize(stream, symbols);
-                stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(imm), CONFIG.colors.asm.immediate);
+                stream.push(", #", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(imm), Kind::Immediate);
                 return;
             }
             Opcode::ORR => {
                 if let Operand::Register(_, 31) = self.operands[1] {
                     if let Operand::Immediate(0) = self.operands[2] {
-                        stream.push("mov ", CONFIG.colors.asm.opcode);
+                        stream.push("mov ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     } else if let Operand::RegShift(style, amt, size, r) = self.operands[2] {
                         if style == ShiftStyle::LSL && amt == 0 {
-                            stream.push("mov ", CONFIG.colors.asm.opcode);
+                            stream.push("mov ", Kind::Mnemonic);
                             self.operands[0].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             Operand::Register(size, r).tokenize(stream, symbols);
                             return;
                         }
                     } else {
-                        stream.push("mov ", CONFIG.colors.asm.opcode);
+                        stream.push("mov ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
                 } else if self.operands[1] == self.operands[2] {
-                    stream.push("mov ", CONFIG.colors.asm.opcode);
+                    stream.push("mov ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("orr", CONFIG.colors.asm.opcode);
+                stream.push("orr", Kind::Mnemonic);
             }
             Opcode::ORN => {
                 if let Operand::Register(_, 31) = self.operands[1] {
-                    stream.push("mvn ", CONFIG.colors.asm.opcode);
+                    stream.push("mvn ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("orn", CONFIG.colors.asm.opcode);
+                stream.push("orn", Kind::Mnemonic);
             }
             Opcode::ANDS => {
                 if let Operand::Register(_, 31) = self.operands[0] {
-                    stream.push("tst ", CONFIG.colors.asm.opcode);
+                    stream.push("tst ", Kind::Mnemonic);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("ands", CONFIG.colors.asm.opcode);
+                stream.push("ands", Kind::Mnemonic);
             }
             Opcode::NOT => {
                 // `This instruction is used by the alias MVN. The alias is always the preferred
                 // disassembly.`
-                stream.push("mvn", CONFIG.colors.asm.opcode);
+                stream.push("mvn", Kind::Mnemonic);
             }
             Opcode::ADDS => {
                 if let Operand::Register(_, 31) = self.operands[0] {
-                    stream.push("cmn ", CONFIG.colors.asm.opcode);
+                    stream.push("cmn ", Kind::Mnemonic);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 } else if let Operand::RegShift(ShiftStyle::LSL, 0, size, reg) = self.operands[2] {
-                    stream.push("adds ", CONFIG.colors.asm.opcode);
+                    stream.push("adds ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(size, reg).tokenize(stream, symbols);
                     return;
                 }
-                stream.push("adds", CONFIG.colors.asm.opcode);
+                stream.push("adds", Kind::Mnemonic);
             }
             Opcode::ADD => {
                 if let Operand::Immediate(0) = self.operands[2] {
                     if let Operand::RegisterOrSP(_, 31) = self.operands[0] {
-                        stream.push("mov ", CONFIG.colors.asm.opcode);
+                        stream.push("mov ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     }
                     if let Operand::RegisterOrSP(_, 31) = self.operands[1] {
-                        stream.push("mov ", CONFIG.colors.asm.opcode);
+                        stream.push("mov ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     }
@@ -446,67 +506,67 @@ impl ToTokens for Instruction {
                 //                } else if let Operand::Register(_, 31) = self.operands[1] {
                 //                    return write!(fmt, "mov {}, {}", self.operands[0], self.operands[2]);
                 } else if let Operand::RegShift(ShiftStyle::LSL, 0, size, reg) = self.operands[2] {
-                    stream.push("add ", CONFIG.colors.asm.opcode);
+                    stream.push("add ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(size, reg).tokenize(stream, symbols);
                     return;
                 }
-                stream.push("add", CONFIG.colors.asm.opcode);
+                stream.push("add", Kind::Mnemonic);
             }
             Opcode::SUBS => {
                 if let Operand::Register(_, 31) = self.operands[0] {
-                    stream.push("cmp ", CONFIG.colors.asm.opcode);
+                    stream.push("cmp ", Kind::Mnemonic);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 } else if let Operand::Register(_, 31) = self.operands[1] {
-                    stream.push("negs ", CONFIG.colors.asm.opcode);
+                    stream.push("negs ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 } else if let Operand::RegShift(ShiftStyle::LSL, 0, size, reg) = self.operands[2] {
-                    stream.push("subs ", CONFIG.colors.asm.opcode);
+                    stream.push("subs ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(size, reg).tokenize(stream, symbols);
                     return;
                 }
-                stream.push("subs", CONFIG.colors.asm.opcode);
+                stream.push("subs", Kind::Mnemonic);
             }
             Opcode::SUB => {
                 if let Operand::Register(_, 31) = self.operands[1] {
-                    stream.push("neg ", CONFIG.colors.asm.opcode);
+                    stream.push("neg ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 } else if let Operand::RegShift(ShiftStyle::LSL, 0, size, reg) = self.operands[2] {
-                    stream.push("sub ", CONFIG.colors.asm.opcode);
+                    stream.push("sub ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(size, reg).tokenize(stream, symbols);
                     return;
                 }
-                stream.push("sub", CONFIG.colors.asm.opcode);
+                stream.push("sub", Kind::Mnemonic);
             }
             Opcode::SBCS => {
                 if let Operand::Register(_, 31) = self.operands[1] {
-                    stream.push("ngcs ", CONFIG.colors.asm.opcode);
+                    stream.push("ngcs ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("sbcs", CONFIG.colors.asm.opcode);
+                stream.push("sbcs", Kind::Mnemonic);
             }
             Opcode::UBFM => {
                 // TODO: handle ubfx alias
@@ -517,15 +577,15 @@ impl ToTokens for Instruction {
                 ) = (self.operands[0], self.operands[1], self.operands[2])
                 {
                     if let Operand::Immediate(7) = self.operands[3] {
-                        stream.push("uxtb ", CONFIG.colors.asm.opcode);
+                        stream.push("uxtb ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     } else if let Operand::Immediate(15) = self.operands[3] {
-                        stream.push("uxth ", CONFIG.colors.asm.opcode);
+                        stream.push("uxth ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     }
@@ -538,11 +598,11 @@ impl ToTokens for Instruction {
                     };
                     match (imms, size) {
                         (63, SizeCode::X) | (31, SizeCode::W) => {
-                            stream.push("lsr ", CONFIG.colors.asm.opcode);
+                            stream.push("lsr ", Kind::Mnemonic);
                             self.operands[0].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             self.operands[1].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             self.operands[2].tokenize(stream, symbols);
                             return;
                         }
@@ -554,31 +614,31 @@ impl ToTokens for Instruction {
                                 unreachable!("operand 3 is a register");
                             };
                             if imms + 1 == immr {
-                                stream.push("lsl ", CONFIG.colors.asm.opcode);
+                                stream.push("lsl ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 self.operands[1].tokenize(stream, symbols);
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex((size - imms - 1) as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
                                 return;
                             }
                             if imms < immr {
-                                stream.push("ubfiz ", CONFIG.colors.asm.opcode);
+                                stream.push("ubfiz ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 self.operands[1].tokenize(stream, symbols);
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex((size - immr) as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex((imms + 1) as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
                                 return;
                             }
@@ -595,13 +655,13 @@ impl ToTokens for Instruction {
                     unreachable!("last two operands of ubfm are always immediates");
                 };
 
-                stream.push("ubfx ", CONFIG.colors.asm.opcode);
+                stream.push("ubfx ", Kind::Mnemonic);
                 self.operands[0].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[1].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[2].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 width.tokenize(stream, symbols);
                 return;
             }
@@ -618,33 +678,33 @@ impl ToTokens for Instruction {
                                 ((-(immr as i8)) as u8) & 0x3f
                             };
                             if rn == 31 {
-                                stream.push("bfc ", CONFIG.colors.asm.opcode);
+                                stream.push("bfc ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex(lsb as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex(width as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
                                 return;
                             } else {
-                                stream.push("bfi ", CONFIG.colors.asm.opcode);
+                                stream.push("bfi ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 self.operands[1].tokenize(stream, symbols);
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex(lsb as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
-                                stream.push(", #", CONFIG.colors.asm.expr);
+                                stream.push(", #", Kind::Expr);
                                 stream.push_owned(
                                     decoder::encode_uhex(width as u64),
-                                    CONFIG.colors.asm.immediate,
+                                    Kind::Immediate,
                                 );
                                 return;
                             }
@@ -654,14 +714,14 @@ impl ToTokens for Instruction {
                         let lsb = immr;
                         let width = imms + 1 - lsb;
 
-                        stream.push("bfxil ", CONFIG.colors.asm.opcode);
+                        stream.push("bfxil ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
-                        stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(lsb as u64), CONFIG.colors.asm.immediate);
-                        stream.push(", #", CONFIG.colors.asm.expr);
-                        stream.push_owned(decoder::encode_uhex(width as u64), CONFIG.colors.asm.immediate);
+                        stream.push(", #", Kind::Expr);
+                        stream.push_owned(decoder::encode_uhex(lsb as u64), Kind::Immediate);
+                        stream.push(", #", Kind::Expr);
+                        stream.push_owned(decoder::encode_uhex(width as u64), Kind::Immediate);
                         return;
                     }
                 }
@@ -669,22 +729,22 @@ impl ToTokens for Instruction {
             Opcode::SBFM => {
                 if let Operand::Immediate(63) = self.operands[3] {
                     if let Operand::Register(SizeCode::X, _) = self.operands[0] {
-                        stream.push("asr ", CONFIG.colors.asm.opcode);
+                        stream.push("asr ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
                 }
                 if let Operand::Immediate(31) = self.operands[3] {
                     if let Operand::Register(SizeCode::W, _) = self.operands[0] {
-                        stream.push("asr ", CONFIG.colors.asm.opcode);
+                        stream.push("asr ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -696,14 +756,14 @@ impl ToTokens for Instruction {
                         unreachable!("operand 1 is always a register");
                     };
                     if let Operand::Immediate(7) = self.operands[3] {
-                        stream.push("sxtb ", CONFIG.colors.asm.opcode);
+                        stream.push("sxtb ", Kind::Mnemonic);
                     } else if let Operand::Immediate(15) = self.operands[3] {
-                        stream.push("sxth ", CONFIG.colors.asm.opcode);
+                        stream.push("sxth ", Kind::Mnemonic);
                     } else if let Operand::Immediate(31) = self.operands[3] {
-                        stream.push("sxtw ", CONFIG.colors.asm.opcode);
+                        stream.push("sxtw ", Kind::Mnemonic);
                     }
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     newsrc.tokenize(stream, symbols);
                     return;
                 }
@@ -720,19 +780,19 @@ impl ToTokens for Instruction {
                         } else {
                             unreachable!("operand 0 is always a register");
                         };
-                        stream.push("sbfiz ", CONFIG.colors.asm.opcode);
+                        stream.push("sbfiz ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
-                        stream.push(", #", CONFIG.colors.asm.expr);
+                        stream.push(", #", Kind::Expr);
                         stream.push_owned(
                             decoder::encode_uhex((size - imms) as u64),
-                            CONFIG.colors.asm.immediate,
+                            Kind::Immediate,
                         );
-                        stream.push(", #", CONFIG.colors.asm.expr);
+                        stream.push(", #", Kind::Expr);
                         stream.push_owned(
                             decoder::encode_uhex((immr + 1) as u64),
-                            CONFIG.colors.asm.immediate,
+                            Kind::Immediate,
                         );
                         return;
                     }
@@ -746,13 +806,13 @@ impl ToTokens for Instruction {
                 } else {
                     unreachable!("last two operands of sbfm are always immediates");
                 };
-                stream.push("sbfx ", CONFIG.colors.asm.opcode);
+                stream.push("sbfx ", Kind::Mnemonic);
                 self.operands[0].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[1].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[2].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 width.tokenize(stream, symbols);
                 return;
             }
@@ -761,19 +821,19 @@ impl ToTokens for Instruction {
                     (self.operands[1], self.operands[2])
                 {
                     if Rn == Rm {
-                        stream.push("ror ", CONFIG.colors.asm.opcode);
+                        stream.push("ror ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[3].tokenize(stream, symbols);
                         return;
                     }
                 }
-                stream.push("extr", CONFIG.colors.asm.opcode);
+                stream.push("extr", Kind::Mnemonic);
             }
             Opcode::RET => {
-                stream.push("ret", CONFIG.colors.asm.opcode);
+                stream.push("ret", Kind::Mnemonic);
                 if let Operand::Register(SizeCode::X, 30) = self.operands[0] {
                     // C5.6.148:  Defaults to X30 if absent.
                     // so ret x30 is probably expected to be read as just `ret`
@@ -781,29 +841,29 @@ impl ToTokens for Instruction {
                 }
             }
             Opcode::SYS(ops) => {
-                stream.push("sys #", CONFIG.colors.asm.opcode);
-                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), CONFIG.colors.asm.immediate);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push("sys #", Kind::Mnemonic);
+                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), Kind::Immediate);
+                stream.push(", ", Kind::Expr);
                 self.operands[1].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[2].tokenize(stream, symbols);
-                stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), CONFIG.colors.asm.immediate);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", #", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), Kind::Immediate);
+                stream.push(", ", Kind::Expr);
                 self.operands[0].tokenize(stream, symbols);
                 return;
             }
             Opcode::SYSL(ops) => {
-                stream.push("sysl ", CONFIG.colors.asm.opcode);
+                stream.push("sysl ", Kind::Mnemonic);
                 self.operands[2].tokenize(stream, symbols);
-                stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), CONFIG.colors.asm.immediate);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", #", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(ops.op1() as u64), Kind::Immediate);
+                stream.push(", ", Kind::Expr);
                 self.operands[0].tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 self.operands[1].tokenize(stream, symbols);
-                stream.push(", #", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), CONFIG.colors.asm.immediate);
+                stream.push(", #", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(ops.op2() as u64), Kind::Immediate);
                 return;
             }
             Opcode::HINT => {
@@ -812,21 +872,21 @@ impl ToTokens for Instruction {
                 {
                     let hint_num = (CRn << 3) | op2 as u16;
                     match hint_num & 0b111111 {
-                        0x0 => stream.push("nop", CONFIG.colors.asm.opcode),
-                        0x1 => stream.push("yield", CONFIG.colors.asm.opcode),
-                        0x2 => stream.push("wfe", CONFIG.colors.asm.opcode),
-                        0x3 => stream.push("wfi", CONFIG.colors.asm.opcode),
-                        0x4 => stream.push("sev", CONFIG.colors.asm.opcode),
-                        0x10 => stream.push("esb", CONFIG.colors.asm.opcode),
-                        0x11 => stream.push("psb csync", CONFIG.colors.asm.opcode),
-                        0x12 => stream.push("tsb csync", CONFIG.colors.asm.opcode),
-                        0x14 => stream.push("csdb", CONFIG.colors.asm.opcode),
-                        0x15 => stream.push("sevl", CONFIG.colors.asm.opcode),
+                        0x0 => stream.push("nop", Kind::Mnemonic),
+                        0x1 => stream.push("yield", Kind::Mnemonic),
+                        0x2 => stream.push("wfe", Kind::Mnemonic),
+                        0x3 => stream.push("wfi", Kind::Mnemonic),
+                        0x4 => stream.push("sev", Kind::Mnemonic),
+                        0x10 => stream.push("esb", Kind::Mnemonic),
+                        0x11 => stream.push("psb csync", Kind::Mnemonic),
+                        0x12 => stream.push("tsb csync", Kind::Mnemonic),
+                        0x14 => stream.push("csdb", Kind::Mnemonic),
+                        0x15 => stream.push("sevl", Kind::Mnemonic),
                         _ => {
-                            stream.push("hint #", CONFIG.colors.asm.opcode);
+                            stream.push("hint #", Kind::Mnemonic);
                             stream.push_owned(
                                 decoder::encode_uhex(hint_num as u64),
-                                CONFIG.colors.asm.immediate,
+                                Kind::Immediate,
                             );
                         }
                     };
@@ -841,18 +901,18 @@ impl ToTokens for Instruction {
                 ) = (self.operands[1], self.operands[2], self.operands[3])
                 {
                     if cond < 0b1110 && rn == rm {
-                        stream.push("cneg ", CONFIG.colors.asm.opcode);
+                        stream.push("cneg ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         Operand::ConditionCode(cond ^ 0x01).tokenize(stream, symbols);
                         return;
                     }
                 } else {
                     unreachable!("operands 2 and 3 are always registers");
                 }
-                stream.push("csneg", CONFIG.colors.asm.opcode);
+                stream.push("csneg", Kind::Mnemonic);
             }
             Opcode::CSINC => {
                 match (self.operands[1], self.operands[2], self.operands[3]) {
@@ -863,17 +923,17 @@ impl ToTokens for Instruction {
                     ) => {
                         if n == m && cond < 0b1110 {
                             if n == 31 {
-                                stream.push("cset ", CONFIG.colors.asm.opcode);
+                                stream.push("cset ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 Operand::ConditionCode(cond ^ 0x01).tokenize(stream, symbols);
                                 return;
                             } else {
-                                stream.push("cinc ", CONFIG.colors.asm.opcode);
+                                stream.push("cinc ", Kind::Mnemonic);
                                 self.operands[0].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 self.operands[1].tokenize(stream, symbols);
-                                stream.push(", ", CONFIG.colors.asm.expr);
+                                stream.push(", ", Kind::Expr);
                                 Operand::ConditionCode(cond ^ 0x01).tokenize(stream, symbols);
                                 return;
                             }
@@ -881,7 +941,7 @@ impl ToTokens for Instruction {
                     }
                     _ => {}
                 }
-                stream.push("csinc", CONFIG.colors.asm.opcode);
+                stream.push("csinc", Kind::Mnemonic);
             }
             Opcode::CSINV => {
                 match (self.operands[1], self.operands[2], self.operands[3]) {
@@ -891,131 +951,131 @@ impl ToTokens for Instruction {
                         Operand::ConditionCode(cond),
                     ) => {
                         if n == m && n != 31 && cond < 0b1110 {
-                            stream.push("cinv ", CONFIG.colors.asm.opcode);
+                            stream.push("cinv ", Kind::Mnemonic);
                             self.operands[0].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             self.operands[1].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             Operand::ConditionCode(cond ^ 0x01).tokenize(stream, symbols);
                             return;
                         } else if n == m && n == 31 && cond < 0b1110 {
-                            stream.push("csetm ", CONFIG.colors.asm.opcode);
+                            stream.push("csetm ", Kind::Mnemonic);
                             self.operands[0].tokenize(stream, symbols);
-                            stream.push(", ", CONFIG.colors.asm.expr);
+                            stream.push(", ", Kind::Expr);
                             Operand::ConditionCode(cond ^ 0x01).tokenize(stream, symbols);
                             return;
                         }
                     }
                     _ => {}
                 }
-                stream.push("csinv", CONFIG.colors.asm.opcode);
+                stream.push("csinv", Kind::Mnemonic);
             }
             Opcode::MADD => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("mul ", CONFIG.colors.asm.opcode);
+                    stream.push("mul ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("madd", CONFIG.colors.asm.opcode);
+                stream.push("madd", Kind::Mnemonic);
             }
             Opcode::MSUB => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("mneg ", CONFIG.colors.asm.opcode);
+                    stream.push("mneg ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("msub", CONFIG.colors.asm.opcode);
+                stream.push("msub", Kind::Mnemonic);
             }
             Opcode::SMADDL => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("smull ", CONFIG.colors.asm.opcode);
+                    stream.push("smull ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("smaddl", CONFIG.colors.asm.opcode);
+                stream.push("smaddl", Kind::Mnemonic);
             }
             Opcode::SMSUBL => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("smnegl ", CONFIG.colors.asm.opcode);
+                    stream.push("smnegl ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("smsubl", CONFIG.colors.asm.opcode);
+                stream.push("smsubl", Kind::Mnemonic);
             }
             Opcode::UMADDL => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("umull ", CONFIG.colors.asm.opcode);
+                    stream.push("umull ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("umaddl", CONFIG.colors.asm.opcode);
+                stream.push("umaddl", Kind::Mnemonic);
             }
             Opcode::UMSUBL => {
                 if let Operand::Register(_, 31) = self.operands[3] {
-                    stream.push("umnegl ", CONFIG.colors.asm.opcode);
+                    stream.push("umnegl ", Kind::Mnemonic);
                     self.operands[0].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[1].tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     self.operands[2].tokenize(stream, symbols);
                     return;
                 }
-                stream.push("umsubl", CONFIG.colors.asm.opcode);
+                stream.push("umsubl", Kind::Mnemonic);
             }
             Opcode::LSLV => {
                 // lslv == lsl (register) and, quoth the manual, `lsl is always the preferred
                 // disassembly`.
-                stream.push("lsl", CONFIG.colors.asm.opcode);
+                stream.push("lsl", Kind::Mnemonic);
             }
             Opcode::LSRV => {
                 // lsrv == lsr (register) and, quoth the manual, `lsr is always the preferred
                 // disassembly`.
-                stream.push("lsr", CONFIG.colors.asm.opcode);
+                stream.push("lsr", Kind::Mnemonic);
             }
             Opcode::ASRV => {
                 // asrv == asr (register) and, quoth the manual, `asr is always the preferred
                 // disassembly`.
-                stream.push("asr", CONFIG.colors.asm.opcode);
+                stream.push("asr", Kind::Mnemonic);
             }
             Opcode::RORV => {
                 // rorv == ror (register) and, quoth the manual, `ror is always the preferred
                 // disassembly`.
-                stream.push("ror", CONFIG.colors.asm.opcode);
+                stream.push("ror", Kind::Mnemonic);
             }
             Opcode::INS => {
                 // `ins (element)` and `ins (general)` both have `mov` as an alias. manual reports
                 // that `mov` is the preferred disassembly.
-                stream.push("mov", CONFIG.colors.asm.opcode);
+                stream.push("mov", Kind::Mnemonic);
             }
             Opcode::DUP => {
                 if let Operand::Register(_, _) = self.operands[1] {
                     // `dup (general)`
-                    stream.push("dup", CONFIG.colors.asm.opcode);
+                    stream.push("dup", Kind::Mnemonic);
                 } else {
                     // `dup (element)`
                     // manual says `mov` is the preferred disassembly here? but capstone uses
                     // `dup`.
-                    stream.push("mov", CONFIG.colors.asm.opcode);
+                    stream.push("mov", Kind::Mnemonic);
                 }
             }
             Opcode::UMOV => {
@@ -1027,13 +1087,13 @@ impl ToTokens for Instruction {
                     if (reg_sz == SizeCode::W && elem_sz == SIMDSizeCode::S)
                         || (reg_sz == SizeCode::X && elem_sz == SIMDSizeCode::D)
                     {
-                        stream.push("mov ", CONFIG.colors.asm.opcode);
+                        stream.push("mov ", Kind::Mnemonic);
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[1].tokenize(stream, symbols);
                         return;
                     } else {
-                        stream.push("umov", CONFIG.colors.asm.opcode);
+                        stream.push("umov", Kind::Mnemonic);
                     }
                 }
             }
@@ -1041,12 +1101,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stadd ", CONFIG.colors.asm.opcode);
+                            stream.push("stadd ", Kind::Mnemonic);
                         } else {
-                            stream.push("staddl ", CONFIG.colors.asm.opcode);
+                            stream.push("staddl ", Kind::Mnemonic);
                         }
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1057,12 +1117,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stclr ", CONFIG.colors.asm.opcode);
+                            stream.push("stclr ", Kind::Mnemonic);
                         } else {
-                            stream.push("stclrl ", CONFIG.colors.asm.opcode);
+                            stream.push("stclrl ", Kind::Mnemonic);
                         }
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1073,12 +1133,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stset ", CONFIG.colors.asm.opcode);
+                            stream.push("stset ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsetl ", CONFIG.colors.asm.opcode);
+                            stream.push("stsetl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1089,12 +1149,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsmax ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmax ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsmaxl ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmaxl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1105,12 +1165,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsmin ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmin ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsminl ", CONFIG.colors.asm.opcode);
+                            stream.push("stsminl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1121,12 +1181,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stumax ", CONFIG.colors.asm.opcode);
+                            stream.push("stumax ", Kind::Mnemonic);
                         } else {
-                            stream.push("stumaxl ", CONFIG.colors.asm.opcode);
+                            stream.push("stumaxl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1137,12 +1197,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stumin ", CONFIG.colors.asm.opcode);
+                            stream.push("stumin ", Kind::Mnemonic);
                         } else {
-                            stream.push("stuminl ", CONFIG.colors.asm.opcode);
+                            stream.push("stuminl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1153,12 +1213,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("steor ", CONFIG.colors.asm.opcode);
+                            stream.push("steor ", Kind::Mnemonic);
                         } else {
-                            stream.push("steorl ", CONFIG.colors.asm.opcode);
+                            stream.push("steorl ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1169,12 +1229,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("staddh ", CONFIG.colors.asm.opcode);
+                            stream.push("staddh ", Kind::Mnemonic);
                         } else {
-                            stream.push("staddlh ", CONFIG.colors.asm.opcode);
+                            stream.push("staddlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1185,12 +1245,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stclrh ", CONFIG.colors.asm.opcode);
+                            stream.push("stclrh ", Kind::Mnemonic);
                         } else {
-                            stream.push("stclrlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stclrlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1201,12 +1261,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stseth ", CONFIG.colors.asm.opcode);
+                            stream.push("stseth ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsetlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stsetlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1217,12 +1277,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsmaxh ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmaxh ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsmaxlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmaxlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1233,12 +1293,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsminh ", CONFIG.colors.asm.opcode);
+                            stream.push("stsminh ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsminlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stsminlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1249,12 +1309,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stumaxh ", CONFIG.colors.asm.opcode);
+                            stream.push("stumaxh ", Kind::Mnemonic);
                         } else {
-                            stream.push("stumaxlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stumaxlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1265,12 +1325,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stuminh ", CONFIG.colors.asm.opcode);
+                            stream.push("stuminh ", Kind::Mnemonic);
                         } else {
-                            stream.push("stuminlh ", CONFIG.colors.asm.opcode);
+                            stream.push("stuminlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1281,12 +1341,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("steorh ", CONFIG.colors.asm.opcode);
+                            stream.push("steorh ", Kind::Mnemonic);
                         } else {
-                            stream.push("steorlh ", CONFIG.colors.asm.opcode);
+                            stream.push("steorlh ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1297,12 +1357,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("staddb ", CONFIG.colors.asm.opcode);
+                            stream.push("staddb ", Kind::Mnemonic);
                         } else {
-                            stream.push("staddlb ", CONFIG.colors.asm.opcode);
+                            stream.push("staddlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1313,12 +1373,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stclrb ", CONFIG.colors.asm.opcode);
+                            stream.push("stclrb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stclrlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stclrlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1329,12 +1389,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsetb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsetb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsetlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsetlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1345,12 +1405,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsmaxb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmaxb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsmaxlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsmaxlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1361,12 +1421,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stsminb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsminb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stsminlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stsminlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1377,12 +1437,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stumaxb ", CONFIG.colors.asm.opcode);
+                            stream.push("stumaxb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stumaxlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stumaxlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1393,12 +1453,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("stuminb ", CONFIG.colors.asm.opcode);
+                            stream.push("stuminb ", Kind::Mnemonic);
                         } else {
-                            stream.push("stuminlb ", CONFIG.colors.asm.opcode);
+                            stream.push("stuminlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1409,12 +1469,12 @@ impl ToTokens for Instruction {
                 if let Operand::Register(_, rt) = self.operands[1] {
                     if rt == 31 && ar & 0b10 == 0b00 {
                         if ar & 0b01 == 0b00 {
-                            stream.push("steorb ", CONFIG.colors.asm.opcode);
+                            stream.push("steorb ", Kind::Mnemonic);
                         } else {
-                            stream.push("steorlb ", CONFIG.colors.asm.opcode);
+                            stream.push("steorlb ", Kind::Mnemonic);
                         };
                         self.operands[0].tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
+                        stream.push(", ", Kind::Expr);
                         self.operands[2].tokenize(stream, symbols);
                         return;
                     }
@@ -1425,28 +1485,28 @@ impl ToTokens for Instruction {
         };
 
         if self.operands[0] != Operand::Nothing {
-            stream.push(" ", CONFIG.colors.asm.expr);
+            stream.push(" ", Kind::Expr);
             self.operands[0].tokenize(stream, symbols);
         } else {
             return;
         }
 
         if self.operands[1] != Operand::Nothing {
-            stream.push(", ", CONFIG.colors.asm.expr);
+            stream.push(", ", Kind::Expr);
             self.operands[1].tokenize(stream, symbols);
         } else {
             return;
         }
 
         if self.operands[2] != Operand::Nothing {
-            stream.push(", ", CONFIG.colors.asm.expr);
+            stream.push(", ", Kind::Expr);
             self.operands[2].tokenize(stream, symbols);
         } else {
             return;
         }
 
         if self.operands[3] != Operand::Nothing {
-            stream.push(", ", CONFIG.colors.asm.expr);
+            stream.push(", ", Kind::Expr);
             self.operands[3].tokenize(stream, symbols);
         } else {
             return;
@@ -2919,42 +2979,42 @@ impl ToTokens for Opcode {
             Opcode::SUBPS => "subps",
 
             Opcode::Bcc(cond) => {
-                stream.push("b.", CONFIG.colors.asm.opcode);
+                stream.push("b.", Kind::Mnemonic);
                 Operand::ConditionCode(cond).tokenize(stream, symbols);
                 return;
             }
             Opcode::DMB(option) => {
                 return match option {
-                    0b0001 => stream.push("dmb oshld", CONFIG.colors.asm.opcode),
-                    0b0010 => stream.push("dmb oshst", CONFIG.colors.asm.opcode),
-                    0b0011 => stream.push("dmb osh", CONFIG.colors.asm.opcode),
-                    0b0101 => stream.push("dmb nshld", CONFIG.colors.asm.opcode),
-                    0b0110 => stream.push("dmb nshst", CONFIG.colors.asm.opcode),
-                    0b0111 => stream.push("dmb nsh", CONFIG.colors.asm.opcode),
-                    0b1001 => stream.push("dmb ishld", CONFIG.colors.asm.opcode),
-                    0b1010 => stream.push("dmb ishst", CONFIG.colors.asm.opcode),
-                    0b1011 => stream.push("dmb ish", CONFIG.colors.asm.opcode),
-                    0b1101 => stream.push("dmb ld", CONFIG.colors.asm.opcode),
-                    0b1110 => stream.push("dmb st", CONFIG.colors.asm.opcode),
-                    0b1111 => stream.push("dmb sy", CONFIG.colors.asm.opcode),
-                    _ => stream.push_owned(format!("dmb #{option}"), CONFIG.colors.asm.opcode),
+                    0b0001 => stream.push("dmb oshld", Kind::Mnemonic),
+                    0b0010 => stream.push("dmb oshst", Kind::Mnemonic),
+                    0b0011 => stream.push("dmb osh", Kind::Mnemonic),
+                    0b0101 => stream.push("dmb nshld", Kind::Mnemonic),
+                    0b0110 => stream.push("dmb nshst", Kind::Mnemonic),
+                    0b0111 => stream.push("dmb nsh", Kind::Mnemonic),
+                    0b1001 => stream.push("dmb ishld", Kind::Mnemonic),
+                    0b1010 => stream.push("dmb ishst", Kind::Mnemonic),
+                    0b1011 => stream.push("dmb ish", Kind::Mnemonic),
+                    0b1101 => stream.push("dmb ld", Kind::Mnemonic),
+                    0b1110 => stream.push("dmb st", Kind::Mnemonic),
+                    0b1111 => stream.push("dmb sy", Kind::Mnemonic),
+                    _ => stream.push_owned(format!("dmb #{option}"), Kind::Mnemonic),
                 };
             }
             Opcode::DSB(option) => {
                 return match option {
-                    0b0001 => stream.push("dsb oshld", CONFIG.colors.asm.opcode),
-                    0b0010 => stream.push("dsb oshst", CONFIG.colors.asm.opcode),
-                    0b0011 => stream.push("dsb osh", CONFIG.colors.asm.opcode),
-                    0b0101 => stream.push("dsb nshld", CONFIG.colors.asm.opcode),
-                    0b0110 => stream.push("dsb nshst", CONFIG.colors.asm.opcode),
-                    0b0111 => stream.push("dsb nsh", CONFIG.colors.asm.opcode),
-                    0b1001 => stream.push("dsb ishld", CONFIG.colors.asm.opcode),
-                    0b1010 => stream.push("dsb ishst", CONFIG.colors.asm.opcode),
-                    0b1011 => stream.push("dsb ish", CONFIG.colors.asm.opcode),
-                    0b1101 => stream.push("dsb ld", CONFIG.colors.asm.opcode),
-                    0b1110 => stream.push("dsb st", CONFIG.colors.asm.opcode),
-                    0b1111 => stream.push("dsb sy", CONFIG.colors.asm.opcode),
-                    _ => stream.push_owned(format!("dsb #{option}"), CONFIG.colors.asm.opcode),
+                    0b0001 => stream.push("dsb oshld", Kind::Mnemonic),
+                    0b0010 => stream.push("dsb oshst", Kind::Mnemonic),
+                    0b0011 => stream.push("dsb osh", Kind::Mnemonic),
+                    0b0101 => stream.push("dsb nshld", Kind::Mnemonic),
+                    0b0110 => stream.push("dsb nshst", Kind::Mnemonic),
+                    0b0111 => stream.push("dsb nsh", Kind::Mnemonic),
+                    0b1001 => stream.push("dsb ishld", Kind::Mnemonic),
+                    0b1010 => stream.push("dsb ishst", Kind::Mnemonic),
+                    0b1011 => stream.push("dsb ish", Kind::Mnemonic),
+                    0b1101 => stream.push("dsb ld", Kind::Mnemonic),
+                    0b1110 => stream.push("dsb st", Kind::Mnemonic),
+                    0b1111 => stream.push("dsb sy", Kind::Mnemonic),
+                    _ => stream.push_owned(format!("dsb #{option}"), Kind::Mnemonic),
                 };
             }
             Opcode::HINT => "hint",
@@ -3004,7 +3064,7 @@ impl ToTokens for Opcode {
             }
         };
 
-        stream.push(text, CONFIG.colors.asm.opcode);
+        stream.push(text, Kind::Mnemonic);
     }
 }
 
@@ -3015,7 +3075,7 @@ fn format_register_32(stream: &mut TokenStream, reg: u16) {
         "w27", "w28", "w29", "w30", "w31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_64(stream: &mut TokenStream, reg: u16) {
@@ -3025,7 +3085,7 @@ fn format_register_64(stream: &mut TokenStream, reg: u16) {
         "x27", "x28", "x29", "x30", "x31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_b(stream: &mut TokenStream, reg: u16) {
@@ -3035,7 +3095,7 @@ fn format_register_b(stream: &mut TokenStream, reg: u16) {
         "b27", "b28", "b29", "b30", "b31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_h(stream: &mut TokenStream, reg: u16) {
@@ -3045,7 +3105,7 @@ fn format_register_h(stream: &mut TokenStream, reg: u16) {
         "h27", "h28", "h29", "h30", "h31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_s(stream: &mut TokenStream, reg: u16) {
@@ -3055,7 +3115,7 @@ fn format_register_s(stream: &mut TokenStream, reg: u16) {
         "s27", "s28", "s29", "s30", "s31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_d(stream: &mut TokenStream, reg: u16) {
@@ -3065,7 +3125,7 @@ fn format_register_d(stream: &mut TokenStream, reg: u16) {
         "d27", "d28", "d29", "d30", "d31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_q(stream: &mut TokenStream, reg: u16) {
@@ -3075,7 +3135,7 @@ fn format_register_q(stream: &mut TokenStream, reg: u16) {
         "q27", "q28", "q29", "q30", "q31",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 fn format_register_ctrl(stream: &mut TokenStream, reg: u16) {
@@ -3084,7 +3144,7 @@ fn format_register_ctrl(stream: &mut TokenStream, reg: u16) {
         "c14", "c15",
     ];
 
-    stream.push(LOOKUP[reg as usize], CONFIG.colors.asm.register);
+    stream.push(LOOKUP[reg as usize], Kind::Register);
 }
 
 /// the way a shift operation is carried out.
@@ -3322,6 +3382,53 @@ impl Operand {
             _ => unreachable!(),
         }
     }
+
+    /// What kind of operand this is, e.g. `64-bit register`.
+    fn kind(&self) -> String {
+        let kind = match self {
+            Operand::Register(_, 31) => "zero register",
+            Operand::Register(SizeCode::X, _) | Operand::RegisterPair(SizeCode::X, _) => {
+                "64-bit register"
+            }
+            Operand::Register(SizeCode::W, _) | Operand::RegisterPair(SizeCode::W, _) => {
+                "32-bit register"
+            }
+            Operand::RegisterOrSP(_, 31) => "stack pointer",
+            Operand::RegisterOrSP(SizeCode::X, _) => "64-bit register",
+            Operand::RegisterOrSP(SizeCode::W, _) => "32-bit register",
+            Operand::SIMDRegister(..) => "SIMD register",
+            Operand::SIMDRegisterElements(..) => "SIMD vector",
+            Operand::SIMDRegisterElementsLane(..)
+            | Operand::SIMDRegisterElementsMultipleLane(..) => "SIMD vector lane",
+            Operand::SIMDRegisterGroup(..) => "SIMD register group",
+            Operand::SIMDRegisterGroupLane(..) => "SIMD register group lane",
+            Operand::ConditionCode(cond) => {
+                return format!("condition, {}", crate::reference::CONDITIONS[*cond as usize]);
+            }
+            Operand::PCOffset(_) => "pc-relative offset",
+            Operand::Immediate(_) | Operand::Imm16(_) => "immediate",
+            Operand::Imm64(_) | Operand::Imm64Special(_) => "address",
+            Operand::ImmediateDouble(_) => "floating-point immediate",
+            Operand::ImmShift(..) | Operand::ImmShiftMSL(..) => "shifted immediate",
+            Operand::RegShift(..) => "shifted register",
+            Operand::RegRegOffset(..) => "memory at a register plus a shifted register",
+            Operand::RegPreIndex(_, 0, false) => "memory at a register",
+            Operand::RegPreIndex(_, _, false) => "memory at a register plus an offset",
+            Operand::RegPreIndex(_, _, true) => {
+                "memory at a register plus an offset, written back"
+            }
+            Operand::RegPostIndex(..) | Operand::RegPostIndexReg(..) => {
+                "memory at a register, adding an offset to it afterwards"
+            }
+            Operand::PrefetchOp(_) => "prefetch operation",
+            Operand::SystemReg(_) => "system register",
+            Operand::ControlReg(_) => "control register",
+            Operand::PstateField(_) => "pstate field",
+            Operand::Nothing => "nothing",
+        };
+
+        kind.to_string()
+    }
 }
 
 impl ToTokens for Operand {
@@ -3331,10 +3438,10 @@ impl ToTokens for Operand {
                 if *reg == 31 {
                     match size {
                         SizeCode::X => {
-                            stream.push("xzr", CONFIG.colors.asm.register);
+                            stream.push("xzr", Kind::Register);
                         }
                         SizeCode::W => {
-                            stream.push("wzr", CONFIG.colors.asm.register);
+                            stream.push("wzr", Kind::Register);
                         }
                     }
                 } else {
@@ -3350,7 +3457,7 @@ impl ToTokens for Operand {
             }
             Operand::RegisterPair(size, reg) => {
                 Operand::Register(*size, *reg).tokenize(stream, symbols);
-                stream.push(", ", CONFIG.colors.asm.expr);
+                stream.push(", ", Kind::Expr);
                 Operand::Register(*size, *reg + 1).tokenize(stream, symbols);
             }
             Operand::ControlReg(reg) => {
@@ -3362,7 +3469,7 @@ impl ToTokens for Operand {
                 let policy = op & 1;
 
                 if ty == 0b11 || target == 0b11 {
-                    stream.push_owned(format!("{:#02x}", op), CONFIG.colors.asm.immediate);
+                    stream.push_owned(format!("{:#02x}", op), Kind::Immediate);
                 } else {
                     let op = format!(
                         "{}{}{}",
@@ -3370,15 +3477,15 @@ impl ToTokens for Operand {
                         ["l1", "l2", "l3"][target as usize],
                         ["keep", "strm"][policy as usize],
                     );
-                    stream.push_owned(op, CONFIG.colors.asm.opcode);
+                    stream.push_owned(op, Kind::Mnemonic);
                 }
             }
             Operand::SystemReg(reg) => {
                 // TODO: look up system register names better
                 match reg {
-                    0x4000 => stream.push("midr_el1", CONFIG.colors.asm.register),
-                    0x5e82 => stream.push("tpidr_el0", CONFIG.colors.asm.register),
-                    0x5f02 => stream.push("cntvct_el0", CONFIG.colors.asm.register),
+                    0x4000 => stream.push("midr_el1", Kind::Register),
+                    0x5e82 => stream.push("tpidr_el0", Kind::Register),
+                    0x5f02 => stream.push("cntvct_el0", Kind::Register),
                     _ => {
                         // syntax for otherwise-undescribed system register names is described in
                         // MRS or similar, S<op0>_<op1>_<Cn>_<Cm>_<op2>
@@ -3389,22 +3496,22 @@ impl ToTokens for Operand {
                         let op1 = (reg >> 11) & 0b111;
                         let op0 = ((reg >> 14) & 0b1) + 2;
 
-                        stream.push_owned(format!("s{op0}"), CONFIG.colors.asm.immediate);
-                        stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{op1}"), CONFIG.colors.asm.immediate);
-                        stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("c{CRn}"), CONFIG.colors.asm.register);
-                        stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("c{CRm}"), CONFIG.colors.asm.register);
-                        stream.push("_", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{op2}"), CONFIG.colors.asm.immediate);
+                        stream.push_owned(format!("s{op0}"), Kind::Immediate);
+                        stream.push("_", Kind::Expr);
+                        stream.push_owned(format!("{op1}"), Kind::Immediate);
+                        stream.push("_", Kind::Expr);
+                        stream.push_owned(format!("c{CRn}"), Kind::Register);
+                        stream.push("_", Kind::Expr);
+                        stream.push_owned(format!("c{CRm}"), Kind::Register);
+                        stream.push("_", Kind::Expr);
+                        stream.push_owned(format!("{op2}"), Kind::Immediate);
                     }
                 }
             }
             Operand::PstateField(reg) => {
                 // `MSR (immediate)` writes to the `PSTATE` registers, setting a few bit patterns as
                 // selected by `reg`.
-                stream.push_owned(format!("pstate.{:#x}", reg), CONFIG.colors.asm.register);
+                stream.push_owned(format!("pstate.{:#x}", reg), Kind::Register);
             }
             Operand::SIMDRegister(size, reg) => match size {
                 SIMDSizeCode::B => format_register_b(stream, *reg),
@@ -3416,11 +3523,11 @@ impl ToTokens for Operand {
             Operand::SIMDRegisterElements(vector_width, reg, lane_width) => {
                 let num_items = vector_width.width() / lane_width.width();
                 let op = format!("v{}.{}{}", reg, num_items, lane_width.name());
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned(op, Kind::Register);
             }
             Operand::SIMDRegisterElementsLane(_vector_width, reg, lane_width, lane) => {
                 let op = format!("v{}.{}[{}]", reg, lane_width.name(), lane);
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned(op, Kind::Register);
             }
             Operand::SIMDRegisterElementsMultipleLane(
                 _vector_width,
@@ -3430,47 +3537,47 @@ impl ToTokens for Operand {
                 num_lanes,
             ) => {
                 let op = format!("v{}.{}{}[{}]", reg, num_lanes, lane_width.name(), lane);
-                stream.push_owned(op, CONFIG.colors.asm.register);
+                stream.push_owned(op, Kind::Register);
             }
             Operand::SIMDRegisterGroup(vector_width, reg, lane_width, group_size) => {
                 let num_items = vector_width.width() / lane_width.width();
                 let format_reg = |stream: &mut TokenStream, reg, elems, lane_size: SIMDSizeCode| {
                     let op = format!("v{}.{}{}", reg, elems, lane_size.name());
-                    stream.push_owned(op, CONFIG.colors.asm.register);
+                    stream.push_owned(op, Kind::Register);
                 };
 
-                stream.push("{", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
                 format_reg(stream, *reg, num_items, *lane_width);
                 for i in 1..*group_size {
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     format_reg(stream, (*reg + i as u16) % 32, num_items, *lane_width);
                 }
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("}", Kind::Bracket);
             }
             Operand::SIMDRegisterGroupLane(reg, lane_width, group_size, lane) => {
                 let format_reg = |stream: &mut TokenStream, reg, lane_size: SIMDSizeCode| {
                     let op = format!("v{}.{}", reg, lane_size.name());
-                    stream.push_owned(op, CONFIG.colors.asm.register);
+                    stream.push_owned(op, Kind::Register);
                 };
 
-                stream.push("{", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
                 format_reg(stream, *reg, *lane_width);
                 for i in 1..*group_size {
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     format_reg(stream, (*reg + i as u16) % 32, *lane_width);
                 }
-                stream.push("}[", CONFIG.colors.brackets);
-                stream.push_owned(lane.to_string(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("}[", Kind::Bracket);
+                stream.push_owned(lane.to_string(), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegisterOrSP(size, reg) => {
                 if *reg == 31 {
                     match size {
                         SizeCode::X => {
-                            stream.push("sp", CONFIG.colors.asm.register);
+                            stream.push("sp", Kind::Register);
                         }
                         SizeCode::W => {
-                            stream.push("wsp", CONFIG.colors.asm.register);
+                            stream.push("wsp", Kind::Register);
                         }
                     }
                 } else {
@@ -3486,108 +3593,106 @@ impl ToTokens for Operand {
             }
             Operand::ConditionCode(cond) => {
                 match cond {
-                    0b0000 => stream.push("eq", CONFIG.colors.asm.opcode),
-                    0b0010 => stream.push("hs", CONFIG.colors.asm.opcode),
-                    0b0100 => stream.push("mi", CONFIG.colors.asm.opcode),
-                    0b0110 => stream.push("vs", CONFIG.colors.asm.opcode),
-                    0b1000 => stream.push("hi", CONFIG.colors.asm.opcode),
-                    0b1010 => stream.push("ge", CONFIG.colors.asm.opcode),
-                    0b1100 => stream.push("gt", CONFIG.colors.asm.opcode),
-                    0b1110 => stream.push("al", CONFIG.colors.asm.opcode),
-                    0b0001 => stream.push("ne", CONFIG.colors.asm.opcode),
-                    0b0011 => stream.push("lo", CONFIG.colors.asm.opcode),
-                    0b0101 => stream.push("pl", CONFIG.colors.asm.opcode),
-                    0b0111 => stream.push("vc", CONFIG.colors.asm.opcode),
-                    0b1001 => stream.push("ls", CONFIG.colors.asm.opcode),
-                    0b1011 => stream.push("lt", CONFIG.colors.asm.opcode),
-                    0b1101 => stream.push("le", CONFIG.colors.asm.opcode),
+                    0b0000 => stream.push("eq", Kind::Mnemonic),
+                    0b0010 => stream.push("hs", Kind::Mnemonic),
+                    0b0100 => stream.push("mi", Kind::Mnemonic),
+                    0b0110 => stream.push("vs", Kind::Mnemonic),
+                    0b1000 => stream.push("hi", Kind::Mnemonic),
+                    0b1010 => stream.push("ge", Kind::Mnemonic),
+                    0b1100 => stream.push("gt", Kind::Mnemonic),
+                    0b1110 => stream.push("al", Kind::Mnemonic),
+                    0b0001 => stream.push("ne", Kind::Mnemonic),
+                    0b0011 => stream.push("lo", Kind::Mnemonic),
+                    0b0101 => stream.push("pl", Kind::Mnemonic),
+                    0b0111 => stream.push("vc", Kind::Mnemonic),
+                    0b1001 => stream.push("ls", Kind::Mnemonic),
+                    0b1011 => stream.push("lt", Kind::Mnemonic),
+                    0b1101 => stream.push("le", Kind::Mnemonic),
                     // `The Condition code NV exists only to provide a valid disassembly of the
                     // 0b1111 encoding, otherwise its behavior is identical to AL`.
-                    0b1111 => stream.push("nv", CONFIG.colors.asm.opcode),
+                    0b1111 => stream.push("nv", Kind::Mnemonic),
                     _ => unreachable!(),
                 }
             }
             Operand::PCOffset(offs) => {
                 if *offs >= 0 {
-                    stream.push("$", CONFIG.colors.asm.expr);
-                    stream.push("+", CONFIG.colors.asm.immediate);
+                    stream.push("$", Kind::Expr);
+                    stream.push("+", Kind::Immediate);
                 } else {
-                    stream.push("$", CONFIG.colors.asm.expr);
+                    stream.push("$", Kind::Expr);
                 }
-                stream.push_owned(decoder::encode_hex(*offs), CONFIG.colors.asm.immediate);
+                stream.push_owned(decoder::encode_hex(*offs), Kind::Immediate);
             }
-            Operand::Immediate(imm) => match symbols.get_sym_by_addr(*imm as usize) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
+            Operand::Immediate(imm) => {
+                match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                    Some((symbol, _)) => {
+                        stream.push("<", Kind::Immediate);
+                        stream.push_symbol(symbol.name(), *imm as usize);
+                        stream.push(">", Kind::Immediate);
+                    }
+                    None => {
+                        stream.push("#", Kind::Expr);
+                        stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
                     }
-                    stream.push(">", CONFIG.colors.asm.immediate);
-                }
-                None => {
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
                 }
-            },
+            }
             Operand::ImmediateDouble(d) => {
                 if *d as i64 as f64 == *d {
                     let imm = format!("#{d:0.1}");
-                    stream.push_owned(imm, CONFIG.colors.asm.immediate);
+                    stream.push_owned(imm, Kind::Immediate);
                 } else {
                     let imm = format!("#{d:0.}");
-                    stream.push_owned(imm, CONFIG.colors.asm.immediate);
+                    stream.push_owned(imm, Kind::Immediate);
                 }
             }
-            Operand::Imm16(imm) => match symbols.get_sym_by_addr(*imm as usize) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.immediate);
+            Operand::Imm16(imm) => match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                Some((symbol, _)) => {
+                    stream.push("<", Kind::Immediate);
+                    stream.push_symbol(symbol.name(), *imm as usize);
+                    stream.push(">", Kind::Immediate);
                 }
                 None => {
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                    stream.push("#", Kind::Expr);
+                    stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
                 }
             },
-            Operand::Imm64(imm) => match symbols.get_sym_by_addr(*imm as usize) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.immediate);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.immediate);
+            Operand::Imm64(imm) => match decoder::symbol_at(stream, symbols, *imm as usize, true) {
+                Some((symbol, _)) => {
+                    stream.push("<", Kind::Immediate);
+                    stream.push_symbol(symbol.name(), *imm as usize);
+                    stream.push(">", Kind::Immediate);
                 }
                 None => {
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                    let text = decoder::encode_uhex(*imm);
+                    stream.push("#", Kind::Expr);
+                    stream.push_addr(text, Kind::Immediate, *imm as usize);
                 }
             },
             Operand::Imm64Special(imm) => {
-                stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push("#", Kind::Expr);
+                let text = decoder::encode_uhex(*imm);
+                stream.push_addr(text, Kind::Immediate, *imm as usize);
             }
             Operand::ImmShift(imm, shift) => {
-                stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push("#", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
 
                 if *shift != 0 {
-                    stream.push(", ", CONFIG.colors.asm.expr);
-                    stream.push("lsl ", CONFIG.colors.asm.opcode);
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(shift.to_string(), CONFIG.colors.asm.immediate);
+                    stream.push(", ", Kind::Expr);
+                    stream.push("lsl ", Kind::Mnemonic);
+                    stream.push("#", Kind::Expr);
+                    stream.push_owned(shift.to_string(), Kind::Immediate);
                 }
             }
             Operand::ImmShiftMSL(imm, shift) => {
-                stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_uhex(*imm as u64), CONFIG.colors.asm.immediate);
+                stream.push("#", Kind::Expr);
+                stream.push_owned(decoder::encode_uhex(*imm as u64), Kind::Immediate);
 
                 if *shift != 0 {
-                    stream.push(", ", CONFIG.colors.asm.expr);
-                    stream.push("msl ", CONFIG.colors.asm.opcode);
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(shift.to_string(), CONFIG.colors.asm.immediate);
+                    stream.push(", ", Kind::Expr);
+                    stream.push("msl ", Kind::Mnemonic);
+                    stream.push("#", Kind::Expr);
+                    stream.push_owned(shift.to_string(), Kind::Immediate);
                 }
             }
             Operand::RegShift(shift_type, amount, size, reg) => match size {
@@ -3598,15 +3703,15 @@ impl ToTokens for Operand {
                         Operand::Register(SizeCode::X, *reg).tokenize(stream, symbols);
                     } else if *amount != 0 {
                         Operand::Register(SizeCode::X, *reg).tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
-                        stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
-                        stream.push(" ", CONFIG.colors.asm.expr);
-                        stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(", ", Kind::Expr);
+                        stream.push(shift_type.as_str(), Kind::Mnemonic);
+                        stream.push(" ", Kind::Expr);
+                        stream.push("#", Kind::Expr);
+                        stream.push_owned(amount.to_string(), Kind::Immediate);
                     } else {
                         Operand::Register(SizeCode::X, *reg).tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
-                        stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
+                        stream.push(", ", Kind::Expr);
+                        stream.push(shift_type.as_str(), Kind::Mnemonic);
                     }
                 }
                 SizeCode::W => {
@@ -3614,78 +3719,78 @@ impl ToTokens for Operand {
                         Operand::Register(SizeCode::W, *reg).tokenize(stream, symbols);
                     } else if *amount != 0 {
                         Operand::Register(SizeCode::W, *reg).tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
-                        stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
-                        stream.push(" ", CONFIG.colors.asm.expr);
-                        stream.push("#", CONFIG.colors.asm.expr);
-                        stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(", ", Kind::Expr);
+                        stream.push(shift_type.as_str(), Kind::Mnemonic);
+                        stream.push(" ", Kind::Expr);
+                        stream.push("#", Kind::Expr);
+                        stream.push_owned(amount.to_string(), Kind::Immediate);
                     } else {
                         Operand::Register(SizeCode::W, *reg).tokenize(stream, symbols);
-                        stream.push(", ", CONFIG.colors.asm.expr);
-                        stream.push(shift_type.as_str(), CONFIG.colors.asm.opcode);
+                        stream.push(", ", Kind::Expr);
+                        stream.push(shift_type.as_str(), Kind::Mnemonic);
                     }
                 }
             },
             Operand::RegRegOffset(reg, index_reg, index_size, extend, amount) => {
                 if extend == &ShiftStyle::LSL && *amount == 0 {
-                    stream.push("[", CONFIG.colors.brackets);
+                    stream.push("[", Kind::Bracket);
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(*index_size, *index_reg).tokenize(stream, symbols);
-                    stream.push("]", CONFIG.colors.brackets);
+                    stream.push("]", Kind::Bracket);
                 } else if ((extend == &ShiftStyle::UXTW && index_size == &SizeCode::W)
                     || (extend == &ShiftStyle::UXTX && index_size == &SizeCode::X))
                     && *amount == 0
                 {
-                    stream.push("[", CONFIG.colors.brackets);
+                    stream.push("[", Kind::Bracket);
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(*index_size, *index_reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
-                    stream.push(extend.as_str(), CONFIG.colors.asm.opcode);
-                    stream.push("]", CONFIG.colors.brackets);
+                    stream.push(", ", Kind::Expr);
+                    stream.push(extend.as_str(), Kind::Mnemonic);
+                    stream.push("]", Kind::Bracket);
                 } else {
-                    stream.push("[", CONFIG.colors.brackets);
+                    stream.push("[", Kind::Bracket);
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                     Operand::Register(*index_size, *index_reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
-                    stream.push(extend.as_str(), CONFIG.colors.asm.opcode);
-                    stream.push(" ", CONFIG.colors.asm.expr);
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(amount.to_string(), CONFIG.colors.asm.immediate);
-                    stream.push("]", CONFIG.colors.brackets);
+                    stream.push(", ", Kind::Expr);
+                    stream.push(extend.as_str(), Kind::Mnemonic);
+                    stream.push(" ", Kind::Expr);
+                    stream.push("#", Kind::Expr);
+                    stream.push_owned(amount.to_string(), Kind::Immediate);
+                    stream.push("]", Kind::Bracket);
                 }
             }
             Operand::RegPreIndex(reg, offset, wback_bit) => {
                 if *offset != 0 || *wback_bit {
-                    stream.push("[", CONFIG.colors.brackets);
+                    stream.push("[", Kind::Bracket);
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                    stream.push(", ", CONFIG.colors.asm.expr);
-                    stream.push("#", CONFIG.colors.asm.expr);
-                    stream.push_owned(decoder::encode_hex(*offset as i64), CONFIG.colors.asm.immediate);
-                    stream.push("]", CONFIG.colors.brackets);
+                    stream.push(", ", Kind::Expr);
+                    stream.push("#", Kind::Expr);
+                    stream.push_owned(decoder::encode_hex(*offset as i64), Kind::Immediate);
+                    stream.push("]", Kind::Bracket);
 
                     if *wback_bit {
-                        stream.push("!", CONFIG.colors.asm.expr);
+                        stream.push("!", Kind::Expr);
                     }
                 } else {
-                    stream.push("[", CONFIG.colors.brackets);
+                    stream.push("[", Kind::Bracket);
                     Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                    stream.push("]", CONFIG.colors.brackets);
+                    stream.push("]", Kind::Bracket);
                 }
             }
             Operand::RegPostIndex(reg, offset) => {
-                stream.push("[", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
                 Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                stream.push("], ", CONFIG.colors.asm.expr);
-                stream.push("#", CONFIG.colors.asm.expr);
-                stream.push_owned(decoder::encode_hex(*offset as i64), CONFIG.colors.asm.immediate);
+                stream.push("], ", Kind::Expr);
+                stream.push("#", Kind::Expr);
+                stream.push_owned(decoder::encode_hex(*offset as i64), Kind::Immediate);
             }
             Operand::RegPostIndexReg(reg, offset_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
                 Operand::RegisterOrSP(SizeCode::X, *reg).tokenize(stream, symbols);
-                stream.push("], ", CONFIG.colors.asm.expr);
+                stream.push("], ", Kind::Expr);
                 format_register_64(stream, *offset_reg);
             }
             Operand::Nothing => panic!("Tried to format nothing opcode"),
@@ -3715,6 +3820,10 @@ impl Decodable for Decoder {
     fn max_width(&self) -> usize {
         4
     }
+
+    fn instruction_alignment(&self) -> usize {
+        4
+    }
 }
 
 #[inline(always)]
@@ -7,3 +7,5 @@
 pub mod armv7;
 /// `yaxpeax-arm`'s `ARMv8` decoder and `Arch` implementation.
 pub mod armv8;
+
+mod reference;
@@ -0,0 +1,1070 @@
+//! Reference of common ARM instructions, shown when hovering them in the listing.
+
+use decoder::Reference;
+
+/// What each condition code checks, indexed by it's encoding.
+pub(crate) const CONDITIONS: [&str; 16] = [
+    "equal (Z set)",
+    "not equal (Z clear)",
+    "higher or same, unsigned (C set)",
+    "lower, unsigned (C clear)",
+    "negative (N set)",
+    "positive or zero (N clear)",
+    "overflow (V set)",
+    "no overflow (V clear)",
+    "higher, unsigned (C set and Z clear)",
+    "lower or same, unsigned (C clear or Z set)",
+    "greater or equal, signed (N and V equal)",
+    "less, signed (N and V differ)",
+    "greater, signed (Z clear and N and V equal)",
+    "less or equal, signed (Z set or N and V differ)",
+    "always",
+    "always",
+];
+
+/// ARMv7 and Thumb instructions sorted by mnemonic, as they're searched by [`decoder::lookup`].
+/// Flags are those written by the variants that set flags.
+pub(crate) const ARMV7: &[Reference] = &[
+    Reference {
+        mnemonic: "adc",
+        summary: "Adds the operand and the carry flag to the register.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "add",
+        summary: "Adds the operands.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "adr",
+        summary: "Computes an address relative to the program counter.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "and",
+        summary: "Bitwise ANDs the operands.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "asr",
+        summary: "Shifts right, keeping the sign.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "b",
+        summary: "Branches to the target.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bic",
+        summary: "Bitwise ANDs the first operand with the inverted second.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "bkpt",
+        summary: "Raises a breakpoint exception.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bl",
+        summary: "Branches to the target, storing the return address in lr.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "blx",
+        summary: "Branches to the target, storing the return address in lr and switching between \
+            ARM and Thumb.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bx",
+        summary: "Branches to the address in the register, switching between ARM and Thumb by it's \
+            lowest bit.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cbnz",
+        summary: "Branches to the target if the register isn't zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cbz",
+        summary: "Branches to the target if the register is zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "clz",
+        summary: "Counts the leading zero bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmn",
+        summary: "Adds the operands, only keeping the flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "cmp",
+        summary: "Subtracts the second operand from the first, only keeping the flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "dmb",
+        summary: "Orders memory accesses before the barrier before those after it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "dsb",
+        summary: "Waits for memory accesses before the barrier to complete.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "eor",
+        summary: "Bitwise XORs the operands.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "isb",
+        summary: "Flushes the pipeline, so later instructions are fetched again.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "it",
+        summary: "Makes up to four following Thumb instructions conditional.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldm",
+        summary: "Loads several registers from consecutive words in memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldr",
+        summary: "Loads a word from memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrb",
+        summary: "Loads a byte from memory, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrd",
+        summary: "Loads two words from memory into a pair of registers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrex",
+        summary: "Loads a word from memory, marking it for exclusive access.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrh",
+        summary: "Loads a halfword from memory, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrsb",
+        summary: "Loads a byte from memory, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrsh",
+        summary: "Loads a halfword from memory, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lsl",
+        summary: "Shifts left, filling in zeroes.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "lsr",
+        summary: "Shifts right, filling in zeroes.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "mla",
+        summary: "Multiplies two registers and adds a third.",
+        flags: "N Z",
+    },
+    Reference {
+        mnemonic: "mls",
+        summary: "Multiplies two registers and subtracts the product from a third.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mov",
+        summary: "Copies the operand into the register.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "movt",
+        summary: "Writes the immediate to the top halfword of the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mrs",
+        summary: "Copies a status register into a general purpose register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "msr",
+        summary: "Writes a general purpose register or immediate to a status register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mul",
+        summary: "Multiplies the operands.",
+        flags: "N Z",
+    },
+    Reference {
+        mnemonic: "mvn",
+        summary: "Copies the inverted operand into the register.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "nop",
+        summary: "Does nothing.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "orr",
+        summary: "Bitwise ORs the operands.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "pop",
+        summary: "Loads registers from the stack, incrementing sp.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "push",
+        summary: "Stores registers on the stack, decrementing sp.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "rev",
+        summary: "Reverses the order of the bytes in a word.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ror",
+        summary: "Rotates right.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "rsb",
+        summary: "Subtracts the first operand from the second.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "rsc",
+        summary: "Subtracts the first operand and the inverted carry flag from the second.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "sbc",
+        summary: "Subtracts the operand and the inverted carry flag.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "sdiv",
+        summary: "Divides the operands as signed.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "smull",
+        summary: "Multiplies the operands as signed, producing a 64-bit result in two registers.",
+        flags: "N Z",
+    },
+    Reference {
+        mnemonic: "stm",
+        summary: "Stores several registers to consecutive words in memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "str",
+        summary: "Stores a word to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strb",
+        summary: "Stores the low byte of the register to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strd",
+        summary: "Stores a pair of registers to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strex",
+        summary: "Stores a word to memory if it's still marked for exclusive access.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strh",
+        summary: "Stores the low halfword of the register to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sub",
+        summary: "Subtracts the second operand from the first.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "svc",
+        summary: "Calls into the operating system.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sxtb",
+        summary: "Sign-extends a byte.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sxth",
+        summary: "Sign-extends a halfword.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "tbb",
+        summary: "Branches forward by twice the byte read from a table.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "tbh",
+        summary: "Branches forward by twice the halfword read from a table.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "teq",
+        summary: "Bitwise XORs the operands, only keeping the flags.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "tst",
+        summary: "Bitwise ANDs the operands, only keeping the flags.",
+        flags: "N Z C",
+    },
+    Reference {
+        mnemonic: "udf",
+        summary: "Raises an undefined instruction exception.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "udiv",
+        summary: "Divides the operands as unsigned.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "umull",
+        summary: "Multiplies the operands as unsigned, producing a 64-bit result in two registers.",
+        flags: "N Z",
+    },
+    Reference {
+        mnemonic: "uxtb",
+        summary: "Zero-extends a byte.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "uxth",
+        summary: "Zero-extends a halfword.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "wfe",
+        summary: "Waits for an event.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "wfi",
+        summary: "Waits for an interrupt.",
+        flags: "",
+    },
+];
+
+/// AArch64 instructions sorted by mnemonic, as they're searched by [`decoder::lookup`].
+pub(crate) const AARCH64: &[Reference] = &[
+    Reference {
+        mnemonic: "adc",
+        summary: "Adds the operands and the carry flag.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "adcs",
+        summary: "Adds the operands and the carry flag, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "add",
+        summary: "Adds the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "adds",
+        summary: "Adds the operands, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "adr",
+        summary: "Computes an address relative to the program counter.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "adrp",
+        summary: "Computes the address of a 4KB page relative to the program counter.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "and",
+        summary: "Bitwise ANDs the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ands",
+        summary: "Bitwise ANDs the operands, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "asr",
+        summary: "Shifts right, keeping the sign.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "asrv",
+        summary: "Shifts right by a register, keeping the sign.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "b",
+        summary: "Branches to the target.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bfc",
+        summary: "Clears a bitfield in the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bfi",
+        summary: "Inserts the low bits of the operand into a bitfield of the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bfm",
+        summary: "Copies a bitfield into the register, leaving the other bits alone.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bfxil",
+        summary: "Copies a bitfield of the operand into the low bits of the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bic",
+        summary: "Bitwise ANDs the first operand with the inverted second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bics",
+        summary: "Bitwise ANDs the first operand with the inverted second, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "bl",
+        summary: "Branches to the target, storing the return address in x30.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "blr",
+        summary: "Branches to the address in the register, storing the return address in x30.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "br",
+        summary: "Branches to the address in the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "brk",
+        summary: "Raises a breakpoint exception.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cbnz",
+        summary: "Branches to the target if the register isn't zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cbz",
+        summary: "Branches to the target if the register is zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ccmn",
+        summary: "Adds the operands setting flags if the condition holds, otherwise sets the flags \
+            to the immediate.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "ccmp",
+        summary: "Compares the operands if the condition holds, otherwise sets the flags to the \
+            immediate.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "cinc",
+        summary: "Selects the operand if the condition fails, otherwise the operand plus one.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cinv",
+        summary: "Selects the operand if the condition fails, otherwise the inverted operand.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "clz",
+        summary: "Counts the leading zero bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmn",
+        summary: "Adds the operands, only keeping the flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "cmp",
+        summary: "Subtracts the second operand from the first, only keeping the flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "cneg",
+        summary: "Selects the operand if the condition fails, otherwise the negated operand.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "csel",
+        summary: "Selects the first operand if the condition holds, otherwise the second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cset",
+        summary: "Sets the register to one if the condition holds, otherwise zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "csetm",
+        summary: "Sets all bits of the register if the condition holds, otherwise clears them.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "csinc",
+        summary: "Selects the first operand if the condition holds, otherwise the second plus one.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "csinv",
+        summary: "Selects the first operand if the condition holds, otherwise the inverted second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "csneg",
+        summary: "Selects the first operand if the condition holds, otherwise the negated second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "dmb",
+        summary: "Orders memory accesses before the barrier before those after it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "dsb",
+        summary: "Waits for memory accesses before the barrier to complete.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "eon",
+        summary: "Bitwise XORs the first operand with the inverted second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "eor",
+        summary: "Bitwise XORs the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "extr",
+        summary: "Extracts a register from a pair of registers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fadd",
+        summary: "Adds floating-point values.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fcmp",
+        summary: "Compares floating-point values, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "fcmpe",
+        summary: "Compares floating-point values, setting flags and raising an exception on NaN.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "fcsel",
+        summary: "Selects the first floating-point operand if the condition holds, otherwise the \
+            second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fcvt",
+        summary: "Converts between floating-point precisions.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fdiv",
+        summary: "Divides floating-point values.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fmov",
+        summary: "Copies a floating-point value.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fmul",
+        summary: "Multiplies floating-point values.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "fsub",
+        summary: "Subtracts floating-point values.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "hint",
+        summary: "Hints the processor, e.g. nop, yield or pointer authentication.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "hlt",
+        summary: "Halts for an external debugger.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "isb",
+        summary: "Flushes the pipeline, so later instructions are fetched again.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldar",
+        summary: "Loads from memory, ordering later memory accesses after it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldaxr",
+        summary: "Loads from memory marking it for exclusive access, ordering later accesses after \
+            it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldp",
+        summary: "Loads a pair of registers from memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldr",
+        summary: "Loads a register from memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrb",
+        summary: "Loads a byte from memory, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrh",
+        summary: "Loads a halfword from memory, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrsb",
+        summary: "Loads a byte from memory, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrsh",
+        summary: "Loads a halfword from memory, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldrsw",
+        summary: "Loads a word from memory, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldur",
+        summary: "Loads a register from memory at an unscaled offset.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ldxr",
+        summary: "Loads from memory, marking it for exclusive access.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lsl",
+        summary: "Shifts left, filling in zeroes.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lslv",
+        summary: "Shifts left by a register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lsr",
+        summary: "Shifts right, filling in zeroes.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lsrv",
+        summary: "Shifts right by a register, filling in zeroes.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "madd",
+        summary: "Multiplies two registers and adds a third.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mneg",
+        summary: "Multiplies the operands and negates the product.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mov",
+        summary: "Copies the operand into the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movk",
+        summary: "Writes a 16-bit immediate into the register, keeping the other bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movn",
+        summary: "Writes the inverse of a shifted 16-bit immediate into the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movz",
+        summary: "Writes a shifted 16-bit immediate into the register, zeroing the other bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mrs",
+        summary: "Reads a system register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "msr",
+        summary: "Writes a system register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "msub",
+        summary: "Multiplies two registers and subtracts the product from a third.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mul",
+        summary: "Multiplies the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mvn",
+        summary: "Copies the inverted operand into the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "neg",
+        summary: "Negates the operand.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "negs",
+        summary: "Negates the operand, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "ngc",
+        summary: "Negates the operand, subtracting the inverted carry flag.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ngcs",
+        summary: "Negates the operand, subtracting the inverted carry flag and setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "nop",
+        summary: "Does nothing.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "orn",
+        summary: "Bitwise ORs the first operand with the inverted second.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "orr",
+        summary: "Bitwise ORs the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "prfm",
+        summary: "Hints that memory will be accessed soon.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ret",
+        summary: "Returns to the address in x30, or the given register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "rev",
+        summary: "Reverses the order of the bytes in the register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ror",
+        summary: "Rotates right.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "rorv",
+        summary: "Rotates right by a register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sbc",
+        summary: "Subtracts the second operand and the inverted carry flag from the first.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sbcs",
+        summary: "Subtracts the second operand and the inverted carry flag from the first, setting \
+            flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "sbfiz",
+        summary: "Copies the low bits of the operand into a sign-extended bitfield.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sbfm",
+        summary: "Copies a bitfield into the register, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sbfx",
+        summary: "Extracts a bitfield of the operand, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "scvtf",
+        summary: "Converts a signed integer to floating-point.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sdiv",
+        summary: "Divides the operands as signed.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sev",
+        summary: "Signals an event to all processors.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sevl",
+        summary: "Signals an event to this processor.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "smaddl",
+        summary: "Multiplies two 32-bit registers as signed and adds a 64-bit third.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "smulh",
+        summary: "Multiplies the operands as signed, keeping the high 64 bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "smull",
+        summary: "Multiplies two 32-bit registers as signed, producing a 64-bit result.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "stlr",
+        summary: "Stores to memory, ordering earlier memory accesses before it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "stlxr",
+        summary: "Stores to memory if it's still marked for exclusive access, ordering earlier \
+            accesses before it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "stp",
+        summary: "Stores a pair of registers to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "str",
+        summary: "Stores a register to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strb",
+        summary: "Stores the low byte of the register to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "strh",
+        summary: "Stores the low halfword of the register to memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "stur",
+        summary: "Stores a register to memory at an unscaled offset.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "stxr",
+        summary: "Stores to memory if it's still marked for exclusive access.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sub",
+        summary: "Subtracts the second operand from the first.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "subs",
+        summary: "Subtracts the second operand from the first, setting flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "svc",
+        summary: "Calls into the operating system.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sxtb",
+        summary: "Sign-extends a byte.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sxth",
+        summary: "Sign-extends a halfword.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sxtw",
+        summary: "Sign-extends a word.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "tbnz",
+        summary: "Branches to the target if the tested bit isn't zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "tbz",
+        summary: "Branches to the target if the tested bit is zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "tst",
+        summary: "Bitwise ANDs the operands, only keeping the flags.",
+        flags: "N Z C V",
+    },
+    Reference {
+        mnemonic: "ubfiz",
+        summary: "Copies the low bits of the operand into a zero-extended bitfield.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ubfm",
+        summary: "Copies a bitfield into the register, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ubfx",
+        summary: "Extracts a bitfield of the operand, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ucvtf",
+        summary: "Converts an unsigned integer to floating-point.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "udiv",
+        summary: "Divides the operands as unsigned.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "umaddl",
+        summary: "Multiplies two 32-bit registers as unsigned and adds a 64-bit third.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "umov",
+        summary: "Copies a SIMD vector lane into a general purpose register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "umulh",
+        summary: "Multiplies the operands as unsigned, keeping the high 64 bits.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "umull",
+        summary: "Multiplies two 32-bit registers as unsigned, producing a 64-bit result.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "uxtb",
+        summary: "Zero-extends a byte.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "uxth",
+        summary: "Zero-extends a halfword.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "wfe",
+        summary: "Waits for an event.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "wfi",
+        summary: "Waits for an interrupt.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "yield",
+        summary: "Hints that this thread could let others run.",
+        flags: "",
+    },
+];
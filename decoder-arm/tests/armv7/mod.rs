@@ -528,8 +528,8 @@ fn test_unconditional() {
     test_armv6([0x0f, 0x05, 0x4d, 0xf8], "srsda sp, 0xf");
     test_armv6([0x0f, 0x05, 0xed, 0xf9], "srsib sp!, 0xf");
     test_armv6([0x0f, 0x05, 0xed, 0xf8], "srsia sp!, 0xf");
-    test_armv5([0x01, 0x02, 0x03, 0xfb], "blx $+0xc0806");
-    test_armv5([0x01, 0x02, 0x03, 0xfa], "blx $+0xc0804");
+    test_armv5([0x01, 0x02, 0x03, 0xfb], "blx $+0xc080e");
+    test_armv5([0x01, 0x02, 0x03, 0xfa], "blx $+0xc080c");
     test_armv5([0x12, 0x34, 0xcf, 0xfc], "stc2l p4, c3, [pc], {0x12}");
     test_armv5([0x12, 0x34, 0xdf, 0xfc], "ldc2l p4, c3, [pc], {0x12}");
     test_armv5([0x34, 0x78, 0xff, 0xfc], "ldc2l p8, c7, [pc], 0xd0");
@@ -656,6 +656,33 @@ fn test_decode_span() {
     }
     //    panic!("done");
 }
+
+#[test]
+fn test_describe() {
+    let symbols = debugvault::Index::default();
+    let describe = |data: [u8; 4]| {
+        let inst = InstDecoder::default().decode(&mut Reader::new(&data[..])).unwrap();
+        inst.describe(&symbols).unwrap()
+    };
+
+    let description = describe([0x02, 0x00, 0x91, 0xe0]);
+    assert_eq!(description.mnemonic, "add");
+    assert_eq!(description.flags, "N Z C V");
+    assert_eq!(
+        description.operands,
+        [
+            ("r0".to_string(), "register".to_string()),
+            ("r1".to_string(), "register".to_string()),
+            ("r2".to_string(), "register".to_string()),
+        ]
+    );
+    assert!(description.notes.is_empty());
+
+    // addeq r0, r1, r2 doesn't set flags
+    let description = describe([0x02, 0x00, 0x81, 0x00]);
+    assert_eq!(description.flags, "");
+    assert_eq!(description.notes, ["Only executes if equal (Z set)."]);
+}
 /*
  * from debian 5.0.10 bash 3.2-4_arm
  *   0x0001bee4      24c09fe5       ldr ip, sym.__libc_csu_fini
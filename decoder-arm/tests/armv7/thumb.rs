@@ -1,5 +1,5 @@
 use arm::armv7::Instruction;
-use decoder::{Decodable, Reader};
+use decoder::{Decodable, Decoded, Reader};
 
 type InstDecoder = arm::armv7::Decoder;
 
@@ -4136,3 +4136,16 @@ fn test_decode_simd_32b_cases() {
         "vstmdb r3!, {s11, s12, s13, s14, s15, s16, s17, s18, s19, s20, s21, s22, s23, s24, s25, s26, s27, s28, s29, s30, s31}"
     );
 }
+
+#[test]
+fn test_width() {
+    // movs r0, #1
+    let mut reader = Reader::new(&[0x01, 0x20, 0x00, 0x00]);
+    let instr = InstDecoder::default_thumb().decode(&mut reader).unwrap();
+    assert_eq!(instr.width(), 2);
+
+    // b.w $+0xc308
+    let mut reader = Reader::new(&[0x0c, 0xf0, 0x84, 0xb9]);
+    let instr = InstDecoder::default_thumb().decode(&mut reader).unwrap();
+    assert_eq!(instr.width(), 4);
+}
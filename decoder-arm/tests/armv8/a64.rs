@@ -1,4 +1,4 @@
-use decoder::{ErrorKind, Decodable, Reader};
+use decoder::{ErrorKind, Decodable, Decoded, Reader};
 use arm::armv8::a64::{Instruction, Operand, Opcode, SizeCode, ShiftStyle};
 
 type InstDecoder = arm::armv8::a64::Decoder;
@@ -4923,3 +4923,28 @@ fn test_bitfield() {
 
     assert!(errs.is_empty());
 }
+
+#[test]
+fn test_describe() {
+    let symbols = debugvault::Index::default();
+    let describe = |data: [u8; 4]| {
+        let inst = InstDecoder::default().decode(&mut Reader::new(&data[..])).unwrap();
+        inst.describe(&symbols).unwrap()
+    };
+
+    let description = describe([0x21, 0x00, 0x00, 0xcb]);
+    assert_eq!(description.mnemonic, "sub");
+    assert_eq!(description.flags, "");
+    assert_eq!(description.operands[0], ("x1".to_string(), "64-bit register".to_string()));
+    assert!(description.notes.is_empty());
+
+    // mov x0, x19 is encoded as orr x0, xzr, x19
+    let description = describe([0xe0, 0x03, 0x13, 0xaa]);
+    assert_eq!(description.mnemonic, "mov");
+    assert_eq!(description.operands[1], ("xzr".to_string(), "zero register".to_string()));
+    assert_eq!(description.notes, ["Alias of orr, whose operands are listed."]);
+
+    let description = describe([0x21, 0x01, 0x00, 0x54]);
+    assert_eq!(description.mnemonic, "b");
+    assert_eq!(description.notes, ["Only branches if not equal (Z clear)."]);
+}
@@ -0,0 +1,690 @@
+//! AVR disassembler.
+//!
+//! AVR is a Harvard architecture, the code in flash and the data in SRAM each have their own
+//! addresses starting at zero. Binaries place SRAM at [`DATA_SPACE`] and EEPROM at
+//! [`EEPROM_SPACE`], as avr-gcc does, such that addresses of data don't collide with those of
+//! code.
+
+mod tests;
+
+use debugvault::Index;
+use decoder::{encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+/// Where the data memory starts in the address space of a binary.
+pub const DATA_SPACE: usize = 0x800000;
+
+/// Where the EEPROM starts in the address space of a binary.
+pub const EEPROM_SPACE: usize = 0x810000;
+
+macro_rules! operands {
+    [] => {([$crate::Operand::Nothing; 2], 0)};
+    [$($x:expr),+ $(,)?] => {{
+        let mut operands = [$crate::Operand::Nothing; 2];
+        let mut idx = 0;
+        $(
+            idx += 1;
+            operands[idx - 1] = $x;
+        )*
+
+        (operands, idx)
+    }};
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Opcode {
+    // *arithmetic and logic*
+    ADD,
+    ADC,
+    ADIW,
+    SUB,
+    SUBI,
+    SBC,
+    SBCI,
+    SBIW,
+    AND,
+    ANDI,
+    OR,
+    ORI,
+    EOR,
+    COM,
+    NEG,
+    INC,
+    DEC,
+    MUL,
+    MULS,
+    MULSU,
+    FMUL,
+    FMULS,
+    FMULSU,
+    DES,
+    // *branches*
+    RJMP,
+    IJMP,
+    EIJMP,
+    JMP,
+    RCALL,
+    ICALL,
+    EICALL,
+    CALL,
+    RET,
+    RETI,
+    CPSE,
+    CP,
+    CPC,
+    CPI,
+    SBRC,
+    SBRS,
+    SBIC,
+    SBIS,
+    BRBS,
+    BRBC,
+    // *data transfer*
+    MOV,
+    MOVW,
+    LDI,
+    LDS,
+    LD,
+    LDD,
+    STS,
+    ST,
+    STD,
+    LPM,
+    ELPM,
+    SPM,
+    IN,
+    OUT,
+    PUSH,
+    POP,
+    XCH,
+    LAS,
+    LAC,
+    LAT,
+    // *bit and bit-test*
+    LSR,
+    ROR,
+    ASR,
+    SWAP,
+    BSET,
+    BCLR,
+    SBI,
+    CBI,
+    BST,
+    BLD,
+    // *mcu control*
+    NOP,
+    SLEEP,
+    WDR,
+    BREAK,
+    // *psuedo instructions*
+    LSL,
+    ROL,
+    TST,
+    CLR,
+    SER,
+    SEC,
+    SEZ,
+    SEN,
+    SEV,
+    SES,
+    SEH,
+    SET,
+    SEI,
+    CLC,
+    CLZ,
+    CLN,
+    CLV,
+    CLS,
+    CLH,
+    CLT,
+    CLI,
+    BRCS,
+    BREQ,
+    BRMI,
+    BRVS,
+    BRLT,
+    BRHS,
+    BRTS,
+    BRIE,
+    BRCC,
+    BRNE,
+    BRPL,
+    BRVC,
+    BRGE,
+    BRHC,
+    BRTC,
+    BRID,
+}
+
+impl Opcode {
+    pub fn as_str(&self) -> &'static str {
+        OPCODE_NAMES[*self as usize]
+    }
+
+    fn is_branch(&self) -> bool {
+        (Self::BRBS as u8..=Self::BRBC as u8).contains(&(*self as u8))
+            || (Self::BRCS as u8..=Self::BRID as u8).contains(&(*self as u8))
+    }
+}
+
+static OPCODE_NAMES: [&str; 115] = [
+    "add", "adc", "adiw", "sub", "subi", "sbc", "sbci", "sbiw", "and", "andi", "or", "ori", "eor",
+    "com", "neg", "inc", "dec", "mul", "muls", "mulsu", "fmul", "fmuls", "fmulsu", "des", "rjmp",
+    "ijmp", "eijmp", "jmp", "rcall", "icall", "eicall", "call", "ret", "reti", "cpse", "cp", "cpc",
+    "cpi", "sbrc", "sbrs", "sbic", "sbis", "brbs", "brbc", "mov", "movw", "ldi", "lds", "ld", "ldd",
+    "sts", "st", "std", "lpm", "elpm", "spm", "in", "out", "push", "pop", "xch", "las", "lac",
+    "lat", "lsr", "ror", "asr", "swap", "bset", "bclr", "sbi", "cbi", "bst", "bld", "nop", "sleep",
+    "wdr", "break", "lsl", "rol", "tst", "clr", "ser", "sec", "sez", "sen", "sev", "ses", "seh",
+    "set", "sei", "clc", "clz", "cln", "clv", "cls", "clh", "clt", "cli", "brcs", "breq", "brmi",
+    "brvs", "brlt", "brhs", "brts", "brie", "brcc", "brne", "brpl", "brvc", "brge", "brhc", "brtc",
+    "brid",
+];
+
+/// Status register flags, by the bit they're set with `bset` and cleared with `bclr`.
+const SETS: [Opcode; 8] = [
+    Opcode::SEC,
+    Opcode::SEZ,
+    Opcode::SEN,
+    Opcode::SEV,
+    Opcode::SES,
+    Opcode::SEH,
+    Opcode::SET,
+    Opcode::SEI,
+];
+
+const CLEARS: [Opcode; 8] = [
+    Opcode::CLC,
+    Opcode::CLZ,
+    Opcode::CLN,
+    Opcode::CLV,
+    Opcode::CLS,
+    Opcode::CLH,
+    Opcode::CLT,
+    Opcode::CLI,
+];
+
+/// Branches taken when a status register flag is set, by the flag's bit.
+const BRANCHES_SET: [Opcode; 8] = [
+    Opcode::BRCS,
+    Opcode::BREQ,
+    Opcode::BRMI,
+    Opcode::BRVS,
+    Opcode::BRLT,
+    Opcode::BRHS,
+    Opcode::BRTS,
+    Opcode::BRIE,
+];
+
+const BRANCHES_CLEAR: [Opcode; 8] = [
+    Opcode::BRCC,
+    Opcode::BRNE,
+    Opcode::BRPL,
+    Opcode::BRVC,
+    Opcode::BRGE,
+    Opcode::BRHC,
+    Opcode::BRTC,
+    Opcode::BRID,
+];
+
+/// Registers that point into data memory, made of a pair of general purpose registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pointer {
+    X,
+    Y,
+    Z,
+}
+
+/// How a pointer register is used to address memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Plain,
+    PostIncrement,
+    PreDecrement,
+    Displacement(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Register(u8),
+    Immediate(u8),
+    /// Bit within a register or I/O register.
+    Bit(u8),
+    /// Address in the I/O space.
+    Io(u8),
+    Pointer(Pointer, Mode),
+    /// Offset in bytes from the end of the instruction, before it's address is known.
+    Relative(i16),
+    /// Address in flash.
+    Address(usize),
+    /// Address in data memory.
+    Data(u16),
+}
+
+/// Names of I/O registers that are at the same address on every device that has them.
+fn io_name(addr: u8) -> Option<&'static str> {
+    match addr {
+        0x3b => Some("RAMPZ"),
+        0x3c => Some("EIND"),
+        0x3d => Some("SPL"),
+        0x3e => Some("SPH"),
+        0x3f => Some("SREG"),
+        _ => None,
+    }
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let address = |stream: &mut TokenStream, addr: usize, shown: String| {
+            match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                }
+                None => stream.push_addr(shown, Kind::Immediate, addr),
+            }
+        };
+
+        match *self {
+            Self::Register(reg) => stream.push_owned(format!("r{reg}"), Kind::Register),
+            Self::Immediate(imm) => {
+                stream.push_owned(format!("{imm:#04x}"), Kind::Immediate)
+            }
+            Self::Bit(bit) => stream.push_owned(bit.to_string(), Kind::Immediate),
+            Self::Io(addr) => match io_name(addr) {
+                Some(name) => stream.push(name, Kind::Register),
+                None => stream.push_owned(format!("{addr:#04x}"), Kind::Immediate),
+            },
+            Self::Pointer(ptr, mode) => {
+                let ptr = match ptr {
+                    Pointer::X => "X",
+                    Pointer::Y => "Y",
+                    Pointer::Z => "Z",
+                };
+
+                match mode {
+                    Mode::Plain => stream.push(ptr, Kind::Register),
+                    Mode::PostIncrement => {
+                        stream.push(ptr, Kind::Register);
+                        stream.push("+", Kind::Expr);
+                    }
+                    Mode::PreDecrement => {
+                        stream.push("-", Kind::Expr);
+                        stream.push(ptr, Kind::Register);
+                    }
+                    Mode::Displacement(disp) => {
+                        stream.push(ptr, Kind::Register);
+                        stream.push("+", Kind::Expr);
+                        stream.push_owned(disp.to_string(), Kind::Immediate);
+                    }
+                }
+            }
+            Self::Relative(offset) => {
+                stream.push_owned(format!(".{offset:+}"), Kind::Immediate)
+            }
+            Self::Address(addr) => address(stream, addr, encode_uhex(addr as u64)),
+            Self::Data(addr) => address(stream, DATA_SPACE + addr as usize, format!("{addr:#06x}")),
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    opcode: Opcode,
+    operands: [Operand; 2],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    fn new(opcode: Opcode, (operands, operand_count): ([Operand; 2], usize), len: usize) -> Self {
+        Self {
+            opcode,
+            operands,
+            operand_count,
+            len,
+        }
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        stream.push(self.opcode.as_str(), Kind::Mnemonic);
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            if let Operand::Relative(offset) = *operand {
+                let next = addr.wrapping_add(self.len);
+                *operand = Operand::Address(next.wrapping_add_signed(offset as isize));
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.opcode {
+            Opcode::RCALL | Opcode::CALL => XrefKind::Call,
+            Opcode::RJMP | Opcode::JMP => XrefKind::Jump,
+            opcode if opcode.is_branch() => XrefKind::Jump,
+            _ => XrefKind::Data,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) => Some((addr, kind)),
+            Operand::Data(addr) => Some((DATA_SPACE + addr as usize, XrefKind::Data)),
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.opcode {
+            Opcode::RJMP | Opcode::JMP | Opcode::IJMP | Opcode::EIJMP => Flow::Jump,
+            Opcode::RET | Opcode::RETI => Flow::Return,
+            opcode if opcode.is_branch() => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(reader).map_err(|err| Error::new(err, 2))
+    }
+
+    fn max_width(&self) -> usize {
+        4
+    }
+
+    fn instruction_alignment(&self) -> usize {
+        2
+    }
+}
+
+fn next_word(reader: &mut decoder::Reader) -> Result<u16, ErrorKind> {
+    let mut bytes = [0u8; 2];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+    use Operand::{Immediate, Register};
+
+    let word = next_word(reader)?;
+
+    // most instructions have a 5-bit destination and source register, or a 4-bit destination
+    // register in r16..r31 with an 8-bit immediate
+    let rd = Register((word >> 4 & 0b11111) as u8);
+    let rr = Register(((word >> 5 & 0b10000) | (word & 0b1111)) as u8);
+    let rd_high = Register(16 + (word >> 4 & 0b1111) as u8);
+    let imm = Immediate(((word >> 4 & 0b11110000) | (word & 0b1111)) as u8);
+
+    let inst = match word >> 12 {
+        0b0000 => match word >> 8 & 0b1111 {
+            0b0000 if word == 0 => Instruction::new(NOP, operands![], 2),
+            0b0001 => {
+                let rd = Register(2 * (word >> 4 & 0b1111) as u8);
+                let rr = Register(2 * (word & 0b1111) as u8);
+                Instruction::new(MOVW, operands![rd, rr], 2)
+            }
+            0b0010 => {
+                let rr = Register(16 + (word & 0b1111) as u8);
+                Instruction::new(MULS, operands![rd_high, rr], 2)
+            }
+            0b0011 => {
+                let variant = (word >> 6 & 0b10) | (word >> 3 & 1);
+                let opcode = [MULSU, FMUL, FMULS, FMULSU][variant as usize];
+                let rd = Register(16 + (word >> 4 & 0b111) as u8);
+                let rr = Register(16 + (word & 0b111) as u8);
+                Instruction::new(opcode, operands![rd, rr], 2)
+            }
+            0b0100..=0b0111 => Instruction::new(CPC, operands![rd, rr], 2),
+            0b1000..=0b1011 => Instruction::new(SBC, operands![rd, rr], 2),
+            0b1100..=0b1111 if rd == rr => Instruction::new(LSL, operands![rd], 2),
+            0b1100..=0b1111 => Instruction::new(ADD, operands![rd, rr], 2),
+            _ => return Err(ErrorKind::InvalidOpcode),
+        },
+        0b0001 => match word >> 10 & 0b11 {
+            0b00 => Instruction::new(CPSE, operands![rd, rr], 2),
+            0b01 => Instruction::new(CP, operands![rd, rr], 2),
+            0b10 => Instruction::new(SUB, operands![rd, rr], 2),
+            _ if rd == rr => Instruction::new(ROL, operands![rd], 2),
+            _ => Instruction::new(ADC, operands![rd, rr], 2),
+        },
+        0b0010 => match word >> 10 & 0b11 {
+            0b00 if rd == rr => Instruction::new(TST, operands![rd], 2),
+            0b00 => Instruction::new(AND, operands![rd, rr], 2),
+            0b01 if rd == rr => Instruction::new(CLR, operands![rd], 2),
+            0b01 => Instruction::new(EOR, operands![rd, rr], 2),
+            0b10 => Instruction::new(OR, operands![rd, rr], 2),
+            _ => Instruction::new(MOV, operands![rd, rr], 2),
+        },
+        0b0011 => Instruction::new(CPI, operands![rd_high, imm], 2),
+        0b0100 => Instruction::new(SBCI, operands![rd_high, imm], 2),
+        0b0101 => Instruction::new(SUBI, operands![rd_high, imm], 2),
+        0b0110 => Instruction::new(ORI, operands![rd_high, imm], 2),
+        0b0111 => Instruction::new(ANDI, operands![rd_high, imm], 2),
+        0b1000 | 0b1010 => {
+            let disp = ((word >> 8 & 0b100000) | (word >> 7 & 0b11000) | (word & 0b111)) as u8;
+            let ptr = if word & 0b1000 != 0 { Pointer::Y } else { Pointer::Z };
+            let mode = if disp == 0 { Mode::Plain } else { Mode::Displacement(disp) };
+            let ptr = Operand::Pointer(ptr, mode);
+
+            match (word & 0x200 != 0, disp) {
+                (false, 0) => Instruction::new(LD, operands![rd, ptr], 2),
+                (false, _) => Instruction::new(LDD, operands![rd, ptr], 2),
+                (true, 0) => Instruction::new(ST, operands![ptr, rd], 2),
+                (true, _) => Instruction::new(STD, operands![ptr, rd], 2),
+            }
+        }
+        0b1001 => decode_misc(reader, word, rd, rr)?,
+        0b1011 => {
+            let io = Operand::Io(((word >> 5 & 0b110000) | (word & 0b1111)) as u8);
+            match word & 0x800 {
+                0 => Instruction::new(IN, operands![rd, io], 2),
+                _ => Instruction::new(OUT, operands![io, rd], 2),
+            }
+        }
+        0b1100 | 0b1101 => {
+            // sign extend the 12-bit offset in words
+            let offset = ((word << 4) as i16 >> 4) * 2;
+            let opcode = if word >> 12 == 0b1100 { RJMP } else { RCALL };
+            Instruction::new(opcode, operands![Operand::Relative(offset)], 2)
+        }
+        0b1110 if imm == Immediate(0xff) => Instruction::new(SER, operands![rd_high], 2),
+        0b1110 => Instruction::new(LDI, operands![rd_high, imm], 2),
+        _ => {
+            let bit = (word & 0b111) as usize;
+            match word >> 10 & 0b11 {
+                0b00 | 0b01 => {
+                    // sign extend the 7-bit offset in words
+                    let offset = ((word >> 3 << 9) as i16 >> 9) * 2;
+                    let opcode = match word & 0x400 {
+                        0 => BRANCHES_SET[bit],
+                        _ => BRANCHES_CLEAR[bit],
+                    };
+
+                    Instruction::new(opcode, operands![Operand::Relative(offset)], 2)
+                }
+                _ if word & 0b1000 != 0 => return Err(ErrorKind::InvalidOpcode),
+                bits => {
+                    let opcode = match (bits, word & 0x200 != 0) {
+                        (0b10, false) => BLD,
+                        (0b10, true) => BST,
+                        (_, false) => SBRC,
+                        (_, true) => SBRS,
+                    };
+
+                    Instruction::new(opcode, operands![rd, Operand::Bit(bit as u8)], 2)
+                }
+            }
+        }
+    };
+
+    Ok(inst)
+}
+
+/// Decodes the instructions starting with `1001`, which includes loads and stores through
+/// pointers, instructions with a single operand and the 32-bit jumps and calls.
+fn decode_misc(
+    reader: &mut decoder::Reader,
+    word: u16,
+    rd: Operand,
+    rr: Operand,
+) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+    use Operand::Pointer as Ptr;
+
+    let inst = match word >> 9 & 0b111 {
+        // loads and stores
+        0b000 | 0b001 => {
+            let store = word & 0x200 != 0;
+            let operand = match word & 0b1111 {
+                0b0000 => {
+                    let addr = Operand::Data(next_word(reader)?);
+                    return Ok(match store {
+                        false => Instruction::new(LDS, operands![rd, addr], 4),
+                        true => Instruction::new(STS, operands![addr, rd], 4),
+                    });
+                }
+                0b0001 => Ptr(Pointer::Z, Mode::PostIncrement),
+                0b0010 => Ptr(Pointer::Z, Mode::PreDecrement),
+                0b1001 => Ptr(Pointer::Y, Mode::PostIncrement),
+                0b1010 => Ptr(Pointer::Y, Mode::PreDecrement),
+                0b1100 => Ptr(Pointer::X, Mode::Plain),
+                0b1101 => Ptr(Pointer::X, Mode::PostIncrement),
+                0b1110 => Ptr(Pointer::X, Mode::PreDecrement),
+                0b1111 => {
+                    let opcode = if store { PUSH } else { POP };
+                    return Ok(Instruction::new(opcode, operands![rd], 2));
+                }
+                bits @ 0b0100..=0b0111 => {
+                    let opcode = match (store, bits) {
+                        (false, 0b0100) | (false, 0b0101) => LPM,
+                        (false, _) => ELPM,
+                        (true, 0b0100) => XCH,
+                        (true, 0b0101) => LAS,
+                        (true, 0b0110) => LAC,
+                        (true, _) => LAT,
+                    };
+
+                    let mode = match (store, bits & 1) {
+                        (false, 1) => Mode::PostIncrement,
+                        _ => Mode::Plain,
+                    };
+
+                    let ptr = Ptr(Pointer::Z, mode);
+                    return Ok(match store {
+                        false => Instruction::new(opcode, operands![rd, ptr], 2),
+                        true => Instruction::new(opcode, operands![ptr, rd], 2),
+                    });
+                }
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+
+            match store {
+                false => Instruction::new(LD, operands![rd, operand], 2),
+                true => Instruction::new(ST, operands![operand, rd], 2),
+            }
+        }
+        0b010 => match word & 0b1111 {
+            0b0000 => Instruction::new(COM, operands![rd], 2),
+            0b0001 => Instruction::new(NEG, operands![rd], 2),
+            0b0010 => Instruction::new(SWAP, operands![rd], 2),
+            0b0011 => Instruction::new(INC, operands![rd], 2),
+            0b0101 => Instruction::new(ASR, operands![rd], 2),
+            0b0110 => Instruction::new(LSR, operands![rd], 2),
+            0b0111 => Instruction::new(ROR, operands![rd], 2),
+            0b1010 => Instruction::new(DEC, operands![rd], 2),
+            0b1000 if word & 0x100 == 0 => {
+                let flag = (word >> 4 & 0b111) as usize;
+                let opcode = if word & 0x80 == 0 { SETS[flag] } else { CLEARS[flag] };
+                Instruction::new(opcode, operands![], 2)
+            }
+            0b1000 => {
+                let (opcode, operands) = match word >> 4 & 0b1111 {
+                    0b0000 => (RET, operands![]),
+                    0b0001 => (RETI, operands![]),
+                    0b1000 => (SLEEP, operands![]),
+                    0b1001 => (BREAK, operands![]),
+                    0b1010 => (WDR, operands![]),
+                    0b1100 => (LPM, operands![]),
+                    0b1101 => (ELPM, operands![]),
+                    0b1110 => (SPM, operands![]),
+                    0b1111 => (SPM, operands![Ptr(Pointer::Z, Mode::PostIncrement)]),
+                    _ => return Err(ErrorKind::InvalidOpcode),
+                };
+
+                Instruction::new(opcode, operands, 2)
+            }
+            0b1001 => match word {
+                0x9409 => Instruction::new(IJMP, operands![], 2),
+                0x9419 => Instruction::new(EIJMP, operands![], 2),
+                0x9509 => Instruction::new(ICALL, operands![], 2),
+                0x9519 => Instruction::new(EICALL, operands![], 2),
+                _ => return Err(ErrorKind::InvalidOpcode),
+            },
+            0b1011 if word & 0x100 == 0 => {
+                let round = Operand::Immediate((word >> 4 & 0b1111) as u8);
+                Instruction::new(DES, operands![round], 2)
+            }
+            0b1100..=0b1111 => {
+                // 22-bit address in words, split between both words
+                let high = ((word >> 3 & 0b111110) | (word & 1)) as usize;
+                let addr = (high << 16 | next_word(reader)? as usize) * 2;
+                let opcode = if word & 0b10 == 0 { JMP } else { CALL };
+                Instruction::new(opcode, operands![Operand::Address(addr)], 4)
+            }
+            _ => return Err(ErrorKind::InvalidOpcode),
+        },
+        0b011 => {
+            let rd = Operand::Register(24 + 2 * (word >> 4 & 0b11) as u8);
+            let imm = Operand::Immediate(((word >> 2 & 0b110000) | (word & 0b1111)) as u8);
+            let opcode = if word & 0x100 == 0 { ADIW } else { SBIW };
+            Instruction::new(opcode, operands![rd, imm], 2)
+        }
+        0b100 | 0b101 => {
+            let io = Operand::Io((word >> 3 & 0b11111) as u8);
+            let bit = Operand::Bit((word & 0b111) as u8);
+            let opcode = match word >> 8 & 0b11 {
+                0b00 => CBI,
+                0b01 => SBIC,
+                0b10 => SBI,
+                _ => SBIS,
+            };
+
+            Instruction::new(opcode, operands![io, bit], 2)
+        }
+        _ => Instruction::new(MUL, operands![rd, rr], 2),
+    };
+
+    Ok(inst)
+}
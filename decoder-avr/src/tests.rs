@@ -0,0 +1,87 @@
+#![cfg(test)]
+
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let decoder = crate::Decoder;
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn startup() {
+    test_display(&[0x0c, 0x94, 0x34, 0x00], "jmp 0x68");
+    test_display(&[0x11, 0x24], "clr r1");
+    test_display(&[0x1f, 0xbe], "out SREG, r1");
+    test_display(&[0xcf, 0xef], "ser r28");
+    test_display(&[0xd8, 0xe0], "ldi r29, 0x08");
+    test_display(&[0xde, 0xbf], "out SPH, r29");
+    test_display(&[0x0e, 0x94, 0x40, 0x00], "call 0x80");
+    test_display(&[0xf8, 0x94], "cli");
+}
+
+#[test]
+fn relative() {
+    test_display_at(0x100, &[0xff, 0xcf], "rjmp 0x100");
+    test_display_at(0x100, &[0xe1, 0xf7], "brne 0xfa");
+    test_display_at(0x100, &[0x01, 0xd0], "rcall 0x104");
+}
+
+#[test]
+fn data() {
+    test_display(&[0x80, 0x91, 0x00, 0x01], "lds r24, 0x0100");
+    test_display(&[0x90, 0x93, 0x01, 0x01], "sts 0x0101, r25");
+    test_display(&[0x8d, 0x91], "ld r24, X+");
+    test_display(&[0x89, 0x81], "ldd r24, Y+1");
+    test_display(&[0x80, 0x81], "ld r24, Z");
+    test_display(&[0x92, 0x93], "st -Z, r25");
+    test_display(&[0x85, 0x91], "lpm r24, Z+");
+    test_display(&[0x0f, 0x92], "push r0");
+    test_display(&[0x0f, 0x90], "pop r0");
+}
+
+#[test]
+fn arithmetic() {
+    test_display(&[0x01, 0x96], "adiw r24, 0x01");
+    test_display(&[0x88, 0x0f], "lsl r24");
+    test_display(&[0x99, 0x1f], "rol r25");
+    test_display(&[0x88, 0x23], "tst r24");
+    test_display(&[0x9c, 0x01], "movw r18, r24");
+    test_display(&[0x26, 0x9f], "mul r18, r22");
+    test_display(&[0x8f, 0x5f], "subi r24, 0xff");
+}
+
+#[test]
+fn io() {
+    test_display(&[0x85, 0xb1], "in r24, 0x05");
+    test_display(&[0x2d, 0x9a], "sbi 0x05, 5");
+    test_display(&[0x2d, 0x98], "cbi 0x05, 5");
+    test_display(&[0x87, 0xfd], "sbrc r24, 7");
+}
+
+#[test]
+fn control() {
+    test_display(&[0x08, 0x95], "ret");
+    test_display(&[0x18, 0x95], "reti");
+    test_display(&[0x09, 0x94], "ijmp");
+    test_display(&[0x88, 0x95], "sleep");
+    test_display(&[0x78, 0x94], "sei");
+    test_display(&[0x00, 0x00], "nop");
+    test_display(&[0xff, 0xff], "Error { kind: InvalidOpcode, size: 2 }");
+}
@@ -0,0 +1,603 @@
+//! eBPF disassembler.
+//!
+//! Instructions are 8 bytes, except for `lddw` which loads a 64-bit immediate split over two.
+//! Mnemonics follow the assembler syntax of ubpf, with the width of the operation as a suffix.
+
+mod tests;
+
+use debugvault::Index;
+use decoder::{encode_hex, encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+macro_rules! operands {
+    [] => {([$crate::Operand::Nothing; 3], 0)};
+    [$($x:expr),+ $(,)?] => {{
+        let mut operands = [$crate::Operand::Nothing; 3];
+        let mut idx = 0;
+        $(
+            idx += 1;
+            operands[idx - 1] = $x;
+        )*
+
+        (operands, idx)
+    }};
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Opcode {
+    // *arithmetic and logic*
+    ADD,
+    SUB,
+    MUL,
+    DIV,
+    SDIV,
+    OR,
+    AND,
+    LSH,
+    RSH,
+    NEG,
+    MOD,
+    SMOD,
+    XOR,
+    MOV,
+    MOVSXB,
+    MOVSXH,
+    MOVSXW,
+    ARSH,
+    LE,
+    BE,
+    BSWAP,
+    // *loads and stores*
+    LDDW,
+    LDABS,
+    LDIND,
+    LDX,
+    LDXS,
+    ST,
+    STX,
+    // *atomics*
+    ATOMIC_ADD,
+    ATOMIC_OR,
+    ATOMIC_AND,
+    ATOMIC_XOR,
+    ATOMIC_FETCH_ADD,
+    ATOMIC_FETCH_OR,
+    ATOMIC_FETCH_AND,
+    ATOMIC_FETCH_XOR,
+    XCHG,
+    CMPXCHG,
+    // *jumps*
+    JA,
+    JEQ,
+    JGT,
+    JGE,
+    JSET,
+    JNE,
+    JSGT,
+    JSGE,
+    JLT,
+    JLE,
+    JSLT,
+    JSLE,
+    CALL,
+    EXIT,
+}
+
+impl Opcode {
+    pub fn as_str(&self) -> &'static str {
+        OPCODE_NAMES[*self as usize]
+    }
+
+    fn is_branch(&self) -> bool {
+        (Self::JEQ as u8..=Self::JSLE as u8).contains(&(*self as u8))
+    }
+}
+
+static OPCODE_NAMES: [&str; 52] = [
+    "add", "sub", "mul", "div", "sdiv", "or", "and", "lsh", "rsh", "neg", "mod", "smod", "xor",
+    "mov", "movsxb", "movsxh", "movsxw", "arsh", "le", "be", "bswap", "lddw", "ldabs", "ldind",
+    "ldx", "ldxs", "st", "stx", "atomic_add", "atomic_or", "atomic_and", "atomic_xor",
+    "atomic_fetch_add", "atomic_fetch_or", "atomic_fetch_and", "atomic_fetch_xor", "xchg",
+    "cmpxchg", "ja", "jeq", "jgt", "jge", "jset", "jne", "jsgt", "jsge", "jlt", "jle", "jslt",
+    "jsle", "call", "exit",
+];
+
+/// Helper functions of the kernel by the number they're called with, as in `linux/bpf.h`.
+static HELPERS: [&str; 212] = [
+    "unspec", "map_lookup_elem", "map_update_elem", "map_delete_elem", "probe_read",
+    "ktime_get_ns", "trace_printk", "get_prandom_u32", "get_smp_processor_id", "skb_store_bytes",
+    "l3_csum_replace", "l4_csum_replace", "tail_call", "clone_redirect", "get_current_pid_tgid",
+    "get_current_uid_gid", "get_current_comm", "get_cgroup_classid", "skb_vlan_push",
+    "skb_vlan_pop", "skb_get_tunnel_key", "skb_set_tunnel_key", "perf_event_read", "redirect",
+    "get_route_realm", "perf_event_output", "skb_load_bytes", "get_stackid", "csum_diff",
+    "skb_get_tunnel_opt", "skb_set_tunnel_opt", "skb_change_proto", "skb_change_type",
+    "skb_under_cgroup", "get_hash_recalc", "get_current_task", "probe_write_user",
+    "current_task_under_cgroup", "skb_change_tail", "skb_pull_data", "csum_update",
+    "set_hash_invalid", "get_numa_node_id", "skb_change_head", "xdp_adjust_head",
+    "probe_read_str", "get_socket_cookie", "get_socket_uid", "set_hash", "setsockopt",
+    "skb_adjust_room", "redirect_map", "sk_redirect_map", "sock_map_update", "xdp_adjust_meta",
+    "perf_event_read_value", "perf_prog_read_value", "getsockopt", "override_return",
+    "sock_ops_cb_flags_set", "msg_redirect_map", "msg_apply_bytes", "msg_cork_bytes",
+    "msg_pull_data", "bind", "xdp_adjust_tail", "skb_get_xfrm_state", "get_stack",
+    "skb_load_bytes_relative", "fib_lookup", "sock_hash_update", "msg_redirect_hash",
+    "sk_redirect_hash", "lwt_push_encap", "lwt_seg6_store_bytes", "lwt_seg6_adjust_srh",
+    "lwt_seg6_action", "rc_repeat", "rc_keydown", "skb_cgroup_id", "get_current_cgroup_id",
+    "get_local_storage", "sk_select_reuseport", "skb_ancestor_cgroup_id", "sk_lookup_tcp",
+    "sk_lookup_udp", "sk_release", "map_push_elem", "map_pop_elem", "map_peek_elem",
+    "msg_push_data", "msg_pop_data", "rc_pointer_rel", "spin_lock", "spin_unlock", "sk_fullsock",
+    "tcp_sock", "skb_ecn_set_ce", "get_listener_sock", "skc_lookup_tcp", "tcp_check_syncookie",
+    "sysctl_get_name", "sysctl_get_current_value", "sysctl_get_new_value",
+    "sysctl_set_new_value", "strtol", "strtoul", "sk_storage_get", "sk_storage_delete",
+    "send_signal", "tcp_gen_syncookie", "skb_output", "probe_read_user", "probe_read_kernel",
+    "probe_read_user_str", "probe_read_kernel_str", "tcp_send_ack", "send_signal_thread",
+    "jiffies64", "read_branch_records", "get_ns_current_pid_tgid", "xdp_output",
+    "get_netns_cookie", "get_current_ancestor_cgroup_id", "sk_assign", "ktime_get_boot_ns",
+    "seq_printf", "seq_write", "sk_cgroup_id", "sk_ancestor_cgroup_id", "ringbuf_output",
+    "ringbuf_reserve", "ringbuf_submit", "ringbuf_discard", "ringbuf_query", "csum_level",
+    "skc_to_tcp6_sock", "skc_to_tcp_sock", "skc_to_tcp_timewait_sock",
+    "skc_to_tcp_request_sock", "skc_to_udp6_sock", "get_task_stack", "load_hdr_opt",
+    "store_hdr_opt", "reserve_hdr_opt", "inode_storage_get", "inode_storage_delete", "d_path",
+    "copy_from_user", "snprintf_btf", "seq_printf_btf", "skb_cgroup_classid", "redirect_neigh",
+    "per_cpu_ptr", "this_cpu_ptr", "redirect_peer", "task_storage_get", "task_storage_delete",
+    "get_current_task_btf", "bprm_opts_set", "ktime_get_coarse_ns", "ima_inode_hash",
+    "sock_from_file", "check_mtu", "for_each_map_elem", "snprintf", "sys_bpf",
+    "btf_find_by_name_kind", "sys_close", "timer_init", "timer_set_callback", "timer_start",
+    "timer_cancel", "get_func_ip", "get_attach_cookie", "task_pt_regs", "get_branch_snapshot",
+    "trace_vprintk", "skc_to_unix_sock", "kallsyms_lookup_name", "find_vma", "loop", "strncmp",
+    "get_func_arg", "get_func_ret", "get_func_arg_cnt", "get_retval", "set_retval",
+    "xdp_get_buff_len", "xdp_load_bytes", "xdp_store_bytes", "copy_from_user_task",
+    "skb_set_tstamp", "ima_file_hash", "kptr_xchg", "map_lookup_percpu_elem",
+    "skc_to_mptcp_sock", "dynptr_from_mem", "ringbuf_reserve_dynptr", "ringbuf_submit_dynptr",
+    "ringbuf_discard_dynptr", "dynptr_read", "dynptr_write", "dynptr_data",
+    "tcp_raw_gen_syncookie_ipv4", "tcp_raw_gen_syncookie_ipv6", "tcp_raw_check_syncookie_ipv4",
+    "tcp_raw_check_syncookie_ipv6", "ktime_get_tai_ns", "user_ringbuf_drain", "cgrp_storage_get",
+    "cgrp_storage_delete",
+];
+
+/// Name of the helper function called with `id`, e.g. `bpf_map_lookup_elem`.
+pub fn helper_name(id: u32) -> Option<String> {
+    match HELPERS.get(id as usize) {
+        Some(name) if id != 0 => Some(format!("bpf_{name}")),
+        _ => None,
+    }
+}
+
+/// What's appended to the mnemonic, which is the width the instruction operates on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suffix {
+    None,
+    /// 64-bit arithmetic or atomics.
+    Bits64,
+    /// 32-bit arithmetic, atomics or comparisons.
+    Bits32,
+    /// Bits swapped by byte order conversions.
+    Swap(u8),
+    Byte,
+    Half,
+    Word,
+    Double,
+}
+
+impl Suffix {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Bits64 | Self::Swap(64) => "64",
+            Self::Bits32 | Self::Swap(32) => "32",
+            Self::Swap(_) => "16",
+            Self::Byte => "b",
+            Self::Half => "h",
+            Self::Word => "w",
+            Self::Double => "dw",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Register(u8),
+    Immediate(i32),
+    /// 64-bit immediate of `lddw`, which can be the address of a map or global.
+    Wide(u64),
+    /// Memory at a register plus an offset.
+    Memory(u8, i16),
+    /// Offset in bytes from the end of the instruction, before it's address is known.
+    Relative(i64),
+    Address(usize),
+    /// Kernel function called by it's number.
+    Helper(u32),
+    /// Kernel function called by it's BTF id.
+    Kfunc(u32),
+    /// Map referred to by the file descriptor it was loaded as.
+    MapFd(i32),
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let address = |stream: &mut TokenStream, addr: usize, exact: bool| {
+            match decoder::symbol_at(stream, symbols, addr, exact) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                }
+                None => stream.push_addr(encode_uhex(addr as u64), Kind::Immediate, addr),
+            }
+        };
+
+        match *self {
+            Self::Register(reg) => stream.push_owned(format!("r{reg}"), Kind::Register),
+            Self::Immediate(imm) => {
+                stream.push_owned(encode_hex(imm as i64), Kind::Immediate)
+            }
+            Self::Wide(imm) => address(stream, imm as usize, true),
+            Self::Memory(reg, offset) => {
+                stream.push("[", Kind::Delimiter);
+                stream.push_owned(format!("r{reg}"), Kind::Register);
+                if offset >= 0 {
+                    stream.push("+", Kind::Expr);
+                }
+                stream.push_owned(encode_hex(offset as i64), Kind::Immediate);
+                stream.push("]", Kind::Delimiter);
+            }
+            Self::Relative(offset) => {
+                let sign = if offset < 0 { "" } else { "+" };
+                let offset = format!("{sign}{}", encode_hex(offset));
+                stream.push_owned(offset, Kind::Immediate)
+            }
+            Self::Address(addr) => address(stream, addr, false),
+            Self::Helper(id) => match helper_name(id) {
+                Some(name) => stream.push_owned(name, Kind::Label),
+                None => stream.push_owned(format!("helper_{id}"), Kind::Label),
+            },
+            Self::Kfunc(id) => stream.push_owned(format!("kfunc_{id}"), Kind::Label),
+            Self::MapFd(fd) => {
+                stream.push("map_fd", Kind::Label);
+                stream.push("(", Kind::Delimiter);
+                stream.push_owned(fd.to_string(), Kind::Immediate);
+                stream.push(")", Kind::Delimiter);
+            }
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    opcode: Opcode,
+    suffix: Suffix,
+    operands: [Operand; 3],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    fn new(
+        opcode: Opcode,
+        suffix: Suffix,
+        (operands, operand_count): ([Operand; 3], usize),
+        len: usize,
+    ) -> Self {
+        Self {
+            opcode,
+            suffix,
+            operands,
+            operand_count,
+            len,
+        }
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let mnemonic = format!("{}{}", self.opcode.as_str(), self.suffix.as_str());
+        stream.push_owned(mnemonic, Kind::Mnemonic);
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            if let Operand::Relative(offset) = *operand {
+                let next = addr.wrapping_add(self.len);
+                *operand = Operand::Address(next.wrapping_add_signed(offset as isize));
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.opcode {
+            Opcode::CALL => XrefKind::Call,
+            Opcode::JA => XrefKind::Jump,
+            opcode if opcode.is_branch() => XrefKind::Jump,
+            _ => XrefKind::Data,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) => Some((addr, kind)),
+            // relocated addresses of maps and globals, rather than any constant
+            Operand::Wide(imm) if imm != 0 && imm <= u32::MAX as u64 => {
+                Some((imm as usize, XrefKind::Data))
+            }
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.opcode {
+            Opcode::JA => Flow::Jump,
+            Opcode::EXIT => Flow::Return,
+            opcode if opcode.is_branch() => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(reader).map_err(|err| Error::new(err, 8))
+    }
+
+    fn max_width(&self) -> usize {
+        16
+    }
+
+    fn instruction_alignment(&self) -> usize {
+        8
+    }
+}
+
+/// Fields of an instruction.
+struct Raw {
+    code: u8,
+    dst: u8,
+    src: u8,
+    off: i16,
+    imm: i32,
+}
+
+fn next_raw(reader: &mut decoder::Reader) -> Result<Raw, ErrorKind> {
+    let mut bytes = [0u8; 8];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(Raw {
+        code: bytes[0],
+        dst: bytes[1] & 0xf,
+        src: bytes[1] >> 4,
+        off: i16::from_le_bytes([bytes[2], bytes[3]]),
+        imm: i32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+    })
+}
+
+const CLASS_LD: u8 = 0x00;
+const CLASS_LDX: u8 = 0x01;
+const CLASS_ST: u8 = 0x02;
+const CLASS_STX: u8 = 0x03;
+const CLASS_ALU: u8 = 0x04;
+const CLASS_JMP: u8 = 0x05;
+const CLASS_JMP32: u8 = 0x06;
+const CLASS_ALU64: u8 = 0x07;
+
+const MODE_IMM: u8 = 0x00;
+const MODE_ABS: u8 = 0x20;
+const MODE_IND: u8 = 0x40;
+const MODE_MEM: u8 = 0x60;
+const MODE_MEMSX: u8 = 0x80;
+const MODE_ATOMIC: u8 = 0xc0;
+
+/// `src` of calls to other functions of the program, instead of to a helper.
+const PSEUDO_CALL: u8 = 1;
+/// `src` of calls to kernel functions by their BTF id.
+const PSEUDO_KFUNC_CALL: u8 = 2;
+/// `src` of `lddw` loading the file descriptor of a map.
+const PSEUDO_MAP_FD: u8 = 1;
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    let raw = next_raw(reader)?;
+    if raw.dst > 10 || raw.src > 10 {
+        return Err(ErrorKind::InvalidRegister);
+    }
+
+    match raw.code & 0b111 {
+        CLASS_ALU | CLASS_ALU64 => decode_alu(&raw),
+        CLASS_JMP | CLASS_JMP32 => decode_jmp(&raw),
+        _ => decode_mem(reader, &raw),
+    }
+}
+
+fn decode_alu(raw: &Raw) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+
+    let is_64 = raw.code & 0b111 == CLASS_ALU64;
+    let suffix = if is_64 { Suffix::Bits64 } else { Suffix::Bits32 };
+    let dst = Operand::Register(raw.dst);
+    let src = match raw.code & 0x08 {
+        0 => Operand::Immediate(raw.imm),
+        _ => Operand::Register(raw.src),
+    };
+
+    let opcode = match (raw.code >> 4, raw.off) {
+        (0x0, 0) => ADD,
+        (0x1, 0) => SUB,
+        (0x2, 0) => MUL,
+        (0x3, 0) => DIV,
+        (0x3, 1) => SDIV,
+        (0x4, 0) => OR,
+        (0x5, 0) => AND,
+        (0x6, 0) => LSH,
+        (0x7, 0) => RSH,
+        (0x8, 0) => return Ok(Instruction::new(NEG, suffix, operands![dst], 8)),
+        (0x9, 0) => MOD,
+        (0x9, 1) => SMOD,
+        (0xa, 0) => XOR,
+        (0xb, 0) => MOV,
+        // sign extending moves only exist between registers
+        (0xb, 8) if raw.code & 0x08 != 0 => MOVSXB,
+        (0xb, 16) if raw.code & 0x08 != 0 => MOVSXH,
+        (0xb, 32) if raw.code & 0x08 != 0 && is_64 => MOVSXW,
+        (0xc, 0) => ARSH,
+        (0xd, 0) => {
+            let opcode = match (is_64, raw.code & 0x08 != 0) {
+                (false, false) => LE,
+                (false, true) => BE,
+                (true, false) => BSWAP,
+                (true, true) => return Err(ErrorKind::InvalidOpcode),
+            };
+
+            let bits = match raw.imm {
+                16 | 32 | 64 => raw.imm as u8,
+                _ => return Err(ErrorKind::InvalidOperand),
+            };
+
+            return Ok(Instruction::new(opcode, Suffix::Swap(bits), operands![dst], 8));
+        }
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    Ok(Instruction::new(opcode, suffix, operands![dst, src], 8))
+}
+
+fn decode_jmp(raw: &Raw) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+
+    let is_32 = raw.code & 0b111 == CLASS_JMP32;
+    let suffix = if is_32 { Suffix::Bits32 } else { Suffix::None };
+    let target = Operand::Relative(raw.off as i64 * 8);
+
+    let opcode = match raw.code >> 4 {
+        // `gotol`, with a 32-bit offset in the immediate
+        0x0 if is_32 => {
+            let target = Operand::Relative(raw.imm as i64 * 8);
+            return Ok(Instruction::new(JA, Suffix::None, operands![target], 8));
+        }
+        0x0 => return Ok(Instruction::new(JA, Suffix::None, operands![target], 8)),
+        0x1 => JEQ,
+        0x2 => JGT,
+        0x3 => JGE,
+        0x4 => JSET,
+        0x5 => JNE,
+        0x6 => JSGT,
+        0x7 => JSGE,
+        0x8 if !is_32 => {
+            let target = match raw.src {
+                0 => Operand::Helper(raw.imm as u32),
+                PSEUDO_CALL => Operand::Relative(raw.imm as i64 * 8),
+                PSEUDO_KFUNC_CALL => Operand::Kfunc(raw.imm as u32),
+                _ => return Err(ErrorKind::InvalidOperand),
+            };
+
+            return Ok(Instruction::new(CALL, Suffix::None, operands![target], 8));
+        }
+        0x9 if !is_32 => return Ok(Instruction::new(EXIT, Suffix::None, operands![], 8)),
+        0xa => JLT,
+        0xb => JLE,
+        0xc => JSLT,
+        0xd => JSLE,
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let dst = Operand::Register(raw.dst);
+    let src = match raw.code & 0x08 {
+        0 => Operand::Immediate(raw.imm),
+        _ => Operand::Register(raw.src),
+    };
+
+    Ok(Instruction::new(opcode, suffix, operands![dst, src, target], 8))
+}
+
+fn decode_mem(reader: &mut decoder::Reader, raw: &Raw) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+
+    let size = match raw.code & 0x18 {
+        0x00 => Suffix::Word,
+        0x08 => Suffix::Half,
+        0x10 => Suffix::Byte,
+        _ => Suffix::Double,
+    };
+
+    let dst = Operand::Register(raw.dst);
+    let src = Operand::Register(raw.src);
+
+    let inst = match (raw.code & 0b111, raw.code & 0xe0) {
+        (CLASS_LD, MODE_IMM) if size == Suffix::Double => {
+            let high = next_raw(reader)?;
+            if high.code != 0 || high.dst != 0 || high.src != 0 || high.off != 0 {
+                return Err(ErrorKind::InvalidOperand);
+            }
+
+            let imm = (high.imm as u32 as u64) << 32 | raw.imm as u32 as u64;
+            let imm = match raw.src {
+                PSEUDO_MAP_FD => Operand::MapFd(raw.imm),
+                _ => Operand::Wide(imm),
+            };
+
+            Instruction::new(LDDW, Suffix::None, operands![dst, imm], 16)
+        }
+        // legacy packet access, through an offset into the packet in r6
+        (CLASS_LD, MODE_ABS) => {
+            Instruction::new(LDABS, size, operands![Operand::Immediate(raw.imm)], 8)
+        }
+        (CLASS_LD, MODE_IND) => {
+            Instruction::new(LDIND, size, operands![src, Operand::Immediate(raw.imm)], 8)
+        }
+        (CLASS_LDX, MODE_MEM) => {
+            Instruction::new(LDX, size, operands![dst, Operand::Memory(raw.src, raw.off)], 8)
+        }
+        (CLASS_LDX, MODE_MEMSX) if size != Suffix::Double => {
+            Instruction::new(LDXS, size, operands![dst, Operand::Memory(raw.src, raw.off)], 8)
+        }
+        (CLASS_ST, MODE_MEM) => {
+            let mem = Operand::Memory(raw.dst, raw.off);
+            Instruction::new(ST, size, operands![mem, Operand::Immediate(raw.imm)], 8)
+        }
+        (CLASS_STX, MODE_MEM) => {
+            Instruction::new(STX, size, operands![Operand::Memory(raw.dst, raw.off), src], 8)
+        }
+        (CLASS_STX, MODE_ATOMIC) if matches!(size, Suffix::Word | Suffix::Double) => {
+            let opcode = match raw.imm {
+                0x00 => ATOMIC_ADD,
+                0x40 => ATOMIC_OR,
+                0x50 => ATOMIC_AND,
+                0xa0 => ATOMIC_XOR,
+                0x01 => ATOMIC_FETCH_ADD,
+                0x41 => ATOMIC_FETCH_OR,
+                0x51 => ATOMIC_FETCH_AND,
+                0xa1 => ATOMIC_FETCH_XOR,
+                0xe1 => XCHG,
+                0xf1 => CMPXCHG,
+                _ => return Err(ErrorKind::InvalidOperand),
+            };
+
+            let suffix = if size == Suffix::Double { Suffix::Bits64 } else { Suffix::Bits32 };
+            let mem = Operand::Memory(raw.dst, raw.off);
+            Instruction::new(opcode, suffix, operands![mem, src], 8)
+        }
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    Ok(inst)
+}
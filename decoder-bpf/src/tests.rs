@@ -0,0 +1,83 @@
+#![cfg(test)]
+
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let decoder = crate::Decoder;
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn alu() {
+    test_display(&[0xbf, 0x16, 0, 0, 0, 0, 0, 0], "mov64 r6, r1");
+    test_display(&[0xb7, 0x00, 0, 0, 0x01, 0, 0, 0], "mov64 r0, 0x1");
+    test_display(&[0x07, 0x0a, 0, 0, 0xf8, 0xff, 0xff, 0xff], "add64 r10, -0x8");
+    test_display(&[0x04, 0x01, 0, 0, 0x10, 0, 0, 0], "add32 r1, 0x10");
+    test_display(&[0x87, 0x01, 0, 0, 0, 0, 0, 0], "neg64 r1");
+    test_display(&[0x3f, 0x21, 0x01, 0, 0, 0, 0, 0], "sdiv64 r1, r2");
+    test_display(&[0xbf, 0x21, 0x08, 0, 0, 0, 0, 0], "movsxb64 r1, r2");
+    test_display(&[0xdc, 0x01, 0, 0, 0x10, 0, 0, 0], "be16 r1");
+    test_display(&[0xd7, 0x01, 0, 0, 0x40, 0, 0, 0], "bswap64 r1");
+    test_display(&[0xbf, 0xb1, 0, 0, 0, 0, 0, 0], "Error { kind: InvalidRegister, size: 8 }");
+}
+
+#[test]
+fn memory() {
+    test_display(&[0x61, 0x12, 0x04, 0, 0, 0, 0, 0], "ldxw r2, [r1+0x4]");
+    test_display(&[0x7b, 0x1a, 0xf8, 0xff, 0, 0, 0, 0], "stxdw [r10-0x8], r1");
+    test_display(&[0x62, 0x0a, 0xfc, 0xff, 0, 0, 0, 0], "stw [r10-0x4], 0x0");
+    test_display(&[0x91, 0x12, 0, 0, 0, 0, 0, 0], "ldxsb r2, [r1+0x0]");
+    test_display(&[0x30, 0, 0, 0, 0x0c, 0, 0, 0], "ldabsb 0xc");
+    test_display(&[0xdb, 0x21, 0, 0, 0x01, 0, 0, 0], "atomic_fetch_add64 [r1+0x0], r2");
+    test_display(&[0xc3, 0x21, 0, 0, 0xe1, 0, 0, 0], "xchg32 [r1+0x0], r2");
+}
+
+#[test]
+fn lddw() {
+    test_display(
+        &[0x18, 0x11, 0, 0, 0x05, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        "lddw r1, map_fd(5)",
+    );
+    test_display(
+        &[0x18, 0x01, 0, 0, 0x00, 0x20, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0],
+        "lddw r1, 0x100002000",
+    );
+    test_display(&[0x18, 0x01, 0, 0, 0, 0, 0, 0], "Error { kind: ExhaustedInput, size: 8 }");
+}
+
+#[test]
+fn jumps() {
+    test_display_at(0x100, &[0x05, 0, 0x02, 0, 0, 0, 0, 0], "ja 0x118");
+    test_display_at(0x100, &[0x15, 0x01, 0xfe, 0xff, 0, 0, 0, 0], "jeq r1, 0x0, 0xf8");
+    test_display_at(0x100, &[0x2e, 0x21, 0x01, 0, 0, 0, 0, 0], "jgt32 r1, r2, 0x110");
+    test_display_at(0x100, &[0x06, 0, 0, 0, 0x01, 0, 0, 0], "ja 0x110");
+    test_display(&[0x95, 0, 0, 0, 0, 0, 0, 0], "exit");
+}
+
+#[test]
+fn calls() {
+    test_display(&[0x85, 0, 0, 0, 0x01, 0, 0, 0], "call bpf_map_lookup_elem");
+    test_display(&[0x85, 0, 0, 0, 0x06, 0, 0, 0], "call bpf_trace_printk");
+    test_display(&[0x85, 0, 0, 0, 0x82, 0, 0, 0], "call bpf_ringbuf_output");
+    test_display(&[0x85, 0, 0, 0, 0xd3, 0, 0, 0], "call bpf_cgrp_storage_delete");
+    test_display(&[0x85, 0, 0, 0, 0xe8, 0x03, 0, 0], "call helper_1000");
+    test_display_at(0x100, &[0x85, 0x10, 0, 0, 0x03, 0, 0, 0], "call 0x120");
+    test_display(&[0x85, 0x20, 0, 0, 0x07, 0, 0, 0], "call kfunc_7");
+}
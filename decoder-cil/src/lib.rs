@@ -0,0 +1,649 @@
+//! Common Intermediate Language disassembler, the bytecode of .NET.
+//!
+//! Instructions refer to strings, types, fields and methods by their metadata token, which are
+//! resolved through a [`Pool`] of the module they're in. Method bodies start with a header and
+//! may end in exception handling clauses, which the pool marks so they're shown as directives
+//! in place of being decoded as instructions. Mnemonics follow ILDasm.
+
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use debugvault::Index;
+use decoder::{encode_hex, encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+/// Table of the user strings referred to by `ldstr`, in the high byte of a token.
+pub const USER_STRING: u32 = 0x70;
+
+/// What's stored in front of or after the instructions of a method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Header {
+    /// Header of a method body, with the token of it's local variables' signature.
+    Method {
+        len: usize,
+        max_stack: u16,
+        locals: u32,
+    },
+    /// Sections of exception handling clauses following a method's instructions.
+    Clauses { len: usize, count: usize },
+}
+
+/// Metadata of a module, resolving the tokens of instructions.
+#[derive(Debug, Default)]
+pub struct Pool {
+    /// Strings of `ldstr` by their token.
+    pub strings: HashMap<u32, Arc<str>>,
+    /// Names of the types, fields, methods and signatures of the metadata tables by token.
+    pub names: HashMap<u32, Arc<str>>,
+    /// Address of the body of methods defined in the module, by their token.
+    pub code: HashMap<u32, usize>,
+    /// Headers and exception handling clauses of method bodies by their address.
+    pub headers: HashMap<usize, Header>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Immediate(i64),
+    Float(f64),
+    Argument(u16),
+    Local(u16),
+    /// Offset in bytes from the start of the instruction, before it's address is known.
+    Relative(i64),
+    Address(usize),
+    /// Targets of `switch`, relative until their addresses are known.
+    Targets(Vec<i64>),
+    Addresses(Vec<usize>),
+    String(Arc<str>),
+    /// Type, field, method or signature together with the address of it's body, if it's a
+    /// method of the module.
+    Member(Arc<str>, Option<usize>),
+    /// Signature of the local variables of a method.
+    Locals(Arc<str>),
+    /// Token that isn't in the metadata.
+    Token(u32),
+}
+
+fn address(stream: &mut TokenStream, symbols: &Index, addr: usize) {
+    match decoder::symbol_at(stream, symbols, addr, false) {
+        Some((symbol, offset)) => {
+            stream.push_symbol(symbol.name(), addr);
+            stream.push_offset(offset, addr);
+        }
+        None => stream.push_addr(encode_uhex(addr as u64), Kind::Immediate, addr),
+    }
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        match self {
+            Self::Immediate(imm) => {
+                stream.push_owned(encode_hex(*imm), Kind::Immediate)
+            }
+            Self::Float(imm) => stream.push_owned(format!("{imm:?}"), Kind::Immediate),
+            Self::Argument(idx) => {
+                stream.push_owned(format!("arg{idx}"), Kind::Register)
+            }
+            Self::Local(idx) => stream.push_owned(format!("V_{idx}"), Kind::Register),
+            Self::Relative(offset) => {
+                let sign = if *offset < 0 { "" } else { "+" };
+                let offset = format!("{sign}{}", encode_hex(*offset));
+                stream.push_owned(offset, Kind::Immediate)
+            }
+            Self::Address(addr) => address(stream, symbols, *addr),
+            Self::Targets(offsets) => {
+                stream.push("(", Kind::Delimiter);
+                for (idx, offset) in offsets.iter().enumerate() {
+                    if idx != 0 {
+                        stream.push(", ", Kind::Plain);
+                    }
+                    Self::Relative(*offset).tokenize(stream, symbols);
+                }
+                stream.push(")", Kind::Delimiter);
+            }
+            Self::Addresses(addrs) => {
+                stream.push("(", Kind::Delimiter);
+                for (idx, addr) in addrs.iter().enumerate() {
+                    if idx != 0 {
+                        stream.push(", ", Kind::Plain);
+                    }
+                    address(stream, symbols, *addr);
+                }
+                stream.push(")", Kind::Delimiter);
+            }
+            Self::String(string) => {
+                stream.push_owned(format!("{string:?}"), Kind::String)
+            }
+            Self::Member(name, _) => stream.push_owned(name.to_string(), Kind::Label),
+            Self::Locals(signature) => {
+                stream.push("locals ", Kind::Mnemonic);
+                stream.push_owned(signature.to_string(), Kind::Primitive)
+            }
+            Self::Token(token) => {
+                stream.push_owned(format!("token@{token:08x}"), Kind::Expr)
+            }
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    /// Opcode, with two byte opcodes prefixed by `0xfe`.
+    op: u16,
+    operands: [Operand; 2],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    fn new(mnemonic: &'static str, op: u16, operands: Vec<Operand>, len: usize) -> Self {
+        let operand_count = operands.len();
+        let mut operands = operands.into_iter();
+        let mut next = || operands.next().unwrap_or_default();
+        Self {
+            mnemonic,
+            op,
+            operands: [next(), next()],
+            operand_count,
+            len,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+
+    /// Whether the instruction is a method header or exception handling clauses.
+    pub fn is_directive(&self) -> bool {
+        self.mnemonic.starts_with('.')
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        stream.push(self.mnemonic, Kind::Mnemonic);
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            match operand {
+                Operand::Relative(offset) => {
+                    *operand = Operand::Address(addr.wrapping_add_signed(*offset as isize));
+                }
+                Operand::Targets(offsets) => {
+                    let addrs = offsets
+                        .iter()
+                        .map(|&offset| addr.wrapping_add_signed(offset as isize))
+                        .collect();
+                    *operand = Operand::Addresses(addrs);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        if self.is_directive() {
+            return None;
+        }
+
+        let kind = match self.op {
+            // calls and constructors
+            0x28 | 0x6f | 0x73 => XrefKind::Call,
+            // jumps to another method, branches and leaves
+            0x27 | 0x2b..=0x44 | 0xdd | 0xde => XrefKind::Jump,
+            _ => XrefKind::Data,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) | Operand::Member(_, Some(addr)) => Some((addr, kind)),
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        if self.is_directive() {
+            return Flow::Next;
+        }
+
+        match self.op {
+            // conditional branches
+            0x2c..=0x37 | 0x39..=0x44 => Flow::Branch,
+            // branches and leaves, switches fall through when out of range
+            0x2b | 0x38 | 0xdd | 0xde => Flow::Jump,
+            // jumps to another method, returns, throws and the ends of handlers
+            0x27 | 0x2a | 0x7a | 0xdc | 0xfe11 | 0xfe1a => Flow::Return,
+            _ => Flow::Next,
+        }
+    }
+}
+
+pub struct Decoder<'a> {
+    pub pool: &'a Pool,
+    /// Address the input starts at, used to find the method headers and exception handling
+    /// clauses marked in the pool.
+    pub addr: usize,
+}
+
+impl decoder::Decodable for Decoder<'_> {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(self, reader).map_err(|err| Error::new(err, 1))
+    }
+
+    fn max_width(&self) -> usize {
+        9
+    }
+}
+
+/// How the operand of an opcode is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Nothing,
+    I8,
+    U8,
+    I32,
+    I64,
+    F32,
+    F64,
+    Arg8,
+    Arg16,
+    Local8,
+    Local16,
+    Branch8,
+    Branch32,
+    Switch,
+    Token,
+}
+
+/// Mnemonics and operands of the opcodes of a single byte.
+static ONE_BYTE: [(&str, Encoding); 0xe1] = {
+    use Encoding::*;
+
+    let mut opcodes = [("", Nothing); 0xe1];
+    let known = [
+        (0x00, "nop", Nothing),
+        (0x01, "break", Nothing),
+        (0x02, "ldarg.0", Nothing),
+        (0x03, "ldarg.1", Nothing),
+        (0x04, "ldarg.2", Nothing),
+        (0x05, "ldarg.3", Nothing),
+        (0x06, "ldloc.0", Nothing),
+        (0x07, "ldloc.1", Nothing),
+        (0x08, "ldloc.2", Nothing),
+        (0x09, "ldloc.3", Nothing),
+        (0x0a, "stloc.0", Nothing),
+        (0x0b, "stloc.1", Nothing),
+        (0x0c, "stloc.2", Nothing),
+        (0x0d, "stloc.3", Nothing),
+        (0x0e, "ldarg.s", Arg8),
+        (0x0f, "ldarga.s", Arg8),
+        (0x10, "starg.s", Arg8),
+        (0x11, "ldloc.s", Local8),
+        (0x12, "ldloca.s", Local8),
+        (0x13, "stloc.s", Local8),
+        (0x14, "ldnull", Nothing),
+        (0x15, "ldc.i4.m1", Nothing),
+        (0x16, "ldc.i4.0", Nothing),
+        (0x17, "ldc.i4.1", Nothing),
+        (0x18, "ldc.i4.2", Nothing),
+        (0x19, "ldc.i4.3", Nothing),
+        (0x1a, "ldc.i4.4", Nothing),
+        (0x1b, "ldc.i4.5", Nothing),
+        (0x1c, "ldc.i4.6", Nothing),
+        (0x1d, "ldc.i4.7", Nothing),
+        (0x1e, "ldc.i4.8", Nothing),
+        (0x1f, "ldc.i4.s", I8),
+        (0x20, "ldc.i4", I32),
+        (0x21, "ldc.i8", I64),
+        (0x22, "ldc.r4", F32),
+        (0x23, "ldc.r8", F64),
+        (0x25, "dup", Nothing),
+        (0x26, "pop", Nothing),
+        (0x27, "jmp", Token),
+        (0x28, "call", Token),
+        (0x29, "calli", Token),
+        (0x2a, "ret", Nothing),
+        (0x2b, "br.s", Branch8),
+        (0x2c, "brfalse.s", Branch8),
+        (0x2d, "brtrue.s", Branch8),
+        (0x2e, "beq.s", Branch8),
+        (0x2f, "bge.s", Branch8),
+        (0x30, "bgt.s", Branch8),
+        (0x31, "ble.s", Branch8),
+        (0x32, "blt.s", Branch8),
+        (0x33, "bne.un.s", Branch8),
+        (0x34, "bge.un.s", Branch8),
+        (0x35, "bgt.un.s", Branch8),
+        (0x36, "ble.un.s", Branch8),
+        (0x37, "blt.un.s", Branch8),
+        (0x38, "br", Branch32),
+        (0x39, "brfalse", Branch32),
+        (0x3a, "brtrue", Branch32),
+        (0x3b, "beq", Branch32),
+        (0x3c, "bge", Branch32),
+        (0x3d, "bgt", Branch32),
+        (0x3e, "ble", Branch32),
+        (0x3f, "blt", Branch32),
+        (0x40, "bne.un", Branch32),
+        (0x41, "bge.un", Branch32),
+        (0x42, "bgt.un", Branch32),
+        (0x43, "ble.un", Branch32),
+        (0x44, "blt.un", Branch32),
+        (0x45, "switch", Switch),
+        (0x46, "ldind.i1", Nothing),
+        (0x47, "ldind.u1", Nothing),
+        (0x48, "ldind.i2", Nothing),
+        (0x49, "ldind.u2", Nothing),
+        (0x4a, "ldind.i4", Nothing),
+        (0x4b, "ldind.u4", Nothing),
+        (0x4c, "ldind.i8", Nothing),
+        (0x4d, "ldind.i", Nothing),
+        (0x4e, "ldind.r4", Nothing),
+        (0x4f, "ldind.r8", Nothing),
+        (0x50, "ldind.ref", Nothing),
+        (0x51, "stind.ref", Nothing),
+        (0x52, "stind.i1", Nothing),
+        (0x53, "stind.i2", Nothing),
+        (0x54, "stind.i4", Nothing),
+        (0x55, "stind.i8", Nothing),
+        (0x56, "stind.r4", Nothing),
+        (0x57, "stind.r8", Nothing),
+        (0x58, "add", Nothing),
+        (0x59, "sub", Nothing),
+        (0x5a, "mul", Nothing),
+        (0x5b, "div", Nothing),
+        (0x5c, "div.un", Nothing),
+        (0x5d, "rem", Nothing),
+        (0x5e, "rem.un", Nothing),
+        (0x5f, "and", Nothing),
+        (0x60, "or", Nothing),
+        (0x61, "xor", Nothing),
+        (0x62, "shl", Nothing),
+        (0x63, "shr", Nothing),
+        (0x64, "shr.un", Nothing),
+        (0x65, "neg", Nothing),
+        (0x66, "not", Nothing),
+        (0x67, "conv.i1", Nothing),
+        (0x68, "conv.i2", Nothing),
+        (0x69, "conv.i4", Nothing),
+        (0x6a, "conv.i8", Nothing),
+        (0x6b, "conv.r4", Nothing),
+        (0x6c, "conv.r8", Nothing),
+        (0x6d, "conv.u4", Nothing),
+        (0x6e, "conv.u8", Nothing),
+        (0x6f, "callvirt", Token),
+        (0x70, "cpobj", Token),
+        (0x71, "ldobj", Token),
+        (0x72, "ldstr", Token),
+        (0x73, "newobj", Token),
+        (0x74, "castclass", Token),
+        (0x75, "isinst", Token),
+        (0x76, "conv.r.un", Nothing),
+        (0x79, "unbox", Token),
+        (0x7a, "throw", Nothing),
+        (0x7b, "ldfld", Token),
+        (0x7c, "ldflda", Token),
+        (0x7d, "stfld", Token),
+        (0x7e, "ldsfld", Token),
+        (0x7f, "ldsflda", Token),
+        (0x80, "stsfld", Token),
+        (0x81, "stobj", Token),
+        (0x82, "conv.ovf.i1.un", Nothing),
+        (0x83, "conv.ovf.i2.un", Nothing),
+        (0x84, "conv.ovf.i4.un", Nothing),
+        (0x85, "conv.ovf.i8.un", Nothing),
+        (0x86, "conv.ovf.u1.un", Nothing),
+        (0x87, "conv.ovf.u2.un", Nothing),
+        (0x88, "conv.ovf.u4.un", Nothing),
+        (0x89, "conv.ovf.u8.un", Nothing),
+        (0x8a, "conv.ovf.i.un", Nothing),
+        (0x8b, "conv.ovf.u.un", Nothing),
+        (0x8c, "box", Token),
+        (0x8d, "newarr", Token),
+        (0x8e, "ldlen", Nothing),
+        (0x8f, "ldelema", Token),
+        (0x90, "ldelem.i1", Nothing),
+        (0x91, "ldelem.u1", Nothing),
+        (0x92, "ldelem.i2", Nothing),
+        (0x93, "ldelem.u2", Nothing),
+        (0x94, "ldelem.i4", Nothing),
+        (0x95, "ldelem.u4", Nothing),
+        (0x96, "ldelem.i8", Nothing),
+        (0x97, "ldelem.i", Nothing),
+        (0x98, "ldelem.r4", Nothing),
+        (0x99, "ldelem.r8", Nothing),
+        (0x9a, "ldelem.ref", Nothing),
+        (0x9b, "stelem.i", Nothing),
+        (0x9c, "stelem.i1", Nothing),
+        (0x9d, "stelem.i2", Nothing),
+        (0x9e, "stelem.i4", Nothing),
+        (0x9f, "stelem.i8", Nothing),
+        (0xa0, "stelem.r4", Nothing),
+        (0xa1, "stelem.r8", Nothing),
+        (0xa2, "stelem.ref", Nothing),
+        (0xa3, "ldelem", Token),
+        (0xa4, "stelem", Token),
+        (0xa5, "unbox.any", Token),
+        (0xb3, "conv.ovf.i1", Nothing),
+        (0xb4, "conv.ovf.u1", Nothing),
+        (0xb5, "conv.ovf.i2", Nothing),
+        (0xb6, "conv.ovf.u2", Nothing),
+        (0xb7, "conv.ovf.i4", Nothing),
+        (0xb8, "conv.ovf.u4", Nothing),
+        (0xb9, "conv.ovf.i8", Nothing),
+        (0xba, "conv.ovf.u8", Nothing),
+        (0xc2, "refanyval", Token),
+        (0xc3, "ckfinite", Nothing),
+        (0xc6, "mkrefany", Token),
+        (0xd0, "ldtoken", Token),
+        (0xd1, "conv.u2", Nothing),
+        (0xd2, "conv.u1", Nothing),
+        (0xd3, "conv.i", Nothing),
+        (0xd4, "conv.ovf.i", Nothing),
+        (0xd5, "conv.ovf.u", Nothing),
+        (0xd6, "add.ovf", Nothing),
+        (0xd7, "add.ovf.un", Nothing),
+        (0xd8, "mul.ovf", Nothing),
+        (0xd9, "mul.ovf.un", Nothing),
+        (0xda, "sub.ovf", Nothing),
+        (0xdb, "sub.ovf.un", Nothing),
+        (0xdc, "endfinally", Nothing),
+        (0xdd, "leave", Branch32),
+        (0xde, "leave.s", Branch8),
+        (0xdf, "stind.i", Nothing),
+        (0xe0, "conv.u", Nothing),
+    ];
+
+    let mut idx = 0;
+    while idx < known.len() {
+        let (op, mnemonic, kind) = known[idx];
+        opcodes[op] = (mnemonic, kind);
+        idx += 1;
+    }
+
+    opcodes
+};
+
+/// Mnemonics and operands of the opcodes following `0xfe`.
+static TWO_BYTE: [(&str, Encoding); 0x1f] = {
+    use Encoding::*;
+
+    [
+        ("arglist", Nothing),
+        ("ceq", Nothing),
+        ("cgt", Nothing),
+        ("cgt.un", Nothing),
+        ("clt", Nothing),
+        ("clt.un", Nothing),
+        ("ldftn", Token),
+        ("ldvirtftn", Token),
+        ("", Nothing),
+        ("ldarg", Arg16),
+        ("ldarga", Arg16),
+        ("starg", Arg16),
+        ("ldloc", Local16),
+        ("ldloca", Local16),
+        ("stloc", Local16),
+        ("localloc", Nothing),
+        ("", Nothing),
+        ("endfilter", Nothing),
+        ("unaligned.", U8),
+        ("volatile.", Nothing),
+        ("tail.", Nothing),
+        ("initobj", Token),
+        ("constrained.", Token),
+        ("cpblk", Nothing),
+        ("initblk", Nothing),
+        ("no.", U8),
+        ("rethrow", Nothing),
+        ("", Nothing),
+        ("sizeof", Token),
+        ("refanytype", Nothing),
+        ("readonly.", Nothing),
+    ]
+};
+
+fn read<const N: usize>(reader: &mut decoder::Reader) -> Result<[u8; N], ErrorKind> {
+    let mut bytes = [0u8; N];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(bytes)
+}
+
+/// Skip `len` bytes.
+fn skip(reader: &mut decoder::Reader, mut len: usize) -> Result<(), ErrorKind> {
+    let mut buf = [0u8; 64];
+    while len > 0 {
+        let chunk = len.min(buf.len());
+        reader.next_n(&mut buf[..chunk]).ok_or(ErrorKind::ExhaustedInput)?;
+        len -= chunk;
+    }
+
+    Ok(())
+}
+
+/// What `token` refers to, or the token itself if it's not in the metadata.
+fn token(pool: &Pool, token: u32) -> Operand {
+    if token >> 24 == USER_STRING {
+        return match pool.strings.get(&token) {
+            Some(string) => Operand::String(string.clone()),
+            None => Operand::Token(token),
+        };
+    }
+
+    match pool.names.get(&token) {
+        Some(name) => Operand::Member(name.clone(), pool.code.get(&token).copied()),
+        None => Operand::Token(token),
+    }
+}
+
+/// Method header or exception handling clauses at the reader, shown as a directive.
+fn directive(
+    pool: &Pool,
+    header: &Header,
+    reader: &mut decoder::Reader,
+) -> Result<Instruction, ErrorKind> {
+    match *header {
+        Header::Method {
+            len,
+            max_stack,
+            locals,
+        } => {
+            skip(reader, len)?;
+            let mut operands = vec![Operand::Immediate(max_stack as i64)];
+            if let Some(signature) = pool.names.get(&locals) {
+                operands.push(Operand::Locals(signature.clone()));
+            }
+
+            Ok(Instruction::new(".maxstack", 0, operands, len))
+        }
+        Header::Clauses { len, count } => {
+            skip(reader, len)?;
+            let operands = vec![Operand::Immediate(count as i64)];
+            Ok(Instruction::new(".clauses", 0, operands, len))
+        }
+    }
+}
+
+fn decode(decoder: &Decoder, reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    reader.mark();
+    let start = decoder.addr + reader.total_offset();
+    if let Some(header) = decoder.pool.headers.get(&start) {
+        return directive(decoder.pool, header, reader);
+    }
+
+    let [byte] = read(reader)?;
+    let (op, (mnemonic, kind)) = match byte {
+        0xfe => {
+            let [byte] = read(reader)?;
+            let opcode = TWO_BYTE.get(byte as usize).ok_or(ErrorKind::InvalidOpcode)?;
+            (0xfe00 | byte as u16, *opcode)
+        }
+        _ => {
+            let opcode = ONE_BYTE.get(byte as usize).ok_or(ErrorKind::InvalidOpcode)?;
+            (byte as u16, *opcode)
+        }
+    };
+
+    if mnemonic.is_empty() {
+        return Err(ErrorKind::InvalidOpcode);
+    }
+
+    // branches are relative to the end of the instruction
+    let operands = match kind {
+        Encoding::Nothing => vec![],
+        Encoding::I8 => vec![Operand::Immediate(read::<1>(reader)?[0] as i8 as i64)],
+        Encoding::U8 => vec![Operand::Immediate(read::<1>(reader)?[0] as i64)],
+        Encoding::I32 => vec![Operand::Immediate(i32::from_le_bytes(read(reader)?) as i64)],
+        Encoding::I64 => vec![Operand::Immediate(i64::from_le_bytes(read(reader)?))],
+        Encoding::F32 => vec![Operand::Float(f32::from_le_bytes(read(reader)?) as f64)],
+        Encoding::F64 => vec![Operand::Float(f64::from_le_bytes(read(reader)?))],
+        Encoding::Arg8 => vec![Operand::Argument(read::<1>(reader)?[0] as u16)],
+        Encoding::Arg16 => vec![Operand::Argument(u16::from_le_bytes(read(reader)?))],
+        Encoding::Local8 => vec![Operand::Local(read::<1>(reader)?[0] as u16)],
+        Encoding::Local16 => vec![Operand::Local(u16::from_le_bytes(read(reader)?))],
+        Encoding::Branch8 => {
+            let offset = read::<1>(reader)?[0] as i8 as i64;
+            vec![Operand::Relative(offset + reader.offset() as i64)]
+        }
+        Encoding::Branch32 => {
+            let offset = i32::from_le_bytes(read(reader)?) as i64;
+            vec![Operand::Relative(offset + reader.offset() as i64)]
+        }
+        Encoding::Switch => {
+            let count = u32::from_le_bytes(read(reader)?) as usize;
+            let len = count.checked_mul(4).ok_or(ErrorKind::InvalidOperand)?;
+            let end = (reader.offset() + len) as i64;
+            let mut targets = Vec::with_capacity(count.min(0x100));
+            for _ in 0..count {
+                targets.push(i32::from_le_bytes(read(reader)?) as i64 + end);
+            }
+
+            vec![Operand::Targets(targets)]
+        }
+        Encoding::Token => vec![token(decoder.pool, u32::from_le_bytes(read(reader)?))],
+    };
+
+    Ok(Instruction::new(mnemonic, op, operands, reader.offset()))
+}
@@ -0,0 +1,113 @@
+#![cfg(test)]
+
+use crate::{Header, Pool};
+use decoder::{Decodable, Decoded, Flow, ToTokens};
+
+fn pool() -> Pool {
+    let mut pool = Pool::default();
+    pool.strings.insert(0x7000_0001, "Hello, world!".into());
+    pool.names.insert(0x0a00_0001, "System.Console.WriteLine(string)".into());
+    pool.names.insert(0x0600_0002, "Program.Run(int)".into());
+    pool.names.insert(0x0400_0001, "Program.count".into());
+    pool.names.insert(0x1100_0001, "(int, string)".into());
+    pool.code.insert(0x0600_0002, 0x2000);
+    pool.headers.insert(
+        0x1000,
+        Header::Method {
+            len: 12,
+            max_stack: 8,
+            locals: 0x1100_0001,
+        },
+    );
+    pool.headers.insert(0x1800, Header::Clauses { len: 28, count: 1 });
+    pool
+}
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool, addr };
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn hello_world() {
+    test_display(&[0x72, 0x01, 0x00, 0x00, 0x70], "ldstr \"Hello, world!\"");
+    test_display(
+        &[0x28, 0x01, 0x00, 0x00, 0x0a],
+        "call System.Console.WriteLine(string)",
+    );
+    test_display(&[0x2a], "ret");
+}
+
+#[test]
+fn constants() {
+    test_display(&[0x15], "ldc.i4.m1");
+    test_display(&[0x1f, 0xfe], "ldc.i4.s -0x2");
+    test_display(&[0x20, 0x00, 0x01, 0x00, 0x00], "ldc.i4 0x100");
+    test_display(&[0x21, 0, 0, 0, 0, 0, 1, 0, 0], "ldc.i8 0x10000000000");
+    test_display(&[0x22, 0x00, 0x00, 0xc0, 0x3f], "ldc.r4 1.5");
+    test_display(&[0x7e, 0x01, 0x00, 0x00, 0x04], "ldsfld Program.count");
+    test_display(&[0x8d, 0x05, 0x00, 0x00, 0x01], "newarr token@01000005");
+    test_display(&[0x24], "Error { kind: InvalidOpcode, size: 1 }");
+    test_display(&[0xfe, 0x08], "Error { kind: InvalidOpcode, size: 1 }");
+}
+
+#[test]
+fn locals() {
+    test_display(&[0x02], "ldarg.0");
+    test_display(&[0x11, 0x04], "ldloc.s V_4");
+    test_display(&[0x10, 0x01], "starg.s arg1");
+    test_display(&[0xfe, 0x0c, 0x00, 0x01], "ldloc V_256");
+    test_display(&[0xfe, 0x01], "ceq");
+}
+
+#[test]
+fn branches() {
+    test_display_at(0x100, &[0x2b, 0xfe], "br.s 0x100");
+    test_display_at(0x100, &[0x2c, 0x06], "brfalse.s 0x108");
+    test_display_at(0x100, &[0x38, 0x00, 0x01, 0x00, 0x00], "br 0x205");
+    test_display_at(0x100, &[0xde, 0x02], "leave.s 0x104");
+
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool, addr: 0 };
+    let mut reader = decoder::Reader::new(&[0x28, 0x02, 0x00, 0x00, 0x06]);
+    let inst = decoder.decode(&mut reader).unwrap();
+    assert_eq!(inst.reference(), Some((0x2000, decoder::XrefKind::Call)));
+    assert_eq!(inst.flow(), Flow::Next);
+
+    let mut reader = decoder::Reader::new(&[0x7a]);
+    assert_eq!(decoder.decode(&mut reader).unwrap().flow(), Flow::Return);
+}
+
+#[test]
+fn switches() {
+    // targets are relative to the end of the instruction
+    let switch = [0x45, 0x02, 0, 0, 0, 0x00, 0, 0, 0, 0x10, 0, 0, 0];
+    test_display_at(0x100, &switch, "switch (0x10d, 0x11d)");
+}
+
+#[test]
+fn headers() {
+    let mut body = vec![0x13, 0x30, 0x08, 0x00, 0x01, 0, 0, 0, 0x01, 0, 0, 0x11];
+    body.push(0x2a);
+    test_display_at(0x1000, &body, ".maxstack 0x8, locals (int, string)");
+    test_display_at(0x100c, &[0x2a], "ret");
+    test_display_at(0x1800, &[0; 28], ".clauses 0x1");
+}
@@ -0,0 +1,576 @@
+//! Dalvik bytecode disassembler.
+//!
+//! Instructions are made of 16-bit code units and refer to strings, types, fields and methods by
+//! their index into the pools of the DEX file they're in, which are passed in as a [`Pool`].
+//! Mnemonics and operands follow the syntax of smali, with Java names in place of descriptors.
+
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use debugvault::Index;
+use decoder::{encode_hex, encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+/// Constants of a DEX file by their index, as shown in place of the index.
+#[derive(Debug, Default)]
+pub struct Pool {
+    pub strings: Vec<Arc<str>>,
+    pub types: Vec<Arc<str>>,
+    pub fields: Vec<Arc<str>>,
+    pub methods: Vec<Arc<str>>,
+    pub protos: Vec<Arc<str>>,
+    /// Address of the code of the methods defined in the file, by their index.
+    pub code: HashMap<u32, usize>,
+}
+
+/// What an index of an instruction refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexKind {
+    String,
+    Type,
+    Field,
+    Method,
+    Proto,
+    CallSite,
+    MethodHandle,
+}
+
+impl IndexKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Type => "type",
+            Self::Field => "field",
+            Self::Method => "method",
+            Self::Proto => "proto",
+            Self::CallSite => "call_site",
+            Self::MethodHandle => "method_handle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Register(u16),
+    /// Up to five registers passed to a method or array.
+    Registers([u16; 5], u8),
+    /// Registers from the first up to and including the last.
+    Range(u16, u16),
+    Immediate(i64),
+    /// Offset in bytes from the start of the instruction, before it's address is known.
+    Relative(i64),
+    Address(usize),
+    String(Arc<str>),
+    Type(Arc<str>),
+    Field(Arc<str>),
+    /// Method together with the address of it's code, if it's in the same file.
+    Method(Arc<str>, Option<usize>),
+    Proto(Arc<str>),
+    /// Index that isn't in the pool, or of a kind that isn't resolved.
+    Index(IndexKind, u32),
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let register = |stream: &mut TokenStream, reg: u16| {
+            stream.push_owned(format!("v{reg}"), Kind::Register)
+        };
+
+        match self {
+            Self::Register(reg) => register(stream, *reg),
+            Self::Registers(regs, len) => {
+                stream.push("{", Kind::Delimiter);
+                for (idx, reg) in regs[..*len as usize].iter().enumerate() {
+                    if idx != 0 {
+                        stream.push(", ", Kind::Plain);
+                    }
+                    register(stream, *reg);
+                }
+                stream.push("}", Kind::Delimiter);
+            }
+            Self::Range(first, last) => {
+                stream.push("{", Kind::Delimiter);
+                register(stream, *first);
+                stream.push(" .. ", Kind::Plain);
+                register(stream, *last);
+                stream.push("}", Kind::Delimiter);
+            }
+            Self::Immediate(imm) => {
+                stream.push_owned(encode_hex(*imm), Kind::Immediate)
+            }
+            Self::Relative(offset) => {
+                let sign = if *offset < 0 { "" } else { "+" };
+                let offset = format!("{sign}{}", encode_hex(*offset));
+                stream.push_owned(offset, Kind::Immediate)
+            }
+            Self::Address(addr) => match decoder::symbol_at(stream, symbols, *addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), *addr);
+                    stream.push_offset(offset, *addr);
+                }
+                None => {
+                    let text = encode_uhex(*addr as u64);
+                    stream.push_addr(text, Kind::Immediate, *addr)
+                }
+            },
+            Self::String(string) => {
+                stream.push_owned(format!("{string:?}"), Kind::String)
+            }
+            Self::Type(name) | Self::Proto(name) => {
+                stream.push_owned(name.to_string(), Kind::Primitive)
+            }
+            Self::Field(name) => stream.push_owned(name.to_string(), Kind::Component),
+            Self::Method(name, _) => stream.push_owned(name.to_string(), Kind::Label),
+            Self::Index(kind, idx) => {
+                stream.push_owned(format!("{}@{idx}", kind.as_str()), Kind::Expr)
+            }
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    mnemonic: &'static str,
+    op: u8,
+    operands: [Operand; 3],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    fn new(mnemonic: &'static str, op: u8, operands: Vec<Operand>, len: usize) -> Self {
+        let operand_count = operands.len();
+        let mut operands = operands.into_iter();
+        let mut next = || operands.next().unwrap_or_default();
+        Self {
+            mnemonic,
+            op,
+            operands: [next(), next(), next()],
+            operand_count,
+            len,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        self.mnemonic
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        stream.push(self.mnemonic, Kind::Mnemonic);
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            if let Operand::Relative(offset) = *operand {
+                *operand = Operand::Address(addr.wrapping_add_signed(offset as isize));
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.op {
+            // invokes
+            0x6e..=0x78 | 0xfa..=0xfd => XrefKind::Call,
+            // gotos and conditional branches
+            0x28..=0x2a | 0x32..=0x3d => XrefKind::Jump,
+            _ => XrefKind::Data,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) | Operand::Method(_, Some(addr)) => Some((addr, kind)),
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.op {
+            0x28..=0x2a => Flow::Jump,
+            0x32..=0x3d => Flow::Branch,
+            // returns and throw
+            0x0e..=0x11 | 0x27 => Flow::Return,
+            _ => Flow::Next,
+        }
+    }
+}
+
+pub struct Decoder<'a> {
+    pub pool: &'a Pool,
+}
+
+impl decoder::Decodable for Decoder<'_> {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(self.pool, reader).map_err(|err| Error::new(err, 2))
+    }
+
+    fn max_width(&self) -> usize {
+        10
+    }
+
+    fn instruction_alignment(&self) -> usize {
+        2
+    }
+}
+
+/// How the operands of an instruction are encoded, named as in the Dalvik documentation by the
+/// number of code units, registers and the kind of the last operand.
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    F10x,
+    F12x,
+    F11n,
+    F11x,
+    F10t,
+    F20t,
+    F22x,
+    F21t,
+    F21s,
+    /// Upper 16 bits of a 32-bit constant.
+    F21h,
+    /// Upper 16 bits of a 64-bit constant.
+    F21hw,
+    F21c(IndexKind),
+    F23x,
+    F22b,
+    F22t,
+    F22s,
+    F22c(IndexKind),
+    F32x,
+    F30t,
+    F31t,
+    F31i,
+    F31c(IndexKind),
+    F35c(IndexKind),
+    F3rc(IndexKind),
+    F45cc,
+    F4rcc,
+    F51l,
+}
+
+static CMP: [&str; 5] = ["cmpl-float", "cmpg-float", "cmpl-double", "cmpg-double", "cmp-long"];
+
+static IF: [&str; 6] = ["if-eq", "if-ne", "if-lt", "if-ge", "if-gt", "if-le"];
+
+static IFZ: [&str; 6] = ["if-eqz", "if-nez", "if-ltz", "if-gez", "if-gtz", "if-lez"];
+
+static ARRAY: [&str; 14] = [
+    "aget", "aget-wide", "aget-object", "aget-boolean", "aget-byte", "aget-char", "aget-short",
+    "aput", "aput-wide", "aput-object", "aput-boolean", "aput-byte", "aput-char", "aput-short",
+];
+
+static INSTANCE: [&str; 14] = [
+    "iget", "iget-wide", "iget-object", "iget-boolean", "iget-byte", "iget-char", "iget-short",
+    "iput", "iput-wide", "iput-object", "iput-boolean", "iput-byte", "iput-char", "iput-short",
+];
+
+static STATIC: [&str; 14] = [
+    "sget", "sget-wide", "sget-object", "sget-boolean", "sget-byte", "sget-char", "sget-short",
+    "sput", "sput-wide", "sput-object", "sput-boolean", "sput-byte", "sput-char", "sput-short",
+];
+
+static INVOKE: [&str; 5] = [
+    "invoke-virtual", "invoke-super", "invoke-direct", "invoke-static", "invoke-interface",
+];
+
+static INVOKE_RANGE: [&str; 5] = [
+    "invoke-virtual/range", "invoke-super/range", "invoke-direct/range", "invoke-static/range",
+    "invoke-interface/range",
+];
+
+static UNARY: [&str; 21] = [
+    "neg-int", "not-int", "neg-long", "not-long", "neg-float", "neg-double", "int-to-long",
+    "int-to-float", "int-to-double", "long-to-int", "long-to-float", "long-to-double",
+    "float-to-int", "float-to-long", "float-to-double", "double-to-int", "double-to-long",
+    "double-to-float", "int-to-byte", "int-to-char", "int-to-short",
+];
+
+static BINARY: [&str; 32] = [
+    "add-int", "sub-int", "mul-int", "div-int", "rem-int", "and-int", "or-int", "xor-int",
+    "shl-int", "shr-int", "ushr-int", "add-long", "sub-long", "mul-long", "div-long", "rem-long",
+    "and-long", "or-long", "xor-long", "shl-long", "shr-long", "ushr-long", "add-float",
+    "sub-float", "mul-float", "div-float", "rem-float", "add-double", "sub-double", "mul-double",
+    "div-double", "rem-double",
+];
+
+static BINARY_2ADDR: [&str; 32] = [
+    "add-int/2addr", "sub-int/2addr", "mul-int/2addr", "div-int/2addr", "rem-int/2addr",
+    "and-int/2addr", "or-int/2addr", "xor-int/2addr", "shl-int/2addr", "shr-int/2addr",
+    "ushr-int/2addr", "add-long/2addr", "sub-long/2addr", "mul-long/2addr", "div-long/2addr",
+    "rem-long/2addr", "and-long/2addr", "or-long/2addr", "xor-long/2addr", "shl-long/2addr",
+    "shr-long/2addr", "ushr-long/2addr", "add-float/2addr", "sub-float/2addr",
+    "mul-float/2addr", "div-float/2addr", "rem-float/2addr", "add-double/2addr",
+    "sub-double/2addr", "mul-double/2addr", "div-double/2addr", "rem-double/2addr",
+];
+
+static LIT16: [&str; 8] = [
+    "add-int/lit16", "rsub-int", "mul-int/lit16", "div-int/lit16", "rem-int/lit16",
+    "and-int/lit16", "or-int/lit16", "xor-int/lit16",
+];
+
+static LIT8: [&str; 11] = [
+    "add-int/lit8", "rsub-int/lit8", "mul-int/lit8", "div-int/lit8", "rem-int/lit8",
+    "and-int/lit8", "or-int/lit8", "xor-int/lit8", "shl-int/lit8", "shr-int/lit8",
+    "ushr-int/lit8",
+];
+
+/// Mnemonic and format of opcode `op`, None for unused opcodes.
+fn lookup(op: u8) -> Option<(&'static str, Format)> {
+    use Format::*;
+
+    let idx = |first: u8| (op - first) as usize;
+    Some(match op {
+        0x00 => ("nop", F10x),
+        0x01 => ("move", F12x),
+        0x02 => ("move/from16", F22x),
+        0x03 => ("move/16", F32x),
+        0x04 => ("move-wide", F12x),
+        0x05 => ("move-wide/from16", F22x),
+        0x06 => ("move-wide/16", F32x),
+        0x07 => ("move-object", F12x),
+        0x08 => ("move-object/from16", F22x),
+        0x09 => ("move-object/16", F32x),
+        0x0a => ("move-result", F11x),
+        0x0b => ("move-result-wide", F11x),
+        0x0c => ("move-result-object", F11x),
+        0x0d => ("move-exception", F11x),
+        0x0e => ("return-void", F10x),
+        0x0f => ("return", F11x),
+        0x10 => ("return-wide", F11x),
+        0x11 => ("return-object", F11x),
+        0x12 => ("const/4", F11n),
+        0x13 => ("const/16", F21s),
+        0x14 => ("const", F31i),
+        0x15 => ("const/high16", F21h),
+        0x16 => ("const-wide/16", F21s),
+        0x17 => ("const-wide/32", F31i),
+        0x18 => ("const-wide", F51l),
+        0x19 => ("const-wide/high16", F21hw),
+        0x1a => ("const-string", F21c(IndexKind::String)),
+        0x1b => ("const-string/jumbo", F31c(IndexKind::String)),
+        0x1c => ("const-class", F21c(IndexKind::Type)),
+        0x1d => ("monitor-enter", F11x),
+        0x1e => ("monitor-exit", F11x),
+        0x1f => ("check-cast", F21c(IndexKind::Type)),
+        0x20 => ("instance-of", F22c(IndexKind::Type)),
+        0x21 => ("array-length", F12x),
+        0x22 => ("new-instance", F21c(IndexKind::Type)),
+        0x23 => ("new-array", F22c(IndexKind::Type)),
+        0x24 => ("filled-new-array", F35c(IndexKind::Type)),
+        0x25 => ("filled-new-array/range", F3rc(IndexKind::Type)),
+        0x26 => ("fill-array-data", F31t),
+        0x27 => ("throw", F11x),
+        0x28 => ("goto", F10t),
+        0x29 => ("goto/16", F20t),
+        0x2a => ("goto/32", F30t),
+        0x2b => ("packed-switch", F31t),
+        0x2c => ("sparse-switch", F31t),
+        0x2d..=0x31 => (CMP[idx(0x2d)], F23x),
+        0x32..=0x37 => (IF[idx(0x32)], F22t),
+        0x38..=0x3d => (IFZ[idx(0x38)], F21t),
+        0x44..=0x51 => (ARRAY[idx(0x44)], F23x),
+        0x52..=0x5f => (INSTANCE[idx(0x52)], F22c(IndexKind::Field)),
+        0x60..=0x6d => (STATIC[idx(0x60)], F21c(IndexKind::Field)),
+        0x6e..=0x72 => (INVOKE[idx(0x6e)], F35c(IndexKind::Method)),
+        0x74..=0x78 => (INVOKE_RANGE[idx(0x74)], F3rc(IndexKind::Method)),
+        0x7b..=0x8f => (UNARY[idx(0x7b)], F12x),
+        0x90..=0xaf => (BINARY[idx(0x90)], F23x),
+        0xb0..=0xcf => (BINARY_2ADDR[idx(0xb0)], F12x),
+        0xd0..=0xd7 => (LIT16[idx(0xd0)], F22s),
+        0xd8..=0xe2 => (LIT8[idx(0xd8)], F22b),
+        0xfa => ("invoke-polymorphic", F45cc),
+        0xfb => ("invoke-polymorphic/range", F4rcc),
+        0xfc => ("invoke-custom", F35c(IndexKind::CallSite)),
+        0xfd => ("invoke-custom/range", F3rc(IndexKind::CallSite)),
+        0xfe => ("const-method-handle", F21c(IndexKind::MethodHandle)),
+        0xff => ("const-method-type", F21c(IndexKind::Proto)),
+        _ => return None,
+    })
+}
+
+/// Next code unit.
+fn unit(reader: &mut decoder::Reader) -> Result<u16, ErrorKind> {
+    let mut bytes = [0u8; 2];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+/// Next two code units, least significant first.
+fn units32(reader: &mut decoder::Reader) -> Result<u32, ErrorKind> {
+    Ok(unit(reader)? as u32 | (unit(reader)? as u32) << 16)
+}
+
+/// Constant at `idx` of the pool, or the index itself if it's not in there.
+fn constant(pool: &Pool, kind: IndexKind, idx: u32) -> Operand {
+    let get = |names: &[Arc<str>]| names.get(idx as usize).cloned();
+    let resolved = match kind {
+        IndexKind::String => get(&pool.strings).map(Operand::String),
+        IndexKind::Type => get(&pool.types).map(Operand::Type),
+        IndexKind::Field => get(&pool.fields).map(Operand::Field),
+        IndexKind::Method => get(&pool.methods)
+            .map(|name| Operand::Method(name, pool.code.get(&idx).copied())),
+        IndexKind::Proto => get(&pool.protos).map(Operand::Proto),
+        IndexKind::CallSite | IndexKind::MethodHandle => None,
+    };
+
+    resolved.unwrap_or(Operand::Index(kind, idx))
+}
+
+/// Registers of format 35c and 45cc, packed as `A|G|op` followed by `F|E|D|C`.
+fn register_list(first: u16, rest: u16) -> Result<Operand, ErrorKind> {
+    let count = (first >> 12) as u8;
+    if count > 5 {
+        return Err(ErrorKind::InvalidOperand);
+    }
+
+    let regs = [rest & 0xf, (rest >> 4) & 0xf, (rest >> 8) & 0xf, rest >> 12, (first >> 8) & 0xf];
+    Ok(Operand::Registers(regs, count))
+}
+
+/// Skip `len` bytes.
+fn skip(reader: &mut decoder::Reader, mut len: usize) -> Result<(), ErrorKind> {
+    let mut buf = [0u8; 64];
+    while len > 0 {
+        let chunk = len.min(buf.len());
+        reader.next_n(&mut buf[..chunk]).ok_or(ErrorKind::ExhaustedInput)?;
+        len -= chunk;
+    }
+
+    Ok(())
+}
+
+/// Table of a switch or array data following the code of a method, which is shown as a single
+/// instruction with the number of entries.
+fn decode_payload(reader: &mut decoder::Reader, ident: u16) -> Result<Instruction, ErrorKind> {
+    let size = unit(reader)? as usize;
+    let (mnemonic, count, units) = match ident {
+        0x0100 => (".packed-switch", size, 4 + size * 2),
+        0x0200 => (".sparse-switch", size, 2 + size * 4),
+        0x0300 => {
+            // the size read is the width of the elements
+            let count = units32(reader)? as usize;
+            let bytes = size.checked_mul(count).ok_or(ErrorKind::InvalidOperand)?;
+            (".array-data", count, 4 + bytes.div_ceil(2))
+        }
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let read = reader.offset();
+    skip(reader, units * 2 - read)?;
+    let count = Operand::Immediate(count as i64);
+    Ok(Instruction::new(mnemonic, 0, vec![count], units * 2))
+}
+
+fn decode(pool: &Pool, reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    use Format::*;
+
+    reader.mark();
+    let first = unit(reader)?;
+    let op = first as u8;
+    let aa = first >> 8;
+    let a = (first >> 8) & 0xf;
+    let b = first >> 12;
+
+    if op == 0 && aa != 0 {
+        return decode_payload(reader, first);
+    }
+
+    let (mnemonic, format) = lookup(op).ok_or(ErrorKind::InvalidOpcode)?;
+    let reg = Operand::Register;
+    let imm = Operand::Immediate;
+    let rel = |units: i64| Operand::Relative(units * 2);
+
+    let operands: Vec<Operand> = match format {
+        F10x => vec![],
+        F12x => vec![reg(a), reg(b)],
+        F11n => vec![reg(a), imm(((b as i16) << 12 >> 12) as i64)],
+        F11x => vec![reg(aa)],
+        F10t => vec![rel(aa as u8 as i8 as i64)],
+        F20t => vec![rel(unit(reader)? as i16 as i64)],
+        F22x => vec![reg(aa), reg(unit(reader)?)],
+        F21t => vec![reg(aa), rel(unit(reader)? as i16 as i64)],
+        F21s => vec![reg(aa), imm(unit(reader)? as i16 as i64)],
+        F21h => vec![reg(aa), imm(((unit(reader)? as i32) << 16) as i64)],
+        F21hw => vec![reg(aa), imm(((unit(reader)? as u64) << 48) as i64)],
+        F21c(kind) => vec![reg(aa), constant(pool, kind, unit(reader)? as u32)],
+        F23x => {
+            let bc = unit(reader)?;
+            vec![reg(aa), reg(bc & 0xff), reg(bc >> 8)]
+        }
+        F22b => {
+            let bc = unit(reader)?;
+            vec![reg(aa), reg(bc & 0xff), imm((bc >> 8) as u8 as i8 as i64)]
+        }
+        F22t => vec![reg(a), reg(b), rel(unit(reader)? as i16 as i64)],
+        F22s => vec![reg(a), reg(b), imm(unit(reader)? as i16 as i64)],
+        F22c(kind) => vec![reg(a), reg(b), constant(pool, kind, unit(reader)? as u32)],
+        F32x => vec![reg(unit(reader)?), reg(unit(reader)?)],
+        F30t => vec![rel(units32(reader)? as i32 as i64)],
+        F31t => vec![reg(aa), rel(units32(reader)? as i32 as i64)],
+        F31i => vec![reg(aa), imm(units32(reader)? as i32 as i64)],
+        F31c(kind) => vec![reg(aa), constant(pool, kind, units32(reader)?)],
+        F35c(kind) => {
+            let idx = unit(reader)? as u32;
+            vec![register_list(first, unit(reader)?)?, constant(pool, kind, idx)]
+        }
+        F3rc(kind) => {
+            let idx = unit(reader)? as u32;
+            let start = unit(reader)?;
+            let range = match aa {
+                0 => Operand::Registers([0; 5], 0),
+                _ => Operand::Range(start, start.wrapping_add(aa - 1)),
+            };
+            vec![range, constant(pool, kind, idx)]
+        }
+        F45cc => {
+            let method = unit(reader)? as u32;
+            let regs = register_list(first, unit(reader)?)?;
+            let proto = unit(reader)? as u32;
+            vec![
+                regs,
+                constant(pool, IndexKind::Method, method),
+                constant(pool, IndexKind::Proto, proto),
+            ]
+        }
+        F4rcc => {
+            let method = unit(reader)? as u32;
+            let start = unit(reader)?;
+            let proto = unit(reader)? as u32;
+            let range = Operand::Range(start, start.wrapping_add(aa.max(1) - 1));
+            vec![
+                range,
+                constant(pool, IndexKind::Method, method),
+                constant(pool, IndexKind::Proto, proto),
+            ]
+        }
+        F51l => {
+            let low = units32(reader)? as u64;
+            let high = units32(reader)? as u64;
+            vec![reg(aa), imm((high << 32 | low) as i64)]
+        }
+    };
+
+    Ok(Instruction::new(mnemonic, op, operands, reader.offset()))
+}
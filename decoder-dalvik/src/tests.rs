@@ -0,0 +1,101 @@
+#![cfg(test)]
+
+use crate::Pool;
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn pool() -> Pool {
+    let mut pool = Pool::default();
+    pool.strings.push("Hello\n".into());
+    pool.types.push("java.lang.StringBuilder".into());
+    pool.fields.push("java.lang.System.out".into());
+    pool.methods.push("java.io.PrintStream.println(java.lang.String)".into());
+    pool.methods.push("com.example.Main.run()".into());
+    pool.code.insert(1, 0x2000);
+    pool
+}
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool };
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn moves_and_constants() {
+    test_display(&[0x0e, 0x00], "return-void");
+    test_display(&[0x01, 0x21], "move v1, v2");
+    test_display(&[0x12, 0xf0], "const/4 v0, -0x1");
+    test_display(&[0x13, 0x01, 0x00, 0x01], "const/16 v1, 0x100");
+    test_display(&[0x15, 0x00, 0xf0, 0x7f], "const/high16 v0, 0x7ff00000");
+    test_display(&[0x0c, 0x03], "move-result-object v3");
+    test_display(&[0x1a, 0x00, 0x00, 0x00], "const-string v0, \"Hello\\n\"");
+    test_display(&[0x1a, 0x00, 0x05, 0x00], "const-string v0, string@5");
+    test_display(&[0x3e, 0x00], "Error { kind: InvalidOpcode, size: 2 }");
+}
+
+#[test]
+fn objects() {
+    test_display(&[0x22, 0x00, 0x00, 0x00], "new-instance v0, java.lang.StringBuilder");
+    test_display(&[0x62, 0x00, 0x00, 0x00], "sget-object v0, java.lang.System.out");
+    test_display(&[0x54, 0x10, 0x00, 0x00], "iget-object v0, v1, java.lang.System.out");
+    test_display(&[0x44, 0x00, 0x01, 0x02], "aget v0, v1, v2");
+    test_display(&[0xd8, 0x00, 0x01, 0xff], "add-int/lit8 v0, v1, -0x1");
+    test_display(&[0xb0, 0x10], "add-int/2addr v0, v1");
+}
+
+#[test]
+fn invokes() {
+    test_display(
+        &[0x6e, 0x20, 0x00, 0x00, 0x10, 0x00],
+        "invoke-virtual {v0, v1}, java.io.PrintStream.println(java.lang.String)",
+    );
+    test_display(&[0x71, 0x00, 0x01, 0x00, 0x00, 0x00], "invoke-static {}, com.example.Main.run()");
+    test_display(
+        &[0x74, 0x03, 0x00, 0x00, 0x04, 0x00],
+        "invoke-virtual/range {v4 .. v6}, java.io.PrintStream.println(java.lang.String)",
+    );
+
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool };
+    let mut reader = decoder::Reader::new(&[0x71, 0x00, 0x01, 0x00, 0x00, 0x00]);
+    let inst = decoder.decode(&mut reader).unwrap();
+    assert_eq!(inst.reference(), Some((0x2000, decoder::XrefKind::Call)));
+}
+
+#[test]
+fn branches() {
+    test_display_at(0x100, &[0x28, 0xfe], "goto 0xfc");
+    test_display_at(0x100, &[0x38, 0x00, 0x04, 0x00], "if-eqz v0, 0x108");
+    test_display_at(0x100, &[0x33, 0x10, 0x03, 0x00], "if-ne v0, v1, 0x106");
+    test_display_at(0x100, &[0x2b, 0x00, 0x08, 0x00, 0x00, 0x00], "packed-switch v0, 0x110");
+}
+
+#[test]
+fn payloads() {
+    let packed = [0x00, 0x01, 0x02, 0x00, 0, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0];
+    test_display(&packed, ".packed-switch 0x2");
+
+    let array = [0x00, 0x03, 0x01, 0x00, 0x03, 0x00, 0x00, 0x00, 1, 2, 3, 0];
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool };
+    let mut reader = decoder::Reader::new(&array);
+    let inst = decoder.decode(&mut reader).unwrap();
+    assert_eq!(inst.width(), 12);
+}
@@ -0,0 +1,380 @@
+//! Java bytecode disassembler.
+//!
+//! Instructions refer to constants by their index into the constant pool of the class they're
+//! in, which is passed in as a [`Pool`]. Mnemonics follow `javap`, with the constants shown in
+//! place of their index.
+
+mod tests;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use debugvault::Index;
+use decoder::{encode_hex, encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+/// Entry of a constant pool, resolved to what's shown for it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(Arc<str>),
+    Class(Arc<str>),
+    Field(Arc<str>),
+    Method(Arc<str>),
+    /// Method handles, method types and call sites of `invokedynamic`.
+    Other(Arc<str>),
+}
+
+/// Constant pool of a class.
+#[derive(Debug, Default)]
+pub struct Pool {
+    /// Constants by their index, which starts at one. Longs and doubles take up two indices.
+    pub constants: Vec<Option<Constant>>,
+    /// Address of the code of methods referred to by the pool, by their index.
+    pub code: HashMap<u16, usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Immediate(i64),
+    /// Index of a local variable.
+    Local(u16),
+    /// Offset in bytes from the start of the instruction, before it's address is known.
+    Relative(i64),
+    Address(usize),
+    /// Constant together with the address of it's code, if it's a method that's loaded.
+    Constant(Constant, Option<usize>),
+    /// Index that isn't in the constant pool.
+    Index(u16),
+    /// Element type of `newarray`.
+    Primitive(&'static str),
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        match self {
+            Self::Immediate(imm) => {
+                stream.push_owned(encode_hex(*imm), Kind::Immediate)
+            }
+            Self::Local(idx) => {
+                stream.push_owned(format!("local{idx}"), Kind::Register)
+            }
+            Self::Relative(offset) => {
+                let sign = if *offset < 0 { "" } else { "+" };
+                let offset = format!("{sign}{}", encode_hex(*offset));
+                stream.push_owned(offset, Kind::Immediate)
+            }
+            Self::Address(addr) => match decoder::symbol_at(stream, symbols, *addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), *addr);
+                    stream.push_offset(offset, *addr);
+                }
+                None => {
+                    let text = encode_uhex(*addr as u64);
+                    stream.push_addr(text, Kind::Immediate, *addr)
+                }
+            },
+            Self::Constant(constant, _) => match constant {
+                Constant::Integer(imm) => {
+                    stream.push_owned(encode_hex(*imm as i64), Kind::Immediate)
+                }
+                Constant::Long(imm) => {
+                    stream.push_owned(format!("{}L", encode_hex(*imm)), Kind::Immediate)
+                }
+                Constant::Float(imm) => {
+                    stream.push_owned(format!("{imm:?}f"), Kind::Immediate)
+                }
+                Constant::Double(imm) => {
+                    stream.push_owned(format!("{imm:?}"), Kind::Immediate)
+                }
+                Constant::String(string) => {
+                    stream.push_owned(format!("{string:?}"), Kind::String)
+                }
+                Constant::Class(name) => {
+                    stream.push_owned(name.to_string(), Kind::Primitive)
+                }
+                Constant::Field(name) => {
+                    stream.push_owned(name.to_string(), Kind::Component)
+                }
+                Constant::Method(name) | Constant::Other(name) => {
+                    stream.push_owned(name.to_string(), Kind::Label)
+                }
+            },
+            Self::Index(idx) => stream.push_owned(format!("#{idx}"), Kind::Expr),
+            Self::Primitive(name) => stream.push(name, Kind::Primitive),
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    op: u8,
+    /// Whether the instruction is prefixed by `wide`, which widens the index of locals.
+    wide: bool,
+    operands: [Operand; 3],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    fn new(op: u8, operands: Vec<Operand>, len: usize) -> Self {
+        let operand_count = operands.len();
+        let mut operands = operands.into_iter();
+        let mut next = || operands.next().unwrap_or_default();
+        Self {
+            op,
+            wide: false,
+            operands: [next(), next(), next()],
+            operand_count,
+            len,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        MNEMONICS[self.op as usize]
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        if self.wide {
+            stream.push("wide ", Kind::Mnemonic);
+        }
+        stream.push(self.mnemonic(), Kind::Mnemonic);
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            if let Operand::Relative(offset) = *operand {
+                *operand = Operand::Address(addr.wrapping_add_signed(offset as isize));
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.op {
+            // invokes
+            0xb6..=0xba => XrefKind::Call,
+            _ => XrefKind::Jump,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) | Operand::Constant(_, Some(addr)) => Some((addr, kind)),
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.op {
+            // conditional branches
+            0x99..=0xa6 | 0xc6 | 0xc7 => Flow::Branch,
+            // gotos and switches, whose cases aren't followed
+            0xa7 | 0xc8 | 0xaa | 0xab => Flow::Jump,
+            // returns, throw and returns from subroutines
+            0xa9 | 0xac..=0xb1 | 0xbf => Flow::Return,
+            _ => Flow::Next,
+        }
+    }
+}
+
+pub struct Decoder<'a> {
+    pub pool: &'a Pool,
+    /// Address the input starts at, which switches pad their operands to a multiple of four
+    /// from.
+    pub addr: usize,
+}
+
+impl decoder::Decodable for Decoder<'_> {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(self, reader).map_err(|err| Error::new(err, 1))
+    }
+
+    fn max_width(&self) -> usize {
+        6
+    }
+}
+
+static MNEMONICS: [&str; 256] = {
+    let mut names = ["(bad)"; 256];
+    let known = [
+        "nop", "aconst_null", "iconst_m1", "iconst_0", "iconst_1", "iconst_2", "iconst_3",
+        "iconst_4", "iconst_5", "lconst_0", "lconst_1", "fconst_0", "fconst_1", "fconst_2",
+        "dconst_0", "dconst_1", "bipush", "sipush", "ldc", "ldc_w", "ldc2_w", "iload", "lload",
+        "fload", "dload", "aload", "iload_0", "iload_1", "iload_2", "iload_3", "lload_0",
+        "lload_1", "lload_2", "lload_3", "fload_0", "fload_1", "fload_2", "fload_3", "dload_0",
+        "dload_1", "dload_2", "dload_3", "aload_0", "aload_1", "aload_2", "aload_3", "iaload",
+        "laload", "faload", "daload", "aaload", "baload", "caload", "saload", "istore", "lstore",
+        "fstore", "dstore", "astore", "istore_0", "istore_1", "istore_2", "istore_3", "lstore_0",
+        "lstore_1", "lstore_2", "lstore_3", "fstore_0", "fstore_1", "fstore_2", "fstore_3",
+        "dstore_0", "dstore_1", "dstore_2", "dstore_3", "astore_0", "astore_1", "astore_2",
+        "astore_3", "iastore", "lastore", "fastore", "dastore", "aastore", "bastore", "castore",
+        "sastore", "pop", "pop2", "dup", "dup_x1", "dup_x2", "dup2", "dup2_x1", "dup2_x2", "swap",
+        "iadd", "ladd", "fadd", "dadd", "isub", "lsub", "fsub", "dsub", "imul", "lmul", "fmul",
+        "dmul", "idiv", "ldiv", "fdiv", "ddiv", "irem", "lrem", "frem", "drem", "ineg", "lneg",
+        "fneg", "dneg", "ishl", "lshl", "ishr", "lshr", "iushr", "lushr", "iand", "land", "ior",
+        "lor", "ixor", "lxor", "iinc", "i2l", "i2f", "i2d", "l2i", "l2f", "l2d", "f2i", "f2l",
+        "f2d", "d2i", "d2l", "d2f", "i2b", "i2c", "i2s", "lcmp", "fcmpl", "fcmpg", "dcmpl",
+        "dcmpg", "ifeq", "ifne", "iflt", "ifge", "ifgt", "ifle", "if_icmpeq", "if_icmpne",
+        "if_icmplt", "if_icmpge", "if_icmpgt", "if_icmple", "if_acmpeq", "if_acmpne", "goto",
+        "jsr", "ret", "tableswitch", "lookupswitch", "ireturn", "lreturn", "freturn", "dreturn",
+        "areturn", "return", "getstatic", "putstatic", "getfield", "putfield", "invokevirtual",
+        "invokespecial", "invokestatic", "invokeinterface", "invokedynamic", "new", "newarray",
+        "anewarray", "arraylength", "athrow", "checkcast", "instanceof", "monitorenter",
+        "monitorexit", "wide", "multianewarray", "ifnull", "ifnonnull", "goto_w", "jsr_w",
+        "breakpoint",
+    ];
+
+    let mut idx = 0;
+    while idx < known.len() {
+        names[idx] = known[idx];
+        idx += 1;
+    }
+
+    names[0xfe] = "impdep1";
+    names[0xff] = "impdep2";
+    names
+};
+
+/// Element types of `newarray` by their code, starting at four.
+static PRIMITIVES: [&str; 8] =
+    ["boolean", "char", "float", "double", "byte", "short", "int", "long"];
+
+fn u8(reader: &mut decoder::Reader) -> Result<u8, ErrorKind> {
+    reader.next().ok_or(ErrorKind::ExhaustedInput)
+}
+
+fn u16(reader: &mut decoder::Reader) -> Result<u16, ErrorKind> {
+    let mut bytes = [0u8; 2];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(u16::from_be_bytes(bytes))
+}
+
+fn i32(reader: &mut decoder::Reader) -> Result<i32, ErrorKind> {
+    let mut bytes = [0u8; 4];
+    reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+    Ok(i32::from_be_bytes(bytes))
+}
+
+/// Skip `len` bytes.
+fn skip(reader: &mut decoder::Reader, mut len: usize) -> Result<(), ErrorKind> {
+    let mut buf = [0u8; 64];
+    while len > 0 {
+        let chunk = len.min(buf.len());
+        reader.next_n(&mut buf[..chunk]).ok_or(ErrorKind::ExhaustedInput)?;
+        len -= chunk;
+    }
+
+    Ok(())
+}
+
+/// Constant at `idx` of the pool, or the index itself if it's not in there.
+fn constant(pool: &Pool, idx: u16) -> Operand {
+    match pool.constants.get(idx as usize) {
+        Some(Some(constant)) => Operand::Constant(constant.clone(), pool.code.get(&idx).copied()),
+        _ => Operand::Index(idx),
+    }
+}
+
+fn decode(decoder: &Decoder, reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    reader.mark();
+    let start = decoder.addr + reader.total_offset();
+    let op = u8(reader)?;
+
+    // `wide` widens the index of the local that follows, and the constant of `iinc`
+    if op == 0xc4 {
+        let op = u8(reader)?;
+        let local = Operand::Local(u16(reader)?);
+        let operands = match op {
+            0x15..=0x19 | 0x36..=0x3a | 0xa9 => vec![local],
+            0x84 => vec![local, Operand::Immediate(u16(reader)? as i16 as i64)],
+            _ => return Err(ErrorKind::InvalidOpcode),
+        };
+
+        let mut inst = Instruction::new(op, operands, reader.offset());
+        inst.wide = true;
+        return Ok(inst);
+    }
+
+    let operands = match op {
+        0x10 => vec![Operand::Immediate(u8(reader)? as i8 as i64)],
+        0x11 => vec![Operand::Immediate(u16(reader)? as i16 as i64)],
+        0x12 => vec![constant(decoder.pool, u8(reader)? as u16)],
+        0x13 | 0x14 => vec![constant(decoder.pool, u16(reader)?)],
+        // loads, stores and returns from subroutines
+        0x15..=0x19 | 0x36..=0x3a | 0xa9 => vec![Operand::Local(u8(reader)? as u16)],
+        0x84 => {
+            let local = Operand::Local(u8(reader)? as u16);
+            vec![local, Operand::Immediate(u8(reader)? as i8 as i64)]
+        }
+        0x99..=0xa8 | 0xc6 | 0xc7 => vec![Operand::Relative(u16(reader)? as i16 as i64)],
+        0xc8 | 0xc9 => vec![Operand::Relative(i32(reader)? as i64)],
+        0xaa | 0xab => {
+            // operands start at a multiple of four
+            let padding = (4 - (start + 1) % 4) % 4;
+            skip(reader, padding)?;
+
+            let default = Operand::Relative(i32(reader)? as i64);
+            if op == 0xaa {
+                let low = i32(reader)?;
+                let high = i32(reader)?;
+                let count = (high as i64 - low as i64 + 1).max(0) as usize;
+                skip(reader, count * 4)?;
+
+                vec![Operand::Immediate(low as i64), Operand::Immediate(high as i64), default]
+            } else {
+                let pairs = i32(reader)?.max(0) as usize;
+                skip(reader, pairs * 8)?;
+
+                vec![Operand::Immediate(pairs as i64), default]
+            }
+        }
+        // fields, invokes, classes and arrays of classes
+        0xb2..=0xb8 | 0xbb | 0xbd | 0xc0 | 0xc1 => vec![constant(decoder.pool, u16(reader)?)],
+        0xb9 => {
+            let method = constant(decoder.pool, u16(reader)?);
+            let count = u8(reader)?;
+            skip(reader, 1)?;
+            vec![method, Operand::Immediate(count as i64)]
+        }
+        0xba => {
+            let call_site = constant(decoder.pool, u16(reader)?);
+            skip(reader, 2)?;
+            vec![call_site]
+        }
+        0xbc => match u8(reader)? {
+            atype @ 4..=11 => vec![Operand::Primitive(PRIMITIVES[atype as usize - 4])],
+            _ => return Err(ErrorKind::InvalidOperand),
+        },
+        0xc5 => {
+            let class = constant(decoder.pool, u16(reader)?);
+            vec![class, Operand::Immediate(u8(reader)? as i64)]
+        }
+        0xcb..=0xfd => return Err(ErrorKind::InvalidOpcode),
+        _ => vec![],
+    };
+
+    Ok(Instruction::new(op, operands, reader.offset()))
+}
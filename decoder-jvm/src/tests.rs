@@ -0,0 +1,97 @@
+#![cfg(test)]
+
+use crate::{Constant, Pool};
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn pool() -> Pool {
+    let mut pool = Pool::default();
+    pool.constants.push(None);
+    pool.constants.push(Some(Constant::String("Hello, world!".into())));
+    pool.constants.push(Some(Constant::Field("java.lang.System.out".into())));
+    pool.constants.push(Some(Constant::Method(
+        "java.io.PrintStream.println(java.lang.String)".into(),
+    )));
+    pool.constants.push(Some(Constant::Class("java.lang.StringBuilder".into())));
+    pool.constants.push(Some(Constant::Method("Main.run()".into())));
+    pool.constants.push(Some(Constant::Long(1 << 40)));
+    pool.constants.push(None);
+    pool.code.insert(5, 0x2000);
+    pool
+}
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool, addr };
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn hello_world() {
+    test_display(&[0xb2, 0x00, 0x02], "getstatic java.lang.System.out");
+    test_display(&[0x12, 0x01], "ldc \"Hello, world!\"");
+    test_display(
+        &[0xb6, 0x00, 0x03],
+        "invokevirtual java.io.PrintStream.println(java.lang.String)",
+    );
+    test_display(&[0xb1], "return");
+}
+
+#[test]
+fn constants() {
+    test_display(&[0x10, 0xff], "bipush -0x1");
+    test_display(&[0x11, 0x01, 0x00], "sipush 0x100");
+    test_display(&[0x14, 0x00, 0x06], "ldc2_w 0x10000000000L");
+    test_display(&[0x13, 0x00, 0x09], "ldc_w #9");
+    test_display(&[0xbb, 0x00, 0x04], "new java.lang.StringBuilder");
+    test_display(&[0xbc, 0x0a], "newarray int");
+    test_display(&[0xcb], "Error { kind: InvalidOpcode, size: 1 }");
+}
+
+#[test]
+fn locals() {
+    test_display(&[0x2a], "aload_0");
+    test_display(&[0x15, 0x04], "iload local4");
+    test_display(&[0x84, 0x01, 0xff], "iinc local1, -0x1");
+    test_display(&[0xc4, 0x84, 0x01, 0x00, 0x00, 0x10], "wide iinc local256, 0x10");
+}
+
+#[test]
+fn branches() {
+    test_display_at(0x100, &[0xa7, 0xff, 0xfd], "goto 0xfd");
+    test_display_at(0x100, &[0x99, 0x00, 0x08], "ifeq 0x108");
+    test_display_at(0x100, &[0xc8, 0x00, 0x00, 0x01, 0x00], "goto_w 0x200");
+
+    let pool = pool();
+    let decoder = crate::Decoder { pool: &pool, addr: 0 };
+    let mut reader = decoder::Reader::new(&[0xb8, 0x00, 0x05]);
+    let inst = decoder.decode(&mut reader).unwrap();
+    assert_eq!(inst.reference(), Some((0x2000, decoder::XrefKind::Call)));
+}
+
+#[test]
+fn switches() {
+    // padded to the next multiple of four, with cases for 1 and 2
+    let table = [
+        0xaa, 0, 0, 0, 0, 0, 0, 0x20, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0x18, 0, 0, 0, 0x1c,
+    ];
+    test_display_at(0x100, &table, "tableswitch 0x1, 0x2, 0x120");
+
+    test_display_at(0x103, &[0xab, 0, 0, 0, 0x10, 0, 0, 0, 0], "lookupswitch 0x0, 0x113");
+}
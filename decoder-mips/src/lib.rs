@@ -5,8 +5,7 @@ mod tests;
 use decoder::{Error, ErrorKind};
 use debugvault::Index;
 use std::borrow::Cow;
-use tokenizing::{colors, TokenStream};
-use config::CONFIG;
+use tokenizing::{Kind, TokenStream};
 
 macro_rules! operands {
     [] => {([$crate::EMPTY_OPERAND; 3], 0)};
@@ -93,6 +92,10 @@ impl decoder::Decodable for Decoder {
     fn max_width(&self) -> usize {
         4
     }
+
+    fn instruction_alignment(&self) -> usize {
+        4
+    }
 }
 
 fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
@@ -246,24 +249,24 @@ fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
 
 impl decoder::ToTokens for Instruction {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
-        stream.push(self.mnemomic, CONFIG.colors.asm.opcode);
+        stream.push(self.mnemomic, Kind::Mnemonic);
 
         // there are operands
         if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
+            stream.push(" ", Kind::Plain);
 
             // iterate through operands
             for idx in 0..self.operand_count {
                 let operand = self.operands[idx].clone();
 
                 match operand {
-                    Cow::Owned(s) => stream.push_owned(s, CONFIG.colors.asm.immediate),
-                    Cow::Borrowed(s) => stream.push(s, CONFIG.colors.asm.register),
+                    Cow::Owned(s) => stream.push_owned(s, Kind::Immediate),
+                    Cow::Borrowed(s) => stream.push(s, Kind::Register),
                 };
 
                 // separator
                 if idx != self.operand_count - 1 {
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                 }
             }
         }
@@ -0,0 +1,469 @@
+//! MSP430 disassembler.
+//!
+//! Instructions are made of a 16-bit word, followed by up to two words for the source and
+//! destination operands that have an index, address or immediate. Emulated instructions, such as
+//! `ret` and `clr`, are shown in place of the instructions they are encoded as.
+
+mod tests;
+
+use debugvault::Index;
+use decoder::{encode_uhex, Error, ErrorKind, Flow, ToTokens, XrefKind};
+use tokenizing::{Kind, TokenStream};
+
+macro_rules! operands {
+    [] => {([$crate::Operand::Nothing; 2], 0)};
+    [$($x:expr),+ $(,)?] => {{
+        let mut operands = [$crate::Operand::Nothing; 2];
+        let mut idx = 0;
+        $(
+            idx += 1;
+            operands[idx - 1] = $x;
+        )*
+
+        (operands, idx)
+    }};
+}
+
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Opcode {
+    // *double operand*
+    MOV,
+    ADD,
+    ADDC,
+    SUBC,
+    SUB,
+    CMP,
+    DADD,
+    BIT,
+    BIC,
+    BIS,
+    XOR,
+    AND,
+    // *single operand*
+    RRC,
+    SWPB,
+    RRA,
+    SXT,
+    PUSH,
+    CALL,
+    RETI,
+    // *jumps*
+    JNE,
+    JEQ,
+    JNC,
+    JC,
+    JN,
+    JGE,
+    JL,
+    JMP,
+    // *emulated instructions*
+    NOP,
+    RET,
+    POP,
+    BR,
+    CLR,
+    INC,
+    INCD,
+    DEC,
+    DECD,
+    TST,
+    INV,
+    RLA,
+    RLC,
+    ADC,
+    SBC,
+    DADC,
+    CLRC,
+    SETC,
+    CLRZ,
+    SETZ,
+    CLRN,
+    SETN,
+    DINT,
+    EINT,
+}
+
+impl Opcode {
+    pub fn as_str(&self) -> &'static str {
+        OPCODE_NAMES[*self as usize]
+    }
+
+    fn is_branch(&self) -> bool {
+        (Self::JNE as u8..Self::JMP as u8).contains(&(*self as u8))
+    }
+}
+
+static OPCODE_NAMES: [&str; 51] = [
+    "mov", "add", "addc", "subc", "sub", "cmp", "dadd", "bit", "bic", "bis", "xor", "and", "rrc",
+    "swpb", "rra", "sxt", "push", "call", "reti", "jne", "jeq", "jnc", "jc", "jn", "jge", "jl",
+    "jmp", "nop", "ret", "pop", "br", "clr", "inc", "incd", "dec", "decd", "tst", "inv", "rla",
+    "rlc", "adc", "sbc", "dadc", "clrc", "setc", "clrz", "setz", "clrn", "setn", "dint", "eint",
+];
+
+const PC: u8 = 0;
+const SP: u8 = 1;
+const SR: u8 = 2;
+const CG: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Operand {
+    #[default]
+    Nothing,
+    Register(u8),
+    /// Memory at a register plus an index.
+    Indexed(i16, u8),
+    /// Memory at a register.
+    Indirect(u8),
+    /// Memory at a register, which is incremented afterwards.
+    Increment(u8),
+    Immediate(u16),
+    /// Memory at an address.
+    Absolute(u16),
+    /// Offset in bytes from the start of the instruction, before it's address is known.
+    Relative(i32),
+    Address(usize),
+    /// Address called or branched to, given as an immediate.
+    Target(usize),
+}
+
+fn register_name(reg: u8) -> String {
+    match reg {
+        PC => "pc".to_string(),
+        SP => "sp".to_string(),
+        SR => "sr".to_string(),
+        _ => format!("r{reg}"),
+    }
+}
+
+impl ToTokens for Operand {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let address = |stream: &mut TokenStream, addr: usize| {
+            match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                }
+                None => stream.push_addr(encode_uhex(addr as u64), Kind::Immediate, addr),
+            }
+        };
+
+        match *self {
+            Self::Register(reg) => {
+                stream.push_owned(register_name(reg), Kind::Register)
+            }
+            Self::Indexed(idx, reg) => {
+                stream.push_owned(idx.to_string(), Kind::Immediate);
+                stream.push("(", Kind::Delimiter);
+                stream.push_owned(register_name(reg), Kind::Register);
+                stream.push(")", Kind::Delimiter);
+            }
+            Self::Indirect(reg) => {
+                stream.push("@", Kind::Expr);
+                stream.push_owned(register_name(reg), Kind::Register);
+            }
+            Self::Increment(reg) => {
+                stream.push("@", Kind::Expr);
+                stream.push_owned(register_name(reg), Kind::Register);
+                stream.push("+", Kind::Expr);
+            }
+            Self::Immediate(imm) => {
+                // constants are shown as they're written, such as #-1
+                let imm = match imm as i16 {
+                    imm @ -1..=9 => format!("#{imm}"),
+                    _ => format!("#{imm:#x}"),
+                };
+
+                stream.push_owned(imm, Kind::Immediate);
+            }
+            Self::Absolute(addr) => {
+                stream.push("&", Kind::Expr);
+                address(stream, addr as usize);
+            }
+            Self::Relative(offset) => {
+                stream.push_owned(format!("${offset:+}"), Kind::Immediate)
+            }
+            Self::Address(addr) => address(stream, addr),
+            Self::Target(addr) => {
+                stream.push("#", Kind::Expr);
+                address(stream, addr);
+            }
+            Self::Nothing => unreachable!("empty operand encountered"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    opcode: Opcode,
+    /// Whether the instruction operates on bytes instead of words.
+    byte: bool,
+    operands: [Operand; 2],
+    operand_count: usize,
+    len: usize,
+}
+
+impl Instruction {
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+}
+
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        stream.push(self.opcode.as_str(), Kind::Mnemonic);
+        if self.byte {
+            stream.push(".b", Kind::Mnemonic);
+        }
+
+        for (idx, operand) in self.operands().iter().enumerate() {
+            stream.push(if idx == 0 { " " } else { ", " }, Kind::Plain);
+            operand.tokenize(stream, symbols);
+        }
+    }
+}
+
+impl decoder::Decoded for Instruction {
+    fn width(&self) -> usize {
+        self.len
+    }
+
+    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
+        for operand in &mut self.operands[..self.operand_count] {
+            if let Operand::Relative(offset) = *operand {
+                *operand = Operand::Address(addr.wrapping_add_signed(offset as isize) & 0xffff);
+            }
+        }
+    }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.opcode {
+            Opcode::CALL => XrefKind::Call,
+            Opcode::BR | Opcode::JMP => XrefKind::Jump,
+            opcode if opcode.is_branch() => XrefKind::Jump,
+            _ => XrefKind::Data,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Target(addr) => Some((addr, kind)),
+            Operand::Address(addr) if self.opcode == Opcode::JMP || self.opcode.is_branch() => {
+                Some((addr, kind))
+            }
+            Operand::Address(addr) => Some((addr, XrefKind::Data)),
+            Operand::Absolute(addr) => Some((addr as usize, XrefKind::Data)),
+            _ => None,
+        })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.opcode {
+            Opcode::JMP | Opcode::BR => Flow::Jump,
+            Opcode::RET | Opcode::RETI => Flow::Return,
+            opcode if opcode.is_branch() => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Decoder;
+
+impl decoder::Decodable for Decoder {
+    type Instruction = Instruction;
+
+    fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
+        decode(reader).map_err(|err| Error::new(err, 2))
+    }
+
+    fn max_width(&self) -> usize {
+        6
+    }
+
+    fn instruction_alignment(&self) -> usize {
+        2
+    }
+}
+
+/// Reads the words of an instruction, keeping track of how many were read.
+struct Words<'a, 'b> {
+    reader: &'a mut decoder::Reader<'b>,
+    len: usize,
+}
+
+impl Words<'_, '_> {
+    fn next(&mut self) -> Result<u16, ErrorKind> {
+        let mut bytes = [0u8; 2];
+        self.reader.next_n(&mut bytes).ok_or(ErrorKind::ExhaustedInput)?;
+        self.len += 2;
+        Ok(u16::from_le_bytes(bytes))
+    }
+
+    /// Source operand of register `reg` in addressing mode `mode`.
+    fn source(&mut self, reg: u8, mode: u16) -> Result<Operand, ErrorKind> {
+        Ok(match (reg, mode) {
+            (_, 0b00) if reg != CG => Operand::Register(reg),
+            // the constant generators
+            (SR, 0b10) => Operand::Immediate(4),
+            (SR, 0b11) => Operand::Immediate(8),
+            (CG, 0b00) => Operand::Immediate(0),
+            (CG, 0b01) => Operand::Immediate(1),
+            (CG, 0b10) => Operand::Immediate(2),
+            (CG, _) => Operand::Immediate(0xffff),
+            (PC, 0b11) => Operand::Immediate(self.next()?),
+            (_, 0b01) => self.indexed(reg)?,
+            (_, 0b10) => Operand::Indirect(reg),
+            _ => Operand::Increment(reg),
+        })
+    }
+
+    /// Destination operand of register `reg` in addressing mode `mode`.
+    fn destination(&mut self, reg: u8, mode: u16) -> Result<Operand, ErrorKind> {
+        match mode {
+            0 => Ok(Operand::Register(reg)),
+            _ => self.indexed(reg),
+        }
+    }
+
+    /// Operand with an index, which is relative to the index itself for the pc and an absolute
+    /// address for the sr.
+    fn indexed(&mut self, reg: u8) -> Result<Operand, ErrorKind> {
+        let offset = self.len as i32;
+        let idx = self.next()?;
+        Ok(match reg {
+            PC => Operand::Relative(offset + idx as i16 as i32),
+            SR => Operand::Absolute(idx),
+            _ => Operand::Indexed(idx as i16, reg),
+        })
+    }
+}
+
+fn decode(reader: &mut decoder::Reader) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+
+    let mut words = Words { reader, len: 0 };
+    let word = words.next()?;
+
+    let (opcode, byte, operands) = match word >> 12 {
+        0b0001 => {
+            if word >> 10 != 0b000100 {
+                return Err(ErrorKind::InvalidOpcode);
+            }
+
+            let opcode = match word >> 7 & 0b111 {
+                0b000 => RRC,
+                0b001 => SWPB,
+                0b010 => RRA,
+                0b011 => SXT,
+                0b100 => PUSH,
+                0b101 => CALL,
+                0b110 => RETI,
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+
+            let byte = word & 0x40 != 0;
+            if byte && matches!(opcode, SWPB | SXT | CALL | RETI) {
+                return Err(ErrorKind::InvalidOpcode);
+            }
+
+            match opcode {
+                RETI => (RETI, false, operands![]),
+                CALL => match words.source((word & 0b1111) as u8, word >> 4 & 0b11)? {
+                    Operand::Immediate(imm) => (CALL, false, operands![target(imm)]),
+                    operand => (CALL, false, operands![operand]),
+                },
+                _ => {
+                    let operand = words.source((word & 0b1111) as u8, word >> 4 & 0b11)?;
+                    (opcode, byte, operands![operand])
+                }
+            }
+        }
+        0b0010 | 0b0011 => {
+            let opcode = [JNE, JEQ, JNC, JC, JN, JGE, JL, JMP][(word >> 10 & 0b111) as usize];
+            // sign extend the 10-bit offset in words
+            let offset = ((word << 6) as i16 >> 6) as i32 * 2 + 2;
+            (opcode, false, operands![Operand::Relative(offset)])
+        }
+        0b0100..=0b1111 => {
+            let opcode = [MOV, ADD, ADDC, SUBC, SUB, CMP, DADD, BIT, BIC, BIS, XOR, AND]
+                [(word >> 12) as usize - 0b0100];
+            let byte = word & 0x40 != 0;
+            let src = words.source((word >> 8 & 0b1111) as u8, word >> 4 & 0b11)?;
+            let dst = words.destination((word & 0b1111) as u8, word >> 7 & 1)?;
+            emulate(opcode, byte, src, dst)
+        }
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let (operands, operand_count) = operands;
+    Ok(Instruction {
+        opcode,
+        byte,
+        operands,
+        operand_count,
+        len: words.len,
+    })
+}
+
+fn target(imm: u16) -> Operand {
+    Operand::Target(imm as usize)
+}
+
+/// Instructions with two operands that are better known as an emulated instruction.
+fn emulate(
+    opcode: Opcode,
+    byte: bool,
+    src: Operand,
+    dst: Operand,
+) -> (Opcode, bool, ([Operand; 2], usize)) {
+    use Opcode::*;
+    use Operand::{Immediate, Increment, Register};
+
+    // all bits set, which differs between byte and word instructions unless it's a constant
+    let ones = |imm: u16| imm == 0xffff || (byte && imm == 0xff);
+
+    let emulated = match (opcode, src, dst) {
+        (MOV, Immediate(0), Register(CG)) => NOP,
+        (MOV, Increment(SP), Register(PC)) => RET,
+        (MOV, Increment(SP), _) => return (POP, byte, operands![dst]),
+        (MOV, Immediate(imm), Register(PC)) => return (BR, false, operands![target(imm)]),
+        (MOV, _, Register(PC)) => return (BR, false, operands![src]),
+        (MOV, Immediate(0), _) => CLR,
+        (ADD, Immediate(1), _) => INC,
+        (ADD, Immediate(2), _) => INCD,
+        (SUB, Immediate(1), _) => DEC,
+        (SUB, Immediate(2), _) => DECD,
+        (CMP, Immediate(0), _) => TST,
+        (XOR, Immediate(imm), _) if ones(imm) => INV,
+        (ADD, _, _) if src == dst => RLA,
+        (ADDC, _, _) if src == dst => RLC,
+        (ADDC, Immediate(0), _) => ADC,
+        (SUBC, Immediate(0), _) => SBC,
+        (DADD, Immediate(0), _) => DADC,
+        (BIC | BIS, Immediate(imm), Register(SR)) if !byte => {
+            let emulated = match (opcode, imm) {
+                (BIC, 1) => CLRC,
+                (BIS, 1) => SETC,
+                (BIC, 2) => CLRZ,
+                (BIS, 2) => SETZ,
+                (BIC, 4) => CLRN,
+                (BIS, 4) => SETN,
+                (BIC, 8) => DINT,
+                (BIS, 8) => EINT,
+                _ => return (opcode, byte, operands![src, dst]),
+            };
+
+            return (emulated, false, operands![]);
+        }
+        _ => return (opcode, byte, operands![src, dst]),
+    };
+
+    match emulated {
+        NOP | RET => (emulated, false, operands![]),
+        _ => (emulated, byte, operands![dst]),
+    }
+}
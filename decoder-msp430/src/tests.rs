@@ -0,0 +1,72 @@
+#![cfg(test)]
+
+use decoder::{Decodable, Decoded, ToTokens};
+
+fn test_display(bytes: &[u8], str: &str) {
+    test_display_at(0, bytes, str);
+}
+
+fn test_display_at(addr: usize, bytes: &[u8], str: &str) {
+    let mut reader = decoder::Reader::new(bytes);
+    let mut line = tokenizing::TokenStream::new();
+    let symbols = debugvault::Index::default();
+    let decoder = crate::Decoder;
+
+    let decoded = match decoder.decode(&mut reader) {
+        Ok(mut inst) => {
+            assert_eq!(inst.width(), bytes.len());
+            inst.update_rel_addrs(addr, None);
+            inst.tokenize(&mut line, &symbols);
+            line.to_string()
+        }
+        Err(err) => format!("{err:?}"),
+    };
+
+    assert_eq!(decoded, str);
+}
+
+#[test]
+fn double_operand() {
+    test_display(&[0x3f, 0x40, 0x34, 0x12], "mov #0x1234, r15");
+    test_display(&[0x5f, 0x42, 0x20, 0x00], "mov.b &0x20, r15");
+    test_display(&[0x8e, 0x4f, 0x02, 0x00], "mov r15, 2(r14)");
+    test_display(&[0x3f, 0x4e], "mov @r14+, r15");
+    test_display(&[0x2f, 0xf2], "and #4, r15");
+}
+
+#[test]
+fn single_operand() {
+    test_display(&[0x0a, 0x12], "push r10");
+    test_display(&[0xb0, 0x12, 0x00, 0xc0], "call #0xc000");
+    test_display(&[0x00, 0x13], "reti");
+    test_display(&[0x8f, 0x10], "swpb r15");
+}
+
+#[test]
+fn emulated() {
+    test_display(&[0x0f, 0x43], "clr r15");
+    test_display(&[0x30, 0x41], "ret");
+    test_display(&[0x03, 0x43], "nop");
+    test_display(&[0x3b, 0x41], "pop r11");
+    test_display(&[0x30, 0x40, 0x00, 0x10], "br #0x1000");
+    test_display(&[0x1f, 0x53], "inc r15");
+    test_display(&[0x5c, 0x83], "dec.b r12");
+    test_display(&[0x0f, 0x93], "tst r15");
+    test_display(&[0x32, 0xc2], "dint");
+    test_display(&[0x32, 0xd2], "eint");
+    test_display(&[0x3e, 0xe3], "inv r14");
+    test_display(&[0x0f, 0x5f], "rla r15");
+}
+
+#[test]
+fn relative() {
+    test_display_at(0x100, &[0xff, 0x3f], "jmp 0x100");
+    test_display_at(0x100, &[0x03, 0x20], "jne 0x108");
+    test_display_at(0x100, &[0x1f, 0x40, 0x10, 0x00], "mov 0x112, r15");
+}
+
+#[test]
+fn invalid() {
+    test_display(&[0x00, 0x00], "Error { kind: InvalidOpcode, size: 2 }");
+    test_display(&[0x3f, 0x40], "Error { kind: ExhaustedInput, size: 2 }");
+}
@@ -2,16 +2,16 @@
 
 mod tests;
 
-use decoder::{Error, ErrorKind, ToTokens};
+use decoder::{Error, ErrorKind, ToTokens, XrefKind};
 use debugvault::Index;
 use once_cell::sync::Lazy;
-use tokenizing::{TokenStream, colors};
-use config::CONFIG;
+use tokenizing::{Kind, TokenStream};
+use std::borrow::Cow;
 
 macro_rules! operands {
-    [] => {([$crate::Operand::Nothing; 3], 0)};
+    [] => {([$crate::Operand::Nothing; 5], 0)};
     [$($x:expr),+ $(,)?] => {{
-        let mut operands = [$crate::Operand::Nothing; 3];
+        let mut operands = [$crate::Operand::Nothing; 5];
         let mut idx = 0;
         $(
             idx += 1;
@@ -92,7 +92,8 @@ impl Register {
     }
 }
 
-/// Opcodes for risc-v 32-bit and 64-bit instructions.
+/// Opcodes for risc-v 32-bit and 64-bit instructions. Compressed instructions use the opcode
+/// of the instruction they expand to.
 ///
 /// *There is no support for 128-bit instruction encoding.*
 #[allow(non_camel_case_types)]
@@ -356,60 +357,20 @@ pub enum Opcode {
     FCVT_LU_Q,
     FCVT_Q_L,
     FCVT_Q_LU,
-    // *rv32c/rv64c instructions*
-    C_ADDI4SPN,
-    C_FLD,
-    C_LW,
-    C_FLW,
-    C_LD,
-    C_FSD,
-    C_SW,
-    C_FSW,
-    C_SD,
-    C_NOP,
-    C_ADDI,
-    C_JAL,
-    C_ADDIW,
-    C_LI,
-    C_ADDI16SP,
-    C_LUI,
-    C_SRLI,
-    C_SRLI64,
-    C_SRAI,
-    C_SRAI64,
-    C_ANDI,
-    C_SUB,
-    C_XOR,
-    C_OR,
-    C_AND,
-    C_SUBW,
-    C_ADDW,
-    C_J,
-    C_BEQZ,
-    C_BNEZ,
-    C_SLLI,
-    C_SLLI64,
-    C_FLDSP,
-    C_LWSP,
-    C_FLWSP,
-    C_LDSP,
-    C_JR,
-    C_MV,
-    C_EBREAK,
-    C_JALR,
-    C_ADD,
-    C_FSDSP,
-    C_SWSP,
-    C_FSWSP,
-    C_SDSP,
+    // *privileged instructions*
+    SRET,
+    MRET,
+    WFI,
+    SFENCE_VMA,
 }
 
+
 impl Opcode {
     fn is_relative(&self) -> bool {
         matches!(
             self,
             Self::JAL |
-            Self::JALR |
+            Self::J |
             Self::BEQ |
             Self::BNE |
             Self::BLT |
@@ -421,15 +382,37 @@ impl Opcode {
             Self::BLEZ |
             Self::BGEZ |
             Self::BLTZ |
-            Self::BGTZ |
-            Self::C_JAL |
-            Self::C_BEQZ |
-            Self::C_BNEZ
+            Self::BGTZ
+        )
+    }
+
+    fn is_load(&self) -> bool {
+        matches!(
+            self,
+            Self::LB |
+            Self::LH |
+            Self::LW |
+            Self::LBU |
+            Self::LHU |
+            Self::LWU |
+            Self::LD |
+            Self::FLW |
+            Self::FLD |
+            Self::FLQ
+        )
+    }
+
+    fn is_store(&self) -> bool {
+        matches!(
+            self,
+            Self::SB | Self::SH | Self::SW | Self::SD | Self::FSW | Self::FSD | Self::FSQ
         )
     }
 }
 
-static OPCODE_NAMES: [&str; 284] = [
+const OPCODE_COUNT: usize = 243;
+
+static OPCODE_NAMES: [&str; OPCODE_COUNT] = [
     "invalid",
     "la",
     "lla",
@@ -669,51 +652,10 @@ static OPCODE_NAMES: [&str; 284] = [
     "fcvt.lu.q",
     "fcvt.q.l",
     "fcvt.q.lu",
-    "c.addi4spn",
-    "c.fld",
-    "c.lw",
-    "c.flw",
-    "c.ld",
-    "c.fsd",
-    "c.sw",
-    "c.fsw",
-    "c.sd",
-    "c.nop",
-    "c.addi",
-    "c.jal",
-    "c.addiw",
-    "c.li",
-    "c.addi16sp",
-    "c.lui",
-    "c.srli",
-    "c.srli64",
-    "c.srai",
-    "c.srai64",
-    "c.andi",
-    "c.sub",
-    "c.xor",
-    "c.or",
-    "c.and",
-    "c.subw",
-    "c.addw",
-    "c.j",
-    "c.beqz",
-    "c.bnez",
-    "c.slli",
-    "c.slli64",
-    "c.fldsp",
-    "c.lwsp",
-    "c.flwsp",
-    "c.ldsp",
-    "c.jr",
-    "c.mv",
-    "c.ebreak",
-    "c.jalr",
-    "c.add",
-    "c.fsdsp",
-    "c.swsp",
-    "c.fswsp",
-    "c.sdsp",
+    "sret",
+    "mret",
+    "wfi",
+    "sfence.vma",
 ];
 
 impl Opcode {
@@ -722,28 +664,93 @@ impl Opcode {
     }
 }
 
+/// Name of a control and status register, if it's one of the standard ones.
+pub fn csr_name(csr: u16) -> Option<Cow<'static, str>> {
+    // sorted by number
+    #[rustfmt::skip]
+    const NAMES: [(u16, &str); 61] = [
+        (0x001, "fflags"), (0x002, "frm"), (0x003, "fcsr"),
+        (0x100, "sstatus"), (0x104, "sie"), (0x105, "stvec"), (0x106, "scounteren"),
+        (0x10a, "senvcfg"), (0x140, "sscratch"), (0x141, "sepc"), (0x142, "scause"),
+        (0x143, "stval"), (0x144, "sip"), (0x180, "satp"),
+        (0x300, "mstatus"), (0x301, "misa"), (0x302, "medeleg"), (0x303, "mideleg"),
+        (0x304, "mie"), (0x305, "mtvec"), (0x306, "mcounteren"), (0x30a, "menvcfg"),
+        (0x310, "mstatush"), (0x31a, "menvcfgh"), (0x320, "mcountinhibit"),
+        (0x340, "mscratch"), (0x341, "mepc"), (0x342, "mcause"), (0x343, "mtval"),
+        (0x344, "mip"), (0x34a, "mtinst"), (0x34b, "mtval2"),
+        (0x7a0, "tselect"), (0x7a1, "tdata1"), (0x7a2, "tdata2"), (0x7a3, "tdata3"),
+        (0x7b0, "dcsr"), (0x7b1, "dpc"), (0x7b2, "dscratch0"), (0x7b3, "dscratch1"),
+        (0xb00, "mcycle"), (0xb02, "minstret"), (0xb80, "mcycleh"), (0xb82, "minstreth"),
+        (0xc00, "cycle"), (0xc01, "time"), (0xc02, "instret"),
+        (0xc80, "cycleh"), (0xc81, "timeh"), (0xc82, "instreth"),
+        (0xda0, "scountovf"), (0xdb0, "stopi"),
+        (0xf11, "mvendorid"), (0xf12, "marchid"), (0xf13, "mimpid"), (0xf14, "mhartid"),
+        (0xf15, "mconfigptr"),
+        (0xfb0, "mtopi"), (0xfb1, "mtopei"), (0xfb2, "mtopei"), (0xfb3, "mtopei"),
+    ];
+
+    if let Ok(idx) = NAMES.binary_search_by_key(&csr, |&(num, _)| num) {
+        return Some(Cow::Borrowed(NAMES[idx].1));
+    }
+
+    // numbered registers
+    let (prefix, num, suffix) = match csr {
+        0x323..=0x33f => ("mhpmevent", csr - 0x320, ""),
+        0x3a0..=0x3af => ("pmpcfg", csr - 0x3a0, ""),
+        0x3b0..=0x3ef => ("pmpaddr", csr - 0x3b0, ""),
+        0xb03..=0xb1f => ("mhpmcounter", csr - 0xb00, ""),
+        0xb83..=0xb9f => ("mhpmcounter", csr - 0xb80, "h"),
+        0xc03..=0xc1f => ("hpmcounter", csr - 0xc00, ""),
+        0xc83..=0xc9f => ("hpmcounter", csr - 0xc80, "h"),
+        _ => return None,
+    };
+
+    Some(Cow::Owned(format!("{prefix}{num}{suffix}")))
+}
+
+/// Names of the rounding modes of floating-point instructions, by their encoding.
+static ROUNDING_MODES: [&str; 8] = ["rne", "rtz", "rdn", "rup", "rmm", "", "", "dyn"];
+
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub enum Operand {
     #[default]
     Nothing,
     Register(Register),
     Immediate(i32),
+    /// Memory at an offset from a register, e.g. `8(sp)`.
+    Memory(Register, i32),
+    /// Absolute address, either of a branch target or computed together with `auipc`.
+    Address(usize),
+    /// Control and status register.
+    Csr(u16),
+    /// Rounding mode of a floating-point instruction, only there if it isn't dynamic.
+    Rounding(u8),
 }
 
 impl ToTokens for Operand {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
-        match self {
-            Self::Register(reg) => stream.push(reg.as_str(), CONFIG.colors.asm.register),
-            Self::Immediate(imm) => {
-                match symbols.get_sym_by_addr(*imm as usize) {
-                    Some(symbol) => {
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                    }
-                    None => stream.push_owned(imm.to_string(), CONFIG.colors.asm.immediate),
-                }
+        match *self {
+            Self::Register(reg) => stream.push(reg.as_str(), Kind::Register),
+            Self::Immediate(imm) => stream.push_owned(imm.to_string(), Kind::Immediate),
+            Self::Memory(reg, offset) => {
+                stream.push_owned(offset.to_string(), Kind::Immediate);
+                stream.push("(", Kind::Expr);
+                stream.push(reg.as_str(), Kind::Register);
+                stream.push(")", Kind::Expr);
             }
+            Self::Address(addr) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                }
+                None => stream.push_addr(format!("{addr:#x}"), Kind::Immediate, addr),
+            },
+            Self::Csr(csr) => match csr_name(csr) {
+                Some(Cow::Borrowed(name)) => stream.push(name, Kind::Register),
+                Some(Cow::Owned(name)) => stream.push_owned(name, Kind::Register),
+                None => stream.push_owned(format!("{csr:#x}"), Kind::Immediate),
+            },
+            Self::Rounding(rm) => stream.push(ROUNDING_MODES[rm as usize], Kind::Expr),
             Self::Nothing => unreachable!("empty operand encountered"),
         }
     }
@@ -752,9 +759,51 @@ impl ToTokens for Operand {
 #[derive(Debug, Clone)]
 pub struct Instruction {
     opcode: Opcode,
-    operands: [Operand; 3],
+    operands: [Operand; 5],
     operand_count: usize,
     len: usize,
+    /// Ordering of atomic instructions, the `aq` bit followed by the `rl` bit.
+    ordering: u8,
+    /// Name of the compressed instruction it's shown as, for those that leave out operands of
+    /// the instruction they expand to, e.g. `c.or s1, s0` for `or s1, s1, s0`.
+    compressed: Option<&'static str>,
+}
+
+impl Instruction {
+    fn new(opcode: Opcode, (operands, operand_count): ([Operand; 5], usize), len: usize) -> Self {
+        Self {
+            opcode,
+            operands,
+            operand_count,
+            len,
+            ordering: 0,
+            compressed: None,
+        }
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    pub fn operands(&self) -> &[Operand] {
+        &self.operands[..self.operand_count]
+    }
+
+    /// Register the instruction jumps through and the offset added to it, for `jalr` and the
+    /// pseudo-instructions it's shown as.
+    fn jump_register(&self) -> Option<(Register, Register, i32)> {
+        match (self.opcode, self.operands) {
+            (Opcode::RET, _) => Some((Register::Zero, Register::Ra, 0)),
+            (Opcode::JR, [Operand::Register(rs), ..]) => Some((Register::Zero, rs, 0)),
+            (Opcode::JALR, [Operand::Register(rs), Operand::Nothing, ..]) => {
+                Some((Register::Ra, rs, 0))
+            }
+            (Opcode::JALR, [Operand::Register(rd), Operand::Memory(rs, offset), ..]) => {
+                Some((rd, rs, offset))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl decoder::Decoded for Instruction {
@@ -762,19 +811,88 @@ impl decoder::Decoded for Instruction {
         self.len
     }
 
-    fn update_rel_addrs(&mut self, addr: usize, _: Option<&Instruction>) {
-        if !self.opcode.is_relative() {
+    fn update_rel_addrs(&mut self, addr: usize, prev_inst: Option<&Instruction>) {
+        if self.opcode.is_relative() {
+            for operand in &mut self.operands[..self.operand_count] {
+                if let Operand::Immediate(imm) = *operand {
+                    *operand = Operand::Address(addr.wrapping_add_signed(imm as isize));
+                }
+            }
+            return;
+        }
+
+        // `auipc` adds the upper 20 bits of an offset to it's own address, the instruction that
+        // follows adds the lower 12 bits to the register it wrote. Only pairs right after each
+        // other are resolved, which is how they're emitted for `la`, `call` and `tail`.
+        let (reg, upper) = match prev_inst {
+            Some(Instruction {
+                opcode: Opcode::AUIPC,
+                operands: [Operand::Register(reg), Operand::Immediate(imm), ..],
+                len,
+                ..
+            }) => (*reg, addr.wrapping_sub(*len).wrapping_add_signed((*imm << 12) as isize)),
+            _ => return,
+        };
+
+        if reg == Register::Zero {
+            return;
+        }
+
+        if let Some((rd, rs, offset)) = self.jump_register() {
+            if rs != reg {
+                return;
+            }
+
+            let target = Operand::Address(upper.wrapping_add_signed(offset as isize));
+            (self.opcode, self.operands, self.operand_count) = match rd {
+                Register::Ra => (Opcode::CALL, operands![target].0, 1),
+                Register::Zero => (Opcode::TAIL, operands![target].0, 1),
+                rd => (Opcode::JALR, operands![Operand::Register(rd), target].0, 2),
+            };
             return;
         }
 
-        for operand in &mut self.operands[..self.operand_count] {
-            if let Operand::Immediate(imm) = operand {
-                // questionable at best.
-                // ensuring all the i64 conversion are correct is hard
-                *imm = imm.saturating_add_unsigned(addr as u32);
+        match (self.opcode, self.operands, self.operand_count) {
+            // `addi rd, rs, 0` is shown as `mv` and `c.addi rd, imm` without the source
+            (Opcode::ADDI, [rd, Operand::Register(rs), Operand::Immediate(lo), ..], 3)
+            | (Opcode::ADDI, [rd @ Operand::Register(rs), Operand::Immediate(lo), ..], 2)
+                if rs == reg =>
+            {
+                let target = Operand::Address(upper.wrapping_add_signed(lo as isize));
+                self.opcode = Opcode::LLA;
+                self.operands = operands![rd, target].0;
+                self.operand_count = 2;
+                self.compressed = None;
+            }
+            (Opcode::MV, [rd, Operand::Register(rs), ..], 2) if rs == reg => {
+                self.opcode = Opcode::LLA;
+                self.operands = operands![rd, Operand::Address(upper)].0;
+            }
+            (opcode, [_, Operand::Memory(base, offset), ..], 2)
+                if base == reg && (opcode.is_load() || opcode.is_store()) =>
+            {
+                self.operands[1] = Operand::Address(upper.wrapping_add_signed(offset as isize));
             }
+            _ => {}
         }
     }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        let kind = match self.opcode {
+            Opcode::JAL | Opcode::CALL => XrefKind::Call,
+            Opcode::JALR => XrefKind::Call,
+            Opcode::TAIL => XrefKind::Jump,
+            Opcode::LLA => XrefKind::Data,
+            opcode if opcode.is_relative() => XrefKind::Jump,
+            opcode if opcode.is_load() || opcode.is_store() => XrefKind::Data,
+            _ => return None,
+        };
+
+        self.operands().iter().find_map(|operand| match *operand {
+            Operand::Address(addr) => Some((addr, kind)),
+            _ => None,
+        })
+    }
 }
 
 pub struct Decoder {
@@ -785,12 +903,18 @@ impl decoder::Decodable for Decoder {
     type Instruction = Instruction;
 
     fn decode(&self, reader: &mut decoder::Reader) -> Result<Self::Instruction, Error> {
-        decode(reader, self).map_err(|err| Error::new(err, 4))
+        // compressed instructions are 2 bytes, which is all that's read of them
+        reader.mark();
+        decode(reader, self).map_err(|err| Error::new(err, reader.offset().max(2)))
     }
 
     fn max_width(&self) -> usize {
         4
     }
+
+    fn instruction_alignment(&self) -> usize {
+        2
+    }
 }
 
 fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction, ErrorKind> {
@@ -800,7 +924,8 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
     let mut word1 = [0u8; 2];
     reader.next_n(&mut word1).ok_or(ErrorKind::ExhaustedInput)?;
 
-    // check if the instruction is compressed
+    // check if the instruction is compressed, these are decoded as the instruction they expand
+    // to, such that they're shown the same
     if word1[0] & 0b11 != 0b11 {
         let bytes = u16::from_le_bytes(word1);
         let opcode = bytes & 0b11;
@@ -809,60 +934,63 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
         let decoded_inst = match opcode {
             0b00 => match jump3 {
                 0b000 => decode_addi4spn(bytes),
-                0b001 => decode_comp_fsld(C_FLD, bytes),
-                0b010 => decode_comp_slw(C_LW, bytes),
-                0b011 if !is_64 => decode_comp_fslw(C_FLW, bytes),
-                0b011 if is_64 => decode_comp_sld(C_LD, bytes),
-                0b101 => decode_comp_fsld(C_FSD, bytes),
-                0b110 => decode_comp_slw(C_SW, bytes),
-                0b111 if !is_64 => decode_comp_fslw(C_FSW, bytes),
-                0b111 if is_64 => decode_comp_sld(C_SD, bytes),
+                0b001 => decode_comp_mem(FLD, bytes, true),
+                0b010 => decode_comp_mem(LW, bytes, false),
+                0b011 if !is_64 => decode_comp_mem(FLW, bytes, true),
+                0b011 if is_64 => decode_comp_mem(LD, bytes, false),
+                0b101 => decode_comp_mem(FSD, bytes, true),
+                0b110 => decode_comp_mem(SW, bytes, false),
+                0b111 if !is_64 => decode_comp_mem(FSW, bytes, true),
+                0b111 if is_64 => decode_comp_mem(SD, bytes, false),
                 _ => Err(ErrorKind::InvalidOpcode),
             },
             0b01 => match jump3 {
-                0b000 if bytes >> 7 & 0b11111 == 0 => decode_comp_unique(C_NOP),
-                0b000 if bytes >> 7 & 0b11111 != 0 => decode_comp_addi(C_ADDI, bytes),
-                0b001 if !is_64 => decode_comp_jump(C_JAL, bytes),
-                0b001 if is_64 => decode_comp_addi(C_ADDIW, bytes),
-                0b010 => decode_comp_li(C_LI, bytes),
+                0b000 => decode_comp_addi(ADDI, bytes),
+                0b001 if !is_64 => decode_comp_jump(Register::Ra, bytes),
+                0b001 if is_64 => decode_comp_addi(ADDIW, bytes),
+                0b010 => decode_comp_li(bytes),
                 0b011 if bytes >> 7 & 0b11111 == 2 => decode_addi16sp(bytes),
-                0b011 if bytes >> 7 & 0b11111 != 2 => decode_comp_li(C_LUI, bytes),
+                0b011 if bytes >> 7 & 0b11111 != 2 => decode_comp_lui(bytes),
                 0b100 => match bytes >> 10 & 0b11 {
-                    0b00 => decode_comp_shift(C_SRLI, bytes),
-                    0b01 => decode_comp_shift(C_SRAI, bytes),
+                    0b00 => decode_comp_shift(SRLI, bytes, is_64),
+                    0b01 => decode_comp_shift(SRAI, bytes, is_64),
+                    0b10 => decode_comp_andi(bytes),
                     0b11 => match (bytes >> 5 & 0b11, bytes >> 12 & 0b1) {
-                        (0b00, 0b0) if !is_64 => decode_comp_arith(C_SUB, bytes),
-                        (0b01, 0b0) => decode_comp_arith(C_XOR, bytes),
-                        (0b10, 0b0) => decode_comp_arith(C_OR, bytes),
-                        (0b11, 0b0) => decode_comp_arith(C_AND, bytes),
-                        (0b00, 0b1) if is_64 => decode_comp_arith(C_SUBW, bytes),
-                        (0b01, 0b1) if is_64 => decode_comp_arith(C_ADDW, bytes),
+                        (0b00, 0b0) => decode_comp_arith(SUB, bytes),
+                        (0b01, 0b0) => decode_comp_arith(XOR, bytes),
+                        (0b10, 0b0) => decode_comp_arith(OR, bytes),
+                        (0b11, 0b0) => decode_comp_arith(AND, bytes),
+                        (0b00, 0b1) if is_64 => decode_comp_arith(SUBW, bytes),
+                        (0b01, 0b1) if is_64 => decode_comp_arith(ADDW, bytes),
                         _ => Err(ErrorKind::InvalidOpcode),
                     },
                     _ => Err(ErrorKind::InvalidOpcode),
                 },
-                0b101 => decode_comp_jump(C_J, bytes),
-                0b110 => decode_comp_branch(C_BEQZ, bytes),
-                0b111 => decode_comp_branch(C_BNEZ, bytes),
+                0b101 => decode_comp_jump(Register::Zero, bytes),
+                0b110 => decode_comp_branch(BEQ, bytes),
+                0b111 => decode_comp_branch(BNE, bytes),
                 _ => Err(ErrorKind::InvalidOpcode),
             },
             0b10 => match jump3 {
-                0b000 => decode_comp_shift(C_SLLI, bytes),
-                0b001 => decode_comp_ldsp(C_FLDSP, bytes),
-                0b010 => decode_comp_lwsp(C_LWSP, bytes),
-                0b011 if !is_64 => decode_comp_lwsp(C_FLWSP, bytes),
-                0b011 if is_64 => decode_comp_ldsp(C_LDSP, bytes),
-                0b100 => match (bytes >> 12 & 0b1, bytes >> 2 & 0b11111) {
-                    (0b0, 0b0) => decode_comp_jumpr(bytes),
-                    (0b0, _) => decode_comp_mv(bytes),
-                    (0b1, 0b0) => decode_comp_unique(C_EBREAK),
-                    (0b1, _) => decode_comp_add(bytes),
+                0b000 => decode_comp_shift(SLLI, bytes, is_64),
+                0b001 => decode_comp_load_sp(FLD, bytes, true),
+                0b010 => decode_comp_load_sp(LW, bytes, false),
+                0b011 if !is_64 => decode_comp_load_sp(FLW, bytes, true),
+                0b011 if is_64 => decode_comp_load_sp(LD, bytes, false),
+                0b100 => match (bytes >> 12 & 0b1, bytes >> 7 & 0b11111, bytes >> 2 & 0b11111) {
+                    (0b0, _, 0b0) => decode_comp_jumpr(Register::Zero, bytes),
+                    (0b0, _, _) => decode_comp_add(Register::Zero, bytes),
+                    (0b1, 0b0, 0b0) => decode_comp_unique(EBREAK),
+                    (0b1, _, 0b0) => decode_comp_jumpr(Register::Ra, bytes),
+                    (0b1, rd, _) => {
+                        Register::get(rd as u32).and_then(|rd| decode_comp_add(rd, bytes))
+                    }
                     _ => Err(ErrorKind::InvalidOpcode),
                 },
-                0b101 => decode_comp_sdsp(C_FSDSP, bytes),
-                0b110 => decode_comp_swsp(C_SWSP, bytes),
-                0b111 if !is_64 => decode_comp_swsp(C_FSWSP, bytes),
-                0b111 if is_64 => decode_comp_sdsp(C_SDSP, bytes),
+                0b101 => decode_comp_store_sp(FSD, bytes, true),
+                0b110 => decode_comp_store_sp(SW, bytes, false),
+                0b111 if !is_64 => decode_comp_store_sp(FSW, bytes, true),
+                0b111 if is_64 => decode_comp_store_sp(SD, bytes, false),
                 _ => Err(ErrorKind::InvalidOpcode),
             },
             _ => Err(ErrorKind::InvalidOpcode),
@@ -879,7 +1007,14 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
     let decoded_inst = match opcode {
         _ if dword == 0b000000000000_00000_000_00000_1110011 => decode_unique(ECALL),
         _ if dword == 0b000000000001_00000_000_00000_1110011 => decode_unique(EBREAK),
-        0b0001111 => decode_unique(FENCE),
+        _ if dword == 0b000100000010_00000_000_00000_1110011 => decode_unique(SRET),
+        _ if dword == 0b001100000010_00000_000_00000_1110011 => decode_unique(MRET),
+        _ if dword == 0b000100000101_00000_000_00000_1110011 => decode_unique(WFI),
+        0b0001111 => match dword >> 12 & 0b111 {
+            0b000 => decode_unique(FENCE),
+            0b001 => decode_unique(FENCE_I),
+            _ => Err(ErrorKind::InvalidOpcode),
+        },
         0b0110111 => decode_double(LUI, dword),
         0b0010111 => decode_double(AUIPC, dword),
         0b1101111 => decode_jump(dword),
@@ -894,20 +1029,20 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
             _ => Err(ErrorKind::InvalidOpcode),
         },
         0b0000011 => match dword >> 12 & 0b111 {
-            0b000 => decode_immediate(LB, dword),
-            0b001 => decode_immediate(LH, dword),
-            0b010 => decode_immediate(LW, dword),
-            0b011 if is_64 => decode_immediate(LD, dword),
-            0b100 => decode_immediate(LBU, dword),
-            0b101 => decode_immediate(LHU, dword),
-            0b110 if is_64 => decode_immediate(LWU, dword),
+            0b000 => decode_load(LB, dword, false),
+            0b001 => decode_load(LH, dword, false),
+            0b010 => decode_load(LW, dword, false),
+            0b011 if is_64 => decode_load(LD, dword, false),
+            0b100 => decode_load(LBU, dword, false),
+            0b101 => decode_load(LHU, dword, false),
+            0b110 if is_64 => decode_load(LWU, dword, false),
             _ => Err(ErrorKind::InvalidOpcode),
         },
         0b0100011 => match dword >> 12 & 0b111 {
-            0b000 => decode_store(SB, dword),
-            0b001 => decode_store(SH, dword),
-            0b010 => decode_store(SW, dword),
-            0b011 if is_64 => decode_store(SD, dword),
+            0b000 => decode_store(SB, dword, false),
+            0b001 => decode_store(SH, dword, false),
+            0b010 => decode_store(SW, dword, false),
+            0b011 if is_64 => decode_store(SD, dword, false),
             _ => Err(ErrorKind::InvalidOpcode),
         },
         0b0010011 => match dword >> 12 & 0b111 {
@@ -918,8 +1053,8 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
             0b110 => decode_immediate(ORI, dword),
             0b111 => decode_immediate(ANDI, dword),
             0b001 => decode_arith(SLLI, dword, decoder),
-            0b101 if dword >> 26 == 0b0000001 => decode_arith(SRAI, dword, decoder),
-            0b101 if dword >> 26 == 0b0000000 => decode_arith(SRLI, dword, decoder),
+            0b101 if dword >> 26 == 0b010000 => decode_arith(SRAI, dword, decoder),
+            0b101 if dword >> 26 == 0b000000 => decode_arith(SRLI, dword, decoder),
             _ => Err(ErrorKind::InvalidOpcode),
         },
         0b0011011 => match dword >> 12 & 0b111 {
@@ -947,6 +1082,10 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
                 0b101 => decode_triplet(SRA, dword),
                 _ => Err(ErrorKind::InvalidOpcode),
             },
+            0b0000001 => {
+                const OPCODES: [Opcode; 8] = [MUL, MULH, MULHSU, MULHU, DIV, DIVU, REM, REMU];
+                decode_triplet(OPCODES[(dword >> 12 & 0b111) as usize], dword)
+            }
             _ => Err(ErrorKind::InvalidOpcode),
         },
         0b0111011 => match dword >> 25 {
@@ -962,8 +1101,62 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
                 0b101 => decode_triplet(SRAW, dword),
                 _ => Err(ErrorKind::InvalidOpcode),
             },
+            0b0000001 => match dword >> 12 & 0b111 {
+                0b000 => decode_triplet(MULW, dword),
+                0b100 => decode_triplet(DIVW, dword),
+                0b101 => decode_triplet(DIVUW, dword),
+                0b110 => decode_triplet(REMW, dword),
+                0b111 => decode_triplet(REMUW, dword),
+                _ => Err(ErrorKind::InvalidOpcode),
+            },
+            _ => Err(ErrorKind::InvalidOpcode),
+        },
+        0b1110011 => match dword >> 12 & 0b111 {
+            0b000 if dword >> 25 == 0b0001001 && dword >> 7 & 0b11111 == 0 => {
+                decode_sfence_vma(dword)
+            }
+            0b001 => decode_csr(CSRRW, dword),
+            0b010 => decode_csr(CSRRS, dword),
+            0b011 => decode_csr(CSRRC, dword),
+            0b101 => decode_csr(CSRRWI, dword),
+            0b110 => decode_csr(CSRRSI, dword),
+            0b111 => decode_csr(CSRRCI, dword),
+            _ => Err(ErrorKind::InvalidOpcode),
+        },
+        0b0101111 => match dword >> 12 & 0b111 {
+            0b010 => decode_atomic(
+                [
+                    LR_W, SC_W, AMOSWAP_W, AMOADD_W, AMOXOR_W, AMOAND_W, AMOOR_W, AMOMIN_W,
+                    AMOMAX_W, AMOMINU_W, AMOMAXU_W,
+                ],
+                dword,
+            ),
+            0b011 if is_64 => decode_atomic(
+                [
+                    LR_D, SC_D, AMOSWAP_D, AMOADD_D, AMOXOR_D, AMOAND_D, AMOOR_D, AMOMIN_D,
+                    AMOMAX_D, AMOMINU_D, AMOMAXU_D,
+                ],
+                dword,
+            ),
             _ => Err(ErrorKind::InvalidOpcode),
         },
+        0b0000111 => match dword >> 12 & 0b111 {
+            0b010 => decode_load(FLW, dword, true),
+            0b011 => decode_load(FLD, dword, true),
+            0b100 => decode_load(FLQ, dword, true),
+            _ => Err(ErrorKind::InvalidOpcode),
+        },
+        0b0100111 => match dword >> 12 & 0b111 {
+            0b010 => decode_store(FSW, dword, true),
+            0b011 => decode_store(FSD, dword, true),
+            0b100 => decode_store(FSQ, dword, true),
+            _ => Err(ErrorKind::InvalidOpcode),
+        },
+        0b1000011 => decode_fused([FMADD_S, FMADD_D, FMADD_Q], dword),
+        0b1000111 => decode_fused([FMSUB_S, FMSUB_D, FMSUB_Q], dword),
+        0b1001011 => decode_fused([FNMSUB_S, FNMSUB_D, FNMSUB_Q], dword),
+        0b1001111 => decode_fused([FNMADD_S, FNMADD_D, FNMADD_Q], dword),
+        0b1010011 => decode_op_fp(dword, is_64),
         _ => Err(ErrorKind::InvalidOpcode),
     };
 
@@ -972,11 +1165,19 @@ fn decode(reader: &mut decoder::Reader, decoder: &Decoder) -> Result<Instruction
 
 impl ToTokens for Instruction {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
-        stream.push(self.opcode.as_str(), CONFIG.colors.asm.opcode);
+        match (self.compressed, self.ordering) {
+            (Some(name), _) => stream.push(name, Kind::Mnemonic),
+            (None, 0b00) => stream.push(self.opcode.as_str(), Kind::Mnemonic),
+            (None, ordering) => {
+                let suffix = [".rl", ".aq", ".aqrl"][ordering as usize - 1];
+                let opcode = format!("{}{suffix}", self.opcode.as_str());
+                stream.push_owned(opcode, Kind::Mnemonic);
+            }
+        }
 
         // there are operands
         if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
+            stream.push(" ", Kind::Plain);
 
             // iterate through operands
             for idx in 0..self.operand_count {
@@ -984,313 +1185,215 @@ impl ToTokens for Instruction {
 
                 // separator
                 if idx != self.operand_count - 1 {
-                    stream.push(", ", CONFIG.colors.asm.expr);
+                    stream.push(", ", Kind::Expr);
                 }
             }
         }
     }
 }
 
-// NOTE: doing closure assignment in `map_to_psuedo` makes the compiler
-// assign function mappings in the array on each call.
-static MAPPING: Lazy<[fn(&mut Instruction); 284]> = Lazy::new(|| unsafe {
-    const DO_NOTHING: fn(&mut Instruction) = |_| {};
-    static mut MAPPING: [fn(&mut Instruction); 284] = [DO_NOTHING; 284];
-
-    MAPPING[Opcode::C_ADDI as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::Zero)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.opcode = Opcode::NOP;
-            inst.operand_count = 0;
-            return;
-        }
-
-        if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::LI;
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-            return;
-        }
-
-        if inst.operands[0] == inst.operands[1] && inst.operands[2] == Operand::Immediate(0) {
-            inst.opcode = Opcode::MV;
-            inst.operand_count = 2;
-            return;
-        }
+/// Show compressed instructions whose first source register is also the destination as the
+/// compressed instruction, which leaves it out, e.g. `addi a0, a0, 1` as `c.addi a0, 1`.
+fn fold_destination(inst: &mut Instruction) {
+    if inst.len != 2 || inst.operand_count != 3 || inst.operands[0] != inst.operands[1] {
+        return;
+    }
 
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
+    inst.compressed = match inst.opcode {
+        Opcode::ADDI => Some("c.addi"),
+        Opcode::ADDIW => Some("c.addiw"),
+        Opcode::ADD => Some("c.add"),
+        Opcode::ADDW => Some("c.addw"),
+        Opcode::XOR => Some("c.xor"),
+        Opcode::OR => Some("c.or"),
+        Opcode::AND => Some("c.and"),
+        Opcode::SRAI => Some("c.srai"),
+        Opcode::SRLI => Some("c.srli"),
+        Opcode::SLLI => Some("c.slli"),
+        _ => return,
     };
 
-    MAPPING[Opcode::C_ADDW as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
+    inst.operands.swap(1, 2);
+    inst.operand_count = 2;
+}
 
-    MAPPING[Opcode::ADDW as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
+// NOTE: doing closure assignment in `map_to_psuedo` makes the compiler
+// assign function mappings in the array on each call.
+static MAPPING: Lazy<[fn(&mut Instruction); OPCODE_COUNT]> = Lazy::new(|| {
+    const DO_NOTHING: fn(&mut Instruction) = |_| {};
+    let mut mapping = [DO_NOTHING; OPCODE_COUNT];
+
+    const ZERO: Operand = Operand::Register(Register::Zero);
+    const RA: Operand = Operand::Register(Register::Ra);
+
+    for opcode in [
+        Opcode::ADDW,
+        Opcode::XOR,
+        Opcode::OR,
+        Opcode::AND,
+        Opcode::SRAI,
+        Opcode::SRLI,
+        Opcode::SLLI,
+    ] {
+        mapping[opcode as usize] = fold_destination;
+    }
 
-    MAPPING[Opcode::ADDI as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::Zero)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
+    mapping[Opcode::ADDI as usize] = |inst| {
+        if inst.operands[..3] == [ZERO, ZERO, Operand::Immediate(0)] {
             inst.opcode = Opcode::NOP;
             inst.operand_count = 0;
             return;
         }
 
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::LI;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[0] == inst.operands[1] && inst.operands[2] == Operand::Immediate(0) {
-            inst.opcode = Opcode::MV;
-            inst.operand_count = 2;
-            return;
-        }
-
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::ADD as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_ADD as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_ADDIW as usize] = |inst| {
         if inst.operands[2] == Operand::Immediate(0) {
-            inst.opcode = Opcode::SEXT_W;
+            inst.opcode = Opcode::MV;
             inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
+        fold_destination(inst);
     };
 
-    MAPPING[Opcode::ADDIW as usize] = |inst| {
+    mapping[Opcode::ADDIW as usize] = |inst| {
         if inst.operands[2] == Operand::Immediate(0) {
             inst.opcode = Opcode::SEXT_W;
             inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_XOR as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
+        fold_destination(inst);
     };
 
-    MAPPING[Opcode::XOR as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
+    mapping[Opcode::ADD as usize] = |inst| {
+        if inst.operands[1] == ZERO {
+            inst.opcode = Opcode::MV;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::XORI as usize] = |inst| {
-        if inst.operands[2] == Operand::Immediate(-1) {
-            inst.opcode = Opcode::NOT;
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_AND as usize] = |inst| {
-        if inst.operands[2] == Operand::Immediate(-1) {
-            inst.opcode = Opcode::NOT;
-            inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
+        fold_destination(inst);
     };
 
-    MAPPING[Opcode::AND as usize] = |inst| {
+    mapping[Opcode::XORI as usize] = |inst| {
         if inst.operands[2] == Operand::Immediate(-1) {
             inst.opcode = Opcode::NOT;
             inst.operand_count = 2;
-            return;
-        }
-
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::OR as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_OR as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_SUB as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::NEG;
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::SUB as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+    mapping[Opcode::SUB as usize] = |inst| {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::NEG;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::C_SUBW as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::NEG;
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::SUBW as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::NEG;
+    mapping[Opcode::SUBW as usize] = |inst| {
+        if inst.operands[1] == ZERO {
+            inst.opcode = Opcode::NEGW;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::SLTIU as usize] = |inst| {
+    mapping[Opcode::SLTIU as usize] = |inst| {
         if inst.operands[2] == Operand::Immediate(1) {
             inst.opcode = Opcode::SEQZ;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::SLTU as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+    mapping[Opcode::SLTU as usize] = |inst| {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::SNEZ;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::SLT as usize] = |inst| {
-        if inst.operands[2] == Operand::Register(Register::Zero) {
+    mapping[Opcode::SLT as usize] = |inst| {
+        if inst.operands[2] == ZERO {
             inst.opcode = Opcode::SLTZ;
             inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::SGTZ;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJ_S as usize] = |inst| {
+    mapping[Opcode::FSGNJ_S as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FMV_S;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJX_S as usize] = |inst| {
+    mapping[Opcode::FSGNJX_S as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FABS_S;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJN_S as usize] = |inst| {
+    mapping[Opcode::FSGNJN_S as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FNEG_S;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJ_D as usize] = |inst| {
+    mapping[Opcode::FSGNJ_D as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FMV_D;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJX_D as usize] = |inst| {
+    mapping[Opcode::FSGNJX_D as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FABS_D;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::FSGNJN_D as usize] = |inst| {
+    mapping[Opcode::FSGNJN_D as usize] = |inst| {
         if inst.operands[1] == inst.operands[2] {
             inst.opcode = Opcode::FNEG_D;
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::BEQ as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+    mapping[Opcode::BEQ as usize] = |inst| {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::BEQZ;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::BNE as usize] = |inst| {
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+    mapping[Opcode::BNE as usize] = |inst| {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::BNEZ;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::BGE as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero) {
+    mapping[Opcode::BGE as usize] = |inst| {
+        if inst.operands[0] == ZERO {
             inst.opcode = Opcode::BLEZ;
             inst.operands.swap(0, 1);
             inst.operands.swap(1, 2);
@@ -1298,265 +1401,143 @@ static MAPPING: Lazy<[fn(&mut Instruction); 284]> = Lazy::new(|| unsafe {
             return;
         }
 
-        if inst.operands[1] == Operand::Register(Register::Zero) {
+        if inst.operands[1] == ZERO {
             inst.opcode = Opcode::BGEZ;
-            inst.operands.swap(0, 1);
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::BLT as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::BLTZ;
+    mapping[Opcode::BLT as usize] = |inst| {
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::BGTZ;
             inst.operands.swap(0, 1);
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
             return;
         }
 
-        if inst.operands[1] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::BGTZ;
-            inst.operands.swap(0, 1);
+        if inst.operands[1] == ZERO {
+            inst.opcode = Opcode::BLTZ;
             inst.operands.swap(1, 2);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::C_JAL as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero) {
-            inst.opcode = Opcode::C_J;
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra) {
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-        }
-    };
-
-    MAPPING[Opcode::JAL as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero) {
+    mapping[Opcode::JAL as usize] = |inst| {
+        if inst.operands[0] == ZERO {
             inst.opcode = Opcode::J;
             inst.operands.swap(0, 1);
             inst.operand_count = 1;
             return;
         }
 
-        if inst.operands[0] == Operand::Register(Register::Ra) {
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-        }
-    };
-
-    MAPPING[Opcode::C_JALR as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.opcode = Opcode::RET;
-            inst.operand_count = 0;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.opcode = Opcode::C_JR;
-            inst.operands.swap(0, 2);
-            inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.opcode = Opcode::RET;
-            inst.operand_count = 0;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-        {
-            inst.opcode = Opcode::CALL;
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::T1)
-        {
-            inst.opcode = Opcode::TAIL;
+        if inst.operands[0] == RA {
             inst.operands.swap(0, 1);
             inst.operand_count = 1;
         }
     };
 
-    MAPPING[Opcode::JALR as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
+    mapping[Opcode::JALR as usize] = |inst| match (inst.operands[0], inst.operands[1]) {
+        (ZERO, Operand::Memory(Register::Ra, 0)) => {
             inst.opcode = Opcode::RET;
             inst.operand_count = 0;
-            return;
         }
-
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
+        (ZERO, Operand::Memory(rs, 0)) => {
             inst.opcode = Opcode::JR;
-            inst.operands.swap(0, 2);
-            inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.opcode = Opcode::RET;
-            inst.operand_count = 0;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[2] == Operand::Immediate(0)
-        {
-            inst.operands.swap(0, 1);
+            inst.operands = operands![Operand::Register(rs)].0;
             inst.operand_count = 1;
-            return;
-        }
-
-        if inst.operands[0] == Operand::Register(Register::Ra)
-            && inst.operands[1] == Operand::Register(Register::Ra)
-        {
-            inst.opcode = Opcode::CALL;
-            inst.operands.swap(0, 1);
-            inst.operand_count = 1;
-            return;
         }
-
-        if inst.operands[0] == Operand::Register(Register::Zero)
-            && inst.operands[1] == Operand::Register(Register::T1)
-        {
-            inst.opcode = Opcode::TAIL;
-            inst.operands.swap(0, 1);
+        (RA, Operand::Memory(rs, 0)) => {
+            inst.operands = operands![Operand::Register(rs)].0;
             inst.operand_count = 1;
         }
+        _ => {}
     };
 
-    MAPPING[Opcode::AUIPC as usize] = |inst| {
-        if inst.operands[0] == Operand::Register(Register::Ra) {
-            inst.opcode = Opcode::CALL;
-
-            match (inst.operands[0], inst.operands[1]) {
-                (Operand::Register(reg), Operand::Immediate(mut imm)) => {
-                    // offset[31 : 12] + offset[11] where register is bit's [11:6]
-                    imm <<= 1;
-                    imm |= ((reg as u16 & 0b10000) >> 4) as i32;
-                    inst.operands[0] = Operand::Immediate(imm);
-                    inst.operand_count = 1;
+    mapping[Opcode::CSRRS as usize] = |inst| {
+        if inst.operands[2] == ZERO {
+            inst.opcode = match inst.operands[1] {
+                Operand::Csr(0x001) => Opcode::FRFLAGS,
+                Operand::Csr(0x002) => Opcode::FRRM,
+                Operand::Csr(0x003) => Opcode::FRCSR,
+                Operand::Csr(0xc00) => Opcode::RDCYCLE,
+                Operand::Csr(0xc01) => Opcode::RDTIME,
+                Operand::Csr(0xc02) => Opcode::RDINSTRET,
+                _ => {
+                    inst.opcode = Opcode::CSRR;
+                    inst.operand_count = 2;
+                    return;
                 }
-                _ => unreachable!(),
-            }
-
+            };
+            inst.operand_count = 1;
             return;
         }
 
-        if inst.operands[0] == Operand::Register(Register::T1) {
-            inst.opcode = Opcode::TAIL;
-
-            match (inst.operands[0], inst.operands[1]) {
-                (Operand::Register(reg), Operand::Immediate(mut imm)) => {
-                    // offset[31 : 12] + offset[11] where register is bit's [11:6]
-                    imm <<= 1;
-                    imm |= ((reg as u16 & 0b10000) >> 4) as i32;
-                    inst.operands[0] = Operand::Immediate(imm);
-                    inst.operand_count = 1;
-                }
-                _ => unreachable!(),
-            }
-        }
-    };
-
-    MAPPING[Opcode::C_SRAI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::SRAI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
-
-    MAPPING[Opcode::C_SRAI64 as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::CSRS;
+            inst.operands.copy_within(1..3, 0);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::C_SRLI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
-        }
-    };
+    mapping[Opcode::CSRRW as usize] = |inst| {
+        let opcode = match inst.operands[1] {
+            Operand::Csr(0x001) => Opcode::FSFLAGS,
+            Operand::Csr(0x002) => Opcode::FSRM,
+            Operand::Csr(0x003) => Opcode::FSCSR,
+            _ if inst.operands[0] == ZERO => {
+                inst.opcode = Opcode::CSRW;
+                inst.operands.copy_within(1..3, 0);
+                inst.operand_count = 2;
+                return;
+            }
+            _ => return,
+        };
 
-    MAPPING[Opcode::SRLI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
-            inst.operand_count = 2;
+        // the floating-point registers have their own pseudo-instructions
+        inst.opcode = opcode;
+        inst.operands[1] = inst.operands[2];
+        inst.operand_count = 2;
+        if inst.operands[0] == ZERO {
+            inst.operands.swap(0, 1);
+            inst.operand_count = 1;
         }
     };
 
-    MAPPING[Opcode::C_SRLI64 as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
+    mapping[Opcode::CSRRC as usize] = |inst| {
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::CSRC;
+            inst.operands.copy_within(1..3, 0);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::C_SLLI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
+    mapping[Opcode::CSRRWI as usize] = |inst| {
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::CSRWI;
+            inst.operands.copy_within(1..3, 0);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::SLLI as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
+    mapping[Opcode::CSRRSI as usize] = |inst| {
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::CSRSI;
+            inst.operands.copy_within(1..3, 0);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING[Opcode::C_SLLI64 as usize] = |inst| {
-        if inst.operands[0] == inst.operands[1] {
-            inst.operands.swap(1, 2);
+    mapping[Opcode::CSRRCI as usize] = |inst| {
+        if inst.operands[0] == ZERO {
+            inst.opcode = Opcode::CSRCI;
+            inst.operands.copy_within(1..3, 0);
             inst.operand_count = 2;
         }
     };
 
-    MAPPING
+    mapping
 });
 
 #[inline]
@@ -1565,6 +1546,21 @@ fn map_to_psuedo(mut inst: Instruction) -> Instruction {
     inst
 }
 
+/// Integer or floating-point register, `num` being it's number within either.
+#[inline]
+fn register(num: u32, float: bool) -> Result<Register, ErrorKind> {
+    Register::get(if float { num + 32 } else { num })
+}
+
+/// Decode's rounding mode of floating-point instructions, which is left out if it's dynamic.
+fn rounding(dword: u32) -> Result<Option<Operand>, ErrorKind> {
+    match dword >> 12 & 0b111 {
+        0b101 | 0b110 => Err(ErrorKind::InvalidOperand),
+        0b111 => Ok(None),
+        rm => Ok(Some(Operand::Rounding(rm as u8))),
+    }
+}
+
 /// Decode's beqz and bnez instructions.
 fn decode_comp_branch(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
     let rs = Register::get_int(word >> 7 & 0b111)?;
@@ -1583,22 +1579,17 @@ fn decode_comp_branch(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKin
         imm |= (imm | 0b1111111000000000) as i16 as i32;
     }
 
-    let (operands, operand_count) = operands![
-        Operand::Register(rs),
+    let operands = operands![
         Operand::Register(rs),
+        Operand::Register(Register::Zero),
         Operand::Immediate(imm)
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
-/// Decode's beqz and bnez instructions.
-fn decode_comp_jump(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
+/// Decode's j and jal instructions, which link to `rd`.
+fn decode_comp_jump(rd: Register, word: u16) -> Result<Instruction, ErrorKind> {
     let mut imm = 0;
 
     imm |= word >> 1 & 0b100000000000;
@@ -1617,32 +1608,19 @@ fn decode_comp_jump(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind>
         imm |= (imm | 0b1111000000000000) as i16 as i32;
     }
 
-    let (operands, operand_count) = operands![Operand::Immediate(imm)];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    let operands = operands![Operand::Register(rd), Operand::Immediate(imm)];
+    Ok(Instruction::new(Opcode::JAL, operands, 2))
 }
 
-/// Decode's j and jal instructions.
-fn decode_comp_jumpr(word: u16) -> Result<Instruction, ErrorKind> {
+/// Decode's jr and jalr instructions, which link to `rd`.
+fn decode_comp_jumpr(rd: Register, word: u16) -> Result<Instruction, ErrorKind> {
     let rs = Register::get((word >> 7 & 0b11111) as u32)?;
+    if rs == Register::Zero {
+        return Err(ErrorKind::InvalidOperand);
+    }
 
-    let (operands, operand_count) = operands![
-        Operand::Register(Register::Ra),
-        Operand::Register(rs),
-        Operand::Immediate(0)
-    ];
-
-    Ok(Instruction {
-        opcode: Opcode::C_JALR,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    let operands = operands![Operand::Register(rd), Operand::Memory(rs, 0)];
+    Ok(Instruction::new(Opcode::JALR, operands, 2))
 }
 
 /// Decode's sub, or, xor, and, subw and addw instructions.
@@ -1650,60 +1628,75 @@ fn decode_comp_arith(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind
     let rd = Register::get_int(word >> 7 & 0b111)?;
     let rs = Register::get_int(word >> 2 & 0b111)?;
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rd),
         Operand::Register(rs),
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
 /// Decode's srli, srai and slli instructions.
-fn decode_comp_shift(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rd = Register::get_int(word >> 7 & 0b111)?;
+fn decode_comp_shift(opcode: Opcode, word: u16, is_64: bool) -> Result<Instruction, ErrorKind> {
+    // slli can shift any register, the others only the popular ones
+    let rd = match opcode {
+        Opcode::SLLI => Register::get((word >> 7 & 0b11111) as u32)?,
+        _ => Register::get_int(word >> 7 & 0b111)?,
+    };
+
     let shamt = (word >> 7 & 0b100000) | (word >> 2 & 0b11111);
+    if !is_64 && shamt >= 32 {
+        return Err(ErrorKind::InvalidOperand);
+    }
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rd),
         Operand::Immediate(shamt as i32)
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
-/// Decode's addi and addiw instructions.
-fn decode_comp_addi(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rd = Register::get((word >> 7 & 0b11111) as u32)?;
+/// Decode's the sign-extended 6-bit immediate of compressed instructions.
+fn comp_immediate(word: u16) -> i32 {
     let mut imm = (((word >> 7) & 0b100000) | ((word >> 2) & 0b11111)) as i16;
 
     if imm & 0b100000 != 0 {
         imm = (imm | 0b11000000) as i8 as i16;
     }
 
-    let (operands, operand_count) = operands![
+    imm as i32
+}
+
+/// Decode's addi, addiw and nop instructions.
+fn decode_comp_addi(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
+    let rd = Register::get((word >> 7 & 0b11111) as u32)?;
+    if opcode == Opcode::ADDIW && rd == Register::Zero {
+        return Err(ErrorKind::InvalidOperand);
+    }
+
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rd),
-        Operand::Immediate(imm as i32),
+        Operand::Immediate(comp_immediate(word)),
+    ];
+
+    Ok(Instruction::new(opcode, operands, 2))
+}
+
+/// Decode's andi instruction.
+fn decode_comp_andi(word: u16) -> Result<Instruction, ErrorKind> {
+    let rd = Register::get_int(word >> 7 & 0b111)?;
+
+    let operands = operands![
+        Operand::Register(rd),
+        Operand::Register(rd),
+        Operand::Immediate(comp_immediate(word)),
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(Opcode::ANDI, operands, 2))
 }
 
 /// Decode's addi16sp instruction also represented as addi sp, sp, imm*16.
@@ -1722,17 +1715,17 @@ fn decode_addi16sp(word: u16) -> Result<Instruction, ErrorKind> {
         imm = (imm | 0b1111110000000000) as i16 as i32;
     }
 
-    let (operands, operand_count) = operands![Operand::Immediate(imm)];
+    if imm == 0 {
+        return Err(ErrorKind::InvalidOperand);
+    }
 
-    Ok(Instruction {
-        opcode: Opcode::C_ADDI16SP,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    // the stack pointer it adds to is implied
+    let mut inst = Instruction::new(Opcode::ADDI, operands![Operand::Immediate(imm)], 2);
+    inst.compressed = Some("c.addi16sp");
+    Ok(inst)
 }
 
-/// Decode's addi14spn instruction also represented as addi sp, rs, imm*4.
+/// Decode's addi14spn instruction also represented as addi rd, sp, imm*4.
 fn decode_addi4spn(word: u16) -> Result<Instruction, ErrorKind> {
     let rd = Register::get_int(word >> 2 & 0b111)?;
     let mut imm = 0;
@@ -1742,241 +1735,124 @@ fn decode_addi4spn(word: u16) -> Result<Instruction, ErrorKind> {
     imm |= word >> 2 & 0b0000001000;
     imm |= word >> 4 & 0b0000000100;
 
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode: Opcode::C_ADDI4SPN,
-        operands,
-        operand_count,
-        len: 2,
-    })
-}
-
-/// Decode's add instruction.
-fn decode_comp_add(word: u16) -> Result<Instruction, ErrorKind> {
-    let rd = Register::get((word >> 7 & 0b11111) as u32)?;
-    let rs = Register::get((word >> 2 & 0b11111) as u32)?;
+    // this includes an instruction of all zeros, which is defined to be illegal
+    if imm == 0 {
+        return Err(ErrorKind::InvalidOpcode);
+    }
 
-    let (operands, operand_count) = operands![
-        Operand::Register(rd),
+    let operands = operands![
         Operand::Register(rd),
-        Operand::Register(rs)
+        Operand::Register(Register::Sp),
+        Operand::Immediate(imm as i32)
     ];
 
-    Ok(Instruction {
-        opcode: Opcode::C_ADD,
-        operands,
-        operand_count,
-        len: 2,
-    })
-}
-
-/// Decode's li and lui instructions.
-fn decode_comp_li(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let mut imm = (((word >> 7) & 0b100000) | ((word >> 2) & 0b11111)) as i16;
-    let rs = Register::get((word >> 7 & 0b11111) as u32)?;
-
-    if imm & 0b100000 != 0 {
-        imm = (imm | 0b11000000) as i8 as i16;
-    }
-
-    let (operands, operand_count) =
-        operands![Operand::Register(rs), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
-}
-
-/// Decode's store word relative to sp instruction for both integers and floats.
-fn decode_comp_swsp(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rd = Register::get((word >> 2 & 0b11111) as u32)?;
-    let imm = (word >> 1 & 0b11000000) | (word >> 7 & 0b111100);
-
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(Opcode::ADDI, operands, 2))
 }
 
-/// Decode's store double relative to sp instruction for both integers and floats.
-fn decode_comp_sdsp(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rd = Register::get((word >> 2 & 0b11111) as u32)?;
-    let imm = (word >> 1 & 0b111000000) | (word >> 7 & 0b111000);
-
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
-}
-
-/// Decode's load word relative to sp instruction for both integers and floats.
-fn decode_comp_lwsp(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
+/// Decode's add and mv instructions, mv adding to the `zero` register.
+fn decode_comp_add(rs1: Register, word: u16) -> Result<Instruction, ErrorKind> {
     let rd = Register::get((word >> 7 & 0b11111) as u32)?;
-    let imm = (word << 4 & 0b11000000) | (word >> 7 & 0b100000) | (word >> 2 & 0b11100);
+    let rs2 = Register::get((word >> 2 & 0b11111) as u32)?;
 
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
+    let operands = operands![
+        Operand::Register(rd),
+        Operand::Register(rs1),
+        Operand::Register(rs2)
+    ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(Opcode::ADD, operands, 2))
 }
 
-/// Decode's load double relative to sp instruction for both integers and floats.
-fn decode_comp_ldsp(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
+/// Decode's li instruction.
+fn decode_comp_li(word: u16) -> Result<Instruction, ErrorKind> {
     let rd = Register::get((word >> 7 & 0b11111) as u32)?;
-    let imm = (word << 4 & 0b111000000) | (word >> 7 & 0b100000) | (word >> 2 & 0b11000);
 
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
-}
-
-/// Decode's load word instruction for integers.
-fn decode_comp_slw(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rs1 = Register::get_int(word >> 2 & 0b111)?;
-    let rs2 = Register::get_int(word >> 7 & 0b111)?;
-    let imm = (word << 1 & 0b1000000) | (word >> 7 & 0b111000) | (word >> 4 & 0b100);
-
-    let (operands, operand_count) = operands![
-        Operand::Register(rs1),
-        Operand::Register(rs2),
-        Operand::Immediate(imm as i32)
+    let operands = operands![
+        Operand::Register(rd),
+        Operand::Register(Register::Zero),
+        Operand::Immediate(comp_immediate(word))
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(Opcode::ADDI, operands, 2))
 }
 
-/// Decode's load double instruction for integers.
-fn decode_comp_sld(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rs1 = Register::get_int(word >> 2 & 0b111)?;
-    let rs2 = Register::get_int(word >> 7 & 0b111)?;
-    let imm = (word << 1 & 0b11000000) | (word >> 7 & 0b111000);
-
-    let (operands, operand_count) = operands![
-        Operand::Register(rs1),
-        Operand::Register(rs2),
-        Operand::Immediate(imm as i32)
-    ];
+/// Decode's lui instruction.
+fn decode_comp_lui(word: u16) -> Result<Instruction, ErrorKind> {
+    let rd = Register::get((word >> 7 & 0b11111) as u32)?;
+    let imm = comp_immediate(word);
+    if imm == 0 {
+        return Err(ErrorKind::InvalidOperand);
+    }
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    // the immediate is the sign-extended upper 20 bits
+    let operands = operands![Operand::Register(rd), Operand::Immediate(imm & 0xfffff)];
+    Ok(Instruction::new(Opcode::LUI, operands, 2))
 }
 
-/// Decode's load word instruction for floats.
-fn decode_comp_fslw(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rs1 = Register::get_fp(word >> 2 & 0b111)?;
-    let rs2 = Register::get_fp(word >> 7 & 0b111)?;
-    let imm = (word << 1 & 0b1000000) | (word >> 7 & 0b111000) | (word >> 4 & 0b100);
+/// Decode's store and load instructions relative to sp, for both integers and floats.
+fn decode_comp_load_sp(opcode: Opcode, word: u16, float: bool) -> Result<Instruction, ErrorKind> {
+    let rd = register((word >> 7 & 0b11111) as u32, float)?;
+    if rd == Register::Zero {
+        return Err(ErrorKind::InvalidOperand);
+    }
 
-    let (operands, operand_count) = operands![
-        Operand::Register(rs1),
-        Operand::Register(rs2),
-        Operand::Immediate(imm as i32)
-    ];
+    let imm = match opcode {
+        Opcode::LW | Opcode::FLW => {
+            (word << 4 & 0b11000000) | (word >> 7 & 0b100000) | (word >> 2 & 0b11100)
+        }
+        _ => (word << 4 & 0b111000000) | (word >> 7 & 0b100000) | (word >> 2 & 0b11000),
+    };
+
+    let operands =
+        operands![Operand::Register(rd), Operand::Memory(Register::Sp, imm as i32)];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
-/// Decode's load double instruction for floats.
-fn decode_comp_fsld(opcode: Opcode, word: u16) -> Result<Instruction, ErrorKind> {
-    let rs1 = Register::get_fp(word >> 2 & 0b111)?;
-    let rs2 = Register::get_fp(word >> 7 & 0b111)?;
-    let imm = (word << 1 & 0b11000000) | (word >> 7 & 0b111000);
+/// Decode's store instructions relative to sp, for both integers and floats.
+fn decode_comp_store_sp(opcode: Opcode, word: u16, float: bool) -> Result<Instruction, ErrorKind> {
+    let rs = register((word >> 2 & 0b11111) as u32, float)?;
+    let imm = match opcode {
+        Opcode::SW | Opcode::FSW => (word >> 1 & 0b11000000) | (word >> 7 & 0b111100),
+        _ => (word >> 1 & 0b111000000) | (word >> 7 & 0b111000),
+    };
 
-    let (operands, operand_count) = operands![
-        Operand::Register(rs1),
-        Operand::Register(rs2),
-        Operand::Immediate(imm as i32)
-    ];
+    let operands =
+        operands![Operand::Register(rs), Operand::Memory(Register::Sp, imm as i32)];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
-/// Decode's move instruction.
-fn decode_comp_mv(word: u16) -> Result<Instruction, ErrorKind> {
-    let rs = Register::get((word >> 2 & 0b11111) as u32)?;
-    let rd = Register::get((word >> 7 & 0b11111) as u32)?;
-
-    let (operands, operand_count) = operands![Operand::Register(rd), Operand::Register(rs)];
+/// Decode's load and store instructions, for both integers and floats.
+fn decode_comp_mem(opcode: Opcode, word: u16, float: bool) -> Result<Instruction, ErrorKind> {
+    let reg = match float {
+        true => Register::get_fp(word >> 2 & 0b111)?,
+        false => Register::get_int(word >> 2 & 0b111)?,
+    };
+    let base = Register::get_int(word >> 7 & 0b111)?;
+    let imm = match opcode {
+        Opcode::LW | Opcode::SW | Opcode::FLW | Opcode::FSW => {
+            (word << 1 & 0b1000000) | (word >> 7 & 0b111000) | (word >> 4 & 0b100)
+        }
+        _ => (word << 1 & 0b11000000) | (word >> 7 & 0b111000),
+    };
 
-    Ok(Instruction {
-        opcode: Opcode::C_MV,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    let operands = operands![Operand::Register(reg), Operand::Memory(base, imm as i32)];
+    Ok(Instruction::new(opcode, operands, 2))
 }
 
 /// Decode's instructions with weird formatting that aren't yet handled.
 fn decode_comp_unique(opcode: Opcode) -> Result<Instruction, ErrorKind> {
-    let (operands, operand_count) = operands![];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 2,
-    })
+    Ok(Instruction::new(opcode, operands![], 2))
 }
 
 /// Decode's instructions with weird formatting that aren't yet handled.
 fn decode_unique(opcode: Opcode) -> Result<Instruction, ErrorKind> {
-    let (operands, operand_count) = operands![];
-
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, operands![], 4))
 }
 
-/// Decode's sb, sh, sw and sd store instructions.
-fn decode_store(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind> {
+/// Decode's sb, sh, sw, sd, fsw, fsd and fsq store instructions.
+fn decode_store(opcode: Opcode, dword: u32, float: bool) -> Result<Instruction, ErrorKind> {
     let mut imm = 0;
 
     imm |= ((dword & 0b11111110000000000000000000000000) as i32 >> 20) as u32;
@@ -1984,20 +1860,20 @@ fn decode_store(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind> {
 
     let imm = imm as i32;
     let rs1 = Register::get(dword >> 15 & 0b11111)?;
-    let rs2 = Register::get(dword >> 20 & 0b11111)?;
+    let rs2 = register(dword >> 20 & 0b11111, float)?;
 
-    let (operands, operand_count) = operands![
-        Operand::Register(rs2),
-        Operand::Register(rs1),
-        Operand::Immediate(imm),
-    ];
+    let operands = operands![Operand::Register(rs2), Operand::Memory(rs1, imm)];
+    Ok(Instruction::new(opcode, operands, 4))
+}
+
+/// Decode's integer and floating-point load instructions.
+fn decode_load(opcode: Opcode, dword: u32, float: bool) -> Result<Instruction, ErrorKind> {
+    let rd = register(dword >> 7 & 0b11111, float)?;
+    let rs = Register::get(dword >> 15 & 0b11111)?;
+    let imm = dword as i32 >> 20;
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    let operands = operands![Operand::Register(rd), Operand::Memory(rs, imm)];
+    Ok(Instruction::new(opcode, operands, 4))
 }
 
 /// Decode's beq, bne, blt, bge, bltu and bgeu branch instructions.
@@ -2013,18 +1889,13 @@ fn decode_branch(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind> {
     imm |= dword >> 20 & 0b011111100000;
     imm |= dword >> 7 & 0b000000011110;
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rs1),
         Operand::Register(rs2),
         Operand::Immediate(imm as i32),
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, operands, 4))
 }
 
 /// Decode's jump instruction.
@@ -2038,29 +1909,18 @@ fn decode_jump(dword: u32) -> Result<Instruction, ErrorKind> {
     imm |= dword >> 9 & 0b00000000100000000000;
     imm |= dword >> 20 & 0b00000000011111111110;
 
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
-
-    Ok(Instruction {
-        opcode: Opcode::JAL,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    let operands = operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
+    Ok(Instruction::new(Opcode::JAL, operands, 4))
 }
 
-/// Decode's ret instruction.
-fn decode_jumpr(bytes: u32) -> Result<Instruction, ErrorKind> {
-    let imm = bytes as i32 >> 20;
-    let rd = Register::get(bytes >> 7 & 0b11111)?;
-    let (operands, operand_count) = operands![Operand::Register(rd), Operand::Immediate(imm)];
-
-    Ok(Instruction {
-        opcode: Opcode::JALR,
-        operands,
-        operand_count,
-        len: 4,
-    })
+/// Decode's jump to register instruction.
+fn decode_jumpr(dword: u32) -> Result<Instruction, ErrorKind> {
+    let imm = dword as i32 >> 20;
+    let rd = Register::get(dword >> 7 & 0b11111)?;
+    let rs = Register::get(dword >> 15 & 0b11111)?;
+
+    let operands = operands![Operand::Register(rd), Operand::Memory(rs, imm)];
+    Ok(Instruction::new(Opcode::JALR, operands, 4))
 }
 
 /// Decode's instructions that have two registers and an immediate.
@@ -2069,18 +1929,13 @@ fn decode_immediate(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind
     let rs = Register::get(dword >> 15 & 0b11111)?;
     let imm = dword as i32 >> 20;
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rs),
         Operand::Immediate(imm),
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, operands, 4))
 }
 
 /// Decode's slli, srai, srli, slliw, sraiw and srliw  instruction's.
@@ -2094,18 +1949,13 @@ fn decode_arith(opcode: Opcode, dword: u32, opts: &Decoder) -> Result<Instructio
         dword >> 20 & 0b11111
     };
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rs),
         Operand::Immediate(shamt as i32),
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, operands, 4))
 }
 
 /// Decode's instructions that have three registers.
@@ -2114,18 +1964,13 @@ fn decode_triplet(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind>
     let rs1 = Register::get(dword >> 15 & 0b11111)?;
     let rs2 = Register::get(dword >> 20 & 0b11111)?;
 
-    let (operands, operand_count) = operands![
+    let operands = operands![
         Operand::Register(rd),
         Operand::Register(rs1),
         Operand::Register(rs2)
     ];
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, operands, 4))
 }
 
 /// Decode's instructions that have have a registers and an immediate.
@@ -2133,13 +1978,214 @@ fn decode_double(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind> {
     let imm = dword >> 12;
     let rd = Register::get(dword >> 7 & 0b11111)?;
 
-    let (operands, operand_count) =
-        operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
+    let operands = operands![Operand::Register(rd), Operand::Immediate(imm as i32)];
+    Ok(Instruction::new(opcode, operands, 4))
+}
+
+/// Decode's sfence.vma instruction.
+fn decode_sfence_vma(dword: u32) -> Result<Instruction, ErrorKind> {
+    let rs1 = Register::get(dword >> 15 & 0b11111)?;
+    let rs2 = Register::get(dword >> 20 & 0b11111)?;
+
+    let operands = operands![Operand::Register(rs1), Operand::Register(rs2)];
+    Ok(Instruction::new(Opcode::SFENCE_VMA, operands, 4))
+}
+
+/// Decode's instructions that read and write a control and status register, either from a
+/// register or from a 5-bit immediate.
+fn decode_csr(opcode: Opcode, dword: u32) -> Result<Instruction, ErrorKind> {
+    let rd = Register::get(dword >> 7 & 0b11111)?;
+    let csr = (dword >> 20) as u16;
+    let src = match opcode {
+        Opcode::CSRRWI | Opcode::CSRRSI | Opcode::CSRRCI => {
+            Operand::Immediate((dword >> 15 & 0b11111) as i32)
+        }
+        _ => Operand::Register(Register::get(dword >> 15 & 0b11111)?),
+    };
+
+    let operands = operands![Operand::Register(rd), Operand::Csr(csr), src];
+    Ok(Instruction::new(opcode, operands, 4))
+}
+
+/// Decode's load-reserved, store-conditional and atomic memory instructions, `opcodes` being
+/// the ones of a width ordered as lr, sc, amoswap, amoadd, amoxor, amoand, amoor, amomin,
+/// amomax, amominu and amomaxu.
+fn decode_atomic(opcodes: [Opcode; 11], dword: u32) -> Result<Instruction, ErrorKind> {
+    let opcode = match dword >> 27 {
+        0b00010 if dword >> 20 & 0b11111 == 0 => opcodes[0],
+        0b00011 => opcodes[1],
+        0b00001 => opcodes[2],
+        0b00000 => opcodes[3],
+        0b00100 => opcodes[4],
+        0b01100 => opcodes[5],
+        0b01000 => opcodes[6],
+        0b10000 => opcodes[7],
+        0b10100 => opcodes[8],
+        0b11000 => opcodes[9],
+        0b11100 => opcodes[10],
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let rd = Register::get(dword >> 7 & 0b11111)?;
+    let rs1 = Register::get(dword >> 15 & 0b11111)?;
+    let rs2 = Register::get(dword >> 20 & 0b11111)?;
+
+    let operands = match opcode == opcodes[0] {
+        true => operands![Operand::Register(rd), Operand::Memory(rs1, 0)],
+        false => operands![
+            Operand::Register(rd),
+            Operand::Register(rs2),
+            Operand::Memory(rs1, 0)
+        ],
+    };
+
+    let mut inst = Instruction::new(opcode, operands, 4);
+    inst.ordering = (dword >> 25 & 0b11) as u8;
+    Ok(inst)
+}
+
+/// Decode's fused multiply-add instructions, `opcodes` being the ones of single, double and
+/// quad precision.
+fn decode_fused(opcodes: [Opcode; 3], dword: u32) -> Result<Instruction, ErrorKind> {
+    let opcode = match dword >> 25 & 0b11 {
+        0b00 => opcodes[0],
+        0b01 => opcodes[1],
+        0b11 => opcodes[2],
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let rd = register(dword >> 7 & 0b11111, true)?;
+    let rs1 = register(dword >> 15 & 0b11111, true)?;
+    let rs2 = register(dword >> 20 & 0b11111, true)?;
+    let rs3 = register(dword >> 27, true)?;
+
+    let (mut operands, mut operand_count) = operands![
+        Operand::Register(rd),
+        Operand::Register(rs1),
+        Operand::Register(rs2),
+        Operand::Register(rs3),
+    ];
+
+    if let Some(rm) = rounding(dword)? {
+        operands[operand_count] = rm;
+        operand_count += 1;
+    }
+
+    Ok(Instruction::new(opcode, (operands, operand_count), 4))
+}
+
+/// Decode's floating-point instructions that aren't loads, stores or fused.
+fn decode_op_fp(dword: u32, is_64: bool) -> Result<Instruction, ErrorKind> {
+    use Opcode::*;
+
+    // lowest bits of funct7 select single, double or quad precision
+    let fmt = match dword >> 25 & 0b11 {
+        0b00 => 0,
+        0b01 => 1,
+        0b11 => 2,
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let funct3 = dword >> 12 & 0b111;
+    let rs2 = dword >> 20 & 0b11111;
+    let is_64_only = |idx: u32| idx >= 2 && !is_64;
+
+    // (opcode, rd is float, rs1 is float, has rs2, has rounding mode)
+    let (opcode, rd, rs1, has_rs2, has_rm) = match dword >> 27 {
+        0b00000 => ([FADD_S, FADD_D, FADD_Q][fmt], true, true, true, true),
+        0b00001 => ([FSUB_S, FSUB_D, FSUB_Q][fmt], true, true, true, true),
+        0b00010 => ([FMUL_S, FMUL_D, FMUL_Q][fmt], true, true, true, true),
+        0b00011 => ([FDIV_S, FDIV_D, FDIV_Q][fmt], true, true, true, true),
+        0b01011 if rs2 == 0 => ([FSQRT_S, FSQRT_D, FSQRT_Q][fmt], true, true, false, true),
+        0b00100 => {
+            let opcode = match funct3 {
+                0b000 => [FSGNJ_S, FSGNJ_D, FSGNJ_Q][fmt],
+                0b001 => [FSGNJN_S, FSGNJN_D, FSGNJN_Q][fmt],
+                0b010 => [FSGNJX_S, FSGNJX_D, FSGNJX_Q][fmt],
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+            (opcode, true, true, true, false)
+        }
+        0b00101 => {
+            let opcode = match funct3 {
+                0b000 => [FMIN_S, FMIN_D, FMIN_Q][fmt],
+                0b001 => [FMAX_S, FMAX_D, FMAX_Q][fmt],
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+            (opcode, true, true, true, false)
+        }
+        // conversions between precisions, rs2 being the precision converted from
+        0b01000 => {
+            let opcode = match (fmt, rs2) {
+                (0, 0b01) => FCVT_S_D,
+                (0, 0b11) => FCVT_S_Q,
+                (1, 0b00) => FCVT_D_S,
+                (1, 0b11) => FCVT_D_Q,
+                (2, 0b00) => FCVT_Q_S,
+                (2, 0b01) => FCVT_Q_D,
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+            (opcode, true, true, false, true)
+        }
+        0b10100 => {
+            let opcode = match funct3 {
+                0b000 => [FLE_S, FLE_D, FLE_Q][fmt],
+                0b001 => [FLT_S, FLT_D, FLT_Q][fmt],
+                0b010 => [FEQ_S, FEQ_D, FEQ_Q][fmt],
+                _ => return Err(ErrorKind::InvalidOpcode),
+            };
+            (opcode, false, true, true, false)
+        }
+        // conversions to integers, rs2 being the integer's width and signedness
+        0b11000 if rs2 < 4 && !is_64_only(rs2) => {
+            let opcodes = match rs2 {
+                0b00 => [FCVT_W_S, FCVT_W_D, FCVT_W_Q],
+                0b01 => [FCVT_WU_S, FCVT_WU_D, FCVT_WU_Q],
+                0b10 => [FCVT_L_S, FCVT_L_D, FCVT_L_Q],
+                _ => [FCVT_LU_S, FCVT_LU_D, FCVT_LU_Q],
+            };
+            (opcodes[fmt], false, true, false, true)
+        }
+        // conversions from integers
+        0b11010 if rs2 < 4 && !is_64_only(rs2) => {
+            let opcodes = match rs2 {
+                0b00 => [FCVT_S_W, FCVT_D_W, FCVT_Q_W],
+                0b01 => [FCVT_S_WU, FCVT_D_WU, FCVT_Q_WU],
+                0b10 => [FCVT_S_L, FCVT_D_L, FCVT_Q_L],
+                _ => [FCVT_S_LU, FCVT_D_LU, FCVT_Q_LU],
+            };
+            (opcodes[fmt], true, false, false, true)
+        }
+        0b11100 if rs2 == 0 => match (funct3, fmt) {
+            (0b000, 0) => (FMV_X_W, false, true, false, false),
+            (0b000, 1) if is_64 => (FMV_X_D, false, true, false, false),
+            (0b001, _) => ([FCLASS_S, FCLASS_D, FCLASS_Q][fmt], false, true, false, false),
+            _ => return Err(ErrorKind::InvalidOpcode),
+        },
+        0b11110 if rs2 == 0 && funct3 == 0b000 => match fmt {
+            0 => (FMV_W_X, true, false, false, false),
+            1 if is_64 => (FMV_D_X, true, false, false, false),
+            _ => return Err(ErrorKind::InvalidOpcode),
+        },
+        _ => return Err(ErrorKind::InvalidOpcode),
+    };
+
+    let (mut operands, mut operand_count) = operands![
+        Operand::Register(register(dword >> 7 & 0b11111, rd)?),
+        Operand::Register(register(dword >> 15 & 0b11111, rs1)?),
+    ];
+
+    if has_rs2 {
+        operands[operand_count] = Operand::Register(register(rs2, true)?);
+        operand_count += 1;
+    }
+
+    if has_rm {
+        if let Some(rm) = rounding(dword)? {
+            operands[operand_count] = rm;
+            operand_count += 1;
+        }
+    }
 
-    Ok(Instruction {
-        opcode,
-        operands,
-        operand_count,
-        len: 4,
-    })
+    Ok(Instruction::new(opcode, (operands, operand_count), 4))
 }
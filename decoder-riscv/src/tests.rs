@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use decoder::{Decodable, ToTokens};
+use decoder::{Decodable, Decoded, ToTokens};
 use object::{Object, ObjectSection, SectionKind};
 
 macro_rules! decode_instructions {
@@ -104,9 +104,9 @@ fn deref() -> Result<(), Box<dyn std::error::Error>> {
 
     let test = [
         "lui a0, 4096",
-        "c.li a1, 12",
-        "c.sw a1, a0, 0",
-        "c.li a0, 0",
+        "li a1, 12",
+        "sw a1, 0(a0)",
+        "li a0, 0",
         "ret",
     ];
 
@@ -531,51 +531,51 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     let test = [
-        "c.li a3, 0",
+        "li a3, 0",
         "beq a2, a3, 18",
         "add a4, a0, a3",
-        "sb a1, a4, 0",
+        "sb a1, 0(a4)",
         "c.addi a3, 1",
         "bne a2, a3, -10",
         "ret",
         "c.addi16sp -352",
-        "c.sdsp s0, 344",
-        "c.sdsp s1, 336",
-        "c.sdsp s2, 328",
-        "c.sdsp s3, 320",
-        "c.sdsp s4, 312",
-        "c.sdsp s5, 304",
-        "c.sdsp s6, 296",
-        "c.sdsp s7, 288",
-        "c.sdsp s8, 280",
-        "c.sdsp s9, 272",
-        "c.sdsp s10, 264",
-        "c.sdsp s11, 256",
-        "c.li a2, 0",
+        "sd s0, 344(sp)",
+        "sd s1, 336(sp)",
+        "sd s2, 328(sp)",
+        "sd s3, 320(sp)",
+        "sd s4, 312(sp)",
+        "sd s5, 304(sp)",
+        "sd s6, 296(sp)",
+        "sd s7, 288(sp)",
+        "sd s8, 280(sp)",
+        "sd s9, 272(sp)",
+        "sd s10, 264(sp)",
+        "sd s11, 256(sp)",
+        "li a2, 0",
         "li a3, 64",
-        "c.mv a6, sp",
+        "mv a6, sp",
         "beq a2, a3, 48",
         "add a5, a1, a2",
-        "lb s1, a5, 0",
-        "lbu s0, a5, 1",
+        "lb s1, 0(a5)",
+        "lbu s0, 1(a5)",
         "c.slli s1, 24",
-        "lbu a4, a5, 2",
+        "lbu a4, 2(a5)",
         "c.slli s0, 16",
-        "lbu a5, a5, 3",
+        "lbu a5, 3(a5)",
         "c.or s1, s0",
         "c.slli a4, 8",
         "c.or a4, s1",
         "c.or a4, a5",
         "add a5, a6, a2",
-        "c.sw a4, a5, 0",
+        "sw a4, 0(a5)",
         "c.addi a2, 4",
         "bne a2, a3, -40",
-        "c.li a1, 0",
+        "li a1, 0",
         "li a7, 192",
-        "c.mv a6, sp",
+        "mv a6, sp",
         "beq a1, a7, 88",
         "add a4, a6, a1",
-        "lwu a5, a4, 56",
+        "lwu a5, 56(a4)",
         "srli s1, a5, 17",
         "slliw s0, a5, 15",
         "c.or s1, s0",
@@ -583,8 +583,8 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
         "slliw a3, a5, 13",
         "c.or a3, s0",
         "c.xor a3, s1",
-        "c.lw s1, a4, 36",
-        "lwu s0, a4, 4",
+        "lw s1, 36(a4)",
+        "lwu s0, 4(a4)",
         "c.srli a5, 10",
         "c.xor a3, a5",
         "c.addw a3, s1",
@@ -594,40 +594,40 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
         "srli s1, s0, 18",
         "slli a2, s0, 14",
         "c.or a2, s1",
-        "c.lw s1, a4, 0",
+        "lw s1, 0(a4)",
         "c.xor a2, a5",
         "srli a5, s0, 3",
         "c.xor a2, a5",
         "c.addw a3, s1",
         "c.addw a2, a3",
-        "c.sw a2, a4, 64",
+        "sw a2, 64(a4)",
         "c.addi a1, 4",
         "bne a1, a7, -80",
-        "c.li s9, 0",
-        "lw t5, a0, 80",
-        "lw t4, a0, 84",
-        "lw t3, a0, 88",
-        "lw t2, a0, 92",
-        "lw t1, a0, 96",
-        "lw t0, a0, 100",
-        "lw a7, a0, 104",
-        "lw a6, a0, 108",
+        "li s9, 0",
+        "lw t5, 80(a0)",
+        "lw t4, 84(a0)",
+        "lw t3, 88(a0)",
+        "lw t2, 92(a0)",
+        "lw t1, 96(a0)",
+        "lw t0, 100(a0)",
+        "lw a7, 104(a0)",
+        "lw a6, 108(a0)",
         "li t6, 256",
-        "c.lui a2, 16",
+        "lui a2, 16",
         "addi s3, a2, 344",
-        "c.mv s2, sp",
-        "c.mv s7, t3",
-        "c.mv s4, t2",
-        "c.mv s6, t1",
-        "c.mv a3, t0",
-        "c.mv s10, a7",
-        "c.mv s5, a6",
-        "c.mv s8, t4",
-        "c.mv s0, t5",
-        "c.mv s11, s10",
-        "c.mv s10, a3",
-        "c.mv a3, s6",
-        "c.mv s1, s7",
+        "mv s2, sp",
+        "mv s7, t3",
+        "mv s4, t2",
+        "mv s6, t1",
+        "mv a3, t0",
+        "mv s10, a7",
+        "mv s5, a6",
+        "mv s8, t4",
+        "mv s0, t5",
+        "mv s11, s10",
+        "mv s10, a3",
+        "mv a3, s6",
+        "mv s1, s7",
         "beq s9, t6, 146",
         "srliw a4, a3, 6",
         "slliw a1, a3, 26",
@@ -644,11 +644,11 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
         "not a4, a3",
         "and a4, s11, a4",
         "add a5, s3, s9",
-        "c.lw a5, a5, 0",
+        "lw a5, 0(a5)",
         "add a1, s2, s9",
-        "c.lw a1, a1, 0",
+        "lw a1, 0(a1)",
         "addw a2, s6, a2",
-        "addw a2, s5",
+        "addw a2, a2, s5",
         "c.addw a2, a4",
         "c.addw a2, a5",
         "c.addw a1, a2",
@@ -669,221 +669,221 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
         "c.xor a4, a5",
         "c.addw a2, a4",
         "addw s6, a1, s4",
-        "c.mv s7, s8",
-        "c.mv s8, s0",
+        "mv s7, s8",
+        "mv s8, s0",
         "addw s0, a2, a1",
         "c.addi s9, 4",
-        "c.mv s4, s1",
-        "c.mv s5, s11",
-        "c.j -152",
+        "mv s4, s1",
+        "mv s5, s11",
+        "j -152",
         "addw a1, s0, t5",
-        "c.sw a1, a0, 80",
+        "sw a1, 80(a0)",
         "addw a1, s8, t4",
-        "c.sw a1, a0, 84",
+        "sw a1, 84(a0)",
         "addw a1, s1, t3",
-        "c.sw a1, a0, 88",
+        "sw a1, 88(a0)",
         "addw a1, s4, t2",
-        "c.sw a1, a0, 92",
+        "sw a1, 92(a0)",
         "addw a1, a3, t1",
-        "c.sw a1, a0, 96",
+        "sw a1, 96(a0)",
         "addw a1, s10, t0",
-        "c.sw a1, a0, 100",
+        "sw a1, 100(a0)",
         "addw a1, s11, a7",
-        "c.sw a1, a0, 104",
+        "sw a1, 104(a0)",
         "addw a1, s5, a6",
-        "c.sw a1, a0, 108",
-        "c.ldsp s0, 344",
-        "c.ldsp s1, 336",
-        "c.ldsp s2, 328",
-        "c.ldsp s3, 320",
-        "c.ldsp s4, 312",
-        "c.ldsp s5, 304",
-        "c.ldsp s6, 296",
-        "c.ldsp s7, 288",
-        "c.ldsp s8, 280",
-        "c.ldsp s9, 272",
-        "c.ldsp s10, 264",
-        "c.ldsp s11, 256",
+        "sw a1, 108(a0)",
+        "ld s0, 344(sp)",
+        "ld s1, 336(sp)",
+        "ld s2, 328(sp)",
+        "ld s3, 320(sp)",
+        "ld s4, 312(sp)",
+        "ld s5, 304(sp)",
+        "ld s6, 296(sp)",
+        "ld s7, 288(sp)",
+        "ld s8, 280(sp)",
+        "ld s9, 272(sp)",
+        "ld s10, 264(sp)",
+        "ld s11, 256(sp)",
         "c.addi16sp 352",
         "ret",
-        "c.lui a1, 18",
-        "ld a1, a1, 1632",
-        "c.lui a2, 18",
-        "ld a2, a2, 1640",
-        "c.sd a1, a0, 80",
-        "c.lui a1, 18",
-        "ld a1, a1, 1648",
-        "c.sd a2, a0, 88",
-        "c.lui a2, 18",
-        "ld a2, a2, 1656",
-        "c.sd a1, a0, 96",
-        "c.li a1, 0",
-        "c.sw a1, a0, 64",
-        "c.sd a1, a0, 72",
-        "c.sd a2, a0, 104",
+        "lui a1, 18",
+        "ld a1, 1632(a1)",
+        "lui a2, 18",
+        "ld a2, 1640(a2)",
+        "sd a1, 80(a0)",
+        "lui a1, 18",
+        "ld a1, 1648(a1)",
+        "sd a2, 88(a0)",
+        "lui a2, 18",
+        "ld a2, 1656(a2)",
+        "sd a1, 96(a0)",
+        "li a1, 0",
+        "sw a1, 64(a0)",
+        "sd a1, 72(a0)",
+        "sd a2, 104(a0)",
         "ret",
         "c.addi16sp -48",
-        "c.sdsp ra, 40",
-        "c.sdsp s0, 32",
-        "c.sdsp s1, 24",
-        "c.sdsp s2, 16",
-        "c.sdsp s3, 8",
-        "c.sdsp s4, 0",
-        "c.mv s3, a2",
-        "c.mv s2, a1",
-        "c.mv s1, a0",
-        "c.li s0, 0",
+        "sd ra, 40(sp)",
+        "sd s0, 32(sp)",
+        "sd s1, 24(sp)",
+        "sd s2, 16(sp)",
+        "sd s3, 8(sp)",
+        "sd s4, 0(sp)",
+        "mv s3, a2",
+        "mv s2, a1",
+        "mv s1, a0",
+        "li s0, 0",
         "li s4, 64",
         "slli a0, s0, 32",
         "c.srli a0, 32",
         "bgeu a0, s3, 54",
         "c.add a0, s2",
-        "lwu a1, s1, 64",
-        "lb a0, a0, 0",
+        "lwu a1, 64(s1)",
+        "lb a0, 0(a0)",
         "c.add a1, s1",
-        "sb a0, a1, 0",
-        "c.lw a0, s1, 64",
+        "sb a0, 0(a1)",
+        "lw a0, 64(s1)",
         "c.addiw a0, 1",
-        "c.sw a0, s1, 64",
+        "sw a0, 64(s1)",
         "bne a0, s4, 24",
-        "c.mv a0, s1",
-        "c.mv a1, s1",
+        "mv a0, s1",
+        "mv a1, s1",
         "jal -576",
-        "c.ld a0, s1, 72",
-        "addi a0, 512",
-        "c.sd a0, s1, 72",
-        "sw zero, s1, 64",
+        "ld a0, 72(s1)",
+        "addi a0, a0, 512",
+        "sd a0, 72(s1)",
+        "sw zero, 64(s1)",
         "c.addiw s0, 1",
-        "c.j -58",
-        "c.ldsp ra, 40",
-        "c.ldsp s0, 32",
-        "c.ldsp s1, 24",
-        "c.ldsp s2, 16",
-        "c.ldsp s3, 8",
-        "c.ldsp s4, 0",
+        "j -58",
+        "ld ra, 40(sp)",
+        "ld s0, 32(sp)",
+        "ld s1, 24(sp)",
+        "ld s2, 16(sp)",
+        "ld s3, 8(sp)",
+        "ld s4, 0(sp)",
         "c.addi16sp 48",
         "ret",
         "c.addi sp, -32",
-        "c.sdsp ra, 24",
-        "c.sdsp s0, 16",
-        "c.sdsp s1, 8",
-        "c.mv s0, a0",
-        "lwu a0, a0, 64",
-        "c.mv s1, a1",
+        "sd ra, 24(sp)",
+        "sd s0, 16(sp)",
+        "sd s1, 8(sp)",
+        "mv s0, a0",
+        "lwu a0, 64(a0)",
+        "mv s1, a1",
         "sext.w a1, a0",
         "add a2, s0, a0",
         "li a3, 128",
         "li a4, 56",
-        "sb a3, a2, 0",
+        "sb a3, 0(a2)",
         "bgeu a1, a4, 32",
         "addi a1, s0, 1",
         "li a2, 55",
         "beq a0, a2, 60",
         "add a3, a1, a0",
         "c.addi a0, 1",
-        "sb zero, a3, 0",
+        "sb zero, 0(a3)",
         "bne a0, a2, -10",
-        "c.j 42",
+        "j 42",
         "li a1, 63",
         "c.addiw a0, 1",
         "bltu a1, a0, 14",
         "add a2, s0, a0",
-        "sb zero, a2, 0",
-        "c.j -14",
-        "c.mv a0, s0",
-        "c.mv a1, s0",
+        "sb zero, 0(a2)",
+        "j -14",
+        "mv a0, s0",
+        "mv a1, s0",
         "jal -704",
         "li a2, 56",
-        "c.mv a0, s0",
-        "c.li a1, 0",
+        "mv a0, s0",
+        "li a1, 0",
         "jal -738",
-        "c.lw a0, s0, 64",
-        "c.ld a1, s0, 72",
+        "lw a0, 64(s0)",
+        "ld a1, 72(s0)",
         "c.slli a0, 35",
         "c.srli a0, 32",
         "c.add a0, a1",
-        "c.sd a0, s0, 72",
-        "sb a0, s0, 63",
+        "sd a0, 72(s0)",
+        "sb a0, 63(s0)",
         "srli a1, a0, 8",
-        "sb a1, s0, 62",
+        "sb a1, 62(s0)",
         "srli a1, a0, 16",
-        "sb a1, s0, 61",
+        "sb a1, 61(s0)",
         "srli a1, a0, 24",
-        "sb a1, s0, 60",
+        "sb a1, 60(s0)",
         "srli a1, a0, 32",
-        "sb a1, s0, 59",
+        "sb a1, 59(s0)",
         "srli a1, a0, 40",
-        "sb a1, s0, 58",
+        "sb a1, 58(s0)",
         "srli a1, a0, 48",
-        "sb a1, s0, 57",
+        "sb a1, 57(s0)",
         "c.srli a0, 56",
-        "sb a0, s0, 56",
-        "c.mv a0, s0",
-        "c.mv a1, s0",
+        "sb a0, 56(s0)",
+        "mv a0, s0",
+        "mv a1, s0",
         "jal -794",
-        "c.li a0, 0",
+        "li a0, 0",
         "addi a1, s1, 16",
-        "c.li a2, 4",
-        "c.li a3, 24",
+        "li a2, 4",
+        "li a3, 24",
         "beq a0, a2, 102",
-        "c.lw a4, s0, 80",
+        "lw a4, 80(s0)",
         "slliw a5, a0, 3",
         "subw a5, a3, a5",
         "srlw a4, a4, a5",
         "add s1, a1, a0",
-        "sb a4, s1, -16",
-        "c.lw a4, s0, 84",
+        "sb a4, -16(s1)",
+        "lw a4, 84(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, -12",
-        "c.lw a4, s0, 88",
+        "sb a4, -12(s1)",
+        "lw a4, 88(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, -8",
-        "c.lw a4, s0, 92",
+        "sb a4, -8(s1)",
+        "lw a4, 92(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, -4",
-        "c.lw a4, s0, 96",
+        "sb a4, -4(s1)",
+        "lw a4, 96(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, 0",
-        "c.lw a4, s0, 100",
+        "sb a4, 0(s1)",
+        "lw a4, 100(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, 4",
-        "c.lw a4, s0, 104",
+        "sb a4, 4(s1)",
+        "lw a4, 104(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, 8",
-        "c.lw a4, s0, 108",
+        "sb a4, 8(s1)",
+        "lw a4, 108(s0)",
         "srlw a4, a4, a5",
-        "sb a4, s1, 12",
+        "sb a4, 12(s1)",
         "c.addi a0, 1",
         "bne a0, a2, -94",
-        "c.ldsp ra, 24",
-        "c.ldsp s0, 16",
-        "c.ldsp s1, 8",
+        "ld ra, 24(sp)",
+        "ld s0, 16(sp)",
+        "ld s1, 8(sp)",
         "c.addi16sp 32",
         "ret",
         "c.addi16sp -128",
-        "c.sdsp ra, 120",
-        "c.swsp zero, 72",
-        "c.sdsp zero, 80",
-        "c.lui a0, 18",
-        "ld a0, a0, 1664",
-        "c.lui a1, 18",
-        "ld a1, a1, 1672",
-        "c.lui a2, 18",
-        "ld a2, a2, 1680",
-        "c.lui a3, 18",
-        "ld a3, a3, 1688",
-        "c.sdsp a0, 88",
-        "c.sdsp a1, 96",
-        "c.sdsp a2, 104",
-        "c.sdsp a3, 112",
-        "c.addi4spn a0, 8",
-        "c.lui a1, 1",
+        "sd ra, 120(sp)",
+        "sw zero, 72(sp)",
+        "sd zero, 80(sp)",
+        "lui a0, 18",
+        "ld a0, 1664(a0)",
+        "lui a1, 18",
+        "ld a1, 1672(a1)",
+        "lui a2, 18",
+        "ld a2, 1680(a2)",
+        "lui a3, 18",
+        "ld a3, 1688(a3)",
+        "sd a0, 88(sp)",
+        "sd a1, 96(sp)",
+        "sd a2, 104(sp)",
+        "sd a3, 112(sp)",
+        "addi a0, sp, 8",
+        "lui a1, 1",
         "li a2, 1024",
         "jal -458",
-        "c.addi4spn a0, 8",
-        "c.lui a1, 2",
+        "addi a0, sp, 8",
+        "lui a1, 2",
         "jal -364",
-        "c.ldsp ra, 120",
+        "ld ra, 120(sp)",
         "c.addi16sp 128",
         "ret",
     ];
@@ -897,3 +897,152 @@ fn sha256() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// Decodes each of `bytes` in turn from `addr` on, resolving their addresses as the processor
+/// does.
+fn decode_bytes(addr: usize, bytes: &[u8]) -> Vec<String> {
+    let mut reader = decoder::Reader::new(bytes);
+    let symbols = debugvault::Index::default();
+    let decoder = crate::Decoder { is_64: true };
+    let mut prev: Option<crate::Instruction> = None;
+    let mut decoded = Vec::new();
+    let mut addr = addr;
+
+    loop {
+        match decoder.decode(&mut reader) {
+            Ok(mut inst) => {
+                let mut line = tokenizing::TokenStream::new();
+                inst.update_rel_addrs(addr, prev.as_ref());
+                inst.tokenize(&mut line, &symbols);
+                decoded.push(line.to_string());
+                addr += inst.width();
+                prev = Some(inst);
+            }
+            Err(err) => {
+                if err.kind == decoder::ErrorKind::ExhaustedInput {
+                    break;
+                }
+
+                decoded.push(format!("{err:?}"));
+                addr += err.size();
+                prev = None;
+            }
+        }
+    }
+
+    decoded
+}
+
+fn test_display(bytes: &[u8], str: &str) {
+    assert_eq!(decode_bytes(0x1000, bytes), [str]);
+}
+
+#[test]
+fn csr() {
+    test_display(&[0x73, 0x25, 0x40, 0xf1], "csrr a0, mhartid");
+    test_display(&[0x73, 0x10, 0x55, 0x30], "csrw mtvec, a0");
+    test_display(&[0x73, 0x95, 0x05, 0x34], "csrrw a0, mscratch, a1");
+    test_display(&[0x73, 0x60, 0x04, 0x30], "csrsi mstatus, 8");
+    test_display(&[0x73, 0x25, 0x00, 0xc0], "rdcycle a0");
+    test_display(&[0x73, 0x95, 0x25, 0x00], "fsrm a0, a1");
+    assert_eq!(crate::csr_name(0x3b2).as_deref(), Some("pmpaddr2"));
+    assert_eq!(crate::csr_name(0x7ff), None);
+}
+
+#[test]
+fn mul_div() {
+    test_display(&[0x33, 0x05, 0xb5, 0x02], "mul a0, a0, a1");
+    test_display(&[0x3b, 0xd5, 0xc5, 0x02], "divuw a0, a1, a2");
+}
+
+#[test]
+fn atomic() {
+    test_display(&[0x2f, 0x25, 0xb6, 0x00], "amoadd.w a0, a1, 0(a2)");
+    test_display(&[0x2f, 0xa5, 0x05, 0x14], "lr.w.aq a0, 0(a1)");
+    test_display(&[0x2f, 0xb5, 0xc5, 0x1e], "sc.d.aqrl a0, a2, 0(a1)");
+}
+
+#[test]
+fn float() {
+    test_display(&[0x53, 0x75, 0xb5, 0x00], "fadd.s fa0, fa0, fa1");
+    test_display(&[0x53, 0x95, 0xc5, 0x02], "fadd.d fa0, fa1, fa2, rtz");
+    test_display(&[0x53, 0x15, 0x05, 0xc0], "fcvt.w.s a0, fa0, rtz");
+    test_display(&[0x53, 0x85, 0x05, 0xe0], "fmv.x.w a0, fa1");
+    test_display(&[0x53, 0x95, 0xb5, 0x22], "fneg.d fa0, fa1");
+    test_display(&[0x07, 0x35, 0x81, 0x00], "fld fa0, 8(sp)");
+    test_display(&[0x27, 0x2e, 0xa4, 0xfe], "fsw fa0, -4(s0)");
+    test_display(&[0x43, 0xf5, 0xc5, 0x68], "fmadd.s fa0, fa1, fa2, fa3");
+}
+
+#[test]
+fn privileged() {
+    test_display(&[0x73, 0x00, 0x20, 0x30], "mret");
+    test_display(&[0x73, 0x00, 0x50, 0x10], "wfi");
+    test_display(&[0x73, 0x00, 0x05, 0x12], "sfence.vma a0, zero");
+    test_display(&[0x0f, 0x10, 0x00, 0x00], "fence.i");
+}
+
+#[test]
+fn psuedo() {
+    test_display(&[0x67, 0x80, 0x00, 0x00], "ret");
+    test_display(&[0x67, 0x00, 0x05, 0x00], "jr a0");
+    test_display(&[0xe7, 0x00, 0x05, 0x00], "jalr a0");
+    test_display(&[0xe7, 0x02, 0x85, 0x00], "jalr t0, 8(a0)");
+    test_display(&[0x13, 0x05, 0xf0, 0xff], "li a0, -1");
+    test_display(&[0x13, 0x85, 0x05, 0x00], "mv a0, a1");
+    test_display(&[0x13, 0xc5, 0xf5, 0xff], "not a0, a1");
+    test_display(&[0x33, 0x05, 0xb0, 0x40], "neg a0, a1");
+    test_display(&[0x3b, 0x05, 0xb0, 0x40], "negw a0, a1");
+    test_display(&[0x1b, 0x85, 0x05, 0x00], "sext.w a0, a1");
+    test_display(&[0x13, 0xb5, 0x15, 0x00], "seqz a0, a1");
+    test_display(&[0x33, 0x35, 0xb0, 0x00], "snez a0, a1");
+    test_display(&[0x13, 0x55, 0x35, 0x40], "srai a0, a0, 3");
+}
+
+#[test]
+fn compressed() {
+    test_display(&[0x01, 0x00], "nop");
+    test_display(&[0xb1, 0x45], "li a1, 12");
+    test_display(&[0x0c, 0xc1], "sw a1, 0(a0)");
+    test_display(&[0x82, 0x80], "ret");
+    test_display(&[0x2e, 0x85], "mv a0, a1");
+    test_display(&[0x2e, 0x95], "c.add a0, a1");
+    test_display(&[0x01, 0x11], "c.addi sp, -32");
+    test_display(&[0x79, 0x71], "c.addi16sp -48");
+    test_display(&[0x45, 0x8d], "c.or a0, s1");
+    test_display(&[0xe2, 0x60], "ld ra, 24(sp)");
+    test_display(&[0x02, 0x95], "jalr a0");
+    test_display(&[0x02, 0x90], "ebreak");
+    test_display(&[0x0a, 0x05], "c.slli a0, 2");
+    test_display(&[0x7d, 0x99], "andi a0, a0, -1");
+    test_display(&[0x08, 0x25], "fld fa0, 8(a0)");
+    test_display(&[0x01, 0xc5], "beqz a0, 0x1008");
+    test_display(&[0x00, 0x00], "Error { kind: InvalidOpcode, size: 2 }");
+}
+
+#[test]
+fn auipc_pairs() {
+    let decoded = decode_bytes(
+        0x1000,
+        &[
+            0x17, 0x25, 0x00, 0x00, // auipc a0, 2
+            0x13, 0x05, 0x05, 0xff, // addi a0, a0, -16
+            0x17, 0x25, 0x00, 0x00, // auipc a0, 2
+            0x83, 0x35, 0x85, 0x00, // ld a1, 8(a0)
+            0x97, 0x00, 0x00, 0x00, // auipc ra, 0
+            0xe7, 0x80, 0x00, 0x01, // jalr ra, 16(ra)
+        ],
+    );
+
+    assert_eq!(
+        decoded,
+        [
+            "auipc a0, 2",
+            "lla a0, 0x2ff0",
+            "auipc a0, 2",
+            "ld a1, 0x3010",
+            "auipc ra, 0",
+            "call 0x1020",
+        ]
+    );
+}
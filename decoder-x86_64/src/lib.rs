@@ -9,11 +9,11 @@
 
 pub mod long_mode;
 pub mod protected_mode;
+mod reference;
 mod safer_unchecked;
 
 use debugvault::Index;
-use tokenizing::TokenStream;
-use config::CONFIG;
+use tokenizing::{Kind, TokenStream};
 
 const MEM_SIZE_STRINGS: [&str; 64] = [
     "byte ", "word ", "BUG ", "dword ", "ptr ", "far ", "BUG ", "qword ", "BUG ", "mword ", "BUG ",
@@ -29,14 +29,14 @@ struct Number(i32);
 impl decoder::ToTokens for Number {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
         if self.0 == i32::MIN {
-            stream.push(" - ", CONFIG.colors.asm.expr);
-            stream.push("0x7fffffff", CONFIG.colors.asm.immediate);
+            stream.push(" - ", Kind::Expr);
+            stream.push("0x7fffffff", Kind::Immediate);
         } else if self.0 < 0 {
-            stream.push(" - ", CONFIG.colors.asm.expr);
-            stream.push_owned(decoder::encode_hex(-self.0 as i64), CONFIG.colors.asm.immediate);
+            stream.push(" - ", Kind::Expr);
+            stream.push_owned(decoder::encode_hex(-self.0 as i64), Kind::Immediate);
         } else {
-            stream.push(" + ", CONFIG.colors.asm.expr);
-            stream.push_owned(decoder::encode_hex(self.0 as i64), CONFIG.colors.asm.immediate);
+            stream.push(" + ", Kind::Expr);
+            stream.push_owned(decoder::encode_hex(self.0 as i64), Kind::Immediate);
         }
     }
 }
@@ -0,0 +1,246 @@
+//! Tokenizing of instructions in AT&T syntax, as used by the GNU assembler.
+//!
+//! Operands are in reverse order of the Intel syntax, registers are prefixed with `%` and
+//! immediates with `$`. Memory is shown as `disp(base, index, scale)`, it's size being a suffix
+//! of the mnemonic if no register operand tells it.
+
+use crate::long_mode::display::regspec_label;
+use crate::long_mode::{Instruction, MergeMode, Opcode, Operand, RegSpec};
+
+use debugvault::Index;
+use decoder::ToTokens;
+use tokenizing::{Kind, TokenStream};
+
+/// Base, index along with it's scale, and displacement of a memory operand.
+struct Memory {
+    base: Option<RegSpec>,
+    index: Option<(RegSpec, u8)>,
+    disp: i64,
+    mask: Option<RegSpec>,
+}
+
+fn register(stream: &mut TokenStream, spec: &RegSpec) {
+    stream.push_owned(format!("%{}", regspec_label(spec)), Kind::Register);
+}
+
+fn braced(stream: &mut TokenStream, text: &'static str) {
+    stream.push("{", Kind::Bracket);
+    stream.push(text, Kind::Register);
+    stream.push("}", Kind::Bracket);
+}
+
+fn mask(stream: &mut TokenStream, spec: &RegSpec) {
+    stream.push("{", Kind::Bracket);
+    register(stream, spec);
+    stream.push("}", Kind::Bracket);
+}
+
+impl Operand {
+    fn immediate(&self) -> Option<i64> {
+        match *self {
+            Operand::ImmediateI8(imm) => Some(imm as i64),
+            Operand::ImmediateU8(imm) => Some(imm as i64),
+            Operand::ImmediateI16(imm) => Some(imm as i64),
+            Operand::ImmediateU16(imm) => Some(imm as i64),
+            Operand::ImmediateI32(imm) => Some(imm as i64),
+            Operand::ImmediateU32(imm) => Some(imm as i64),
+            Operand::ImmediateI64(imm) => Some(imm),
+            Operand::ImmediateU64(imm) => Some(imm as i64),
+            _ => None,
+        }
+    }
+
+    fn memory(&self) -> Option<Memory> {
+        let (base, index, disp, mask) = match *self {
+            Operand::DisplacementU32(disp) => (None, None, disp as i64, None),
+            Operand::DisplacementU64(disp) => (None, None, disp as i64, None),
+            Operand::RegDeref(base) => (Some(base), None, 0, None),
+            Operand::RegDisp(base, disp) => (Some(base), None, disp as i64, None),
+            Operand::RegScale(index, scale) => (None, Some((index, scale)), 0, None),
+            Operand::RegScaleDisp(index, scale, disp) => {
+                (None, Some((index, scale)), disp as i64, None)
+            }
+            Operand::RegIndexBase(base, index) => (Some(base), Some((index, 1)), 0, None),
+            Operand::RegIndexBaseDisp(base, index, disp) => {
+                (Some(base), Some((index, 1)), disp as i64, None)
+            }
+            Operand::RegIndexBaseScale(base, index, scale) => {
+                (Some(base), Some((index, scale)), 0, None)
+            }
+            Operand::RegIndexBaseScaleDisp(base, index, scale, disp) => {
+                (Some(base), Some((index, scale)), disp as i64, None)
+            }
+            Operand::RegDerefMasked(base, mask) => (Some(base), None, 0, Some(mask)),
+            Operand::RegDispMasked(base, disp, mask) => {
+                (Some(base), None, disp as i64, Some(mask))
+            }
+            Operand::RegScaleMasked(index, scale, mask) => {
+                (None, Some((index, scale)), 0, Some(mask))
+            }
+            Operand::RegScaleDispMasked(index, scale, disp, mask) => {
+                (None, Some((index, scale)), disp as i64, Some(mask))
+            }
+            Operand::RegIndexBaseMasked(base, index, mask) => {
+                (Some(base), Some((index, 1)), 0, Some(mask))
+            }
+            Operand::RegIndexBaseDispMasked(base, index, disp, mask) => {
+                (Some(base), Some((index, 1)), disp as i64, Some(mask))
+            }
+            Operand::RegIndexBaseScaleMasked(base, index, scale, mask) => {
+                (Some(base), Some((index, scale)), 0, Some(mask))
+            }
+            Operand::RegIndexBaseScaleDispMasked(base, index, scale, disp, mask) => {
+                (Some(base), Some((index, scale)), disp as i64, Some(mask))
+            }
+            _ => return None,
+        };
+
+        Some(Memory {
+            base,
+            index,
+            disp,
+            mask,
+        })
+    }
+
+    fn is_register(&self) -> bool {
+        matches!(
+            self,
+            Operand::Register(_)
+                | Operand::RegisterMaskMerge(..)
+                | Operand::RegisterMaskMergeSae(..)
+                | Operand::RegisterMaskMergeSaeNoround(..)
+        )
+    }
+
+    /// Tokenize the operand in AT&T syntax, `imm_override` being the address it refers to.
+    pub(crate) fn tokenize_att(
+        &self,
+        stream: &mut TokenStream,
+        symbols: &Index,
+        imm_override: Option<usize>,
+    ) {
+        let symbolic = self.immediate().is_some() || self.is_memory();
+        if let Some(addr) = imm_override.filter(|_| symbolic) {
+            if let Some((symbol, offset)) = decoder::symbol_at(stream, symbols, addr, false) {
+                stream.push("<", Kind::Label);
+                stream.push_symbol(symbol.name(), addr);
+                stream.push_offset(offset, addr);
+                stream.push(">", Kind::Label);
+                return;
+            }
+
+            // branch targets are addresses rather than immediates
+            if self.immediate().is_some() {
+                let text = decoder::encode_hex(addr as i64);
+                stream.push_addr(text, Kind::Immediate, addr);
+                return;
+            }
+        }
+
+        if let Some(imm) = self.immediate() {
+            stream.push("$", Kind::Expr);
+            stream.push_owned(decoder::encode_hex(imm), Kind::Immediate);
+            return;
+        }
+
+        if let Some(memory) = self.memory() {
+            let (base, index) = (memory.base, memory.index);
+            if memory.disp != 0 || (base.is_none() && index.is_none()) {
+                stream.push_owned(decoder::encode_hex(memory.disp), Kind::Immediate);
+            }
+
+            if base.is_some() || index.is_some() {
+                stream.push("(", Kind::Bracket);
+                if let Some(base) = base {
+                    register(stream, &base);
+                }
+                if let Some((index, scale)) = index {
+                    stream.push(",", Kind::Expr);
+                    register(stream, &index);
+                    stream.push(",", Kind::Expr);
+                    stream.push_owned(scale.to_string(), Kind::Immediate);
+                }
+                stream.push(")", Kind::Bracket);
+            }
+
+            if let Some(ref spec) = memory.mask {
+                mask(stream, spec);
+            }
+            return;
+        }
+
+        match *self {
+            Operand::Register(ref spec) => register(stream, spec),
+            Operand::RegisterMaskMerge(ref spec, ref mask_reg, merge_mode)
+            | Operand::RegisterMaskMergeSae(ref spec, ref mask_reg, merge_mode, _)
+            | Operand::RegisterMaskMergeSaeNoround(ref spec, ref mask_reg, merge_mode) => {
+                register(stream, spec);
+
+                if mask_reg.num != 0 {
+                    mask(stream, mask_reg);
+                }
+                if let MergeMode::Zero = merge_mode {
+                    braced(stream, "z");
+                }
+
+                match *self {
+                    Operand::RegisterMaskMergeSae(.., sae_mode) => {
+                        sae_mode.tokenize(stream, symbols)
+                    }
+                    Operand::RegisterMaskMergeSaeNoround(..) => braced(stream, "sae"),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Instruction {
+    /// Suffix of the mnemonic telling the size of the memory accessed, if no register operand
+    /// tells it already.
+    pub(crate) fn att_suffix(&self) -> &'static str {
+        let mut memory = false;
+        for idx in 0..self.operand_count {
+            let op = Operand::from_spec(self, self.operands[idx as usize]);
+            if op.is_register() {
+                return "";
+            }
+            memory |= op.is_memory();
+        }
+
+        match self.mem_size {
+            1 if memory => "b",
+            2 if memory => "w",
+            4 if memory => "l",
+            8 if memory => "q",
+            _ => "",
+        }
+    }
+
+    /// Tokenize operand `idx` in AT&T syntax along with it's segment.
+    pub(crate) fn tokenize_att_operand(
+        &self,
+        idx: u8,
+        op: &Operand,
+        stream: &mut TokenStream,
+        symbols: &Index,
+        imm_override: Option<usize>,
+    ) {
+        let indirect = matches!(
+            self.opcode,
+            Opcode::CALL | Opcode::CALLF | Opcode::JMP | Opcode::JMPF
+        );
+        if indirect && op.immediate().is_none() {
+            stream.push("*", Kind::Expr);
+        }
+
+        if let Some(prefix) = self.segment_override_for_op(idx) {
+            stream.push_owned(format!("%{prefix}"), Kind::Segment);
+            stream.push(":", Kind::Expr);
+        }
+
+        op.tokenize_att(stream, symbols, imm_override);
+    }
+}
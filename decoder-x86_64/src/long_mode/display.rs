@@ -8,8 +8,7 @@ use crate::{Number, MEM_SIZE_STRINGS};
 
 use decoder::ToTokens;
 use debugvault::Index;
-use tokenizing::{colors, TokenStream};
-use config::CONFIG;
+use tokenizing::{Kind, Syntax, TokenStream};
 
 impl fmt::Display for Decoder {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -266,412 +265,389 @@ impl Operand {
         addr: usize,
     ) -> bool {
         match *self {
-            Operand::ImmediateI8(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateI8(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateU8(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateU8(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateI16(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateI16(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateU16(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateU16(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateI32(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateI32(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateU32(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateU32(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateI64(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateI64(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
-            Operand::ImmediateU64(_) => match symbols.get_sym_by_addr(addr) {
-                Some(symbol) => {
-                    stream.push("<", CONFIG.colors.asm.label);
-                    for token in symbol.name() {
-                        stream.push_token(token.clone());
-                    }
-                    stream.push(">", CONFIG.colors.asm.label);
+            Operand::ImmediateU64(_) => match decoder::symbol_at(stream, symbols, addr, false) {
+                Some((symbol, offset)) => {
+                    stream.push("<", Kind::Label);
+                    stream.push_symbol(symbol.name(), addr);
+                    stream.push_offset(offset, addr);
+                    stream.push(">", Kind::Label);
                 }
                 None => {
                     let text = decoder::encode_hex(addr as i64);
-                    stream.push_owned(text, CONFIG.colors.asm.immediate);
+                    stream.push_addr(text, Kind::Immediate, addr);
                 }
             },
             Operand::DisplacementU32(_) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
                         let text = decoder::encode_hex(addr as i64);
-                        stream.push_owned(text, CONFIG.colors.asm.immediate);
+                        stream.push_addr(text, Kind::Immediate, addr);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::DisplacementU64(_) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
                         let text = decoder::encode_hex(addr as i64);
-                        stream.push_owned(text, CONFIG.colors.asm.immediate);
+                        stream.push_addr(text, Kind::Immediate, addr);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegDisp(ref spec, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(spec), Kind::Register);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegScale(ref spec, scale) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(spec), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Immediate);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegScaleDisp(ref spec, scale, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(format!("{scale}"), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(spec), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(format!("{scale}"), Kind::Immediate);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBase(ref base, ref index) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseDisp(ref base, ref index, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseScale(ref base, ref index, scale) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Immediate);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleDisp(ref base, ref index, scale, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Immediate);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegDispMasked(ref spec, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(spec), Kind::Register);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegScaleMasked(ref spec, scale, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(spec), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Register);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegScaleDispMasked(ref spec, scale, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
-                        stream.push(" ", colors::WHITE);
+                        stream.push(regspec_label(spec), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Register);
+                        stream.push(" ", Kind::Plain);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseMasked(ref base, ref index, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseDispMasked(ref base, ref index, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" ", colors::WHITE);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
+                        stream.push(" ", Kind::Plain);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleMasked(ref base, ref index, scale, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Immediate);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleDispMasked(
                 ref base,
@@ -680,29 +656,28 @@ impl Operand {
                 disp,
                 ref mask_reg,
             ) => {
-                stream.push("[", CONFIG.colors.brackets);
-                match symbols.get_sym_by_addr(addr) {
-                    Some(symbol) => {
-                        stream.push("<", CONFIG.colors.asm.label);
-                        for token in symbol.name() {
-                            stream.push_token(token.clone());
-                        }
-                        stream.push(">", CONFIG.colors.asm.label);
+                stream.push("[", Kind::Bracket);
+                match decoder::symbol_at(stream, symbols, addr, false) {
+                    Some((symbol, offset)) => {
+                        stream.push("<", Kind::Label);
+                        stream.push_symbol(symbol.name(), addr);
+                        stream.push_offset(offset, addr);
+                        stream.push(">", Kind::Label);
                     }
                     None => {
-                        stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                        stream.push(" + ", CONFIG.colors.asm.expr);
-                        stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                        stream.push(" * ", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                        stream.push(regspec_label(base), Kind::Register);
+                        stream.push(" + ", Kind::Expr);
+                        stream.push(regspec_label(index), Kind::Register);
+                        stream.push(" * ", Kind::Expr);
+                        stream.push_owned(scale.to_string(), Kind::Immediate);
                         Number(disp).tokenize(stream, symbols);
                     }
                 }
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             _ => return false,
         }
@@ -728,236 +703,236 @@ impl Operand {
         match *self {
             Operand::ImmediateU8(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateI8(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateU16(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateI16(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateU32(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateI32(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateU64(imm) => {
                 let text = decoder::encode_hex(imm as i64);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::ImmediateI64(imm) => {
                 let text = decoder::encode_hex(imm);
-                stream.push_owned(text, CONFIG.colors.asm.immediate);
+                stream.push_owned(text, Kind::Immediate);
             }
             Operand::Register(ref spec) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push(regspec_label(spec), Kind::Register);
             }
             Operand::RegisterMaskMerge(ref spec, ref mask, merge_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push(regspec_label(spec), Kind::Register);
 
                 if mask.num != 0 {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push(regspec_label(mask), Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
                 if let MergeMode::Zero = merge_mode {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push("z", Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
             }
             Operand::RegisterMaskMergeSae(ref spec, ref mask, merge_mode, sae_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push(regspec_label(spec), Kind::Register);
 
                 if mask.num != 0 {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push(regspec_label(mask), Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
                 if let MergeMode::Zero = merge_mode {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push("z", Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
 
                 sae_mode.tokenize(stream, symbols);
             }
             Operand::RegisterMaskMergeSaeNoround(ref spec, ref mask, merge_mode) => {
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push(regspec_label(spec), Kind::Register);
 
                 if mask.num != 0 {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push(regspec_label(mask), CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push(regspec_label(mask), Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
                 if let MergeMode::Zero = merge_mode {
-                    stream.push("{", CONFIG.colors.brackets);
-                    stream.push("z", CONFIG.colors.asm.register);
-                    stream.push("}", CONFIG.colors.brackets);
+                    stream.push("{", Kind::Bracket);
+                    stream.push("z", Kind::Register);
+                    stream.push("}", Kind::Bracket);
                 }
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push("sae", CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push("sae", Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::DisplacementU32(imm) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push_owned(decoder::encode_hex(imm as i64), Kind::Immediate);
+                stream.push("]", Kind::Bracket);
             }
             Operand::DisplacementU64(imm) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push_owned(decoder::encode_hex(imm as i64), CONFIG.colors.asm.immediate);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push_owned(decoder::encode_hex(imm as i64), Kind::Immediate);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegDisp(ref spec, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegDeref(ref spec) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegScale(ref spec, scale) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegScaleDisp(ref spec, scale, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(format!("{scale}"), CONFIG.colors.asm.immediate);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(format!("{scale}"), Kind::Immediate);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBase(ref base, ref index) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseDisp(ref base, ref index, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseScale(ref base, ref index, scale) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleDisp(ref base, ref index, scale, disp) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
             }
             Operand::RegDispMasked(ref spec, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegDerefMasked(ref spec, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegScaleMasked(ref spec, scale, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Register);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegScaleDispMasked(ref spec, scale, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(spec), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.register);
-                stream.push(" ", colors::WHITE);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(spec), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Register);
+                stream.push(" ", Kind::Plain);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseMasked(ref base, ref index, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseDispMasked(ref base, ref index, disp, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" ", colors::WHITE);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push(" ", Kind::Plain);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleMasked(ref base, ref index, scale, ref mask_reg) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::RegIndexBaseScaleDispMasked(
                 ref base,
@@ -966,18 +941,18 @@ impl Operand {
                 disp,
                 ref mask_reg,
             ) => {
-                stream.push("[", CONFIG.colors.brackets);
-                stream.push(regspec_label(base), CONFIG.colors.asm.register);
-                stream.push(" + ", CONFIG.colors.asm.expr);
-                stream.push(regspec_label(index), CONFIG.colors.asm.register);
-                stream.push(" * ", CONFIG.colors.asm.expr);
-                stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
+                stream.push("[", Kind::Bracket);
+                stream.push(regspec_label(base), Kind::Register);
+                stream.push(" + ", Kind::Expr);
+                stream.push(regspec_label(index), Kind::Register);
+                stream.push(" * ", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
                 Number(disp).tokenize(stream, symbols);
-                stream.push("]", CONFIG.colors.brackets);
+                stream.push("]", Kind::Bracket);
 
-                stream.push("{", CONFIG.colors.brackets);
-                stream.push(regspec_label(mask_reg), CONFIG.colors.asm.register);
-                stream.push("}", CONFIG.colors.brackets);
+                stream.push("{", Kind::Bracket);
+                stream.push(regspec_label(mask_reg), Kind::Register);
+                stream.push("}", Kind::Bracket);
             }
             Operand::Nothing => {}
         }
@@ -2430,8 +2405,115 @@ impl Opcode {
     }
 }
 
-impl ToTokens for Instruction {
-    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+impl Operand {
+    /// What kind of operand this is, e.g. `qword register`.
+    fn kind(&self) -> String {
+        match self {
+            Operand::ImmediateI8(_) => "sign-extended byte immediate".to_string(),
+            Operand::ImmediateU8(_) => "byte immediate".to_string(),
+            Operand::ImmediateI16(_) => "sign-extended word immediate".to_string(),
+            Operand::ImmediateU16(_) => "word immediate".to_string(),
+            Operand::ImmediateI32(_) => "sign-extended dword immediate".to_string(),
+            Operand::ImmediateU32(_) => "dword immediate".to_string(),
+            Operand::ImmediateI64(_) => "sign-extended qword immediate".to_string(),
+            Operand::ImmediateU64(_) => "qword immediate".to_string(),
+            Operand::Register(reg) => format!("{} register", reg.class().name()),
+            Operand::RegisterMaskMerge(reg, mask, _)
+            | Operand::RegisterMaskMergeSae(reg, mask, _, _)
+            | Operand::RegisterMaskMergeSaeNoround(reg, mask, _) => {
+                format!("{} register masked by {mask}", reg.class().name())
+            }
+            Operand::DisplacementU32(_) | Operand::DisplacementU64(_) => {
+                "memory at an absolute address".to_string()
+            }
+            Operand::RegDisp(RegSpec::RIP, _) => "memory relative to rip".to_string(),
+            Operand::RegDeref(base) => format!("memory at {base}"),
+            Operand::RegDisp(base, _) => format!("memory at {base} plus a displacement"),
+            Operand::RegScale(index, scale) => format!("memory at {index} times {scale}"),
+            Operand::RegIndexBase(base, index) => format!("memory at {base} plus {index}"),
+            Operand::RegIndexBaseDisp(base, index, _) => {
+                format!("memory at {base} plus {index} plus a displacement")
+            }
+            Operand::RegScaleDisp(index, scale, _) => {
+                format!("memory at {index} times {scale} plus a displacement")
+            }
+            Operand::RegIndexBaseScale(base, index, scale) => {
+                format!("memory at {base} plus {index} times {scale}")
+            }
+            Operand::RegIndexBaseScaleDisp(base, index, scale, _) => {
+                format!("memory at {base} plus {index} times {scale} plus a displacement")
+            }
+            _ if self.is_memory() => "masked memory".to_string(),
+            _ => "operand".to_string(),
+        }
+    }
+}
+
+impl Instruction {
+    /// Address an immediate got resolved to, if it's shown as one.
+    fn resolved_imm(&self) -> Option<usize> {
+        // slightly hacky but for `int` instructions we tend to incorrectly try to
+        // do symbolic resolution on the immediate which isn't correct
+        if self.imm_override && self.opcode != Opcode::INT && self.opcode != Opcode::INTO {
+            Some(self.imm as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Tokenize operand `idx` along with it's access size and segment in the syntax of `stream`,
+    /// returning the operand.
+    fn tokenize_operand(
+        &self,
+        idx: u8,
+        stream: &mut TokenStream,
+        symbols: &Index,
+        imm_override: Option<usize>,
+    ) -> Operand {
+        let op = Operand::from_spec(self, self.operands[idx as usize]);
+        if stream.format.syntax == Syntax::Att {
+            self.tokenize_att_operand(idx, &op, stream, symbols, imm_override);
+            return op;
+        }
+
+        if op.is_memory() {
+            stream.push(
+                MEM_SIZE_STRINGS[self.mem_size as usize - 1],
+                Kind::Annotation,
+            );
+        }
+
+        if let Some(prefix) = self.segment_override_for_op(idx) {
+            stream.push_owned(prefix.to_string(), Kind::Segment);
+            stream.push(":", Kind::Expr);
+        }
+
+        op.tokenize(stream, symbols, imm_override);
+        op
+    }
+
+    /// Each operand as shown in the listing, along with what kind of operand it is.
+    pub(crate) fn operand_descriptions(&self, symbols: &Index) -> Vec<(String, String)> {
+        let imm_override = self.resolved_imm();
+        let mut descriptions = Vec::new();
+
+        for idx in 0..self.operand_count {
+            if self.operands[idx as usize] == OperandSpec::Nothing {
+                continue;
+            }
+
+            let mut stream = TokenStream::new();
+            let op = self.tokenize_operand(idx, &mut stream, symbols, imm_override);
+            descriptions.push((stream.to_string(), op.kind()));
+        }
+
+        descriptions
+    }
+}
+
+impl Instruction {
+    /// Mnemonic along with the prefixes changing what it does.
+    fn prefixed_mnemonic(&self) -> String {
         let opcode_name = self.opcode.name();
         let mut op = String::with_capacity(opcode_name.len());
 
@@ -2462,122 +2544,108 @@ impl ToTokens for Instruction {
         }
 
         op.push_str(opcode_name);
-        stream.push_owned(op, CONFIG.colors.asm.opcode);
-
-        // slightly hacky but for `int` instructions we tend to incorrectly try to
-        // do symbolic resolution on the immediate which isn't correct
-        let imm_override =
-            if self.imm_override && self.opcode != Opcode::INT && self.opcode != Opcode::INTO {
-                Some(self.imm as usize)
-            } else {
-                None
-            };
-
-        if self.operand_count > 0 {
-            stream.push(" ", colors::WHITE);
-
-            let op = Operand::from_spec(self, self.operands[0]);
+        op
+    }
 
-            if op.is_memory() {
-                stream.push(
-                    MEM_SIZE_STRINGS[self.mem_size as usize - 1],
-                    CONFIG.colors.asm.annotation,
-                );
-            }
+    /// Tokenize the `{1toN}` following memory operand `op` at `idx` if it's broadcast.
+    fn tokenize_broadcast(&self, idx: u8, op: &Operand, stream: &mut TokenStream) {
+        if let Some(evex) = self.prefixes.evex() {
+            if evex.broadcast() && op.is_memory() {
+                let scale = if self.opcode == Opcode::VCVTPD2PS
+                    || self.opcode == Opcode::VCVTTPD2UDQ
+                    || self.opcode == Opcode::VCVTPD2UDQ
+                    || self.opcode == Opcode::VCVTUDQ2PD
+                    || self.opcode == Opcode::VCVTPS2PD
+                    || self.opcode == Opcode::VCVTQQ2PS
+                    || self.opcode == Opcode::VCVTDQ2PD
+                    || self.opcode == Opcode::VCVTTPD2DQ
+                    || self.opcode == Opcode::VFPCLASSPS
+                    || self.opcode == Opcode::VFPCLASSPD
+                    || self.opcode == Opcode::VCVTNEPS2BF16
+                    || self.opcode == Opcode::VCVTUQQ2PS
+                    || self.opcode == Opcode::VCVTPD2DQ
+                    || self.opcode == Opcode::VCVTTPS2UQQ
+                    || self.opcode == Opcode::VCVTPS2UQQ
+                    || self.opcode == Opcode::VCVTTPS2QQ
+                    || self.opcode == Opcode::VCVTPS2QQ
+                {
+                    if self.opcode == Opcode::VFPCLASSPS
+                        || self.opcode == Opcode::VCVTNEPS2BF16
+                    {
+                        if evex.vex().l() {
+                            8
+                        } else if evex.lp() {
+                            16
+                        } else {
+                            4
+                        }
+                    } else {
+                        // vcvtpd2ps is "cool": in broadcast mode, it can read a
+                        // double-precision float (qword), resize to single-precision,
+                        // then broadcast that to the whole destination register. this
+                        // means we need to show `xmm, qword [addr]{1to4}` if vector
+                        // size is 256. likewise, scale of 8 for the same truncation
+                        // reason if vector size is 512.
+                        // vcvtudq2pd is the same story.
+                        // vfpclassp{s,d} is a mystery to me.
+                        if evex.vex().l() {
+                            4
+                        } else if evex.lp() {
+                            8
+                        } else {
+                            2
+                        }
+                    }
+                } else {
+                    // this should never be `None` - that would imply two
+                    // memory operands for a broadcasted operation.
+                    if let Some(width) =
+                        Operand::from_spec(self, self.operands[idx as usize - 1]).width()
+                    {
+                        width / self.mem_size
+                    } else {
+                        0
+                    }
+                };
 
-            if let Some(prefix) = self.segment_override_for_op(0) {
-                stream.push_owned(prefix.to_string(), CONFIG.colors.asm.segment);
-                stream.push(":", CONFIG.colors.asm.expr);
+                stream.push("{", Kind::Bracket);
+                stream.push("1to", Kind::Expr);
+                stream.push_owned(scale.to_string(), Kind::Immediate);
+                stream.push("}", Kind::Bracket);
             }
+        }
+    }
+}
 
-            op.tokenize(stream, symbols, imm_override);
-
-            for idx in 1..self.operand_count {
-                if self.operands[idx as usize] == OperandSpec::Nothing {
-                    continue;
-                }
-
-                stream.push(", ", CONFIG.colors.asm.expr);
+impl ToTokens for Instruction {
+    fn tokenize(&self, stream: &mut TokenStream, symbols: &Index) {
+        let att = stream.format.syntax == Syntax::Att;
+        let mut op = self.prefixed_mnemonic();
+        if att {
+            op.push_str(self.att_suffix());
+        }
+        stream.push_owned(op, Kind::Mnemonic);
 
-                let op = Operand::from_spec(self, self.operands[idx as usize]);
-                if op.is_memory() {
-                    stream.push(
-                        MEM_SIZE_STRINGS[self.mem_size as usize - 1],
-                        CONFIG.colors.asm.annotation,
-                    );
-                }
-                if let Some(prefix) = self.segment_override_for_op(idx) {
-                    stream.push_owned(prefix.to_string(), CONFIG.colors.asm.segment);
-                    stream.push(":", CONFIG.colors.asm.expr);
-                }
+        let imm_override = self.resolved_imm();
 
-                op.tokenize(stream, symbols, imm_override);
+        // AT&T syntax has the operands in reverse
+        let mut first = true;
+        for n in 0..self.operand_count {
+            let idx = if att { self.operand_count - 1 - n } else { n };
+            if idx != 0 && self.operands[idx as usize] == OperandSpec::Nothing {
+                continue;
+            }
 
-                if let Some(evex) = self.prefixes.evex() {
-                    if evex.broadcast() && op.is_memory() {
-                        let scale = if self.opcode == Opcode::VCVTPD2PS
-                            || self.opcode == Opcode::VCVTTPD2UDQ
-                            || self.opcode == Opcode::VCVTPD2UDQ
-                            || self.opcode == Opcode::VCVTUDQ2PD
-                            || self.opcode == Opcode::VCVTPS2PD
-                            || self.opcode == Opcode::VCVTQQ2PS
-                            || self.opcode == Opcode::VCVTDQ2PD
-                            || self.opcode == Opcode::VCVTTPD2DQ
-                            || self.opcode == Opcode::VFPCLASSPS
-                            || self.opcode == Opcode::VFPCLASSPD
-                            || self.opcode == Opcode::VCVTNEPS2BF16
-                            || self.opcode == Opcode::VCVTUQQ2PS
-                            || self.opcode == Opcode::VCVTPD2DQ
-                            || self.opcode == Opcode::VCVTTPS2UQQ
-                            || self.opcode == Opcode::VCVTPS2UQQ
-                            || self.opcode == Opcode::VCVTTPS2QQ
-                            || self.opcode == Opcode::VCVTPS2QQ
-                        {
-                            if self.opcode == Opcode::VFPCLASSPS
-                                || self.opcode == Opcode::VCVTNEPS2BF16
-                            {
-                                if evex.vex().l() {
-                                    8
-                                } else if evex.lp() {
-                                    16
-                                } else {
-                                    4
-                                }
-                            } else {
-                                // vcvtpd2ps is "cool": in broadcast mode, it can read a
-                                // double-precision float (qword), resize to single-precision,
-                                // then broadcast that to the whole destination register. this
-                                // means we need to show `xmm, qword [addr]{1to4}` if vector
-                                // size is 256. likewise, scale of 8 for the same truncation
-                                // reason if vector size is 512.
-                                // vcvtudq2pd is the same story.
-                                // vfpclassp{s,d} is a mystery to me.
-                                if evex.vex().l() {
-                                    4
-                                } else if evex.lp() {
-                                    8
-                                } else {
-                                    2
-                                }
-                            }
-                        } else {
-                            // this should never be `None` - that would imply two
-                            // memory operands for a broadcasted operation.
-                            if let Some(width) =
-                                Operand::from_spec(self, self.operands[idx as usize - 1]).width()
-                            {
-                                width / self.mem_size
-                            } else {
-                                0
-                            }
-                        };
+            if first {
+                stream.push(" ", Kind::Plain);
+                first = false;
+            } else {
+                stream.push(", ", Kind::Expr);
+            }
 
-                        stream.push("{", CONFIG.colors.brackets);
-                        stream.push("1to", CONFIG.colors.asm.expr);
-                        stream.push_owned(scale.to_string(), CONFIG.colors.asm.immediate);
-                        stream.push("}", CONFIG.colors.brackets);
-                    }
-                }
+            let op = self.tokenize_operand(idx, stream, symbols, imm_override);
+            if idx != 0 {
+                self.tokenize_broadcast(idx, &op, stream);
             }
         }
     }
@@ -1,3 +1,4 @@
+mod att;
 mod display;
 mod evex;
 mod tests;
@@ -11,10 +12,10 @@ use std::hash::{Hash, Hasher};
 use crate::safer_unchecked::unreachable_kinda_unchecked as unreachable_unchecked;
 pub use crate::MemoryAccessSize;
 
-use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
+use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens, XrefKind};
+use decoder::{Description, Flow, JumpTable, TableEntry};
 use debugvault::Index;
-use tokenizing::TokenStream;
-use config::CONFIG;
+use tokenizing::{Kind, TokenStream};
 
 /// an `x86_64` register, including its number and type. if `fmt` is enabled, name too.
 ///
@@ -591,7 +592,7 @@ impl SaeMode {
 
 impl ToTokens for SaeMode {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
-        stream.push("{", CONFIG.colors.brackets);
+        stream.push("{", Kind::Bracket);
         stream.push(
             match self {
                 SaeMode::RoundNearest => "rne-sae",
@@ -599,9 +600,9 @@ impl ToTokens for SaeMode {
                 SaeMode::RoundUp => "ru-sae",
                 SaeMode::RoundZero => "rz-sae",
             },
-            CONFIG.colors.asm.register,
+            Kind::Register,
         );
-        stream.push("}", CONFIG.colors.brackets);
+        stream.push("}", Kind::Bracket);
     }
 }
 
@@ -2737,6 +2738,170 @@ impl Decoded for Instruction {
             self.imm_override = true;
         }
     }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        if !self.imm_override {
+            return None;
+        }
+
+        let operand = |idx: usize| Operand::from_spec(self, self.operands[idx]);
+        let count = self.operand_count as usize;
+        let has_memory = (0..count).any(|idx| operand(idx).is_memory());
+        let has_immediate = (0..count).any(|idx| {
+            matches!(
+                operand(idx),
+                Operand::ImmediateI8(_)
+                | Operand::ImmediateU8(_)
+                | Operand::ImmediateI16(_)
+                | Operand::ImmediateU16(_)
+                | Operand::ImmediateI32(_)
+                | Operand::ImmediateU32(_)
+                | Operand::ImmediateI64(_)
+                | Operand::ImmediateU64(_)
+            )
+        });
+
+        let kind = match self.opcode {
+            // both operands got resolved, we can't tell which one ended up in `imm`
+            _ if has_memory && has_immediate => return None,
+            _ if has_memory => XrefKind::Data,
+            Opcode::CALL => XrefKind::Call,
+            Opcode::JMP | Opcode::LOOP | Opcode::LOOPZ | Opcode::LOOPNZ | Opcode::JRCXZ => {
+                XrefKind::Jump
+            }
+            opcode if opcode.is_jcc() => XrefKind::Jump,
+            // immediates are resolved as if they're relative, which only holds for branches
+            _ => return None,
+        };
+
+        Some((self.imm as usize, kind))
+    }
+
+    fn jump_table(&self, prev: &[(usize, &Self)]) -> Option<JumpTable> {
+        if self.opcode != Opcode::JMP {
+            return None;
+        }
+
+        let (addr, entry) = match self.operand(0) {
+            // jmp qword [idx * 8 + table]
+            Operand::RegScaleDisp(_, 8, disp) => (disp as i64 as usize, TableEntry::Absolute(8)),
+            // lea base, [rip + table]
+            // movsxd offset, dword [base + idx * 4]
+            // add offset, base
+            // jmp offset
+            Operand::Register(target) => {
+                let (dst, src) = prev.iter().find_map(|(_, inst)| {
+                    match (inst.opcode, inst.operand(0), inst.operand(1)) {
+                        (Opcode::ADD, Operand::Register(dst), Operand::Register(src))
+                            if dst == target =>
+                        {
+                            Some((dst, src))
+                        }
+                        _ => None,
+                    }
+                })?;
+
+                let base = prev.iter().find_map(|(_, inst)| {
+                    match (inst.opcode, inst.operand(0), inst.operand(1)) {
+                        (
+                            Opcode::MOVSXD,
+                            Operand::Register(offset),
+                            Operand::RegIndexBaseScale(base, _, 4),
+                        ) if (offset == dst && base == src) || (offset == src && base == dst) => {
+                            Some(base)
+                        }
+                        _ => None,
+                    }
+                })?;
+
+                let table = prev.iter().find_map(|(_, inst)| {
+                    match (inst.opcode, inst.operand(0), inst.operand(1)) {
+                        (Opcode::LEA, Operand::Register(reg), Operand::RegDisp(RegSpec::RIP, _))
+                            if reg == base && inst.imm_override =>
+                        {
+                            Some(inst.imm as usize)
+                        }
+                        _ => None,
+                    }
+                })?;
+
+                (table, TableEntry::Relative32)
+            }
+            _ => return None,
+        };
+
+        // cmp idx, max
+        // ja default
+        let len = prev.windows(2).find_map(|pair| {
+            let ((_, branch), (cmp_addr, cmp)) = (pair[0], pair[1]);
+            if cmp.opcode != Opcode::CMP || cmp.operand_count != 2 {
+                return None;
+            }
+
+            let max = match cmp.operand(1) {
+                Operand::ImmediateI8(_)
+                | Operand::ImmediateU8(_)
+                | Operand::ImmediateI32(_)
+                | Operand::ImmediateU32(_) => cmp.encoded_imm(cmp_addr),
+                _ => return None,
+            };
+
+            match branch.opcode {
+                Opcode::JA => Some(max as usize + 1),
+                Opcode::JNB => Some(max as usize),
+                _ => None,
+            }
+        });
+
+        Some(JumpTable { addr, entry, len })
+    }
+
+    fn flow(&self) -> Flow {
+        match self.opcode {
+            Opcode::JMP | Opcode::JMPF => Flow::Jump,
+            Opcode::RETURN
+            | Opcode::RETF
+            | Opcode::IRET
+            | Opcode::IRETD
+            | Opcode::IRETQ
+            | Opcode::SYSRET
+            | Opcode::SYSEXIT
+            | Opcode::HLT
+            | Opcode::UD2 => Flow::Return,
+            Opcode::LOOP | Opcode::LOOPZ | Opcode::LOOPNZ | Opcode::JRCXZ => Flow::Branch,
+            opcode if opcode.is_jcc() => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+
+    fn describe(&self, symbols: &Index) -> Option<Description> {
+        let mnemonic = self.opcode.to_string();
+        let operands = self.operand_descriptions(symbols);
+        let mut description = Description::new(crate::reference::REFERENCE, &mnemonic, operands);
+
+        if self.prefixes.lock() {
+            description.notes.push("Locked, accessing memory atomically.".to_string());
+        }
+
+        let repeated = match self.opcode {
+            Opcode::MOVS | Opcode::LODS | Opcode::STOS | Opcode::INS | Opcode::OUTS => {
+                self.prefixes.rep_any().then_some("Repeated rcx times.")
+            }
+            Opcode::CMPS if self.prefixes.rep() => {
+                Some("Repeated while equal, at most rcx times.")
+            }
+            Opcode::CMPS if self.prefixes.repnz() => {
+                Some("Repeated while not equal, at most rcx times.")
+            }
+            _ => None,
+        };
+
+        if let Some(repeated) = repeated {
+            description.notes.push(repeated.to_string());
+        }
+
+        Some(description)
+    }
 }
 
 impl Decodable for Decoder {
@@ -4333,6 +4498,16 @@ impl Instruction {
         self.opcode
     }
 
+    /// Immediate as encoded of the instruction at `addr`, as [`Decoded::update_rel_addrs`]
+    /// resolves them as if they're relative.
+    pub fn encoded_imm(&self, addr: usize) -> u64 {
+        if self.imm_override {
+            self.imm.wrapping_sub(addr as u64 + self.length as u64)
+        } else {
+            self.imm
+        }
+    }
+
     /// get the `Operand` at the provided index.
     ///
     /// panics if the index is `>= 4`.
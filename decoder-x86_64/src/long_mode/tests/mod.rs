@@ -6394,6 +6394,63 @@ fn from_reports() {
     test_display(&[0xf3, 0x0f, 0x1e, 0x0f], "nop dword [rdi], ecx");
 }
 
+/// Decode `data` at `addr`, returning the last instruction's jump table.
+fn find_jump_table(data: &[u8], mut addr: usize) -> Option<decoder::JumpTable> {
+    let mut reader = Reader::new(data);
+    let mut insts = Vec::new();
+
+    while let Ok(mut inst) = Decoder::default().decode(&mut reader) {
+        inst.update_rel_addrs(addr, None);
+        let width = inst.width();
+        insts.push((addr, inst));
+        addr += width;
+    }
+
+    let ((_, jump), prev) = insts.split_last()?;
+    let prev: Vec<_> = prev.iter().rev().map(|(addr, inst)| (*addr, inst)).collect();
+    jump.jump_table(&prev)
+}
+
+#[test]
+fn jump_tables() {
+    use decoder::{JumpTable, TableEntry};
+
+    // cmp edi, 0x5
+    // ja 0x1025
+    // mov edi, edi
+    // lea rax, [rip + 0xff2]
+    // movsxd rdx, dword [rax + rdi * 4]
+    // add rdx, rax
+    // jmp rdx
+    let pic = [
+        0x83, 0xff, 0x05, 0x77, 0x20, 0x89, 0xff, 0x48, 0x8d, 0x05, 0xf2, 0x0f, 0x00, 0x00, 0x48,
+        0x63, 0x14, 0xb8, 0x48, 0x01, 0xc2, 0xff, 0xe2,
+    ];
+
+    assert_eq!(
+        find_jump_table(&pic, 0x1000),
+        Some(JumpTable {
+            addr: 0x2000,
+            entry: TableEntry::Relative32,
+            len: Some(6),
+        })
+    );
+
+    // jmp qword [rdi * 8 + 0x402000]
+    let absolute = [0xff, 0x24, 0xfd, 0x00, 0x20, 0x40, 0x00];
+    assert_eq!(
+        find_jump_table(&absolute, 0x401000),
+        Some(JumpTable {
+            addr: 0x402000,
+            entry: TableEntry::Absolute(8),
+            len: None,
+        })
+    );
+
+    // jmp rdx, without the table being loaded beforehand
+    assert_eq!(find_jump_table(&[0xff, 0xe2], 0x1000), None);
+}
+
 mod reg_masks {
     use crate::long_mode::RegSpec;
 
@@ -6457,3 +6514,100 @@ mod reg_masks {
         RegSpec::zmm(32);
     }
 }
+
+#[test]
+fn control_flow_kind() {
+    use decoder::Flow;
+
+    let flow = |data: &[u8]| Decoder::default().decode(&mut Reader::new(data)).unwrap().flow();
+
+    // call 0x5
+    assert_eq!(flow(&[0xe8, 0x00, 0x00, 0x00, 0x00]), Flow::Next);
+    // jz 0x2
+    assert_eq!(flow(&[0x74, 0x00]), Flow::Branch);
+    // loop 0x2
+    assert_eq!(flow(&[0xe2, 0x00]), Flow::Branch);
+    // jmp 0x2
+    assert_eq!(flow(&[0xeb, 0x00]), Flow::Jump);
+    // jmp rax
+    assert_eq!(flow(&[0xff, 0xe0]), Flow::Jump);
+    // ret
+    assert_eq!(flow(&[0xc3]), Flow::Return);
+    // ud2
+    assert_eq!(flow(&[0x0f, 0x0b]), Flow::Return);
+}
+
+#[test]
+fn describe() {
+    let symbols = debugvault::Index::default();
+    let describe = |data: &[u8]| {
+        let inst = Decoder::default().decode(&mut Reader::new(data)).unwrap();
+        inst.describe(&symbols).unwrap()
+    };
+
+    // xor ecx, dword [rax]
+    let description = describe(&[0x33, 0x08]);
+    assert_eq!(description.mnemonic, "xor");
+    assert!(description.summary.is_some());
+    assert_eq!(description.flags, "CF OF SF ZF PF");
+    assert_eq!(
+        description.operands,
+        [
+            ("ecx".to_string(), "dword register".to_string()),
+            ("dword [rax]".to_string(), "memory at rax".to_string()),
+        ]
+    );
+
+    // lock add dword [rax], ecx
+    assert_eq!(describe(&[0xf0, 0x01, 0x08]).notes, ["Locked, accessing memory atomically."]);
+    // rep stos byte es:[rdi], al
+    assert_eq!(describe(&[0xf3, 0xaa]).notes, ["Repeated rcx times."]);
+    // fld dword [rax]
+    assert_eq!(describe(&[0xd9, 0x00]).summary, None);
+}
+
+#[test]
+fn address_tokens() {
+    let symbols = debugvault::Index::default();
+    let referred = |data: &[u8]| {
+        let mut inst = Decoder::default().decode(&mut Reader::new(data)).unwrap();
+        inst.update_rel_addrs(0x1000, None);
+        let tokens = inst.tokens(&symbols);
+        tokens.iter().filter_map(|token| token.target).collect::<Vec<_>>()
+    };
+
+    // jmp 0x1010
+    assert_eq!(referred(&[0xeb, 0x0e]), [0x1010]);
+    // call 0x1015
+    assert_eq!(referred(&[0xe8, 0x10, 0x00, 0x00, 0x00]), [0x1015]);
+    // mov eax, dword [rax]
+    assert_eq!(referred(&[0x8b, 0x00]), []);
+}
+
+#[test]
+fn formats() {
+    let symbols = debugvault::Index::default();
+    let format = |data: &[u8], f: fn(&mut tokenizing::Format)| {
+        let inst = Decoder::default().decode(&mut Reader::new(data)).unwrap();
+        let mut stream = tokenizing::TokenStream::new();
+        f(&mut stream.format);
+        inst.tokenize(&mut stream, &symbols);
+        stream.to_string()
+    };
+    let att = |format: &mut tokenizing::Format| format.syntax = tokenizing::Syntax::Att;
+
+    assert_eq!(format(&[0xb8, 0x01, 0x00, 0x00, 0x00], att), "mov $0x1, %eax");
+    assert_eq!(format(&[0x8b, 0x44, 0x88, 0x10], att), "mov 0x10(%rax,%rcx,4), %eax");
+    assert_eq!(format(&[0xc7, 0x00, 0x01, 0x00, 0x00, 0x00], att), "movl $0x1, (%rax)");
+    assert_eq!(format(&[0xff, 0xe0], att), "jmp *%rax");
+    assert_eq!(
+        format(&[0x64, 0x8b, 0x04, 0x25, 0x28, 0x00, 0x00, 0x00], att),
+        "mov %fs:0x28, %eax"
+    );
+
+    let uppercase = |format: &mut tokenizing::Format| format.uppercase = true;
+    assert_eq!(format(&[0xb8, 0x01, 0x00, 0x00, 0x00], uppercase), "MOV eax, 0x1");
+
+    let decimal = |format: &mut tokenizing::Format| format.hex_immediates = false;
+    assert_eq!(format(&[0x83, 0xe8, 0xf0], decimal), "sub eax, -16");
+}
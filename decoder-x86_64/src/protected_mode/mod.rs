@@ -1,3 +1,4 @@
+mod att;
 mod display;
 mod evex;
 mod tests;
@@ -11,10 +12,10 @@ use std::hash::{Hash, Hasher};
 use crate::safer_unchecked::unreachable_kinda_unchecked as unreachable_unchecked;
 pub use crate::MemoryAccessSize;
 
-use decoder::{Decoded, Decodable, Error, ErrorKind, Reader, ToTokens};
-use tokenizing::TokenStream;
+use decoder::{Decoded, Decodable, Error, ErrorKind, Flow, Reader, ToTokens, XrefKind};
+use decoder::Description;
+use tokenizing::{Kind, TokenStream};
 use debugvault::Index;
-use config::CONFIG;
 
 /// an `x86` register, including its number and type. if `fmt` is enabled, name too.
 ///
@@ -505,7 +506,7 @@ impl SaeMode {
 
 impl ToTokens for SaeMode {
     fn tokenize(&self, stream: &mut TokenStream, _: &Index) {
-        stream.push("{", CONFIG.colors.brackets);
+        stream.push("{", Kind::Bracket);
         stream.push(
             match self {
                 SaeMode::RoundNearest => "rne-sae",
@@ -513,9 +514,9 @@ impl ToTokens for SaeMode {
                 SaeMode::RoundUp => "ru-sae",
                 SaeMode::RoundZero => "rz-sae",
             },
-            CONFIG.colors.asm.register,
+            Kind::Register,
         );
-        stream.push("}", CONFIG.colors.brackets);
+        stream.push("}", Kind::Bracket);
     }
 }
 
@@ -2659,6 +2660,89 @@ impl Decoded for Instruction {
             self.imm_override = true;
         }
     }
+
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        if !self.imm_override {
+            return None;
+        }
+
+        let operand = |idx: usize| Operand::from_spec(self, self.operands[idx]);
+        let count = self.operand_count as usize;
+        let has_memory = (0..count).any(|idx| operand(idx).is_memory());
+        let has_immediate = (0..count).any(|idx| {
+            matches!(
+                operand(idx),
+                Operand::ImmediateI8(_)
+                | Operand::ImmediateU8(_)
+                | Operand::ImmediateI16(_)
+                | Operand::ImmediateU16(_)
+                | Operand::ImmediateI32(_)
+                | Operand::ImmediateU32(_)
+            )
+        });
+
+        let kind = match self.opcode {
+            // both operands got resolved, we can't tell which one ended up in `imm`
+            _ if has_memory && has_immediate => return None,
+            _ if has_memory => XrefKind::Data,
+            Opcode::CALL => XrefKind::Call,
+            Opcode::JMP | Opcode::LOOP | Opcode::LOOPZ | Opcode::LOOPNZ | Opcode::JECXZ => {
+                XrefKind::Jump
+            }
+            opcode if opcode.is_jcc() => XrefKind::Jump,
+            // immediates are resolved as if they're relative, which only holds for branches
+            _ => return None,
+        };
+
+        Some((self.imm as usize, kind))
+    }
+
+    fn flow(&self) -> Flow {
+        match self.opcode {
+            Opcode::JMP | Opcode::JMPF => Flow::Jump,
+            Opcode::RETURN
+            | Opcode::RETF
+            | Opcode::IRET
+            | Opcode::IRETD
+            | Opcode::IRETQ
+            | Opcode::SYSRET
+            | Opcode::SYSEXIT
+            | Opcode::HLT
+            | Opcode::UD2 => Flow::Return,
+            Opcode::LOOP | Opcode::LOOPZ | Opcode::LOOPNZ | Opcode::JECXZ => Flow::Branch,
+            opcode if opcode.is_jcc() => Flow::Branch,
+            _ => Flow::Next,
+        }
+    }
+
+    fn describe(&self, symbols: &Index) -> Option<Description> {
+        let mnemonic = self.opcode.to_string();
+        let operands = self.operand_descriptions(symbols);
+        let mut description = Description::new(crate::reference::REFERENCE, &mnemonic, operands);
+
+        if self.prefixes.lock() {
+            description.notes.push("Locked, accessing memory atomically.".to_string());
+        }
+
+        let repeated = match self.opcode {
+            Opcode::MOVS | Opcode::LODS | Opcode::STOS | Opcode::INS | Opcode::OUTS => {
+                self.prefixes.rep_any().then_some("Repeated ecx times.")
+            }
+            Opcode::CMPS if self.prefixes.rep() => {
+                Some("Repeated while equal, at most ecx times.")
+            }
+            Opcode::CMPS if self.prefixes.repnz() => {
+                Some("Repeated while not equal, at most ecx times.")
+            }
+            _ => None,
+        };
+
+        if let Some(repeated) = repeated {
+            description.notes.push(repeated.to_string());
+        }
+
+        Some(description)
+    }
 }
 
 impl decoder::Decodable for Decoder {
@@ -0,0 +1,740 @@
+//! Reference of common x86 instructions, shown when hovering them in the listing. Registers are
+//! named as in 64-bit mode.
+
+use decoder::Reference;
+
+/// Sorted by mnemonic, as it's searched by [`decoder::lookup`].
+pub(crate) const REFERENCE: &[Reference] = &[
+    Reference {
+        mnemonic: "adc",
+        summary: "Adds the source and the carry flag to the destination.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "add",
+        summary: "Adds the source to the destination.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "and",
+        summary: "Bitwise ANDs the source into the destination.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "bsf",
+        summary: "Stores the index of the lowest set bit of the source, setting ZF if it is zero.",
+        flags: "ZF",
+    },
+    Reference {
+        mnemonic: "bsr",
+        summary: "Stores the index of the highest set bit of the source, setting ZF if it is zero.",
+        flags: "ZF",
+    },
+    Reference {
+        mnemonic: "bswap",
+        summary: "Reverses the order of the bytes in a register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "bt",
+        summary: "Copies the selected bit of the destination into the carry flag.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "btc",
+        summary: "Copies the selected bit of the destination into the carry flag and complements \
+            it.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "btr",
+        summary: "Copies the selected bit of the destination into the carry flag and clears it.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "bts",
+        summary: "Copies the selected bit of the destination into the carry flag and sets it.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "call",
+        summary: "Pushes the address of the next instruction and jumps to the target.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cbw",
+        summary: "Sign-extends al into ax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cdq",
+        summary: "Sign-extends eax into edx:eax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cdqe",
+        summary: "Sign-extends eax into rax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "clc",
+        summary: "Clears the carry flag.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "cld",
+        summary: "Clears the direction flag, making string instructions go forwards.",
+        flags: "DF",
+    },
+    Reference {
+        mnemonic: "cli",
+        summary: "Clears the interrupt flag, masking interrupts.",
+        flags: "IF",
+    },
+    Reference {
+        mnemonic: "cmc",
+        summary: "Complements the carry flag.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "cmova",
+        summary: "Copies the source into the destination if above, unsigned (CF and ZF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovb",
+        summary: "Copies the source into the destination if below, unsigned (CF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovg",
+        summary: "Copies the source into the destination if greater, signed (ZF clear and SF and \
+            OF equal).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovge",
+        summary: "Copies the source into the destination if greater or equal, signed (SF and OF \
+            equal).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovl",
+        summary: "Copies the source into the destination if less, signed (SF and OF differ).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovle",
+        summary: "Copies the source into the destination if less or equal, signed (ZF set or SF \
+            and OF differ).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovna",
+        summary: "Copies the source into the destination if not above, unsigned (CF or ZF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovnb",
+        summary: "Copies the source into the destination if not below, unsigned (CF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovno",
+        summary: "Copies the source into the destination if no overflow (OF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovnp",
+        summary: "Copies the source into the destination if parity is odd (PF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovns",
+        summary: "Copies the source into the destination if not negative (SF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovnz",
+        summary: "Copies the source into the destination if not zero or not equal (ZF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovo",
+        summary: "Copies the source into the destination if overflow (OF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovp",
+        summary: "Copies the source into the destination if parity is even (PF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovs",
+        summary: "Copies the source into the destination if negative (SF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmovz",
+        summary: "Copies the source into the destination if zero or equal (ZF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cmp",
+        summary: "Subtracts the source from the destination, only keeping the flags.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "cmps",
+        summary: "Compares the values at rsi and rdi, advancing both.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "cmpxchg",
+        summary: "Stores the source in the destination if it equals the accumulator, otherwise \
+            loads the destination into the accumulator.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "cpuid",
+        summary: "Returns processor identification and feature information in eax, ebx, ecx and \
+            edx.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cqo",
+        summary: "Sign-extends rax into rdx:rax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cwd",
+        summary: "Sign-extends ax into dx:ax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "cwde",
+        summary: "Sign-extends ax into eax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "dec",
+        summary: "Subtracts one from the destination, leaving the carry flag alone.",
+        flags: "OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "div",
+        summary: "Divides the accumulator, extended with rdx, by the source as unsigned, storing \
+            the quotient in rax and the remainder in rdx.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "endbr32",
+        summary: "Marks a valid target of indirect branches when control-flow enforcement is on.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "endbr64",
+        summary: "Marks a valid target of indirect branches when control-flow enforcement is on.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "enter",
+        summary: "Pushes rbp and allocates a stack frame of the given size.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "hlt",
+        summary: "Halts the processor until the next interrupt.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "idiv",
+        summary: "Divides the accumulator, extended with rdx, by the source as signed, storing the \
+            quotient in rax and the remainder in rdx.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "imul",
+        summary: "Multiplies the operands as signed.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "in",
+        summary: "Reads from an I/O port.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "inc",
+        summary: "Adds one to the destination, leaving the carry flag alone.",
+        flags: "OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "int",
+        summary: "Raises a software interrupt with the given vector.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ja",
+        summary: "Jumps to the target if above, unsigned (CF and ZF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jb",
+        summary: "Jumps to the target if below, unsigned (CF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jecxz",
+        summary: "Jumps to the target if ecx is zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jg",
+        summary: "Jumps to the target if greater, signed (ZF clear and SF and OF equal).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jge",
+        summary: "Jumps to the target if greater or equal, signed (SF and OF equal).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jl",
+        summary: "Jumps to the target if less, signed (SF and OF differ).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jle",
+        summary: "Jumps to the target if less or equal, signed (ZF set or SF and OF differ).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jmp",
+        summary: "Jumps to the target.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jna",
+        summary: "Jumps to the target if not above, unsigned (CF or ZF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jnb",
+        summary: "Jumps to the target if not below, unsigned (CF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jno",
+        summary: "Jumps to the target if no overflow (OF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jnp",
+        summary: "Jumps to the target if parity is odd (PF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jns",
+        summary: "Jumps to the target if not negative (SF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jnz",
+        summary: "Jumps to the target if not zero or not equal (ZF clear).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jo",
+        summary: "Jumps to the target if overflow (OF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jp",
+        summary: "Jumps to the target if parity is even (PF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jrcxz",
+        summary: "Jumps to the target if rcx is zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "js",
+        summary: "Jumps to the target if negative (SF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "jz",
+        summary: "Jumps to the target if zero or equal (ZF set).",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lea",
+        summary: "Stores the address the memory operand computes, without accessing memory.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "leave",
+        summary: "Releases the stack frame, restoring rsp from rbp and popping rbp.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "lods",
+        summary: "Loads the value at rsi into the accumulator, advancing rsi.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "loop",
+        summary: "Decrements rcx and jumps to the target unless it became zero.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "loopnz",
+        summary: "Decrements rcx and jumps to the target unless it became zero or ZF is set.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "loopz",
+        summary: "Decrements rcx and jumps to the target unless it became zero or ZF is clear.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mov",
+        summary: "Copies the source into the destination.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movaps",
+        summary: "Copies 16 bytes of aligned packed single-precision floats.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movd",
+        summary: "Copies a dword between a general purpose and a vector register.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movdqa",
+        summary: "Copies 16 bytes of aligned packed integers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movdqu",
+        summary: "Copies 16 bytes of unaligned packed integers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movq",
+        summary: "Copies a qword between general purpose and vector registers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movs",
+        summary: "Copies the value at rsi to rdi, advancing both.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movsd",
+        summary: "Copies a scalar double-precision float.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movss",
+        summary: "Copies a scalar single-precision float.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movsx",
+        summary: "Copies the source into the destination, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movsxd",
+        summary: "Copies a dword into the destination, sign-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movups",
+        summary: "Copies 16 bytes of unaligned packed single-precision floats.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "movzx",
+        summary: "Copies the source into the destination, zero-extending it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "mul",
+        summary: "Multiplies the accumulator by the source as unsigned, storing the result in \
+            rdx:rax.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "neg",
+        summary: "Negates the destination in two's complement.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "nop",
+        summary: "Does nothing.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "not",
+        summary: "Inverts every bit of the destination.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "or",
+        summary: "Bitwise ORs the source into the destination.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "out",
+        summary: "Writes to an I/O port.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "pop",
+        summary: "Loads the value on top of the stack into the destination and removes it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "popf",
+        summary: "Pops the flags register off the stack.",
+        flags: "CF OF SF ZF AF PF DF IF TF",
+    },
+    Reference {
+        mnemonic: "push",
+        summary: "Pushes the source onto the stack.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "pushf",
+        summary: "Pushes the flags register onto the stack.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "pxor",
+        summary: "Bitwise XORs packed integers.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "rcl",
+        summary: "Rotates the destination left through the carry flag.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "rcr",
+        summary: "Rotates the destination right through the carry flag.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "rdtsc",
+        summary: "Loads the time-stamp counter into edx:eax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "ret",
+        summary: "Pops the return address off the stack and jumps to it.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "rol",
+        summary: "Rotates the destination left.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "ror",
+        summary: "Rotates the destination right.",
+        flags: "CF OF",
+    },
+    Reference {
+        mnemonic: "sal",
+        summary: "Shifts the destination left, filling in zeroes.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "sar",
+        summary: "Shifts the destination right, keeping it's sign.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "sbb",
+        summary: "Subtracts the source and the carry flag from the destination.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "scas",
+        summary: "Compares the accumulator with the value at rdi, advancing rdi.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "seta",
+        summary: "Sets the byte to 1 if above, unsigned (CF and ZF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setae",
+        summary: "Sets the byte to 1 if not below, unsigned (CF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setb",
+        summary: "Sets the byte to 1 if below, unsigned (CF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setbe",
+        summary: "Sets the byte to 1 if not above, unsigned (CF or ZF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setg",
+        summary: "Sets the byte to 1 if greater, signed (ZF clear and SF and OF equal), otherwise \
+            to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setge",
+        summary: "Sets the byte to 1 if greater or equal, signed (SF and OF equal), otherwise to \
+            0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setl",
+        summary: "Sets the byte to 1 if less, signed (SF and OF differ), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setle",
+        summary: "Sets the byte to 1 if less or equal, signed (ZF set or SF and OF differ), \
+            otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setno",
+        summary: "Sets the byte to 1 if no overflow (OF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setnp",
+        summary: "Sets the byte to 1 if parity is odd (PF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setns",
+        summary: "Sets the byte to 1 if not negative (SF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setnz",
+        summary: "Sets the byte to 1 if not zero or not equal (ZF clear), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "seto",
+        summary: "Sets the byte to 1 if overflow (OF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setp",
+        summary: "Sets the byte to 1 if parity is even (PF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sets",
+        summary: "Sets the byte to 1 if negative (SF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "setz",
+        summary: "Sets the byte to 1 if zero or equal (ZF set), otherwise to 0.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "shl",
+        summary: "Shifts the destination left, filling in zeroes.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "shld",
+        summary: "Shifts the destination left, filling in bits from the source.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "shr",
+        summary: "Shifts the destination right, filling in zeroes.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "shrd",
+        summary: "Shifts the destination right, filling in bits from the source.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "stc",
+        summary: "Sets the carry flag.",
+        flags: "CF",
+    },
+    Reference {
+        mnemonic: "std",
+        summary: "Sets the direction flag, making string instructions go backwards.",
+        flags: "DF",
+    },
+    Reference {
+        mnemonic: "sti",
+        summary: "Sets the interrupt flag, unmasking interrupts.",
+        flags: "IF",
+    },
+    Reference {
+        mnemonic: "stos",
+        summary: "Stores the accumulator at rdi, advancing rdi.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sub",
+        summary: "Subtracts the source from the destination.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "syscall",
+        summary: "Calls into the operating system, which reads the call number from rax.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "sysenter",
+        summary: "Calls into the operating system through the fast system call entry.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "test",
+        summary: "Bitwise ANDs the operands, only keeping the flags.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "ud2",
+        summary: "Raises an invalid opcode exception.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "xadd",
+        summary: "Exchanges the operands, storing their sum in the destination.",
+        flags: "CF OF SF ZF AF PF",
+    },
+    Reference {
+        mnemonic: "xchg",
+        summary: "Exchanges the operands.",
+        flags: "",
+    },
+    Reference {
+        mnemonic: "xor",
+        summary: "Bitwise XORs the source into the destination.",
+        flags: "CF OF SF ZF PF",
+    },
+    Reference {
+        mnemonic: "xorps",
+        summary: "Bitwise XORs packed single-precision floats.",
+        flags: "",
+    },
+];
@@ -1,7 +1,8 @@
 //! Shared behaviour required between decoder crates.
 
 use std::fmt::Debug;
-use debugvault::Index;
+use std::sync::Arc;
+use debugvault::{Index, Symbol};
 use tokenizing::{TokenStream, Token};
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
@@ -56,6 +57,129 @@ pub enum ErrorKind {
     Unpredictable,
 }
 
+/// How an instruction refers to another address.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum XrefKind {
+    /// Control flow that returns, e.g. `call` or `bl`.
+    Call,
+    /// Control flow that doesn't return, both conditional and unconditional.
+    Jump,
+    /// Any read, write or address computation.
+    Data,
+}
+
+/// Where execution goes after an instruction, as far as the instruction alone tells.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Flow {
+    /// Continues with the next instruction, calls included.
+    Next,
+    /// Either goes to [`Decoded::reference`] or continues with the next instruction.
+    Branch,
+    /// Never continues with the next instruction, including jumps to unknown targets.
+    Jump,
+    /// Leaves the function, either returning or stopping execution e.g. `ud2`.
+    Return,
+}
+
+/// How the entries of a [`JumpTable`] encode their target.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TableEntry {
+    /// Absolute addresses of the given width in bytes.
+    Absolute(usize),
+    /// Signed 32-bit offsets from the start of the table.
+    Relative32,
+}
+
+impl TableEntry {
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Absolute(width) => *width,
+            Self::Relative32 => 4,
+        }
+    }
+}
+
+/// Table an indirect jump reads it's target from, as generated for `switch` statements.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct JumpTable {
+    /// Address of the first entry.
+    pub addr: usize,
+    pub entry: TableEntry,
+    /// Number of entries, if the bounds check on the index was found.
+    pub len: Option<usize>,
+}
+
+/// Entry of an ISA reference, describing the instructions with a given mnemonic.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Reference {
+    pub mnemonic: &'static str,
+    /// What the instruction does, in a sentence.
+    pub summary: &'static str,
+    /// Status flags the instruction writes, separated by spaces.
+    pub flags: &'static str,
+}
+
+/// Find the entry of `mnemonic` in `table`, which has to be sorted by mnemonic.
+pub fn lookup(table: &'static [Reference], mnemonic: &str) -> Option<&'static Reference> {
+    let idx = table.binary_search_by(|entry| entry.mnemonic.cmp(mnemonic)).ok()?;
+    Some(&table[idx])
+}
+
+/// Breakdown of an instruction, as shown when hovering it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Description {
+    pub mnemonic: String,
+    /// What the instruction does, if it's in the ISA reference.
+    pub summary: Option<&'static str>,
+    /// Status flags the instruction writes, separated by spaces.
+    pub flags: &'static str,
+    /// Each operand as it's shown, along with what kind of operand it is.
+    pub operands: Vec<(String, String)>,
+    /// Anything else changing what the instruction does, e.g. a condition or prefix.
+    pub notes: Vec<String>,
+}
+
+impl Description {
+    /// Describe an instruction using it's entry in `table`.
+    pub fn new(
+        table: &'static [Reference],
+        mnemonic: &str,
+        operands: Vec<(String, String)>,
+    ) -> Self {
+        let reference = lookup(table, mnemonic);
+        Self {
+            mnemonic: mnemonic.to_string(),
+            summary: reference.map(|reference| reference.summary),
+            flags: reference.map_or("", |reference| reference.flags),
+            operands,
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// Symbol to show `addr` as along with the offset into it, `None` if addresses are shown as is
+/// in `stream`'s format.
+///
+/// Unless the symbol has to be `exact`, the closest symbol before `addr` is used, which only
+/// makes sense for what's known to be an address such as a branch target.
+pub fn symbol_at(
+    stream: &TokenStream,
+    symbols: &Index,
+    addr: usize,
+    exact: bool,
+) -> Option<(Arc<Symbol>, usize)> {
+    if !stream.format.symbols {
+        return None;
+    }
+
+    if exact {
+        return symbols.get_sym_by_addr(addr).map(|symbol| (symbol, 0));
+    }
+
+    let (start, symbol) = symbols.get_sym_containing(addr)?;
+    Some((symbol, addr - start))
+}
+
 pub trait ToTokens {
     fn tokenize(&self, stream: &mut TokenStream, symbols: &Index);
 }
@@ -68,6 +192,29 @@ pub trait Decoded: ToTokens {
         stream.inner
     }
     fn update_rel_addrs(&mut self, addr: usize, prev_inst: Option<&Self>);
+
+    /// Absolute address referenced by the instruction.
+    /// Only meaningful after [`Decoded::update_rel_addrs`] has been called.
+    fn reference(&self) -> Option<(usize, XrefKind)> {
+        None
+    }
+
+    /// Jump table an indirect jump goes through, `prev` being the instructions right before it
+    /// and their address, closest first.
+    /// Only meaningful after [`Decoded::update_rel_addrs`] has been called.
+    fn jump_table(&self, _prev: &[(usize, &Self)]) -> Option<JumpTable> {
+        None
+    }
+
+    /// Where execution goes after the instruction, defaults to always continuing.
+    fn flow(&self) -> Flow {
+        Flow::Next
+    }
+
+    /// Breakdown of the instruction shown when hovering it, if the ISA has a reference.
+    fn describe(&self, _symbols: &Index) -> Option<Description> {
+        None
+    }
 }
 
 pub trait Decodable {
@@ -75,6 +222,11 @@ pub trait Decodable {
 
     fn decode(&self, reader: &mut Reader) -> Result<Self::Instruction, Error>;
     fn max_width(&self) -> usize;
+
+    /// Alignment of every instruction's address, `1` if there's no requirement.
+    fn instruction_alignment(&self) -> usize {
+        1
+    }
 }
 
 pub struct Reader<'data> {
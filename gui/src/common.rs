@@ -1,4 +1,7 @@
-pub const FONT: egui::FontId = egui::FontId::new(14.0, egui::FontFamily::Monospace);
+/// Monospaced font the panels are drawn with, at the size set in the settings.
+pub fn font() -> egui::FontId {
+    egui::FontId::new(config::SETTINGS.read().unwrap().font_size, egui::FontFamily::Monospace)
+}
 
 pub struct Timer {
     start: std::time::Instant,
@@ -22,20 +25,66 @@ impl Timer {
     }
 }
 
+/// Scroll area state that sticks to the bottom, unless the user scrolled away from it.
+#[derive(Default)]
+pub struct StickyScroll {
+    paused: bool,
+    jump_to_bottom: bool,
+}
+
+impl StickyScroll {
+    /// Resume sticking to the bottom on the next frame.
+    pub fn resume(&mut self) {
+        self.jump_to_bottom = true;
+    }
+
+    pub fn show<R>(
+        &mut self,
+        ui: &mut egui::Ui,
+        area: egui::ScrollArea,
+        add_contents: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> R {
+        let jump_to_bottom = std::mem::take(&mut self.jump_to_bottom);
+        let output = area.stick_to_bottom(!self.paused).show(ui, |ui| {
+            let inner = add_contents(ui);
+            if jump_to_bottom {
+                ui.scroll_to_cursor(Some(egui::Align::BOTTOM));
+            }
+            inner
+        });
+
+        let visible_bottom = output.state.offset.y + output.inner_rect.height();
+        let at_bottom = visible_bottom + 1.0 >= output.content_size.y;
+        self.paused = !jump_to_bottom && !at_bottom;
+
+        if self.paused {
+            let size = egui::vec2(120.0, 24.0);
+            let margin = egui::vec2(10.0, 10.0);
+            let rect = egui::Rect::from_min_size(output.inner_rect.max - size - margin, size);
+            if ui.put(rect, egui::Button::new("Jump to latest")).clicked() {
+                self.resume();
+            }
+        }
+
+        output.inner
+    }
+}
+
 pub trait Display {
     fn show(&mut self, ui: &mut egui::Ui);
 }
 
 pub fn tokens_to_layoutjob(tokens: Vec<tokenizing::Token>) -> egui::text::LayoutJob {
     let mut job = egui::text::LayoutJob::default();
+    let theme = tokenizing::theme();
 
     for token in tokens {
         job.append(
             &token.text,
             0.0,
             egui::TextFormat {
-                font_id: FONT,
-                color: token.color,
+                font_id: font(),
+                color: theme.color(token.kind),
                 ..Default::default()
             },
         );
@@ -43,3 +92,22 @@ pub fn tokens_to_layoutjob(tokens: Vec<tokenizing::Token>) -> egui::text::Layout
 
     job
 }
+
+/// Index of the token under the pointer in a label showing `job`, if it's hovered.
+///
+/// Each token is it's own section of the layout job, so the glyph hit gives the token.
+pub fn hovered_token(
+    ui: &egui::Ui,
+    response: &egui::Response,
+    job: egui::text::LayoutJob,
+) -> Option<usize> {
+    let pos = (response.hover_pos()? - response.rect.min).to_pos2();
+    let galley = ui.fonts(|fonts| fonts.layout_job(job));
+
+    galley
+        .rows
+        .iter()
+        .flat_map(|row| &row.glyphs)
+        .find(|glyph| glyph.logical_rect().contains(pos))
+        .map(|glyph| glyph.section_index as usize)
+}
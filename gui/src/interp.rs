@@ -1,5 +1,12 @@
-use crate::tprint;
-use commands::{Command, CommandError};
+use crate::session::{Step, Stopped};
+use crate::{tprint, BinaryKind};
+use commands::{
+    BreakCondition, Command, CommandError, Examine, FollowFork, Format, Info, Machine, Setting,
+    SignalHandling,
+};
+use debugger::{signals, Condition, Handling, WatchKind, Watchpoint};
+use processor::BytePattern;
+use std::sync::Arc;
 
 impl super::UI {
     /// Runs all queued commands, returning if they trigger a process exit.
@@ -13,7 +20,10 @@ impl super::UI {
         let index = self.panels.processor().map(|proc| &proc.index).unwrap_or(&empty_index);
 
         match Command::parse(index, cmd, 0) {
-            Ok(Command::Load(path)) => self.offload_binary_processing(path),
+            Ok(Command::Load(path)) => self.offload_binary_processing(path, BinaryKind::Object),
+            Ok(Command::LoadImage(image, path)) => {
+                self.offload_binary_processing(path, BinaryKind::Image(image))
+            }
             Ok(Command::PrintPath) => match std::env::current_dir() {
                 Ok(path) => tprint!(
                     self.panels.terminal(),
@@ -46,7 +56,7 @@ impl super::UI {
                     }
                 };
 
-                if listing.jump(addr) {
+                if listing.jump(addr) || listing.jump_near(addr) {
                     tprint!(self.panels.terminal(), "Jumped to address {addr:#X}.");
                 } else {
                     tprint!(self.panels.terminal(), "Address {addr:#X} is undefined.");
@@ -54,15 +64,504 @@ impl super::UI {
 
                 self.panels.load_src(addr);
             }
+            Ok(Command::Search(pattern)) => {
+                let processor = match self.panels.processor() {
+                    Some(processor) => Arc::clone(processor),
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                let quoted = pattern.strip_prefix('"').and_then(|p| p.strip_suffix('"'));
+                let results = match (quoted, BytePattern::parse(&pattern)) {
+                    (Some(text), _) => processor.search_text(text, None),
+                    (None, Some(bytes)) => processor.search_bytes(&bytes, None),
+                    (None, None) => processor.search_text(&pattern, None),
+                };
+
+                let truncated = results.is_truncated();
+                let results = results.into_matches();
+
+                match results.len() {
+                    0 => tprint!(self.panels.terminal(), "No matches found."),
+                    1 => tprint!(self.panels.terminal(), "Found 1 match."),
+                    n if truncated => tprint!(
+                        self.panels.terminal(),
+                        "Found more than {n} matches, press F3 to go to the next one."
+                    ),
+                    n => tprint!(
+                        self.panels.terminal(),
+                        "Found {n} matches, press F3 to go to the next one."
+                    ),
+                }
+
+                if let Some(listing) = self.panels.listing() {
+                    listing.set_search_results(results);
+                }
+            }
+            Ok(Command::Patch(addr, src)) => {
+                let processor = match self.panels.processor() {
+                    Some(processor) => Arc::clone(processor),
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                match processor.patch_asm(addr, &src) {
+                    Ok(len) => {
+                        tprint!(self.panels.terminal(), "Patched {len} bytes at {addr:#X}.");
+                        self.panels.binary_patched();
+                    }
+                    Err(err) => tprint!(self.panels.terminal(), "{err:?}"),
+                }
+            }
+            Ok(Command::Break(addr)) => {
+                if self.panels.breakpoints().toggle(addr) {
+                    tprint!(self.panels.terminal(), "Set breakpoint at {addr:#X}.");
+                } else {
+                    tprint!(self.panels.terminal(), "Cleared breakpoint at {addr:#X}.");
+                }
+
+                self.panels.breakpoints_changed();
+            }
+            Ok(Command::PendingBreak(name)) => {
+                if self.panels.breakpoints().toggle_pending(&name) {
+                    tprint!(
+                        self.panels.terminal(),
+                        "Function '{name}' isn't in the binary, it's breakpoint is set once a \
+                         library defining it is loaded."
+                    );
+                } else {
+                    tprint!(self.panels.terminal(), "Cleared pending breakpoint on '{name}'.");
+                }
+
+                self.panels.breakpoints_changed();
+            }
+            Ok(Command::BreakIf(addr, condition)) => {
+                let BreakCondition { expr, hits, log } = condition;
+                let condition = Condition { expr, hits, log };
+
+                self.panels.breakpoints().set_conditional(addr, condition);
+                tprint!(self.panels.terminal(), "Set conditional breakpoint at {addr:#X}.");
+                self.panels.breakpoints_changed();
+            }
+            Ok(Command::HardwareBreak(addr)) => {
+                let breakpoints = Arc::clone(self.panels.breakpoints());
+
+                if breakpoints.is_hardware(addr) {
+                    breakpoints.toggle(addr);
+                    tprint!(self.panels.terminal(), "Cleared hardware breakpoint at {addr:#X}.");
+                } else {
+                    match breakpoints.set_hardware(addr, true) {
+                        Ok(()) => tprint!(
+                            self.panels.terminal(),
+                            "Set hardware breakpoint at {addr:#X}."
+                        ),
+                        Err(err) => tprint!(
+                            self.panels.terminal(),
+                            "Failed to set hardware breakpoint: {err}"
+                        ),
+                    }
+                }
+
+                self.panels.breakpoints_changed();
+            }
+            Ok(Command::Watch(addr)) => {
+                let breakpoints = Arc::clone(self.panels.breakpoints());
+
+                if breakpoints.remove_watchpoint(addr) {
+                    tprint!(self.panels.terminal(), "Removed watchpoint at {addr:#X}.");
+                } else {
+                    let len = [8, 4, 2, 1].into_iter().find(|len| addr % len == 0).unwrap_or(1);
+                    let watchpoint = Watchpoint {
+                        addr,
+                        len,
+                        kind: WatchKind::Write,
+                    };
+
+                    match breakpoints.set_watchpoint(watchpoint) {
+                        Ok(()) => tprint!(
+                            self.panels.terminal(),
+                            "Watching writes to {len} byte(s) at {addr:#X}."
+                        ),
+                        Err(err) => {
+                            tprint!(self.panels.terminal(), "Failed to set watchpoint: {err}")
+                        }
+                    }
+                }
+
+                self.panels.breakpoints_changed();
+            }
+            Ok(Command::Print(expr)) => {
+                let processor = self.panels.processor().cloned();
+                let index = processor.as_ref().map_or(&empty_index, |proc| &proc.index);
+
+                // Without a process only symbols and numbers can be evaluated.
+                let value = match self.panels.stopped() {
+                    Some(stopped) => expr.eval_in(index, &stopped),
+                    None => expr.eval(index),
+                };
+
+                match value {
+                    Ok(value) => tprint!(self.panels.terminal(), "{value} ({value:#x})"),
+                    Err(err) => self.panels.terminal().print_error(err),
+                }
+            }
+            Ok(Command::Examine(examine, expr)) => {
+                let processor = self.panels.processor().cloned();
+                let index = processor.as_ref().map_or(&empty_index, |proc| &proc.index);
+
+                let rows = match self.panels.stopped() {
+                    Some(stopped) => expr
+                        .eval_in(index, &stopped)
+                        .map(|addr| examine_memory(&stopped, addr as usize, examine)),
+                    None => {
+                        tprint!(self.panels.terminal(), "No process is being debugged.");
+                        return true;
+                    }
+                };
+
+                match rows {
+                    Ok(Some(rows)) => {
+                        for row in rows {
+                            tprint!(self.panels.terminal(), "{row}");
+                        }
+                    }
+                    Ok(None) => tprint!(self.panels.terminal(), "Failed to read memory."),
+                    Err(err) => self.panels.terminal().print_error(err),
+                }
+            }
+            Ok(Command::Run(args)) => self.panels.run(args),
+            Ok(Command::Attach(pid)) => self.panels.request_attach(pid),
+            Ok(Command::Core(path)) => self.panels.request_core(path),
+            Ok(Command::Detach) => match self.panels.detach_session() {
+                Some(true) => tprint!(self.panels.terminal(), "Detached from process."),
+                Some(false) => tprint!(self.panels.terminal(), "Process wasn't attached to."),
+                None => tprint!(self.panels.terminal(), "No process is being debugged."),
+            },
+            Ok(Command::Trace(enabled)) => {
+                self.panels.set_trace_syscalls(enabled);
+                if enabled {
+                    tprint!(self.panels.terminal(), "Tracing syscalls of processes started next.");
+                } else {
+                    tprint!(
+                        self.panels.terminal(),
+                        "Not tracing syscalls of processes started next."
+                    );
+                }
+            }
+            Ok(Command::Record(enabled)) => {
+                self.panels.set_recording(enabled);
+                if enabled {
+                    tprint!(self.panels.terminal(), "Recording the instructions executed.");
+                } else {
+                    tprint!(self.panels.terminal(), "Not recording the instructions executed.");
+                }
+            }
+            Ok(Command::Set(Setting::FollowFork(follow))) => {
+                let (follow, msg) = match follow {
+                    FollowFork::Parent => {
+                        (debugger::FollowFork::Parent, "Following the parent after forks.")
+                    }
+                    FollowFork::Child => {
+                        (debugger::FollowFork::Child, "Following the child after forks.")
+                    }
+                    FollowFork::Ask => {
+                        (debugger::FollowFork::Ask, "Stopping at forks to ask which to follow.")
+                    }
+                };
+
+                self.panels.set_follow_fork(follow);
+                tprint!(self.panels.terminal(), "{msg}");
+            }
+            Ok(Command::Set(Setting::Libraries(enabled))) => {
+                self.panels.set_load_libraries(enabled);
+                if enabled {
+                    tprint!(self.panels.terminal(), "Following imports into shared libraries.");
+                } else {
+                    tprint!(
+                        self.panels.terminal(),
+                        "Following imports into shared libraries only whilst debugging."
+                    );
+                }
+            }
+            Ok(Command::Set(Setting::Theme(name))) => {
+                crate::style::change_settings(|settings| settings.theme = name.to_string());
+                tprint!(self.panels.terminal(), "Switched to the {name} theme.");
+            }
+            Ok(Command::Set(Setting::Syntax(syntax))) => {
+                crate::style::change_settings(|settings| settings.format.syntax = syntax);
+                tprint!(self.panels.terminal(), "Showing x86 in {} syntax.", syntax.name());
+            }
+            Ok(Command::Set(Setting::Uppercase(enabled))) => {
+                crate::style::change_settings(|settings| settings.format.uppercase = enabled);
+                let case = if enabled { "uppercase" } else { "lowercase" };
+                tprint!(self.panels.terminal(), "Showing mnemonics in {case}.");
+            }
+            Ok(Command::Set(Setting::HexImmediates(enabled))) => {
+                crate::style::change_settings(|settings| settings.format.hex_immediates = enabled);
+                let radix = if enabled { "hexadecimal" } else { "decimal" };
+                tprint!(self.panels.terminal(), "Showing immediates in {radix}.");
+            }
+            Ok(Command::Set(Setting::OpcodeBytes(enabled))) => {
+                crate::style::change_settings(|settings| settings.format.opcode_bytes = enabled);
+                if enabled {
+                    tprint!(self.panels.terminal(), "Showing the bytes of instructions.");
+                } else {
+                    tprint!(self.panels.terminal(), "Hiding the bytes of instructions.");
+                }
+            }
+            Ok(Command::Set(Setting::Symbols(enabled))) => {
+                crate::style::change_settings(|settings| settings.format.symbols = enabled);
+                if enabled {
+                    tprint!(self.panels.terminal(), "Showing addresses as symbol plus offset.");
+                } else {
+                    tprint!(self.panels.terminal(), "Showing addresses as is.");
+                }
+            }
+            Ok(Command::Set(Setting::Args(args))) => {
+                if args.is_empty() {
+                    tprint!(self.panels.terminal(), "Starting processes without arguments.");
+                } else {
+                    let joined = args.join(" ");
+                    tprint!(self.panels.terminal(), "Starting processes with '{joined}'.");
+                }
+
+                self.panels.run_config().args = args;
+            }
+            Ok(Command::Set(Setting::Env(name, value))) => {
+                tprint!(self.panels.terminal(), "Starting processes with {name}='{value}'.");
+                self.panels.run_config().env.set_var(name, value);
+            }
+            Ok(Command::Set(Setting::Aslr(enabled))) => {
+                if enabled {
+                    tprint!(self.panels.terminal(), "Randomizing the addresses of processes.");
+                } else if cfg!(target_os = "linux") {
+                    tprint!(self.panels.terminal(), "Not randomizing the addresses of processes.");
+                } else {
+                    tprint!(
+                        self.panels.terminal(),
+                        "Address randomization can only be turned off on linux."
+                    );
+                }
+
+                self.panels.run_config().env.disable_aslr = !enabled && cfg!(target_os = "linux");
+            }
+            Ok(Command::Continue) => self.panels.resume(None),
+            Ok(Command::StepLine) => self.panels.resume(Some(Step::Line)),
+            Ok(Command::Next) => self.panels.resume(Some(Step::Over)),
+            Ok(Command::Step) => self.panels.resume(Some(Step::Instruction)),
+            Ok(Command::Finish) => self.panels.resume(Some(Step::Out)),
+            Ok(Command::ReverseStep) => self.panels.resume(Some(Step::Back)),
+            Ok(Command::Thread(tid)) => self.panels.select_thread(tid),
+            Ok(Command::Handle(signal, SignalHandling { stop, pass })) => {
+                let sig = match signals::number(&signal) {
+                    Some(sig) => sig,
+                    None => {
+                        tprint!(self.panels.terminal(), "Signal '{signal}' is unknown.");
+                        return true;
+                    }
+                };
+
+                let mut handled = self.panels.signals().clone();
+                let previous = handled.get(sig);
+                let handling = Handling {
+                    stop: stop.unwrap_or(previous.stop),
+                    pass: pass.unwrap_or(previous.pass),
+                };
+
+                if handling != previous {
+                    handled.set(sig, handling);
+                    self.panels.set_signals(handled);
+                }
+
+                tprint!(self.panels.terminal(), "{}", signal_handling(sig, handling));
+            }
+            Ok(Command::Kill) => {
+                if self.panels.kill_session() {
+                    tprint!(self.panels.terminal(), "Killed process.");
+                } else {
+                    tprint!(self.panels.terminal(), "No process is being debugged.");
+                }
+            }
+            Ok(Command::Info(Info::Registers)) => {
+                let registers = match self.panels.registers() {
+                    Some(registers) => registers.clone(),
+                    None => {
+                        tprint!(self.panels.terminal(), "No process is being debugged.");
+                        return true;
+                    }
+                };
+
+                for (name, value) in registers.general.iter().chain(&registers.segments) {
+                    tprint!(self.panels.terminal(), "{name:<8} {value:#018x}");
+                }
+
+                let flags: Vec<&str> =
+                    registers.flags.iter().filter(|(_, set)| *set).map(|(name, _)| *name).collect();
+                tprint!(self.panels.terminal(), "{:<8} [{}]", "flags", flags.join(" "));
+            }
+            Ok(Command::Info(Info::Breakpoints)) => {
+                let breakpoints = Arc::clone(self.panels.breakpoints());
+                let addrs = breakpoints.addrs();
+                let watchpoints = breakpoints.watchpoints();
+                let pending = breakpoints.pending();
+
+                if addrs.is_empty() && watchpoints.is_empty() && pending.is_empty() {
+                    tprint!(self.panels.terminal(), "No breakpoints or watchpoints set.");
+                }
+
+                for addr in addrs {
+                    let mut line = match breakpoints.is_hardware(addr) {
+                        true => format!("hardware breakpoint {addr:#X}"),
+                        false => format!("breakpoint {addr:#X}"),
+                    };
+                    if let Some(function) = breakpoints.function(addr) {
+                        line += &format!(" ({function})");
+                    }
+                    if let Some(condition) = breakpoints.condition(addr) {
+                        if let Some(expr) = condition.expr {
+                            line += &format!(" if {expr}");
+                        }
+                        if let Some(hits) = condition.hits {
+                            line += &format!(" after {hits}");
+                        }
+                        if let Some(msg) = condition.log {
+                            line += &format!(" log \"{msg}\"");
+                        }
+                    }
+
+                    tprint!(self.panels.terminal(), "{line}");
+                }
+
+                for wp in watchpoints {
+                    let kind = match wp.kind {
+                        WatchKind::Write => "writes",
+                        WatchKind::ReadWrite => "reads and writes",
+                        WatchKind::Execute => "executes",
+                    };
+
+                    tprint!(
+                        self.panels.terminal(),
+                        "watchpoint {:#X} ({} byte(s), {kind})",
+                        wp.addr,
+                        wp.len
+                    );
+                }
+
+                for name in pending {
+                    tprint!(self.panels.terminal(), "pending breakpoint {name}");
+                }
+            }
+            Ok(Command::Info(Info::Sections)) => {
+                let processor = match self.panels.processor() {
+                    Some(processor) => Arc::clone(processor),
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                for section in processor.sections() {
+                    tprint!(
+                        self.panels.terminal(),
+                        "{:0>10X}..{:0>10X} {}",
+                        section.start,
+                        section.end,
+                        section.name
+                    );
+                }
+            }
+            Ok(Command::Info(Info::Libraries)) => {
+                // A process being debugged shows the libraries it actually loaded.
+                let loaded = self.panels.session().map(|session| session.libraries().to_vec());
+                if let Some(libraries) = loaded.filter(|libraries| !libraries.is_empty()) {
+                    for library in libraries {
+                        let path = library.path.display();
+                        tprint!(self.panels.terminal(), "{:0>10X} {path}", library.bias);
+                    }
+
+                    return true;
+                }
+
+                let processor = match self.panels.processor() {
+                    Some(processor) => Arc::clone(processor),
+                    None => {
+                        tprint!(self.panels.terminal(), "No targets loaded.");
+                        return true;
+                    }
+                };
+
+                let libraries = processor.libraries();
+                if libraries.is_empty() {
+                    tprint!(self.panels.terminal(), "The binary doesn't import any libraries.");
+                }
+
+                for library in libraries {
+                    let path = match library.path {
+                        Some(path) => path.display().to_string(),
+                        None => "not found".to_string(),
+                    };
+
+                    tprint!(self.panels.terminal(), "{} => {path}", library.name);
+                }
+            }
+            Ok(Command::Info(Info::Signals)) => {
+                let handled = self.panels.signals();
+                let lines: Vec<String> = signals::standard()
+                    .map(|sig| signal_handling(sig, handled.get(sig)))
+                    .collect();
+
+                for line in lines {
+                    tprint!(self.panels.terminal(), "{line}");
+                }
+            }
+            Ok(Command::Coverage(path)) => self.panels.load_coverage(path),
+            Ok(Command::Script(path)) => {
+                let ctx = scripting::Context {
+                    processor: self.panels.processor().cloned(),
+                    breakpoints: Arc::clone(self.panels.breakpoints()),
+                };
+
+                let output = match scripting::run_file(&ctx, &path) {
+                    Ok(output) => output,
+                    Err(err) => {
+                        self.panels.terminal().print_error(err);
+                        return true;
+                    }
+                };
+
+                for line in output.printed {
+                    tprint!(self.panels.terminal(), "{line}");
+                }
+
+                if output.annotations_changed {
+                    self.panels.annotations_changed();
+                }
+
+                if output.breakpoints_changed {
+                    self.panels.breakpoints_changed();
+                }
+
+                if let Some(addr) = output.goto {
+                    if let Some(listing) = self.panels.listing() {
+                        listing.jump(addr);
+                    }
+                }
+            }
             Ok(Command::Quit) => return false,
             Ok(Command::Clear) => {
                 log::LOGGER.write().unwrap().clear();
                 self.panels.terminal().clear();
             }
-            Ok(Command::Help) => tprint!(self.panels.terminal(), "{}", commands::CMD_HELP),
+            Ok(Command::Help(name)) => {
+                tprint!(self.panels.terminal(), "{}", commands::help(name))
+            }
             Err((err, _)) => {
                 if err != CommandError::Missing("command") {
-                    tprint!(self.panels.terminal(), "{err}");
+                    self.panels.terminal().print_error(err);
                 }
             }
         }
@@ -70,3 +569,45 @@ impl super::UI {
         true
     }
 }
+
+/// Name of signal `sig` followed by whether it stops the process and is passed on to it.
+fn signal_handling(sig: i32, handling: Handling) -> String {
+    let name = signals::name(sig).map_or_else(|| sig.to_string(), String::from);
+    let stop = if handling.stop { "stop" } else { "nostop" };
+    let pass = if handling.pass { "pass" } else { "nopass" };
+    format!("{name:<10} {stop:<6} {pass}")
+}
+
+/// Rows of `examine.count` units of memory at `addr`, each starting with it's address.
+/// None if the memory can't be read.
+fn examine_memory(stopped: &Stopped, addr: usize, examine: Examine) -> Option<Vec<String>> {
+    let bytes = stopped.read(addr, examine.count * examine.size)?;
+    let per_row = match examine.format {
+        Format::Char => 8,
+        _ => 16 / examine.size,
+    };
+
+    let units = bytes.chunks(examine.size).map(|unit| {
+        let mut word = [0; 8];
+        word[..unit.len()].copy_from_slice(unit);
+        let value = u64::from_le_bytes(word);
+        let shift = 64 - examine.size as u32 * 8;
+
+        match examine.format {
+            Format::Hex => format!("{value:#0width$x}", width = examine.size * 2 + 2),
+            Format::Signed => (((value << shift) as i64) >> shift).to_string(),
+            Format::Unsigned => value.to_string(),
+            Format::Octal => format!("{value:#o}"),
+            Format::Char => format!("{:?}", value as u8 as char),
+        }
+    });
+
+    let units: Vec<String> = units.collect();
+    let rows = units
+        .chunks(per_row)
+        .enumerate()
+        .map(|(idx, row)| format!("{:#x}: {}", addr + idx * per_row * examine.size, row.join(" ")))
+        .collect();
+
+    Some(rows)
+}
@@ -0,0 +1,192 @@
+//! Translation of the keys that are pressed into what they're bound to do.
+//!
+//! Actions are bound to shortcuts by their name in the settings file, those that aren't set
+//! there keeping the defaults in [`config::DEFAULT_KEYS`]. Letters typed without a modifier go
+//! to the terminal, so the defaults all use a modifier or a key that doesn't type anything, such
+//! as a function key or `Escape`.
+
+use config::Settings;
+
+/// Something done from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Open,
+    Save,
+    Undo,
+    Redo,
+    Goto,
+    /// Start typing a search in the terminal.
+    Search,
+    NextResult,
+    PreviousResult,
+    Back,
+    Forward,
+    /// Rename the function at the selected instruction.
+    Rename,
+    Comment,
+    Bookmark,
+    ToggleBreakpoint,
+    Continue,
+    /// Execute a single instruction of the debugged process.
+    Step,
+    /// Execute the debugged process until the next source line, stepping over calls.
+    Next,
+    /// Execute the debugged process until the current function returns.
+    StepOut,
+    /// Undo the last instruction the debugged process executed whilst recording.
+    StepBack,
+    /// Switch to the next tab of the tabs that are shown.
+    NextTab,
+    Preferences,
+}
+
+impl Action {
+    pub const ALL: [Self; 21] = [
+        Self::Open,
+        Self::Save,
+        Self::Undo,
+        Self::Redo,
+        Self::Goto,
+        Self::Search,
+        Self::NextResult,
+        Self::PreviousResult,
+        Self::Back,
+        Self::Forward,
+        Self::Rename,
+        Self::Comment,
+        Self::Bookmark,
+        Self::ToggleBreakpoint,
+        Self::Continue,
+        Self::Step,
+        Self::Next,
+        Self::StepOut,
+        Self::StepBack,
+        Self::NextTab,
+        Self::Preferences,
+    ];
+
+    /// Name the action is bound by in the settings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Save => "save",
+            Self::Undo => "undo",
+            Self::Redo => "redo",
+            Self::Goto => "goto",
+            Self::Search => "search",
+            Self::NextResult => "next_result",
+            Self::PreviousResult => "previous_result",
+            Self::Back => "back",
+            Self::Forward => "forward",
+            Self::Rename => "rename",
+            Self::Comment => "comment",
+            Self::Bookmark => "bookmark",
+            Self::ToggleBreakpoint => "toggle_breakpoint",
+            Self::Continue => "continue",
+            Self::Step => "step",
+            Self::Next => "next",
+            Self::StepOut => "step_out",
+            Self::StepBack => "step_back",
+            Self::NextTab => "next_tab",
+            Self::Preferences => "preferences",
+        }
+    }
+
+    /// Whether the action is taken whilst typing in a text field, instead of the keys being
+    /// typed. Undoing, for one, undoes what's typed.
+    pub fn while_editing(self) -> bool {
+        matches!(self, Self::Open | Self::Save | Self::Goto | Self::Preferences)
+    }
+}
+
+/// Shortcuts of every action.
+pub struct Keymap {
+    bindings: Vec<(egui::KeyboardShortcut, Action)>,
+}
+
+impl Keymap {
+    pub fn new(settings: &Settings) -> Self {
+        let mut bindings = Vec::new();
+        for action in Action::ALL {
+            for shortcut in settings.shortcuts(action.name()) {
+                bindings.push((shortcut, action));
+            }
+        }
+
+        Self { bindings }
+    }
+
+    /// Action bound to exactly the keys pressed, ignoring whether they're Ctrl or Cmd on macOS.
+    fn lookup(&self, key: egui::Key, modifiers: egui::Modifiers) -> Option<Action> {
+        self.bindings.iter().find_map(|(shortcut, action)| {
+            let matches = shortcut.logical_key == key
+                && shortcut.modifiers.command == modifiers.command
+                && shortcut.modifiers.shift == modifiers.shift
+                && shortcut.modifiers.alt == modifiers.alt;
+
+            matches.then_some(*action)
+        })
+    }
+
+    /// Take the key presses bound to an action out of `events`, along with the text they'd
+    /// type. Only actions taken `while_editing` are taken out if `editing`.
+    pub fn translate(&self, events: &mut Vec<egui::Event>, editing: bool) -> Vec<Action> {
+        let mut actions = Vec::new();
+        let mut consumed = false;
+
+        events.retain(|event| {
+            // the text of a key press follows it
+            if let egui::Event::Text(..) = event {
+                return !std::mem::take(&mut consumed);
+            }
+
+            consumed = false;
+            if let egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } = event
+            {
+                match self.lookup(*key, *modifiers) {
+                    Some(action) if !editing || action.while_editing() => {
+                        actions.push(action);
+                        consumed = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            !consumed
+        });
+
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctrl_is_cmd() {
+        let keymap = Keymap::new(&Settings::default());
+
+        // Ctrl+Z as it's pressed on linux and windows, Cmd+Z as it's pressed on macOS.
+        let ctrl = egui::Modifiers {
+            ctrl: true,
+            command: true,
+            ..egui::Modifiers::NONE
+        };
+        let cmd = egui::Modifiers {
+            mac_cmd: true,
+            command: true,
+            ..egui::Modifiers::NONE
+        };
+
+        assert_eq!(keymap.lookup(egui::Key::Z, ctrl), Some(Action::Undo));
+        assert_eq!(keymap.lookup(egui::Key::Z, cmd), Some(Action::Undo));
+        assert_eq!(keymap.lookup(egui::Key::Z, cmd | egui::Modifiers::SHIFT), Some(Action::Redo));
+        assert_eq!(keymap.lookup(egui::Key::Z, egui::Modifiers::NONE), None);
+    }
+}
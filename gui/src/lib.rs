@@ -2,7 +2,9 @@ mod common;
 mod fmt;
 mod icon;
 mod interp;
+mod keymap;
 mod panes;
+mod session;
 mod style;
 mod wgpu_backend;
 mod widgets;
@@ -55,9 +57,90 @@ pub enum WinitEvent {
 /// Global UI events.
 pub enum UIEvent {
     BinaryRequested(std::path::PathBuf),
-    BinaryFailed(processor::Error),
-    BinaryLoaded(processor::Processor),
+    /// Binary without a header, loaded as code of the given architecture.
+    RawRequested(std::path::PathBuf, processor::RawOptions),
+    /// Object at the given index of an archive or fat Mach-O.
+    MemberRequested(std::path::PathBuf, usize),
+    /// Load of the given id failed.
+    BinaryFailed(usize, processor::Error),
+    /// Load of the given id failed as the binary doesn't have a known header.
+    BinaryUnrecognized(usize, std::path::PathBuf),
+    /// Load of the given id stopped as the binary is an archive or fat Mach-O holding the given
+    /// objects.
+    BinaryContainer(usize, std::path::PathBuf, Vec<String>),
+    BinaryLoaded(usize, processor::Processor),
+    ProjectRequested(std::path::PathBuf),
+    /// Binary of a project together with the block boundaries it stored.
+    ProjectLoaded(usize, processor::Processor, Vec<usize>),
     GotoAddr(usize),
+    OpenListing(usize),
+    /// Show where a function the given binary imports is defined in the library exporting it.
+    FollowImport(Arc<processor::Processor>, processor::Import),
+    /// Library loaded to follow an import or the debugged process into, together with it's
+    /// block boundaries and the address to show.
+    LibraryLoaded(Arc<processor::Processor>, Vec<usize>, usize),
+    GotoHex(usize),
+    /// Show a frame of the call stack in the listing and the source.
+    GotoFrame(usize),
+    /// Show the control flow graph of the function containing an address.
+    ShowFlowGraph(usize),
+    /// Show the pseudocode of the function containing an address.
+    ShowPseudocode(usize),
+    /// An instruction was selected in the listing, which other panes follow.
+    AddrSelected(usize),
+    /// Emulate from an address with the given initial registers.
+    Emulate(usize, Vec<(String, u64)>),
+    BreakpointsChanged,
+    AnnotationsChanged,
+    /// Bytes of the binary were patched, so code has to be shown as re-decoded.
+    BinaryPatched,
+    /// A section was toggled being decoded as code.
+    SectionsChanged,
+    DebuggerStopped(usize, debugger::Stop, Option<debugger::Registers>, Vec<debugger::Frame>),
+    /// Threads of the stopped process, sent before it's stop.
+    ThreadsListed(usize, Vec<debugger::Thread>),
+    /// Another thread of the stopped process is being inspected.
+    ThreadSelected(usize, Option<debugger::Registers>, Vec<debugger::Frame>),
+    SelectThread(u32),
+    /// The stopped process is shown against the binary it exec'd, which is now loaded.
+    DebuggerReloaded(usize, Option<debugger::Registers>, Vec<debugger::Frame>),
+    DebuggerFailed(usize),
+    SyscallTraced(usize, debugger::Syscall),
+    /// Shared libraries loaded into the process changed to the given ones.
+    LibrariesChanged(usize, Vec<debugger::Library>),
+    /// Instructions the process executed whilst recording, sent on each stop.
+    TraceRecorded(usize, Arc<debugger::Trace>),
+    /// Write the recorded instructions to a text or JSON file.
+    ExportTrace,
+    /// Message of a breakpoint that logs instead of stopping.
+    BreakpointLogged(usize, String),
+    /// Change the memory or a register of the stopped process.
+    WriteProcess(session::Edit),
+    /// Undo the last change to the stopped process since it stopped.
+    UndoWrite,
+    /// Memory or a register of the stopped process changed, leaving the given number of
+    /// changes that can be undone.
+    ProcessWritten(usize, Option<debugger::Registers>, Vec<debugger::Frame>, usize),
+    /// Changing the stopped process failed for the given reason.
+    WriteFailed(usize, String),
+    /// Text the started process wrote to one of it's streams.
+    ProcessOutput(usize, session::Stream, String),
+    /// Text typed for the started process' stdin.
+    SendInput(String),
+    /// Close the started process' stdin.
+    CloseInput,
+    DumpMemory(debugger::MemoryRegion),
+}
+
+/// How a requested binary is parsed.
+enum BinaryKind {
+    Object,
+    /// Image of a dyld shared cache, or member of an archive or fat Mach-O, by it's name.
+    Image(String),
+    /// Member of an archive or fat Mach-O by it's index.
+    Member(usize),
+    /// File without a header.
+    Raw(processor::RawOptions),
 }
 
 #[derive(Clone)]
@@ -137,23 +220,93 @@ impl UI {
 
     pub fn process_args(&mut self) {
         if let Some(path) = commands::ARGS.path.as_ref().cloned() {
-            self.offload_binary_processing(path);
+            let args = &commands::ARGS;
+            if args.raw {
+                let arch = args.arch.as_deref().unwrap_or_default();
+                match processor::RawOptions::parse(arch, args.base, args.big_endian) {
+                    Some(options) => self.offload_binary_processing(path, BinaryKind::Raw(options)),
+                    // Let the user pick from the architectures that are supported.
+                    None => self.panels.ask_for_raw_options(path),
+                }
+            } else if let Some(image) = args.image.clone() {
+                self.offload_binary_processing(path, BinaryKind::Image(image));
+            } else {
+                self.offload_binary_processing(path, BinaryKind::Object);
+            }
+        }
+
+        if let Some(pid) = commands::ARGS.attach {
+            self.panels.request_attach(pid);
+        }
+
+        if let Some(addr) = commands::ARGS.connect.clone() {
+            self.panels.request_connect(addr);
+        }
+
+        if let Some(path) = commands::ARGS.core.clone() {
+            self.panels.request_core(path);
         }
     }
 
-    fn offload_binary_processing(&mut self, path: std::path::PathBuf) {
-        // don't load multiple binaries at a time
+    /// Load the binary at `path` as the given `kind` of binary.
+    fn offload_binary_processing(&mut self, path: std::path::PathBuf, kind: BinaryKind) {
+        // The binary being loaded is replaced, together with whatever was waiting on it.
         if self.panels.is_loading() {
-            return;
+            self.panels.cancel_attach();
         }
 
-        self.panels.start_loading();
+        let loading = self.panels.start_loading();
         let ui_queue = self.ui_queue.clone();
 
         std::thread::spawn(move || {
-            match processor::Processor::parse(&path) {
-                Ok(diss) => ui_queue.push(UIEvent::BinaryLoaded(diss)),
-                Err(err) => ui_queue.push(UIEvent::BinaryFailed(err)),
+            let processor = match kind {
+                BinaryKind::Object => processor::Processor::parse_with(&path, &loading),
+                BinaryKind::Image(image) => {
+                    processor::Processor::parse_image_with(&path, &image, &loading)
+                }
+                BinaryKind::Member(idx) => {
+                    processor::Processor::parse_member_with(&path, idx, &loading)
+                }
+                BinaryKind::Raw(options) => {
+                    processor::Processor::parse_raw_with(&path, &options, &loading)
+                }
+            };
+
+            match processor {
+                Ok(diss) => ui_queue.push(UIEvent::BinaryLoaded(loading.id(), diss)),
+                Err(processor::Error::UnknownFormat) => {
+                    ui_queue.push(UIEvent::BinaryUnrecognized(loading.id(), path))
+                }
+                Err(processor::Error::Container(members)) => {
+                    ui_queue.push(UIEvent::BinaryContainer(loading.id(), path, members))
+                }
+                Err(err) => ui_queue.push(UIEvent::BinaryFailed(loading.id(), err)),
+            };
+        });
+    }
+
+    fn offload_project_processing(&mut self, path: std::path::PathBuf) {
+        if self.panels.is_loading() {
+            self.panels.cancel_attach();
+        }
+
+        let loading = self.panels.start_loading();
+        let ui_queue = self.ui_queue.clone();
+
+        std::thread::spawn(move || {
+            let load = || -> Result<_, processor::Error> {
+                let project = processor::Project::load(&path).map_err(processor::Error::Project)?;
+                let processor = processor::Processor::parse_with(&project.binary, &loading)?;
+                processor.apply_project(&project)?;
+                Ok((processor, project.boundaries))
+            };
+
+            let id = loading.id();
+            match load() {
+                Ok((diss, boundaries)) => {
+                    ui_queue.push(UIEvent::ProjectLoaded(id, diss, boundaries))
+                }
+                Err(err) => ui_queue.push(UIEvent::BinaryFailed(id, err)),
             };
         });
     }
@@ -163,6 +316,16 @@ impl UI {
         while let Ok(event) = self.arch.menu_channel.try_recv() {
             match event.id.0.as_str() {
                 "open" => self.panels.ask_for_binary(),
+                "open_raw" => self.panels.ask_for_raw_binary(),
+                "open_core" => self.panels.ask_for_core(),
+                "open_project" => self.panels.ask_for_project(),
+                "save_project" => self.panels.save_project(),
+                "import_coverage" => self.panels.ask_for_coverage(),
+                "export_listing" => self.panels.export_listing(),
+                "save_patched" => self.panels.save_patched_binary(),
+                "save_unpacked" => self.panels.save_unpacked_binary(),
+                "close_binary" => self.panels.close_shown_binary(),
+                "preferences" => self.panels.open_preferences(),
                 panes::SOURCE => {
                     self.panels.goto_window(panes::SOURCE);
                     self.arch.bar.set_checked(panes::SOURCE);
@@ -185,26 +348,112 @@ impl UI {
 
         while let Some(event) = self.ui_queue.inner.pop() {
             match event {
-                UIEvent::BinaryFailed(err) => {
-                    self.panels.stop_loading();
-                    log::warning!("{err:?}");
+                UIEvent::BinaryFailed(id, err) => {
+                    // Loads that were replaced are cancelled, so they aren't reported.
+                    if self.panels.stop_loading(id) {
+                        self.panels.cancel_attach();
+                        log::warning!("{err:?}");
+                    }
+                }
+                UIEvent::BinaryUnrecognized(id, path) => {
+                    if self.panels.stop_loading(id) {
+                        self.panels.cancel_attach();
+                        self.panels.ask_for_raw_options(path);
+                    }
+                }
+                UIEvent::BinaryContainer(id, path, members) => {
+                    if self.panels.stop_loading(id) {
+                        self.panels.cancel_attach();
+                        self.panels.ask_for_member(path, members);
+                    }
                 }
                 UIEvent::BinaryRequested(path) => {
-                    self.offload_binary_processing(path);
+                    self.offload_binary_processing(path, BinaryKind::Object);
                 }
-                UIEvent::BinaryLoaded(disassembly) => {
+                UIEvent::RawRequested(path, options) => {
+                    self.offload_binary_processing(path, BinaryKind::Raw(options));
+                }
+                UIEvent::MemberRequested(path, idx) => {
+                    self.offload_binary_processing(path, BinaryKind::Member(idx));
+                }
+                UIEvent::BinaryLoaded(id, disassembly) => {
+                    if !self.panels.stop_loading(id) {
+                        continue;
+                    }
+
                     #[cfg(target_os = "macos")]
                     self.arch.bar.set_path(&disassembly.path);
 
-                    self.panels.stop_loading();
-                    self.panels.load_binary(disassembly);
+                    self.panels.load_binary(disassembly, None);
+                }
+                UIEvent::ProjectRequested(path) => {
+                    self.offload_project_processing(path);
+                }
+                UIEvent::ProjectLoaded(id, disassembly, boundaries) => {
+                    if !self.panels.stop_loading(id) {
+                        continue;
+                    }
+
+                    #[cfg(target_os = "macos")]
+                    self.arch.bar.set_path(&disassembly.path);
+
+                    self.panels.load_binary(disassembly, Some(boundaries));
                 }
                 UIEvent::GotoAddr(addr) => {
                     if let Some(listing) = self.panels.listing() {
-                        listing.jump(addr);
-                        self.panels.goto_window(panes::DISASSEMBLY);
+                        if !listing.jump(addr) {
+                            listing.jump_near(addr);
+                        }
+                        let focused = self.panels.focused_listing();
+                        self.panels.goto_window(focused);
                     }
                 }
+                UIEvent::OpenListing(addr) => self.panels.open_listing(addr),
+                UIEvent::FollowImport(binary, import) => self.panels.follow_import(binary, import),
+                UIEvent::LibraryLoaded(library, boundaries, addr) => {
+                    self.panels.library_loaded(library, boundaries, addr)
+                }
+                UIEvent::GotoHex(addr) => self.panels.goto_hex(addr),
+                UIEvent::GotoFrame(addr) => self.panels.goto_frame(addr),
+                UIEvent::ShowFlowGraph(addr) => self.panels.show_flow_graph(addr),
+                UIEvent::ShowPseudocode(addr) => self.panels.show_pseudocode(addr),
+                UIEvent::AddrSelected(addr) => self.panels.addr_selected(addr),
+                UIEvent::Emulate(addr, state) => self.panels.emulate(addr, &state),
+                UIEvent::BreakpointsChanged => self.panels.breakpoints_changed(),
+                UIEvent::AnnotationsChanged => self.panels.annotations_changed(),
+                UIEvent::BinaryPatched => self.panels.binary_patched(),
+                UIEvent::SectionsChanged => self.panels.sections_changed(),
+                UIEvent::DebuggerStopped(id, stop, registers, frames) => {
+                    self.panels.debugger_stopped(id, stop, registers, frames)
+                }
+                UIEvent::ThreadsListed(id, threads) => self.panels.threads_listed(id, threads),
+                UIEvent::ThreadSelected(id, registers, frames) => {
+                    self.panels.thread_selected(id, registers, frames)
+                }
+                UIEvent::SelectThread(tid) => self.panels.select_thread(tid),
+                UIEvent::DebuggerReloaded(id, registers, frames) => {
+                    self.panels.debugger_reloaded(id, registers, frames)
+                }
+                UIEvent::DebuggerFailed(id) => self.panels.debugger_failed(id),
+                UIEvent::SyscallTraced(id, syscall) => self.panels.syscall_traced(id, syscall),
+                UIEvent::LibrariesChanged(id, libraries) => {
+                    self.panels.libraries_changed(id, libraries)
+                }
+                UIEvent::TraceRecorded(id, trace) => self.panels.trace_recorded(id, trace),
+                UIEvent::ExportTrace => self.panels.export_trace(),
+                UIEvent::BreakpointLogged(id, msg) => self.panels.breakpoint_logged(id, msg),
+                UIEvent::WriteProcess(edit) => self.panels.write_process(edit),
+                UIEvent::UndoWrite => self.panels.undo_write(),
+                UIEvent::ProcessWritten(id, registers, frames, undoable) => {
+                    self.panels.process_written(id, registers, frames, undoable)
+                }
+                UIEvent::WriteFailed(id, msg) => self.panels.write_failed(id, msg),
+                UIEvent::ProcessOutput(id, stream, text) => {
+                    self.panels.process_output(id, stream, text)
+                }
+                UIEvent::SendInput(text) => self.panels.send_input(text),
+                UIEvent::CloseInput => self.panels.close_input(),
+                UIEvent::DumpMemory(region) => self.panels.dump_memory(region),
             }
         }
     }
@@ -250,7 +499,9 @@ impl UI {
                         self.instance.resize(size.width, size.height);
                         self.window.request_redraw();
                     }
-                    WindowEvent::DroppedFile(path) => self.offload_binary_processing(path),
+                    WindowEvent::DroppedFile(path) => {
+                        self.offload_binary_processing(path, BinaryKind::Object)
+                    }
                     WindowEvent::CloseRequested => target.exit(),
                     _ => {}
                 },
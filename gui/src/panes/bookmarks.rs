@@ -0,0 +1,85 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+pub struct Bookmarks {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+}
+
+impl Bookmarks {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+        }
+    }
+}
+
+fn tokenize_bookmark(processor: &Processor, addr: usize, label: &str) -> Vec<Token> {
+    let mut tokens = vec![
+        Token::from_string(format!("{addr:0>10X}"), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+    ];
+
+    if !label.is_empty() {
+        tokens.push(Token::from_string(label.to_string(), Kind::Plain));
+        tokens.push(Token::from_str(" in ", Kind::Muted));
+    }
+
+    match processor.index.get_sym_containing(addr) {
+        Some((start, sym)) => {
+            let sym = processor.shown_symbol(start, sym);
+            tokens.extend(sym.name().iter().cloned());
+            if addr != start {
+                tokens.push(Token::from_string(
+                    format!("+{:#x}", addr - start),
+                    Kind::Immediate,
+                ));
+            }
+        }
+        None => tokens.push(Token::from_str("??", Kind::Comment)),
+    }
+
+    tokens
+}
+
+impl Display for Bookmarks {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        // Bookmarks are few, so they're read again every frame to show changes right away.
+        let bookmarks = self.processor.annotations.read().unwrap().bookmarks.clone();
+
+        if bookmarks.is_empty() {
+            let text = "No bookmarks, add one through the context menu of the listing.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let mut removed = None;
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            for (&addr, label) in &bookmarks {
+                let tokens = tokenize_bookmark(&self.processor, addr, label);
+                let response = ui.link(tokens_to_layoutjob(tokens));
+
+                if response.clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(addr));
+                }
+
+                response.context_menu(|ui| {
+                    if ui.button("Remove bookmark").clicked() {
+                        removed = Some(addr);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if let Some(addr) = removed {
+            self.processor.remove_bookmark(addr);
+            self.ui_queue.push(UIEvent::AnnotationsChanged);
+        }
+    }
+}
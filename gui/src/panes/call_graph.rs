@@ -0,0 +1,113 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use processor::{CallGraph, Processor};
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+/// Most functions to list at the top level, the rest can be found by filtering.
+const MAX_ROOTS: usize = 500;
+
+pub struct CallGraphView {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    graph: CallGraph,
+    filter: String,
+    /// Whether to expand into callers instead of callees.
+    show_callers: bool,
+}
+
+impl CallGraphView {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let graph = processor.call_graph();
+
+        Self {
+            processor,
+            ui_queue,
+            graph,
+            filter: String::new(),
+            show_callers: false,
+        }
+    }
+
+    fn edges(&self, func: usize) -> &[usize] {
+        if self.show_callers {
+            self.graph.callers(func)
+        } else {
+            self.graph.callees(func)
+        }
+    }
+
+    fn function_name(&self, func: usize) -> egui::text::LayoutJob {
+        let sym = &self.processor.index.syms[func];
+        let mut tokens = Vec::new();
+        tokens.push(Token::from_string(format!("{:0>10X}", sym.addr), Kind::Address));
+        tokens.push(Token::from_str(" | ", Kind::Plain));
+        tokens.extend(sym.item.name().iter().cloned());
+        tokens_to_layoutjob(tokens)
+    }
+
+    /// Show `func`, lazily expanding into it's edges as the header is opened.
+    fn show_node(&self, ui: &mut egui::Ui, func: usize) {
+        let addr = self.processor.index.syms[func].addr;
+
+        if self.edges(func).is_empty() {
+            if ui.link(self.function_name(func)).clicked() {
+                self.ui_queue.push(UIEvent::GotoAddr(addr));
+            }
+            return;
+        }
+
+        let response = egui::CollapsingHeader::new(self.function_name(func))
+            .id_source(func)
+            .show(ui, |ui| {
+                for &edge in self.edges(func) {
+                    self.show_node(ui, edge);
+                }
+            });
+
+        let header = response.header_response.on_hover_text("double click to go to function");
+        if header.double_clicked() {
+            self.ui_queue.push(UIEvent::GotoAddr(addr));
+        }
+    }
+}
+
+impl Display for CallGraphView {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("filter").font(font()));
+            ui.selectable_value(&mut self.show_callers, false, "callees");
+            ui.selectable_value(&mut self.show_callers, true, "callers");
+
+            if ui.button("refresh").clicked() {
+                self.graph = self.processor.call_graph();
+            }
+        });
+
+        let filter = self.filter.to_lowercase();
+        let roots = (0..self.graph.len()).filter(|&func| {
+            let sym = &self.processor.index.syms[func].item;
+            !sym.intrinsic()
+                && !self.edges(func).is_empty()
+                && sym.as_str().to_lowercase().contains(&filter)
+        });
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            let mut shown = 0;
+            for func in roots {
+                if shown == MAX_ROOTS {
+                    ui.label(
+                        egui::RichText::new("more functions, use the filter to find them…")
+                            .font(font())
+                            .color(theme().muted),
+                    );
+                    break;
+                }
+
+                self.show_node(ui, func);
+                shown += 1;
+            }
+        });
+    }
+}
@@ -0,0 +1,93 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use debugger::Frame;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+pub struct CallStack {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Frames at the last stop, innermost first.
+    frames: Vec<Frame>,
+}
+
+impl CallStack {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, frames: Vec<Frame>) {
+        self.frames = frames;
+    }
+
+    /// Frames at the last stop, innermost first.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+}
+
+/// Address used for looking up a frame, return addresses are past the call made.
+fn frame_addr(idx: usize, frame: &Frame) -> usize {
+    match idx {
+        0 => frame.pc,
+        _ => frame.pc.wrapping_sub(1),
+    }
+}
+
+fn tokenize_frame(processor: &Processor, idx: usize, frame: &Frame) -> Vec<Token> {
+    let addr = frame_addr(idx, frame);
+    let mut tokens = vec![
+        Token::from_string(format!("#{idx:<3}"), Kind::Muted),
+        Token::from_string(format!("{:0>10X}", frame.pc), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+    ];
+
+    match processor.index.get_sym_containing(addr) {
+        Some((start, sym)) => {
+            tokens.extend(sym.name().iter().cloned());
+            if frame.pc != start {
+                tokens.push(Token::from_string(
+                    format!("+{:#x}", frame.pc - start),
+                    Kind::Immediate,
+                ));
+            }
+        }
+        None => tokens.push(Token::from_str("??", Kind::Comment)),
+    }
+
+    if let Some(file_attr) = processor.index.get_file_containing(addr) {
+        let file = file_attr.path.file_name().unwrap_or_default().to_string_lossy();
+        tokens.push(Token::from_string(
+            format!(" at {file}:{}", file_attr.line),
+            Kind::Comment,
+        ));
+    }
+
+    tokens
+}
+
+impl Display for CallStack {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.frames.is_empty() {
+            let text = "No process is being debugged, start one with `run`.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            for (idx, frame) in self.frames.iter().enumerate() {
+                let tokens = tokenize_frame(&self.processor, idx, frame);
+                let link = ui.link(tokens_to_layoutjob(tokens));
+                if link.on_hover_text("show in the listing and source").clicked() {
+                    self.ui_queue.push(UIEvent::GotoFrame(frame_addr(idx, frame)));
+                }
+            }
+        });
+    }
+}
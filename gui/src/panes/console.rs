@@ -0,0 +1,140 @@
+use crate::common::*;
+use crate::session::Stream;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokenizing::theme;
+
+/// Most lines kept around, older ones are dropped first.
+const MAX_LINES: usize = 100_000;
+
+/// Output of the process started by the debugger, along with a line for typing it's input.
+pub struct Console {
+    ui_queue: Arc<UiQueue>,
+    /// Session of the process whose output is shown.
+    session: Option<usize>,
+    /// Lines along with the stream they were written to.
+    lines: VecDeque<(Stream, String)>,
+    /// Whether the last line wasn't ended yet, so output continues it.
+    open: bool,
+    input: String,
+}
+
+impl Console {
+    pub fn new(ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            ui_queue,
+            session: None,
+            lines: VecDeque::new(),
+            open: false,
+            input: String::new(),
+        }
+    }
+
+    /// Show the output of the process of session `id` from now on.
+    pub fn start(&mut self, id: usize) {
+        self.session = Some(id);
+        self.lines.clear();
+        self.open = false;
+    }
+
+    /// Session of the process whose output is shown.
+    pub fn session(&self) -> Option<usize> {
+        self.session
+    }
+
+    pub fn push(&mut self, stream: Stream, text: &str) {
+        let mut parts = text.split('\n').peekable();
+        while let Some(part) = parts.next() {
+            let ended = parts.peek().is_some();
+            let part = match ended {
+                true => part.strip_suffix('\r').unwrap_or(part),
+                false => part,
+            };
+
+            match self.lines.back_mut() {
+                // Output of both streams is interleaved a line at a time.
+                Some((last, line)) if self.open && *last == stream => line.push_str(part),
+                _ if part.is_empty() && !ended => {}
+                _ => {
+                    if self.lines.len() == MAX_LINES {
+                        self.lines.pop_front();
+                    }
+
+                    self.lines.push_back((stream, part.to_string()));
+                }
+            }
+
+            if ended {
+                self.open = false;
+            } else if !part.is_empty() {
+                self.open = true;
+            }
+        }
+    }
+}
+
+fn stream_color(stream: Stream) -> egui::Color32 {
+    match stream {
+        Stream::Stdin => theme().muted,
+        Stream::Stdout => theme().plain,
+        Stream::Stderr => CONFIG.colors.role_color("invalid"),
+    }
+}
+
+impl Display for Console {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+        egui::TopBottomPanel::bottom("console input").show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                let input = egui::TextEdit::singleline(&mut self.input)
+                    .font(font())
+                    .hint_text("input sent to the process on enter")
+                    .desired_width(ui.available_width() - 200.0);
+                let response = ui.add(input);
+
+                let entered =
+                    response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if entered || ui.button("Send").clicked() {
+                    let line = std::mem::take(&mut self.input) + "\n";
+                    self.ui_queue.push(UIEvent::SendInput(line));
+                    response.request_focus();
+                }
+
+                let button = ui.button("Close input");
+                if button.on_hover_text("Send the end of the input").clicked() {
+                    self.ui_queue.push(UIEvent::CloseInput);
+                }
+
+                if ui.button("Clear").clicked() {
+                    self.lines.clear();
+                    self.open = false;
+                }
+            });
+        });
+
+        if self.lines.is_empty() {
+            let text = match self.session {
+                Some(_) => "The process hasn't written anything yet.",
+                None => "No output, processes started with `run` show their output here.",
+            };
+
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let area = egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .drag_to_scroll(false)
+            .stick_to_bottom(true);
+
+        area.show_rows(ui, row_height, self.lines.len(), |ui, row_range| {
+            for (stream, line) in self.lines.range(row_range) {
+                let text = egui::RichText::new(line).font(font()).color(stream_color(*stream));
+                ui.label(text);
+            }
+        });
+    }
+}
@@ -0,0 +1,89 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use processor::{Emulation as Emulated, Processor};
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+/// Most instructions emulated at once.
+const MAX_STEPS: usize = 10_000;
+
+/// Registers after each instruction emulated from an address in the listing.
+pub struct Emulation {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    emulated: Option<Emulated>,
+}
+
+impl Emulation {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            emulated: None,
+        }
+    }
+
+    /// Emulate from `addr` with the registers in `state`, returns whether the binary's
+    /// architecture can be emulated.
+    pub fn emulate(&mut self, addr: usize, state: &[(String, u64)]) -> bool {
+        self.emulated = self.processor.emulate(addr, state, MAX_STEPS);
+        self.emulated.is_some()
+    }
+}
+
+fn tokenize_registers(registers: &[(&'static str, Option<u64>)]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (idx, &(reg, value)) in registers.iter().enumerate() {
+        if idx != 0 {
+            tokens.push(Token::from_str(", ", Kind::Delimiter));
+        }
+
+        tokens.push(Token::from_str(reg, Kind::Register));
+        tokens.push(Token::from_str(" = ", Kind::Plain));
+        tokens.push(match value {
+            Some(value) => Token::from_string(format!("{value:#x}"), Kind::Immediate),
+            None => Token::from_str("?", Kind::Muted),
+        });
+    }
+
+    tokens
+}
+
+impl Display for Emulation {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let emulated = match self.emulated {
+            Some(ref emulated) => emulated,
+            None => {
+                let text = "Nothing emulated, right click an instruction to emulate from it.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        let text = format!(
+            "{} instructions emulated from {:#x}, stopped as {}",
+            emulated.steps.len(),
+            emulated.start,
+            emulated.stop
+        );
+        ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+        area.show_rows(ui, row_height, emulated.steps.len(), |ui, row_range| {
+            for step in &emulated.steps[row_range] {
+                ui.horizontal(|ui| {
+                    let addr = egui::RichText::new(format!("{:#x}", step.addr)).font(font());
+                    if ui.link(addr).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(step.addr));
+                    }
+
+                    let text = format!("{:<40}", step.text);
+                    ui.label(egui::RichText::new(text).font(font()).color(theme().plain));
+                    ui.label(tokens_to_layoutjob(tokenize_registers(&step.registers)));
+                });
+            }
+        });
+    }
+}
@@ -0,0 +1,253 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use config::CONFIG;
+use processor::{ControlFlowGraph, Coverage, EdgeKind, Processor};
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+/// Space between blocks in the same layer and between layers.
+const SPACING: egui::Vec2 = egui::vec2(40.0, 60.0);
+
+/// Space between a block's border and it's text.
+const PADDING: f32 = 6.0;
+
+/// Length of an edge's arrow head.
+const ARROW: f32 = 8.0;
+
+/// A function's control flow graph, with it's blocks laid out once the text is measured.
+struct Function {
+    addr: usize,
+    name: Vec<Token>,
+    graph: ControlFlowGraph,
+    /// Tokens of each block.
+    tokens: Vec<Vec<Token>>,
+    /// Where each block is drawn, relative to the top left of the graph.
+    rects: Vec<egui::Rect>,
+}
+
+/// Basic blocks of a function, connected by the edges between them.
+pub struct FlowGraph {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    func: Option<Function>,
+    /// Instructions executed according to the last imported coverage file.
+    coverage: Option<Arc<Coverage>>,
+}
+
+impl FlowGraph {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            func: None,
+            coverage: None,
+        }
+    }
+
+    /// Color the blocks that were covered.
+    pub fn set_coverage(&mut self, coverage: Arc<Coverage>) {
+        self.coverage = Some(coverage);
+    }
+
+    /// Show the function starting at `addr`, returns whether it has any instructions.
+    pub fn show_function(&mut self, addr: usize) -> bool {
+        let graph = match self.processor.cfg(addr) {
+            Some(graph) => graph,
+            None => return false,
+        };
+
+        let name = match self.processor.index.get_sym_by_addr(addr) {
+            Some(sym) => self.processor.shown_symbol(addr, sym).name().to_vec(),
+            None => vec![Token::from_string(format!("{addr:#x}"), Kind::Address)],
+        };
+
+        let tokens = graph
+            .blocks
+            .iter()
+            .map(|block| self.processor.basic_block_tokens(block))
+            .collect();
+
+        self.func = Some(Function {
+            addr,
+            name,
+            graph,
+            tokens,
+            rects: Vec::new(),
+        });
+
+        true
+    }
+}
+
+/// Layer of each block, such that edges that don't loop back always go down.
+fn layers(graph: &ControlFlowGraph) -> Vec<usize> {
+    let mut layers = vec![0; graph.blocks.len()];
+
+    // Blocks are sorted by address, so all but loops point to blocks further on.
+    for edge in &graph.edges {
+        if edge.to > edge.from {
+            layers[edge.to] = layers[edge.to].max(layers[edge.from] + 1);
+        }
+    }
+
+    layers
+}
+
+/// Place blocks of `sizes` in their layer, centering each layer.
+fn layout(graph: &ControlFlowGraph, sizes: &[egui::Vec2]) -> Vec<egui::Rect> {
+    let layers = layers(graph);
+    let count = layers.iter().max().map_or(0, |max| max + 1);
+
+    let mut widths = vec![0.0f32; count];
+    let mut heights = vec![0.0f32; count];
+    for (&layer, size) in layers.iter().zip(sizes) {
+        if widths[layer] != 0.0 {
+            widths[layer] += SPACING.x;
+        }
+        widths[layer] += size.x;
+        heights[layer] = heights[layer].max(size.y);
+    }
+
+    let max_width = widths.iter().copied().fold(0.0, f32::max);
+    let mut tops = Vec::with_capacity(count);
+    let mut top = 0.0;
+    for height in &heights {
+        tops.push(top);
+        top += height + SPACING.y;
+    }
+
+    let mut lefts: Vec<f32> = widths.iter().map(|width| (max_width - width) / 2.0).collect();
+    let mut rects = Vec::with_capacity(sizes.len());
+    for (&layer, &size) in layers.iter().zip(sizes) {
+        let min = egui::pos2(lefts[layer], tops[layer]);
+        rects.push(egui::Rect::from_min_size(min, size));
+        lefts[layer] += size.x + SPACING.x;
+    }
+
+    rects
+}
+
+fn edge_color(kind: EdgeKind) -> egui::Color32 {
+    match kind {
+        EdgeKind::True => theme().segment,
+        EdgeKind::False => theme().invalid,
+        EdgeKind::Always => theme().text,
+    }
+}
+
+/// Draw an edge between two blocks, loops going around the right of the graph.
+fn paint_edge(
+    painter: &egui::Painter,
+    from: egui::Rect,
+    to: egui::Rect,
+    right: f32,
+    kind: EdgeKind,
+) {
+    let stroke = egui::Stroke::new(1.5, edge_color(kind));
+    let start = from.center_bottom();
+    let end = to.center_top() - egui::vec2(0.0, ARROW);
+
+    let points = if to.min.y > from.max.y {
+        let bend = to.min.y - SPACING.y / 2.0;
+        vec![start, egui::pos2(start.x, bend), egui::pos2(end.x, bend), end]
+    } else {
+        let below = start.y + SPACING.y / 4.0;
+        let above = to.min.y - SPACING.y / 4.0;
+        vec![
+            start,
+            egui::pos2(start.x, below),
+            egui::pos2(right, below),
+            egui::pos2(right, above),
+            egui::pos2(end.x, above),
+            end,
+        ]
+    };
+
+    painter.add(egui::Shape::line(points, stroke));
+    painter.arrow(end, egui::vec2(0.0, ARROW), stroke);
+}
+
+impl Display for FlowGraph {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let func = match self.func {
+            Some(ref mut func) => func,
+            None => {
+                let text = "No function shown, pick `Show flow graph` on an instruction's menu.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(tokens_to_layoutjob(func.name.clone()));
+            if ui.button("refresh").clicked() {
+                if let Some(graph) = self.processor.cfg(func.addr) {
+                    func.tokens = graph
+                        .blocks
+                        .iter()
+                        .map(|block| self.processor.basic_block_tokens(block))
+                        .collect();
+                    func.graph = graph;
+                    func.rects.clear();
+                }
+            }
+        });
+
+        let galleys: Vec<_> = func
+            .tokens
+            .iter()
+            .map(|tokens| ui.fonts(|fonts| fonts.layout_job(tokens_to_layoutjob(tokens.clone()))))
+            .collect();
+
+        if func.rects.is_empty() {
+            let sizes: Vec<egui::Vec2> = galleys
+                .iter()
+                .map(|galley| galley.size() + egui::vec2(PADDING, PADDING) * 2.0)
+                .collect();
+
+            func.rects = layout(&func.graph, &sizes);
+        }
+
+        // Room on the right for loops to go around the blocks.
+        let size = func.rects.iter().fold(egui::Vec2::ZERO, |size, rect| {
+            size.max(rect.max.to_vec2())
+        }) + egui::vec2(SPACING.x, ARROW);
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(true);
+        area.show(ui, |ui| {
+            let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+            let origin = response.rect.min.to_vec2();
+            let right = origin.x + size.x - SPACING.x / 2.0;
+
+            for edge in &func.graph.edges {
+                let from = func.rects[edge.from].translate(origin);
+                let to = func.rects[edge.to].translate(origin);
+                paint_edge(&painter, from, to, right, edge.kind);
+            }
+
+            for (idx, (rect, galley)) in func.rects.iter().zip(galleys).enumerate() {
+                let block = &func.graph.blocks[idx];
+                let covered = self.coverage.as_ref().is_some_and(|coverage| {
+                    block.instructions.iter().any(|&addr| coverage.contains(addr))
+                });
+
+                let fill = match covered {
+                    true => CONFIG.colors.role_color("covered").linear_multiply(0.25),
+                    false => theme().bg_secondary,
+                };
+
+                let rect = rect.translate(origin);
+                let border = egui::Stroke::new(1.0, theme().muted);
+                painter.rect(rect, 2.0, fill, border);
+                painter.galley(rect.min + egui::vec2(PADDING, PADDING), galley, theme().plain);
+
+                let id = ui.id().with(block.start());
+                let response = ui.interact(rect, id, egui::Sense::click());
+                if response.on_hover_text("double click to go to block").double_clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(block.start()));
+                }
+            }
+        });
+    }
+}
+
@@ -1,23 +1,77 @@
 use crate::common::*;
+use crate::style::STYLE;
 use crate::{UiQueue, UIEvent};
-use config::CONFIG;
+use debugvault::fuzzy::FuzzyMatch;
+use debugvault::{FoldGenerics, Symbol};
 use processor_shared::Addressed;
-use processor::Processor;
-use std::sync::Arc;
-use tokenizing::{colors, Token};
+use processor::{Coverage, Processor};
+use std::sync::{Arc, Mutex};
+use tokenizing::{theme, Kind, Token};
+
+/// Most matches shown for a search.
+const MAX_MATCHES: usize = 1000;
+
+/// Generic arguments longer than this are folded unless they're expanded.
+const MAX_GENERICS_LEN: usize = 24;
+
+/// A search running in the background.
+struct Search {
+    query: String,
+    results: Arc<Mutex<Option<Vec<FuzzyMatch>>>>,
+}
+
+impl Search {
+    fn start(processor: &Arc<Processor>, query: &str) -> Self {
+        let results = Arc::new(Mutex::new(None));
+
+        {
+            let processor = Arc::clone(processor);
+            let results = Arc::clone(&results);
+            let query = query.to_string();
+            std::thread::spawn(move || {
+                let matches = processor.fuzzy_search(&query, MAX_MATCHES);
+                *results.lock().unwrap() = Some(matches);
+            });
+        }
+
+        Self {
+            query: query.to_string(),
+            results,
+        }
+    }
+}
+
+/// Line of the list of functions.
+enum Line {
+    /// Address and name of a function, along with it's full name if generics were folded.
+    Function(usize, Vec<Token>, Option<String>),
+    /// Heading of the stubs jumping to imported functions, which are listed last.
+    Imports,
+}
 
 pub struct Functions {
     processor: Arc<Processor>,
     ui_queue: Arc<UiQueue>,
-    lines: Vec<(usize, Vec<Token>)>,
+    lines: Vec<Line>,
     lines_count: usize,
     min_row: usize,
     max_row: usize,
+    query: String,
+    /// Only one search runs at a time, the next one starts once it's finished.
+    pending: Option<Search>,
+    /// Query of the last finished search together with it's matches.
+    matches: Option<(String, Vec<FuzzyMatch>)>,
+    /// Index into the matches of the one Enter jumps to.
+    selected: usize,
+    fold_generics: bool,
+    /// Instructions executed according to the last imported coverage file, shown as the
+    /// percentage of each function that was executed.
+    coverage: Option<Arc<Coverage>>,
 }
 
 impl Functions {
     pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
-        let function_count = processor.index.named_funcs_count();
+        let function_count = lines_count(&processor);
 
         Self {
             processor,
@@ -26,55 +80,302 @@ impl Functions {
             lines_count: function_count,
             min_row: 0,
             max_row: 0,
+            query: String::new(),
+            pending: None,
+            matches: None,
+            selected: 0,
+            fold_generics: true,
+            coverage: None,
+        }
+    }
+
+    pub fn set_coverage(&mut self, coverage: Arc<Coverage>) {
+        self.coverage = Some(coverage);
+        self.refresh();
+    }
+
+    /// Show renamed functions by their new name.
+    pub fn refresh(&mut self) {
+        self.min_row = 0;
+        self.max_row = 0;
+        self.matches = None;
+    }
+
+    /// Collect the results of the running search and start a new one if the query changed.
+    fn poll_search(&mut self) {
+        let finished = self.pending.as_ref().and_then(|search| {
+            let results = search.results.lock().unwrap().take()?;
+            Some((search.query.clone(), results))
+        });
+
+        if let Some(finished) = finished {
+            self.pending = None;
+            self.matches = Some(finished);
+            self.selected = 0;
+        }
+
+        let up_to_date = self.matches.as_ref().is_some_and(|(query, _)| *query == self.query);
+        if self.pending.is_none() && !self.query.is_empty() && !up_to_date {
+            self.pending = Some(Search::start(&self.processor, &self.query));
         }
     }
 }
 
-fn tokenize_functions(index: &debugvault::Index, range: std::ops::Range<usize>) -> Vec<(usize, Vec<Token>)> {
-    let mut functions = Vec::new();
-    let lines_to_read = range.end - range.start;
-    let lines = index
-        .functions()
-        .filter(|func| !func.item.intrinsic())
+fn tokenize_prefix(addr: usize, func: &Symbol) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    tokens.push(Token::from_string(format!("{addr:0>10X}"), Kind::Plain));
+    tokens.push(Token::from_str(" | ", Kind::Plain));
+
+    if let Some(module) = func.module() {
+        tokens.push(Token::from_string(module.to_string(), Kind::Component));
+        tokens.push(Token::from_str("!", Kind::Delimiter));
+    }
+
+    tokens
+}
+
+/// Number of lines listing the functions, including the heading of the imports.
+fn lines_count(processor: &Processor) -> usize {
+    let index = &processor.index;
+    index.named_funcs_count() + (index.plt_stubs_count() > 0) as usize
+}
+
+fn tokenize_functions(
+    processor: &Processor,
+    range: std::ops::Range<usize>,
+    fold: FoldGenerics,
+    coverage: Option<&Coverage>,
+) -> Vec<Line> {
+    let named = || processor.index.functions().filter(|func| !func.item.intrinsic());
+    let has_imports = processor.index.plt_stubs_count() > 0;
+
+    // Stubs jumping to imported functions are grouped after every other function.
+    let lines = named()
+        .filter(|func| !func.item.plt_stub())
+        .map(Some)
+        .chain(has_imports.then_some(None))
+        .chain(named().filter(|func| func.item.plt_stub()).map(Some))
         .skip(range.start)
-        .take(lines_to_read + 10);
+        .take(range.end - range.start + 10);
 
-    for Addressed { addr, item } in lines {
-        let mut tokens = Vec::new();
-        tokens.push(Token::from_string(format!("{addr:0>10X}"), colors::WHITE));
-        tokens.push(Token::from_str(" | ", colors::WHITE));
+    let mut functions = Vec::new();
+    for line in lines {
+        let Addressed { addr, item } = match line {
+            Some(func) => func,
+            None => {
+                functions.push(Line::Imports);
+                continue;
+            }
+        };
 
-        if let Some(module) = item.module() {
-            tokens.push(Token::from_string(module.to_string(), CONFIG.colors.asm.component));
-            tokens.push(Token::from_str("!", CONFIG.colors.delimiter));
+        let item = processor.shown_symbol(*addr, Arc::clone(item));
+        let mut tokens = tokenize_prefix(*addr, &item);
+        if let Some(coverage) = coverage {
+            let percent = match processor.function_coverage(coverage, *addr) {
+                Some((covered, total)) if total > 0 => format!(" {:>3}%", covered * 100 / total),
+                _ => "    -".to_string(),
+            };
+            tokens.insert(1, Token::from_string(percent, Kind::Immediate));
         }
 
-        for token in item.name() {
-            tokens.push(token.clone());
-        }
+        let name = item.display(fold);
+        let len = |tokens: &[Token]| tokens.iter().map(|t| t.text.len()).sum::<usize>();
+        let folded = (len(&name) != len(item.name())).then(|| item.as_str().to_string());
+        tokens.extend(name);
 
-        functions.push((*addr, tokens));
+        functions.push(Line::Function(*addr, tokens, folded));
     }
 
     functions
 }
 
+/// Layout of a match with the matched characters highlighted.
+fn match_layoutjob(m: &FuzzyMatch) -> egui::text::LayoutJob {
+    let mut job = tokens_to_layoutjob(tokenize_prefix(m.addr, &m.symbol));
+    // Matches are made against demangled names, there's nothing to highlight in mangled ones.
+    let positions = if debugvault::showing_mangled() { &[][..] } else { &m.positions[..] };
+    let mut positions = positions.iter().copied().peekable();
+    let mut offset = 0;
+    let theme = theme();
+
+    let mut append = |text: &str, color: egui::Color32, matched: bool| {
+        let background = if matched {
+            theme.highlight.linear_multiply(0.5)
+        } else {
+            egui::Color32::TRANSPARENT
+        };
+
+        job.append(
+            text,
+            0.0,
+            egui::TextFormat {
+                font_id: font(),
+                color,
+                background,
+                ..Default::default()
+            },
+        );
+    };
+
+    for token in m.symbol.name() {
+        let text = &token.text[..];
+        let mut start = 0;
+        let mut matched = false;
+
+        // Split the token into runs of either matched or unmatched characters.
+        for (idx, _) in text.char_indices() {
+            let is_match = positions.peek() == Some(&(offset + idx));
+            if is_match {
+                positions.next();
+            }
+
+            if is_match != matched {
+                append(&text[start..idx], theme.color(token.kind), matched);
+                start = idx;
+                matched = is_match;
+            }
+        }
+
+        append(&text[start..], theme.color(token.kind), matched);
+        offset += text.len();
+    }
+
+    job
+}
+
+impl Functions {
+    fn show_matches(&mut self, ui: &mut egui::Ui, enter: bool) {
+        let matches = match self.matches {
+            Some((_, ref matches)) => matches,
+            None => return,
+        };
+
+        if matches.is_empty() {
+            ui.label(egui::RichText::new("no matches").font(font()).color(theme().muted));
+            return;
+        }
+
+        let (up, down) = ui.input(|input| {
+            (input.key_pressed(egui::Key::ArrowUp), input.key_pressed(egui::Key::ArrowDown))
+        });
+
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+
+        if down {
+            self.selected = (self.selected + 1).min(matches.len() - 1);
+        }
+
+        self.selected = self.selected.min(matches.len() - 1);
+
+        if enter {
+            self.ui_queue.push(UIEvent::GotoAddr(matches[self.selected].addr));
+        }
+
+        let mut area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+        if up || down {
+            let spacing = ui.spacing().item_spacing.y;
+            let offset = self.selected.saturating_sub(5) as f32 * (row_height + spacing);
+            area = area.vertical_scroll_offset(offset);
+        }
+
+        area.show_rows(ui, row_height, matches.len(), |ui, row_range| {
+            for idx in row_range {
+                let response = ui.link(match_layoutjob(&matches[idx]));
+
+                if idx == self.selected {
+                    ui.painter().rect_filled(response.rect, 0.0, STYLE.selection_color);
+                }
+
+                if response.clicked() {
+                    self.selected = idx;
+                    self.ui_queue.push(UIEvent::GotoAddr(matches[idx].addr));
+                }
+            }
+        });
+    }
+}
+
 impl Display for Functions {
     fn show(&mut self, ui: &mut egui::Ui) {
+        let search = egui::TextEdit::singleline(&mut self.query)
+            .font(font())
+            .hint_text("Search functions")
+            .desired_width(f32::INFINITY);
+        let response = ui.add(search);
+        let enter = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+
+        let mut show_mangled = debugvault::showing_mangled();
+        ui.horizontal(|ui| {
+            let fold = egui::Checkbox::new(&mut self.fold_generics, "fold generic arguments");
+            if ui.add(fold).changed() {
+                self.refresh();
+            }
+
+            // Names are shown mangled everywhere, so the listing is refreshed as well.
+            if ui.checkbox(&mut show_mangled, "mangled names").changed() {
+                debugvault::show_mangled(show_mangled);
+                self.ui_queue.push(UIEvent::AnnotationsChanged);
+            }
+        });
+
+        if self.query.is_empty() {
+            self.pending = None;
+            self.matches = None;
+        } else {
+            self.poll_search();
+
+            if self.pending.is_some() {
+                ui.label(egui::RichText::new("searching…").font(font()).color(theme().muted));
+                ui.ctx().request_repaint();
+            }
+
+            self.show_matches(ui, enter);
+            return;
+        }
+
         let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
 
-        area.show_rows(ui, FONT.size, self.lines_count, |ui, row_range| {
+        area.show_rows(ui, font().size, self.lines_count, |ui, row_range| {
             if row_range != (self.min_row..self.max_row) {
-                self.lines = tokenize_functions(&self.processor.index, row_range.clone());
-                self.lines_count = self.processor.index.named_funcs_count();
+                let fold = if self.fold_generics {
+                    FoldGenerics::Longer(MAX_GENERICS_LEN)
+                } else {
+                    FoldGenerics::Never
+                };
+
+                self.lines = tokenize_functions(
+                    &self.processor,
+                    row_range.clone(),
+                    fold,
+                    self.coverage.as_deref(),
+                );
+                self.lines_count = lines_count(&self.processor);
                 self.min_row = row_range.start;
                 self.max_row = row_range.end;
             }
 
-            for (addr, line) in self.lines.iter() {
+            for line in self.lines.iter() {
+                let (addr, line, full_name) = match line {
+                    Line::Function(addr, line, full_name) => (addr, line, full_name),
+                    Line::Imports => {
+                        let text = egui::RichText::new("Imports").font(font()).color(theme().muted);
+                        ui.label(text);
+                        continue;
+                    }
+                };
+
                 let output = tokens_to_layoutjob(line.clone());
+                let mut response = ui.link(output);
+
+                // Folded names are expanded when hovered.
+                if let Some(full_name) = full_name {
+                    response = response.on_hover_text(full_name);
+                }
 
-                if ui.link(output).clicked() {
+                if response.clicked() {
                     self.ui_queue.push(UIEvent::GotoAddr(*addr));
                 }
             }
@@ -0,0 +1,338 @@
+use crate::common::*;
+use crate::session;
+use crate::style::STYLE;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugger::Handle;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+/// Number of bytes shown per row.
+const BYTES_PER_ROW: usize = 16;
+
+/// Memory copied out of the debugged process.
+pub struct Dump {
+    /// Runtime address of the first byte.
+    pub addr: usize,
+    pub bytes: Vec<u8>,
+    /// What the memory belongs to, e.g. a mapped file.
+    pub name: String,
+}
+
+/// Row of bytes being edited.
+struct Edit {
+    /// File offset of the row's first byte, or it's runtime address when editing process
+    /// memory.
+    offset: usize,
+    text: String,
+    /// Whether the memory of the debugged process is edited rather than the binary.
+    process: bool,
+    /// Bytes written to the process once the user confirmed it.
+    confirming: Option<Vec<u8>>,
+}
+
+pub struct Hex {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Shown instead of the binary's bytes when set.
+    dump: Option<Dump>,
+    /// Row to scroll to on the next frame.
+    scroll_to: Option<usize>,
+    /// File offset of the byte last jumped to.
+    highlighted: Option<usize>,
+    editing: Option<Edit>,
+    /// Changes to the stopped process that can be undone.
+    undoable: usize,
+}
+
+impl Hex {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            dump: None,
+            scroll_to: None,
+            highlighted: None,
+            editing: None,
+            undoable: 0,
+        }
+    }
+
+    /// Scroll to the file offset backing `addr`, returns false if it isn't stored in the file.
+    pub fn jump(&mut self, addr: usize) -> bool {
+        match self.processor.file_offset(addr) {
+            Some(offset) => {
+                self.dump = None;
+                self.scroll_to = Some(offset / BYTES_PER_ROW);
+                self.highlighted = Some(offset);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Show process memory instead of the binary's bytes.
+    pub fn show_dump(&mut self, dump: Dump) {
+        self.dump = Some(dump);
+        self.scroll_to = Some(0);
+        self.highlighted = None;
+    }
+
+    /// Read the process memory that's shown again, after the process was written to.
+    pub fn reload_dump(&mut self, handle: &Handle) {
+        if let Some(ref mut dump) = self.dump {
+            if let Ok(bytes) = handle.read_memory(dump.addr, dump.bytes.len()) {
+                dump.bytes = bytes;
+            }
+        }
+    }
+
+    pub fn set_undoable(&mut self, undoable: usize) {
+        self.undoable = undoable;
+    }
+
+    /// Start editing the row of bytes at file `offset`.
+    fn start_edit(&mut self, offset: usize) {
+        let bytes = self.processor.patched_bytes(offset, BYTES_PER_ROW);
+        self.editing = Some(Edit {
+            offset,
+            text: format_bytes(&bytes),
+            process: false,
+            confirming: None,
+        });
+    }
+
+    /// Start editing the row of process memory at `offset` into the dump.
+    fn start_process_edit(&mut self, offset: usize) {
+        let dump = match self.dump {
+            Some(ref dump) => dump,
+            None => return,
+        };
+
+        let end = (offset + BYTES_PER_ROW).min(dump.bytes.len());
+        self.editing = Some(Edit {
+            offset: dump.addr + offset,
+            text: format_bytes(&dump.bytes[offset..end]),
+            process: true,
+            confirming: None,
+        });
+    }
+
+    /// Draw the window for editing a row of bytes, if one is being edited.
+    fn show_editor(&mut self, ctx: &egui::Context) {
+        let mut edit = match self.editing.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+
+        let title = match edit.process {
+            true => format!("Edit memory at {:#x}", edit.offset),
+            false => format!("Edit bytes at offset {:#x}", edit.offset),
+        };
+
+        let mut open = true;
+        let mut submitted = false;
+        let mut confirmed = false;
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match edit.confirming {
+                    Some(ref bytes) => {
+                        let text = format!(
+                            "Write {} byte(s) to the process? This can be undone until it's \
+                             resumed.",
+                            bytes.len()
+                        );
+                        ui.label(egui::RichText::new(text).font(font()));
+                        ui.horizontal(|ui| {
+                            confirmed = ui.button("Write").clicked();
+                            if ui.button("Cancel").clicked() {
+                                edit.confirming = None;
+                            }
+                        });
+                    }
+                    None => {
+                        let input = egui::TextEdit::singleline(&mut edit.text)
+                            .font(font())
+                            .hint_text("bytes in hex, e.g. 90 90")
+                            .desired_width(400.0);
+                        let response = ui.add(input);
+                        response.request_focus();
+
+                        submitted = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    }
+                }
+
+                if ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                    open = false;
+                }
+            });
+
+        if confirmed {
+            let bytes = edit.confirming.take().unwrap_or_default();
+            let edit = session::Edit::Memory(edit.offset, bytes);
+            self.ui_queue.push(UIEvent::WriteProcess(edit));
+        } else if submitted {
+            let patched = match processor::parse_hex(&edit.text) {
+                // Process memory is only written to once confirmed.
+                Some(bytes) if edit.process && !bytes.is_empty() => {
+                    edit.confirming = Some(bytes);
+                    self.editing = Some(edit);
+                    return;
+                }
+                Some(bytes) => !edit.process && self.processor.patch(edit.offset, &bytes),
+                None => false,
+            };
+
+            if patched {
+                self.ui_queue.push(UIEvent::BinaryPatched);
+            } else {
+                let reason = match edit.process {
+                    true => " isn't made of bytes written in hex.",
+                    false => " isn't made of bytes written in hex that fit in the file.",
+                };
+
+                log::complex!(
+                    w "[hex::edit] ",
+                    r format!("'{}'", edit.text),
+                    w reason,
+                );
+                // Invalid bytes are left to be corrected.
+                self.editing = Some(edit);
+            }
+        } else if open {
+            self.editing = Some(edit);
+        }
+    }
+}
+
+/// Bytes written in hex, separated by spaces.
+fn format_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ")
+}
+
+fn tokenize_row(
+    bytes: &[u8],
+    base: usize,
+    offset: usize,
+    highlighted: Option<usize>,
+    patched: impl Fn(usize) -> bool,
+) -> Vec<Token> {
+    let mut tokens = Vec::with_capacity(BYTES_PER_ROW * 2 + 4);
+    tokens.push(Token::from_string(format!("{:0>10X}", base + offset), Kind::Address));
+    tokens.push(Token::from_str("  ", Kind::Plain));
+
+    for idx in 0..BYTES_PER_ROW {
+        let text = match bytes.get(idx) {
+            Some(byte) => format!("{byte:02x} "),
+            None => "   ".to_string(),
+        };
+
+        let color = if highlighted == Some(offset + idx) {
+            Kind::Plain
+        } else if patched(offset + idx) {
+            CONFIG.colors.role_kind("changed")
+        } else {
+            Kind::Bytes
+        };
+
+        tokens.push(Token::from_string(text, color));
+
+        // Split the row in two groups of 8 bytes.
+        if idx == BYTES_PER_ROW / 2 - 1 {
+            tokens.push(Token::from_str(" ", Kind::Plain));
+        }
+    }
+
+    tokens.push(Token::from_str(" |", Kind::Delimiter));
+    let ascii: String = bytes
+        .iter()
+        .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+        .collect();
+    tokens.push(Token::from_string(ascii, Kind::Plain));
+    tokens.push(Token::from_str("|", Kind::Delimiter));
+    tokens
+}
+
+impl Display for Hex {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if let Some(ref dump) = self.dump {
+            let mut show_file = false;
+
+            ui.horizontal(|ui| {
+                let text = format!(
+                    "Memory of {} at {:#x}..{:#x}",
+                    dump.name,
+                    dump.addr,
+                    dump.addr + dump.bytes.len()
+                );
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                show_file = ui.button("Show binary").clicked();
+
+                if self.undoable > 0 && ui.button("Undo change").clicked() {
+                    self.ui_queue.push(UIEvent::UndoWrite);
+                }
+            });
+
+            if show_file {
+                self.dump = None;
+                self.scroll_to = Some(0);
+            }
+        }
+
+        let in_file = self.dump.is_none();
+        let (base, bytes) = match self.dump {
+            Some(ref dump) => (dump.addr, dump.bytes.as_slice()),
+            None => (0, self.processor.file_bytes()),
+        };
+        let row_count = bytes.len().div_ceil(BYTES_PER_ROW);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+        let mut area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        if let Some(row) = self.scroll_to.take() {
+            let spacing = ui.spacing().item_spacing.y;
+            area = area.vertical_scroll_offset(row as f32 * (row_height + spacing));
+        }
+
+        let processor = &self.processor;
+        let mut edit = None;
+        area.show_rows(ui, row_height, row_count, |ui, row_range| {
+            for row in row_range {
+                let offset = row * BYTES_PER_ROW;
+                let end = (offset + BYTES_PER_ROW).min(bytes.len());
+
+                // Patches are only kept track of for the binary, process memory is written to.
+                let tokens = if in_file {
+                    let row_bytes = processor.patched_bytes(offset, end - offset);
+                    let patched = |offset| processor.is_patched(offset);
+                    tokenize_row(&row_bytes, base, offset, self.highlighted, patched)
+                } else {
+                    tokenize_row(&bytes[offset..end], base, offset, self.highlighted, |_| false)
+                };
+                let response = ui.label(tokens_to_layoutjob(tokens)).interact(egui::Sense::click());
+
+                if self.highlighted.map(|offset| offset / BYTES_PER_ROW) == Some(row) {
+                    ui.painter().rect_filled(response.rect, 0.0, STYLE.selection_color);
+                }
+
+                response.context_menu(|ui| {
+                    if ui.button("Edit bytes").clicked() {
+                        edit = Some(offset);
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        match edit {
+            Some(offset) if in_file => self.start_edit(offset),
+            Some(offset) => self.start_process_edit(offset),
+            None => {}
+        }
+
+        self.show_editor(ui.ctx());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::common::*;
+use processor::Processor;
+use std::sync::{Arc, OnceLock};
+use tokenizing::theme;
+
+pub struct Info {
+    /// Metadata followed by the exploit mitigations, hashed in the background.
+    metadata: Arc<OnceLock<String>>,
+}
+
+impl Info {
+    pub fn new(processor: Arc<Processor>) -> Self {
+        let metadata = Arc::new(OnceLock::new());
+
+        {
+            let metadata = Arc::clone(&metadata);
+            std::thread::spawn(move || {
+                let _ = metadata.set(describe(&processor));
+            });
+        }
+
+        Self { metadata }
+    }
+}
+
+fn describe(processor: &Processor) -> String {
+    let mut text = Vec::new();
+    let _ = processor.metadata().write_text(&mut text);
+
+    if let Some(security) = processor.checksec() {
+        text.extend_from_slice(b"\nsecurity\n");
+        let _ = security.write_text(&mut text);
+    }
+
+    String::from_utf8_lossy(&text).into_owned()
+}
+
+impl Display for Info {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let text = match self.metadata.get() {
+            Some(text) => text,
+            None => {
+                ui.label(egui::RichText::new("hashing…").font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        let hint = "click a line to copy it's value";
+        ui.label(egui::RichText::new(hint).font(font()).color(theme().muted));
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            for line in text.lines() {
+                let text = egui::RichText::new(line).font(font()).color(theme().plain);
+                let label = egui::Label::new(text).sense(egui::Sense::click());
+
+                if ui.add(label).clicked() {
+                    // Lines are a name followed by the value, or just a value when indented.
+                    let value = match line.trim_start().split_once(char::is_whitespace) {
+                        Some((_, value)) if !line.starts_with(' ') => value.trim_start(),
+                        _ => line.trim_start(),
+                    };
+                    ui.ctx().copy_text(value.to_string());
+                }
+            }
+        });
+    }
+}
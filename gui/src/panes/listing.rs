@@ -1,13 +1,19 @@
+use super::trace::Heatmap;
+use crate::style::STYLE;
+use crate::widgets::minimap::{self, Marker, Minimap};
 use crate::{common::*, UIEvent, UiQueue};
 use config::CONFIG;
-use debugvault::Index;
+use debugger::BreakpointSet;
 use egui::mutex::RwLock;
 use egui::Color32;
 use infinite_scroll::{Callback, InfiniteScroll};
-use processor::{Block, BlockContent, Processor};
+use processor::{
+    AnnotationKind, ArmMode, Block, BlockContent, Coverage, Definition, Processor, Redefine,
+};
+use std::ops::Range;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use tokenizing::{colors, Token, TokenStream};
+use tokenizing::{theme, Kind, Token, TokenStream};
 
 pub struct Listing {
     processor: Arc<Processor>,
@@ -17,25 +23,124 @@ pub struct Listing {
     scroll: InfiniteScroll<Block, usize>,
     reset_position: Arc<AtomicUsize>,
     current_addr: usize,
-    jump_list: Vec<usize>,
+    /// Locations jumped away from, the last one being the most recent.
+    back: Vec<usize>,
+    /// Locations gone back from, cleared by any new jump.
+    forward: Vec<usize>,
+    /// Anchor and head of the block addresses selected by the user.
+    selection: Option<(usize, usize)>,
+    /// Breakpoints toggled through the gutter.
+    breakpoints: Arc<BreakpointSet>,
+    /// Addresses found by the last search, stepped through with a shortcut.
+    search_results: Vec<usize>,
+    /// Index of the search result last jumped to.
+    search_idx: usize,
+    /// Comment or name being edited.
+    editing: Option<Edit>,
+    /// Address of the token last clicked, highlighted wherever it's shown.
+    highlight: Option<usize>,
+    /// Address of the token last right-clicked, which it's context menu is for.
+    menu_target: Option<usize>,
+    /// Strip of the whole binary shown next to the listing.
+    minimap: Minimap,
+    /// Execution counts of the last recorded trace, coloring the instructions executed.
+    heatmap: Option<Arc<Heatmap>>,
+    /// Instructions executed according to the last imported coverage file.
+    coverage: Option<Arc<Coverage>>,
+}
+
+/// Most locations remembered to go back to.
+const MAX_HISTORY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    Comment,
+    Rename,
+    Bookmark,
+    /// Instructions or bytes written in hex replacing an instruction.
+    Patch,
+    /// C declaration of a function.
+    Prototype,
+    /// Initial registers of emulating from an instruction.
+    Emulate,
+}
+
+struct Edit {
+    addr: usize,
+    kind: EditKind,
+    text: String,
 }
 
 impl Listing {
-    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+    /// Boundaries are computed in the background unless they're `known`, e.g. from a project.
+    pub fn new(
+        processor: Arc<Processor>,
+        ui_queue: Arc<UiQueue>,
+        breakpoints: Arc<BreakpointSet>,
+        known: Option<Vec<usize>>,
+    ) -> Self {
         let boundaries: Arc<RwLock<Vec<usize>>> = Arc::default();
+        let reset_position = Arc::new(AtomicUsize::new(0));
 
-        {
-            // Compute boundaries on a separate thread to prevent GUI from blocking.
-            let processor = Arc::clone(&processor);
-            let boundaries = Arc::clone(&boundaries);
-            std::thread::spawn(move || {
-                let mut locked_boundaries = boundaries.write();
-                *locked_boundaries = processor.compute_block_boundaries();
-            });
+        let startup_addr = processor.startup_addr();
+        match known {
+            Some(known) => {
+                let boundary = match known.binary_search(&startup_addr) {
+                    Ok(idx) => idx,
+                    Err(idx) => idx.saturating_sub(1),
+                };
+
+                reset_position.store(boundary, Ordering::SeqCst);
+                *boundaries.write() = known;
+            }
+            None => {
+                compute_boundaries(&processor, &boundaries, &reset_position, Some(startup_addr))
+            }
+        }
+
+        let mut listing = Self::with_boundaries(
+            processor,
+            ui_queue,
+            boundaries,
+            reset_position,
+            breakpoints,
+        );
+        listing.current_addr = startup_addr;
+        listing
+    }
+
+    pub fn processor(&self) -> &Arc<Processor> {
+        &self.processor
+    }
+
+    /// Create an independent view over the same binary, starting at `addr`.
+    pub fn fork(&self, addr: usize) -> Self {
+        let boundary = match self.boundaries.read().binary_search(&addr) {
+            Ok(idx) => idx,
+            Err(idx) => idx.saturating_sub(1),
         };
 
-        let reset_position = Arc::new(AtomicUsize::new(0));
+        let reset_position = Arc::new(AtomicUsize::new(boundary));
+        let mut listing = Self::with_boundaries(
+            Arc::clone(&self.processor),
+            Arc::clone(&self.ui_queue),
+            Arc::clone(&self.boundaries),
+            reset_position,
+            Arc::clone(&self.breakpoints),
+        );
+        listing.minimap = self.minimap.fork();
+        listing.heatmap = self.heatmap.clone();
+        listing.coverage = self.coverage.clone();
+        listing
+    }
 
+    fn with_boundaries(
+        processor: Arc<Processor>,
+        ui_queue: Arc<UiQueue>,
+        boundaries: Arc<RwLock<Vec<usize>>>,
+        reset_position: Arc<AtomicUsize>,
+        breakpoints: Arc<BreakpointSet>,
+    ) -> Self {
         let start_loader = {
             let reset_position = Arc::clone(&reset_position);
             let boundaries = Arc::clone(&boundaries);
@@ -45,10 +150,14 @@ impl Listing {
                 let boundaries = Arc::clone(&boundaries);
                 let processor = Arc::clone(&processor);
 
-                let block_idx = cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
+                let reset_position = Arc::clone(&reset_position);
 
                 std::thread::spawn(move || {
                     let boundaries = boundaries.read();
+                    // Read after acquiring the boundaries as they might still be computed,
+                    // which can move the reset position.
+                    let block_idx =
+                        cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
                     let mut all_blocks = Vec::new();
 
                     if block_idx == 0 {
@@ -93,10 +202,14 @@ impl Listing {
                 let boundaries = Arc::clone(&boundaries);
                 let processor = Arc::clone(&processor);
 
-                let block_idx = cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
+                let reset_position = Arc::clone(&reset_position);
 
                 std::thread::spawn(move || {
                     let boundaries = boundaries.read();
+                    // Read after acquiring the boundaries as they might still be computed,
+                    // which can move the reset position.
+                    let block_idx =
+                        cursor.unwrap_or_else(|| reset_position.load(Ordering::SeqCst));
                     let mut all_blocks = Vec::new();
 
                     let mut idx = block_idx;
@@ -128,6 +241,7 @@ impl Listing {
 
         let scroll = InfiniteScroll::new().start_loader(start_loader).end_loader(end_loader);
         let current_addr = processor.sections().next().unwrap().start;
+        let minimap = Minimap::new(&processor);
 
         Self {
             scroll,
@@ -136,13 +250,298 @@ impl Listing {
             processor,
             reset_position,
             current_addr,
-            jump_list: Vec::new(),
+            back: Vec::new(),
+            forward: Vec::new(),
+            selection: None,
+            breakpoints,
+            search_results: Vec::new(),
+            search_idx: 0,
+            editing: None,
+            highlight: None,
+            menu_target: None,
+            minimap,
+            heatmap: None,
+            coverage: None,
+        }
+    }
+
+    /// Re-tokenize the blocks shown, e.g. after the annotations changed.
+    pub fn refresh(&mut self) {
+        self.scroll.reset();
+    }
+
+    fn start_edit(&mut self, addr: usize, kind: EditKind) {
+        let annotations = self.processor.annotations.read().unwrap();
+        let text = match kind {
+            EditKind::Comment => annotations.comments.get(&addr).cloned(),
+            EditKind::Rename => annotations.renames.get(&addr).cloned().or_else(|| {
+                self.processor.index.get_sym_by_addr(addr).map(|sym| sym.as_str().to_string())
+            }),
+            EditKind::Bookmark => annotations.bookmarks.get(&addr).cloned(),
+            EditKind::Patch => self.processor.instruction_bytes(addr).map(|bytes| {
+                bytes.iter().map(|byte| format!("{byte:02x}")).collect::<Vec<_>>().join(" ")
+            }),
+            EditKind::Prototype => annotations.prototypes.get(&addr).cloned().or_else(|| {
+                self.processor.index.get_prototype(addr).map(|proto| proto.to_string())
+            }),
+            EditKind::Emulate => None,
+        };
+
+        self.editing = Some(Edit {
+            addr,
+            kind,
+            text: text.unwrap_or_default(),
+        });
+    }
+
+    /// Draw the window for editing a comment or name, if one is being edited.
+    fn show_editor(&mut self, ctx: &egui::Context) {
+        let mut edit = match self.editing.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+
+        let title = match edit.kind {
+            EditKind::Comment => format!("Comment at {:#x}", edit.addr),
+            EditKind::Rename => format!("Rename function at {:#x}", edit.addr),
+            EditKind::Bookmark => format!("Bookmark {:#x}", edit.addr),
+            EditKind::Patch => format!("Patch bytes at {:#x}", edit.addr),
+            EditKind::Prototype => format!("Prototype of function at {:#x}", edit.addr),
+            EditKind::Emulate => format!("Emulate from {:#x}", edit.addr),
+        };
+
+        let hint = match edit.kind {
+            EditKind::Bookmark => "optional label",
+            EditKind::Patch => "instructions or bytes in hex, e.g. xor eax, eax; ret",
+            EditKind::Prototype => "e.g. int open(const char *path, int flags)",
+            EditKind::Emulate => "initial registers, e.g. rdi=0x10, rsi=5",
+            _ => "leave empty to remove",
+        };
+
+        let mut open = true;
+        let mut submitted = false;
+        egui::Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let input = egui::TextEdit::singleline(&mut edit.text)
+                    .font(font())
+                    .hint_text(hint)
+                    .desired_width(400.0);
+                let response = ui.add(input);
+                response.request_focus();
+
+                submitted = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                if ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                    open = false;
+                }
+            });
+
+        if submitted && edit.kind == EditKind::Patch {
+            // Invalid instructions are left to be corrected.
+            if !self.patch(edit.addr, &edit.text) {
+                self.editing = Some(edit);
+            }
+        } else if submitted && edit.kind == EditKind::Prototype {
+            // Invalid declarations are left to be corrected.
+            if self.processor.annotate(AnnotationKind::Prototype, edit.addr, &edit.text) {
+                self.ui_queue.push(UIEvent::AnnotationsChanged);
+            } else {
+                log::complex!(
+                    w "[listing::prototype] ",
+                    r format!("'{}' isn't a valid C prototype", edit.text),
+                    w ".",
+                );
+                self.editing = Some(edit);
+            }
+        } else if submitted && edit.kind == EditKind::Emulate {
+            // Invalid registers are left to be corrected.
+            match processor::emulate::parse_state(&edit.text) {
+                Ok(state) => self.ui_queue.push(UIEvent::Emulate(edit.addr, state)),
+                Err(entry) => {
+                    log::complex!(
+                        w "[listing::emulate] ",
+                        r format!("'{entry}' isn't a register and value"),
+                        w ".",
+                    );
+                    self.editing = Some(edit);
+                }
+            }
+        } else if submitted {
+            let kind = match edit.kind {
+                EditKind::Comment => AnnotationKind::Comment,
+                EditKind::Rename => AnnotationKind::Rename,
+                EditKind::Bookmark => AnnotationKind::Bookmark,
+                EditKind::Patch | EditKind::Prototype | EditKind::Emulate => unreachable!(),
+            };
+
+            self.processor.annotate(kind, edit.addr, &edit.text);
+            self.ui_queue.push(UIEvent::AnnotationsChanged);
+        } else if open {
+            self.editing = Some(edit);
+        }
+    }
+
+    /// Overwrite the code at `addr` with the instructions or bytes written in hex in `text`.
+    fn patch(&self, addr: usize, text: &str) -> bool {
+        if let Err(err) = self.processor.patch_asm(addr, text) {
+            log::complex!(
+                w "[listing::patch] failed to patch ",
+                g format!("{addr:#x}"),
+                w ": ",
+                r format!("{err:?}"),
+            );
+            return false;
+        }
+
+        self.ui_queue.push(UIEvent::BinaryPatched);
+        true
+    }
+
+    /// Overwrite the instruction at `addr` with as many NOPs as fit.
+    fn replace_with_nops(&self, addr: usize) {
+        let len = self.processor.instruction_bytes(addr).map_or(0, |bytes| bytes.len());
+
+        match self.processor.nops(len) {
+            Some(nops) if !nops.is_empty() => {
+                if self.processor.patch_addr(addr, &nops) {
+                    self.ui_queue.push(UIEvent::BinaryPatched);
+                }
+            }
+            _ => log::complex!(
+                w "[listing::patch] no NOPs fit the instruction at ",
+                g format!("{addr:#x}"),
+                w ".",
+            ),
+        }
+    }
+
+    /// Show the binary's bytes as patched, whose code might now be split into other blocks.
+    pub fn binary_patched(&mut self) {
+        compute_boundaries(
+            &self.processor,
+            &self.boundaries,
+            &self.reset_position,
+            Some(self.current_addr),
+        );
+        self.scroll.reset();
+    }
+
+    /// Show a section as code or as bytes after it was toggled being decoded as code.
+    pub fn sections_changed(&mut self) {
+        self.binary_patched();
+    }
+
+    /// Start of every block, waiting for them to be computed if need be.
+    pub fn boundaries(&self) -> Vec<usize> {
+        self.boundaries.read().clone()
+    }
+
+    /// Range of addresses currently selected, the end being exclusive.
+    fn selected_range(&self) -> Option<(usize, usize)> {
+        let (anchor, head) = self.selection?;
+        let (start, last) = (anchor.min(head), anchor.max(head));
+        let boundaries = self.boundaries.read();
+        let end = match boundaries.binary_search(&last) {
+            Ok(idx) => boundaries.get(idx + 1).copied().unwrap_or(last),
+            Err(..) => last,
+        };
+
+        Some((start, end))
+    }
+
+    /// The selection, or the block at `addr` if it isn't part of the selection.
+    fn selected_range_or_block(&mut self, addr: usize) -> (usize, usize) {
+        match self.selected_range() {
+            Some((start, end)) if (start..end).contains(&addr) => (start, end),
+            _ => {
+                self.selection = Some((addr, addr));
+                self.selected_range().unwrap()
+            }
+        }
+    }
+
+    /// Re-decode the selection, or the block at `addr` if it isn't part of the selection.
+    pub fn reanalyze(&mut self, addr: usize) {
+        let (start, end) = self.selected_range_or_block(addr);
+        self.processor.reanalyze_range(start, end);
+        compute_boundaries(
+            &self.processor,
+            &self.boundaries,
+            &self.reset_position,
+            Some(self.current_addr),
+        );
+        self.scroll.reset();
+    }
+
+    /// Define the bytes at `addr` as code, data or code of an instruction set, or forget how they
+    /// were defined if `definition` is `None`. Data spans the selection if `addr` is part of it.
+    pub fn define(&mut self, addr: usize, definition: Option<Definition>) {
+        let redefine = match definition {
+            Some(Definition::Code) => Redefine::Code(addr),
+            Some(Definition::Arm) => Redefine::Mode(addr, ArmMode::Arm),
+            Some(Definition::Thumb) => Redefine::Mode(addr, ArmMode::Thumb),
+            Some(Definition::Data) => {
+                let (start, end) = self.selected_range_or_block(addr);
+                Redefine::Data(start, end)
+            }
+            None => Redefine::Undefine(addr),
+        };
+
+        let changed = self.processor.redefine(redefine);
+
+        // Only the blocks that changed are computed again, instead of those of every section.
+        if let Some(changed) = changed {
+            let mut boundaries = self.boundaries.write();
+            let start = boundaries.partition_point(|&addr| addr < changed.start);
+            let end = boundaries.partition_point(|&addr| addr < changed.end);
+            let replaced = self.processor.compute_range_boundaries(changed.start, changed.end);
+            boundaries.splice(start..end, replaced);
+
+            let boundary = match boundaries.binary_search(&self.current_addr) {
+                Ok(idx) => idx,
+                Err(idx) => idx.saturating_sub(1),
+            };
+            self.reset_position.store(boundary, Ordering::SeqCst);
+            drop(boundaries);
+
+            self.scroll.reset();
+        }
+    }
+
+    /// Address the keyboard acts on, the head of the selection or else the top of the listing.
+    fn cursor(&self) -> usize {
+        match self.selection {
+            Some((_, head)) => head,
+            None => self.current_addr,
+        }
+    }
+
+    /// Start editing the comment, name or bookmark at the cursor.
+    pub fn edit_selected(&mut self, kind: EditKind) {
+        self.start_edit(self.cursor(), kind);
+    }
+
+    /// Toggle the breakpoint at the cursor, if it's at an instruction.
+    pub fn toggle_breakpoint(&mut self) {
+        let addr = self.cursor();
+        if self.processor.instruction_bytes(addr).is_some() {
+            self.breakpoints.toggle(addr);
+            self.ui_queue.push(UIEvent::BreakpointsChanged);
         }
     }
 
+    fn select(&mut self, addr: usize, extend: bool) {
+        self.selection = match self.selection {
+            Some((anchor, _)) if extend => Some((anchor, addr)),
+            _ => Some((addr, addr)),
+        };
+    }
+
     pub fn jump(&mut self, addr: usize) -> bool {
         if let Ok(boundary) = self.boundaries.read().binary_search(&addr) {
-            self.jump_list.push(self.current_addr);
+            self.remember_location();
             self.reset_position.store(boundary, Ordering::SeqCst);
             self.scroll.reset();
             return true;
@@ -151,19 +550,139 @@ impl Listing {
         false
     }
 
+    /// Remember the current location to go back to, forgetting where we went back from.
+    fn remember_location(&mut self) {
+        if self.back.len() == MAX_HISTORY {
+            self.back.remove(0);
+        }
+
+        self.back.push(self.current_addr);
+        self.forward.clear();
+    }
+
+    /// Show the block containing `addr` without remembering the current location.
+    fn show_near(&mut self, addr: usize) {
+        let boundary = match self.boundaries.read().binary_search(&addr) {
+            Ok(idx) => idx,
+            Err(idx) => idx.saturating_sub(1),
+        };
+
+        self.reset_position.store(boundary, Ordering::SeqCst);
+        self.scroll.reset();
+    }
+
+    pub fn go_back(&mut self) {
+        if let Some(addr) = self.back.pop() {
+            self.forward.push(self.current_addr);
+            self.show_near(addr);
+        }
+    }
+
+    pub fn go_forward(&mut self) {
+        if let Some(addr) = self.forward.pop() {
+            self.back.push(self.current_addr);
+            self.show_near(addr);
+        }
+    }
+
+    /// Jump to the block containing `addr`, even if `addr` isn't the start of a block.
+    pub fn jump_near(&mut self, addr: usize) -> bool {
+        let boundary = match self.boundaries.read().binary_search(&addr) {
+            Ok(idx) => idx,
+            Err(0) => return false,
+            Err(idx) => idx - 1,
+        };
+
+        self.remember_location();
+        self.reset_position.store(boundary, Ordering::SeqCst);
+        self.scroll.reset();
+        true
+    }
+
+    /// Draw the minimap in `rect`, scrolling to wherever it's clicked or dragged to.
+    fn show_minimap(&mut self, ui: &mut egui::Ui, rect: egui::Rect, view: Range<usize>) {
+        let patches = self.processor.patched_addrs();
+        let breakpoints = self.breakpoints.addrs();
+        let markers = [
+            (Marker::Patch, patches.as_slice()),
+            (Marker::Breakpoint, breakpoints.as_slice()),
+            (Marker::SearchHit, self.search_results.as_slice()),
+        ];
+
+        let (response, pointed) = self.minimap.show(ui, rect, view, &markers);
+        if response.clicked() || response.drag_started() {
+            self.remember_location();
+        }
+
+        if !(response.clicked() || response.dragged()) {
+            return;
+        }
+
+        if let Some(addr) = pointed {
+            let boundary = |addr| match self.boundaries.read().binary_search(&addr) {
+                Ok(idx) => idx,
+                Err(idx) => idx.saturating_sub(1),
+            };
+
+            // only reload the blocks once the pointer moves past the one shown at the top
+            if boundary(addr) != boundary(self.current_addr) {
+                self.show_near(addr);
+            }
+        }
+    }
+
+    /// Replace the results of the last search and jump to the first result.
+    pub fn set_search_results(&mut self, results: Vec<usize>) {
+        self.search_results = results;
+        self.search_idx = 0;
+
+        if let Some(&addr) = self.search_results.first() {
+            self.jump_near(addr);
+        }
+    }
+
+    pub fn step_search_results(&mut self, backwards: bool) {
+        let len = self.search_results.len();
+        if len == 0 {
+            return;
+        }
+
+        self.search_idx = if backwards {
+            (self.search_idx + len - 1) % len
+        } else {
+            (self.search_idx + 1) % len
+        };
+
+        self.jump_near(self.search_results[self.search_idx]);
+    }
+
+    /// Color the instructions by how often they were executed.
+    pub fn set_heatmap(&mut self, heatmap: Arc<Heatmap>) {
+        self.heatmap = Some(heatmap);
+    }
+
+    /// Color the instructions that were covered.
+    pub fn set_coverage(&mut self, coverage: Arc<Coverage>) {
+        self.coverage = Some(coverage);
+    }
+
+    /// Go back and forward with the mouse's side buttons, keys being handled by the keymap.
     pub fn record_input(&mut self, events: &mut Vec<egui::Event>) {
         events.retain(|event| match event {
-            egui::Event::Key {
-                key: egui::Key::Escape,
+            egui::Event::PointerButton {
+                button: egui::PointerButton::Extra1,
                 pressed: true,
-                modifiers: egui::Modifiers::NONE,
                 ..
             } => {
-                if let Some(addr) = self.jump_list.pop() {
-                    let boundary = self.boundaries.read().binary_search(&addr).unwrap();
-                    self.reset_position.store(boundary, Ordering::SeqCst);
-                    self.scroll.reset();
-                }
+                self.go_back();
+                false
+            }
+            egui::Event::PointerButton {
+                button: egui::PointerButton::Extra2,
+                pressed: true,
+                ..
+            } => {
+                self.go_forward();
                 false
             }
             _ => true,
@@ -171,19 +690,30 @@ impl Listing {
     }
 }
 
-fn split_instruction_by_label(tokens: Vec<Token>) -> (Vec<Token>, Vec<Token>, Vec<Token>) {
-    let start = tokens.iter().position(|token| token.text.contains('<'));
-    let end = tokens.iter().rposition(|token| token.text.contains('>'));
-
-    if let (Some(start), Some(end)) = (start, end) {
-        return (
-            tokens[..start].to_vec(),
-            tokens[start..=end].to_vec(),
-            tokens[end + 1..].to_vec(),
-        );
-    }
+/// Compute boundaries on a separate thread to prevent GUI from blocking.
+///
+/// If an address to keep is given, the reset position is moved to it's new boundary.
+fn compute_boundaries(
+    processor: &Arc<Processor>,
+    boundaries: &Arc<RwLock<Vec<usize>>>,
+    reset_position: &Arc<AtomicUsize>,
+    keep_addr: Option<usize>,
+) {
+    let processor = Arc::clone(processor);
+    let boundaries = Arc::clone(boundaries);
+    let reset_position = Arc::clone(reset_position);
+    std::thread::spawn(move || {
+        let mut locked_boundaries = boundaries.write();
+        *locked_boundaries = processor.compute_block_boundaries();
 
-    (tokens, Vec::new(), Vec::new())
+        if let Some(addr) = keep_addr {
+            let boundary = match locked_boundaries.binary_search(&addr) {
+                Ok(idx) => idx,
+                Err(idx) => idx.saturating_sub(1),
+            };
+            reset_position.store(boundary, Ordering::SeqCst);
+        }
+    });
 }
 
 fn draw_horizontal_line(ui: &mut egui::Ui) {
@@ -192,7 +722,7 @@ fn draw_horizontal_line(ui: &mut egui::Ui) {
 
     let dashed_line = egui::Shape::dashed_line(
         &[egui::pos2(5.0, y), egui::pos2(ui.available_width(), y)],
-        egui::Stroke::new(thickness, colors::WHITE),
+        egui::Stroke::new(thickness, theme().plain),
         10.0,
         5.0,
     );
@@ -201,23 +731,152 @@ fn draw_horizontal_line(ui: &mut egui::Ui) {
     ui.painter().extend(dashed_line);
 }
 
-fn draw_instruction(ui: &mut egui::Ui, tokens: Vec<Token>, index: &Index, ui_queue: &UiQueue) {
-    let (a, b, c) = split_instruction_by_label(tokens);
-    let label = tokens_to_layoutjob(b);
-    let label_text = label.text.clone();
+/// Appends the number of tokens and glyphs to each line, as a rough measure of how
+/// expensive a line is to layout.
+fn annotate_render_cost(tokens: Vec<Token>) -> Vec<Token> {
+    fn annotation(token_count: usize, glyph_count: usize) -> Token {
+        let text = format!("  ; {token_count} tokens, {glyph_count} glyphs");
+        Token::from_string(text, Kind::Comment)
+    }
 
-    ui.horizontal(|ui| {
-        ui.style_mut().spacing.item_spacing.x = 0.0;
+    let mut annotated = Vec::with_capacity(tokens.len() + 1);
+    let mut token_count = 0;
+    let mut glyph_count = 0;
 
-        ui.label(tokens_to_layoutjob(a));
-        if ui.link(label).clicked() {
-            let label_without_arrows = &label_text[1..][..label_text.len() - 2];
-            if let Some(addr) = index.get_func_by_name(label_without_arrows) {
-                ui_queue.push(UIEvent::GotoAddr(addr));
+    for token in tokens {
+        let mut lines = token.text.split('\n').peekable();
+        while let Some(line) = lines.next() {
+            if !line.is_empty() {
+                token_count += 1;
+                glyph_count += line.chars().count();
+                annotated.push(Token::from_string(line.to_string(), token.kind));
+            }
+
+            if lines.peek().is_some() {
+                if token_count > 0 {
+                    annotated.push(annotation(token_count, glyph_count));
+                }
+                annotated.push(Token::from_str("\n", token.kind));
+                token_count = 0;
+                glyph_count = 0;
             }
         }
-        ui.label(tokens_to_layoutjob(c));
-    });
+    }
+
+    if token_count > 0 {
+        annotated.push(annotation(token_count, glyph_count));
+    }
+
+    annotated
+}
+
+/// Width of the column left of the listing where breakpoints are toggled.
+const GUTTER_WIDTH: f32 = 14.0;
+
+/// Draw the gutter of a line, returns whether it got clicked.
+/// Draw the gutter of a line, returning whether it was clicked. Hardware breakpoints are drawn
+/// as squares.
+fn draw_gutter(ui: &mut egui::Ui, clickable: bool, has_breakpoint: bool, hardware: bool) -> bool {
+    let sense = if clickable {
+        egui::Sense::click()
+    } else {
+        egui::Sense::hover()
+    };
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(GUTTER_WIDTH, font().size), sense);
+    let color = CONFIG.colors.role_color("breakpoint");
+
+    if has_breakpoint && hardware {
+        let square = egui::Rect::from_center_size(rect.center(), egui::vec2(8.0, 8.0));
+        ui.painter().rect_filled(square, 1.0, color);
+    } else if has_breakpoint {
+        ui.painter().circle_filled(rect.center(), 4.0, color);
+    } else if clickable && response.hovered() {
+        ui.painter().circle_stroke(rect.center(), 4.0, egui::Stroke::new(1.0, color));
+    }
+
+    response.clicked()
+}
+
+/// Draw the instruction set of a line of 32-bit ARM code.
+fn draw_mode(ui: &mut egui::Ui, mode: ArmMode) {
+    let letter = match mode {
+        ArmMode::Arm => "A",
+        ArmMode::Thumb => "T",
+        ArmMode::Data => "D",
+    };
+
+    ui.label(egui::RichText::new(letter).font(font()).color(theme().muted));
+    ui.add_space(font().size / 2.0);
+}
+
+/// Draw a line of tokens, returning it's response along with the index of the token hovered.
+///
+/// Tokens referring to the `highlight`ed address are drawn on a highlighted background.
+fn draw_line(
+    ui: &mut egui::Ui,
+    tokens: &[Token],
+    highlight: Option<usize>,
+) -> (egui::Response, Option<usize>) {
+    let mut job = tokens_to_layoutjob(tokens.to_vec());
+    if highlight.is_some() {
+        for (section, token) in job.sections.iter_mut().zip(tokens) {
+            if token.target == highlight {
+                section.format.background = STYLE.highlight_color;
+            }
+        }
+    }
+
+    let response = ui.add(egui::Label::new(job.clone()).sense(egui::Sense::click()));
+    let hovered = hovered_token(ui, &response, job);
+    if hovered.is_some_and(|idx| tokens[idx].target.is_some()) {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+    }
+
+    (response, hovered)
+}
+
+/// Draw the line of an instruction, describing it when the tokens in `instruction` are hovered.
+fn draw_instruction(
+    ui: &mut egui::Ui,
+    tokens: &[Token],
+    instruction: Range<usize>,
+    processor: &Processor,
+    addr: usize,
+    highlight: Option<usize>,
+) -> (egui::Response, Option<usize>) {
+    let (response, hovered) = draw_line(ui, tokens, highlight);
+
+    if !hovered.is_some_and(|idx| instruction.contains(&idx)) {
+        return (response, hovered);
+    }
+
+    let response = match processor.describe(addr) {
+        Some(description) => response.on_hover_ui_at_pointer(|ui| {
+            ui.label(egui::RichText::new(&description.mnemonic).font(font()).strong());
+            if let Some(summary) = description.summary {
+                ui.label(summary);
+            }
+            if !description.flags.is_empty() {
+                ui.label(format!("Flags: {}", description.flags));
+            }
+            if !description.operands.is_empty() {
+                ui.separator();
+                egui::Grid::new("operands").show(ui, |ui| {
+                    for (text, kind) in &description.operands {
+                        ui.label(egui::RichText::new(text).font(font()));
+                        ui.label(kind);
+                        ui.end_row();
+                    }
+                });
+            }
+            for note in &description.notes {
+                ui.label(note);
+            }
+        }),
+        None => response,
+    };
+
+    (response, hovered)
 }
 
 impl Display for Listing {
@@ -228,51 +887,364 @@ impl Display for Listing {
             .animated(false);
 
         let start_y = ui.cursor().min.y;
+        let mut strip = ui.available_rect_before_wrap();
+        strip.min.x = strip.max.x - minimap::WIDTH;
+        let mut last_addr = self.current_addr;
 
         area.show(ui, |ui| {
-            ui.set_width(ui.available_width());
+            ui.set_width(ui.available_width() - minimap::WIDTH);
 
             let mut idx = 0;
+            let mut clicked = None;
+            let mut reanalyze = None;
+            let mut define = None;
+            let mut edit = None;
+            let mut nop = None;
+            let mut highlighted = None;
+            let selection = self.selection;
+            let highlight = self.highlight;
+            let heatmap = self.heatmap.clone();
+            let coverage = self.coverage.clone();
             self.scroll.ui(ui, 10, |ui, _, block| {
                 if idx == 0 {
                     self.current_addr = block.addr;
                 }
+                last_addr = block.addr;
 
                 if let BlockContent::SectionStart { .. } = block.content {
                     draw_horizontal_line(ui);
                 }
 
                 let mut stream = TokenStream::new();
-                block.tokenize(&mut stream);
+                // tokens of the mnemonic and operands, telling them apart from the other columns
+                let instruction = match block.row() {
+                    Some(row) => row.tokenize(&mut stream),
+                    None => {
+                        block.tokenize(&mut stream);
+                        0..0
+                    }
+                };
+
+                if commands::ARGS.debug_render {
+                    stream.inner = annotate_render_cost(stream.inner);
+                }
+
+                let is_instruction = matches!(block.content, BlockContent::Instruction { .. });
+                let (response, hovered) = ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+
+                    let has_breakpoint = is_instruction && self.breakpoints.contains(block.addr);
+                    let hardware = has_breakpoint && self.breakpoints.is_hardware(block.addr);
+                    if draw_gutter(ui, is_instruction, has_breakpoint, hardware) {
+                        self.breakpoints.toggle(block.addr);
+                        self.ui_queue.push(UIEvent::BreakpointsChanged);
+                    }
 
-                match block.content {
-                    BlockContent::Instruction { .. } => {
-                        draw_instruction(ui, stream.inner, &self.processor.index, &self.ui_queue);
+                    if let Some(mode) = self.processor.arm_mode(block.addr) {
+                        draw_mode(ui, mode);
                     }
-                    BlockContent::Label { .. } => {
-                        if ui.link(tokens_to_layoutjob(stream.inner)).clicked() {
-                            self.ui_queue.push(UIEvent::GotoAddr(block.addr));
+
+                    match block.content {
+                        BlockContent::Instruction { .. } => draw_instruction(
+                            ui,
+                            &stream.inner,
+                            instruction,
+                            &self.processor,
+                            block.addr,
+                            highlight,
+                        ),
+                        BlockContent::Label { .. } => {
+                            let response = ui.link(tokens_to_layoutjob(stream.inner.clone()));
+                            if response.clicked() {
+                                self.ui_queue.push(UIEvent::GotoAddr(block.addr));
+                            }
+                            (response, None)
                         }
+                        _ => draw_line(ui, &stream.inner, highlight),
+                    }
+                })
+                .inner;
+
+                // address or symbol under the pointer
+                let target = hovered.and_then(|idx| stream.inner[idx].target);
+
+                if let Some((anchor, head)) = selection {
+                    if (anchor.min(head)..=anchor.max(head)).contains(&block.addr) {
+                        ui.painter().rect_filled(response.rect, 0.0, STYLE.selection_color);
+                    }
+                }
+
+                if is_instruction && coverage.as_ref().is_some_and(|cov| cov.contains(block.addr)) {
+                    let color = CONFIG.colors.role_color("covered").linear_multiply(0.25);
+                    ui.painter().rect_filled(response.rect, 0.0, color);
+                }
+
+                if is_instruction {
+                    if let Some(color) = heatmap.as_ref().and_then(|map| map.color(block.addr)) {
+                        ui.painter().rect_filled(response.rect, 0.0, color);
                     }
-                    _ => {
-                        ui.label(tokens_to_layoutjob(stream.inner));
+                }
+
+                if response.clicked() {
+                    clicked = Some(block.addr);
+                    highlighted = Some(target);
+                }
+
+                if response.double_clicked() {
+                    if let Some(addr) = target {
+                        self.ui_queue.push(UIEvent::GotoAddr(addr));
                     }
                 }
 
+                if response.secondary_clicked() {
+                    self.menu_target = target;
+                }
+
+                response.context_menu(|ui| {
+                    if let Some(addr) = self.menu_target {
+                        if ui.button(format!("Go to {addr:#x}")).clicked() {
+                            self.ui_queue.push(UIEvent::GotoAddr(addr));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Copy address").clicked() {
+                            ui.ctx().copy_text(format!("{addr:#x}"));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Rename address").clicked() {
+                            edit = Some((addr, EditKind::Rename));
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Comment address").clicked() {
+                            edit = Some((addr, EditKind::Comment));
+                            ui.close_menu();
+                        }
+
+                        let xrefs = self.processor.xrefs_to(addr);
+                        ui.add_enabled_ui(!xrefs.is_empty(), |ui| {
+                            ui.menu_button(format!("Xrefs ({})", xrefs.len()), |ui| {
+                                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                    for xref in xrefs {
+                                        let text = format!("{:#x} ({:?})", xref.from, xref.kind);
+                                        if ui.button(text).clicked() {
+                                            self.ui_queue.push(UIEvent::GotoAddr(xref.from));
+                                            ui.close_menu();
+                                        }
+                                    }
+                                });
+                            });
+                        });
+
+                        ui.separator();
+                    }
+
+                    if ui.button("Reanalyze").clicked() {
+                        reanalyze = Some(block.addr);
+                        ui.close_menu();
+                    }
+
+                    if is_instruction {
+                        for target in self.processor.overlapping_targets(block.addr) {
+                            if ui.button(format!("Define as code at {target:#x}")).clicked() {
+                                define = Some((target, Some(Definition::Code)));
+                                ui.close_menu();
+                            }
+                        }
+
+                        if ui.button("Define as data").clicked() {
+                            define = Some((block.addr, Some(Definition::Data)));
+                            ui.close_menu();
+                        }
+                    }
+
+                    let is_bytes = matches!(block.content, BlockContent::Bytes { .. });
+                    if is_bytes
+                        && self.processor.is_code(block.addr)
+                        && ui.button("Define as code").clicked()
+                    {
+                        define = Some((block.addr, Some(Definition::Code)));
+                        ui.close_menu();
+                    }
+
+                    // Raw firmware has no mapping symbols telling ARM and Thumb code apart.
+                    let mode = self.processor.arm_mode(block.addr);
+                    if mode.is_some_and(|mode| mode != ArmMode::Arm)
+                        && ui.button("Decode as ARM").clicked()
+                    {
+                        define = Some((block.addr, Some(Definition::Arm)));
+                        ui.close_menu();
+                    }
+
+                    if mode.is_some_and(|mode| mode != ArmMode::Thumb)
+                        && ui.button("Decode as Thumb").clicked()
+                    {
+                        define = Some((block.addr, Some(Definition::Thumb)));
+                        ui.close_menu();
+                    }
+
+                    if self.processor.definition(block.addr).is_some()
+                        && ui.button("Undefine").clicked()
+                    {
+                        define = Some((block.addr, None));
+                        ui.close_menu();
+                    }
+
+                    if is_instruction && ui.button("Comment").clicked() {
+                        edit = Some((block.addr, EditKind::Comment));
+                        ui.close_menu();
+                    }
+
+                    if is_instruction && ui.button("Patch bytes").clicked() {
+                        edit = Some((block.addr, EditKind::Patch));
+                        ui.close_menu();
+                    }
+
+                    if is_instruction && ui.button("Replace with NOPs").clicked() {
+                        nop = Some(block.addr);
+                        ui.close_menu();
+                    }
+
+                    if is_instruction {
+                        // Code that checks itself or is in ROM can't have traps written into it.
+                        let mut hardware = self.breakpoints.is_hardware(block.addr);
+                        let free = hardware || self.breakpoints.free_debug_registers() > 0;
+                        let checkbox = egui::Checkbox::new(&mut hardware, "Hardware breakpoint");
+                        let response = ui
+                            .add_enabled(free, checkbox)
+                            .on_disabled_hover_text("All debug registers are in use.");
+
+                        if response.clicked()
+                            && self.breakpoints.set_hardware(block.addr, hardware).is_ok()
+                        {
+                            self.ui_queue.push(UIEvent::BreakpointsChanged);
+                            ui.close_menu();
+                        }
+                    }
+
+                    let is_label = matches!(block.content, BlockContent::Label { .. });
+                    if is_label && ui.button("Rename").clicked() {
+                        edit = Some((block.addr, EditKind::Rename));
+                        ui.close_menu();
+                    }
+
+                    if is_label && ui.button("Set prototype").clicked() {
+                        edit = Some((block.addr, EditKind::Prototype));
+                        ui.close_menu();
+                    }
+
+                    let bookmarked = self
+                        .processor
+                        .annotations
+                        .read()
+                        .unwrap()
+                        .bookmarks
+                        .contains_key(&block.addr);
+
+                    let bookmark = if bookmarked { "Edit bookmark" } else { "Bookmark" };
+                    if ui.button(bookmark).clicked() {
+                        edit = Some((block.addr, EditKind::Bookmark));
+                        ui.close_menu();
+                    }
+
+                    if bookmarked && ui.button("Remove bookmark").clicked() {
+                        self.processor.remove_bookmark(block.addr);
+                        self.ui_queue.push(UIEvent::AnnotationsChanged);
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Open in new tab").clicked() {
+                        self.ui_queue.push(UIEvent::OpenListing(block.addr));
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Show in hex view").clicked() {
+                        self.ui_queue.push(UIEvent::GotoHex(block.addr));
+                        ui.close_menu();
+                    }
+
+                    if let Some(import) = self.processor.import_at(block.addr) {
+                        if ui.button("Go to definition in library").clicked() {
+                            let processor = Arc::clone(&self.processor);
+                            self.ui_queue.push(UIEvent::FollowImport(processor, import));
+                            ui.close_menu();
+                        }
+                    }
+
+                    if is_instruction && ui.button("Show flow graph").clicked() {
+                        self.ui_queue.push(UIEvent::ShowFlowGraph(block.addr));
+                        ui.close_menu();
+                    }
+
+                    if is_instruction && ui.button("Show pseudocode").clicked() {
+                        self.ui_queue.push(UIEvent::ShowPseudocode(block.addr));
+                        ui.close_menu();
+                    }
+
+                    if is_instruction && ui.button("Emulate from here").clicked() {
+                        edit = Some((block.addr, EditKind::Emulate));
+                        ui.close_menu();
+                    }
+
+                    let callers = self.processor.callers(block.addr);
+                    ui.add_enabled_ui(!callers.is_empty(), |ui| {
+                        ui.menu_button(format!("Callers ({})", callers.len()), |ui| {
+                            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                                for caller in callers {
+                                    if ui.button(format!("{caller:#x}")).clicked() {
+                                        self.ui_queue.push(UIEvent::GotoAddr(caller));
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        });
+                    });
+                });
+
                 idx += 1;
             });
 
+            if let Some(addr) = clicked {
+                let extend = ui.input(|input| input.modifiers.shift);
+                self.select(addr, extend);
+                self.ui_queue.push(UIEvent::AddrSelected(addr));
+            }
+
+            if let Some(highlight) = highlighted {
+                self.highlight = highlight;
+            }
+
+            if let Some(addr) = reanalyze {
+                self.reanalyze(addr);
+            }
+
+            if let Some((addr, definition)) = define {
+                self.define(addr, definition);
+            }
+
+            if let Some((addr, kind)) = edit {
+                self.start_edit(addr, kind);
+            }
+
+            if let Some(addr) = nop {
+                self.replace_with_nops(addr);
+            }
+
             ui.vertical_centered(|ui| {
                 ui.set_visible(self.scroll.bottom_loading_state().loading());
                 ui.spinner();
             });
         });
 
+        self.show_editor(ui.ctx());
+        self.show_minimap(ui, strip, self.current_addr..last_addr);
+
         // Overlay current section.
         let text = self.processor.section_name(self.current_addr).unwrap();
         let max_width = ui.available_width();
         let size = egui::vec2(9.0 * text.len() as f32, 25.0);
-        let offset = egui::pos2(8.0, start_y + 6.0);
+        let offset = egui::pos2(8.0 + minimap::WIDTH, start_y + 6.0);
         let rect = egui::Rect::from_two_pos(
             egui::pos2(max_width - offset.x, offset.y),
             egui::pos2(max_width - offset.x - size.x, offset.y + size.y),
@@ -282,7 +1254,7 @@ impl Display for Listing {
             rect.expand2(egui::vec2(5.0, 0.0)),
             0.0,
             {
-                let color = CONFIG.colors.bg_primary;
+                let color = theme().bg_primary;
                 Color32::from_rgb(
                     (color[0] as f32 * 1.1) as u8,
                     (color[1] as f32 * 1.1) as u8,
@@ -296,7 +1268,7 @@ impl Display for Listing {
             rect.center(),
             egui::Align2::CENTER_CENTER,
             text,
-            FONT,
+            font(),
             egui::Color32::WHITE,
         );
     }
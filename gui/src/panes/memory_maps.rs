@@ -0,0 +1,83 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use debugger::MemoryRegion;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+pub struct MemoryMaps {
+    ui_queue: Arc<UiQueue>,
+    /// Regions at the last stop.
+    regions: Vec<MemoryRegion>,
+    filter: String,
+}
+
+impl MemoryMaps {
+    pub fn new(ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            ui_queue,
+            regions: Vec::new(),
+            filter: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, regions: Vec<MemoryRegion>) {
+        self.regions = regions;
+    }
+}
+
+fn tokenize_region(region: &MemoryRegion) -> Vec<Token> {
+    let path = region.path.as_deref().unwrap_or("");
+
+    vec![
+        Token::from_string(format!("{:0>12x}", region.start), Kind::Address),
+        Token::from_str("-", Kind::Plain),
+        Token::from_string(format!("{:0>12x}", region.end), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(region.perms.to_string(), Kind::Component),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{:>8x}", region.offset), Kind::Immediate),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(path.to_string(), Kind::Comment),
+    ]
+}
+
+impl Display for MemoryMaps {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.regions.is_empty() {
+            let text = "No process is being debugged, start one with `run`.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new("Filter").font(font()).color(theme().muted));
+            ui.text_edit_singleline(&mut self.filter);
+        });
+
+        let regions: Vec<&MemoryRegion> = self
+            .regions
+            .iter()
+            .filter(|region| {
+                self.filter.is_empty()
+                    || region.path.as_deref().is_some_and(|path| path.contains(&self.filter))
+            })
+            .collect();
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let row_height = ui.spacing().interact_size.y;
+
+        area.show_rows(ui, row_height, regions.len(), |ui, row_range| {
+            for region in &regions[row_range] {
+                ui.horizontal(|ui| {
+                    let readable = region.perms.read && !region.is_empty();
+                    let dump = ui.add_enabled(readable, egui::Button::new("Dump"));
+                    if dump.on_hover_text("Show the region in the hex view").clicked() {
+                        self.ui_queue.push(UIEvent::DumpMemory((*region).clone()));
+                    }
+
+                    ui.label(tokens_to_layoutjob(tokenize_region(region)));
+                });
+            }
+        });
+    }
+}
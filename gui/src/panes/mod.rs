@@ -1,16 +1,45 @@
+mod bookmarks;
+mod call_graph;
+mod call_stack;
+mod console;
+mod emulation;
+mod flow_graph;
 mod functions;
+mod hex;
+mod info;
 mod listing;
+mod memory_maps;
+mod overview;
+mod pseudocode;
+mod registers;
+mod segments;
 mod source_code;
-
-use crate::style::{EGUI, STYLE};
-use crate::widgets::{Donut, Terminal};
+mod strings;
+mod syscalls;
+mod threads;
+mod trace;
+mod variables;
+
+use crate::style::STYLE;
+use crate::widgets::{
+    Donut, GotoPalette, LoadProgress, MemberPicker, Preferences, ProcessPicker, RawLoader,
+    RunConfig, Terminal,
+};
+use crate::keymap::{Action, Keymap};
+use crate::session::{self, DebugInfo, Session, Step, Stream};
 use crate::{common::*, WinitQueue};
-use config::CONFIG;
+use commands::ExportFormat;
+use config::SETTINGS;
+use debugger::{
+    BreakpointSet, CallFrameInfo, FollowFork, Frame, Library, LineTable, MemoryRegion, Registers,
+    Signals, Stop, Syscall, Thread, Trace,
+};
 use egui_tiles::{Container, SimplificationOptions, Tile, TileId, Tiles, Tree, UiResponse};
-use processor::Processor;
-use tokenizing::colors;
+use processor::{project, Edit, Import, Processor};
+use tokenizing::{theme, Theme};
 
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub type Identifier = &'static str;
@@ -19,11 +48,76 @@ pub const SOURCE: Identifier = crate::icon!(EMBED2, " Source");
 pub const DISASSEMBLY: Identifier = crate::icon!(PARAGRAPH_LEFT, " Disassembly");
 pub const FUNCTIONS: Identifier = crate::icon!(LIGATURE, " Functions");
 pub const LOGGING: Identifier = crate::icon!(TERMINAL, " Logs");
+pub const HEX: Identifier = crate::icon!(TABLE2, " Hex");
+pub const CALL_GRAPH: Identifier = crate::icon!(TREE, " Call graph");
+pub const STRINGS: Identifier = crate::icon!(QUOTES_LEFT, " Strings");
+pub const REGISTERS: Identifier = crate::icon!(STACK, " Registers");
+pub const MEMORY_MAPS: Identifier = crate::icon!(MAP, " Memory maps");
+pub const SYSCALLS: Identifier = crate::icon!(HISTORY, " Syscalls");
+pub const CONSOLE: Identifier = crate::icon!(DISPLAY, " Console");
+pub const TRACE: Identifier = crate::icon!(ROAD, " Trace");
+pub const CALL_STACK: Identifier = crate::icon!(LIST_NUMBERED, " Call stack");
+pub const VARIABLES: Identifier = crate::icon!(PRICE_TAGS, " Variables");
+pub const THREADS: Identifier = crate::icon!(COGS, " Threads");
+pub const FLOW_GRAPH: Identifier = crate::icon!(SHUFFLE, " Flow graph");
+pub const PSEUDOCODE: Identifier = crate::icon!(FILE_TEXT2, " Pseudocode");
+pub const EMULATION: Identifier = crate::icon!(LAB, " Emulation");
+pub const OVERVIEW: Identifier = crate::icon!(EQUALIZER, " Overview");
+pub const INFO: Identifier = crate::icon!(INFO, " Info");
+pub const BOOKMARKS: Identifier = crate::icon!(BOOKMARKS, " Bookmarks");
+pub const SEGMENTS: Identifier = crate::icon!(LIST, " Segments");
+
+/// Panels by the name they have in the settings.
+pub const PANELS: &[(&str, Identifier)] = &[
+    ("disassembly", DISASSEMBLY),
+    ("functions", FUNCTIONS),
+    ("source", SOURCE),
+    ("hex", HEX),
+    ("call graph", CALL_GRAPH),
+    ("strings", STRINGS),
+    ("registers", REGISTERS),
+    ("memory maps", MEMORY_MAPS),
+    ("syscalls", SYSCALLS),
+    ("console", CONSOLE),
+    ("trace", TRACE),
+    ("call stack", CALL_STACK),
+    ("variables", VARIABLES),
+    ("threads", THREADS),
+    ("flow graph", FLOW_GRAPH),
+    ("pseudocode", PSEUDOCODE),
+    ("emulation", EMULATION),
+    ("overview", OVERVIEW),
+    ("info", INFO),
+    ("bookmarks", BOOKMARKS),
+    ("segments", SEGMENTS),
+    ("logs", LOGGING),
+];
+
+/// Largest region of process memory that's copied into the hex view.
+const MAX_DUMP_LEN: usize = 16 * 1024 * 1024;
 
 enum PanelKind {
     Disassembly(listing::Listing),
     Functions(functions::Functions),
     Source(source_code::Source),
+    Hex(hex::Hex),
+    CallGraph(call_graph::CallGraphView),
+    Strings(strings::Strings),
+    Registers(registers::RegistersView),
+    MemoryMaps(memory_maps::MemoryMaps),
+    Syscalls(syscalls::Syscalls),
+    Console(console::Console),
+    Trace(trace::TraceView),
+    CallStack(call_stack::CallStack),
+    Variables(variables::Variables),
+    Threads(threads::Threads),
+    FlowGraph(flow_graph::FlowGraph),
+    Pseudocode(pseudocode::Pseudocode),
+    Emulation(emulation::Emulation),
+    Overview(overview::Overview),
+    Info(info::Info),
+    Bookmarks(bookmarks::Bookmarks),
+    Segments(segments::Segments),
     Logging,
 }
 
@@ -32,6 +126,11 @@ pub struct Tabs {
     terminal: Terminal,
     processor: Option<Arc<Processor>>,
     donut: Donut,
+    log_scroll: StickyScroll,
+    /// Listing that commands and jumps apply to, the one last interacted with.
+    focused_listing: Identifier,
+    /// Number of listings opened besides the main one.
+    extra_listings: usize,
 }
 
 impl Tabs {
@@ -45,6 +144,9 @@ impl Tabs {
             terminal: Terminal::new(),
             processor: None,
             donut: Donut::new(false),
+            log_scroll: StickyScroll::default(),
+            focused_listing: DISASSEMBLY,
+            extra_listings: 0,
         }
     }
 }
@@ -62,14 +164,14 @@ impl egui_tiles::Behavior<Identifier> for Tabs {
         active: bool,
     ) -> egui::Color32 {
         if active {
-            CONFIG.colors.bg_primary
+            theme().bg_primary
         } else {
-            CONFIG.colors.bg_secondary
+            theme().bg_secondary
         }
     }
 
     fn tab_bar_color(&self, _: &egui::Visuals) -> egui::Color32 {
-        CONFIG.colors.bg_secondary
+        theme().bg_secondary
     }
 
     fn drag_preview_color(&self, _: &egui::Visuals) -> egui::Color32 {
@@ -102,20 +204,44 @@ impl egui_tiles::Behavior<Identifier> for Tabs {
         pane: &mut Identifier,
     ) -> UiResponse {
         // Set pane background color.
-        ui.painter().rect_filled(ui.max_rect(), 0.0, CONFIG.colors.bg_primary);
+        ui.painter().rect_filled(ui.max_rect(), 0.0, theme().bg_primary);
 
         egui::Frame::default().inner_margin(egui::Margin::same(5.0)).show(ui, |ui| {
             match self.mapping.get_mut(pane) {
-                Some(PanelKind::Disassembly(disassembly)) => disassembly.show(ui),
+                Some(PanelKind::Disassembly(disassembly)) => {
+                    let interacted = ui.input(|input| input.pointer.any_pressed());
+                    if interacted && ui.rect_contains_pointer(ui.max_rect()) {
+                        self.focused_listing = *pane;
+                    }
+
+                    disassembly.show(ui)
+                }
                 Some(PanelKind::Functions(functions)) => functions.show(ui),
                 Some(PanelKind::Source(src)) => src.show(ui),
+                Some(PanelKind::Hex(hex)) => hex.show(ui),
+                Some(PanelKind::CallGraph(graph)) => graph.show(ui),
+                Some(PanelKind::Strings(strings)) => strings.show(ui),
+                Some(PanelKind::Registers(registers)) => registers.show(ui),
+                Some(PanelKind::MemoryMaps(maps)) => maps.show(ui),
+                Some(PanelKind::Syscalls(syscalls)) => syscalls.show(ui),
+                Some(PanelKind::Console(console)) => console.show(ui),
+                Some(PanelKind::Trace(trace)) => trace.show(ui),
+                Some(PanelKind::CallStack(call_stack)) => call_stack.show(ui),
+                Some(PanelKind::Variables(variables)) => variables.show(ui),
+                Some(PanelKind::Threads(threads)) => threads.show(ui),
+                Some(PanelKind::FlowGraph(graph)) => graph.show(ui),
+                Some(PanelKind::Pseudocode(code)) => code.show(ui),
+                Some(PanelKind::Emulation(emulation)) => emulation.show(ui),
+                Some(PanelKind::Overview(overview)) => overview.show(ui),
+                Some(PanelKind::Info(info)) => info.show(ui),
+                Some(PanelKind::Bookmarks(bookmarks)) => bookmarks.show(ui),
+                Some(PanelKind::Segments(segments)) => segments.show(ui),
                 Some(PanelKind::Logging) => {
                     let area = egui::ScrollArea::vertical()
                         .auto_shrink([false, false])
-                        .drag_to_scroll(false)
-                        .stick_to_bottom(true);
+                        .drag_to_scroll(false);
 
-                    area.show(ui, |ui| {
+                    self.log_scroll.show(ui, area, |ui| {
                         let layout = log::LOGGER.read().unwrap().format();
                         ui.label(layout);
                     });
@@ -128,108 +254,1829 @@ impl egui_tiles::Behavior<Identifier> for Tabs {
     }
 }
 
+/// A binary that's open but not shown, with it's panes and process kept as they were.
+struct Workspace {
+    tree: Tree<Identifier>,
+    mapping: BTreeMap<Identifier, PanelKind>,
+    processor: Arc<Processor>,
+    focused_listing: Identifier,
+    extra_listings: usize,
+    breakpoints: Arc<BreakpointSet>,
+    session: Option<Session>,
+}
+
+/// Tabs shown before any layout was changed, those of the panels set to be open in the settings.
+fn default_tree() -> Tree<Identifier> {
+    let mut tiles = Tiles::default();
+    let mut tabs: Vec<TileId> = SETTINGS
+        .read()
+        .unwrap()
+        .panels
+        .iter()
+        .filter_map(|name| PANELS.iter().find(|(panel, _)| *panel == name.as_str()))
+        .map(|(_, ident)| tiles.insert_pane(*ident))
+        .collect();
+
+    if tabs.is_empty() {
+        tabs.push(tiles.insert_pane(DISASSEMBLY));
+    }
+
+    let root: TileId = tiles.insert_tab_tile(tabs);
+    Tree::new("tree", root, tiles)
+}
+
 pub struct Panels {
     tree: Tree<Identifier>,
     panes: Tabs,
+    /// Binaries that are open in the order they were opened, the one shown being `None` as
+    /// it's in the panes.
+    workspaces: Vec<Option<Workspace>>,
+    /// Index of the binary shown into `workspaces`.
+    active: usize,
     ui_queue: Arc<crate::UiQueue>,
     #[allow(dead_code)] // used on windows and linux for top bar
     winit_queue: WinitQueue,
-    loading: bool,
+    /// Binary being loaded, if any.
+    loading: Option<LoadProgress>,
+    /// Shared with the listings and the debugger.
+    breakpoints: Arc<BreakpointSet>,
+    session: Option<Session>,
+    /// Process to attach to once it's binary is loaded.
+    pending_attach: Option<u32>,
+    /// Remote stub to connect to once the binary it's debugging is loaded.
+    pending_connect: Option<String>,
+    /// Core dump to inspect once the binary that wrote it is loaded.
+    pending_core: Option<PathBuf>,
+    /// Session whose process exec'd the binary that's loading, which keeps being debugged.
+    pending_exec: Option<usize>,
+    process_picker: ProcessPicker,
+    raw_loader: RawLoader,
+    member_picker: MemberPicker,
+    /// How the next debugged process is debugged.
+    session_options: session::Options,
+    /// Arguments and environment of processes started with `run`.
+    run_config: RunConfig,
+    /// Whether imports are followed into shared libraries when no process is debugged.
+    load_libraries: bool,
+    /// Shared libraries loaded to follow imports or processes into, kept to be shown again.
+    libraries: Vec<Arc<Processor>>,
+    goto_palette: GotoPalette,
+    /// Whether a text field other than the terminal had focus during the last frame.
+    text_input_focused: bool,
+    /// Theme and font size the widgets were last styled with.
+    styled: (Theme, f32),
+    /// Format the listings were last tokenized with.
+    formatted: tokenizing::Format,
+    preferences: Preferences,
 }
 
 impl Panels {
     pub fn new(ui_queue: Arc<crate::UiQueue>, winit_queue: WinitQueue) -> Self {
-        let mut tiles = Tiles::default();
-        let tabs = vec![
-            tiles.insert_pane(DISASSEMBLY),
-            tiles.insert_pane(FUNCTIONS),
-            tiles.insert_pane(LOGGING),
-        ];
-        let root: TileId = tiles.insert_tab_tile(tabs);
-        let tree = Tree::new("tree", root, tiles);
-
         Self {
-            tree,
+            tree: default_tree(),
             panes: Tabs::new(),
+            workspaces: Vec::new(),
+            active: 0,
             ui_queue,
             winit_queue,
-            loading: false,
+            loading: None,
+            breakpoints: Arc::new(BreakpointSet::default()),
+            session: None,
+            pending_attach: None,
+            pending_connect: None,
+            pending_core: None,
+            pending_exec: None,
+            process_picker: ProcessPicker::default(),
+            raw_loader: RawLoader::default(),
+            member_picker: MemberPicker::default(),
+            session_options: session::Options {
+                trace_syscalls: commands::ARGS.tracing,
+                ..session::Options::default()
+            },
+            run_config: RunConfig::default(),
+            load_libraries: false,
+            libraries: Vec::new(),
+            goto_palette: GotoPalette::default(),
+            text_input_focused: false,
+            styled: (theme(), SETTINGS.read().unwrap().font_size),
+            formatted: tokenizing::format(),
+            preferences: Preferences::default(),
+        }
+    }
+
+    /// The focused listing.
+    pub fn listing(&mut self) -> Option<&mut listing::Listing> {
+        self.panes.mapping.get_mut(self.panes.focused_listing).and_then(|kind| match kind {
+            PanelKind::Disassembly(listing) => Some(listing),
+            _ => None,
+        })
+    }
+
+    pub fn focused_listing(&self) -> Identifier {
+        self.panes.focused_listing
+    }
+
+    /// Open another listing tab at `addr`, independent of the other listings.
+    pub fn open_listing(&mut self, addr: usize) {
+        let listing = match self.listing() {
+            Some(listing) => listing.fork(addr),
+            None => return,
+        };
+
+        self.panes.extra_listings += 1;
+
+        // Identifiers are static as most tabs are known at compile time, these are few enough
+        // that leaking them is fine.
+        let title = format!("{DISASSEMBLY} ({})", self.panes.extra_listings + 1);
+        let title: Identifier = Box::leak(title.into_boxed_str());
+
+        self.panes.mapping.insert(title, PanelKind::Disassembly(listing));
+        self.panes.focused_listing = title;
+        self.goto_window(title);
+    }
+
+    /// Take the shown binary out of the panes, leaving them as they are without a binary.
+    fn take_workspace(&mut self) -> Option<Workspace> {
+        let processor = self.panes.processor.take()?;
+
+        // The logs aren't of any binary.
+        let mut mapping = std::mem::take(&mut self.panes.mapping);
+        mapping.remove(LOGGING);
+        self.panes.mapping.insert(LOGGING, PanelKind::Logging);
+
+        Some(Workspace {
+            tree: std::mem::replace(&mut self.tree, default_tree()),
+            mapping,
+            processor,
+            focused_listing: std::mem::replace(&mut self.panes.focused_listing, DISASSEMBLY),
+            extra_listings: std::mem::take(&mut self.panes.extra_listings),
+            breakpoints: std::mem::take(&mut self.breakpoints),
+            session: self.session.take(),
+        })
+    }
+
+    fn put_workspace(&mut self, workspace: Workspace) {
+        self.tree = workspace.tree;
+        self.panes.mapping = workspace.mapping;
+        self.panes.mapping.insert(LOGGING, PanelKind::Logging);
+        self.panes.processor = Some(workspace.processor);
+        self.panes.focused_listing = workspace.focused_listing;
+        self.panes.extra_listings = workspace.extra_listings;
+        self.breakpoints = workspace.breakpoints;
+        self.session = workspace.session;
+    }
+
+    /// Binaries that are open, in the order they were opened.
+    fn binaries(&self) -> impl Iterator<Item = &Arc<Processor>> {
+        self.workspaces.iter().map(|workspace| match workspace {
+            Some(workspace) => &workspace.processor,
+            None => self.panes.processor.as_ref().unwrap(),
+        })
+    }
+
+    /// Show the binary opened at `idx`, keeping the one shown as it is.
+    pub fn switch_binary(&mut self, idx: usize) {
+        if idx == self.active || idx >= self.workspaces.len() || self.is_loading() {
+            return;
+        }
+
+        self.workspaces[self.active] = self.take_workspace();
+        if let Some(workspace) = self.workspaces[idx].take() {
+            self.put_workspace(workspace);
+        }
+
+        self.active = idx;
+    }
+
+    /// Close the binary opened at `idx`, along with the process debugged from it.
+    pub fn close_binary(&mut self, idx: usize) {
+        if idx >= self.workspaces.len() || self.is_loading() {
+            return;
+        }
+
+        if idx != self.active {
+            self.workspaces.remove(idx);
+            if idx < self.active {
+                self.active -= 1;
+            }
+            return;
+        }
+
+        self.take_workspace();
+        self.workspaces.remove(idx);
+        self.active = idx.min(self.workspaces.len().saturating_sub(1));
+        if let Some(workspace) = self.workspaces.get_mut(self.active).and_then(Option::take) {
+            self.put_workspace(workspace);
+        }
+    }
+
+    pub fn close_shown_binary(&mut self) {
+        self.close_binary(self.active);
+    }
+
+    /// Show the binary debugged by session `id`, returns false if it isn't debugging any.
+    fn show_session(&mut self, id: usize) -> bool {
+        let of_session = |session: &Option<Session>| session.as_ref().map(Session::id) == Some(id);
+        if of_session(&self.session) {
+            return true;
+        }
+
+        let idx = self.workspaces.iter().position(|workspace| {
+            workspace.as_ref().is_some_and(|workspace| of_session(&workspace.session))
+        });
+
+        match idx {
+            Some(idx) => {
+                self.switch_binary(idx);
+                of_session(&self.session)
+            }
+            None => false,
+        }
+    }
+
+    /// Panes of the binary debugged by session `id`, whether it's shown or not.
+    fn session_mapping(&mut self, id: usize) -> Option<&mut BTreeMap<Identifier, PanelKind>> {
+        if self.session.as_ref().map(Session::id) == Some(id) {
+            return Some(&mut self.panes.mapping);
+        }
+
+        self.workspaces.iter_mut().flatten().find_map(|workspace| match workspace.session {
+            Some(ref session) if session.id() == id => Some(&mut workspace.mapping),
+            _ => None,
+        })
+    }
+
+    /// Show the bytes backing `addr` in the hex view.
+    pub fn goto_hex(&mut self, addr: usize) {
+        if let Some(PanelKind::Hex(hex)) = self.panes.mapping.get_mut(HEX) {
+            if hex.jump(addr) {
+                self.goto_window(HEX);
+            } else {
+                log::complex!(
+                    w "[panels::goto_hex] ",
+                    g format!("{addr:#x}"),
+                    w " isn't stored in the binary.",
+                );
+            }
+        }
+    }
+
+    pub fn breakpoints(&self) -> &Arc<BreakpointSet> {
+        &self.breakpoints
+    }
+
+    pub fn session(&mut self) -> Option<&mut Session> {
+        self.session.as_mut()
+    }
+
+    /// Start debugging `processor`'s binary, killing the process debugged before.
+    pub fn start_session(
+        &mut self,
+        processor: &Arc<Processor>,
+        args: Vec<String>,
+    ) -> Result<(), debugger::Error> {
+        self.session = None;
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+        self.clear_syscalls();
+        let session = Session::start(
+            processor.path.clone(),
+            args,
+            self.run_config.env.clone(),
+            Arc::clone(&self.breakpoints),
+            processor.entrypoint,
+            debug_info(processor),
+            self.session_options.clone(),
+            Arc::clone(&self.ui_queue),
+        )?;
+
+        if let Some(PanelKind::Console(console)) = self.panes.mapping.get_mut(CONSOLE) {
+            console.start(session.id());
+        }
+
+        self.session = Some(session);
+        Ok(())
+    }
+
+    /// Start debugging the loaded binary with `args`, which replace the configured arguments
+    /// unless there are none.
+    pub fn run(&mut self, args: Vec<String>) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        if !args.is_empty() {
+            self.run_config.args = args;
+        }
+
+        match self.start_session(&processor, self.run_config.args.clone()) {
+            Ok(()) => {
+                crate::tprint!(self.terminal(), "Started process {}.", processor.path.display())
+            }
+            Err(err) => crate::tprint!(self.terminal(), "Failed to start process: {err}"),
+        }
+    }
+
+    /// Arguments and environment of processes started with `run`.
+    pub fn run_config(&mut self) -> &mut RunConfig {
+        &mut self.run_config
+    }
+
+    /// Start the debugged process again with the arguments it was started with.
+    pub fn restart_session(&mut self) {
+        let args = match self.session {
+            Some(ref session) if session.is_attached() || session.is_post_mortem() => {
+                let msg = "Only processes started with `run` can be restarted.";
+                crate::tprint!(self.terminal(), "{msg}");
+                return;
+            }
+            Some(ref session) => session.args().to_vec(),
+            None => {
+                crate::tprint!(self.terminal(), "No process is being debugged.");
+                return;
+            }
+        };
+
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => return,
+        };
+
+        match self.start_session(&processor, args) {
+            Ok(()) => crate::tprint!(
+                self.terminal(),
+                "Restarted process {}.",
+                processor.path.display()
+            ),
+            Err(err) => crate::tprint!(self.terminal(), "Failed to restart process: {err}"),
+        }
+    }
+
+    /// Load the binary `pid` was started from and attach to it once loaded.
+    pub fn request_attach(&mut self, pid: u32) {
+        if self.is_loading() {
+            crate::tprint!(self.terminal(), "Can't attach whilst a binary is loading.");
+            return;
+        }
+
+        let path = match debugger::exe_path(pid) {
+            Ok(path) => path,
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to find the binary of {pid}: {err}");
+                return;
+            }
+        };
+
+        self.pending_attach = Some(pid);
+        self.ui_queue.push(crate::UIEvent::BinaryRequested(path));
+    }
+
+    /// Connect to the remote stub at `addr` once the binary being loaded is.
+    pub fn request_connect(&mut self, addr: String) {
+        self.pending_connect = Some(addr);
+    }
+
+    /// Inspect the core dump at `path` against the binary being loaded, or if there's none the
+    /// binary that wrote it.
+    pub fn request_core(&mut self, path: PathBuf) {
+        if self.is_loading() {
+            self.pending_core = Some(path);
+            return;
+        }
+
+        if self.processor().is_some() {
+            self.pending_core = Some(path);
+            self.open_pending_core();
+            return;
+        }
+
+        match debugger::coredump::executable(&path) {
+            Ok(binary) => {
+                self.pending_core = Some(path);
+                self.ui_queue.push(crate::UIEvent::BinaryRequested(binary));
+            }
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to find the binary of the core: {err}")
+            }
+        }
+    }
+
+    /// Forget about attaching or the binary a process exec'd after the binary failed to load.
+    pub fn cancel_attach(&mut self) {
+        self.pending_attach = None;
+        self.pending_connect = None;
+        self.pending_core = None;
+        self.pending_exec = None;
+    }
+
+    /// Attach to `pid`, whose binary must be the one loaded.
+    pub fn attach_session(&mut self, pid: u32) -> Result<(), debugger::Error> {
+        let (entrypoint, debug) = match self.processor() {
+            Some(processor) => (processor.entrypoint, debug_info(processor)),
+            None => return Ok(()),
+        };
+
+        self.session = None;
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+        self.clear_syscalls();
+        self.session = Some(Session::attach(
+            pid,
+            Arc::clone(&self.breakpoints),
+            entrypoint,
+            debug,
+            self.session_options.clone(),
+            Arc::clone(&self.ui_queue),
+        )?);
+
+        Ok(())
+    }
+
+    /// Debug the target of the remote stub at `addr`, which must be running the loaded binary.
+    pub fn connect_session(&mut self, addr: String) -> Result<(), debugger::Error> {
+        let debug = match self.processor() {
+            Some(processor) => debug_info(processor),
+            None => return Ok(()),
+        };
+
+        self.session = None;
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+        self.clear_syscalls();
+        self.session = Some(Session::connect(
+            addr,
+            Arc::clone(&self.breakpoints),
+            debug,
+            Arc::clone(&self.ui_queue),
+        )?);
+
+        Ok(())
+    }
+
+    /// Inspect the core dump at `path`, which must have been written by the loaded binary.
+    pub fn core_session(&mut self, path: PathBuf) -> Result<(), debugger::Error> {
+        let (entrypoint, debug) = match self.processor() {
+            Some(processor) => (processor.entrypoint, debug_info(processor)),
+            None => return Ok(()),
+        };
+
+        self.session = None;
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+        self.clear_syscalls();
+        self.session = Some(Session::core(path, entrypoint, debug, Arc::clone(&self.ui_queue))?);
+
+        Ok(())
+    }
+
+    fn open_pending_core(&mut self) {
+        if let Some(path) = self.pending_core.take() {
+            match self.core_session(path.clone()) {
+                Ok(()) => crate::tprint!(self.terminal(), "Opened core {}.", path.display()),
+                Err(err) => crate::tprint!(self.terminal(), "Failed to open core: {err}"),
+            }
+        }
+    }
+
+    pub fn kill_session(&mut self) -> bool {
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+
+        match self.session.take() {
+            Some(session) => {
+                // Attached processes would otherwise be left running.
+                session.handle().kill();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop debugging an attached process, returns whether the session was attached.
+    pub fn detach_session(&mut self) -> Option<bool> {
+        if !self.session.as_ref()?.is_attached() {
+            return Some(false);
+        }
+
+        self.session = None;
+        self.update_registers(None);
+        self.update_memory_maps(Vec::new());
+        self.update_call_stack(Vec::new());
+        self.update_variables(None, &[]);
+        self.update_threads(Vec::new());
+        Some(true)
+    }
+
+    pub fn breakpoints_changed(&mut self) {
+        if let Some(ref session) = self.session {
+            session.breakpoints_changed();
+        }
+    }
+
+    /// Show comments and renames made by the user in all panes.
+    pub fn annotations_changed(&mut self) {
+        for kind in self.panes.mapping.values_mut() {
+            match kind {
+                PanelKind::Disassembly(listing) => listing.refresh(),
+                PanelKind::Functions(functions) => functions.refresh(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Undo the last edit made to the analysis.
+    pub fn undo(&mut self) {
+        let edit = match self.panes.processor {
+            Some(ref processor) => processor.undo(),
+            None => return,
+        };
+
+        match edit {
+            Some(edit) => self.edited(edit),
+            None => crate::tprint!(self.terminal(), "Nothing to undo."),
+        }
+    }
+
+    /// Redo the last edit that was undone.
+    pub fn redo(&mut self) {
+        let edit = match self.panes.processor {
+            Some(ref processor) => processor.redo(),
+            None => return,
+        };
+
+        match edit {
+            Some(edit) => self.edited(edit),
+            None => crate::tprint!(self.terminal(), "Nothing to redo."),
+        }
+    }
+
+    /// Show what changed after `edit` was undone or redone.
+    fn edited(&mut self, edit: Edit) {
+        match edit {
+            Edit::Annotation { .. } => self.annotations_changed(),
+            Edit::Patch { .. } | Edit::Definition { .. } => self.binary_patched(),
+            Edit::CodeSection { .. } => self.sections_changed(),
+        }
+    }
+
+    /// Show the patched bytes in all listings.
+    pub fn binary_patched(&mut self) {
+        for kind in self.panes.mapping.values_mut() {
+            if let PanelKind::Disassembly(listing) = kind {
+                listing.binary_patched();
+            }
+        }
+    }
+
+    /// A section was toggled being decoded as code, so it's blocks are computed again.
+    pub fn sections_changed(&mut self) {
+        for kind in self.panes.mapping.values_mut() {
+            match kind {
+                PanelKind::Disassembly(listing) => listing.sections_changed(),
+                PanelKind::Segments(segments) => segments.sections_changed(),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn debugger_stopped(
+        &mut self,
+        id: usize,
+        stop: Stop,
+        registers: Option<Registers>,
+        frames: Vec<Frame>,
+    ) {
+        if !self.show_session(id) {
+            return;
+        }
+
+        if let Some(ref mut session) = self.session {
+            session.stopped();
+        }
+
+        self.update_variables(registers.as_ref(), &frames);
+        self.update_registers(registers);
+        self.update_call_stack(frames);
+        self.set_undoable(0);
+
+        let regions = match (stop, &self.session) {
+            (Stop::Exited(..) | Stop::Killed(..), _) | (_, None) => Vec::new(),
+            (_, Some(session)) => session.handle().memory_maps().unwrap_or_default(),
+        };
+        self.update_memory_maps(regions.clone());
+
+        crate::tprint!(self.terminal(), "{stop}");
+
+        match stop {
+            Stop::Attached(addr)
+            | Stop::Breakpoint(addr)
+            | Stop::Stepped(addr)
+            | Stop::Watchpoint(_, addr)
+            | Stop::Signal(_, addr)
+            | Stop::Forked(_, addr) => self.show_stop(addr, &regions),
+            Stop::Exec(pid, _) => self.load_exec(id, pid),
+            Stop::Exited(..) | Stop::Killed(..) => self.session = None,
+        }
+
+        if let (Stop::Forked(..), FollowFork::Ask) = (stop, self.session_options.follow_fork) {
+            crate::tprint!(
+                self.terminal(),
+                "Use 'set follow-fork child' to debug the child once continued."
+            );
+        }
+    }
+
+    /// Show where the process stopped in the binary's listing, or in the listing of the library
+    /// it stopped in.
+    fn show_stop(&mut self, addr: usize, regions: &[MemoryRegion]) {
+        let binary = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => return,
+        };
+
+        // Listings of libraries have addresses of their own.
+        if !self.listing().is_some_and(|listing| Arc::ptr_eq(listing.processor(), &binary)) {
+            self.panes.focused_listing = DISASSEMBLY;
+        }
+
+        if binary.section_by_addr(addr).is_none() {
+            if let Some((path, offset)) = mapped_file(&binary, addr, regions) {
+                self.show_library(path, move |library| library.addr_by_offset(offset));
+                return;
+            }
+        }
+
+        if let Some(listing) = self.listing() {
+            listing.jump(addr);
+            let focused = self.focused_listing();
+            self.goto_window(focused);
+        }
+
+        self.load_src(addr);
+    }
+
+    pub fn set_load_libraries(&mut self, enabled: bool) {
+        self.load_libraries = enabled;
+    }
+
+    /// Show where `binary` imports `import` from, loading the library that exports it in the
+    /// background.
+    pub fn follow_import(&mut self, binary: Arc<Processor>, import: Import) {
+        if self.session.is_none() && !self.load_libraries {
+            crate::tprint!(
+                self.terminal(),
+                "Use 'set libraries on' to follow imports whilst not debugging."
+            );
+            return;
+        }
+
+        crate::tprint!(self.terminal(), "Looking for {} in the imported libraries.", import.name);
+
+        let ui_queue = Arc::clone(&self.ui_queue);
+        let loaded = self.libraries.clone();
+        std::thread::spawn(move || {
+            let library = binary.libraries().into_iter().find(|library| {
+                import.module.as_deref().map_or(true, |module| library.is_module(module))
+                    && library.exports(&import.name)
+            });
+
+            match library.and_then(|library| library.path) {
+                Some(path) => load_library(&ui_queue, &loaded, path, |library| {
+                    library.export_by_name(&import.name)
+                }),
+                None => log::complex!(
+                    w "[panels::follow_import] ",
+                    w "None of the libraries found exports ",
+                    g import.name,
+                    w ".",
+                ),
+            }
+        });
+    }
+
+    /// Show the address `target` finds in the library at `path`, loading it in the background
+    /// unless it's listing is open.
+    fn show_library(
+        &mut self,
+        path: PathBuf,
+        target: impl FnOnce(&Processor) -> Option<usize> + Send + 'static,
+    ) {
+        let path = path.canonicalize().unwrap_or(path);
+        let library = self.libraries.iter().find(|library| library.path == path).cloned();
+
+        if let Some(library) = library {
+            let title = library_title(&library);
+            if self.panes.mapping.contains_key(title.as_str()) {
+                if let Some(addr) = target(&library) {
+                    self.library_loaded(library, Vec::new(), addr);
+                }
+                return;
+            }
+        }
+
+        let ui_queue = Arc::clone(&self.ui_queue);
+        let loaded = self.libraries.clone();
+        std::thread::spawn(move || load_library(&ui_queue, &loaded, path, target));
+    }
+
+    /// Show `addr` in the listing of `library`, opening one if it isn't open yet.
+    pub fn library_loaded(&mut self, library: Arc<Processor>, boundaries: Vec<usize>, addr: usize) {
+        if !self.libraries.iter().any(|loaded| Arc::ptr_eq(loaded, &library)) {
+            self.libraries.push(Arc::clone(&library));
+        }
+
+        let title = library_title(&library);
+        let title: Identifier = match self.panes.mapping.get_key_value(title.as_str()) {
+            Some((title, _)) => title,
+            None => {
+                let title: Identifier = Box::leak(title.into_boxed_str());
+
+                // Breakpoints are set by the binary's addresses, which the library doesn't share.
+                let listing = listing::Listing::new(
+                    library,
+                    self.ui_queue.clone(),
+                    Arc::default(),
+                    Some(boundaries),
+                );
+
+                self.panes.mapping.insert(title, PanelKind::Disassembly(listing));
+                title
+            }
+        };
+
+        if let Some(PanelKind::Disassembly(listing)) = self.panes.mapping.get_mut(title) {
+            if !listing.jump(addr) {
+                listing.jump_near(addr);
+            }
+        }
+
+        self.panes.focused_listing = title;
+        self.goto_window(title);
+    }
+
+    /// Load the binary process `pid` of session `id` exec'd, the session is kept.
+    fn load_exec(&mut self, id: usize, pid: u32) {
+        if self.is_loading() {
+            crate::tprint!(self.terminal(), "Can't load the exec'd binary whilst loading.");
+            return;
+        }
+
+        match debugger::exe_path(pid) {
+            Ok(path) => {
+                self.pending_exec = Some(id);
+                self.ui_queue.push(crate::UIEvent::BinaryRequested(path));
+            }
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to find the binary of {pid}: {err}")
+            }
+        }
+    }
+
+    /// Show the process that exec'd against it's binary, now that it's loaded.
+    pub fn debugger_reloaded(
+        &mut self,
+        id: usize,
+        registers: Option<Registers>,
+        frames: Vec<Frame>,
+    ) {
+        if !self.show_session(id) {
+            return;
+        }
+
+        let regions = match self.session {
+            Some(ref session) => session.handle().memory_maps().unwrap_or_default(),
+            None => return,
+        };
+
+        let pc = frames.first().map(|frame| frame.pc);
+        self.update_variables(registers.as_ref(), &frames);
+        self.update_registers(registers);
+        self.update_memory_maps(regions);
+        self.update_call_stack(frames);
+
+        if let Some(pc) = pc {
+            self.goto_frame(pc);
+        }
+    }
+
+    pub fn threads_listed(&mut self, id: usize, threads: Vec<Thread>) {
+        if let Some(PanelKind::Threads(view)) =
+            self.session_mapping(id).and_then(|mapping| mapping.get_mut(THREADS))
+        {
+            view.update(threads);
+        }
+    }
+
+    /// Show the registers and call stack of the thread that's now inspected.
+    pub fn thread_selected(&mut self, id: usize, registers: Option<Registers>, frames: Vec<Frame>) {
+        if !self.show_session(id) {
+            return;
+        }
+
+        let pc = frames.first().map(|frame| frame.pc);
+        self.update_variables(registers.as_ref(), &frames);
+        self.update_registers(registers);
+        self.update_call_stack(frames);
+
+        if let Some(pc) = pc {
+            crate::tprint!(self.terminal(), "Switched to a thread stopped at {pc:#x}.");
+            self.goto_frame(pc);
+        }
+    }
+
+    /// Inspect thread `tid` of the stopped process.
+    pub fn select_thread(&mut self, tid: u32) {
+        let known = match self.panes.mapping.get(THREADS) {
+            Some(PanelKind::Threads(view)) => view.contains(tid),
+            _ => false,
+        };
+
+        let selected = match self.session {
+            Some(ref session) if known => session.select_thread(tid),
+            Some(_) => {
+                crate::tprint!(self.terminal(), "Thread {tid} doesn't exist.");
+                return;
+            }
+            None => {
+                crate::tprint!(self.terminal(), "No process is being debugged.");
+                return;
+            }
+        };
+
+        if !selected {
+            crate::tprint!(self.terminal(), "Can't switch threads whilst the process is running.");
+        }
+    }
+
+    /// Continue the stopped process, or step it if `step` is set.
+    pub fn resume(&mut self, step: Option<Step>) {
+        let resumed = self.session.as_mut().map(|session| match step {
+            Some(how) => (session.is_post_mortem(), session.step(how)),
+            None => (session.is_post_mortem(), session.resume()),
+        });
+
+        match resumed {
+            Some((true, _)) => crate::tprint!(self.terminal(), "Cores can't be resumed."),
+            Some((false, true)) => {}
+            Some((false, false)) => crate::tprint!(self.terminal(), "Process is already running."),
+            None => crate::tprint!(self.terminal(), "No process is being debugged."),
+        }
+    }
+
+    pub fn debugger_failed(&mut self, id: usize) {
+        if self.show_session(id) {
+            self.session = None;
+            self.update_registers(None);
+            self.update_memory_maps(Vec::new());
+            self.update_call_stack(Vec::new());
+            self.update_variables(None, &[]);
+            self.update_threads(Vec::new());
+        }
+    }
+
+    /// Stop the running process, which is then shown like any other stop.
+    pub fn pause(&mut self) {
+        match self.session.as_ref().map(Session::pause) {
+            Some(true) => {}
+            Some(false) => crate::tprint!(self.terminal(), "Process isn't running."),
+            None => crate::tprint!(self.terminal(), "No process is being debugged."),
+        }
+    }
+
+    /// Show what the started process of session `id` wrote, even once it exited.
+    pub fn process_output(&mut self, id: usize, stream: Stream, text: String) {
+        let workspaces = self.workspaces.iter_mut().flatten();
+        let mappings = std::iter::once(&mut self.panes.mapping)
+            .chain(workspaces.map(|workspace| &mut workspace.mapping));
+
+        for mapping in mappings {
+            if let Some(PanelKind::Console(console)) = mapping.get_mut(CONSOLE) {
+                if console.session() == Some(id) {
+                    console.push(stream, &text);
+                }
+            }
+        }
+    }
+
+    /// Write `text` to the stdin of the started process.
+    pub fn send_input(&mut self, text: String) {
+        let sent = match self.session {
+            Some(ref session) => session.send_input(text.clone().into_bytes()),
+            None => {
+                crate::tprint!(self.terminal(), "No process is being debugged.");
+                return;
+            }
+        };
+
+        if !sent {
+            let msg = "Only the input of processes started with `run` that's still open can be \
+                       sent.";
+            crate::tprint!(self.terminal(), "{msg}");
+            return;
+        }
+
+        if let Some(PanelKind::Console(console)) = self.panes.mapping.get_mut(CONSOLE) {
+            console.push(Stream::Stdin, &text);
+        }
+    }
+
+    /// Close the stdin of the started process, which it reads as the end of it's input.
+    pub fn close_input(&mut self) {
+        if !self.session.as_mut().is_some_and(Session::close_input) {
+            crate::tprint!(self.terminal(), "There's no open input of a started process.");
+        }
+    }
+
+    /// Trace the syscalls of processes debugged from now on.
+    pub fn set_trace_syscalls(&mut self, enabled: bool) {
+        self.session_options.trace_syscalls = enabled;
+    }
+
+    /// Choose which process to debug after forks, including of the process being debugged.
+    pub fn set_follow_fork(&mut self, follow: FollowFork) {
+        self.session_options.follow_fork = follow;
+        if let Some(ref session) = self.session {
+            session.set_follow_fork(follow);
+        }
+    }
+
+    /// How signals are handled by processes debugged from now on.
+    pub fn signals(&self) -> &Signals {
+        &self.session_options.signals
+    }
+
+    /// Choose which signals stop processes and which are passed on to them, including the
+    /// process being debugged.
+    pub fn set_signals(&mut self, signals: Signals) {
+        if let Some(ref session) = self.session {
+            session.set_signals(signals.clone());
+        }
+        self.session_options.signals = signals;
+    }
+
+    /// Record the instructions executed whilst continuing processes debugged from now on,
+    /// including the process being debugged.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.session_options.record = enabled;
+        if let Some(ref mut session) = self.session {
+            session.set_recording(enabled);
+        }
+    }
+
+    /// Show the instructions recorded so far, coloring the listings by how often each was
+    /// executed.
+    pub fn trace_recorded(&mut self, id: usize, recorded: Arc<Trace>) {
+        let mapping = match self.session_mapping(id) {
+            Some(mapping) => mapping,
+            None => return,
+        };
+
+        let binary = match mapping.get_mut(TRACE) {
+            Some(PanelKind::Trace(view)) => {
+                view.update(Arc::clone(&recorded));
+                Arc::clone(view.processor())
+            }
+            _ => return,
+        };
+
+        // Listings of libraries are left alone, as the addresses are those of the binary.
+        let heatmap = Arc::new(trace::Heatmap::new(recorded));
+        for kind in mapping.values_mut() {
+            if let PanelKind::Disassembly(listing) = kind {
+                if Arc::ptr_eq(listing.processor(), &binary) {
+                    listing.set_heatmap(Arc::clone(&heatmap));
+                }
+            }
+        }
+    }
+
+    /// Write the instructions last recorded to a text or JSON file.
+    pub fn export_trace(&mut self) {
+        let recorded = match self.panes.mapping.get(TRACE) {
+            Some(PanelKind::Trace(view)) => view.trace().cloned(),
+            _ => None,
+        };
+
+        let recorded = match recorded {
+            Some(recorded) if !recorded.is_empty() => recorded,
+            _ => {
+                crate::tprint!(self.terminal(), "No instructions have been recorded.");
+                return;
+            }
+        };
+
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .add_filter("JSON", &["json"])
+            .set_file_name("trace.txt");
+
+        let path = match dialog.save_file() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        crate::tprint!(self.terminal(), "Exporting trace to {}.", path.display());
+
+        // Traces can be hundreds of megabytes, so they're written in the background.
+        std::thread::spawn(move || {
+            let export = || -> std::io::Result<()> {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+                match json {
+                    true => recorded.write_json(&mut file),
+                    false => recorded.write_text(&mut file),
+                }
+            };
+
+            match export() {
+                Ok(()) => log::trace!("Exported trace to {}.", path.display()),
+                Err(err) => log::warning!("Failed to export trace: {err}."),
+            }
+        });
+    }
+
+    pub fn syscall_traced(&mut self, id: usize, syscall: Syscall) {
+        if let Some(PanelKind::Syscalls(view)) =
+            self.session_mapping(id).and_then(|mapping| mapping.get_mut(SYSCALLS))
+        {
+            view.push(syscall);
+        }
+    }
+
+    /// Keep the libraries loaded into the process of session `id`, printing which ones were
+    /// loaded or unloaded if it's shown.
+    pub fn libraries_changed(&mut self, id: usize, libraries: Vec<Library>) {
+        let shown = self.session.as_ref().map(Session::id) == Some(id);
+        let session = self
+            .session
+            .iter_mut()
+            .chain(self.workspaces.iter_mut().flatten().filter_map(|ws| ws.session.as_mut()))
+            .find(|session| session.id() == id);
+
+        let previous = match session {
+            Some(session) => session.set_libraries(libraries.clone()),
+            None => return,
+        };
+
+        if !shown {
+            return;
+        }
+
+        for library in previous.iter().filter(|library| !libraries.contains(library)) {
+            crate::tprint!(self.terminal(), "Unloaded {}.", library.path.display());
+        }
+
+        for library in libraries.iter().filter(|library| !previous.contains(library)) {
+            crate::tprint!(
+                self.terminal(),
+                "Loaded {} at {:#X}.",
+                library.path.display(),
+                library.bias
+            );
+        }
+    }
+
+    pub fn breakpoint_logged(&mut self, id: usize, msg: String) {
+        if self.session_mapping(id).is_some() {
+            crate::tprint!(self.terminal(), "{msg}");
+        }
+    }
+
+    pub fn write_failed(&mut self, id: usize, msg: String) {
+        if self.session_mapping(id).is_some() {
+            crate::tprint!(self.terminal(), "{msg}");
+        }
+    }
+
+    /// Change the memory or a register of the stopped process.
+    pub fn write_process(&mut self, edit: session::Edit) {
+        let session = match self.session {
+            Some(ref session) => session,
+            None => {
+                crate::tprint!(self.terminal(), "No process is being debugged.");
+                return;
+            }
+        };
+
+        if session.is_post_mortem() {
+            crate::tprint!(self.terminal(), "Cores can't be changed.");
+        } else if !session.write(edit) {
+            crate::tprint!(self.terminal(), "Can't change the process whilst it's running.");
+        }
+    }
+
+    /// Undo the last change to the stopped process since it stopped.
+    pub fn undo_write(&mut self) {
+        if let Some(ref session) = self.session {
+            if !session.undo() {
+                crate::tprint!(self.terminal(), "Can't change the process whilst it's running.");
+            }
+        }
+    }
+
+    /// Show the registers and memory of the stopped process after it was changed.
+    pub fn process_written(
+        &mut self,
+        id: usize,
+        registers: Option<Registers>,
+        frames: Vec<Frame>,
+        undoable: usize,
+    ) {
+        if !self.show_session(id) {
+            return;
+        }
+
+        self.update_variables(registers.as_ref(), &frames);
+        self.update_registers(registers);
+        self.update_call_stack(frames);
+        self.set_undoable(undoable);
+
+        // Process memory shown in the hex view might have been written to.
+        let handle = self.session.as_ref().map(|session| session.handle().clone());
+        if let Some(handle) = handle {
+            if let Some(PanelKind::Hex(hex)) = self.panes.mapping.get_mut(HEX) {
+                hex.reload_dump(&handle);
+            }
+        }
+    }
+
+    /// Number of changes to the stopped process that can be undone, offered by the panes
+    /// making them.
+    fn set_undoable(&mut self, undoable: usize) {
+        if let Some(PanelKind::Registers(view)) = self.panes.mapping.get_mut(REGISTERS) {
+            view.set_undoable(undoable);
+        }
+
+        if let Some(PanelKind::Hex(hex)) = self.panes.mapping.get_mut(HEX) {
+            hex.set_undoable(undoable);
+        }
+    }
+
+    fn clear_syscalls(&mut self) {
+        if let Some(PanelKind::Syscalls(view)) = self.panes.mapping.get_mut(SYSCALLS) {
+            view.clear();
+        }
+    }
+
+    fn update_registers(&mut self, registers: Option<Registers>) {
+        if let Some(PanelKind::Registers(view)) = self.panes.mapping.get_mut(REGISTERS) {
+            view.update(registers);
+        }
+    }
+
+    /// Registers of the debugged process at it's last stop.
+    pub fn registers(&self) -> Option<&Registers> {
+        match self.panes.mapping.get(REGISTERS) {
+            Some(PanelKind::Registers(view)) => view.current(),
+            _ => None,
+        }
+    }
+
+    /// The selected thread if the process is stopped, for evaluating expressions against.
+    pub fn stopped(&self) -> Option<session::Stopped<'_>> {
+        let session = self.session.as_ref().filter(|session| !session.is_running())?;
+        let registers = self.registers()?;
+        let pc = registers.get("rip").or_else(|| registers.get("pc"))?;
+
+        // The innermost frame's pc is where the thread stopped in the binary.
+        let frame = match self.panes.mapping.get(CALL_STACK) {
+            Some(PanelKind::CallStack(view)) => view.frames().first(),
+            _ => None,
+        };
+
+        Some(session::Stopped {
+            registers,
+            handle: session.handle(),
+            bias: frame.map_or(0, |frame| (pc as usize).wrapping_sub(frame.pc)),
+        })
+    }
+
+    fn update_memory_maps(&mut self, regions: Vec<MemoryRegion>) {
+        if let Some(PanelKind::MemoryMaps(view)) = self.panes.mapping.get_mut(MEMORY_MAPS) {
+            view.update(regions);
+        }
+    }
+
+    fn update_call_stack(&mut self, frames: Vec<Frame>) {
+        if let Some(PanelKind::CallStack(view)) = self.panes.mapping.get_mut(CALL_STACK) {
+            view.update(frames);
+        }
+    }
+
+    /// Evaluate the variables of the innermost frame, `registers` being None once the process
+    /// is gone.
+    fn update_variables(&mut self, registers: Option<&Registers>, frames: &[Frame]) {
+        let handle = self.session.as_ref().map(|session| session.handle());
+        if let Some(PanelKind::Variables(view)) = self.panes.mapping.get_mut(VARIABLES) {
+            match (registers, handle) {
+                (Some(registers), Some(handle)) => view.update(Some((registers, frames, handle))),
+                _ => view.update(None),
+            }
+        }
+    }
+
+    fn update_threads(&mut self, threads: Vec<Thread>) {
+        if let Some(PanelKind::Threads(view)) = self.panes.mapping.get_mut(THREADS) {
+            view.update(threads);
+        }
+    }
+
+    /// Show a frame of the call stack in the listing and the source.
+    pub fn goto_frame(&mut self, addr: usize) {
+        if let Some(listing) = self.listing() {
+            if !listing.jump(addr) {
+                listing.jump_near(addr);
+            }
+            let focused = self.focused_listing();
+            self.goto_window(focused);
+        }
+
+        self.load_src(addr);
+    }
+
+    /// Show the control flow graph of the function containing `addr`.
+    pub fn show_flow_graph(&mut self, addr: usize) {
+        let func = match self.processor().and_then(|proc| proc.index.get_sym_containing(addr)) {
+            Some((func, _)) => func,
+            None => {
+                crate::tprint!(self.terminal(), "No function contains {addr:#X}.");
+                return;
+            }
+        };
+
+        if let Some(PanelKind::FlowGraph(graph)) = self.panes.mapping.get_mut(FLOW_GRAPH) {
+            if graph.show_function(func) {
+                self.goto_window(FLOW_GRAPH);
+            } else {
+                crate::tprint!(self.terminal(), "Function at {func:#X} has no instructions.");
+            }
+        }
+    }
+
+    /// Show the pseudocode of the function containing `addr`.
+    pub fn show_pseudocode(&mut self, addr: usize) {
+        if let Some(PanelKind::Pseudocode(code)) = self.panes.mapping.get_mut(PSEUDOCODE) {
+            if code.select(addr) {
+                self.goto_window(PSEUDOCODE);
+            } else {
+                crate::tprint!(self.terminal(), "No function contains {addr:#X}.");
+            }
+        }
+    }
+
+    /// Emulate from `addr` with the registers in `state`.
+    pub fn emulate(&mut self, addr: usize, state: &[(String, u64)]) {
+        if let Some(PanelKind::Emulation(emulation)) = self.panes.mapping.get_mut(EMULATION) {
+            if emulation.emulate(addr, state) {
+                self.goto_window(EMULATION);
+            } else {
+                crate::tprint!(self.terminal(), "Emulating this architecture isn't supported.");
+            }
+        }
+    }
+
+    /// Follow the instruction selected in the listing.
+    pub fn addr_selected(&mut self, addr: usize) {
+        if let Some(PanelKind::Pseudocode(code)) = self.panes.mapping.get_mut(PSEUDOCODE) {
+            code.select(addr);
+        }
+    }
+
+    /// Copy a region of the debugged process' memory into the hex view.
+    pub fn dump_memory(&mut self, region: MemoryRegion) {
+        let session = match self.session {
+            Some(ref session) => session,
+            None => return,
+        };
+
+        let len = region.len().min(MAX_DUMP_LEN);
+        let bytes = match session.handle().read_memory(region.start, len) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to read memory: {err}");
+                return;
+            }
+        };
+
+        if len < region.len() {
+            crate::tprint!(self.terminal(), "Only showing the first {len:#x} bytes of the region.");
+        }
+
+        let dump = hex::Dump {
+            addr: region.start,
+            bytes,
+            name: region.path.unwrap_or_else(|| "anonymous memory".to_string()),
+        };
+
+        if let Some(PanelKind::Hex(hex)) = self.panes.mapping.get_mut(HEX) {
+            hex.show_dump(dump);
+            self.goto_window(HEX);
+        }
+    }
+
+    #[inline]
+    pub fn processor(&mut self) -> Option<&Arc<Processor>> {
+        self.panes.processor.as_ref()
+    }
+
+    #[inline]
+    pub fn terminal(&mut self) -> &mut Terminal {
+        &mut self.panes.terminal
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Start loading a binary, cancelling the one being loaded if any.
+    pub fn start_loading(&mut self) -> processor::Loading {
+        if let Some(previous) = self.loading.take() {
+            previous.cancel();
+        }
+
+        // create new donut to restart internal timer
+        self.panes.donut = Donut::new(false);
+        let loading = LoadProgress::default();
+        let handle = loading.loading();
+        self.loading = Some(loading);
+        handle
+    }
+
+    /// Stop waiting on load `id`, returns false if it was replaced by another load.
+    pub fn stop_loading(&mut self, id: usize) -> bool {
+        match self.loading {
+            Some(ref loading) if loading.id() == id => {
+                self.loading = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Show the source line of the instruction at `addr`.
+    pub fn load_src(&mut self, addr: usize) {
+        let processor = match self.processor() {
+            Some(processor) => Arc::clone(processor),
+            None => return,
+        };
+
+        let file_attr = match processor.index.get_file_containing(addr) {
+            Some(file_attr) => file_attr,
+            None => return,
+        };
+
+        if let Ok(src) = std::fs::read_to_string(&file_attr.path) {
+            let src = source_code::Source::new(
+                &src,
+                file_attr,
+                &processor.index,
+                Arc::clone(&self.breakpoints),
+                Arc::clone(&self.ui_queue),
+            );
+            self.panes.mapping.insert(SOURCE, PanelKind::Source(src));
+        }
+    }
+
+    /// Show `processor`'s binary, `boundaries` being the listing's blocks if already known.
+    pub fn load_binary(&mut self, processor: Processor, boundaries: Option<Vec<usize>>) {
+        let processor = Arc::new(processor);
+        crate::style::change_settings(|settings| settings.opened(&processor.path));
+
+        {
+            // Finish indexing in the background whilst the binary is already shown.
+            let processor = Arc::clone(&processor);
+            std::thread::spawn(move || processor.index.complete());
+        }
+
+        // A process that exec'd the binary keeps being debugged.
+        let exec = match self.pending_exec.take() {
+            Some(id) if self.session.as_ref().map(Session::id) == Some(id) => self.session.take(),
+            _ => None,
+        };
+
+        // The binary replaces the one the process exec'd from, otherwise the binaries that are
+        // open are kept besides it.
+        if self.workspaces.is_empty() {
+            self.workspaces.push(None);
+        } else if exec.is_none() {
+            self.workspaces[self.active] = self.take_workspace();
+            self.workspaces.push(None);
+            self.active = self.workspaces.len() - 1;
+        }
+
+        // Breakpoints and the process of the previous binary.
+        self.session = None;
+        self.breakpoints.clear();
+
+        // Listings of the previous binary.
+        self.panes.mapping.retain(|_, kind| !matches!(kind, PanelKind::Disassembly(..)));
+        self.panes.focused_listing = DISASSEMBLY;
+
+        self.panes.mapping.insert(
+            DISASSEMBLY,
+            PanelKind::Disassembly(listing::Listing::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+                self.breakpoints.clone(),
+                boundaries,
+            )),
+        );
+
+        self.panes.mapping.insert(
+            FUNCTIONS,
+            PanelKind::Functions(functions::Functions::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            HEX,
+            PanelKind::Hex(hex::Hex::new(processor.clone(), self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            CALL_GRAPH,
+            PanelKind::CallGraph(call_graph::CallGraphView::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            STRINGS,
+            PanelKind::Strings(strings::Strings::new(processor.clone(), self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            REGISTERS,
+            PanelKind::Registers(registers::RegistersView::new(self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            MEMORY_MAPS,
+            PanelKind::MemoryMaps(memory_maps::MemoryMaps::new(self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(SYSCALLS, PanelKind::Syscalls(syscalls::Syscalls::default()));
+
+        self.panes.mapping.insert(
+            CONSOLE,
+            PanelKind::Console(console::Console::new(self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            TRACE,
+            PanelKind::Trace(trace::TraceView::new(processor.clone(), self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            CALL_STACK,
+            PanelKind::CallStack(call_stack::CallStack::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            VARIABLES,
+            PanelKind::Variables(variables::Variables::new(processor.clone())),
+        );
+
+        self.panes.mapping.insert(
+            THREADS,
+            PanelKind::Threads(threads::Threads::new(processor.clone(), self.ui_queue.clone())),
+        );
+
+        self.panes.mapping.insert(
+            FLOW_GRAPH,
+            PanelKind::FlowGraph(flow_graph::FlowGraph::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            PSEUDOCODE,
+            PanelKind::Pseudocode(pseudocode::Pseudocode::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            EMULATION,
+            PanelKind::Emulation(emulation::Emulation::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            OVERVIEW,
+            PanelKind::Overview(overview::Overview::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(INFO, PanelKind::Info(info::Info::new(processor.clone())));
+
+        self.panes.mapping.insert(
+            BOOKMARKS,
+            PanelKind::Bookmarks(bookmarks::Bookmarks::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        self.panes.mapping.insert(
+            SEGMENTS,
+            PanelKind::Segments(segments::Segments::new(
+                processor.clone(),
+                self.ui_queue.clone(),
+            )),
+        );
+
+        if let Some(session) = exec {
+            session.exec(debug_info(&processor));
+            self.session = Some(session);
+        }
+
+        self.panes.processor = Some(processor);
+
+        if let Some(pid) = self.pending_attach.take() {
+            match self.attach_session(pid) {
+                Ok(()) => crate::tprint!(self.terminal(), "Attaching to {pid}."),
+                Err(err) => crate::tprint!(self.terminal(), "Failed to attach to {pid}: {err}"),
+            }
+        }
+
+        if let Some(addr) = self.pending_connect.take() {
+            match self.connect_session(addr.clone()) {
+                Ok(()) => crate::tprint!(self.terminal(), "Connected to {addr}."),
+                Err(err) => crate::tprint!(self.terminal(), "Failed to connect to {addr}: {err}"),
+            }
+        }
+
+        self.open_pending_core();
+    }
+
+    pub fn open_preferences(&mut self) {
+        self.preferences.open();
+    }
+
+    pub fn ask_for_binary(&self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.ui_queue.push(crate::UIEvent::BinaryRequested(path));
+        }
+    }
+
+    /// Pick a file to load without looking for a header, such as firmware.
+    pub fn ask_for_raw_binary(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.raw_loader.open(path);
+        }
+    }
+
+    /// Pick a core dump or minidump to inspect.
+    pub fn ask_for_core(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.request_core(path);
+        }
+    }
+
+    /// Ask for the architecture and base address to load `path` with.
+    pub fn ask_for_raw_options(&mut self, path: PathBuf) {
+        self.raw_loader.open(path);
+    }
+
+    /// Ask which of the `members` of the archive or fat Mach-O at `path` to load.
+    pub fn ask_for_member(&mut self, path: PathBuf, members: Vec<String>) {
+        self.member_picker.open(path, members);
+    }
+
+    pub fn ask_for_project(&self) {
+        let dialog = rfd::FileDialog::new().add_filter("Bite project", &[project::EXTENSION]);
+        if let Some(path) = dialog.pick_file() {
+            self.ui_queue.push(crate::UIEvent::ProjectRequested(path));
+        }
+    }
+
+    /// Pick a drcov, lcov or address list file with the coverage of the loaded binary.
+    pub fn ask_for_coverage(&mut self) {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            self.load_coverage(path);
+        }
+    }
+
+    /// Color the instructions of the loaded binary that were executed according to the
+    /// coverage file at `path`, showing the percentage of each function that was.
+    pub fn load_coverage(&mut self, path: PathBuf) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let coverage = match processor.load_coverage(&path) {
+            Ok(coverage) => Arc::new(coverage),
+            Err(err) => {
+                crate::tprint!(self.terminal(), "Failed to load coverage: {err:?}");
+                return;
+            }
+        };
+
+        // Listings of libraries are left alone, as the addresses are those of the binary.
+        for kind in self.panes.mapping.values_mut() {
+            match kind {
+                PanelKind::Disassembly(listing) if Arc::ptr_eq(listing.processor(), &processor) => {
+                    listing.set_coverage(Arc::clone(&coverage));
+                }
+                PanelKind::FlowGraph(graph) => graph.set_coverage(Arc::clone(&coverage)),
+                PanelKind::Functions(functions) => functions.set_coverage(Arc::clone(&coverage)),
+                _ => {}
+            }
+        }
+
+        crate::tprint!(
+            self.terminal(),
+            "Loaded {} covered ranges from {}.",
+            coverage.len(),
+            path.display()
+        );
+    }
+
+    /// Save the analysis and annotations of the loaded binary to a project file.
+    pub fn save_project(&mut self) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        let boundaries = match self.panes.mapping.get(DISASSEMBLY) {
+            Some(PanelKind::Disassembly(listing)) => listing.boundaries(),
+            _ => Vec::new(),
+        };
+
+        let name = processor.path.file_name().unwrap_or_default().to_string_lossy();
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Bite project", &[project::EXTENSION])
+            .set_file_name(format!("{name}.{}", project::EXTENSION));
+
+        let path = match dialog.save_file() {
+            Some(path) => path,
+            None => return,
+        };
+
+        match processor.project(boundaries).save(&path) {
+            Ok(()) => crate::tprint!(self.terminal(), "Saved project to {}.", path.display()),
+            Err(err) => crate::tprint!(self.terminal(), "Failed to save project: {err:?}"),
         }
     }
 
-    pub fn listing(&mut self) -> Option<&mut listing::Listing> {
-        self.panes.mapping.get_mut(DISASSEMBLY).and_then(|kind| match kind {
-            PanelKind::Disassembly(listing) => Some(listing),
-            _ => None,
-        })
-    }
+    /// Write the listing of the loaded binary to a text, HTML or JSON file.
+    pub fn export_listing(&mut self) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
 
-    #[inline]
-    pub fn processor(&mut self) -> Option<&Arc<Processor>> {
-        self.panes.processor.as_ref()
-    }
+        let boundaries = match self.panes.mapping.get(DISASSEMBLY) {
+            Some(PanelKind::Disassembly(listing)) => listing.boundaries(),
+            _ => Vec::new(),
+        };
 
-    #[inline]
-    pub fn terminal(&mut self) -> &mut Terminal {
-        &mut self.panes.terminal
-    }
+        let name = processor.path.file_name().unwrap_or_default().to_string_lossy();
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .add_filter("HTML", &["html", "htm"])
+            .add_filter("JSON", &["json"])
+            .set_file_name(format!("{name}.txt"));
 
-    pub fn is_loading(&self) -> bool {
-        self.loading
-    }
+        let path = match dialog.save_file() {
+            Some(path) => path,
+            None => return,
+        };
 
-    pub fn start_loading(&mut self) {
-        // create new donut to restart internal timer
-        self.panes.donut = Donut::new(false);
-        self.loading = true;
-    }
+        let format = path
+            .extension()
+            .and_then(|ext| ExportFormat::parse(&ext.to_string_lossy()))
+            .unwrap_or_default();
 
-    pub fn stop_loading(&mut self) {
-        self.loading = false;
+        crate::tprint!(self.terminal(), "Exporting listing to {}.", path.display());
+
+        // Listings can be hundreds of megabytes, so they're written in the background.
+        std::thread::spawn(move || {
+            let export = || -> std::io::Result<()> {
+                let mut file = std::io::BufWriter::new(std::fs::File::create(&path)?);
+                processor.export(&boundaries, format, &mut file)
+            };
+
+            match export() {
+                Ok(()) => log::trace!("Exported listing to {}.", path.display()),
+                Err(err) => log::warning!("Failed to export listing: {err}."),
+            }
+        });
     }
 
-    /// Jump to both the source and the assembly.
-    pub fn load_src(&mut self, addr: usize) {
-        let file_attr = match self.processor().and_then(|proc| proc.index.get_file_by_addr(addr)) {
-            Some(file_attr) => file_attr,
-            None => return,
+    /// Write the loaded binary with it's patches applied to a new file.
+    pub fn save_patched_binary(&mut self) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
         };
 
-        if let Ok(src) = std::fs::read_to_string(&file_attr.path) {
-            let src = source_code::Source::new(&src, file_attr);
-            self.panes.mapping.insert(SOURCE, PanelKind::Source(src));
+        if processor.patch_count() == 0 {
+            crate::tprint!(self.terminal(), "No bytes have been patched.");
+            return;
         }
-    }
 
-    pub fn load_binary(&mut self, processor: Processor) {
-        let processor = Arc::new(processor);
-
-        self.panes.mapping.insert(
-            DISASSEMBLY,
-            PanelKind::Disassembly(listing::Listing::new(
-                processor.clone(),
-                self.ui_queue.clone(),
-            )),
-        );
+        let name = processor.path.file_name().unwrap_or_default().to_string_lossy();
+        let dialog = rfd::FileDialog::new().set_file_name(format!("{name}.patched"));
 
-        self.panes.mapping.insert(
-            FUNCTIONS,
-            PanelKind::Functions(functions::Functions::new(
-                processor.clone(),
-                self.ui_queue.clone(),
-            )),
-        );
+        let path = match dialog.save_file() {
+            Some(path) => path,
+            None => return,
+        };
 
-        self.panes.processor = Some(processor);
+        std::thread::spawn(move || match processor.save_patched(&path) {
+            Ok(()) => log::trace!("Saved patched binary to {}.", path.display()),
+            Err(err) => log::warning!("Failed to save patched binary: {err}."),
+        });
     }
 
-    pub fn ask_for_binary(&self) {
-        if let Some(path) = rfd::FileDialog::new().pick_file() {
-            self.ui_queue.push(crate::UIEvent::BinaryRequested(path));
+    /// Write the loaded binary as it was analyzed, i.e. with it's compression or packing removed,
+    /// to a new file.
+    pub fn save_unpacked_binary(&mut self) {
+        let processor = match self.panes.processor {
+            Some(ref processor) => Arc::clone(processor),
+            None => {
+                crate::tprint!(self.terminal(), "No targets loaded.");
+                return;
+            }
+        };
+
+        if processor.unpacking().is_empty() {
+            crate::tprint!(self.terminal(), "Nothing was unpacked.");
+            return;
         }
+
+        let name = processor.path.file_name().unwrap_or_default().to_string_lossy();
+        let dialog = rfd::FileDialog::new().set_file_name(format!("{name}.unpacked"));
+
+        let path = match dialog.save_file() {
+            Some(path) => path,
+            None => return,
+        };
+
+        std::thread::spawn(move || match processor.save_unpacked(&path) {
+            Ok(()) => log::trace!("Saved unpacked binary to {}.", path.display()),
+            Err(err) => log::warning!("Failed to save unpacked binary: {err}."),
+        });
     }
 
     pub fn handle_events(&mut self, events: &mut Vec<egui::Event>) {
+        let keymap = Keymap::new(&SETTINGS.read().unwrap());
+        for action in keymap.translate(events, self.text_input_focused) {
+            self.perform(action);
+        }
+
+        // Text fields in the panes take the rest of the keyboard input whilst focused.
+        if self.text_input_focused {
+            return;
+        }
+
         if let Some(listing) = self.listing() {
             listing.record_input(events);
         }
@@ -276,6 +2123,37 @@ impl Panels {
         }
     }
 
+    /// Buttons for switching between the binaries that are open, if there are several.
+    fn binary_switcher(&mut self, ui: &mut egui::Ui) {
+        if self.workspaces.len() < 2 {
+            return;
+        }
+
+        let mut switched = None;
+        let mut closed = None;
+        for (idx, processor) in self.binaries().enumerate() {
+            let name = processor.path.file_name().unwrap_or_default().to_string_lossy();
+            let response = ui.selectable_label(idx == self.active, name.to_string());
+            if response.on_hover_text(processor.path.display().to_string()).clicked() {
+                switched = Some(idx);
+            }
+
+            if ui.small_button(crate::icon!(CROSS)).on_hover_text("close").clicked() {
+                closed = Some(idx);
+            }
+
+            ui.separator();
+        }
+
+        if let Some(idx) = switched {
+            self.switch_binary(idx);
+        }
+
+        if let Some(idx) = closed {
+            self.close_binary(idx);
+        }
+    }
+
     /// Show some close/maximize/minimize buttons for the native window.
     #[cfg(any(target_family = "windows", target_os = "linux"))]
     fn top_bar_native(&mut self, ui: &mut egui::Ui) {
@@ -314,12 +2192,112 @@ impl Panels {
                     ui.close_menu();
                 }
 
+                ui.menu_button(crate::icon!(FOLDER_OPEN, " Open recent"), |ui| {
+                    let recent = SETTINGS.read().unwrap().recent_files.clone();
+                    if recent.is_empty() {
+                        ui.label(egui::RichText::new("nothing yet").color(theme().muted));
+                    }
+
+                    for path in recent {
+                        if ui.button(path.display().to_string()).clicked() {
+                            self.ui_queue.push(crate::UIEvent::BinaryRequested(path));
+                            ui.close_menu();
+                        }
+                    }
+                });
+
+                if ui.button(crate::icon!(FOLDER_OPEN, " Open raw binary")).clicked() {
+                    self.ask_for_raw_binary();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FOLDER_OPEN, " Open core dump")).clicked() {
+                    self.ask_for_core();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FOLDER_DOWNLOAD, " Open Project")).clicked() {
+                    self.ask_for_project();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FLOPPY_DISK, " Save Project")).clicked() {
+                    self.save_project();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FOLDER_DOWNLOAD, " Import coverage")).clicked() {
+                    self.ask_for_coverage();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(UPLOAD, " Export listing")).clicked() {
+                    self.export_listing();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FLOPPY_DISK, " Save patched binary")).clicked() {
+                    self.save_patched_binary();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(FLOPPY_DISK, " Save unpacked binary")).clicked() {
+                    self.save_unpacked_binary();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(TARGET, " Attach to process")).clicked() {
+                    self.process_picker.open();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(PLAY3, " Run configuration")).clicked() {
+                    self.run_config.open();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(CROSS, " Close binary")).clicked() {
+                    self.close_shown_binary();
+                    ui.close_menu();
+                }
+
                 if ui.button(crate::icon!(CROSS, " Exit")).clicked() {
                     self.winit_queue.push(crate::WinitEvent::CloseRequest);
                     ui.close_menu();
                 }
             });
 
+            ui.menu_button("Edit", |ui| {
+                let (undo, redo) = match self.panes.processor {
+                    Some(ref processor) => (processor.can_undo(), processor.can_redo()),
+                    None => (false, false),
+                };
+
+                let settings = SETTINGS.read().unwrap();
+                let undo_keys = settings.keys("undo").to_string();
+                let redo_keys = settings.keys("redo").to_string();
+                drop(settings);
+
+                let button =
+                    egui::Button::new(crate::icon!(UNDO2, " Undo")).shortcut_text(undo_keys);
+                if ui.add_enabled(undo, button).clicked() {
+                    self.undo();
+                    ui.close_menu();
+                }
+
+                let button =
+                    egui::Button::new(crate::icon!(REDO2, " Redo")).shortcut_text(redo_keys);
+                if ui.add_enabled(redo, button).clicked() {
+                    self.redo();
+                    ui.close_menu();
+                }
+
+                if ui.button(crate::icon!(COG, " Preferences")).clicked() {
+                    self.preferences.open();
+                    ui.close_menu();
+                }
+            });
+
             ui.menu_button("Windows", |ui| {
                 if ui.button(DISASSEMBLY).clicked() {
                     self.goto_window(DISASSEMBLY);
@@ -331,6 +2309,96 @@ impl Panels {
                     ui.close_menu();
                 }
 
+                if ui.button(STRINGS).clicked() {
+                    self.goto_window(STRINGS);
+                    ui.close_menu();
+                }
+
+                if ui.button(CALL_GRAPH).clicked() {
+                    self.goto_window(CALL_GRAPH);
+                    ui.close_menu();
+                }
+
+                if ui.button(REGISTERS).clicked() {
+                    self.goto_window(REGISTERS);
+                    ui.close_menu();
+                }
+
+                if ui.button(MEMORY_MAPS).clicked() {
+                    self.goto_window(MEMORY_MAPS);
+                    ui.close_menu();
+                }
+
+                if ui.button(SYSCALLS).clicked() {
+                    self.goto_window(SYSCALLS);
+                    ui.close_menu();
+                }
+
+                if ui.button(CONSOLE).clicked() {
+                    self.goto_window(CONSOLE);
+                    ui.close_menu();
+                }
+
+                if ui.button(TRACE).clicked() {
+                    self.goto_window(TRACE);
+                    ui.close_menu();
+                }
+
+                if ui.button(CALL_STACK).clicked() {
+                    self.goto_window(CALL_STACK);
+                    ui.close_menu();
+                }
+
+                if ui.button(VARIABLES).clicked() {
+                    self.goto_window(VARIABLES);
+                    ui.close_menu();
+                }
+
+                if ui.button(THREADS).clicked() {
+                    self.goto_window(THREADS);
+                    ui.close_menu();
+                }
+
+                if ui.button(FLOW_GRAPH).clicked() {
+                    self.goto_window(FLOW_GRAPH);
+                    ui.close_menu();
+                }
+
+                if ui.button(PSEUDOCODE).clicked() {
+                    self.goto_window(PSEUDOCODE);
+                    ui.close_menu();
+                }
+
+                if ui.button(EMULATION).clicked() {
+                    self.goto_window(EMULATION);
+                    ui.close_menu();
+                }
+
+                if ui.button(HEX).clicked() {
+                    self.goto_window(HEX);
+                    ui.close_menu();
+                }
+
+                if ui.button(OVERVIEW).clicked() {
+                    self.goto_window(OVERVIEW);
+                    ui.close_menu();
+                }
+
+                if ui.button(INFO).clicked() {
+                    self.goto_window(INFO);
+                    ui.close_menu();
+                }
+
+                if ui.button(BOOKMARKS).clicked() {
+                    self.goto_window(BOOKMARKS);
+                    ui.close_menu();
+                }
+
+                if ui.button(SEGMENTS).clicked() {
+                    self.goto_window(SEGMENTS);
+                    ui.close_menu();
+                }
+
                 if ui.button(SOURCE).clicked() {
                     self.goto_window(SOURCE);
                     ui.close_menu();
@@ -342,6 +2410,26 @@ impl Panels {
                 }
             });
 
+            ui.menu_button("Theme", |ui| {
+                for builtin in Theme::BUILTIN {
+                    let selected = self.styled.0.name == builtin.name;
+                    if ui.selectable_label(selected, builtin.name).clicked() {
+                        crate::style::change_settings(|settings| {
+                            settings.theme = builtin.name.to_string()
+                        });
+                        ui.close_menu();
+                    }
+                }
+            });
+
+            ui.separator();
+            self.binary_switcher(ui);
+
+            if self.session.is_some() {
+                ui.separator();
+                self.run_controls(ui);
+            }
+
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Max), |ui| {
                 ui.spacing_mut().item_spacing.x = 5.0;
                 self.top_bar_native(ui);
@@ -357,55 +2445,162 @@ impl Panels {
         }
     }
 
-    fn input(&mut self, ctx: &mut egui::Context) {
-        let modifier = if cfg!(target_os = "macos") {
-            egui::Modifiers::MAC_CMD
-        } else {
-            egui::Modifiers::CTRL
+    /// Buttons controlling the debugged process, along with the shortcuts doing the same.
+    fn run_controls(&mut self, ui: &mut egui::Ui) {
+        let (running, post_mortem, started, recording) = match self.session {
+            Some(ref session) => (
+                session.is_running(),
+                session.is_post_mortem(),
+                !session.is_attached() && !session.is_post_mortem(),
+                session.is_recording(),
+            ),
+            None => return,
+        };
+
+        let stopped = !running && !post_mortem;
+        let settings = SETTINGS.read().unwrap();
+        let mut control = |icon: &str, name: &str, action: Option<Action>, enabled: bool| {
+            let hover = match action.map(|action| settings.keys(action.name())) {
+                Some(keys) if !keys.is_empty() => format!("{name} ({keys})"),
+                _ => name.to_string(),
+            };
+
+            ui.add_enabled(enabled, egui::Button::new(icon)).on_hover_text(hover).clicked()
         };
 
-        if ctx.input_mut(|i| i.consume_key(modifier, egui::Key::O)) {
-            self.ask_for_binary();
+        let resume = control(crate::icon!(PLAY3), "Continue", Some(Action::Continue), stopped);
+        let pause = control(crate::icon!(PAUSE2), "Pause", None, running);
+        let step = control(crate::icon!(ARROW_DOWN), "Step into", None, stopped);
+        let next = control(crate::icon!(ARROW_RIGHT), "Step over", Some(Action::Next), stopped);
+        let step_out = control(crate::icon!(ARROW_UP), "Step out", Some(Action::StepOut), stopped);
+        let back = Some(Action::StepBack);
+        let step_back = control(crate::icon!(ARROW_LEFT), "Step back", back, stopped && recording);
+        let restart = control(crate::icon!(LOOP2), "Restart", None, started);
+        let kill = control(crate::icon!(STOP2), "Kill", None, !post_mortem);
+        drop(settings);
+
+        if resume {
+            self.resume(None);
+        } else if pause {
+            self.pause();
+        } else if step {
+            self.resume(Some(Step::Line));
+        } else if next {
+            self.resume(Some(Step::Over));
+        } else if step_out {
+            self.resume(Some(Step::Out));
+        } else if step_back {
+            self.resume(Some(Step::Back));
+        } else if restart {
+            self.restart_session();
+        } else if kill && self.kill_session() {
+            crate::tprint!(self.terminal(), "Killed process.");
         }
+    }
 
-        // alt-tab'ing between tabs
-        if ctx.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, egui::Key::Tab)) {
-            for id in self.tree.active_tiles() {
-                if let Some(parent_id) = self.tree.tiles.parent_of(id) {
-                    let parent = self.tree.tiles.get_mut(parent_id).unwrap();
-                    if let Tile::Container(Container::Tabs(tabs)) = parent {
-                        if tabs.children.len() < 2 {
-                            continue;
-                        }
-                        let active_tab = match tabs.active {
-                            Some(active) => active,
-                            None => continue,
-                        };
-
-                        let mut active_idx = 0;
-                        for (idx, child) in tabs.children.iter().enumerate() {
-                            if *child == active_tab {
-                                active_idx = idx;
-                            }
-                        }
+    /// Do what a shortcut is bound to.
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::Open => self.ask_for_binary(),
+            Action::Save => self.save_project(),
+            Action::Undo => self.undo(),
+            Action::Redo => self.redo(),
+            Action::Goto if self.panes.processor.is_some() => self.goto_palette.open(),
+            Action::Goto => {}
+            Action::Search => self.terminal().set_line("search "),
+            Action::Continue => self.resume(None),
+            Action::Step => self.resume(Some(Step::Instruction)),
+            Action::Next => self.resume(Some(Step::Over)),
+            Action::StepOut => self.resume(Some(Step::Out)),
+            Action::StepBack => self.resume(Some(Step::Back)),
+            Action::NextTab => self.next_tab(),
+            Action::Preferences => self.open_preferences(),
+            _ => {
+                let listing = match self.listing() {
+                    Some(listing) => listing,
+                    None => return,
+                };
+
+                match action {
+                    Action::NextResult => listing.step_search_results(false),
+                    Action::PreviousResult => listing.step_search_results(true),
+                    Action::Back => listing.go_back(),
+                    Action::Forward => listing.go_forward(),
+                    Action::Rename => listing.edit_selected(listing::EditKind::Rename),
+                    Action::Comment => listing.edit_selected(listing::EditKind::Comment),
+                    Action::Bookmark => listing.edit_selected(listing::EditKind::Bookmark),
+                    Action::ToggleBreakpoint => listing.toggle_breakpoint(),
+                    _ => unreachable!(),
+                }
+            }
+        }
+    }
 
-                        let next_idx = (active_idx + 1) % tabs.children.len();
-                        let next_id = tabs.children[next_idx];
-                        tabs.set_active(next_id);
-                        break;
+    /// Switch to the next tab of the first tabs shown that have several.
+    fn next_tab(&mut self) {
+        for id in self.tree.active_tiles() {
+            if let Some(parent_id) = self.tree.tiles.parent_of(id) {
+                let parent = self.tree.tiles.get_mut(parent_id).unwrap();
+                if let Tile::Container(Container::Tabs(tabs)) = parent {
+                    if tabs.children.len() < 2 {
+                        continue;
+                    }
+                    let active_tab = match tabs.active {
+                        Some(active) => active,
+                        None => continue,
+                    };
+
+                    let mut active_idx = 0;
+                    for (idx, child) in tabs.children.iter().enumerate() {
+                        if *child == active_tab {
+                            active_idx = idx;
+                        }
                     }
+
+                    let next_idx = (active_idx + 1) % tabs.children.len();
+                    let next_id = tabs.children[next_idx];
+                    tabs.set_active(next_id);
+                    break;
                 }
             }
         }
     }
 
     pub fn draw(&mut self, ctx: &mut egui::Context) {
-        // generic keyboard inputs
-        self.input(ctx);
+        // the theme or font size may have been changed from the menu, preferences or terminal
+        let styled = (theme(), SETTINGS.read().unwrap().font_size);
+        if self.styled != styled {
+            self.styled = styled;
+            ctx.set_style(crate::style::egui(&styled.0));
+        }
+
+        // instructions are tokenized as they're decoded, so they're decoded again to be shown
+        // in another format
+        let format = tokenizing::format();
+        if self.formatted != format {
+            self.formatted = format;
+            self.annotations_changed();
+        }
 
         #[cfg(any(target_family = "windows", target_os = "linux"))]
         egui::TopBottomPanel::top("top bar").show(ctx, |ui| self.top_bar(ui));
 
+        // The native menu bar can't show which binary is shown.
+        #[cfg(target_os = "macos")]
+        if self.workspaces.len() > 1 {
+            egui::TopBottomPanel::top("binaries").show(ctx, |ui| {
+                ui.horizontal(|ui| self.binary_switcher(ui));
+            });
+        }
+
+        // Nor can it show the state of the debugged process.
+        #[cfg(target_os = "macos")]
+        if self.session.is_some() {
+            egui::TopBottomPanel::top("run controls").show(ctx, |ui| {
+                ui.horizontal(|ui| self.run_controls(ui));
+            });
+        }
+
         // terminal needs to be rendered last as it can take focus away from other panels
         let terminal = egui::TopBottomPanel::bottom("terminal")
             .min_height(80.0)
@@ -414,13 +2609,14 @@ impl Panels {
             .frame({
                 egui::Frame::default()
                     .inner_margin(egui::Margin::same(STYLE.separator_width * 2.0))
-                    .fill(colors::GRAY35)
+                    .fill(theme().border)
             });
 
-        let mut visuals = EGUI.visuals.clone();
+        let base = ctx.style().visuals.clone();
+        let mut visuals = base.clone();
 
         // set alternative background color
-        visuals.extreme_bg_color = CONFIG.colors.bg_secondary;
+        visuals.extreme_bg_color = theme().bg_secondary;
         // disable on-hover highlighting for terminal
         visuals.widgets.active.fg_stroke = egui::Stroke::NONE;
         visuals.widgets.hovered.fg_stroke = egui::Stroke::NONE;
@@ -428,25 +2624,54 @@ impl Panels {
         ctx.set_visuals(visuals);
 
         let request_focus = self.terminal().should_reset_cursor();
+        let indexing = self.panes.processor.as_ref().is_some_and(|proc| !proc.index.is_complete());
         let term_response = terminal.show(ctx, |ui| {
             let response = ui
                 .with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
-                    self.panes.terminal.show(ui)
+                    self.panes.terminal.show(ui, indexing)
                 });
 
             response.inner
         });
 
-        ctx.set_visuals(EGUI.visuals.clone());
+        ctx.set_visuals(base);
+
+        self.preferences.show(ctx);
+
+        if let Some(pid) = self.process_picker.show(ctx) {
+            self.request_attach(pid);
+        }
+
+        if self.run_config.show(ctx) {
+            self.run(Vec::new());
+        }
+
+        if let Some((path, options)) = self.raw_loader.show(ctx) {
+            self.ui_queue.push(crate::UIEvent::RawRequested(path, options));
+        }
+
+        if let Some((path, idx)) = self.member_picker.show(ctx) {
+            self.ui_queue.push(crate::UIEvent::MemberRequested(path, idx));
+        }
+
+        if let Some(ref processor) = self.panes.processor {
+            if let Some(addr) = self.goto_palette.show(ctx, processor) {
+                self.ui_queue.push(crate::UIEvent::GotoAddr(addr));
+            }
+        }
 
         let frame = egui::Frame::default().inner_margin(egui::Margin::same(0.0));
         egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
-            if self.loading {
+            if let Some(ref mut loading) = self.loading {
                 ui.spacing_mut().item_spacing.y = 20.0;
                 ui.with_layout(
                     egui::Layout::top_down_justified(egui::Align::Center),
                     |ui| {
                         self.panes.donut.show(ui);
+                        ui.vertical_centered(|ui| {
+                            ui.spacing_mut().item_spacing.y = 4.0;
+                            loading.show(ui);
+                        });
                         log::PROGRESS.show(ui);
                     },
                 );
@@ -459,5 +2684,108 @@ impl Panels {
                 ui.ctx().memory_mut(|m| m.request_focus(term_response.inner.id));
             }
         });
+
+        let terminal_id = term_response.inner.id;
+        self.text_input_focused = ctx.memory(|m| m.focused()).is_some_and(|id| id != terminal_id);
+    }
+}
+
+/// The binary's `.eh_frame`, used for unwinding the stack of the debugged process.
+/// Title of the listing showing a shared library.
+fn library_title(library: &Processor) -> String {
+    let name = match library.path.file_name() {
+        Some(name) => name.to_string_lossy(),
+        None => library.path.to_string_lossy(),
+    };
+
+    format!("{DISASSEMBLY} ({name})")
+}
+
+/// Load the library at `path` unless it was `loaded` before, showing it at the address `target`
+/// finds in it.
+fn load_library(
+    ui_queue: &crate::UiQueue,
+    loaded: &[Arc<Processor>],
+    path: PathBuf,
+    target: impl FnOnce(&Processor) -> Option<usize>,
+) {
+    let path = path.canonicalize().unwrap_or(path);
+    let library = match loaded.iter().find(|library| library.path == path) {
+        Some(library) => Arc::clone(library),
+        None => match Processor::parse(&path) {
+            Ok(library) => {
+                library.index.complete();
+                Arc::new(library)
+            }
+            Err(err) => {
+                log::complex!(
+                    w "[panels::load_library] ",
+                    w "Failed to load ",
+                    g path.display().to_string(),
+                    w format!(": {err:?}."),
+                );
+                return;
+            }
+        },
+    };
+
+    match target(&library) {
+        Some(addr) => {
+            let boundaries = library.compute_block_boundaries();
+            ui_queue.push(crate::UIEvent::LibraryLoaded(library, boundaries, addr));
+        }
+        None => log::complex!(
+            w "[panels::load_library] ",
+            g path.display().to_string(),
+            w " doesn't have the code that was looked for.",
+        ),
+    }
+}
+
+/// File backing where `addr` of `binary` is mapped in the process, together with the offset
+/// into it, unless it's the binary itself.
+fn mapped_file(
+    binary: &Processor,
+    addr: usize,
+    regions: &[MemoryRegion],
+) -> Option<(PathBuf, usize)> {
+    let path = binary.path.canonicalize().ok()?;
+    let of_binary =
+        |region: &MemoryRegion| region.path.as_deref().map(Path::new) == Some(path.as_path());
+
+    // Stops are reported by the binary's addresses, so they're moved by where it was loaded.
+    let bias = regions.iter().filter(|region| of_binary(region)).find_map(|region| {
+        let start = region.offset as usize;
+        binary.sections().find_map(|section| {
+            let offset = binary.file_offset(section.start)?;
+            (start..start + region.len()).contains(&offset).then(|| {
+                region.start.wrapping_add(offset - start).wrapping_sub(section.start)
+            })
+        })
+    })?;
+
+    let runtime = addr.wrapping_add(bias);
+    let region = regions.iter().find(|region| (region.start..region.end).contains(&runtime))?;
+    let file = PathBuf::from(region.path.as_ref()?);
+    if of_binary(region) || !file.is_file() {
+        return None;
+    }
+
+    Some((file, runtime - region.start + region.offset as usize))
+}
+
+fn debug_info(processor: &Arc<Processor>) -> DebugInfo {
+    let cfi = match processor.sections().find(|section| section.name == ".eh_frame") {
+        Some(section) => CallFrameInfo::new(section.bytes().to_vec(), section.start),
+        None => CallFrameInfo::default(),
+    };
+
+    let rows = processor.index.file_attrs.iter();
+    let rows = rows.map(|row| (row.addr, Arc::clone(&row.item.path), row.item.line));
+
+    DebugInfo {
+        cfi,
+        lines: LineTable::new(rows.collect()),
+        processor: Some(Arc::clone(processor)),
     }
 }
@@ -0,0 +1,173 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use config::CONFIG;
+use processor::{ByteClass, Chunk, Processor};
+use std::sync::{Arc, OnceLock};
+use tokenizing::theme;
+
+/// Chunks shown per row.
+const COLUMNS: usize = 64;
+
+/// Height of a row of chunks.
+const ROW_HEIGHT: f32 = 8.0;
+
+fn class_color(class: ByteClass) -> egui::Color32 {
+    match class {
+        ByteClass::Zeroes => theme().border,
+        ByteClass::Text => CONFIG.colors.role_color("string"),
+        ByteClass::Random => egui::Color32::from_rgb(0xe0, 0x40, 0x40),
+        ByteClass::Binary => egui::Color32::from_rgb(0x40, 0x90, 0xe0),
+    }
+}
+
+fn class_name(class: ByteClass) -> &'static str {
+    match class {
+        ByteClass::Zeroes => "zeroes",
+        ByteClass::Text => "text",
+        ByteClass::Random => "compressed/encrypted",
+        ByteClass::Binary => "binary",
+    }
+}
+
+/// Color of a chunk, brighter the higher it's entropy.
+fn chunk_color(chunk: &Chunk) -> egui::Color32 {
+    let color = class_color(chunk.class);
+    let t = 0.3 + 0.7 * (chunk.entropy / 8.0);
+    let bg = theme().bg_primary;
+    let lerp = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * t) as u8;
+    let (r, g, b) = (lerp(bg.r(), color.r()), lerp(bg.g(), color.g()), lerp(bg.b(), color.b()));
+    egui::Color32::from_rgb(r, g, b)
+}
+
+pub struct Overview {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Measured in the background.
+    chunks: Arc<OnceLock<Vec<Chunk>>>,
+    /// File offsets where sections start, together with their names.
+    sections: Vec<(usize, String)>,
+}
+
+impl Overview {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let chunks = Arc::new(OnceLock::new());
+
+        {
+            let processor = Arc::clone(&processor);
+            let chunks = Arc::clone(&chunks);
+            std::thread::spawn(move || {
+                let _ = chunks.set(processor.overview());
+            });
+        }
+
+        let mut sections: Vec<(usize, String)> = processor
+            .sections()
+            .filter_map(|section| {
+                let offset = processor.file_offset(section.start)?;
+                Some((offset, section.name.clone()))
+            })
+            .collect();
+        sections.sort_unstable_by_key(|(offset, _)| *offset);
+
+        Self {
+            processor,
+            ui_queue,
+            chunks,
+            sections,
+        }
+    }
+
+    /// Name of the section containing `offset`.
+    fn section_name(&self, offset: usize) -> Option<&str> {
+        let idx = self.sections.partition_point(|(start, _)| *start <= offset).checked_sub(1)?;
+        Some(&self.sections[idx].1)
+    }
+}
+
+impl Display for Overview {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let chunks = match self.chunks.get() {
+            Some(chunks) => chunks,
+            None => {
+                ui.label(egui::RichText::new("measuring…").font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            for class in [ByteClass::Zeroes, ByteClass::Text, ByteClass::Binary, ByteClass::Random]
+            {
+                let text = egui::RichText::new(format!("■ {}", class_name(class)));
+                ui.label(text.font(font()).color(class_color(class)));
+            }
+            let text = "brighter means higher entropy, white lines mark sections";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+        });
+
+        let rows = chunks.len().div_ceil(COLUMNS);
+        let mut clicked = None;
+
+        let area = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        area.show(ui, |ui| {
+            let size = egui::vec2(ui.available_width(), rows as f32 * ROW_HEIGHT);
+            let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+            let origin = response.rect.min;
+            let width = size.x / COLUMNS as f32;
+
+            let cell = |idx: usize| {
+                let x = (idx % COLUMNS) as f32 * width;
+                let y = (idx / COLUMNS) as f32 * ROW_HEIGHT;
+                egui::Rect::from_min_size(origin + egui::vec2(x, y), egui::vec2(width, ROW_HEIGHT))
+            };
+
+            for (idx, chunk) in chunks.iter().enumerate() {
+                painter.rect_filled(cell(idx), 0.0, chunk_color(chunk));
+            }
+
+            let stroke = egui::Stroke::new(1.0, theme().plain);
+            for (offset, _) in &self.sections {
+                let idx = chunks.partition_point(|chunk| chunk.offset + chunk.len <= *offset);
+                if idx < chunks.len() {
+                    let rect = cell(idx);
+                    painter.line_segment([rect.left_top(), rect.left_bottom()], stroke);
+                }
+            }
+
+            let hovered = response.hover_pos().and_then(|pos| {
+                let pos = pos - origin;
+                let column = ((pos.x / width) as usize).min(COLUMNS - 1);
+                let idx = (pos.y / ROW_HEIGHT) as usize * COLUMNS + column;
+                chunks.get(idx)
+            });
+
+            if let Some(chunk) = hovered {
+                let text = format!(
+                    "{:#x}..{:#x} in {}\n{} with {:.2} bits of entropy per byte",
+                    chunk.offset,
+                    chunk.offset + chunk.len,
+                    self.section_name(chunk.offset).unwrap_or("no section"),
+                    class_name(chunk.class),
+                    chunk.entropy,
+                );
+
+                if response.on_hover_text(text).clicked() {
+                    clicked = Some(chunk.offset);
+                }
+            }
+        });
+
+        if let Some(offset) = clicked {
+            match self.processor.addr_by_offset(offset) {
+                Some(addr) => {
+                    self.ui_queue.push(UIEvent::GotoHex(addr));
+                    self.ui_queue.push(UIEvent::GotoAddr(addr));
+                }
+                None => log::complex!(
+                    w "[overview] offset ",
+                    g format!("{offset:#x}"),
+                    w " isn't part of a loaded section.",
+                ),
+            }
+        }
+    }
+}
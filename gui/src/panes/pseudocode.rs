@@ -0,0 +1,103 @@
+use crate::common::*;
+use crate::style::STYLE;
+use crate::{UiQueue, UIEvent};
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::theme;
+
+/// Experimental pseudo-C of the function containing the instruction selected in the listing.
+pub struct Pseudocode {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    code: Option<processor::Pseudocode>,
+    /// Instruction selected in the listing, whose lines are highlighted.
+    selected: Option<usize>,
+    /// Whether the selected lines still have to be scrolled to.
+    scroll_to_selected: bool,
+}
+
+impl Pseudocode {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            code: None,
+            selected: None,
+            scroll_to_selected: false,
+        }
+    }
+
+    /// Show the function containing `addr` with the lines lifted from it highlighted, returns
+    /// whether there's such a function with any instructions.
+    pub fn select(&mut self, addr: usize) -> bool {
+        let func = match self.processor.index.get_sym_containing(addr) {
+            Some((func, _)) => func,
+            None => return false,
+        };
+
+        if self.code.as_ref().map(|code| code.func) != Some(func) {
+            match self.processor.pseudocode(func) {
+                Some(code) => self.code = Some(code),
+                None => return false,
+            }
+        }
+
+        self.selected = Some(addr);
+        self.scroll_to_selected = true;
+        true
+    }
+}
+
+impl Display for Pseudocode {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let code = match self.code {
+            Some(ref mut code) => code,
+            None => {
+                let text = "No function shown, select an instruction in the listing.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            let text = "experimental, double click a line to go to it's instruction";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            if ui.button("refresh").clicked() {
+                if let Some(refreshed) = self.processor.pseudocode(code.func) {
+                    *code = refreshed;
+                }
+            }
+        });
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            let mut scrolled = false;
+            for line in &code.lines {
+                let color = if line.text.ends_with(':') {
+                    theme().label
+                } else if line.text.trim_start().starts_with("asm(") {
+                    theme().muted
+                } else {
+                    theme().plain
+                };
+
+                let text = egui::RichText::new(&line.text).font(font()).color(color);
+                let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+
+                if self.selected == Some(line.addr) {
+                    ui.painter().rect_filled(response.rect, 0.0, STYLE.selection_color);
+                    if self.scroll_to_selected && !scrolled {
+                        response.scroll_to_me(Some(egui::Align::Center));
+                        scrolled = true;
+                    }
+                }
+
+                if response.double_clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(line.addr));
+                }
+            }
+        });
+
+        self.scroll_to_selected = false;
+    }
+}
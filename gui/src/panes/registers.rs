@@ -0,0 +1,239 @@
+use crate::common::*;
+use crate::session::Edit;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugger::Registers;
+use std::sync::Arc;
+use tokenizing::theme;
+
+/// Register whose value is being changed.
+struct Editing {
+    name: &'static str,
+    /// Value at the last stop.
+    value: u64,
+    text: String,
+    /// Value that's written once the user confirmed it.
+    confirming: Option<u64>,
+}
+
+pub struct RegistersView {
+    ui_queue: Arc<UiQueue>,
+    /// Registers at the last stop.
+    current: Option<Registers>,
+    /// Registers at the stop before, used for highlighting what changed.
+    previous: Option<Registers>,
+    editing: Option<Editing>,
+    /// Changes to the stopped process that can be undone.
+    undoable: usize,
+}
+
+impl RegistersView {
+    pub fn new(ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            ui_queue,
+            current: None,
+            previous: None,
+            editing: None,
+            undoable: 0,
+        }
+    }
+
+    /// Show the registers of a new stop, [`None`] once the process is gone.
+    pub fn update(&mut self, registers: Option<Registers>) {
+        match registers {
+            Some(registers) => self.previous = self.current.replace(registers),
+            None => {
+                self.current = None;
+                self.previous = None;
+                self.editing = None;
+            }
+        }
+    }
+
+    /// Registers at the last stop, if a process is being debugged.
+    pub fn current(&self) -> Option<&Registers> {
+        self.current.as_ref()
+    }
+
+    pub fn set_undoable(&mut self, undoable: usize) {
+        self.undoable = undoable;
+    }
+
+    /// Draw the window for changing a register, if one is being changed.
+    fn show_editor(&mut self, ctx: &egui::Context) {
+        let mut edit = match self.editing.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+
+        let mut open = true;
+        let mut submitted = false;
+        let mut confirmed = false;
+        egui::Window::new(format!("Set {}", edit.name))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                match edit.confirming {
+                    Some(value) => {
+                        let text = format!(
+                            "Change {} from {:#x} to {value:#x}? This can be undone until the \
+                             process is resumed.",
+                            edit.name, edit.value
+                        );
+                        ui.label(egui::RichText::new(text).font(font()));
+                        ui.horizontal(|ui| {
+                            confirmed = ui.button("Set").clicked();
+                            if ui.button("Cancel").clicked() {
+                                edit.confirming = None;
+                            }
+                        });
+                    }
+                    None => {
+                        let input = egui::TextEdit::singleline(&mut edit.text)
+                            .font(font())
+                            .hint_text("value in hex with a 0x prefix, or in decimal")
+                            .desired_width(300.0);
+                        let response = ui.add(input);
+                        response.request_focus();
+
+                        submitted = ui.input(|input| input.key_pressed(egui::Key::Enter));
+                    }
+                }
+
+                if ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+                    open = false;
+                }
+            });
+
+        if confirmed {
+            let value = edit.confirming.unwrap_or(edit.value);
+            let edit = Edit::Register(edit.name.to_string(), value);
+            self.ui_queue.push(UIEvent::WriteProcess(edit));
+        } else if submitted {
+            match parse_value(&edit.text) {
+                Some(value) => edit.confirming = Some(value),
+                None => log::complex!(
+                    w "[registers::edit] ",
+                    r format!("'{}'", edit.text),
+                    w " isn't a number.",
+                ),
+            }
+
+            // Invalid values are left to be corrected.
+            self.editing = Some(edit);
+        } else if open {
+            self.editing = Some(edit);
+        }
+    }
+}
+
+/// Value written in hex with a `0x` prefix or in decimal, negative numbers being two's
+/// complement.
+fn parse_value(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok().or_else(|| text.parse::<i64>().ok().map(|value| value as u64)),
+    }
+}
+
+fn value_color(changed: bool) -> egui::Color32 {
+    if changed {
+        CONFIG.colors.role_color("changed")
+    } else {
+        theme().immediate
+    }
+}
+
+/// Draw a group of registers, returning the register whose value was double-clicked.
+fn show_group(
+    ui: &mut egui::Ui,
+    title: &str,
+    registers: &[(&'static str, u64)],
+    previous: Option<&Registers>,
+) -> Option<(&'static str, u64)> {
+    ui.label(egui::RichText::new(title).font(font()).color(theme().muted));
+
+    let mut clicked = None;
+    egui::Grid::new(title).num_columns(2).spacing([20.0, 0.0]).show(ui, |ui| {
+        for &(name, value) in registers {
+            let changed = previous.and_then(|prev| prev.get(name)).is_some_and(|v| v != value);
+
+            let label = egui::RichText::new(name).font(font()).color(theme().register);
+            let text = egui::RichText::new(format!("{value:#018x}"))
+                .font(font())
+                .color(value_color(changed));
+
+            ui.label(label);
+            let response = ui
+                .add(egui::Label::new(text).sense(egui::Sense::click()))
+                .on_hover_text("Double-click to change");
+
+            if response.double_clicked() {
+                clicked = Some((name, value));
+            }
+            ui.end_row();
+        }
+    });
+
+    clicked
+}
+
+impl Display for RegistersView {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let registers = match self.current {
+            Some(ref registers) => registers,
+            None => {
+                let text = "No process is being debugged, start one with `run`.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        let previous = self.previous.as_ref();
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        let mut clicked = None;
+        area.show(ui, |ui| {
+            if self.undoable > 0 && ui.button("Undo change").clicked() {
+                self.ui_queue.push(UIEvent::UndoWrite);
+            }
+
+            clicked = clicked.or(show_group(ui, "General purpose", &registers.general, previous));
+            ui.add_space(font().size);
+
+            ui.label(egui::RichText::new("Flags").font(font()).color(theme().muted));
+            ui.horizontal_wrapped(|ui| {
+                for &(name, set) in &registers.flags {
+                    let changed =
+                        previous.and_then(|prev| prev.flag(name)).is_some_and(|prev| prev != set);
+
+                    let color = if changed {
+                        value_color(true)
+                    } else if set {
+                        theme().plain
+                    } else {
+                        theme().muted
+                    };
+
+                    ui.label(egui::RichText::new(name).font(font()).color(color));
+                }
+            });
+            ui.add_space(font().size);
+
+            clicked = clicked.or(show_group(ui, "Segments", &registers.segments, previous));
+        });
+
+        if let Some((name, value)) = clicked {
+            self.editing = Some(Editing {
+                name,
+                value,
+                text: format!("{value:#x}"),
+                confirming: None,
+            });
+        }
+
+        self.show_editor(ui.ctx());
+    }
+}
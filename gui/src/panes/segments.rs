@@ -0,0 +1,152 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use processor::{Layout, LayoutSection, LayoutSegment, Permissions, Processor};
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+pub struct Segments {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    layout: Layout,
+}
+
+impl Segments {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let layout = processor.layout();
+
+        Self {
+            processor,
+            ui_queue,
+            layout,
+        }
+    }
+
+    /// Read the layout again after a section was toggled being decoded as code.
+    pub fn sections_changed(&mut self) {
+        self.layout = self.processor.layout();
+    }
+}
+
+fn tokenize_range(
+    start: usize,
+    end: usize,
+    perms: Option<Permissions>,
+    offset: Option<u64>,
+) -> Vec<Token> {
+    let perms = perms.map_or("---".to_string(), |perms| perms.to_string());
+    let offset = offset.map_or("-".to_string(), |offset| format!("{offset:#x}"));
+
+    vec![
+        Token::from_string(format!("{start:0>10X}"), Kind::Address),
+        Token::from_str("-", Kind::Plain),
+        Token::from_string(format!("{end:0>10X}"), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(perms, Kind::Component),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{offset:>10}"), Kind::Immediate),
+        Token::from_str(" | ", Kind::Plain),
+    ]
+}
+
+fn tokenize_segment(segment: &LayoutSegment) -> Vec<Token> {
+    let mut tokens = tokenize_range(segment.start, segment.end, segment.perms, segment.offset);
+    tokens.push(Token::from_string(segment.name.clone(), Kind::Plain));
+    tokens
+}
+
+fn tokenize_section(section: &LayoutSection) -> Vec<Token> {
+    let mut tokens = tokenize_range(section.start, section.end, section.perms, section.offset);
+    tokens.push(Token::from_string(format!("{:<20} ", section.name), Kind::Plain));
+    tokens.push(Token::from_string(section.kind.clone(), Kind::Comment));
+    tokens
+}
+
+/// Menu for showing `addr` in the listing or the hex view.
+fn goto_menu(ui: &mut egui::Ui, ui_queue: &UiQueue, addr: usize) {
+    if ui.button("Show in listing").clicked() {
+        ui_queue.push(UIEvent::GotoAddr(addr));
+        ui.close_menu();
+    }
+
+    if ui.button("Show in hex").clicked() {
+        ui_queue.push(UIEvent::GotoHex(addr));
+        ui.close_menu();
+    }
+}
+
+/// Show `section`, returning whether it's now decoded as code if the user toggled it.
+fn show_section(ui: &mut egui::Ui, ui_queue: &UiQueue, section: &LayoutSection) -> Option<bool> {
+    let mut toggled = None;
+
+    ui.horizontal(|ui| {
+        let mut code = section.code;
+        let checkbox = ui.add_enabled(section.decodable, egui::Checkbox::without_text(&mut code));
+        if checkbox.on_hover_text("decode as code").changed() {
+            toggled = Some(code);
+        }
+
+        let response = ui.link(tokens_to_layoutjob(tokenize_section(section)));
+        if response.clicked() {
+            ui_queue.push(UIEvent::GotoAddr(section.start));
+        }
+
+        response.context_menu(|ui| goto_menu(ui, ui_queue, section.start));
+    });
+
+    toggled
+}
+
+impl Display for Segments {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.layout.segments.is_empty() && self.layout.sections.is_empty() {
+            let text = "The binary doesn't have any segments or sections.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let hint = "click to go to a section, right click to show it in the hex view";
+        ui.label(egui::RichText::new(hint).font(font()).color(theme().muted));
+
+        let mut toggled = None;
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            for (idx, segment) in self.layout.segments.iter().enumerate() {
+                let header = tokens_to_layoutjob(tokenize_segment(segment));
+                let response = egui::CollapsingHeader::new(header)
+                    .id_source(idx)
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for section in &segment.sections {
+                            if let Some(code) = show_section(ui, &self.ui_queue, section) {
+                                toggled = Some((section.start, code));
+                            }
+                        }
+                    });
+
+                response.header_response.context_menu(|ui| {
+                    goto_menu(ui, &self.ui_queue, segment.start);
+                });
+            }
+
+            if !self.layout.sections.is_empty() {
+                let header = egui::RichText::new("Outside of segments")
+                    .font(font())
+                    .color(theme().muted);
+
+                egui::CollapsingHeader::new(header).id_source("outside").show(ui, |ui| {
+                    for section in &self.layout.sections {
+                        if let Some(code) = show_section(ui, &self.ui_queue, section) {
+                            toggled = Some((section.start, code));
+                        }
+                    }
+                });
+            }
+        });
+
+        if let Some((start, code)) = toggled {
+            if self.processor.set_code_section(start, code).is_some() {
+                self.ui_queue.push(UIEvent::SectionsChanged);
+            }
+        }
+    }
+}
@@ -9,16 +9,24 @@ use tree_sitter::{Language, QueryError};
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
 
 use crate::common::*;
+use crate::{UIEvent, UiQueue};
 use config::CONFIG;
-use debugvault::FileAttr;
-use tokenizing::colors;
+use debugger::BreakpointSet;
+use debugvault::{FileAttr, Index};
+use tokenizing::{theme, Kind, Theme};
 
 pub struct Source {
     src: String,
     lines: Vec<Line>,
+    /// Lowest address of each line, None for lines without code.
+    addrs: Vec<Option<usize>>,
+    /// Breakpoints toggled by clicking the line numbers.
+    breakpoints: Arc<BreakpointSet>,
+    ui_queue: Arc<UiQueue>,
     max_number_width: usize,
     scroll: Option<usize>,
-    cache: (Range<usize>, Arc<Galley>),
+    /// Rows last laid out and the theme and font they were drawn with.
+    cache: (Range<usize>, Theme, egui::FontId, Arc<Galley>),
 }
 
 struct Line {
@@ -65,8 +73,8 @@ fn compute_sections<P: AsRef<Path>>(path: P, src: &str) -> Vec<HighlightedSectio
                 if let Some(style) = styles.last() {
                     sections.push(HighlightedSection {
                         range: start..end,
-                        fg_color: CONFIG.colors.get_by_style(style),
-                        bg_color: Color32::TRANSPARENT,
+                        kind: config::style_kind(style),
+                        highlighted: false,
                     });
                 }
             }
@@ -91,8 +99,8 @@ fn compute_sections<P: AsRef<Path>>(path: P, src: &str) -> Vec<HighlightedSectio
             // this is a non-highlighted section
             sections.push(HighlightedSection {
                 range: last_end..section.range.start,
-                fg_color: CONFIG.colors.get_by_style("none"),
-                bg_color: Color32::TRANSPARENT,
+                kind: Kind::Plain,
+                highlighted: false,
             });
         }
         last_end = section_end;
@@ -102,8 +110,8 @@ fn compute_sections<P: AsRef<Path>>(path: P, src: &str) -> Vec<HighlightedSectio
     if last_end < src.len() {
         sections.push(HighlightedSection {
             range: last_end..src.len(),
-            fg_color: Color32::WHITE,
-            bg_color: Color32::TRANSPARENT,
+            kind: Kind::Plain,
+            highlighted: false,
         });
     }
 
@@ -134,8 +142,14 @@ fn find_matching_sections(
 }
 
 impl Source {
-    pub fn new(src: &str, file_attr: &FileAttr) -> Self {
-        let max_width = (src.lines().count().ilog10() + 1) as usize;
+    pub fn new(
+        src: &str,
+        file_attr: &FileAttr,
+        index: &Index,
+        breakpoints: Arc<BreakpointSet>,
+        ui_queue: Arc<UiQueue>,
+    ) -> Self {
+        let max_width = (src.lines().count().max(1).ilog10() + 1) as usize;
         let mut lines = Vec::new();
         let sections = compute_sections(&file_attr.path, &src);
 
@@ -144,14 +158,14 @@ impl Source {
             let line_nr = idx + 1;
             let line_len = line.len();
             let mut line = Line {
-                number: format!("{line_nr:max_width$}\n"),
+                number: format!("  {line_nr:max_width$}\n"),
                 sections: find_matching_sections(line, offset, &sections),
             };
 
             if line_nr == file_attr.line {
                 for section in line.sections.iter_mut() {
-                    section.bg_color = CONFIG.colors.src.highlight.linear_multiply(0.5);
-                    section.fg_color = Color32::WHITE;
+                    section.kind = Kind::Plain;
+                    section.highlighted = true;
                 }
             }
 
@@ -159,8 +173,18 @@ impl Source {
             offset += line_len + 1;
         }
 
+        let mut addrs = vec![None; lines.len()];
+        for row in index.file_attrs.iter().filter(|row| row.item.path == file_attr.path) {
+            let slot = row.item.line.checked_sub(1).and_then(|idx| addrs.get_mut(idx));
+            if let Some(slot) = slot {
+                *slot = Some(slot.map_or(row.addr, |addr| addr.min(row.addr)));
+            }
+        }
+
         let cache = (
             0..0,
+            theme(),
+            font(),
             Arc::new(Galley {
                 job: Arc::new(LayoutJob::default()),
                 rows: Vec::new(),
@@ -176,6 +200,9 @@ impl Source {
         Self {
             src: src.to_string(),
             lines,
+            addrs,
+            breakpoints,
+            ui_queue,
             max_number_width: max_width,
             scroll: Some(file_attr.line.saturating_sub(1)),
             cache,
@@ -185,21 +212,29 @@ impl Source {
 
 impl Source {
     fn show_code(&mut self, ui: &mut egui::Ui, row_range: Range<usize>) {
-        if self.cache.0 == row_range {
-            ui.label(Arc::clone(&self.cache.1));
+        let theme = theme();
+        let font = font();
+        if self.cache.0 == row_range && self.cache.1 == theme && self.cache.2 == font {
+            ui.label(Arc::clone(&self.cache.3));
             return;
         }
 
         let mut output = LayoutJob::default();
         for line in &self.lines[row_range.clone()] {
             for section in &line.sections {
+                let background = if section.highlighted {
+                    theme.highlight.linear_multiply(0.5)
+                } else {
+                    Color32::TRANSPARENT
+                };
+
                 output.append(
                     &self.src[section.range.clone()],
                     0.0,
                     egui::TextFormat {
-                        color: section.fg_color,
-                        background: section.bg_color,
-                        font_id: FONT,
+                        color: theme.color(section.kind),
+                        background,
+                        font_id: font.clone(),
                         ..Default::default()
                     },
                 );
@@ -207,7 +242,7 @@ impl Source {
         }
 
         let output = ui.fonts(|f| f.layout_job(output));
-        self.cache = (row_range, Arc::clone(&output));
+        self.cache = (row_range, theme, font, Arc::clone(&output));
         ui.label(output);
     }
 
@@ -216,23 +251,46 @@ impl Source {
         for line in &self.lines[row_range.clone()] {
             output.push_str(&line.number);
         }
-        ui.label(egui::RichText::new(output).font(FONT).color(colors::GRAY60));
+
+        let text = egui::RichText::new(output).font(font()).color(theme().muted);
+        let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+        let row_height = response.rect.height() / row_range.len().max(1) as f32;
+        let color = CONFIG.colors.role_color("breakpoint");
+
+        // breakpoints are drawn in the margin left of the line numbers
+        for (idx, addr) in self.addrs[row_range.clone()].iter().enumerate() {
+            if addr.is_some_and(|addr| self.breakpoints.contains(addr)) {
+                let y = response.rect.top() + (idx as f32 + 0.5) * row_height;
+                let center = egui::pos2(response.rect.left() + 4.0, y);
+                ui.painter().circle_filled(center, 4.0, color);
+            }
+        }
+
+        let clicked = response.interact_pointer_pos().filter(|_| response.clicked());
+        if let Some(pos) = clicked {
+            let idx = row_range.start + ((pos.y - response.rect.top()) / row_height) as usize;
+            if let Some(Some(addr)) = self.addrs.get(idx) {
+                self.breakpoints.toggle(*addr);
+                self.ui_queue.push(UIEvent::BreakpointsChanged);
+            }
+        }
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui) {
         let mut area = egui::ScrollArea::vertical().auto_shrink(false).drag_to_scroll(false);
 
         if let Some(scroll) = self.scroll.take() {
-            let row_height = FONT.size;
+            let row_height = font().size;
             let spacing_y = ui.spacing().item_spacing.y;
             let y = scroll as f32 * (row_height + spacing_y);
             area = area.vertical_scroll_offset(y)
         }
 
-        area.show_rows(ui, FONT.size, self.lines.len(), |ui, row_range| {
+        area.show_rows(ui, font().size, self.lines.len(), |ui, row_range| {
             let pad = 8.0;
-            let char_width = ui.fonts(|f| f.glyph_width(&FONT, '1'));
-            let width = char_width * self.max_number_width as f32 + pad;
+            let char_width = ui.fonts(|f| f.glyph_width(&font(), '1'));
+            // the line numbers are preceded by room for a breakpoint
+            let width = char_width * (self.max_number_width + 2) as f32 + pad;
             let split = width / ui.available_width();
 
             let overshoot = 5;
@@ -294,8 +352,9 @@ impl LanguageConfig<'_> {
 #[derive(Clone, Debug)]
 struct HighlightedSection {
     range: Range<usize>,
-    fg_color: Color32,
-    bg_color: Color32,
+    kind: Kind,
+    /// Whether it's on the line being shown.
+    highlighted: bool,
 }
 
 impl PartialOrd for HighlightedSection {
@@ -0,0 +1,99 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use config::CONFIG;
+use processor::{FoundString, Processor, Xref};
+use processor_shared::SearchResults;
+use std::sync::{Arc, OnceLock};
+use tokenizing::{theme, Kind, Token};
+
+pub struct Strings {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Scanned for in the background.
+    strings: Arc<OnceLock<SearchResults<FoundString>>>,
+    /// String last clicked on together with it's references.
+    selected: Option<(usize, Vec<Xref>)>,
+}
+
+impl Strings {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        let strings = Arc::new(OnceLock::new());
+
+        {
+            let processor = Arc::clone(&processor);
+            let strings = Arc::clone(&strings);
+            std::thread::spawn(move || {
+                let _ = strings.set(processor.find_strings(None));
+            });
+        }
+
+        Self {
+            processor,
+            ui_queue,
+            strings,
+            selected: None,
+        }
+    }
+}
+
+fn tokenize_string(processor: &Processor, string: &FoundString) -> Vec<Token> {
+    let section = processor.section_name(string.addr).unwrap_or("?");
+
+    vec![
+        Token::from_string(format!("{:0>10X}", string.addr), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{section:<16}"), Kind::Component),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{:<6}", string.encoding.as_str()), Kind::Comment),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{:?}", string.text), CONFIG.colors.role_kind("string")),
+    ]
+}
+
+impl Display for Strings {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let strings = match self.strings.get() {
+            Some(strings) => strings,
+            None => {
+                ui.label(egui::RichText::new("scanning…").font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        if strings.is_truncated() {
+            let text = "more strings were found, increase `search.max_results` to see them.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+        }
+
+        if let Some((addr, xrefs)) = &self.selected {
+            ui.horizontal_wrapped(|ui| {
+                let text = format!("{addr:#x} referenced by {} instruction(s)", xrefs.len());
+                ui.label(egui::RichText::new(text).font(font()).color(theme().plain));
+
+                for xref in xrefs {
+                    let link = egui::RichText::new(format!("{:#x}", xref.from)).font(font());
+                    if ui.link(link).clicked() {
+                        self.ui_queue.push(UIEvent::GotoAddr(xref.from));
+                    }
+                }
+            });
+        }
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        let mut clicked = None;
+
+        area.show_rows(ui, font().size, strings.matches().len(), |ui, row_range| {
+            for string in &strings.matches()[row_range] {
+                let line = tokenize_string(&self.processor, string);
+                if ui.link(tokens_to_layoutjob(line)).clicked() {
+                    clicked = Some(string.addr);
+                }
+            }
+        });
+
+        if let Some(addr) = clicked {
+            self.selected = Some((addr, self.processor.xrefs_to(addr)));
+            self.ui_queue.push(UIEvent::GotoAddr(addr));
+        }
+    }
+}
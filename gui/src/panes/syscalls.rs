@@ -0,0 +1,100 @@
+use crate::common::*;
+use config::CONFIG;
+use debugger::Syscall;
+use std::collections::VecDeque;
+use tokenizing::{theme, Kind, Token};
+
+/// Most syscalls kept around, older ones are dropped first.
+const MAX_SYSCALLS: usize = 100_000;
+
+/// Role the names of syscalls are shown with.
+const SYSCALL_ROLE: &str = "syscall";
+
+/// Syscalls made by the debugged process, strace-style.
+pub struct Syscalls {
+    syscalls: VecDeque<Syscall>,
+}
+
+impl Default for Syscalls {
+    fn default() -> Self {
+        config::register_role(SYSCALL_ROLE, Kind::Mnemonic);
+
+        Self {
+            syscalls: VecDeque::new(),
+        }
+    }
+}
+
+impl Syscalls {
+    pub fn push(&mut self, syscall: Syscall) {
+        if self.syscalls.len() == MAX_SYSCALLS {
+            self.syscalls.pop_front();
+        }
+
+        self.syscalls.push_back(syscall);
+    }
+
+    pub fn clear(&mut self) {
+        self.syscalls.clear();
+    }
+}
+
+fn tokenize_syscall(syscall: &Syscall) -> Vec<Token> {
+    let name = match syscall.name {
+        Some(name) => name.to_string(),
+        None => format!("syscall_{}", syscall.nr),
+    };
+
+    let mut tokens = vec![
+        Token::from_string(name, CONFIG.colors.role_kind(SYSCALL_ROLE)),
+        Token::from_str("(", Kind::Bracket),
+    ];
+
+    for (idx, arg) in syscall.args.iter().enumerate() {
+        if idx != 0 {
+            tokens.push(Token::from_str(", ", Kind::Delimiter));
+        }
+
+        let color = if arg.starts_with('"') {
+            CONFIG.colors.role_kind("string")
+        } else {
+            Kind::Immediate
+        };
+
+        tokens.push(Token::from_string(arg.clone(), color));
+    }
+
+    tokens.push(Token::from_str(")", Kind::Bracket));
+    tokens.push(Token::from_str(" = ", Kind::Plain));
+
+    let color = if syscall.failed() {
+        CONFIG.colors.role_kind("invalid")
+    } else {
+        Kind::Immediate
+    };
+
+    tokens.push(Token::from_string(syscall.format_ret(), color));
+    tokens
+}
+
+impl Display for Syscalls {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.syscalls.is_empty() {
+            let text = "No syscalls traced, enable tracing with `trace on` before `run`.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let area = egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .drag_to_scroll(false)
+            .stick_to_bottom(true);
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+        area.show_rows(ui, row_height, self.syscalls.len(), |ui, row_range| {
+            for syscall in self.syscalls.range(row_range) {
+                ui.label(tokens_to_layoutjob(tokenize_syscall(syscall)));
+            }
+        });
+    }
+}
@@ -0,0 +1,92 @@
+use crate::common::*;
+use crate::{UiQueue, UIEvent};
+use debugger::{Stop, Thread};
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+pub struct Threads {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    /// Threads at the last stop, ordered by their id.
+    threads: Vec<Thread>,
+}
+
+impl Threads {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            threads: Vec::new(),
+        }
+    }
+
+    pub fn update(&mut self, threads: Vec<Thread>) {
+        self.threads = threads;
+    }
+
+    pub fn contains(&self, tid: u32) -> bool {
+        self.threads.iter().any(|thread| thread.tid == tid)
+    }
+}
+
+fn tokenize_thread(processor: &Processor, thread: &Thread) -> Vec<Token> {
+    let marker = if thread.current { "* " } else { "  " };
+    let mut tokens = vec![
+        Token::from_str(marker, Kind::Plain),
+        Token::from_string(format!("{:<8}", thread.tid), Kind::Muted),
+        Token::from_string(format!("{:<16} ", thread.name), Kind::Plain),
+        Token::from_string(format!("{:0>10X}", thread.pc), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+    ];
+
+    match processor.index.get_sym_containing(thread.pc) {
+        Some((start, sym)) => {
+            tokens.extend(sym.name().iter().cloned());
+            if thread.pc != start {
+                tokens.push(Token::from_string(
+                    format!("+{:#x}", thread.pc - start),
+                    Kind::Immediate,
+                ));
+            }
+        }
+        None => tokens.push(Token::from_str("??", Kind::Comment)),
+    }
+
+    let reason = match thread.stop {
+        Some(Stop::Breakpoint(..)) => Some("breakpoint".to_string()),
+        Some(Stop::Stepped(..)) => Some("stepped".to_string()),
+        Some(Stop::Watchpoint(addr, _)) => Some(format!("watchpoint {addr:#x}")),
+        Some(Stop::Signal(sig, _)) => Some(format!("signal {sig}")),
+        Some(Stop::Forked(pid, _)) => Some(format!("forked {pid}")),
+        Some(Stop::Exec(..)) => Some("exec".to_string()),
+        Some(Stop::Attached(..) | Stop::Exited(..) | Stop::Killed(..)) | None => None,
+    };
+
+    if let Some(reason) = reason {
+        tokens.push(Token::from_string(format!(" ({reason})"), Kind::Comment));
+    }
+
+    tokens
+}
+
+impl Display for Threads {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.threads.is_empty() {
+            let text = "No process is being debugged, start one with `run`.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+        area.show(ui, |ui| {
+            for thread in &self.threads {
+                let tokens = tokenize_thread(&self.processor, thread);
+                let link = ui.link(tokens_to_layoutjob(tokens));
+                if link.on_hover_text("inspect this thread").clicked() {
+                    self.ui_queue.push(UIEvent::SelectThread(thread.tid));
+                }
+            }
+        });
+    }
+}
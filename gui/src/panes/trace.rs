@@ -0,0 +1,132 @@
+use crate::common::*;
+use crate::{UIEvent, UiQueue};
+use config::CONFIG;
+use debugger::Trace;
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::{theme, Kind, Token};
+
+/// How often the instructions of a trace were executed, shown as the background of the
+/// listing.
+pub struct Heatmap {
+    trace: Arc<Trace>,
+    /// Most times any instruction was executed.
+    max: usize,
+}
+
+impl Heatmap {
+    pub fn new(trace: Arc<Trace>) -> Self {
+        let max = trace.counts().values().copied().max().unwrap_or(0);
+        Self { trace, max }
+    }
+
+    /// Background of the instruction at `addr`, stronger the more often it was executed on a
+    /// logarithmic scale. None if it wasn't executed.
+    pub fn color(&self, addr: usize) -> Option<egui::Color32> {
+        let count = *self.trace.counts().get(&addr)?;
+        let heat = match self.max {
+            0 | 1 => 1.0,
+            max => (count as f32).ln() / (max as f32).ln(),
+        };
+
+        Some(CONFIG.colors.role_color("heat").linear_multiply(0.1 + 0.3 * heat))
+    }
+}
+
+/// Instructions executed by the debugged process whilst recording, in the order it did.
+pub struct TraceView {
+    processor: Arc<Processor>,
+    ui_queue: Arc<UiQueue>,
+    trace: Option<Arc<Trace>>,
+}
+
+impl TraceView {
+    pub fn new(processor: Arc<Processor>, ui_queue: Arc<UiQueue>) -> Self {
+        Self {
+            processor,
+            ui_queue,
+            trace: None,
+        }
+    }
+
+    /// Binary the instructions are from.
+    pub fn processor(&self) -> &Arc<Processor> {
+        &self.processor
+    }
+
+    pub fn update(&mut self, trace: Arc<Trace>) {
+        self.trace = Some(trace);
+    }
+
+    pub fn trace(&self) -> Option<&Arc<Trace>> {
+        self.trace.as_ref()
+    }
+}
+
+fn tokenize_addr(processor: &Processor, idx: usize, addr: usize) -> Vec<Token> {
+    let mut tokens = vec![
+        Token::from_string(format!("{idx:<10}"), Kind::Muted),
+        Token::from_string(format!("{addr:0>10X}"), Kind::Address),
+        Token::from_str(" | ", Kind::Plain),
+    ];
+
+    match processor.index.get_sym_containing(addr) {
+        Some((start, sym)) => {
+            tokens.extend(sym.name().iter().cloned());
+            if addr != start {
+                tokens.push(Token::from_string(format!("+{:#x}", addr - start), Kind::Immediate));
+            }
+        }
+        None => tokens.push(Token::from_str("??", Kind::Comment)),
+    }
+
+    tokens
+}
+
+impl Display for TraceView {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        let trace = match self.trace {
+            Some(ref trace) if !trace.is_empty() => trace,
+            _ => {
+                let text = "No instructions recorded, enable recording with `record on` and \
+                            continue the process.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                return;
+            }
+        };
+
+        ui.horizontal(|ui| {
+            let mut summary = format!(
+                "{} instructions recorded in {} KiB",
+                trace.len(),
+                trace.size().div_ceil(1024)
+            );
+
+            if trace.is_full() {
+                summary += ", the trace is full";
+            }
+
+            ui.label(egui::RichText::new(summary).font(font()).color(theme().muted));
+            if ui.button("Export").on_hover_text("Write to a text or JSON file").clicked() {
+                self.ui_queue.push(UIEvent::ExportTrace);
+            }
+        });
+
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+        let area = egui::ScrollArea::both()
+            .auto_shrink([false, false])
+            .drag_to_scroll(false)
+            .stick_to_bottom(true);
+
+        area.show_rows(ui, row_height, trace.len(), |ui, row_range| {
+            let addrs = trace.iter_from(row_range.start).take(row_range.len());
+            for (idx, addr) in row_range.zip(addrs) {
+                let tokens = tokenize_addr(&self.processor, idx, addr);
+                let link = ui.link(tokens_to_layoutjob(tokens));
+                if link.on_hover_text("show in the listing").clicked() {
+                    self.ui_queue.push(UIEvent::GotoAddr(addr));
+                }
+            }
+        });
+    }
+}
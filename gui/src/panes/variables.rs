@@ -0,0 +1,230 @@
+use crate::common::*;
+use config::CONFIG;
+use debugger::variables::Context;
+use debugger::{Frame, Handle, Registers};
+use debugvault::variables::{Layout, Variable};
+use processor::Processor;
+use std::sync::Arc;
+use tokenizing::theme;
+
+/// Most characters of a string that are shown.
+const MAX_STRING_LEN: usize = 64;
+
+/// Most bytes of an aggregate that are shown.
+const MAX_BYTES_LEN: usize = 16;
+
+/// A variable along with it's value at the last stop.
+struct Shown {
+    name: String,
+    ty: String,
+    param: bool,
+    /// None if it couldn't be read.
+    value: Option<String>,
+    changed: bool,
+}
+
+pub struct Variables {
+    processor: Arc<Processor>,
+    /// Function stopped in, as found in the binary.
+    function: Option<usize>,
+    variables: Vec<Shown>,
+}
+
+impl Variables {
+    pub fn new(processor: Arc<Processor>) -> Self {
+        Self {
+            processor,
+            function: None,
+            variables: Vec::new(),
+        }
+    }
+
+    /// Evaluate the variables of the innermost frame, [`None`] once the process is gone.
+    pub fn update(&mut self, stop: Option<(&Registers, &[Frame], &Handle)>) {
+        let (registers, frames, handle, pc) = match stop {
+            Some((registers, frames, handle)) if !frames.is_empty() => {
+                (registers, frames, handle, frames[0].pc)
+            }
+            _ => return self.clear(),
+        };
+
+        let index = &self.processor.index;
+        let (function, scope) = match (index.get_sym_containing(pc), index.get_scope(pc)) {
+            (Some((function, _)), Some(scope)) => (function, scope),
+            _ => return self.clear(),
+        };
+
+        let runtime_pc = registers.get("rip").or_else(|| registers.get("pc")).unwrap_or(0);
+        let read = |addr: usize, len: usize| handle.read_memory(addr, len);
+        let ctx = Context {
+            registers,
+            // The caller's stack pointer is where it was before the call.
+            cfa: frames.get(1).map(|caller| caller.sp),
+            bias: (runtime_pc as usize).wrapping_sub(pc),
+            address_size: scope.address_size,
+            read: &read,
+        };
+
+        let frame_base = scope.frame_base.at(pc);
+        let previous = match self.function {
+            Some(prev) if prev == function => std::mem::take(&mut self.variables),
+            _ => Vec::new(),
+        };
+
+        self.function = Some(function);
+        self.variables = scope
+            .visible(pc)
+            .map(|var| {
+                let value = evaluate(&ctx, var, pc, frame_base);
+                let changed = previous
+                    .iter()
+                    .find(|prev| prev.name == var.name && prev.param == var.param)
+                    .is_some_and(|prev| prev.value != value);
+
+                Shown {
+                    name: var.name.clone(),
+                    ty: var.ty.clone(),
+                    param: var.param,
+                    value,
+                    changed,
+                }
+            })
+            .collect();
+    }
+
+    fn clear(&mut self) {
+        self.function = None;
+        self.variables.clear();
+    }
+}
+
+/// Value of `var` formatted according to it's type, None if it can't be read.
+fn evaluate(ctx: &Context, var: &Variable, pc: usize, frame_base: Option<&[u8]>) -> Option<String> {
+    let expr = var.location.at(pc)?;
+    let place = ctx.locate(expr, frame_base).ok()?;
+    let size = var.layout.size(ctx.address_size as usize);
+    let bytes = ctx.read(&place, size).ok()??;
+    Some(format_value(ctx, &var.layout, &bytes, true))
+}
+
+/// Integer stored in the little endian `bytes`, sign extended if `signed`.
+fn integer(bytes: &[u8], signed: bool) -> u64 {
+    let mut word = [0; 8];
+    let len = bytes.len().min(8);
+    word[..len].copy_from_slice(&bytes[..len]);
+
+    let value = u64::from_le_bytes(word);
+    match len {
+        1..=7 if signed => {
+            let shift = 64 - len as u32 * 8;
+            ((value << shift) as i64 >> shift) as u64
+        }
+        _ => value,
+    }
+}
+
+/// `bytes` shown as a value with `layout`, following pointers one level if `deref`.
+fn format_value(ctx: &Context, layout: &Layout, bytes: &[u8], deref: bool) -> String {
+    match *layout {
+        Layout::Signed(_) => (integer(bytes, true) as i64).to_string(),
+        Layout::Unsigned(_) => integer(bytes, false).to_string(),
+        Layout::Float(_) => match *bytes {
+            [_, _, _, _] => f32::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+            [_, _, _, _, _, _, _, _] => f64::from_le_bytes(bytes.try_into().unwrap()).to_string(),
+            _ => format_bytes(bytes),
+        },
+        Layout::Bool => bytes.iter().any(|&byte| byte != 0).to_string(),
+        Layout::Char => {
+            let byte = bytes.first().copied().unwrap_or(0);
+            format!("{} {:?}", byte as i8, byte as char)
+        }
+        Layout::Pointer(ref pointee) => {
+            let addr = integer(bytes, false) as usize;
+            let mut text = format!("{addr:#x}");
+            if deref && addr != 0 {
+                if let Some(value) = format_pointee(ctx, pointee, addr) {
+                    text += " → ";
+                    text += &value;
+                }
+            }
+            text
+        }
+        Layout::Other(_) => format_bytes(bytes),
+    }
+}
+
+/// Bytes of a value that's not a number, e.g. `{01 00 00 00 ..}`.
+fn format_bytes(bytes: &[u8]) -> String {
+    let shown = &bytes[..bytes.len().min(MAX_BYTES_LEN)];
+    let mut text: Vec<String> = shown.iter().map(|byte| format!("{byte:02x}")).collect();
+    if bytes.len() > MAX_BYTES_LEN {
+        text.push("..".to_string());
+    }
+
+    format!("{{{}}}", text.join(" "))
+}
+
+/// Value `addr` points to, strings being read up to their nul terminator.
+fn format_pointee(ctx: &Context, pointee: &Layout, addr: usize) -> Option<String> {
+    if *pointee == Layout::Char {
+        let bytes = (ctx.read)(addr, MAX_STRING_LEN).ok()?;
+        let len = bytes.iter().position(|&byte| byte == 0).unwrap_or(bytes.len());
+        let mut text = format!("{:?}", String::from_utf8_lossy(&bytes[..len]));
+        if len == bytes.len() {
+            text += "..";
+        }
+        return Some(text);
+    }
+
+    let size = pointee.size(ctx.address_size as usize);
+    if size == 0 {
+        return None;
+    }
+
+    let bytes = (ctx.read)(addr, size).ok()?;
+    Some(format_value(ctx, pointee, &bytes, false))
+}
+
+fn show_group(ui: &mut egui::Ui, title: &str, variables: &[&Shown]) {
+    ui.label(egui::RichText::new(title).font(font()).color(theme().muted));
+
+    egui::Grid::new(title).num_columns(3).spacing([20.0, 0.0]).show(ui, |ui| {
+        for var in variables {
+            let value = match var.value {
+                Some(ref value) if var.changed => egui::RichText::new(value)
+                    .font(font())
+                    .color(CONFIG.colors.role_color("changed")),
+                Some(ref value) => {
+                    egui::RichText::new(value).font(font()).color(theme().immediate)
+                }
+                None => egui::RichText::new("<unavailable>").font(font()).color(theme().muted),
+            };
+
+            ui.label(egui::RichText::new(&var.name).font(font()).color(theme().variable));
+            ui.label(egui::RichText::new(&var.ty).font(font()).color(theme().tipe));
+            ui.label(value);
+            ui.end_row();
+        }
+    });
+}
+
+impl Display for Variables {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        if self.function.is_none() {
+            let text = "No variables, the process isn't stopped in a function with debug info.";
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+            return;
+        }
+
+        let area = egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+
+        area.show(ui, |ui| {
+            let (params, locals): (Vec<&Shown>, Vec<&Shown>) =
+                self.variables.iter().partition(|var| var.param);
+
+            show_group(ui, "Parameters", &params);
+            ui.add_space(font().size);
+            show_group(ui, "Locals", &locals);
+        });
+    }
+}
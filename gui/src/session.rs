@@ -0,0 +1,783 @@
+//! A process being debugged.
+//!
+//! The tracee can only be controlled from the thread that started it, so each session owns a
+//! thread running the [`Target`] that reports back through the [`UiQueue`].
+
+use crate::{UIEvent, UiQueue};
+use commands::{CompleteExpr, ExprError, Machine};
+use debugger::{
+    lines, trace, BreakpointSet, CallFrameInfo, Condition, Console, CoreTarget, Debugger,
+    Environment, FollowFork, Handle, Library, LineTable, Registers, RemoteTarget, Signals, Stop,
+    Target, Trace,
+};
+use debugger::trace::History;
+use processor::Processor;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+
+/// Used for telling apart stops of previous sessions.
+static SESSION_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// How processes are debugged, applied to sessions started afterwards.
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    pub trace_syscalls: bool,
+    pub follow_fork: FollowFork,
+    /// Which signals stop processes and which are passed on to them.
+    pub signals: Signals,
+    /// Whether the instructions executed whilst continuing are recorded.
+    pub record: bool,
+}
+
+/// What's known of the debugged binary, for walking it's stack and stepping it by line.
+#[derive(Default)]
+pub struct DebugInfo {
+    pub cfi: CallFrameInfo,
+    pub lines: LineTable,
+    /// Symbols the conditions of breakpoints can refer to.
+    pub processor: Option<Arc<Processor>>,
+}
+
+/// How far the selected thread is stepped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Execute a single instruction.
+    Instruction,
+    /// Execute until another source line, stepping into calls.
+    Line,
+    /// Execute until another source line, stepping over calls.
+    Over,
+    /// Execute until the current function returns.
+    Out,
+    /// Undo the last instruction executed whilst recording.
+    Back,
+}
+
+/// Standard stream of the tracee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdin,
+    Stdout,
+    Stderr,
+}
+
+/// Read what the tracee writes to `stream` in the background until it exits, pushing it as
+/// it comes in.
+fn forward_output(
+    id: usize,
+    stream: Stream,
+    mut pipe: impl Read + Send + 'static,
+    ui_queue: Arc<UiQueue>,
+) {
+    std::thread::spawn(move || {
+        let mut buf = [0; 4096];
+        let mut pending = Vec::new();
+
+        // Only ends once every process holding the other end exited.
+        while let Ok(len @ 1..) = pipe.read(&mut buf) {
+            pending.extend_from_slice(&buf[..len]);
+
+            // Characters split between reads are completed by the next read.
+            let valid = match std::str::from_utf8(&pending) {
+                Err(err) if err.error_len().is_none() => err.valid_up_to(),
+                _ => pending.len(),
+            };
+
+            let text = String::from_utf8_lossy(&pending[..valid]).into_owned();
+            pending.drain(..valid);
+            ui_queue.push(UIEvent::ProcessOutput(id, stream, text));
+        }
+    });
+}
+
+/// Write what's sent to the tracee's stdin in the background, as it blocks whilst the tracee
+/// isn't reading. Stdin is closed once the sender is dropped.
+fn forward_input(mut pipe: impl Write + Send + 'static) -> mpsc::Sender<Vec<u8>> {
+    let (input_tx, input_rx) = mpsc::channel::<Vec<u8>>();
+
+    std::thread::spawn(move || {
+        for bytes in input_rx {
+            if pipe.write_all(&bytes).is_err() {
+                break;
+            }
+        }
+    });
+
+    input_tx
+}
+
+/// Selected thread of the stopped tracee, which terminal expressions are evaluated against.
+pub struct Stopped<'a> {
+    pub registers: &'a Registers,
+    pub handle: &'a Handle,
+    /// Offset between addresses in the binary and where it's loaded.
+    pub bias: usize,
+}
+
+impl Machine for Stopped<'_> {
+    fn register(&self, name: &str) -> Option<u64> {
+        let value = self.registers.get(name);
+
+        // Names of the program counter and stack pointer that work on every architecture.
+        match name {
+            "pc" => value.or_else(|| self.registers.get("rip")),
+            "sp" => value.or_else(|| self.registers.get("rsp")),
+            _ => value,
+        }
+    }
+
+    fn read(&self, addr: usize, len: usize) -> Option<Vec<u8>> {
+        self.handle.read_memory(addr, len).ok()
+    }
+
+    fn bias(&self) -> usize {
+        self.bias
+    }
+}
+
+/// What hitting a conditional breakpoint does.
+enum Hit {
+    Stop,
+    Continue,
+    /// Print the message and continue.
+    Log(String),
+}
+
+/// Whether hitting the breakpoint at `addr` with `condition` stops the tracee, `hits` counting
+/// the hits where the condition held.
+fn check(
+    target: &dyn Target,
+    debug: &DebugInfo,
+    addr: usize,
+    condition: &Condition,
+    hits: &mut usize,
+) -> Result<Hit, ExprError> {
+    let registers = match target.registers() {
+        Ok(registers) => registers,
+        Err(_) => return Ok(Hit::Stop),
+    };
+
+    let pc = registers.get("rip").or_else(|| registers.get("pc")).unwrap_or(0);
+    let handle = target.handle();
+    let stopped = Stopped {
+        registers: &registers,
+        handle: &handle,
+        bias: (pc as usize).wrapping_sub(addr),
+    };
+
+    let empty_index = debugvault::Index::default();
+    let index = debug.processor.as_ref().map_or(&empty_index, |proc| &proc.index);
+    let mut eval = |expr: &CompleteExpr| expr.eval_in(index, &stopped);
+
+    if let Some(ref expr) = condition.expr {
+        if eval(&CompleteExpr::parse(expr)?)? == 0 {
+            return Ok(Hit::Continue);
+        }
+    }
+
+    *hits += 1;
+    if condition.hits.is_some_and(|from| *hits < from) {
+        return Ok(Hit::Continue);
+    }
+
+    match condition.log {
+        Some(ref msg) => Ok(Hit::Log(commands::format_message(msg, &mut eval)?)),
+        None => Ok(Hit::Stop),
+    }
+}
+
+/// Change to the memory or registers of the stopped tracee.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    /// Bytes written at a runtime address.
+    Memory(usize, Vec<u8>),
+    /// Value of a register of the selected thread.
+    Register(String, u64),
+}
+
+/// Make `edit` to the stopped `target`, returning the edit that undoes it.
+fn apply(target: &mut dyn Target, edit: Edit) -> Result<Edit, debugger::Error> {
+    match edit {
+        Edit::Memory(addr, bytes) => {
+            let original = target.read_memory(addr, bytes.len())?;
+            target.write_memory(addr, &bytes)?;
+            Ok(Edit::Memory(addr, original))
+        }
+        Edit::Register(name, value) => {
+            let original = target.registers()?.get(&name).ok_or(debugger::Error::InvalidRegister)?;
+            target.write_registers(&[(name.as_str(), value)])?;
+            Ok(Edit::Register(name, original))
+        }
+    }
+}
+
+/// What the debugger thread is asked to do whilst the tracee is stopped.
+enum Request {
+    Resume,
+    Step(Step),
+    SelectThread(u32),
+    FollowFork(FollowFork),
+    Signals(Signals),
+    /// Start recording the instructions executed with a new trace, or stop recording.
+    Record(bool),
+    /// The tracee exec'd the binary that's now loaded.
+    Exec(DebugInfo),
+    Write(Edit),
+    /// Undo the last edit since the tracee stopped.
+    Undo,
+}
+
+pub struct Session {
+    id: usize,
+    handle: Handle,
+    /// Requests to the debugger thread, it stops once this is dropped.
+    requests: mpsc::Sender<Request>,
+    running: bool,
+    /// Attached processes and remote targets are left running when the session ends.
+    attached: bool,
+    /// Whether it's a core dump, which can't be resumed.
+    post_mortem: bool,
+    /// Arguments a started process was started with.
+    args: Vec<String>,
+    /// Sends input to a started process' stdin, None once it's closed.
+    input: Option<mpsc::Sender<Vec<u8>>>,
+    /// Whether the instructions executed whilst continuing are recorded.
+    recording: bool,
+    /// Shared libraries loaded into the tracee, as of the last time they changed.
+    libraries: Vec<Library>,
+}
+
+impl Session {
+    /// Start `path` set up as `env` describes and run it until it hits a breakpoint.
+    #[allow(clippy::too_many_arguments)]
+    pub fn start(
+        path: PathBuf,
+        args: Vec<String>,
+        env: Environment,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        debug: DebugInfo,
+        options: Options,
+        ui_queue: Arc<UiQueue>,
+    ) -> Result<Self, debugger::Error> {
+        let (console, stdio) = Console::new()?;
+        let shared = Arc::clone(&breakpoints);
+        let started = args.clone();
+        let queue = Arc::clone(&ui_queue);
+        let mut session = Self::launch(queue, false, options, debug, shared, move || {
+            let stdio = Some(stdio);
+            let debugger = Debugger::spawn(&path, &started, &env, breakpoints, entrypoint, stdio)?;
+            Ok(Box::new(debugger))
+        })?;
+
+        forward_output(session.id, Stream::Stdout, console.stdout, Arc::clone(&ui_queue));
+        forward_output(session.id, Stream::Stderr, console.stderr, ui_queue);
+        session.input = Some(forward_input(console.stdin));
+        session.args = args;
+        Ok(session)
+    }
+
+    /// Attach to the running process `pid`, which stays stopped until resumed.
+    pub fn attach(
+        pid: u32,
+        breakpoints: Arc<BreakpointSet>,
+        entrypoint: usize,
+        debug: DebugInfo,
+        options: Options,
+        ui_queue: Arc<UiQueue>,
+    ) -> Result<Self, debugger::Error> {
+        let shared = Arc::clone(&breakpoints);
+        Self::launch(ui_queue, true, options, debug, shared, move || {
+            Ok(Box::new(Debugger::attach(pid, breakpoints, entrypoint)?))
+        })
+    }
+
+    /// Connect to a remote stub listening at `addr`, the target stays stopped until resumed.
+    pub fn connect(
+        addr: String,
+        breakpoints: Arc<BreakpointSet>,
+        debug: DebugInfo,
+        ui_queue: Arc<UiQueue>,
+    ) -> Result<Self, debugger::Error> {
+        let shared = Arc::clone(&breakpoints);
+        Self::launch(ui_queue, true, Options::default(), debug, shared, move || {
+            Ok(Box::new(RemoteTarget::connect(&addr, breakpoints)?))
+        })
+    }
+
+    /// Inspect the core dump at `path`, written by the loaded binary with `entrypoint`.
+    pub fn core(
+        path: PathBuf,
+        entrypoint: usize,
+        debug: DebugInfo,
+        ui_queue: Arc<UiQueue>,
+    ) -> Result<Self, debugger::Error> {
+        let options = Options::default();
+        let mut session = Self::launch(ui_queue, false, options, debug, Arc::default(), move || {
+            Ok(Box::new(CoreTarget::open(&path, entrypoint)?))
+        })?;
+
+        session.post_mortem = true;
+        Ok(session)
+    }
+
+    fn launch<F>(
+        ui_queue: Arc<UiQueue>,
+        attached: bool,
+        options: Options,
+        mut debug: DebugInfo,
+        breakpoints: Arc<BreakpointSet>,
+        create: F,
+    ) -> Result<Self, debugger::Error>
+    where
+        F: FnOnce() -> Result<Box<dyn Target>, debugger::Error> + Send + 'static,
+    {
+        let id = SESSION_ID.fetch_add(1, Ordering::Relaxed);
+        let (handle_tx, handle_rx) = mpsc::channel();
+        let (requests_tx, requests_rx) = mpsc::channel();
+        let recording = options.record;
+
+        std::thread::spawn(move || {
+            let mut debugger = match create() {
+                Ok(debugger) => debugger,
+                Err(err) => {
+                    let _ = handle_tx.send(Err(err));
+                    return;
+                }
+            };
+
+            if options.trace_syscalls {
+                let ui_queue = Arc::clone(&ui_queue);
+                let traced = debugger.trace_syscalls(Box::new(move |syscall| {
+                    ui_queue.push(UIEvent::SyscallTraced(id, syscall));
+                }));
+
+                if let Err(err) = traced {
+                    log::complex!(
+                        w "[session::run] ",
+                        y "failed to trace syscalls: ",
+                        w format!("{err}"),
+                    );
+                }
+            }
+
+            // Only processes traced on linux report their libraries.
+            let libraries_queue = Arc::clone(&ui_queue);
+            let _ = debugger.watch_libraries(Box::new(move |libraries| {
+                libraries_queue.push(UIEvent::LibrariesChanged(id, libraries));
+            }));
+
+            if let Err(err) = debugger.set_follow_fork(options.follow_fork) {
+                log::complex!(
+                    w "[session::run] ",
+                    y "failed to follow forks: ",
+                    w format!("{err}"),
+                );
+            }
+
+            if let Err(err) = debugger.set_signals(options.signals) {
+                log::complex!(
+                    w "[session::run] ",
+                    y "failed to handle signals: ",
+                    w format!("{err}"),
+                );
+            }
+
+            let _ = handle_tx.send(Ok(debugger.handle()));
+
+            // Attached processes are stopped wherever they were.
+            let mut attached = match attached {
+                true => debugger.pc().ok().map(Stop::Attached),
+                false => None,
+            };
+
+            // How far the tracee was asked to step instead of continuing.
+            let mut step = None;
+
+            // Times the condition of each conditional breakpoint held.
+            let mut hits: HashMap<usize, usize> = HashMap::new();
+
+            // Instructions executed whilst continuing, if they're being recorded.
+            let mut recording = options.record;
+            let mut executed = Trace::default();
+            let mut history = History::default();
+
+            loop {
+                // Execution that isn't recorded can't be stepped back through.
+                if !recording || !matches!(step, None | Some(Step::Instruction | Step::Back)) {
+                    history.clear();
+                }
+
+                let stores = |pc: usize, registers: &Registers| -> Vec<(usize, usize)> {
+                    let processor = match debug.processor {
+                        Some(ref processor) => processor,
+                        None => return Vec::new(),
+                    };
+
+                    let runtime_pc = registers.get("rip").or_else(|| registers.get("pc"));
+                    processor
+                        .stores(pc, runtime_pc.unwrap_or(0), &registers.general)
+                        .into_iter()
+                        .map(|(addr, len)| (addr as usize, len))
+                        .collect()
+                };
+
+                // Conditions are only checked when continuing, steps stop at any breakpoint.
+                let stepping = step.is_some();
+                let next = || match step.take() {
+                    Some(Step::Instruction) if recording => {
+                        trace::step(&mut *debugger, &mut executed, &mut history, &stores)
+                    }
+                    Some(Step::Instruction) => debugger.step(),
+                    Some(Step::Back) => match history.step_back(&mut *debugger)? {
+                        Some(stop) => Ok(stop),
+                        None => {
+                            log::complex!(
+                                w "[session::run] ",
+                                y "there are no recorded instructions to step back through",
+                            );
+                            debugger.pc().map(Stop::Stepped)
+                        }
+                    },
+                    Some(Step::Out) => lines::step_out(&mut *debugger, &debug.cfi, &breakpoints),
+                    Some(how) => {
+                        let over = how == Step::Over;
+                        lines::step_line(&mut *debugger, &debug.lines, &breakpoints, over)
+                    }
+                    None if recording => trace::record(
+                        &mut *debugger,
+                        &breakpoints,
+                        &mut executed,
+                        &mut history,
+                        &stores,
+                    ),
+                    None => debugger.cont(),
+                };
+
+                let stop = match attached.take().map(Ok).unwrap_or_else(next) {
+                    Ok(stop) => stop,
+                    Err(debugger::Error::Detached) => return,
+                    Err(err) => {
+                        log::complex!(
+                            w "[session::run] ",
+                            y "failed to continue: ",
+                            w format!("{err}"),
+                        );
+                        ui_queue.push(UIEvent::DebuggerFailed(id));
+                        return;
+                    }
+                };
+
+                if matches!(stop, Stop::Exited(..) | Stop::Killed(..)) {
+                    if recording {
+                        ui_queue.push(UIEvent::TraceRecorded(id, Arc::new(executed)));
+                    }
+
+                    ui_queue.push(UIEvent::ThreadsListed(id, Vec::new()));
+                    ui_queue.push(UIEvent::DebuggerStopped(id, stop, None, Vec::new()));
+                    return;
+                }
+
+                let condition = match stop {
+                    Stop::Breakpoint(addr) if !stepping => {
+                        breakpoints.condition(addr).map(|condition| (addr, condition))
+                    }
+                    _ => None,
+                };
+
+                if let Some((addr, condition)) = condition {
+                    let hits = hits.entry(addr).or_default();
+                    match check(&*debugger, &debug, addr, &condition, hits) {
+                        Ok(Hit::Stop) => {}
+                        Ok(Hit::Continue) => continue,
+                        Ok(Hit::Log(msg)) => {
+                            ui_queue.push(UIEvent::BreakpointLogged(id, msg));
+                            continue;
+                        }
+                        Err(err) => {
+                            let msg = format!("Condition of breakpoint at {addr:#X} failed: {err}");
+                            ui_queue.push(UIEvent::BreakpointLogged(id, msg));
+                        }
+                    }
+                }
+
+                if recording {
+                    ui_queue.push(UIEvent::TraceRecorded(id, Arc::new(executed.clone())));
+                }
+
+                let registers = debugger.registers().ok();
+                let frames = debugger.backtrace(&debug.cfi).unwrap_or_default();
+                ui_queue.push(UIEvent::ThreadsListed(id, debugger.threads().unwrap_or_default()));
+                ui_queue.push(UIEvent::DebuggerStopped(id, stop, registers, frames));
+
+                // Edits that undo the ones made during this stop, the last one made last.
+                let mut undo: Vec<Edit> = Vec::new();
+
+                loop {
+                    match requests_rx.recv() {
+                        Ok(Request::Resume) => break,
+                        Ok(Request::Step(how)) => {
+                            step = Some(how);
+                            break;
+                        }
+                        Ok(Request::SelectThread(tid)) => {
+                            history.clear();
+                            if let Err(err) = debugger.select_thread(tid) {
+                                log::complex!(
+                                    w "[session::run] ",
+                                    y format!("failed to select thread {tid}: "),
+                                    w format!("{err}"),
+                                );
+                                continue;
+                            }
+
+                            let registers = debugger.registers().ok();
+                            let frames = debugger.backtrace(&debug.cfi).unwrap_or_default();
+                            let threads = debugger.threads().unwrap_or_default();
+                            ui_queue.push(UIEvent::ThreadsListed(id, threads));
+                            ui_queue.push(UIEvent::ThreadSelected(id, registers, frames));
+                        }
+                        Ok(Request::FollowFork(follow)) => {
+                            if let Err(err) = debugger.set_follow_fork(follow) {
+                                log::complex!(
+                                    w "[session::run] ",
+                                    y "failed to follow forks: ",
+                                    w format!("{err}"),
+                                );
+                            }
+                        }
+                        Ok(Request::Signals(signals)) => {
+                            if let Err(err) = debugger.set_signals(signals) {
+                                log::complex!(
+                                    w "[session::run] ",
+                                    y "failed to handle signals: ",
+                                    w format!("{err}"),
+                                );
+                            }
+                        }
+                        Ok(Request::Record(enabled)) => {
+                            if enabled && !recording {
+                                executed = Trace::default();
+                            }
+
+                            recording = enabled;
+                            history.clear();
+                        }
+                        Ok(Request::Exec(new)) => {
+                            debug = new;
+
+                            let registers = debugger.registers().ok();
+                            let frames = debugger.backtrace(&debug.cfi).unwrap_or_default();
+                            let threads = debugger.threads().unwrap_or_default();
+                            ui_queue.push(UIEvent::ThreadsListed(id, threads));
+                            ui_queue.push(UIEvent::DebuggerReloaded(id, registers, frames));
+                        }
+                        Ok(Request::Write(edit)) => {
+                            history.clear();
+                            let what = match edit {
+                                Edit::Memory(addr, _) => format!("write memory at {addr:#x}"),
+                                Edit::Register(ref name, _) => format!("set {name}"),
+                            };
+
+                            match apply(&mut *debugger, edit) {
+                                Ok(inverse) => undo.push(inverse),
+                                Err(err) => {
+                                    let msg = format!("Failed to {what}: {err}");
+                                    ui_queue.push(UIEvent::WriteFailed(id, msg));
+                                    continue;
+                                }
+                            }
+
+                            let registers = debugger.registers().ok();
+                            let frames = debugger.backtrace(&debug.cfi).unwrap_or_default();
+                            let undoable = undo.len();
+                            ui_queue.push(UIEvent::ProcessWritten(id, registers, frames, undoable));
+                        }
+                        Ok(Request::Undo) => {
+                            let edit = match undo.pop() {
+                                Some(edit) => edit,
+                                None => continue,
+                            };
+
+                            if let Err(err) = apply(&mut *debugger, edit) {
+                                let msg = format!("Failed to undo the last change: {err}");
+                                ui_queue.push(UIEvent::WriteFailed(id, msg));
+                            }
+
+                            let registers = debugger.registers().ok();
+                            let frames = debugger.backtrace(&debug.cfi).unwrap_or_default();
+                            let undoable = undo.len();
+                            ui_queue.push(UIEvent::ProcessWritten(id, registers, frames, undoable));
+                        }
+                        // Dropping the debugger kills or detaches from the tracee.
+                        Err(_) => return,
+                    }
+                }
+            }
+        });
+
+        let handle = handle_rx.recv().map_err(|_| debugger::Error::Exited)??;
+
+        Ok(Self {
+            id,
+            handle,
+            requests: requests_tx,
+            running: !attached,
+            attached,
+            post_mortem: false,
+            args: Vec::new(),
+            input: None,
+            recording,
+            libraries: Vec::new(),
+        })
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.attached
+    }
+
+    pub fn is_post_mortem(&self) -> bool {
+        self.post_mortem
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Whether the instructions executed whilst continuing are recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    pub fn stopped(&mut self) {
+        self.running = false;
+    }
+
+    /// Continue after a stop, returns false if the tracee is already running or is a core.
+    pub fn resume(&mut self) -> bool {
+        if self.running || self.post_mortem {
+            return false;
+        }
+
+        self.running = self.requests.send(Request::Resume).is_ok();
+        self.running
+    }
+
+    /// Step the selected thread after a stop, returns false if the tracee is already running
+    /// or is a core.
+    pub fn step(&mut self, how: Step) -> bool {
+        if self.running || self.post_mortem {
+            return false;
+        }
+
+        self.running = self.requests.send(Request::Step(how)).is_ok();
+        self.running
+    }
+
+    /// Inspect thread `tid` of the stopped tracee, returns false if the tracee is running.
+    pub fn select_thread(&self, tid: u32) -> bool {
+        !self.running && self.requests.send(Request::SelectThread(tid)).is_ok()
+    }
+
+    /// Choose which process to debug after the tracee forks, applied once it stops if it's
+    /// running.
+    pub fn set_follow_fork(&self, follow: FollowFork) {
+        let _ = self.requests.send(Request::FollowFork(follow));
+    }
+
+    /// Choose which signals stop the tracee and which are passed on to it, applied once it
+    /// stops if it's running.
+    pub fn set_signals(&self, signals: Signals) {
+        let _ = self.requests.send(Request::Signals(signals));
+    }
+
+    /// Record the instructions executed whilst continuing in a new trace, or stop recording,
+    /// applied once the tracee stops if it's running.
+    pub fn set_recording(&mut self, enabled: bool) {
+        self.recording = enabled;
+        let _ = self.requests.send(Request::Record(enabled));
+    }
+
+    /// Debug the tracee against the binary it exec'd, which is now loaded.
+    pub fn exec(&self, debug: DebugInfo) {
+        let _ = self.requests.send(Request::Exec(debug));
+    }
+
+    /// Change the memory or a register of the stopped tracee, returns false if it's running or
+    /// is a core.
+    pub fn write(&self, edit: Edit) -> bool {
+        !self.running && !self.post_mortem && self.requests.send(Request::Write(edit)).is_ok()
+    }
+
+    /// Undo the last change made since the tracee stopped, returns false if it's running.
+    pub fn undo(&self) -> bool {
+        !self.running && self.requests.send(Request::Undo).is_ok()
+    }
+
+    /// Shared libraries loaded into the tracee, empty if it doesn't report them.
+    pub fn libraries(&self) -> &[Library] {
+        &self.libraries
+    }
+
+    /// Replace the libraries loaded into the tracee, returning the previous ones.
+    pub fn set_libraries(&mut self, libraries: Vec<Library>) -> Vec<Library> {
+        std::mem::replace(&mut self.libraries, libraries)
+    }
+
+    /// Arguments a started process was started with, empty for any other tracee.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Stop the running tracee, returns false if it's already stopped or is a core.
+    pub fn pause(&self) -> bool {
+        if !self.running || self.post_mortem {
+            return false;
+        }
+
+        self.handle.pause();
+        true
+    }
+
+    /// Write `bytes` to the stdin of a started process, returns false if it's input was closed
+    /// or the tracee wasn't started by us.
+    pub fn send_input(&self, bytes: Vec<u8>) -> bool {
+        self.input.as_ref().is_some_and(|input| input.send(bytes).is_ok())
+    }
+
+    /// Close the stdin of a started process, which it reads as the end of it's input.
+    pub fn close_input(&mut self) -> bool {
+        self.input.take().is_some()
+    }
+
+    /// Apply changes to the breakpoints whilst the tracee is running.
+    pub fn breakpoints_changed(&self) {
+        // Breakpoints are applied anyways before resuming, and before each step whilst recording.
+        if self.running && !self.recording {
+            self.handle.interrupt();
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        match self.attached {
+            // A stopped tracee is detached from by the debugger thread once it's dropped.
+            true if self.running => self.handle.detach(),
+            true => {}
+            false => self.handle.kill(),
+        }
+    }
+}
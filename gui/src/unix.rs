@@ -51,6 +51,13 @@ mod macos {
             let app_m = ManuallyDrop::new(Submenu::new("App", true));
             bar.append(&*app_m)?;
             app_m.append_items(&[
+                &MenuItem::with_id(
+                    "preferences",
+                    "Preferences...",
+                    true,
+                    Some(Accelerator::new(Some(Modifiers::SUPER), Code::Comma)),
+                ),
+                &PredefinedMenuItem::separator(),
                 &PredefinedMenuItem::services(None),
                 &PredefinedMenuItem::separator(),
                 &PredefinedMenuItem::hide(None),
@@ -63,6 +70,20 @@ mod macos {
                     true,
                     Some(Accelerator::new(Some(Modifiers::SUPER), Code::KeyO)),
                 ),
+                &MenuItem::with_id("open_raw", "Open Raw Binary...", true, None),
+                &MenuItem::with_id("open_core", "Open Core Dump...", true, None),
+                &MenuItem::with_id("open_project", "Open Project...", true, None),
+                &MenuItem::with_id(
+                    "save_project",
+                    "Save Project...",
+                    true,
+                    Some(Accelerator::new(Some(Modifiers::SUPER), Code::KeyS)),
+                ),
+                &MenuItem::with_id("import_coverage", "Import Coverage...", true, None),
+                &MenuItem::with_id("export_listing", "Export Listing...", true, None),
+                &MenuItem::with_id("save_patched", "Save Patched Binary...", true, None),
+                &MenuItem::with_id("save_unpacked", "Save Unpacked Binary...", true, None),
+                &MenuItem::with_id("close_binary", "Close Binary", true, None),
                 &PredefinedMenuItem::quit(None),
             ])?;
 
@@ -87,7 +87,7 @@ impl Display for Donut {
         // HACK: has to be done this way since egui can't center two
         // widgets at once (progress bar and donut).
         let panel = ui.max_rect();
-        let mut font = FONT;
+        let mut font = font();
         font.size /= 1.5;
         let rect = ui.painter().text(
             panel.center(),
@@ -0,0 +1,143 @@
+use crate::common::*;
+use crate::style::STYLE;
+use debugvault::fuzzy::FuzzyMatch;
+use processor::Processor;
+use tokenizing::{theme, Kind, Token};
+
+/// Most symbols suggested at once.
+const MAX_SUGGESTIONS: usize = 20;
+
+/// Palette for jumping to an address, symbol or expression such as `main + 0x40`.
+#[derive(Default)]
+pub struct GotoPalette {
+    open: bool,
+    query: String,
+    /// Query the suggestions were made for.
+    suggested_for: Option<String>,
+    /// Where the query evaluates to, or why it doesn't.
+    target: Option<Result<usize, String>>,
+    suggestions: Vec<FuzzyMatch>,
+    /// Row Enter jumps to, the target being the first row if there is one.
+    selected: usize,
+}
+
+impl GotoPalette {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.query.clear();
+        self.suggested_for = None;
+        self.selected = 0;
+    }
+
+    fn update(&mut self, processor: &Processor) {
+        if self.suggested_for.as_deref() == Some(&self.query) {
+            return;
+        }
+
+        let query = self.query.trim();
+        if query.is_empty() {
+            self.target = None;
+            self.suggestions.clear();
+        } else {
+            let target = commands::eval_address(query, &processor.index);
+            self.target = Some(target.map_err(|err| err.to_string()));
+            self.suggestions = processor.fuzzy_search(query, MAX_SUGGESTIONS);
+        }
+
+        self.suggested_for = Some(self.query.clone());
+        self.selected = 0;
+    }
+
+    /// Addresses of the rows, starting with the target.
+    fn rows(&self) -> Vec<usize> {
+        let target = self.target.as_ref().and_then(|target| target.as_ref().ok()).copied();
+        target.into_iter().chain(self.suggestions.iter().map(|m| m.addr)).collect()
+    }
+
+    /// Draw the palette if it's open, returns the address to jump to.
+    pub fn show(&mut self, ctx: &egui::Context, processor: &Processor) -> Option<usize> {
+        if !self.open {
+            return None;
+        }
+
+        let mut picked = None;
+        let (enter, escape, up, down) = ctx.input(|input| {
+            (
+                input.key_pressed(egui::Key::Enter),
+                input.key_pressed(egui::Key::Escape),
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::ArrowDown),
+            )
+        });
+
+        egui::Window::new("Go to")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                let input = egui::TextEdit::singleline(&mut self.query)
+                    .font(font())
+                    .hint_text("address, symbol or expression e.g. main + 0x40")
+                    .desired_width(500.0);
+                ui.add(input).request_focus();
+
+                self.update(processor);
+                let rows = self.rows();
+
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+
+                if down {
+                    self.selected = (self.selected + 1).min(rows.len().saturating_sub(1));
+                }
+
+                if enter {
+                    picked = rows.get(self.selected).copied();
+                }
+
+                let mut row = 0;
+                let mut draw_row = |ui: &mut egui::Ui, text: egui::WidgetText, addr: usize| {
+                    let response = ui.link(text);
+                    if row == self.selected {
+                        ui.painter().rect_filled(response.rect, 0.0, STYLE.selection_color);
+                    }
+
+                    if response.clicked() {
+                        picked = Some(addr);
+                    }
+
+                    row += 1;
+                };
+
+                match self.target {
+                    Some(Ok(addr)) => {
+                        let text = egui::RichText::new(format!("Go to {addr:#x}"))
+                            .font(font())
+                            .color(theme().address);
+                        draw_row(ui, text.into(), addr);
+                    }
+                    Some(Err(ref err)) if self.suggestions.is_empty() => {
+                        ui.label(egui::RichText::new(err).font(font()).color(theme().muted));
+                    }
+                    _ => {}
+                }
+
+                for m in &self.suggestions {
+                    let mut tokens = vec![Token::from_string(
+                        format!("{:0>10X}  ", m.addr),
+                        Kind::Address,
+                    )];
+                    tokens.extend_from_slice(m.symbol.name());
+                    draw_row(ui, tokens_to_layoutjob(tokens).into(), m.addr);
+                }
+            });
+
+        if escape || picked.is_some() {
+            self.open = false;
+        }
+
+        picked
+    }
+}
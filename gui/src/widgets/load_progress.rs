@@ -0,0 +1,71 @@
+use crate::common::*;
+use processor::{Loading, Progress, Stage};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use tokenizing::theme;
+
+/// Stages of the binary being loaded, shown whilst waiting on it.
+pub struct LoadProgress {
+    loading: Loading,
+    progress: Receiver<Progress>,
+    /// Stage being run, if any has started.
+    current: Option<Stage>,
+    /// Stages that finished, with how long they took and a summary of what they found.
+    finished: Vec<(Stage, Duration, String)>,
+}
+
+impl Default for LoadProgress {
+    fn default() -> Self {
+        let (loading, progress) = Loading::new();
+
+        Self {
+            loading,
+            progress,
+            current: None,
+            finished: Vec::new(),
+        }
+    }
+}
+
+impl LoadProgress {
+    /// Handle passed to the thread loading the binary.
+    pub fn loading(&self) -> Loading {
+        self.loading.clone()
+    }
+
+    pub fn id(&self) -> usize {
+        self.loading.id()
+    }
+
+    pub fn cancel(&self) {
+        self.loading.cancel();
+    }
+
+    fn update(&mut self) {
+        while let Ok(progress) = self.progress.try_recv() {
+            match progress {
+                Progress::Started(stage) => self.current = Some(stage),
+                Progress::Finished(stage, elapsed, summary) => {
+                    self.current = None;
+                    self.finished.push((stage, elapsed, summary));
+                }
+            }
+        }
+    }
+}
+
+impl Display for LoadProgress {
+    fn show(&mut self, ui: &mut egui::Ui) {
+        self.update();
+
+        for (stage, elapsed, summary) in &self.finished {
+            let text = format!("{} took {elapsed:.2?}, found {summary}.", stage.name());
+            ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+        }
+
+        if let Some(stage) = self.current {
+            let text = format!("{}/{} {}...", stage.number(), Stage::ALL.len(), stage.name());
+            ui.label(egui::RichText::new(text).font(font()).color(theme().plain));
+        }
+    }
+}
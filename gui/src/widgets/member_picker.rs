@@ -0,0 +1,85 @@
+use crate::common::*;
+use std::path::PathBuf;
+use tokenizing::{theme, Kind, Token};
+
+/// Window listing the members of an archive or the slices of a fat Mach-O for picking one to load.
+#[derive(Default)]
+pub struct MemberPicker {
+    /// Container being loaded, the window is closed if there's none.
+    path: Option<PathBuf>,
+    members: Vec<String>,
+    filter: String,
+}
+
+impl MemberPicker {
+    /// Ask which of the `members` of `path` to load.
+    pub fn open(&mut self, path: PathBuf, members: Vec<String>) {
+        self.path = Some(path);
+        self.members = members;
+        self.filter.clear();
+    }
+
+    /// Draw the window if it's open, returns the container and the index of the picked member.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<(PathBuf, usize)> {
+        let path = self.path.clone()?;
+        let mut open = true;
+        let mut picked = None;
+
+        egui::Window::new("Load member")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([400.0, 400.0])
+            .show(ctx, |ui| {
+                let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                let count = self.members.len();
+                let text = format!("{name} holds {count} objects, pick one to load.");
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Filter").font(font()).color(theme().muted));
+                    ui.text_edit_singleline(&mut self.filter);
+                });
+
+                let filter = self.filter.to_lowercase();
+                let members: Vec<(usize, &String)> = self
+                    .members
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, name)| name.to_lowercase().contains(&filter))
+                    .collect();
+
+                if members.is_empty() {
+                    let text = "No objects found.";
+                    ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                    return;
+                }
+
+                let area =
+                    egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+                let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+                area.show_rows(ui, row_height, members.len(), |ui, row_range| {
+                    for &(idx, name) in &members[row_range] {
+                        let link = egui::Link::new(tokens_to_layoutjob(tokenize_member(idx, name)));
+                        if ui.add(link).clicked() {
+                            picked = Some((path.clone(), idx));
+                        }
+                    }
+                });
+            });
+
+        if !open || picked.is_some() {
+            self.path = None;
+        }
+
+        picked
+    }
+}
+
+fn tokenize_member(idx: usize, name: &str) -> Vec<Token> {
+    vec![
+        Token::from_string(format!("{idx:>4}"), Kind::Immediate),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(name.to_string(), Kind::Plain),
+    ]
+}
@@ -0,0 +1,224 @@
+//! Strip standing in for the scrollbar of a listing, showing the whole of the binary with
+//! markers for where things are.
+//!
+//! Sections are laid out one after the other from top to bottom, the gaps between them being
+//! left out, so that each byte that's loaded takes up the same height.
+
+use crate::common::*;
+use crate::style::STYLE;
+use config::CONFIG;
+use processor::Processor;
+use processor_shared::SectionKind;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+use tokenizing::theme;
+
+/// Width of the strip.
+pub const WIDTH: f32 = 20.0;
+
+/// What a marker points out, each kind being drawn in it's own lane of the strip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Marker {
+    Function,
+    String,
+    Patch,
+    Breakpoint,
+    SearchHit,
+}
+
+impl Marker {
+    const ALL: [Self; 5] = [
+        Self::Function,
+        Self::String,
+        Self::Patch,
+        Self::Breakpoint,
+        Self::SearchHit,
+    ];
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            Self::Function => theme().function,
+            Self::String => theme().string,
+            Self::Patch => CONFIG.colors.role_color("user"),
+            Self::Breakpoint => CONFIG.colors.role_color("breakpoint"),
+            Self::SearchHit => theme().highlight,
+        }
+    }
+
+    fn lane(self) -> usize {
+        Self::ALL.iter().position(|&marker| marker == self).unwrap()
+    }
+}
+
+/// Section as it's laid out on the strip.
+#[derive(Clone)]
+struct Span {
+    start: usize,
+    end: usize,
+    /// Bytes laid out above the section.
+    offset: usize,
+    code: bool,
+}
+
+/// Addresses of the functions and strings, found in the background.
+struct Landmarks {
+    functions: Vec<usize>,
+    strings: Vec<usize>,
+}
+
+pub struct Minimap {
+    spans: Vec<Span>,
+    /// Bytes laid out on the strip.
+    len: usize,
+    landmarks: Arc<OnceLock<Landmarks>>,
+}
+
+impl Minimap {
+    pub fn new(processor: &Arc<Processor>) -> Self {
+        let landmarks = Arc::new(OnceLock::new());
+
+        {
+            let processor = Arc::clone(processor);
+            let landmarks = Arc::clone(&landmarks);
+            std::thread::spawn(move || {
+                let functions = processor.index.functions().map(|func| func.addr).collect();
+                let strings = processor.find_strings(None).into_matches();
+                let strings = strings.into_iter().map(|string| string.addr).collect();
+                let _ = landmarks.set(Landmarks { functions, strings });
+            });
+        }
+
+        let mut sections: Vec<_> = processor.sections().collect();
+        sections.sort_unstable_by_key(|section| section.start);
+
+        let mut spans = Vec::with_capacity(sections.len());
+        let mut len = 0;
+        for section in sections {
+            spans.push(Span {
+                start: section.start,
+                end: section.end,
+                offset: len,
+                code: section.kind == SectionKind::Code,
+            });
+            len += section.end - section.start;
+        }
+
+        Self {
+            spans,
+            len,
+            landmarks,
+        }
+    }
+
+    /// Another minimap of the same binary, sharing what was found in the background.
+    pub fn fork(&self) -> Self {
+        Self {
+            spans: self.spans.clone(),
+            len: self.len,
+            landmarks: Arc::clone(&self.landmarks),
+        }
+    }
+
+    /// How far down the strip `addr` is, from 0 to 1. None if it isn't loaded.
+    fn fraction(&self, addr: usize) -> Option<f32> {
+        let idx = self.spans.partition_point(|span| span.start <= addr).checked_sub(1)?;
+        let span = &self.spans[idx];
+        if addr >= span.end {
+            return None;
+        }
+
+        Some((span.offset + addr - span.start) as f32 / self.len.max(1) as f32)
+    }
+
+    /// Address `fraction` of the way down the strip.
+    fn addr_at(&self, fraction: f32) -> Option<usize> {
+        let offset = (fraction.clamp(0.0, 1.0) * self.len as f32) as usize;
+        let idx = self.spans.partition_point(|span| span.offset <= offset).checked_sub(1)?;
+        let span = &self.spans[idx];
+        Some((span.start + offset - span.offset).min(span.end.saturating_sub(1)))
+    }
+
+    /// Draw the strip in `rect`, with the addresses in `view` outlined as the part that's shown.
+    ///
+    /// Returns the response along with the address pointed at, if any.
+    pub fn show(
+        &self,
+        ui: &mut egui::Ui,
+        rect: egui::Rect,
+        view: Range<usize>,
+        markers: &[(Marker, &[usize])],
+    ) -> (egui::Response, Option<usize>) {
+        let response = ui.interact(rect, ui.id().with("minimap"), egui::Sense::click_and_drag());
+        let painter = ui.painter_at(rect);
+        let y = |fraction: f32| rect.top() + fraction * rect.height();
+
+        painter.rect_filled(rect, 0.0, theme().bg_secondary);
+        for span in self.spans.iter().filter(|span| span.code) {
+            let top = y(span.offset as f32 / self.len.max(1) as f32);
+            let bottom = y((span.offset + span.end - span.start) as f32 / self.len.max(1) as f32);
+            let code = egui::Rect::from_x_y_ranges(rect.x_range(), top..=bottom);
+            painter.rect_filled(code, 0.0, theme().border);
+        }
+
+        let landmarks = self.landmarks.get();
+        let found = landmarks.iter().flat_map(|landmarks| {
+            [
+                (Marker::Function, landmarks.functions.as_slice()),
+                (Marker::String, landmarks.strings.as_slice()),
+            ]
+        });
+
+        let lane_width = rect.width() / Marker::ALL.len() as f32;
+        for (marker, addrs) in found.chain(markers.iter().copied()) {
+            // markers falling on the same row of pixels are drawn once
+            let mut rows = vec![false; rect.height().max(1.0) as usize];
+            for &addr in addrs {
+                if let Some(fraction) = self.fraction(addr) {
+                    let row = (fraction * rows.len() as f32) as usize;
+                    rows[row.min(rows.len() - 1)] = true;
+                }
+            }
+
+            let left = rect.left() + marker.lane() as f32 * lane_width;
+            let mut row = 0;
+            while row < rows.len() {
+                if !rows[row] {
+                    row += 1;
+                    continue;
+                }
+
+                let start = row;
+                while row < rows.len() && rows[row] {
+                    row += 1;
+                }
+
+                let min = egui::pos2(left, rect.top() + start as f32);
+                let size = egui::vec2(lane_width, (row - start) as f32);
+                painter.rect_filled(egui::Rect::from_min_size(min, size), 0.0, marker.color());
+            }
+        }
+
+        if let (Some(start), Some(end)) = (self.fraction(view.start), self.fraction(view.end)) {
+            let bottom = y(end).max(y(start) + 2.0);
+            let shown = egui::Rect::from_x_y_ranges(rect.x_range(), y(start)..=bottom);
+            painter.rect(
+                shown,
+                0.0,
+                STYLE.selection_color,
+                egui::Stroke::new(1.0, theme().plain),
+            );
+        }
+
+        let pointed = response
+            .interact_pointer_pos()
+            .or(response.hover_pos())
+            .and_then(|pos| self.addr_at((pos.y - rect.top()) / rect.height()));
+
+        let response = match pointed {
+            Some(addr) => response.on_hover_text_at_pointer(format!("{addr:#x}")),
+            None => response,
+        };
+
+        (response, pointed)
+    }
+}
@@ -1,9 +1,25 @@
 mod donut;
+mod goto_palette;
+mod load_progress;
+mod member_picker;
+pub mod minimap;
+mod preferences;
+mod process_picker;
+mod raw_loader;
+mod run_config;
 mod terminal;
 mod text_edit;
 mod text_select;
 
 pub use donut::Donut;
+pub use goto_palette::GotoPalette;
+pub use load_progress::LoadProgress;
+pub use member_picker::MemberPicker;
+pub use minimap::Minimap;
+pub use preferences::Preferences;
+pub use process_picker::ProcessPicker;
+pub use raw_loader::RawLoader;
+pub use run_config::RunConfig;
 pub use terminal::Terminal;
 pub use text_edit::TextEdit;
 pub use text_select::TextSelection;
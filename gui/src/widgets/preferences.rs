@@ -0,0 +1,148 @@
+use crate::common::*;
+use crate::panes::PANELS;
+use config::{Settings, DEFAULT_KEYS, FONT_SIZES, SETTINGS};
+use tokenizing::{theme, Syntax, Theme};
+
+/// Window for changing the settings, which take effect and are saved as soon as they change.
+#[derive(Default)]
+pub struct Preferences {
+    open: bool,
+}
+
+fn label(ui: &mut egui::Ui, text: &str) {
+    ui.label(egui::RichText::new(text).font(font()));
+}
+
+impl Preferences {
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    /// Draw the window if it's open, applying whatever was changed in it.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+
+        // Edited from a copy so that the settings aren't locked whilst the window is drawn.
+        let mut settings = SETTINGS.read().unwrap().clone();
+
+        egui::Window::new("Preferences")
+            .open(&mut self.open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("preferences").num_columns(2).show(ui, |ui| {
+                    general(ui, &mut settings);
+                });
+
+                ui.separator();
+                shortcuts(ui, &mut settings);
+                ui.separator();
+
+                let text = format!("{} recently opened binaries", settings.recent_files.len());
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                    if ui.button("Clear").clicked() {
+                        settings.recent_files.clear();
+                    }
+                });
+            });
+
+        crate::style::change_settings(|current| *current = settings);
+    }
+}
+
+fn general(ui: &mut egui::Ui, settings: &mut Settings) {
+    label(ui, "Font size");
+    ui.add(egui::Slider::new(&mut settings.font_size, FONT_SIZES).step_by(1.0));
+    ui.end_row();
+
+    label(ui, "Theme");
+    egui::ComboBox::from_id_source("theme")
+        .selected_text(settings.theme.clone())
+        .show_ui(ui, |ui| {
+            for builtin in Theme::BUILTIN {
+                ui.selectable_value(&mut settings.theme, builtin.name.to_string(), builtin.name);
+            }
+        });
+    ui.end_row();
+
+    let format = &mut settings.format;
+    label(ui, "x86 syntax");
+    ui.horizontal(|ui| {
+        for syntax in Syntax::ALL {
+            ui.radio_value(&mut format.syntax, syntax, syntax.name());
+        }
+    });
+    ui.end_row();
+
+    label(ui, "Immediates");
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut format.hex_immediates, true, "Hexadecimal");
+        ui.radio_value(&mut format.hex_immediates, false, "Decimal");
+    });
+    ui.end_row();
+
+    label(ui, "Instructions");
+    ui.vertical(|ui| {
+        ui.checkbox(&mut format.uppercase, "Uppercase mnemonics");
+        ui.checkbox(&mut format.opcode_bytes, "Opcode bytes");
+        ui.checkbox(&mut format.symbols, "Addresses as symbol plus offset");
+    });
+    ui.end_row();
+
+    label(ui, "Bytes shown");
+    ui.add(egui::Slider::new(&mut format.bytes_width, 1..=16));
+    ui.end_row();
+
+    label(ui, "Mnemonic column");
+    ui.add(egui::Slider::new(&mut format.mnemonic_width, 4..=16).suffix(" chars"));
+    ui.end_row();
+
+    label(ui, "Comment column");
+    ui.add(egui::Slider::new(&mut format.instruction_width, 16..=80).suffix(" chars"));
+    ui.end_row();
+
+    label(ui, "Open at start");
+    ui.vertical(|ui| {
+        for (name, _) in PANELS {
+            let mut checked = settings.panels.iter().any(|panel| panel == name);
+            if ui.checkbox(&mut checked, *name).changed() {
+                if checked {
+                    settings.panels.push(name.to_string());
+                } else {
+                    settings.panels.retain(|panel| panel != name);
+                }
+            }
+        }
+    });
+    ui.end_row();
+}
+
+fn shortcuts(ui: &mut egui::Ui, settings: &mut Settings) {
+    let text = "Shortcuts, several being separated by commas, e.g. Ctrl+G, Ctrl+P";
+    ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+
+    egui::Grid::new("shortcuts").num_columns(3).show(ui, |ui| {
+        for (action, default) in DEFAULT_KEYS {
+            label(ui, action);
+
+            let mut keys = settings.keys(action).to_string();
+            if ui.text_edit_singleline(&mut keys).changed() {
+                if keys == *default {
+                    settings.keys.remove(*action);
+                } else {
+                    settings.keys.insert(action.to_string(), keys.clone());
+                }
+            }
+
+            let valid = settings.shortcuts(action).len();
+            if !keys.trim().is_empty() && valid != keys.split(',').count() {
+                let text = egui::RichText::new("invalid").font(font()).color(theme().invalid);
+                ui.label(text);
+            }
+            ui.end_row();
+        }
+    });
+}
@@ -0,0 +1,106 @@
+use crate::common::*;
+use debugger::ProcessInfo;
+use tokenizing::{theme, Kind, Token};
+
+/// Window listing the running processes for picking one to attach to.
+#[derive(Default)]
+pub struct ProcessPicker {
+    open: bool,
+    processes: Vec<ProcessInfo>,
+    filter: String,
+}
+
+impl ProcessPicker {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        self.processes = debugger::processes();
+    }
+
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        if self.filter.is_empty() {
+            return true;
+        }
+
+        let filter = self.filter.to_lowercase();
+        process.pid.to_string().starts_with(&filter)
+            || process.name.to_lowercase().contains(&filter)
+            || process
+                .path
+                .as_ref()
+                .is_some_and(|path| path.to_string_lossy().to_lowercase().contains(&filter))
+    }
+
+    /// Draw the window if it's open, returns the pid of the picked process.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<u32> {
+        if !self.open {
+            return None;
+        }
+
+        let mut open = self.open;
+        let mut picked = None;
+
+        egui::Window::new("Attach to process")
+            .open(&mut open)
+            .collapsible(false)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new("Filter").font(font()).color(theme().muted));
+                    ui.text_edit_singleline(&mut self.filter);
+
+                    if ui.button("Refresh").clicked() {
+                        self.refresh();
+                    }
+                });
+
+                let processes: Vec<&ProcessInfo> =
+                    self.processes.iter().filter(|process| self.matches(process)).collect();
+
+                if processes.is_empty() {
+                    let text = "No processes found.";
+                    ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                    return;
+                }
+
+                let area =
+                    egui::ScrollArea::both().auto_shrink([false, false]).drag_to_scroll(false);
+                let row_height = ui.fonts(|fonts| fonts.row_height(&font()));
+
+                area.show_rows(ui, row_height, processes.len(), |ui, row_range| {
+                    for process in &processes[row_range] {
+                        // Processes we can't inspect most likely can't be traced either.
+                        let response = ui.add_enabled(
+                            process.path.is_some(),
+                            egui::Link::new(tokens_to_layoutjob(tokenize_process(process))),
+                        );
+
+                        if response.clicked() {
+                            picked = Some(process.pid);
+                        }
+                    }
+                });
+            });
+
+        self.open = open && picked.is_none();
+        picked
+    }
+}
+
+fn tokenize_process(process: &ProcessInfo) -> Vec<Token> {
+    let path = match process.path {
+        Some(ref path) => path.display().to_string(),
+        None => String::new(),
+    };
+
+    vec![
+        Token::from_string(format!("{:>8}", process.pid), Kind::Immediate),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(format!("{:<16}", process.name), Kind::Plain),
+        Token::from_str(" | ", Kind::Plain),
+        Token::from_string(path, Kind::Comment),
+    ]
+}
@@ -0,0 +1,115 @@
+use crate::common::*;
+use object::Endianness;
+use processor::{RawOptions, ARCHITECTURES};
+use std::path::PathBuf;
+use tokenizing::theme;
+
+/// Window for choosing how to load a file without an object header, such as firmware.
+pub struct RawLoader {
+    /// File being loaded, the window is closed if there's none.
+    path: Option<PathBuf>,
+    /// Index into [`ARCHITECTURES`].
+    arch: usize,
+    big_endian: bool,
+    base: String,
+}
+
+impl Default for RawLoader {
+    fn default() -> Self {
+        Self {
+            path: None,
+            arch: 0,
+            big_endian: false,
+            base: "0x0".to_string(),
+        }
+    }
+}
+
+impl RawLoader {
+    /// Ask how to load `path`, keeping the options of the last raw file.
+    pub fn open(&mut self, path: PathBuf) {
+        self.path = Some(path);
+    }
+
+    /// Options currently entered, None if the base isn't a valid address.
+    fn options(&self) -> Option<RawOptions> {
+        let index = debugvault::Index::default();
+        let mut options = RawOptions::new(ARCHITECTURES[self.arch].1);
+        options.base = commands::eval_address(&self.base, &index).ok()?;
+        options.endianness = if self.big_endian {
+            Endianness::Big
+        } else {
+            Endianness::Little
+        };
+
+        Some(options)
+    }
+
+    /// Draw the window if it's open, returns the file to load and how to load it.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<(PathBuf, RawOptions)> {
+        let path = self.path.clone()?;
+        let mut open = true;
+        let mut picked = None;
+
+        egui::Window::new("Load raw binary")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+                let text = format!("{name} has no known header, it will be loaded as code.");
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+
+                egui::Grid::new("raw options").num_columns(2).show(ui, |ui| {
+                    ui.label(egui::RichText::new("Architecture").font(font()));
+                    let previous = self.arch;
+                    egui::ComboBox::from_id_source("raw arch")
+                        .selected_text(ARCHITECTURES[self.arch].0)
+                        .show_ui(ui, |ui| {
+                            for (idx, (name, arch)) in ARCHITECTURES.iter().enumerate() {
+                                let bits = arch.address_size().map_or(0, |size| size.bytes() * 8);
+                                let text = format!("{name} ({bits}-bit)");
+                                ui.selectable_value(&mut self.arch, idx, text);
+                            }
+                        });
+
+                    // Picking an architecture resets the endianness to the one it usually has.
+                    if self.arch != previous {
+                        let options = RawOptions::new(ARCHITECTURES[self.arch].1);
+                        self.big_endian = options.endianness == Endianness::Big;
+                    }
+                    ui.end_row();
+
+                    ui.label(egui::RichText::new("Endianness").font(font()));
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.big_endian, false, "Little");
+                        ui.radio_value(&mut self.big_endian, true, "Big");
+                    });
+                    ui.end_row();
+
+                    ui.label(egui::RichText::new("Base address").font(font()));
+                    ui.text_edit_singleline(&mut self.base);
+                    ui.end_row();
+                });
+
+                let text = "Intel HEX, SREC and TI-TXT files load at the addresses they store.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+
+                let options = self.options();
+                if options.is_none() {
+                    let text = "Base address isn't a valid address.";
+                    ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                }
+
+                if ui.add_enabled(options.is_some(), egui::Button::new("Load")).clicked() {
+                    picked = options.map(|options| (path.clone(), options));
+                }
+            });
+
+        if !open || picked.is_some() {
+            self.path = None;
+        }
+
+        picked
+    }
+}
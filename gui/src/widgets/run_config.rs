@@ -0,0 +1,140 @@
+use crate::common::*;
+use debugger::Environment;
+use std::path::{Path, PathBuf};
+use tokenizing::theme;
+
+/// Window for choosing how processes started with `run` are set up.
+#[derive(Default)]
+pub struct RunConfig {
+    open: bool,
+    /// Arguments used when `run` is given none.
+    pub args: Vec<String>,
+    pub env: Environment,
+    /// Text of the fields as they're typed.
+    args_text: String,
+    cwd_text: String,
+    stdin_text: String,
+    /// Name and value of the variable being added.
+    new_var: (String, String),
+}
+
+fn label(ui: &mut egui::Ui, text: &str) {
+    ui.label(egui::RichText::new(text).font(font()));
+}
+
+fn path_text(path: Option<&Path>) -> String {
+    path.map(|path| path.display().to_string()).unwrap_or_default()
+}
+
+/// Path typed into a field, None if it was left empty.
+fn text_path(text: &str) -> Option<PathBuf> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| PathBuf::from(text))
+}
+
+impl RunConfig {
+    pub fn open(&mut self) {
+        self.open = true;
+        self.args_text = self.args.join(" ");
+        self.cwd_text = path_text(self.env.cwd.as_deref());
+        self.stdin_text = path_text(self.env.stdin.as_deref());
+    }
+
+    /// Draw the window if it's open, applying whatever was changed in it. Returns whether a
+    /// process should be started.
+    pub fn show(&mut self, ctx: &egui::Context) -> bool {
+        if !self.open {
+            return false;
+        }
+
+        let mut open = true;
+        let mut run = false;
+
+        egui::Window::new("Run configuration")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("run configuration").num_columns(2).show(ui, |ui| {
+                    label(ui, "Arguments");
+                    if ui.text_edit_singleline(&mut self.args_text).changed() {
+                        self.args = self.args_text.split_whitespace().map(String::from).collect();
+                    }
+                    ui.end_row();
+
+                    label(ui, "Working directory");
+                    let cwd = egui::TextEdit::singleline(&mut self.cwd_text).hint_text("ours");
+                    if ui.add(cwd).changed() {
+                        self.env.cwd = text_path(&self.cwd_text);
+                    }
+                    ui.end_row();
+
+                    label(ui, "Input");
+                    ui.horizontal(|ui| {
+                        let stdin =
+                            egui::TextEdit::singleline(&mut self.stdin_text).hint_text("console");
+                        if ui.add(stdin).changed() {
+                            self.env.stdin = text_path(&self.stdin_text);
+                        }
+
+                        if ui.button("Browse").clicked() {
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                self.stdin_text = path.display().to_string();
+                                self.env.stdin = Some(path);
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    label(ui, "Environment");
+                    ui.vertical(|ui| self.vars(ui));
+                    ui.end_row();
+
+                    label(ui, "Randomization");
+                    let aslr = egui::Checkbox::new(&mut self.env.disable_aslr, "Disable ASLR");
+                    ui.add_enabled(cfg!(target_os = "linux"), aslr)
+                        .on_disabled_hover_text("Only linux can turn it off for a process.");
+                    ui.end_row();
+                });
+
+                let text = "Applies to processes started afterwards, the variables are set on \
+                            top of the ones bite was started with.";
+                ui.label(egui::RichText::new(text).font(font()).color(theme().muted));
+                run = ui.button("Run").clicked();
+            });
+
+        self.open = open && !run;
+        run
+    }
+
+    /// Variables that are set, along with a row for adding one.
+    fn vars(&mut self, ui: &mut egui::Ui) {
+        let mut removed = None;
+        for (idx, (name, value)) in self.env.vars.iter().enumerate() {
+            ui.horizontal(|ui| {
+                label(ui, &format!("{name}={value}"));
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(idx);
+                }
+            });
+        }
+
+        if let Some(idx) = removed {
+            self.env.vars.remove(idx);
+        }
+
+        let added = ui.horizontal(|ui| {
+            let (name, value) = &mut self.new_var;
+            ui.add(egui::TextEdit::singleline(name).hint_text("name").desired_width(120.0));
+            ui.add(egui::TextEdit::singleline(value).hint_text("value").desired_width(200.0));
+
+            let valid = !name.is_empty() && !name.contains('=');
+            ui.add_enabled(valid, egui::Button::new("Add")).clicked()
+        });
+
+        if added.inner {
+            let (name, value) = std::mem::take(&mut self.new_var);
+            self.env.set_var(name, value);
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use crate::common::*;
-use crate::style::EGUI;
 use crate::widgets::TextSelection;
 
+use config::CONFIG;
 use debugvault::Index;
-use tokenizing::colors;
+use tokenizing::theme;
 
 use egui::text::LayoutJob;
 use once_cell::sync::Lazy;
+use std::fmt::Write;
+use std::ops::Range;
 use std::path::PathBuf;
 
 static HISTORY_PATH: Lazy<PathBuf> = Lazy::new(|| {
@@ -106,12 +108,15 @@ impl Autocomplete {
 
 pub struct Terminal {
     prompt: String,
+    /// Parts of the prompt that are errors.
+    errors: Vec<Range<usize>>,
     commands: Vec<String>,
     commands_unprocessed: usize,
     command_position: usize,
     cursor_position: usize, // byte offset
     reset_cursor: bool,
     autocomplete: Autocomplete,
+    scroll: StickyScroll,
 }
 
 impl Terminal {
@@ -131,11 +136,13 @@ impl Terminal {
 
         Self {
             prompt: String::new(),
+            errors: Vec::new(),
             commands,
             command_position,
             commands_unprocessed: 0,
             cursor_position: 0,
             reset_cursor: true,
+            scroll: StickyScroll::default(),
             autocomplete: Autocomplete::default(),
         }
     }
@@ -155,11 +162,27 @@ impl Terminal {
         self.autocomplete.clear();
     }
 
+    /// Replace the line being typed with `line`, e.g. the start of a command.
+    pub fn set_line(&mut self, line: &str) {
+        self.clear_line();
+        self.commands[self.command_position].push_str(line);
+        self.move_to_end();
+        self.reset_cursor = true;
+    }
+
     pub fn clear(&mut self) {
         self.prompt.clear();
+        self.errors.clear();
         self.clear_line();
     }
 
+    /// Print an error, colored differently from the rest of the output.
+    pub fn print_error(&mut self, err: impl std::fmt::Display) {
+        let start = self.prompt.len();
+        let _ = writeln!(self, "error: {err}");
+        self.errors.push(start..self.prompt.len());
+    }
+
     /// Search through newer commands, finding one that isn't empty.
     fn scroll_to_next_cmd(&mut self) {
         while self.command_position != self.commands.len() - 1 {
@@ -497,6 +520,7 @@ impl Terminal {
 
         if events_processed > 0 {
             self.reset_cursor = true;
+            self.scroll.resume();
             // store new commands recorded
             let _ = self.save_command_history();
         }
@@ -504,17 +528,18 @@ impl Terminal {
         events_processed
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) -> egui::Response {
+    /// Show the prompt, noting that symbols can't be autocompleted yet whilst `indexing`.
+    pub fn show(&mut self, ui: &mut egui::Ui, indexing: bool) -> egui::Response {
         let area = egui::ScrollArea::vertical()
             .auto_shrink([false, false])
             .drag_to_scroll(false)
-            .stick_to_bottom(true)
             .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::AlwaysHidden);
 
-        let response = area.show(ui, |ui| {
+        let mut scroll = std::mem::take(&mut self.scroll);
+        let response = scroll.show(ui, area, |ui| {
             let title = "(bite) ";
             let input = self.current_line();
-            let color = EGUI.noninteractive().fg_stroke.color;
+            let color = ui.visuals().widgets.noninteractive.fg_stroke.color;
 
             let mut output = LayoutJob::default();
             let mut append = |s: &str, color: egui::Color32| {
@@ -522,19 +547,30 @@ impl Terminal {
                     &s,
                     0.0,
                     egui::TextFormat {
-                        font_id: FONT,
+                        font_id: font(),
                         color,
                         ..Default::default()
                     },
                 );
             };
 
-            append(&self.prompt, color);
+            let mut printed = 0;
+            for error in &self.errors {
+                append(&self.prompt[printed..error.start], color);
+                append(&self.prompt[error.clone()], CONFIG.colors.role_color("invalid"));
+                printed = error.end;
+            }
+
+            append(&self.prompt[printed..], color);
             append(title, color);
             append(input, color);
 
             if let Some(suggestion) = self.autocomplete.term_suggestion() {
-                append(suggestion, colors::GRAY60);
+                append(suggestion, theme().muted);
+            }
+
+            if indexing && !input.is_empty() {
+                append("  (still indexing…)", theme().muted);
             }
 
             let mut text_area = TextSelection::precomputed(&output);
@@ -548,7 +584,8 @@ impl Terminal {
             ui.add_sized(ui.available_size(), text_area)
         });
 
-        response.inner
+        self.scroll = scroll;
+        response
     }
 }
 
@@ -1,4 +1,3 @@
-use crate::style::EGUI;
 use crate::widgets::TextEdit;
 
 use egui::text::LayoutJob;
@@ -51,7 +50,7 @@ impl egui::Widget for TextSelection<'_> {
                 (None, None) => LayoutJob::simple(
                     self.text.clone(),
                     self.font.clone(),
-                    EGUI.noninteractive().fg_stroke.color,
+                    ui.visuals().widgets.noninteractive.fg_stroke.color,
                     wrap_width,
                 ),
             };
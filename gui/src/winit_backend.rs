@@ -52,13 +52,24 @@ impl Platform {
         fonts.families.get_mut(&FontFamily::Monospace).unwrap().push("hack".to_owned());
         fonts.families.get_mut(&FontFamily::Monospace).unwrap().push("icons".to_owned());
 
-        if let Some((font_name, bytes)) = find_cjk_font().and_then(read_font) {
+        // Fonts to fall back to for glyphs that are missing from hack, e.g. demangled symbols
+        // with non-ASCII identifiers.
+        for (font_name, bytes) in find_fallback_fonts().into_iter().filter_map(read_font) {
+            if fonts.font_data.contains_key(&font_name) {
+                continue;
+            }
+
             fonts.font_data.insert(font_name.clone(), FontData::from_owned(bytes));
-            fonts.families.get_mut(&FontFamily::Monospace).unwrap().push(font_name);
+            fonts.families.get_mut(&FontFamily::Monospace).unwrap().push(font_name.clone());
+            fonts.families.get_mut(&FontFamily::Proportional).unwrap().push(font_name);
         }
 
         context.set_fonts(fonts);
-        context.set_style(crate::style::EGUI.clone());
+        let settings = config::SETTINGS.read().unwrap();
+        crate::style::switch_theme(&settings.theme);
+        tokenizing::set_format(settings.format);
+        drop(settings);
+        context.set_style(crate::style::egui(&tokenizing::theme()));
 
         let mut viewports = HashMap::default();
         viewports.insert(
@@ -376,30 +387,70 @@ impl Platform {
     }
 }
 
+/// Paths of fonts to use when a glyph is missing, in order of preference.
+fn find_fallback_fonts() -> Vec<String> {
+    [find_unicode_font(), find_cjk_font()].into_iter().flatten().collect()
+}
+
 #[cfg(unix)]
-fn find_cjk_font() -> Option<String> {
+fn fc_match(pattern: &str) -> Option<String> {
     let output = std::process::Command::new("fc-match")
         .arg("--verbose")
-        .arg(":lang=zh:charset=6c49")
+        .arg(pattern)
         .output()
         .ok()?;
 
     let stdout = std::str::from_utf8(&output.stdout).ok()?;
-    let font_line = stdout
+    stdout
         .lines()
         .find_map(|line| line.split_once("\tfile: \""))
-        .and_then(|(_, line)| line.rfind("\"").map(|idx| &line[..idx]));
+        .and_then(|(_, line)| line.rfind("\"").map(|idx| line[..idx].to_string()))
+}
+
+/// Monospace font covering latin extended, greek, cyrillic and math symbols.
+#[cfg(unix)]
+fn find_unicode_font() -> Option<String> {
+    let font_line = fc_match(":spacing=mono:charset=100-17f 370-3ff 400-4ff 2200-22ff");
 
     #[cfg(target_os = "macos")]
-    let font_line = font_line.unwrap_or("/System/Library/Fonts/Hiragino Sans GB.ttc");
+    let font_line = font_line.unwrap_or("/System/Library/Fonts/Menlo.ttc".to_string());
 
     #[cfg(target_os = "linux")]
-    let font_line = font_line.unwrap_or("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc");
+    let font_line =
+        font_line.unwrap_or("/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf".to_string());
 
     let font_path = font_line.split(':').next()?.trim();
     Some(font_path.to_string())
 }
 
+#[cfg(unix)]
+fn find_cjk_font() -> Option<String> {
+    let font_line = fc_match(":lang=zh:charset=6c49");
+
+    #[cfg(target_os = "macos")]
+    let font_line = font_line.unwrap_or("/System/Library/Fonts/Hiragino Sans GB.ttc".to_string());
+
+    #[cfg(target_os = "linux")]
+    let font_line =
+        font_line.unwrap_or("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc".to_string());
+
+    let font_path = font_line.split(':').next()?.trim();
+    Some(font_path.to_string())
+}
+
+#[cfg(windows)]
+fn find_unicode_font() -> Option<String> {
+    let font_file = {
+        // C:/Windows/Fonts/seguisym.ttf
+        let mut font_path = std::path::PathBuf::from(std::env::var("SystemRoot").ok()?);
+        font_path.push("Fonts");
+        font_path.push("seguisym.ttf");
+        font_path.to_str()?.to_string().replace("\\", "/")
+    };
+
+    Some(font_file)
+}
+
 #[cfg(windows)]
 fn find_cjk_font() -> Option<String> {
     let font_file = {
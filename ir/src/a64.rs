@@ -0,0 +1,693 @@
+//! Lifting of AArch64 instructions.
+//!
+//! Only general purpose registers are lifted, instructions using SIMD and floating point
+//! registers aren't.
+
+use crate::{add, result_flags, sub, truncate};
+use crate::{BinOp, Builder, Expr, Flag, Lift, Stmt, UnOp, Value, Var};
+use arm::armv8::a64::{Instruction, Opcode, Operand, ShiftStyle, SizeCode};
+
+const REGISTERS: [&str; 31] = [
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30",
+];
+
+const STACK_POINTER: &str = "sp";
+const LINK_REGISTER: &str = "x30";
+
+fn bytes(size: SizeCode) -> u8 {
+    match size {
+        SizeCode::X => 8,
+        SizeCode::W => 4,
+    }
+}
+
+/// Register `num`, where 31 is either the stack pointer or the zero register which is None.
+fn register(num: u16, sp: bool) -> Option<Option<&'static str>> {
+    match num {
+        31 if sp => Some(Some(STACK_POINTER)),
+        31 => Some(None),
+        num => REGISTERS.get(num as usize).map(|name| Some(*name)),
+    }
+}
+
+/// Value of register `num`, zero extended.
+fn read(b: &mut Builder, size: SizeCode, num: u16, sp: bool) -> Option<Value> {
+    let full = match register(num, sp)? {
+        Some(name) => Value::reg(name),
+        None => return Some(Value::Const(0)),
+    };
+
+    Some(truncate(b, full, bytes(size)))
+}
+
+/// Write `value` to register `num`, where 32-bit registers are zero extended.
+fn write(b: &mut Builder, size: SizeCode, num: u16, sp: bool, value: Value) -> Option<()> {
+    // Writes to the zero register are discarded.
+    if let Some(name) = register(num, sp)? {
+        match size {
+            SizeCode::X => b.assign(Var::Reg(name), Expr::Value(value)),
+            SizeCode::W => b.assign(Var::Reg(name), Expr::zext(value, 4)),
+        }
+    }
+
+    Some(())
+}
+
+/// `value` of `size` bytes shifted or extended by `style`.
+fn shift(b: &mut Builder, value: Value, style: ShiftStyle, amount: u8, size: u8) -> Value {
+    let plain = matches!(
+        style,
+        ShiftStyle::LSL
+            | ShiftStyle::LSR
+            | ShiftStyle::ASR
+            | ShiftStyle::ROR
+            | ShiftStyle::UXTX
+            | ShiftStyle::SXTX
+    );
+    if plain && amount == 0 {
+        return truncate(b, value, size);
+    }
+
+    let amount = Value::Const(amount as u64);
+    let shifted = match style {
+        ShiftStyle::LSL => b.binary(BinOp::Shl, value, amount),
+        ShiftStyle::LSR => b.binary(BinOp::Shr, value, amount),
+        ShiftStyle::ASR => {
+            let signed = b.temp(Expr::sext(value, size));
+            b.binary(BinOp::Sar, signed, amount)
+        }
+        ShiftStyle::ROR => rotate(b, value, amount, size),
+        ShiftStyle::UXTX | ShiftStyle::SXTX => b.binary(BinOp::Shl, value, amount),
+        style => {
+            let extended = match style {
+                ShiftStyle::UXTB => Expr::zext(value, 1),
+                ShiftStyle::UXTH => Expr::zext(value, 2),
+                ShiftStyle::UXTW => Expr::zext(value, 4),
+                ShiftStyle::SXTB => Expr::sext(value, 1),
+                ShiftStyle::SXTH => Expr::sext(value, 2),
+                _ => Expr::sext(value, 4),
+            };
+
+            let extended = b.temp(extended);
+            b.binary(BinOp::Shl, extended, amount)
+        }
+    };
+
+    truncate(b, shifted, size)
+}
+
+/// `value` of `size` bytes rotated right by `amount`.
+fn rotate(b: &mut Builder, value: Value, amount: Value, size: u8) -> Value {
+    let right = b.binary(BinOp::Shr, value, amount);
+    let left_amount = b.binary(BinOp::Sub, Value::Const(size as u64 * 8), amount);
+    let left = b.binary(BinOp::Shl, value, left_amount);
+    let rotated = b.binary(BinOp::Or, right, left);
+    truncate(b, rotated, size)
+}
+
+/// Whether condition code `cc` holds.
+fn condition(b: &mut Builder, cc: u8) -> Value {
+    let zero = Value::flag(Flag::Zero);
+    let negative = Value::flag(Flag::Sign);
+    let carry = Value::flag(Flag::Carry);
+    let overflow = Value::flag(Flag::Overflow);
+
+    let holds = match cc >> 1 {
+        0 => zero,
+        1 => carry,
+        2 => negative,
+        3 => overflow,
+        4 => {
+            let not_zero = b.binary(BinOp::Xor, zero, Value::Const(1));
+            b.binary(BinOp::And, carry, not_zero)
+        }
+        5 => b.binary(BinOp::Eq, negative, overflow),
+        6 => {
+            let not_zero = b.binary(BinOp::Xor, zero, Value::Const(1));
+            let greater_or_equal = b.binary(BinOp::Eq, negative, overflow);
+            b.binary(BinOp::And, not_zero, greater_or_equal)
+        }
+        // `al` and `nv` always hold.
+        _ => return Value::Const(1),
+    };
+
+    // Odd conditions are the inverse of the even one before them.
+    match cc & 1 {
+        1 => b.binary(BinOp::Xor, holds, Value::Const(1)),
+        _ => holds,
+    }
+}
+
+/// Sets the flags by the result of a bitwise operation.
+fn logic_flags(b: &mut Builder, result: Value, size: u8) {
+    result_flags(b, result, size);
+    b.assign(Var::Flag(Flag::Carry), Expr::Value(Value::Const(0)));
+    b.assign(Var::Flag(Flag::Overflow), Expr::Value(Value::Const(0)));
+}
+
+/// Memory an operand refers to, with the base register's value after the access if it's
+/// written back.
+struct Memory {
+    addr: Value,
+    writeback: Option<(u16, Value)>,
+}
+
+impl Memory {
+    /// Address `offset` bytes into the memory.
+    fn at(&self, b: &mut Builder, offset: u64) -> Value {
+        match offset {
+            0 => self.addr,
+            offset => b.binary(BinOp::Add, self.addr, Value::Const(offset)),
+        }
+    }
+}
+
+struct Lifter<'a> {
+    inst: &'a Instruction,
+    addr: u64,
+}
+
+impl Lifter<'_> {
+    fn operand(&self, idx: usize) -> Operand {
+        self.inst.operands[idx]
+    }
+
+    /// Size of the register operand at `idx`.
+    fn size(&self, idx: usize) -> Option<SizeCode> {
+        match self.operand(idx) {
+            Operand::Register(size, _) | Operand::RegisterOrSP(size, _) => Some(size),
+            _ => None,
+        }
+    }
+
+    /// Value of the operand at `idx`, as an operand of `size` bytes.
+    fn value(&self, b: &mut Builder, idx: usize, size: u8) -> Option<Value> {
+        Some(match self.operand(idx) {
+            Operand::Register(code, num) => read(b, code, num, false)?,
+            Operand::RegisterOrSP(code, num) => read(b, code, num, true)?,
+            Operand::RegShift(style, amount, code, num) => {
+                let value = read(b, code, num, false)?;
+                shift(b, value, style, amount, size)
+            }
+            Operand::Immediate(imm) => Value::Const(imm as u64),
+            Operand::Imm16(imm) => Value::Const(imm as u64),
+            Operand::Imm64(imm) | Operand::Imm64Special(imm) => Value::Const(imm),
+            Operand::ImmShift(imm, amount) => Value::Const((imm as u64) << amount),
+            Operand::PCOffset(offset) => Value::Const(self.addr.wrapping_add_signed(offset)),
+            _ => return None,
+        })
+    }
+
+    fn write(&self, b: &mut Builder, idx: usize, value: Value) -> Option<()> {
+        match self.operand(idx) {
+            Operand::Register(code, num) => write(b, code, num, false, value),
+            Operand::RegisterOrSP(code, num) => write(b, code, num, true, value),
+            _ => None,
+        }
+    }
+
+    /// Memory the operand at `idx` refers to.
+    fn memory(&self, b: &mut Builder, idx: usize) -> Option<Memory> {
+        let base = |b: &mut Builder, num: u16| read(b, SizeCode::X, num, true);
+        let offset = |b: &mut Builder, base: Value, offset: i32| match offset {
+            0 => base,
+            offset => b.binary(BinOp::Add, base, Value::Const(offset as i64 as u64)),
+        };
+
+        Some(match self.operand(idx) {
+            Operand::RegPreIndex(num, off, writeback) => {
+                let base = base(b, num)?;
+                let addr = offset(b, base, off);
+                Memory {
+                    addr,
+                    writeback: writeback.then_some((num, addr)),
+                }
+            }
+            Operand::RegPostIndex(num, off) => {
+                let addr = base(b, num)?;
+                let addr = b.temp(Expr::Value(addr));
+                let after = offset(b, addr, off);
+                Memory {
+                    addr,
+                    writeback: Some((num, after)),
+                }
+            }
+            Operand::RegPostIndexReg(num, offset) => {
+                let addr = base(b, num)?;
+                let addr = b.temp(Expr::Value(addr));
+                let offset = read(b, SizeCode::X, offset, false)?;
+                let after = b.binary(BinOp::Add, addr, offset);
+                Memory {
+                    addr,
+                    writeback: Some((num, after)),
+                }
+            }
+            Operand::RegRegOffset(num, index, code, style, amount) => {
+                let base = base(b, num)?;
+                let index = read(b, code, index, false)?;
+                let index = shift(b, index, style, amount, 8);
+                Memory {
+                    addr: b.binary(BinOp::Add, base, index),
+                    writeback: None,
+                }
+            }
+            // Loads from the page computed by an `adrp` before them and literal loads.
+            Operand::Imm64(addr) => Memory {
+                addr: Value::Const(addr),
+                writeback: None,
+            },
+            Operand::PCOffset(offset) => Memory {
+                addr: Value::Const(self.addr.wrapping_add_signed(offset)),
+                writeback: None,
+            },
+            _ => return None,
+        })
+    }
+
+    fn writeback(&self, b: &mut Builder, memory: &Memory) -> Option<()> {
+        match memory.writeback {
+            Some((num, value)) => write(b, SizeCode::X, num, true, value),
+            None => Some(()),
+        }
+    }
+
+    /// Load `size` bytes into the registers at `regs`, one after another in memory.
+    fn load(&self, b: &mut Builder, regs: usize, size: Option<u8>, signed: bool) -> Option<()> {
+        let memory = self.memory(b, regs)?;
+        for idx in 0..regs {
+            let code = self.size(idx)?;
+            let size = size.unwrap_or(bytes(code));
+            let addr = memory.at(b, idx as u64 * size as u64);
+            let mut value = b.temp(Expr::Load(addr, size));
+            if signed {
+                value = b.temp(Expr::sext(value, size));
+            }
+            self.write(b, idx, value)?;
+        }
+
+        self.writeback(b, &memory)
+    }
+
+    /// Store `size` bytes of the registers at `regs`, one after another in memory.
+    fn store(&self, b: &mut Builder, regs: usize, size: Option<u8>) -> Option<()> {
+        let memory = self.memory(b, regs)?;
+        for idx in 0..regs {
+            let code = self.size(idx)?;
+            let size = size.unwrap_or(bytes(code));
+            let addr = memory.at(b, idx as u64 * size as u64);
+            let value = self.value(b, idx, size)?;
+            b.push(Stmt::Store { addr, value, size });
+        }
+
+        self.writeback(b, &memory)
+    }
+
+    fn lift(&self, b: &mut Builder) -> Option<()> {
+        let next = Value::Const(self.addr.wrapping_add(4));
+
+        match self.inst.opcode {
+            // Hints include `nop` and pointer authentication, which is assumed to succeed.
+            Opcode::HINT => {}
+            Opcode::ADR => {
+                let addr = self.value(b, 1, 8)?;
+                self.write(b, 0, addr)?;
+            }
+            Opcode::ADRP => {
+                let addr = match self.operand(1) {
+                    Operand::PCOffset(offset) => (self.addr & !0xfff).wrapping_add_signed(offset),
+                    _ => match self.value(b, 1, 8)? {
+                        Value::Const(addr) => addr,
+                        Value::Var(_) => return None,
+                    },
+                };
+                self.write(b, 0, Value::Const(addr))?;
+            }
+            Opcode::MOVZ | Opcode::MOVN => {
+                let size = bytes(self.size(0)?);
+                let imm = self.value(b, 1, size)?;
+                let imm = match self.inst.opcode {
+                    Opcode::MOVN => b.temp(Expr::Unary(UnOp::Not, imm)),
+                    _ => imm,
+                };
+                let imm = truncate(b, imm, size);
+                self.write(b, 0, imm)?;
+            }
+            Opcode::MOVK => {
+                let (imm, amount) = match self.operand(1) {
+                    Operand::ImmShift(imm, amount) => (imm as u64, amount),
+                    _ => return None,
+                };
+
+                let size = self.size(0)?;
+                let current = self.value(b, 0, bytes(size))?;
+                let mask = Value::Const(!(0xffff << amount));
+                let kept = b.binary(BinOp::And, current, mask);
+                let value = b.binary(BinOp::Or, kept, Value::Const(imm << amount));
+                self.write(b, 0, value)?;
+            }
+            Opcode::ADD | Opcode::ADDS | Opcode::SUB | Opcode::SUBS => {
+                let size = bytes(self.size(0)?);
+                let lhs = self.value(b, 1, size)?;
+                let rhs = self.value(b, 2, size)?;
+                let result = match self.inst.opcode {
+                    Opcode::ADD => {
+                        let sum = b.binary(BinOp::Add, lhs, rhs);
+                        truncate(b, sum, size)
+                    }
+                    Opcode::SUB => {
+                        let diff = b.binary(BinOp::Sub, lhs, rhs);
+                        truncate(b, diff, size)
+                    }
+                    Opcode::ADDS => {
+                        let sum = add(b, lhs, rhs, size);
+                        b.assign(Var::Flag(Flag::Carry), Expr::Binary(BinOp::Ult, sum, lhs));
+                        sum
+                    }
+                    _ => {
+                        // The carry is set when the subtraction doesn't borrow.
+                        let carry = Expr::Binary(BinOp::Ule, rhs, lhs);
+                        b.assign(Var::Flag(Flag::Carry), carry);
+                        sub(b, lhs, rhs, size)
+                    }
+                };
+                self.write(b, 0, result)?;
+            }
+            Opcode::AND
+            | Opcode::ANDS
+            | Opcode::ORR
+            | Opcode::EOR
+            | Opcode::BIC
+            | Opcode::BICS
+            | Opcode::ORN
+            | Opcode::EON => {
+                let opcode = self.inst.opcode;
+                let size = bytes(self.size(0)?);
+                let lhs = self.value(b, 1, size)?;
+                let mut rhs = self.value(b, 2, size)?;
+                if matches!(
+                    opcode,
+                    Opcode::BIC | Opcode::BICS | Opcode::ORN | Opcode::EON
+                ) {
+                    let inverted = b.temp(Expr::Unary(UnOp::Not, rhs));
+                    rhs = truncate(b, inverted, size);
+                }
+
+                let op = match opcode {
+                    Opcode::ORR | Opcode::ORN => BinOp::Or,
+                    Opcode::EOR | Opcode::EON => BinOp::Xor,
+                    _ => BinOp::And,
+                };
+
+                let result = b.binary(op, lhs, rhs);
+                let result = truncate(b, result, size);
+                if matches!(opcode, Opcode::ANDS | Opcode::BICS) {
+                    logic_flags(b, result, size);
+                }
+                self.write(b, 0, result)?;
+            }
+            Opcode::MADD | Opcode::MSUB => {
+                let size = bytes(self.size(0)?);
+                let lhs = self.value(b, 1, size)?;
+                let rhs = self.value(b, 2, size)?;
+                let acc = self.value(b, 3, size)?;
+                let product = b.binary(BinOp::Mul, lhs, rhs);
+                let op = match self.inst.opcode {
+                    Opcode::MADD => BinOp::Add,
+                    _ => BinOp::Sub,
+                };
+                let result = b.binary(op, acc, product);
+                let result = truncate(b, result, size);
+                self.write(b, 0, result)?;
+            }
+            Opcode::UDIV | Opcode::SDIV | Opcode::LSLV | Opcode::LSRV | Opcode::ASRV => {
+                let size = bytes(self.size(0)?);
+                let mut lhs = self.value(b, 1, size)?;
+                let mut rhs = self.value(b, 2, size)?;
+                let op = match self.inst.opcode {
+                    Opcode::UDIV => BinOp::UDiv,
+                    Opcode::SDIV => BinOp::SDiv,
+                    Opcode::LSLV => BinOp::Shl,
+                    Opcode::LSRV => BinOp::Shr,
+                    _ => BinOp::Sar,
+                };
+
+                if matches!(op, BinOp::SDiv | BinOp::Sar) {
+                    lhs = b.temp(Expr::sext(lhs, size));
+                }
+                if op == BinOp::SDiv {
+                    rhs = b.temp(Expr::sext(rhs, size));
+                }
+                // Shifts are by the amount modulo the register's width.
+                if matches!(op, BinOp::Shl | BinOp::Shr | BinOp::Sar) {
+                    rhs = b.binary(BinOp::And, rhs, Value::Const(size as u64 * 8 - 1));
+                }
+
+                let result = b.binary(op, lhs, rhs);
+                let result = truncate(b, result, size);
+                self.write(b, 0, result)?;
+            }
+            Opcode::RORV => {
+                let size = bytes(self.size(0)?);
+                let value = self.value(b, 1, size)?;
+                let amount = self.value(b, 2, size)?;
+                let amount = b.binary(BinOp::And, amount, Value::Const(size as u64 * 8 - 1));
+                let result = rotate(b, value, amount, size);
+                self.write(b, 0, result)?;
+            }
+            Opcode::UBFM | Opcode::SBFM => {
+                let size = bytes(self.size(0)?);
+                let (immr, imms) = match (self.operand(2), self.operand(3)) {
+                    (Operand::Immediate(immr), Operand::Immediate(imms)) => (immr, imms),
+                    _ => return None,
+                };
+
+                // The field's top bit is moved to the top, such that shifting it back right
+                // either zero or sign extends it.
+                let value = self.value(b, 1, size)?;
+                let top = Value::Const(63 - imms as u64);
+                let op = match self.inst.opcode {
+                    Opcode::UBFM => BinOp::Shr,
+                    _ => BinOp::Sar,
+                };
+
+                let at_top = b.binary(BinOp::Shl, value, top);
+                let result = if imms >= immr {
+                    b.binary(op, at_top, Value::Const(63 - imms as u64 + immr as u64))
+                } else {
+                    let field = b.binary(op, at_top, top);
+                    let amount = Value::Const(size as u64 * 8 - immr as u64);
+                    b.binary(BinOp::Shl, field, amount)
+                };
+
+                let result = truncate(b, result, size);
+                self.write(b, 0, result)?;
+            }
+            Opcode::CSEL | Opcode::CSINC | Opcode::CSINV | Opcode::CSNEG => {
+                let cc = match self.operand(3) {
+                    Operand::ConditionCode(cc) => cc,
+                    _ => return None,
+                };
+
+                let size = bytes(self.size(0)?);
+                let then = self.value(b, 1, size)?;
+                let otherwise = self.value(b, 2, size)?;
+                let otherwise = match self.inst.opcode {
+                    Opcode::CSINC => b.binary(BinOp::Add, otherwise, Value::Const(1)),
+                    Opcode::CSINV => b.temp(Expr::Unary(UnOp::Not, otherwise)),
+                    Opcode::CSNEG => b.temp(Expr::Unary(UnOp::Neg, otherwise)),
+                    _ => otherwise,
+                };
+                let otherwise = truncate(b, otherwise, size);
+
+                let cond = condition(b, cc);
+                let result = b.temp(Expr::Select {
+                    cond,
+                    then,
+                    otherwise,
+                });
+                self.write(b, 0, result)?;
+            }
+            Opcode::LDR
+            | Opcode::LDUR
+            | Opcode::LDAR
+            | Opcode::LDLAR
+            | Opcode::LDAXR
+            | Opcode::LDXR
+            | Opcode::LDTR => self.load(b, 1, None, false)?,
+            Opcode::LDRB
+            | Opcode::LDURB
+            | Opcode::LDARB
+            | Opcode::LDLARB
+            | Opcode::LDAXRB
+            | Opcode::LDXRB
+            | Opcode::LDTRB => self.load(b, 1, Some(1), false)?,
+            Opcode::LDRH
+            | Opcode::LDURH
+            | Opcode::LDARH
+            | Opcode::LDLARH
+            | Opcode::LDAXRH
+            | Opcode::LDXRH
+            | Opcode::LDTRH => self.load(b, 1, Some(2), false)?,
+            Opcode::LDRSB | Opcode::LDURSB | Opcode::LDTRSB => self.load(b, 1, Some(1), true)?,
+            Opcode::LDRSH | Opcode::LDURSH | Opcode::LDTRSH => self.load(b, 1, Some(2), true)?,
+            Opcode::LDRSW | Opcode::LDURSW | Opcode::LDTRSW => self.load(b, 1, Some(4), true)?,
+            Opcode::LDP | Opcode::LDNP => self.load(b, 2, None, false)?,
+            Opcode::LDPSW => self.load(b, 2, Some(4), true)?,
+            Opcode::STR | Opcode::STUR | Opcode::STLR | Opcode::STLLR | Opcode::STTR => {
+                self.store(b, 1, None)?
+            }
+            Opcode::STRB | Opcode::STURB | Opcode::STLRB | Opcode::STLLRB | Opcode::STTRB => {
+                self.store(b, 1, Some(1))?
+            }
+            Opcode::STRH | Opcode::STURH | Opcode::STLRH | Opcode::STLLRH | Opcode::STTRH => {
+                self.store(b, 1, Some(2))?
+            }
+            Opcode::STP | Opcode::STNP => self.store(b, 2, None)?,
+            Opcode::B => {
+                let target = self.value(b, 0, 8)?;
+                b.push(Stmt::Jump(target));
+            }
+            Opcode::Bcc(cc) => {
+                let cond = condition(b, cc);
+                let target = self.value(b, 0, 8)?;
+                b.push(Stmt::Branch { cond, target });
+            }
+            Opcode::CBZ | Opcode::CBNZ => {
+                let size = bytes(self.size(0)?);
+                let value = self.value(b, 0, size)?;
+                let op = match self.inst.opcode {
+                    Opcode::CBZ => BinOp::Eq,
+                    _ => BinOp::Ne,
+                };
+                let cond = b.binary(op, value, Value::Const(0));
+                let target = self.value(b, 1, 8)?;
+                b.push(Stmt::Branch { cond, target });
+            }
+            Opcode::TBZ | Opcode::TBNZ => {
+                let bit = match self.operand(1) {
+                    Operand::Imm16(bit) => bit as u32,
+                    _ => return None,
+                };
+
+                let value = self.value(b, 0, 8)?;
+                let set = b.bit(value, bit);
+                let cond = match self.inst.opcode {
+                    Opcode::TBZ => b.binary(BinOp::Eq, set, Value::Const(0)),
+                    _ => set,
+                };
+                let target = self.value(b, 2, 8)?;
+                b.push(Stmt::Branch { cond, target });
+            }
+            Opcode::BR => {
+                let target = self.value(b, 0, 8)?;
+                b.push(Stmt::Jump(target));
+            }
+            Opcode::BL | Opcode::BLR => {
+                // Copied as `blr x30` calls the link register from before the call.
+                let target = self.value(b, 0, 8)?;
+                let target = b.temp(Expr::Value(target));
+                b.assign(Var::Reg(LINK_REGISTER), Expr::Value(next));
+                b.push(Stmt::Call(target));
+            }
+            Opcode::RET => {
+                let target = match self.operand(0) {
+                    Operand::Nothing => Value::reg(LINK_REGISTER),
+                    _ => self.value(b, 0, 8)?,
+                };
+                b.push(Stmt::Return(target));
+            }
+            Opcode::SVC => b.push(Stmt::Syscall),
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+impl Lift for Instruction {
+    fn lift(&self, addr: usize, builder: &mut Builder) -> Option<()> {
+        let lifter = Lifter {
+            inst: self,
+            addr: addr as u64,
+        };
+
+        lifter.lift(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arm::armv8::a64::Decoder;
+    use decoder::{Decodable, Decoded, Reader};
+
+    fn lift(word: u32) -> Vec<String> {
+        let bytes = word.to_le_bytes();
+        let mut inst = Decoder::default().decode(&mut Reader::new(&bytes)).unwrap();
+        inst.update_rel_addrs(0x1000, None);
+        inst.stmts(0x1000).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn arithmetic() {
+        // add x0, x1, #0x10
+        assert_eq!(lift(0x91004020), ["t0 = x1 + 0x10", "x0 = t0"]);
+
+        // cmp w0, #0x1
+        assert_eq!(
+            lift(0x7100041f),
+            [
+                "t0 = zext4(x0)",
+                "cf = 0x1 <=u t0",
+                "t1 = t0 - 0x1",
+                "t2 = zext4(t1)",
+                "zf = t2 == 0x0",
+                "t3 = t2 >> 0x1f",
+                "sf = t3 & 0x1",
+                "t4 = t0 ^ 0x1",
+                "t5 = t0 ^ t2",
+                "t6 = t4 & t5",
+                "t7 = t6 >> 0x1f",
+                "of = t7 & 0x1",
+            ]
+        );
+
+        // mov x0, x1
+        assert_eq!(lift(0xaa0103e0), ["t0 = 0x0 | x1", "x0 = t0"]);
+    }
+
+    #[test]
+    fn memory() {
+        // stp x29, x30, [sp, #-0x10]!
+        assert_eq!(
+            lift(0xa9bf7bfd),
+            [
+                "t0 = sp + 0xfffffffffffffff0",
+                "store8(t0, x29)",
+                "t1 = t0 + 0x8",
+                "store8(t1, x30)",
+                "sp = t0",
+            ]
+        );
+
+        // ldr w0, [x1, #0x8]
+        assert_eq!(
+            lift(0xb9400820),
+            ["t0 = x1 + 0x8", "t1 = load4(t0)", "x0 = zext4(t1)"]
+        );
+    }
+
+    #[test]
+    fn control_flow() {
+        // b.ne $+0x10
+        assert_eq!(lift(0x54000081), ["t0 = zf ^ 0x1", "if t0 jump 0x1010"]);
+
+        // bl $+0x100
+        assert_eq!(lift(0x94000040), ["t0 = 0x1100", "x30 = 0x1004", "call t0"]);
+
+        // ret
+        assert_eq!(lift(0xd65f03c0), ["return x30"]);
+    }
+}
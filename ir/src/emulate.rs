@@ -0,0 +1,278 @@
+//! Interpreter of lifted instructions, used to resolve values an analysis can't, e.g. obfuscated
+//! constants or computed jump targets.
+//!
+//! Values that aren't known, like registers that weren't given an initial value, are tracked as
+//! such. Anything computed from them is unknown as well, instead of being guessed.
+
+use crate::{Expr, Flag, Stmt, UnOp, Value, Var};
+use std::collections::HashMap;
+
+/// Memory read when the emulation hasn't written to an address itself, e.g. a binary's sections.
+pub trait Memory {
+    fn byte(&self, addr: u64) -> Option<u8>;
+}
+
+impl Memory for () {
+    fn byte(&self, _: u64) -> Option<u8> {
+        None
+    }
+}
+
+/// How emulation continues after an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Continues with the instruction at the address.
+    Continue(u64),
+    /// The instruction couldn't be lifted.
+    Unknown,
+    /// The condition of a branch or the target of a jump isn't known.
+    Unresolved,
+    /// System calls aren't emulated.
+    Syscall,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Emulator {
+    registers: HashMap<String, u64>,
+    flags: HashMap<Flag, u64>,
+    /// Bytes written, where `None` is an unknown value.
+    written: HashMap<u64, Option<u8>>,
+}
+
+impl Emulator {
+    pub fn set(&mut self, reg: &str, value: u64) {
+        match self.registers.get_mut(reg) {
+            Some(current) => *current = value,
+            None => {
+                self.registers.insert(reg.to_string(), value);
+            }
+        }
+    }
+
+    pub fn register(&self, reg: &str) -> Option<u64> {
+        self.registers.get(reg).copied()
+    }
+
+    /// Registers with a known value.
+    pub fn registers(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.registers.iter().map(|(reg, value)| (reg.as_str(), *value))
+    }
+
+    pub fn write(&mut self, addr: u64, bytes: &[u8]) {
+        for (offset, byte) in bytes.iter().enumerate() {
+            self.written.insert(addr.wrapping_add(offset as u64), Some(*byte));
+        }
+    }
+
+    /// Addresses of the bytes written, whether the values written are known or not.
+    pub fn written(&self) -> impl Iterator<Item = u64> + '_ {
+        self.written.keys().copied()
+    }
+
+    /// Little endian value of `size` bytes at `addr`.
+    pub fn read(&self, memory: &impl Memory, addr: u64, size: u8) -> Option<u64> {
+        let mut value = 0;
+        for offset in (0..size as u64).rev() {
+            let addr = addr.wrapping_add(offset);
+            let byte = match self.written.get(&addr) {
+                Some(byte) => *byte,
+                None => memory.byte(addr),
+            };
+            value = value << 8 | byte? as u64;
+        }
+
+        Some(value)
+    }
+
+    fn store(&mut self, addr: u64, value: Option<u64>, size: u8) {
+        for offset in 0..size as u64 {
+            let byte = value.map(|value| (value >> (offset * 8)) as u8);
+            self.written.insert(addr.wrapping_add(offset), byte);
+        }
+    }
+
+    fn var(&self, temps: &HashMap<u32, u64>, var: Var) -> Option<u64> {
+        match var {
+            Var::Reg(reg) => self.register(reg),
+            Var::Temp(idx) => temps.get(&idx).copied(),
+            Var::Flag(flag) => self.flags.get(&flag).copied(),
+        }
+    }
+
+    fn value(&self, temps: &HashMap<u32, u64>, value: Value) -> Option<u64> {
+        match value {
+            Value::Var(var) => self.var(temps, var),
+            Value::Const(value) => Some(value),
+        }
+    }
+
+    fn assign(&mut self, temps: &mut HashMap<u32, u64>, var: Var, value: Option<u64>) {
+        match (var, value) {
+            (Var::Reg(reg), Some(value)) => self.set(reg, value),
+            (Var::Reg(reg), None) => {
+                self.registers.remove(reg);
+            }
+            (Var::Temp(idx), Some(value)) => {
+                temps.insert(idx, value);
+            }
+            (Var::Temp(idx), None) => {
+                temps.remove(&idx);
+            }
+            (Var::Flag(flag), Some(value)) => {
+                self.flags.insert(flag, value);
+            }
+            (Var::Flag(flag), None) => {
+                self.flags.remove(&flag);
+            }
+        }
+    }
+
+    fn eval(&self, memory: &impl Memory, temps: &HashMap<u32, u64>, expr: &Expr) -> Option<u64> {
+        let value = |value| self.value(temps, value);
+
+        match *expr {
+            Expr::Value(v) => value(v),
+            Expr::Load(addr, size) => self.read(memory, value(addr)?, size),
+            Expr::Unary(UnOp::Not, v) => value(v).map(|v| !v),
+            Expr::Unary(UnOp::Neg, v) => value(v).map(u64::wrapping_neg),
+            Expr::Binary(op, lhs, rhs) => Some(op.apply(value(lhs)?, value(rhs)?)),
+            Expr::Extend {
+                value: v,
+                size,
+                signed,
+            } => {
+                let v = value(v)?;
+                let unused = 64u32.saturating_sub(size as u32 * 8);
+                if unused == 0 {
+                    Some(v)
+                } else if signed {
+                    Some((((v << unused) as i64) >> unused) as u64)
+                } else {
+                    Some((v << unused) >> unused)
+                }
+            }
+            Expr::Select {
+                cond,
+                then,
+                otherwise,
+            } => match value(cond)? {
+                0 => value(otherwise),
+                _ => value(then),
+            },
+        }
+    }
+
+    /// Emulate the statements of the instruction at `addr` that's `width` bytes long.
+    ///
+    /// Stores to an unknown address are skipped, so memory may be assumed to hold values the
+    /// instruction overwrote.
+    pub fn step(&mut self, memory: &impl Memory, addr: u64, width: u64, stmts: &[Stmt]) -> Flow {
+        let mut temps = HashMap::new();
+
+        for stmt in stmts {
+            match *stmt {
+                Stmt::Assign(var, ref expr) => {
+                    let result = self.eval(memory, &temps, expr);
+                    self.assign(&mut temps, var, result);
+                }
+                Stmt::Store {
+                    addr,
+                    value: v,
+                    size,
+                } => {
+                    if let Some(addr) = self.value(&temps, addr) {
+                        let v = self.value(&temps, v);
+                        self.store(addr, v, size);
+                    }
+                }
+                Stmt::Jump(target) | Stmt::Call(target) | Stmt::Return(target) => {
+                    return match self.value(&temps, target) {
+                        Some(target) => Flow::Continue(target),
+                        None => Flow::Unresolved,
+                    };
+                }
+                Stmt::Branch { cond, target } => match self.value(&temps, cond) {
+                    Some(0) => {}
+                    Some(_) => match self.value(&temps, target) {
+                        Some(target) => return Flow::Continue(target),
+                        None => return Flow::Unresolved,
+                    },
+                    None => return Flow::Unresolved,
+                },
+                Stmt::Syscall => return Flow::Syscall,
+                Stmt::Unknown => return Flow::Unknown,
+            }
+        }
+
+        Flow::Continue(addr.wrapping_add(width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BinOp, Builder};
+
+    struct Bytes(u64, Vec<u8>);
+
+    impl Memory for Bytes {
+        fn byte(&self, addr: u64) -> Option<u8> {
+            let offset = addr.checked_sub(self.0)?;
+            self.1.get(offset as usize).copied()
+        }
+    }
+
+    #[test]
+    fn values() {
+        let memory = Bytes(0x2000, vec![0x10, 0x20, 0x30, 0x40]);
+        let mut emu = Emulator::default();
+        emu.set("rdi", 0x2000);
+
+        // rax = sext2(load2(rdi + 2)) + rsi
+        let mut b = Builder::default();
+        let addr = b.binary(BinOp::Add, Value::reg("rdi"), Value::Const(2));
+        let loaded = b.temp(Expr::Load(addr, 2));
+        let extended = b.temp(Expr::sext(loaded, 2));
+        b.assign(Var::Reg("rcx"), Expr::Value(extended));
+        let sum = b.binary(BinOp::Add, extended, Value::reg("rsi"));
+        b.assign(Var::Reg("rax"), Expr::Value(sum));
+
+        assert_eq!(emu.step(&memory, 0x1000, 4, &b.stmts), Flow::Continue(0x1004));
+        assert_eq!(emu.register("rcx"), Some(0x4030));
+        assert_eq!(emu.register("rax"), None);
+
+        // Stores are read back over the memory they overwrite.
+        emu.write(0x2001, &[0xff]);
+        assert_eq!(emu.read(&memory, 0x2000, 4), Some(0x4030ff10));
+        emu.store(0x2000, None, 1);
+        assert_eq!(emu.read(&memory, 0x2000, 4), None);
+
+        let mut written: Vec<u64> = emu.written().collect();
+        written.sort_unstable();
+        assert_eq!(written, [0x2000, 0x2001]);
+    }
+
+    #[test]
+    fn control_flow() {
+        let mut emu = Emulator::default();
+        emu.set("rax", 3);
+
+        // if rax == 3 jump 0x2000
+        let mut b = Builder::default();
+        let cond = b.binary(BinOp::Eq, Value::reg("rax"), Value::Const(3));
+        b.push(Stmt::Branch {
+            cond,
+            target: Value::Const(0x2000),
+        });
+        assert_eq!(emu.step(&(), 0x1000, 2, &b.stmts), Flow::Continue(0x2000));
+
+        emu.set("rax", 4);
+        assert_eq!(emu.step(&(), 0x1000, 2, &b.stmts), Flow::Continue(0x1002));
+
+        // jump rbx
+        let jump = [Stmt::Jump(Value::reg("rbx"))];
+        assert_eq!(emu.step(&(), 0x1000, 2, &jump), Flow::Unresolved);
+        emu.set("rbx", 0x3000);
+        assert_eq!(emu.step(&(), 0x1000, 2, &jump), Flow::Continue(0x3000));
+    }
+}
@@ -0,0 +1,505 @@
+//! Architecture independent intermediate representation of instructions, such that analyses
+//! like constant propagation, taint tracking or emulation are written once for every
+//! architecture with a lifter.
+//!
+//! Each instruction is lifted into statements operating on 64-bit values. Registers are always
+//! written in full, e.g. writing `eax` zero extends into `rax` while writing `al` merges into it.
+//! Temporaries are only used within the instruction they're assigned in and are assigned once,
+//! so an instruction's statements are in SSA form while registers, flags and memory aren't.
+//!
+//! ```text
+//! add eax, dword [rdi + 0x8]
+//!
+//! t0 = zext4(rax)
+//! t1 = rdi + 0x8
+//! t2 = load4(t1)
+//! t3 = t0 + t2
+//! t4 = zext4(t3)
+//! ...
+//! rax = zext4(t4)
+//! ```
+//!
+//! x86_64 and AArch64 are lifted, instructions that aren't understood are lifted as
+//! [`Stmt::Unknown`].
+
+mod a64;
+pub mod emulate;
+mod x64;
+
+use std::fmt;
+
+/// Status flag, set by comparisons and arithmetic to either zero or one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Flag {
+    Zero,
+    /// Top bit of the result.
+    Sign,
+    /// Carry out of unsigned arithmetic. Subtracting sets it when borrowing on x86_64 and when
+    /// not borrowing on AArch64, which the conditions lifted from branches account for.
+    Carry,
+    /// Signed overflow.
+    Overflow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Var {
+    /// Full width register, e.g. `rax`, `x0` or `fs_base`.
+    Reg(&'static str),
+    /// Value local to the instruction it's assigned in.
+    Temp(u32),
+    Flag(Flag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Value {
+    Var(Var),
+    Const(u64),
+}
+
+impl Value {
+    pub fn reg(name: &'static str) -> Self {
+        Self::Var(Var::Reg(name))
+    }
+
+    pub fn flag(flag: Flag) -> Self {
+        Self::Var(Var::Flag(flag))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnOp {
+    Not,
+    Neg,
+}
+
+/// Operation on two 64-bit values. Comparisons result in either zero or one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    /// Unsigned division, where dividing by zero gives zero.
+    UDiv,
+    /// Signed division, where dividing by zero gives zero.
+    SDiv,
+    And,
+    Or,
+    Xor,
+    /// Shift left, by amounts of 64 or more giving zero.
+    Shl,
+    /// Logical shift right, by amounts of 64 or more giving zero.
+    Shr,
+    /// Arithmetic shift right, by amounts of 64 or more filling the result with the sign.
+    Sar,
+    Eq,
+    Ne,
+    /// Unsigned less than.
+    Ult,
+    /// Unsigned less than or equal.
+    Ule,
+    /// Signed less than.
+    Slt,
+    /// Signed less than or equal.
+    Sle,
+}
+
+impl BinOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::UDiv => "/u",
+            Self::SDiv => "/s",
+            Self::And => "&",
+            Self::Or => "|",
+            Self::Xor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Sar => ">>s",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Ult => "<u",
+            Self::Ule => "<=u",
+            Self::Slt => "<s",
+            Self::Sle => "<=s",
+        }
+    }
+
+    /// Result of the operation on `lhs` and `rhs`.
+    pub fn apply(self, lhs: u64, rhs: u64) -> u64 {
+        let shift = |rhs: u64| u32::try_from(rhs).ok().filter(|&rhs| rhs < 64);
+        match self {
+            Self::Add => lhs.wrapping_add(rhs),
+            Self::Sub => lhs.wrapping_sub(rhs),
+            Self::Mul => lhs.wrapping_mul(rhs),
+            Self::UDiv => lhs.checked_div(rhs).unwrap_or(0),
+            Self::SDiv => (lhs as i64).checked_div(rhs as i64).unwrap_or(0) as u64,
+            Self::And => lhs & rhs,
+            Self::Or => lhs | rhs,
+            Self::Xor => lhs ^ rhs,
+            Self::Shl => shift(rhs).map_or(0, |rhs| lhs << rhs),
+            Self::Shr => shift(rhs).map_or(0, |rhs| lhs >> rhs),
+            Self::Sar => ((lhs as i64) >> shift(rhs).unwrap_or(63)) as u64,
+            Self::Eq => (lhs == rhs) as u64,
+            Self::Ne => (lhs != rhs) as u64,
+            Self::Ult => (lhs < rhs) as u64,
+            Self::Ule => (lhs <= rhs) as u64,
+            Self::Slt => ((lhs as i64) < (rhs as i64)) as u64,
+            Self::Sle => ((lhs as i64) <= (rhs as i64)) as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Value(Value),
+    /// Little endian load of `size` bytes, zero extended.
+    Load(Value, u8),
+    Unary(UnOp, Value),
+    Binary(BinOp, Value, Value),
+    /// Low `size` bytes of a value, extended to 64 bits.
+    Extend {
+        value: Value,
+        size: u8,
+        signed: bool,
+    },
+    /// `then` if `cond` isn't zero, otherwise `otherwise`.
+    Select {
+        cond: Value,
+        then: Value,
+        otherwise: Value,
+    },
+}
+
+impl Expr {
+    /// Low `size` bytes of `value`, zero extended.
+    pub fn zext(value: Value, size: u8) -> Self {
+        Self::Extend {
+            value,
+            size,
+            signed: false,
+        }
+    }
+
+    /// Low `size` bytes of `value`, sign extended.
+    pub fn sext(value: Value, size: u8) -> Self {
+        Self::Extend {
+            value,
+            size,
+            signed: true,
+        }
+    }
+
+    /// Variables the expression reads.
+    pub fn vars(&self) -> impl Iterator<Item = Var> {
+        let values = match *self {
+            Self::Value(value)
+            | Self::Load(value, _)
+            | Self::Unary(_, value)
+            | Self::Extend { value, .. } => [Some(value), None, None],
+            Self::Binary(_, lhs, rhs) => [Some(lhs), Some(rhs), None],
+            Self::Select {
+                cond,
+                then,
+                otherwise,
+            } => [Some(cond), Some(then), Some(otherwise)],
+        };
+
+        values.into_iter().flatten().filter_map(|value| match value {
+            Value::Var(var) => Some(var),
+            Value::Const(_) => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    Assign(Var, Expr),
+    /// Little endian store of the low `size` bytes of `value`.
+    Store {
+        addr: Value,
+        value: Value,
+        size: u8,
+    },
+    Jump(Value),
+    /// Jump to `target` if `cond` isn't zero, otherwise continue with the next statement.
+    Branch {
+        cond: Value,
+        target: Value,
+    },
+    /// Call returning to the next instruction, which the statements before it already saved
+    /// where the architecture keeps it.
+    Call(Value),
+    /// Return to the address given, which the statements before it already popped or read.
+    Return(Value),
+    /// System call or software interrupt.
+    Syscall,
+    /// Instruction that isn't lifted, so any register, flag or memory could be changed.
+    Unknown,
+}
+
+impl fmt::Display for Flag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Zero => "zf",
+            Self::Sign => "sf",
+            Self::Carry => "cf",
+            Self::Overflow => "of",
+        })
+    }
+}
+
+impl fmt::Display for Var {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reg(name) => f.write_str(name),
+            Self::Temp(idx) => write!(f, "t{idx}"),
+            Self::Flag(flag) => flag.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Var(var) => var.fmt(f),
+            Self::Const(value) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Value(value) => value.fmt(f),
+            Self::Load(addr, size) => write!(f, "load{size}({addr})"),
+            Self::Unary(UnOp::Not, value) => write!(f, "~{value}"),
+            Self::Unary(UnOp::Neg, value) => write!(f, "-{value}"),
+            Self::Binary(op, lhs, rhs) => write!(f, "{lhs} {} {rhs}", op.symbol()),
+            Self::Extend {
+                value,
+                size,
+                signed,
+            } => {
+                let kind = if *signed { "sext" } else { "zext" };
+                write!(f, "{kind}{size}({value})")
+            }
+            Self::Select {
+                cond,
+                then,
+                otherwise,
+            } => write!(f, "{cond} ? {then} : {otherwise}"),
+        }
+    }
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Assign(var, expr) => write!(f, "{var} = {expr}"),
+            Self::Store { addr, value, size } => write!(f, "store{size}({addr}, {value})"),
+            Self::Jump(target) => write!(f, "jump {target}"),
+            Self::Branch { cond, target } => write!(f, "if {cond} jump {target}"),
+            Self::Call(target) => write!(f, "call {target}"),
+            Self::Return(target) => write!(f, "return {target}"),
+            Self::Syscall => f.write_str("syscall"),
+            Self::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// Statements of an instruction being lifted.
+#[derive(Debug, Default)]
+pub struct Builder {
+    stmts: Vec<Stmt>,
+    temps: u32,
+}
+
+impl Builder {
+    /// Value of `expr`, assigned to a new temporary.
+    pub fn temp(&mut self, expr: Expr) -> Value {
+        let temp = Var::Temp(self.temps);
+        self.temps += 1;
+        self.stmts.push(Stmt::Assign(temp, expr));
+        Value::Var(temp)
+    }
+
+    pub fn assign(&mut self, var: Var, expr: Expr) {
+        self.stmts.push(Stmt::Assign(var, expr));
+    }
+
+    pub fn push(&mut self, stmt: Stmt) {
+        self.stmts.push(stmt);
+    }
+
+    /// `lhs op rhs`, assigned to a new temporary unless both are constant.
+    pub fn binary(&mut self, op: BinOp, lhs: Value, rhs: Value) -> Value {
+        match (lhs, rhs) {
+            (Value::Const(lhs), Value::Const(rhs)) => Value::Const(op.apply(lhs, rhs)),
+            _ => self.temp(Expr::Binary(op, lhs, rhs)),
+        }
+    }
+
+    /// Whether `value` is zero, assigned to a new temporary.
+    pub fn is_zero(&mut self, value: Value) -> Value {
+        self.binary(BinOp::Eq, value, Value::Const(0))
+    }
+
+    /// Bit `bit` of `value`, assigned to a new temporary.
+    pub fn bit(&mut self, value: Value, bit: u32) -> Value {
+        let shifted = self.binary(BinOp::Shr, value, Value::Const(bit as u64));
+        self.binary(BinOp::And, shifted, Value::Const(1))
+    }
+}
+
+/// Low `size` bytes of `value`.
+pub(crate) fn truncate(b: &mut Builder, value: Value, size: u8) -> Value {
+    match value {
+        _ if size >= 8 => value,
+        Value::Const(value) => Value::Const(value & ((1 << (size * 8)) - 1)),
+        value => b.temp(Expr::zext(value, size)),
+    }
+}
+
+/// Top bit of `value` of `size` bytes.
+fn sign(b: &mut Builder, value: Value, size: u8) -> Expr {
+    let shifted = b.binary(BinOp::Shr, value, Value::Const(size as u64 * 8 - 1));
+    Expr::Binary(BinOp::And, shifted, Value::Const(1))
+}
+
+/// Sets the zero and sign flag by `result` of `size` bytes.
+pub(crate) fn result_flags(b: &mut Builder, result: Value, size: u8) {
+    b.assign(
+        Var::Flag(Flag::Zero),
+        Expr::Binary(BinOp::Eq, result, Value::Const(0)),
+    );
+    let sign = sign(b, result, size);
+    b.assign(Var::Flag(Flag::Sign), sign);
+}
+
+/// Sum of `lhs` and `rhs` of `size` bytes, setting all flags but the carry by it.
+pub(crate) fn add(b: &mut Builder, lhs: Value, rhs: Value, size: u8) -> Value {
+    let sum = b.binary(BinOp::Add, lhs, rhs);
+    let sum = truncate(b, sum, size);
+    result_flags(b, sum, size);
+
+    // The operands have the same sign which differs from the sum's.
+    let lhs_changed = b.binary(BinOp::Xor, lhs, sum);
+    let rhs_changed = b.binary(BinOp::Xor, rhs, sum);
+    let changed = b.binary(BinOp::And, lhs_changed, rhs_changed);
+    let overflow = sign(b, changed, size);
+    b.assign(Var::Flag(Flag::Overflow), overflow);
+    sum
+}
+
+/// Difference of `lhs` and `rhs` of `size` bytes, setting all flags but the carry by it.
+pub(crate) fn sub(b: &mut Builder, lhs: Value, rhs: Value, size: u8) -> Value {
+    let diff = b.binary(BinOp::Sub, lhs, rhs);
+    let diff = truncate(b, diff, size);
+    result_flags(b, diff, size);
+
+    // The operands have different signs and the difference's differs from `lhs`.
+    let differ = b.binary(BinOp::Xor, lhs, rhs);
+    let changed = b.binary(BinOp::Xor, lhs, diff);
+    let both = b.binary(BinOp::And, differ, changed);
+    let overflow = sign(b, both, size);
+    b.assign(Var::Flag(Flag::Overflow), overflow);
+    diff
+}
+
+/// Instructions that can be lifted into the IR.
+pub trait Lift {
+    /// Lift the instruction at `addr` into `builder`, returns None if it isn't understood.
+    fn lift(&self, addr: usize, builder: &mut Builder) -> Option<()>;
+
+    /// Statements of the instruction at `addr`, a single [`Stmt::Unknown`] if it isn't
+    /// understood.
+    fn stmts(&self, addr: usize) -> Vec<Stmt> {
+        let mut builder = Builder::default();
+        match self.lift(addr, &mut builder) {
+            Some(()) => builder.stmts,
+            None => vec![Stmt::Unknown],
+        }
+    }
+}
+
+/// Statements of instructions lifted one at a time, together with the address of the
+/// instruction each is lifted from.
+pub struct Lifted<T> {
+    insts: T,
+    addr: usize,
+    stmts: std::vec::IntoIter<Stmt>,
+}
+
+impl<'a, I: Lift + 'a, T: Iterator<Item = (usize, &'a I)>> Iterator for Lifted<T> {
+    type Item = (usize, Stmt);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(stmt) = self.stmts.next() {
+                return Some((self.addr, stmt));
+            }
+
+            let (addr, inst) = self.insts.next()?;
+            self.addr = addr;
+            self.stmts = inst.stmts(addr).into_iter();
+        }
+    }
+}
+
+/// Lift `insts`, each with the address it's at, as they're iterated.
+pub fn lift<'a, I, T>(insts: T) -> Lifted<T::IntoIter>
+where
+    I: Lift + 'a,
+    T: IntoIterator<Item = (usize, &'a I)>,
+{
+    Lifted {
+        insts: insts.into_iter(),
+        addr: 0,
+        stmts: Vec::new().into_iter(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn operations() {
+        assert_eq!(BinOp::Sub.apply(1, 2), u64::MAX);
+        assert_eq!(BinOp::UDiv.apply(7, 0), 0);
+        assert_eq!(BinOp::SDiv.apply(-8i64 as u64, 2), -4i64 as u64);
+        assert_eq!(BinOp::Shl.apply(1, 64), 0);
+        assert_eq!(BinOp::Sar.apply(1 << 63, 100), u64::MAX);
+        assert_eq!(BinOp::Slt.apply(u64::MAX, 0), 1);
+        assert_eq!(BinOp::Ult.apply(u64::MAX, 0), 0);
+    }
+
+    #[test]
+    fn display() {
+        let mut builder = Builder::default();
+        let rdi = Value::reg("rdi");
+        let addr = builder.binary(BinOp::Add, rdi, Value::Const(8));
+        let loaded = builder.temp(Expr::Load(addr, 4));
+        builder.assign(Var::Reg("rax"), Expr::sext(loaded, 4));
+        builder.push(Stmt::Branch {
+            cond: Value::flag(Flag::Zero),
+            target: Value::Const(0x1000),
+        });
+
+        let text: Vec<String> = builder.stmts.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            text,
+            [
+                "t0 = rdi + 0x8",
+                "t1 = load4(t0)",
+                "rax = sext4(t1)",
+                "if zf jump 0x1000"
+            ]
+        );
+
+        let vars: Vec<Var> = Expr::Binary(BinOp::Add, rdi, Value::Const(1)).vars().collect();
+        assert_eq!(vars, [Var::Reg("rdi")]);
+    }
+}
@@ -0,0 +1,585 @@
+//! Lifting of x86_64 instructions.
+//!
+//! Parity and the carry and overflow of shifts and multiplications aren't lifted, so those
+//! instructions leave them as they were.
+
+use crate::{add, result_flags, sub, truncate};
+use crate::{BinOp, Builder, Expr, Flag, Lift, Stmt, UnOp, Value, Var};
+use decoder::Decoded;
+use x86_64::long_mode::{
+    register_class, ConditionCode, Instruction, Opcode, Operand, RegSpec, Segment,
+};
+
+const STACK_POINTER: &str = "rsp";
+
+/// 64-bit register `reg` is part of and the byte it starts at within it, None if it isn't a
+/// general purpose register.
+fn gpr(reg: RegSpec) -> Option<(&'static str, u8)> {
+    let class = reg.class();
+    if class == register_class::Q
+        || class == register_class::D
+        || class == register_class::W
+        || class == register_class::RB
+    {
+        Some((RegSpec::q(reg.num()).name(), 0))
+    } else if class == register_class::B && reg.num() >= 4 {
+        // ah through bh are the second byte of rax through rbx.
+        Some((RegSpec::q(reg.num() - 4).name(), 1))
+    } else if class == register_class::B {
+        Some((RegSpec::q(reg.num()).name(), 0))
+    } else {
+        None
+    }
+}
+
+/// Sets the flags by the result of a bitwise operation.
+fn logic_flags(b: &mut Builder, result: Value, size: u8) {
+    result_flags(b, result, size);
+    b.assign(Var::Flag(Flag::Carry), Expr::Value(Value::Const(0)));
+    b.assign(Var::Flag(Flag::Overflow), Expr::Value(Value::Const(0)));
+}
+
+/// Whether `cc` holds, None for conditions on the parity flag.
+fn condition(b: &mut Builder, cc: ConditionCode) -> Option<Value> {
+    let zero = Value::flag(Flag::Zero);
+    let sign = Value::flag(Flag::Sign);
+    let carry = Value::flag(Flag::Carry);
+    let overflow = Value::flag(Flag::Overflow);
+    let not = |b: &mut Builder, value: Value| b.binary(BinOp::Xor, value, Value::Const(1));
+
+    Some(match cc {
+        ConditionCode::O => overflow,
+        ConditionCode::NO => not(b, overflow),
+        ConditionCode::B => carry,
+        ConditionCode::AE => not(b, carry),
+        ConditionCode::Z => zero,
+        ConditionCode::NZ => not(b, zero),
+        ConditionCode::A => {
+            let below_or_equal = b.binary(BinOp::Or, carry, zero);
+            not(b, below_or_equal)
+        }
+        ConditionCode::BE => b.binary(BinOp::Or, carry, zero),
+        ConditionCode::S => sign,
+        ConditionCode::NS => not(b, sign),
+        ConditionCode::P | ConditionCode::NP => return None,
+        ConditionCode::L => b.binary(BinOp::Ne, sign, overflow),
+        ConditionCode::GE => b.binary(BinOp::Eq, sign, overflow),
+        ConditionCode::G => {
+            let less = b.binary(BinOp::Ne, sign, overflow);
+            let less_or_equal = b.binary(BinOp::Or, zero, less);
+            not(b, less_or_equal)
+        }
+        ConditionCode::LE => {
+            let less = b.binary(BinOp::Ne, sign, overflow);
+            b.binary(BinOp::Or, zero, less)
+        }
+    })
+}
+
+/// Value of the register `reg`, zero extended.
+fn read_reg(b: &mut Builder, reg: RegSpec) -> Option<Value> {
+    let (name, offset) = gpr(reg)?;
+    let full = Value::reg(name);
+    Some(match (reg.width(), offset) {
+        (8, _) => full,
+        (size, 0) => b.temp(Expr::zext(full, size)),
+        _ => {
+            let high = b.binary(BinOp::Shr, full, Value::Const(8));
+            b.temp(Expr::zext(high, 1))
+        }
+    })
+}
+
+/// Write `value` to the register `reg`, where 32-bit registers are zero extended and smaller
+/// ones keep the rest of the register.
+fn write_reg(b: &mut Builder, reg: RegSpec, value: Value) -> Option<()> {
+    let (name, offset) = gpr(reg)?;
+    let var = Var::Reg(name);
+    match reg.width() {
+        8 => b.assign(var, Expr::Value(value)),
+        4 => b.assign(var, Expr::zext(value, 4)),
+        size => {
+            let shift = offset as u64 * 8;
+            let mask = ((1u64 << (size * 8)) - 1) << shift;
+            let kept = b.binary(BinOp::And, Value::reg(name), Value::Const(!mask));
+            let mut low = truncate(b, value, size);
+            if shift != 0 {
+                low = b.binary(BinOp::Shl, low, Value::Const(shift));
+            }
+            b.assign(var, Expr::Binary(BinOp::Or, kept, low));
+        }
+    }
+
+    Some(())
+}
+
+/// Where an operand is read from and written to.
+#[derive(Clone, Copy)]
+enum Place {
+    Reg(RegSpec),
+    /// Memory at an address of some size.
+    Memory(Value, u8),
+}
+
+struct Lifter<'a> {
+    inst: &'a Instruction,
+    addr: usize,
+    /// Address of the next instruction, which is what `rip` reads as.
+    next: u64,
+}
+
+impl Lifter<'_> {
+    /// Size of the operand at `idx` in bytes, None for immediates.
+    fn size(&self, idx: u8) -> Option<u8> {
+        match self.inst.operand(idx) {
+            Operand::Register(reg) => Some(reg.width()),
+            op if op.is_memory() => self.inst.mem_size()?.bytes_size(),
+            _ => None,
+        }
+    }
+
+    /// Immediate operand at `idx` as it's encoded, sign extended to 64 bits.
+    fn immediate(&self, idx: u8) -> Option<u64> {
+        let imm = self.inst.encoded_imm(self.addr);
+        Some(match self.inst.operand(idx) {
+            Operand::ImmediateI8(_) => imm as i8 as i64 as u64,
+            Operand::ImmediateU8(_) => imm as u8 as u64,
+            Operand::ImmediateI16(_) => imm as i16 as i64 as u64,
+            Operand::ImmediateU16(_) => imm as u16 as u64,
+            Operand::ImmediateI32(_) => imm as i32 as i64 as u64,
+            Operand::ImmediateU32(_) => imm as u32 as u64,
+            Operand::ImmediateI64(_) | Operand::ImmediateU64(_) => imm,
+            _ => return None,
+        })
+    }
+
+    /// Address the memory operand at `idx` refers to.
+    fn address(&self, b: &mut Builder, idx: u8) -> Option<Value> {
+        let (base, index, scale, disp) = match self.inst.operand(idx) {
+            Operand::DisplacementU32(disp) => (None, None, 1, disp as u64),
+            Operand::DisplacementU64(disp) => (None, None, 1, disp),
+            Operand::RegDeref(base) => (Some(base), None, 1, 0),
+            Operand::RegDisp(base, disp) => (Some(base), None, 1, disp as i64 as u64),
+            Operand::RegScale(index, scale) => (None, Some(index), scale, 0),
+            Operand::RegScaleDisp(index, scale, disp) => {
+                (None, Some(index), scale, disp as i64 as u64)
+            }
+            Operand::RegIndexBase(base, index) => (Some(base), Some(index), 1, 0),
+            Operand::RegIndexBaseDisp(base, index, disp) => {
+                (Some(base), Some(index), 1, disp as i64 as u64)
+            }
+            Operand::RegIndexBaseScale(base, index, scale) => (Some(base), Some(index), scale, 0),
+            Operand::RegIndexBaseScaleDisp(base, index, scale, disp) => {
+                (Some(base), Some(index), scale, disp as i64 as u64)
+            }
+            _ => return None,
+        };
+
+        let mut addr = Value::Const(disp);
+        if let Some(index) = index {
+            let index = self.reg(b, index)?;
+            let index = b.binary(BinOp::Mul, index, Value::Const(scale as u64));
+            addr = b.binary(BinOp::Add, index, addr);
+        }
+
+        if let Some(base) = base {
+            let base = self.reg(b, base)?;
+            addr = b.binary(BinOp::Add, base, addr);
+        }
+
+        // Only the fs and gs segments have a base, which is used for thread local storage.
+        match self.inst.segment_override_for_op(idx) {
+            Some(Segment::FS) => addr = b.binary(BinOp::Add, Value::reg("fs_base"), addr),
+            Some(Segment::GS) => addr = b.binary(BinOp::Add, Value::reg("gs_base"), addr),
+            _ => {}
+        }
+
+        Some(addr)
+    }
+
+    /// Value of the register `reg` used in an address.
+    fn reg(&self, b: &mut Builder, reg: RegSpec) -> Option<Value> {
+        match reg {
+            RegSpec::RIP => Some(Value::Const(self.next)),
+            reg => read_reg(b, reg),
+        }
+    }
+
+    fn place(&self, b: &mut Builder, idx: u8) -> Option<Place> {
+        match self.inst.operand(idx) {
+            Operand::Register(reg) => Some(Place::Reg(reg)),
+            op if op.is_memory() => {
+                let addr = self.address(b, idx)?;
+                Some(Place::Memory(addr, self.size(idx)?))
+            }
+            _ => None,
+        }
+    }
+
+    fn read(&self, b: &mut Builder, place: Place) -> Option<Value> {
+        match place {
+            Place::Reg(reg) => read_reg(b, reg),
+            Place::Memory(addr, size) => Some(b.temp(Expr::Load(addr, size))),
+        }
+    }
+
+    fn write(&self, b: &mut Builder, place: Place, value: Value) -> Option<()> {
+        match place {
+            Place::Reg(reg) => write_reg(b, reg, value),
+            Place::Memory(addr, size) => {
+                b.push(Stmt::Store { addr, value, size });
+                Some(())
+            }
+        }
+    }
+
+    /// Value of the operand at `idx`, where immediates are truncated to `size` bytes.
+    fn value(&self, b: &mut Builder, idx: u8, size: u8) -> Option<Value> {
+        if let Some(imm) = self.immediate(idx) {
+            return Some(truncate(b, Value::Const(imm), size));
+        }
+
+        let place = self.place(b, idx)?;
+        self.read(b, place)
+    }
+
+    /// Where the jump or call with it's target at operand `idx` goes to.
+    fn target(&self, b: &mut Builder, idx: u8) -> Option<Value> {
+        match self.immediate(idx) {
+            Some(offset) => Some(Value::Const(self.next.wrapping_add(offset))),
+            None => self.value(b, idx, 8),
+        }
+    }
+
+    fn push(&self, b: &mut Builder, value: Value) {
+        let rsp = Value::reg(STACK_POINTER);
+        b.assign(
+            Var::Reg(STACK_POINTER),
+            Expr::Binary(BinOp::Sub, rsp, Value::Const(8)),
+        );
+        b.push(Stmt::Store {
+            addr: rsp,
+            value,
+            size: 8,
+        });
+    }
+
+    fn pop(&self, b: &mut Builder) -> Value {
+        let rsp = Value::reg(STACK_POINTER);
+        let value = b.temp(Expr::Load(rsp, 8));
+        b.assign(
+            Var::Reg(STACK_POINTER),
+            Expr::Binary(BinOp::Add, rsp, Value::Const(8)),
+        );
+        value
+    }
+
+    fn lift(&self, b: &mut Builder) -> Option<()> {
+        let inst = self.inst;
+        let opcode = inst.opcode();
+        let count = inst.operand_count();
+
+        match opcode {
+            Opcode::NOP | Opcode::ENDBR64 => {}
+            Opcode::MOV => {
+                let dst = self.place(b, 0)?;
+                let src = self.value(b, 1, self.size(0)?)?;
+                self.write(b, dst, src)?;
+            }
+            Opcode::MOVZX => {
+                let dst = self.place(b, 0)?;
+                let src = self.value(b, 1, 8)?;
+                self.write(b, dst, src)?;
+            }
+            Opcode::MOVSX | Opcode::MOVSXD => {
+                let dst = self.place(b, 0)?;
+                let src = self.value(b, 1, 8)?;
+                let src = b.temp(Expr::sext(src, self.size(1)?));
+                self.write(b, dst, src)?;
+            }
+            Opcode::LEA => {
+                let dst = self.place(b, 0)?;
+                let addr = self.address(b, 1)?;
+                self.write(b, dst, addr)?;
+            }
+            Opcode::XCHG => {
+                let lhs = self.place(b, 0)?;
+                let rhs = self.place(b, 1)?;
+                let lhs_value = self.read(b, lhs)?;
+                let lhs_value = b.temp(Expr::Value(lhs_value));
+                let rhs_value = self.read(b, rhs)?;
+                self.write(b, lhs, rhs_value)?;
+                self.write(b, rhs, lhs_value)?;
+            }
+            Opcode::PUSH => {
+                // Copied as `push rsp` pushes the stack pointer from before the push.
+                let value = self.value(b, 0, 8)?;
+                let value = b.temp(Expr::Value(value));
+                self.push(b, value);
+            }
+            Opcode::POP => {
+                let dst = self.place(b, 0)?;
+                let value = self.pop(b);
+                self.write(b, dst, value)?;
+            }
+            Opcode::LEAVE => {
+                b.assign(Var::Reg(STACK_POINTER), Expr::Value(Value::reg("rbp")));
+                let rbp = self.pop(b);
+                b.assign(Var::Reg("rbp"), Expr::Value(rbp));
+            }
+            Opcode::CDQE => b.assign(Var::Reg("rax"), Expr::sext(Value::reg("rax"), 4)),
+            Opcode::CWDE => {
+                let value = b.temp(Expr::sext(Value::reg("rax"), 2));
+                b.assign(Var::Reg("rax"), Expr::zext(value, 4));
+            }
+            Opcode::CQO => {
+                let rax = Value::reg("rax");
+                b.assign(
+                    Var::Reg("rdx"),
+                    Expr::Binary(BinOp::Sar, rax, Value::Const(63)),
+                );
+            }
+            Opcode::CDQ => {
+                let eax = b.temp(Expr::sext(Value::reg("rax"), 4));
+                let sign = b.binary(BinOp::Sar, eax, Value::Const(63));
+                b.assign(Var::Reg("rdx"), Expr::zext(sign, 4));
+            }
+            Opcode::ADD | Opcode::SUB | Opcode::CMP if count == 2 => {
+                let size = self.size(0)?;
+                let dst = self.place(b, 0)?;
+                let lhs = self.read(b, dst)?;
+                let rhs = self.value(b, 1, size)?;
+                let result = match opcode {
+                    Opcode::ADD => {
+                        let sum = add(b, lhs, rhs, size);
+                        b.assign(Var::Flag(Flag::Carry), Expr::Binary(BinOp::Ult, sum, lhs));
+                        sum
+                    }
+                    _ => {
+                        let borrow = Expr::Binary(BinOp::Ult, lhs, rhs);
+                        b.assign(Var::Flag(Flag::Carry), borrow);
+                        sub(b, lhs, rhs, size)
+                    }
+                };
+
+                if opcode != Opcode::CMP {
+                    self.write(b, dst, result)?;
+                }
+            }
+            Opcode::AND | Opcode::OR | Opcode::XOR | Opcode::TEST if count == 2 => {
+                let size = self.size(0)?;
+                let dst = self.place(b, 0)?;
+                let lhs = self.read(b, dst)?;
+                let rhs = self.value(b, 1, size)?;
+                let op = match opcode {
+                    Opcode::OR => BinOp::Or,
+                    Opcode::XOR => BinOp::Xor,
+                    _ => BinOp::And,
+                };
+
+                let result = b.binary(op, lhs, rhs);
+                logic_flags(b, result, size);
+                if opcode != Opcode::TEST {
+                    self.write(b, dst, result)?;
+                }
+            }
+            Opcode::INC | Opcode::DEC | Opcode::NEG | Opcode::NOT => {
+                let size = self.size(0)?;
+                let dst = self.place(b, 0)?;
+                let value = self.read(b, dst)?;
+                let one = Value::Const(1);
+                let result = match opcode {
+                    // The carry flag is kept by increments and decrements.
+                    Opcode::INC => add(b, value, one, size),
+                    Opcode::DEC => sub(b, value, one, size),
+                    Opcode::NEG => {
+                        let borrow = Expr::Binary(BinOp::Ne, value, Value::Const(0));
+                        b.assign(Var::Flag(Flag::Carry), borrow);
+                        sub(b, Value::Const(0), value, size)
+                    }
+                    _ => {
+                        let result = b.temp(Expr::Unary(UnOp::Not, value));
+                        truncate(b, result, size)
+                    }
+                };
+
+                self.write(b, dst, result)?;
+            }
+            Opcode::SHL | Opcode::SAL | Opcode::SHR | Opcode::SAR => {
+                let size = self.size(0)?;
+                let dst = self.place(b, 0)?;
+                let value = self.read(b, dst)?;
+                let amount = match count {
+                    1 => Value::Const(1),
+                    _ => self.value(b, 1, 1)?,
+                };
+
+                let mask = if size == 8 { 63 } else { 31 };
+                let amount = b.binary(BinOp::And, amount, Value::Const(mask));
+                let result = match opcode {
+                    Opcode::SHR => b.binary(BinOp::Shr, value, amount),
+                    Opcode::SAR => {
+                        let signed = b.temp(Expr::sext(value, size));
+                        b.binary(BinOp::Sar, signed, amount)
+                    }
+                    _ => b.binary(BinOp::Shl, value, amount),
+                };
+
+                let result = truncate(b, result, size);
+                // Shifting by zero doesn't change the flags.
+                if amount != Value::Const(0) {
+                    result_flags(b, result, size);
+                }
+                self.write(b, dst, result)?;
+            }
+            Opcode::IMUL if count >= 2 => {
+                let size = self.size(0)?;
+                let dst = self.place(b, 0)?;
+                let (lhs, rhs) = match count {
+                    2 => (self.read(b, dst)?, self.value(b, 1, size)?),
+                    _ => (self.value(b, 1, size)?, self.value(b, 2, size)?),
+                };
+
+                // The low bits of the product are the same whether signed or not.
+                let product = b.binary(BinOp::Mul, lhs, rhs);
+                let product = truncate(b, product, size);
+                self.write(b, dst, product)?;
+            }
+            opcode if opcode.is_cmovcc() => {
+                let cond = condition(b, opcode.condition()?)?;
+                let dst = self.place(b, 0)?;
+                let current = self.read(b, dst)?;
+                let src = self.value(b, 1, self.size(0)?)?;
+                let value = b.temp(Expr::Select {
+                    cond,
+                    then: src,
+                    otherwise: current,
+                });
+                self.write(b, dst, value)?;
+            }
+            opcode if opcode.is_setcc() => {
+                let cond = condition(b, opcode.condition()?)?;
+                let dst = self.place(b, 0)?;
+                self.write(b, dst, cond)?;
+            }
+            opcode if opcode.is_jcc() => {
+                let cond = condition(b, opcode.condition()?)?;
+                let target = self.target(b, 0)?;
+                b.push(Stmt::Branch { cond, target });
+            }
+            Opcode::JMP => {
+                let target = self.target(b, 0)?;
+                b.push(Stmt::Jump(target));
+            }
+            Opcode::CALL => {
+                let target = self.target(b, 0)?;
+                self.push(b, Value::Const(self.next));
+                b.push(Stmt::Call(target));
+            }
+            Opcode::RETURN => {
+                let target = self.pop(b);
+                if let Some(extra) = self.immediate(0) {
+                    let rsp = Value::reg(STACK_POINTER);
+                    let extra = Value::Const(extra as u16 as u64);
+                    b.assign(
+                        Var::Reg(STACK_POINTER),
+                        Expr::Binary(BinOp::Add, rsp, extra),
+                    );
+                }
+                b.push(Stmt::Return(target));
+            }
+            Opcode::SYSCALL => b.push(Stmt::Syscall),
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+impl Lift for Instruction {
+    fn lift(&self, addr: usize, builder: &mut Builder) -> Option<()> {
+        let lifter = Lifter {
+            inst: self,
+            addr,
+            next: (addr + self.width()) as u64,
+        };
+
+        lifter.lift(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x86_64::long_mode::Decoder;
+
+    fn lift(bytes: &[u8]) -> Vec<String> {
+        let mut inst = Decoder::default().decode_slice(bytes).unwrap();
+        inst.update_rel_addrs(0x1000, None);
+        inst.stmts(0x1000).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn registers() {
+        // add eax, dword [rdi + 0x8]
+        assert_eq!(
+            lift(&[0x03, 0x47, 0x08]),
+            [
+                "t0 = zext4(rax)",
+                "t1 = rdi + 0x8",
+                "t2 = load4(t1)",
+                "t3 = t0 + t2",
+                "t4 = zext4(t3)",
+                "zf = t4 == 0x0",
+                "t5 = t4 >> 0x1f",
+                "sf = t5 & 0x1",
+                "t6 = t0 ^ t4",
+                "t7 = t2 ^ t4",
+                "t8 = t6 & t7",
+                "t9 = t8 >> 0x1f",
+                "of = t9 & 0x1",
+                "cf = t4 <u t0",
+                "rax = zext4(t4)",
+            ]
+        );
+
+        // mov ah, 0x1
+        assert_eq!(
+            lift(&[0xb4, 0x01]),
+            ["t0 = rax & 0xffffffffffff00ff", "rax = t0 | 0x100"]
+        );
+    }
+
+    #[test]
+    fn control_flow() {
+        // push rbp
+        assert_eq!(
+            lift(&[0x55]),
+            ["t0 = rbp", "rsp = rsp - 0x8", "store8(rsp, t0)"]
+        );
+
+        // call $+0x10
+        assert_eq!(
+            lift(&[0xe8, 0x10, 0x00, 0x00, 0x00]),
+            ["rsp = rsp - 0x8", "store8(rsp, 0x1005)", "call 0x1015"]
+        );
+
+        // jnz $-0x10
+        assert_eq!(lift(&[0x75, 0xf0]), ["t0 = zf ^ 0x1", "if t0 jump 0xff2"]);
+
+        // ret
+        assert_eq!(
+            lift(&[0xc3]),
+            ["t0 = load8(rsp)", "rsp = rsp + 0x8", "return t0"]
+        );
+
+        // mov rax, qword [rip + 0x10]
+        assert_eq!(
+            lift(&[0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0x00]),
+            ["t0 = load8(0x1017)", "rax = t0"]
+        );
+
+        // ud2
+        assert_eq!(lift(&[0x0f, 0x0b]), ["unknown"]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Edits made by the user on top of the analysis.
+
+use debugvault::types::Prototype;
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+
+/// What's annotated, each kind kept by address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationKind {
+    Comment,
+    Rename,
+    Bookmark,
+    Prototype,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Annotations {
+    /// End-of-line comments by the address of their instruction.
+    pub comments: BTreeMap<PhysAddr, String>,
+    /// Names given to functions, replacing their symbol.
+    pub renames: BTreeMap<PhysAddr, String>,
+    /// Addresses the user wants to get back to, with an optional label.
+    pub bookmarks: BTreeMap<PhysAddr, String>,
+    /// C declarations of functions, overriding those found in the debug info.
+    pub prototypes: BTreeMap<PhysAddr, String>,
+}
+
+/// Insert `text` at `addr`, or remove what's there if `text` is empty.
+fn set(map: &mut BTreeMap<PhysAddr, String>, addr: PhysAddr, text: &str) {
+    let text = text.trim();
+    if text.is_empty() {
+        map.remove(&addr);
+    } else {
+        map.insert(addr, text.to_string());
+    }
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.comments.is_empty()
+            && self.renames.is_empty()
+            && self.bookmarks.is_empty()
+            && self.prototypes.is_empty()
+    }
+
+    /// Set the comment at `addr`, an empty comment removes it.
+    pub fn set_comment(&mut self, addr: PhysAddr, text: &str) {
+        set(&mut self.comments, addr, text);
+    }
+
+    /// Rename the function at `addr`, an empty name restores it's symbol.
+    pub fn set_rename(&mut self, addr: PhysAddr, name: &str) {
+        set(&mut self.renames, addr, name);
+    }
+
+    /// Declare the function at `addr` as `decl`, an empty declaration removes it.
+    ///
+    /// Returns false without changing anything if `decl` isn't a valid C prototype.
+    pub fn set_prototype(&mut self, addr: PhysAddr, decl: &str) -> bool {
+        if decl.trim().is_empty() {
+            self.prototypes.remove(&addr);
+            return true;
+        }
+
+        match Prototype::parse(decl) {
+            Some(prototype) => {
+                self.prototypes.insert(addr, prototype.to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Bookmark `addr`, replacing the label of an existing bookmark.
+    pub fn set_bookmark(&mut self, addr: PhysAddr, label: &str) {
+        self.bookmarks.insert(addr, label.trim().to_string());
+    }
+
+    pub fn remove_bookmark(&mut self, addr: PhysAddr) {
+        self.bookmarks.remove(&addr);
+    }
+
+    /// Annotations of `kind`, by their address.
+    pub fn of(&self, kind: AnnotationKind) -> &BTreeMap<PhysAddr, String> {
+        match kind {
+            AnnotationKind::Comment => &self.comments,
+            AnnotationKind::Rename => &self.renames,
+            AnnotationKind::Bookmark => &self.bookmarks,
+            AnnotationKind::Prototype => &self.prototypes,
+        }
+    }
+
+    /// Put back the annotation of `kind` at `addr` exactly as it was, `None` if there wasn't one.
+    pub(crate) fn restore(&mut self, kind: AnnotationKind, addr: PhysAddr, text: Option<String>) {
+        let map = match kind {
+            AnnotationKind::Comment => &mut self.comments,
+            AnnotationKind::Rename => &mut self.renames,
+            AnnotationKind::Bookmark => &mut self.bookmarks,
+            AnnotationKind::Prototype => &mut self.prototypes,
+        };
+
+        match text {
+            Some(text) => map.insert(addr, text),
+            None => map.remove(&addr),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_clear() {
+        let mut annotations = Annotations::default();
+
+        annotations.set_comment(0x1000, "  checks the header ");
+        annotations.set_rename(0x1000, "parse_header");
+        assert_eq!(annotations.comments[&0x1000], "checks the header");
+        assert_eq!(annotations.renames[&0x1000], "parse_header");
+
+        annotations.set_comment(0x1000, " ");
+        annotations.set_rename(0x1000, "");
+        assert!(annotations.is_empty());
+
+        // Bookmarks don't need a label.
+        annotations.set_bookmark(0x2000, " ");
+        assert_eq!(annotations.bookmarks[&0x2000], "");
+        annotations.set_bookmark(0x2000, "decrypt loop");
+        assert_eq!(annotations.bookmarks[&0x2000], "decrypt loop");
+        annotations.remove_bookmark(0x2000);
+        assert!(annotations.is_empty());
+
+        // Prototypes are stored as they're shown.
+        assert!(annotations.set_prototype(0x3000, "int  open(const char*path,int flags);"));
+        assert_eq!(annotations.prototypes[&0x3000], "int open(const char *path, int flags)");
+        assert!(!annotations.set_prototype(0x3000, "open"));
+        assert!(annotations.set_prototype(0x3000, ""));
+        assert!(annotations.is_empty());
+
+        // Undoing puts back exactly what was there.
+        annotations.restore(AnnotationKind::Bookmark, 0x2000, Some(String::new()));
+        assert_eq!(annotations.of(AnnotationKind::Bookmark)[&0x2000], "");
+        annotations.restore(AnnotationKind::Bookmark, 0x2000, None);
+        assert!(annotations.is_empty());
+    }
+}
@@ -0,0 +1,795 @@
+//! Assembling the instructions that patches are commonly made of.
+//!
+//! Only register and immediate operands are supported, as patches mostly redirect control flow
+//! or change a constant. Branch targets are absolute addresses or names of functions.
+
+use crate::Processor;
+use object::Architecture;
+use processor_shared::PhysAddr;
+use std::fmt;
+
+pub enum Error {
+    UnsupportedArch(Architecture),
+    /// Source doesn't contain any instructions.
+    Empty,
+    UnknownMnemonic(String),
+    /// Instruction with operands that aren't supported for it's mnemonic.
+    InvalidOperands(String),
+    /// Instruction with an immediate or branch target that can't be encoded.
+    OutOfRange(String),
+    /// Assembled bytes extend past the end of the section.
+    DoesntFit(usize),
+    /// What's left of the last instruction overwritten can't be filled with NOPs.
+    Misaligned(usize),
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedArch(arch) => {
+                f.write_fmt(format_args!("Assembling {arch:?} instructions isn't supported."))
+            }
+            Self::Empty => f.write_str("No instructions to assemble."),
+            Self::UnknownMnemonic(name) => f.write_fmt(format_args!("Unknown mnemonic '{name}'.")),
+            Self::InvalidOperands(inst) => {
+                f.write_fmt(format_args!("Invalid operands in '{inst}'."))
+            }
+            Self::OutOfRange(inst) => {
+                f.write_fmt(format_args!("Operand out of range in '{inst}'."))
+            }
+            Self::DoesntFit(len) => {
+                f.write_fmt(format_args!("{len} bytes don't fit in the section."))
+            }
+            Self::Misaligned(len) => {
+                f.write_fmt(format_args!("Remaining {len} bytes can't be filled with NOPs."))
+            }
+        }
+    }
+}
+
+/// Instruction split into it's lowercase mnemonic and operands.
+struct Inst<'src> {
+    src: &'src str,
+    mnemonic: String,
+    operands: Vec<String>,
+}
+
+impl<'src> Inst<'src> {
+    fn parse(src: &'src str) -> Self {
+        let src = src.trim();
+        let (mnemonic, operands) = src.split_once(char::is_whitespace).unwrap_or((src, ""));
+        let operands = operands
+            .split(',')
+            .map(|op| op.trim().to_ascii_lowercase())
+            .filter(|op| !op.is_empty())
+            .collect();
+
+        Self {
+            src,
+            mnemonic: mnemonic.to_ascii_lowercase(),
+            operands,
+        }
+    }
+
+    fn invalid(&self) -> Error {
+        Error::InvalidOperands(self.src.to_string())
+    }
+
+    fn out_of_range(&self) -> Error {
+        Error::OutOfRange(self.src.to_string())
+    }
+}
+
+/// Number in hex with a `0x` prefix or decimal, optionally prefixed with `#` and/or negated.
+fn parse_imm(s: &str) -> Option<i64> {
+    let s = s.strip_prefix('#').unwrap_or(s);
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => s.parse().ok()?,
+    };
+
+    Some(if negative { -value } else { value })
+}
+
+/// Absolute address of a branch target, either a number or a function's name.
+fn parse_target(s: &str, resolve: &dyn Fn(&str) -> Option<usize>) -> Option<i64> {
+    parse_imm(s).or_else(|| resolve(s).map(|addr| addr as i64))
+}
+
+/// Assemble `src` as if it were placed at `addr`, instructions are separated by ';' or newlines.
+pub fn assemble(
+    arch: Architecture,
+    addr: PhysAddr,
+    src: &str,
+    resolve: &dyn Fn(&str) -> Option<usize>,
+) -> Result<Vec<u8>, Error> {
+    let encode = match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 => x86::encode_64,
+        Architecture::I386 => x86::encode_32,
+        Architecture::Aarch64 => aarch64::encode,
+        _ => return Err(Error::UnsupportedArch(arch)),
+    };
+
+    let mut bytes = Vec::new();
+    for line in src.split([';', '\n']).filter(|line| !line.trim().is_empty()) {
+        let inst = Inst::parse(line);
+        let pc = addr as i64 + bytes.len() as i64;
+        encode(&inst, pc, resolve, &mut bytes)?;
+    }
+
+    if bytes.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    Ok(bytes)
+}
+
+mod x86 {
+    use super::{parse_imm, parse_target, Error, Inst};
+
+    const REGS_64: [&str; 16] = [
+        "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12",
+        "r13", "r14", "r15",
+    ];
+
+    const REGS_32: [&str; 16] = [
+        "eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d",
+        "r12d", "r13d", "r14d", "r15d",
+    ];
+
+    const CONDITIONS: &[(&str, u8)] = &[
+        ("o", 0x0),
+        ("no", 0x1),
+        ("b", 0x2),
+        ("c", 0x2),
+        ("nae", 0x2),
+        ("ae", 0x3),
+        ("nb", 0x3),
+        ("nc", 0x3),
+        ("e", 0x4),
+        ("z", 0x4),
+        ("ne", 0x5),
+        ("nz", 0x5),
+        ("be", 0x6),
+        ("na", 0x6),
+        ("a", 0x7),
+        ("nbe", 0x7),
+        ("s", 0x8),
+        ("ns", 0x9),
+        ("p", 0xa),
+        ("pe", 0xa),
+        ("np", 0xb),
+        ("po", 0xb),
+        ("l", 0xc),
+        ("nge", 0xc),
+        ("ge", 0xd),
+        ("nl", 0xd),
+        ("le", 0xe),
+        ("ng", 0xe),
+        ("g", 0xf),
+        ("nle", 0xf),
+    ];
+
+    /// Mnemonics that are supported with operands, besides arithmetic and conditional jumps.
+    const MNEMONICS: &[&str] = &[
+        "ret", "retn", "int", "jmp", "call", "push", "pop", "mov", "test", "shl", "sal", "shr",
+        "sar", "inc", "dec", "not", "neg",
+    ];
+
+    /// Opcode extensions of the arithmetic instructions sharing opcodes 0x81 and 0x83.
+    const ARITHMETIC: &[(&str, u8)] = &[
+        ("add", 0),
+        ("or", 1),
+        ("adc", 2),
+        ("sbb", 3),
+        ("and", 4),
+        ("sub", 5),
+        ("xor", 6),
+        ("cmp", 7),
+    ];
+
+    #[derive(Clone, Copy)]
+    struct Reg {
+        num: u8,
+        wide: bool,
+    }
+
+    struct Encoder<'a> {
+        long_mode: bool,
+        bytes: &'a mut Vec<u8>,
+    }
+
+    impl Encoder<'_> {
+        fn reg(&self, s: &str) -> Option<Reg> {
+            if let Some(num) = REGS_64.iter().position(|&reg| reg == s) {
+                return self.long_mode.then_some(Reg { num: num as u8, wide: true });
+            }
+
+            let num = REGS_32.iter().position(|&reg| reg == s)? as u8;
+            (self.long_mode || num < 8).then_some(Reg { num, wide: false })
+        }
+
+        /// Prefix selecting 64-bit operands and the upper 8 registers.
+        fn rex(&mut self, wide: bool, reg: u8, rm: u8) {
+            let rex = 0x40 | (wide as u8) << 3 | (reg >> 3) << 2 | rm >> 3;
+            if rex != 0x40 {
+                self.bytes.push(rex);
+            }
+        }
+
+        /// Opcode operating on two registers, `reg` is encoded in the ModRM's reg field.
+        fn reg_rm(&mut self, opcode: &[u8], reg: u8, rm: Reg) {
+            self.rex(rm.wide, reg, rm.num);
+            self.bytes.extend_from_slice(opcode);
+            self.bytes.push(0xc0 | (reg & 7) << 3 | rm.num & 7);
+        }
+
+        /// Immediate of an instruction operating on `reg`, None if it doesn't fit in 32 bits.
+        fn imm32(reg: Reg, imm: i64) -> Option<u32> {
+            // Immediates are sign extended to 64 bits.
+            let max = if reg.wide { i32::MAX as i64 } else { u32::MAX as i64 };
+            (i32::MIN as i64..=max).contains(&imm).then_some(imm as u32)
+        }
+
+        fn rel8_or_32(&mut self, short: &[u8], near: &[u8], pc: i64, target: i64) -> bool {
+            let rel = target - (pc + short.len() as i64 + 1);
+            if let Ok(rel) = i8::try_from(rel) {
+                self.bytes.extend_from_slice(short);
+                self.bytes.push(rel as u8);
+                return true;
+            }
+
+            self.rel32(near, pc, target)
+        }
+
+        fn rel32(&mut self, opcode: &[u8], pc: i64, target: i64) -> bool {
+            let rel = target - (pc + opcode.len() as i64 + 4);
+            match i32::try_from(rel) {
+                Ok(rel) => {
+                    self.bytes.extend_from_slice(opcode);
+                    self.bytes.extend_from_slice(&rel.to_le_bytes());
+                    true
+                }
+                Err(_) => false,
+            }
+        }
+    }
+
+    pub(super) fn encode_64(
+        inst: &Inst,
+        pc: i64,
+        resolve: &dyn Fn(&str) -> Option<usize>,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        encode(inst, pc, resolve, &mut Encoder { long_mode: true, bytes })
+    }
+
+    pub(super) fn encode_32(
+        inst: &Inst,
+        pc: i64,
+        resolve: &dyn Fn(&str) -> Option<usize>,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        encode(inst, pc, resolve, &mut Encoder { long_mode: false, bytes })
+    }
+
+    fn encode(
+        inst: &Inst,
+        pc: i64,
+        resolve: &dyn Fn(&str) -> Option<usize>,
+        enc: &mut Encoder,
+    ) -> Result<(), Error> {
+        let ops: Vec<&str> = inst.operands.iter().map(String::as_str).collect();
+        let mnemonic = inst.mnemonic.as_str();
+
+        let fixed: &[u8] = match mnemonic {
+            "nop" => &[0x90],
+            "ret" | "retn" if ops.is_empty() => &[0xc3],
+            "int3" => &[0xcc],
+            "hlt" => &[0xf4],
+            "leave" => &[0xc9],
+            "cdq" => &[0x99],
+            "syscall" if enc.long_mode => &[0x0f, 0x05],
+            "ud2" => &[0x0f, 0x0b],
+            _ => &[],
+        };
+
+        if !fixed.is_empty() {
+            if !ops.is_empty() {
+                return Err(inst.invalid());
+            }
+
+            enc.bytes.extend_from_slice(fixed);
+            return Ok(());
+        }
+
+        let arithmetic = ARITHMETIC.iter().find(|(name, _)| *name == mnemonic).map(|(_, ext)| *ext);
+        let condition = mnemonic
+            .strip_prefix('j')
+            .and_then(|cond| CONDITIONS.iter().find(|(name, _)| *name == cond))
+            .map(|(_, code)| *code);
+
+        match (mnemonic, ops.as_slice()) {
+            ("ret" | "retn", [imm]) => {
+                let imm = parse_imm(imm).ok_or_else(|| inst.invalid())?;
+                let imm = u16::try_from(imm).map_err(|_| inst.out_of_range())?;
+                enc.bytes.push(0xc2);
+                enc.bytes.extend_from_slice(&imm.to_le_bytes());
+            }
+            ("int", [imm]) => {
+                let imm = parse_imm(imm).ok_or_else(|| inst.invalid())?;
+                let imm = u8::try_from(imm).map_err(|_| inst.out_of_range())?;
+                enc.bytes.extend_from_slice(&[0xcd, imm]);
+            }
+            ("jmp" | "call", [op]) if enc.reg(op).is_some() => {
+                let reg = enc.reg(op).unwrap();
+                if reg.wide != enc.long_mode {
+                    return Err(inst.invalid());
+                }
+
+                let ext = if mnemonic == "jmp" { 4 } else { 2 };
+                enc.reg_rm(&[0xff], ext, Reg { wide: false, ..reg });
+            }
+            ("jmp", [target]) => {
+                let target = parse_target(target, resolve).ok_or_else(|| inst.invalid())?;
+                if !enc.rel8_or_32(&[0xeb], &[0xe9], pc, target) {
+                    return Err(inst.out_of_range());
+                }
+            }
+            ("call", [target]) => {
+                let target = parse_target(target, resolve).ok_or_else(|| inst.invalid())?;
+                if !enc.rel32(&[0xe8], pc, target) {
+                    return Err(inst.out_of_range());
+                }
+            }
+            (_, [target]) if condition.is_some() => {
+                let cond = condition.unwrap();
+                let target = parse_target(target, resolve).ok_or_else(|| inst.invalid())?;
+                if !enc.rel8_or_32(&[0x70 | cond], &[0x0f, 0x80 | cond], pc, target) {
+                    return Err(inst.out_of_range());
+                }
+            }
+            ("push" | "pop", [op]) => {
+                if let Some(reg) = enc.reg(op) {
+                    if reg.wide != enc.long_mode {
+                        return Err(inst.invalid());
+                    }
+
+                    let base = if mnemonic == "push" { 0x50 } else { 0x58 };
+                    enc.rex(false, 0, reg.num);
+                    enc.bytes.push(base | reg.num & 7);
+                    return Ok(());
+                }
+
+                let imm = parse_imm(op).filter(|_| mnemonic == "push");
+                let imm = imm.ok_or_else(|| inst.invalid())?;
+                match i8::try_from(imm) {
+                    Ok(imm) => enc.bytes.extend_from_slice(&[0x6a, imm as u8]),
+                    Err(_) => {
+                        let imm = i32::try_from(imm).map_err(|_| inst.out_of_range())?;
+                        enc.bytes.push(0x68);
+                        enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                    }
+                }
+            }
+            ("mov", [dst, src]) => {
+                let dst = enc.reg(dst).ok_or_else(|| inst.invalid())?;
+
+                if let Some(src) = enc.reg(src) {
+                    if src.wide != dst.wide {
+                        return Err(inst.invalid());
+                    }
+
+                    enc.reg_rm(&[0x89], src.num, dst);
+                    return Ok(());
+                }
+
+                let imm = parse_imm(src).ok_or_else(|| inst.invalid())?;
+                if (0..=u32::MAX as i64).contains(&imm) || !dst.wide {
+                    // Writing the lower 32 bits zeroes the upper ones.
+                    let imm = Encoder::imm32(Reg { wide: false, ..dst }, imm);
+                    let imm = imm.ok_or_else(|| inst.out_of_range())?;
+                    enc.rex(false, 0, dst.num);
+                    enc.bytes.push(0xb8 | dst.num & 7);
+                    enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                } else if let Some(imm) = Encoder::imm32(dst, imm) {
+                    enc.reg_rm(&[0xc7], 0, dst);
+                    enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                } else {
+                    enc.rex(true, 0, dst.num);
+                    enc.bytes.push(0xb8 | dst.num & 7);
+                    enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                }
+            }
+            (_, [dst, src]) if arithmetic.is_some() || mnemonic == "test" => {
+                let dst = enc.reg(dst).ok_or_else(|| inst.invalid())?;
+
+                if let Some(src) = enc.reg(src) {
+                    if src.wide != dst.wide {
+                        return Err(inst.invalid());
+                    }
+
+                    let opcode = match arithmetic {
+                        Some(ext) => ext << 3 | 0x01,
+                        None => 0x85,
+                    };
+                    enc.reg_rm(&[opcode], src.num, dst);
+                    return Ok(());
+                }
+
+                let imm = parse_imm(src).ok_or_else(|| inst.invalid())?;
+                let ext = match arithmetic {
+                    Some(ext) => ext,
+                    None => {
+                        let imm = Encoder::imm32(dst, imm).ok_or_else(|| inst.out_of_range())?;
+                        enc.reg_rm(&[0xf7], 0, dst);
+                        enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                        return Ok(());
+                    }
+                };
+
+                if let Ok(imm) = i8::try_from(imm) {
+                    enc.reg_rm(&[0x83], ext, dst);
+                    enc.bytes.push(imm as u8);
+                } else {
+                    let imm = Encoder::imm32(dst, imm).ok_or_else(|| inst.out_of_range())?;
+                    enc.reg_rm(&[0x81], ext, dst);
+                    enc.bytes.extend_from_slice(&imm.to_le_bytes());
+                }
+            }
+            ("shl" | "sal" | "shr" | "sar", [dst, imm]) => {
+                let dst = enc.reg(dst).ok_or_else(|| inst.invalid())?;
+                let imm = parse_imm(imm).ok_or_else(|| inst.invalid())?;
+                let imm = u8::try_from(imm).map_err(|_| inst.out_of_range())?;
+                let ext = match mnemonic {
+                    "shr" => 5,
+                    "sar" => 7,
+                    _ => 4,
+                };
+
+                enc.reg_rm(&[0xc1], ext, dst);
+                enc.bytes.push(imm);
+            }
+            ("inc" | "dec" | "not" | "neg", [op]) => {
+                let reg = enc.reg(op).ok_or_else(|| inst.invalid())?;
+                let (opcode, ext) = match mnemonic {
+                    "inc" => (0xff, 0),
+                    "dec" => (0xff, 1),
+                    "not" => (0xf7, 2),
+                    _ => (0xf7, 3),
+                };
+
+                enc.reg_rm(&[opcode], ext, reg);
+            }
+            _ if condition.is_some() || arithmetic.is_some() || MNEMONICS.contains(&mnemonic) => {
+                return Err(inst.invalid());
+            }
+            _ => return Err(Error::UnknownMnemonic(inst.mnemonic.clone())),
+        }
+
+        Ok(())
+    }
+}
+
+mod aarch64 {
+    use super::{parse_imm, parse_target, Error, Inst};
+
+    const CONDITIONS: &[(&str, u32)] = &[
+        ("eq", 0x0),
+        ("ne", 0x1),
+        ("cs", 0x2),
+        ("hs", 0x2),
+        ("cc", 0x3),
+        ("lo", 0x3),
+        ("mi", 0x4),
+        ("pl", 0x5),
+        ("vs", 0x6),
+        ("vc", 0x7),
+        ("hi", 0x8),
+        ("ls", 0x9),
+        ("ge", 0xa),
+        ("lt", 0xb),
+        ("gt", 0xc),
+        ("le", 0xd),
+        ("al", 0xe),
+    ];
+
+    /// Mnemonics that are supported, besides conditional branches.
+    const MNEMONICS: &[&str] = &[
+        "nop", "ret", "br", "blr", "brk", "svc", "b", "bl", "cbz", "cbnz", "mov", "add", "adds",
+        "sub", "subs", "cmp", "cmn",
+    ];
+
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    struct Reg {
+        num: u32,
+        wide: bool,
+        /// Register 31 refers to the stack pointer instead of the zero register.
+        sp: bool,
+    }
+
+    fn reg(s: &str) -> Option<Reg> {
+        let (wide, num, sp) = match s {
+            "sp" => (true, 31, true),
+            "wsp" => (false, 31, true),
+            "xzr" => (true, 31, false),
+            "wzr" => (false, 31, false),
+            "lr" => (true, 30, false),
+            "fp" => (true, 29, false),
+            _ => {
+                let (wide, num) = match s.split_at(1) {
+                    ("x", num) => (true, num),
+                    ("w", num) => (false, num),
+                    _ => return None,
+                };
+
+                let num: u32 = num.parse().ok().filter(|&num| num < 31)?;
+                (wide, num, false)
+            }
+        };
+
+        Some(Reg { num, wide, sp })
+    }
+
+    /// Branch offset in instructions, None if it's misaligned or doesn't fit in `bits`.
+    fn offset(pc: i64, target: i64, bits: u32) -> Option<u32> {
+        let off = target - pc;
+        let limit = 1i64 << (bits + 1);
+        (off % 4 == 0 && (-limit..limit).contains(&off))
+            .then_some(((off >> 2) as u32) & ((1 << bits) - 1))
+    }
+
+    /// Move wide immediate, either the value or it's inverse shifted into one 16-bit chunk.
+    fn mov_imm(rd: Reg, imm: i64) -> Option<u32> {
+        let bits = if rd.wide { 64 } else { 32 };
+        let mask = if rd.wide { u64::MAX } else { u32::MAX as u64 };
+
+        // Values outside of the register's width are only allowed when negative.
+        if !rd.wide && !(i32::MIN as i64..=u32::MAX as i64).contains(&imm) {
+            return None;
+        }
+
+        let value = imm as u64 & mask;
+        for (opcode, value) in [(0x5280_0000, value), (0x1280_0000, !value & mask)] {
+            for hw in 0..bits / 16 {
+                if value & !(0xffff << (hw * 16)) == 0 {
+                    let chunk = (value >> (hw * 16)) as u32;
+                    return Some((rd.wide as u32) << 31 | opcode | hw << 21 | chunk << 5 | rd.num);
+                }
+            }
+        }
+
+        None
+    }
+
+    pub(super) fn encode(
+        inst: &Inst,
+        pc: i64,
+        resolve: &dyn Fn(&str) -> Option<usize>,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        let ops: Vec<&str> = inst.operands.iter().map(String::as_str).collect();
+        let mnemonic = inst.mnemonic.as_str();
+        let condition = mnemonic
+            .strip_prefix("b.")
+            .and_then(|cond| CONDITIONS.iter().find(|(name, _)| *name == cond))
+            .map(|(_, code)| *code);
+
+        let target = || {
+            let target = ops.last().and_then(|op| parse_target(op, resolve));
+            target.ok_or_else(|| inst.invalid())
+        };
+        let imm16 = |op: &str| -> Result<u32, Error> {
+            let imm = parse_imm(op).ok_or_else(|| inst.invalid())?;
+            u16::try_from(imm).map(u32::from).map_err(|_| inst.out_of_range())
+        };
+
+        let word = match (mnemonic, ops.as_slice()) {
+            ("nop", []) => 0xd503_201f,
+            ("ret", []) => 0xd65f_03c0,
+            ("ret" | "br" | "blr", [op]) => {
+                let rn = reg(op).filter(|rn| rn.wide && !rn.sp).ok_or_else(|| inst.invalid())?;
+                let opcode = match mnemonic {
+                    "ret" => 0xd65f_0000,
+                    "br" => 0xd61f_0000,
+                    _ => 0xd63f_0000,
+                };
+
+                opcode | rn.num << 5
+            }
+            ("brk", [imm]) => 0xd420_0000 | imm16(imm)? << 5,
+            ("svc", [imm]) => 0xd400_0001 | imm16(imm)? << 5,
+            ("b" | "bl", [_]) => {
+                let off = offset(pc, target()?, 26).ok_or_else(|| inst.out_of_range())?;
+                let opcode = if mnemonic == "b" { 0x1400_0000 } else { 0x9400_0000 };
+                opcode | off
+            }
+            (_, [_]) if condition.is_some() => {
+                let off = offset(pc, target()?, 19).ok_or_else(|| inst.out_of_range())?;
+                0x5400_0000 | off << 5 | condition.unwrap()
+            }
+            ("cbz" | "cbnz", [rt, _]) => {
+                let rt = reg(rt).filter(|rt| !rt.sp).ok_or_else(|| inst.invalid())?;
+                let off = offset(pc, target()?, 19).ok_or_else(|| inst.out_of_range())?;
+                let opcode = if mnemonic == "cbz" { 0x3400_0000 } else { 0x3500_0000 };
+                (rt.wide as u32) << 31 | opcode | off << 5 | rt.num
+            }
+            ("mov", [rd, src]) => {
+                let rd = reg(rd).ok_or_else(|| inst.invalid())?;
+                match reg(src) {
+                    Some(rm) if rm.wide != rd.wide => return Err(inst.invalid()),
+                    // The stack pointer is only accessible through `add`.
+                    Some(rm) if rm.sp || rd.sp => {
+                        (rd.wide as u32) << 31 | 0x1100_0000 | rm.num << 5 | rd.num
+                    }
+                    Some(rm) => (rd.wide as u32) << 31 | 0x2a00_03e0 | rm.num << 16 | rd.num,
+                    None if rd.sp => return Err(inst.invalid()),
+                    None => {
+                        let imm = parse_imm(src).ok_or_else(|| inst.invalid())?;
+                        mov_imm(rd, imm).ok_or_else(|| inst.out_of_range())?
+                    }
+                }
+            }
+            ("add" | "adds" | "sub" | "subs" | "cmp" | "cmn", _) => {
+                // Comparisons discard the result into the zero register.
+                let (opcode, ops) = match (mnemonic, ops.as_slice()) {
+                    ("add", [rd, rn, op]) => (0x0100_0000, [*rd, *rn, *op]),
+                    ("adds", [rd, rn, op]) => (0x2100_0000, [*rd, *rn, *op]),
+                    ("sub", [rd, rn, op]) => (0x4100_0000, [*rd, *rn, *op]),
+                    ("subs", [rd, rn, op]) => (0x6100_0000, [*rd, *rn, *op]),
+                    ("cmp", [rn, op]) => (0x6100_0000, ["xzr", *rn, *op]),
+                    ("cmn", [rn, op]) => (0x2100_0000, ["xzr", *rn, *op]),
+                    _ => return Err(inst.invalid()),
+                };
+
+                let rn = reg(ops[1]).ok_or_else(|| inst.invalid())?;
+                let rd = match reg(ops[0]) {
+                    Some(rd) if ops[0] == "xzr" => Reg { wide: rn.wide, ..rd },
+                    Some(rd) => rd,
+                    None => return Err(inst.invalid()),
+                };
+
+                if rd.wide != rn.wide {
+                    return Err(inst.invalid());
+                }
+
+                let sf = (rd.wide as u32) << 31;
+                match reg(ops[2]) {
+                    Some(rm) if rm.wide != rd.wide || rm.sp || rn.sp => {
+                        return Err(inst.invalid());
+                    }
+                    Some(rm) => sf | 0x0a00_0000 | opcode | rm.num << 16 | rn.num << 5 | rd.num,
+                    None => {
+                        let imm = parse_imm(ops[2]).ok_or_else(|| inst.invalid())?;
+                        let (shift, imm) = match imm {
+                            0..=0xfff => (0, imm as u32),
+                            _ if imm & 0xfff == 0 && imm >> 12 <= 0xfff => (1, (imm >> 12) as u32),
+                            _ => return Err(inst.out_of_range()),
+                        };
+
+                        sf | 0x1000_0000 | opcode | shift << 22 | imm << 10 | rn.num << 5 | rd.num
+                    }
+                }
+            }
+            _ if condition.is_some() || MNEMONICS.contains(&mnemonic) => {
+                return Err(inst.invalid());
+            }
+            _ => return Err(Error::UnknownMnemonic(inst.mnemonic.clone())),
+        };
+
+        // Instructions are little-endian regardless of the data's endianness.
+        bytes.extend_from_slice(&u32::to_le_bytes(word));
+        Ok(())
+    }
+}
+
+impl Processor {
+    /// Assemble `src` as if it were placed at `addr`, resolving branch targets by function name.
+    pub fn assemble(&self, addr: PhysAddr, src: &str) -> Result<Vec<u8>, Error> {
+        assemble(self.arch, addr, src, &|name| self.index.get_func_by_name(name))
+    }
+
+    /// Overwrite the code at `addr` with assembled instructions or bytes written in hex.
+    ///
+    /// What's left of the last instruction overwritten is filled with NOPs, so that decoding
+    /// stays in sync. Returns the number of bytes written.
+    pub fn patch_asm(&self, addr: PhysAddr, src: &str) -> Result<usize, Error> {
+        let mut bytes = match crate::parse_hex(src) {
+            Some(bytes) => bytes,
+            None => self.assemble(addr, src)?,
+        };
+
+        let end = addr + bytes.len();
+        let mut next = addr;
+        while next < end {
+            match self.instruction_bytes(next) {
+                Some(inst) if !inst.is_empty() => next += inst.len(),
+                _ => break,
+            }
+        }
+
+        if next > end {
+            let nops = self.nops(next - end).ok_or(Error::Misaligned(next - end))?;
+            bytes.extend(nops);
+        }
+
+        if !self.patch_addr(addr, &bytes) {
+            return Err(Error::DoesntFit(bytes.len()));
+        }
+
+        Ok(bytes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asm(arch: Architecture, addr: PhysAddr, src: &str) -> Vec<u8> {
+        let resolve = |name: &str| (name == "exit").then_some(0x402000);
+        match assemble(arch, addr, src, &resolve) {
+            Ok(bytes) => bytes,
+            Err(err) => panic!("failed to assemble '{src}': {err:?}"),
+        }
+    }
+
+    #[test]
+    fn x86_64() {
+        let x86 = |src| asm(Architecture::X86_64, 0x401000, src);
+
+        assert_eq!(x86("jmp 0x401050"), [0xeb, 0x4e]);
+        assert_eq!(x86("call exit"), [0xe8, 0xfb, 0x0f, 0x00, 0x00]);
+        assert_eq!(x86("je 0x401000"), [0x74, 0xfe]);
+        assert_eq!(x86("jne 0x500000"), [0x0f, 0x85, 0xfa, 0xef, 0x0f, 0x00]);
+        assert_eq!(x86("jmp rax"), [0xff, 0xe0]);
+        assert_eq!(x86("xor eax, eax; ret"), [0x31, 0xc0, 0xc3]);
+        assert_eq!(x86("mov r8, rsp"), [0x49, 0x89, 0xe0]);
+        assert_eq!(x86("mov eax, 1"), [0xb8, 0x01, 0x00, 0x00, 0x00]);
+        assert_eq!(x86("mov rax, -1"), [0x48, 0xc7, 0xc0, 0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(x86("sub rsp, 0x28"), [0x48, 0x83, 0xec, 0x28]);
+        assert_eq!(x86("cmp ecx, 0x1000"), [0x81, 0xf9, 0x00, 0x10, 0x00, 0x00]);
+        assert_eq!(x86("push rbp\npop r12"), [0x55, 0x41, 0x5c]);
+        assert_eq!(x86("inc r9d"), [0x41, 0xff, 0xc1]);
+    }
+
+    #[test]
+    fn x86_errors() {
+        let x86 = |src| assemble(Architecture::X86_64, 0, src, &|_| None);
+
+        assert!(matches!(x86("frobnicate eax"), Err(Error::UnknownMnemonic(_))));
+        assert!(matches!(x86("mov eax, rbx"), Err(Error::InvalidOperands(_))));
+        assert!(matches!(x86("add rax, 0x100000000"), Err(Error::OutOfRange(_))));
+        assert!(matches!(x86(" ; "), Err(Error::Empty)));
+        assert!(matches!(
+            assemble(Architecture::I386, 0, "push r8", &|_| None),
+            Err(Error::InvalidOperands(_))
+        ));
+    }
+
+    #[test]
+    fn aarch64() {
+        let arm = |src| asm(Architecture::Aarch64, 0x1000, src);
+        let word = |word: u32| word.to_le_bytes().to_vec();
+
+        assert_eq!(arm("ret"), word(0xd65f03c0));
+        assert_eq!(arm("mov x0, #1"), word(0xd2800020));
+        assert_eq!(arm("mov w1, #-1"), word(0x12800001));
+        assert_eq!(arm("mov x29, sp"), word(0x910003fd));
+        assert_eq!(arm("mov x0, x1"), word(0xaa0103e0));
+        assert_eq!(arm("b 0x1010"), word(0x14000004));
+        assert_eq!(arm("bl 0x1000"), word(0x94000000));
+        assert_eq!(arm("b.ne 0x1008"), word(0x54000041));
+        assert_eq!(arm("cbz w3, 0x1004"), word(0x34000023));
+        assert_eq!(arm("cmp x0, #5"), word(0xf100141f));
+        assert_eq!(arm("add x0, x1, x2"), word(0x8b020020));
+        assert_eq!(arm("sub sp, sp, #0x20"), word(0xd10083ff));
+
+        let misaligned = assemble(Architecture::Aarch64, 0x1000, "b 0x1002", &|_| None);
+        assert!(matches!(misaligned, Err(Error::OutOfRange(_))));
+    }
+}
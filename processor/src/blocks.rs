@@ -1,4 +1,5 @@
-use crate::Processor;
+use crate::strings::c_string;
+use crate::{Code, Processor};
 use binformat::elf::{Elf32Dyn, Elf32Sym, Elf64Dyn, Elf64Sym};
 use binformat::pe::ExceptionDirectoryEntry;
 use binformat::ToData;
@@ -7,11 +8,29 @@ use debugvault::Symbol;
 use object::Endian;
 use processor_shared::{encode_hex_bytes_truncated, Section, SectionKind};
 use std::mem::size_of;
+use std::ops::Range;
 use std::sync::Arc;
-use tokenizing::{colors, Token, TokenStream};
+use tokenizing::{Kind, MaybeStatic, Token, TokenStream};
 
 const BYTES_BLOCK_SIZE: usize = 256;
 
+/// Bytes shown per line, also the most a run of unrecognized bytes in a data section spans.
+const BYTES_PER_LINE: usize = 32;
+
+/// Shortest run of zeroes in a data section that is collapsed into a single line.
+const MIN_ZERO_RUN: usize = 16;
+
+/// Longest string recognized in a data section, so unterminated text isn't scanned forever.
+const MAX_DATA_STRING_LEN: usize = 4096;
+
+/// Item in a data section, see [`Processor::data_item`].
+enum DataItem {
+    Pointer(u64),
+    String,
+    Zeroes,
+    Bytes,
+}
+
 #[derive(Debug)]
 pub enum BlockContent {
     SectionStart {
@@ -24,7 +43,10 @@ pub enum BlockContent {
         symbol: Arc<Symbol>,
     },
     Instruction {
-        inst: Vec<Token>,
+        mnemonic: Vec<Token>,
+        operands: Vec<Token>,
+        /// Notes about the instruction along with the user's comment, each starting with `  ;`.
+        comments: Vec<Token>,
         bytes: String,
     },
     Error {
@@ -42,6 +64,12 @@ pub enum BlockContent {
         value: u64,
         symbol: Option<Arc<Symbol>>,
     },
+    /// Entry of a jump table.
+    Case {
+        case: usize,
+        target: usize,
+        symbol: Option<Arc<Symbol>>,
+    },
     DataStructure {
         ident: &'static str,
         /// (addr, field, type, value).
@@ -50,6 +78,10 @@ pub enum BlockContent {
     Bytes {
         bytes: Vec<u8>,
     },
+    /// Run of zeroes, including variables the loader zero-fills.
+    Zeroes {
+        len: usize,
+    },
 }
 
 #[derive(Debug)]
@@ -58,7 +90,136 @@ pub struct Block {
     pub content: BlockContent,
 }
 
+/// Push the address each line of a block starts with.
+fn address_column(stream: &mut TokenStream, addr: usize) {
+    stream.push_addr(format!("{addr:0>10X}  "), Kind::Address, addr);
+}
+
+/// Number of characters in `tokens`.
+fn text_width(tokens: &[Token]) -> usize {
+    tokens.iter().map(|token| token.text.chars().count()).sum()
+}
+
+/// Push spaces padding `width` characters up to `column`, at least `min` of them.
+fn pad(stream: &mut TokenStream, width: usize, column: usize, min: usize) {
+    let spaces = column.saturating_sub(width).max(min);
+    if spaces > 0 {
+        stream.push_owned(" ".repeat(spaces), Kind::Plain);
+    }
+}
+
+/// Split the tokens of an instruction into it's mnemonic and it's operands.
+///
+/// Decoders push the mnemonic first, possibly in several tokens and with the space separating
+/// it from the operands.
+fn split_mnemonic(mut inst: Vec<Token>) -> (Vec<Token>, Vec<Token>) {
+    let split = inst.iter().position(|token| token.kind != Kind::Mnemonic);
+    let mut operands = inst.split_off(split.unwrap_or(inst.len()));
+
+    let mnemonic: String = inst.iter().map(|token| &token.text as &str).collect();
+    let mnemonic = match mnemonic.trim() {
+        "" => Vec::new(),
+        text => vec![Token::from_string(text.to_string(), Kind::Mnemonic)],
+    };
+
+    while let Some(token) = operands.first_mut() {
+        let text = token.text.trim_start();
+        if !text.is_empty() {
+            if text.len() != token.text.len() {
+                token.text = MaybeStatic::Dynamic(Arc::from(text));
+            }
+            break;
+        }
+        operands.remove(0);
+    }
+
+    (mnemonic, operands)
+}
+
+/// Line of an instruction split into it's columns, see [`Block::row`].
+#[derive(Debug)]
+pub struct Row {
+    pub addr: usize,
+    /// Bytes encoding the instruction, padded to the width of the column.
+    pub bytes: String,
+    pub mnemonic: Vec<Token>,
+    pub operands: Vec<Token>,
+    pub comments: Vec<Token>,
+}
+
+impl Row {
+    /// Tokenize the columns that are shown, padded to the widths of the stream's format so
+    /// that those of consecutive rows line up.
+    ///
+    /// Returns the range of tokens that are the mnemonic and operands.
+    pub fn tokenize(&self, stream: &mut TokenStream) -> Range<usize> {
+        let format = stream.format;
+
+        address_column(stream, self.addr);
+        if format.opcode_bytes {
+            stream.push_owned(self.bytes.clone(), Kind::Bytes);
+        }
+
+        let start = stream.inner.len();
+        stream.inner.extend_from_slice(&self.mnemonic);
+        if !self.mnemonic.is_empty() && !self.operands.is_empty() {
+            pad(stream, text_width(&self.mnemonic), format.mnemonic_width, 1);
+        }
+        stream.inner.extend_from_slice(&self.operands);
+        let end = stream.inner.len();
+
+        if !self.comments.is_empty() {
+            let width = text_width(&stream.inner[start..end]);
+            pad(stream, width, format.instruction_width, 0);
+            stream.inner.extend_from_slice(&self.comments);
+        }
+
+        start..end
+    }
+
+    /// The mnemonic and operands as text, separated by a space.
+    pub fn instruction(&self) -> String {
+        let mut text = String::new();
+        text.extend(self.mnemonic.iter().map(|token| &token.text as &str));
+        if !self.operands.is_empty() {
+            text.push(' ');
+            text.extend(self.operands.iter().map(|token| &token.text as &str));
+        }
+        text
+    }
+}
+
 impl Block {
+    /// Columns of the block if it's an instruction, or bytes that failed to decode as one.
+    pub fn row(&self) -> Option<Row> {
+        match &self.content {
+            BlockContent::Instruction {
+                mnemonic,
+                operands,
+                comments,
+                bytes,
+            } => Some(Row {
+                addr: self.addr,
+                bytes: bytes.clone(),
+                mnemonic: mnemonic.clone(),
+                operands: operands.clone(),
+                comments: comments.clone(),
+            }),
+            BlockContent::Error { err, bytes } => Some(Row {
+                addr: self.addr,
+                bytes: bytes.clone(),
+                mnemonic: vec![
+                    Token::from_str("<", Kind::Bracket),
+                    Token::from_string(format!("{err:?}"), Kind::Invalid),
+                    Token::from_str(">", Kind::Bracket),
+                ],
+                operands: Vec::new(),
+                comments: Vec::new(),
+            }),
+            _ => None,
+        }
+    }
+
     /// Length of block when tokenized.
     pub fn len(&self) -> usize {
         match &self.content {
@@ -69,75 +230,70 @@ impl Block {
             BlockContent::Error { .. } => 1,
             BlockContent::CString { bytes } => bytes.len() + 1,
             BlockContent::Pointer { .. } => 1,
+            BlockContent::Case { .. } => 1,
             BlockContent::Got { .. } => 1,
             BlockContent::DataStructure { fields, .. } => 2 + fields.len(),
             BlockContent::Bytes { bytes } => (bytes.len() / 32) + 1,
+            BlockContent::Zeroes { .. } => 1,
         }
     }
 
     pub fn tokenize(&self, stream: &mut TokenStream) {
         match &self.content {
             BlockContent::Label { symbol } => {
-                stream.push("\n<", CONFIG.colors.asm.label);
-                stream.inner.extend_from_slice(symbol.name());
-                stream.push(">", CONFIG.colors.asm.label);
+                stream.push("\n<", Kind::Label);
+                stream.push_symbol(symbol.name(), self.addr);
+                stream.push(">", Kind::Label);
             }
             BlockContent::SectionStart { section } => {
-                stream.push("section started", colors::WHITE);
-                stream.push_owned(format!(" {} ", section.name), CONFIG.colors.asm.section);
-                stream.push("{", CONFIG.colors.brackets);
+                stream.push("section started", Kind::Plain);
+                stream.push_owned(format!(" {} ", section.name), Kind::Section);
+                stream.push("{", Kind::Bracket);
                 if section.ident == "UNKNOWN" {
-                    stream.push_owned(format!("{:?}", section.kind), CONFIG.colors.asm.component);
+                    stream.push_owned(format!("{:?}", section.kind), Kind::Component);
                 } else {
-                    stream.push(section.ident, CONFIG.colors.asm.component);
+                    stream.push(section.ident, Kind::Component);
                 }
-                stream.push("} ", CONFIG.colors.brackets);
-                stream.push_owned(format!("{:x}", section.start), colors::GREEN);
-                stream.push("-", CONFIG.colors.delimiter);
-                stream.push_owned(format!("{:x}", section.end), colors::GREEN);
+                stream.push("} ", Kind::Bracket);
+                stream.push_owned(format!("{:x}", section.start), Kind::Segment);
+                stream.push("-", Kind::Delimiter);
+                stream.push_owned(format!("{:x}", section.end), Kind::Segment);
             }
             BlockContent::SectionEnd { section } => {
-                stream.push("section ended", colors::WHITE);
-                stream.push_owned(format!(" {} ", section.name), CONFIG.colors.asm.section);
-                stream.push("{", CONFIG.colors.brackets);
+                stream.push("section ended", Kind::Plain);
+                stream.push_owned(format!(" {} ", section.name), Kind::Section);
+                stream.push("{", Kind::Bracket);
                 if section.ident == "UNKNOWN" {
-                    stream.push_owned(format!("{:?}", section.kind), CONFIG.colors.asm.component);
+                    stream.push_owned(format!("{:?}", section.kind), Kind::Component);
                 } else {
-                    stream.push(section.ident, CONFIG.colors.asm.component);
+                    stream.push(section.ident, Kind::Component);
+                }
+                stream.push("} ", Kind::Bracket);
+                stream.push_owned(format!("{:x}", section.start), Kind::Segment);
+                stream.push("-", Kind::Delimiter);
+                stream.push_owned(format!("{:x}", section.end), Kind::Segment);
+            }
+            BlockContent::Instruction { .. } | BlockContent::Error { .. } => {
+                if let Some(row) = self.row() {
+                    row.tokenize(stream);
                 }
-                stream.push("} ", CONFIG.colors.brackets);
-                stream.push_owned(format!("{:x}", section.start), colors::GREEN);
-                stream.push("-", CONFIG.colors.delimiter);
-                stream.push_owned(format!("{:x}", section.end), colors::GREEN);
-            }
-            BlockContent::Instruction { inst, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
-                stream.inner.extend_from_slice(&inst);
-            }
-            BlockContent::Error { err, bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(bytes.clone(), CONFIG.colors.bytes);
-                stream.push("<", CONFIG.colors.brackets);
-                stream.push_owned(format!("{err:?}"), CONFIG.colors.asm.invalid);
-                stream.push(">", CONFIG.colors.brackets);
             }
             BlockContent::CString { bytes } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
+                address_column(stream, self.addr);
                 let lossy_string = String::from_utf8_lossy(&bytes);
                 let escaped = format!("\"{}\"", lossy_string.escape_debug());
-                stream.push_owned(escaped, CONFIG.colors.asm.string);
+                stream.push_owned(escaped, CONFIG.colors.role_kind("string"));
             }
             BlockContent::Got { symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push("<", CONFIG.colors.asm.label);
+                address_column(stream, self.addr);
+                stream.push("<", Kind::Label);
                 let name = symbol.name();
                 if name.is_empty() {
-                    stream.push("unresolved", CONFIG.colors.asm.invalid);
+                    stream.push("unresolved", Kind::Invalid);
                 } else {
                     stream.inner.extend_from_slice(symbol.name());
                 }
-                stream.push(">", CONFIG.colors.asm.label);
+                stream.push(">", Kind::Label);
             }
             BlockContent::DataStructure { ident, fields } => {
                 // addr  struct Ident {
@@ -146,44 +302,67 @@ impl Block {
                 // addr  }
                 let start_addr = fields[0].0;
                 let end_addr = fields[fields.len() - 1].0;
-                stream.push_owned(format!("{:0>10X}  ", start_addr), CONFIG.colors.address);
-                stream.push("struct ", CONFIG.colors.src.keyword);
-                stream.push(ident, CONFIG.colors.src.tipe);
-                stream.push(" {\n", CONFIG.colors.delimiter);
+                address_column(stream, start_addr);
+                stream.push("struct ", Kind::Keyword);
+                stream.push(ident, Kind::Type);
+                stream.push(" {\n", Kind::Delimiter);
                 for (addr, name, tipe, value) in fields {
-                    stream.push_owned(format!("{:0>10X}  ", addr), CONFIG.colors.address);
-                    stream.push("    ", colors::WHITE);
-                    stream.push(name, CONFIG.colors.src.field);
-                    stream.push(": ", colors::WHITE);
-                    stream.push(tipe, CONFIG.colors.src.tipe);
-                    stream.push(" = ", CONFIG.colors.delimiter);
-                    stream.push_owned(value.clone(), CONFIG.colors.src.constant);
-                    stream.push("\n", colors::WHITE);
+                    address_column(stream, *addr);
+                    stream.push("    ", Kind::Plain);
+                    stream.push(name, Kind::Field);
+                    stream.push(": ", Kind::Plain);
+                    stream.push(tipe, Kind::Type);
+                    stream.push(" = ", Kind::Delimiter);
+                    stream.push_owned(value.clone(), Kind::Constant);
+                    stream.push("\n", Kind::Plain);
                 }
-                stream.push_owned(format!("{:0>10X}  ", end_addr), CONFIG.colors.address);
-                stream.push("}", CONFIG.colors.delimiter);
+                address_column(stream, end_addr);
+                stream.push("}", Kind::Delimiter);
             }
             BlockContent::Pointer { value, symbol, .. } => {
-                stream.push_owned(format!("{:0>10X}  ", self.addr), CONFIG.colors.address);
-                stream.push_owned(format!("{:#x}", value), CONFIG.colors.bytes);
+                address_column(stream, self.addr);
+                let text = format!("{:#x}", value);
+                stream.push_addr(text, Kind::Bytes, *value as usize);
                 if let Some(symbol) = symbol {
-                    stream.push(" <", CONFIG.colors.asm.label);
-                    stream.inner.extend_from_slice(symbol.name());
-                    stream.push(">", CONFIG.colors.asm.label);
+                    stream.push(" <", Kind::Label);
+                    stream.push_symbol(symbol.name(), *value as usize);
+                    stream.push(">", Kind::Label);
+                }
+            }
+            BlockContent::Case {
+                case,
+                target,
+                symbol,
+            } => {
+                address_column(stream, self.addr);
+                stream.push("case ", Kind::Keyword);
+                stream.push_owned(case.to_string(), Kind::Constant);
+                stream.push(" -> ", Kind::Delimiter);
+                stream.push_addr(format!("{target:#x}"), Kind::Address, *target);
+                if let Some(symbol) = symbol {
+                    stream.push(" <", Kind::Label);
+                    stream.push_symbol(symbol.name(), *target);
+                    stream.push(">", Kind::Label);
                 }
             }
             BlockContent::Bytes { bytes } => {
                 let mut off = 0;
                 for chunk in bytes.chunks(32) {
-                    stream.push_owned(format!("{:0>10X}  ", self.addr + off), CONFIG.colors.address);
+                    address_column(stream, self.addr + off);
                     let s = processor_shared::encode_hex_bytes_truncated(chunk, usize::MAX, false);
-                    stream.push_owned(s, CONFIG.colors.bytes);
-                    stream.push("\n", colors::WHITE);
+                    stream.push_owned(s, Kind::Bytes);
+                    stream.push("\n", Kind::Plain);
                     off += chunk.len();
                 }
                 // Pop last newline.
                 stream.inner.pop();
             }
+            BlockContent::Zeroes { len } => {
+                address_column(stream, self.addr);
+                stream.push("zero-filled ", Kind::Keyword);
+                stream.push_owned(format!("{len:#x}"), Kind::Constant);
+                stream.push(" bytes", Kind::Plain);
+            }
         }
     }
 }
@@ -192,15 +371,19 @@ impl Processor {
     /// Use this instead of get_sym_by_addr for any case where a section symbol
     /// might conflict with a label.
     fn get_symbol_by_addr(&self, addr: usize, section: &Section) -> Option<Arc<Symbol>> {
-        if addr == section.start {
-            if let Some(symbol) = self.index.get_sym_by_addr(addr) {
-                if symbol.as_str() == section.name {
-                    return None;
-                }
-            }
+        let symbol = self.index.get_sym_by_addr(addr)?;
+        if addr == section.start && symbol.as_str() == section.name {
+            return None;
         }
 
-        self.index.get_sym_by_addr(addr)
+        Some(self.shown_symbol(addr, symbol))
+    }
+
+    /// Case and target of the jump table entry at `addr`.
+    fn table_entry(&self, addr: usize) -> Option<(usize, usize)> {
+        let code = self.code.read().unwrap();
+        let (table, case) = code.tables.entry(addr)?;
+        Some((case, table.targets[case]))
     }
 
     /// Parse blocks given an address boundary.
@@ -231,7 +414,7 @@ impl Processor {
                 });
 
                 // Empty sections at the same address but won't be accounted for otherwise.
-                if start.bytes().is_empty() {
+                if start.start == start.end {
                     blocks.push(Block {
                         addr,
                         content: BlockContent::SectionEnd {
@@ -259,13 +442,27 @@ impl Processor {
             return blocks;
         }
 
-        match section.kind {
+        if let Some((case, target)) = self.table_entry(addr) {
+            let symbol = self.index.get_sym_by_addr(target);
+            blocks.push(Block {
+                addr,
+                content: BlockContent::Case {
+                    case,
+                    target,
+                    symbol: symbol.map(|symbol| self.shown_symbol(target, symbol)),
+                },
+            });
+            return blocks;
+        }
+
+        match self.section_kind(section) {
             SectionKind::Code => self.parse_code(addr, section, &mut blocks),
             SectionKind::Ptr32 => self.parse_pointer(addr, section, 4, &mut blocks),
             SectionKind::Ptr64 => self.parse_pointer(addr, section, 8, &mut blocks),
             SectionKind::Got32 => self.parse_got(addr, 4, section, &mut blocks),
             SectionKind::Got64 => self.parse_got(addr, 4, section, &mut blocks),
             SectionKind::CString => self.parse_cstring(addr, section, &mut blocks),
+            SectionKind::Data => self.parse_data(addr, section, &mut blocks),
             SectionKind::ExceptionDirEntry => {
                 self.parse_datastructure::<ExceptionDirectoryEntry>(addr, section, &mut blocks);
             }
@@ -285,7 +482,11 @@ impl Processor {
             // As a note, we calculate the byte boundaries in blocks of [`BYTES_BLOCK_SIZE`],
             // so this block can be up to [`BYTES_BLOCK_SIZE`] bytes.
             _ => {
-                let bytes = section.bytes_by_addr(addr, BYTES_BLOCK_SIZE).to_vec();
+                let len = match self.code.read().unwrap().tables.next(addr) {
+                    Some(table) => BYTES_BLOCK_SIZE.min(table - addr),
+                    None => BYTES_BLOCK_SIZE,
+                };
+                let bytes = section.bytes_by_addr(addr, len).to_vec();
                 blocks.push(Block {
                     addr,
                     content: BlockContent::Bytes { bytes },
@@ -324,7 +525,9 @@ impl Processor {
 
     fn parse_pointer(&self, addr: usize, section: &Section, size: usize, blocks: &mut Vec<Block>) {
         let bytes = section.bytes_by_addr(addr, size);
-        let value = if size == 4 {
+        let value = if let Ok(idx) = self.pointers.search(addr) {
+            self.pointers[idx].item as u64
+        } else if size == 4 {
             self.endianness.read_u32_bytes(bytes.try_into().unwrap()) as u64
         } else {
             self.endianness.read_u64_bytes(bytes.try_into().unwrap())
@@ -349,9 +552,161 @@ impl Processor {
         });
     }
 
+    fn parse_data(&self, addr: usize, section: &Section, blocks: &mut Vec<Block>) {
+        if let Some(symbol) = self.get_symbol_by_addr(addr, section) {
+            blocks.push(Block {
+                addr,
+                content: BlockContent::Label { symbol },
+            });
+        }
+
+        let code = self.code.read().unwrap();
+        let content = match self.data_item(addr, section, &code) {
+            (DataItem::Pointer(value), _) => {
+                let symbol = self.index.get_sym_by_addr(value as usize);
+                BlockContent::Pointer {
+                    value,
+                    symbol: symbol.map(|symbol| self.shown_symbol(value as usize, symbol)),
+                }
+            }
+            (DataItem::String, len) => BlockContent::CString {
+                bytes: section.bytes_by_addr(addr, len - 1).to_vec(),
+            },
+            (DataItem::Zeroes, len) => BlockContent::Zeroes { len },
+            (DataItem::Bytes, len) => BlockContent::Bytes {
+                bytes: section.bytes_by_addr(addr, len).to_vec(),
+            },
+        };
+
+        blocks.push(Block { addr, content });
+    }
+
+    /// Width of a pointer given the architecture.
+    fn pointer_width(&self) -> usize {
+        self.arch.address_size().map_or(8, |size| size.bytes() as usize)
+    }
+
+    /// Where a data item starting at `addr` has to end, as items don't run into the next
+    /// symbol or jump table.
+    fn data_limit(&self, addr: usize, section: &Section, code: &Code) -> usize {
+        let mut limit = section.end;
+
+        let idx = self.index.syms.partition_point(|sym| sym.addr <= addr);
+        if let Some(sym) = self.index.syms.get(idx) {
+            limit = limit.min(sym.addr);
+        }
+
+        if let Some(table) = code.tables.next(addr) {
+            limit = limit.min(table);
+        }
+
+        limit
+    }
+
+    /// Value of the pointer at `addr` if it points into one of the loaded sections.
+    fn data_pointer(&self, addr: usize, bytes: &[u8]) -> Option<u64> {
+        let width = self.pointer_width();
+        let value = match self.pointers.search(addr) {
+            Ok(idx) => self.pointers[idx].item as u64,
+            Err(..) => {
+                let bytes = bytes.get(..width)?;
+                if width == 4 {
+                    self.endianness.read_u32_bytes(bytes.try_into().unwrap()) as u64
+                } else {
+                    self.endianness.read_u64_bytes(bytes.try_into().unwrap())
+                }
+            }
+        };
+
+        if value == 0 {
+            return None;
+        }
+
+        let section = self.section_by_addr(value as usize)?;
+        match section.kind {
+            SectionKind::Unloaded | SectionKind::Debug => None,
+            _ => Some(value),
+        }
+    }
+
+    /// Pointer, string or run of zeroes at `addr` and the number of bytes it spans.
+    ///
+    /// Looking for a string means scanning for it's NUL byte, so it's only done when asked.
+    fn typed_data_item(
+        &self,
+        addr: usize,
+        limit: usize,
+        section: &Section,
+        strings: bool,
+    ) -> Option<(DataItem, usize)> {
+        let bytes = section.bytes_by_addr(addr, limit - addr);
+        let width = self.pointer_width();
+
+        if addr % width == 0 && addr + width <= limit {
+            if let Some(value) = self.data_pointer(addr, bytes) {
+                return Some((DataItem::Pointer(value), width));
+            }
+        }
+
+        if strings {
+            let min_len = CONFIG.search.min_string_len.max(1);
+            let text = &bytes[..bytes.len().min(MAX_DATA_STRING_LEN)];
+            if let Some(text) = c_string(text, min_len) {
+                return Some((DataItem::String, text.len() + 1));
+            }
+        }
+
+        // Sections such as the .bss don't store their bytes, the loader zero-fills them.
+        let zeroes = match bytes.iter().position(|&b| b != 0) {
+            Some(len) => len,
+            None => limit - addr,
+        };
+
+        if zeroes >= MIN_ZERO_RUN {
+            return Some((DataItem::Zeroes, zeroes));
+        }
+
+        None
+    }
+
+    /// Item of a data section at `addr` and the number of bytes it spans.
+    ///
+    /// Bytes that aren't recognized as anything are grouped until the next recognized item,
+    /// looking for pointers at aligned addresses and for strings after a NUL byte.
+    fn data_item(&self, addr: usize, section: &Section, code: &Code) -> (DataItem, usize) {
+        let limit = self.data_limit(addr, section, code);
+        if let Some(item) = self.typed_data_item(addr, limit, section, true) {
+            return item;
+        }
+
+        let width = self.pointer_width();
+        let end = limit.min(addr + BYTES_PER_LINE);
+        let mut baddr = addr + 1;
+        while baddr < end {
+            let after_nul = section.bytes_by_addr(baddr - 1, 1) == [0];
+            if (baddr % width == 0 || after_nul)
+                && self.typed_data_item(baddr, limit, section, after_nul).is_some()
+            {
+                break;
+            }
+
+            baddr += 1;
+        }
+
+        (DataItem::Bytes, baddr - addr)
+    }
+
+    /// Characters the bytes of an instruction are padded or cut short to, the column being no
+    /// wider than the longest instruction needs.
+    fn bytes_column_width(&self) -> usize {
+        let width = self.max_instruction_width.min(tokenizing::format().bytes_width);
+        width.max(1) * 3 + 1
+    }
+
     fn parse_code(&self, addr: usize, section: &Section, blocks: &mut Vec<Block>) {
-        let opt_inst = self.instruction_by_addr(addr);
-        let opt_err = self.error_by_addr(addr);
+        let code = self.code.read().unwrap();
+        let opt_inst = code.instruction_by_addr(addr);
+        let opt_err = code.error_by_addr(addr);
 
         if opt_inst.is_some() || opt_err.is_some() {
             if let Some(symbol) = self.get_symbol_by_addr(addr, section) {
@@ -364,22 +719,53 @@ impl Processor {
 
         if let Some(inst) = opt_inst {
             let width = self.instruction_width(&inst);
-            let inst = self.instruction_tokens(&inst, &self.index);
-            let bytes = section.bytes_by_addr(addr, width);
-            let bytes =
-                encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let (mnemonic, operands) = split_mnemonic(self.instruction_tokens(inst, &self.index));
+            let mut comments = Vec::new();
+            if CONFIG.analysis.check_alignment && self.is_misaligned(addr) {
+                let kind = CONFIG.colors.role_kind("invalid");
+                comments.push(Token::from_str("  ; misaligned", kind));
+            }
+            for target in self.overlapping(&code, addr) {
+                comments.push(Token::from_string(
+                    format!("  ; overlaps branch target {target:#x}"),
+                    CONFIG.colors.role_kind("invalid"),
+                ));
+            }
+            if let Some(table) = code.tables.by_jump(addr) {
+                comments.push(Token::from_string(
+                    format!("  ; switch with {} cases", table.targets.len()),
+                    Kind::Comment,
+                ));
+            }
+            if let Some(prototype) = self.called_prototype(&code, addr) {
+                comments.push(Token::from_string(format!("  ; {prototype}"), Kind::Comment));
+            } else if let Some(arg) = self.argument_name(&code, addr) {
+                comments.push(Token::from_string(format!("  ; {arg}"), Kind::Comment));
+            }
+            if let Some(comment) = self.annotations.read().unwrap().comments.get(&addr) {
+                comments.push(Token::from_string(
+                    format!("  ; {comment}"),
+                    CONFIG.colors.role_kind("user"),
+                ));
+            }
+            let bytes = self.bytes_by_addr(section, addr, width);
+            let bytes = encode_hex_bytes_truncated(&bytes, self.bytes_column_width(), true);
 
             blocks.push(Block {
                 addr,
-                content: BlockContent::Instruction { inst, bytes },
+                content: BlockContent::Instruction {
+                    mnemonic,
+                    operands,
+                    comments,
+                    bytes,
+                },
             });
             return;
         }
 
         if let Some(err) = opt_err {
-            let bytes = section.bytes_by_addr(addr, err.size());
-            let bytes =
-                encode_hex_bytes_truncated(&bytes, self.max_instruction_width * 3 + 1, true);
+            let bytes = self.bytes_by_addr(section, addr, err.size());
+            let bytes = encode_hex_bytes_truncated(&bytes, self.bytes_column_width(), true);
 
             blocks.push(Block {
                 addr,
@@ -392,21 +778,25 @@ impl Processor {
         }
 
         // If we don't find any code, find bytes at the boundary.
-        self.parse_bytes(addr, section, blocks);
+        self.parse_bytes(addr, section, &code, blocks);
     }
 
-    fn parse_bytes(&self, addr: usize, section: &Section, blocks: &mut Vec<Block>) {
+    fn parse_bytes(&self, addr: usize, section: &Section, code: &Code, blocks: &mut Vec<Block>) {
         let mut baddr = addr;
         loop {
             if baddr == section.end {
                 break;
             }
 
-            if self.instruction_by_addr(baddr).is_some() {
+            if code.instruction_by_addr(baddr).is_some() {
+                break;
+            }
+
+            if code.error_by_addr(baddr).is_some() {
                 break;
             }
 
-            if self.error_by_addr(baddr).is_some() {
+            if baddr != addr && code.tables.entry(baddr).is_some() {
                 break;
             }
 
@@ -454,18 +844,18 @@ impl Processor {
         }
 
         // Don't bother calculating boundaries for sections that don't contain data at compile
-        // time. Sections like the .bss will be filled by the program loader but there's little
-        // point to reading them here.
-        if section.bytes().is_empty() {
+        // time, unless they hold variables that are worth labeling such as the .bss.
+        if section.bytes().is_empty() && section.kind != SectionKind::Data {
             boundaries.push(section.start);
             boundaries.push(section.end);
             return boundaries;
         }
 
         boundaries.push(section.start);
-        match section.kind {
+        match self.section_kind(section) {
             SectionKind::Code => self.compute_code_boundaries(section, &mut boundaries),
             SectionKind::CString => self.compute_cstring_boundaries(section, &mut boundaries),
+            SectionKind::Data => self.compute_data_boundaries(section, &mut boundaries),
             SectionKind::Ptr32 | SectionKind::Got32 => {
                 let mut addr = section.start;
                 while addr < section.end {
@@ -516,12 +906,19 @@ impl Processor {
                 }
             }
             // For any other section kinds just assume they evenly
-            // split in blocks of [`BYTES_BLOCK_SIZE`].
+            // split in blocks of [`BYTES_BLOCK_SIZE`], besides jump tables.
             _ => {
+                let code = self.code.read().unwrap();
                 let mut addr = section.start;
                 while addr < section.end {
                     boundaries.push(addr);
-                    addr += BYTES_BLOCK_SIZE;
+                    addr = match code.tables.entry(addr) {
+                        Some((table, _)) => addr + table.entry.width(),
+                        None => match code.tables.next(addr) {
+                            Some(table) => table.min(addr + BYTES_BLOCK_SIZE),
+                            None => addr + BYTES_BLOCK_SIZE,
+                        },
+                    };
                 }
             }
         }
@@ -530,20 +927,45 @@ impl Processor {
     }
 
     fn compute_code_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
-        let mut addr = section.start;
+        let code = self.code.read().unwrap();
+        self.code_boundaries(&code, section.start, section.end, boundaries);
+    }
+
+    /// Boundaries of the blocks in `start..end` of a code section, used to update the listing
+    /// after part of a section is decoded again.
+    pub fn compute_range_boundaries(&self, start: usize, end: usize) -> Vec<usize> {
+        let mut boundaries = Vec::new();
+        if let Some(section) = self.section_by_addr(start) {
+            if self.section_is_code(section) {
+                let code = self.code.read().unwrap();
+                self.code_boundaries(&code, start, end.min(section.end), &mut boundaries);
+            }
+        }
+
+        boundaries
+    }
+
+    fn code_boundaries(&self, code: &Code, start: usize, end: usize, boundaries: &mut Vec<usize>) {
+        let mut addr = start;
 
         loop {
-            if addr == section.end {
+            if addr >= end {
                 break;
             }
 
-            if let Some(inst) = self.instruction_by_addr(addr) {
+            if let Some((table, _)) = code.tables.entry(addr) {
+                boundaries.push(addr);
+                addr += table.entry.width();
+                continue;
+            }
+
+            if let Some(inst) = code.instruction_by_addr(addr) {
                 boundaries.push(addr);
                 addr += self.instruction_width(inst);
                 continue;
             }
 
-            if let Some(err) = self.error_by_addr(addr) {
+            if let Some(err) = code.error_by_addr(addr) {
                 boundaries.push(addr);
                 addr += err.size();
                 continue;
@@ -551,15 +973,19 @@ impl Processor {
 
             let mut baddr = addr;
             loop {
-                if baddr == section.end {
+                if baddr >= end {
                     break;
                 }
 
-                if self.instruction_by_addr(baddr).is_some() {
+                if code.instruction_by_addr(baddr).is_some() {
                     break;
                 }
 
-                if self.error_by_addr(baddr).is_some() {
+                if code.error_by_addr(baddr).is_some() {
+                    break;
+                }
+
+                if code.tables.entry(baddr).is_some() {
                     break;
                 }
 
@@ -574,6 +1000,19 @@ impl Processor {
         }
     }
 
+    fn compute_data_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
+        let code = self.code.read().unwrap();
+        let mut addr = section.start;
+
+        while addr < section.end {
+            boundaries.push(addr);
+            addr += match code.tables.entry(addr) {
+                Some((table, _)) => table.entry.width(),
+                None => self.data_item(addr, section, &code).1,
+            };
+        }
+    }
+
     fn compute_cstring_boundaries(&self, section: &Section, boundaries: &mut Vec<usize>) {
         let mut start_off = 0;
         for (idx, &byte) in section.bytes().iter().enumerate() {
@@ -588,3 +1027,60 @@ impl Processor {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(tokens: &[Token]) -> String {
+        tokens.iter().map(|token| &token.text as &str).collect()
+    }
+
+    #[test]
+    fn mnemonic_split() {
+        let (mnemonic, operands) = split_mnemonic(vec![
+            Token::from_str("mov", Kind::Mnemonic),
+            Token::from_str(" ", Kind::Plain),
+            Token::from_str("eax", Kind::Register),
+        ]);
+        assert_eq!(text(&mnemonic), "mov");
+        assert_eq!(text(&operands), "eax");
+
+        let (mnemonic, operands) = split_mnemonic(vec![
+            Token::from_str("stc ", Kind::Mnemonic),
+            Token::from_str("p1", Kind::Register),
+        ]);
+        assert_eq!(text(&mnemonic), "stc");
+        assert_eq!(text(&operands), "p1");
+
+        let (mnemonic, operands) = split_mnemonic(vec![Token::from_str("ret", Kind::Mnemonic)]);
+        assert_eq!(text(&mnemonic), "ret");
+        assert!(operands.is_empty());
+    }
+
+    #[test]
+    fn columns() {
+        let row = Row {
+            addr: 0x1000,
+            bytes: "89 c8 ".to_string(),
+            mnemonic: vec![Token::from_str("mov", Kind::Mnemonic)],
+            operands: vec![Token::from_str("eax, ecx", Kind::Plain)],
+            comments: vec![Token::from_str("  ; note", Kind::Comment)],
+        };
+
+        let mut stream = TokenStream::new();
+        stream.format = tokenizing::Format::DEFAULT;
+        let instruction = row.tokenize(&mut stream);
+        assert_eq!(text(&stream.inner[instruction]), "mov     eax, ecx");
+        assert_eq!(
+            stream.to_string(),
+            format!("0000001000  89 c8 mov     eax, ecx{}  ; note", " ".repeat(24))
+        );
+
+        let mut stream = TokenStream::new();
+        stream.format.opcode_bytes = false;
+        stream.format.mnemonic_width = 0;
+        row.tokenize(&mut stream);
+        assert!(stream.to_string().starts_with("0000001000  mov eax, ecx"));
+    }
+}
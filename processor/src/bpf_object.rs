@@ -0,0 +1,116 @@
+//! Linking of BPF objects, whose programs refer to maps, globals and each other through
+//! relocations that are only applied once they're loaded into the kernel.
+
+use memmap2::{Mmap, MmapMut};
+use processor_shared::{PhysAddr, Section, SectionKind};
+
+/// Opcode of `lddw`, which relocations point at a map or global.
+const LDDW: u8 = 0x18;
+
+/// Opcode of `call`, which relocations point at another function if it's source register is set.
+const CALL: u8 = 0x85;
+
+/// Apply the `relocations` of `(address, target)` to a copy of the code sections, which are
+/// backed by the returned memory afterwards instead of the file.
+pub(crate) fn relocate(
+    sections: &mut [Section],
+    relocations: &[(PhysAddr, PhysAddr)],
+) -> std::io::Result<Mmap> {
+    let code: Vec<usize> = (0..sections.len())
+        .filter(|&idx| sections[idx].kind == SectionKind::Code)
+        .collect();
+
+    let len = code.iter().map(|&idx| sections[idx].bytes().len()).sum::<usize>();
+    let mut memory = MmapMut::map_anon(len.max(1))?;
+
+    let mut offsets = Vec::with_capacity(code.len());
+    let mut offset = 0;
+    for &idx in code.iter() {
+        let bytes = sections[idx].bytes();
+        memory[offset..offset + bytes.len()].copy_from_slice(bytes);
+        offsets.push(offset);
+        offset += bytes.len();
+    }
+
+    for &(addr, target) in relocations {
+        let found = code.iter().zip(offsets.iter()).find(|(&idx, _)| {
+            sections[idx].start <= addr && addr < sections[idx].start + sections[idx].bytes().len()
+        });
+
+        if let Some((&idx, &base)) = found {
+            let section = &sections[idx];
+            let offset = base + addr - section.start;
+            apply(&mut memory[offset..base + section.bytes().len()], addr, target);
+        }
+    }
+
+    let memory = memory.make_read_only()?;
+    let bytes: &'static [u8] = unsafe { std::mem::transmute(&memory[..]) };
+    for (&idx, &offset) in code.iter().zip(offsets.iter()) {
+        let section = &sections[idx];
+        let len = section.bytes().len();
+        sections[idx] = Section::new(
+            section.name.clone(),
+            section.ident,
+            section.kind.clone(),
+            &bytes[offset..offset + len],
+            section.start,
+            section.end,
+        );
+    }
+
+    Ok(memory)
+}
+
+/// Point the instruction at `addr` to `target`.
+fn apply(inst: &mut [u8], addr: PhysAddr, target: PhysAddr) {
+    if inst.len() < 8 {
+        return;
+    }
+
+    let imm = i32::from_le_bytes([inst[4], inst[5], inst[6], inst[7]]) as isize;
+    match inst[0] {
+        LDDW if inst.len() >= 16 => {
+            // the immediate holds the offset into the global
+            let value = target.wrapping_add_signed(imm) as u64;
+            inst[4..8].copy_from_slice(&(value as u32).to_le_bytes());
+            inst[12..16].copy_from_slice(&((value >> 32) as u32).to_le_bytes());
+        }
+        CALL if inst[1] >> 4 == 1 => {
+            // the immediate holds the offset in instructions from the symbol, minus one
+            let callee = target.wrapping_add_signed((imm + 1) * 8);
+            let offset = (callee as isize - (addr as isize + 8)) / 8;
+            inst[4..8].copy_from_slice(&(offset as i32).to_le_bytes());
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relocations() {
+        // lddw r1, 0x10 into .rodata at 0x2000
+        let mut lddw = [0x18, 0x01, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        apply(&mut lddw, 0x1000, 0x2000);
+        assert_eq!(lddw[4..8], [0x10, 0x20, 0, 0]);
+        assert_eq!(lddw[12..16], [0, 0, 0, 0]);
+
+        // call to the third instruction of .text at 0x1100
+        let mut call = [0x85, 0x10, 0, 0, 0x01, 0, 0, 0];
+        apply(&mut call, 0x1000, 0x1100);
+        assert_eq!(i32::from_le_bytes([call[4], call[5], call[6], call[7]]), 33);
+
+        // call to a global function
+        let mut call = [0x85, 0x10, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        apply(&mut call, 0x1000, 0x1008);
+        assert_eq!(i32::from_le_bytes([call[4], call[5], call[6], call[7]]), 0);
+
+        // helpers aren't relocated
+        let mut helper = [0x85, 0, 0, 0, 0x01, 0, 0, 0];
+        apply(&mut helper, 0x1000, 0x1100);
+        assert_eq!(helper[4], 0x01);
+    }
+}
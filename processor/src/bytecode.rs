@@ -0,0 +1,390 @@
+//! Bytecode of Android apps and Java programs. DEX and class files store the code of each method
+//! apart from the rest of the file, which is copied into a code section per file. APKs and JARs
+//! are loaded as all of the DEX or class files they contain.
+
+use crate::{class, dex, zip, Error, Headers, Isa, Loading, Processor, Stage};
+use debugvault::Index;
+use memmap2::{Mmap, MmapMut};
+use object::Endianness;
+use processor_shared::{AddressMap, PhysAddr, Section, SectionKind, Segment};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::PathBuf;
+
+/// Address the code of the first file is placed at.
+const BASE: PhysAddr = 0x10000;
+
+/// Alignment of the code of methods, as switches of Java bytecode pad their operands to a
+/// multiple of four from the start of their method.
+const METHOD_ALIGN: usize = 4;
+
+/// Alignment of the code section of each file.
+const SECTION_ALIGN: usize = 0x1000;
+
+/// Format of a file holding bytecode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Dex,
+    Class,
+    /// Zip archive, such as an APK or JAR.
+    Archive,
+}
+
+impl Format {
+    pub(crate) fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"dex\n") {
+            return Some(Self::Dex);
+        }
+
+        // Fat Mach-O's share the magic, with the number of architectures in place of the
+        // version, which is far below the version of the first Java release.
+        if bytes.starts_with(&[0xca, 0xfe, 0xba, 0xbe]) {
+            let major = bytes.get(6..8).map_or(0, |v| u16::from_be_bytes([v[0], v[1]]));
+            if major >= 45 {
+                return Some(Self::Class);
+            }
+        }
+
+        if bytes.starts_with(b"PK\x03\x04") {
+            return Some(Self::Archive);
+        }
+
+        None
+    }
+}
+
+/// Constant pools of the files code is loaded from.
+pub(crate) struct Pools<P> {
+    /// Pools by the address of their file's code section, sorted by address.
+    pools: Vec<(PhysAddr, P)>,
+}
+
+impl<P> Pools<P> {
+    /// Pool of the file whose code `addr` is in.
+    pub(crate) fn at(&self, addr: PhysAddr) -> &P {
+        let idx = self.pools.partition_point(|(start, _)| *start <= addr);
+        &self.pools[idx.saturating_sub(1)].1
+    }
+}
+
+/// Method of a DEX or class file.
+pub(crate) struct Method<'a> {
+    /// Fully qualified name with the types of it's parameters, e.g.
+    /// `com.example.Main.run(int, java.lang.String)`.
+    pub name: String,
+    pub code: &'a [u8],
+}
+
+/// DEX or class file.
+pub(crate) struct Program<'a, P> {
+    pub pool: P,
+    pub methods: Vec<Method<'a>>,
+}
+
+/// Constant pool referring to methods by name.
+pub(crate) trait Link {
+    /// Resolve the methods the pool refers to, given the address of each method by name.
+    fn link(&mut self, methods: &HashMap<&str, PhysAddr>);
+}
+
+impl Link for dalvik::Pool {
+    fn link(&mut self, methods: &HashMap<&str, PhysAddr>) {
+        for (idx, name) in self.methods.iter().enumerate() {
+            if let Some(&addr) = methods.get(&**name) {
+                self.code.insert(idx as u32, addr);
+            }
+        }
+    }
+}
+
+impl Link for jvm::Pool {
+    fn link(&mut self, methods: &HashMap<&str, PhysAddr>) {
+        for (idx, constant) in self.constants.iter().enumerate() {
+            if let Some(jvm::Constant::Method(name)) = constant {
+                if let Some(&addr) = methods.get(&**name) {
+                    self.code.insert(idx as u16, addr);
+                }
+            }
+        }
+    }
+}
+
+/// Code of files laid out one after another, backed by `memory`.
+struct Layout<P> {
+    memory: Mmap,
+    sections: Vec<Section>,
+    segments: Vec<Segment>,
+    /// Names of the methods, sorted by address.
+    methods: Vec<(PhysAddr, String)>,
+    pools: Pools<P>,
+}
+
+/// Place the methods of each of the `programs` in a code section named after it's file.
+fn layout<P: Link>(programs: Vec<(String, Program<P>)>) -> std::io::Result<Layout<P>> {
+    let mut image = Vec::new();
+    let mut files = Vec::with_capacity(programs.len());
+    let mut methods = Vec::new();
+    let mut pools = Vec::with_capacity(programs.len());
+
+    for (name, program) in programs {
+        image.resize(image.len().next_multiple_of(SECTION_ALIGN), 0);
+        let start = image.len();
+
+        // padding is zeroed, which is a `nop` in both Dalvik and Java bytecode
+        for method in program.methods {
+            image.resize(image.len().next_multiple_of(METHOD_ALIGN), 0);
+            methods.push((BASE + image.len(), method.name));
+            image.extend_from_slice(method.code);
+        }
+
+        files.push((name, start, image.len()));
+        pools.push((BASE + start, program.pool));
+    }
+
+    // Methods are called by name, which may be in any of the files.
+    let by_name = methods.iter().map(|(addr, name)| (name.as_str(), *addr)).collect();
+    for (_, pool) in pools.iter_mut() {
+        pool.link(&by_name);
+    }
+
+    let mut memory = MmapMut::map_anon(image.len().max(1))?;
+    memory[..image.len()].copy_from_slice(&image);
+    let memory = memory.make_read_only()?;
+    let bytes: &'static [u8] = unsafe { std::mem::transmute(&memory[..]) };
+
+    let mut sections = Vec::with_capacity(files.len());
+    let mut segments = Vec::with_capacity(files.len());
+    for (name, start, end) in files {
+        segments.push(Segment {
+            name: format!("{name} (generated)"),
+            start: BASE + start,
+            end: BASE + end,
+        });
+        sections.push(Section::new(
+            name,
+            "GENERATED",
+            SectionKind::Code,
+            &bytes[start..end],
+            BASE + start,
+            BASE + end,
+        ));
+    }
+
+    Ok(Layout {
+        memory,
+        sections,
+        segments,
+        methods,
+        pools: Pools { pools },
+    })
+}
+
+/// Parse each of the `files` of `format` with `parse`.
+fn parse_all<'a, P>(
+    files: &'a [(String, Cow<'static, [u8]>)],
+    format: Format,
+    parse: fn(&'a [u8]) -> Option<Program<'a, P>>,
+) -> Result<Vec<(String, Program<'a, P>)>, Error> {
+    files
+        .iter()
+        .filter(|(_, bytes)| Format::detect(bytes) == Some(format))
+        .map(|(name, bytes)| match parse(bytes) {
+            Some(program) => Ok((name.clone(), program)),
+            None => Err(Error::InvalidBytecode(name.clone())),
+        })
+        .collect()
+}
+
+/// Fully qualified name of a type given it's `descriptor`, e.g. `java.lang.String[]` for
+/// `[Ljava/lang/String;`.
+pub(crate) fn java_type(descriptor: &str) -> String {
+    let base = descriptor.trim_start_matches('[');
+    let dims = descriptor.len() - base.len();
+    let name = match base {
+        "B" => "byte",
+        "C" => "char",
+        "D" => "double",
+        "F" => "float",
+        "I" => "int",
+        "J" => "long",
+        "S" => "short",
+        "Z" => "boolean",
+        "V" => "void",
+        _ => base.strip_prefix('L').and_then(|b| b.strip_suffix(';')).unwrap_or(base),
+    };
+
+    name.replace('/', ".") + &"[]".repeat(dims)
+}
+
+/// Parameter types and return type of a method given it's `descriptor`, e.g. `int` and
+/// `java.lang.String` returning `void` for `(ILjava/lang/String;)V`.
+pub(crate) fn method_type(descriptor: &str) -> (Vec<String>, String) {
+    let (mut params, ret) = descriptor
+        .strip_prefix('(')
+        .and_then(|descriptor| descriptor.split_once(')'))
+        .unwrap_or(("", descriptor));
+
+    let mut types = Vec::new();
+    while !params.is_empty() {
+        let dims = params.len() - params.trim_start_matches('[').len();
+        let len = match params[dims..].chars().next() {
+            Some('L') => params[dims..].find(';').map_or(params.len() - dims, |end| end + 1),
+            Some(c) => c.len_utf8(),
+            None => 0,
+        };
+
+        types.push(java_type(&params[..dims + len]));
+        params = &params[dims + len..];
+    }
+
+    (types, java_type(ret))
+}
+
+impl Processor {
+    /// Load the DEX file, class file, APK or JAR at `path`, which is of the given `format`.
+    pub(crate) fn parse_bytecode(
+        path: PathBuf,
+        file: File,
+        mmap: Mmap,
+        format: Format,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        let now = std::time::Instant::now();
+        let started = loading.start(Stage::Headers)?;
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        let files: Vec<(String, Cow<[u8]>)> = match format {
+            Format::Archive => {
+                let bytecode = |name: &str| name.ends_with(".dex") || name.ends_with(".class");
+                let files = zip::extract(binary, bytecode).ok_or(Error::InvalidBytecode(name))?;
+                files.into_iter().map(|(name, bytes)| (name, Cow::Owned(bytes))).collect()
+            }
+            Format::Dex | Format::Class => vec![(name, Cow::Borrowed(binary))],
+        };
+
+        // APKs are made of DEX files, JARs of a class file per class.
+        let is_dex = files.iter().any(|(_, bytes)| Format::detect(bytes) == Some(Format::Dex));
+        let (memory, sections, segments, methods, isa, endianness) = if is_dex {
+            let programs = parse_all(&files, Format::Dex, dex::parse)?;
+            if programs.is_empty() {
+                return Err(Error::UnknownFormat);
+            }
+
+            let layout = layout(programs).map_err(Error::IO)?;
+            let isa = Isa::Dalvik(layout.pools);
+            let (sections, segments) = (layout.sections, layout.segments);
+            (layout.memory, sections, segments, layout.methods, isa, Endianness::Little)
+        } else {
+            let programs = parse_all(&files, Format::Class, class::parse)?;
+            if programs.is_empty() {
+                return Err(Error::UnknownFormat);
+            }
+
+            let layout = layout(programs).map_err(Error::IO)?;
+            let isa = Isa::Jvm(layout.pools);
+            let (sections, segments) = (layout.sections, layout.segments);
+            (layout.memory, sections, segments, layout.methods, isa, Endianness::Big)
+        };
+
+        loading.finish(
+            Stage::Headers,
+            started,
+            format!("{} methods in {} files", methods.len(), sections.len()),
+        );
+
+        let started = loading.start(Stage::Symbols)?;
+
+        // Java programs start at their `main` method, Android apps have no single entrypoint.
+        let entrypoint = methods
+            .iter()
+            .find(|(_, name)| name.ends_with(".main(java.lang.String[])"))
+            .map_or(0, |(addr, _)| *addr);
+
+        let mut index = Index::default();
+        index.insert_identified(methods);
+
+        loading.finish(
+            Stage::Symbols,
+            started,
+            format!("{} named functions", index.named_funcs_count()),
+        );
+
+        log::complex!(
+            w "[processor::parse_bytecode] loading ",
+            w format!("{path:?}"),
+            w " as ",
+            b isa.name(),
+            w " in ",
+            y sections.len().to_string(),
+            w " sections.",
+        );
+
+        let headers = Headers {
+            entrypoint,
+            entrypoints: Vec::new(),
+            modes: Vec::new(),
+            thumb_functions: Vec::new(),
+            index,
+            sections,
+            segments,
+            pointers: AddressMap::default(),
+            exports: AddressMap::default(),
+            isa,
+            endianness,
+            convention: None,
+        };
+
+        Self::analyze(path, file, mmap, vec![memory], headers, now, loading)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(Format::detect(b"dex\n035\0"), Some(Format::Dex));
+        assert_eq!(Format::detect(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52]), Some(Format::Class));
+        assert_eq!(Format::detect(&[0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 2]), None);
+        assert_eq!(Format::detect(b"PK\x03\x04"), Some(Format::Archive));
+        assert_eq!(Format::detect(b"\x7fELF"), None);
+    }
+
+    #[test]
+    fn descriptors() {
+        assert_eq!(java_type("I"), "int");
+        assert_eq!(java_type("[[J"), "long[][]");
+        assert_eq!(java_type("Ljava/lang/String;"), "java.lang.String");
+        assert_eq!(java_type("[Landroid/os/Bundle;"), "android.os.Bundle[]");
+
+        let (params, ret) = method_type("(I[Ljava/lang/String;Z)V");
+        assert_eq!(params, ["int", "java.lang.String[]", "boolean"]);
+        assert_eq!(ret, "void");
+        assert_eq!(method_type("()[B"), (Vec::new(), "byte[]".to_string()));
+    }
+
+    #[test]
+    fn linking() {
+        static CODE: [u8; 6] = [0xb1, 0x2a, 0xb7, 0x00, 0x01, 0xb1];
+
+        let mut pool = jvm::Pool::default();
+        pool.constants.push(None);
+        pool.constants.push(Some(jvm::Constant::Method("Main.run()".into())));
+        let program = Program {
+            pool,
+            methods: vec![
+                Method { name: "Main.main(java.lang.String[])".to_string(), code: &CODE[..1] },
+                Method { name: "Main.run()".to_string(), code: &CODE[1..] },
+            ],
+        };
+
+        let layout = layout(vec![("Main.class".to_string(), program)]).unwrap();
+        assert_eq!(layout.methods[0].0, BASE);
+        assert_eq!(layout.methods[1].0, BASE + 4);
+        assert_eq!(layout.sections[0].bytes(), [0xb1, 0, 0, 0, 0x2a, 0xb7, 0x00, 0x01, 0xb1]);
+        assert_eq!(layout.pools.at(BASE + 4).code.get(&1), Some(&(BASE + 4)));
+    }
+}
@@ -0,0 +1,69 @@
+//! Calls between functions, derived from the cross references of call instructions.
+
+use crate::{Processor, XrefKind};
+
+/// Directed graph over functions, identified by their index in [`debugvault::Index::syms`].
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// Functions called by each function, sorted and deduplicated.
+    callees: Vec<Vec<usize>>,
+    /// Functions calling each function, sorted and deduplicated.
+    callers: Vec<Vec<usize>>,
+}
+
+impl CallGraph {
+    /// Number of functions in the graph, including those without any calls.
+    pub fn len(&self) -> usize {
+        self.callees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.callees.is_empty()
+    }
+
+    pub fn callees(&self, func: usize) -> &[usize] {
+        self.callees.get(func).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    pub fn callers(&self, func: usize) -> &[usize] {
+        self.callers.get(func).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl Processor {
+    /// Build the graph of calls between known functions.
+    ///
+    /// Calls are attributed to the closest function preceding the call instruction, calls to
+    /// addresses without a symbol are left out.
+    pub fn call_graph(&self) -> CallGraph {
+        let syms = &self.index.syms;
+        let mut graph = CallGraph {
+            callees: vec![Vec::new(); syms.len()],
+            callers: vec![Vec::new(); syms.len()],
+        };
+
+        let code = self.code.read().unwrap();
+        for xref in code.xrefs.iter().filter(|xref| xref.kind == XrefKind::Call) {
+            let caller = match syms.search(xref.from) {
+                Ok(idx) => idx,
+                Err(0) => continue,
+                Err(idx) => idx - 1,
+            };
+
+            let callee = match syms.search(xref.to) {
+                Ok(idx) => idx,
+                Err(..) => continue,
+            };
+
+            graph.callees[caller].push(callee);
+            graph.callers[callee].push(caller);
+        }
+
+        for edges in graph.callees.iter_mut().chain(graph.callers.iter_mut()) {
+            edges.sort_unstable();
+            edges.dedup();
+        }
+
+        graph
+    }
+}
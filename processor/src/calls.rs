@@ -0,0 +1,283 @@
+//! Arguments of calls to functions with a known prototype, found by which instructions before
+//! the call write the registers the calling convention passes arguments in.
+
+use crate::{Code, Instruction, Processor, XrefKind};
+use debugvault::types::Prototype;
+use decoder::Flow;
+use object::Architecture;
+use processor_shared::PhysAddr;
+
+/// Most instructions between an argument being set up and the call it's passed to.
+const MAX_ARGUMENT_DISTANCE: usize = 16;
+
+/// Instructions that call a function, even if their target isn't known.
+const CALLS: &[&str] = &[
+    "call", "bl", "blr", "blx", "jal", "jalr", "bal", "c.jal", "c.jalr", "rcall", "icall", "eicall",
+];
+
+/// Where a function's arguments are passed, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallingConvention {
+    /// System V AMD64, used by x86_64 everywhere but Windows.
+    SysV,
+    /// Microsoft x64.
+    Win64,
+    /// 32-bit x86, where all arguments are pushed right to left.
+    Cdecl,
+    /// 32-bit ARM.
+    Aapcs,
+    /// 64-bit ARM.
+    Aapcs64,
+    RiscV,
+    /// 32-bit MIPS.
+    O32,
+    /// 64-bit MIPS.
+    N64,
+    /// avr-gcc, where arguments take a pair of registers each, counting down from r25.
+    Avr,
+    /// BPF, where helpers and functions take up to five arguments in r1 to r5.
+    Bpf,
+}
+
+impl CallingConvention {
+    /// Convention functions of `arch` use unless told otherwise, None if it isn't known.
+    pub fn native(arch: Architecture, windows: bool) -> Option<Self> {
+        Some(match arch {
+            Architecture::X86_64 if windows => Self::Win64,
+            Architecture::X86_64 => Self::SysV,
+            Architecture::I386 | Architecture::X86_64_X32 => Self::Cdecl,
+            Architecture::Arm => Self::Aapcs,
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => Self::Aapcs64,
+            Architecture::Riscv32 | Architecture::Riscv64 => Self::RiscV,
+            Architecture::Mips => Self::O32,
+            Architecture::Mips64 => Self::N64,
+            Architecture::Avr => Self::Avr,
+            Architecture::Bpf => Self::Bpf,
+            _ => return None,
+        })
+    }
+
+    /// Registers arguments are passed in, each by all names that can write it.
+    pub(crate) fn registers(self) -> &'static [&'static [&'static str]] {
+        match self {
+            Self::SysV => &[
+                &["rdi", "edi", "di", "dil"],
+                &["rsi", "esi", "si", "sil"],
+                &["rdx", "edx", "dx", "dl"],
+                &["rcx", "ecx", "cx", "cl"],
+                &["r8", "r8d", "r8w", "r8b"],
+                &["r9", "r9d", "r9w", "r9b"],
+            ],
+            Self::Win64 => &[
+                &["rcx", "ecx", "cx", "cl"],
+                &["rdx", "edx", "dx", "dl"],
+                &["r8", "r8d", "r8w", "r8b"],
+                &["r9", "r9d", "r9w", "r9b"],
+            ],
+            Self::Cdecl => &[],
+            Self::Aapcs => &[&["r0", "a1"], &["r1", "a2"], &["r2", "a3"], &["r3", "a4"]],
+            Self::Aapcs64 => &[
+                &["x0", "w0"],
+                &["x1", "w1"],
+                &["x2", "w2"],
+                &["x3", "w3"],
+                &["x4", "w4"],
+                &["x5", "w5"],
+                &["x6", "w6"],
+                &["x7", "w7"],
+            ],
+            Self::RiscV => &[
+                &["a0", "x10"],
+                &["a1", "x11"],
+                &["a2", "x12"],
+                &["a3", "x13"],
+                &["a4", "x14"],
+                &["a5", "x15"],
+                &["a6", "x16"],
+                &["a7", "x17"],
+            ],
+            Self::O32 => &[&["a0", "$a0"], &["a1", "$a1"], &["a2", "$a2"], &["a3", "$a3"]],
+            Self::N64 => &[
+                &["a0", "$a0"],
+                &["a1", "$a1"],
+                &["a2", "$a2"],
+                &["a3", "$a3"],
+                &["a4", "$a4"],
+                &["a5", "$a5"],
+                &["a6", "$a6"],
+                &["a7", "$a7"],
+            ],
+            Self::Avr => &[
+                &["r24", "r25"],
+                &["r22", "r23"],
+                &["r20", "r21"],
+                &["r18", "r19"],
+                &["r16", "r17"],
+                &["r14", "r15"],
+                &["r12", "r13"],
+                &["r10", "r11"],
+                &["r8", "r9"],
+            ],
+            Self::Bpf => &[&["r1"], &["r2"], &["r3"], &["r4"], &["r5"]],
+        }
+    }
+
+    /// Index of the argument passed in `register`.
+    pub fn argument(self, register: &str) -> Option<usize> {
+        self.registers().iter().position(|names| names.contains(&register))
+    }
+
+    /// Whether arguments are pushed on the stack instead of passed in registers.
+    pub fn is_stack(self) -> bool {
+        self == Self::Cdecl
+    }
+}
+
+/// Lowercase mnemonic of an instruction's text and it's first operand.
+fn split_operands(text: &str) -> (String, &str) {
+    let text = text.trim();
+    let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let first = operands.split(',').next().unwrap_or("").trim();
+    (mnemonic.to_ascii_lowercase(), first)
+}
+
+/// Whether an instruction with `mnemonic` writes it's first operand, which isn't the case for
+/// comparisons, pushes and stores.
+fn writes_first_operand(mnemonic: &str) -> bool {
+    let reads = matches!(
+        mnemonic,
+        "cmp" | "cmn" | "test" | "tst" | "teq" | "bt" | "push" | "sb" | "sh" | "sw" | "sd"
+            | "cp" | "cpc" | "cpi" | "cpse" | "sbrc" | "sbrs"
+    );
+    let stores = mnemonic.starts_with("st")
+        || mnemonic.starts_with("c.s")
+        || mnemonic.starts_with("fs")
+        || mnemonic.starts_with("sw")
+        || mnemonic.starts_with("sd");
+
+    !reads && !stores
+}
+
+/// Whether the instruction at `addr` with `mnemonic` calls a function, together with the
+/// function if it's known.
+fn call_target(code: &Code, addr: PhysAddr, mnemonic: &str) -> Option<Option<PhysAddr>> {
+    match code.xrefs.from(addr).iter().find(|xref| xref.kind == XrefKind::Call) {
+        Some(xref) => Some(Some(xref.to)),
+        None if CALLS.contains(&mnemonic) => Some(None),
+        None => None,
+    }
+}
+
+impl Processor {
+    /// Prototype of the function at `addr`, either given by the user or from the debug info.
+    pub fn prototype(&self, addr: PhysAddr) -> Option<Prototype> {
+        if let Some(decl) = self.annotations.read().unwrap().prototypes.get(&addr) {
+            return Prototype::parse(decl);
+        }
+
+        self.index.get_prototype(addr).cloned()
+    }
+
+    /// Text of `inst`, as it's tokens don't say which is the mnemonic or an operand.
+    pub(crate) fn instruction_text(&self, inst: &Instruction) -> String {
+        let tokens = self.instruction_tokens(inst, &self.index);
+        String::from_iter(tokens.iter().map(|token| &token.text[..]))
+    }
+
+    /// Prototype of the function the instruction at `addr` calls.
+    pub(crate) fn called_prototype(&self, code: &Code, addr: PhysAddr) -> Option<Prototype> {
+        let target = code.xrefs.from(addr).iter().find(|xref| xref.kind == XrefKind::Call)?.to;
+        self.prototype(target)
+    }
+
+    /// Name of the argument the instruction at `addr` sets up for the call following it, if
+    /// the called function has a known prototype.
+    pub(crate) fn argument_name(&self, code: &Code, addr: PhysAddr) -> Option<String> {
+        let convention = self.convention?;
+        let inst = code.instruction_by_addr(addr)?;
+        if (self.instruction_flow)(inst) != Flow::Next {
+            return None;
+        }
+
+        let text = self.instruction_text(inst);
+        let (mnemonic, dest) = split_operands(&text);
+        if call_target(code, addr, &mnemonic).is_some() {
+            return None;
+        }
+
+        // Arguments on the stack are found by how many are pushed after them.
+        let pushed = convention.is_stack() && mnemonic == "push";
+        let arg = if pushed {
+            None
+        } else if writes_first_operand(&mnemonic) {
+            Some(convention.argument(dest)?)
+        } else {
+            return None;
+        };
+
+        let mut pushes = 0;
+        let mut next = addr + self.instruction_width(inst);
+        for _ in 0..MAX_ARGUMENT_DISTANCE {
+            // The call might be reached without passing through the argument.
+            if code.xrefs.to(next).iter().any(|xref| xref.kind == XrefKind::Jump) {
+                return None;
+            }
+
+            let inst = code.instruction_by_addr(next)?;
+            let text = self.instruction_text(inst);
+            let (mnemonic, dest) = split_operands(&text);
+
+            if let Some(target) = call_target(code, next, &mnemonic) {
+                let prototype = self.prototype(target?)?;
+                let idx = arg.unwrap_or(pushes);
+                return (idx < prototype.params.len()).then(|| prototype.arg_name(idx));
+            }
+
+            if (self.instruction_flow)(inst) != Flow::Next {
+                return None;
+            }
+
+            match arg {
+                None if mnemonic == "push" => pushes += 1,
+                // The argument is overwritten before the call.
+                Some(arg)
+                    if writes_first_operand(&mnemonic) && convention.argument(dest) == Some(arg) =>
+                {
+                    return None;
+                }
+                _ => {}
+            }
+
+            next += self.instruction_width(inst);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arguments() {
+        let sysv = CallingConvention::native(Architecture::X86_64, false).unwrap();
+        let win64 = CallingConvention::native(Architecture::X86_64, true).unwrap();
+        assert_eq!(sysv.argument("edi"), Some(0));
+        assert_eq!(sysv.argument("r9b"), Some(5));
+        assert_eq!(win64.argument("rcx"), Some(0));
+        assert_eq!(win64.argument("rdi"), None);
+        assert!(CallingConvention::native(Architecture::I386, false).unwrap().is_stack());
+
+        let avr = CallingConvention::native(Architecture::Avr, false).unwrap();
+        assert_eq!(avr.argument("r24"), Some(0));
+        assert_eq!(avr.argument("r23"), Some(1));
+        assert!(!writes_first_operand("cpi"));
+
+        assert_eq!(split_operands("mov edi, 0x1"), ("mov".to_string(), "edi"));
+        assert_eq!(split_operands("ret"), ("ret".to_string(), ""));
+        assert!(writes_first_operand("lea"));
+        assert!(!writes_first_operand("str"));
+        assert!(!writes_first_operand("cmp"));
+    }
+}
@@ -0,0 +1,303 @@
+//! Control flow within a function, split into basic blocks.
+
+use crate::{Processor, XrefKind};
+use config::CONFIG;
+use decoder::Flow;
+use processor_shared::PhysAddr;
+use std::collections::{BTreeMap, BTreeSet};
+use tokenizing::{Kind, Token};
+
+/// Most instructions followed in a single function.
+const MAX_INSTRUCTIONS: usize = 65_536;
+
+/// How execution gets from one block to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Conditional branch that's taken.
+    True,
+    /// Conditional branch that isn't taken, continuing with the next instruction.
+    False,
+    /// Unconditional jumps, cases of jump tables and running into the next block.
+    Always,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    /// Index of the block the edge leaves.
+    pub from: usize,
+    /// Index of the block the edge enters.
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// Instructions that are always executed one after the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicBlock {
+    /// Addresses of the block's instructions, in order.
+    pub instructions: Vec<PhysAddr>,
+}
+
+impl BasicBlock {
+    pub fn start(&self) -> PhysAddr {
+        self.instructions[0]
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ControlFlowGraph {
+    /// Sorted by address, starting with the function's entry.
+    pub blocks: Vec<BasicBlock>,
+    /// Sorted by the block they leave.
+    pub edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    /// Edges leaving block `idx`.
+    pub fn successors(&self, idx: usize) -> impl Iterator<Item = &Edge> {
+        let start = self.edges.partition_point(|edge| edge.from < idx);
+        self.edges[start..].iter().take_while(move |edge| edge.from == idx)
+    }
+
+    /// Block containing the instruction at `addr`.
+    pub fn block_by_addr(&self, addr: PhysAddr) -> Option<usize> {
+        self.blocks.iter().position(|block| block.instructions.contains(&addr))
+    }
+}
+
+/// An instruction reached from the function's entry.
+#[derive(Debug)]
+struct Step {
+    width: usize,
+    flow: Flow,
+    /// Jump targets inside of the function.
+    targets: Vec<PhysAddr>,
+}
+
+/// Split the instructions reached into blocks and connect them.
+fn build(steps: &BTreeMap<PhysAddr, Step>) -> ControlFlowGraph {
+    let mut leaders = BTreeSet::new();
+    for (&addr, step) in steps {
+        leaders.extend(step.targets.iter().copied());
+        if step.flow != Flow::Next {
+            leaders.insert(addr + step.width);
+        }
+    }
+
+    let mut graph = ControlFlowGraph::default();
+    let mut next = None;
+    for (&addr, step) in steps {
+        match graph.blocks.last_mut() {
+            Some(block) if next == Some(addr) && !leaders.contains(&addr) => {
+                block.instructions.push(addr);
+            }
+            _ => graph.blocks.push(BasicBlock {
+                instructions: vec![addr],
+            }),
+        }
+
+        next = Some(addr + step.width);
+    }
+
+    let block_at = |addr: PhysAddr| {
+        graph.blocks.binary_search_by_key(&addr, BasicBlock::start).ok()
+    };
+
+    let mut edges = Vec::new();
+    for (from, block) in graph.blocks.iter().enumerate() {
+        let last = *block.instructions.last().unwrap();
+        let step = &steps[&last];
+        let fallthrough = block_at(last + step.width);
+
+        let (taken, not_taken) = match step.flow {
+            Flow::Next => (EdgeKind::Always, Some(EdgeKind::Always)),
+            Flow::Branch => (EdgeKind::True, Some(EdgeKind::False)),
+            Flow::Jump => (EdgeKind::Always, None),
+            Flow::Return => continue,
+        };
+
+        for to in step.targets.iter().filter_map(|&target| block_at(target)) {
+            edges.push(Edge {
+                from,
+                to,
+                kind: taken,
+            });
+        }
+
+        if let (Some(kind), Some(to)) = (not_taken, fallthrough) {
+            edges.push(Edge {
+                from,
+                to,
+                kind,
+            });
+        }
+    }
+
+    edges.dedup();
+    graph.edges = edges;
+    graph
+}
+
+impl Processor {
+    /// Control flow of the function starting at `func`.
+    ///
+    /// Instructions are followed from the entry until they leave the function, which ends at
+    /// the next symbol. Returns `None` if there isn't an instruction at `func`.
+    pub fn cfg(&self, func: PhysAddr) -> Option<ControlFlowGraph> {
+        let syms = &self.index.syms.mapping;
+        let next_sym = syms.get(syms.partition_point(|sym| sym.addr <= func));
+        let section_end = self.section_by_addr(func)?.end;
+        let end = next_sym.map_or(section_end, |sym| sym.addr.min(section_end));
+
+        let code = self.code.read().unwrap();
+        code.instruction_by_addr(func)?;
+
+        let mut steps = BTreeMap::new();
+        let mut queue = vec![func];
+
+        while let Some(addr) = queue.pop() {
+            if steps.len() == MAX_INSTRUCTIONS || steps.contains_key(&addr) {
+                continue;
+            }
+
+            let inst = match code.instruction_by_addr(addr) {
+                Some(inst) => inst,
+                None => continue,
+            };
+
+            let width = self.instruction_width(inst);
+            let mut targets: Vec<PhysAddr> = code
+                .xrefs
+                .from(addr)
+                .iter()
+                .filter(|xref| xref.kind == XrefKind::Jump)
+                .map(|xref| xref.to)
+                .filter(|to| (func..end).contains(to))
+                .collect();
+
+            targets.sort_unstable();
+            targets.dedup();
+
+            // Architectures that don't describe their flow still reference where they jump to.
+            let flow = match (self.instruction_flow)(inst) {
+                Flow::Next if !targets.is_empty() => Flow::Branch,
+                flow => flow,
+            };
+
+            queue.extend(targets.iter().copied());
+
+            if matches!(flow, Flow::Next | Flow::Branch) && addr + width < end {
+                queue.push(addr + width);
+            }
+
+            steps.insert(
+                addr,
+                Step {
+                    width,
+                    flow,
+                    targets,
+                },
+            );
+        }
+
+        Some(build(&steps))
+    }
+
+    /// Tokens of `block`, a line for each instruction.
+    pub fn basic_block_tokens(&self, block: &BasicBlock) -> Vec<Token> {
+        let code = self.code.read().unwrap();
+        let annotations = self.annotations.read().unwrap();
+        let mut tokens = Vec::new();
+
+        for (idx, &addr) in block.instructions.iter().enumerate() {
+            if idx != 0 {
+                tokens.push(Token::from_str("\n", Kind::Plain));
+            }
+
+            tokens.push(Token::from_string(format!("{addr:0>10X}  "), Kind::Address));
+            if let Some(inst) = code.instruction_by_addr(addr) {
+                tokens.extend(self.instruction_tokens(inst, &self.index));
+            }
+
+            if let Some(comment) = annotations.comments.get(&addr) {
+                tokens.push(Token::from_string(
+                    format!("  ; {comment}"),
+                    CONFIG.colors.role_kind("user"),
+                ));
+            }
+        }
+
+        tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(width: usize, flow: Flow, targets: &[PhysAddr]) -> Step {
+        Step {
+            width,
+            flow,
+            targets: targets.to_vec(),
+        }
+    }
+
+    #[test]
+    fn branches() {
+        // 0x0: test; 0x2: jz 0x8; 0x4: inc; 0x6: jmp 0xa; 0x8: dec; 0xa: ret
+        let steps = BTreeMap::from([
+            (0x0, step(2, Flow::Next, &[])),
+            (0x2, step(2, Flow::Branch, &[0x8])),
+            (0x4, step(2, Flow::Next, &[])),
+            (0x6, step(2, Flow::Jump, &[0xa])),
+            (0x8, step(2, Flow::Next, &[])),
+            (0xa, step(1, Flow::Return, &[])),
+        ]);
+
+        let graph = build(&steps);
+        let starts: Vec<PhysAddr> = graph.blocks.iter().map(BasicBlock::start).collect();
+        assert_eq!(starts, [0x0, 0x4, 0x8, 0xa]);
+        assert_eq!(graph.blocks[0].instructions, [0x0, 0x2]);
+
+        let edges: Vec<(usize, usize, EdgeKind)> =
+            graph.edges.iter().map(|edge| (edge.from, edge.to, edge.kind)).collect();
+        assert_eq!(
+            edges,
+            [
+                (0, 2, EdgeKind::True),
+                (0, 1, EdgeKind::False),
+                (1, 3, EdgeKind::Always),
+                (2, 3, EdgeKind::Always),
+            ]
+        );
+
+        assert_eq!(graph.successors(0).count(), 2);
+        assert_eq!(graph.successors(3).count(), 0);
+        assert_eq!(graph.block_by_addr(0x6), Some(1));
+    }
+
+    #[test]
+    fn loops() {
+        // 0x0: mov; 0x2: dec; 0x4: jnz 0x2; 0x6: ret
+        let steps = BTreeMap::from([
+            (0x0, step(2, Flow::Next, &[])),
+            (0x2, step(2, Flow::Next, &[])),
+            (0x4, step(2, Flow::Branch, &[0x2])),
+            (0x6, step(1, Flow::Return, &[])),
+        ]);
+
+        let graph = build(&steps);
+        assert_eq!(graph.blocks.len(), 3);
+
+        let edges: Vec<(usize, usize, EdgeKind)> =
+            graph.edges.iter().map(|edge| (edge.from, edge.to, edge.kind)).collect();
+        assert_eq!(
+            edges,
+            [
+                (0, 1, EdgeKind::Always),
+                (1, 1, EdgeKind::True),
+                (1, 2, EdgeKind::False),
+            ]
+        );
+    }
+}
@@ -0,0 +1,608 @@
+//! Exploit mitigations a binary was built with, as read from it's headers and symbols.
+
+use crate::report::json_array;
+use crate::Processor;
+use object::read::elf::{Dyn, ElfFile, FileHeader, ProgramHeader};
+use object::read::macho::{MachHeader, MachOFile};
+use object::read::pe::{ImageNtHeaders, ImageOptionalHeader, PeFile};
+use object::LittleEndian as LE;
+use object::{elf, macho, pe};
+use object::{BinaryFormat, Object, ObjectSymbol};
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+use std::mem::offset_of;
+
+/// `IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS`, debug directory entry with more DLL
+/// characteristics.
+const IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS: u32 = 20;
+
+/// Extended DLL characteristic of PE's compatible with CET's shadow stack.
+const IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT: u32 = 0x1;
+
+/// `WIN_CERTIFICATE` type of an Authenticode signature.
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 2;
+
+/// Encoded object identifier of SHA-256, as found in signatures using it.
+const SHA256_OID: &[u8] = &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+/// Names of the DLL characteristics of PE's by their flag.
+const DLL_CHARACTERISTICS: [(u16, &str); 11] = [
+    (pe::IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA, "HIGH_ENTROPY_VA"),
+    (pe::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE, "DYNAMIC_BASE"),
+    (pe::IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY, "FORCE_INTEGRITY"),
+    (pe::IMAGE_DLLCHARACTERISTICS_NX_COMPAT, "NX_COMPAT"),
+    (pe::IMAGE_DLLCHARACTERISTICS_NO_ISOLATION, "NO_ISOLATION"),
+    (pe::IMAGE_DLLCHARACTERISTICS_NO_SEH, "NO_SEH"),
+    (pe::IMAGE_DLLCHARACTERISTICS_NO_BIND, "NO_BIND"),
+    (pe::IMAGE_DLLCHARACTERISTICS_APPCONTAINER, "APPCONTAINER"),
+    (pe::IMAGE_DLLCHARACTERISTICS_WDM_DRIVER, "WDM_DRIVER"),
+    (pe::IMAGE_DLLCHARACTERISTICS_GUARD_CF, "GUARD_CF"),
+    (pe::IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE, "TERMINAL_SERVER_AWARE"),
+];
+
+/// How much of an ELF's data written by the dynamic linker is made read-only afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relro {
+    None,
+    /// Everything but the GOT entries of functions, which are resolved as they're first called.
+    Partial,
+    /// Everything, as all symbols are resolved when the binary is loaded.
+    Full,
+}
+
+impl Relro {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Partial => "partial",
+            Self::Full => "full",
+        }
+    }
+}
+
+/// Whether the binary can be loaded at a random address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pie {
+    No,
+    Yes,
+    /// Shared library, which is always position independent.
+    Library,
+}
+
+impl Pie {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::No => "no",
+            Self::Yes => "yes",
+            Self::Library => "library",
+        }
+    }
+}
+
+/// Signature embedded in the binary, Authenticode for PE's and a code signature for Mach-O's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeSignature {
+    /// Bytes the signature takes up.
+    pub size: usize,
+    /// Whether the SHA-256 digest that's signed is the binary's, None if it isn't an Authenticode
+    /// signature using SHA-256. The certificates aren't checked.
+    pub digest_matches: Option<bool>,
+}
+
+/// Exploit mitigations of an ELF, PE or Mach-O. Fields that don't apply to the format are left
+/// at their default.
+#[derive(Debug, Clone)]
+pub struct Security {
+    pub format: BinaryFormat,
+    /// Only for ELF's.
+    pub relro: Relro,
+    /// Whether functions check a stack canary before returning.
+    pub canary: bool,
+    /// Whether the stack, and for PE's any data, isn't executable.
+    pub nx: bool,
+    pub pie: Pie,
+    /// Functions replaced by variants checking the size of their buffers, as done by
+    /// `_FORTIFY_SOURCE`, sorted. Not for PE's.
+    pub fortified: Vec<String>,
+    /// Whether indirect calls are checked by Control Flow Guard, only for PE's.
+    pub cfg: bool,
+    /// Whether indirect branches have to land on an `endbr`, only for ELF's.
+    pub ibt: bool,
+    /// Whether the binary is compatible with CET's shadow stack, not for Mach-O's.
+    pub shadow_stack: bool,
+    /// Not for ELF's, which aren't signed.
+    pub signature: Option<CodeSignature>,
+    /// Names of the DLL characteristics that are set, only for PE's.
+    pub dll_characteristics: Vec<&'static str>,
+}
+
+impl Security {
+    fn new(format: BinaryFormat) -> Self {
+        Self {
+            format,
+            relro: Relro::None,
+            canary: false,
+            nx: false,
+            pie: Pie::No,
+            fortified: Vec::new(),
+            cfg: false,
+            ibt: false,
+            shadow_stack: false,
+            signature: None,
+            dll_characteristics: Vec::new(),
+        }
+    }
+}
+
+/// Whether the symbols include those of the stack protector, along with the functions replaced
+/// by their fortified variants, e.g. `memcpy` for `__memcpy_chk`.
+fn canary_and_fortified<'a>(names: impl Iterator<Item = &'a str>) -> (bool, Vec<String>) {
+    let mut canary = false;
+    let mut fortified = Vec::new();
+
+    for name in names.filter(|name| name.starts_with("__")) {
+        // Mach-O's prefix symbols with another underscore.
+        let name = name.trim_start_matches('_');
+        match name.strip_suffix("_chk") {
+            Some(func) if !func.is_empty() => fortified.push(func.to_string()),
+            _ => canary |= matches!(name, "stack_chk_fail" | "stack_chk_guard"),
+        }
+    }
+
+    fortified.sort_unstable();
+    fortified.dedup();
+    (canary, fortified)
+}
+
+fn symbol_names<'data: 'file, 'file>(obj: &'file impl Object<'data, 'file>) -> Vec<&'data str> {
+    obj.symbols()
+        .chain(obj.dynamic_symbols())
+        .filter_map(|symbol| symbol.name().ok())
+        .collect()
+}
+
+fn elf_security<Elf: FileHeader>(elf: &ElfFile<Elf>) -> Security {
+    let endian = elf.endian();
+    let data = elf.data();
+    let mut security = Security::new(BinaryFormat::Elf);
+    (security.canary, security.fortified) = canary_and_fortified(symbol_names(elf).into_iter());
+
+    let mut relro = false;
+    let mut bind_now = false;
+    let mut interpreter = false;
+    let mut pie_flag = false;
+
+    for segment in elf.raw_segments() {
+        match segment.p_type(endian) {
+            elf::PT_GNU_RELRO => relro = true,
+            elf::PT_GNU_STACK => security.nx = segment.p_flags(endian) & elf::PF_X == 0,
+            elf::PT_INTERP => interpreter = true,
+            elf::PT_DYNAMIC => {
+                let entries = segment.dynamic(endian, data).ok().flatten().unwrap_or_default();
+                for entry in entries {
+                    let value = entry.val32(endian).unwrap_or_default();
+                    match entry.tag32(endian) {
+                        Some(elf::DT_BIND_NOW) => bind_now = true,
+                        Some(elf::DT_FLAGS) => bind_now |= value & elf::DF_BIND_NOW != 0,
+                        Some(elf::DT_FLAGS_1) => {
+                            bind_now |= value & elf::DF_1_NOW != 0;
+                            pie_flag |= value & elf::DF_1_PIE != 0;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            // Notes are usually in both segments, the features are those of the whole binary.
+            elf::PT_NOTE | elf::PT_GNU_PROPERTY => {
+                let mut notes = match segment.notes(endian, data) {
+                    Ok(Some(notes)) => notes,
+                    _ => continue,
+                };
+
+                while let Ok(Some(note)) = notes.next() {
+                    let mut properties = match note.gnu_properties(endian) {
+                        Some(properties) => properties,
+                        None => continue,
+                    };
+
+                    while let Ok(Some(property)) = properties.next() {
+                        if property.pr_type() != elf::GNU_PROPERTY_X86_FEATURE_1_AND {
+                            continue;
+                        }
+
+                        let features = property.data_u32(endian).unwrap_or_default();
+                        security.ibt = features & elf::GNU_PROPERTY_X86_FEATURE_1_IBT != 0;
+                        security.shadow_stack =
+                            features & elf::GNU_PROPERTY_X86_FEATURE_1_SHSTK != 0;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    security.relro = match (relro, bind_now) {
+        (false, _) => Relro::None,
+        (true, false) => Relro::Partial,
+        (true, true) => Relro::Full,
+    };
+
+    // Executables that are position independent are shared objects with an interpreter.
+    security.pie = match elf.raw_header().e_type(endian) {
+        elf::ET_DYN if interpreter || pie_flag => Pie::Yes,
+        elf::ET_DYN => Pie::Library,
+        _ => Pie::No,
+    };
+
+    security
+}
+
+/// Read a little-endian word of 8 bytes for 64-bit PE's, otherwise 4 bytes.
+fn read_word(bytes: &[u8], offset: usize, is_64: bool) -> Option<u64> {
+    match is_64 {
+        true => Some(u64::from_le_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?)),
+        false => Some(u32::from_le_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?) as u64),
+    }
+}
+
+/// Security cookie used by `/GS` and the Control Flow Guard flags from the load config.
+fn load_config<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> Option<(u64, u32)> {
+    let dir = pe.data_directory(pe::IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG)?;
+    let bytes = pe.section_table().pe_data_at(pe.data(), dir.virtual_address.get(LE))?;
+
+    // The directory grew over time, the fields it's too small for are zero.
+    let size = read_word(bytes, 0, false)? as usize;
+    let bytes = bytes.get(..size)?;
+
+    let is_64 = pe.nt_headers().is_type_64();
+    let (cookie, guard_flags) = match is_64 {
+        true => (
+            offset_of!(pe::ImageLoadConfigDirectory64, security_cookie),
+            offset_of!(pe::ImageLoadConfigDirectory64, guard_flags),
+        ),
+        false => (
+            offset_of!(pe::ImageLoadConfigDirectory32, security_cookie),
+            offset_of!(pe::ImageLoadConfigDirectory32, guard_flags),
+        ),
+    };
+
+    Some((
+        read_word(bytes, cookie, is_64).unwrap_or_default(),
+        read_word(bytes, guard_flags, false).unwrap_or_default() as u32,
+    ))
+}
+
+/// Whether the debug directory marks the PE as compatible with CET's shadow stack.
+fn cet_compatible<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> bool {
+    let data = pe.data();
+    let bytes = pe
+        .data_directory(pe::IMAGE_DIRECTORY_ENTRY_DEBUG)
+        .and_then(|dir| dir.data(data, &pe.section_table()).ok())
+        .unwrap_or_default();
+
+    let count = bytes.len() / std::mem::size_of::<pe::ImageDebugDirectory>();
+    let entries = match object::pod::slice_from_bytes::<pe::ImageDebugDirectory>(bytes, count) {
+        Ok((entries, _)) => entries,
+        Err(_) => return false,
+    };
+
+    entries
+        .iter()
+        .filter(|entry| entry.typ.get(LE) == IMAGE_DEBUG_TYPE_EX_DLLCHARACTERISTICS)
+        .filter_map(|entry| {
+            let offset = entry.pointer_to_raw_data.get(LE) as usize;
+            read_word(data, offset, false)
+        })
+        .any(|flags| flags as u32 & IMAGE_DLLCHARACTERISTICS_EX_CET_COMPAT != 0)
+}
+
+/// Authenticode signature of the PE `file`, checking the SHA-256 digest it signs against the
+/// file's.
+fn authenticode<Pe: ImageNtHeaders>(pe: &PeFile<Pe>, file: &[u8]) -> Option<CodeSignature> {
+    let dir = pe.data_directory(pe::IMAGE_DIRECTORY_ENTRY_SECURITY)?;
+
+    // Unlike other directories, it's address is an offset into the file.
+    let start = dir.virtual_address.get(LE) as usize;
+    let end = start.checked_add(dir.size.get(LE) as usize)?;
+    let cert = file.get(start..end).filter(|cert| cert.len() > 8)?;
+
+    let cert_type = u16::from_le_bytes([cert[6], cert[7]]);
+    let blob = &cert[8..];
+    if cert_type != WIN_CERT_TYPE_PKCS_SIGNED_DATA || !contains(blob, SHA256_OID) {
+        return Some(CodeSignature {
+            size: cert.len(),
+            digest_matches: None,
+        });
+    }
+
+    // The digest leaves out the checksum, the entry of the security directory and the
+    // certificates, which can only be written once the rest of the file is.
+    let optional_header = pe.dos_header().nt_headers_offset() as usize + 4 + 20;
+    let checksum = optional_header + 64;
+    let directories = optional_header + if pe.nt_headers().is_type_64() { 112 } else { 96 };
+    let security = directories + pe::IMAGE_DIRECTORY_ENTRY_SECURITY * 8;
+    if !(checksum + 4 <= security && security + 8 <= start) {
+        return None;
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(&file[..checksum]);
+    hasher.update(&file[checksum + 4..security]);
+    hasher.update(&file[security + 8..start]);
+    hasher.update(&file[end..]);
+
+    // The digest is signed as an octet string.
+    let mut digest = vec![0x04, 0x20];
+    digest.extend_from_slice(&hasher.finalize());
+
+    Some(CodeSignature {
+        size: cert.len(),
+        digest_matches: Some(contains(blob, &digest)),
+    })
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+fn pe_security<Pe: ImageNtHeaders>(pe: &PeFile<Pe>, file: &[u8]) -> Security {
+    let mut security = Security::new(BinaryFormat::Pe);
+    let flags = pe.nt_headers().optional_header().dll_characteristics();
+    let relocs_stripped =
+        pe.nt_headers().file_header().characteristics.get(LE) & pe::IMAGE_FILE_RELOCS_STRIPPED;
+
+    security.dll_characteristics = DLL_CHARACTERISTICS
+        .iter()
+        .filter(|&&(flag, _)| flags & flag != 0)
+        .map(|&(_, name)| name)
+        .collect();
+
+    let (cookie, guard_flags) = load_config(pe).unwrap_or_default();
+    security.canary = cookie != 0;
+    security.nx = flags & pe::IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0;
+    security.pie = match flags & pe::IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0 {
+        true if relocs_stripped == 0 => Pie::Yes,
+        _ => Pie::No,
+    };
+    security.cfg = flags & pe::IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0
+        && guard_flags & pe::IMAGE_GUARD_CF_INSTRUMENTED != 0;
+    security.shadow_stack = cet_compatible(pe);
+    security.signature = authenticode(pe, file);
+    security
+}
+
+fn macho_security<Mach: MachHeader>(macho: &MachOFile<Mach>) -> Security {
+    let endian = macho.endian();
+    let header = macho.raw_header();
+    let flags = header.flags(endian);
+    let mut security = Security::new(BinaryFormat::MachO);
+    (security.canary, security.fortified) = canary_and_fortified(symbol_names(macho).into_iter());
+
+    security.nx = flags & macho::MH_ALLOW_STACK_EXECUTION == 0;
+    security.pie = match header.filetype(endian) {
+        macho::MH_DYLIB | macho::MH_BUNDLE => Pie::Library,
+        _ if flags & macho::MH_PIE != 0 => Pie::Yes,
+        _ => Pie::No,
+    };
+
+    if let Ok(mut commands) = header.load_commands(endian, macho.data(), 0) {
+        while let Ok(Some(command)) = commands.next() {
+            if command.cmd() != macho::LC_CODE_SIGNATURE {
+                continue;
+            }
+
+            if let Ok(signature) = command.data::<macho::LinkeditDataCommand<Mach::Endian>>() {
+                security.signature = Some(CodeSignature {
+                    size: signature.datasize.get(endian) as usize,
+                    digest_matches: None,
+                });
+            }
+        }
+    }
+
+    security
+}
+
+/// Exploit mitigations of the object in `file`, None if it isn't an ELF, PE or Mach-O.
+fn checksec(file: &[u8]) -> Option<Security> {
+    match object::File::parse(file).ok()? {
+        object::File::Elf32(elf) => Some(elf_security(&elf)),
+        object::File::Elf64(elf) => Some(elf_security(&elf)),
+        object::File::Pe32(pe) => Some(pe_security(&pe, file)),
+        object::File::Pe64(pe) => Some(pe_security(&pe, file)),
+        object::File::MachO32(macho) => Some(macho_security(&macho)),
+        object::File::MachO64(macho) => Some(macho_security(&macho)),
+        _ => None,
+    }
+}
+
+fn yes_no(enabled: bool) -> &'static str {
+    if enabled {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+impl Security {
+    fn is_elf(&self) -> bool {
+        self.format == BinaryFormat::Elf
+    }
+
+    fn is_pe(&self) -> bool {
+        self.format == BinaryFormat::Pe
+    }
+
+    fn format_name(&self) -> &'static str {
+        match self.format {
+            BinaryFormat::Elf => "elf",
+            BinaryFormat::Pe => "pe",
+            _ => "macho",
+        }
+    }
+
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{{\n  \"format\": \"{}\"", self.format_name())?;
+
+        match self.is_elf() {
+            true => write!(w, ",\n  \"relro\": \"{}\"", self.relro.name())?,
+            false => w.write_all(b",\n  \"relro\": null")?,
+        }
+
+        write!(w, ",\n  \"canary\": {}", self.canary)?;
+        write!(w, ",\n  \"nx\": {}", self.nx)?;
+        write!(w, ",\n  \"pie\": \"{}\"", self.pie.name())?;
+
+        json_array(w, "fortified", &self.fortified, |w, func| write!(w, "\"{func}\""))?;
+
+        let write_opt = |w: &mut W, key: &str, value: Option<bool>| match value {
+            Some(value) => write!(w, ",\n  \"{key}\": {value}"),
+            None => write!(w, ",\n  \"{key}\": null"),
+        };
+
+        write_opt(w, "cfg", self.is_pe().then_some(self.cfg))?;
+        write_opt(w, "ibt", self.is_elf().then_some(self.ibt))?;
+        let shadow_stack = (self.format != BinaryFormat::MachO).then_some(self.shadow_stack);
+        write_opt(w, "shadow_stack", shadow_stack)?;
+        write_opt(w, "signed", (!self.is_elf()).then_some(self.signature.is_some()))?;
+        write_opt(w, "digest_matches", self.signature.and_then(|sig| sig.digest_matches))?;
+
+        json_array(w, "dll_characteristics", &self.dll_characteristics, |w, name| {
+            write!(w, "\"{name}\"")
+        })?;
+
+        w.write_all(b"\n}\n")?;
+        w.flush()
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        if self.is_elf() {
+            writeln!(w, "relro     {}", self.relro.name())?;
+        }
+
+        writeln!(w, "canary    {}", yes_no(self.canary))?;
+        writeln!(w, "nx        {}", yes_no(self.nx))?;
+        writeln!(w, "pie       {}", self.pie.name())?;
+
+        if !self.is_pe() {
+            match self.fortified.len() {
+                0 => writeln!(w, "fortify   no")?,
+                len => {
+                    writeln!(w, "fortify   yes, {len} functions: {}", self.fortified.join(", "))?
+                }
+            }
+        }
+
+        if self.is_pe() {
+            writeln!(w, "cfg       {}", yes_no(self.cfg))?;
+        }
+
+        if self.format != BinaryFormat::MachO {
+            let features: Vec<&str> = [(self.ibt, "ibt"), (self.shadow_stack, "shadow stack")]
+                .into_iter()
+                .filter_map(|(enabled, name)| enabled.then_some(name))
+                .collect();
+
+            match features.is_empty() {
+                true => writeln!(w, "cet       no")?,
+                false => writeln!(w, "cet       {}", features.join(", "))?,
+            }
+        }
+
+        if !self.is_elf() {
+            match self.signature {
+                None => writeln!(w, "signed    no")?,
+                Some(CodeSignature { size, digest_matches: None }) => {
+                    writeln!(w, "signed    yes, {size} bytes")?
+                }
+                Some(CodeSignature { size, digest_matches: Some(true) }) => {
+                    writeln!(w, "signed    yes, {size} bytes, digest matches")?
+                }
+                Some(CodeSignature { size, digest_matches: Some(false) }) => {
+                    writeln!(w, "signed    yes, {size} bytes, digest doesn't match")?
+                }
+            }
+        }
+
+        if self.is_pe() {
+            match self.dll_characteristics.is_empty() {
+                true => writeln!(w, "dllchars  none")?,
+                false => writeln!(w, "dllchars  {}", self.dll_characteristics.join(", "))?,
+            }
+        }
+
+        w.flush()
+    }
+}
+
+impl Processor {
+    /// Exploit mitigations the binary was built with, None if it isn't an ELF, PE or Mach-O.
+    pub fn checksec(&self) -> Option<Security> {
+        checksec(self.file_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols() {
+        let names = [
+            "main",
+            "__stack_chk_fail",
+            "__memcpy_chk",
+            "___strcpy_chk",
+            "__memcpy_chk",
+            "__chk",
+            "stack_chk_fail",
+        ];
+
+        let (canary, fortified) = canary_and_fortified(names.into_iter());
+        assert!(canary);
+        assert_eq!(fortified, ["memcpy", "strcpy"]);
+
+        // Symbols that only look like the stack protector's aren't it.
+        let (canary, fortified) = canary_and_fortified(["stack_chk_fail"].into_iter());
+        assert!(!canary);
+        assert!(fortified.is_empty());
+    }
+
+    #[test]
+    fn words() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(read_word(&bytes, 0, false), Some(0x0403_0201));
+        assert_eq!(read_word(&bytes, 0, true), Some(0x0807_0605_0403_0201));
+        assert_eq!(read_word(&bytes, 4, false), Some(0x0807_0605));
+        assert_eq!(read_word(&bytes, 4, true), None);
+
+        assert!(contains(&bytes, &[3, 4, 5]));
+        assert!(!contains(&bytes, &[3, 5]));
+        assert!(!contains(&bytes[..2], &bytes));
+    }
+
+    #[test]
+    fn pe_text() {
+        let mut security = Security::new(BinaryFormat::Pe);
+        security.nx = true;
+        security.pie = Pie::Yes;
+        security.signature = Some(CodeSignature {
+            size: 0x2000,
+            digest_matches: Some(false),
+        });
+        security.dll_characteristics = vec!["DYNAMIC_BASE", "NX_COMPAT"];
+
+        let mut text = Vec::new();
+        security.write_text(&mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            "canary    no\n\
+             nx        yes\n\
+             pie       yes\n\
+             cfg       no\n\
+             cet       no\n\
+             signed    yes, 8192 bytes, digest doesn't match\n\
+             dllchars  DYNAMIC_BASE, NX_COMPAT\n"
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Parsing of class files, which hold the Java bytecode of a class.
+
+use crate::bytecode::{java_type, method_type, Method, Program};
+use jvm::{Constant, Pool};
+
+/// Tag of entries referring to fields.
+const FIELD_REF: u8 = 9;
+
+/// Entry of the constant pool as it's stored, referring to other entries by their index.
+#[derive(Clone, Copy)]
+enum Entry<'a> {
+    Utf8(&'a [u8]),
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class(u16),
+    String(u16),
+    /// Field, method or interface method by it's tag, class and name and type.
+    Member(u8, u16, u16),
+    NameAndType(u16, u16),
+    /// Method handle by the member it refers to.
+    MethodHandle(u16),
+    MethodType(u16),
+    /// Dynamically computed constant or call site by it's name and type.
+    Dynamic(u16),
+    /// Modules and packages, which aren't referred to by code.
+    Other,
+}
+
+/// Big-endian reader of a class file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.offset..self.offset.checked_add(len)?)?;
+        self.offset += len;
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.bytes(1).map(|bytes| bytes[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.bytes(2).map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some((self.u32()? as u64) << 32 | self.u32()? as u64)
+    }
+}
+
+/// Constant pool of a class file, as stored.
+struct Entries<'a>(Vec<Option<Entry<'a>>>);
+
+impl<'a> Entries<'a> {
+    fn get(&self, idx: u16) -> Option<Entry<'a>> {
+        self.0.get(idx as usize).copied().flatten()
+    }
+
+    fn utf8(&self, idx: u16) -> Option<String> {
+        match self.get(idx)? {
+            Entry::Utf8(bytes) => Some(String::from_utf8_lossy(bytes).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Name of the class at `idx`, which is either a binary name or the descriptor of an
+    /// array.
+    fn class(&self, idx: u16) -> Option<String> {
+        let name = match self.get(idx)? {
+            Entry::Class(name) => self.utf8(name)?,
+            _ => return None,
+        };
+
+        Some(match name.starts_with('[') {
+            true => java_type(&name),
+            false => name.replace('/', "."),
+        })
+    }
+
+    /// Name of the name and type at `idx`, with the parameters of it's descriptor if it's a
+    /// method.
+    fn name_and_type(&self, idx: u16) -> Option<String> {
+        let (name, descriptor) = match self.get(idx)? {
+            Entry::NameAndType(name, descriptor) => (self.utf8(name)?, self.utf8(descriptor)?),
+            _ => return None,
+        };
+
+        Some(match descriptor.starts_with('(') {
+            true => format!("{name}({})", method_type(&descriptor).0.join(", ")),
+            false => name,
+        })
+    }
+
+    /// Fully qualified name of a field or method.
+    fn member(&self, class: u16, nat: u16) -> Option<String> {
+        Some(format!("{}.{}", self.class(class)?, self.name_and_type(nat)?))
+    }
+
+    /// Constant at `idx` as shown by instructions referring to it.
+    fn constant(&self, idx: u16) -> Option<Constant> {
+        Some(match self.get(idx)? {
+            Entry::Integer(imm) => Constant::Integer(imm),
+            Entry::Float(imm) => Constant::Float(imm),
+            Entry::Long(imm) => Constant::Long(imm),
+            Entry::Double(imm) => Constant::Double(imm),
+            Entry::Class(..) => Constant::Class(self.class(idx)?.into()),
+            Entry::String(string) => Constant::String(self.utf8(string)?.into()),
+            Entry::Member(tag, class, nat) => {
+                let name = self.member(class, nat)?.into();
+                match tag {
+                    FIELD_REF => Constant::Field(name),
+                    _ => Constant::Method(name),
+                }
+            }
+            Entry::MethodHandle(member) => match self.get(member)? {
+                Entry::Member(_, class, nat) => Constant::Other(self.member(class, nat)?.into()),
+                _ => return None,
+            },
+            Entry::MethodType(descriptor) => {
+                let (params, ret) = method_type(&self.utf8(descriptor)?);
+                Constant::Other(format!("{ret}({})", params.join(", ")).into())
+            }
+            Entry::Dynamic(nat) => Constant::Other(self.name_and_type(nat)?.into()),
+            Entry::Utf8(..) | Entry::NameAndType(..) | Entry::Other => return None,
+        })
+    }
+}
+
+/// Constant pool at the reader, which is advanced past it.
+fn entries<'a>(reader: &mut Reader<'a>) -> Option<Entries<'a>> {
+    let count = reader.u16()? as usize;
+
+    // indices start at one, longs and doubles take up two
+    let mut entries = vec![None];
+    while entries.len() < count {
+        let tag = reader.u8()?;
+        let entry = match tag {
+            1 => {
+                let len = reader.u16()? as usize;
+                Entry::Utf8(reader.bytes(len)?)
+            }
+            3 => Entry::Integer(reader.u32()? as i32),
+            4 => Entry::Float(f32::from_bits(reader.u32()?)),
+            5 => Entry::Long(reader.u64()? as i64),
+            6 => Entry::Double(f64::from_bits(reader.u64()?)),
+            7 => Entry::Class(reader.u16()?),
+            8 => Entry::String(reader.u16()?),
+            9..=11 => Entry::Member(tag, reader.u16()?, reader.u16()?),
+            12 => Entry::NameAndType(reader.u16()?, reader.u16()?),
+            15 => {
+                reader.u8()?;
+                Entry::MethodHandle(reader.u16()?)
+            }
+            16 => Entry::MethodType(reader.u16()?),
+            17 | 18 => {
+                reader.u16()?;
+                Entry::Dynamic(reader.u16()?)
+            }
+            19 | 20 => {
+                reader.u16()?;
+                Entry::Other
+            }
+            _ => return None,
+        };
+
+        entries.push(Some(entry));
+        if tag == 5 || tag == 6 {
+            entries.push(None);
+        }
+    }
+
+    Some(Entries(entries))
+}
+
+/// Attributes at the reader by their name and contents, which the reader is advanced past.
+fn attributes<'a>(reader: &mut Reader<'a>) -> Option<Vec<(u16, &'a [u8])>> {
+    let count = reader.u16()?;
+    let mut attributes = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name = reader.u16()?;
+        let len = reader.u32()? as usize;
+        attributes.push((name, reader.bytes(len)?));
+    }
+
+    Some(attributes)
+}
+
+/// Program of the class file `bytes`, None if it's truncated or refers to data outside of it.
+pub(crate) fn parse(bytes: &[u8]) -> Option<Program<'_, Pool>> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.u32()? != 0xcafebabe {
+        return None;
+    }
+
+    // minor and major version
+    reader.u32()?;
+
+    let entries = entries(&mut reader)?;
+    let _access = reader.u16()?;
+    let class = entries.class(reader.u16()?)?;
+    let _super_class = reader.u16()?;
+
+    let interfaces = reader.u16()? as usize;
+    reader.bytes(interfaces * 2)?;
+
+    let fields = reader.u16()?;
+    for _ in 0..fields {
+        reader.bytes(6)?;
+        attributes(&mut reader)?;
+    }
+
+    let mut methods = Vec::new();
+    for _ in 0..reader.u16()? {
+        let _access = reader.u16()?;
+        let name = entries.utf8(reader.u16()?)?;
+        let (params, _) = method_type(&entries.utf8(reader.u16()?)?);
+
+        // abstract and native methods have no code
+        for (attribute, data) in attributes(&mut reader)? {
+            if entries.utf8(attribute).as_deref() != Some("Code") {
+                continue;
+            }
+
+            let mut code = Reader { bytes: data, offset: 0 };
+            let _max_stack = code.u16()?;
+            let _max_locals = code.u16()?;
+            let len = code.u32()? as usize;
+            methods.push(Method {
+                name: format!("{class}.{name}({})", params.join(", ")),
+                code: code.bytes(len)?,
+            });
+        }
+    }
+
+    let constants = (0..entries.0.len()).map(|idx| entries.constant(idx as u16)).collect();
+    let pool = Pool {
+        constants,
+        code: Default::default(),
+    };
+
+    Some(Program { pool, methods })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Class file of a class `Main` whose method `main(java.lang.String[])` calls itself.
+    fn file() -> Vec<u8> {
+        let mut bytes = vec![0xca, 0xfe, 0xba, 0xbe, 0, 0, 0, 52];
+        let utf8 = |bytes: &mut Vec<u8>, string: &str| {
+            bytes.push(1);
+            bytes.extend_from_slice(&(string.len() as u16).to_be_bytes());
+            bytes.extend_from_slice(string.as_bytes());
+        };
+
+        bytes.extend_from_slice(&[0, 12]);
+        utf8(&mut bytes, "Main"); // 1
+        bytes.extend_from_slice(&[7, 0, 1]); // 2: class Main
+        utf8(&mut bytes, "main"); // 3
+        utf8(&mut bytes, "([Ljava/lang/String;)V"); // 4
+        bytes.extend_from_slice(&[12, 0, 3, 0, 4]); // 5: name and type of main
+        bytes.extend_from_slice(&[10, 0, 2, 0, 5]); // 6: method Main.main
+        utf8(&mut bytes, "Code"); // 7
+        bytes.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0x10, 0]); // 8 and 9: long 0x1000
+        bytes.extend_from_slice(&[8, 0, 1]); // 10: string "Main"
+        bytes.extend_from_slice(&[19, 0, 1]); // 11: module
+
+        // public class Main with no super class, interfaces or fields
+        bytes.extend_from_slice(&[0, 0x21, 0, 2, 0, 0, 0, 0, 0, 0]);
+
+        // public static void main(String[]) with it's code attribute
+        bytes.extend_from_slice(&[0, 1, 0, 0x09, 0, 3, 0, 4, 0, 1, 0, 7, 0, 0, 0, 16]);
+        bytes.extend_from_slice(&[0, 1, 0, 1, 0, 0, 0, 4, 0x2a, 0xb8, 0, 6, 0, 0, 0, 0]);
+        bytes.extend_from_slice(&[0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn methods() {
+        let bytes = file();
+        let program = parse(&bytes).unwrap();
+
+        assert_eq!(program.methods.len(), 1);
+        assert_eq!(program.methods[0].name, "Main.main(java.lang.String[])");
+        assert_eq!(program.methods[0].code, [0x2a, 0xb8, 0, 6]);
+
+        let constants = &program.pool.constants;
+        assert_eq!(constants.len(), 12);
+        assert_eq!(constants[2], Some(Constant::Class("Main".into())));
+        assert_eq!(constants[6], Some(Constant::Method("Main.main(java.lang.String[])".into())));
+        assert_eq!(constants[8], Some(Constant::Long(0x1000)));
+        assert_eq!(constants[9], None);
+        assert_eq!(constants[10], Some(Constant::String("Main".into())));
+
+        assert!(parse(&bytes[..bytes.len() - 4]).is_none());
+    }
+}
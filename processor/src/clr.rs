@@ -0,0 +1,1140 @@
+//! Metadata of .NET assemblies, which are PE files with a COR20 header describing the types and
+//! methods of their CIL code in a set of tables.
+
+use cil::{Header, Pool};
+use object::pe;
+use object::read::pe::{ImageNtHeaders, PeFile};
+use object::LittleEndian as LE;
+use object::Object;
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Signature the metadata root starts with, "BSJB".
+const METADATA_SIGNATURE: usize = 0x424a5342;
+
+/// Number of tables whose layout is known, any others are only found in portable PDBs.
+const TABLES: usize = 0x2d;
+
+/// How deeply signatures and nested types are followed, as malformed metadata may loop.
+const MAX_DEPTH: usize = 16;
+
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD_PTR: usize = 0x03;
+const FIELD: usize = 0x04;
+const METHOD_PTR: usize = 0x05;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const MEMBER_REF: usize = 0x0a;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1a;
+const TYPE_SPEC: usize = 0x1b;
+const ASSEMBLY_REF: usize = 0x23;
+const NESTED_CLASS: usize = 0x29;
+const GENERIC_PARAM: usize = 0x2a;
+const METHOD_SPEC: usize = 0x2b;
+
+/// Tables are referred to by an index into one of several tables, tagged in it's low bits.
+struct CodedIndex {
+    bits: u32,
+    /// Tables by their tag, with tags that aren't used out of range of the tables.
+    tables: &'static [usize],
+}
+
+const TYPE_DEF_OR_REF: CodedIndex = CodedIndex {
+    bits: 2,
+    tables: &[TYPE_DEF, TYPE_REF, TYPE_SPEC],
+};
+
+const HAS_CONSTANT: CodedIndex = CodedIndex {
+    bits: 2,
+    tables: &[FIELD, PARAM, PROPERTY],
+};
+
+const HAS_CUSTOM_ATTRIBUTE: CodedIndex = CodedIndex {
+    bits: 5,
+    tables: &[
+        METHOD_DEF, FIELD, TYPE_REF, TYPE_DEF, PARAM, 0x09, MEMBER_REF, 0x00, 0x0e, PROPERTY,
+        EVENT, STAND_ALONE_SIG, MODULE_REF, TYPE_SPEC, 0x20, ASSEMBLY_REF, 0x26, 0x27, 0x28,
+        GENERIC_PARAM, 0x2c, METHOD_SPEC,
+    ],
+};
+
+const HAS_FIELD_MARSHAL: CodedIndex = CodedIndex {
+    bits: 1,
+    tables: &[FIELD, PARAM],
+};
+
+const HAS_DECL_SECURITY: CodedIndex = CodedIndex {
+    bits: 2,
+    tables: &[TYPE_DEF, METHOD_DEF, 0x20],
+};
+
+const MEMBER_REF_PARENT: CodedIndex = CodedIndex {
+    bits: 3,
+    tables: &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC],
+};
+
+const HAS_SEMANTICS: CodedIndex = CodedIndex {
+    bits: 1,
+    tables: &[EVENT, PROPERTY],
+};
+
+const METHOD_DEF_OR_REF: CodedIndex = CodedIndex {
+    bits: 1,
+    tables: &[METHOD_DEF, MEMBER_REF],
+};
+
+const MEMBER_FORWARDED: CodedIndex = CodedIndex {
+    bits: 1,
+    tables: &[FIELD, METHOD_DEF],
+};
+
+const IMPLEMENTATION: CodedIndex = CodedIndex {
+    bits: 2,
+    tables: &[0x26, ASSEMBLY_REF, 0x27],
+};
+
+const CUSTOM_ATTRIBUTE_TYPE: CodedIndex = CodedIndex {
+    bits: 3,
+    tables: &[usize::MAX, usize::MAX, METHOD_DEF, MEMBER_REF, usize::MAX],
+};
+
+const RESOLUTION_SCOPE: CodedIndex = CodedIndex {
+    bits: 2,
+    tables: &[0x00, MODULE_REF, ASSEMBLY_REF, TYPE_REF],
+};
+
+const TYPE_OR_METHOD_DEF: CodedIndex = CodedIndex {
+    bits: 1,
+    tables: &[TYPE_DEF, METHOD_DEF],
+};
+
+/// Kind of value stored in a column of a table.
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    String,
+    Guid,
+    Blob,
+    /// Index into the given table.
+    Table(usize),
+    Coded(&'static CodedIndex),
+}
+
+/// Columns of each table by it's number.
+static SCHEMA: [&[Column]; TABLES] = {
+    use Column::*;
+
+    [
+        // Module
+        &[U16, String, Guid, Guid, Guid],
+        // TypeRef
+        &[Coded(&RESOLUTION_SCOPE), String, String],
+        // TypeDef
+        &[U32, String, String, Coded(&TYPE_DEF_OR_REF), Table(FIELD), Table(METHOD_DEF)],
+        // FieldPtr
+        &[Table(FIELD)],
+        // Field
+        &[U16, String, Blob],
+        // MethodPtr
+        &[Table(METHOD_DEF)],
+        // MethodDef
+        &[U32, U16, U16, String, Blob, Table(PARAM)],
+        // ParamPtr
+        &[Table(PARAM)],
+        // Param
+        &[U16, U16, String],
+        // InterfaceImpl
+        &[Table(TYPE_DEF), Coded(&TYPE_DEF_OR_REF)],
+        // MemberRef
+        &[Coded(&MEMBER_REF_PARENT), String, Blob],
+        // Constant, whose type is padded to two bytes
+        &[U16, Coded(&HAS_CONSTANT), Blob],
+        // CustomAttribute
+        &[Coded(&HAS_CUSTOM_ATTRIBUTE), Coded(&CUSTOM_ATTRIBUTE_TYPE), Blob],
+        // FieldMarshal
+        &[Coded(&HAS_FIELD_MARSHAL), Blob],
+        // DeclSecurity
+        &[U16, Coded(&HAS_DECL_SECURITY), Blob],
+        // ClassLayout
+        &[U16, U32, Table(TYPE_DEF)],
+        // FieldLayout
+        &[U32, Table(FIELD)],
+        // StandAloneSig
+        &[Blob],
+        // EventMap
+        &[Table(TYPE_DEF), Table(EVENT)],
+        // EventPtr
+        &[Table(EVENT)],
+        // Event
+        &[U16, String, Coded(&TYPE_DEF_OR_REF)],
+        // PropertyMap
+        &[Table(TYPE_DEF), Table(PROPERTY)],
+        // PropertyPtr
+        &[Table(PROPERTY)],
+        // Property
+        &[U16, String, Blob],
+        // MethodSemantics
+        &[U16, Table(METHOD_DEF), Coded(&HAS_SEMANTICS)],
+        // MethodImpl
+        &[Table(TYPE_DEF), Coded(&METHOD_DEF_OR_REF), Coded(&METHOD_DEF_OR_REF)],
+        // ModuleRef
+        &[String],
+        // TypeSpec
+        &[Blob],
+        // ImplMap
+        &[U16, Coded(&MEMBER_FORWARDED), String, Table(MODULE_REF)],
+        // FieldRVA
+        &[U32, Table(FIELD)],
+        // EncLog
+        &[U32, U32],
+        // EncMap
+        &[U32],
+        // Assembly
+        &[U32, U16, U16, U16, U16, U32, Blob, String, String],
+        // AssemblyProcessor
+        &[U32],
+        // AssemblyOS
+        &[U32, U32, U32],
+        // AssemblyRef
+        &[U16, U16, U16, U16, U32, Blob, String, String, Blob],
+        // AssemblyRefProcessor
+        &[U32, Table(ASSEMBLY_REF)],
+        // AssemblyRefOS
+        &[U32, U32, U32, Table(ASSEMBLY_REF)],
+        // File
+        &[U32, String, Blob],
+        // ExportedType
+        &[U32, U32, String, String, Coded(&IMPLEMENTATION)],
+        // ManifestResource
+        &[U32, U32, String, Coded(&IMPLEMENTATION)],
+        // NestedClass
+        &[Table(TYPE_DEF), Table(TYPE_DEF)],
+        // GenericParam
+        &[U16, U16, Coded(&TYPE_OR_METHOD_DEF), String],
+        // MethodSpec
+        &[Coded(&METHOD_DEF_OR_REF), Blob],
+        // GenericParamConstraint
+        &[Table(GENERIC_PARAM), Coded(&TYPE_DEF_OR_REF)],
+    ]
+};
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Compressed unsigned integer at `offset`, which is advanced past it.
+fn compressed(bytes: &[u8], offset: &mut usize) -> Option<usize> {
+    let first = *bytes.get(*offset)? as usize;
+    let (value, len) = match first {
+        0x00..=0x7f => (first, 1),
+        0x80..=0xbf => ((first & 0x3f) << 8 | *bytes.get(*offset + 1)? as usize, 2),
+        0xc0..=0xdf => {
+            let rest = bytes.get(*offset + 1..*offset + 4)?;
+            let value = (first & 0x1f) << 24
+                | (rest[0] as usize) << 16
+                | (rest[1] as usize) << 8
+                | rest[2] as usize;
+            (value, 4)
+        }
+        _ => return None,
+    };
+
+    *offset += len;
+    Some(value)
+}
+
+/// Rows of a table, with the offset and size of each column.
+struct Table<'a> {
+    rows: usize,
+    row_size: usize,
+    columns: Vec<(usize, usize)>,
+    bytes: &'a [u8],
+}
+
+/// Heaps and tables of the metadata root.
+struct Metadata<'a> {
+    strings: &'a [u8],
+    user_strings: &'a [u8],
+    blobs: &'a [u8],
+    tables: Vec<Table<'a>>,
+}
+
+impl<'a> Metadata<'a> {
+    fn parse(root: &'a [u8]) -> Option<Self> {
+        if u32_at(root, 0)? != METADATA_SIGNATURE {
+            return None;
+        }
+
+        // the version string is padded to a multiple of four
+        let version_len = u32_at(root, 12)?;
+        let mut offset = 16 + version_len;
+        let count = u16_at(root, offset + 2)?;
+        offset += 4;
+
+        let mut stream_of = HashMap::new();
+        for _ in 0..count {
+            let start = u32_at(root, offset)?;
+            let size = u32_at(root, offset + 4)?;
+            let name = root.get(offset + 8..)?;
+            let len = name.iter().position(|&b| b == 0)?;
+            stream_of.insert(&name[..len], root.get(start..start.checked_add(size)?)?);
+            offset += 8 + (len + 1).next_multiple_of(4);
+        }
+
+        // uncompressed tables are stored in `#-`, which only differs by having pointer tables
+        let tables = stream_of.get(&b"#~"[..]).or(stream_of.get(&b"#-"[..]))?;
+        let stream = |name: &[u8]| stream_of.get(name).copied().unwrap_or_default();
+        Some(Self {
+            strings: stream(b"#Strings"),
+            user_strings: stream(b"#US"),
+            blobs: stream(b"#Blob"),
+            tables: Self::tables(tables)?,
+        })
+    }
+
+    fn tables(stream: &'a [u8]) -> Option<Vec<Table<'a>>> {
+        let heap_sizes = *stream.get(6)?;
+        let valid = u32_at(stream, 8)? as u64 | (u32_at(stream, 12)? as u64) << 32;
+        if valid >> TABLES != 0 {
+            return None;
+        }
+
+        let mut offset = 24;
+        let mut rows = [0; TABLES];
+        for (table, rows) in rows.iter_mut().enumerate() {
+            if valid & (1 << table) != 0 {
+                *rows = u32_at(stream, offset)?;
+                offset += 4;
+            }
+        }
+
+        // extra data of uncompressed tables
+        if heap_sizes & 0x40 != 0 {
+            offset += 4;
+        }
+
+        let heap_size = |flag: u8| if heap_sizes & flag != 0 { 4 } else { 2 };
+        let index_size = |table: usize| if rows[table] < 0x10000 { 2 } else { 4 };
+        let coded_size = |coded: &CodedIndex| {
+            let max = coded.tables.iter().map(|&t| rows.get(t).copied().unwrap_or(0)).max();
+            if max.unwrap_or(0) < 1 << (16 - coded.bits) {
+                2
+            } else {
+                4
+            }
+        };
+
+        let mut tables = Vec::with_capacity(TABLES);
+        for (table, schema) in SCHEMA.iter().enumerate() {
+            let mut row_size = 0;
+            let mut columns = Vec::with_capacity(schema.len());
+            for column in schema.iter() {
+                let size = match *column {
+                    Column::U16 => 2,
+                    Column::U32 => 4,
+                    Column::String => heap_size(0x01),
+                    Column::Guid => heap_size(0x02),
+                    Column::Blob => heap_size(0x04),
+                    Column::Table(table) => index_size(table),
+                    Column::Coded(coded) => coded_size(coded),
+                };
+
+                columns.push((row_size, size));
+                row_size += size;
+            }
+
+            let len = rows[table].checked_mul(row_size)?;
+            tables.push(Table {
+                rows: rows[table],
+                row_size,
+                columns,
+                bytes: stream.get(offset..offset.checked_add(len)?)?,
+            });
+            offset += len;
+        }
+
+        Some(tables)
+    }
+
+    fn rows(&self, table: usize) -> usize {
+        self.tables[table].rows
+    }
+
+    /// Value of the `column` of a `row` of the `table`, where rows start at one.
+    fn get(&self, table: usize, row: usize, column: usize) -> Option<usize> {
+        let table = &self.tables[table];
+        if row == 0 || row > table.rows {
+            return None;
+        }
+
+        let (offset, size) = *table.columns.get(column)?;
+        let offset = (row - 1) * table.row_size + offset;
+        match size {
+            2 => u16_at(table.bytes, offset),
+            _ => u32_at(table.bytes, offset),
+        }
+    }
+
+    /// Table and row of a coded index stored in the `column` of a `row` of the `table`.
+    fn coded(&self, table: usize, row: usize, column: usize) -> Option<(usize, usize)> {
+        let coded = match SCHEMA[table].get(column)? {
+            Column::Coded(coded) => coded,
+            _ => return None,
+        };
+
+        let value = self.get(table, row, column)?;
+        let tag = value & ((1 << coded.bits) - 1);
+        Some((*coded.tables.get(tag)?, value >> coded.bits))
+    }
+
+    fn string(&self, idx: usize) -> Option<String> {
+        let bytes = self.strings.get(idx..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+
+    fn blob(&self, idx: usize) -> Option<&'a [u8]> {
+        let mut offset = idx;
+        let len = compressed(self.blobs, &mut offset)?;
+        self.blobs.get(offset..offset.checked_add(len)?)
+    }
+
+    /// Strings of `ldstr` by their token, which holds their offset into the heap.
+    fn user_strings(&self) -> HashMap<u32, Arc<str>> {
+        let heap = self.user_strings;
+        let mut strings = HashMap::new();
+
+        // each string is UTF-16 followed by a byte marking whether it has special characters
+        let mut offset = 1;
+        while offset < heap.len() {
+            let start = offset;
+            let Some(len) = compressed(heap, &mut offset) else {
+                break;
+            };
+
+            let Some(data) = heap.get(offset..offset + len) else {
+                break;
+            };
+
+            let units: Vec<u16> =
+                data.chunks_exact(2).map(|unit| u16::from_le_bytes([unit[0], unit[1]])).collect();
+            let token = cil::USER_STRING << 24 | start as u32;
+            strings.insert(token, String::from_utf16_lossy(&units).into());
+            offset += len;
+        }
+
+        strings
+    }
+}
+
+/// Names of the types and members referred to by tokens.
+struct Names<'m, 'a> {
+    metadata: &'m Metadata<'a>,
+    /// Enclosing type of nested types by their row.
+    enclosing: HashMap<usize, usize>,
+    /// Type each field is a member of by the field's row.
+    field_owners: Vec<usize>,
+    /// Type each method is a member of by the method's row.
+    method_owners: Vec<usize>,
+}
+
+impl<'m, 'a> Names<'m, 'a> {
+    fn new(metadata: &'m Metadata<'a>) -> Option<Self> {
+        let mut enclosing = HashMap::new();
+        for row in 1..=metadata.rows(NESTED_CLASS) {
+            let nested = metadata.get(NESTED_CLASS, row, 0)?;
+            enclosing.insert(nested, metadata.get(NESTED_CLASS, row, 1)?);
+        }
+
+        Some(Self {
+            metadata,
+            enclosing,
+            field_owners: Self::owners(metadata, 4, FIELD, FIELD_PTR)?,
+            method_owners: Self::owners(metadata, 5, METHOD_DEF, METHOD_PTR)?,
+        })
+    }
+
+    /// Type owning each row of `table`, given the `column` of types holding the first row of
+    /// their list of members. Lists index into the `ptr` table instead if it's present.
+    fn owners(metadata: &Metadata, column: usize, table: usize, ptr: usize) -> Option<Vec<usize>> {
+        let has_ptr = metadata.rows(ptr) != 0;
+        let len = if has_ptr { metadata.rows(ptr) } else { metadata.rows(table) };
+        let types = metadata.rows(TYPE_DEF);
+
+        let mut owners = vec![0; metadata.rows(table) + 1];
+        for ty in 1..=types {
+            let start = metadata.get(TYPE_DEF, ty, column)?;
+            let end = match ty < types {
+                true => metadata.get(TYPE_DEF, ty + 1, column)?,
+                false => len + 1,
+            };
+
+            for idx in start..end.min(len + 1) {
+                let member = if has_ptr { metadata.get(ptr, idx, 0)? } else { idx };
+                if let Some(owner) = owners.get_mut(member) {
+                    *owner = ty;
+                }
+            }
+        }
+
+        Some(owners)
+    }
+
+    /// Name of the row of a table referred to by tokens.
+    fn name(&self, table: usize, row: usize) -> Option<String> {
+        match table {
+            TYPE_DEF => self.type_def(row, 0),
+            TYPE_REF => self.type_ref(row, 0),
+            TYPE_SPEC => self.type_spec(row, 0),
+            FIELD => self.field(row),
+            METHOD_DEF => self.method_def(row),
+            MEMBER_REF => self.member_ref(row),
+            METHOD_SPEC => self.method_spec(row),
+            STAND_ALONE_SIG => self.stand_alone_sig(row),
+            _ => None,
+        }
+    }
+
+    fn type_def(&self, row: usize, depth: usize) -> Option<String> {
+        let name = self.metadata.string(self.metadata.get(TYPE_DEF, row, 1)?)?;
+        if let Some(&outer) = self.enclosing.get(&row) {
+            if depth < MAX_DEPTH {
+                return Some(format!("{}.{name}", self.type_def(outer, depth + 1)?));
+            }
+        }
+
+        let namespace = self.metadata.string(self.metadata.get(TYPE_DEF, row, 2)?)?;
+        Some(match namespace.is_empty() {
+            true => name,
+            false => format!("{namespace}.{name}"),
+        })
+    }
+
+    fn type_ref(&self, row: usize, depth: usize) -> Option<String> {
+        let name = self.metadata.string(self.metadata.get(TYPE_REF, row, 1)?)?;
+        if let Some((TYPE_REF, outer)) = self.metadata.coded(TYPE_REF, row, 0) {
+            if depth < MAX_DEPTH {
+                return Some(format!("{}.{name}", self.type_ref(outer, depth + 1)?));
+            }
+        }
+
+        let namespace = self.metadata.string(self.metadata.get(TYPE_REF, row, 2)?)?;
+        Some(match namespace.is_empty() {
+            true => name,
+            false => format!("{namespace}.{name}"),
+        })
+    }
+
+    fn type_spec(&self, row: usize, depth: usize) -> Option<String> {
+        let blob = self.metadata.blob(self.metadata.get(TYPE_SPEC, row, 0)?)?;
+        self.ty(blob, &mut 0, depth)
+    }
+
+    /// Type, field or method that's the parent of a member reference.
+    fn parent(&self, table: usize, row: usize) -> Option<String> {
+        match table {
+            TYPE_DEF => self.type_def(row, 0),
+            TYPE_REF => self.type_ref(row, 0),
+            TYPE_SPEC => self.type_spec(row, 0),
+            MODULE_REF => self.metadata.string(self.metadata.get(MODULE_REF, row, 0)?),
+            _ => None,
+        }
+    }
+
+    /// Name of a member of `owner`, with the parameters of it's signature if it's a method.
+    fn member(&self, owner: Option<String>, name: usize, signature: usize) -> Option<String> {
+        let name = self.metadata.string(name)?;
+        let name = match owner {
+            Some(owner) => format!("{owner}.{name}"),
+            None => name,
+        };
+
+        // fields start with 0x6, signatures that can't be read leave out the parameters
+        let blob = self.metadata.blob(signature)?;
+        if blob.first() == Some(&0x06) {
+            return Some(name);
+        }
+
+        Some(match self.method(blob, &mut 0, 0) {
+            Some((params, _)) => format!("{name}({})", params.join(", ")),
+            None => name,
+        })
+    }
+
+    fn field(&self, row: usize) -> Option<String> {
+        let owner = self.type_def(*self.field_owners.get(row)?, 0);
+        let name = self.metadata.get(FIELD, row, 1)?;
+        self.member(owner, name, self.metadata.get(FIELD, row, 2)?)
+    }
+
+    fn method_def(&self, row: usize) -> Option<String> {
+        let owner = self.type_def(*self.method_owners.get(row)?, 0);
+        let name = self.metadata.get(METHOD_DEF, row, 3)?;
+        self.member(owner, name, self.metadata.get(METHOD_DEF, row, 4)?)
+    }
+
+    fn member_ref(&self, row: usize) -> Option<String> {
+        let (table, parent) = self.metadata.coded(MEMBER_REF, row, 0)?;
+
+        // references to the definition of a varargs method, with the types passed in it's
+        // signature
+        if table == METHOD_DEF {
+            return self.method_def(parent);
+        }
+
+        let name = self.metadata.get(MEMBER_REF, row, 1)?;
+        let signature = self.metadata.get(MEMBER_REF, row, 2)?;
+        self.member(self.parent(table, parent), name, signature)
+    }
+
+    /// Name of a generic method with it's type arguments, e.g. `Array.Empty<int>()`.
+    fn method_spec(&self, row: usize) -> Option<String> {
+        let name = match self.metadata.coded(METHOD_SPEC, row, 0)? {
+            (METHOD_DEF, method) => self.method_def(method)?,
+            (_, method) => self.member_ref(method)?,
+        };
+
+        let blob = self.metadata.blob(self.metadata.get(METHOD_SPEC, row, 1)?)?;
+        let mut offset = 1;
+        let count = compressed(blob, &mut offset)?;
+        let args = (0..count)
+            .map(|_| self.ty(blob, &mut offset, 0))
+            .collect::<Option<Vec<_>>>()?;
+
+        let split = name.find('(').unwrap_or(name.len());
+        Some(format!("{}<{}>{}", &name[..split], args.join(", "), &name[split..]))
+    }
+
+    /// Types of the local variables of a method, or the signature of a `calli` target.
+    fn stand_alone_sig(&self, row: usize) -> Option<String> {
+        let blob = self.metadata.blob(self.metadata.get(STAND_ALONE_SIG, row, 0)?)?;
+        if blob.first() != Some(&0x07) {
+            let (params, ret) = self.method(blob, &mut 0, 0)?;
+            return Some(format!("{ret}({})", params.join(", ")));
+        }
+
+        let mut offset = 1;
+        let count = compressed(blob, &mut offset)?;
+        let locals = (0..count)
+            .map(|_| self.ty(blob, &mut offset, 0))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(format!("({})", locals.join(", ")))
+    }
+
+    /// Parameter types and return type of the method signature at `offset`, which is advanced
+    /// past it.
+    fn method(
+        &self,
+        blob: &[u8],
+        offset: &mut usize,
+        depth: usize,
+    ) -> Option<(Vec<String>, String)> {
+        let convention = *blob.get(*offset)?;
+        *offset += 1;
+
+        // number of generic parameters
+        if convention & 0x10 != 0 {
+            compressed(blob, offset)?;
+        }
+
+        let count = compressed(blob, offset)?;
+        let ret = self.ty(blob, offset, depth)?;
+        let params = (0..count)
+            .map(|_| self.ty(blob, offset, depth))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((params, ret))
+    }
+
+    /// Type at `offset` of a signature, which is advanced past it, named as in C#.
+    fn ty(&self, blob: &[u8], offset: &mut usize, depth: usize) -> Option<String> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let depth = depth + 1;
+        let element = *blob.get(*offset)?;
+        *offset += 1;
+
+        Some(match element {
+            0x01 => "void".to_string(),
+            0x02 => "bool".to_string(),
+            0x03 => "char".to_string(),
+            0x04 => "sbyte".to_string(),
+            0x05 => "byte".to_string(),
+            0x06 => "short".to_string(),
+            0x07 => "ushort".to_string(),
+            0x08 => "int".to_string(),
+            0x09 => "uint".to_string(),
+            0x0a => "long".to_string(),
+            0x0b => "ulong".to_string(),
+            0x0c => "float".to_string(),
+            0x0d => "double".to_string(),
+            0x0e => "string".to_string(),
+            0x0f => format!("{}*", self.ty(blob, offset, depth)?),
+            0x10 => format!("ref {}", self.ty(blob, offset, depth)?),
+            // value types and classes by their TypeDef, TypeRef or TypeSpec
+            0x11 | 0x12 => {
+                let value = compressed(blob, offset)?;
+                let row = value >> 2;
+                match value & 3 {
+                    0 => self.type_def(row, depth)?,
+                    1 => self.type_ref(row, depth)?,
+                    2 => self.type_spec(row, depth)?,
+                    _ => return None,
+                }
+            }
+            0x13 => format!("!{}", compressed(blob, offset)?),
+            0x14 => {
+                let ty = self.ty(blob, offset, depth)?;
+                let rank = compressed(blob, offset)?;
+
+                // sizes and lower bounds of each dimension, which are left out
+                for _ in 0..2 {
+                    for _ in 0..compressed(blob, offset)? {
+                        compressed(blob, offset)?;
+                    }
+                }
+
+                format!("{ty}[{}]", ",".repeat(rank.saturating_sub(1)))
+            }
+            0x15 => {
+                let ty = self.ty(blob, offset, depth)?;
+                let count = compressed(blob, offset)?;
+                let args = (0..count)
+                    .map(|_| self.ty(blob, offset, depth))
+                    .collect::<Option<Vec<_>>>()?;
+
+                format!("{ty}<{}>", args.join(", "))
+            }
+            0x16 => "TypedReference".to_string(),
+            0x18 => "nint".to_string(),
+            0x19 => "nuint".to_string(),
+            0x1b => {
+                let (params, ret) = self.method(blob, offset, depth)?;
+                let types: Vec<String> = params.into_iter().chain([ret]).collect();
+                format!("delegate*<{}>", types.join(", "))
+            }
+            0x1c => "object".to_string(),
+            0x1d => format!("{}[]", self.ty(blob, offset, depth)?),
+            0x1e => format!("!!{}", compressed(blob, offset)?),
+            // custom modifiers by their type, which are left out
+            0x1f | 0x20 => {
+                compressed(blob, offset)?;
+                self.ty(blob, offset, depth)?
+            }
+            // start of the variable arguments and pinned locals
+            0x41 | 0x45 => self.ty(blob, offset, depth)?,
+            _ => return None,
+        })
+    }
+}
+
+/// Header and exception handling clauses of the method body `bytes` by their offset, along
+/// with the size of the body.
+fn body(bytes: &[u8]) -> Option<(Vec<(usize, Header)>, usize)> {
+    let first = *bytes.first()?;
+    let (header, len, more_sections) = match first & 3 {
+        // tiny headers have no locals and a stack of at most 8 values
+        2 => {
+            let header = Header::Method {
+                len: 1,
+                max_stack: 8,
+                locals: 0,
+            };
+
+            (header, 1 + (first >> 2) as usize, false)
+        }
+        3 => {
+            let flags = u16_at(bytes, 0)?;
+            let header_len = (flags >> 12) * 4;
+            if header_len < 12 {
+                return None;
+            }
+
+            let header = Header::Method {
+                len: header_len,
+                max_stack: u16_at(bytes, 2)? as u16,
+                locals: u32_at(bytes, 8)? as u32,
+            };
+
+            (header, header_len.checked_add(u32_at(bytes, 4)?)?, flags & 0x8 != 0)
+        }
+        _ => return None,
+    };
+
+    bytes.get(..len)?;
+    let mut parts = vec![(0, header)];
+    let mut end = len;
+    let mut more_sections = more_sections;
+
+    // sections are aligned to four bytes and either small or fat
+    while more_sections {
+        let start = end.next_multiple_of(4);
+        let kind = *bytes.get(start)?;
+        let (len, count) = match kind & 0x40 != 0 {
+            true => {
+                let len = u32_at(bytes, start)? >> 8;
+                (len, len.saturating_sub(4) / 24)
+            }
+            false => {
+                let len = *bytes.get(start + 1)? as usize;
+                (len, len.saturating_sub(4) / 12)
+            }
+        };
+
+        if len < 4 {
+            return None;
+        }
+
+        bytes.get(start..start.checked_add(len)?)?;
+        parts.push((start, Header::Clauses { len, count }));
+        end = start + len;
+        more_sections = kind & 0x80 != 0;
+    }
+
+    Some((parts, end))
+}
+
+/// CIL code of an assembly.
+pub(crate) struct Module {
+    pub pool: Pool,
+    /// Names of the methods with a body by the address of it's header, sorted by address.
+    pub methods: Vec<(PhysAddr, String)>,
+    /// Method the assembly starts at, unless it's native code.
+    pub entrypoint: Option<PhysAddr>,
+    /// Range of the method bodies, which are stored together.
+    pub code: Option<(PhysAddr, PhysAddr)>,
+    /// Whether the image has no native code other than a stub starting the runtime.
+    pub il_only: bool,
+}
+
+/// Module described by the COR20 `header` of an image at `base`, where `data_at` reads the
+/// image from a relative address.
+fn module<'a>(
+    header: &[u8],
+    base: PhysAddr,
+    data_at: impl Fn(u32) -> Option<&'a [u8]>,
+) -> Option<Module> {
+    let root = data_at(u32_at(header, 8)? as u32)?;
+    let root = root.get(..u32_at(header, 12)?)?;
+    let flags = u32_at(header, 16)? as u32;
+    let entrypoint = u32_at(header, 20)? as u32;
+
+    let metadata = Metadata::parse(root)?;
+    let names = Names::new(&metadata)?;
+    let mut pool = Pool {
+        strings: metadata.user_strings(),
+        ..Default::default()
+    };
+
+    for table in [
+        TYPE_REF,
+        TYPE_DEF,
+        FIELD,
+        METHOD_DEF,
+        MEMBER_REF,
+        STAND_ALONE_SIG,
+        TYPE_SPEC,
+        METHOD_SPEC,
+    ] {
+        for row in 1..=metadata.rows(table) {
+            if let Some(name) = names.name(table, row) {
+                pool.names.insert((table << 24 | row) as u32, name.into());
+            }
+        }
+    }
+
+    let mut methods = Vec::new();
+    let mut code: Option<(PhysAddr, PhysAddr)> = None;
+    for row in 1..=metadata.rows(METHOD_DEF) {
+        let rva = metadata.get(METHOD_DEF, row, 0)?;
+        let impl_flags = metadata.get(METHOD_DEF, row, 1)?;
+
+        // abstract methods have no body, native and runtime provided ones aren't CIL
+        if rva == 0 || impl_flags & 3 != 0 {
+            continue;
+        }
+
+        let (parts, len) = body(data_at(rva as u32)?)?;
+        let addr = base + rva;
+        for (offset, header) in parts {
+            pool.headers.insert(addr + offset, header);
+        }
+
+        code = Some(match code {
+            Some((start, end)) => (start.min(addr), end.max(addr + len)),
+            None => (addr, addr + len),
+        });
+
+        let token = (METHOD_DEF << 24 | row) as u32;
+        let name = match pool.names.get(&token) {
+            Some(name) => name.to_string(),
+            None => format!("method_{row}"),
+        };
+        pool.code.insert(token, addr);
+        methods.push((addr, name));
+    }
+
+    methods.sort_unstable_by_key(|(addr, _)| *addr);
+    let entrypoint = match flags & pe::COMIMAGE_FLAGS_NATIVE_ENTRYPOINT {
+        0 => pool.code.get(&entrypoint).copied(),
+        _ => None,
+    };
+
+    Some(Module {
+        pool,
+        methods,
+        entrypoint,
+        code,
+        il_only: flags & pe::COMIMAGE_FLAGS_ILONLY != 0,
+    })
+}
+
+/// Module of a .NET assembly, None if the image has no COR20 header or it's metadata is
+/// malformed.
+pub(crate) fn parse<Pe: ImageNtHeaders>(pe: &PeFile<Pe>) -> Option<Module> {
+    let directory = pe.data_directory(pe::IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR)?;
+    let (rva, size) = (directory.virtual_address.get(LE), directory.size.get(LE));
+    if rva == 0 || size == 0 {
+        return None;
+    }
+
+    let sections = pe.section_table();
+    let data_at = |rva| sections.pe_data_at(pe.data(), rva);
+    let module = data_at(rva)
+        .and_then(|header| header.get(..size as usize))
+        .and_then(|header| module(header, pe.relative_address_base() as PhysAddr, data_at));
+
+    if module.is_none() {
+        log::complex!(
+            w "[clr::parse] ",
+            r "metadata of the COR20 header is malformed",
+            w ", decoding native code.",
+        );
+    }
+
+    module
+}
+
+/// Mark the `code` of an IL-only image's method bodies as the only code, splitting the section
+/// it's in. Other code sections hold only the stub starting the runtime.
+pub(crate) fn split_sections(sections: &mut Vec<Section>, code: Option<(PhysAddr, PhysAddr)>) {
+    let mut split = Vec::with_capacity(sections.len() + 2);
+    for mut section in sections.drain(..) {
+        if section.kind != SectionKind::Code {
+            split.push(section);
+            continue;
+        }
+
+        let (start, end) = match code {
+            Some((start, end)) if start >= section.start && end <= section.end => (start, end),
+            _ => {
+                section.kind = SectionKind::Data;
+                split.push(section);
+                continue;
+            }
+        };
+
+        let bytes: &'static [u8] = unsafe { std::mem::transmute(section.bytes()) };
+        let slice = |from: PhysAddr, to: PhysAddr| {
+            let len = bytes.len();
+            &bytes[(from - section.start).min(len)..(to - section.start).min(len)]
+        };
+
+        for (kind, from, to) in [
+            (SectionKind::Data, section.start, start),
+            (SectionKind::Code, start, end),
+            (SectionKind::Data, end, section.end),
+        ] {
+            if from == to {
+                continue;
+            }
+
+            let name = match kind {
+                SectionKind::Code => format!("{} (CIL)", section.name),
+                _ => section.name.clone(),
+            };
+
+            split.push(Section::new(name, section.ident, kind, slice(from, to), from, to));
+        }
+    }
+
+    *sections = split;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Image whose relative addresses are offsets into it, with the COR20 header at 0, the
+    /// metadata at 0x100 and method bodies at 0x400. `Program.Main(string[])` calls
+    /// `System.Console.WriteLine(string)` with a string and `Program.Run(int)`, which has a fat
+    /// header and a clause.
+    fn image() -> Vec<u8> {
+        let mut image = vec![0; 0x500];
+        let mut put = |offset: usize, bytes: &[u8]| {
+            image[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+
+        // COR20 header of an IL-only image starting at MethodDef 1
+        put(0x00, &[0x48, 0, 0, 0, 2, 0, 5, 0]);
+        put(0x08, &[0x00, 0x01, 0, 0, 0x00, 0x03, 0, 0, 1, 0, 0, 0, 1, 0, 0, 6]);
+
+        // metadata root with a version of "v4.0" and four streams
+        put(0x100, b"BSJB\x01\0\x01\0\0\0\0\0\x04\0\0\0v4.0\0\0\x04\0");
+        put(0x118, &[0x60, 0, 0, 0, 0x80, 0, 0, 0]);
+        put(0x120, b"#~\0\0");
+        put(0x124, &[0x00, 1, 0, 0, 0x40, 0, 0, 0]);
+        put(0x12c, b"#Strings\0\0\0\0");
+        put(0x138, &[0x00, 2, 0, 0, 0x20, 0, 0, 0]);
+        put(0x140, b"#US\0");
+        put(0x144, &[0x20, 2, 0, 0, 0x20, 0, 0, 0]);
+        put(0x14c, b"#Blob\0\0\0");
+
+        // Module, TypeRef, TypeDef, MethodDef and MemberRef with small heaps
+        put(0x160, &[0, 0, 0, 0, 2, 0, 0, 0, 0x47, 0x04, 0, 0, 0, 0, 0, 0]);
+        put(0x170, &[0; 8]);
+        put(0x178, &[1, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0]);
+        // Module named "m"
+        put(0x18c, &[0, 0, 0x01, 0, 0, 0, 0, 0, 0, 0]);
+        // TypeRef System.Console in AssemblyRef 1
+        put(0x196, &[0x06, 0, 0x24, 0, 0x1d, 0]);
+        // TypeDefs <Module> and Program, whose methods start at 1
+        put(0x19c, &[0, 0, 0, 0, 0x03, 0, 0, 0, 0, 0, 1, 0, 1, 0]);
+        put(0x1aa, &[1, 0, 0, 0, 0x0c, 0, 0, 0, 0, 0, 1, 0, 1, 0]);
+        // MethodDefs Main and Run
+        put(0x1b8, &[0x00, 0x04, 0, 0, 0, 0, 0x16, 0, 0x14, 0, 0x01, 0, 1, 0]);
+        put(0x1c6, &[0x14, 0x04, 0, 0, 0, 0, 0x16, 0, 0x19, 0, 0x07, 0, 1, 0]);
+        // MemberRef WriteLine of TypeRef 1
+        put(0x1d4, &[0x09, 0, 0x2c, 0, 0x0c, 0]);
+
+        put(0x200, b"\0m\0<Module>\0Program\0Main\0Run\0System\0Console\0WriteLine\0");
+
+        // "Hi" at offset 1
+        put(0x301, &[0x05, b'H', 0, b'i', 0, 0]);
+
+        // signatures of Main, Run and WriteLine
+        put(0x321, &[0x05, 0x00, 0x01, 0x01, 0x1d, 0x0e]);
+        put(0x327, &[0x04, 0x00, 0x01, 0x08, 0x08]);
+        put(0x32c, &[0x04, 0x00, 0x01, 0x01, 0x0e]);
+
+        // tiny body of Main doing ldstr, call WriteLine, ldc.i4.1, call Run, pop and ret
+        put(0x400, &[18 << 2 | 2, 0x72, 1, 0, 0, 0x70, 0x28, 1, 0, 0, 0x0a, 0x17]);
+        put(0x40c, &[0x28, 2, 0, 0, 0x06, 0x26, 0x2a]);
+
+        // fat body of Run doing ldarg.0 and ret, followed by a small section with one clause
+        put(0x414, &[0x0b, 0x30, 1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0x02, 0x2a]);
+        put(0x424, &[0x01, 0x10, 0, 0]);
+        image
+    }
+
+    fn parse(image: &[u8]) -> Option<Module> {
+        module(&image[..0x48], 0x400000, |rva| image.get(rva as usize..))
+    }
+
+    #[test]
+    fn metadata() {
+        let image = image();
+        let module = parse(&image).unwrap();
+        let pool = &module.pool;
+
+        assert!(module.il_only);
+        assert_eq!(module.entrypoint, Some(0x400400));
+        assert_eq!(module.code, Some((0x400400, 0x400434)));
+        assert_eq!(
+            module.methods,
+            [
+                (0x400400, "Program.Main(string[])".to_string()),
+                (0x400414, "Program.Run(int)".to_string()),
+            ]
+        );
+
+        assert_eq!(pool.strings.get(&0x7000_0001).map(|s| &**s), Some("Hi"));
+        assert_eq!(pool.names.get(&0x0100_0001).map(|s| &**s), Some("System.Console"));
+        assert_eq!(
+            pool.names.get(&0x0a00_0001).map(|s| &**s),
+            Some("System.Console.WriteLine(string)")
+        );
+        assert_eq!(pool.code.get(&0x0600_0002), Some(&0x400414));
+        assert_eq!(
+            pool.headers.get(&0x400400),
+            Some(&Header::Method {
+                len: 1,
+                max_stack: 8,
+                locals: 0
+            })
+        );
+        assert_eq!(
+            pool.headers.get(&0x400414),
+            Some(&Header::Method {
+                len: 12,
+                max_stack: 1,
+                locals: 0
+            })
+        );
+        assert_eq!(pool.headers.get(&0x400424), Some(&Header::Clauses { len: 16, count: 1 }));
+
+        assert!(parse(&image[..0x420]).is_none());
+        assert!(parse(&image[..0x300]).is_none());
+    }
+
+    #[test]
+    fn signatures() {
+        let image = image();
+        let root = &image[0x100..0x400];
+        let metadata = Metadata::parse(root).unwrap();
+        let names = Names::new(&metadata).unwrap();
+
+        // generic instance of a class from TypeRef 1, a 2D array and a pointer to a pointer
+        let blob = [0x15, 0x12, 0x05, 0x02, 0x08, 0x1c];
+        assert_eq!(names.ty(&blob, &mut 0, 0).unwrap(), "System.Console<int, object>");
+        let blob = [0x14, 0x0c, 0x02, 0x00, 0x00];
+        assert_eq!(names.ty(&blob, &mut 0, 0).unwrap(), "float[,]");
+        let blob = [0x0f, 0x0f, 0x05];
+        assert_eq!(names.ty(&blob, &mut 0, 0).unwrap(), "byte**");
+        assert!(names.ty(&[0x0f; 64], &mut 0, 0).is_none());
+
+        let mut offset = 0;
+        assert_eq!(compressed(&[0xc0, 0x01, 0x02, 0x03], &mut offset), Some(0x010203));
+        assert_eq!(offset, 4);
+    }
+
+    #[test]
+    fn sections() {
+        static TEXT: [u8; 0x40] = [0; 0x40];
+
+        let text = |kind| Section::new(".text".to_string(), "UNKNOWN", kind, &TEXT, 0x2000, 0x2040);
+        let mut sections = vec![text(SectionKind::Code)];
+        split_sections(&mut sections, Some((0x2010, 0x2030)));
+
+        let ranges: Vec<_> = sections.iter().map(|s| (s.kind.clone(), s.start, s.end)).collect();
+        assert_eq!(
+            ranges,
+            [
+                (SectionKind::Data, 0x2000, 0x2010),
+                (SectionKind::Code, 0x2010, 0x2030),
+                (SectionKind::Data, 0x2030, 0x2040),
+            ]
+        );
+        assert_eq!(sections[1].name, ".text (CIL)");
+        assert_eq!(sections[1].bytes().len(), 0x20);
+
+        let mut sections = vec![text(SectionKind::Code)];
+        split_sections(&mut sections, None);
+        assert_eq!(sections[0].kind, SectionKind::Data);
+    }
+}
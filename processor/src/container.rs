@@ -0,0 +1,196 @@
+//! Objects stored in a container, i.e. the members of a static archive or the slices of a fat
+//! Mach-O, which are opened one at a time.
+
+use crate::{Error, Loading, Processor};
+use memmap2::{Mmap, MmapMut};
+use object::read::archive::ArchiveFile;
+use object::read::macho::{FatArch, FatHeader};
+use object::FileKind;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Name of a slice of a fat Mach-O, e.g. `x86_64` or `arm64e`.
+fn slice_name<Fat: FatArch>(arch: &Fat) -> String {
+    use object::macho::*;
+
+    let (cputype, subtype) = (arch.cputype(), arch.cpusubtype() & !CPU_SUBTYPE_MASK);
+    match (cputype, subtype) {
+        (CPU_TYPE_X86, _) => "i386",
+        (CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_H) => "x86_64h",
+        (CPU_TYPE_X86_64, _) => "x86_64",
+        (CPU_TYPE_ARM, CPU_SUBTYPE_ARM_V7S) => "armv7s",
+        (CPU_TYPE_ARM, CPU_SUBTYPE_ARM_V7K) => "armv7k",
+        (CPU_TYPE_ARM, CPU_SUBTYPE_ARM_V7) => "armv7",
+        (CPU_TYPE_ARM, _) => "arm",
+        (CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64E) => "arm64e",
+        (CPU_TYPE_ARM64, _) => "arm64",
+        (CPU_TYPE_ARM64_32, _) => "arm64_32",
+        (CPU_TYPE_POWERPC, _) => "ppc",
+        (CPU_TYPE_POWERPC64, _) => "ppc64",
+        _ => return format!("cpu {cputype:#x}:{subtype:#x}"),
+    }
+    .to_string()
+}
+
+/// Objects of the archive or fat Mach-O `binary` by their name, None if it's neither.
+///
+/// Archive members that aren't objects, like the symbol table or text files, are left out and
+/// names occurring more than once get the number of the occurrence appended, e.g. `init.o (2)`.
+fn objects(binary: &[u8]) -> Option<Vec<(String, &[u8])>> {
+    let mut objects = Vec::new();
+    match FileKind::parse(binary).ok()? {
+        FileKind::Archive => {
+            for member in ArchiveFile::parse(binary).ok()?.members().filter_map(Result::ok) {
+                let Ok(data) = member.data(binary) else { continue };
+                if object::File::parse(data).is_ok() {
+                    let name = String::from_utf8_lossy(member.name());
+                    objects.push((name.into_owned(), data));
+                }
+            }
+        }
+        FileKind::MachOFat32 => {
+            for arch in FatHeader::parse_arch32(binary).ok()? {
+                objects.push((slice_name(arch), arch.data(binary).ok()?));
+            }
+        }
+        FileKind::MachOFat64 => {
+            for arch in FatHeader::parse_arch64(binary).ok()? {
+                objects.push((slice_name(arch), arch.data(binary).ok()?));
+            }
+        }
+        _ => return None,
+    }
+
+    let mut seen = HashMap::new();
+    for (name, _) in objects.iter_mut() {
+        let count = seen.entry(name.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            *name = format!("{name} ({count})");
+        }
+    }
+
+    Some(objects)
+}
+
+/// Names of the objects in the archive or fat Mach-O `binary`, empty if it's neither.
+pub(crate) fn names(binary: &[u8]) -> Vec<String> {
+    objects(binary).unwrap_or_default().into_iter().map(|(name, _)| name).collect()
+}
+
+fn map(path: &Path) -> Result<(std::fs::File, Mmap), Error> {
+    let file = std::fs::File::open(path).map_err(Error::IO)?;
+    let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
+    Ok((file, mmap))
+}
+
+impl Processor {
+    /// Names of the objects in the archive or fat Mach-O at `path`, which are either the file
+    /// names of the archive's members or the architectures of the Mach-O's slices.
+    pub fn members<P: AsRef<Path>>(path: P) -> Result<Vec<String>, Error> {
+        let (_, mmap) = map(path.as_ref())?;
+        match objects(&mmap[..]) {
+            Some(objects) => Ok(objects.into_iter().map(|(name, _)| name).collect()),
+            None => Err(Error::UnknownFormat),
+        }
+    }
+
+    /// Analyze the object at `idx` of [`Processor::members`] of the archive or fat Mach-O at
+    /// `path`.
+    pub fn parse_member<P: AsRef<Path>>(path: P, idx: usize) -> Result<Self, Error> {
+        Self::parse_member_with(path, idx, &Loading::default())
+    }
+
+    /// Parse the object at `idx` of the container at `path`, reporting progress through
+    /// `loading`.
+    pub fn parse_member_with<P: AsRef<Path>>(
+        path: P,
+        idx: usize,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let (file, archive) = map(path.as_ref())?;
+        let objects = objects(&archive[..]).ok_or(Error::UnknownFormat)?;
+        let count = objects.len();
+        let (name, data) = objects
+            .into_iter()
+            .nth(idx)
+            .ok_or_else(|| Error::ImageNotFound(format!("#{idx}")))?;
+
+        // The object is copied out so it's file offsets and raw bytes are the member's own.
+        let mut mmap = MmapMut::map_anon(data.len().max(1)).map_err(Error::IO)?;
+        mmap[..data.len()].copy_from_slice(data);
+        let mmap = mmap.make_read_only().map_err(Error::IO)?;
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..data.len()]) };
+        let obj = object::File::parse(binary)?;
+
+        log::complex!(
+            w "[processor::parse_member] extracted ",
+            b name,
+            w " out of ",
+            y count.to_string(),
+            w " objects.",
+        );
+
+        Self::parse_object(path.as_ref().to_path_buf(), file, mmap, vec![archive], obj, loading)
+    }
+
+    /// Analyze the object named `name` in the file at `path`, which is either an image of a dyld
+    /// shared cache or one of [`Processor::members`] of an archive or fat Mach-O.
+    pub fn parse_image<P: AsRef<Path>>(path: P, name: &str) -> Result<Self, Error> {
+        Self::parse_image_with(path, name, &Loading::default())
+    }
+
+    /// Parse the object named `name` in the file at `path`, reporting progress through
+    /// `loading`.
+    pub fn parse_image_with<P: AsRef<Path>>(
+        path: P,
+        name: &str,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        match Self::members(path.as_ref()) {
+            Ok(members) => match members.iter().position(|member| member == name) {
+                Some(idx) => Self::parse_member_with(path, idx, loading),
+                None => Err(Error::ImageNotFound(name.to_string())),
+            },
+            Err(..) => Self::parse_dyld_cache_with(path, name, loading),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Archive holding a symbol table, a text file and two ELF objects named `a.o`.
+    fn archive() -> Vec<u8> {
+        let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        elf.extend_from_slice(&[1, 0, 0x3e, 0, 1, 0, 0, 0]);
+        elf.extend_from_slice(&[0; 24]);
+        elf.extend_from_slice(&[0, 0, 0, 0, 64, 0, 0, 0, 0, 0, 64, 0, 0, 0, 0, 0]);
+
+        let mut bytes = b"!<arch>\n".to_vec();
+        let mut member = |name: &str, data: &[u8]| {
+            let len = data.len();
+            let header = format!("{name:<16}{:<12}{:<6}{:<6}{:<8}{len:<10}`\n", 0, 0, 0, 644);
+            bytes.extend_from_slice(header.as_bytes());
+            bytes.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                bytes.push(b'\n');
+            }
+        };
+
+        member("/", &[0, 0, 0, 0]);
+        member("a.o/", &elf);
+        member("notes.txt/", b"not an object");
+        member("a.o/", &elf);
+        bytes
+    }
+
+    #[test]
+    fn members() {
+        let bytes = archive();
+        assert_eq!(names(&bytes), ["a.o", "a.o (2)"]);
+        assert_eq!(objects(&bytes).unwrap()[0].1.len(), 64);
+        assert!(objects(b"\x7fELF").is_none());
+    }
+}
@@ -0,0 +1,435 @@
+//! Instructions covered by running the binary, imported from the files fuzzers and coverage
+//! tools write.
+//!
+//! Supported are drcov files as written by DynamoRIO and most fuzzers' coverage modes, lcov
+//! tracefiles which are mapped to instructions through the binary's line info, and lists of
+//! addresses in hex, one per line.
+
+use crate::Processor;
+use object::{BinaryFormat, Object};
+use processor_shared::PhysAddr;
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+pub enum Error {
+    IO(std::io::Error),
+    /// File is a raw or indexed LLVM profile, which only counts how often regions of the
+    /// source were executed.
+    Profile,
+    /// File isn't text, nor a drcov file.
+    UnknownFormat,
+    /// Line that couldn't be parsed, counting from one.
+    InvalidLine(usize),
+    /// drcov file ended before all of it's basic blocks.
+    Truncated,
+    /// drcov file doesn't list a module with the binary's file name.
+    ModuleNotFound(String),
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("{err}.")),
+            Self::Profile => f.write_str(
+                "LLVM profiles aren't supported, merge them with 'llvm-profdata merge' and \
+                 export them with 'llvm-cov export -format=lcov'.",
+            ),
+            Self::UnknownFormat => f.write_str("File isn't a drcov, lcov or address list file."),
+            Self::InvalidLine(line) => f.write_fmt(format_args!("Invalid line {line}.")),
+            Self::Truncated => f.write_str("drcov file is truncated."),
+            Self::ModuleNotFound(name) => {
+                f.write_fmt(format_args!("drcov file doesn't contain module '{name}'."))
+            }
+        }
+    }
+}
+
+/// Ranges of the binary's addresses that were executed.
+#[derive(Debug, Default, Clone)]
+pub struct Coverage {
+    /// Sorted by their start and not overlapping.
+    ranges: Vec<Range<PhysAddr>>,
+}
+
+impl Coverage {
+    pub fn new(mut ranges: Vec<Range<PhysAddr>>) -> Self {
+        ranges.retain(|range| !range.is_empty());
+        ranges.sort_unstable_by_key(|range| range.start);
+
+        let mut merged: Vec<Range<PhysAddr>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+
+        Self { ranges: merged }
+    }
+
+    /// Whether the instruction at `addr` was executed.
+    pub fn contains(&self, addr: PhysAddr) -> bool {
+        let idx = self.ranges.partition_point(|range| range.start <= addr);
+        idx > 0 && addr < self.ranges[idx - 1].end
+    }
+
+    /// Number of separate ranges that were executed.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// Whether `bytes` start with the magic of a raw (`.profraw`) or indexed (`.profdata`) LLVM
+/// profile, in either byte order.
+fn is_profile(bytes: &[u8]) -> bool {
+    const MAGICS: [&[u8; 8]; 4] =
+        [b"\x81rforpl\xff", b"\xfflprofr\x81", b"\x81iforpl\xff", b"\xfflprofi\x81"];
+
+    MAGICS.iter().any(|magic| bytes.starts_with(&magic[..]))
+}
+
+/// Number in hex, with or without a `0x` prefix.
+fn parse_hex(text: &str) -> Option<usize> {
+    let text = text.trim();
+    let digits = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")).unwrap_or(text);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+/// Addresses in hex, one per line, each marking the instruction at it as executed. Empty
+/// lines and those starting with a `#` are skipped.
+fn parse_addresses(text: &str) -> Result<Vec<Range<PhysAddr>>, Error> {
+    let mut ranges = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Columns after the address, such as hit counts, don't matter.
+        let addr = line.split(|c: char| c.is_whitespace() || c == ',').next().unwrap_or("");
+        let addr = parse_hex(addr).ok_or(Error::InvalidLine(idx + 1))?;
+        ranges.push(addr..addr + 1);
+    }
+
+    Ok(ranges)
+}
+
+/// Lines with a non-zero count in each source file of an lcov tracefile.
+fn parse_lcov(text: &str) -> Result<Vec<(PathBuf, BTreeSet<usize>)>, Error> {
+    let mut files: Vec<(PathBuf, BTreeSet<usize>)> = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(path) = line.strip_prefix("SF:") {
+            files.push((PathBuf::from(path), BTreeSet::new()));
+            continue;
+        }
+
+        // Records of functions and branches are only made of lines that are also listed here.
+        let Some(record) = line.strip_prefix("DA:") else {
+            continue;
+        };
+
+        let mut fields = record.split(',');
+        let line = fields.next().and_then(|line| line.parse::<usize>().ok());
+        let count = fields.next().and_then(|count| count.parse::<f64>().ok());
+        let (Some(line), Some(count), Some((_, lines))) = (line, count, files.last_mut()) else {
+            return Err(Error::InvalidLine(idx + 1));
+        };
+
+        if count > 0.0 {
+            lines.insert(line);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Line of a drcov header, along with the bytes after it.
+fn split_line(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = bytes.iter().position(|&byte| byte == b'\n')?;
+    Some((&bytes[..end], &bytes[end + 1..]))
+}
+
+/// Basic blocks of the module called `name` in a drcov file, given the address the binary
+/// starts at.
+///
+/// Block offsets are relative to the module entry they refer to. Newer versions list each of a
+/// module's segments as a separate entry, these are placed relative to the module's first one.
+fn parse_drcov(
+    bytes: &[u8],
+    name: &OsStr,
+    base: PhysAddr,
+) -> Result<Vec<Range<PhysAddr>>, Error> {
+    // Version 1 doesn't name it's columns.
+    let mut columns: Vec<String> = ["id", "base", "end", "entry", "path"].map(String::from).into();
+    let mut modules_left = 0;
+    // Start of each entry, along with the entry of the module it's a segment of.
+    let mut entries: HashMap<usize, (usize, usize)> = HashMap::new();
+    let mut ids = Vec::new();
+    let mut rest = bytes;
+    let mut line_no = 0;
+
+    let bb_count = loop {
+        let (line, tail) = split_line(rest).ok_or(Error::Truncated)?;
+        rest = tail;
+        line_no += 1;
+
+        let line = std::str::from_utf8(line).map_err(|_| Error::InvalidLine(line_no))?;
+        let line = line.trim_end_matches('\r');
+
+        if let Some(table) = line.strip_prefix("Module Table:") {
+            // Either `Module Table: <count>` or `Module Table: version <v>, count <count>`.
+            let count = table.rsplit(' ').next().and_then(|count| count.parse().ok());
+            modules_left = count.ok_or(Error::InvalidLine(line_no))?;
+        } else if let Some(names) = line.strip_prefix("Columns:") {
+            columns = names.split(',').map(|name| name.trim().to_string()).collect();
+        } else if let Some(table) = line.strip_prefix("BB Table:") {
+            let count = table.split_whitespace().next().and_then(|count| count.parse().ok());
+            break count.ok_or(Error::InvalidLine(line_no))?;
+        } else if modules_left > 0 {
+            modules_left -= 1;
+
+            // Paths are last and might contain commas.
+            let fields: Vec<&str> = line.splitn(columns.len(), ',').map(str::trim).collect();
+            let field = |names: &[&str]| {
+                names.iter().find_map(|name| {
+                    let idx = columns.iter().position(|column| column == name)?;
+                    fields.get(idx).copied()
+                })
+            };
+
+            let id = field(&["id"]).and_then(|id| id.parse::<usize>().ok());
+            let start = field(&["start", "base"]).and_then(parse_hex);
+            let (Some(id), Some(start)) = (id, start) else {
+                return Err(Error::InvalidLine(line_no));
+            };
+
+            let containing = field(&["containing_id"]).and_then(|id| id.parse().ok());
+            entries.insert(id, (start, containing.unwrap_or(id)));
+
+            let path = Path::new(field(&["path"]).unwrap_or(""));
+            if path.file_name() == Some(name) {
+                ids.push(id);
+            }
+        }
+        // Other lines such as the version and flavor don't matter.
+    };
+
+    if ids.is_empty() {
+        return Err(Error::ModuleNotFound(name.to_string_lossy().into_owned()));
+    }
+
+    // Offset of each of the module's entries to the module's first one.
+    let offsets: HashMap<usize, usize> = ids
+        .iter()
+        .map(|&id| {
+            let (start, containing) = entries[&id];
+            let module_start = entries.get(&containing).map_or(start, |&(start, _)| start);
+            (id, start.wrapping_sub(module_start))
+        })
+        .collect();
+
+    let mut ranges = Vec::new();
+    let mut push = |id: usize, offset: usize, size: usize| {
+        if let Some(entry_offset) = offsets.get(&id) {
+            let start = base.wrapping_add(*entry_offset).wrapping_add(offset);
+            ranges.push(start..start + size);
+        }
+    };
+
+    if rest.starts_with(b"module[") {
+        // Written as text, each block as `module[  <id>]: 0x<offset>, <size>`.
+        let text = std::str::from_utf8(rest).map_err(|_| Error::InvalidLine(line_no + 1))?;
+        for (idx, line) in text.lines().take(bb_count).enumerate() {
+            let block = line.strip_prefix("module[").and_then(|line| {
+                let (id, block) = line.split_once("]:")?;
+                let (offset, size) = block.split_once(',')?;
+                Some((id.trim().parse().ok()?, parse_hex(offset)?, size.trim().parse().ok()?))
+            });
+
+            let (id, offset, size) = block.ok_or(Error::InvalidLine(line_no + idx + 1))?;
+            push(id, offset, size);
+        }
+    } else {
+        // Each block is a 32-bit offset, 16-bit size and 16-bit module id.
+        let table = rest.get(..bb_count * 8).ok_or(Error::Truncated)?;
+        for entry in table.chunks_exact(8) {
+            let offset = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let size = u16::from_le_bytes([entry[4], entry[5]]);
+            let id = u16::from_le_bytes([entry[6], entry[7]]);
+            push(id as usize, offset as usize, size as usize);
+        }
+    }
+
+    Ok(ranges)
+}
+
+impl Processor {
+    /// Read which instructions were executed from a drcov, lcov or address list file, telling
+    /// them apart by their contents.
+    pub fn load_coverage(&self, path: &Path) -> Result<Coverage, Error> {
+        let bytes = std::fs::read(path).map_err(Error::IO)?;
+
+        if is_profile(&bytes) {
+            return Err(Error::Profile);
+        }
+
+        if bytes.starts_with(b"DRCOV VERSION") {
+            let name = self.path.file_name().unwrap_or_default();
+            let ranges = parse_drcov(&bytes, name, self.image_base())?;
+            return Ok(Coverage::new(ranges));
+        }
+
+        let text = std::str::from_utf8(&bytes).map_err(|_| Error::UnknownFormat)?;
+        let ranges = match text.lines().any(|line| line.starts_with("SF:")) {
+            true => self.line_ranges(&parse_lcov(text)?),
+            false => parse_addresses(text)?,
+        };
+
+        Ok(Coverage::new(ranges))
+    }
+
+    /// Address the binary's image starts at, which offsets into drcov modules are relative to.
+    fn image_base(&self) -> PhysAddr {
+        match object::File::parse(self.file_bytes()) {
+            Ok(obj) if obj.format() != BinaryFormat::Elf => obj.relative_address_base() as PhysAddr,
+            // ELF images are mapped from the page their first segment is on.
+            _ => self.segments().map(|segment| segment.start).min().unwrap_or(0) & !0xfff,
+        }
+    }
+
+    /// Instructions generated for the given lines of each source file, using the line info.
+    fn line_ranges(&self, files: &[(PathBuf, BTreeSet<usize>)]) -> Vec<Range<PhysAddr>> {
+        let rows = &self.index.file_attrs;
+        let mut ranges = Vec::new();
+
+        for (idx, row) in rows.iter().enumerate() {
+            let covered = files.iter().any(|(path, lines)| {
+                lines.contains(&row.item.line)
+                    && (path.ends_with(&row.item.path) || row.item.path.ends_with(path))
+            });
+
+            if !covered {
+                continue;
+            }
+
+            // A row's instructions go up to the next row, without leaving the section.
+            let next = rows[idx..].iter().map(|next| next.addr).find(|&addr| addr > row.addr);
+            let section_end = self.section_by_addr(row.addr).map(|section| section.end);
+            let end = match (next, section_end) {
+                (Some(next), Some(section_end)) => next.min(section_end),
+                (next, section_end) => next.or(section_end).unwrap_or(row.addr + 1),
+            };
+
+            ranges.push(row.addr..end);
+        }
+
+        ranges
+    }
+
+    /// Number of instructions of the function starting at `func` that were executed, along
+    /// with how many instructions it has.
+    pub fn function_coverage(&self, coverage: &Coverage, func: PhysAddr) -> Option<(usize, usize)> {
+        let graph = self.cfg(func)?;
+        let instructions = graph.blocks.iter().flat_map(|block| block.instructions.iter());
+        let (covered, total) = instructions.fold((0, 0), |(covered, total), &addr| {
+            (covered + coverage.contains(addr) as usize, total + 1)
+        });
+
+        Some((covered, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merged() {
+        let coverage = Coverage::new(vec![0x1010..0x1020, 0x1000..0x1008, 0x1008..0x1010, 5..5]);
+        assert_eq!(coverage.len(), 1);
+        assert!(coverage.contains(0x1000));
+        assert!(coverage.contains(0x101f));
+        assert!(!coverage.contains(0x1020));
+        assert!(!coverage.contains(5));
+        assert!(!Coverage::default().contains(0));
+    }
+
+    #[test]
+    fn addresses() {
+        let text = "# from afl-showmap\n0x401000\n\n401010, 3\n";
+        assert_eq!(parse_addresses(text).unwrap(), vec![0x401000..0x401001, 0x401010..0x401011]);
+        assert!(matches!(parse_addresses("0x10\nmain\n"), Err(Error::InvalidLine(2))));
+    }
+
+    #[test]
+    fn lcov() {
+        let text = "TN:\nSF:/src/main.c\nFN:3,main\nDA:3,1\nDA:4,0\nDA:5,12\nend_of_record\n\
+                    SF:/src/util.c\nDA:1,0\nend_of_record\n";
+        let files = parse_lcov(text).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, Path::new("/src/main.c"));
+        assert_eq!(files[0].1.iter().copied().collect::<Vec<_>>(), [3, 5]);
+        assert!(files[1].1.is_empty());
+        assert!(matches!(parse_lcov("DA:1,1\n"), Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn drcov() {
+        let mut bytes = b"DRCOV VERSION: 2\nDRCOV FLAVOR: drcov\n\
+                          Module Table: version 2, count 2\n\
+                          Columns: id, base, end, entry, checksum, timestamp, path\n \
+                          0, 0x7f0000000000, 0x7f0000100000, 0x0, 0x0, 0x0, /lib/libc.so.6\n \
+                          1, 0x555555554000, 0x555555556000, 0x0, 0x0, 0x0, /tmp/target\n\
+                          BB Table: 3 bbs\n"
+            .to_vec();
+
+        for (offset, size, id) in [(0x1130u32, 8u16, 1u16), (0x20, 4, 0), (0x1140, 16, 1)] {
+            bytes.extend(offset.to_le_bytes());
+            bytes.extend(size.to_le_bytes());
+            bytes.extend(id.to_le_bytes());
+        }
+
+        let ranges = parse_drcov(&bytes, OsStr::new("target"), 0).unwrap();
+        assert_eq!(ranges, vec![0x1130..0x1138, 0x1140..0x1150]);
+
+        let ranges = parse_drcov(&bytes, OsStr::new("target"), 0x400000).unwrap();
+        assert_eq!(ranges[0], 0x401130..0x401138);
+
+        assert!(matches!(
+            parse_drcov(&bytes, OsStr::new("other"), 0),
+            Err(Error::ModuleNotFound(_))
+        ));
+        assert!(matches!(
+            parse_drcov(&bytes[..bytes.len() - 1], OsStr::new("target"), 0),
+            Err(Error::Truncated)
+        ));
+    }
+
+    #[test]
+    fn drcov_segments() {
+        let text = "DRCOV VERSION: 3\nDRCOV FLAVOR: drcov\nModule Table: version 4, count 2\n\
+                    Columns: id, containing_id, start, end, entry, offset, path\n\
+                    0, 0, 0x555555554000, 0x555555555000, 0x0, 0x0, /tmp/target\n\
+                    1, 0, 0x555555555000, 0x555555556000, 0x0, 0x1000, /tmp/target\n\
+                    BB Table: 2 bbs\nmodule[  1]: 0x0000000000000130,  8\n\
+                    module[  0]: 0x0000000000000040,   2\n";
+
+        let ranges = parse_drcov(text.as_bytes(), OsStr::new("target"), 0).unwrap();
+        assert_eq!(ranges, vec![0x1130..0x1138, 0x40..0x42]);
+    }
+
+    #[test]
+    fn profiles() {
+        assert!(is_profile(b"\x81rforpl\xff\x08\x00\x00\x00"));
+        assert!(is_profile(b"\xfflprofi\x81"));
+        assert!(!is_profile(b"DRCOV VERSION: 2\n"));
+    }
+}
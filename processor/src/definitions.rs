@@ -0,0 +1,397 @@
+//! Overrides of how code sections are decoded. Sections are decoded by sweeping through them,
+//! which goes out of sync when data is mixed in with the code or when instructions overlap, as
+//! happens when a branch targets the middle of an instruction. The user can then decide what's
+//! code and what's data.
+
+use crate::modes::Modes;
+use crate::{drop_instruction, ArmMode, Code, Edit, Isa, Processor};
+use decoder::XrefKind;
+use processor_shared::{PhysAddr, Section};
+use std::ops::Range;
+
+/// How the user defined a range of a code section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Definition {
+    /// Decoded from the start of the range, replacing any instruction overlapping it.
+    Code,
+    /// Shown as bytes instead of instructions.
+    Data,
+    /// Decoded as ARM instructions, for 32-bit ARM.
+    Arm,
+    /// Decoded as Thumb instructions, for 32-bit ARM.
+    Thumb,
+}
+
+/// A change the user makes to how a code section is decoded, see [`Processor::redefine`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redefine {
+    /// Decode from an address up to the next function.
+    Code(PhysAddr),
+    /// Decode from an address in an instruction set, up to where the mode changes next.
+    Mode(PhysAddr, ArmMode),
+    /// Show a range as bytes.
+    Data(PhysAddr, PhysAddr),
+    /// Forget how the bytes at an address were defined.
+    Undefine(PhysAddr),
+}
+
+/// Definitions made by the user and the instruction sets they chose, as they were before a
+/// [`Redefine`] so that it can be undone.
+#[derive(Debug, Clone)]
+pub struct Definitions {
+    definitions: Vec<(PhysAddr, PhysAddr, Definition)>,
+    modes: Modes,
+}
+
+impl Code {
+    /// Forget the instructions and errors starting in `range`, along with what they refer to.
+    pub(crate) fn undecode(&mut self, isa: &Isa, range: Range<PhysAddr>) {
+        self.instructions.retain_mut(|inst| {
+            if range.contains(&inst.addr) {
+                drop_instruction(isa, &mut inst.item);
+                return false;
+            }
+            true
+        });
+        self.errors.retain(|err| !range.contains(&err.addr));
+        self.xrefs.remove_from(range.clone());
+        self.tables.remove_from(range);
+    }
+}
+
+impl Processor {
+    /// Start and end of the instruction or error decoded over `addr`, if there's one.
+    pub(crate) fn decoded_over(&self, code: &Code, addr: PhysAddr) -> Option<Range<PhysAddr>> {
+        let idx = code.instructions.partition_point(|inst| inst.addr <= addr);
+        if let Some(inst) = idx.checked_sub(1).map(|idx| &code.instructions[idx]) {
+            let end = inst.addr + self.instruction_width(&inst.item);
+            if addr < end {
+                return Some(inst.addr..end);
+            }
+        }
+
+        let idx = code.errors.partition_point(|err| err.addr <= addr);
+        let err = &code.errors[idx.checked_sub(1)?];
+        let end = err.addr + err.item.size();
+        (addr < end).then_some(err.addr..end)
+    }
+
+    /// Where the function at `addr` ends, that is at the next symbol or the end of `section`.
+    pub(crate) fn function_end(&self, section: &Section, addr: PhysAddr) -> PhysAddr {
+        let next = match self.index.syms.search(addr + 1) {
+            Ok(idx) | Err(idx) => self.index.syms.get(idx).map(|sym| sym.addr),
+        };
+
+        next.unwrap_or(section.end).min(section.end)
+    }
+
+    /// `start..end` widened to the instructions around it, as bytes that aren't decoded are
+    /// shown together up to the next instruction.
+    fn changed_range(&self, section: &Section, start: PhysAddr, end: PhysAddr) -> Range<PhysAddr> {
+        let code = self.code.read().unwrap();
+        let (mut start, mut end) = (start, end);
+
+        while start > section.start && self.decoded_over(&code, start - 1).is_none() {
+            start -= 1;
+        }
+
+        while end < section.end
+            && code.instruction_by_addr(end).is_none()
+            && code.error_by_addr(end).is_none()
+        {
+            end += 1;
+        }
+
+        start..end
+    }
+
+    /// Targets of branches into the middle of the instruction at `addr`, where different
+    /// instructions are decoded when they're reached by the branch.
+    pub fn overlapping_targets(&self, addr: PhysAddr) -> Vec<PhysAddr> {
+        self.overlapping(&self.code.read().unwrap(), addr)
+    }
+
+    pub(crate) fn overlapping(&self, code: &Code, addr: PhysAddr) -> Vec<PhysAddr> {
+        let width = match code.instruction_by_addr(addr) {
+            Some(inst) => self.instruction_width(inst),
+            None => return Vec::new(),
+        };
+
+        let mut targets: Vec<PhysAddr> = code
+            .xrefs
+            .to_range(addr + 1..addr + width)
+            .iter()
+            .filter(|xref| xref.kind != XrefKind::Data)
+            .map(|xref| xref.to)
+            .collect();
+
+        targets.dedup();
+        targets
+    }
+
+    /// How the user defined the bytes at `addr`, if they did.
+    pub fn definition(&self, addr: PhysAddr) -> Option<Definition> {
+        let definitions = self.definitions.lock().unwrap();
+        let mut containing = definitions.iter().rev();
+        let (_, _, definition) =
+            containing.find(|(start, end, _)| (*start..*end).contains(&addr))?;
+        Some(*definition)
+    }
+
+    /// Decode from `addr` up to the next function, replacing the instruction `addr` is in the
+    /// middle of. Returns the range of addresses whose blocks changed.
+    pub fn define_code(&self, addr: PhysAddr) -> Option<Range<PhysAddr>> {
+        let section = self.section_by_addr(addr).filter(|s| self.section_is_code(s))?;
+        let end = self.function_end(section, addr);
+        self.decode_defined(section, addr, end, Definition::Code)
+    }
+
+    /// Decode from `addr` in `mode`, up to where the mode changes next. Returns the range of
+    /// addresses whose blocks changed.
+    pub fn define_mode(&self, addr: PhysAddr, mode: ArmMode) -> Option<Range<PhysAddr>> {
+        let section = self.section_by_addr(addr).filter(|s| self.section_is_code(s))?;
+        let end = match self.modes.read().unwrap().next_change(addr) {
+            Some(change) => change.min(section.end),
+            None => section.end,
+        };
+
+        self.define_mode_range(addr, end, mode)
+    }
+
+    fn define_mode_range(
+        &self,
+        start: PhysAddr,
+        end: PhysAddr,
+        mode: ArmMode,
+    ) -> Option<Range<PhysAddr>> {
+        if self.arch != object::Architecture::Arm {
+            return None;
+        }
+
+        let section = self.section_by_addr(start).filter(|s| self.section_is_code(s))?;
+        let end = end.min(section.end);
+        let definition = match mode {
+            ArmMode::Arm => Definition::Arm,
+            ArmMode::Thumb => Definition::Thumb,
+            ArmMode::Data => return self.define_data(start, end),
+        };
+
+        self.modes.write().unwrap().set(start, end, mode);
+        self.decode_defined(section, start, end, definition)
+    }
+
+    /// Decode `start..end` as defined by the user, replacing the instruction `start` is in the
+    /// middle of.
+    fn decode_defined(
+        &self,
+        section: &Section,
+        start: PhysAddr,
+        end: PhysAddr,
+        definition: Definition,
+    ) -> Option<Range<PhysAddr>> {
+        if start >= end {
+            return None;
+        }
+
+        let mut code = self.code.write().unwrap();
+        if let Some(overlapped) = self.decoded_over(&code, start) {
+            code.undecode(&self.isa, overlapped.start..start);
+        }
+        drop(code);
+
+        self.definitions.lock().unwrap().push((start, end, definition));
+        let (_, end) = self.redecode(section, start, end);
+        Some(self.changed_range(section, start, end))
+    }
+
+    /// Show `start..end` as bytes instead of instructions. Returns the range of addresses whose
+    /// blocks changed.
+    pub fn define_data(&self, start: PhysAddr, end: PhysAddr) -> Option<Range<PhysAddr>> {
+        let section = self.section_by_addr(start).filter(|s| self.section_is_code(s))?;
+        let end = end.min(section.end);
+        if start >= end {
+            return None;
+        }
+
+        self.definitions.lock().unwrap().push((start, end, Definition::Data));
+        self.code.write().unwrap().undecode(&self.isa, start..end);
+        Some(self.changed_range(section, start, end))
+    }
+
+    /// Forget how the user defined the bytes at `addr`, decoding them as the rest of the
+    /// section. Returns the range of addresses whose blocks changed.
+    pub fn undefine(&self, addr: PhysAddr) -> Option<Range<PhysAddr>> {
+        let section = self.section_by_addr(addr)?;
+        let (start, end, data_before) = {
+            let mut definitions = self.definitions.lock().unwrap();
+            let mut range: Option<(PhysAddr, PhysAddr)> = None;
+            definitions.retain(|&(start, end, _)| {
+                if !(start..end).contains(&addr) {
+                    return true;
+                }
+
+                range = Some(range.map_or((start, end), |(s, e)| (s.min(start), e.max(end))));
+                false
+            });
+
+            let (start, end) = range?;
+            let data_before = definitions
+                .iter()
+                .filter(|&&(_, data_end, kind)| kind == Definition::Data && data_end <= start)
+                .map(|&(_, data_end, _)| data_end)
+                .max();
+
+            (start, end, data_before)
+        };
+
+        self.modes.write().unwrap().unset(addr);
+
+        // Continue from the instruction before the range, as defining code might have replaced
+        // the one overlapping it, without decoding bytes still defined as data.
+        let from = self.changed_range(section, start, end).start;
+        let from = data_before.map_or(from, |data_end| from.max(data_end));
+        let (_, end) = self.redecode(section, from, end);
+        Some(self.changed_range(section, from, end))
+    }
+
+    /// Redefine how bytes of a code section are decoded, recording it so that it can be undone.
+    /// Returns the range of addresses whose blocks changed.
+    pub fn redefine(&self, redefine: Redefine) -> Option<Range<PhysAddr>> {
+        let before = Definitions {
+            definitions: self.definitions(),
+            modes: self.modes.read().unwrap().clone(),
+        };
+
+        let changed = self.apply_redefine(redefine)?;
+        self.record(Edit::Definition {
+            redefine,
+            before,
+            changed: changed.clone(),
+        });
+
+        Some(changed)
+    }
+
+    pub(crate) fn apply_redefine(&self, redefine: Redefine) -> Option<Range<PhysAddr>> {
+        match redefine {
+            Redefine::Code(addr) => self.define_code(addr),
+            Redefine::Mode(addr, mode) => self.define_mode(addr, mode),
+            Redefine::Data(start, end) => self.define_data(start, end),
+            Redefine::Undefine(addr) => self.undefine(addr),
+        }
+    }
+
+    /// Go back to the definitions `before` a redefinition that changed the blocks in `changed`,
+    /// by decoding them again and redoing the definitions overlapping them.
+    pub(crate) fn restore_definitions(&self, before: &Definitions, changed: &Range<PhysAddr>) {
+        let section = match self.section_by_addr(changed.start) {
+            Some(section) => section,
+            None => return,
+        };
+
+        *self.modes.write().unwrap() = before.modes.clone();
+        self.redecode(section, changed.start, changed.end.min(section.end));
+
+        let overlapping: Vec<_> = before
+            .definitions
+            .iter()
+            .copied()
+            .filter(|&(start, end, _)| start < changed.end && changed.start < end)
+            .collect();
+        self.apply_definitions(&overlapping);
+
+        // Redoing definitions records them and their modes again.
+        *self.definitions.lock().unwrap() = before.definitions.clone();
+        *self.modes.write().unwrap() = before.modes.clone();
+    }
+
+    /// Definitions made by the user, in the order they were made.
+    pub(crate) fn definitions(&self) -> Vec<(PhysAddr, PhysAddr, Definition)> {
+        self.definitions.lock().unwrap().clone()
+    }
+
+    /// Redo definitions made by the user, e.g. as they're loaded from a project.
+    pub(crate) fn apply_definitions(&self, definitions: &[(PhysAddr, PhysAddr, Definition)]) {
+        for &(start, end, definition) in definitions {
+            match definition {
+                Definition::Code => self.define_code(start),
+                Definition::Data => self.define_data(start, end),
+                Definition::Arm => self.define_mode_range(start, end, ArmMode::Arm),
+                Definition::Thumb => self.define_mode_range(start, end, ArmMode::Thumb),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RawOptions;
+    use object::{Architecture, Endianness};
+
+    fn parse(name: &str, bytes: &[u8]) -> Processor {
+        let name = format!("bite_definitions_{name}_{}.bin", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, bytes).unwrap();
+
+        let options = RawOptions {
+            arch: Architecture::X86_64,
+            endianness: Endianness::Little,
+            base: 0x1000,
+        };
+        let processor = Processor::parse_raw(&path, &options).unwrap();
+        let _ = std::fs::remove_file(&path);
+        processor
+    }
+
+    fn addrs(processor: &Processor) -> Vec<PhysAddr> {
+        let code = processor.code.read().unwrap();
+        code.instructions.iter().map(|inst| inst.addr).collect()
+    }
+
+    #[test]
+    fn undefined_data() {
+        // mov eax, 1; ret; nop
+        let processor = parse("undefined_data", &[0xb8, 0x01, 0x00, 0x00, 0x00, 0xc3, 0x90]);
+        assert_eq!(addrs(&processor), [0x1000, 0x1005, 0x1006]);
+
+        processor.define_data(0x1000, 0x1005).unwrap();
+        assert_eq!(processor.definition(0x1002), Some(Definition::Data));
+        assert_eq!(addrs(&processor), [0x1005, 0x1006]);
+
+        processor.undefine(0x1002).unwrap();
+        assert_eq!(processor.definition(0x1002), None);
+        assert_eq!(addrs(&processor), [0x1000, 0x1005, 0x1006]);
+    }
+
+    #[test]
+    fn code_in_instruction() {
+        // mov eax, 1; ret; nop
+        let processor = parse("code_in_instruction", &[0xb8, 0x01, 0x00, 0x00, 0x00, 0xc3, 0x90]);
+
+        // add [rax], eax; add [rax], al replace the `mov` they're in the middle of.
+        processor.define_code(0x1001).unwrap();
+        assert_eq!(processor.definition(0x1001), Some(Definition::Code));
+        assert_eq!(addrs(&processor), [0x1001, 0x1003, 0x1005, 0x1006]);
+    }
+
+    #[test]
+    fn overlapping_branches() {
+        // jmp 0x1003; mov eax, 0x90909090; lea rax, [rip - 10]; ret
+        let processor = parse(
+            "overlapping_branches",
+            &[
+                0xeb, 0x01, 0xb8, 0x90, 0x90, 0x90, 0x90, 0x48, 0x8d, 0x05, 0xf6, 0xff, 0xff, 0xff,
+                0xc3,
+            ],
+        );
+        assert_eq!(addrs(&processor), [0x1000, 0x1002, 0x1007, 0x100e]);
+
+        // The `lea` refers to 0x1004 in the middle of the `mov`, but only as data.
+        let code = processor.code.read().unwrap();
+        assert!(code.xrefs.to_range(0x1004..0x1005).iter().any(|xref| xref.from == 0x1007));
+        drop(code);
+        assert_eq!(processor.overlapping_targets(0x1002), [0x1003]);
+        assert_eq!(processor.overlapping_targets(0x1007), []);
+    }
+}
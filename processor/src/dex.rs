@@ -0,0 +1,245 @@
+//! Parsing of DEX files, which hold the Dalvik bytecode of Android apps.
+
+use crate::bytecode::{java_type, Method, Program};
+use dalvik::Pool;
+use std::sync::Arc;
+
+/// Size of the header, which points at the tables of the file.
+const HEADER_SIZE: usize = 0x70;
+
+/// Offset of the code units of a method from the start of it's code item.
+const INSNS_OFFSET: usize = 16;
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Unsigned LEB128 at `offset`, which is advanced past it.
+fn uleb128(bytes: &[u8], offset: &mut usize) -> Option<usize> {
+    let mut value = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes.get(*offset)?;
+        *offset += 1;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Offsets of the items of the table whose size and offset are stored in the header at `at`,
+/// where each item is `size` bytes.
+fn table(bytes: &[u8], at: usize, size: usize) -> Option<impl Iterator<Item = usize>> {
+    let count = u32_at(bytes, at)?;
+    let offset = u32_at(bytes, at + 4)?;
+    if offset.checked_add(count.checked_mul(size)?)? > bytes.len() {
+        return None;
+    }
+
+    Some((0..count).map(move |idx| offset + idx * size))
+}
+
+/// String stored at `offset`, which is MUTF-8 prefixed by it's length in UTF-16 code units.
+fn string(bytes: &[u8], mut offset: usize) -> Option<Arc<str>> {
+    uleb128(bytes, &mut offset)?;
+    let bytes = bytes.get(offset..)?;
+    let len = bytes.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..len]).into())
+}
+
+/// Types of the list at `offset`, joined by commas.
+fn type_list(bytes: &[u8], offset: usize, types: &[Arc<str>]) -> Option<String> {
+    if offset == 0 {
+        return Some(String::new());
+    }
+
+    let count = u32_at(bytes, offset)?;
+    let mut list = Vec::with_capacity(count.min(0x100));
+    for idx in 0..count {
+        let ty = u16_at(bytes, offset + 4 + idx * 2)?;
+        list.push(&**types.get(ty)?);
+    }
+
+    Some(list.join(", "))
+}
+
+/// Program of the DEX file `bytes`, None if it's truncated or refers to data outside of it.
+pub(crate) fn parse(bytes: &[u8]) -> Option<Program<'_, Pool>> {
+    if !bytes.starts_with(b"dex\n") || bytes.len() < HEADER_SIZE {
+        return None;
+    }
+
+    let strings = table(bytes, 0x38, 4)?
+        .map(|offset| string(bytes, u32_at(bytes, offset)?))
+        .collect::<Option<Vec<_>>>()?;
+
+    let types = table(bytes, 0x40, 4)?
+        .map(|offset| Some(java_type(strings.get(u32_at(bytes, offset)?)?).into()))
+        .collect::<Option<Vec<Arc<str>>>>()?;
+
+    // parameters are shown apart from the return type in names of methods
+    let mut params = Vec::new();
+    let mut protos = Vec::new();
+    for offset in table(bytes, 0x48, 12)? {
+        let ret = types.get(u32_at(bytes, offset + 4)?)?;
+        let list = type_list(bytes, u32_at(bytes, offset + 8)?, &types)?;
+        protos.push(format!("{ret}({list})").into());
+        params.push(list);
+    }
+
+    let fields = table(bytes, 0x50, 8)?
+        .map(|offset| {
+            let class = types.get(u16_at(bytes, offset)?)?;
+            let name = strings.get(u32_at(bytes, offset + 4)?)?;
+            Some(format!("{class}.{name}").into())
+        })
+        .collect::<Option<Vec<Arc<str>>>>()?;
+
+    let methods = table(bytes, 0x58, 8)?
+        .map(|offset| {
+            let class = types.get(u16_at(bytes, offset)?)?;
+            let params = params.get(u16_at(bytes, offset + 2)?)?;
+            let name = strings.get(u32_at(bytes, offset + 4)?)?;
+            Some(format!("{class}.{name}({params})").into())
+        })
+        .collect::<Option<Vec<Arc<str>>>>()?;
+
+    let mut code = Vec::new();
+    for offset in table(bytes, 0x60, 32)? {
+        let mut offset = u32_at(bytes, offset + 24)?;
+        if offset == 0 {
+            continue;
+        }
+
+        let static_fields = uleb128(bytes, &mut offset)?;
+        let instance_fields = uleb128(bytes, &mut offset)?;
+        let direct_methods = uleb128(bytes, &mut offset)?;
+        let virtual_methods = uleb128(bytes, &mut offset)?;
+
+        // fields are stored as the difference to the previous index and their access flags
+        for _ in 0..static_fields.saturating_add(instance_fields) {
+            uleb128(bytes, &mut offset)?;
+            uleb128(bytes, &mut offset)?;
+        }
+
+        // indices of the direct and virtual methods are each relative to their list
+        for count in [direct_methods, virtual_methods] {
+            let mut idx = 0usize;
+            for _ in 0..count {
+                idx = idx.checked_add(uleb128(bytes, &mut offset)?)?;
+                uleb128(bytes, &mut offset)?;
+                let code_off = uleb128(bytes, &mut offset)?;
+
+                // abstract and native methods have no code
+                if code_off == 0 {
+                    continue;
+                }
+
+                let units = u32_at(bytes, code_off + 12)?;
+                let start = code_off + INSNS_OFFSET;
+                let insns = bytes.get(start..start.checked_add(units.checked_mul(2)?)?)?;
+                code.push(Method {
+                    name: methods.get(idx)?.to_string(),
+                    code: insns,
+                });
+            }
+        }
+    }
+
+    let pool = Pool {
+        strings,
+        types,
+        fields,
+        methods,
+        protos,
+        code: Default::default(),
+    };
+
+    Some(Program {
+        pool,
+        methods: code,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// DEX file of a class `Main` with a static method `run(int)` doing `const/4 v0, 1` and
+    /// `return v0`.
+    fn file() -> Vec<u8> {
+        let mut bytes = vec![0; 0x110];
+        bytes[..8].copy_from_slice(b"dex\n035\0");
+
+        let mut put = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        // strings "I", "LMain;" and "run"
+        put(0x38, 3);
+        put(0x3c, 0x70);
+        put(0x70, 0xc0);
+        put(0x74, 0xc3);
+        put(0x78, 0xcb);
+
+        // types int and Main
+        put(0x40, 2);
+        put(0x44, 0x80);
+        put(0x80, 0);
+        put(0x84, 1);
+
+        // proto returning int with it's parameters at 0xd8
+        put(0x48, 1);
+        put(0x4c, 0x88);
+        put(0x90, 0xd8);
+
+        // method Main.run
+        put(0x58, 1);
+        put(0x5c, 0x94);
+        put(0x94, 1);
+        put(0x98, 2);
+
+        // class Main with it's class data at 0xe0
+        put(0x60, 1);
+        put(0x64, 0xa0);
+        put(0xb8, 0xe0);
+
+        // parameters of the proto, an int
+        put(0xd8, 1);
+
+        // code item with two code units
+        put(0xfc, 2);
+        put(0x100, 0x000f_1012);
+
+        bytes[0xc0..0xc3].copy_from_slice(b"\x01I\0");
+        bytes[0xc3..0xcb].copy_from_slice(b"\x06LMain;\0");
+        bytes[0xcb..0xd0].copy_from_slice(b"\x03run\0");
+
+        // a direct static method at index 0 with it's code item at 0xf0
+        bytes[0xe0..0xe8].copy_from_slice(&[0, 0, 1, 0, 0, 0x08, 0xf0, 0x01]);
+        bytes
+    }
+
+    #[test]
+    fn methods() {
+        let bytes = file();
+        let program = parse(&bytes).unwrap();
+
+        assert_eq!(&*program.pool.types[1], "Main");
+        assert_eq!(&*program.pool.protos[0], "int(int)");
+        assert_eq!(&*program.pool.methods[0], "Main.run(int)");
+        assert_eq!(program.methods.len(), 1);
+        assert_eq!(program.methods[0].name, "Main.run(int)");
+        assert_eq!(program.methods[0].code, [0x12, 0x10, 0x0f, 0x00]);
+
+        assert!(parse(&bytes[..0x90]).is_none());
+    }
+}
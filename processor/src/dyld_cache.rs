@@ -0,0 +1,132 @@
+//! Images of a dyld shared cache, the file macOS and iOS combine their system libraries into.
+
+use crate::{Error, Loading, Processor};
+use memmap2::Mmap;
+use object::read::macho::DyldCache;
+use object::Endianness;
+use std::path::{Path, PathBuf};
+
+/// Whether the image at `path` is the one named `name`, either by it's full path or file name.
+fn is_image(path: &str, name: &str) -> bool {
+    path == name || path.rsplit('/').next() == Some(name)
+}
+
+/// Files a cache at `path` is split into besides the main one, e.g. `dyld_shared_cache_arm64e.1`
+/// or `dyld_shared_cache_arm64e.01` followed by `dyld_shared_cache_arm64e.symbols`.
+fn subcache_paths(path: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    let with_suffix = |suffix: &str| {
+        let mut path = path.as_os_str().to_os_string();
+        path.push(".");
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+
+    for idx in 1.. {
+        let short = with_suffix(&idx.to_string());
+        let padded = with_suffix(&format!("{idx:02}"));
+        match (short.is_file(), padded.is_file()) {
+            (true, _) => paths.push(short),
+            (false, true) => paths.push(padded),
+            (false, false) => break,
+        }
+    }
+
+    let symbols = with_suffix("symbols");
+    if symbols.is_file() {
+        paths.push(symbols);
+    }
+
+    paths
+}
+
+fn map(path: &Path) -> Result<(std::fs::File, Mmap), Error> {
+    let file = std::fs::File::open(path).map_err(Error::IO)?;
+    let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
+    Ok((file, mmap))
+}
+
+impl Processor {
+    /// Extract the image named `image` from the shared cache at `path` and analyze it.
+    ///
+    /// Images are found by their full path, e.g. `/usr/lib/libobjc.A.dylib`, or their file name,
+    /// e.g. `Foundation`.
+    pub fn parse_dyld_cache<P: AsRef<Path>>(path: P, image: &str) -> Result<Self, Error> {
+        Self::parse_dyld_cache_with(path, image, &Loading::default())
+    }
+
+    /// Parse `image` of the shared cache at `path`, reporting progress through `loading`.
+    pub fn parse_dyld_cache_with<P: AsRef<Path>>(
+        path: P,
+        image: &str,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let (file, mmap) = map(path.as_ref())?;
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+
+        let mut subcaches = Vec::new();
+        for path in subcache_paths(path.as_ref()) {
+            subcaches.push(map(&path)?.1);
+        }
+
+        let subcache_data: Vec<&'static [u8]> = subcaches
+            .iter()
+            .map(|mmap| unsafe { std::mem::transmute(&mmap[..]) })
+            .collect();
+
+        let cache = DyldCache::<Endianness>::parse(binary, &subcache_data)?;
+        let obj = cache
+            .images()
+            .find(|img| img.path().is_ok_and(|path| is_image(path, image)))
+            .ok_or_else(|| Error::ImageNotFound(image.to_string()))?
+            .parse_object()?;
+
+        log::complex!(
+            w "[processor::parse_dyld_cache] extracted ",
+            b image,
+            w " from a cache split into ",
+            y (subcaches.len() + 1).to_string(),
+            w " files.",
+        );
+
+        Self::parse_object(path.as_ref().to_path_buf(), file, mmap, subcaches, obj, loading)
+    }
+
+    /// Paths of all images in the shared cache at `path`.
+    pub fn dyld_cache_images<P: AsRef<Path>>(path: P) -> Result<Vec<String>, Error> {
+        let (_, mmap) = map(path.as_ref())?;
+        let subcaches = subcache_paths(path.as_ref())
+            .iter()
+            .map(|path| map(path).map(|(_, mmap)| mmap))
+            .collect::<Result<Vec<Mmap>, Error>>()?;
+
+        let subcache_data: Vec<&[u8]> = subcaches.iter().map(|mmap| &mmap[..]).collect();
+        let cache = DyldCache::<Endianness>::parse(&mmap[..], &subcache_data)?;
+        let images = cache
+            .images()
+            .filter_map(|img| img.path().ok())
+            .map(str::to_string)
+            .collect();
+
+        Ok(images)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_names() {
+        let path = "/System/Library/Frameworks/Foundation.framework/Versions/C/Foundation";
+        assert!(is_image(path, "Foundation"));
+        assert!(is_image(path, path));
+        assert!(!is_image(path, "Foundation.framework"));
+        assert!(!is_image("/usr/lib/libobjc.A.dylib", "libobjc"));
+    }
+
+    #[test]
+    fn missing_subcaches() {
+        assert!(subcache_paths(Path::new("/nonexistent/dyld_shared_cache_arm64e")).is_empty());
+    }
+}
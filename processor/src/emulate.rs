@@ -0,0 +1,211 @@
+//! Emulation of the instructions following an address, lifted into the [`ir`] and interpreted
+//! one after another from registers given by the user. Memory is read from the binary's sections
+//! with any patches applied.
+
+use crate::Processor;
+use ir::emulate::{Emulator, Flow, Memory};
+use ir::{Stmt, Var};
+use object::Architecture;
+use processor_shared::PhysAddr;
+use std::fmt;
+
+/// Where the stack pointer starts if the initial state doesn't include it.
+pub const STACK_ADDR: u64 = 0x7fff_ffff_0000;
+
+/// Why emulation stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The maximum number of instructions was emulated.
+    Limit,
+    /// There's no instruction at the address emulation continued at.
+    NoInstruction(PhysAddr),
+    /// An instruction couldn't be lifted.
+    Unlifted,
+    /// The condition of a branch or the target of a jump isn't known.
+    Unresolved,
+    Syscall,
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Limit => f.write_str("reached the maximum number of instructions"),
+            Self::NoInstruction(addr) => write!(f, "no instruction at {addr:#x}"),
+            Self::Unlifted => f.write_str("instruction isn't supported"),
+            Self::Unresolved => f.write_str("branch depends on an unknown value"),
+            Self::Syscall => f.write_str("system calls aren't emulated"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmulatedStep {
+    pub addr: PhysAddr,
+    pub text: String,
+    /// Registers the instruction wrote, with `None` if their value isn't known.
+    pub registers: Vec<(&'static str, Option<u64>)>,
+}
+
+#[derive(Debug)]
+pub struct Emulation {
+    pub start: PhysAddr,
+    pub steps: Vec<EmulatedStep>,
+    pub stop: StopReason,
+}
+
+/// Parses the initial registers of an emulation, e.g. `rdi=0x10, rsi=5`.
+pub fn parse_state(text: &str) -> Result<Vec<(String, u64)>, String> {
+    let mut state = Vec::new();
+    for entry in text.split([',', ' ']).filter(|entry| !entry.is_empty()) {
+        let (reg, value) = entry.split_once('=').ok_or_else(|| entry.to_string())?;
+        let value = match value.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+
+        match value {
+            Ok(value) if !reg.is_empty() => state.push((reg.to_ascii_lowercase(), value)),
+            _ => return Err(entry.to_string()),
+        }
+    }
+
+    Ok(state)
+}
+
+struct Sections<'a>(&'a Processor);
+
+impl Memory for Sections<'_> {
+    fn byte(&self, addr: u64) -> Option<u8> {
+        let addr = addr as PhysAddr;
+        let section = self.0.section_by_addr(addr)?;
+        self.0.bytes_by_addr(section, addr, 1).first().copied()
+    }
+}
+
+impl Processor {
+    /// Emulate at most `limit` instructions starting at `addr`, with the registers in `state`.
+    ///
+    /// Returns `None` if the binary's architecture isn't lifted.
+    pub fn emulate(
+        &self,
+        addr: PhysAddr,
+        state: &[(String, u64)],
+        limit: usize,
+    ) -> Option<Emulation> {
+        let stack_pointer = match self.arch {
+            Architecture::X86_64 => "rsp",
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => "sp",
+            _ => return None,
+        };
+
+        let mut emu = Emulator::default();
+        emu.set(stack_pointer, STACK_ADDR);
+        for (reg, value) in state {
+            emu.set(reg, *value);
+        }
+
+        let memory = Sections(self);
+        let mut steps = Vec::new();
+        let mut next = addr;
+        let stop = loop {
+            if steps.len() == limit {
+                break StopReason::Limit;
+            }
+
+            let (width, text) = {
+                let code = self.code.read().unwrap();
+                match code.instruction_by_addr(next) {
+                    Some(inst) => (self.instruction_width(inst), self.instruction_text(inst)),
+                    None => break StopReason::NoInstruction(next),
+                }
+            };
+
+            let stmts = self.lift(next)?;
+            let flow = emu.step(&memory, next as u64, width as u64, &stmts);
+
+            let mut registers: Vec<(&'static str, Option<u64>)> = Vec::new();
+            for stmt in &stmts {
+                if let Stmt::Assign(Var::Reg(reg), _) = *stmt {
+                    if !registers.iter().any(|&(written, _)| written == reg) {
+                        registers.push((reg, emu.register(reg)));
+                    }
+                }
+            }
+
+            steps.push(EmulatedStep {
+                addr: next,
+                text,
+                registers,
+            });
+
+            match flow {
+                Flow::Continue(addr) => next = addr as PhysAddr,
+                Flow::Unknown => break StopReason::Unlifted,
+                Flow::Unresolved => break StopReason::Unresolved,
+                Flow::Syscall => break StopReason::Syscall,
+            }
+        };
+
+        Some(Emulation {
+            start: addr,
+            steps,
+            stop,
+        })
+    }
+
+    /// Memory the instruction at `addr` writes when it's executed at the runtime address `pc`
+    /// with `registers`, as the runtime address and length of each write.
+    ///
+    /// Writes through addresses computed from memory, and by instructions that can't be lifted,
+    /// aren't known and left out.
+    pub fn stores(&self, addr: PhysAddr, pc: u64, registers: &[(&str, u64)]) -> Vec<(u64, usize)> {
+        let width = {
+            let code = self.code.read().unwrap();
+            match code.instruction_by_addr(addr) {
+                Some(inst) => self.instruction_width(inst),
+                None => return Vec::new(),
+            }
+        };
+
+        let stmts = match self.lift_at(addr, pc as PhysAddr) {
+            Some(stmts) => stmts,
+            None => return Vec::new(),
+        };
+
+        let mut emu = Emulator::default();
+        for &(reg, value) in registers {
+            emu.set(reg, value);
+        }
+
+        emu.step(&(), pc, width as u64, &stmts);
+        let mut written: Vec<u64> = emu.written().collect();
+        written.sort_unstable();
+
+        // Bytes written next to each other are a single write.
+        let mut stores: Vec<(u64, usize)> = Vec::new();
+        for addr in written {
+            match stores.last_mut() {
+                Some((start, len)) if start.wrapping_add(*len as u64) == addr => *len += 1,
+                _ => stores.push((addr, 1)),
+            }
+        }
+
+        stores
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state() {
+        assert_eq!(
+            parse_state("RDI=0x10, rsi=5"),
+            Ok(vec![("rdi".to_string(), 0x10), ("rsi".to_string(), 5)])
+        );
+        assert_eq!(parse_state(""), Ok(Vec::new()));
+        assert_eq!(parse_state("rdi"), Err("rdi".to_string()));
+        assert_eq!(parse_state("rdi=0xzz"), Err("rdi=0xzz".to_string()));
+    }
+}
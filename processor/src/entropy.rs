@@ -0,0 +1,166 @@
+//! Overview of the file's contents, such as to spot packed or encrypted regions.
+
+use crate::Processor;
+use processor_shared::PhysAddr;
+
+/// Most chunks the file is split into, larger files get larger chunks.
+const MAX_CHUNKS: usize = 4096;
+
+/// Smallest chunk, below which the entropy isn't telling.
+const MIN_CHUNK_SIZE: usize = 256;
+
+/// Bits of entropy per byte above which a chunk is likely compressed or encrypted.
+const HIGH_ENTROPY: f32 = 7.2;
+
+/// Fraction of a chunk's bytes that have to be of a kind for it to be classified as such.
+const CLASS_THRESHOLD: f32 = 0.9;
+
+/// What most of the bytes in a chunk look like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteClass {
+    /// Padding or zero-initialized data.
+    Zeroes,
+    /// Printable ASCII.
+    Text,
+    /// Likely compressed or encrypted.
+    Random,
+    /// Code, tables and anything else.
+    Binary,
+}
+
+/// Slice of the file as shown in an overview.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chunk {
+    /// Offset into [`Processor::file_bytes`].
+    pub offset: usize,
+    pub len: usize,
+    /// Shannon entropy in bits per byte, from 0 to 8.
+    pub entropy: f32,
+    pub class: ByteClass,
+}
+
+/// Shannon entropy of `bytes` in bits per byte.
+pub fn entropy(bytes: &[u8]) -> f32 {
+    if bytes.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+
+    let len = bytes.len() as f32;
+    counts
+        .iter()
+        .filter(|&&count| count != 0)
+        .map(|&count| {
+            let p = count as f32 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+fn classify(bytes: &[u8], entropy: f32) -> ByteClass {
+    let len = bytes.len().max(1) as f32;
+    let zeroes = bytes.iter().filter(|&&b| b == 0).count() as f32;
+    let text = bytes
+        .iter()
+        .filter(|&&b| b.is_ascii_graphic() || matches!(b, b' ' | b'\t' | b'\n' | b'\r'))
+        .count() as f32;
+
+    if zeroes / len >= CLASS_THRESHOLD {
+        ByteClass::Zeroes
+    } else if text / len >= CLASS_THRESHOLD {
+        ByteClass::Text
+    } else if entropy >= HIGH_ENTROPY {
+        ByteClass::Random
+    } else {
+        ByteClass::Binary
+    }
+}
+
+/// Split `bytes` into at most [`MAX_CHUNKS`] chunks and measure each of them.
+fn chunks(bytes: &[u8]) -> Vec<Chunk> {
+    let chunk_size = bytes.len().div_ceil(MAX_CHUNKS).max(MIN_CHUNK_SIZE);
+
+    bytes
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let entropy = entropy(chunk);
+            Chunk {
+                offset: idx * chunk_size,
+                len: chunk.len(),
+                entropy,
+                class: classify(chunk, entropy),
+            }
+        })
+        .collect()
+}
+
+impl Processor {
+    /// Entropy and byte class of the whole file, in chunks ordered by offset.
+    pub fn overview(&self) -> Vec<Chunk> {
+        chunks(self.file_bytes())
+    }
+
+    /// Address of the byte at `offset` in [`Processor::file_bytes`], the inverse of
+    /// [`Processor::file_offset`].
+    ///
+    /// None if the byte isn't part of a loaded section, e.g. headers or debug info.
+    pub fn addr_by_offset(&self, offset: usize) -> Option<PhysAddr> {
+        let file = self.file_bytes().as_ptr_range();
+
+        self.sections().find_map(|section| {
+            let bytes = section.bytes().as_ptr_range();
+            if bytes.start < file.start || bytes.end > file.end {
+                return None;
+            }
+
+            let start = bytes.start as usize - file.start as usize;
+            let end = bytes.end as usize - file.start as usize;
+            (start..end).contains(&offset).then(|| section.start + (offset - start))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_bounds() {
+        assert_eq!(entropy(&[]), 0.0);
+        assert_eq!(entropy(&[0x41; 64]), 0.0);
+
+        let all: Vec<u8> = (0..=255).collect();
+        assert!((entropy(&all) - 8.0).abs() < 1e-4);
+
+        // Two equally likely bytes carry a single bit.
+        assert!((entropy(b"abababab") - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn classes() {
+        let all: Vec<u8> = (0..=255).cycle().take(1024).collect();
+        assert_eq!(classify(&[0; 256], 0.0), ByteClass::Zeroes);
+        assert_eq!(classify(b"hello, world!\n", entropy(b"hello, world!\n")), ByteClass::Text);
+        assert_eq!(classify(&all, entropy(&all)), ByteClass::Random);
+        assert_eq!(classify(&[0x48, 0x89, 0xe5, 0x00], 2.0), ByteClass::Binary);
+    }
+
+    #[test]
+    fn chunk_sizes() {
+        assert!(chunks(&[]).is_empty());
+
+        let small = chunks(&[0; 1000]);
+        assert_eq!(small.len(), 4);
+        assert_eq!(small[3].offset, 3 * MIN_CHUNK_SIZE);
+        assert_eq!(small[3].len, 1000 - 3 * MIN_CHUNK_SIZE);
+
+        let large = chunks(&vec![0; MAX_CHUNKS * 1000]);
+        assert_eq!(large.len(), MAX_CHUNKS);
+        assert!(large.iter().all(|chunk| chunk.len == 1000));
+    }
+}
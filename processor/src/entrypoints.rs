@@ -0,0 +1,404 @@
+//! Code that's only reached through the binary's headers and runtime, such as constructors,
+//! TLS callbacks, exception handlers and the interrupt vectors of firmware. Stripped binaries
+//! don't name these, so they're found here such that they're shown as functions and decoded from
+//! their start.
+
+use crate::Processor;
+use object::read::pe::{ImageNtHeaders, PeFile};
+use object::{pe, Architecture, Endianness, LittleEndian as LE, Object, ObjectSection};
+use processor_shared::{PhysAddr, Section, SectionKind};
+
+/// Reads from the binary's sections by address.
+struct Reader<'a> {
+    sections: &'a [Section],
+    endianness: Endianness,
+}
+
+impl Reader<'_> {
+    fn bytes(&self, addr: PhysAddr, len: usize) -> Option<&[u8]> {
+        let section = self.sections.iter().find(|s| addr >= s.start && addr < s.end)?;
+        let bytes = section.bytes_by_addr(addr, len);
+        (bytes.len() == len).then_some(bytes)
+    }
+
+    fn u16(&self, addr: PhysAddr) -> Option<u16> {
+        let bytes = self.bytes(addr, 2)?.try_into().unwrap();
+        Some(match self.endianness {
+            Endianness::Little => u16::from_le_bytes(bytes),
+            Endianness::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    fn u32(&self, addr: PhysAddr) -> Option<u32> {
+        let bytes = self.bytes(addr, 4)?.try_into().unwrap();
+        Some(match self.endianness {
+            Endianness::Little => u32::from_le_bytes(bytes),
+            Endianness::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    fn u64(&self, addr: PhysAddr) -> Option<u64> {
+        let bytes = self.bytes(addr, 8)?.try_into().unwrap();
+        Some(match self.endianness {
+            Endianness::Little => u64::from_le_bytes(bytes),
+            Endianness::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    fn pointer(&self, addr: PhysAddr, is_64: bool) -> Option<PhysAddr> {
+        match is_64 {
+            true => self.u64(addr).map(|ptr| ptr as PhysAddr),
+            false => self.u32(addr).map(|ptr| ptr as PhysAddr),
+        }
+    }
+}
+
+/// Functions the headers of `obj` refer to, named by how they're reached.
+pub(crate) fn find(obj: &object::File, sections: &[Section]) -> Vec<(PhysAddr, String)> {
+    let reader = Reader {
+        sections,
+        endianness: obj.endianness(),
+    };
+
+    let mut found = Vec::new();
+    match obj {
+        object::File::Elf32(_) | object::File::Elf64(_) => elf(obj, &reader, &mut found),
+        object::File::Pe32(pe) => pe_image(pe, &reader, &mut found),
+        object::File::Pe64(pe) => pe_image(pe, &reader, &mut found),
+        _ => {}
+    }
+
+    // Only code is worth naming, pointers to zero are unrelocated or absent entries.
+    found.retain(|(addr, _)| {
+        sections
+            .iter()
+            .any(|s| s.kind == SectionKind::Code && *addr >= s.start && *addr < s.end)
+    });
+    found.sort_unstable_by_key(|(addr, _)| *addr);
+    found.dedup_by_key(|(addr, _)| *addr);
+    found
+}
+
+/// Interrupt handlers of microcontroller firmware, which is loaded without a header. The handler
+/// of the reset vector is named `reset`.
+pub(crate) fn vectors(arch: Architecture, sections: &[Section]) -> Vec<(PhysAddr, String)> {
+    let reader = Reader {
+        sections,
+        endianness: Endianness::Little,
+    };
+
+    let mut handlers = match arch {
+        Architecture::Avr => avr_vectors(&reader),
+        Architecture::Msp430 => msp430_vectors(&reader),
+        _ => return Vec::new(),
+    };
+
+    // Vectors without a handler of their own share a default one.
+    let mut shared: Vec<PhysAddr> = Vec::new();
+    for (idx, &(addr, _)) in handlers.iter().enumerate() {
+        if handlers[..idx].iter().any(|(prev, _)| *prev == addr) {
+            shared.push(addr);
+        }
+    }
+
+    handlers.retain(|(addr, _)| {
+        sections
+            .iter()
+            .any(|s| s.kind == SectionKind::Code && *addr >= s.start && *addr < s.end)
+    });
+    handlers.sort_by_key(|(addr, _)| *addr);
+    handlers.dedup_by_key(|(addr, _)| *addr);
+    for (addr, name) in handlers.iter_mut() {
+        if shared.contains(addr) && name != "reset" {
+            *name = "default_vector".to_string();
+        }
+    }
+
+    handlers
+}
+
+/// Jumps at the start of flash, which are `jmp` on devices with more than 8K of flash and `rjmp`
+/// on those with less.
+fn avr_vectors(reader: &Reader) -> Vec<(PhysAddr, String)> {
+    const MAX_VECTORS: usize = 128;
+
+    let mut handlers = Vec::new();
+    let wide = reader.u16(0).is_some_and(|word| word & 0xfe0e == 0x940c);
+    for idx in 0..MAX_VECTORS {
+        let name = if idx == 0 { "reset".to_string() } else { format!("vector_{idx}") };
+        let handler = match wide {
+            true => {
+                let addr = idx * 4;
+                match (reader.u16(addr), reader.u16(addr + 2)) {
+                    (Some(word), Some(low)) if word & 0xfe0e == 0x940c => {
+                        let high = ((word >> 3 & 0x3e) | (word & 1)) as PhysAddr;
+                        (high << 16 | low as PhysAddr) * 2
+                    }
+                    _ => break,
+                }
+            }
+            false => {
+                let addr = idx * 2;
+                match reader.u16(addr) {
+                    Some(word) if word & 0xf000 == 0xc000 => {
+                        let offset = ((word << 4) as i16 >> 4) as isize * 2;
+                        (addr + 2).wrapping_add_signed(offset)
+                    }
+                    _ => break,
+                }
+            }
+        };
+
+        handlers.push((handler, name));
+    }
+
+    handlers
+}
+
+/// Table of handlers at the end of the 16-bit address space, the last one being reset.
+fn msp430_vectors(reader: &Reader) -> Vec<(PhysAddr, String)> {
+    const VECTORS: PhysAddr = 0xffe0;
+
+    let mut handlers = Vec::new();
+    for idx in 0..16 {
+        let handler = match reader.u16(VECTORS + idx * 2) {
+            Some(0xffff) | Some(0) | None => continue,
+            Some(handler) => handler as PhysAddr,
+        };
+
+        let name = if idx == 15 { "reset".to_string() } else { format!("vector_{idx}") };
+        handlers.push((handler, name));
+    }
+
+    handlers
+}
+
+/// Constructors and destructors of the init and fini arrays, together with the functions
+/// unwinding information exists for.
+fn elf(obj: &object::File, reader: &Reader, found: &mut Vec<(PhysAddr, String)>) {
+    let is_64 = obj.is_64();
+    let ptr_size = if is_64 { 8 } else { 4 };
+
+    for section in obj.sections() {
+        let prefix = match section.name() {
+            Ok(".preinit_array") => "preinit_array",
+            Ok(".init_array") => "init_array",
+            Ok(".fini_array") => "fini_array",
+            Ok(".eh_frame_hdr") => {
+                eh_frame_hdr(section.address() as PhysAddr, reader, found);
+                continue;
+            }
+            _ => continue,
+        };
+
+        let start = section.address() as PhysAddr;
+        for idx in 0..section.size() as usize / ptr_size {
+            if let Some(addr) = reader.pointer(start + idx * ptr_size, is_64) {
+                found.push((addr, format!("{prefix}_{idx}")));
+            }
+        }
+    }
+}
+
+/// Start of each function in the binary search table of `.eh_frame_hdr`, which lists every
+/// function that can be unwound through, including those only called by exception handling.
+///
+/// Only tables of 32-bit offsets from the start of the section are read, which is what linkers
+/// emit.
+fn eh_frame_hdr(addr: PhysAddr, reader: &Reader, found: &mut Vec<(PhysAddr, String)>) {
+    const DW_EH_PE_UDATA4: u8 = 0x03;
+    const DW_EH_PE_DATAREL_SDATA4: u8 = 0x3b;
+
+    let (eh_frame_ptr_enc, count_enc, table_enc) = match reader.bytes(addr, 4) {
+        Some(&[1, eh_frame_ptr_enc, count_enc, table_enc]) => {
+            (eh_frame_ptr_enc, count_enc, table_enc)
+        }
+        _ => return,
+    };
+
+    if count_enc != DW_EH_PE_UDATA4 || table_enc != DW_EH_PE_DATAREL_SDATA4 {
+        return;
+    }
+
+    // The encoded pointer to `.eh_frame` comes before the count.
+    let ptr_size = match eh_frame_ptr_enc & 0x0f {
+        0x03 | 0x0b => 4,
+        0x04 | 0x0c => 8,
+        _ => return,
+    };
+
+    let count_addr = addr + 4 + ptr_size;
+    let count = match reader.u32(count_addr) {
+        Some(count) => count as usize,
+        None => return,
+    };
+
+    let table = count_addr + 4;
+    for idx in 0..count {
+        let offset = match reader.u32(table + idx * 8) {
+            Some(offset) => offset as i32 as isize,
+            None => return,
+        };
+
+        let func = addr.wrapping_add_signed(offset);
+        found.push((func, format!("sub_{func:x}")));
+    }
+}
+
+/// TLS callbacks, exception handlers and the valid targets of indirect calls.
+fn pe_image<Pe: ImageNtHeaders>(
+    pe: &PeFile<Pe>,
+    reader: &Reader,
+    found: &mut Vec<(PhysAddr, String)>,
+) {
+    let base = pe.relative_address_base() as PhysAddr;
+    let is_64 = pe.is_64();
+    let directory = |id: usize| {
+        let dir = pe.data_directory(id)?;
+        let (rva, size) = (dir.virtual_address.get(LE), dir.size.get(LE));
+        (rva != 0 && size != 0).then_some((base + rva as PhysAddr, size as usize))
+    };
+
+    // Null terminated array of callbacks, called before the entrypoint for every thread.
+    if let Some((tls, _)) = directory(pe::IMAGE_DIRECTORY_ENTRY_TLS) {
+        let ptr_size = if is_64 { 8 } else { 4 };
+        let callbacks = reader.pointer(tls + 3 * ptr_size, is_64).unwrap_or(0);
+        if callbacks != 0 {
+            let mut idx = 0;
+            while let Some(addr) = reader.pointer(callbacks + idx * ptr_size, is_64) {
+                if addr == 0 {
+                    break;
+                }
+
+                found.push((addr, format!("tls_callback_{idx}")));
+                idx += 1;
+            }
+        }
+    }
+
+    // Unwinding information of each function, along with it's exception handler. Other
+    // architectures than x64 have their own layout of the table.
+    let pdata = match pe.architecture() {
+        Architecture::X86_64 => directory(pe::IMAGE_DIRECTORY_ENTRY_EXCEPTION),
+        _ => None,
+    };
+
+    if let Some((pdata, size)) = pdata {
+        for idx in 0..size / 12 {
+            let entry = pdata + idx * 12;
+            let (begin, unwind) = match (reader.u32(entry), reader.u32(entry + 8)) {
+                (Some(begin), Some(unwind)) => (begin as PhysAddr, unwind as PhysAddr),
+                _ => break,
+            };
+
+            let func = base + begin;
+            found.push((func, format!("sub_{func:x}")));
+
+            if let Some(handler) = exception_handler(reader, base + unwind) {
+                let handler = base + handler;
+                found.push((handler, format!("exception_handler_{handler:x}")));
+            }
+        }
+    }
+
+    if let Some((config, size)) = directory(pe::IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG) {
+        load_config(reader, base, config, size, is_64, found);
+    }
+}
+
+/// Handler of an x64 `UNWIND_INFO` at `addr`, if it has one.
+fn exception_handler(reader: &Reader, addr: PhysAddr) -> Option<PhysAddr> {
+    const UNW_FLAG_EHANDLER: u8 = 0x1;
+    const UNW_FLAG_UHANDLER: u8 = 0x2;
+
+    let header = reader.bytes(addr, 4)?;
+    let flags = header[0] >> 3;
+    if flags & (UNW_FLAG_EHANDLER | UNW_FLAG_UHANDLER) == 0 {
+        return None;
+    }
+
+    // Unwind codes are two bytes each, padded to an even number of them.
+    let codes = (header[2] as usize + 1) & !1;
+    reader.u32(addr + 4 + codes * 2).map(|rva| rva as PhysAddr)
+}
+
+/// Safe SEH handlers on 32-bit and functions that are valid targets of indirect calls when
+/// control flow guard is enabled.
+fn load_config(
+    reader: &Reader,
+    base: PhysAddr,
+    config: PhysAddr,
+    size: usize,
+    is_64: bool,
+    found: &mut Vec<(PhysAddr, String)>,
+) {
+    const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xf000_0000;
+    const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+
+    // The directory grew over time, so fields past it's size aren't there.
+    let size = reader.u32(config).map_or(size, |len| len as usize).min(size);
+    let field = |offset: usize| {
+        let len = if is_64 { 8 } else { 4 };
+        if offset + len > size {
+            return None;
+        }
+
+        reader.pointer(config + offset, is_64)
+    };
+
+    let (seh_table, guard_table, guard_flags) = match is_64 {
+        true => (None, 0x80, 0x90),
+        false => (Some(0x40), 0x50, 0x58),
+    };
+
+    if let Some(offset) = seh_table {
+        if let (Some(table), Some(count)) = (field(offset), field(offset + 4)) {
+            for rva in (0..count).map_while(|idx| reader.u32(table + idx * 4)) {
+                let handler = base + rva as PhysAddr;
+                found.push((handler, format!("seh_handler_{handler:x}")));
+            }
+        }
+    }
+
+    let ptr_size = if is_64 { 8 } else { 4 };
+    let (table, count) = match (field(guard_table), field(guard_table + ptr_size)) {
+        (Some(table), Some(count)) if table != 0 => (table, count),
+        _ => return,
+    };
+
+    // Entries can be followed by extra bytes of metadata.
+    let flags = match guard_flags + 4 <= size {
+        true => reader.u32(config + guard_flags).unwrap_or(0),
+        false => 0,
+    };
+    let stride = 4
+        + ((flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+            >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT) as usize;
+
+    for rva in (0..count).map_while(|idx| reader.u32(table + idx * stride)) {
+        let func = base + rva as PhysAddr;
+        found.push((func, format!("sub_{func:x}")));
+    }
+}
+
+impl Processor {
+    /// Decode from each of `addrs` up to the next function, where decoding the section as a
+    /// whole went out of sync with the instructions starting there.
+    pub(crate) fn decode_entrypoints(&self, addrs: &[PhysAddr]) {
+        for &addr in addrs {
+            let section = match self.section_by_addr(addr) {
+                Some(section) if section.kind == SectionKind::Code => section,
+                _ => continue,
+            };
+
+            if self.code.read().unwrap().instruction_by_addr(addr).is_some() {
+                continue;
+            }
+
+            let end = self.function_end(section, addr);
+            if addr < end {
+                self.redecode(section, addr, end);
+            }
+        }
+    }
+}
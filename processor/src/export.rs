@@ -0,0 +1,238 @@
+//! Exporting the listing as plain text, HTML or JSON.
+//!
+//! Listings of large binaries easily reach hundreds of megabytes, so blocks are parsed and
+//! written one boundary at a time instead of building the output in memory.
+
+use crate::{Block, BlockContent, Processor};
+use commands::ExportFormat;
+use processor_shared::PhysAddr;
+use std::io::{self, Write};
+use tokenizing::{Color32, Theme, Token, TokenStream};
+
+fn kind(content: &BlockContent) -> &'static str {
+    match content {
+        BlockContent::SectionStart { .. } => "section_start",
+        BlockContent::SectionEnd { .. } => "section_end",
+        BlockContent::Label { .. } => "label",
+        BlockContent::Instruction { .. } => "instruction",
+        BlockContent::Error { .. } => "error",
+        BlockContent::CString { .. } => "string",
+        BlockContent::Got { .. } => "got",
+        BlockContent::Pointer { .. } => "pointer",
+        BlockContent::Case { .. } => "case",
+        BlockContent::DataStructure { .. } => "struct",
+        BlockContent::Bytes { .. } => "bytes",
+        BlockContent::Zeroes { .. } => "zeroes",
+    }
+}
+
+fn text(tokens: &[Token]) -> String {
+    tokens.iter().map(|token| &token.text as &str).collect()
+}
+
+fn hex_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn write_html_escaped<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    let mut start = 0;
+
+    for (idx, c) in s.char_indices() {
+        let escaped = match c {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' => "&quot;",
+            _ => continue,
+        };
+
+        w.write_all(&s.as_bytes()[start..idx])?;
+        w.write_all(escaped.as_bytes())?;
+        start = idx + c.len_utf8();
+    }
+
+    w.write_all(&s.as_bytes()[start..])
+}
+
+/// Write `s` as a JSON string, quoted and escaped.
+pub fn write_json_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    w.write_all(b"\"")?;
+
+    let mut start = 0;
+    for (idx, c) in s.char_indices() {
+        let escaped = match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if c.is_control() => format!("\\u{:04x}", c as u32),
+            _ => continue,
+        };
+
+        w.write_all(&s.as_bytes()[start..idx])?;
+        w.write_all(escaped.as_bytes())?;
+        start = idx + c.len_utf8();
+    }
+
+    w.write_all(&s.as_bytes()[start..])?;
+    w.write_all(b"\"")
+}
+
+/// Writes blocks in one of the [`ExportFormat`]s.
+struct Exporter<'a, W: Write> {
+    w: &'a mut W,
+    format: ExportFormat,
+    stream: TokenStream,
+    /// Theme that HTML is colored with, the one current when exporting started.
+    theme: Theme,
+    /// Whether a JSON object has been written, which requires the next one to be separated.
+    written: bool,
+}
+
+impl<W: Write> Exporter<'_, W> {
+    fn header(&mut self, title: &str) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Text => Ok(()),
+            ExportFormat::Html => {
+                self.w.write_all(b"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n")?;
+                self.w.write_all(b"<title>")?;
+                write_html_escaped(self.w, title)?;
+                self.w.write_all(b"</title>\n</head>\n")?;
+                writeln!(
+                    self.w,
+                    "<body style=\"background: {}; color: {}; margin: 0\">",
+                    hex_color(self.theme.bg_primary),
+                    hex_color(self.theme.plain),
+                )?;
+                self.w.write_all(b"<pre style=\"font-family: monospace; padding: 1em\">\n")
+            }
+            ExportFormat::Json => self.w.write_all(b"["),
+        }
+    }
+
+    fn footer(&mut self) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Text => Ok(()),
+            ExportFormat::Html => self.w.write_all(b"</pre>\n</body>\n</html>\n"),
+            ExportFormat::Json => self.w.write_all(b"\n]\n"),
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> io::Result<()> {
+        self.stream.clear();
+        block.tokenize(&mut self.stream);
+
+        match self.format {
+            ExportFormat::Text => writeln!(self.w, "{}", self.stream.to_string()),
+            ExportFormat::Html => {
+                for token in &self.stream.inner {
+                    let color = hex_color(self.theme.color(token.kind));
+                    write!(self.w, "<span style=\"color: {color}\">")?;
+                    write_html_escaped(self.w, &token.text)?;
+                    self.w.write_all(b"</span>")?;
+                }
+
+                self.w.write_all(b"\n")
+            }
+            ExportFormat::Json => self.json_block(block),
+        }
+    }
+
+    fn json_block(&mut self, block: &Block) -> io::Result<()> {
+        let separator: &[u8] = if self.written { b",\n" } else { b"\n" };
+        self.w.write_all(separator)?;
+        self.written = true;
+
+        write!(self.w, "{{\"addr\":{},\"kind\":\"{}\"", block.addr, kind(&block.content))?;
+
+        match (&block.content, block.row()) {
+            (BlockContent::Instruction { .. }, Some(row)) => {
+                let mut inst = row.instruction();
+                inst.extend(row.comments.iter().map(|token| &token.text as &str));
+
+                self.w.write_all(b",\"bytes\":")?;
+                write_json_str(self.w, row.bytes.trim())?;
+                self.w.write_all(b",\"mnemonic\":")?;
+                write_json_str(self.w, &text(&row.mnemonic))?;
+                self.w.write_all(b",\"operands\":")?;
+                write_json_str(self.w, &text(&row.operands))?;
+                self.w.write_all(b",\"text\":")?;
+                write_json_str(self.w, &inst)?;
+            }
+            (BlockContent::Error { bytes, .. }, _) => {
+                self.w.write_all(b",\"bytes\":")?;
+                write_json_str(self.w, bytes.trim())?;
+                self.w.write_all(b",\"text\":")?;
+                write_json_str(self.w, self.stream.to_string().trim())?;
+            }
+            _ => {
+                self.w.write_all(b",\"text\":")?;
+                write_json_str(self.w, self.stream.to_string().trim())?;
+            }
+        }
+
+        self.w.write_all(b"}")
+    }
+}
+
+impl Processor {
+    /// Write the blocks starting at each of the `boundaries` to `w`.
+    ///
+    /// `w` isn't buffered, so wrap it in a [`std::io::BufWriter`] when writing to a file.
+    pub fn export<W: Write>(
+        &self,
+        boundaries: &[PhysAddr],
+        format: ExportFormat,
+        w: &mut W,
+    ) -> io::Result<()> {
+        let mut exporter = Exporter {
+            w,
+            format,
+            stream: TokenStream::new(),
+            theme: tokenizing::theme(),
+            written: false,
+        };
+
+        let title = self.path.file_name().unwrap_or_default().to_string_lossy();
+        exporter.header(&title)?;
+
+        for &addr in boundaries {
+            for block in self.parse_blocks(addr) {
+                exporter.block(&block)?;
+            }
+        }
+
+        exporter.footer()?;
+        exporter.w.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escaped(f: fn(&mut Vec<u8>, &str) -> io::Result<()>, s: &str) -> String {
+        let mut buf = Vec::new();
+        f(&mut buf, s).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn html_escaping() {
+        assert_eq!(escaped(write_html_escaped, "mov eax, 1"), "mov eax, 1");
+        assert_eq!(
+            escaped(write_html_escaped, "<std::vec::Vec<u8> as Drop>::drop & \"x\""),
+            "&lt;std::vec::Vec&lt;u8&gt; as Drop&gt;::drop &amp; &quot;x&quot;"
+        );
+    }
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(escaped(write_json_str, "push rbp"), "\"push rbp\"");
+        assert_eq!(
+            escaped(write_json_str, "\"a\\b\"\n\t\u{1}é"),
+            "\"\\\"a\\\\b\\\"\\n\\t\\u0001é\""
+        );
+    }
+}
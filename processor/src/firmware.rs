@@ -0,0 +1,345 @@
+//! Intel HEX, Motorola SREC and TI-TXT files, text formats storing firmware as records of bytes
+//! together with the address they're loaded at.
+
+use crate::Error;
+use processor_shared::PhysAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    IntelHex,
+    Srec,
+    /// Used by TI's tools for MSP430 firmware.
+    TiTxt,
+}
+
+impl Format {
+    /// Format of `bytes`, judging by it's first record.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        let start = bytes.iter().position(|b| !b.is_ascii_whitespace())?;
+        match bytes[start..] {
+            [b':', digit, ..] if digit.is_ascii_hexdigit() => Some(Self::IntelHex),
+            [b'S', digit, ..] if digit.is_ascii_digit() => Some(Self::Srec),
+            [b'@', digit, ..] if digit.is_ascii_hexdigit() => Some(Self::TiTxt),
+            _ => None,
+        }
+    }
+}
+
+/// Bytes stored contiguously in memory.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Region {
+    pub start: PhysAddr,
+    pub bytes: Vec<u8>,
+}
+
+impl Region {
+    pub fn end(&self) -> PhysAddr {
+        self.start + self.bytes.len()
+    }
+}
+
+/// Memory described by a firmware file, which can have gaps between it's regions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Image {
+    /// Sorted by address and not overlapping.
+    pub regions: Vec<Region>,
+
+    /// Where execution starts, if the file stores it.
+    pub entrypoint: Option<PhysAddr>,
+}
+
+impl Image {
+    /// Store `bytes` at `addr`, extending the last region if they directly follow it.
+    fn write(&mut self, addr: PhysAddr, bytes: &[u8]) {
+        match self.regions.last_mut() {
+            Some(last) if last.end() == addr => last.bytes.extend_from_slice(bytes),
+            _ => self.regions.push(Region {
+                start: addr,
+                bytes: bytes.to_vec(),
+            }),
+        }
+    }
+
+    /// Sort the regions, merging those that touch. Bytes written later take precedence.
+    fn finish(mut self) -> Self {
+        // Only the last region is ever extended, so the regions are still in write order.
+        let mut extents: Vec<(PhysAddr, PhysAddr)> =
+            self.regions.iter().map(|region| (region.start, region.end())).collect();
+        extents.sort_unstable();
+
+        let mut merged: Vec<Region> = Vec::with_capacity(extents.len());
+        for (start, end) in extents {
+            match merged.last_mut() {
+                Some(last) if start <= last.end() => {
+                    let len = end.max(last.end()) - last.start;
+                    last.bytes.resize(len, 0);
+                }
+                _ => merged.push(Region {
+                    start,
+                    bytes: vec![0; end - start],
+                }),
+            }
+        }
+
+        for region in &self.regions {
+            let idx = merged.partition_point(|merged| merged.start <= region.start) - 1;
+            let offset = region.start - merged[idx].start;
+            merged[idx].bytes[offset..offset + region.bytes.len()].copy_from_slice(&region.bytes);
+        }
+
+        self.regions = merged;
+        self
+    }
+}
+
+/// Parse the records of a file in the given `format`.
+pub fn parse(format: Format, text: &[u8]) -> Result<Image, Error> {
+    match format {
+        Format::IntelHex => parse_intel_hex(text),
+        Format::Srec => parse_srec(text),
+        Format::TiTxt => parse_ti_txt(text),
+    }
+}
+
+/// Record's hex digits after the first `skip` characters, None if they aren't valid hex.
+fn record_bytes(line: &[u8], skip: usize) -> Option<Vec<u8>> {
+    let digits = line.get(skip..)?;
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+
+    digits
+        .chunks_exact(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Big-endian integer stored in `bytes`.
+fn be_addr(bytes: &[u8]) -> PhysAddr {
+    bytes.iter().fold(0, |addr, &byte| (addr << 8) | byte as PhysAddr)
+}
+
+/// Non-empty lines together with their line number, starting at 1.
+fn records(text: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+    text.split(|&b| b == b'\n')
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim_ascii()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// Records are `:LLAAAATT` followed by `LL` bytes of data and a checksum, where the addresses
+/// of data records are relative to the last extended address record.
+fn parse_intel_hex(text: &[u8]) -> Result<Image, Error> {
+    let mut image = Image::default();
+    let mut base = 0;
+
+    for (line_nr, line) in records(text) {
+        let invalid = || Error::InvalidRecord(line_nr);
+
+        if line[0] != b':' {
+            return Err(invalid());
+        }
+
+        let bytes = record_bytes(line, 1).ok_or_else(invalid)?;
+        let len = bytes[0] as usize;
+        if bytes.len() != len + 5 || bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0 {
+            return Err(invalid());
+        }
+
+        let offset = be_addr(&bytes[1..3]);
+        let data = &bytes[4..4 + len];
+        match (bytes[3], len) {
+            (0x00, _) => image.write(base + offset, data),
+            (0x01, _) => break,
+            // Extended segment address, in paragraphs of 16 bytes.
+            (0x02, 2) => base = be_addr(data) << 4,
+            // Start segment address, a CS:IP pair.
+            (0x03, 4) => image.entrypoint = Some((be_addr(&data[..2]) << 4) + be_addr(&data[2..])),
+            // Extended linear address, the upper 16 bits of the address.
+            (0x04, 2) => base = be_addr(data) << 16,
+            // Start linear address.
+            (0x05, 4) => image.entrypoint = Some(be_addr(data)),
+            _ => return Err(invalid()),
+        }
+    }
+
+    Ok(image.finish())
+}
+
+/// Records are `Sn` followed by a byte count, an address whose width depends on `n`, data and a
+/// checksum, where the count covers everything after itself.
+fn parse_srec(text: &[u8]) -> Result<Image, Error> {
+    let mut image = Image::default();
+
+    for (line_nr, line) in records(text) {
+        let invalid = || Error::InvalidRecord(line_nr);
+
+        if line.len() < 2 || line[0] != b'S' {
+            return Err(invalid());
+        }
+
+        let bytes = record_bytes(line, 2).ok_or_else(invalid)?;
+        let len = bytes[0] as usize;
+        if bytes.len() != len + 1 || bytes.iter().fold(0u8, |sum, &b| sum.wrapping_add(b)) != 0xff {
+            return Err(invalid());
+        }
+
+        let addr_len = match line[1] {
+            b'0' | b'1' | b'5' | b'9' => 2,
+            b'2' | b'6' | b'8' => 3,
+            b'3' | b'7' => 4,
+            _ => return Err(invalid()),
+        };
+
+        if len < addr_len + 1 {
+            return Err(invalid());
+        }
+
+        let addr = be_addr(&bytes[1..1 + addr_len]);
+        let data = &bytes[1 + addr_len..bytes.len() - 1];
+        match line[1] {
+            b'1' | b'2' | b'3' => image.write(addr, data),
+            b'7' | b'8' | b'9' => image.entrypoint = Some(addr),
+            // Header and record counts.
+            _ => {}
+        }
+    }
+
+    Ok(image.finish())
+}
+
+/// Sections start with `@ADDR`, followed by lines of bytes separated by spaces that are stored
+/// one after another, up to a `q` that ends the file. There are no checksums.
+fn parse_ti_txt(text: &[u8]) -> Result<Image, Error> {
+    let mut image = Image::default();
+    let mut addr = None;
+
+    for (line_nr, line) in records(text) {
+        let invalid = || Error::InvalidRecord(line_nr);
+
+        match line {
+            [b'q' | b'Q', ..] => break,
+            [b'@', digits @ ..] => {
+                let digits = std::str::from_utf8(digits).map_err(|_| invalid())?;
+                let start = PhysAddr::from_str_radix(digits, 16).map_err(|_| invalid())?;
+                addr = Some(start);
+            }
+            _ => {
+                let start = addr.ok_or_else(invalid)?;
+                let bytes: Vec<u8> = line
+                    .split(|b| b.is_ascii_whitespace())
+                    .filter(|digits| !digits.is_empty())
+                    .map(|digits| match digits.len() {
+                        2 => record_bytes(digits, 0).map(|bytes| bytes[0]),
+                        _ => None,
+                    })
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+
+                image.write(start, &bytes);
+                addr = Some(start + bytes.len());
+            }
+        }
+    }
+
+    Ok(image.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intel_hex() {
+        let text = b":020000040800F2\n\
+                     :0400000001020304F2\n\
+                     :02000400AABB95\n\
+                     :020010000506E3\n\
+                     :0400000508000101ED\n\
+                     :00000001FF\n";
+
+        assert_eq!(Format::detect(text), Some(Format::IntelHex));
+
+        let image = parse(Format::IntelHex, text).unwrap();
+        assert_eq!(image.entrypoint, Some(0x8000101));
+        assert_eq!(
+            image.regions,
+            [
+                Region { start: 0x8000000, bytes: vec![1, 2, 3, 4, 0xaa, 0xbb] },
+                Region { start: 0x8000010, bytes: vec![5, 6] },
+            ]
+        );
+    }
+
+    #[test]
+    fn srec() {
+        let text = b"S00600004844521B\n\
+                     S1070100DEADBEEFBF\n\
+                     S3090000200001020304CC\n\
+                     S9030100FB\n";
+
+        assert_eq!(Format::detect(text), Some(Format::Srec));
+
+        let image = parse(Format::Srec, text).unwrap();
+        assert_eq!(image.entrypoint, Some(0x100));
+        assert_eq!(
+            image.regions,
+            [
+                Region { start: 0x100, bytes: vec![0xde, 0xad, 0xbe, 0xef] },
+                Region { start: 0x2000, bytes: vec![1, 2, 3, 4] },
+            ]
+        );
+    }
+
+    #[test]
+    fn ti_txt() {
+        let text = b"@c000\n\
+                     31 40 00 04\n\
+                     3F 40\n\
+                     @fffe\n\
+                     00 C0\n\
+                     q\n";
+
+        assert_eq!(Format::detect(text), Some(Format::TiTxt));
+
+        let image = parse(Format::TiTxt, text).unwrap();
+        assert_eq!(image.entrypoint, None);
+        assert_eq!(
+            image.regions,
+            [
+                Region { start: 0xc000, bytes: vec![0x31, 0x40, 0x00, 0x04, 0x3f, 0x40] },
+                Region { start: 0xfffe, bytes: vec![0x00, 0xc0] },
+            ]
+        );
+
+        assert!(matches!(parse(Format::TiTxt, b"00 01\n"), Err(Error::InvalidRecord(1))));
+        assert!(matches!(parse(Format::TiTxt, b"@0\n0g\n"), Err(Error::InvalidRecord(2))));
+    }
+
+    #[test]
+    fn invalid_checksum() {
+        let text = b":0400000001020304F2\n:0400100001020304FF\n";
+        assert!(matches!(parse(Format::IntelHex, text), Err(Error::InvalidRecord(2))));
+        assert_eq!(Format::detect(b"\x7fELF"), None);
+    }
+
+    #[test]
+    fn overlapping_regions() {
+        let mut image = Image::default();
+        image.write(0x10, &[1, 2, 3, 4]);
+        image.write(0x0, &[9; 2]);
+        image.write(0x12, &[7, 7, 7]);
+
+        let image = image.finish();
+        assert_eq!(image.regions[0], Region { start: 0x0, bytes: vec![9, 9] });
+        assert_eq!(image.regions[1], Region { start: 0x10, bytes: vec![1, 2, 7, 7, 7] });
+
+        // A later record starting before an earlier one still overwrites it.
+        let mut image = Image::default();
+        image.write(0x12, &[1, 2, 3]);
+        image.write(0x10, &[7, 7, 7]);
+
+        let image = image.finish();
+        assert_eq!(image.regions, [Region { start: 0x10, bytes: vec![7, 7, 7, 2, 3] }]);
+    }
+}
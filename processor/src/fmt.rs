@@ -15,6 +15,27 @@ impl fmt::Debug for super::Error {
             Self::UnknownArchitecture(arch) => {
                 f.write_fmt(format_args!("Unsupported architecture: '{arch:?}'."))
             }
+            Self::Project(err) => err.fmt(f),
+            Self::SharedCache => {
+                f.write_str("Object is a dyld shared cache, an image to open has to be given.")
+            }
+            Self::ImageNotFound(name) => {
+                f.write_fmt(format_args!("Object doesn't contain an image named '{name}'."))
+            }
+            Self::Container(names) => f.write_fmt(format_args!(
+                "Object is an archive or fat Mach-O, one of it's objects has to be given: {}.",
+                names.join(", ")
+            )),
+            Self::Cancelled => f.write_str("Loading was cancelled."),
+            Self::UnknownFormat => f.write_str(
+                "Object has no header of a known format, it can still be loaded as a raw binary.",
+            ),
+            Self::InvalidRecord(line) => {
+                f.write_fmt(format_args!("Firmware record on line {line} is invalid."))
+            }
+            Self::InvalidBytecode(name) => {
+                f.write_fmt(format_args!("DEX or class file '{name}' is malformed."))
+            }
         }
     }
 }
@@ -0,0 +1,204 @@
+//! Edits made by the user, kept so that they can be undone and redone.
+
+use crate::definitions::Definitions;
+use crate::{AnnotationKind, Processor, Redefine};
+use processor_shared::PhysAddr;
+use std::ops::Range;
+
+/// Most edits that can be undone, the oldest being forgotten first.
+const MAX_EDITS: usize = 1000;
+
+/// An edit made by the user, along with what it replaced.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// The annotation of `kind` at `addr` changed, `None` meaning there wasn't or isn't one.
+    Annotation {
+        kind: AnnotationKind,
+        addr: PhysAddr,
+        before: Option<String>,
+        after: Option<String>,
+    },
+    /// Bytes at file `offset` were patched, `before` being as they were shown.
+    Patch {
+        offset: usize,
+        before: Vec<u8>,
+        after: Vec<u8>,
+    },
+    /// Bytes of a code section were redefined, changing the blocks in `changed`.
+    Definition {
+        redefine: Redefine,
+        before: Definitions,
+        changed: Range<PhysAddr>,
+    },
+    /// The section at `start` was toggled being decoded as `code`.
+    CodeSection { start: PhysAddr, code: bool },
+}
+
+/// Edits that can be undone, and those undone that can be redone.
+#[derive(Debug, Default)]
+pub(crate) struct History {
+    done: Vec<Edit>,
+    undone: Vec<Edit>,
+}
+
+impl History {
+    /// Remember `edit`, after which what was undone can't be redone.
+    fn push(&mut self, edit: Edit) {
+        if self.done.len() == MAX_EDITS {
+            self.done.remove(0);
+        }
+
+        self.done.push(edit);
+        self.undone.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+impl Processor {
+    pub(crate) fn record(&self, edit: Edit) {
+        self.history.lock().unwrap().push(edit);
+    }
+
+    /// Set the annotation of `kind` at `addr`, recording it so that it can be undone. An empty
+    /// `text` removes it, apart from bookmarks which don't need a label.
+    ///
+    /// Returns false without changing anything if a prototype isn't a valid C declaration.
+    pub fn annotate(&self, kind: AnnotationKind, addr: PhysAddr, text: &str) -> bool {
+        let mut annotations = self.annotations.write().unwrap();
+        let before = annotations.of(kind).get(&addr).cloned();
+
+        match kind {
+            AnnotationKind::Comment => annotations.set_comment(addr, text),
+            AnnotationKind::Rename => annotations.set_rename(addr, text),
+            AnnotationKind::Bookmark => annotations.set_bookmark(addr, text),
+            AnnotationKind::Prototype => {
+                if !annotations.set_prototype(addr, text) {
+                    return false;
+                }
+            }
+        }
+
+        let after = annotations.of(kind).get(&addr).cloned();
+        drop(annotations);
+
+        if before != after {
+            self.record(Edit::Annotation {
+                kind,
+                addr,
+                before,
+                after,
+            });
+        }
+
+        true
+    }
+
+    /// Remove the bookmark at `addr`, recording it so that it can be undone.
+    pub fn remove_bookmark(&self, addr: PhysAddr) {
+        let before = self.annotations.write().unwrap().bookmarks.remove(&addr);
+        if before.is_some() {
+            self.record(Edit::Annotation {
+                kind: AnnotationKind::Bookmark,
+                addr,
+                before,
+                after: None,
+            });
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.history.lock().unwrap().done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.history.lock().unwrap().undone.is_empty()
+    }
+
+    /// Undo the last edit, returning it so that whatever shows it can be updated.
+    pub fn undo(&self) -> Option<Edit> {
+        let edit = self.history.lock().unwrap().done.pop()?;
+
+        match &edit {
+            Edit::Annotation {
+                kind, addr, before, ..
+            } => {
+                self.annotations.write().unwrap().restore(*kind, *addr, before.clone());
+            }
+            Edit::Patch { offset, before, .. } => {
+                self.write_patch(*offset, before);
+            }
+            Edit::Definition {
+                before, changed, ..
+            } => self.restore_definitions(before, changed),
+            Edit::CodeSection { start, code } => {
+                self.apply_code_section(*start, !code);
+            }
+        }
+
+        self.history.lock().unwrap().undone.push(edit.clone());
+        Some(edit)
+    }
+
+    /// Redo the last edit that was undone, returning it so that whatever shows it can be
+    /// updated.
+    pub fn redo(&self) -> Option<Edit> {
+        let edit = self.history.lock().unwrap().undone.pop()?;
+
+        match &edit {
+            Edit::Annotation {
+                kind, addr, after, ..
+            } => {
+                self.annotations.write().unwrap().restore(*kind, *addr, after.clone());
+            }
+            Edit::Patch { offset, after, .. } => {
+                self.write_patch(*offset, after);
+            }
+            Edit::Definition { redefine, .. } => {
+                self.apply_redefine(*redefine);
+            }
+            Edit::CodeSection { start, code } => {
+                self.apply_code_section(*start, *code);
+            }
+        }
+
+        self.history.lock().unwrap().done.push(edit.clone());
+        Some(edit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(addr: PhysAddr) -> Edit {
+        Edit::Annotation {
+            kind: AnnotationKind::Comment,
+            addr,
+            before: None,
+            after: Some("checks the header".to_string()),
+        }
+    }
+
+    #[test]
+    fn forget() {
+        let mut history = History::default();
+        history.push(comment(0x1000));
+        history.undone.push(comment(0x2000));
+
+        // Editing after undoing leaves nothing to redo.
+        history.push(comment(0x3000));
+        assert_eq!(history.done.len(), 2);
+        assert!(history.undone.is_empty());
+
+        for addr in 0..MAX_EDITS {
+            history.push(comment(addr));
+        }
+
+        assert_eq!(history.done.len(), MAX_EDITS);
+        assert!(matches!(history.done[0], Edit::Annotation { addr: 0, .. }));
+    }
+}
@@ -0,0 +1,265 @@
+//! Recovery of jump tables, as generated for `switch` and `match` statements.
+//!
+//! Indirect jumps otherwise end the control flow that's known, leaving their cases without any
+//! references and the tables themselves decoded as instructions.
+
+use crate::{Processor, Xref, XrefKind};
+use decoder::{Decoded, JumpTable, TableEntry};
+use object::Endian;
+use processor_shared::{Addressed, PhysAddr};
+use std::ops::Range;
+
+/// Instructions looked at before an indirect jump for how it computes it's target.
+pub(crate) const WINDOW: usize = 16;
+
+/// Most entries read from a table, also for tables without a known length.
+const MAX_ENTRIES: usize = 1024;
+
+/// A jump table with it's entries resolved.
+#[derive(Debug, Clone)]
+pub struct SwitchTable {
+    /// Address of the indirect jump going through the table.
+    pub jump: PhysAddr,
+    /// Address of the first entry.
+    pub addr: PhysAddr,
+    pub entry: TableEntry,
+    /// Where each case jumps to, in the order of the entries.
+    pub targets: Vec<PhysAddr>,
+}
+
+impl SwitchTable {
+    pub fn end(&self) -> PhysAddr {
+        self.addr + self.targets.len() * self.entry.width()
+    }
+}
+
+/// Tables of all decoded indirect jumps, indexed both by their address and by the jump.
+#[derive(Default)]
+pub(crate) struct SwitchTables {
+    /// Sorted by [`SwitchTable::addr`].
+    by_addr: Vec<SwitchTable>,
+    /// Sorted by [`SwitchTable::jump`].
+    by_jump: Vec<SwitchTable>,
+}
+
+impl SwitchTables {
+    pub fn extend(&mut self, tables: impl IntoIterator<Item = SwitchTable>) {
+        for table in tables {
+            self.by_addr.push(table.clone());
+            self.by_jump.push(table);
+        }
+
+        self.by_addr.sort_unstable_by_key(|table| table.addr);
+        self.by_jump.sort_unstable_by_key(|table| table.jump);
+    }
+
+    /// Forget the tables of jumps in `range`.
+    pub fn remove_from(&mut self, range: Range<PhysAddr>) {
+        self.by_addr.retain(|table| !range.contains(&table.jump));
+        self.by_jump.retain(|table| !range.contains(&table.jump));
+    }
+
+    /// Table the jump at `addr` goes through.
+    pub fn by_jump(&self, addr: PhysAddr) -> Option<&SwitchTable> {
+        let idx = self.by_jump.binary_search_by_key(&addr, |table| table.jump).ok()?;
+        Some(&self.by_jump[idx])
+    }
+
+    /// Table with an entry starting at `addr`, together with the entry's index.
+    pub fn entry(&self, addr: PhysAddr) -> Option<(&SwitchTable, usize)> {
+        let idx = self.by_addr.partition_point(|table| table.addr <= addr).checked_sub(1)?;
+        let table = &self.by_addr[idx];
+        let offset = addr - table.addr;
+
+        if addr >= table.end() || offset % table.entry.width() != 0 {
+            return None;
+        }
+
+        Some((table, offset / table.entry.width()))
+    }
+
+    /// Start of the first table after `addr`.
+    pub fn next(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let idx = self.by_addr.partition_point(|table| table.addr <= addr);
+        self.by_addr.get(idx).map(|table| table.addr)
+    }
+}
+
+/// Indirect jumps through a table in decoded instructions, by the jump's address.
+pub(crate) fn find<I: Decoded>(instructions: &[Addressed<I>]) -> Vec<Addressed<JumpTable>> {
+    let mut tables = Vec::new();
+    let mut prev = Vec::with_capacity(WINDOW);
+
+    for (idx, inst) in instructions.iter().enumerate() {
+        // Only instructions right before the jump, a gap means there was an error decoding.
+        prev.clear();
+        let mut next = inst.addr;
+        for before in instructions[..idx].iter().rev().take(WINDOW) {
+            if before.addr + before.item.width() != next {
+                break;
+            }
+
+            prev.push((before.addr, &before.item));
+            next = before.addr;
+        }
+
+        if let Some(table) = inst.item.jump_table(&prev) {
+            tables.push(Addressed {
+                addr: inst.addr,
+                item: table,
+            });
+        }
+    }
+
+    tables
+}
+
+impl Processor {
+    /// Read the entries of `table`, ending at the first that doesn't point into code.
+    fn resolve_table(&self, jump: PhysAddr, table: &JumpTable) -> Option<SwitchTable> {
+        let section = self.section_by_addr(table.addr)?;
+        let width = table.entry.width();
+        let len = table.len.unwrap_or(MAX_ENTRIES).min(MAX_ENTRIES);
+        let mut targets = Vec::new();
+
+        for idx in 0..len {
+            let addr = table.addr + idx * width;
+
+            // Without a bounds check, tables end where anything else starts.
+            if table.len.is_none() && idx != 0 && self.index.get_sym_by_addr(addr).is_some() {
+                break;
+            }
+
+            let bytes = section.bytes_by_addr(addr, width);
+            if bytes.len() != width {
+                break;
+            }
+
+            let target = match table.entry {
+                TableEntry::Absolute(8) => {
+                    self.endianness.read_u64_bytes(bytes.try_into().unwrap()) as PhysAddr
+                }
+                TableEntry::Absolute(_) => {
+                    self.endianness.read_u32_bytes(bytes.try_into().unwrap()) as PhysAddr
+                }
+                TableEntry::Relative32 => {
+                    let offset = self.endianness.read_u32_bytes(bytes.try_into().unwrap()) as i32;
+                    table.addr.wrapping_add_signed(offset as isize)
+                }
+            };
+
+            if !self.is_code(target) {
+                break;
+            }
+
+            targets.push(target);
+        }
+
+        if targets.is_empty() {
+            return None;
+        }
+
+        Some(SwitchTable {
+            jump,
+            addr: table.addr,
+            entry: table.entry,
+            targets,
+        })
+    }
+
+    /// Resolve the tables of indirect jumps, adding their cases as references and turning
+    /// tables located in code into data.
+    pub(crate) fn add_jump_tables(&self, tables: Vec<Addressed<JumpTable>>) {
+        let tables: Vec<SwitchTable> = tables
+            .iter()
+            .filter_map(|table| self.resolve_table(table.addr, &table.item))
+            .collect();
+
+        if tables.is_empty() {
+            return;
+        }
+
+        let mut xrefs = Vec::new();
+        for table in &tables {
+            let mut targets = table.targets.clone();
+            targets.sort_unstable();
+            targets.dedup();
+
+            xrefs.extend(targets.into_iter().map(|to| Xref {
+                from: table.jump,
+                to,
+                kind: XrefKind::Jump,
+            }));
+
+            if let TableEntry::Absolute(_) = table.entry {
+                xrefs.push(Xref {
+                    from: table.jump,
+                    to: table.addr,
+                    kind: XrefKind::Data,
+                });
+            }
+        }
+
+        let mut code = self.code.write().unwrap();
+        let isa = &self.isa;
+
+        for table in tables.iter().filter(|table| self.is_code(table.addr)) {
+            let range = table.addr..table.end();
+
+            code.instructions.retain_mut(|inst| {
+                if range.contains(&inst.addr) {
+                    crate::drop_instruction(isa, &mut inst.item);
+                    return false;
+                }
+                true
+            });
+            code.errors.retain(|err| !range.contains(&err.addr));
+            code.xrefs.remove_from(range);
+        }
+
+        log::complex!(
+            w "[processor::jump_tables] resolved ",
+            y tables.len().to_string(),
+            w " jump tables.",
+        );
+
+        code.xrefs.extend(xrefs);
+        code.tables.extend(tables);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(jump: PhysAddr, addr: PhysAddr, len: usize) -> SwitchTable {
+        SwitchTable {
+            jump,
+            addr,
+            entry: TableEntry::Relative32,
+            targets: vec![0x1000; len],
+        }
+    }
+
+    #[test]
+    fn entries() {
+        let mut tables = SwitchTables::default();
+        tables.extend([table(0x1100, 0x2000, 4), table(0x1000, 0x2010, 2)]);
+
+        assert_eq!(tables.entry(0x2000).map(|(_, idx)| idx), Some(0));
+        assert_eq!(tables.entry(0x200c).map(|(_, idx)| idx), Some(3));
+        assert_eq!(tables.entry(0x2014).map(|(table, idx)| (table.jump, idx)), Some((0x1000, 1)));
+        assert!(tables.entry(0x2002).is_none());
+        assert!(tables.entry(0x2018).is_none());
+        assert!(tables.entry(0x1fff).is_none());
+
+        assert_eq!(tables.next(0x1f00), Some(0x2000));
+        assert_eq!(tables.next(0x2000), Some(0x2010));
+        assert_eq!(tables.next(0x2010), None);
+        assert_eq!(tables.by_jump(0x1100).map(|table| table.addr), Some(0x2000));
+
+        tables.remove_from(0x1000..0x1001);
+        assert!(tables.entry(0x2014).is_none());
+        assert!(tables.by_jump(0x1000).is_none());
+    }
+}
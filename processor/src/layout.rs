@@ -0,0 +1,400 @@
+//! Segments of a binary and the sections they hold, with where they're stored in the file and
+//! how they're mapped. The user can decide which sections are decoded as code, e.g. when code is
+//! hidden in a section marked as data.
+
+use crate::export::write_json_str;
+use crate::report::json_array;
+use crate::{Edit, Processor};
+use object::{elf, macho, pe};
+use object::{Object, ObjectSection, ObjectSegment, SectionFlags, SegmentFlags};
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Access a segment or section is mapped with.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub exec: bool,
+}
+
+impl Permissions {
+    fn from_elf_segment(p_flags: u32) -> Self {
+        Self {
+            read: p_flags & elf::PF_R != 0,
+            write: p_flags & elf::PF_W != 0,
+            exec: p_flags & elf::PF_X != 0,
+        }
+    }
+
+    fn from_macho(prot: u32) -> Self {
+        Self {
+            read: prot & macho::VM_PROT_READ != 0,
+            write: prot & macho::VM_PROT_WRITE != 0,
+            exec: prot & macho::VM_PROT_EXECUTE != 0,
+        }
+    }
+
+    fn from_coff(characteristics: u32) -> Self {
+        Self {
+            read: characteristics & pe::IMAGE_SCN_MEM_READ != 0,
+            write: characteristics & pe::IMAGE_SCN_MEM_WRITE != 0,
+            exec: characteristics & pe::IMAGE_SCN_MEM_EXECUTE != 0,
+        }
+    }
+
+    fn of_segment(flags: SegmentFlags) -> Option<Self> {
+        match flags {
+            SegmentFlags::Elf { p_flags } => Some(Self::from_elf_segment(p_flags)),
+            SegmentFlags::MachO { initprot, .. } => Some(Self::from_macho(initprot)),
+            SegmentFlags::Coff { characteristics } => Some(Self::from_coff(characteristics)),
+            _ => None,
+        }
+    }
+
+    /// Mach-O sections don't have their own, they're mapped as part of their segment.
+    fn of_section(flags: SectionFlags) -> Option<Self> {
+        match flags {
+            SectionFlags::Elf { sh_flags } => {
+                let sh_flags = sh_flags as u32;
+                (sh_flags & elf::SHF_ALLOC != 0).then_some(Self {
+                    read: true,
+                    write: sh_flags & elf::SHF_WRITE != 0,
+                    exec: sh_flags & elf::SHF_EXECINSTR != 0,
+                })
+            }
+            SectionFlags::Coff { characteristics } => Some(Self::from_coff(characteristics)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Permissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flag = |set: bool, chr: char| if set { chr } else { '-' };
+
+        f.write_fmt(format_args!(
+            "{}{}{}",
+            flag(self.read, 'r'),
+            flag(self.write, 'w'),
+            flag(self.exec, 'x'),
+        ))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutSection {
+    pub name: String,
+    pub kind: String,
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    /// Where the section's bytes are stored in the file, None if they aren't, like a `.bss`.
+    pub offset: Option<u64>,
+    /// None if the format doesn't record them or the section isn't loaded.
+    pub perms: Option<Permissions>,
+    /// Whether the section is decoded as code.
+    pub code: bool,
+    /// Whether the section holds bytes that can be decoded, see [`Processor::set_code_section`].
+    pub decodable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutSegment {
+    pub name: String,
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+    /// Where the segment's bytes are stored in the file, None if it isn't known.
+    pub offset: Option<u64>,
+    /// Number of bytes stored in the file, the rest of the segment is zero-filled.
+    pub file_size: u64,
+    pub perms: Option<Permissions>,
+    /// Sections that are mapped as part of the segment, sorted by address.
+    pub sections: Vec<LayoutSection>,
+}
+
+/// Segments and sections of a binary.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    pub path: String,
+    pub segments: Vec<LayoutSegment>,
+    /// Sections outside of any segment, such as debug info or the sections of object files.
+    pub sections: Vec<LayoutSection>,
+}
+
+/// Section of `obj` that `section` was parsed from, preferring one of the same name as it might
+/// have been split, e.g. into the native code and the CIL of a .NET assembly.
+fn object_section<'data, 'file>(
+    obj: &'file object::File<'data>,
+    section: &Section,
+) -> Option<object::Section<'data, 'file>> {
+    let mut containing = None;
+    for obj_section in obj.sections() {
+        let start = obj_section.address() as PhysAddr;
+        let end = start + obj_section.size() as PhysAddr;
+        if !(start..=end).contains(&section.start) {
+            continue;
+        }
+
+        if obj_section.name().ok() == Some(section.name.as_str()) {
+            return Some(obj_section);
+        }
+
+        containing.get_or_insert(obj_section);
+    }
+
+    containing
+}
+
+fn write_json_opt<W: Write, T: fmt::Display>(w: &mut W, value: Option<T>) -> io::Result<()> {
+    match value {
+        Some(value) => write_json_str(w, &value.to_string()),
+        None => w.write_all(b"null"),
+    }
+}
+
+fn write_json_section<W: Write>(w: &mut W, section: &LayoutSection) -> io::Result<()> {
+    w.write_all(b"{\"name\": ")?;
+    write_json_str(w, &section.name)?;
+    w.write_all(b", \"kind\": ")?;
+    write_json_str(w, &section.kind)?;
+    write!(w, ", \"start\": {}, \"end\": {}, \"offset\": ", section.start, section.end)?;
+    match section.offset {
+        Some(offset) => write!(w, "{offset}")?,
+        None => w.write_all(b"null")?,
+    }
+    w.write_all(b", \"perms\": ")?;
+    write_json_opt(w, section.perms)?;
+    write!(w, ", \"code\": {}}}", section.code)
+}
+
+fn write_text_section<W: Write>(w: &mut W, section: &LayoutSection) -> io::Result<()> {
+    let perms = section.perms.map_or("---".to_string(), |perms| perms.to_string());
+    let offset = section.offset.map_or("-".to_string(), |offset| format!("{offset:#x}"));
+    writeln!(
+        w,
+        "    {:0>10X}..{:0>10X} {perms} {offset:>10} {:<20} {}",
+        section.start, section.end, section.name, section.kind
+    )
+}
+
+impl Layout {
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"{\n  \"path\": ")?;
+        write_json_str(w, &self.path)?;
+
+        json_array(w, "segments", &self.segments, |w, segment| {
+            w.write_all(b"{\"name\": ")?;
+            write_json_str(w, &segment.name)?;
+            write!(w, ", \"start\": {}, \"end\": {}, \"offset\": ", segment.start, segment.end)?;
+            match segment.offset {
+                Some(offset) => write!(w, "{offset}")?,
+                None => w.write_all(b"null")?,
+            }
+            write!(w, ", \"file_size\": {}, \"perms\": ", segment.file_size)?;
+            write_json_opt(w, segment.perms)?;
+            w.write_all(b", \"sections\": [")?;
+            for (idx, section) in segment.sections.iter().enumerate() {
+                if idx != 0 {
+                    w.write_all(b", ")?;
+                }
+                write_json_section(w, section)?;
+            }
+            w.write_all(b"]}")
+        })?;
+
+        json_array(w, "sections", &self.sections, write_json_section)?;
+
+        w.write_all(b"\n}\n")?;
+        w.flush()
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{}", self.path)?;
+
+        writeln!(w, "\n{} segments:", self.segments.len())?;
+        for segment in &self.segments {
+            let perms = segment.perms.map_or("---".to_string(), |perms| perms.to_string());
+            let offset = segment.offset.map_or("-".to_string(), |offset| format!("{offset:#x}"));
+            writeln!(
+                w,
+                "  {:0>10X}..{:0>10X} {perms} {offset:>10} {} ({:#x} bytes in file)",
+                segment.start, segment.end, segment.name, segment.file_size
+            )?;
+
+            for section in &segment.sections {
+                write_text_section(w, section)?;
+            }
+        }
+
+        if !self.sections.is_empty() {
+            writeln!(w, "\n{} sections outside of segments:", self.sections.len())?;
+            for section in &self.sections {
+                write_text_section(w, section)?;
+            }
+        }
+
+        w.flush()
+    }
+}
+
+impl Processor {
+    /// Kind `section` is shown as, which is code if the user chose to decode it.
+    pub(crate) fn section_kind(&self, section: &Section) -> SectionKind {
+        match self.code_sections.lock().unwrap().get(&section.start) {
+            Some(true) => SectionKind::Code,
+            Some(false) if section.kind == SectionKind::Code => SectionKind::Raw,
+            _ => section.kind.clone(),
+        }
+    }
+
+    /// Whether `section` is decoded as code.
+    pub(crate) fn section_is_code(&self, section: &Section) -> bool {
+        self.section_kind(section) == SectionKind::Code
+    }
+
+    /// Decode the section starting at `start` as code, or stop doing so, recording it so that
+    /// it can be undone. Returns the range of addresses whose blocks changed.
+    ///
+    /// Sections that don't store any bytes, like a `.bss`, can't be decoded.
+    pub fn set_code_section(&self, start: PhysAddr, code: bool) -> Option<Range<PhysAddr>> {
+        let changed = self.apply_code_section(start, code)?;
+        self.record(Edit::CodeSection { start, code });
+        Some(changed)
+    }
+
+    pub(crate) fn apply_code_section(
+        &self,
+        start: PhysAddr,
+        code: bool,
+    ) -> Option<Range<PhysAddr>> {
+        let section = self.sections().find(|s| s.start == start && !s.bytes().is_empty())?;
+        if self.section_is_code(section) == code {
+            return None;
+        }
+
+        let mut code_sections = self.code_sections.lock().unwrap();
+        if code == (section.kind == SectionKind::Code) {
+            code_sections.remove(&start);
+        } else {
+            code_sections.insert(start, code);
+        }
+        drop(code_sections);
+
+        if code {
+            self.redecode(section, section.start, section.end);
+        } else {
+            self.code.write().unwrap().undecode(&self.isa, section.start..section.end);
+        }
+
+        Some(section.start..section.end)
+    }
+
+    /// Sections the user toggled being decoded as code, by their start.
+    pub(crate) fn code_sections(&self) -> Vec<(PhysAddr, bool)> {
+        let code_sections = self.code_sections.lock().unwrap();
+        code_sections.iter().map(|(&start, &code)| (start, code)).collect()
+    }
+
+    /// Offset into [`Processor::file_bytes`] where the bytes of `section` are stored, as shown
+    /// by the hex view.
+    fn section_offset(&self, section: &Section) -> Option<u64> {
+        let file = self.file_bytes().as_ptr_range();
+        let bytes = section.bytes().as_ptr_range();
+        if section.bytes().is_empty() || bytes.start < file.start || bytes.end > file.end {
+            return None;
+        }
+
+        Some((bytes.start as usize - file.start as usize) as u64)
+    }
+
+    /// Segments and the sections they hold, with their permissions and file offsets when the
+    /// binary's format records them.
+    pub fn layout(&self) -> Layout {
+        let obj = object::File::parse(self.file_bytes()).ok();
+
+        let mut segments: Vec<LayoutSegment> = self
+            .segments()
+            .map(|segment| {
+                let obj_segment = obj.as_ref().and_then(|obj| {
+                    obj.segments().find(|obj_segment| {
+                        obj_segment.address() as PhysAddr == segment.start
+                            && (obj_segment.address() + obj_segment.size()) as PhysAddr
+                                == segment.end
+                    })
+                });
+
+                let file_range = obj_segment.as_ref().map(|segment| segment.file_range());
+                LayoutSegment {
+                    name: segment.name.clone(),
+                    start: segment.start,
+                    end: segment.end,
+                    offset: file_range.map(|(offset, _)| offset),
+                    file_size: file_range.map_or(0, |(_, size)| size),
+                    perms: obj_segment.and_then(|segment| Permissions::of_segment(segment.flags())),
+                    sections: Vec::new(),
+                }
+            })
+            .collect();
+
+        let mut outside = Vec::new();
+        for section in &self.sections {
+            let obj_section = obj.as_ref().and_then(|obj| object_section(obj, section));
+            let loaded = !matches!(section.kind, SectionKind::Unloaded | SectionKind::Debug);
+            let kind = self.section_kind(section);
+            let mut layout = LayoutSection {
+                name: section.name.clone(),
+                kind: format!("{kind:?}"),
+                start: section.start,
+                end: section.end,
+                offset: self.section_offset(section),
+                perms: obj_section.and_then(|section| Permissions::of_section(section.flags())),
+                code: kind == SectionKind::Code,
+                decodable: loaded && !section.bytes().is_empty(),
+            };
+
+            let segment = segments.iter_mut().find(|segment| {
+                loaded && segment.start <= section.start && section.end <= segment.end
+            });
+
+            match segment {
+                Some(segment) => {
+                    layout.perms = layout.perms.or(segment.perms);
+                    segment.sections.push(layout);
+                }
+                None => outside.push(layout),
+            }
+        }
+
+        Layout {
+            path: self.path.display().to_string(),
+            segments,
+            sections: outside,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permissions() {
+        let perms = Permissions::of_segment(SegmentFlags::Elf { p_flags: elf::PF_R | elf::PF_X });
+        assert_eq!(perms.unwrap().to_string(), "r-x");
+
+        let initprot = macho::VM_PROT_READ | macho::VM_PROT_WRITE;
+        let flags = SegmentFlags::MachO { flags: 0, maxprot: 7, initprot };
+        assert_eq!(Permissions::of_segment(flags).unwrap().to_string(), "rw-");
+
+        // Sections that aren't allocated aren't mapped at all.
+        let flags = SectionFlags::Elf { sh_flags: elf::SHF_WRITE as u64 };
+        assert_eq!(Permissions::of_section(flags), None);
+
+        let sh_flags = (elf::SHF_ALLOC | elf::SHF_EXECINSTR) as u64;
+        let perms = Permissions::of_section(SectionFlags::Elf { sh_flags });
+        assert_eq!(perms.unwrap().to_string(), "r-x");
+        assert_eq!(Permissions::of_section(SectionFlags::MachO { flags: 0 }), None);
+    }
+}
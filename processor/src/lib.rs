@@ -1,12 +1,55 @@
 mod fmt;
+mod annotations;
+mod assembler;
 mod blocks;
+mod bpf_object;
+mod bytecode;
+mod calls;
+mod callgraph;
+mod cfg;
+mod checksec;
+mod class;
+mod coverage;
+mod clr;
+mod container;
+mod definitions;
+mod dex;
+mod dyld_cache;
+pub mod emulate;
+mod entropy;
+mod entrypoints;
+mod export;
+mod firmware;
+mod history;
+mod jump_table;
+mod layout;
+mod libraries;
+mod loading;
+mod lzma;
+mod memory;
+mod metadata;
+mod modes;
+mod patches;
+pub mod project;
+pub mod pseudocode;
+mod raw;
+mod report;
+mod search;
+mod signatures;
+mod strings;
+mod unpack;
+mod upx;
+mod xref;
+mod zip;
 
-use decoder::{Decodable, Decoded};
+use decoder::{Decodable, Decoded, Flow, JumpTable};
 use object::{Endianness, Object, ObjectSegment};
 use object::{Architecture, BinaryFormat};
 use object::read::File as ObjectFile;
 use processor_shared::{AddressMap, Addressed, PhysAddr, Section, SectionKind, Segment};
-use debugvault::Index;
+use debugvault::fuzzy::{self, FuzzyMatch};
+use debugvault::{Index, Symbol};
+use config::{StartupLocation, CONFIG};
 use tokenizing::Token;
 use binformat::{elf, macho, pe, RawSymbol};
 
@@ -16,10 +59,45 @@ use x86_64::protected_mode as x86;
 use arm::armv7 as armv7;
 use arm::armv8::a64 as aarch64;
 
+use history::History;
+use modes::Modes;
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
-pub use blocks::{BlockContent, Block};
+pub use annotations::{AnnotationKind, Annotations};
+pub use assembler::{assemble, Error as AssembleError};
+pub use blocks::{BlockContent, Block, Row};
+pub use calls::CallingConvention;
+pub use callgraph::CallGraph;
+pub use cfg::{BasicBlock, ControlFlowGraph, Edge, EdgeKind};
+pub use checksec::{CodeSignature, Pie, Relro, Security};
+pub use coverage::{Coverage, Error as CoverageError};
+pub use definitions::{Definition, Redefine};
+pub use emulate::{EmulatedStep, Emulation};
+pub use entropy::{ByteClass, Chunk};
+pub use export::write_json_str;
+pub use history::Edit;
+pub use layout::{Layout, LayoutSection, LayoutSegment, Permissions};
+pub use libraries::{Import, Library};
+pub use loading::{Loading, Progress, Stage};
+pub use memory::{AddressSpace, Memory};
+pub use metadata::{
+    format_timestamp, GoBuildInfo, Metadata, RichEntry, RustInfo, SectionHash,
+};
+pub use modes::ArmMode;
+pub use patches::{parse_hex, Patches};
+pub use project::Project;
+pub use pseudocode::Pseudocode;
+pub use raw::{parse_arch, RawOptions, ARCHITECTURES};
+pub use report::{Report, ReportFunction, ReportImport, ReportSection};
+pub use search::BytePattern;
+pub use signatures::{Signature, Signatures};
+pub use strings::{FoundString, StringEncoding};
+pub use unpack::{Packer, Unpacking};
+pub use xref::{Xref, XrefKind};
 
 /// FIXME: This is way too large and way too broad.
 ///        Especially since these are being started for any address with a faulty decoding.
@@ -30,6 +108,22 @@ pub enum Error {
     NotAnExecutable,
     DecompressionFailed(object::Error),
     UnknownArchitecture(object::Architecture),
+    Project(project::Error),
+    /// Object is a dyld shared cache, which can only be opened an image at a time.
+    SharedCache,
+    /// Shared cache, archive or fat Mach-O doesn't contain an object with the given name.
+    ImageNotFound(String),
+    /// Loading was cancelled through it's [`Loading`] handle.
+    Cancelled,
+    /// Object is an archive or fat Mach-O holding the objects with the given names, which are
+    /// opened one at a time through [`Processor::parse_member`].
+    Container(Vec<String>),
+    /// Object doesn't have a header of any known format, see [`Processor::parse_raw`].
+    UnknownFormat,
+    /// Intel HEX, SREC or TI-TXT record on the given line is malformed or has a bad checksum.
+    InvalidRecord(usize),
+    /// DEX or class file with the given name is truncated or refers to data outside of it.
+    InvalidBytecode(String),
 }
 
 pub union Instruction {
@@ -37,95 +131,514 @@ pub union Instruction {
     x64: ManuallyDrop<x86_64::long_mode::Instruction>,
     riscv: ManuallyDrop<riscv::Instruction>,
     mips: ManuallyDrop<mips::Instruction>,
+    avr: ManuallyDrop<avr::Instruction>,
+    msp430: ManuallyDrop<msp430::Instruction>,
+    bpf: ManuallyDrop<bpf::Instruction>,
     armv7: ManuallyDrop<armv7::Instruction>,
     aarch64: ManuallyDrop<aarch64::Instruction>,
+    dalvik: ManuallyDrop<dalvik::Instruction>,
+    jvm: ManuallyDrop<jvm::Instruction>,
+    cil: ManuallyDrop<cil::Instruction>,
 }
 
-macro_rules! impl_recursion {
-    ($symbols:expr, $errors:expr, $instructions:expr, $sections:expr,
-     $max_instruction_width:expr, $decoder:expr, $arch:ident) => {{
-        $max_instruction_width = $decoder.max_width();
+/// Instruction set code is decoded as. Bytecode is decoded together with the constant pools
+/// it's instructions refer to.
+pub(crate) enum Isa {
+    Native(Architecture),
+    Dalvik(bytecode::Pools<dalvik::Pool>),
+    Jvm(bytecode::Pools<jvm::Pool>),
+    /// CIL of a .NET assembly, with the metadata of it's only module.
+    Cil(cil::Pool),
+}
 
-        let width_guess = if $max_instruction_width == 4 {
-            4
-        } else {
-            5
-        };
+impl Isa {
+    fn name(&self) -> String {
+        match self {
+            Isa::Native(arch) => format!("{arch:?}"),
+            Isa::Dalvik(_) => "Dalvik".to_string(),
+            Isa::Jvm(_) => "Jvm".to_string(),
+            Isa::Cil(_) => "Cil".to_string(),
+        }
+    }
+}
 
-        for section in $sections.iter().filter(|s| s.kind == SectionKind::Code) {
-            let mut prev_inst = None;
-            let mut reader = decoder::Reader::new(section.bytes());
-            let mut ip = section.start;
+/// Decode `bytes` as if they were located at `ip`, stopping at `end` or when running out of
+/// input. The last instruction may extend past `end`.
+fn decode_range<D: Decodable>(
+    decoder: &D,
+    bytes: &[u8],
+    mut ip: PhysAddr,
+    end: PhysAddr,
+) -> (Vec<Addressed<D::Instruction>>, Vec<Addressed<decoder::Error>>) {
+    let mut instructions: Vec<Addressed<D::Instruction>> = Vec::new();
+    let mut errors = Vec::new();
+    let mut reader = decoder::Reader::new(bytes);
+    let mut prev_was_error = false;
 
-            log::complex!(
-                w "[processor::recurse] analyzing section ",
-                b &*section.name,
-                w " <",
-                g format!("{:x}", section.start),
-                w "..",
-                g format!("{:x}", section.end),
-                w ">.",
+    while ip < end {
+        // prefetch next cache line line
+        #[cfg(target_arch = "x86")]
+        unsafe {
+            core::arch::x86::_mm_prefetch(
+                reader.as_ptr() as *const i8,
+                core::arch::x86::_MM_HINT_NTA
             );
+        }
 
-            // guessing an average of 5 byte long instructions
-            log::PROGRESS.set("Decoding instructions", section.bytes().len() / width_guess);
+        #[cfg(target_arch = "x86_64")]
+        unsafe {
+            core::arch::x86_64::_mm_prefetch(
+                reader.as_ptr() as *const i8,
+                core::arch::x86_64::_MM_HINT_NTA
+            );
+        }
 
-            loop {
-                // prefetch next cache line line
-                #[cfg(target_arch = "x86")]
-                unsafe {
-                    core::arch::x86::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86::_MM_HINT_NTA
-                    );
-                }
+        match decoder.decode(&mut reader) {
+            Ok(mut instruction) => {
+                let prev_inst = if prev_was_error {
+                    None
+                } else {
+                    instructions.last().map(|inst| &inst.item)
+                };
 
-                #[cfg(target_arch = "x86_64")]
-                unsafe {
-                    core::arch::x86_64::_mm_prefetch(
-                        reader.as_ptr() as *const i8,
-                        core::arch::x86_64::_MM_HINT_NTA
-                    );
-                }
+                instruction.update_rel_addrs(ip, prev_inst);
 
-                match $decoder.decode(&mut reader) {
-                    Ok(mut instruction) => {
-                        instruction.update_rel_addrs(ip, prev_inst);
-
-                        let width = instruction.width();
-                        $instructions.push(Addressed {
-                            addr: ip,
-                            item: Instruction {
-                                $arch: std::mem::ManuallyDrop::new(instruction)
-                            }
-                        });
-
-                        prev_inst = $instructions.last().map(|inst| {
-                            unsafe { &*inst.item.$arch }
-                        });
-                        ip += width;
-                    }
-                    Err(error) => {
-                        if error.kind == decoder::ErrorKind::ExhaustedInput {
-                            break;
-                        }
+                let width = instruction.width();
+                instructions.push(Addressed {
+                    addr: ip,
+                    item: instruction,
+                });
 
-                        let width = error.size();
-                        $errors.push(Addressed {
-                            addr: ip,
-                            item: error
-                        });
-                        prev_inst = None;
-                        ip += width;
-                    }
+                prev_was_error = false;
+                ip += width;
+            }
+            Err(error) => {
+                if error.kind == decoder::ErrorKind::ExhaustedInput {
+                    break;
                 }
 
-                log::PROGRESS.step();
+                let width = error.size();
+                errors.push(Addressed {
+                    addr: ip,
+                    item: error
+                });
+                prev_was_error = true;
+                ip += width;
             }
         }
+
+        log::PROGRESS.step();
+    }
+
+    (instructions, errors)
+}
+
+macro_rules! decode_as {
+    ($decoder:expr, $arch:ident, $bytes:expr, $ip:expr, $end:expr) => {{
+        let (instructions, errors) = decode_range(&$decoder, $bytes, $ip, $end);
+        let xrefs = xref::collect(&instructions);
+        let tables = jump_table::find(&instructions);
+        let instructions = instructions.into_iter().map(|Addressed { addr, item }| Addressed {
+            addr,
+            item: Instruction {
+                $arch: ManuallyDrop::new(item)
+            }
+        });
+
+        (instructions.collect(), errors, xrefs, tables)
     }};
 }
 
+/// Instructions, errors, references and jump tables found when decoding.
+type DecodedRange = (
+    Vec<Addressed<Instruction>>,
+    Vec<Addressed<decoder::Error>>,
+    Vec<Xref>,
+    Vec<Addressed<JumpTable>>,
+);
+
+/// Ranges decoded one after another, merged as if decoded in one go.
+fn merge(parts: impl IntoIterator<Item = DecodedRange>) -> DecodedRange {
+    let mut decoded: DecodedRange = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    for (instructions, errors, xrefs, tables) in parts {
+        decoded.0.extend(instructions);
+        decoded.1.extend(errors);
+        decoded.2.extend(xrefs);
+        decoded.3.extend(tables);
+    }
+
+    decoded
+}
+
+/// Decode 32-bit ARM code in `modes`, where data is skipped.
+fn decode_arm(modes: &Modes, bytes: &[u8], ip: PhysAddr, end: PhysAddr) -> DecodedRange {
+    let end = end.min(ip + bytes.len());
+    let parts = modes.parts(ip, end).into_iter().filter_map(|(start, end, mode)| {
+        let bytes = &bytes[start - ip..];
+        match mode {
+            ArmMode::Arm => Some(decode_as!(armv7::Decoder::default(), armv7, bytes, start, end)),
+            ArmMode::Thumb => {
+                let decoder = armv7::Decoder::default_thumb();
+                Some(decode_as!(decoder, armv7, bytes, start, end))
+            }
+            ArmMode::Data => None,
+        }
+    });
+
+    merge(parts)
+}
+
+/// Decode `bytes` located at `ip` up to `end` using the decoder of the given instruction set.
+fn decode(isa: &Isa, modes: &Modes, bytes: &[u8], ip: PhysAddr, end: PhysAddr) -> DecodedRange {
+    let arch = match isa {
+        Isa::Native(arch) => *arch,
+        Isa::Dalvik(pools) => {
+            let decoder = dalvik::Decoder { pool: pools.at(ip) };
+            return decode_as!(decoder, dalvik, bytes, ip, end);
+        }
+        Isa::Jvm(pools) => {
+            let decoder = jvm::Decoder { pool: pools.at(ip), addr: ip };
+            return decode_as!(decoder, jvm, bytes, ip, end);
+        }
+        Isa::Cil(pool) => {
+            let decoder = cil::Decoder { pool, addr: ip };
+            return decode_as!(decoder, cil, bytes, ip, end);
+        }
+    };
+
+    match arch {
+        Architecture::Riscv32 => {
+            decode_as!(riscv::Decoder { is_64: false }, riscv, bytes, ip, end)
+        }
+        Architecture::Riscv64 => {
+            decode_as!(riscv::Decoder { is_64: true }, riscv, bytes, ip, end)
+        }
+        Architecture::Mips | Architecture::Mips64 => {
+            decode_as!(mips::Decoder, mips, bytes, ip, end)
+        }
+        Architecture::Avr => decode_as!(avr::Decoder, avr, bytes, ip, end),
+        Architecture::Msp430 => decode_as!(msp430::Decoder, msp430, bytes, ip, end),
+        Architecture::Bpf => decode_as!(bpf::Decoder, bpf, bytes, ip, end),
+        Architecture::X86_64_X32 | Architecture::I386 => {
+            decode_as!(x86::Decoder::default(), x86, bytes, ip, end)
+        }
+        Architecture::X86_64 => decode_as!(x64::Decoder::default(), x64, bytes, ip, end),
+        Architecture::Arm => decode_arm(modes, bytes, ip, end),
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+            decode_as!(aarch64::Decoder::default(), aarch64, bytes, ip, end)
+        }
+        arch => unreachable!("{arch:?} isn't decoded, it's rejected when parsing"),
+    }
+}
+
+/// Size of sections below which they aren't worth decoding in parallel.
+const MIN_PARALLEL_LEN: usize = 0x40000;
+
+/// Size of the chunks large sections are split into, which are decoded in parallel.
+const CHUNK_LEN: usize = 0x10000;
+
+/// Where decoding `start..end` is split into chunks of about `chunk_len` bytes, at addresses
+/// instructions can start at given their `alignment`. The splits don't have to fall on
+/// instructions, decoding lines up again after each one.
+fn split_points(
+    start: PhysAddr,
+    end: PhysAddr,
+    chunk_len: usize,
+    alignment: usize,
+) -> Vec<PhysAddr> {
+    let chunk_len = chunk_len.next_multiple_of(alignment.max(1));
+    (start + chunk_len..end).step_by(chunk_len).collect()
+}
+
+/// Where `decoded` ends, that is the end of its last instruction or error.
+fn decoded_end(decoded: &DecodedRange, width: fn(&Instruction) -> usize) -> Option<PhysAddr> {
+    let (instructions, errors, ..) = decoded;
+    let inst_end = instructions.last().map(|inst| inst.addr + width(&inst.item));
+    let err_end = errors.last().map(|err| err.addr + err.item.size());
+    inst_end.max(err_end)
+}
+
+/// Forget what's decoded at addresses `keep` returns false for.
+fn retain_decoded(isa: &Isa, decoded: &mut DecodedRange, keep: impl Fn(PhysAddr) -> bool) {
+    let (instructions, errors, xrefs, tables) = decoded;
+    instructions.retain_mut(|inst| {
+        if keep(inst.addr) {
+            return true;
+        }
+
+        drop_instruction(isa, &mut inst.item);
+        false
+    });
+    errors.retain(|err| keep(err.addr));
+    xrefs.retain(|xref| keep(xref.from));
+    tables.retain(|table| keep(table.addr));
+}
+
+/// Decode a code section split at `splits`, the chunks in between decoded in parallel and
+/// merged as if decoded in one go. Chunks stop being decoded once `loading` is cancelled.
+fn decode_section(
+    isa: &Isa,
+    modes: &Modes,
+    section: &Section,
+    splits: &[PhysAddr],
+    width: fn(&Instruction) -> usize,
+    loading: &Loading,
+) -> Result<DecodedRange, Error> {
+    let bytes = section.bytes();
+    let mut bounds = Vec::with_capacity(splits.len() + 2);
+    bounds.push(section.start);
+    bounds.extend_from_slice(splits);
+    bounds.push(section.start + bytes.len());
+
+    if bounds.len() == 2 {
+        return Ok(decode(isa, modes, bytes, section.start, usize::MAX));
+    }
+
+    let chunks: Vec<(PhysAddr, PhysAddr)> = bounds.windows(2).map(|b| (b[0], b[1])).collect();
+    let next_chunk = AtomicUsize::new(0);
+    let thread_count = std::thread::available_parallelism().map_or(1, |count| count.get());
+    let mut parts: Vec<(usize, DecodedRange)> = std::thread::scope(|s| {
+        let threads: Vec<_> = (0..thread_count.min(chunks.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut parts = Vec::new();
+                    loop {
+                        if loading.is_cancelled() {
+                            break parts;
+                        }
+
+                        let idx = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        let Some(&(start, end)) = chunks.get(idx) else {
+                            break parts;
+                        };
+
+                        let bytes = &bytes[start - section.start..];
+                        parts.push((idx, decode(isa, modes, bytes, start, end)));
+                    }
+                })
+            })
+            .collect();
+
+        threads.into_iter().flat_map(|thread| thread.join().unwrap()).collect()
+    });
+
+    if let Err(err) = loading.check() {
+        for (_, mut part) in parts {
+            retain_decoded(isa, &mut part, |_| false);
+        }
+
+        return Err(err);
+    }
+
+    parts.sort_unstable_by_key(|(idx, _)| *idx);
+    let mut parts = parts.into_iter().map(|(_, part)| part);
+    let mut decoded = parts.next().unwrap_or_default();
+    for (part, &(start, end)) in parts.zip(&chunks[1..]) {
+        append_chunk(isa, modes, section, width, &mut decoded, part, start, end);
+    }
+
+    Ok(decoded)
+}
+
+/// Append `next`, the chunk `start..end` of `section` decoded on its own, to what's decoded
+/// before it. The last instruction before `start` can continue past it, in which case decoding
+/// goes on until the instructions line up with those of `next`. The instructions around the
+/// split are then decoded again in one go, such that jump tables spanning it are found.
+#[allow(clippy::too_many_arguments)]
+fn append_chunk(
+    isa: &Isa,
+    modes: &Modes,
+    section: &Section,
+    width: fn(&Instruction) -> usize,
+    decoded: &mut DecodedRange,
+    mut next: DecodedRange,
+    start: PhysAddr,
+    end: PhysAddr,
+) {
+    let bytes = section.bytes();
+    let prev_end = decoded_end(decoded, width).unwrap_or(start).max(start);
+    let starts_at = |addr: PhysAddr| {
+        next.0.binary_search_by_key(&addr, |inst| inst.addr).is_ok()
+            || next.1.binary_search_by_key(&addr, |err| err.addr).is_ok()
+    };
+
+    // Step over instructions from where the chunk before ended, until reaching one of `next`.
+    let mut aligned = prev_end;
+    while aligned < end && !starts_at(aligned) {
+        let mut step = decode(isa, modes, &bytes[aligned - section.start..], aligned, aligned + 1);
+        let step_end = decoded_end(&step, width).unwrap_or(aligned);
+        retain_decoded(isa, &mut step, |_| false);
+        if step_end <= aligned {
+            break;
+        }
+
+        aligned = step_end;
+    }
+
+    // Decode again from the instructions that come before the split, up to a few instructions
+    // after the chunks line up again, which is how far back jump tables are looked for.
+    let before = decoded.0.len().saturating_sub(jump_table::WINDOW);
+    let from = decoded.0.get(before).map_or(start, |inst| inst.addr.min(start));
+    let after = next.0.partition_point(|inst| inst.addr < aligned) + jump_table::WINDOW;
+    let to = match next.0.get(after) {
+        Some(inst) => inst.addr,
+        None => decoded_end(&next, width).unwrap_or(aligned).max(aligned),
+    };
+
+    let mut around = decode(isa, modes, &bytes[from - section.start..], from, to);
+    retain_decoded(isa, &mut around, |addr| addr >= prev_end);
+    retain_decoded(isa, &mut next, |addr| addr >= to);
+
+    let around_end = decoded_end(&around, width).unwrap_or(to);
+    if around_end > to {
+        retain_decoded(isa, &mut next, |addr| addr >= around_end);
+    }
+
+    *decoded = merge([std::mem::take(decoded), around, next]);
+}
+
+/// How many bytes the largest instruction of an instruction set can be.
+fn max_instruction_width(isa: &Isa) -> usize {
+    let arch = match isa {
+        Isa::Native(arch) => *arch,
+        Isa::Dalvik(pools) => return dalvik::Decoder { pool: pools.at(0) }.max_width(),
+        Isa::Jvm(pools) => return jvm::Decoder { pool: pools.at(0), addr: 0 }.max_width(),
+        Isa::Cil(pool) => return cil::Decoder { pool, addr: 0 }.max_width(),
+    };
+
+    match arch {
+        Architecture::Riscv32 => riscv::Decoder { is_64: false }.max_width(),
+        Architecture::Riscv64 => riscv::Decoder { is_64: true }.max_width(),
+        Architecture::Mips | Architecture::Mips64 => mips::Decoder.max_width(),
+        Architecture::Avr => avr::Decoder.max_width(),
+        Architecture::Msp430 => msp430::Decoder.max_width(),
+        Architecture::Bpf => bpf::Decoder.max_width(),
+        Architecture::X86_64_X32 | Architecture::I386 => x86::Decoder::default().max_width(),
+        Architecture::X86_64 => x64::Decoder::default().max_width(),
+        Architecture::Arm => armv7::Decoder::default().max_width(),
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+            aarch64::Decoder::default().max_width()
+        }
+        _ => 0,
+    }
+}
+
+/// Alignment every instruction's address must have given the instruction set.
+fn instruction_alignment(isa: &Isa) -> usize {
+    let arch = match isa {
+        Isa::Native(arch) => *arch,
+        Isa::Dalvik(pools) => {
+            return dalvik::Decoder { pool: pools.at(0) }.instruction_alignment();
+        }
+        Isa::Jvm(pools) => {
+            return jvm::Decoder { pool: pools.at(0), addr: 0 }.instruction_alignment();
+        }
+        Isa::Cil(pool) => return cil::Decoder { pool, addr: 0 }.instruction_alignment(),
+    };
+
+    match arch {
+        Architecture::Riscv32 => riscv::Decoder { is_64: false }.instruction_alignment(),
+        Architecture::Riscv64 => riscv::Decoder { is_64: true }.instruction_alignment(),
+        Architecture::Mips | Architecture::Mips64 => mips::Decoder.instruction_alignment(),
+        Architecture::Avr => avr::Decoder.instruction_alignment(),
+        Architecture::Msp430 => msp430::Decoder.instruction_alignment(),
+        Architecture::Bpf => bpf::Decoder.instruction_alignment(),
+        Architecture::X86_64_X32 | Architecture::I386 => {
+            x86::Decoder::default().instruction_alignment()
+        }
+        Architecture::X86_64 => x64::Decoder::default().instruction_alignment(),
+        Architecture::Arm => armv7::Decoder::default().instruction_alignment(),
+        Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+            aarch64::Decoder::default().instruction_alignment()
+        }
+        _ => 1,
+    }
+}
+
+/// Drops an [`Instruction`] given the instruction set it was decoded for.
+fn drop_instruction(isa: &Isa, inst: &mut Instruction) {
+    let arch = match isa {
+        Isa::Native(arch) => *arch,
+        Isa::Dalvik(_) => return unsafe { ManuallyDrop::drop(&mut inst.dalvik) },
+        Isa::Jvm(_) => return unsafe { ManuallyDrop::drop(&mut inst.jvm) },
+        Isa::Cil(_) => return unsafe { ManuallyDrop::drop(&mut inst.cil) },
+    };
+
+    match arch {
+        Architecture::X86_64 => unsafe { ManuallyDrop::drop(&mut inst.x64) },
+        Architecture::X86_64_X32 | Architecture::I386 => unsafe {
+            ManuallyDrop::drop(&mut inst.x86)
+        },
+        Architecture::Riscv64 | Architecture::Riscv32 => unsafe {
+            ManuallyDrop::drop(&mut inst.riscv)
+        },
+        Architecture::Mips | Architecture::Mips64 => unsafe {
+            ManuallyDrop::drop(&mut inst.mips)
+        },
+        Architecture::Avr => unsafe { ManuallyDrop::drop(&mut inst.avr) },
+        Architecture::Msp430 => unsafe { ManuallyDrop::drop(&mut inst.msp430) },
+        Architecture::Bpf => unsafe { ManuallyDrop::drop(&mut inst.bpf) },
+        _ => {}
+    }
+}
+
+/// Results of decoding the code sections.
+#[derive(Default)]
+struct Code {
+    /// Errors occurred in decoding instructions.
+    /// Sorted by address.
+    errors: AddressMap<decoder::Error>,
+
+    /// Successfully decoded instructions.
+    /// Sorted by address.
+    instructions: AddressMap<Instruction>,
+
+    /// References made by the instructions.
+    xrefs: xref::Xrefs,
+
+    /// Tables of indirect jumps.
+    tables: jump_table::SwitchTables,
+}
+
+impl Code {
+    fn error_by_addr(&self, addr: PhysAddr) -> Option<&decoder::Error> {
+        match self.errors.search(addr) {
+            Ok(idx) => Some(&self.errors[idx].item),
+            Err(..) => None,
+        }
+    }
+
+    fn instruction_by_addr(&self, addr: PhysAddr) -> Option<&Instruction> {
+        match self.instructions.search(addr) {
+            Ok(idx) => Some(&self.instructions[idx].item),
+            Err(..) => None,
+        }
+    }
+}
+
+/// What's known about a binary before it's code is decoded.
+struct Headers {
+    entrypoint: PhysAddr,
+    /// Functions only reached through the headers or runtime, see [`entrypoints`].
+    entrypoints: Vec<PhysAddr>,
+    /// Instruction sets marked by the mapping symbols of 32-bit ARM, see [`modes`].
+    modes: Vec<(PhysAddr, ArmMode)>,
+    /// Functions of 32-bit ARM binaries made of Thumb instructions.
+    thumb_functions: Vec<PhysAddr>,
+    index: Index,
+    /// Sorted by address.
+    sections: Vec<Section>,
+    /// Sorted by address.
+    segments: Vec<Segment>,
+    /// Sorted by address.
+    pointers: AddressMap<PhysAddr>,
+    /// Sorted by address.
+    exports: AddressMap<String>,
+    isa: Isa,
+    endianness: Endianness,
+    convention: Option<CallingConvention>,
+}
+
 /// Architecture agnostic analysis of a module.
 pub struct Processor {
     /// Where execution start. Might be zero in case of libraries.
@@ -141,7 +654,15 @@ pub struct Processor {
     _file: File,
 
     /// A memory map of the binary.
-    _mmap: Mmap,
+    mmap: Mmap,
+
+    /// Memory maps backing the binary besides the main one, such as the files a shared cache is
+    /// split into or the memory image of a firmware file.
+    _backing: Vec<Mmap>,
+
+    /// Layers of compression or packing removed from the file, in which case `mmap` holds the
+    /// unpacked file instead of mapping it.
+    unpacking: Vec<Unpacking>,
 
     /// Object's sections sorted by address.
     sections: Vec<Section>,
@@ -149,85 +670,264 @@ pub struct Processor {
     /// Object's segments sorted by address.
     segments: Vec<Segment>,
 
-    /// Errors occurred in decoding instructions.
-    /// Sorted by address.
-    errors: AddressMap<decoder::Error>,
+    /// Pointers written by the loader, as the binary only stores how to compute them.
+    pointers: AddressMap<PhysAddr>,
 
-    /// Successfully decoded instructions.
-    /// Sorted by address.
-    instructions: AddressMap<Instruction>,
+    /// Symbols the binary makes available to others, sorted by address.
+    exports: AddressMap<String>,
+
+    /// Decoded instructions and errors. Behind a lock as ranges can be reanalyzed.
+    code: RwLock<Code>,
 
     /// How many bytes an instruction given the architecture.
     max_instruction_width: usize,
 
+    /// Alignment of instruction addresses given the architecture.
+    instruction_alignment: usize,
+
     /// Function pointer to an [`Instruction`]'s implementation of [`Decoded::tokens`].
     instruction_tokens: fn(&Instruction, &Index) -> Vec<Token>,
 
     /// Function pointer to an [`Instruction`]'s implementation of [`Decoded::width`].
     instruction_width: fn(&Instruction) -> usize,
 
-    /// Target's instruction set.
+    /// Function pointer to an [`Instruction`]'s implementation of [`Decoded::flow`].
+    instruction_flow: fn(&Instruction) -> Flow,
+
+    /// Target's instruction set, unknown for bytecode.
     arch: Architecture,
 
+    /// Instruction set code is decoded as, which includes the constant pools of bytecode.
+    isa: Isa,
+
     /// Target's endianness.
     endianness: Endianness,
+
+    /// How functions are passed their arguments, if it's known for the architecture.
+    convention: Option<CallingConvention>,
+
+    /// Comments, renames and bookmarks made by the user.
+    pub annotations: RwLock<Annotations>,
+
+    /// Bytes changed by the user.
+    patches: RwLock<Patches>,
+
+    /// Ranges passed to [`Processor::reanalyze_range`], kept for saving projects.
+    reanalyzed: Mutex<Vec<(PhysAddr, PhysAddr)>>,
+
+    /// Ranges defined as code, data or an instruction set by the user, in the order they were
+    /// defined.
+    definitions: Mutex<Vec<(PhysAddr, PhysAddr, Definition)>>,
+
+    /// Sections the user toggled between being decoded as code or not, by their start.
+    code_sections: Mutex<BTreeMap<PhysAddr, bool>>,
+
+    /// Instruction sets of 32-bit ARM code.
+    modes: RwLock<Modes>,
+
+    /// Edits made by the user that can be undone or redone.
+    history: Mutex<History>,
 }
 
 impl Processor {
     pub fn parse<P: AsRef<std::path::Path>>(path: P) -> Result<Self, Error> {
-        let file = std::fs::File::open(path.as_ref()).map_err(Error::IO)?;
-        let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
+        Self::parse_with(path, &Loading::default())
+    }
+
+    /// Parse the object at `path`, reporting progress through `loading`.
+    pub fn parse_with<P: AsRef<std::path::Path>>(
+        path: P,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let (file, mmap, unpacking) = unpack::map(path.as_ref())?;
         let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
-        let obj = ObjectFile::parse(binary)?;
 
+        // Bytecode isn't stored in an object, and class files share their magic with fat Mach-O's.
         let path = path.as_ref().to_path_buf();
+        let mut processor = match bytecode::Format::detect(binary) {
+            Some(format) => Self::parse_bytecode(path, file, mmap, format, loading)?,
+            None => Self::parse_native(path, file, mmap, loading)?,
+        };
+
+        processor.unpacking = unpacking;
+        Ok(processor)
+    }
+
+    /// Parse the object file `mmap` of `path`, which isn't bytecode.
+    fn parse_native(
+        path: std::path::PathBuf,
+        file: File,
+        mmap: Mmap,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+
+        match object::FileKind::parse(binary) {
+            Ok(object::FileKind::DyldCache) => return Err(Error::SharedCache),
+            Ok(
+                object::FileKind::Archive
+                | object::FileKind::MachOFat32
+                | object::FileKind::MachOFat64,
+            ) => return Err(Error::Container(container::names(binary))),
+            Err(..) => return Err(Error::UnknownFormat),
+            Ok(..) => {}
+        }
+
+        let obj = ObjectFile::parse(binary)?;
+        Self::parse_object(path, file, mmap, Vec::new(), obj, loading)
+    }
+
+    /// Analyze `obj`, which is backed by `mmap` and any of the `backing` maps.
+    fn parse_object(
+        path: std::path::PathBuf,
+        file: File,
+        mmap: Mmap,
+        mut backing: Vec<Mmap>,
+        obj: ObjectFile<'static>,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
         let now = std::time::Instant::now();
+        let started = loading.start(Stage::Headers)?;
 
         let mut syms = AddressMap::default();
         let mut sections = Vec::new();
+        let mut pointers = AddressMap::default();
+        let mut modes = Vec::new();
+        let mut thumb_functions = Vec::new();
+        let mut module = None;
         match &obj {
             object::File::MachO32(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                pointers.extend(debug_info.pointers);
             }
             object::File::MachO64(macho) => {
                 let debug_info = macho::MachoDebugInfo::parse(macho)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                pointers.extend(debug_info.pointers);
             }
             object::File::Elf32(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                modes.extend(debug_info.modes);
+                thumb_functions.extend(debug_info.thumb_functions);
             }
             object::File::Elf64(elf) => {
                 let debug_info = elf::ElfDebugInfo::parse(elf)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                if !debug_info.relocations.is_empty() {
+                    let memory = bpf_object::relocate(&mut sections, &debug_info.relocations)
+                        .map_err(Error::IO)?;
+                    backing.push(memory);
+                }
             }
             object::File::Pe32(pe) => {
                 let debug_info = pe::PeDebugInfo::parse(pe)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                module = clr::parse(pe);
             }
             object::File::Pe64(pe) => {
                 let debug_info = pe::PeDebugInfo::parse(pe)?;
                 sections.extend(debug_info.sections);
                 syms.extend(debug_info.syms);
+                module = clr::parse(pe);
             }
             _ => {}
         }
 
+        let mut segments = Vec::new();
+        for segment in obj.segments() {
+            // Segments of ELF's are nameless, and only those that are loaded are iterated.
+            let name = segment.name()?.unwrap_or("LOAD").to_string();
+            let start = segment.address() as PhysAddr;
+            let end = start + segment.size() as PhysAddr;
+
+            segments.push(Segment { name, start, end });
+        }
+
+        // Mixed-mode assemblies hold native code besides their CIL, which is decoded instead.
+        let mut module = match module {
+            Some(module) if !module.il_only => {
+                log::complex!(
+                    w "[processor::parse] ",
+                    w "mixed-mode assembly, decoding native code.",
+                );
+                None
+            }
+            module => module,
+        };
+
+        if let Some(module) = &module {
+            clr::split_sections(&mut sections, module.code);
+        }
+
+        segments.sort_unstable_by_key(|s| s.start);
+        sections.sort_unstable_by_key(|s| s.start);
+        pointers.sort_unstable_by_key(|p| p.addr);
+
+        // Symbols of BPF objects are offsets into their section, which were laid out above.
+        let exports_table = match &obj {
+            object::File::Elf64(elf) if elf::is_bpf_object(elf) => Vec::new(),
+            _ => obj.exports()?,
+        };
+
+        let mut exports = AddressMap::default();
+        for export in exports_table {
+            exports.push(Addressed {
+                addr: export.address() as PhysAddr,
+                item: String::from_utf8_lossy(export.name()).into_owned(),
+            });
+        }
+        exports.sort_unstable_by_key(|e| e.addr);
+
+        loading.finish(
+            Stage::Headers,
+            started,
+            format!("{} sections and {} segments", sections.len(), segments.len()),
+        );
+
+        let started = loading.start(Stage::Symbols)?;
         for section in sections.iter() {
             syms.push(Addressed {
                 addr: section.start,
-                item: RawSymbol { name: &section.name, module: None }
+                item: RawSymbol {
+                    name: &section.name,
+                    module: None,
+                    indirection: None,
+                }
             });
         }
 
-        let index = Index::parse(&obj, &path, syms).map_err(Error::Debug)?;
-        let entrypoint = index.get_func_by_name("entry").unwrap_or(0);
+        let mut index = Index::parse(&obj, &path, syms).map_err(Error::Debug)?;
+        let mut entrypoint = index.get_func_by_name("entry").unwrap_or(0);
+
+        // The entrypoint of an assembly is a stub starting the runtime, which runs it's
+        // entrypoint method.
+        if let Some(module) = &mut module {
+            entrypoint = module.entrypoint.unwrap_or(0);
+            index.insert_identified(std::mem::take(&mut module.methods));
+        }
+
+        let entrypoints = entrypoints::find(&obj, &sections);
+        if !entrypoints.is_empty() {
+            log::complex!(
+                w "[processor::parse] found ",
+                y entrypoints.len().to_string(),
+                w " functions reached through the headers.",
+            );
+        }
+
+        let entrypoints = {
+            let addrs = entrypoints.iter().map(|(addr, _)| *addr).collect();
+            index.insert_identified(entrypoints);
+            addrs
+        };
 
         if entrypoint != 0 {
             log::complex!(
@@ -237,17 +937,11 @@ impl Processor {
             );
         }
 
-        let mut segments = Vec::new();
-        for segment in obj.segments() {
-            let name = segment.name()?.unwrap_or("unknown").to_string();
-            let start = segment.address() as PhysAddr;
-            let end = start + segment.size() as PhysAddr;
-
-            segments.push(Segment { name, start, end });
-        }
-
-        segments.sort_unstable_by_key(|s| s.start);
-        sections.sort_unstable_by_key(|s| s.start);
+        loading.finish(
+            Stage::Symbols,
+            started,
+            format!("{} named functions", index.named_funcs_count()),
+        );
 
         if sections.is_empty() {
             let base = if obj.format() == BinaryFormat::Pe {
@@ -283,124 +977,209 @@ impl Processor {
             segments.push(segment);
         }
 
-        let arch = obj.architecture();
-        let (instruction_tokens, instruction_width) = unsafe {
-            match arch {
-                Architecture::Riscv32 | Architecture::Riscv64 => (
-                    std::mem::transmute(<riscv::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<riscv::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::Mips | Architecture::Mips64 => (
-                    std::mem::transmute(<mips::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<mips::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::X86_64_X32 | Architecture::I386 => (
-                    std::mem::transmute(<x86::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<x86::Instruction as Decoded>::width as usize),
-                ),
-                Architecture::X86_64 => (
-                    std::mem::transmute(<x64::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<x64::Instruction as Decoded>::width as usize),
+        let (isa, convention) = match module {
+            Some(module) => (Isa::Cil(module.pool), None),
+            None => {
+                let arch = obj.architecture();
+                let convention =
+                    CallingConvention::native(arch, obj.format() == BinaryFormat::Pe);
+                (Isa::Native(arch), convention)
+            }
+        };
+
+        let headers = Headers {
+            entrypoint,
+            entrypoints,
+            modes,
+            thumb_functions,
+            index,
+            sections,
+            segments,
+            pointers,
+            exports,
+            isa,
+            endianness: obj.endianness(),
+            convention,
+        };
+
+        Self::analyze(path, file, mmap, backing, headers, now, loading)
+    }
+
+    /// Decode and analyze the code described by `headers`, which is backed by `mmap` and any of
+    /// the `backing` maps.
+    fn analyze(
+        path: std::path::PathBuf,
+        file: File,
+        mmap: Mmap,
+        backing: Vec<Mmap>,
+        headers: Headers,
+        now: std::time::Instant,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let Headers {
+            entrypoint,
+            entrypoints,
+            modes,
+            thumb_functions,
+            mut index,
+            sections,
+            segments,
+            pointers,
+            exports,
+            isa,
+            endianness,
+            convention,
+        } = headers;
+
+        let arch = match isa {
+            Isa::Native(arch) => arch,
+            Isa::Dalvik(_) | Isa::Jvm(_) | Isa::Cil(_) => Architecture::Unknown,
+        };
+
+        let (instruction_tokens, instruction_width, instruction_flow) = unsafe {
+            match &isa {
+                Isa::Dalvik(_) => (
+                    std::mem::transmute(<dalvik::Instruction as Decoded>::tokens as usize),
+                    std::mem::transmute(<dalvik::Instruction as Decoded>::width as usize),
+                    std::mem::transmute(<dalvik::Instruction as Decoded>::flow as usize),
                 ),
-                Architecture::Arm => (
-                    std::mem::transmute(<armv7::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<armv7::Instruction as Decoded>::width as usize),
+                Isa::Jvm(_) => (
+                    std::mem::transmute(<jvm::Instruction as Decoded>::tokens as usize),
+                    std::mem::transmute(<jvm::Instruction as Decoded>::width as usize),
+                    std::mem::transmute(<jvm::Instruction as Decoded>::flow as usize),
                 ),
-                Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => (
-                    std::mem::transmute(<aarch64::Instruction as Decoded>::tokens as usize),
-                    std::mem::transmute(<aarch64::Instruction as Decoded>::width as usize),
+                Isa::Cil(_) => (
+                    std::mem::transmute(<cil::Instruction as Decoded>::tokens as usize),
+                    std::mem::transmute(<cil::Instruction as Decoded>::width as usize),
+                    std::mem::transmute(<cil::Instruction as Decoded>::flow as usize),
                 ),
-                arch => return Err(Error::UnknownArchitecture(arch)),
+                Isa::Native(_) => match arch {
+                    Architecture::Riscv32 | Architecture::Riscv64 => (
+                        std::mem::transmute(<riscv::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<riscv::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<riscv::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Mips | Architecture::Mips64 => (
+                        std::mem::transmute(<mips::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<mips::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<mips::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Avr => (
+                        std::mem::transmute(<avr::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<avr::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<avr::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Msp430 => (
+                        std::mem::transmute(<msp430::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<msp430::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<msp430::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Bpf => (
+                        std::mem::transmute(<bpf::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<bpf::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<bpf::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::X86_64_X32 | Architecture::I386 => (
+                        std::mem::transmute(<x86::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<x86::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<x86::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::X86_64 => (
+                        std::mem::transmute(<x64::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<x64::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<x64::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Arm => (
+                        std::mem::transmute(<armv7::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<armv7::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<armv7::Instruction as Decoded>::flow as usize),
+                    ),
+                    Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => (
+                        std::mem::transmute(<aarch64::Instruction as Decoded>::tokens as usize),
+                        std::mem::transmute(<aarch64::Instruction as Decoded>::width as usize),
+                        std::mem::transmute(<aarch64::Instruction as Decoded>::flow as usize),
+                    ),
+                    arch => return Err(Error::UnknownArchitecture(arch)),
+                },
             }
         };
 
-        let mut instructions = AddressMap::default();
-        let mut errors = AddressMap::default();
-        let max_instruction_width;
-
-        match arch {
-            Architecture::Riscv32 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    riscv::Decoder { is_64: false },
-                    riscv
-                )
-            }
-            Architecture::Riscv64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    riscv::Decoder { is_64: true },
-                    riscv
-                )
-            }
-            Architecture::Mips | Architecture::Mips64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    mips::Decoder::default(),
-                    mips
-                )
-            }
-            Architecture::X86_64_X32 | Architecture::I386 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    x86::Decoder::default(),
-                    x86
-                )
-            }
-            Architecture::X86_64 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    x64::Decoder::default(),
-                    x64
-                )
-            }
-            Architecture::Arm => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    armv7::Decoder::default(),
-                    armv7
-                )
-            },
-            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
-                impl_recursion!(
-                    &index,
-                    &mut errors,
-                    &mut instructions,
-                    &mut sections,
-                    max_instruction_width,
-                    aarch64::Decoder::default(),
-                    aarch64
-                )
-            }
-            _ => unreachable!(),
+        let max_instruction_width = max_instruction_width(&isa);
+        let width_guess = if max_instruction_width == 4 {
+            4
+        } else {
+            5
         };
 
-        instructions.sort_unstable();
-        errors.sort_unstable();
+        let modes = Modes::new(modes);
+        let started = loading.start(Stage::Decoding)?;
+        let mut code = Code::default();
+        let mut tables = Vec::new();
+        for section in sections.iter().filter(|s| s.kind == SectionKind::Code) {
+            loading.check()?;
+
+            log::complex!(
+                w "[processor::recurse] analyzing section ",
+                b &*section.name,
+                w " <",
+                g format!("{:x}", section.start),
+                w "..",
+                g format!("{:x}", section.end),
+                w ">.",
+            );
+
+            // guessing an average of 5 byte long instructions
+            log::PROGRESS.set("Decoding instructions", section.bytes().len() / width_guess);
+
+            // Large sections are split into chunks, which are decoded in parallel.
+            let splits = if section.bytes().len() >= MIN_PARALLEL_LEN {
+                let alignment = instruction_alignment(&isa);
+                split_points(section.start, section.end, CHUNK_LEN, alignment)
+            } else {
+                Vec::new()
+            };
+
+            let (instructions, errors, xrefs, found) =
+                decode_section(&isa, &modes, section, &splits, instruction_width, loading)?;
+            code.instructions.mapping.extend(instructions);
+            code.errors.mapping.extend(errors);
+            code.xrefs.extend(xrefs);
+            tables.extend(found);
+        }
+
+        code.instructions.sort_unstable();
+        code.errors.sort_unstable();
+
+        loading.finish(
+            Stage::Decoding,
+            started,
+            format!("{} instructions", code.instructions.len()),
+        );
+
+        let started = loading.start(Stage::Analysis)?;
+
+        // Instructions referring to strings show them by name.
+        index.insert_strings(strings::string_symbols(&sections, &code.xrefs));
+
+        // Stripped binaries get their statically linked library functions named by signatures.
+        if !CONFIG.analysis.signatures.is_empty() {
+            let sigs = signatures::load_all(&CONFIG.analysis.signatures);
+            let identified = signatures::identify_functions(&sigs, &sections, &code.xrefs, &index);
+
+            log::complex!(
+                w "[processor::parse] identified ",
+                y identified.len().to_string(),
+                w " library functions by their signature.",
+            );
+
+            index.insert_identified(identified);
+        }
+
+        log::complex!(
+            w "[processor::parse] found ",
+            y code.xrefs.len().to_string(),
+            w " cross references.",
+        );
 
         log::complex!(
             w "[processor::parse] took ",
@@ -409,22 +1188,62 @@ impl Processor {
             w format!("{path:?}.")
         );
 
-        Ok(Self {
+        let processor = Self {
             entrypoint,
             path,
             sections,
             segments,
-            errors,
-            instructions,
+            code: RwLock::new(code),
             index,
+            pointers,
+            exports,
             _file: file,
-            _mmap: mmap,
+            mmap,
+            _backing: backing,
+            unpacking: Vec::new(),
             max_instruction_width,
+            instruction_alignment: instruction_alignment(&isa),
             instruction_tokens,
             instruction_width,
+            instruction_flow,
             arch,
-            endianness: obj.endianness(),
-        })
+            isa,
+            endianness,
+            convention,
+            annotations: RwLock::default(),
+            patches: RwLock::default(),
+            reanalyzed: Mutex::default(),
+            definitions: Mutex::default(),
+            code_sections: Mutex::default(),
+            modes: RwLock::new(modes),
+            history: Mutex::default(),
+        };
+
+        processor.add_jump_tables(tables);
+        processor.decode_entrypoints(&entrypoints);
+        processor.decode_thumb_functions(&thumb_functions);
+        processor.follow_mode_switches();
+
+        if CONFIG.analysis.check_alignment {
+            let misaligned = processor.misaligned_addrs();
+            if !misaligned.is_empty() {
+                log::complex!(
+                    w "[processor::parse] found ",
+                    y misaligned.len().to_string(),
+                    w " code addresses that aren't ",
+                    y processor.instruction_alignment.to_string(),
+                    w " byte aligned, these are likely data or decoded in the wrong mode.",
+                );
+            }
+        }
+
+        loading.finish(
+            Stage::Analysis,
+            started,
+            format!("{} cross references", processor.code.read().unwrap().xrefs.len()),
+        );
+
+        Ok(processor)
     }
 
     /// Relatively slow tokenization of an [`Instruction`].
@@ -437,18 +1256,338 @@ impl Processor {
         (self.instruction_width)(instruction)
     }
 
-    pub fn error_by_addr(&self, addr: PhysAddr) -> Option<&decoder::Error> {
-        match self.errors.search(addr) {
-            Ok(idx) => Some(&self.errors[idx].item),
-            Err(..) => None,
+    /// Lifts the instruction at `addr` into the architecture independent [`ir`], if there's
+    /// a lifter for the binary's architecture.
+    pub fn lift(&self, addr: PhysAddr) -> Option<Vec<ir::Stmt>> {
+        self.lift_at(addr, addr)
+    }
+
+    /// Lifts the instruction at `addr` as if it were at `pc`, such as where it's loaded in a
+    /// process, which is what addresses relative to the program counter are relative to.
+    fn lift_at(&self, addr: PhysAddr, pc: PhysAddr) -> Option<Vec<ir::Stmt>> {
+        use ir::Lift;
+
+        let code = self.code.read().unwrap();
+        let inst = code.instruction_by_addr(addr)?;
+        match self.arch {
+            Architecture::X86_64 => Some(unsafe { &inst.x64 }.stmts(pc)),
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+                Some(unsafe { &inst.aarch64 }.stmts(pc))
+            }
+            _ => None,
         }
     }
 
-    pub fn instruction_by_addr(&self, addr: PhysAddr) -> Option<&Instruction> {
-        match self.instructions.search(addr) {
-            Ok(idx) => Some(&self.instructions[idx].item),
-            Err(..) => None,
+    /// Describes the instruction at `addr` using the ISA reference of it's decoder, if there's
+    /// one for the binary's architecture.
+    pub fn describe(&self, addr: PhysAddr) -> Option<decoder::Description> {
+        let code = self.code.read().unwrap();
+        let inst = code.instruction_by_addr(addr)?;
+        match self.arch {
+            Architecture::X86_64 => unsafe { &inst.x64 }.describe(&self.index),
+            Architecture::X86_64_X32 | Architecture::I386 => {
+                unsafe { &inst.x86 }.describe(&self.index)
+            }
+            Architecture::Arm => unsafe { &inst.armv7 }.describe(&self.index),
+            Architecture::Aarch64 | Architecture::Aarch64_Ilp32 => {
+                unsafe { &inst.aarch64 }.describe(&self.index)
+            }
+            _ => None,
+        }
+    }
+
+    /// Addresses and text of the instructions around `addr`, up to `before` instructions in
+    /// front of the one at it and `after` instructions following it.
+    pub fn instructions_around(
+        &self,
+        addr: PhysAddr,
+        before: usize,
+        after: usize,
+    ) -> Vec<(PhysAddr, String)> {
+        let code = self.code.read().unwrap();
+        let idx = code.instructions.partition_point(|inst| inst.addr < addr);
+        let start = idx.saturating_sub(before);
+        let end = (idx + after + 1).min(code.instructions.len());
+
+        code.instructions[start..end]
+            .iter()
+            .map(|inst| {
+                let tokens = self.instruction_tokens(&inst.item, &self.index);
+                (inst.addr, tokens.iter().map(|token| &token.text as &str).collect())
+            })
+            .collect()
+    }
+
+    pub fn error_by_addr(&self, addr: PhysAddr) -> Option<decoder::Error> {
+        self.code.read().unwrap().error_by_addr(addr).copied()
+    }
+
+    /// Re-decodes all code between `start` and `end`, replacing any instructions and errors
+    /// previously decoded in that range. Returns the number of decoded instructions.
+    ///
+    /// Any instruction that starts before `start` but overlaps with the range is kept as is. The
+    /// last instruction can continue past `end`, in which case decoding goes on until the
+    /// instructions line up with the ones decoded before, replacing those they overlap.
+    pub fn reanalyze_range(&self, start: PhysAddr, end: PhysAddr) -> usize {
+        let section = match self.section_by_addr(start) {
+            Some(section) if self.section_is_code(section) => section,
+            _ => return 0,
+        };
+
+        let end = end.min(section.end);
+        if start >= end {
+            return 0;
+        }
+
+        self.reanalyzed.lock().unwrap().push((start, end));
+        self.redecode(section, start, end).0
+    }
+
+    /// Decode `start..end` of `section` with any patches applied, replacing what was decoded.
+    ///
+    /// The last instruction can continue past `end`, in which case decoding goes on until the
+    /// instructions line up with the ones decoded before, replacing those they overlap. Returns
+    /// the number of decoded instructions and where the replaced bytes end.
+    fn redecode(&self, section: &Section, start: PhysAddr, end: PhysAddr) -> (usize, PhysAddr) {
+        let bytes = self.bytes_by_addr(section, start, section.end - start);
+        let modes = self.modes.read().unwrap();
+        let code = self.code.read().unwrap();
+
+        let mut parts = Vec::new();
+        let (mut ip, mut until) = (start, end);
+        loop {
+            let part = decode(&self.isa, &modes, &bytes[ip - start..], ip, until);
+            let (instructions, errors, ..) = &part;
+            let inst_end = instructions.last().map(|i| i.addr + self.instruction_width(&i.item));
+            let err_end = errors.last().map(|err| err.addr + err.item.size());
+            let decoded_end = inst_end.max(err_end).unwrap_or(ip);
+            parts.push(part);
+
+            // Nothing more could be decoded, or the bytes that follow aren't decoded.
+            if decoded_end <= ip || decoded_end >= section.end {
+                ip = decoded_end.max(ip);
+                break;
+            }
+
+            ip = decoded_end;
+            match self.decoded_over(&code, ip) {
+                Some(old) if old.start < ip => until = ip + 1,
+                _ => break,
+            }
+        }
+
+        drop(code);
+        drop(modes);
+
+        let end = ip.max(end);
+        let (instructions, errors, xrefs, tables) = merge(parts);
+        let decoded = instructions.len();
+
+        let mut code = self.code.write().unwrap();
+        code.undecode(&self.isa, start..end);
+
+        code.instructions.mapping.extend(instructions);
+        code.errors.mapping.extend(errors);
+        code.xrefs.extend(xrefs);
+        code.instructions.sort_unstable();
+        code.errors.sort_unstable();
+        drop(code);
+
+        self.add_jump_tables(tables);
+
+        log::complex!(
+            w "[processor::reanalyze] decoded ",
+            y decoded.to_string(),
+            w " instructions in <",
+            g format!("{start:x}"),
+            w "..",
+            g format!("{end:x}"),
+            w ">.",
+        );
+
+        (decoded, end)
+    }
+
+    /// `symbol` at `addr` as shown to the user, that is with the user's renames applied.
+    pub fn shown_symbol(&self, addr: PhysAddr, symbol: Arc<Symbol>) -> Arc<Symbol> {
+        match self.annotations.read().unwrap().renames.get(&addr) {
+            Some(name) => Arc::new(Symbol::user_defined(name)),
+            None => symbol,
+        }
+    }
+
+    /// Named functions matching `pattern` by the names shown to the user, best matches first.
+    pub fn fuzzy_search(&self, pattern: &str, limit: usize) -> Vec<FuzzyMatch> {
+        let renames = self.annotations.read().unwrap().renames.clone();
+
+        // Renamed functions are matched by their new name instead.
+        let mut matches = self.index.fuzzy_search(pattern, limit + renames.len());
+        matches.retain(|m| !renames.contains_key(&m.addr));
+
+        for (addr, name) in renames {
+            if let Some(m) = fuzzy::fuzzy_match(pattern, &name) {
+                matches.push(FuzzyMatch {
+                    addr,
+                    symbol: Arc::new(Symbol::user_defined(&name)),
+                    score: m.score,
+                    positions: m.positions,
+                });
+            }
         }
+
+        fuzzy::sort_matches(&mut matches);
+        matches.truncate(limit);
+        matches
+    }
+
+    /// Snapshot of the analysis and annotations, `boundaries` being the listing's blocks.
+    pub fn project(&self, boundaries: Vec<PhysAddr>) -> Project {
+        Project {
+            binary: self.path.clone(),
+            checksum: project::checksum(&self.mmap),
+            reanalyzed: self.reanalyzed.lock().unwrap().clone(),
+            definitions: self.definitions(),
+            code_sections: self.code_sections(),
+            boundaries,
+            annotations: self.annotations.read().unwrap().clone(),
+        }
+    }
+
+    /// Restore the analysis and annotations of a project saved for this binary.
+    pub fn apply_project(&self, project: &Project) -> Result<(), Error> {
+        if project::checksum(&self.mmap) != project.checksum {
+            return Err(Error::Project(project::Error::Modified(self.path.clone())));
+        }
+
+        // Sections are decoded first, as only code is reanalyzed or defined.
+        for &(start, code) in &project.code_sections {
+            self.apply_code_section(start, code);
+        }
+
+        for &(start, end) in &project.reanalyzed {
+            self.reanalyze_range(start, end);
+        }
+
+        self.apply_definitions(&project.definitions);
+
+        *self.annotations.write().unwrap() = project.annotations.clone();
+
+        // Edits made before the project was applied were replaced by it's own.
+        self.history.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// All references to `addr`, sorted by the address of the referencing instruction.
+    pub fn xrefs_to(&self, addr: PhysAddr) -> Vec<Xref> {
+        let mut xrefs = self.code.read().unwrap().xrefs.to(addr).to_vec();
+        xrefs.sort_unstable_by_key(|xref| xref.from);
+        xrefs
+    }
+
+    /// All references made by the instruction at `addr`.
+    pub fn xrefs_from(&self, addr: PhysAddr) -> Vec<Xref> {
+        self.code.read().unwrap().xrefs.from(addr).to_vec()
+    }
+
+    /// Instructions calling `addr`.
+    pub fn callers(&self, addr: PhysAddr) -> Vec<PhysAddr> {
+        self.xrefs_to(addr)
+            .into_iter()
+            .filter(|xref| xref.kind == XrefKind::Call)
+            .map(|xref| xref.from)
+            .collect()
+    }
+
+    /// Address the listing should start at, given the configured [`StartupLocation`].
+    ///
+    /// Falls back to the entrypoint and then the first section if the location isn't found.
+    pub fn startup_addr(&self) -> PhysAddr {
+        let addr = match CONFIG.startup_location {
+            StartupLocation::EntryPoint => None,
+            StartupLocation::Main => self.main_addr(),
+            StartupLocation::FirstSymbol => self
+                .index
+                .functions()
+                .find(|func| !func.item.intrinsic() && self.is_code(func.addr))
+                .map(|func| func.addr),
+            StartupLocation::FirstSection => self.sections().next().map(|section| section.start),
+        };
+
+        let entrypoint = (self.entrypoint != 0).then_some(self.entrypoint);
+        addr.or(entrypoint)
+            .or_else(|| self.sections().next().map(|section| section.start))
+            .unwrap_or(0)
+    }
+
+    /// Try to find the user's main function by it's common names.
+    pub fn main_addr(&self) -> Option<PhysAddr> {
+        const NAMES: &[&str] = &["main", "_main", "wmain", "WinMain", "wWinMain"];
+
+        NAMES.iter().find_map(|name| self.index.get_func_by_name(name))
+    }
+
+    /// Whether `addr` is located in a code section.
+    pub fn is_code(&self, addr: PhysAddr) -> bool {
+        matches!(
+            self.section_by_addr(addr),
+            Some(section) if self.section_is_code(section)
+        )
+    }
+
+    /// Whether code at `addr` violates the architecture's instruction alignment.
+    pub fn is_misaligned(&self, addr: PhysAddr) -> bool {
+        match self.arm_mode(addr) {
+            Some(ArmMode::Thumb) => addr % 2 != 0,
+            _ => addr % self.instruction_alignment != 0,
+        }
+    }
+
+    /// Addresses of instructions and functions in code sections that aren't aligned to the
+    /// architecture's instruction alignment. Always empty for architectures without one.
+    pub fn misaligned_addrs(&self) -> Vec<PhysAddr> {
+        if self.instruction_alignment == 1 {
+            return Vec::new();
+        }
+
+        let code = self.code.read().unwrap();
+        let mut misaligned: Vec<PhysAddr> = code
+            .instructions
+            .iter()
+            .map(|inst| inst.addr)
+            .filter(|&addr| self.is_misaligned(addr))
+            .collect();
+
+        for func in self.index.functions() {
+            let addr = func.addr;
+            if self.is_code(addr) && self.is_misaligned(addr) {
+                misaligned.push(addr);
+            }
+        }
+
+        misaligned.sort_unstable();
+        misaligned.dedup();
+        misaligned
+    }
+
+    /// Raw bytes of the binary as they're stored on disk.
+    pub fn file_bytes(&self) -> &[u8] {
+        &self.mmap[..]
+    }
+
+    /// Offset into [`Processor::file_bytes`] where `addr` is stored.
+    ///
+    /// None if the address isn't backed by the file, e.g. `.bss` or compressed sections.
+    pub fn file_offset(&self, addr: PhysAddr) -> Option<usize> {
+        let section = self.section_by_addr(addr)?;
+        let file = self.file_bytes().as_ptr_range();
+        let bytes = section.bytes().as_ptr_range();
+
+        if bytes.start < file.start || bytes.end > file.end {
+            return None;
+        }
+
+        let offset = bytes.start as usize - file.start as usize + (addr - section.start);
+        (offset < bytes.end as usize - file.start as usize).then_some(offset)
     }
 
     pub fn segments(&self) -> impl DoubleEndedIterator<Item = &Segment> {
@@ -488,20 +1627,9 @@ impl Processor {
 impl Drop for Processor {
     /// Required `Drop` impl as [`Instruction`]'s a non-copy union.
     fn drop(&mut self) {
-        for Addressed { item: inst, .. } in self.instructions.iter_mut() {
-            match self.arch {
-                Architecture::X86_64 => unsafe { ManuallyDrop::drop(&mut inst.x64) },
-                Architecture::X86_64_X32 | Architecture::I386 => unsafe {
-                    ManuallyDrop::drop(&mut inst.x86)
-                },
-                Architecture::Riscv64 | Architecture::Riscv32 => unsafe {
-                    ManuallyDrop::drop(&mut inst.riscv)
-                },
-                Architecture::Mips | Architecture::Mips64 => unsafe {
-                    ManuallyDrop::drop(&mut inst.mips)
-                },
-                _ => {}
-            }
+        let code = self.code.get_mut().unwrap();
+        for Addressed { item: inst, .. } in code.instructions.iter_mut() {
+            drop_instruction(&self.isa, inst);
         }
     }
 }
@@ -511,3 +1639,120 @@ impl From<object::Error> for Error {
         Error::Object(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splitting() {
+        assert_eq!(split_points(0x1000, 0x2000, 0x400, 4), [0x1400, 0x1800, 0x1c00]);
+        assert_eq!(split_points(0x1000, 0x1400, 0x400, 4), []);
+
+        // Splits are kept at addresses instructions can start at.
+        assert_eq!(split_points(0x1000, 0x1010, 6, 4), [0x1008]);
+    }
+
+    #[test]
+    fn split_decoding() {
+        // push rbp; mov rbp, rsp; pop rbp; ret, twice.
+        static BYTES: [u8; 12] = [
+            0x55, 0x48, 0x89, 0xe5, 0x5d, 0xc3, 0x55, 0x48, 0x89, 0xe5, 0x5d, 0xc3,
+        ];
+        let section = Section::new(
+            ".text".to_string(),
+            "__TEXT",
+            SectionKind::Code,
+            &BYTES,
+            0x1000,
+            0x1000 + BYTES.len(),
+        );
+
+        let addrs = |(instructions, ..): DecodedRange| -> Vec<PhysAddr> {
+            instructions.into_iter().map(|inst| inst.addr).collect()
+        };
+
+        let isa = Isa::Native(Architecture::X86_64);
+        let modes = Modes::default();
+        let width = |inst: &Instruction| unsafe { inst.x64.width() };
+        let loading = Loading::default();
+        let decode_split = |splits: &[PhysAddr]| {
+            addrs(decode_section(&isa, &modes, &section, splits, width, &loading).unwrap())
+        };
+
+        let whole = decode_split(&[]);
+        assert_eq!(whole, [0x1000, 0x1001, 0x1004, 0x1005, 0x1006, 0x1007, 0x100a, 0x100b]);
+        assert_eq!(decode_split(&[0x1006]), whole);
+
+        // The split at 0x1002 is in the middle of `mov rbp, rsp`.
+        assert_eq!(decode_split(&[0x1002, 0x1008]), whole);
+
+        loading.cancel();
+        assert!(decode_section(&isa, &modes, &section, &[0x1006], width, &loading).is_err());
+    }
+
+    #[test]
+    fn split_jump_table() {
+        // cmp edi, 5; ja; mov edi, edi; lea rax, [rip + 0xff2]; movsxd rdx, [rax + rdi * 4];
+        // add rdx, rax; jmp rdx
+        static BYTES: [u8; 23] = [
+            0x83, 0xff, 0x05, 0x77, 0x20, 0x89, 0xff, 0x48, 0x8d, 0x05, 0xf2, 0x0f, 0x00, 0x00,
+            0x48, 0x63, 0x14, 0xb8, 0x48, 0x01, 0xc2, 0xff, 0xe2,
+        ];
+        let section = Section::new(
+            ".text".to_string(),
+            "__TEXT",
+            SectionKind::Code,
+            &BYTES,
+            0x1000,
+            0x1000 + BYTES.len(),
+        );
+
+        let isa = Isa::Native(Architecture::X86_64);
+        let modes = Modes::default();
+        let width = |inst: &Instruction| unsafe { inst.x64.width() };
+        let loading = Loading::default();
+        let decode_split = |splits: &[PhysAddr]| -> (Vec<PhysAddr>, Vec<PhysAddr>) {
+            let (instructions, _, _, tables) =
+                decode_section(&isa, &modes, &section, splits, width, &loading).unwrap();
+            let addrs = instructions.into_iter().map(|inst| inst.addr).collect();
+            (addrs, tables.into_iter().map(|table| table.addr).collect())
+        };
+
+        let (whole, tables) = decode_split(&[]);
+        assert_eq!(tables, [0x1015]);
+
+        // The split at 0x1010 is in the middle of `movsxd rdx, [rax + rdi * 4]`.
+        assert_eq!(decode_split(&[0x1010]), (whole, tables));
+    }
+
+    #[test]
+    fn redecoding_realigns() {
+        // mov eax, 1; ret; nop
+        let name = format!("bite_redecoding_realigns_{}.bin", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, [0xb8, 0x01, 0x00, 0x00, 0x00, 0xc3, 0x90]).unwrap();
+
+        let options = RawOptions {
+            arch: Architecture::X86_64,
+            endianness: Endianness::Little,
+            base: 0x1000,
+        };
+        let processor = Processor::parse_raw(&path, &options).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let addrs = || -> Vec<PhysAddr> {
+            let code = processor.code.read().unwrap();
+            code.instructions.iter().map(|inst| inst.addr).collect()
+        };
+        assert_eq!(addrs(), [0x1000, 0x1005, 0x1006]);
+
+        // The instruction continuing past the end is still decoded as a whole.
+        assert_eq!(processor.reanalyze_range(0x1000, 0x1002), 1);
+        assert_eq!(addrs(), [0x1000, 0x1005, 0x1006]);
+
+        // add [rax], eax; add [rax], al, up to where `ret` was decoded before.
+        assert_eq!(processor.reanalyze_range(0x1001, 0x1002), 2);
+        assert_eq!(addrs(), [0x1000, 0x1001, 0x1003, 0x1005, 0x1006]);
+    }
+}
@@ -0,0 +1,433 @@
+//! Shared libraries a binary imports, found the way the loader of it's platform would find them.
+
+use crate::Processor;
+use binformat::{elf, macho, pe, Dependencies};
+use object::Object;
+use processor_shared::PhysAddr;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Where the loader on Linux reads the directories it searches besides the default ones.
+const LD_SO_CONF: &str = "/etc/ld.so.conf";
+
+/// Directories the loader on Linux searches last.
+const DEFAULT_DIRS: &[&str] = &["/lib64", "/usr/lib64", "/lib", "/usr/lib"];
+
+/// How deep the `include` directives of `/etc/ld.so.conf` are followed.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Bytes of an ELF header up to and including `e_machine`.
+const ELF_IDENT_LEN: usize = 20;
+
+/// A shared library imported by a binary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    /// Name as the binary imports it, e.g. `libc.so.6` or `@rpath/libfoo.dylib`.
+    pub name: String,
+    /// Where it was found, None if it isn't in any of the directories searched.
+    pub path: Option<PathBuf>,
+}
+
+/// A function a binary imports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Name the library exports it by.
+    pub name: String,
+    /// Library it's imported from, which ELF's don't record.
+    pub module: Option<String>,
+}
+
+impl Library {
+    /// Whether this is the library an import's `module` refers to, which is named without it's
+    /// directory and for PE's without it's extension.
+    pub fn is_module(&self, module: &str) -> bool {
+        let path = Path::new(&self.name);
+        let matches = |name: Option<&std::ffi::OsStr>| {
+            name.and_then(|name| name.to_str())
+                .is_some_and(|name| name.eq_ignore_ascii_case(module))
+        };
+
+        matches(path.file_name()) || matches(path.file_stem())
+    }
+
+    /// Whether the library exports `name`, read without analyzing the library.
+    pub fn exports(&self, name: &str) -> bool {
+        let bytes = match self.path.as_ref().map(std::fs::read) {
+            Some(Ok(bytes)) => bytes,
+            _ => return false,
+        };
+
+        match object::File::parse(&bytes[..]).and_then(|obj| obj.exports()) {
+            Ok(exports) => exports.iter().any(|export| export.name() == name.as_bytes()),
+            Err(..) => false,
+        }
+    }
+}
+
+/// Replace `$ORIGIN` in a directory of `DT_RPATH` or `DT_RUNPATH` with the binary's directory.
+fn expand_origin(dir: &str, origin: &Path) -> PathBuf {
+    let origin = origin.to_string_lossy();
+    PathBuf::from(dir.replace("${ORIGIN}", &origin).replace("$ORIGIN", &origin))
+}
+
+/// Replace the `@loader_path` and `@executable_path` prefixes of a Mach-O's dylib or rpath,
+/// which are the same for libraries the main binary imports.
+fn expand_loader_path(path: &str, origin: &Path) -> PathBuf {
+    for prefix in ["@loader_path", "@executable_path"] {
+        if let Some(rest) = path.strip_prefix(prefix) {
+            return origin.join(rest.trim_start_matches('/'));
+        }
+    }
+
+    PathBuf::from(path)
+}
+
+/// Directories listed in the contents of `/etc/ld.so.conf` and the patterns of the files it
+/// includes, comments being ignored.
+fn parse_ld_so_conf(conf: &str) -> (Vec<PathBuf>, Vec<String>) {
+    let mut dirs = Vec::new();
+    let mut includes = Vec::new();
+
+    for line in conf.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if let Some(pattern) = line.strip_prefix("include") {
+            if pattern.starts_with(char::is_whitespace) {
+                includes.extend(pattern.split_whitespace().map(str::to_string));
+                continue;
+            }
+        }
+
+        dirs.extend(line.split([':', ',', ' ', '\t']).filter(|s| !s.is_empty()).map(PathBuf::from));
+    }
+
+    (dirs, includes)
+}
+
+/// Whether `name` matches a pattern with at most one `*`, as the includes of `/etc/ld.so.conf`
+/// are.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
+
+/// Directories listed in the configuration file at `path` and the files it includes.
+fn ld_so_conf_dirs(path: &Path, depth: usize, dirs: &mut Vec<PathBuf>) {
+    let conf = match std::fs::read_to_string(path) {
+        Ok(conf) => conf,
+        Err(..) => return,
+    };
+
+    let (listed, includes) = parse_ld_so_conf(&conf);
+    dirs.extend(listed);
+
+    if depth == MAX_INCLUDE_DEPTH {
+        return;
+    }
+
+    let conf_dir = path.parent().unwrap_or(Path::new("/"));
+    for include in includes {
+        // Relative includes are relative to the file including them.
+        let include = conf_dir.join(include);
+        let (dir, pattern) = match (include.parent(), include.file_name()) {
+            (Some(dir), Some(pattern)) => (dir, pattern.to_string_lossy()),
+            _ => continue,
+        };
+
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .flatten()
+                .filter(|entry| matches_pattern(&pattern, &entry.file_name().to_string_lossy()))
+                .map(|entry| entry.path())
+                .collect(),
+            Err(..) => continue,
+        };
+
+        paths.sort();
+        for path in paths {
+            ld_so_conf_dirs(&path, depth + 1, dirs);
+        }
+    }
+}
+
+/// Whether ELF headers starting with `ident` and `other` are of the same class, endianness and
+/// machine, as the loader skips libraries that aren't.
+fn elf_compatible(ident: &[u8], other: &[u8]) -> bool {
+    ident.len() >= ELF_IDENT_LEN
+        && other.len() >= ELF_IDENT_LEN
+        && ident[4..6] == other[4..6]
+        && ident[18..20] == other[18..20]
+}
+
+/// First of `dirs` containing a file named `name`, ignoring the case of the name if the binary
+/// comes from a platform that does. `accept` is asked whether the file is usable.
+fn find_in<'a>(
+    dirs: impl IntoIterator<Item = &'a PathBuf>,
+    name: &str,
+    ignore_case: bool,
+    accept: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    for dir in dirs {
+        let path = dir.join(name);
+        if path.is_file() && accept(&path) {
+            return Some(path);
+        }
+
+        if !ignore_case {
+            continue;
+        }
+
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(..) => continue,
+        };
+
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().eq_ignore_ascii_case(name)
+                && entry.path().is_file()
+                && accept(&entry.path())
+            {
+                return Some(entry.path());
+            }
+        }
+    }
+
+    None
+}
+
+/// Directories listed in the environment variable `var`.
+fn env_dirs(var: &str) -> Vec<PathBuf> {
+    match std::env::var_os(var) {
+        Some(paths) => {
+            std::env::split_paths(&paths).filter(|dir| !dir.as_os_str().is_empty()).collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+impl Processor {
+    /// Shared libraries the binary imports in the order they're loaded, together with where
+    /// they were found.
+    pub fn libraries(&self) -> Vec<Library> {
+        let origin = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        match object::File::parse(self.file_bytes()) {
+            Ok(object::File::Elf32(elf)) => {
+                self.elf_libraries(elf::dependencies(&elf).unwrap_or_default(), &origin)
+            }
+            Ok(object::File::Elf64(elf)) => {
+                self.elf_libraries(elf::dependencies(&elf).unwrap_or_default(), &origin)
+            }
+            Ok(object::File::MachO32(macho)) => {
+                macho_libraries(macho::dependencies(&macho).unwrap_or_default(), &origin)
+            }
+            Ok(object::File::MachO64(macho)) => {
+                macho_libraries(macho::dependencies(&macho).unwrap_or_default(), &origin)
+            }
+            Ok(object::File::Pe32(pe)) => {
+                pe_libraries(pe::import_names(&pe).unwrap_or_default(), &origin)
+            }
+            Ok(object::File::Pe64(pe)) => {
+                pe_libraries(pe::import_names(&pe).unwrap_or_default(), &origin)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Search the directories of `deps` first, followed by those of `LD_LIBRARY_PATH` and the
+    /// system's.
+    fn elf_libraries(&self, deps: Dependencies, origin: &Path) -> Vec<Library> {
+        let rpaths = deps.rpaths.iter().map(|dir| expand_origin(dir, origin));
+        let runpaths = deps.runpaths.iter().map(|dir| expand_origin(dir, origin));
+
+        // DT_RPATH is ignored if there's a DT_RUNPATH.
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        if deps.runpaths.is_empty() {
+            dirs.extend(rpaths);
+        }
+        dirs.extend(env_dirs("LD_LIBRARY_PATH"));
+        dirs.extend(runpaths);
+        ld_so_conf_dirs(Path::new(LD_SO_CONF), 0, &mut dirs);
+        dirs.extend(DEFAULT_DIRS.iter().map(PathBuf::from));
+
+        let ident = self.file_bytes();
+        let compatible = |path: &Path| {
+            let mut header = [0; ELF_IDENT_LEN];
+            let read = File::open(path).and_then(|mut file| file.read_exact(&mut header));
+            read.is_ok() && elf_compatible(&header, ident)
+        };
+
+        deps.libraries
+            .into_iter()
+            .map(|name| {
+                let path = if name.contains('/') {
+                    Some(PathBuf::from(&name)).filter(|path| path.is_file())
+                } else {
+                    find_in(&dirs, &name, false, compatible)
+                };
+
+                Library { name, path }
+            })
+            .collect()
+    }
+
+    /// Address of the function or variable the binary exports by `name`.
+    pub fn export_by_name(&self, name: &str) -> Option<PhysAddr> {
+        self.exports.iter().find(|export| export.item == name).map(|export| export.addr)
+    }
+
+    /// Function imported through the symbol at `addr`, or through the symbol the instruction at
+    /// `addr` refers to, e.g. a call to a PLT stub.
+    pub fn import_at(&self, addr: PhysAddr) -> Option<Import> {
+        let imported = |addr: PhysAddr| {
+            self.index
+                .get_sym_by_addr(addr)
+                .filter(|symbol| symbol.imported() || symbol.indirection().is_some())
+        };
+
+        let symbol = imported(addr)
+            .or_else(|| self.xrefs_from(addr).into_iter().find_map(|xref| imported(xref.to)))?;
+
+        Some(Import {
+            name: symbol.raw_name(),
+            module: symbol.module().map(str::to_string),
+        })
+    }
+}
+
+/// Expand the `@rpath` of dylibs using the binary's `LC_RPATH` entries, searching
+/// `DYLD_LIBRARY_PATH` by the dylib's file name first.
+fn macho_libraries(deps: Dependencies, origin: &Path) -> Vec<Library> {
+    let rpaths: Vec<PathBuf> =
+        deps.rpaths.iter().map(|rpath| expand_loader_path(rpath, origin)).collect();
+    let dyld_dirs = env_dirs("DYLD_LIBRARY_PATH");
+
+    deps.libraries
+        .into_iter()
+        .map(|name| {
+            let file_name = name.rsplit('/').next().unwrap_or(&name);
+            let path = find_in(&dyld_dirs, file_name, false, |_| true).or_else(|| {
+                match name.strip_prefix("@rpath/") {
+                    Some(rest) => find_in(&rpaths, rest, false, |_| true),
+                    None => Some(expand_loader_path(&name, origin)).filter(|path| path.is_file()),
+                }
+            });
+
+            Library { name, path }
+        })
+        .collect()
+}
+
+/// Search the binary's directory followed by the system's and those of `PATH`, ignoring the
+/// case of the names as Windows does.
+fn pe_libraries(imports: Vec<(String, String)>, origin: &Path) -> Vec<Library> {
+    let mut dirs = vec![origin.to_path_buf()];
+    if let Some(root) = std::env::var_os("SystemRoot") {
+        let root = PathBuf::from(root);
+        dirs.push(root.join("System32"));
+        dirs.push(root);
+    }
+    dirs.extend(env_dirs("PATH"));
+
+    let mut modules: Vec<String> = Vec::new();
+    for (module, _) in imports {
+        if !modules.iter().any(|seen| seen.eq_ignore_ascii_case(&module)) {
+            modules.push(module);
+        }
+    }
+
+    modules
+        .into_iter()
+        .map(|name| {
+            let path = find_in(&dirs, &name, true, |_| true);
+            Library { name, path }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn origin() {
+        let origin = Path::new("/opt/app/bin");
+        assert_eq!(expand_origin("$ORIGIN/../lib", origin), Path::new("/opt/app/bin/../lib"));
+        assert_eq!(expand_origin("${ORIGIN}", origin), Path::new("/opt/app/bin"));
+        assert_eq!(expand_origin("/usr/local/lib", origin), Path::new("/usr/local/lib"));
+
+        assert_eq!(
+            expand_loader_path("@loader_path/../Frameworks", origin),
+            Path::new("/opt/app/bin/../Frameworks")
+        );
+        assert_eq!(
+            expand_loader_path("@executable_path/libfoo.dylib", origin),
+            Path::new("/opt/app/bin/libfoo.dylib")
+        );
+        assert_eq!(
+            expand_loader_path("/usr/lib/libSystem.B.dylib", origin),
+            Path::new("/usr/lib/libSystem.B.dylib")
+        );
+    }
+
+    #[test]
+    fn ld_so_conf() {
+        let conf = "# libc default configuration\n\
+                    include /etc/ld.so.conf.d/*.conf\n\
+                    /usr/local/lib # added by hand\n\
+                    /opt/a:/opt/b\n\
+                    includes/lib\n";
+
+        let (dirs, includes) = parse_ld_so_conf(conf);
+        assert_eq!(dirs, ["/usr/local/lib", "/opt/a", "/opt/b", "includes/lib"].map(PathBuf::from));
+        assert_eq!(includes, ["/etc/ld.so.conf.d/*.conf"]);
+
+        assert!(matches_pattern("*.conf", "x86_64-linux-gnu.conf"));
+        assert!(matches_pattern("libc.conf", "libc.conf"));
+        assert!(!matches_pattern("*.conf", "README"));
+        assert!(!matches_pattern("a*a", "a"));
+    }
+
+    #[test]
+    fn elf_compatibility() {
+        let mut x86_64 = [0; ELF_IDENT_LEN];
+        x86_64[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        x86_64[18] = 0x3e;
+
+        let mut i386 = x86_64;
+        i386[4] = 1;
+        i386[18] = 0x03;
+
+        assert!(elf_compatible(&x86_64, &x86_64));
+        assert!(!elf_compatible(&x86_64, &i386));
+        assert!(!elf_compatible(&x86_64, &x86_64[..4]));
+    }
+
+    #[test]
+    fn modules() {
+        let library = Library {
+            name: "KERNEL32.dll".to_string(),
+            path: None,
+        };
+
+        assert!(library.is_module("kernel32"));
+        assert!(library.is_module("KERNEL32.dll"));
+        assert!(!library.is_module("user32"));
+
+        let library = Library {
+            name: "/usr/lib/libSystem.B.dylib".to_string(),
+            path: None,
+        };
+
+        assert!(library.is_module("libSystem.B.dylib"));
+    }
+}
@@ -0,0 +1,163 @@
+//! Stages binaries are loaded in, reported to whoever is waiting on them.
+
+use crate::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Number of loads started, used to tell them apart.
+static LOADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Steps of loading a binary, in the order they're run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Parsing the object's sections, segments and exports.
+    Headers,
+    /// Parsing symbols and debug info.
+    Symbols,
+    /// Linear sweep over the code sections.
+    Decoding,
+    /// Naming strings and library functions and resolving jump tables.
+    Analysis,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Headers, Stage::Symbols, Stage::Decoding, Stage::Analysis];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Headers => "Parsing headers",
+            Stage::Symbols => "Parsing symbols",
+            Stage::Decoding => "Decoding instructions",
+            Stage::Analysis => "Analyzing code",
+        }
+    }
+
+    /// Position of the stage, starting at 1.
+    pub fn number(self) -> usize {
+        Stage::ALL.iter().position(|&stage| stage == self).unwrap_or(0) + 1
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress {
+    Started(Stage),
+    /// Stage finished, with how long it took and a summary of what it found.
+    Finished(Stage, Duration, String),
+}
+
+/// Handle to a binary being loaded, reporting it's progress and allowing it to be cancelled.
+#[derive(Debug, Clone)]
+pub struct Loading {
+    id: usize,
+    progress: Option<Sender<Progress>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Default for Loading {
+    /// Load that isn't reported on.
+    fn default() -> Self {
+        Self {
+            id: LOADS.fetch_add(1, Ordering::Relaxed),
+            progress: None,
+            cancelled: Arc::default(),
+        }
+    }
+}
+
+impl Loading {
+    /// Load reporting it's progress to the returned receiver.
+    pub fn new() -> (Self, Receiver<Progress>) {
+        let (sender, receiver) = mpsc::channel();
+        let loading = Self {
+            progress: Some(sender),
+            ..Self::default()
+        };
+
+        (loading, receiver)
+    }
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Stop loading at the next stage or section, making it fail with [`Error::Cancelled`].
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fail if loading was cancelled.
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
+        Ok(())
+    }
+
+    /// Start running `stage`, returning when it started.
+    pub(crate) fn start(&self, stage: Stage) -> Result<Instant, Error> {
+        self.check()?;
+        self.send(Progress::Started(stage));
+        Ok(Instant::now())
+    }
+
+    pub(crate) fn finish(&self, stage: Stage, started: Instant, summary: String) {
+        let elapsed = started.elapsed();
+
+        log::complex!(
+            w "[processor::parse] ",
+            b stage.name(),
+            w " took ",
+            y format!("{elapsed:#?}"),
+            w format!(", {summary}."),
+        );
+
+        self.send(Progress::Finished(stage, elapsed, summary));
+    }
+
+    fn send(&self, progress: Progress) {
+        // Whoever was waiting might have moved on to another binary.
+        if let Some(ref sender) = self.progress {
+            let _ = sender.send(progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages() {
+        let (loading, progress) = Loading::new();
+        assert_ne!(loading.id(), Loading::default().id());
+
+        let started = loading.start(Stage::Headers).unwrap();
+        loading.finish(Stage::Headers, started, "3 sections".to_string());
+
+        assert_eq!(progress.try_recv(), Ok(Progress::Started(Stage::Headers)));
+        match progress.try_recv() {
+            Ok(Progress::Finished(Stage::Headers, _, summary)) => assert_eq!(summary, "3 sections"),
+            progress => panic!("expected the headers to finish, got {progress:?}"),
+        }
+
+        assert_eq!(Stage::Headers.number(), 1);
+        assert_eq!(Stage::Analysis.number(), Stage::ALL.len());
+    }
+
+    #[test]
+    fn cancel() {
+        let (loading, progress) = Loading::new();
+        loading.clone().cancel();
+
+        assert!(loading.is_cancelled());
+        assert!(matches!(loading.start(Stage::Symbols), Err(Error::Cancelled)));
+        assert!(progress.try_recv().is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! Decoding of LZMA, the LZMA2 chunks wrapping it and the xz files holding those, as found in
+//! compressed firmware and binaries packed with UPX.
+
+/// Bits of precision of the probabilities.
+const PROB_BITS: u32 = 11;
+
+/// Probability of either bit before anything has been decoded.
+const PROB_INIT: u16 = 1 << (PROB_BITS - 1);
+
+/// Shortest match, which is what a length of zero encodes.
+const MIN_MATCH: usize = 2;
+
+/// Position slots from which distances are coded with fixed probabilities and an aligned tail.
+const END_POS_MODEL: usize = 14;
+
+/// Distances below which the position slot's extra bits all have their own probability.
+const FULL_DISTANCES: usize = 128;
+
+/// Number of states the decoder tracks about the kinds of packets decoded last.
+const STATES: usize = 12;
+
+/// Magic of an xz stream.
+const XZ_MAGIC: &[u8] = b"\xfd7zXZ\0";
+
+/// Filter id of LZMA2 in an xz block header.
+const XZ_LZMA2: u64 = 0x21;
+
+/// Literal context bits, literal position bits and position bits a stream is coded with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Properties {
+    pub lc: u32,
+    pub lp: u32,
+    pub pb: u32,
+}
+
+impl Properties {
+    /// Properties as packed into a byte by LZMA and LZMA2 headers, e.g. `0x5d`.
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        if byte >= 9 * 5 * 5 {
+            return None;
+        }
+
+        let byte = byte as u32;
+        Some(Self {
+            lc: byte % 9,
+            lp: (byte / 9) % 5,
+            pb: byte / 45,
+        })
+    }
+}
+
+struct RangeDecoder<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    range: u32,
+    code: u32,
+}
+
+impl<'a> RangeDecoder<'a> {
+    fn new(bytes: &'a [u8]) -> Option<Self> {
+        // The first byte is always zero, as the encoder's cache starts out empty.
+        if *bytes.first()? != 0 {
+            return None;
+        }
+
+        let code = u32::from_be_bytes(bytes.get(1..5)?.try_into().ok()?);
+        Some(Self {
+            bytes,
+            offset: 5,
+            range: u32::MAX,
+            code,
+        })
+    }
+
+    fn normalize(&mut self) -> Option<()> {
+        if self.range < 1 << 24 {
+            self.range <<= 8;
+            self.code = (self.code << 8) | *self.bytes.get(self.offset)? as u32;
+            self.offset += 1;
+        }
+
+        Some(())
+    }
+
+    fn bit(&mut self, prob: &mut u16) -> Option<u32> {
+        let bound = (self.range >> PROB_BITS) * *prob as u32;
+        let bit = if self.code < bound {
+            *prob += ((1 << PROB_BITS) - *prob) >> 5;
+            self.range = bound;
+            0
+        } else {
+            *prob -= *prob >> 5;
+            self.code -= bound;
+            self.range -= bound;
+            1
+        };
+
+        self.normalize()?;
+        Some(bit)
+    }
+
+    /// Bits coded with a fixed probability of one half, most significant first.
+    fn direct_bits(&mut self, count: u32) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..count {
+            self.range >>= 1;
+            let bit = (self.code >= self.range) as u32;
+            if bit == 1 {
+                self.code -= self.range;
+            }
+            value = (value << 1) | bit;
+            self.normalize()?;
+        }
+
+        Some(value)
+    }
+
+    /// Value of `count` bits decoded through the tree of probabilities `probs`, which holds
+    /// `1 << count` probabilities.
+    fn tree(&mut self, probs: &mut [u16], count: u32) -> Option<u32> {
+        let mut idx = 1;
+        for _ in 0..count {
+            idx = (idx << 1) | self.bit(&mut probs[idx as usize])?;
+        }
+
+        Some(idx - (1 << count))
+    }
+
+    /// Like [`RangeDecoder::tree`], but with the least significant bit decoded first.
+    fn reverse_tree(&mut self, probs: &mut [u16], count: u32) -> Option<u32> {
+        let mut idx = 1;
+        let mut value = 0;
+        for bit_idx in 0..count {
+            let bit = self.bit(&mut probs[idx as usize])?;
+            idx = (idx << 1) | bit;
+            value |= bit << bit_idx;
+        }
+
+        Some(value)
+    }
+}
+
+/// Probabilities of the lengths of matches.
+struct LenDecoder {
+    choice: u16,
+    choice2: u16,
+    low: Vec<[u16; 1 << 3]>,
+    mid: Vec<[u16; 1 << 3]>,
+    high: [u16; 1 << 8],
+}
+
+impl LenDecoder {
+    fn new() -> Self {
+        Self {
+            choice: PROB_INIT,
+            choice2: PROB_INIT,
+            low: vec![[PROB_INIT; 1 << 3]; 1 << 4],
+            mid: vec![[PROB_INIT; 1 << 3]; 1 << 4],
+            high: [PROB_INIT; 1 << 8],
+        }
+    }
+
+    fn decode(&mut self, rc: &mut RangeDecoder, pos_state: usize) -> Option<usize> {
+        if rc.bit(&mut self.choice)? == 0 {
+            return Some(rc.tree(&mut self.low[pos_state], 3)? as usize);
+        }
+
+        if rc.bit(&mut self.choice2)? == 0 {
+            return Some(8 + rc.tree(&mut self.mid[pos_state], 3)? as usize);
+        }
+
+        Some(16 + rc.tree(&mut self.high, 8)? as usize)
+    }
+}
+
+/// State of an LZMA decoder, which carries over between the chunks of an LZMA2 stream.
+pub(crate) struct Decoder {
+    props: Properties,
+    literals: Vec<u16>,
+    is_match: [u16; STATES << 4],
+    is_rep: [u16; STATES],
+    is_rep_g0: [u16; STATES],
+    is_rep_g1: [u16; STATES],
+    is_rep_g2: [u16; STATES],
+    is_rep0_long: [u16; STATES << 4],
+    pos_slots: [[u16; 1 << 6]; 4],
+    pos: [u16; 1 + FULL_DISTANCES - END_POS_MODEL],
+    align: [u16; 1 << 4],
+    len: LenDecoder,
+    rep_len: LenDecoder,
+    state: usize,
+    reps: [usize; 4],
+    /// Offset into the output where the dictionary starts, matches can't reach before it.
+    dict_start: usize,
+}
+
+impl Decoder {
+    pub fn new(props: Properties) -> Self {
+        Self {
+            props,
+            literals: vec![PROB_INIT; 0x300 << (props.lc + props.lp)],
+            is_match: [PROB_INIT; STATES << 4],
+            is_rep: [PROB_INIT; STATES],
+            is_rep_g0: [PROB_INIT; STATES],
+            is_rep_g1: [PROB_INIT; STATES],
+            is_rep_g2: [PROB_INIT; STATES],
+            is_rep0_long: [PROB_INIT; STATES << 4],
+            pos_slots: [[PROB_INIT; 1 << 6]; 4],
+            pos: [PROB_INIT; 1 + FULL_DISTANCES - END_POS_MODEL],
+            align: [PROB_INIT; 1 << 4],
+            len: LenDecoder::new(),
+            rep_len: LenDecoder::new(),
+            state: 0,
+            reps: [0; 4],
+            dict_start: 0,
+        }
+    }
+
+    fn literal(&mut self, rc: &mut RangeDecoder, out: &mut Vec<u8>) -> Option<()> {
+        let Properties { lc, lp, .. } = self.props;
+        let prev = match out.len() > self.dict_start {
+            true => out[out.len() - 1] as usize,
+            false => 0,
+        };
+
+        let pos = out.len() - self.dict_start;
+        let lit_state = ((pos & ((1 << lp) - 1)) << lc) + (prev >> (8 - lc));
+        let probs = &mut self.literals[0x300 * lit_state..0x300 * (lit_state + 1)];
+
+        let mut symbol = 1;
+        if self.state >= 7 {
+            // After a match, the byte following the match's source predicts the literal.
+            let mut match_byte = out[out.len() - self.reps[0] - 1] as usize;
+            while symbol < 0x100 {
+                let match_bit = (match_byte >> 7) & 1;
+                match_byte <<= 1;
+                let bit = rc.bit(&mut probs[((1 + match_bit) << 8) + symbol])? as usize;
+                symbol = (symbol << 1) | bit;
+                if match_bit != bit {
+                    break;
+                }
+            }
+        }
+
+        while symbol < 0x100 {
+            symbol = (symbol << 1) | rc.bit(&mut probs[symbol])? as usize;
+        }
+
+        out.push((symbol - 0x100) as u8);
+        self.state = match self.state {
+            0..=3 => 0,
+            4..=9 => self.state - 3,
+            _ => self.state - 6,
+        };
+
+        Some(())
+    }
+
+    /// Distance of a match of `len`, u32::MAX for the end marker.
+    fn distance(&mut self, rc: &mut RangeDecoder, len: usize) -> Option<usize> {
+        let slot = rc.tree(&mut self.pos_slots[len.min(3)], 6)? as usize;
+        if slot < 4 {
+            return Some(slot);
+        }
+
+        let direct = (slot as u32 >> 1) - 1;
+        let mut dist = (2 | (slot & 1)) << direct;
+        if slot < END_POS_MODEL {
+            dist += rc.reverse_tree(&mut self.pos[dist - slot..], direct)? as usize;
+        } else {
+            dist += (rc.direct_bits(direct - 4)? as usize) << 4;
+            dist += rc.reverse_tree(&mut self.align, 4)? as usize;
+        }
+
+        Some(dist)
+    }
+
+    /// Decode `bytes` onto `out` until it holds `end` bytes, or until the end marker if the
+    /// length isn't known. Returns the number of bytes of input used.
+    pub fn decode(
+        &mut self,
+        bytes: &[u8],
+        out: &mut Vec<u8>,
+        end: Option<usize>,
+    ) -> Option<usize> {
+        let mut rc = RangeDecoder::new(bytes)?;
+        let pb_mask = (1 << self.props.pb) - 1;
+
+        while end.is_none_or(|end| out.len() < end) {
+            let pos_state = (out.len() - self.dict_start) & pb_mask;
+            let state = self.state;
+
+            if rc.bit(&mut self.is_match[(state << 4) + pos_state])? == 0 {
+                self.literal(&mut rc, out)?;
+                continue;
+            }
+
+            let len = if rc.bit(&mut self.is_rep[state])? == 1 {
+                if out.len() == self.dict_start {
+                    return None;
+                }
+
+                if rc.bit(&mut self.is_rep_g0[state])? == 0 {
+                    if rc.bit(&mut self.is_rep0_long[(state << 4) + pos_state])? == 0 {
+                        self.state = if state < 7 { 9 } else { 11 };
+                        out.push(out[out.len() - self.reps[0] - 1]);
+                        continue;
+                    }
+                } else {
+                    let dist = if rc.bit(&mut self.is_rep_g1[state])? == 0 {
+                        self.reps[1]
+                    } else if rc.bit(&mut self.is_rep_g2[state])? == 0 {
+                        let dist = self.reps[2];
+                        self.reps[2] = self.reps[1];
+                        dist
+                    } else {
+                        let dist = self.reps[3];
+                        self.reps[3] = self.reps[2];
+                        self.reps[2] = self.reps[1];
+                        dist
+                    };
+
+                    self.reps[1] = self.reps[0];
+                    self.reps[0] = dist;
+                }
+
+                self.state = if state < 7 { 8 } else { 11 };
+                self.rep_len.decode(&mut rc, pos_state)?
+            } else {
+                self.reps = [0, self.reps[0], self.reps[1], self.reps[2]];
+                let len = self.len.decode(&mut rc, pos_state)?;
+                self.state = if state < 7 { 7 } else { 10 };
+                self.reps[0] = self.distance(&mut rc, len)?;
+
+                if self.reps[0] == u32::MAX as usize {
+                    break;
+                }
+
+                len
+            };
+
+            let dist = self.reps[0] + 1;
+            if dist > out.len() - self.dict_start {
+                return None;
+            }
+
+            let count = match end {
+                Some(end) => (len + MIN_MATCH).min(end - out.len()),
+                None => len + MIN_MATCH,
+            };
+
+            for _ in 0..count {
+                out.push(out[out.len() - dist]);
+            }
+        }
+
+        Some(rc.offset)
+    }
+}
+
+/// LZMA stream `bytes` coded with `props`, decoded up to `len` bytes or to the end marker.
+pub(crate) fn decode(props: Properties, bytes: &[u8], len: Option<usize>) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(len.unwrap_or(bytes.len() * 4));
+    Decoder::new(props).decode(bytes, &mut out, len)?;
+    Some(out)
+}
+
+/// LZMA2 chunks of `bytes` decoded onto `out`, returns the number of bytes of input used.
+fn decode_lzma2(bytes: &[u8], out: &mut Vec<u8>) -> Option<usize> {
+    let mut offset = 0;
+    let mut decoder: Option<Decoder> = None;
+
+    loop {
+        let control = *bytes.get(offset)?;
+        offset += 1;
+
+        match control {
+            0x00 => return Some(offset),
+            // Chunks stored as is, which reset the dictionary if the control is 1.
+            0x01 | 0x02 => {
+                let size = u16::from_be_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?);
+                let size = size as usize + 1;
+                offset += 2;
+
+                if control == 0x01 {
+                    if let Some(ref mut decoder) = decoder {
+                        decoder.dict_start = out.len();
+                    }
+                }
+
+                out.extend_from_slice(bytes.get(offset..offset + size)?);
+                offset += size;
+            }
+            0x80.. => {
+                let header = bytes.get(offset..offset + 4)?;
+                let unpacked = ((control as usize & 0x1f) << 16)
+                    + u16::from_be_bytes([header[0], header[1]]) as usize
+                    + 1;
+                let packed = u16::from_be_bytes([header[2], header[3]]) as usize + 1;
+                offset += 4;
+
+                // Resets of the state, of the properties and of the dictionary.
+                let reset = (control >> 5) & 3;
+                if reset >= 2 {
+                    let props = Properties::from_byte(*bytes.get(offset)?)?;
+                    offset += 1;
+
+                    let dict_start = match decoder {
+                        Some(ref decoder) if reset == 2 => decoder.dict_start,
+                        _ => out.len(),
+                    };
+
+                    let mut new = Decoder::new(props);
+                    new.dict_start = dict_start;
+                    decoder = Some(new);
+                } else if reset == 1 {
+                    let old = decoder.as_ref()?;
+                    let mut new = Decoder::new(old.props);
+                    new.dict_start = old.dict_start;
+                    decoder = Some(new);
+                }
+
+                let decoder = decoder.as_mut()?;
+                let chunk = bytes.get(offset..offset + packed)?;
+                decoder.decode(chunk, out, Some(out.len() + unpacked))?;
+                offset += packed;
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Variable-length integer of an xz header, seven bits at a time.
+fn varint(bytes: &[u8], offset: &mut usize) -> Option<u64> {
+    let mut value = 0u64;
+    for shift in (0..63).step_by(7) {
+        let byte = *bytes.get(*offset)?;
+        *offset += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+
+    None
+}
+
+/// Whether `bytes` start with the magic of an xz stream.
+pub(crate) fn is_xz(bytes: &[u8]) -> bool {
+    bytes.starts_with(XZ_MAGIC)
+}
+
+/// Contents of the xz stream `bytes`, None if it's malformed or uses filters besides LZMA2.
+///
+/// Integrity checks are skipped, as a corrupt stream doesn't decode to anything meaningful anyway.
+pub(crate) fn decode_xz(bytes: &[u8]) -> Option<Vec<u8>> {
+    if !is_xz(bytes) {
+        return None;
+    }
+
+    let check = *bytes.get(7)? & 0xf;
+    let check_len = match check {
+        0 => 0,
+        _ => 4 << ((check - 1) / 3),
+    };
+
+    let mut out = Vec::new();
+    let mut offset = 12;
+    loop {
+        // An index follows the last block.
+        let header_len = *bytes.get(offset)? as usize;
+        if header_len == 0 {
+            return Some(out);
+        }
+
+        let header = bytes.get(offset..offset + (header_len + 1) * 4)?;
+        let flags = header[1];
+        let mut pos = 2;
+        if flags & 0x40 != 0 {
+            varint(header, &mut pos)?;
+        }
+        if flags & 0x80 != 0 {
+            varint(header, &mut pos)?;
+        }
+
+        // Branch/call/jump filters and delta coding would precede LZMA2.
+        if flags & 3 != 0 || varint(header, &mut pos)? != XZ_LZMA2 {
+            return None;
+        }
+
+        offset += header.len();
+        offset += decode_lzma2(bytes.get(offset..)?, &mut out)?;
+        offset = offset.next_multiple_of(4) + check_len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn properties() {
+        assert_eq!(Properties::from_byte(0x5d), Some(Properties { lc: 3, lp: 0, pb: 2 }));
+        assert_eq!(Properties::from_byte(225), None);
+    }
+
+    #[test]
+    fn lzma() {
+        // "hello hello hello hello" as compressed by liblzma, without an end marker.
+        let bytes = [0x00, 0x34, 0x19, 0x49, 0xee, 0x8d, 0xe9, 0x56, 0x0a, 0xb5, 0xe0, 0x00];
+        let props = Properties { lc: 3, lp: 0, pb: 2 };
+        assert_eq!(decode(props, &bytes, Some(23)).unwrap(), b"hello hello hello hello");
+        assert!(decode(props, &bytes[..4], Some(23)).is_none());
+    }
+
+    #[test]
+    fn xz() {
+        let bytes = [
+            0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00, 0x00, 0x01, 0x69, 0x22, 0xde, 0x36, 0x02, 0x00,
+            0x21, 0x01, 0x16, 0x00, 0x00, 0x00, 0x74, 0x2f, 0xe5, 0xa3, 0xe0, 0x00, 0x16, 0x00,
+            0x0b, 0x5d, 0x00, 0x34, 0x19, 0x49, 0xee, 0x8d, 0xe9, 0x56, 0x0a, 0xb5, 0xe0, 0x00,
+            0x00, 0x00, 0xe3, 0x51, 0x3d, 0x8d, 0x00, 0x01, 0x23, 0x17, 0x8d, 0x47, 0xfc, 0x1d,
+            0x90, 0x42, 0x99, 0x0d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x59, 0x5a,
+        ];
+
+        assert_eq!(decode_xz(&bytes).unwrap(), b"hello hello hello hello");
+        assert!(decode_xz(&bytes[..30]).is_none());
+    }
+}
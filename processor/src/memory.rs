@@ -0,0 +1,104 @@
+//! Separate memories of Harvard architectures such as AVR, where code and data each have their
+//! own addresses starting at zero. Binaries place every memory at a fixed offset in a single
+//! address space, which is what sections and references refer to, while instructions show the
+//! address within the memory they access.
+
+use crate::Processor;
+use object::Architecture;
+use processor_shared::PhysAddr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressSpace {
+    /// Flash the code is executed from.
+    Program,
+    /// SRAM, together with the registers and I/O mapped into it.
+    Data,
+    Eeprom,
+}
+
+impl AddressSpace {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Program => "flash",
+            Self::Data => "sram",
+            Self::Eeprom => "eeprom",
+        }
+    }
+}
+
+/// Addresses of a binary that are in a single memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    pub space: AddressSpace,
+    /// Start of the memory's sections, in the address space of the binary.
+    pub start: PhysAddr,
+    /// End of the memory's sections, in the address space of the binary.
+    pub end: PhysAddr,
+}
+
+/// Memory `addr` of an AVR binary is in, together with the offset it's at within it.
+fn avr_address_space(addr: PhysAddr) -> Option<(AddressSpace, PhysAddr)> {
+    // Fuses, lock bits and the signature follow at 0x820000 and up, which aren't memory.
+    const EEPROM_END: PhysAddr = 0x820000;
+
+    if addr < avr::DATA_SPACE {
+        Some((AddressSpace::Program, addr))
+    } else if addr < avr::EEPROM_SPACE {
+        Some((AddressSpace::Data, addr - avr::DATA_SPACE))
+    } else if addr < EEPROM_END {
+        Some((AddressSpace::Eeprom, addr - avr::EEPROM_SPACE))
+    } else {
+        None
+    }
+}
+
+impl Processor {
+    /// Memory `addr` is in, together with it's address within that memory. None for
+    /// architectures with a single address space.
+    pub fn address_space(&self, addr: PhysAddr) -> Option<(AddressSpace, PhysAddr)> {
+        match self.arch {
+            Architecture::Avr => avr_address_space(addr),
+            _ => None,
+        }
+    }
+
+    /// Memories the sections of the binary are in, empty for architectures with a single
+    /// address space.
+    pub fn memories(&self) -> Vec<Memory> {
+        let mut memories: Vec<Memory> = Vec::new();
+        for section in self.sections() {
+            let space = match self.address_space(section.start) {
+                Some((space, _)) => space,
+                None => continue,
+            };
+
+            match memories.iter_mut().find(|memory| memory.space == space) {
+                Some(memory) => {
+                    memory.start = memory.start.min(section.start);
+                    memory.end = memory.end.max(section.end);
+                }
+                None => memories.push(Memory {
+                    space,
+                    start: section.start,
+                    end: section.end,
+                }),
+            }
+        }
+
+        memories.sort_by_key(|memory| memory.start);
+        memories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avr_spaces() {
+        assert_eq!(avr_address_space(0x68), Some((AddressSpace::Program, 0x68)));
+        assert_eq!(avr_address_space(0x800100), Some((AddressSpace::Data, 0x100)));
+        assert_eq!(avr_address_space(0x810010), Some((AddressSpace::Eeprom, 0x10)));
+        assert_eq!(avr_address_space(0x820000), None);
+    }
+}
@@ -0,0 +1,605 @@
+//! Hashes and build information for triaging a binary, e.g. to look it up elsewhere.
+
+use crate::export::write_json_str;
+use crate::report::json_array;
+use crate::{Memory, Processor, Unpacking};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::io::{self, Write};
+
+/// Start of the build information Go embeds in binaries.
+const GO_BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+
+/// Go writes the strings inline since 1.18 instead of pointing to them.
+const GO_BUILDINFO_INLINE: u8 = 0x2;
+
+/// Panic locations in the standard library refer to it's sources by the compiler's commit.
+const RUSTC_PATH: &[u8] = b"/rustc/";
+
+/// Panic locations in dependencies refer to their sources in cargo's registry.
+const CARGO_REGISTRY_PATH: &[u8] = b"/registry/src/";
+
+/// "DanS" and "Rich" delimit the rich header, "DanS" is masked by the checksum.
+const RICH_START: u32 = 0x536e6144;
+const RICH_END: &[u8] = b"Rich";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionHash {
+    pub name: String,
+    pub md5: String,
+    pub sha256: String,
+}
+
+/// Tool the MSVC linker recorded as having contributed objects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RichEntry {
+    pub product: u16,
+    pub build: u16,
+    /// Number of objects built by this tool.
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoBuildInfo {
+    /// Compiler version, e.g. `go1.21.3`. Unknown for binaries built before Go 1.18.
+    pub version: Option<String>,
+    /// Package path of the main package.
+    pub path: Option<String>,
+    /// Main module and it's version.
+    pub module: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustInfo {
+    /// Commit of the compiler the standard library was built from.
+    pub commit: Option<String>,
+    /// Crates from cargo's registry, e.g. `serde-1.0.193`, sorted.
+    pub crates: Vec<String>,
+}
+
+/// Hashes and build information of a binary.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+    pub md5: String,
+    pub sha256: String,
+    /// MD5 of the imported functions, only for PE's.
+    pub imphash: Option<String>,
+    /// Seconds since the unix epoch the binary was linked at, only for PE's.
+    pub timestamp: Option<u32>,
+    pub rich_header: Vec<RichEntry>,
+    pub sections: Vec<SectionHash>,
+    /// Separate memories of Harvard architectures, see [`Processor::memories`].
+    pub memories: Vec<Memory>,
+    pub go: Option<GoBuildInfo>,
+    pub rust: Option<RustInfo>,
+    /// Layers of compression or packing removed before the hashes were taken, outermost first.
+    pub unpacking: Vec<Unpacking>,
+}
+
+fn md5(bytes: &[u8]) -> String {
+    format!("{:x}", Md5::digest(bytes))
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Offsets of all occurrences of `needle` in `haystack`.
+fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    haystack.windows(needle.len()).enumerate().filter_map(move |(idx, window)| {
+        (window == needle).then_some(idx)
+    })
+}
+
+/// Import hash as computed by pefile, from `(module, function)` pairs in import table order.
+fn imphash(imports: &[(String, String)]) -> Option<String> {
+    if imports.is_empty() {
+        return None;
+    }
+
+    let names: Vec<String> = imports
+        .iter()
+        .map(|(module, func)| {
+            let module = module.to_lowercase();
+            let module = match module.rsplit_once('.') {
+                Some((stem, "dll" | "ocx" | "sys")) => stem.to_string(),
+                _ => module,
+            };
+
+            format!("{module}.{}", func.to_lowercase())
+        })
+        .collect();
+
+    Some(md5(names.join(",").as_bytes()))
+}
+
+/// Entries of the rich header MSVC's linker hides between the DOS stub and the PE header.
+fn rich_header(file: &[u8]) -> Vec<RichEntry> {
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = file.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+    };
+
+    let pe_offset = match read_u32(0x3c) {
+        Some(offset) if file.starts_with(b"MZ") => offset as usize,
+        _ => return Vec::new(),
+    };
+
+    let stub = &file[..pe_offset.min(file.len())];
+    let end = match find_all(stub, RICH_END).find(|offset| offset % 4 == 0) {
+        Some(end) => end,
+        None => return Vec::new(),
+    };
+
+    let key = match read_u32(end + 4) {
+        Some(key) => key,
+        None => return Vec::new(),
+    };
+
+    let mut start = end;
+    loop {
+        start = match start.checked_sub(4) {
+            Some(start) => start,
+            None => return Vec::new(),
+        };
+
+        if read_u32(start).map(|value| value ^ key) == Some(RICH_START) {
+            break;
+        }
+    }
+
+    // "DanS" is followed by three padding values, then pairs of a tool and a count.
+    let mut entries = Vec::new();
+    let mut offset = start + 16;
+    while offset + 8 <= end {
+        let comp_id = read_u32(offset).unwrap() ^ key;
+        let count = read_u32(offset + 4).unwrap() ^ key;
+        entries.push(RichEntry {
+            product: (comp_id >> 16) as u16,
+            build: comp_id as u16,
+            count,
+        });
+        offset += 8;
+    }
+
+    entries
+}
+
+/// Read a string prefixed by it's length as a varint.
+fn varint_str(bytes: &[u8]) -> Option<(&[u8], &[u8])> {
+    let mut len = 0usize;
+    for (idx, &byte) in bytes.iter().enumerate().take(10) {
+        len |= ((byte & 0x7f) as usize) << (7 * idx);
+        if byte & 0x80 == 0 {
+            let rest = &bytes[idx + 1..];
+            return (len <= rest.len()).then(|| rest.split_at(len));
+        }
+    }
+
+    None
+}
+
+/// Build information Go embeds in binaries, such as the compiler version and main module.
+fn go_build_info(file: &[u8]) -> Option<GoBuildInfo> {
+    let offset = find_all(file, GO_BUILDINFO_MAGIC).next()?;
+    let header = file.get(offset..offset + 32)?;
+
+    if header[15] & GO_BUILDINFO_INLINE == 0 {
+        return Some(GoBuildInfo {
+            version: None,
+            path: None,
+            module: None,
+        });
+    }
+
+    let (version, rest) = varint_str(&file[offset + 32..])?;
+    let (mut modinfo, _) = varint_str(rest)?;
+
+    // The module information is wrapped in 16 byte sentinels.
+    if modinfo.len() >= 33 && modinfo[modinfo.len() - 17] == b'\n' {
+        modinfo = &modinfo[16..modinfo.len() - 16];
+    }
+
+    let modinfo = String::from_utf8_lossy(modinfo);
+
+    let mut path = None;
+    let mut module = None;
+    for line in modinfo.lines() {
+        let mut fields = line.split('\t');
+        match fields.next() {
+            Some("path") => path = fields.next().map(str::to_string),
+            Some("mod") => {
+                module = fields.next().map(|name| match fields.next() {
+                    Some(version) => format!("{name} {version}"),
+                    None => name.to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Some(GoBuildInfo {
+        version: Some(String::from_utf8_lossy(version).into_owned()),
+        path,
+        module,
+    })
+}
+
+/// Path component starting at `bytes`, if it's made of characters found in crate names.
+fn path_component(bytes: &[u8]) -> Option<&str> {
+    let len = bytes
+        .iter()
+        .position(|&b| !(b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'+')))?;
+
+    if len == 0 || !matches!(bytes[len], b'/' | b'\\') {
+        return None;
+    }
+
+    std::str::from_utf8(&bytes[..len]).ok()
+}
+
+/// Compiler commit and crates of a Rust binary, found through the paths of panic locations.
+fn rust_info(file: &[u8]) -> Option<RustInfo> {
+    let commit = find_all(file, RUSTC_PATH).find_map(|offset| {
+        let hash = file.get(offset + RUSTC_PATH.len()..offset + RUSTC_PATH.len() + 40)?;
+        let hash = std::str::from_utf8(hash).ok()?;
+        hash.bytes().all(|b| b.is_ascii_hexdigit()).then(|| hash.to_string())
+    });
+
+    let mut crates: Vec<String> = find_all(file, CARGO_REGISTRY_PATH)
+        .filter_map(|offset| {
+            // Sources are kept under the registry's index, e.g. `index.crates.io-6f17d22bba15001f`.
+            let index = &file[offset + CARGO_REGISTRY_PATH.len()..];
+            let index_len = path_component(index)?.len();
+            path_component(&index[index_len + 1..]).map(str::to_string)
+        })
+        .collect();
+
+    crates.sort_unstable();
+    crates.dedup();
+
+    if commit.is_none() && crates.is_empty() {
+        return None;
+    }
+
+    Some(RustInfo { commit, crates })
+}
+
+/// `secs` since the unix epoch as a UTC date, e.g. `2021-03-04 12:00:00 UTC`.
+pub fn format_timestamp(secs: u32) -> String {
+    let days = (secs / 86400) as i64;
+    let time = secs % 86400;
+
+    // Days to a civil date, shifting the year to start in March so leap days come last.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+impl Metadata {
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"{\n  \"md5\": ")?;
+        write_json_str(w, &self.md5)?;
+        w.write_all(b",\n  \"sha256\": ")?;
+        write_json_str(w, &self.sha256)?;
+
+        w.write_all(b",\n  \"imphash\": ")?;
+        match self.imphash {
+            Some(ref imphash) => write_json_str(w, imphash)?,
+            None => w.write_all(b"null")?,
+        }
+
+        match self.timestamp {
+            Some(timestamp) => write!(w, ",\n  \"timestamp\": {timestamp}")?,
+            None => w.write_all(b",\n  \"timestamp\": null")?,
+        }
+
+        json_array(w, "rich_header", &self.rich_header, |w, entry| {
+            write!(
+                w,
+                "{{\"product\": {}, \"build\": {}, \"count\": {}}}",
+                entry.product, entry.build, entry.count
+            )
+        })?;
+
+        json_array(w, "sections", &self.sections, |w, section| {
+            w.write_all(b"{\"name\": ")?;
+            write_json_str(w, &section.name)?;
+            w.write_all(b", \"md5\": ")?;
+            write_json_str(w, &section.md5)?;
+            w.write_all(b", \"sha256\": ")?;
+            write_json_str(w, &section.sha256)?;
+            w.write_all(b"}")
+        })?;
+
+        json_array(w, "memories", &self.memories, |w, memory| {
+            write!(
+                w,
+                "{{\"name\": \"{}\", \"start\": {}, \"end\": {}}}",
+                memory.space.name(),
+                memory.start,
+                memory.end
+            )
+        })?;
+
+        json_array(w, "unpacking", &self.unpacking, |w, layer| {
+            w.write_all(b"{\"packer\": ")?;
+            write_json_str(w, layer.packer.name())?;
+            w.write_all(b", \"method\": ")?;
+            match layer.method {
+                Some(method) => write_json_str(w, method)?,
+                None => w.write_all(b"null")?,
+            }
+            write!(w, ", \"packed\": {}, \"unpacked\": {}}}", layer.packed_len, layer.unpacked_len)
+        })?;
+
+        let write_opt = |w: &mut W, value: &Option<String>| match value {
+            Some(value) => write_json_str(w, value),
+            None => w.write_all(b"null"),
+        };
+
+        w.write_all(b",\n  \"go\": ")?;
+        match self.go {
+            Some(ref go) => {
+                w.write_all(b"{\"version\": ")?;
+                write_opt(w, &go.version)?;
+                w.write_all(b", \"path\": ")?;
+                write_opt(w, &go.path)?;
+                w.write_all(b", \"module\": ")?;
+                write_opt(w, &go.module)?;
+                w.write_all(b"}")?;
+            }
+            None => w.write_all(b"null")?,
+        }
+
+        w.write_all(b",\n  \"rust\": ")?;
+        match self.rust {
+            Some(ref rust) => {
+                w.write_all(b"{\"commit\": ")?;
+                write_opt(w, &rust.commit)?;
+                w.write_all(b", \"crates\": [")?;
+                for (idx, krate) in rust.crates.iter().enumerate() {
+                    if idx != 0 {
+                        w.write_all(b", ")?;
+                    }
+                    write_json_str(w, krate)?;
+                }
+                w.write_all(b"]}")?;
+            }
+            None => w.write_all(b"null")?,
+        }
+
+        w.write_all(b"\n}\n")?;
+        w.flush()
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "md5       {}", self.md5)?;
+        writeln!(w, "sha256    {}", self.sha256)?;
+
+        for layer in &self.unpacking {
+            writeln!(w, "unpacked  {layer}")?;
+        }
+
+        if let Some(ref imphash) = self.imphash {
+            writeln!(w, "imphash   {imphash}")?;
+        }
+
+        if let Some(timestamp) = self.timestamp {
+            writeln!(w, "linked    {} ({timestamp:#x})", format_timestamp(timestamp))?;
+        }
+
+        if let Some(ref go) = self.go {
+            let version = go.version.as_deref().unwrap_or("older than go1.18");
+            writeln!(w, "go        {version}")?;
+            if let Some(ref path) = go.path {
+                writeln!(w, "  path    {path}")?;
+            }
+            if let Some(ref module) = go.module {
+                writeln!(w, "  module  {module}")?;
+            }
+        }
+
+        if let Some(ref rust) = self.rust {
+            let commit = rust.commit.as_deref().unwrap_or("unknown compiler");
+            writeln!(w, "rust      {commit}, {} crates", rust.crates.len())?;
+            for krate in &rust.crates {
+                writeln!(w, "  {krate}")?;
+            }
+        }
+
+        if !self.rich_header.is_empty() {
+            writeln!(w, "\n{} rich header entries:", self.rich_header.len())?;
+            for entry in &self.rich_header {
+                writeln!(
+                    w,
+                    "  product {:#06x} build {:>6} count {}",
+                    entry.product, entry.build, entry.count
+                )?;
+            }
+        }
+
+        writeln!(w, "\n{} sections:", self.sections.len())?;
+        for section in &self.sections {
+            writeln!(w, "  {:<20} {} {}", section.name, section.md5, section.sha256)?;
+        }
+
+        if !self.memories.is_empty() {
+            writeln!(w, "\n{} memories:", self.memories.len())?;
+            for memory in &self.memories {
+                let size = memory.end - memory.start;
+                writeln!(
+                    w,
+                    "  {:<20} {:#x}-{:#x} ({size} bytes)",
+                    memory.space.name(),
+                    memory.start,
+                    memory.end
+                )?;
+            }
+        }
+
+        w.flush()
+    }
+}
+
+impl Processor {
+    /// Hashes of the file and it's sections together with any build information found.
+    pub fn metadata(&self) -> Metadata {
+        let file = self.file_bytes();
+
+        let (imphash, timestamp) = match object::File::parse(file) {
+            Ok(object::File::Pe32(pe)) => {
+                let imports = binformat::pe::import_names(&pe).unwrap_or_default();
+                (imphash(&imports), Some(binformat::pe::timestamp(&pe)))
+            }
+            Ok(object::File::Pe64(pe)) => {
+                let imports = binformat::pe::import_names(&pe).unwrap_or_default();
+                (imphash(&imports), Some(binformat::pe::timestamp(&pe)))
+            }
+            _ => (None, None),
+        };
+
+        let sections = self
+            .sections()
+            .filter(|section| !section.bytes().is_empty())
+            .map(|section| SectionHash {
+                name: section.name.clone(),
+                md5: md5(section.bytes()),
+                sha256: sha256(section.bytes()),
+            })
+            .collect();
+
+        Metadata {
+            md5: md5(file),
+            sha256: sha256(file),
+            imphash,
+            timestamp,
+            rich_header: rich_header(file),
+            sections,
+            memories: self.memories(),
+            go: go_build_info(file),
+            rust: rust_info(file),
+            unpacking: self.unpacking().to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes() {
+        assert_eq!(md5(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn imphash_normalizes_names() {
+        let socket = binformat::ordinals::lookup("ws2_32.DLL", 23).unwrap();
+        let imports = [
+            ("KERNEL32.dll".to_string(), "ExitProcess".to_string()),
+            ("ws2_32.DLL".to_string(), socket.to_string()),
+            ("MFC42.dll".to_string(), "ord6453".to_string()),
+            ("msvcrt".to_string(), "printf".to_string()),
+        ];
+
+        let names = b"kernel32.exitprocess,ws2_32.socket,mfc42.ord6453,msvcrt.printf";
+        assert_eq!(imphash(&imports), Some(md5(names)));
+        assert_eq!(imphash(&[]), None);
+    }
+
+    #[test]
+    fn rich_header_entries() {
+        let key = 0x1234_5678u32;
+        let mut file = vec![0u8; 0x80];
+        file[..2].copy_from_slice(b"MZ");
+
+        let values = [RICH_START, 0, 0, 0, (0x0104 << 16) | 30133, 5, (0x0105 << 16) | 30134, 7];
+        for value in values {
+            file.extend_from_slice(&(value ^ key).to_le_bytes());
+        }
+        file.extend_from_slice(RICH_END);
+        file.extend_from_slice(&key.to_le_bytes());
+
+        let pe_offset = file.len() as u32;
+        file[0x3c..0x40].copy_from_slice(&pe_offset.to_le_bytes());
+        file.extend_from_slice(b"PE\0\0");
+
+        assert_eq!(
+            rich_header(&file),
+            [
+                RichEntry { product: 0x0104, build: 30133, count: 5 },
+                RichEntry { product: 0x0105, build: 30134, count: 7 },
+            ]
+        );
+        assert!(rich_header(b"\x7fELF").is_empty());
+    }
+
+    #[test]
+    fn go_inline_build_info() {
+        let mut file = b"padding".to_vec();
+        file.extend_from_slice(GO_BUILDINFO_MAGIC);
+        file.extend_from_slice(&[8, GO_BUILDINFO_INLINE]);
+        file.resize(file.len() + 16, 0);
+
+        let sentinel = [0xaa; 16];
+        let modinfo = b"path\texample.com/cmd\nmod\texample.com\tv1.2.3\th1:abc\n";
+        file.push(8);
+        file.extend_from_slice(b"go1.21.3");
+        file.push((modinfo.len() + 32) as u8);
+        file.extend_from_slice(&sentinel);
+        file.extend_from_slice(modinfo);
+        file.extend_from_slice(&sentinel);
+
+        assert_eq!(
+            go_build_info(&file),
+            Some(GoBuildInfo {
+                version: Some("go1.21.3".to_string()),
+                path: Some("example.com/cmd".to_string()),
+                module: Some("example.com v1.2.3".to_string()),
+            })
+        );
+        assert_eq!(go_build_info(b"no build info"), None);
+    }
+
+    #[test]
+    fn rust_commit_and_crates() {
+        let file = b"\0/rustc/90c541806f23a127002de5b4038be731ba1458ca/library/core/src/fmt.rs\0\
+            /home/u/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.193/src/de.rs\0\
+            /home/u/.cargo/registry/src/index.crates.io-6f17d22bba15001f/libc-0.2.150/src/lib.rs\0\
+            /home/u/.cargo/registry/src/index.crates.io-6f17d22bba15001f/serde-1.0.193/src/ser.rs";
+
+        assert_eq!(
+            rust_info(file),
+            Some(RustInfo {
+                commit: Some("90c541806f23a127002de5b4038be731ba1458ca".to_string()),
+                crates: vec!["libc-0.2.150".to_string(), "serde-1.0.193".to_string()],
+            })
+        );
+        assert_eq!(rust_info(b"/usr/lib/libc.so"), None);
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(format_timestamp(1_614_859_200), "2021-03-04 12:00:00 UTC");
+    }
+}
@@ -0,0 +1,230 @@
+//! Instruction sets of 32-bit ARM code, which switches between ARM and Thumb instructions and
+//! mixes in data. Mapping symbols mark where each starts. Binaries without them have Thumb
+//! functions known by their address having the lowest bit set, and by being called through
+//! `blx`, which always switches to the other instruction set.
+
+use crate::{Instruction, Processor};
+use arm::armv7::Opcode;
+use decoder::Decoded;
+use object::Architecture;
+use processor_shared::{PhysAddr, SectionKind};
+
+pub use binformat::elf::ArmMode;
+
+/// Mode of the code from each address on, code before the first being ARM.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Modes {
+    /// Modes found in the binary or inferred while decoding it. Sorted by address.
+    inferred: Vec<(PhysAddr, ArmMode)>,
+    /// Ranges the user chose the mode of, in the order they were chosen.
+    overrides: Vec<(PhysAddr, PhysAddr, ArmMode)>,
+    /// Modes found in the binary with the user's choices applied. Sorted by address.
+    modes: Vec<(PhysAddr, ArmMode)>,
+    /// Whether the binary has mapping symbols, which leave nothing to infer.
+    exact: bool,
+}
+
+/// Switch to `mode` for `start..end` of `modes`, after which the mode is as it was.
+fn set(modes: &mut Vec<(PhysAddr, ArmMode)>, start: PhysAddr, end: PhysAddr, mode: ArmMode) {
+    let after = mode_at(modes, end);
+    modes.retain(|&(addr, _)| !(start..end).contains(&addr));
+
+    let idx = modes.partition_point(|&(addr, _)| addr < start);
+    modes.insert(idx, (start, mode));
+    if modes.get(idx + 1).map(|&(addr, _)| addr) != Some(end) {
+        modes.insert(idx + 1, (end, after));
+    }
+
+    modes.dedup_by(|next, prev| next.1 == prev.1);
+}
+
+fn mode_at(modes: &[(PhysAddr, ArmMode)], addr: PhysAddr) -> ArmMode {
+    let idx = modes.partition_point(|&(start, _)| start <= addr);
+    idx.checked_sub(1).map_or(ArmMode::Arm, |idx| modes[idx].1)
+}
+
+impl Modes {
+    /// Modes marked by the mapping symbols of a binary, which may not have any.
+    pub fn new(mapping_symbols: Vec<(PhysAddr, ArmMode)>) -> Self {
+        Self {
+            exact: !mapping_symbols.is_empty(),
+            inferred: mapping_symbols.clone(),
+            overrides: Vec::new(),
+            modes: mapping_symbols,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    pub fn at(&self, addr: PhysAddr) -> ArmMode {
+        mode_at(&self.modes, addr)
+    }
+
+    /// Where the mode next changes after `addr`, if it does.
+    pub fn next_change(&self, addr: PhysAddr) -> Option<PhysAddr> {
+        let idx = self.modes.partition_point(|&(start, _)| start <= addr);
+        self.modes.get(idx).map(|&(start, _)| start)
+    }
+
+    /// Parts of `start..end` that are each in a single mode.
+    pub fn parts(&self, start: PhysAddr, end: PhysAddr) -> Vec<(PhysAddr, PhysAddr, ArmMode)> {
+        let mut parts = Vec::new();
+        let mut addr = start;
+        while addr < end {
+            let part_end = self.next_change(addr).map_or(end, |change| change.min(end));
+            parts.push((addr, part_end, self.at(addr)));
+            addr = part_end;
+        }
+
+        parts
+    }
+
+    /// Code in `start..end` found to be in `mode` while decoding.
+    pub fn infer(&mut self, start: PhysAddr, end: PhysAddr, mode: ArmMode) {
+        set(&mut self.inferred, start, end, mode);
+        self.apply_overrides();
+    }
+
+    /// The user chose `start..end` to be in `mode`.
+    pub fn set(&mut self, start: PhysAddr, end: PhysAddr, mode: ArmMode) {
+        self.overrides.push((start, end, mode));
+        self.apply_overrides();
+    }
+
+    /// Forget the modes the user chose for ranges containing `addr`.
+    pub fn unset(&mut self, addr: PhysAddr) {
+        self.overrides.retain(|&(start, end, _)| !(start..end).contains(&addr));
+        self.apply_overrides();
+    }
+
+    fn apply_overrides(&mut self) {
+        self.modes = self.inferred.clone();
+        for &(start, end, mode) in &self.overrides {
+            set(&mut self.modes, start, end, mode);
+        }
+    }
+}
+
+impl Processor {
+    /// Instruction set of the code at `addr`, for 32-bit ARM binaries.
+    pub fn arm_mode(&self, addr: PhysAddr) -> Option<ArmMode> {
+        match self.arch {
+            Architecture::Arm => Some(self.modes.read().unwrap().at(addr)),
+            _ => None,
+        }
+    }
+
+    /// Decode each function at `addrs` as Thumb instructions.
+    pub(crate) fn decode_thumb_functions(&self, addrs: &[PhysAddr]) {
+        if self.modes.read().unwrap().is_exact() {
+            return;
+        }
+
+        for &addr in addrs {
+            let section = match self.section_by_addr(addr) {
+                Some(section) if section.kind == SectionKind::Code => section,
+                _ => continue,
+            };
+
+            let end = self.function_end(section, addr);
+            self.modes.write().unwrap().infer(addr, end, ArmMode::Thumb);
+            self.redecode(section, addr, end);
+        }
+    }
+
+    /// Decode the functions called through `blx` in the other instruction set than the caller,
+    /// for binaries without mapping symbols.
+    pub(crate) fn follow_mode_switches(&self) {
+        if self.arch != Architecture::Arm || self.modes.read().unwrap().is_exact() {
+            return;
+        }
+
+        let mut targets: Vec<(PhysAddr, ArmMode)> = {
+            let code = self.code.read().unwrap();
+            code.instructions.iter().filter_map(|inst| mode_switch(&inst.item)).collect()
+        };
+
+        targets.sort_unstable_by_key(|&(addr, _)| addr);
+        targets.dedup_by_key(|&mut (addr, _)| addr);
+
+        let mut switched = 0;
+        for (addr, mode) in targets {
+            let section = match self.section_by_addr(addr) {
+                Some(section) if section.kind == SectionKind::Code => section,
+                _ => continue,
+            };
+
+            if self.modes.read().unwrap().at(addr) == mode {
+                continue;
+            }
+
+            let end = self.function_end(section, addr);
+            self.modes.write().unwrap().infer(addr, end, mode);
+            self.redecode(section, addr, end);
+            switched += 1;
+        }
+
+        if switched > 0 {
+            log::complex!(
+                w "[processor::parse] switched instruction set of ",
+                y switched.to_string(),
+                w " functions called through blx.",
+            );
+        }
+    }
+}
+
+/// Target of a `blx` to an address, together with the instruction set it switches to.
+fn mode_switch(inst: &Instruction) -> Option<(PhysAddr, ArmMode)> {
+    let inst = unsafe { &inst.armv7 };
+    if inst.opcode != Opcode::BLX {
+        return None;
+    }
+
+    let (target, _) = inst.reference()?;
+    match inst.thumb() {
+        // ARM instructions are word aligned, with the target computed from an aligned PC.
+        true => Some((target & !3, ArmMode::Arm)),
+        false => Some((target, ArmMode::Thumb)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        let mut modes = Modes::new(Vec::new());
+        assert!(!modes.is_exact());
+
+        modes.infer(0x1000, 0x1100, ArmMode::Thumb);
+        assert_eq!(modes.at(0xfff), ArmMode::Arm);
+        assert_eq!(modes.at(0x1000), ArmMode::Thumb);
+        assert_eq!(modes.at(0x1100), ArmMode::Arm);
+        assert_eq!(
+            modes.parts(0xf00, 0x1200),
+            [
+                (0xf00, 0x1000, ArmMode::Arm),
+                (0x1000, 0x1100, ArmMode::Thumb),
+                (0x1100, 0x1200, ArmMode::Arm)
+            ]
+        );
+
+        // Choices of the user apply on top of what's inferred, until they're forgotten.
+        modes.set(0x1080, 0x1200, ArmMode::Data);
+        modes.infer(0x1180, 0x1300, ArmMode::Thumb);
+        assert_eq!(modes.at(0x1000), ArmMode::Thumb);
+        assert_eq!(modes.at(0x1180), ArmMode::Data);
+        assert_eq!(modes.next_change(0x1180), Some(0x1200));
+        assert_eq!(modes.at(0x1200), ArmMode::Thumb);
+
+        modes.unset(0x1100);
+        assert_eq!(modes.at(0x1080), ArmMode::Thumb);
+        assert_eq!(modes.at(0x1100), ArmMode::Arm);
+        assert_eq!(modes.at(0x1180), ArmMode::Thumb);
+        assert_eq!(modes.at(0x1300), ArmMode::Arm);
+    }
+}
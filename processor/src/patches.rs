@@ -0,0 +1,392 @@
+//! Bytes changed by the user, overlaid on the binary as the mapped file is never written to.
+
+use crate::{Edit, Processor};
+use object::{Architecture, Endianness};
+use processor_shared::{PhysAddr, Section};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Patched bytes by their offset into the file.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Patches {
+    bytes: BTreeMap<usize, u8>,
+}
+
+impl Patches {
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Number of bytes patched.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.bytes.contains_key(&offset)
+    }
+
+    /// Whether any of the `len` bytes at `offset` are patched.
+    pub fn overlaps(&self, offset: usize, len: usize) -> bool {
+        self.bytes.range(offset..offset.saturating_add(len)).next().is_some()
+    }
+
+    /// Write `bytes` at `offset`, where bytes matching the `original` ones undo their patch.
+    pub fn write(&mut self, offset: usize, bytes: &[u8], original: &[u8]) {
+        for (idx, (&byte, &original)) in bytes.iter().zip(original).enumerate() {
+            if byte == original {
+                self.bytes.remove(&(offset + idx));
+            } else {
+                self.bytes.insert(offset + idx, byte);
+            }
+        }
+    }
+
+    /// Overlay the patches on `buf`, which holds the file's bytes starting at `offset`.
+    pub fn apply(&self, offset: usize, buf: &mut [u8]) {
+        for (&patched, &byte) in self.bytes.range(offset..offset + buf.len()) {
+            buf[patched - offset] = byte;
+        }
+    }
+
+    /// Patched bytes and their offsets, sorted by offset.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.bytes.iter().map(|(&offset, &byte)| (offset, byte))
+    }
+}
+
+/// Parse bytes written in hex, such as `90 90` or `c3`.
+pub fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.is_ascii() {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|idx| u8::from_str_radix(&digits[idx..idx + 2], 16).ok())
+        .collect()
+}
+
+/// Instruction that does nothing on `arch`, as stored in little-endian binaries.
+fn nop(arch: Architecture) -> Option<&'static [u8]> {
+    match arch {
+        Architecture::X86_64 | Architecture::X86_64_X32 | Architecture::I386 => Some(&[0x90]),
+        Architecture::Aarch64 => Some(&[0x1f, 0x20, 0x03, 0xd5]),
+        Architecture::Arm => Some(&[0x00, 0xf0, 0x20, 0xe3]),
+        Architecture::Riscv32 | Architecture::Riscv64 => Some(&[0x13, 0x00, 0x00, 0x00]),
+        Architecture::Mips | Architecture::Mips64 => Some(&[0x00, 0x00, 0x00, 0x00]),
+        Architecture::Bpf => Some(&[0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+        _ => None,
+    }
+}
+
+/// `len` bytes worth of NOPs, None if `len` isn't a multiple of the NOP's width.
+fn nops(arch: Architecture, endianness: Endianness, len: usize) -> Option<Vec<u8>> {
+    let mut nop = nop(arch)?.to_vec();
+    if endianness == Endianness::Big {
+        nop.reverse();
+    }
+
+    if len % nop.len() != 0 {
+        return None;
+    }
+
+    Some(nop.repeat(len / nop.len()))
+}
+
+/// Recompute the checksum in a PE's optional header, which Windows checks for drivers and
+/// DLLs loaded at boot. Returns false if the file isn't a PE.
+fn fix_pe_checksum(bytes: &mut [u8]) -> bool {
+    let read_u32 = |bytes: &[u8], offset: usize| -> Option<u32> {
+        let bytes = bytes.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    if !bytes.starts_with(b"MZ") {
+        return false;
+    }
+
+    let pe_offset = match read_u32(bytes, 0x3c) {
+        Some(offset) => offset as usize,
+        None => return false,
+    };
+
+    if bytes.get(pe_offset..pe_offset + 4) != Some(b"PE\0\0") {
+        return false;
+    }
+
+    // The checksum is at the same offset into the optional header for both PE32 and PE32+.
+    let checksum_offset = pe_offset + 4 + 20 + 64;
+    if checksum_offset + 4 > bytes.len() {
+        return false;
+    }
+
+    bytes[checksum_offset..checksum_offset + 4].fill(0);
+
+    let mut sum: u64 = 0;
+    for word in bytes.chunks(2) {
+        let word = match *word {
+            [low, high] => u16::from_le_bytes([low, high]),
+            [low] => low as u16,
+            _ => unreachable!(),
+        };
+
+        sum += word as u64;
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+
+    sum = (sum & 0xffff) + (sum >> 16);
+    let checksum = (sum as u32).wrapping_add(bytes.len() as u32);
+    bytes[checksum_offset..checksum_offset + 4].copy_from_slice(&checksum.to_le_bytes());
+    true
+}
+
+impl Processor {
+    /// Bytes of the file at `offset` with the user's patches applied, only copied if patched.
+    pub fn patched_bytes(&self, offset: usize, len: usize) -> Cow<'_, [u8]> {
+        let file = self.file_bytes();
+        let start = offset.min(file.len());
+        let end = offset.saturating_add(len).min(file.len());
+
+        let patches = self.patches.read().unwrap();
+        if !patches.overlaps(start, end - start) {
+            return Cow::Borrowed(&file[start..end]);
+        }
+
+        let mut bytes = file[start..end].to_vec();
+        patches.apply(start, &mut bytes);
+        Cow::Owned(bytes)
+    }
+
+    /// Bytes at `addr` in `section` with the user's patches applied, only copied if patched.
+    pub(crate) fn bytes_by_addr<'a>(
+        &self,
+        section: &'a Section,
+        addr: PhysAddr,
+        len: usize,
+    ) -> Cow<'a, [u8]> {
+        let bytes = section.bytes_by_addr(addr, len);
+        let patches = self.patches.read().unwrap();
+
+        match self.file_offset(addr) {
+            Some(offset) if patches.overlaps(offset, bytes.len()) => {
+                let mut bytes = bytes.to_vec();
+                patches.apply(offset, &mut bytes);
+                Cow::Owned(bytes)
+            }
+            _ => Cow::Borrowed(bytes),
+        }
+    }
+
+    /// Bytes of the instruction decoded at `addr`, with the user's patches applied.
+    pub fn instruction_bytes(&self, addr: PhysAddr) -> Option<Vec<u8>> {
+        let width = {
+            let code = self.code.read().unwrap();
+            match code.instruction_by_addr(addr) {
+                Some(inst) => self.instruction_width(inst),
+                None => code.error_by_addr(addr)?.size(),
+            }
+        };
+
+        let section = self.section_by_addr(addr)?;
+        Some(self.bytes_by_addr(section, addr, width).into_owned())
+    }
+
+    pub fn is_patched(&self, offset: usize) -> bool {
+        self.patches.read().unwrap().contains(offset)
+    }
+
+    /// Addresses of the patched bytes that are loaded, sorted.
+    pub fn patched_addrs(&self) -> Vec<PhysAddr> {
+        let patches = self.patches.read().unwrap();
+        let mut addrs: Vec<PhysAddr> =
+            patches.iter().filter_map(|(offset, _)| self.addr_by_offset(offset)).collect();
+        addrs.sort_unstable();
+        addrs
+    }
+
+    pub fn patch_count(&self) -> usize {
+        self.patches.read().unwrap().len()
+    }
+
+    /// NOPs filling `len` bytes, None if the architecture's NOP doesn't fit.
+    pub fn nops(&self, len: usize) -> Option<Vec<u8>> {
+        nops(self.arch, self.endianness, len)
+    }
+
+    /// Overwrite the bytes at file `offset`, re-decoding any code they're part of, recording it
+    /// so that it can be undone.
+    ///
+    /// Returns false if the bytes don't fit in the file.
+    pub fn patch(&self, offset: usize, bytes: &[u8]) -> bool {
+        let before = self.patched_bytes(offset, bytes.len()).into_owned();
+        if !self.write_patch(offset, bytes) {
+            return false;
+        }
+
+        self.record(Edit::Patch {
+            offset,
+            before,
+            after: bytes.to_vec(),
+        });
+
+        true
+    }
+
+    pub(crate) fn write_patch(&self, offset: usize, bytes: &[u8]) -> bool {
+        let file = self.file_bytes();
+        let end = match offset.checked_add(bytes.len()) {
+            Some(end) if end <= file.len() => end,
+            _ => return false,
+        };
+
+        self.patches.write().unwrap().write(offset, bytes, &file[offset..end]);
+
+        // Only code has to be decoded again.
+        let addrs = (offset..end).filter_map(|offset| self.addr_by_offset(offset));
+        let code: Vec<PhysAddr> = addrs.filter(|&addr| self.is_code(addr)).collect();
+        if let (Some(&first), Some(&last)) = (code.first(), code.last()) {
+            self.redecode_patched(first, last + 1);
+        }
+
+        log::complex!(
+            w "[processor::patch] patched ",
+            y bytes.len().to_string(),
+            w " bytes at offset ",
+            g format!("{offset:#x}"),
+            w ".",
+        );
+
+        true
+    }
+
+    /// Overwrite the bytes at `addr`, returns false if they aren't all stored in the file.
+    pub fn patch_addr(&self, addr: PhysAddr, bytes: &[u8]) -> bool {
+        let offset = match self.file_offset(addr) {
+            Some(offset) => offset,
+            None => return false,
+        };
+
+        // Sections are contiguous in the file, so the last byte shows whether they all fit.
+        let last = bytes.len().saturating_sub(1);
+        if self.file_offset(addr + last) != Some(offset + last) {
+            return false;
+        }
+
+        self.patch(offset, bytes)
+    }
+
+    /// Re-decode the instructions overlapping `start..end`.
+    fn redecode_patched(&self, start: PhysAddr, end: PhysAddr) {
+        let (start, end) = {
+            let code = self.code.read().unwrap();
+            let instructions = &code.instructions;
+
+            // Start at the instruction the patch starts in.
+            let idx = instructions.partition_point(|inst| inst.addr <= start);
+            let start = match idx.checked_sub(1).map(|idx| &instructions[idx]) {
+                Some(inst) if inst.addr + self.instruction_width(&inst.item) > start => inst.addr,
+                _ => start,
+            };
+
+            // End at the next instruction that wasn't patched, where decoding is back in sync
+            // if the patch replaced whole instructions.
+            let idx = instructions.partition_point(|inst| inst.addr < end);
+            let end = instructions.get(idx).map_or(end, |inst| inst.addr);
+
+            (start, end)
+        };
+
+        let section = match self.section_by_addr(start) {
+            Some(section) if self.section_is_code(section) => section,
+            _ => return,
+        };
+
+        self.redecode(section, start, end.min(section.end));
+    }
+
+    /// The whole file with the user's patches applied, fixing the checksum if it's a PE.
+    pub fn patched_file(&self) -> Vec<u8> {
+        let mut bytes = self.patched_bytes(0, usize::MAX).into_owned();
+        fix_pe_checksum(&mut bytes);
+        bytes
+    }
+
+    /// Write the binary with the user's patches applied to `path`.
+    pub fn save_patched(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.patched_file())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay() {
+        let original = [0x55, 0x48, 0x89, 0xe5];
+        let mut patches = Patches::default();
+
+        patches.write(0x100, &[0x90, 0x48, 0x90], &original[..3]);
+        assert_eq!(patches.len(), 2);
+        assert!(patches.contains(0x100) && !patches.contains(0x101));
+        assert!(patches.overlaps(0xf0, 0x11) && patches.overlaps(0x101, 2));
+        assert!(!patches.overlaps(0xf0, 0x10) && !patches.overlaps(0x103, usize::MAX));
+
+        let mut bytes = original;
+        patches.apply(0x100, &mut bytes);
+        assert_eq!(bytes, [0x90, 0x48, 0x90, 0xe5]);
+
+        let mut bytes = original;
+        patches.apply(0x102, &mut bytes);
+        assert_eq!(bytes, [0x90, 0x48, 0x89, 0xe5]);
+
+        // Writing the original bytes back undoes the patch.
+        patches.write(0x100, &original, &original);
+        assert!(patches.is_empty());
+    }
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_hex("90 90\tc3"), Some(vec![0x90, 0x90, 0xc3]));
+        assert_eq!(parse_hex("E8"), Some(vec![0xe8]));
+        assert_eq!(parse_hex("9"), None);
+        assert_eq!(parse_hex("zz"), None);
+        assert_eq!(parse_hex(" "), None);
+    }
+
+    #[test]
+    fn nop_fill() {
+        assert_eq!(nops(Architecture::X86_64, Endianness::Little, 3), Some(vec![0x90; 3]));
+        assert_eq!(nops(Architecture::Aarch64, Endianness::Little, 2), None);
+        assert_eq!(
+            nops(Architecture::Riscv64, Endianness::Big, 4),
+            Some(vec![0x00, 0x00, 0x00, 0x13])
+        );
+    }
+
+    #[test]
+    fn pe_checksum() {
+        let mut pe = vec![0u8; 0x200];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x80;
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        pe[0x1ff] = 0x12;
+
+        assert!(fix_pe_checksum(&mut pe));
+        let checksum_offset = 0x80 + 4 + 20 + 64;
+        let checksum = u32::from_le_bytes(pe[checksum_offset..][..4].try_into().unwrap());
+
+        // Words summed with their carries folded back in, plus the file's length.
+        let words = 0x5a4d + 0x80 + 0x4550 + 0x1200;
+        assert_eq!(checksum, words + 0x200);
+
+        // The old checksum isn't part of the sum.
+        assert!(fix_pe_checksum(&mut pe));
+        assert_eq!(pe[checksum_offset..][..4], checksum.to_le_bytes());
+
+        assert!(!fix_pe_checksum(&mut [0x7f, b'E', b'L', b'F']));
+    }
+}
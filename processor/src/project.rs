@@ -0,0 +1,388 @@
+//! Project files, storing the analysis of a binary together with the user's annotations.
+//!
+//! Projects use a compact binary encoding: a magic and version followed by LEB128 integers
+//! and length-prefixed strings. Instructions aren't stored as they're quick to decode, unlike
+//! the block boundaries which are stored as is.
+
+use crate::annotations::Annotations;
+use crate::definitions::Definition;
+use processor_shared::PhysAddr;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Extension of project files.
+pub const EXTENSION: &str = "bite";
+
+const MAGIC: &[u8; 4] = b"BITE";
+/// Version 2 added function prototypes, version 3 added definitions of code and data and
+/// version 4 added sections toggled being decoded as code.
+const VERSION: u64 = 4;
+
+pub enum Error {
+    IO(std::io::Error),
+    /// File isn't a project.
+    InvalidMagic,
+    /// Project was saved by a newer version.
+    UnsupportedVersion(u64),
+    /// File ended early or contains values out of range.
+    Corrupted,
+    /// Binary changed since the project was saved.
+    Modified(PathBuf),
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("{err}.")),
+            Self::InvalidMagic => f.write_str("File isn't a bite project."),
+            Self::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("Unsupported project version: '{version}'."))
+            }
+            Self::Corrupted => f.write_str("Project file is corrupted."),
+            Self::Modified(path) => {
+                f.write_fmt(format_args!("Binary {path:?} changed since the project was saved."))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Project {
+    /// Where the binary is located.
+    pub binary: PathBuf,
+    /// Hash of the binary's contents when the project was saved.
+    pub checksum: u64,
+    /// Ranges re-decoded by the user, in the order they were re-decoded.
+    pub reanalyzed: Vec<(PhysAddr, PhysAddr)>,
+    /// Ranges defined as code, data or an instruction set by the user, in the order they were
+    /// defined.
+    pub definitions: Vec<(PhysAddr, PhysAddr, Definition)>,
+    /// Sections toggled between being decoded as code or not by the user, by their start.
+    pub code_sections: Vec<(PhysAddr, bool)>,
+    /// Start of every block in the listing.
+    pub boundaries: Vec<PhysAddr>,
+    pub annotations: Annotations,
+}
+
+/// FNV-1a hash of `bytes`.
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn uint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                self.buf.push(byte);
+                return;
+            }
+
+            self.buf.push(byte | 0x80);
+        }
+    }
+
+    fn addr(&mut self, addr: PhysAddr) {
+        self.uint(addr as u64);
+    }
+
+    fn str(&mut self, s: &str) {
+        self.uint(s.len() as u64);
+        self.buf.extend_from_slice(s.as_bytes());
+    }
+
+    /// Addresses are sorted, so only the distance to the previous one is stored.
+    fn addrs(&mut self, addrs: &[PhysAddr]) {
+        self.uint(addrs.len() as u64);
+
+        let mut prev = 0;
+        for &addr in addrs {
+            self.addr(addr.wrapping_sub(prev));
+            prev = addr;
+        }
+    }
+
+    fn map(&mut self, map: &BTreeMap<PhysAddr, String>) {
+        self.uint(map.len() as u64);
+
+        for (&addr, text) in map {
+            self.addr(addr);
+            self.str(text);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.bytes.len() {
+            return Err(Error::Corrupted);
+        }
+
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn uint(&mut self) -> Result<u64, Error> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(Error::Corrupted)
+    }
+
+    fn addr(&mut self) -> Result<PhysAddr, Error> {
+        PhysAddr::try_from(self.uint()?).map_err(|_| Error::Corrupted)
+    }
+
+    /// A length that can't be larger than the remaining bytes, to not allocate absurd amounts
+    /// for corrupted files.
+    fn count(&mut self) -> Result<usize, Error> {
+        match usize::try_from(self.uint()?) {
+            Ok(len) if len <= self.bytes.len() => Ok(len),
+            _ => Err(Error::Corrupted),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let len = self.count()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| Error::Corrupted)
+    }
+
+    fn addrs(&mut self) -> Result<Vec<PhysAddr>, Error> {
+        let len = self.count()?;
+        let mut addrs = Vec::with_capacity(len);
+
+        let mut prev: PhysAddr = 0;
+        for _ in 0..len {
+            prev = prev.wrapping_add(self.addr()?);
+            addrs.push(prev);
+        }
+
+        Ok(addrs)
+    }
+
+    fn map(&mut self) -> Result<BTreeMap<PhysAddr, String>, Error> {
+        let len = self.count()?;
+        let mut map = BTreeMap::new();
+
+        for _ in 0..len {
+            let addr = self.addr()?;
+            map.insert(addr, self.string()?);
+        }
+
+        Ok(map)
+    }
+}
+
+impl Project {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer { buf: MAGIC.to_vec() };
+
+        w.uint(VERSION);
+        w.str(&self.binary.to_string_lossy());
+        w.uint(self.checksum);
+
+        w.uint(self.reanalyzed.len() as u64);
+        for &(start, end) in &self.reanalyzed {
+            w.addr(start);
+            w.addr(end);
+        }
+
+        w.addrs(&self.boundaries);
+        w.map(&self.annotations.comments);
+        w.map(&self.annotations.renames);
+        w.map(&self.annotations.bookmarks);
+        w.map(&self.annotations.prototypes);
+
+        w.uint(self.definitions.len() as u64);
+        for &(start, end, definition) in &self.definitions {
+            w.addr(start);
+            w.addr(end);
+            w.uint(match definition {
+                Definition::Code => 0,
+                Definition::Data => 1,
+                Definition::Arm => 2,
+                Definition::Thumb => 3,
+            });
+        }
+
+        w.uint(self.code_sections.len() as u64);
+        for &(start, code) in &self.code_sections {
+            w.addr(start);
+            w.uint(code as u64);
+        }
+
+        w.buf
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader { bytes };
+
+        if r.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(Error::InvalidMagic);
+        }
+
+        let version = r.uint()?;
+        if version > VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+
+        let binary = PathBuf::from(r.string()?);
+        let checksum = r.uint()?;
+
+        let len = r.count()?;
+        let mut reanalyzed = Vec::with_capacity(len);
+        for _ in 0..len {
+            reanalyzed.push((r.addr()?, r.addr()?));
+        }
+
+        let boundaries = r.addrs()?;
+        let annotations = Annotations {
+            comments: r.map()?,
+            renames: r.map()?,
+            bookmarks: r.map()?,
+            prototypes: if version >= 2 { r.map()? } else { BTreeMap::new() },
+        };
+
+        let mut definitions = Vec::new();
+        if version >= 3 {
+            let len = r.count()?;
+            for _ in 0..len {
+                let (start, end) = (r.addr()?, r.addr()?);
+                let definition = match r.uint()? {
+                    0 => Definition::Code,
+                    1 => Definition::Data,
+                    2 => Definition::Arm,
+                    3 => Definition::Thumb,
+                    _ => return Err(Error::Corrupted),
+                };
+                definitions.push((start, end, definition));
+            }
+        }
+
+        let mut code_sections = Vec::new();
+        if version >= 4 {
+            let len = r.count()?;
+            for _ in 0..len {
+                let start = r.addr()?;
+                let code = match r.uint()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(Error::Corrupted),
+                };
+                code_sections.push((start, code));
+            }
+        }
+
+        Ok(Self {
+            binary,
+            checksum,
+            reanalyzed,
+            definitions,
+            code_sections,
+            boundaries,
+            annotations,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, self.encode()).map_err(Error::IO)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let bytes = std::fs::read(path).map_err(Error::IO)?;
+        Self::decode(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Project {
+        let mut annotations = Annotations::default();
+        annotations.comments.insert(0x1004, "loop counter".to_string());
+        annotations.renames.insert(0x1000, "parse_header".to_string());
+        annotations.bookmarks.insert(usize::MAX, String::new());
+        annotations.prototypes.insert(0x1000, "int parse_header(char *buf)".to_string());
+
+        Project {
+            binary: PathBuf::from("/usr/bin/true"),
+            checksum: checksum(b"\x7fELF"),
+            reanalyzed: vec![(0x2000, 0x2040)],
+            definitions: vec![
+                (0x2010, 0x2018, Definition::Data),
+                (0x2013, 0x2040, Definition::Code),
+                (0x2040, 0x2080, Definition::Thumb),
+            ],
+            code_sections: vec![(0x3000, true), (0x1000, false)],
+            boundaries: vec![0x1000, 0x1004, 0x1100, 0x40_0000],
+            annotations,
+        }
+    }
+
+    #[test]
+    fn roundtrip() {
+        let project = project();
+        assert_eq!(Project::decode(&project.encode()).unwrap(), project);
+
+        let empty = Project::default();
+        assert_eq!(Project::decode(&empty.encode()).unwrap(), empty);
+
+        // Projects saved before code sections existed end after the definitions, those saved
+        // before definitions existed end after the prototypes, and those saved before
+        // prototypes existed end after the bookmarks.
+        let mut older = project;
+        older.code_sections.clear();
+        older.definitions.clear();
+        older.annotations.prototypes.clear();
+        let mut bytes = older.encode();
+        bytes[MAGIC.len()] = 3;
+        bytes.pop();
+        assert_eq!(Project::decode(&bytes).unwrap(), older);
+
+        bytes[MAGIC.len()] = 2;
+        bytes.pop();
+        assert_eq!(Project::decode(&bytes).unwrap(), older);
+
+        bytes[MAGIC.len()] = 1;
+        bytes.pop();
+        assert_eq!(Project::decode(&bytes).unwrap(), older);
+    }
+
+    #[test]
+    fn invalid() {
+        let bytes = project().encode();
+
+        assert!(matches!(Project::decode(b"ELF\x7f"), Err(Error::InvalidMagic)));
+        for len in MAGIC.len()..bytes.len() {
+            assert!(matches!(Project::decode(&bytes[..len]), Err(Error::Corrupted)));
+        }
+
+        let mut newer = MAGIC.to_vec();
+        newer.push(VERSION as u8 + 1);
+        assert!(matches!(Project::decode(&newer), Err(Error::UnsupportedVersion(..))));
+    }
+}
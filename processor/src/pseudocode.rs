@@ -0,0 +1,1143 @@
+//! Experimental pseudo-C of a function. It's basic blocks are lifted into expressions, which are
+//! propagated into the statements using them, such that `mov eax, [rdi]; add eax, 1; ret`
+//! becomes `return *(uint32_t *)rdi + 1;`.
+//!
+//! Only x86_64 is lifted, the instructions of other architectures are shown as inline assembly.
+
+use crate::strings::{c_string, MAX_REFERENCED_LEN};
+use crate::{Code, ControlFlowGraph, EdgeKind, Processor, XrefKind};
+use config::CONFIG;
+use decoder::Decoded;
+use object::Architecture;
+use processor_shared::{PhysAddr, SectionKind};
+use std::collections::BTreeSet;
+use std::fmt;
+use x86_64::long_mode::{self as x64, register_class, ConditionCode, Opcode, Operand, RegSpec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl BinOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::And => "&",
+            Self::Or => "|",
+            Self::Xor => "^",
+            Self::Shl => "<<",
+            Self::Shr => ">>",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+        }
+    }
+
+    /// Result of the operation if both sides are known.
+    fn fold(self, lhs: u64, rhs: u64) -> Option<u64> {
+        Some(match self {
+            Self::Add => lhs.wrapping_add(rhs),
+            Self::Sub => lhs.wrapping_sub(rhs),
+            Self::Mul => lhs.wrapping_mul(rhs),
+            Self::And => lhs & rhs,
+            Self::Or => lhs | rhs,
+            Self::Xor => lhs ^ rhs,
+            Self::Shl => lhs.checked_shl(rhs as u32)?,
+            Self::Shr => lhs.checked_shr(rhs as u32)?,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Const(u64),
+    /// String literal stored at a referenced address.
+    Str(String),
+    /// Register, local variable or parameter.
+    Var(String),
+    /// Address of a symbol, such as a function or global variable.
+    Addr(String),
+    /// Bytes read from an address, their width is zero if it isn't known.
+    Load(Box<Expr>, usize),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+}
+
+impl Expr {
+    /// `lhs op rhs`, with constants folded and operations that do nothing removed.
+    pub fn binary(op: BinOp, lhs: Expr, rhs: Expr) -> Self {
+        match (op, lhs, rhs) {
+            (op, Self::Const(lhs), Self::Const(rhs)) if op.fold(lhs, rhs).is_some() => {
+                Self::Const(op.fold(lhs, rhs).unwrap())
+            }
+            (BinOp::Xor | BinOp::Sub, lhs, rhs) if lhs == rhs => Self::Const(0),
+            (BinOp::Sub, lhs, Self::Const(rhs)) => {
+                Self::binary(BinOp::Add, lhs, Self::Const(rhs.wrapping_neg()))
+            }
+            (
+                BinOp::Add | BinOp::Or | BinOp::Xor | BinOp::Shl | BinOp::Shr,
+                lhs,
+                Self::Const(0),
+            ) => lhs,
+            (BinOp::Mul, lhs, Self::Const(1)) => lhs,
+            // Offsets added one after the other, e.g. `(rdi + 8) + 4`.
+            (BinOp::Add, Self::Binary(BinOp::Add, inner, offset), Self::Const(rhs)) => {
+                match *offset {
+                    Self::Const(lhs) => Self::binary(BinOp::Add, *inner, Self::Const(lhs + rhs)),
+                    offset => Self::Binary(
+                        BinOp::Add,
+                        Box::new(Self::Binary(BinOp::Add, inner, Box::new(offset))),
+                        Box::new(Self::Const(rhs)),
+                    ),
+                }
+            }
+            (op, lhs, rhs) => Self::Binary(op, Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    /// Whether the expression reads the variable `name`.
+    pub fn uses(&self, name: &str) -> bool {
+        match self {
+            Self::Var(var) => var == name,
+            Self::Const(_) | Self::Str(_) | Self::Addr(_) => false,
+            Self::Load(expr, _) | Self::Not(expr) | Self::Neg(expr) => expr.uses(name),
+            Self::Binary(_, lhs, rhs) => lhs.uses(name) || rhs.uses(name),
+            Self::Call(target, args) => target.uses(name) || args.iter().any(|arg| arg.uses(name)),
+        }
+    }
+
+    /// Whether the expression reads memory, so it's value can change by storing to memory.
+    pub fn loads(&self) -> bool {
+        match self {
+            Self::Load(..) | Self::Call(..) => true,
+            Self::Const(_) | Self::Str(_) | Self::Var(_) | Self::Addr(_) => false,
+            Self::Not(expr) | Self::Neg(expr) => expr.loads(),
+            Self::Binary(_, lhs, rhs) => lhs.loads() || rhs.loads(),
+        }
+    }
+
+    /// Expression with every occurrence of `from` replaced by `to`.
+    fn replace(self, from: &Expr, to: &Expr) -> Self {
+        if self == *from {
+            return to.clone();
+        }
+
+        match self {
+            Self::Load(expr, width) => Self::Load(Box::new(expr.replace(from, to)), width),
+            Self::Not(expr) => Self::Not(Box::new(expr.replace(from, to))),
+            Self::Neg(expr) => Self::Neg(Box::new(expr.replace(from, to))),
+            Self::Binary(op, lhs, rhs) => Self::Binary(
+                op,
+                Box::new(lhs.replace(from, to)),
+                Box::new(rhs.replace(from, to)),
+            ),
+            Self::Call(target, args) => Self::Call(
+                Box::new(target.replace(from, to)),
+                args.into_iter().map(|arg| arg.replace(from, to)).collect(),
+            ),
+            expr => expr,
+        }
+    }
+
+    /// Whether the expression can be written next to an operator without parentheses.
+    fn is_atom(&self) -> bool {
+        !matches!(self, Self::Binary(..))
+    }
+}
+
+/// C type of an unsigned integer that's `width` bytes wide.
+fn int_type(width: usize) -> Option<&'static str> {
+    Some(match width {
+        1 => "uint8_t",
+        2 => "uint16_t",
+        4 => "uint32_t",
+        8 => "uint64_t",
+        _ => return None,
+    })
+}
+
+/// Write `expr`, surrounded by parentheses if it's not an atom.
+fn operand(f: &mut fmt::Formatter<'_>, expr: &Expr) -> fmt::Result {
+    if expr.is_atom() {
+        write!(f, "{expr}")
+    } else {
+        write!(f, "({expr})")
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Small numbers are most likely counts or offsets, larger ones addresses or masks.
+            Self::Const(value) if (-0x1000..0x1000).contains(&(*value as i64)) => {
+                write!(f, "{}", *value as i64)
+            }
+            Self::Const(value) => write!(f, "{value:#x}"),
+            Self::Str(text) => write!(f, "{text:?}"),
+            Self::Var(name) => f.write_str(name),
+            Self::Addr(name) => write!(f, "&{name}"),
+            Self::Load(addr, width) => {
+                // Globals are read by their name.
+                if let Self::Addr(ref name) = **addr {
+                    return f.write_str(name);
+                }
+
+                match int_type(*width) {
+                    Some(ty) => write!(f, "*({ty} *)")?,
+                    None => f.write_str("*")?,
+                }
+                operand(f, addr)
+            }
+            Self::Not(expr) => {
+                f.write_str("~")?;
+                operand(f, expr)
+            }
+            Self::Neg(expr) => {
+                f.write_str("-")?;
+                operand(f, expr)
+            }
+            Self::Binary(op, lhs, rhs) => {
+                operand(f, lhs)?;
+
+                // Negative offsets are subtracted, `rdi - 8` instead of `rdi + -8`.
+                if let (BinOp::Add, Self::Const(value)) = (op, &**rhs) {
+                    if (*value as i64) < 0 {
+                        return write!(f, " - {}", Self::Const(value.wrapping_neg()));
+                    }
+                }
+
+                write!(f, " {} ", op.symbol())?;
+                operand(f, rhs)
+            }
+            Self::Call(target, args) => {
+                // Calls bind tighter than dereferencing the pointer that's called.
+                match **target {
+                    Self::Addr(ref name) => f.write_str(name)?,
+                    Self::Load(ref addr, _) if matches!(**addr, Self::Addr(_)) => {
+                        write!(f, "{target}")?
+                    }
+                    Self::Var(_) | Self::Const(_) => write!(f, "{target}")?,
+                    ref target => write!(f, "({target})")?,
+                }
+
+                f.write_str("(")?;
+                for (idx, arg) in args.iter().enumerate() {
+                    if idx != 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{arg}")?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Stmt {
+    Assign(String, Expr),
+    /// Write of a value that's `width` bytes wide to an address.
+    Store(Expr, usize, Expr),
+    /// Expression evaluated for it's side effects, such as a call to a function without a
+    /// return value.
+    Expr(Expr),
+    /// Jump to a block of the function if a condition holds.
+    If(Expr, PhysAddr),
+    Goto(PhysAddr),
+    /// Jump to a computed address or to another function.
+    Jump(Expr),
+    Return(Option<Expr>),
+    /// Instruction that isn't lifted.
+    Asm(String),
+}
+
+impl Stmt {
+    /// Whether execution doesn't continue after the statement.
+    fn is_terminator(&self) -> bool {
+        matches!(self, Self::Goto(_) | Self::Jump(_) | Self::Return(_))
+    }
+}
+
+/// Label of the block starting at `addr`.
+fn label(addr: PhysAddr) -> String {
+    format!("label_{addr:x}")
+}
+
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Assign(name, value) => write!(f, "{name} = {value};"),
+            Self::Store(addr, width, value) => {
+                let target = Expr::Load(Box::new(addr.clone()), *width);
+                write!(f, "{target} = {value};")
+            }
+            Self::Expr(expr) => write!(f, "{expr};"),
+            Self::If(cond, target) => write!(f, "if ({cond}) goto {};", label(*target)),
+            Self::Goto(target) => write!(f, "goto {};", label(*target)),
+            Self::Jump(Expr::Addr(name)) => write!(f, "goto {name};"),
+            Self::Jump(target) => {
+                f.write_str("goto *")?;
+                operand(f, target)?;
+                f.write_str(";")
+            }
+            Self::Return(Some(value)) => write!(f, "return {value};"),
+            Self::Return(None) => f.write_str("return;"),
+            Self::Asm(text) => write!(f, "asm({text:?});"),
+        }
+    }
+}
+
+/// What the flags were last set by, so conditional instructions can be lifted into a comparison.
+#[derive(Debug, Clone)]
+enum Flags {
+    /// Comparison of two values, also set by subtracting one from the other.
+    Cmp(Expr, Expr),
+    /// Bitwise and of two values.
+    Test(Expr, Expr),
+    /// Result of an arithmetic instruction.
+    Result(Expr),
+}
+
+impl Flags {
+    fn uses(&self, name: &str) -> bool {
+        match self {
+            Self::Cmp(lhs, rhs) | Self::Test(lhs, rhs) => lhs.uses(name) || rhs.uses(name),
+            Self::Result(expr) => expr.uses(name),
+        }
+    }
+
+    fn loads(&self) -> bool {
+        match self {
+            Self::Cmp(lhs, rhs) | Self::Test(lhs, rhs) => lhs.loads() || rhs.loads(),
+            Self::Result(expr) => expr.loads(),
+        }
+    }
+
+    /// Comparison that holds if `cc` is true, None if it can't be expressed as one.
+    fn condition(&self, cc: ConditionCode) -> Option<Expr> {
+        let zero = Expr::Const(0);
+        let (lhs, rhs, compared) = match self.clone() {
+            Self::Cmp(lhs, rhs) => (lhs, rhs, true),
+            Self::Test(lhs, rhs) if lhs == rhs => (lhs, zero, false),
+            Self::Test(lhs, rhs) => (Expr::binary(BinOp::And, lhs, rhs), zero, false),
+            Self::Result(expr) => (expr, zero, false),
+        };
+
+        let op = match cc {
+            ConditionCode::Z => BinOp::Eq,
+            ConditionCode::NZ => BinOp::Ne,
+            ConditionCode::B | ConditionCode::L if compared => BinOp::Lt,
+            ConditionCode::AE | ConditionCode::GE if compared => BinOp::Ge,
+            ConditionCode::A | ConditionCode::G if compared => BinOp::Gt,
+            ConditionCode::BE | ConditionCode::LE if compared => BinOp::Le,
+            // Tests and arithmetic clear the overflow flag, so only the sign is compared.
+            ConditionCode::S | ConditionCode::L if !compared => BinOp::Lt,
+            ConditionCode::NS | ConditionCode::GE if !compared => BinOp::Ge,
+            ConditionCode::G if !compared => BinOp::Gt,
+            ConditionCode::LE if !compared => BinOp::Le,
+            ConditionCode::S => {
+                let diff = Expr::binary(BinOp::Sub, lhs, rhs);
+                return Some(Expr::binary(BinOp::Lt, diff, Expr::Const(0)));
+            }
+            ConditionCode::NS => {
+                let diff = Expr::binary(BinOp::Sub, lhs, rhs);
+                return Some(Expr::binary(BinOp::Ge, diff, Expr::Const(0)));
+            }
+            _ => return None,
+        };
+
+        Some(Expr::binary(op, lhs, rhs))
+    }
+}
+
+/// Statements of a basic block being lifted.
+///
+/// Registers aren't assigned when they're written, instead their value is substituted where
+/// they're read. They're only assigned once their value would otherwise be lost or the block
+/// ends.
+struct Lifter {
+    stmts: Vec<(PhysAddr, Stmt)>,
+    /// Registers written but not yet assigned, with the instruction that wrote them.
+    pending: Vec<(PhysAddr, String, Expr)>,
+    /// Registers written since the block started or the last call.
+    written: BTreeSet<String>,
+    flags: Option<Flags>,
+    /// Registers holding a parameter until they're written, with the parameter's name.
+    params: Vec<(String, String)>,
+}
+
+impl Lifter {
+    fn new(params: Vec<(String, String)>) -> Self {
+        Self {
+            stmts: Vec::new(),
+            pending: Vec::new(),
+            written: BTreeSet::new(),
+            flags: None,
+            params,
+        }
+    }
+
+    /// Current value of the register or variable `name`.
+    fn read(&self, name: &str) -> Expr {
+        if let Some((_, _, value)) = self.pending.iter().rev().find(|(_, reg, _)| reg == name) {
+            return value.clone();
+        }
+
+        match self.params.iter().find(|(reg, _)| reg == name) {
+            Some((_, param)) => Expr::Var(param.clone()),
+            None => Expr::Var(name.to_string()),
+        }
+    }
+
+    /// Assign the pending registers whose value `pred` holds for, in the order they're written.
+    /// Returns the registers assigned together with their value.
+    fn flush_if(&mut self, pred: impl Fn(&Expr) -> bool) -> Vec<(String, Expr)> {
+        let (flushed, kept) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, _, value)| pred(value));
+
+        self.pending = kept;
+        flushed
+            .into_iter()
+            .map(|(addr, reg, value)| {
+                self.params.retain(|(param, _)| *param != reg);
+                self.stmts.push((addr, Stmt::Assign(reg.clone(), value.clone())));
+                (reg, value)
+            })
+            .collect()
+    }
+
+    fn flush(&mut self) -> Vec<(String, Expr)> {
+        self.flush_if(|_| true)
+    }
+
+    /// Variable `name` is about to change, so anything that read it's current value has to be
+    /// assigned before.
+    fn invalidate(&mut self, name: &str) {
+        self.flush_if(|value| value.uses(name));
+        self.params.retain(|(reg, _)| reg != name);
+        if self.flags.as_ref().is_some_and(|flags| flags.uses(name)) {
+            self.flags = None;
+        }
+    }
+
+    /// Write `value` to register `reg`, which is propagated into the instructions reading it.
+    fn write(&mut self, addr: PhysAddr, reg: &str, value: Expr) {
+        // Any earlier value that wasn't read is overwritten.
+        self.pending.retain(|(_, pending, _)| pending != reg);
+        self.invalidate(reg);
+        self.written.insert(reg.to_string());
+        self.pending.push((addr, reg.to_string(), value));
+    }
+
+    /// Assign `value` to the local variable `name`.
+    fn assign(&mut self, addr: PhysAddr, name: &str, value: Expr) {
+        self.invalidate(name);
+        self.stmts.push((addr, Stmt::Assign(name.to_string(), value)));
+    }
+
+    /// Store `value` at `ptr`.
+    fn store(&mut self, addr: PhysAddr, ptr: Expr, width: usize, value: Expr) {
+        // Values read from memory before might be overwritten.
+        let flushed = self.flush_if(Expr::loads);
+        if self.flags.as_ref().is_some_and(Flags::loads) {
+            self.flags = None;
+        }
+
+        let ptr = reassigned(ptr, &flushed);
+        let value = reassigned(value, &flushed);
+        self.stmts.push((addr, Stmt::Store(ptr, width, value)));
+    }
+
+    /// Call `target` with the arguments passed in `regs`, of which the first `count` are used.
+    /// If the count isn't known, the arguments are those written before the call.
+    fn call(
+        &mut self,
+        addr: PhysAddr,
+        target: Expr,
+        regs: &[&str],
+        count: Option<usize>,
+        returns: bool,
+    ) {
+        let count = count.unwrap_or_else(|| {
+            regs.iter().take_while(|reg| self.written.contains(**reg)).count()
+        });
+
+        let args: Vec<Expr> = regs.iter().take(count).map(|reg| self.read(reg)).collect();
+
+        // Arguments are passed in registers the call overwrites, as is the one it returns in.
+        self.pending.retain(|(_, reg, _)| !regs.contains(&reg.as_str()) && reg != RETURN_REGISTER);
+        let flushed = self.flush();
+        let args = args.into_iter().map(|arg| reassigned(arg, &flushed)).collect();
+        self.flags = None;
+        for reg in regs {
+            self.written.remove(*reg);
+        }
+
+        let call = Expr::Call(Box::new(target), args);
+        let stmt = if returns {
+            self.invalidate(RETURN_REGISTER);
+            Stmt::Assign(RETURN_REGISTER.to_string(), call)
+        } else {
+            Stmt::Expr(call)
+        };
+
+        self.stmts.push((addr, stmt));
+    }
+
+    /// Jump to `target` if `cond` holds, which ends the block.
+    fn branch(&mut self, addr: PhysAddr, cond: Expr, target: PhysAddr) {
+        // Registers are assigned before the branch, so the condition reads them instead of
+        // what they're assigned.
+        let values: Vec<(String, Expr)> =
+            self.pending.iter().map(|(_, reg, value)| (reg.clone(), value.clone())).collect();
+        let mut cond = reassigned(cond, &values);
+
+        // The condition still reads what some registers held before being assigned.
+        if self.pending.iter().any(|(_, reg, _)| cond.uses(reg)) {
+            self.stmts.push((addr, Stmt::Assign("cond".to_string(), cond)));
+            cond = Expr::Var("cond".to_string());
+        }
+
+        self.flush();
+        self.stmts.push((addr, Stmt::If(cond, target)));
+    }
+
+    /// Lifted instruction that leaves the function.
+    fn exit(&mut self, addr: PhysAddr, stmt: Stmt) {
+        self.stmts.push((addr, stmt));
+        self.pending.clear();
+    }
+
+    /// Instruction that isn't lifted, which could read or write anything.
+    fn asm(&mut self, addr: PhysAddr, text: String) {
+        self.flush();
+        self.flags = None;
+        self.params.clear();
+        self.stmts.push((addr, Stmt::Asm(text)));
+    }
+
+    fn finish(mut self) -> Vec<(PhysAddr, Stmt)> {
+        self.flush();
+        self.stmts
+    }
+}
+
+/// `expr` reading the registers just assigned `values` instead of the values themselves.
+fn reassigned(mut expr: Expr, values: &[(String, Expr)]) -> Expr {
+    for (reg, value) in values {
+        if !matches!(value, Expr::Var(_) | Expr::Const(_)) {
+            expr = expr.replace(value, &Expr::Var(reg.clone()));
+        }
+    }
+
+    expr
+}
+
+/// Register functions return their value in.
+const RETURN_REGISTER: &str = "rax";
+
+/// Line of pseudocode, together with the instruction it's lifted from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub addr: PhysAddr,
+    pub text: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Pseudocode {
+    /// Address of the function.
+    pub func: PhysAddr,
+    pub lines: Vec<Line>,
+}
+
+impl Pseudocode {
+    /// Lines lifted from the instruction at `addr`.
+    pub fn lines_by_addr(&self, addr: PhysAddr) -> impl Iterator<Item = usize> + '_ {
+        self.lines.iter().enumerate().filter(move |(_, line)| line.addr == addr).map(|(idx, _)| idx)
+    }
+}
+
+impl fmt::Display for Pseudocode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Function made of the statements of each block of `cfg`, connected by gotos where blocks
+/// don't fall through into the next one.
+fn emit(
+    func: PhysAddr,
+    header: String,
+    cfg: &ControlFlowGraph,
+    mut blocks: Vec<Vec<(PhysAddr, Stmt)>>,
+) -> Pseudocode {
+    for (idx, stmts) in blocks.iter_mut().enumerate() {
+        if stmts.last().is_some_and(|(_, stmt)| stmt.is_terminator()) {
+            continue;
+        }
+
+        let always: Vec<_> =
+            cfg.successors(idx).filter(|edge| edge.kind == EdgeKind::Always).collect();
+        let next = cfg.successors(idx).find(|edge| edge.kind == EdgeKind::False).or(
+            // Jump tables have many targets, which are shown by the jump itself.
+            match always[..] {
+                [edge] => Some(edge),
+                _ => None,
+            },
+        );
+
+        if let Some(edge) = next.filter(|edge| edge.to != idx + 1) {
+            let addr = *cfg.blocks[idx].instructions.last().unwrap();
+            stmts.push((addr, Stmt::Goto(cfg.blocks[edge.to].start())));
+        }
+    }
+
+    let targets: BTreeSet<PhysAddr> = blocks
+        .iter()
+        .flatten()
+        .filter_map(|(_, stmt)| match stmt {
+            Stmt::If(_, target) | Stmt::Goto(target) => Some(*target),
+            _ => None,
+        })
+        .collect();
+
+    let mut lines = vec![Line {
+        addr: func,
+        text: format!("{header} {{"),
+    }];
+
+    for (block, stmts) in cfg.blocks.iter().zip(blocks) {
+        if targets.contains(&block.start()) {
+            lines.push(Line {
+                addr: block.start(),
+                text: format!("{}:", label(block.start())),
+            });
+        }
+
+        for (addr, stmt) in stmts {
+            lines.push(Line {
+                addr,
+                text: format!("    {stmt}"),
+            });
+        }
+    }
+
+    lines.push(Line {
+        addr: func,
+        text: "}".to_string(),
+    });
+
+    Pseudocode { func, lines }
+}
+
+/// Name of the 64-bit register `reg` is part of, or it's own name if it isn't a general purpose
+/// register.
+fn full_register(reg: RegSpec) -> &'static str {
+    let class = reg.class();
+    if class == register_class::Q
+        || class == register_class::D
+        || class == register_class::W
+        || class == register_class::RB
+    {
+        RegSpec::q(reg.num()).name()
+    } else if class == register_class::B {
+        // ah through bh are the second byte of rax through rbx.
+        RegSpec::q(reg.num() & 0b11).name()
+    } else {
+        reg.name()
+    }
+}
+
+/// Name of the stack variable at `disp` from `base`, if it's the stack or frame pointer.
+fn local(base: RegSpec, disp: i32) -> Option<String> {
+    match full_register(base) {
+        "rbp" if disp < 0 => Some(format!("var_{:x}", disp.unsigned_abs())),
+        "rsp" if disp >= 0 => Some(format!("stack_{disp:x}")),
+        _ => None,
+    }
+}
+
+/// Where an operand is read from or written to.
+enum Place {
+    Reg(&'static str),
+    Local(String),
+    Memory(Expr, usize),
+}
+
+impl Processor {
+    /// Experimental pseudo-C of the function starting at `func`.
+    ///
+    /// Returns `None` if there isn't an instruction at `func`.
+    pub fn pseudocode(&self, func: PhysAddr) -> Option<Pseudocode> {
+        let cfg = self.cfg(func)?;
+        let code = self.code.read().unwrap();
+        let prototype = self.prototype(func);
+
+        let header = match prototype {
+            Some(ref prototype) => prototype.to_string(),
+            None => match self.index.get_sym_by_addr(func) {
+                Some(sym) => format!("uint64_t {}()", self.shown_symbol(func, sym).as_str()),
+                None => format!("uint64_t sub_{func:x}()"),
+            },
+        };
+
+        let regs: Vec<&'static str> = match self.convention {
+            Some(convention) => convention.registers().iter().map(|names| names[0]).collect(),
+            None => Vec::new(),
+        };
+
+        let params: Vec<(String, String)> = match prototype {
+            Some(ref prototype) => (0..prototype.params.len().min(regs.len()))
+                .map(|idx| (regs[idx].to_string(), prototype.arg_name(idx)))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let ctx = Context {
+            code: &code,
+            cfg: &cfg,
+            regs: &regs,
+            returns: prototype.is_none_or(|prototype| prototype.ret != "void"),
+        };
+
+        let mut blocks = Vec::with_capacity(cfg.blocks.len());
+
+        for (idx, block) in cfg.blocks.iter().enumerate() {
+            let mut lifter = Lifter::new(if idx == 0 { params.clone() } else { Vec::new() });
+
+            for &addr in &block.instructions {
+                let inst = match code.instruction_by_addr(addr) {
+                    Some(inst) => inst,
+                    None => continue,
+                };
+
+                let lifted = match self.arch {
+                    Architecture::X86_64 => {
+                        let inst = unsafe { &inst.x64 };
+                        self.lift_x64(&mut lifter, &ctx, addr, inst).is_some()
+                    }
+                    _ => false,
+                };
+
+                if !lifted {
+                    lifter.asm(addr, self.instruction_text(inst));
+                }
+            }
+
+            blocks.push(lifter.finish());
+        }
+
+        Some(emit(func, header, &cfg, blocks))
+    }
+
+    /// String stored at `addr`, if it's in a section holding data.
+    fn string_at(&self, addr: PhysAddr) -> Option<String> {
+        let section = self.section_by_addr(addr)?;
+        if !matches!(section.kind, SectionKind::Raw | SectionKind::CString | SectionKind::Data) {
+            return None;
+        }
+
+        let bytes = section.bytes_by_addr(addr, MAX_REFERENCED_LEN);
+        c_string(bytes, CONFIG.search.min_string_len.max(1)).map(str::to_string)
+    }
+
+    /// Constant address, by what's stored there if it's known.
+    fn address(&self, addr: PhysAddr) -> Expr {
+        if let Some(text) = self.string_at(addr) {
+            return Expr::Str(text);
+        }
+
+        match self.index.get_sym_by_addr(addr) {
+            Some(sym) => Expr::Addr(self.shown_symbol(addr, sym).as_str().to_string()),
+            None => Expr::Const(addr as u64),
+        }
+    }
+}
+
+/// What lifting an instruction needs to know about the function it's part of.
+struct Context<'a> {
+    code: &'a Code,
+    cfg: &'a ControlFlowGraph,
+    /// Registers arguments are passed in.
+    regs: &'a [&'static str],
+    /// Whether the function returns a value.
+    returns: bool,
+}
+
+/// Immediate operand `op` of `inst` at `addr` as it's encoded, extended to 64 bits.
+fn immediate(inst: &x64::Instruction, addr: PhysAddr, op: &Operand) -> Option<u64> {
+    let imm = inst.encoded_imm(addr);
+    Some(match op {
+        Operand::ImmediateI8(_) => imm as i8 as i64 as u64,
+        Operand::ImmediateU8(_) => imm as u8 as u64,
+        Operand::ImmediateI16(_) => imm as i16 as i64 as u64,
+        Operand::ImmediateU16(_) => imm as u16 as u64,
+        Operand::ImmediateI32(_) => imm as i32 as i64 as u64,
+        Operand::ImmediateU32(_) => imm as u32 as u64,
+        Operand::ImmediateI64(_) | Operand::ImmediateU64(_) => imm,
+        _ => return None,
+    })
+}
+
+/// Operation of arithmetic instructions that write their first operand.
+fn arithmetic(opcode: Opcode) -> Option<BinOp> {
+    Some(match opcode {
+        Opcode::ADD => BinOp::Add,
+        Opcode::SUB => BinOp::Sub,
+        Opcode::IMUL => BinOp::Mul,
+        Opcode::AND => BinOp::And,
+        Opcode::OR => BinOp::Or,
+        Opcode::XOR => BinOp::Xor,
+        Opcode::SHL | Opcode::SAL => BinOp::Shl,
+        Opcode::SHR | Opcode::SAR => BinOp::Shr,
+        _ => return None,
+    })
+}
+
+impl Processor {
+    /// Where the operand `op` of the instruction at `addr` is.
+    fn place(
+        &self,
+        lifter: &Lifter,
+        addr: PhysAddr,
+        inst: &x64::Instruction,
+        op: Operand,
+    ) -> Option<Place> {
+        let reg = |reg: RegSpec| lifter.read(full_register(reg));
+        let offset = |disp: i32| Expr::Const(disp as i64 as u64);
+        let scale = |idx: RegSpec, factor: u8| {
+            Expr::binary(BinOp::Mul, reg(idx), Expr::Const(factor as u64))
+        };
+
+        let ptr = match op {
+            Operand::Register(reg) => return Some(Place::Reg(full_register(reg))),
+            Operand::RegDisp(RegSpec::RIP, disp) => {
+                let next = addr + inst.width();
+                self.address(next.wrapping_add_signed(disp as isize))
+            }
+            Operand::RegDeref(base) => match local(base, 0) {
+                Some(name) => return Some(Place::Local(name)),
+                None => reg(base),
+            },
+            Operand::RegDisp(base, disp) => match local(base, disp) {
+                Some(name) => return Some(Place::Local(name)),
+                None => Expr::binary(BinOp::Add, reg(base), offset(disp)),
+            },
+            Operand::DisplacementU32(disp) => self.address(disp as PhysAddr),
+            Operand::DisplacementU64(disp) => self.address(disp as PhysAddr),
+            Operand::RegScale(idx, s) => scale(idx, s),
+            Operand::RegScaleDisp(idx, s, disp) => {
+                Expr::binary(BinOp::Add, scale(idx, s), offset(disp))
+            }
+            Operand::RegIndexBase(base, idx) => Expr::binary(BinOp::Add, reg(base), reg(idx)),
+            Operand::RegIndexBaseDisp(base, idx, disp) => {
+                let ptr = Expr::binary(BinOp::Add, reg(base), reg(idx));
+                Expr::binary(BinOp::Add, ptr, offset(disp))
+            }
+            Operand::RegIndexBaseScale(base, idx, s) => {
+                Expr::binary(BinOp::Add, reg(base), scale(idx, s))
+            }
+            Operand::RegIndexBaseScaleDisp(base, idx, s, disp) => {
+                let ptr = Expr::binary(BinOp::Add, reg(base), scale(idx, s));
+                Expr::binary(BinOp::Add, ptr, offset(disp))
+            }
+            _ => return None,
+        };
+
+        let width = inst.mem_size().and_then(|size| size.bytes_size()).unwrap_or(0);
+        Some(Place::Memory(ptr, width as usize))
+    }
+
+    /// Value of the operand `op` of the instruction at `addr`.
+    fn value(
+        &self,
+        lifter: &Lifter,
+        addr: PhysAddr,
+        inst: &x64::Instruction,
+        op: Operand,
+    ) -> Option<Expr> {
+        if let Some(imm) = immediate(inst, addr, &op) {
+            return Some(Expr::Const(imm));
+        }
+
+        Some(match self.place(lifter, addr, inst, op)? {
+            Place::Reg(reg) => lifter.read(reg),
+            Place::Local(name) => lifter.read(&name),
+            Place::Memory(ptr, width) => Expr::Load(Box::new(ptr), width),
+        })
+    }
+
+    /// Lift the instruction at `addr`, None if it isn't understood.
+    fn lift_x64(
+        &self,
+        lifter: &mut Lifter,
+        ctx: &Context,
+        addr: PhysAddr,
+        inst: &x64::Instruction,
+    ) -> Option<()> {
+        let opcode = inst.opcode();
+        let place = |lifter: &Lifter, idx: u8| self.place(lifter, addr, inst, inst.operand(idx));
+        let value = |lifter: &Lifter, idx: u8| self.value(lifter, addr, inst, inst.operand(idx));
+        let xref = |kind: XrefKind| {
+            ctx.code.xrefs.from(addr).iter().find(|xref| xref.kind == kind).map(|xref| xref.to)
+        };
+
+        match opcode {
+            // Saving registers and setting up the stack frame isn't shown.
+            Opcode::NOP | Opcode::ENDBR64 | Opcode::PUSH | Opcode::POP | Opcode::LEAVE => {}
+            // Sign extending the accumulator doesn't change what it holds.
+            Opcode::CDQE | Opcode::CWDE => {}
+            Opcode::MOV | Opcode::MOVZX | Opcode::MOVSX | Opcode::MOVSXD => {
+                let dst = place(lifter, 0)?;
+                let src = value(lifter, 1)?;
+                write(lifter, addr, dst, src);
+            }
+            Opcode::LEA => {
+                let dst = place(lifter, 0)?;
+                let src = match place(lifter, 1)? {
+                    Place::Memory(ptr, _) => ptr,
+                    Place::Local(name) => Expr::Addr(name),
+                    Place::Reg(_) => return None,
+                };
+                write(lifter, addr, dst, src);
+            }
+            Opcode::CMP => {
+                lifter.flags = Some(Flags::Cmp(value(lifter, 0)?, value(lifter, 1)?));
+            }
+            Opcode::TEST => {
+                lifter.flags = Some(Flags::Test(value(lifter, 0)?, value(lifter, 1)?));
+            }
+            Opcode::IMUL if inst.operand_count() == 3 => {
+                let dst = place(lifter, 0)?;
+                let result = Expr::binary(BinOp::Mul, value(lifter, 1)?, value(lifter, 2)?);
+                write(lifter, addr, dst, result.clone());
+                lifter.flags = Some(Flags::Result(result));
+            }
+            opcode if arithmetic(opcode).is_some() && inst.operand_count() == 2 => {
+                let dst = place(lifter, 0)?;
+                let lhs = value(lifter, 0)?;
+                let rhs = value(lifter, 1)?;
+                let result = Expr::binary(arithmetic(opcode)?, lhs.clone(), rhs.clone());
+                write(lifter, addr, dst, result.clone());
+                lifter.flags = Some(match opcode {
+                    Opcode::SUB => Flags::Cmp(lhs, rhs),
+                    _ => Flags::Result(result),
+                });
+            }
+            Opcode::INC | Opcode::DEC | Opcode::NEG | Opcode::NOT => {
+                let dst = place(lifter, 0)?;
+                let src = value(lifter, 0)?;
+                let result = match opcode {
+                    Opcode::INC => Expr::binary(BinOp::Add, src, Expr::Const(1)),
+                    Opcode::DEC => Expr::binary(BinOp::Sub, src, Expr::Const(1)),
+                    Opcode::NEG => Expr::Neg(Box::new(src)),
+                    _ => Expr::Not(Box::new(src)),
+                };
+                write(lifter, addr, dst, result.clone());
+                if opcode != Opcode::NOT {
+                    lifter.flags = Some(Flags::Result(result));
+                }
+            }
+            opcode if opcode.is_setcc() => {
+                let dst = place(lifter, 0)?;
+                let cond = lifter.flags.as_ref()?.condition(opcode.condition()?)?;
+                write(lifter, addr, dst, cond);
+            }
+            Opcode::CALL => {
+                let (target, prototype) = match xref(XrefKind::Call) {
+                    Some(target) => (self.address(target), self.prototype(target)),
+                    None => (value(lifter, 0)?, None),
+                };
+
+                let (count, returns) = match prototype {
+                    Some(ref prototype) if !prototype.variadic => {
+                        (Some(prototype.params.len()), prototype.ret != "void")
+                    }
+                    Some(ref prototype) => (None, prototype.ret != "void"),
+                    None => (None, true),
+                };
+
+                lifter.call(addr, target, ctx.regs, count, returns);
+            }
+            Opcode::JMP => match xref(XrefKind::Jump) {
+                // Jumps within the function are the edges between it's blocks.
+                Some(target) if ctx.cfg.block_by_addr(target).is_some() => {}
+                Some(target) => lifter.exit(addr, Stmt::Jump(self.address(target))),
+                None => {
+                    let target = value(lifter, 0)?;
+                    lifter.exit(addr, Stmt::Jump(target));
+                }
+            },
+            Opcode::RETURN => {
+                let ret = ctx.returns.then(|| lifter.read(RETURN_REGISTER));
+                lifter.exit(addr, Stmt::Return(ret));
+            }
+            opcode if opcode.is_jcc() => {
+                let target = xref(XrefKind::Jump)?;
+                let cc = opcode.condition()?;
+                let cond = match lifter.flags {
+                    Some(ref flags) => flags.condition(cc),
+                    None => None,
+                };
+
+                // Flags set in a way that isn't lifted are named after the condition.
+                let cond = cond.unwrap_or_else(|| {
+                    Expr::Var(format!("flags_{}", format!("{cc:?}").to_ascii_lowercase()))
+                });
+
+                lifter.branch(addr, cond, target);
+            }
+            _ => return None,
+        }
+
+        Some(())
+    }
+}
+
+/// Write `value` to `place`, except to the stack and frame pointers which only change for the
+/// stack frame.
+fn write(lifter: &mut Lifter, addr: PhysAddr, place: Place, value: Expr) {
+    match place {
+        Place::Reg("rsp") => {}
+        Place::Reg("rbp") if value == lifter.read("rsp") => {}
+        Place::Reg(reg) => lifter.write(addr, reg, value),
+        Place::Local(name) => lifter.assign(addr, &name, value),
+        Place::Memory(ptr, width) => lifter.store(addr, ptr, width, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BasicBlock, Edge};
+
+    fn var(name: &str) -> Expr {
+        Expr::Var(name.to_string())
+    }
+
+    #[test]
+    fn expressions() {
+        let ptr = Expr::binary(BinOp::Add, var("rdi"), Expr::Const(8));
+        let ptr = Expr::binary(BinOp::Sub, ptr, Expr::Const(12));
+        assert_eq!(ptr.to_string(), "rdi - 4");
+        assert_eq!(Expr::binary(BinOp::Xor, var("rax"), var("rax")), Expr::Const(0));
+
+        let load = Expr::Load(Box::new(ptr), 4);
+        let sum = Expr::binary(BinOp::Add, load, Expr::Const(1));
+        assert_eq!(sum.to_string(), "*(uint32_t *)(rdi - 4) + 1");
+
+        let global = Expr::Load(Box::new(Expr::Addr("counter".to_string())), 8);
+        assert_eq!(global.to_string(), "counter");
+        let puts = Expr::Addr("puts".to_string());
+        let call = Expr::Call(Box::new(puts), vec![Expr::Str("hi\n".to_string())]);
+        assert_eq!(call.to_string(), "puts(\"hi\\n\")");
+
+        assert_eq!(Expr::Const(0x401000).to_string(), "0x401000");
+        assert_eq!(Expr::Const(-1i64 as u64).to_string(), "-1");
+    }
+
+    #[test]
+    fn propagation() {
+        // mov eax, [rdi]; add eax, 1; mov [rsi], eax; cmp eax, 10; jl 0x20
+        let mut lifter = Lifter::new(vec![("rdi".to_string(), "src".to_string())]);
+        let loaded = Expr::Load(Box::new(lifter.read("rdi")), 4);
+        lifter.write(0x0, "rax", loaded);
+        let sum = Expr::binary(BinOp::Add, lifter.read("rax"), Expr::Const(1));
+        lifter.write(0x2, "rax", sum);
+        let (ptr, value) = (lifter.read("rsi"), lifter.read("rax"));
+        lifter.store(0x5, ptr, 4, value);
+        lifter.flags = Some(Flags::Cmp(lifter.read("rax"), Expr::Const(10)));
+        let cond = lifter.flags.as_ref().unwrap().condition(ConditionCode::L).unwrap();
+        lifter.branch(0x9, cond, 0x20);
+
+        let stmts: Vec<String> =
+            lifter.finish().iter().map(|(_, stmt)| stmt.to_string()).collect();
+        assert_eq!(
+            stmts,
+            [
+                "rax = *(uint32_t *)src + 1;",
+                "*(uint32_t *)rsi = rax;",
+                "if (rax < 10) goto label_20;"
+            ]
+        );
+
+        // mov edi, 1; mov rsi, rbx; xor eax, eax; call printf; test eax, eax; je 0x40
+        let mut lifter = Lifter::new(Vec::new());
+        lifter.write(0x0, "rdi", Expr::Const(1));
+        let rbx = lifter.read("rbx");
+        lifter.write(0x5, "rsi", rbx);
+        lifter.write(0x8, "rax", Expr::Const(0));
+        let printf = Expr::Addr("printf".to_string());
+        lifter.call(0xa, printf, &["rdi", "rsi", "rdx"], None, true);
+        lifter.flags = Some(Flags::Test(lifter.read("rax"), lifter.read("rax")));
+        let cond = lifter.flags.as_ref().unwrap().condition(ConditionCode::Z).unwrap();
+        lifter.branch(0x11, cond, 0x40);
+
+        let stmts: Vec<String> =
+            lifter.finish().iter().map(|(_, stmt)| stmt.to_string()).collect();
+        assert_eq!(stmts, ["rax = printf(1, rbx);", "if (rax == 0) goto label_40;"]);
+    }
+
+    #[test]
+    fn gotos() {
+        // 0x0: if (...) goto 0x10; 0x8: return; 0x10: loops back to 0x8
+        let cfg = ControlFlowGraph {
+            blocks: [0x0, 0x8, 0x10]
+                .map(|addr| BasicBlock {
+                    instructions: vec![addr],
+                })
+                .to_vec(),
+            edges: vec![
+                Edge { from: 0, to: 2, kind: EdgeKind::True },
+                Edge { from: 0, to: 1, kind: EdgeKind::False },
+                Edge { from: 2, to: 1, kind: EdgeKind::Always },
+            ],
+        };
+
+        let blocks = vec![
+            vec![(0x0, Stmt::If(var("cond"), 0x10))],
+            vec![(0x8, Stmt::Return(None))],
+            vec![(0x10, Stmt::Asm("nop".to_string()))],
+        ];
+
+        let code = emit(0x0, "void f(void)".to_string(), &cfg, blocks);
+        assert_eq!(
+            code.to_string(),
+            "void f(void) {\n\
+             \x20   if (cond) goto label_10;\n\
+             label_8:\n\
+             \x20   return;\n\
+             label_10:\n\
+             \x20   asm(\"nop\");\n\
+             \x20   goto label_8;\n\
+             }\n"
+        );
+        assert_eq!(code.lines_by_addr(0x10).count(), 3);
+    }
+}
@@ -0,0 +1,268 @@
+//! Files without an object header, such as firmware images and shellcode, which are loaded as a
+//! single code section at an address given by the user. Intel HEX, SREC and TI-TXT files are
+//! loaded as a code section per region of memory they describe instead.
+
+use crate::{
+    entrypoints, firmware, CallingConvention, Error, Headers, Isa, Loading, Processor, Stage,
+};
+use debugvault::Index;
+use memmap2::{Mmap, MmapMut};
+use object::{Architecture, Endianness};
+use processor_shared::{AddressMap, PhysAddr, Section, SectionKind, Segment};
+use std::path::Path;
+
+/// Architectures raw binaries can be decoded as, by the name they're given on the command line.
+pub const ARCHITECTURES: &[(&str, Architecture)] = &[
+    ("x86", Architecture::I386),
+    ("x86_64", Architecture::X86_64),
+    ("armv7", Architecture::Arm),
+    ("aarch64", Architecture::Aarch64),
+    ("riscv32", Architecture::Riscv32),
+    ("riscv64", Architecture::Riscv64),
+    ("mips", Architecture::Mips),
+    ("mips64", Architecture::Mips64),
+    ("avr", Architecture::Avr),
+    ("msp430", Architecture::Msp430),
+    ("bpf", Architecture::Bpf),
+];
+
+/// Architecture named `name`, either as in [`ARCHITECTURES`] or by a common alias like `arm64`.
+pub fn parse_arch(name: &str) -> Option<Architecture> {
+    let name = name.to_ascii_lowercase();
+    let name = match name.as_str() {
+        "i386" | "i686" | "x86_32" => "x86",
+        "x64" | "amd64" => "x86_64",
+        "arm" => "armv7",
+        "arm64" | "armv8" => "aarch64",
+        "mips32" => "mips",
+        "ebpf" => "bpf",
+        name => name,
+    };
+
+    ARCHITECTURES.iter().find(|(n, _)| *n == name).map(|(_, arch)| *arch)
+}
+
+/// How to interpret a file that has no object header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawOptions {
+    /// Instruction set the file is decoded as, which also determines it's bitness.
+    pub arch: Architecture,
+
+    /// Endianness of the data, instructions are decoded the same regardless.
+    pub endianness: Endianness,
+
+    /// Address the first byte of the file is loaded at, which is also where execution starts.
+    /// Ignored for Intel HEX, SREC and TI-TXT files, as they store where each of their records go.
+    pub base: PhysAddr,
+}
+
+impl RawOptions {
+    /// File of `arch` loaded at address zero, in the endianness `arch` is most often run in.
+    pub fn new(arch: Architecture) -> Self {
+        let endianness = match arch {
+            Architecture::Mips | Architecture::Mips64 => Endianness::Big,
+            _ => Endianness::Little,
+        };
+
+        Self {
+            arch,
+            endianness,
+            base: 0,
+        }
+    }
+
+    /// Options as given on the command line, None if `arch` isn't a known architecture.
+    pub fn parse(arch: &str, base: Option<PhysAddr>, big_endian: Option<bool>) -> Option<Self> {
+        let mut options = Self::new(parse_arch(arch)?);
+        options.base = base.unwrap_or(0);
+        options.endianness = match big_endian {
+            Some(true) => Endianness::Big,
+            Some(false) => Endianness::Little,
+            None => options.endianness,
+        };
+
+        Some(options)
+    }
+}
+
+impl Processor {
+    /// Analyze the file at `path` as raw code, ignoring any header it might have.
+    pub fn parse_raw<P: AsRef<Path>>(path: P, options: &RawOptions) -> Result<Self, Error> {
+        Self::parse_raw_with(path, options, &Loading::default())
+    }
+
+    /// Parse the raw file at `path`, reporting progress through `loading`.
+    pub fn parse_raw_with<P: AsRef<Path>>(
+        path: P,
+        options: &RawOptions,
+        loading: &Loading,
+    ) -> Result<Self, Error> {
+        let (file, mmap, unpacking) = crate::unpack::map(path.as_ref())?;
+        let binary: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        let now = std::time::Instant::now();
+
+        let started = loading.start(Stage::Headers)?;
+        let (entrypoint, sections, segments, backing) = match firmware::Format::detect(binary) {
+            Some(format) => {
+                let image = firmware::parse(format, binary)?;
+                let memory = image_memory(&image).map_err(Error::IO)?;
+                let bytes: &'static [u8] = unsafe { std::mem::transmute(&memory[..]) };
+                let regions: Vec<_> =
+                    image.regions.iter().map(|r| (r.start, r.bytes.len())).collect();
+                let (sections, segments) = layout(bytes, &regions);
+                let first = image.regions.first().map_or(0, |region| region.start);
+                (image.entrypoint.unwrap_or(first), sections, segments, vec![memory])
+            }
+            None => {
+                let (sections, segments) = layout(binary, &[(options.base, binary.len())]);
+                (options.base, sections, segments, Vec::new())
+            }
+        };
+
+        // Microcontrollers start at the reset vector, in place of an entrypoint in a header.
+        let vectors = entrypoints::vectors(options.arch, &sections);
+        let reset = vectors.iter().find(|(_, name)| name == "reset").map(|(addr, _)| *addr);
+        let entrypoint = match options.arch {
+            Architecture::Msp430 => reset.unwrap_or(entrypoint),
+            _ => entrypoint,
+        };
+
+        let len: usize = sections.iter().map(|section| section.bytes().len()).sum();
+        loading.finish(
+            Stage::Headers,
+            started,
+            format!("{len} bytes in {} regions", sections.len()),
+        );
+
+        // There's nothing to find symbols in, but the stage is still reported as it's expected.
+        let started = loading.start(Stage::Symbols)?;
+        loading.finish(Stage::Symbols, started, "0 named functions".to_string());
+
+        log::complex!(
+            w "[processor::parse_raw] loading ",
+            w format!("{:?}", path.as_ref()),
+            w " as ",
+            b format!("{:?}", options.arch),
+            w " starting at ",
+            g format!("{entrypoint:#x}"),
+            w ".",
+        );
+
+        let mut index = Index::default();
+        let entrypoints = vectors.iter().map(|(addr, _)| *addr).collect();
+        index.insert_identified(vectors);
+
+        let headers = Headers {
+            entrypoint,
+            entrypoints,
+            modes: Vec::new(),
+            thumb_functions: Vec::new(),
+            index,
+            sections,
+            segments,
+            pointers: AddressMap::default(),
+            exports: AddressMap::default(),
+            isa: Isa::Native(options.arch),
+            endianness: options.endianness,
+            convention: CallingConvention::native(options.arch, false),
+        };
+
+        let path = path.as_ref().to_path_buf();
+        let mut processor = Self::analyze(path, file, mmap, backing, headers, now, loading)?;
+        processor.unpacking = unpacking;
+        Ok(processor)
+    }
+}
+
+/// Anonymous memory holding the regions of `image` one after another.
+fn image_memory(image: &firmware::Image) -> std::io::Result<Mmap> {
+    let len = image.regions.iter().map(|region| region.bytes.len()).sum::<usize>();
+    let mut memory = MmapMut::map_anon(len.max(1))?;
+
+    let mut offset = 0;
+    for region in image.regions.iter() {
+        memory[offset..offset + region.bytes.len()].copy_from_slice(&region.bytes);
+        offset += region.bytes.len();
+    }
+
+    memory.make_read_only()
+}
+
+/// A code section and segment for each region of `(start, len)`, whose bytes are stored one
+/// after another in `memory`.
+fn layout(memory: &'static [u8], regions: &[(PhysAddr, usize)]) -> (Vec<Section>, Vec<Segment>) {
+    let mut sections = Vec::with_capacity(regions.len());
+    let mut segments = Vec::with_capacity(regions.len());
+    let mut offset = 0;
+
+    for &(start, len) in regions {
+        let bytes = &memory[offset..offset + len];
+        let end = start.saturating_add(len);
+        let name = format!("raw_{start:x}");
+        offset += len;
+
+        segments.push(Segment {
+            name: format!("{name} (generated)"),
+            start,
+            end,
+        });
+        sections.push(Section::new(name, "GENERATED", SectionKind::Code, bytes, start, end));
+    }
+
+    (sections, segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arch_names() {
+        assert_eq!(parse_arch("armv7"), Some(Architecture::Arm));
+        assert_eq!(parse_arch("ARM64"), Some(Architecture::Aarch64));
+        assert_eq!(parse_arch("amd64"), Some(Architecture::X86_64));
+        assert_eq!(parse_arch("riscv32"), Some(Architecture::Riscv32));
+        assert_eq!(parse_arch("z80"), None);
+
+        for (name, arch) in ARCHITECTURES {
+            assert_eq!(parse_arch(name), Some(*arch));
+        }
+    }
+
+    #[test]
+    fn based_layout() {
+        static FIRMWARE: [u8; 8] = [0; 8];
+        let (sections, segments) = layout(&FIRMWARE, &[(0x8000000, FIRMWARE.len())]);
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].kind, SectionKind::Code);
+        assert_eq!((sections[0].start, sections[0].end), (0x8000000, 0x8000008));
+        assert_eq!((segments[0].start, segments[0].end), (0x8000000, 0x8000008));
+        assert_eq!(RawOptions::new(Architecture::Mips).endianness, Endianness::Big);
+    }
+
+    #[test]
+    fn sparse_layout() {
+        let text = b":0400000001020304F2\n:020010000506E3\n:00000001FF\n";
+        let image = firmware::parse(firmware::Format::IntelHex, text).unwrap();
+        let mmap = image_memory(&image).unwrap();
+        let memory: &'static [u8] = unsafe { std::mem::transmute(&mmap[..]) };
+        let regions: Vec<_> = image.regions.iter().map(|r| (r.start, r.bytes.len())).collect();
+        let (sections, _) = layout(memory, &regions);
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].name, "raw_0");
+        assert_eq!(sections[1].name, "raw_10");
+        assert_eq!(sections[1].bytes(), [5, 6]);
+    }
+
+    #[test]
+    fn options() {
+        let options = RawOptions::parse("mips", Some(0x1000), Some(false)).unwrap();
+        assert_eq!(options.arch, Architecture::Mips);
+        assert_eq!(options.endianness, Endianness::Little);
+        assert_eq!(options.base, 0x1000);
+        assert_eq!(RawOptions::parse("armv7", None, None).unwrap().base, 0);
+        assert!(RawOptions::parse("6502", None, None).is_none());
+    }
+}
@@ -0,0 +1,316 @@
+//! Summary of a binary's analysis for tools that don't need the listing.
+
+use crate::export::write_json_str;
+use crate::{FoundString, Processor};
+use processor_shared::{Addressed, PhysAddr};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSection {
+    pub name: String,
+    pub kind: String,
+    pub start: PhysAddr,
+    pub end: PhysAddr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportFunction {
+    pub addr: PhysAddr,
+    pub name: String,
+    /// Bytes up to the next symbol or the end of the function's section.
+    pub size: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportImport {
+    pub addr: PhysAddr,
+    pub name: String,
+    pub module: String,
+}
+
+/// Sections, functions, imports, exports and strings of a binary, along with the code that isn't
+/// aligned as it should be.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub path: String,
+    pub arch: String,
+    pub entrypoint: PhysAddr,
+    pub sections: Vec<ReportSection>,
+    pub functions: Vec<ReportFunction>,
+    pub imports: Vec<ReportImport>,
+    pub exports: Vec<Addressed<String>>,
+    pub strings: Vec<FoundString>,
+    /// See [`Processor::misaligned_addrs`].
+    pub misaligned: Vec<PhysAddr>,
+}
+
+/// Write `items` as a JSON array, each item written by `item`.
+pub(crate) fn json_array<W: Write, T>(
+    w: &mut W,
+    key: &str,
+    items: &[T],
+    mut item: impl FnMut(&mut W, &T) -> io::Result<()>,
+) -> io::Result<()> {
+    write!(w, ",\n  \"{key}\": [")?;
+    for (idx, value) in items.iter().enumerate() {
+        w.write_all(if idx == 0 { b"\n    " } else { b",\n    " })?;
+        item(w, value)?;
+    }
+
+    if !items.is_empty() {
+        w.write_all(b"\n  ")?;
+    }
+
+    w.write_all(b"]")
+}
+
+impl Report {
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"{\n  \"path\": ")?;
+        write_json_str(w, &self.path)?;
+        w.write_all(b",\n  \"arch\": ")?;
+        write_json_str(w, &self.arch)?;
+        write!(w, ",\n  \"entrypoint\": {}", self.entrypoint)?;
+
+        json_array(w, "sections", &self.sections, |w, section| {
+            w.write_all(b"{\"name\": ")?;
+            write_json_str(w, &section.name)?;
+            w.write_all(b", \"kind\": ")?;
+            write_json_str(w, &section.kind)?;
+            write!(w, ", \"start\": {}, \"end\": {}}}", section.start, section.end)
+        })?;
+
+        json_array(w, "functions", &self.functions, |w, func| {
+            write!(w, "{{\"addr\": {}, \"name\": ", func.addr)?;
+            write_json_str(w, &func.name)?;
+            write!(w, ", \"size\": {}}}", func.size)
+        })?;
+
+        json_array(w, "imports", &self.imports, |w, import| {
+            write!(w, "{{\"addr\": {}, \"name\": ", import.addr)?;
+            write_json_str(w, &import.name)?;
+            w.write_all(b", \"module\": ")?;
+            write_json_str(w, &import.module)?;
+            w.write_all(b"}")
+        })?;
+
+        json_array(w, "exports", &self.exports, |w, export| {
+            write!(w, "{{\"addr\": {}, \"name\": ", export.addr)?;
+            write_json_str(w, &export.item)?;
+            w.write_all(b"}")
+        })?;
+
+        json_array(w, "strings", &self.strings, |w, string| {
+            write!(w, "{{\"addr\": {}, \"encoding\": ", string.addr)?;
+            write_json_str(w, string.encoding.as_str())?;
+            w.write_all(b", \"text\": ")?;
+            write_json_str(w, &string.text)?;
+            w.write_all(b"}")
+        })?;
+
+        json_array(w, "misaligned", &self.misaligned, |w, addr| write!(w, "{addr}"))?;
+
+        w.write_all(b"\n}\n")?;
+        w.flush()
+    }
+
+    pub fn write_text<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} ({}), entrypoint {:#X}", self.path, self.arch, self.entrypoint)?;
+
+        writeln!(w, "\n{} sections:", self.sections.len())?;
+        for section in &self.sections {
+            writeln!(
+                w,
+                "  {:0>10X}..{:0>10X} {:<20} {}",
+                section.start, section.end, section.name, section.kind
+            )?;
+        }
+
+        writeln!(w, "\n{} functions:", self.functions.len())?;
+        for func in &self.functions {
+            writeln!(w, "  {:0>10X} {:>8} {}", func.addr, func.size, func.name)?;
+        }
+
+        writeln!(w, "\n{} imports:", self.imports.len())?;
+        for import in &self.imports {
+            writeln!(w, "  {:0>10X} {} ({})", import.addr, import.name, import.module)?;
+        }
+
+        writeln!(w, "\n{} exports:", self.exports.len())?;
+        for export in &self.exports {
+            writeln!(w, "  {:0>10X} {}", export.addr, export.item)?;
+        }
+
+        writeln!(w, "\n{} strings:", self.strings.len())?;
+        for string in &self.strings {
+            let encoding = string.encoding.as_str();
+            writeln!(w, "  {:0>10X} {:<6} {:?}", string.addr, encoding, string.text)?;
+        }
+
+        writeln!(w, "\n{} misaligned code addresses:", self.misaligned.len())?;
+        for addr in &self.misaligned {
+            writeln!(w, "  {addr:0>10X}")?;
+        }
+
+        w.flush()
+    }
+}
+
+impl Processor {
+    /// Summarize the analysis, strings are limited to `search.max_results`.
+    pub fn report(&self) -> Report {
+        let sections = self
+            .sections()
+            .map(|section| ReportSection {
+                name: section.name.clone(),
+                kind: format!("{:?}", section.kind),
+                start: section.start,
+                end: section.end,
+            })
+            .collect();
+
+        let mut functions = Vec::new();
+        let mut imports = Vec::new();
+        let syms = &self.index.syms.mapping;
+
+        for (idx, sym) in syms.iter().enumerate() {
+            if let Some(module) = sym.item.module() {
+                imports.push(ReportImport {
+                    addr: sym.addr,
+                    name: sym.item.as_str().to_string(),
+                    module: module.to_string(),
+                });
+                continue;
+            }
+
+            let section = match self.section_by_addr(sym.addr) {
+                Some(section) if self.is_code(sym.addr) => section,
+                _ => continue,
+            };
+
+            // Sections are given a symbol at their start.
+            if sym.item.intrinsic() || sym.item.as_str() == section.name {
+                continue;
+            }
+
+            let next = syms[idx + 1..].iter().map(|sym| sym.addr).find(|&addr| addr > sym.addr);
+            let end = next.map_or(section.end, |addr| addr.min(section.end));
+
+            functions.push(ReportFunction {
+                addr: sym.addr,
+                name: sym.item.as_str().to_string(),
+                size: end - sym.addr,
+            });
+        }
+
+        Report {
+            path: self.path.display().to_string(),
+            arch: self.isa.name(),
+            entrypoint: self.entrypoint,
+            sections,
+            functions,
+            imports,
+            exports: self.exports.mapping.clone(),
+            strings: self.find_strings(None).into_matches(),
+            misaligned: self.misaligned_addrs(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArmMode, RawOptions, StringEncoding};
+    use object::{Architecture, Endianness};
+
+    fn report() -> Report {
+        Report {
+            path: "/bin/true".to_string(),
+            arch: "X86_64".to_string(),
+            entrypoint: 0x1040,
+            sections: vec![ReportSection {
+                name: ".text".to_string(),
+                kind: "Code".to_string(),
+                start: 0x1000,
+                end: 0x1100,
+            }],
+            functions: vec![ReportFunction {
+                addr: 0x1040,
+                name: "main".to_string(),
+                size: 0x20,
+            }],
+            imports: vec![ReportImport {
+                addr: 0x3000,
+                name: "puts".to_string(),
+                module: "libc.so.6".to_string(),
+            }],
+            exports: Vec::new(),
+            strings: vec![FoundString {
+                addr: 0x2000,
+                encoding: StringEncoding::Ascii,
+                text: "say \"hi\"".to_string(),
+            }],
+            misaligned: vec![0x1042],
+        }
+    }
+
+    #[test]
+    fn json() {
+        let mut buf = Vec::new();
+        report().write_json(&mut buf).unwrap();
+        let json = String::from_utf8(buf).unwrap();
+
+        assert!(json.starts_with("{\n  \"path\": \"/bin/true\",\n  \"arch\": \"X86_64\""));
+        assert!(json.contains(
+            "\"sections\": [\n    \
+             {\"name\": \".text\", \"kind\": \"Code\", \"start\": 4096, \"end\": 4352}\n  ]"
+        ));
+        assert!(json.contains("{\"addr\": 4160, \"name\": \"main\", \"size\": 32}"));
+        assert!(json.contains("\"module\": \"libc.so.6\""));
+        assert!(json.contains("\"exports\": [],"));
+        assert!(json.contains("\"text\": \"say \\\"hi\\\"\""));
+        assert!(json.contains("\"misaligned\": [\n    4162\n  ]"));
+        assert!(json.ends_with("}\n"));
+    }
+
+    #[test]
+    fn text() {
+        let mut buf = Vec::new();
+        report().write_text(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+
+        assert!(text.starts_with("/bin/true (X86_64), entrypoint 0x1040\n"));
+        assert!(text.contains("\n1 functions:\n  0000001040       32 main\n"));
+        assert!(text.contains("\n0 exports:\n"));
+        assert!(text.ends_with("\n1 misaligned code addresses:\n  0000001042\n"));
+    }
+
+    #[test]
+    fn misaligned() {
+        // mov r0, r0, four times.
+        let name = format!("bite_report_misaligned_{}.bin", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, [0x00, 0x00, 0xa0, 0xe1].repeat(4)).unwrap();
+
+        let options = RawOptions {
+            arch: Architecture::Arm,
+            endianness: Endianness::Little,
+            base: 0x1000,
+        };
+        let processor = Processor::parse_raw(&path, &options).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(processor.report().misaligned, []);
+
+        // ARM instructions are 4 byte aligned.
+        processor.define_code(0x1002).unwrap();
+        assert!(processor.is_misaligned(0x1002));
+        assert!(!processor.is_misaligned(0x1004));
+        assert_eq!(processor.report().misaligned, [0x1002, 0x1006, 0x100a]);
+
+        // Thumb instructions are 2 byte aligned.
+        processor.modes.write().unwrap().set(0x1000, 0x1010, ArmMode::Thumb);
+        assert!(!processor.is_misaligned(0x1002));
+        assert!(processor.is_misaligned(0x1003));
+        assert_eq!(processor.report().misaligned, []);
+    }
+}
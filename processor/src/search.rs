@@ -0,0 +1,167 @@
+use crate::Processor;
+use config::CONFIG;
+use debugvault::Symbol;
+use processor_shared::{PhysAddr, SearchResults};
+use std::sync::Arc;
+
+/// Sequence of bytes where wildcards match any byte, e.g. `E8 ?? ?? ?? ??`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BytePattern(Vec<Option<u8>>);
+
+impl BytePattern {
+    /// Parse whitespace separated hex bytes and `??` wildcards. Bytes don't have to be
+    /// separated, `E8????????` is the same as `E8 ?? ?? ?? ??`.
+    ///
+    /// Returns [`None`] if `s` isn't such a pattern.
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut pattern = Vec::new();
+
+        for part in s.split_whitespace() {
+            if part.len() % 2 != 0 {
+                return None;
+            }
+
+            for pair in part.as_bytes().chunks(2) {
+                if pair == b"??" {
+                    pattern.push(None);
+                    continue;
+                }
+
+                let pair = std::str::from_utf8(pair).ok()?;
+                pattern.push(Some(u8::from_str_radix(pair, 16).ok()?));
+            }
+        }
+
+        (!pattern.is_empty()).then_some(Self(pattern))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn matches(&self, bytes: &[u8]) -> bool {
+        self.0.iter().zip(bytes).all(|(pat, byte)| pat.is_none() || *pat == Some(*byte))
+    }
+}
+
+impl Processor {
+    /// Find all occurrences of `needle` in the loaded sections.
+    ///
+    /// Stops after `limit` matches or [`config::Search::max_results`] if no limit is given.
+    pub fn find_bytes(&self, needle: &[u8], limit: Option<usize>) -> SearchResults<PhysAddr> {
+        let mut results = SearchResults::new(limit.unwrap_or(CONFIG.search.max_results));
+
+        if needle.is_empty() {
+            return results;
+        }
+
+        'sections: for section in self.sections() {
+            for (off, window) in section.bytes().windows(needle.len()).enumerate() {
+                if window == needle && !results.push(section.start + off) {
+                    break 'sections;
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Find all occurrences of `pattern` in the loaded sections.
+    ///
+    /// Stops after `limit` matches or [`config::Search::max_results`] if no limit is given.
+    pub fn search_bytes(
+        &self,
+        pattern: &BytePattern,
+        limit: Option<usize>,
+    ) -> SearchResults<PhysAddr> {
+        let mut results = SearchResults::new(limit.unwrap_or(CONFIG.search.max_results));
+
+        if pattern.is_empty() {
+            return results;
+        }
+
+        'sections: for section in self.sections() {
+            for (off, window) in section.bytes().windows(pattern.len()).enumerate() {
+                if pattern.matches(window) && !results.push(section.start + off) {
+                    break 'sections;
+                }
+            }
+        }
+
+        results
+    }
+
+    /// Find all instructions who's disassembly contains `query`, ignoring case.
+    ///
+    /// Stops after `limit` matches or [`config::Search::max_results`] if no limit is given.
+    pub fn search_text(&self, query: &str, limit: Option<usize>) -> SearchResults<PhysAddr> {
+        let mut results = SearchResults::new(limit.unwrap_or(CONFIG.search.max_results));
+        let query = query.to_lowercase();
+
+        if query.is_empty() {
+            return results;
+        }
+
+        let code = self.code.read().unwrap();
+        for instruction in code.instructions.iter() {
+            let tokens = self.instruction_tokens(&instruction.item, &self.index);
+            let text = String::from_iter(tokens.iter().map(|token| &token.text[..]));
+
+            if text.to_lowercase().contains(&query) && !results.push(instruction.addr) {
+                break;
+            }
+        }
+
+        results
+    }
+
+    /// Find all symbols who's name contains `query`, ignoring case.
+    ///
+    /// Stops after `limit` matches or [`config::Search::max_results`] if no limit is given.
+    pub fn find_symbols(
+        &self,
+        query: &str,
+        limit: Option<usize>,
+    ) -> SearchResults<(PhysAddr, Arc<Symbol>)> {
+        let mut results = SearchResults::new(limit.unwrap_or(CONFIG.search.max_results));
+        let query = query.to_lowercase();
+
+        for sym in self.index.syms.iter() {
+            if sym.item.as_str().to_lowercase().contains(&query)
+                && !results.push((sym.addr, Arc::clone(&sym.item)))
+            {
+                break;
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_pattern() {
+        let pattern = BytePattern::parse("E8 ?? ??").unwrap();
+        assert_eq!(pattern, BytePattern(vec![Some(0xe8), None, None]));
+        assert_eq!(BytePattern::parse("e8????"), Some(pattern));
+
+        assert_eq!(BytePattern::parse("mov"), None);
+        assert_eq!(BytePattern::parse("zz"), None);
+        assert_eq!(BytePattern::parse("  "), None);
+    }
+
+    #[test]
+    fn byte_pattern_wildcards() {
+        let pattern = BytePattern::parse("48 ?? c3").unwrap();
+        assert!(pattern.matches(&[0x48, 0x00, 0xc3]));
+        assert!(pattern.matches(&[0x48, 0xff, 0xc3]));
+        assert!(!pattern.matches(&[0x48, 0xff, 0xc2]));
+    }
+}
@@ -0,0 +1,658 @@
+//! Naming statically linked library functions in stripped binaries by their bytes.
+//!
+//! Signatures are read from IDA's FLIRT `.sig` files or from the textual `.pat` files they're
+//! built from. A function matches when the first 32 bytes match the pattern, the CRC16 of the
+//! bytes that follow matches and any tail bytes match. Referenced names aren't checked.
+
+use crate::xref::{XrefKind, Xrefs};
+use debugvault::Index;
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use processor_shared::{PhysAddr, Section, SectionKind};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const SIG_MAGIC: &[u8] = b"IDASGN";
+
+/// Bytes of a function that are matched against a pattern.
+const PATTERN_LEN: usize = 32;
+
+const FEATURE_COMPRESSED: u16 = 0x10;
+
+const PARSE_MORE_PUBLIC_NAMES: u8 = 0x01;
+const PARSE_READ_TAIL_BYTES: u8 = 0x02;
+const PARSE_READ_REFERENCED_FUNCTIONS: u8 = 0x04;
+const PARSE_MORE_MODULES_WITH_SAME_CRC: u8 = 0x08;
+const PARSE_MORE_MODULES: u8 = 0x10;
+
+const FUNCTION_LOCAL: u8 = 0x02;
+
+pub enum Error {
+    IO(std::io::Error),
+    /// File ended early or contains values out of range.
+    Malformed,
+    /// `.sig` file of a version that isn't 5 through 10.
+    UnsupportedVersion(u8),
+    /// `.pat` line that isn't made of a pattern, CRC, length and names.
+    InvalidLine(usize),
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("{err}.")),
+            Self::Malformed => f.write_str("Signature file is malformed."),
+            Self::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("Unsupported signature file version: '{version}'."))
+            }
+            Self::InvalidLine(line) => f.write_fmt(format_args!("Invalid pattern on line {line}.")),
+        }
+    }
+}
+
+/// Function of a library, named by one or more public names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Leading bytes of the function, `None` where the byte differs per binary.
+    pattern: Vec<Option<u8>>,
+    /// Number of bytes following the pattern covered by `crc16`.
+    crc_len: usize,
+    crc16: u16,
+    /// Bytes after the CRC'd bytes by their offset from the end of the CRC'd bytes.
+    tail: Vec<(usize, u8)>,
+    /// Names at offsets into the function.
+    pub names: Vec<(usize, String)>,
+}
+
+impl Signature {
+    fn matches(&self, bytes: &[u8]) -> bool {
+        if bytes.len() < self.pattern.len() {
+            return false;
+        }
+
+        let pattern_matches = self
+            .pattern
+            .iter()
+            .zip(bytes)
+            .all(|(pattern, byte)| pattern.is_none_or(|pattern| pattern == *byte));
+
+        if !pattern_matches {
+            return false;
+        }
+
+        if self.crc_len > 0 {
+            match bytes.get(PATTERN_LEN..PATTERN_LEN + self.crc_len) {
+                Some(crc_bytes) if crc16(crc_bytes) == self.crc16 => {}
+                _ => return false,
+            }
+        }
+
+        let tail_start = PATTERN_LEN + self.crc_len;
+        self.tail.iter().all(|(offset, value)| bytes.get(tail_start + offset) == Some(value))
+    }
+}
+
+/// CRC16 as computed by FLIRT, that is CRC-16/X-25 with it's bytes swapped.
+fn crc16(bytes: &[u8]) -> u16 {
+    if bytes.is_empty() {
+        return 0;
+    }
+
+    let mut crc: u16 = 0xffff;
+    for &byte in bytes {
+        let mut data = byte;
+        for _ in 0..8 {
+            if (crc ^ data as u16) & 1 != 0 {
+                crc = (crc >> 1) ^ 0x8408;
+            } else {
+                crc >>= 1;
+            }
+            data >>= 1;
+        }
+    }
+
+    (!crc).swap_bytes()
+}
+
+/// Cursor over the big-endian, variable-length encoded body of a `.sig` file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    version: u8,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, Error> {
+        let (&byte, rest) = self.bytes.split_first().ok_or(Error::Malformed)?;
+        self.bytes = rest;
+        Ok(byte)
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(((self.u8()? as u16) << 8) | self.u8()? as u16)
+    }
+
+    fn max_2_bytes(&mut self) -> Result<u32, Error> {
+        let byte = self.u8()? as u32;
+        if byte & 0x80 != 0 {
+            Ok(((byte & 0x7f) << 8) | self.u8()? as u32)
+        } else {
+            Ok(byte)
+        }
+    }
+
+    fn multiple_bytes(&mut self) -> Result<u32, Error> {
+        let byte = self.u8()? as u32;
+        if byte & 0x80 != 0x80 {
+            return Ok(byte);
+        }
+
+        if byte & 0xc0 != 0xc0 {
+            return Ok(((byte & 0x7f) << 8) | self.u8()? as u32);
+        }
+
+        if byte & 0xe0 != 0xe0 {
+            let high = (byte & 0x3f) << 24;
+            return Ok(high | (self.u16()? as u32) << 8 | self.u8()? as u32);
+        }
+
+        Ok((self.u16()? as u32) << 16 | self.u16()? as u32)
+    }
+
+    /// Offsets and lengths are encoded more compactly since version 9.
+    fn offset(&mut self) -> Result<usize, Error> {
+        let offset = if self.version >= 9 {
+            self.multiple_bytes()?
+        } else {
+            self.max_2_bytes()?
+        };
+
+        Ok(offset as usize)
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&[u8], Error> {
+        if len > self.bytes.len() {
+            return Err(Error::Malformed);
+        }
+
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    /// Nodes of the pattern tree, leaves hold the modules matching the bytes leading to it.
+    fn tree(
+        &mut self,
+        prefix: &mut Vec<Option<u8>>,
+        sigs: &mut Vec<Signature>,
+    ) -> Result<(), Error> {
+        let nodes = self.multiple_bytes()?;
+        if nodes == 0 {
+            return self.leaf(prefix, sigs);
+        }
+
+        for _ in 0..nodes {
+            let len = self.u8()? as usize;
+            let mask = if len < 0x10 {
+                self.max_2_bytes()? as u64
+            } else if len <= 0x20 {
+                self.multiple_bytes()? as u64
+            } else if len <= 0x40 {
+                (self.multiple_bytes()? as u64) << 32 | self.multiple_bytes()? as u64
+            } else {
+                return Err(Error::Malformed);
+            };
+
+            let depth = prefix.len();
+            for idx in 0..len {
+                // The first byte of the node is the most significant bit of the mask.
+                let variant = mask & (1 << (len - 1 - idx)) != 0;
+                prefix.push(if variant { None } else { Some(self.u8()?) });
+            }
+
+            self.tree(prefix, sigs)?;
+            prefix.truncate(depth);
+        }
+
+        Ok(())
+    }
+
+    fn leaf(&mut self, prefix: &[Option<u8>], sigs: &mut Vec<Signature>) -> Result<(), Error> {
+        loop {
+            let crc_len = self.u8()? as usize;
+            let crc16 = self.u16()?;
+
+            let flags = loop {
+                // Length of the module, not needed for matching.
+                self.offset()?;
+
+                let mut sig = Signature {
+                    pattern: prefix.to_vec(),
+                    crc_len,
+                    crc16,
+                    tail: Vec::new(),
+                    names: Vec::new(),
+                };
+
+                let flags = self.public_names(&mut sig)?;
+
+                if flags & PARSE_READ_TAIL_BYTES != 0 {
+                    let count = if self.version >= 8 { self.u8()? } else { 1 };
+                    for _ in 0..count {
+                        let offset = self.offset()?;
+                        sig.tail.push((offset, self.u8()?));
+                    }
+                }
+
+                if flags & PARSE_READ_REFERENCED_FUNCTIONS != 0 {
+                    let count = if self.version >= 8 { self.u8()? } else { 1 };
+                    for _ in 0..count {
+                        self.offset()?;
+                        let len = match self.u8()? {
+                            0 => self.multiple_bytes()? as usize,
+                            len => len as usize,
+                        };
+                        self.bytes(len)?;
+                    }
+                }
+
+                sigs.push(sig);
+
+                if flags & PARSE_MORE_MODULES_WITH_SAME_CRC == 0 {
+                    break flags;
+                }
+            };
+
+            if flags & PARSE_MORE_MODULES == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Names of a module, returning the flags that terminate the last name.
+    fn public_names(&mut self, sig: &mut Signature) -> Result<u8, Error> {
+        let mut offset = 0;
+
+        loop {
+            offset += self.offset()?;
+
+            let mut byte = self.u8()?;
+            let mut local = false;
+            if byte < 0x20 {
+                local = byte & FUNCTION_LOCAL != 0;
+                byte = self.u8()?;
+            }
+
+            let mut name = Vec::new();
+            while byte >= 0x20 {
+                name.push(byte);
+                byte = self.u8()?;
+            }
+
+            if !local && !name.is_empty() {
+                sig.names.push((offset, String::from_utf8_lossy(&name).into_owned()));
+            }
+
+            if byte & PARSE_MORE_PUBLIC_NAMES == 0 {
+                return Ok(byte);
+            }
+        }
+    }
+}
+
+/// Parse a FLIRT `.sig` file.
+fn parse_sig(bytes: &[u8]) -> Result<Vec<Signature>, Error> {
+    if !bytes.starts_with(SIG_MAGIC) {
+        return Err(Error::Malformed);
+    }
+
+    let version = *bytes.get(6).ok_or(Error::Malformed)?;
+    if !(5..=10).contains(&version) {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let read_u16 = |offset: usize| -> Result<u16, Error> {
+        let bytes = bytes.get(offset..offset + 2).ok_or(Error::Malformed)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    };
+
+    let features = read_u16(16)?;
+    let name_len = *bytes.get(34).ok_or(Error::Malformed)? as usize;
+
+    // Later versions extend the header with a function count, pattern size and unknown field.
+    let header_len = match version {
+        5 => 37,
+        6 | 7 => 41,
+        8 | 9 => 43,
+        _ => 45,
+    };
+
+    let body = bytes.get(header_len + name_len..).ok_or(Error::Malformed)?;
+    let mut decompressed = Vec::new();
+    let body = if features & FEATURE_COMPRESSED != 0 {
+        let result = if version < 7 {
+            DeflateDecoder::new(body).read_to_end(&mut decompressed)
+        } else {
+            ZlibDecoder::new(body).read_to_end(&mut decompressed)
+        };
+
+        result.map_err(Error::IO)?;
+        &decompressed[..]
+    } else {
+        body
+    };
+
+    let mut reader = Reader {
+        bytes: body,
+        version,
+    };
+
+    let mut sigs = Vec::new();
+    reader.tree(&mut Vec::new(), &mut sigs)?;
+    Ok(sigs)
+}
+
+fn parse_hex_byte(text: &str) -> Option<Option<u8>> {
+    match text {
+        ".." => Some(None),
+        _ => u8::from_str_radix(text, 16).ok().map(Some),
+    }
+}
+
+fn parse_hex_bytes(text: &str) -> Option<Vec<Option<u8>>> {
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return None;
+    }
+
+    (0..text.len()).step_by(2).map(|idx| parse_hex_byte(&text[idx..idx + 2])).collect()
+}
+
+/// Parse a `.pat` file, where each line reads like
+/// `5589E5..<pattern> 1C 3A4F 0040 :0000 _memcpy :0020@ local ^0010 _ref 8B4D..`.
+fn parse_pat(text: &str) -> Result<Vec<Signature>, Error> {
+    let mut sigs = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line == "---" {
+            break;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || Error::InvalidLine(idx + 1);
+        let mut fields = line.split_whitespace();
+
+        let mut pattern = fields.next().and_then(parse_hex_bytes).ok_or_else(invalid)?;
+        while pattern.last() == Some(&None) {
+            pattern.pop();
+        }
+
+        let crc_len = fields.next().and_then(|len| usize::from_str_radix(len, 16).ok());
+        let crc16 = fields.next().and_then(|crc| u16::from_str_radix(crc, 16).ok());
+        let len = fields.next().and_then(|len| usize::from_str_radix(len, 16).ok());
+        let (crc_len, crc16) = match (crc_len, crc16, len) {
+            (Some(crc_len), Some(crc16), Some(_)) => (crc_len, crc16),
+            _ => return Err(invalid()),
+        };
+
+        let mut sig = Signature {
+            pattern,
+            crc_len,
+            crc16,
+            tail: Vec::new(),
+            names: Vec::new(),
+        };
+
+        while let Some(field) = fields.next() {
+            if let Some(offset) = field.strip_prefix(':') {
+                let name = fields.next().ok_or_else(invalid)?;
+                let (offset, local) = match offset.strip_suffix('@') {
+                    Some(offset) => (offset, true),
+                    None => (offset, false),
+                };
+
+                let offset = usize::from_str_radix(offset, 16).map_err(|_| invalid())?;
+                if !local {
+                    sig.names.push((offset, name.to_string()));
+                }
+            } else if field.starts_with('^') {
+                // Referenced names aren't checked.
+                fields.next().ok_or_else(invalid)?;
+            } else {
+                let tail = parse_hex_bytes(field).ok_or_else(invalid)?;
+                sig.tail = tail
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(offset, byte)| byte.map(|byte| (offset, byte)))
+                    .collect();
+            }
+        }
+
+        if !sig.names.is_empty() {
+            sigs.push(sig);
+        }
+    }
+
+    Ok(sigs)
+}
+
+/// Signatures from any number of `.sig` and `.pat` files.
+#[derive(Debug, Default)]
+pub struct Signatures {
+    sigs: Vec<Signature>,
+}
+
+impl Signatures {
+    /// Add the signatures in the file at `path`, `.sig` files are recognized by their magic.
+    pub fn load(&mut self, path: &Path) -> Result<usize, Error> {
+        let bytes = std::fs::read(path).map_err(Error::IO)?;
+
+        let sigs = if bytes.starts_with(SIG_MAGIC) {
+            parse_sig(&bytes)?
+        } else {
+            parse_pat(&String::from_utf8_lossy(&bytes))?
+        };
+
+        let count = sigs.len();
+        self.sigs.extend(sigs);
+        Ok(count)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sigs.is_empty()
+    }
+
+    /// The one signature matching the function starting at `bytes`.
+    ///
+    /// Functions matched by signatures with different names are left alone, as it's
+    /// unknown which of them it is.
+    pub fn identify(&self, bytes: &[u8]) -> Option<&Signature> {
+        let mut matches = self.sigs.iter().filter(|sig| sig.matches(bytes));
+        let found = matches.next()?;
+
+        if matches.any(|sig| sig.names != found.names) {
+            return None;
+        }
+
+        Some(found)
+    }
+}
+
+/// Signatures from all `paths`, files that fail to load are logged and skipped.
+pub(crate) fn load_all(paths: &[PathBuf]) -> Signatures {
+    let mut sigs = Signatures::default();
+
+    for path in paths {
+        match sigs.load(path) {
+            Ok(count) => log::complex!(
+                w "[signatures::load] loaded ",
+                y count.to_string(),
+                w " signatures from ",
+                w format!("{path:?}."),
+            ),
+            Err(err) => log::complex!(
+                w "[signatures::load] failed to load ",
+                w format!("{path:?}: "),
+                r format!("{err:?}"),
+            ),
+        }
+    }
+
+    sigs
+}
+
+/// Names of library functions among the targets of calls that don't have a symbol yet.
+pub(crate) fn identify_functions(
+    sigs: &Signatures,
+    sections: &[Section],
+    xrefs: &Xrefs,
+    index: &Index,
+) -> Vec<(PhysAddr, String)> {
+    let mut targets: Vec<PhysAddr> = xrefs
+        .iter()
+        .filter(|xref| xref.kind == XrefKind::Call)
+        .map(|xref| xref.to)
+        .collect();
+
+    targets.sort_unstable();
+    targets.dedup();
+
+    let mut names = Vec::new();
+    for addr in targets {
+        if index.get_sym_by_addr(addr).is_some() {
+            continue;
+        }
+
+        let section = sections.iter().find(|section| {
+            section.kind == SectionKind::Code && (section.start..section.end).contains(&addr)
+        });
+
+        let section = match section {
+            Some(section) => section,
+            None => continue,
+        };
+
+        if let Some(sig) = sigs.identify(section.bytes_by_addr(addr, usize::MAX)) {
+            names.extend(sig.names.iter().map(|(offset, name)| (addr + offset, name.clone())));
+        }
+    }
+
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flirt_crc16() {
+        // CRC-16/X-25 of the standard check input is 0x906e.
+        assert_eq!(crc16(b"123456789"), 0x6e90);
+        assert_eq!(crc16(b""), 0);
+    }
+
+    #[test]
+    fn variable_length_values() {
+        let mut reader = Reader {
+            bytes: &[0x7f, 0x81, 0x02, 0xc1, 0x02, 0x03, 0x04, 0xe0, 0x01, 0x02, 0x03, 0x04],
+            version: 10,
+        };
+
+        assert_eq!(reader.multiple_bytes().ok(), Some(0x7f));
+        assert_eq!(reader.multiple_bytes().ok(), Some(0x0102));
+        assert_eq!(reader.multiple_bytes().ok(), Some(0x01020304));
+        assert_eq!(reader.multiple_bytes().ok(), Some(0x01020304));
+        assert!(reader.multiple_bytes().is_err());
+    }
+
+    fn sig_file(body: &[u8]) -> Vec<u8> {
+        let mut file = SIG_MAGIC.to_vec();
+        file.push(10);
+        file.resize(45, 0);
+        file[34] = 3;
+        file.extend_from_slice(b"lib");
+        file.extend_from_slice(body);
+        file
+    }
+
+    #[test]
+    fn sig_tree() {
+        let body = [
+            // One node of 4 bytes, the third of which varies.
+            0x01, 0x04, 0x02, 0x55, 0x89, 0xe5,
+            // Leaf with a single module without a CRC, named `memcpy` and with a tail byte.
+            0x00, 0x00, 0x00, 0x00, 0x10, 0x00, b'm', b'e', b'm', b'c', b'p', b'y',
+            PARSE_READ_TAIL_BYTES, 0x01, 0x02, 0xc3,
+        ];
+
+        let sigs = parse_sig(&sig_file(&body)).unwrap();
+        assert_eq!(
+            sigs,
+            [Signature {
+                pattern: vec![Some(0x55), Some(0x89), None, Some(0xe5)],
+                crc_len: 0,
+                crc16: 0,
+                tail: vec![(2, 0xc3)],
+                names: vec![(0, "memcpy".to_string())],
+            }]
+        );
+
+        let mut function = vec![0x55, 0x89, 0x42, 0xe5];
+        function.resize(PATTERN_LEN + 2, 0x90);
+        assert!(!sigs[0].matches(&function));
+        function.push(0xc3);
+        assert!(sigs[0].matches(&function));
+        function[0] = 0x56;
+        assert!(!sigs[0].matches(&function));
+    }
+
+    #[test]
+    fn pat_lines() {
+        let crc = crc16(&[0xaa; 4]);
+        let text = format!(
+            "5589E5........................................................C3 04 {crc:04X} 0030 \
+             :0000 _strlen :0010@ local ^0008 _ref 90..CC\n---\n"
+        );
+
+        let sigs = parse_pat(&text).unwrap();
+        assert_eq!(sigs.len(), 1);
+        assert_eq!(sigs[0].names, [(0, "_strlen".to_string())]);
+        assert_eq!(sigs[0].tail, [(0, 0x90), (2, 0xcc)]);
+
+        let mut function = vec![0x55, 0x89, 0xe5];
+        function.resize(PATTERN_LEN - 1, 0);
+        function.push(0xc3);
+        function.extend_from_slice(&[0xaa; 4]);
+        function.extend_from_slice(&[0x90, 0x00, 0xcc]);
+        assert!(sigs[0].matches(&function));
+
+        function[PATTERN_LEN] = 0xab;
+        assert!(!sigs[0].matches(&function));
+
+        assert!(matches!(parse_pat("5589 zz"), Err(Error::InvalidLine(1))));
+    }
+
+    #[test]
+    fn ambiguous_matches() {
+        let sig = |name: &str| Signature {
+            pattern: vec![Some(0xc3)],
+            crc_len: 0,
+            crc16: 0,
+            tail: Vec::new(),
+            names: vec![(0, name.to_string())],
+        };
+
+        let sigs = Signatures {
+            sigs: vec![sig("ret"), sig("ret")],
+        };
+        assert!(sigs.identify(&[0xc3]).is_some());
+
+        let sigs = Signatures {
+            sigs: vec![sig("ret"), sig("nop_ret")],
+        };
+        assert!(sigs.identify(&[0xc3]).is_none());
+        assert!(sigs.identify(&[0x90]).is_none());
+    }
+}
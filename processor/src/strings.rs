@@ -0,0 +1,309 @@
+//! Extraction of human readable strings from the loaded sections.
+
+use crate::xref::Xrefs;
+use crate::{Processor, XrefKind};
+use config::CONFIG;
+use object::Endianness;
+use processor_shared::{PhysAddr, SearchResults, Section, SectionKind};
+
+/// Longest string looked for at a referenced address.
+pub(crate) const MAX_REFERENCED_LEN: usize = 512;
+
+/// Most characters of a string that end up in it's symbol's name.
+const MAX_NAME_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    Ascii,
+    Utf8,
+    Utf16,
+}
+
+impl StringEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Utf8 => "utf-8",
+            Self::Utf16 => "utf-16",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FoundString {
+    pub addr: PhysAddr,
+    pub encoding: StringEncoding,
+    pub text: String,
+}
+
+fn is_printable(c: char) -> bool {
+    !c.is_control() || c == '\t'
+}
+
+/// Runs of at least `min_len` printable UTF-8 characters.
+fn utf8_strings(
+    bytes: &[u8],
+    min_len: usize,
+    mut found: impl FnMut(usize, StringEncoding, String) -> bool,
+) {
+    let mut idx = 0;
+    let mut start = 0;
+    let mut chars = 0;
+    let mut ascii = true;
+
+    loop {
+        let c = bytes.get(idx..).and_then(|rest| {
+            let len = match *rest.first()? {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return None,
+            };
+
+            let c = std::str::from_utf8(rest.get(..len)?).ok()?.chars().next()?;
+            is_printable(c).then_some((c, len))
+        });
+
+        match c {
+            Some((c, len)) => {
+                ascii &= c.is_ascii();
+                chars += 1;
+                idx += len;
+            }
+            None => {
+                if chars >= min_len {
+                    // the run only consists of valid utf-8
+                    let text = String::from_utf8_lossy(&bytes[start..idx]).into_owned();
+                    let encoding = match ascii {
+                        true => StringEncoding::Ascii,
+                        false => StringEncoding::Utf8,
+                    };
+                    if !found(start, encoding, text) {
+                        return;
+                    }
+                }
+
+                if idx >= bytes.len() {
+                    return;
+                }
+
+                idx += 1;
+                start = idx;
+                chars = 0;
+                ascii = true;
+            }
+        }
+    }
+}
+
+/// Runs of at least `min_len` printable, 2 byte aligned, UTF-16 characters.
+///
+/// Only latin-1 characters are considered, otherwise any ASCII text would also be valid UTF-16.
+fn utf16_strings(
+    bytes: &[u8],
+    endianness: Endianness,
+    min_len: usize,
+    mut found: impl FnMut(usize, StringEncoding, String) -> bool,
+) {
+    let units = bytes.chunks_exact(2).map(|unit| match endianness {
+        Endianness::Little => u16::from_le_bytes([unit[0], unit[1]]),
+        Endianness::Big => u16::from_be_bytes([unit[0], unit[1]]),
+    });
+
+    let mut start = 0;
+    let mut text = String::new();
+    let mut chars = 0;
+
+    for (idx, unit) in units.enumerate() {
+        let c = (unit <= 0xff).then_some(unit as u8 as char);
+        match c.filter(|&c| is_printable(c)) {
+            Some(c) => {
+                if chars == 0 {
+                    start = idx * 2;
+                }
+                text.push(c);
+                chars += 1;
+            }
+            None => {
+                let text = std::mem::take(&mut text);
+                if chars >= min_len && !found(start, StringEncoding::Utf16, text) {
+                    return;
+                }
+
+                chars = 0;
+            }
+        }
+    }
+
+    if chars >= min_len {
+        found(start, StringEncoding::Utf16, text);
+    }
+}
+
+/// NUL-terminated string of printable UTF-8 characters at the start of `bytes`.
+pub(crate) fn c_string(bytes: &[u8], min_len: usize) -> Option<&str> {
+    let len = bytes.iter().position(|&b| b == 0)?;
+    let text = std::str::from_utf8(&bytes[..len]).ok()?;
+
+    if text.chars().count() < min_len || !text.chars().all(is_printable) {
+        return None;
+    }
+
+    Some(text)
+}
+
+/// Name of the symbol for a referenced string, e.g. `str.Hello_world` for "Hello, world!\n".
+fn symbol_name(text: &str) -> String {
+    let mut name = String::from("str.");
+    let mut separated = true;
+
+    for (chars, c) in text.chars().enumerate() {
+        if chars == MAX_NAME_LEN {
+            break;
+        }
+
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+            separated = false;
+        } else if !separated {
+            name.push('_');
+            separated = true;
+        }
+    }
+
+    if name.len() > "str.".len() && separated {
+        name.pop();
+    }
+
+    name
+}
+
+/// Symbols for the strings that instructions refer to, named after their text.
+pub(crate) fn string_symbols(sections: &[Section], xrefs: &Xrefs) -> Vec<(PhysAddr, String)> {
+    let min_len = CONFIG.search.min_string_len.max(1);
+    let mut syms = Vec::new();
+
+    for xref in xrefs.iter().filter(|xref| xref.kind == XrefKind::Data) {
+        let idx = match sections.partition_point(|section| section.start <= xref.to) {
+            0 => continue,
+            idx => idx - 1,
+        };
+
+        let section = &sections[idx];
+        let is_data =
+            matches!(section.kind, SectionKind::Raw | SectionKind::CString | SectionKind::Data);
+        if xref.to >= section.end || !is_data {
+            continue;
+        }
+
+        let bytes = section.bytes_by_addr(xref.to, MAX_REFERENCED_LEN);
+        let name = match c_string(bytes, min_len) {
+            Some(text) => symbol_name(text),
+            None => continue,
+        };
+
+        // Strings without any letters or digits, such as "---", don't make for a name.
+        if name.len() > "str.".len() {
+            syms.push((xref.to, name));
+        }
+    }
+
+    syms.sort_unstable_by_key(|(addr, _)| *addr);
+    syms.dedup_by_key(|(addr, _)| *addr);
+    syms
+}
+
+impl Processor {
+    /// Find all ASCII, UTF-8 and UTF-16 strings in the loaded sections that are at least
+    /// [`config::Search::min_string_len`] characters long, sorted by address.
+    ///
+    /// Stops after `limit` matches or [`config::Search::max_results`] if no limit is given.
+    pub fn find_strings(&self, limit: Option<usize>) -> SearchResults<FoundString> {
+        let limit = limit.unwrap_or(CONFIG.search.max_results);
+        let min_len = CONFIG.search.min_string_len.max(1);
+        let mut strings = Vec::new();
+
+        for section in self.sections() {
+            let mut found = |offset: usize, encoding, text| {
+                strings.push(FoundString {
+                    addr: section.start + offset,
+                    encoding,
+                    text,
+                });
+                strings.len() <= limit
+            };
+
+            utf8_strings(section.bytes(), min_len, &mut found);
+            utf16_strings(section.bytes(), self.endianness, min_len, &mut found);
+
+            if strings.len() > limit {
+                break;
+            }
+        }
+
+        strings.sort_by_key(|string| string.addr);
+
+        let mut results = SearchResults::new(limit);
+        for string in strings {
+            if !results.push(string) {
+                break;
+            }
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(bytes: &[u8], utf16: bool) -> Vec<(usize, StringEncoding, String)> {
+        let mut strings = Vec::new();
+        let found = |addr: usize, encoding: StringEncoding, text: String| {
+            strings.push((addr, encoding, text));
+            true
+        };
+
+        if utf16 {
+            utf16_strings(bytes, Endianness::Little, 4, found);
+        } else {
+            utf8_strings(bytes, 4, found);
+        }
+
+        strings
+    }
+
+    #[test]
+    fn ascii_and_utf8() {
+        let bytes = b"\x00\x01hello\x00ab\x00caf\xc3\xa9s\xff";
+        assert_eq!(
+            collect(bytes, false),
+            [
+                (2, StringEncoding::Ascii, "hello".to_string()),
+                (11, StringEncoding::Utf8, "caf\u{e9}s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn referenced_strings() {
+        assert_eq!(c_string(b"hello\0world", 4), Some("hello"));
+        assert_eq!(c_string(b"hi\0", 4), None);
+        assert_eq!(c_string(b"\x01\x02\x03\x04\0", 4), None);
+        assert_eq!(c_string(b"unterminated", 4), None);
+
+        assert_eq!(symbol_name("Hello, world!\n"), "str.Hello_world");
+        assert_eq!(symbol_name("  %s: %d\n"), "str.s_d");
+        assert_eq!(symbol_name("%%%%"), "str.");
+        assert_eq!(symbol_name("caf\u{e9} au lait"), "str.caf_au_lait");
+        assert_eq!(symbol_name("a".repeat(64).as_str()).len(), "str.".len() + MAX_NAME_LEN);
+    }
+
+    #[test]
+    fn utf16() {
+        let bytes = b"w\x00i\x00d\x00e\x00\x00\x00n\x00o\x00";
+        assert_eq!(collect(bytes, true), [(0, StringEncoding::Utf16, "wide".to_string())]);
+    }
+}
@@ -0,0 +1,200 @@
+//! Compressed and packed files, which are unpacked before they're analyzed as otherwise their
+//! contents would be decoded as garbage.
+
+use crate::{lzma, upx, Error, Processor};
+use memmap2::{Mmap, MmapMut};
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Most layers of compression that are unpacked, e.g. a gzip'd firmware image of a UPX'd ELF.
+const MAX_LAYERS: usize = 4;
+
+/// Largest file that's decompressed, so a small file can't expand into all of memory.
+const MAX_UNPACKED_LEN: u64 = 1 << 32;
+
+/// Compressed or packed format a file is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Packer {
+    Gzip,
+    Xz,
+    Zstd,
+    /// ELF packed by UPX.
+    Upx,
+}
+
+impl Packer {
+    fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&[0x1f, 0x8b, 0x08]) {
+            Some(Self::Gzip)
+        } else if lzma::is_xz(bytes) {
+            Some(Self::Xz)
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Self::Zstd)
+        } else if upx::is_packed(bytes) {
+            Some(Self::Upx)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Xz => "xz",
+            Self::Zstd => "zstd",
+            Self::Upx => "UPX",
+        }
+    }
+}
+
+/// Layer of compression or packing that was removed from a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unpacking {
+    pub packer: Packer,
+    /// How the packer compressed the file, if it supports more than one method.
+    pub method: Option<&'static str>,
+    pub packed_len: usize,
+    pub unpacked_len: usize,
+}
+
+impl fmt::Display for Unpacking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.packer.name())?;
+        if let Some(method) = self.method {
+            write!(f, " ({method})")?;
+        }
+
+        write!(f, ", {} to {} bytes", self.packed_len, self.unpacked_len)
+    }
+}
+
+/// Read all of `reader` as long as it doesn't exceed [`MAX_UNPACKED_LEN`].
+fn read_limited(reader: impl Read) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(MAX_UNPACKED_LEN + 1).read_to_end(&mut bytes).ok()?;
+    (bytes.len() as u64 <= MAX_UNPACKED_LEN).then_some(bytes)
+}
+
+/// Contents of `bytes` stored by `packer`, None if they're malformed.
+fn unpack(packer: Packer, bytes: &[u8]) -> Option<(Option<&'static str>, Vec<u8>)> {
+    match packer {
+        Packer::Gzip => {
+            let decoder = flate2::read::MultiGzDecoder::new(bytes);
+            read_limited(decoder).map(|bytes| (None, bytes))
+        }
+        Packer::Xz => lzma::decode_xz(bytes).map(|bytes| (None, bytes)),
+        Packer::Zstd => {
+            let decoder = ruzstd::StreamingDecoder::new(bytes).ok()?;
+            read_limited(decoder).map(|bytes| (None, bytes))
+        }
+        Packer::Upx => {
+            let (method, bytes) = upx::unpack(bytes)?;
+            Some((Some(method.name()), bytes))
+        }
+    }
+}
+
+/// Map the file at `path`, unpacking it into anonymous memory if it's compressed or packed.
+///
+/// Files that look packed but fail to unpack are mapped as they are.
+pub(crate) fn map(path: &Path) -> Result<(File, Mmap, Vec<Unpacking>), Error> {
+    let file = File::open(path).map_err(Error::IO)?;
+    let mmap = unsafe { Mmap::map(&file).map_err(Error::IO)? };
+
+    let mut layers = Vec::new();
+    let mut unpacked: Option<Vec<u8>> = None;
+    while layers.len() < MAX_LAYERS {
+        let bytes = unpacked.as_deref().unwrap_or(&mmap[..]);
+        let Some(packer) = Packer::detect(bytes) else {
+            break;
+        };
+
+        let Some((method, contents)) = unpack(packer, bytes) else {
+            log::complex!(
+                w "[processor::unpack] ",
+                r format!("failed to unpack {} data", packer.name()),
+                w ", analyzing it as is.",
+            );
+            break;
+        };
+
+        log::complex!(
+            w "[processor::unpack] unpacked ",
+            b packer.name(),
+            w " data from ",
+            y bytes.len().to_string(),
+            w " to ",
+            y contents.len().to_string(),
+            w " bytes.",
+        );
+
+        layers.push(Unpacking {
+            packer,
+            method,
+            packed_len: bytes.len(),
+            unpacked_len: contents.len(),
+        });
+        unpacked = Some(contents);
+    }
+
+    let Some(unpacked) = unpacked else {
+        return Ok((file, mmap, layers));
+    };
+
+    let mut memory = MmapMut::map_anon(unpacked.len().max(1)).map_err(Error::IO)?;
+    memory[..unpacked.len()].copy_from_slice(&unpacked);
+    let memory = memory.make_read_only().map_err(Error::IO)?;
+    Ok((file, memory, layers))
+}
+
+impl Processor {
+    /// Layers of compression or packing removed from the file before it was analyzed, outermost
+    /// first.
+    pub fn unpacking(&self) -> &[Unpacking] {
+        &self.unpacking
+    }
+
+    /// Write the file as it was analyzed, i.e. with any compression or packing removed, to
+    /// `path`.
+    pub fn save_unpacked(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.file_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        let mut gzip = Vec::new();
+        let mut encoder = flate2::write::GzEncoder::new(&mut gzip, flate2::Compression::fast());
+        std::io::Write::write_all(&mut encoder, b"\x7fELF").unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(Packer::detect(&gzip), Some(Packer::Gzip));
+        assert_eq!(unpack(Packer::Gzip, &gzip), Some((None, b"\x7fELF".to_vec())));
+        assert_eq!(unpack(Packer::Gzip, &gzip[..8]), None);
+
+        // A frame holding "bite" as a raw block.
+        let zstd = [0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x58, 0x21, 0x00, 0x00, b'b', b'i', b't', b'e'];
+        assert_eq!(Packer::detect(&zstd), Some(Packer::Zstd));
+        assert_eq!(unpack(Packer::Zstd, &zstd), Some((None, b"bite".to_vec())));
+
+        assert_eq!(Packer::detect(b"\x7fELF\x02\x01\x01"), None);
+    }
+
+    #[test]
+    fn display() {
+        let unpacking = Unpacking {
+            packer: Packer::Upx,
+            method: Some("NRV2E"),
+            packed_len: 100,
+            unpacked_len: 300,
+        };
+
+        assert_eq!(unpacking.to_string(), "UPX (NRV2E), 100 to 300 bytes");
+    }
+}
@@ -0,0 +1,524 @@
+//! Unpacking of ELF's packed with UPX, which compresses the original file in blocks appended to
+//! a stub that decompresses them into memory when run.
+
+use crate::lzma;
+
+/// Magic of the headers UPX writes in front of the compressed blocks.
+const MAGIC: &[u8] = b"UPX!";
+
+/// Size of each of the `l_info`, `p_info` and `b_info` headers.
+const HEADER_LEN: usize = 12;
+
+/// Largest original file that's unpacked.
+const MAX_FILE_LEN: usize = 1 << 30;
+
+/// Compression method of a block by UPX's id for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Method {
+    Nrv2b(Width),
+    Nrv2d(Width),
+    Nrv2e(Width),
+    Lzma,
+}
+
+impl Method {
+    fn from_id(id: u8) -> Option<Self> {
+        let width = match id {
+            2 | 5 | 8 => Width::Le32,
+            3 | 6 | 9 => Width::Byte,
+            4 | 7 | 10 => Width::Le16,
+            14 => return Some(Self::Lzma),
+            _ => return None,
+        };
+
+        Some(match id {
+            2..=4 => Self::Nrv2b(width),
+            5..=7 => Self::Nrv2d(width),
+            _ => Self::Nrv2e(width),
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Nrv2b(..) => "NRV2B",
+            Self::Nrv2d(..) => "NRV2D",
+            Self::Nrv2e(..) => "NRV2E",
+            Self::Lzma => "LZMA",
+        }
+    }
+}
+
+/// How many bits the NRV decompressors refill their bit buffer with at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Width {
+    Byte,
+    Le16,
+    Le32,
+}
+
+/// Bits of an NRV stream, which are interleaved with whole bytes.
+struct Bits<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    width: Width,
+    buf: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.offset)?;
+        self.offset += 1;
+        Some(byte)
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        if self.count == 0 {
+            let bytes = self.bytes.get(self.offset..)?;
+            (self.buf, self.count) = match self.width {
+                Width::Byte => (*bytes.first()? as u32, 8),
+                Width::Le16 => (u16::from_le_bytes(bytes.get(..2)?.try_into().ok()?) as u32, 16),
+                Width::Le32 => (u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?), 32),
+            };
+            self.offset += self.count as usize / 8;
+        }
+
+        self.count -= 1;
+        Some((self.buf >> self.count) & 1)
+    }
+
+    /// Elias-gamma like number, whose bits are each followed by whether they're the last.
+    fn gamma(&mut self) -> Option<u32> {
+        let mut value = 1u32;
+        loop {
+            value = value.wrapping_mul(2).wrapping_add(self.bit()?);
+            if self.bit()? == 1 {
+                return Some(value);
+            }
+        }
+    }
+}
+
+/// Copy `len` bytes starting `dist` bytes back to the end of `out`.
+fn copy_match(out: &mut Vec<u8>, dist: u32, len: u32) -> Option<()> {
+    let start = out.len().checked_sub(dist as usize)?;
+    for idx in 0..len as usize {
+        out.push(out[start + idx]);
+    }
+
+    Some(())
+}
+
+/// Decompress an NRV2B, NRV2D or NRV2E stream, which are all variants of the same LZ77 scheme
+/// differing in how offsets and lengths are coded.
+fn decompress_nrv(method: Method, bytes: &[u8], len: usize) -> Option<Vec<u8>> {
+    let width = match method {
+        Method::Nrv2b(width) | Method::Nrv2d(width) | Method::Nrv2e(width) => width,
+        Method::Lzma => return None,
+    };
+
+    let mut bits = Bits {
+        bytes,
+        offset: 0,
+        width,
+        buf: 0,
+        count: 0,
+    };
+
+    let mut out = Vec::with_capacity(len);
+    let mut last_off = 1u32;
+    loop {
+        while bits.bit()? == 1 {
+            out.push(bits.byte()?);
+        }
+
+        let mut off = match method {
+            Method::Nrv2b(..) => bits.gamma()?,
+            _ => {
+                let mut off = 1u32;
+                loop {
+                    off = off.wrapping_mul(2).wrapping_add(bits.bit()?);
+                    if bits.bit()? == 1 {
+                        break off;
+                    }
+                    off = (off.wrapping_sub(1)).wrapping_mul(2).wrapping_add(bits.bit()?);
+                }
+            }
+        };
+
+        // An offset of two repeats the last one, anything else is followed by it's low byte.
+        let mut match_len = 0;
+        if off == 2 {
+            off = last_off;
+            if !matches!(method, Method::Nrv2b(..)) {
+                match_len = bits.bit()?;
+            }
+        } else {
+            off = off.wrapping_sub(3).wrapping_mul(256).wrapping_add(bits.byte()? as u32);
+            if off == u32::MAX {
+                break;
+            }
+
+            if !matches!(method, Method::Nrv2b(..)) {
+                match_len = (off ^ u32::MAX) & 1;
+                off >>= 1;
+            }
+
+            off = off.wrapping_add(1);
+            last_off = off;
+        }
+
+        let match_len = match method {
+            Method::Nrv2e(..) => {
+                let bit = bits.bit()?;
+                match (match_len, bit) {
+                    (1, _) => 1 + bit,
+                    (_, 1) => 3 + bits.bit()?,
+                    _ => bits.gamma()?.wrapping_add(3),
+                }
+            }
+            _ => {
+                let match_len = match method {
+                    Method::Nrv2b(..) => bits.bit()?,
+                    _ => match_len,
+                };
+
+                match match_len.wrapping_mul(2).wrapping_add(bits.bit()?) {
+                    0 => bits.gamma()?.wrapping_add(2),
+                    match_len => match_len,
+                }
+            }
+        };
+
+        let far = match method {
+            Method::Nrv2b(..) => off > 0xd00,
+            _ => off > 0x500,
+        };
+
+        let match_len = match_len.wrapping_add(far as u32).wrapping_add(1);
+        if out.len() + match_len as usize > len {
+            return None;
+        }
+
+        copy_match(&mut out, off, match_len)?;
+    }
+
+    Some(out)
+}
+
+/// Decompress an LZMA stream, preceded by the two bytes UPX packs it's properties into.
+fn decompress_lzma(bytes: &[u8], len: usize) -> Option<Vec<u8>> {
+    let props = lzma::Properties {
+        pb: (*bytes.first()? & 7) as u32,
+        lp: (*bytes.get(1)? >> 4) as u32,
+        lc: (*bytes.get(1)? & 0xf) as u32,
+    };
+
+    if props.lc > 8 || props.lp > 4 || props.pb > 4 {
+        return None;
+    }
+
+    lzma::decode(props, bytes.get(2..)?, Some(len))
+}
+
+/// Undo the filter `id` UPX applied to code before compressing it, which turns the relative
+/// targets of calls and jumps into absolute ones so they compress better.
+///
+/// The targets are relative to the block, as UPX filters ELF's with no added offset.
+fn unfilter(bytes: &mut [u8], id: u8, cto: u8) -> Option<()> {
+    // Which opcodes have their operand filtered, whether the operand is stored big-endian and
+    // whether it's marked by having `cto` as it's first byte.
+    let (e8, e9, jcc, big_endian, marked) = match id {
+        0x00 => return Some(()),
+        0x11 => (true, false, false, false, false),
+        0x12 => (false, true, false, false, false),
+        0x13 => (true, true, false, false, false),
+        0x14 => (true, false, false, true, false),
+        0x15 => (false, true, false, true, false),
+        0x16 => (true, true, false, true, false),
+        0x24 => (true, false, false, true, true),
+        0x25 => (false, true, false, true, true),
+        0x26 | 0x46 => (true, true, false, true, true),
+        0x49 => (true, true, true, true, true),
+        _ => return None,
+    };
+
+    let mut idx = 0;
+    let mut last_call = 0;
+    while idx + 5 < bytes.len() {
+        let opcode = bytes[idx];
+        let is_jcc = jcc
+            && idx != 0
+            && idx != last_call
+            && bytes[idx - 1] == 0x0f
+            && (0x80..=0x8f).contains(&opcode);
+
+        let filtered = (e8 && opcode == 0xe8) || (e9 && opcode == 0xe9) || is_jcc;
+        if !filtered || (marked && bytes[idx + 1] != cto) {
+            idx += 1;
+            continue;
+        }
+
+        let operand: [u8; 4] = bytes[idx + 1..idx + 5].try_into().ok()?;
+        let target = match big_endian {
+            true => u32::from_be_bytes(operand),
+            false => u32::from_le_bytes(operand),
+        };
+
+        let target = match marked {
+            true => target.wrapping_sub((cto as u32) << 24),
+            false => target,
+        };
+
+        let rel = target.wrapping_sub(idx as u32 + 1);
+        bytes[idx + 1..idx + 5].copy_from_slice(&rel.to_le_bytes());
+        idx += 5;
+        last_call = idx;
+    }
+
+    Some(())
+}
+
+/// Read a `u32` of the packed ELF's endianness.
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let bytes: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(match big_endian {
+        true => u32::from_be_bytes(bytes),
+        false => u32::from_le_bytes(bytes),
+    })
+}
+
+/// Offset of the first compressed block, the original file's length and UPX's block size.
+fn find_blocks(bytes: &[u8], big_endian: bool) -> Option<(usize, usize, usize)> {
+    let mut offset = 0;
+    while let Some(pos) = bytes.get(offset..)?.windows(4).position(|window| window == MAGIC) {
+        // `l_info` holds the magic after a checksum, and is followed by `p_info`.
+        let magic = offset + pos;
+        offset = magic + 1;
+        let Some(l_info) = magic.checked_sub(4) else { continue };
+        let p_info = l_info + HEADER_LEN;
+        let blocks = p_info + HEADER_LEN;
+
+        let (Some(file_len), Some(block_len)) = (
+            u32_at(bytes, p_info + 4, big_endian),
+            u32_at(bytes, p_info + 8, big_endian),
+        ) else {
+            return None;
+        };
+
+        let (Some(unpacked), Some(packed), Some(&method)) = (
+            u32_at(bytes, blocks, big_endian),
+            u32_at(bytes, blocks + 4, big_endian),
+            bytes.get(blocks + 8),
+        ) else {
+            continue;
+        };
+
+        let (file_len, block_len) = (file_len as usize, block_len as usize);
+        let plausible = file_len != 0
+            && file_len <= MAX_FILE_LEN
+            && unpacked != 0
+            && unpacked as usize <= block_len
+            && packed <= unpacked
+            && (packed == unpacked || Method::from_id(method).is_some());
+
+        if plausible {
+            return Some((blocks, file_len, block_len));
+        }
+    }
+
+    None
+}
+
+/// Whether `bytes` are an ELF packed by UPX.
+pub(crate) fn is_packed(bytes: &[u8]) -> bool {
+    let big_endian = bytes.get(5) == Some(&2);
+    bytes.starts_with(b"\x7fELF") && find_blocks(bytes, big_endian).is_some()
+}
+
+/// Original file of the ELF `bytes` packed by UPX together with the compression method used,
+/// None if it isn't packed or uses a method or filter that isn't supported.
+pub(crate) fn unpack(bytes: &[u8]) -> Option<(Method, Vec<u8>)> {
+    if !bytes.starts_with(b"\x7fELF") {
+        return None;
+    }
+
+    let big_endian = bytes[5] == 2;
+    let (mut offset, file_len, block_len) = find_blocks(bytes, big_endian)?;
+    let mut out = Vec::with_capacity(file_len);
+    let mut method = None;
+
+    // Blocks follow each other until one that's empty, each holding the next part of the file.
+    while out.len() < file_len {
+        let unpacked = u32_at(bytes, offset, big_endian)? as usize;
+        let packed = u32_at(bytes, offset + 4, big_endian)? as usize;
+        let (method_id, filter, cto) = match *bytes.get(offset + 8..offset + 11)? {
+            [method_id, filter, cto] => (method_id, filter, cto),
+            _ => return None,
+        };
+        offset += HEADER_LEN;
+
+        if unpacked == 0 {
+            break;
+        }
+
+        if unpacked > block_len || packed > unpacked {
+            return None;
+        }
+
+        let data = bytes.get(offset..offset + packed)?;
+        offset += packed;
+
+        let mut block = if packed == unpacked {
+            data.to_vec()
+        } else {
+            let block_method = Method::from_id(method_id)?;
+            method.get_or_insert(block_method);
+            match block_method {
+                Method::Lzma => decompress_lzma(data, unpacked)?,
+                _ => decompress_nrv(block_method, data, unpacked)?,
+            }
+        };
+
+        if block.len() != unpacked {
+            return None;
+        }
+
+        unfilter(&mut block, filter, cto)?;
+        out.extend_from_slice(&block);
+    }
+
+    // Parts of the file after the last loaded segment might not have been kept.
+    if out.len() > file_len || !out.starts_with(b"\x7fELF") {
+        return None;
+    }
+
+    out.resize(file_len, 0);
+    Some((method?, out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writer of NRV streams with a byte wide bit buffer.
+    #[derive(Default)]
+    struct Writer {
+        out: Vec<u8>,
+        buf: usize,
+        count: u32,
+    }
+
+    impl Writer {
+        fn bit(&mut self, bit: u8) {
+            if self.count == 0 {
+                self.buf = self.out.len();
+                self.out.push(0);
+                self.count = 8;
+            }
+
+            self.count -= 1;
+            self.out[self.buf] |= bit << self.count;
+        }
+
+        fn literal(&mut self, byte: u8) {
+            self.bit(1);
+            self.out.push(byte);
+        }
+
+        fn gamma(&mut self, value: u32) {
+            let bits = 32 - value.leading_zeros();
+            for idx in (0..bits - 1).rev() {
+                self.bit((value >> idx) as u8 & 1);
+                self.bit((idx == 0) as u8);
+            }
+        }
+
+        /// Match of two to four bytes at most 256 bytes back, as coded by NRV2B.
+        fn short_match(&mut self, dist: u32, len: u32) {
+            self.bit(0);
+            self.gamma(3);
+            self.out.push((dist - 1) as u8);
+            self.bit(((len - 1) >> 1) as u8 & 1);
+            self.bit((len - 1) as u8 & 1);
+        }
+
+        fn end(&mut self) {
+            self.bit(0);
+            self.gamma(0x100_0002);
+            self.out.push(0xff);
+        }
+    }
+
+    #[test]
+    fn nrv2b() {
+        let mut writer = Writer::default();
+        for byte in *b"abc" {
+            writer.literal(byte);
+        }
+        writer.short_match(3, 4);
+        writer.literal(b'b');
+        writer.end();
+
+        let method = Method::Nrv2b(Width::Byte);
+        assert_eq!(decompress_nrv(method, &writer.out, 8).unwrap(), b"abcabcab");
+        assert!(decompress_nrv(method, &writer.out, 6).is_none());
+        assert!(decompress_nrv(method, &writer.out[..6], 8).is_none());
+    }
+
+    #[test]
+    fn filters() {
+        // call rel32 0x10 at offset 2, followed by a jz rel32 0x20
+        let original = [0x90, 0x90, 0xe8, 0x10, 0, 0, 0, 0x0f, 0x84, 0x20, 0, 0, 0, 0x90];
+
+        let mut bytes = original;
+        bytes[3..7].copy_from_slice(&(0x10u32 + 3).to_le_bytes());
+        unfilter(&mut bytes, 0x11, 0).unwrap();
+        assert_eq!(bytes, original);
+
+        let mut bytes = original;
+        bytes[3..7].copy_from_slice(&(0x3c00_0000u32 + 0x10 + 3).to_be_bytes());
+        bytes[9..13].copy_from_slice(&(0x3c00_0000u32 + 0x20 + 9).to_be_bytes());
+        unfilter(&mut bytes, 0x49, 0x3c).unwrap();
+        assert_eq!(bytes, original);
+
+        assert!(unfilter(&mut bytes, 0x50, 0).is_none());
+    }
+
+    #[test]
+    fn packed_elf() {
+        let mut writer = Writer::default();
+        for byte in *b"abc" {
+            writer.literal(byte);
+        }
+        for _ in 0..20 {
+            writer.short_match(3, 4);
+        }
+        writer.end();
+
+        let mut original = b"\x7fELF\x02\x01\x01".to_vec();
+        original.resize(0x40, 0);
+        original.extend(b"abc".iter().cycle().take(83));
+
+        let header = |bytes: &mut Vec<u8>, values: [u32; 3]| {
+            for value in values {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        };
+
+        // A stub followed by `l_info`, `p_info`, a stored and a compressed block and the end.
+        let mut bytes = original[..0x40].to_vec();
+        bytes.extend_from_slice(b"stub");
+        header(&mut bytes, [0, u32::from_le_bytes(*b"UPX!"), 0x0d0c]);
+        header(&mut bytes, [0, original.len() as u32, 0x80]);
+        header(&mut bytes, [0x40, 0x40, 0]);
+        bytes.extend_from_slice(&original[..0x40]);
+        header(&mut bytes, [83, writer.out.len() as u32, 3]);
+        bytes.extend_from_slice(&writer.out);
+        header(&mut bytes, [0, u32::from_le_bytes(*b"UPX!"), 0]);
+
+        assert!(is_packed(&bytes));
+        assert_eq!(unpack(&bytes), Some((Method::Nrv2b(Width::Byte), original)));
+        assert!(unpack(&bytes[..0x70]).is_none());
+    }
+}
@@ -0,0 +1,86 @@
+//! Cross references between instructions and the addresses they refer to.
+
+use decoder::Decoded;
+use processor_shared::{Addressed, PhysAddr};
+use std::ops::Range;
+
+pub use decoder::XrefKind;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xref {
+    /// Address of the referencing instruction.
+    pub from: PhysAddr,
+    /// Address being referenced.
+    pub to: PhysAddr,
+    pub kind: XrefKind,
+}
+
+/// References of all decoded instructions, indexed both by source and by target.
+#[derive(Default)]
+pub(crate) struct Xrefs {
+    /// Sorted by [`Xref::from`].
+    by_from: Vec<Xref>,
+    /// Sorted by [`Xref::to`].
+    by_to: Vec<Xref>,
+}
+
+impl Xrefs {
+    pub fn extend(&mut self, xrefs: impl IntoIterator<Item = Xref>) {
+        for xref in xrefs {
+            self.by_from.push(xref);
+            self.by_to.push(xref);
+        }
+
+        self.by_from.sort_unstable_by_key(|xref| xref.from);
+        self.by_to.sort_unstable_by_key(|xref| xref.to);
+    }
+
+    /// Forget all references made by instructions in `range`.
+    pub fn remove_from(&mut self, range: Range<PhysAddr>) {
+        self.by_from.retain(|xref| !range.contains(&xref.from));
+        self.by_to.retain(|xref| !range.contains(&xref.from));
+    }
+
+    pub fn to(&self, addr: PhysAddr) -> &[Xref] {
+        let start = self.by_to.partition_point(|xref| xref.to < addr);
+        let end = self.by_to.partition_point(|xref| xref.to <= addr);
+        &self.by_to[start..end]
+    }
+
+    /// References to any address in `range`, sorted by [`Xref::to`].
+    pub fn to_range(&self, range: Range<PhysAddr>) -> &[Xref] {
+        let start = self.by_to.partition_point(|xref| xref.to < range.start);
+        let end = self.by_to.partition_point(|xref| xref.to < range.end);
+        &self.by_to[start..end]
+    }
+
+    pub fn from(&self, addr: PhysAddr) -> &[Xref] {
+        let start = self.by_from.partition_point(|xref| xref.from < addr);
+        let end = self.by_from.partition_point(|xref| xref.from <= addr);
+        &self.by_from[start..end]
+    }
+
+    /// All references, sorted by [`Xref::from`].
+    pub fn iter(&self) -> impl Iterator<Item = &Xref> {
+        self.by_from.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_from.len()
+    }
+}
+
+/// Collect the references of decoded instructions.
+pub(crate) fn collect<I: Decoded>(instructions: &[Addressed<I>]) -> Vec<Xref> {
+    instructions
+        .iter()
+        .filter_map(|inst| {
+            let (to, kind) = inst.item.reference()?;
+            Some(Xref {
+                from: inst.addr,
+                to,
+                kind,
+            })
+        })
+        .collect()
+}
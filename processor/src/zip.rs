@@ -0,0 +1,153 @@
+//! Reading of zip archives, such as APKs and JARs.
+
+use flate2::read::DeflateDecoder;
+use std::io::Read;
+
+/// Signature of the record ending the archive, which points at the central directory.
+const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+
+/// Signature of each file's entry in the central directory.
+const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+
+/// Signature of the header preceding each file's data.
+const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<usize> {
+    let bytes = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Decompressed files of the `archive` whose name matches `filter`, by their name. None if the
+/// archive is malformed or a file is compressed with anything but deflate.
+pub(crate) fn extract(
+    archive: &[u8],
+    filter: impl Fn(&str) -> bool,
+) -> Option<Vec<(String, Vec<u8>)>> {
+    // the record is 22 bytes followed by a comment of up to 64 KiB
+    let last = archive.len().checked_sub(22)?;
+    let end = (last.saturating_sub(0xffff)..=last)
+        .rev()
+        .find(|&offset| archive[offset..].starts_with(END_OF_DIRECTORY))?;
+
+    let count = u16_at(archive, end + 10)?;
+    let mut offset = u32_at(archive, end + 16)?;
+    let mut files = Vec::new();
+
+    for _ in 0..count {
+        if !archive.get(offset..)?.starts_with(DIRECTORY_ENTRY) {
+            return None;
+        }
+
+        let method = u16_at(archive, offset + 10)?;
+        let compressed_size = u32_at(archive, offset + 20)?;
+        let name_len = u16_at(archive, offset + 28)?;
+        let extra_len = u16_at(archive, offset + 30)?;
+        let comment_len = u16_at(archive, offset + 32)?;
+        let header = u32_at(archive, offset + 42)?;
+        let name = archive.get(offset + 46..offset + 46 + name_len)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        offset += 46 + name_len + extra_len + comment_len;
+
+        if !filter(&name) {
+            continue;
+        }
+
+        // the local header has it's own copy of the name and extra field, which may differ
+        if !archive.get(header..)?.starts_with(LOCAL_HEADER) {
+            return None;
+        }
+
+        let start = header + 30 + u16_at(archive, header + 26)? + u16_at(archive, header + 28)?;
+        let data = archive.get(start..start.checked_add(compressed_size)?)?;
+        let bytes = match method {
+            0 => data.to_vec(),
+            8 => {
+                let mut bytes = Vec::new();
+                DeflateDecoder::new(data).read_to_end(&mut bytes).ok()?;
+                bytes
+            }
+            _ => return None,
+        };
+
+        files.push((name, bytes));
+    }
+
+    Some(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use std::io::Write;
+
+    /// Archive of `files` by `(name, method, data)`, where data is compressed as by `method`.
+    fn build(files: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+
+        for &(name, method, data) in files {
+            let data = match method {
+                8 => {
+                    let mut encoder = DeflateEncoder::new(Vec::new(), Default::default());
+                    encoder.write_all(data).unwrap();
+                    encoder.finish().unwrap()
+                }
+                _ => data.to_vec(),
+            };
+
+            let header = archive.len() as u32;
+            archive.extend_from_slice(LOCAL_HEADER);
+            archive.extend_from_slice(&[0; 4]);
+            archive.extend_from_slice(&method.to_le_bytes());
+            archive.extend_from_slice(&[0; 16]);
+            archive.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            archive.extend_from_slice(&[0; 2]);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(&data);
+
+            directory.extend_from_slice(DIRECTORY_ENTRY);
+            directory.extend_from_slice(&[0; 6]);
+            directory.extend_from_slice(&method.to_le_bytes());
+            directory.extend_from_slice(&[0; 8]);
+            directory.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&[0; 4]);
+            directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]);
+            directory.extend_from_slice(&header.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+
+        let offset = archive.len() as u32;
+        archive.extend_from_slice(&directory);
+        archive.extend_from_slice(END_OF_DIRECTORY);
+        archive.extend_from_slice(&[0; 6]);
+        archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&offset.to_le_bytes());
+        archive.extend_from_slice(&[0; 2]);
+        archive
+    }
+
+    #[test]
+    fn extraction() {
+        let archive = build(&[
+            ("AndroidManifest.xml", 0, b"manifest"),
+            ("classes.dex", 8, b"dex\n035\0dex\n035\0"),
+            ("classes2.dex", 0, b"dex\n039\0"),
+        ]);
+
+        let files = extract(&archive, |name| name.ends_with(".dex")).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0], ("classes.dex".to_string(), b"dex\n035\0dex\n035\0".to_vec()));
+        assert_eq!(files[1], ("classes2.dex".to_string(), b"dex\n039\0".to_vec()));
+
+        assert!(extract(&archive[..archive.len() - 1], |_| true).is_none());
+        assert!(extract(&build(&[("a.class", 12, b"")]), |_| true).is_none());
+    }
+}
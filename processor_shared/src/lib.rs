@@ -28,6 +28,8 @@ pub enum SectionKind {
     Got64,
     /// Null terminated string literals.
     CString,
+    /// Initialized or zero-filled variables, shown as strings, pointers and runs of zeroes.
+    Data,
     /// ExceptionDirectoryEntry's (PE only).
     ExceptionDirEntry,
     /// Elf32Sym.
@@ -247,8 +249,61 @@ pub fn encode_hex_bytes_truncated(bytes: &[u8], max_width: usize, is_padded: boo
     }
 }
 
+/// Results of a search that stops once a maximum number of results is found.
+#[derive(Debug, Clone)]
+pub struct SearchResults<T> {
+    matches: Vec<T>,
+    limit: usize,
+    truncated: bool,
+}
+
+impl<T> SearchResults<T> {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            matches: Vec::new(),
+            limit,
+            truncated: false,
+        }
+    }
+
+    /// Records a match, returns `false` if the limit was reached and the search should stop.
+    pub fn push(&mut self, item: T) -> bool {
+        if self.matches.len() >= self.limit {
+            self.truncated = true;
+            return false;
+        }
+
+        self.matches.push(item);
+        true
+    }
+
+    /// Whether there were more matches than the limit allowed for.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn matches(&self) -> &[T] {
+        &self.matches
+    }
+
+    pub fn into_matches(self) -> Vec<T> {
+        self.matches
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn search_results_truncate() {
+        let mut results = super::SearchResults::new(2);
+        assert!(results.push(1));
+        assert!(results.push(2));
+        assert!(!results.is_truncated());
+        assert!(!results.push(3));
+        assert!(results.is_truncated());
+        assert_eq!(results.matches(), &[1, 2]);
+    }
+
     #[test]
     fn encode_hex_bytes_truncted() {
         assert_eq!(
@@ -0,0 +1,274 @@
+//! Scripts automating analysis, written in [rhai](https://rhai.rs).
+//!
+//! Scripts only get access to the loaded binary and the debugger's breakpoints through the
+//! functions registered here, they can't touch the filesystem or spawn processes.
+
+use debugger::BreakpointSet;
+use processor::{AnnotationKind, Processor};
+use rhai::{Array, Blob, Dynamic, EvalAltResult, Map, INT};
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Most operations a script can perform, stopping scripts that never finish.
+const MAX_OPERATIONS: u64 = 100_000_000;
+
+pub enum Error {
+    IO(std::io::Error),
+    Script(Box<EvalAltResult>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IO(err) => f.write_fmt(format_args!("Failed to read script: {err}.")),
+            Self::Script(err) => f.write_fmt(format_args!("Script failed: {err}.")),
+        }
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// What a script is run against.
+pub struct Context {
+    pub processor: Option<Arc<Processor>>,
+    pub breakpoints: Arc<BreakpointSet>,
+}
+
+/// What a script did that the caller has to show.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Output {
+    /// Lines passed to `print` and `debug`.
+    pub printed: Vec<String>,
+    /// Comments or names were changed.
+    pub annotations_changed: bool,
+    /// Breakpoints were set or cleared.
+    pub breakpoints_changed: bool,
+    /// Last address passed to `show`.
+    pub goto: Option<usize>,
+}
+
+type Result<T> = std::result::Result<T, Box<EvalAltResult>>;
+
+fn loaded(processor: &Option<Arc<Processor>>) -> Result<&Arc<Processor>> {
+    processor.as_ref().ok_or_else(|| "No binary is loaded".into())
+}
+
+fn addr(value: INT) -> Result<usize> {
+    usize::try_from(value).map_err(|_| format!("Invalid address {value}").into())
+}
+
+/// A symbol as seen by scripts, e.g. `#{ addr: 4198400, name: "main" }`.
+fn symbol(addr: usize, name: &str) -> Dynamic {
+    let mut map = Map::new();
+    map.insert("addr".into(), Dynamic::from(addr as INT));
+    map.insert("name".into(), Dynamic::from(name.to_string()));
+    Dynamic::from_map(map)
+}
+
+fn engine(ctx: &Context, output: &Rc<RefCell<Output>>) -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let out = Rc::clone(output);
+    engine.on_print(move |s| out.borrow_mut().printed.push(s.to_string()));
+
+    let out = Rc::clone(output);
+    engine.on_debug(move |s, _, pos| out.borrow_mut().printed.push(format!("{pos:?}: {s}")));
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("functions", move || -> Result<Array> {
+        let processor = loaded(&processor)?;
+        let functions = processor
+            .index
+            .functions()
+            .map(|func| symbol(func.addr, func.item.as_str()))
+            .collect();
+
+        Ok(functions)
+    });
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("sections", move || -> Result<Array> {
+        let processor = loaded(&processor)?;
+        let sections = processor
+            .sections()
+            .map(|section| {
+                let mut map = Map::new();
+                map.insert("name".into(), Dynamic::from(section.name.to_string()));
+                map.insert("start".into(), Dynamic::from(section.start as INT));
+                map.insert("end".into(), Dynamic::from(section.end as INT));
+                Dynamic::from_map(map)
+            })
+            .collect();
+
+        Ok(sections)
+    });
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("symbol", move |value: INT| -> Result<Dynamic> {
+        let processor = loaded(&processor)?;
+        match processor.index.get_sym_by_addr(addr(value)?) {
+            Some(sym) => Ok(Dynamic::from(sym.as_str().to_string())),
+            None => Ok(Dynamic::UNIT),
+        }
+    });
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("address_of", move |name: &str| -> Result<Dynamic> {
+        let processor = loaded(&processor)?;
+        match processor.index.get_func_by_name(name) {
+            Some(addr) => Ok(Dynamic::from(addr as INT)),
+            None => Ok(Dynamic::UNIT),
+        }
+    });
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("read_bytes", move |value: INT, len: INT| -> Result<Blob> {
+        let processor = loaded(&processor)?;
+        let addr = addr(value)?;
+        let section = processor
+            .section_by_addr(addr)
+            .ok_or_else(|| format!("Address {addr:#x} isn't in any section"))?;
+
+        Ok(section.bytes_by_addr(addr, len.max(0) as usize).to_vec())
+    });
+
+    let processor = ctx.processor.clone();
+    engine.register_fn("xrefs_to", move |value: INT| -> Result<Array> {
+        let processor = loaded(&processor)?;
+        let xrefs = processor
+            .xrefs_to(addr(value)?)
+            .into_iter()
+            .map(|xref| Dynamic::from(xref.from as INT))
+            .collect();
+
+        Ok(xrefs)
+    });
+
+    let processor = ctx.processor.clone();
+    let out = Rc::clone(output);
+    engine.register_fn("comment", move |value: INT, text: &str| -> Result<()> {
+        let processor = loaded(&processor)?;
+        processor.annotate(AnnotationKind::Comment, addr(value)?, text);
+        out.borrow_mut().annotations_changed = true;
+        Ok(())
+    });
+
+    let processor = ctx.processor.clone();
+    let out = Rc::clone(output);
+    engine.register_fn("rename", move |value: INT, name: &str| -> Result<()> {
+        let processor = loaded(&processor)?;
+        processor.annotate(AnnotationKind::Rename, addr(value)?, name);
+        out.borrow_mut().annotations_changed = true;
+        Ok(())
+    });
+
+    let processor = ctx.processor.clone();
+    let out = Rc::clone(output);
+    engine.register_fn("prototype", move |value: INT, decl: &str| -> Result<()> {
+        let processor = loaded(&processor)?;
+        if !processor.annotate(AnnotationKind::Prototype, addr(value)?, decl) {
+            return Err(format!("Invalid prototype '{decl}'").into());
+        }
+        out.borrow_mut().annotations_changed = true;
+        Ok(())
+    });
+
+    let breakpoints = Arc::clone(&ctx.breakpoints);
+    let out = Rc::clone(output);
+    engine.register_fn("set_breakpoint", move |value: INT| -> Result<()> {
+        let addr = addr(value)?;
+        if !breakpoints.contains(addr) {
+            breakpoints.toggle(addr);
+            out.borrow_mut().breakpoints_changed = true;
+        }
+        Ok(())
+    });
+
+    let breakpoints = Arc::clone(&ctx.breakpoints);
+    let out = Rc::clone(output);
+    engine.register_fn("clear_breakpoint", move |value: INT| -> Result<()> {
+        let addr = addr(value)?;
+        if breakpoints.contains(addr) {
+            breakpoints.toggle(addr);
+            out.borrow_mut().breakpoints_changed = true;
+        }
+        Ok(())
+    });
+
+    let breakpoints = Arc::clone(&ctx.breakpoints);
+    engine.register_fn("breakpoints", move || -> Array {
+        breakpoints.addrs().into_iter().map(|addr| Dynamic::from(addr as INT)).collect()
+    });
+
+    let out = Rc::clone(output);
+    engine.register_fn("show", move |value: INT| -> Result<()> {
+        out.borrow_mut().goto = Some(addr(value)?);
+        Ok(())
+    });
+
+    engine
+}
+
+/// Run the script `src`.
+pub fn run(ctx: &Context, src: &str) -> std::result::Result<Output, Error> {
+    let output = Rc::new(RefCell::new(Output::default()));
+    engine(ctx, &output).run(src).map_err(Error::Script)?;
+    Ok(output.take())
+}
+
+/// Run the script stored at `path`.
+pub fn run_file<P: AsRef<Path>>(ctx: &Context, path: P) -> std::result::Result<Output, Error> {
+    let src = std::fs::read_to_string(path).map_err(Error::IO)?;
+    run(ctx, &src)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> Context {
+        Context {
+            processor: None,
+            breakpoints: Arc::new(BreakpointSet::default()),
+        }
+    }
+
+    #[test]
+    fn printing() {
+        let output = run(&context(), r#"for i in 0..3 { print(`line ${i}`); }"#).unwrap();
+        assert_eq!(output.printed, ["line 0", "line 1", "line 2"]);
+        assert!(!output.annotations_changed);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let ctx = context();
+        let src = "set_breakpoint(0x1000); set_breakpoint(0x1000); set_breakpoint(0x2000); \
+                   clear_breakpoint(0x2000); print(breakpoints().len()); show(0x1000);";
+
+        let output = run(&ctx, src).unwrap();
+        assert_eq!(ctx.breakpoints.addrs(), [0x1000]);
+        assert_eq!(output.printed, ["1"]);
+        assert_eq!(output.goto, Some(0x1000));
+        assert!(output.breakpoints_changed);
+    }
+
+    #[test]
+    fn without_binary() {
+        assert!(run(&context(), "functions()").is_err());
+        assert!(run(&context(), "set_breakpoint(-1)").is_err());
+    }
+
+    #[test]
+    fn endless() {
+        assert!(run(&context(), "loop {}").is_err());
+    }
+}
@@ -6,19 +6,289 @@
 #[cfg(not(any(target_family = "windows", target_family = "unix")))]
 compile_error!("Bite can only be build for windows, macos and linux.");
 
+mod triage;
+#[cfg(feature = "gui")]
 mod wayland;
 use commands::ARGS;
+use std::io::{self, BufWriter, StdoutLock, Write};
 
 fn main() {
-    #[cfg(target_os = "linux")]
+    #[cfg(all(feature = "gui", target_os = "linux"))]
     if nix::unistd::getuid() == 0.into() {
         wayland::set_env();
     }
 
     if ARGS.disassemble {
-        let mut ui = gui::UI::new().unwrap();
-        ui.process_args();
-        ui.run();
-        return;
+        #[cfg(feature = "gui")]
+        {
+            let mut ui = gui::UI::new().unwrap();
+            ui.process_args();
+            ui.run();
+            return;
+        }
+
+        #[cfg(not(feature = "gui"))]
+        {
+            eprintln!("Bite was built without a GUI, see `--analyze` or `--dump-asm`.");
+            std::process::exit(1);
+        }
+    }
+
+    if ARGS.analyze {
+        analyze();
+    }
+
+    if ARGS.metadata {
+        print_metadata();
+    }
+
+    if ARGS.sections {
+        print_sections();
+    }
+
+    if ARGS.checksec {
+        print_checksec();
+    }
+
+    if ARGS.strings {
+        print_strings();
+    }
+
+    if ARGS.dump_asm {
+        dump_asm();
+    }
+
+    if let Some(ref script) = ARGS.script {
+        run_script(script);
+    }
+
+    if let Some(ref addr) = ARGS.gdbserver {
+        gdbserver(addr);
+    }
+
+    if let Some(ref dir) = ARGS.triage {
+        triage(dir);
+    }
+}
+
+/// Parse the object at `path`, or the image given by `--image` if it's a shared cache, archive or
+/// fat Mach-O.
+fn parse(path: &std::path::Path) -> Result<processor::Processor, processor::Error> {
+    if ARGS.raw {
+        return processor::Processor::parse_raw(path, &raw_options());
+    }
+
+    match ARGS.image {
+        Some(ref image) => processor::Processor::parse_image(path, image),
+        None => processor::Processor::parse(path),
+    }
+}
+
+/// Parse the object given on the command line, exiting if it can't be.
+fn load_or_exit() -> processor::Processor {
+    let path = ARGS.path.as_ref().expect("validated by the cli");
+    match parse(path) {
+        Ok(processor) => processor,
+        Err(err) => {
+            eprintln!("{err:?}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Write `what` to stdout with `f`, exiting if that fails. The output being closed early, as
+/// when it's piped into a program that exits early, isn't a failure.
+fn write_or_exit(what: &str, f: impl FnOnce(&mut BufWriter<StdoutLock>) -> io::Result<()>) {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    if let Err(err) = f(&mut stdout).and_then(|()| stdout.flush()) {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("Failed to write {what}: {err}.");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// How to load a raw object given `--arch`, `--base` and `--endian`.
+fn raw_options() -> processor::RawOptions {
+    let arch = ARGS.arch.as_deref().expect("validated by the cli");
+    match processor::RawOptions::parse(arch, ARGS.base, ARGS.big_endian) {
+        Some(options) => options,
+        None => {
+            let names: Vec<&str> = processor::ARCHITECTURES.iter().map(|(name, _)| *name).collect();
+            eprintln!("Unknown architecture '{arch}', expected one of {}.", names.join(", "));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn dump_asm() {
+    let processor = load_or_exit();
+    let boundaries = processor.compute_block_boundaries();
+    let format = ARGS.format.unwrap_or_default();
+    write_or_exit("listing", |out| processor.export(&boundaries, format, out));
+}
+
+fn analyze() {
+    let processor = load_or_exit();
+    let report = processor.report();
+    write_or_exit("report", |out| {
+        if ARGS.json {
+            report.write_json(out)
+        } else {
+            report.write_text(out)
+        }
+    });
+}
+
+fn print_metadata() {
+    let processor = load_or_exit();
+    let metadata = processor.metadata();
+    write_or_exit("metadata", |out| {
+        if ARGS.json {
+            metadata.write_json(out)
+        } else {
+            metadata.write_text(out)
+        }
+    });
+}
+
+fn print_checksec() {
+    let processor = load_or_exit();
+    let security = match processor.checksec() {
+        Some(security) => security,
+        None => {
+            eprintln!("Mitigations can only be read from ELF, PE and Mach-O objects.");
+            std::process::exit(1);
+        }
+    };
+
+    write_or_exit("mitigations", |out| {
+        if ARGS.json {
+            security.write_json(out)
+        } else {
+            security.write_text(out)
+        }
+    });
+}
+
+fn print_sections() {
+    let processor = load_or_exit();
+    let layout = processor.layout();
+    write_or_exit("sections", |out| {
+        if ARGS.json {
+            layout.write_json(out)
+        } else {
+            layout.write_text(out)
+        }
+    });
+}
+
+fn print_strings() {
+    let processor = load_or_exit();
+    let strings = processor.find_strings(None);
+    write_or_exit("strings", |out| {
+        for string in strings.matches() {
+            writeln!(
+                out,
+                "{:0>10X} {:<20} {:<6} {:?}",
+                string.addr,
+                processor.section_name(string.addr).unwrap_or("?"),
+                string.encoding.as_str(),
+                string.text
+            )?;
+        }
+
+        Ok(())
+    });
+
+    if strings.is_truncated() {
+        eprintln!("Stopped after {} strings, see `search.max_results`.", strings.matches().len());
+    }
+}
+
+fn run_script(script: &std::path::Path) {
+    let processor = load_or_exit();
+    let ctx = scripting::Context {
+        processor: Some(std::sync::Arc::new(processor)),
+        breakpoints: Default::default(),
+    };
+
+    match scripting::run_file(&ctx, script) {
+        Ok(output) => output.printed.iter().for_each(|line| println!("{line}")),
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn gdbserver(addr: &str) {
+    use debugger::{BreakpointSet, Debugger, Environment};
+    use memmap2::Mmap;
+    use object::Object;
+    use std::sync::Arc;
+
+    let path = ARGS.path.as_ref().expect("validated by the cli");
+    // Mapped rather than read, as only the headers are needed.
+    let binary = match std::fs::File::open(path).and_then(|file| unsafe { Mmap::map(&file) }) {
+        Ok(binary) => binary,
+        Err(err) => {
+            eprintln!("Failed to read {path:?}: {err}.");
+            std::process::exit(1);
+        }
+    };
+
+    // Only the entrypoint is needed to find where the binary gets loaded.
+    let entrypoint = match object::File::parse(&binary[..]) {
+        Ok(obj) => obj.entry() as usize,
+        Err(err) => {
+            eprintln!("{err}.");
+            std::process::exit(1);
+        }
+    };
+
+    let breakpoints = Arc::new(BreakpointSet::default());
+    let env = Environment::default();
+    let spawned = Debugger::spawn(path, &[], &env, Arc::clone(&breakpoints), entrypoint, None);
+    let debugger = match spawned {
+        Ok(debugger) => debugger,
+        Err(err) => {
+            eprintln!("Failed to start {path:?}: {err}");
+            std::process::exit(1);
+        }
+    };
+
+    let addr = debugger::gdbserver::listen_addr(addr);
+    let listener = match std::net::TcpListener::bind(&addr) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Failed to listen on {addr}: {err}.");
+            std::process::exit(1);
+        }
+    };
+
+    eprintln!("Process {} is waiting for a connection on {addr}.", debugger.pid());
+    if let Err(err) = debugger::gdbserver::serve(debugger, breakpoints, listener) {
+        eprintln!("{err}");
+        std::process::exit(1);
     }
 }
+
+fn triage(dir: &std::path::Path) {
+    let processor = load_or_exit();
+    let report = match triage::triage(&processor, dir, &ARGS.triage_args) {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("Failed to read the inputs in {dir:?}: {err}.");
+            std::process::exit(1);
+        }
+    };
+
+    write_or_exit("report", |out| {
+        if ARGS.json {
+            report.write_json(out)
+        } else {
+            report.write_markdown(out)
+        }
+    });
+}
@@ -0,0 +1,424 @@
+//! Triage of the inputs a fuzzer found to crash the object, see `--triage`.
+//!
+//! Each input is run under the debugger until the process crashes, exits or runs out of time.
+//! Crashes are grouped by a hash of the innermost frames of their call stack that are in the
+//! object, so that inputs crashing the same way are reported once.
+
+use debugger::{BreakpointSet, CallFrameInfo, Console, Debugger, Environment, Stop};
+use processor::{write_json_str, Processor};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// Time an input is given to crash before the process is killed.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Innermost frames in the object that tell crashes apart.
+const HASHED_FRAMES: usize = 5;
+
+/// Instructions shown before and after the one that crashed.
+const CONTEXT: usize = 4;
+
+/// Argument replaced by the path of the input, as in AFL. Without it the input is written to
+/// the process' stdin.
+const INPUT_ARG: &str = "@@";
+
+/// Signals raised by a process crashing, along with their names.
+#[cfg(unix)]
+const CRASHES: [(i32, &str); 6] = [
+    (nix::libc::SIGILL, "SIGILL"),
+    (nix::libc::SIGTRAP, "SIGTRAP"),
+    (nix::libc::SIGABRT, "SIGABRT"),
+    (nix::libc::SIGBUS, "SIGBUS"),
+    (nix::libc::SIGFPE, "SIGFPE"),
+    (nix::libc::SIGSEGV, "SIGSEGV"),
+];
+
+/// Signals raised by a process crashing, along with their names. Exceptions are reported as
+/// the signal Linux raises for them.
+#[cfg(windows)]
+const CRASHES: [(i32, &str); 6] = [
+    (4, "SIGILL"),
+    (5, "SIGTRAP"),
+    (6, "SIGABRT"),
+    (7, "SIGBUS"),
+    (8, "SIGFPE"),
+    (11, "SIGSEGV"),
+];
+
+fn crash_signal(sig: i32) -> Option<&'static str> {
+    CRASHES.iter().find(|&&(crash, _)| crash == sig).map(|&(_, name)| name)
+}
+
+/// Frame on the call stack of a crash.
+struct Frame {
+    addr: usize,
+    /// Symbol the address is in, followed by the offset into it.
+    symbol: Option<String>,
+}
+
+/// Inputs that crashed the same way.
+struct Crash {
+    /// Hash of the signal and the innermost frames in the object.
+    hash: u64,
+    signal: &'static str,
+    /// Runtime address of the memory accessed, if the signal reports one.
+    fault_addr: Option<usize>,
+    /// Innermost first, starting with where it crashed.
+    frames: Vec<Frame>,
+    /// Addresses and text of the instructions around where it crashed.
+    disassembly: Vec<(usize, String)>,
+    inputs: Vec<PathBuf>,
+}
+
+/// How running an input ended when it didn't crash.
+enum Outcome {
+    Exited(i32),
+    Killed(i32),
+    TimedOut,
+    Failed(String),
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exited(code) => f.write_fmt(format_args!("exited with code {code}")),
+            Self::Killed(sig) => f.write_fmt(format_args!("killed by signal {sig}")),
+            Self::TimedOut => f.write_fmt(format_args!("timed out after {}s", TIMEOUT.as_secs())),
+            Self::Failed(err) => f.write_fmt(format_args!("failed to run: {err}")),
+        }
+    }
+}
+
+/// Crashes of the inputs in a directory, grouped by how they crashed.
+pub struct Report {
+    binary: PathBuf,
+    crashes: Vec<Crash>,
+    /// Inputs that didn't crash and why.
+    others: Vec<(PathBuf, Outcome)>,
+}
+
+/// FNV-1a, which is the same across runs and platforms.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+fn symbolize(processor: &Processor, addr: usize) -> Option<String> {
+    let (start, symbol) = processor.index.get_sym_containing(addr)?;
+    Some(match addr - start {
+        0 => symbol.as_str().to_string(),
+        offset => format!("{}+{offset:#x}", symbol.as_str()),
+    })
+}
+
+/// Where the stopped process crashed, without the inputs.
+fn crash(
+    debugger: &Debugger,
+    processor: &Processor,
+    cfi: &CallFrameInfo,
+    signal: &'static str,
+    pc: usize,
+) -> Crash {
+    let mut addrs: Vec<usize> = match debugger.backtrace(cfi) {
+        Ok(frames) => frames.iter().map(|frame| frame.pc).collect(),
+        Err(_) => Vec::new(),
+    };
+
+    if addrs.first() != Some(&pc) {
+        addrs.insert(0, pc);
+    }
+
+    // Addresses outside of the object differ between runs as libraries are loaded at random.
+    let hash = addrs
+        .iter()
+        .filter(|&&addr| processor.section_by_addr(addr).is_some())
+        .take(HASHED_FRAMES)
+        .fold(fnv1a(0xcbf29ce484222325, signal.as_bytes()), |hash, addr| {
+            fnv1a(hash, &(*addr as u64).to_le_bytes())
+        });
+
+    let frames = addrs
+        .into_iter()
+        .map(|addr| Frame {
+            addr,
+            symbol: symbolize(processor, addr),
+        })
+        .collect();
+
+    Crash {
+        hash,
+        signal,
+        fault_addr: debugger.fault_addr().ok().flatten(),
+        frames,
+        disassembly: processor.instructions_around(pc, CONTEXT, CONTEXT),
+        inputs: Vec::new(),
+    }
+}
+
+/// Run the object with `input`, returning where it crashed.
+fn run(
+    processor: &Processor,
+    cfi: &CallFrameInfo,
+    args: &[String],
+    input: &Path,
+) -> Result<Crash, Outcome> {
+    let failed = |err: &dyn std::fmt::Display| Outcome::Failed(err.to_string());
+
+    let (console, stdio) = Console::new().map_err(|err| failed(&err))?;
+    let by_path = args.iter().any(|arg| arg.contains(INPUT_ARG));
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.replace(INPUT_ARG, &input.to_string_lossy()))
+        .collect();
+
+    let bytes = match by_path {
+        true => Vec::new(),
+        false => std::fs::read(input).map_err(|err| failed(&err))?,
+    };
+
+    let breakpoints = Arc::new(BreakpointSet::default());
+    let env = Environment::default();
+    let (path, entrypoint) = (&processor.path, processor.entrypoint);
+    let mut debugger = Debugger::spawn(path, &args, &env, breakpoints, entrypoint, Some(stdio))
+        .map_err(|err| failed(&err))?;
+
+    // The stdin is closed once the input is written, the output has to be read for the process
+    // not to block on writing it.
+    let Console {
+        mut stdin,
+        stdout,
+        stderr,
+    } = console;
+
+    std::thread::spawn(move || stdin.write_all(&bytes));
+    for mut stream in [stdout, stderr] {
+        std::thread::spawn(move || io::copy(&mut stream, &mut io::sink()));
+    }
+
+    let handle = debugger.handle();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let (done, finished) = mpsc::channel::<()>();
+    {
+        let timed_out = Arc::clone(&timed_out);
+        std::thread::spawn(move || {
+            if finished.recv_timeout(TIMEOUT) == Err(mpsc::RecvTimeoutError::Timeout) {
+                timed_out.store(true, Ordering::Release);
+                handle.kill();
+            }
+        });
+    }
+
+    let result = loop {
+        match debugger.cont() {
+            Ok(Stop::Signal(sig, pc)) => match crash_signal(sig) {
+                Some(signal) => break Ok(crash(&debugger, processor, cfi, signal, pc)),
+                // Other signals are delivered as the process is continued.
+                None => continue,
+            },
+            Ok(Stop::Exited(code)) => break Err(Outcome::Exited(code)),
+            Ok(Stop::Killed(_)) if timed_out.load(Ordering::Acquire) => {
+                break Err(Outcome::TimedOut)
+            }
+            Ok(Stop::Killed(sig)) => break Err(Outcome::Killed(sig)),
+            Ok(_) => continue,
+            Err(err) => break Err(failed(&err)),
+        }
+    };
+
+    drop(done);
+    result
+}
+
+/// Files in `dir` that are inputs, sorted by their name. Dotfiles and the readme AFL writes
+/// next to them are left out.
+fn inputs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut inputs = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with('.') || name == "README.txt" || !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        inputs.push(entry.path());
+    }
+
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Run each input in `dir` with `args`, reporting progress to stderr.
+pub fn triage(processor: &Processor, dir: &Path, args: &[String]) -> io::Result<Report> {
+    let cfi = match processor.sections().find(|section| section.name == ".eh_frame") {
+        Some(section) => CallFrameInfo::new(section.bytes().to_vec(), section.start),
+        None => CallFrameInfo::default(),
+    };
+
+    let inputs = inputs(dir)?;
+    let count = inputs.len();
+    let mut report = Report {
+        binary: processor.path.clone(),
+        crashes: Vec::new(),
+        others: Vec::new(),
+    };
+
+    for (idx, input) in inputs.into_iter().enumerate() {
+        eprintln!("[{}/{count}] {}", idx + 1, input.display());
+
+        match run(processor, &cfi, args, &input) {
+            Ok(mut crash) => match report.crashes.iter_mut().find(|c| c.hash == crash.hash) {
+                Some(known) => known.inputs.push(input),
+                None => {
+                    crash.inputs.push(input);
+                    report.crashes.push(crash);
+                }
+            },
+            Err(outcome) => report.others.push((input, outcome)),
+        }
+    }
+
+    Ok(report)
+}
+
+impl Crash {
+    /// Where it crashed, as a symbol if there's one.
+    fn location(&self) -> String {
+        match self.frames.first() {
+            Some(Frame {
+                symbol: Some(symbol),
+                ..
+            }) => symbol.clone(),
+            Some(frame) => format!("{:#x}", frame.addr),
+            None => "??".to_string(),
+        }
+    }
+
+    fn crash_addr(&self) -> Option<usize> {
+        self.frames.first().map(|frame| frame.addr)
+    }
+}
+
+impl Report {
+    pub fn write_markdown<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "# Triage of {}\n", self.binary.display())?;
+        writeln!(
+            w,
+            "{} inputs, {} unique crashes, {} didn't crash.",
+            self.crashes.iter().map(|crash| crash.inputs.len()).sum::<usize>() + self.others.len(),
+            self.crashes.len(),
+            self.others.len()
+        )?;
+
+        for (idx, crash) in self.crashes.iter().enumerate() {
+            writeln!(w, "\n## Crash {}: {} in {}\n", idx + 1, crash.signal, crash.location())?;
+            writeln!(w, "- Stack hash: `{:016x}`", crash.hash)?;
+            if let Some(addr) = crash.fault_addr {
+                writeln!(w, "- Faulting address: `{addr:#x}`")?;
+            }
+
+            writeln!(w, "- Inputs ({}):", crash.inputs.len())?;
+            for input in &crash.inputs {
+                writeln!(w, "  - `{}`", input.display())?;
+            }
+
+            writeln!(w, "\nCall stack:\n\n```")?;
+            for (idx, frame) in crash.frames.iter().enumerate() {
+                let symbol = frame.symbol.as_deref().unwrap_or("??");
+                writeln!(w, "#{idx:<3} {:0>10X} {symbol}", frame.addr)?;
+            }
+            writeln!(w, "```")?;
+
+            if !crash.disassembly.is_empty() {
+                writeln!(w, "\nDisassembly:\n\n```")?;
+                for (addr, text) in &crash.disassembly {
+                    let marker = if Some(*addr) == crash.crash_addr() { '>' } else { ' ' };
+                    writeln!(w, "{marker} {addr:0>10X}  {text}")?;
+                }
+                writeln!(w, "```")?;
+            }
+        }
+
+        if !self.others.is_empty() {
+            writeln!(w, "\n## Inputs that didn't crash\n")?;
+            for (input, outcome) in &self.others {
+                writeln!(w, "- `{}`: {outcome}", input.display())?;
+            }
+        }
+
+        w.flush()
+    }
+
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"{\n  \"binary\": ")?;
+        write_json_str(w, &self.binary.to_string_lossy())?;
+
+        w.write_all(b",\n  \"crashes\": [")?;
+        for (idx, crash) in self.crashes.iter().enumerate() {
+            w.write_all(if idx == 0 { b"\n    " } else { b",\n    " })?;
+            write!(w, "{{\"hash\": \"{:016x}\", \"signal\": \"{}\"", crash.hash, crash.signal)?;
+            match crash.fault_addr {
+                Some(addr) => write!(w, ", \"fault_addr\": {addr}")?,
+                None => w.write_all(b", \"fault_addr\": null")?,
+            }
+
+            w.write_all(b", \"frames\": [")?;
+            for (idx, frame) in crash.frames.iter().enumerate() {
+                if idx != 0 {
+                    w.write_all(b", ")?;
+                }
+
+                write!(w, "{{\"addr\": {}, \"symbol\": ", frame.addr)?;
+                match frame.symbol {
+                    Some(ref symbol) => write_json_str(w, symbol)?,
+                    None => w.write_all(b"null")?,
+                }
+                w.write_all(b"}")?;
+            }
+
+            w.write_all(b"], \"disassembly\": [")?;
+            for (idx, (addr, text)) in crash.disassembly.iter().enumerate() {
+                if idx != 0 {
+                    w.write_all(b", ")?;
+                }
+
+                write!(w, "{{\"addr\": {addr}, \"text\": ")?;
+                write_json_str(w, text)?;
+                w.write_all(b"}")?;
+            }
+
+            w.write_all(b"], \"inputs\": [")?;
+            for (idx, input) in crash.inputs.iter().enumerate() {
+                if idx != 0 {
+                    w.write_all(b", ")?;
+                }
+
+                write_json_str(w, &input.to_string_lossy())?;
+            }
+            w.write_all(b"]}")?;
+        }
+
+        if !self.crashes.is_empty() {
+            w.write_all(b"\n  ")?;
+        }
+
+        w.write_all(b"],\n  \"others\": [")?;
+        for (idx, (input, outcome)) in self.others.iter().enumerate() {
+            w.write_all(if idx == 0 { b"\n    " } else { b",\n    " })?;
+            w.write_all(b"{\"input\": ")?;
+            write_json_str(w, &input.to_string_lossy())?;
+            w.write_all(b", \"outcome\": ")?;
+            write_json_str(w, &outcome.to_string())?;
+            w.write_all(b"}")?;
+        }
+
+        if !self.others.is_empty() {
+            w.write_all(b"\n  ")?;
+        }
+
+        w.write_all(b"]\n}\n")?;
+        w.flush()
+    }
+}
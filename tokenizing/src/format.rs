@@ -0,0 +1,89 @@
+//! Options for how instructions are turned into tokens, applied whenever they're tokenized.
+
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Format instructions are currently tokenized with.
+static CURRENT: RwLock<Format> = RwLock::new(Format::DEFAULT);
+
+/// The format instructions are currently tokenized with.
+pub fn format() -> Format {
+    *CURRENT.read().unwrap()
+}
+
+/// Tokenize instructions with `format` from now on, those tokenized before being left as is.
+pub fn set_format(format: Format) {
+    *CURRENT.write().unwrap() = format;
+}
+
+/// Flavor of assembly syntax, of which only x86 has several.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Syntax {
+    #[default]
+    Intel,
+    Att,
+}
+
+impl Syntax {
+    pub const ALL: [Self; 2] = [Self::Intel, Self::Att];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Intel => "Intel",
+            Self::Att => "AT&T",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Format {
+    pub syntax: Syntax,
+    /// Whether mnemonics are shown in uppercase.
+    pub uppercase: bool,
+    /// Whether immediates are shown in hexadecimal instead of decimal. Addresses are always
+    /// shown in hexadecimal.
+    pub hex_immediates: bool,
+    /// Whether the bytes encoding an instruction are shown in front of it.
+    pub opcode_bytes: bool,
+    /// Whether addresses are shown as the symbol they're in plus an offset, instead of as is.
+    pub symbols: bool,
+    /// Most bytes shown of an instruction, those of longer instructions being cut short.
+    pub bytes_width: usize,
+    /// Characters mnemonics are padded to, so that the operands following them line up.
+    pub mnemonic_width: usize,
+    /// Characters instructions are padded to, so that the comments following them line up.
+    pub instruction_width: usize,
+}
+
+impl Format {
+    pub const DEFAULT: Self = Self {
+        syntax: Syntax::Intel,
+        uppercase: false,
+        hex_immediates: true,
+        opcode_bytes: true,
+        symbols: true,
+        bytes_width: 8,
+        mnemonic_width: 8,
+        instruction_width: 40,
+    };
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// `text` in decimal if it's a number in hexadecimal, such as `#-0x10`.
+pub(crate) fn to_decimal(text: &str) -> Option<String> {
+    let start = text.find("0x")?;
+    let (prefix, digits) = (&text[..start], &text[start + 2..]);
+    if !prefix.chars().all(|c| matches!(c, '-' | '#' | '$')) {
+        return None;
+    }
+
+    let value = u64::from_str_radix(digits, 16).ok()?;
+    Some(format!("{prefix}{value}"))
+}
@@ -1,8 +1,13 @@
-//! Colors used for rendering text in the GUI.
+//! Tokens of text rendered in the GUI, colored by what they are through a [`Theme`].
 use std::ops::Deref;
 use std::sync::Arc;
 
 pub use egui::Color32;
+pub use format::{format, set_format, Format, Syntax};
+pub use theme::{set_theme, theme, Theme};
+
+mod format;
+mod theme;
 
 // TODO: Uniform colors for different instructions sets.
 //       These groupings are from:
@@ -64,28 +69,77 @@ impl Deref for MaybeStatic {
     }
 }
 
+/// What a token is, deciding it's color through the current [`Theme`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Plain,
+    /// Text of lesser importance, such as a placeholder.
+    Muted,
+    Mnemonic,
+    Register,
+    Immediate,
+    /// Operators and separators inside of an operand.
+    Expr,
+    Bracket,
+    Delimiter,
+    Label,
+    Section,
+    Segment,
+    /// Part of a name, such as a path component of a symbol.
+    Component,
+    Primitive,
+    Pointer,
+    Annotation,
+    Invalid,
+    String,
+    Comment,
+    Bytes,
+    /// An address in the column in front of a line.
+    Address,
+    Keyword,
+    Type,
+    Field,
+    Function,
+    Operator,
+    Variable,
+    Constant,
+    /// A color outside of the theme, such as that of a role set in the config.
+    Custom(Color32),
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub text: MaybeStatic,
-    pub color: Color32,
+    pub kind: Kind,
+    /// The address the token refers to, letting it be interacted with.
+    pub target: Option<usize>,
 }
 
 impl Token {
     #[inline(always)]
-    pub fn from_str(text: &'static str, color: Color32) -> Self {
+    pub fn from_str(text: &'static str, kind: Kind) -> Self {
         Self {
             text: MaybeStatic::Static(text),
-            color,
+            kind,
+            target: None,
         }
     }
 
     #[inline(always)]
-    pub fn from_string(text: String, color: Color32) -> Self {
+    pub fn from_string(text: String, kind: Kind) -> Self {
         Self {
             text: MaybeStatic::Dynamic(Arc::from(text)),
-            color,
+            kind,
+            target: None,
         }
     }
+
+    #[inline(always)]
+    pub fn with_target(mut self, addr: usize) -> Self {
+        self.target = Some(addr);
+        self
+    }
 }
 
 impl PartialEq for Token {
@@ -98,25 +152,59 @@ impl PartialEq for Token {
 #[derive(Debug)]
 pub struct TokenStream {
     pub inner: Vec<Token>,
+    /// Format at the time the stream was created, which tokens are pushed in.
+    pub format: Format,
 }
 
 impl TokenStream {
     pub fn new() -> Self {
         Self {
             inner: Vec::with_capacity(25),
+            format: format(),
         }
     }
 
-    pub fn push_token(&mut self, token: Token) {
+    /// Push `token`, changing the case of mnemonics and the base of immediates to the format's.
+    pub fn push_token(&mut self, mut token: Token) {
+        match token.kind {
+            Kind::Mnemonic if self.format.uppercase => {
+                token.text = MaybeStatic::Dynamic(Arc::from(token.text.to_uppercase()));
+            }
+            Kind::Immediate if !self.format.hex_immediates && token.target.is_none() => {
+                if let Some(text) = format::to_decimal(&token.text) {
+                    token.text = MaybeStatic::Dynamic(Arc::from(text));
+                }
+            }
+            _ => {}
+        }
+
         self.inner.push(token);
     }
 
-    pub fn push(&mut self, text: &'static str, color: Color32) {
-        self.push_token(Token::from_str(text, color));
+    pub fn push(&mut self, text: &'static str, kind: Kind) {
+        self.push_token(Token::from_str(text, kind));
     }
 
-    pub fn push_owned(&mut self, text: String, color: Color32) {
-        self.push_token(Token::from_string(text, color));
+    pub fn push_owned(&mut self, text: String, kind: Kind) {
+        self.push_token(Token::from_string(text, kind));
+    }
+
+    /// Push `text` showing the address `addr`.
+    pub fn push_addr(&mut self, text: String, kind: Kind, addr: usize) {
+        self.push_token(Token::from_string(text, kind).with_target(addr));
+    }
+
+    /// Push the name of a symbol defined at `addr`.
+    pub fn push_symbol(&mut self, name: &[Token], addr: usize) {
+        let name = name.iter().map(|token| token.clone().with_target(addr));
+        self.inner.extend(name);
+    }
+
+    /// Push the `offset` of `addr` into the symbol just pushed, if it isn't the symbol's start.
+    pub fn push_offset(&mut self, offset: usize, addr: usize) {
+        if offset != 0 {
+            self.push_addr(format!("+{offset:#x}"), Kind::Label, addr);
+        }
     }
 
     pub fn clear(&mut self) {
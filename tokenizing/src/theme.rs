@@ -0,0 +1,273 @@
+//! Palettes mapping the kind of a token to it's color, one of which is used at a time.
+
+use crate::{colors, Kind};
+use egui::Color32;
+use std::sync::RwLock;
+
+macro_rules! color {
+    ($r:expr, $g:expr, $b:expr) => {
+        Color32::from_rgb($r, $g, $b)
+    };
+}
+
+/// Theme that tokens are currently colored with.
+static CURRENT: RwLock<Theme> = RwLock::new(Theme::DARK);
+
+/// The theme tokens are currently colored with.
+pub fn theme() -> Theme {
+    *CURRENT.read().unwrap()
+}
+
+/// Color tokens with `theme` from now on, which is picked up the next time they're drawn.
+pub fn set_theme(theme: Theme) {
+    *CURRENT.write().unwrap() = theme;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    /// Whether text is drawn light on a dark background.
+    pub dark: bool,
+    pub plain: Color32,
+    pub muted: Color32,
+    pub mnemonic: Color32,
+    pub register: Color32,
+    pub immediate: Color32,
+    pub expr: Color32,
+    pub bracket: Color32,
+    pub delimiter: Color32,
+    pub label: Color32,
+    pub section: Color32,
+    pub segment: Color32,
+    pub component: Color32,
+    pub primitive: Color32,
+    pub pointer: Color32,
+    pub annotation: Color32,
+    pub invalid: Color32,
+    pub string: Color32,
+    pub comment: Color32,
+    pub bytes: Color32,
+    pub address: Color32,
+    pub keyword: Color32,
+    pub tipe: Color32,
+    pub field: Color32,
+    pub function: Color32,
+    pub operator: Color32,
+    pub variable: Color32,
+    pub constant: Color32,
+    /// Text of widgets that aren't active.
+    pub text: Color32,
+    /// Frames around panels, and what's shown faintly on top of the background.
+    pub border: Color32,
+    /// Background of the text that's searched for or hovered.
+    pub highlight: Color32,
+    pub bg_primary: Color32,
+    pub bg_secondary: Color32,
+}
+
+impl Theme {
+    pub const DARK: Self = Self {
+        name: "dark",
+        dark: true,
+        plain: colors::WHITE,
+        muted: colors::GRAY60,
+        mnemonic: colors::WHITE,
+        register: color!(0xf5, 0x62, 0x81),
+        immediate: color!(0x3e, 0xbc, 0xe6),
+        expr: color!(0x99, 0x99, 0x99),
+        bracket: colors::GRAY60,
+        delimiter: color!(0xa0, 0xa0, 0xa0),
+        label: color!(0x3e, 0xbc, 0xe6),
+        section: color!(0x3e, 0xbc, 0xe6),
+        segment: colors::GREEN,
+        component: color!(0xf5, 0x62, 0x81),
+        primitive: color!(0x69, 0xb5, 0xa9),
+        pointer: color!(0xff, 0x43, 0x43),
+        annotation: color!(0x3e, 0xbc, 0xe6),
+        invalid: color!(0xff, 0x43, 0x43),
+        string: color!(0xe6, 0xab, 0x3e),
+        comment: color!(0xa0, 0xa0, 0xa0),
+        bytes: colors::GREEN,
+        address: color!(0x40, 0x40, 0x40),
+        keyword: color!(0xff, 0x59, 0x00),
+        tipe: color!(0xfa, 0xa5, 0x1b),
+        field: color!(0x28, 0x8c, 0xc7),
+        function: colors::GREEN,
+        operator: color!(0xff, 0xa5, 0x00),
+        variable: color!(0xd4, 0x6c, 0xcb),
+        constant: color!(0x9b, 0x51, 0xc2),
+        text: colors::GRAYAA,
+        border: colors::GRAY35,
+        highlight: color!(0xff, 0x64, 0x00),
+        bg_primary: color!(0x30, 0x30, 0x30),
+        bg_secondary: color!(0x2d, 0x2d, 0x2d),
+    };
+
+    pub const LIGHT: Self = Self {
+        name: "light",
+        dark: false,
+        plain: color!(0x1e, 0x1e, 0x1e),
+        muted: color!(0x8c, 0x8c, 0x8c),
+        mnemonic: color!(0x00, 0x00, 0x00),
+        register: color!(0xa3, 0x15, 0x15),
+        immediate: color!(0x00, 0x70, 0xc1),
+        expr: color!(0x6e, 0x6e, 0x6e),
+        bracket: color!(0x9a, 0x9a, 0x9a),
+        delimiter: color!(0x6e, 0x6e, 0x6e),
+        label: color!(0x00, 0x70, 0xc1),
+        section: color!(0x00, 0x70, 0xc1),
+        segment: color!(0x09, 0x86, 0x58),
+        component: color!(0xa3, 0x15, 0x15),
+        primitive: color!(0x26, 0x7f, 0x99),
+        pointer: color!(0xcd, 0x31, 0x31),
+        annotation: color!(0x00, 0x70, 0xc1),
+        invalid: color!(0xe5, 0x14, 0x00),
+        string: color!(0xb3, 0x5c, 0x00),
+        comment: color!(0x00, 0x80, 0x00),
+        bytes: color!(0x09, 0x86, 0x58),
+        address: color!(0xa0, 0xa0, 0xa0),
+        keyword: color!(0x00, 0x00, 0xff),
+        tipe: color!(0x26, 0x7f, 0x99),
+        field: color!(0x00, 0x10, 0x80),
+        function: color!(0x79, 0x5e, 0x26),
+        operator: color!(0x00, 0x00, 0x00),
+        variable: color!(0x7a, 0x3e, 0x9d),
+        constant: color!(0x09, 0x86, 0x58),
+        text: color!(0x50, 0x50, 0x50),
+        border: color!(0xd8, 0xd8, 0xd8),
+        highlight: color!(0xff, 0xb0, 0x00),
+        bg_primary: color!(0xff, 0xff, 0xff),
+        bg_secondary: color!(0xf0, 0xf0, 0xf0),
+    };
+
+    /// Solarized dark, by Ethan Schoonover.
+    pub const SOLARIZED: Self = Self {
+        name: "solarized",
+        dark: true,
+        plain: color!(0x93, 0xa1, 0xa1),
+        muted: color!(0x58, 0x6e, 0x75),
+        mnemonic: color!(0xee, 0xe8, 0xd5),
+        register: color!(0xcb, 0x4b, 0x16),
+        immediate: color!(0x2a, 0xa1, 0x98),
+        expr: color!(0x83, 0x94, 0x96),
+        bracket: color!(0x58, 0x6e, 0x75),
+        delimiter: color!(0x83, 0x94, 0x96),
+        label: color!(0x26, 0x8b, 0xd2),
+        section: color!(0x26, 0x8b, 0xd2),
+        segment: color!(0x85, 0x99, 0x00),
+        component: color!(0xcb, 0x4b, 0x16),
+        primitive: color!(0x6c, 0x71, 0xc4),
+        pointer: color!(0xdc, 0x32, 0x2f),
+        annotation: color!(0x26, 0x8b, 0xd2),
+        invalid: color!(0xdc, 0x32, 0x2f),
+        string: color!(0xb5, 0x89, 0x00),
+        comment: color!(0x65, 0x7b, 0x83),
+        bytes: color!(0x85, 0x99, 0x00),
+        address: color!(0x58, 0x6e, 0x75),
+        keyword: color!(0x85, 0x99, 0x00),
+        tipe: color!(0xb5, 0x89, 0x00),
+        field: color!(0x26, 0x8b, 0xd2),
+        function: color!(0x26, 0x8b, 0xd2),
+        operator: color!(0xcb, 0x4b, 0x16),
+        variable: color!(0xd3, 0x36, 0x82),
+        constant: color!(0x2a, 0xa1, 0x98),
+        text: color!(0x83, 0x94, 0x96),
+        border: color!(0x0b, 0x44, 0x52),
+        highlight: color!(0xb5, 0x89, 0x00),
+        bg_primary: color!(0x00, 0x2b, 0x36),
+        bg_secondary: color!(0x07, 0x36, 0x42),
+    };
+
+    /// Themes that are built in, the first being the default.
+    pub const BUILTIN: [Self; 3] = [Self::DARK, Self::LIGHT, Self::SOLARIZED];
+
+    /// Built-in theme by it's name.
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::BUILTIN.into_iter().find(|theme| theme.name == name)
+    }
+
+    pub fn color(&self, kind: Kind) -> Color32 {
+        match kind {
+            Kind::Plain => self.plain,
+            Kind::Muted => self.muted,
+            Kind::Mnemonic => self.mnemonic,
+            Kind::Register => self.register,
+            Kind::Immediate => self.immediate,
+            Kind::Expr => self.expr,
+            Kind::Bracket => self.bracket,
+            Kind::Delimiter => self.delimiter,
+            Kind::Label => self.label,
+            Kind::Section => self.section,
+            Kind::Segment => self.segment,
+            Kind::Component => self.component,
+            Kind::Primitive => self.primitive,
+            Kind::Pointer => self.pointer,
+            Kind::Annotation => self.annotation,
+            Kind::Invalid => self.invalid,
+            Kind::String => self.string,
+            Kind::Comment => self.comment,
+            Kind::Bytes => self.bytes,
+            Kind::Address => self.address,
+            Kind::Keyword => self.keyword,
+            Kind::Type => self.tipe,
+            Kind::Field => self.field,
+            Kind::Function => self.function,
+            Kind::Operator => self.operator,
+            Kind::Variable => self.variable,
+            Kind::Constant => self.constant,
+            Kind::Custom(color) => color,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [Kind; 27] = [
+        Kind::Plain,
+        Kind::Muted,
+        Kind::Mnemonic,
+        Kind::Register,
+        Kind::Immediate,
+        Kind::Expr,
+        Kind::Bracket,
+        Kind::Delimiter,
+        Kind::Label,
+        Kind::Section,
+        Kind::Segment,
+        Kind::Component,
+        Kind::Primitive,
+        Kind::Pointer,
+        Kind::Annotation,
+        Kind::Invalid,
+        Kind::String,
+        Kind::Comment,
+        Kind::Bytes,
+        Kind::Address,
+        Kind::Keyword,
+        Kind::Type,
+        Kind::Field,
+        Kind::Function,
+        Kind::Operator,
+        Kind::Variable,
+        Kind::Constant,
+    ];
+
+    #[test]
+    fn builtin() {
+        for theme in Theme::BUILTIN {
+            assert_eq!(Theme::by_name(theme.name), Some(theme));
+
+            // Every kind is drawn opaque and stands out from the background.
+            for kind in KINDS {
+                let color = theme.color(kind);
+                assert_eq!(color.a(), 255, "{kind:?} in {}", theme.name);
+                assert_ne!(color, theme.bg_primary, "{kind:?} in {}", theme.name);
+            }
+
+            let custom = Color32::from_rgb(1, 2, 3);
+            assert_eq!(theme.color(Kind::Custom(custom)), custom);
+        }
+    }
+}